@@ -0,0 +1,77 @@
+#!/bin/bash
+# Benchmark the checkout-critical hot paths and compare against a baseline.
+#
+# Usage:
+#   ./bench-hot-paths.sh save <baseline>             Run and save results as <baseline>
+#   ./bench-hot-paths.sh compare <baseline> [pct]    Run, compare with <baseline> and
+#                                                    fail if any benchmark is more than
+#                                                    pct% slower (default 15)
+#
+# Results are written under target/criterion; regressions are also listed
+# in $GITHUB_STEP_SUMMARY when run in CI.
+set -euo pipefail
+
+mode=${1:-}
+baseline=${2:-}
+threshold=${3:-15}
+
+if [ -z "$mode" ] || [ -z "$baseline" ]; then
+  echo "usage: $0 save|compare <baseline> [threshold-pct]" >&2
+  exit 2
+fi
+
+case "$mode" in
+  save) criterion_args=(--save-baseline "$baseline") ;;
+  # Lenient, so benchmarks that are new in this change are skipped
+  compare) criterion_args=(--baseline-lenient "$baseline") ;;
+  *)
+    echo "unknown mode: $mode" >&2
+    exit 2
+    ;;
+esac
+
+run() {
+  local package=$1 bench=$2
+  # Older revisions may predate a benchmark; skip it there
+  if ! grep -q "name = \"$bench\"" "$package/Cargo.toml"; then
+    echo "=== $package: $bench (not present, skipped) ==="
+    return
+  fi
+  echo "=== $package: $bench ==="
+  cargo bench -p "$package" --bench "$bench" -- "${criterion_args[@]}"
+}
+
+if [ "$mode" = compare ]; then
+  # Drop comparisons left over from earlier runs
+  find target/criterion -path '*/change' -type d -prune -exec rm -rf {} + 2>/dev/null || true
+fi
+
+run paykit-lib selection_benchmarks
+run paykit-lib uri_benchmarks
+run paykit-interactive message_benchmarks
+run paykit-mobile ffi_benchmarks
+
+[ "$mode" = compare ] || exit 0
+
+# Criterion writes the relative change in mean time for every benchmark it
+# could compare to change/estimates.json
+summary=${GITHUB_STEP_SUMMARY:-/dev/null}
+echo "| Benchmark | Change |" >> "$summary"
+echo "|-----------|--------|" >> "$summary"
+regressions=0
+while IFS= read -r estimates; do
+  id=${estimates#target/criterion/}
+  id=${id%/change/estimates.json}
+  change=$(jq '.mean.point_estimate * 100' "$estimates")
+  printf '| %s | %+.1f%% |\n' "$id" "$change" >> "$summary"
+  if jq -e --argjson limit "$threshold" '.mean.point_estimate * 100 > $limit' "$estimates" > /dev/null; then
+    printf '⚠️  %s is %.1f%% slower than %s\n' "$id" "$change" "$baseline"
+    regressions=$((regressions + 1))
+  fi
+done < <(find target/criterion -path '*/change/estimates.json' | sort)
+
+if [ "$regressions" -gt 0 ]; then
+  echo "$regressions benchmark(s) regressed by more than $threshold%"
+  exit 1
+fi
+echo "No benchmark regressed by more than $threshold%"
@@ -0,0 +1,175 @@
+/*
+ * Paykit C API
+ *
+ * Stable C interface to the Paykit client for hosts that cannot load the
+ * UniFFI bindings (React Native JSI, Flutter dart:ffi, C/C++ terminals).
+ *
+ * Conventions:
+ * - Handles are opaque; free them with the matching *_free function.
+ * - Every fallible function returns a PaykitStatus. On failure,
+ *   paykit_last_error_message() describes the error on the calling thread.
+ * - String inputs are NUL-terminated UTF-8; optional inputs may be NULL.
+ * - String outputs are written to char** out-parameters and must be freed
+ *   with paykit_string_free().
+ * - Structured data is JSON; see each function for the shape.
+ */
+
+#ifndef PAYKIT_H
+#define PAYKIT_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define PAYKIT_CAPI_ABI_VERSION 1
+
+typedef enum PaykitStatus {
+    PAYKIT_STATUS_OK = 0,
+    PAYKIT_STATUS_NULL_POINTER = 1,
+    PAYKIT_STATUS_INVALID_UTF8 = 2,
+    PAYKIT_STATUS_INVALID_JSON = 3,
+    PAYKIT_STATUS_TRANSPORT = 10,
+    PAYKIT_STATUS_VALIDATION = 11,
+    PAYKIT_STATUS_NOT_FOUND = 12,
+    PAYKIT_STATUS_SERIALIZATION = 13,
+    PAYKIT_STATUS_INTERNAL = 14,
+    PAYKIT_STATUS_TIMEOUT = 15,
+    PAYKIT_STATUS_CONNECTION = 16,
+    PAYKIT_STATUS_AUTHENTICATION = 17,
+    PAYKIT_STATUS_SESSION = 18,
+    PAYKIT_STATUS_RATE_LIMITED = 19,
+    PAYKIT_STATUS_PERMISSION_DENIED = 20,
+    PAYKIT_STATUS_PAYMENT_HELD = 21,
+    PAYKIT_STATUS_APPROVAL_REQUIRED = 22,
+    PAYKIT_STATUS_CLIENT_SHUTDOWN = 23,
+    /* Rust code panicked; do not use the handle again. */
+    PAYKIT_STATUS_PANIC = 99,
+} PaykitStatus;
+
+typedef struct PaykitClientHandle PaykitClientHandle;
+typedef struct PaykitTransport PaykitTransport;
+typedef struct PaykitStorageResponse PaykitStorageResponse;
+
+/* ---- Library ---------------------------------------------------------- */
+
+/* Compare with PAYKIT_CAPI_ABI_VERSION at startup. */
+uint32_t paykit_abi_version(void);
+
+/* Static string; do not free. */
+const char *paykit_version(void);
+
+/* Last error on this thread, or NULL. Valid until the next Paykit call on
+ * the same thread; do not free. */
+const char *paykit_last_error_message(void);
+
+void paykit_string_free(char *value);
+
+/* ---- Client ----------------------------------------------------------- */
+
+PaykitStatus paykit_client_new(PaykitClientHandle **out);
+/* Stop the client, waiting up to timeout_ms for calls in progress. Later
+ * calls return PAYKIT_STATUS_CLIENT_SHUTDOWN; the handle must still be
+ * freed. Returns PAYKIT_STATUS_TIMEOUT if calls were still running. */
+PaykitStatus paykit_client_shutdown(const PaykitClientHandle *client, uint64_t timeout_ms);
+void paykit_client_free(PaykitClientHandle *client);
+
+/* ["lightning", "onchain", ...] */
+PaykitStatus paykit_list_methods(const PaykitClientHandle *client, char **out_json);
+
+PaykitStatus paykit_validate_endpoint(const PaykitClientHandle *client,
+                                      const char *method_id,
+                                      const char *endpoint,
+                                      bool *out_valid);
+
+/* supported_json: [{"method_id": "...", "endpoint": "..."}, ...]
+ * preferences_json (nullable): {"strategy": "balanced" | "cost_optimized" |
+ *   "speed_optimized" | "privacy_optimized", "excluded_methods": [...],
+ *   "max_fee_sats": n, "max_confirmation_time_secs": n}
+ * out_json: {"primary_method", "fallback_methods", "reason"} */
+PaykitStatus paykit_select_method(const PaykitClientHandle *client,
+                                  const char *supported_json,
+                                  uint64_t amount_sats,
+                                  const char *preferences_json,
+                                  char **out_json);
+
+/* Executors cannot be registered from C yet: results are simulated.
+ * metadata_json and memo are nullable.
+ * out_json: {"execution_id", "method_id", "endpoint", "amount_sats",
+ *   "success", "executed_at", "execution_data", "error"}
+ * A failed payment returns PAYKIT_STATUS_OK with "success": false. */
+PaykitStatus paykit_execute_payment(const PaykitClientHandle *client,
+                                    const char *method_id,
+                                    const char *endpoint,
+                                    uint64_t amount_sats,
+                                    const char *metadata_json,
+                                    const char *memo,
+                                    char **out_json);
+
+/* out_json: [{"method_id": "...", "endpoint": "..."}, ...] */
+PaykitStatus paykit_fetch_supported_payments(const PaykitClientHandle *client,
+                                             const PaykitTransport *transport,
+                                             const char *owner,
+                                             char **out_json);
+
+/* Writes NULL to out_endpoint when the method is not published. */
+PaykitStatus paykit_fetch_payment_endpoint(const PaykitClientHandle *client,
+                                           const PaykitTransport *transport,
+                                           const char *owner,
+                                           const char *method_id,
+                                           char **out_endpoint);
+
+/* amount, currency and memo are nullable.
+ * out_json: {"receipt_id", "payer", "payee", "method_id", "amount",
+ *   "currency", "created_at", "metadata"} */
+PaykitStatus paykit_create_receipt(const PaykitClientHandle *client,
+                                   const char *payer,
+                                   const char *payee,
+                                   const char *method_id,
+                                   const char *amount,
+                                   const char *currency,
+                                   const char *memo,
+                                   char **out_json);
+
+/* ---- Transports ------------------------------------------------------- */
+
+/* Read path from owner's public storage. Leave the response empty when the
+ * file does not exist. Return 0 on success. */
+typedef int32_t (*PaykitGetFn)(void *user_data,
+                               const char *owner,
+                               const char *path,
+                               PaykitStorageResponse *response);
+
+/* List files under prefix, pushing one entry each. Return 0 on success. */
+typedef int32_t (*PaykitListFn)(void *user_data,
+                                const char *owner,
+                                const char *prefix,
+                                PaykitStorageResponse *response);
+
+/* Callbacks may run on any thread, concurrently. */
+typedef struct PaykitReadCallbacks {
+    void *user_data;
+    PaykitGetFn get;
+    PaykitListFn list;
+    /* Called once when the transport is freed; may be NULL. */
+    void (*free_user_data)(void *user_data);
+} PaykitReadCallbacks;
+
+PaykitStatus paykit_transport_new_mock(PaykitTransport **out);
+PaykitStatus paykit_transport_new_callbacks(PaykitReadCallbacks callbacks,
+                                            PaykitTransport **out);
+void paykit_transport_free(PaykitTransport *transport);
+
+/* Only valid inside a callback; strings are copied. */
+PaykitStatus paykit_storage_response_set(PaykitStorageResponse *response, const char *content);
+PaykitStatus paykit_storage_response_push(PaykitStorageResponse *response, const char *entry);
+PaykitStatus paykit_storage_response_set_error(PaykitStorageResponse *response,
+                                               const char *message);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* PAYKIT_H */
@@ -0,0 +1,440 @@
+//! Client handle and its operations.
+
+use crate::transport::PaykitTransport;
+use crate::types::{ExecutionJson, PaymentMethodJson, PreferencesJson, ReceiptJson, SelectionJson};
+use crate::{
+    check_out, ffi_call, parse_json, read_opt_str, read_str, write_json, write_string, CapiError,
+    CapiResult, PaykitStatus,
+};
+use paykit_mobile::PaykitClient;
+use std::ffi::c_char;
+use std::sync::Arc;
+
+/// Opaque client handle.
+pub struct PaykitClientHandle {
+    inner: Arc<PaykitClient>,
+}
+
+/// Borrow a handle argument.
+///
+/// # Safety
+///
+/// `ptr` must be NULL or a live handle.
+unsafe fn handle<'a, T>(ptr: *const T, name: &str) -> CapiResult<&'a T> {
+    ptr.as_ref().ok_or_else(|| {
+        CapiError::new(
+            PaykitStatus::NullPointer,
+            format!("{} must not be NULL", name),
+        )
+    })
+}
+
+/// Create a client for mainnet.
+///
+/// # Safety
+///
+/// `out` must be a valid, writable pointer.
+#[no_mangle]
+pub unsafe extern "C" fn paykit_client_new(out: *mut *mut PaykitClientHandle) -> PaykitStatus {
+    ffi_call(|| {
+        check_out(out, "out")?;
+        let client = PaykitClientHandle {
+            inner: PaykitClient::new()?,
+        };
+        *out = Box::into_raw(Box::new(client));
+        Ok(())
+    })
+}
+
+/// Shut a client down, waiting up to `timeout_ms` for calls in progress.
+///
+/// Later calls on the handle fail with [`PaykitStatus::ClientShutdown`];
+/// it must still be freed with [`paykit_client_free`].
+///
+/// # Safety
+///
+/// `client` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn paykit_client_shutdown(
+    client: *const PaykitClientHandle,
+    timeout_ms: u64,
+) -> PaykitStatus {
+    ffi_call(|| {
+        let client = handle(client, "client")?;
+        client.inner.shutdown(timeout_ms)?;
+        Ok(())
+    })
+}
+
+/// Free a client. NULL is ignored.
+///
+/// # Safety
+///
+/// `client` must be NULL or a handle that has not been freed yet, and no
+/// other thread may be using it.
+#[no_mangle]
+pub unsafe extern "C" fn paykit_client_free(client: *mut PaykitClientHandle) {
+    if !client.is_null() {
+        drop(Box::from_raw(client));
+    }
+}
+
+/// Registered payment methods as a JSON array of method IDs.
+///
+/// # Safety
+///
+/// `client` must be a live handle and `out_json` a valid, writable pointer.
+#[no_mangle]
+pub unsafe extern "C" fn paykit_list_methods(
+    client: *const PaykitClientHandle,
+    out_json: *mut *mut c_char,
+) -> PaykitStatus {
+    ffi_call(|| {
+        let client = handle(client, "client")?;
+        check_out(out_json, "out_json")?;
+        write_json(out_json, &client.inner.list_methods())
+    })
+}
+
+/// Check whether `endpoint` is valid for `method_id`.
+///
+/// # Safety
+///
+/// `client` must be a live handle, the strings valid, and `out_valid` a
+/// valid, writable pointer.
+#[no_mangle]
+pub unsafe extern "C" fn paykit_validate_endpoint(
+    client: *const PaykitClientHandle,
+    method_id: *const c_char,
+    endpoint: *const c_char,
+    out_valid: *mut bool,
+) -> PaykitStatus {
+    ffi_call(|| {
+        let client = handle(client, "client")?;
+        check_out(out_valid, "out_valid")?;
+        *out_valid = client.inner.validate_endpoint(
+            read_str(method_id, "method_id")?.to_string(),
+            read_str(endpoint, "endpoint")?.to_string(),
+        )?;
+        Ok(())
+    })
+}
+
+/// Select the method to pay with.
+///
+/// `supported_json` is an array of `{"method_id", "endpoint"}` objects.
+/// `preferences_json` may be NULL, or an object with optional `strategy`
+/// (`balanced`, `cost_optimized`, `speed_optimized`, `privacy_optimized`),
+/// `excluded_methods`, `max_fee_sats` and `max_confirmation_time_secs`.
+///
+/// Writes `{"primary_method", "fallback_methods", "reason"}`.
+///
+/// # Safety
+///
+/// `client` must be a live handle, the strings valid, and `out_json` a
+/// valid, writable pointer.
+#[no_mangle]
+pub unsafe extern "C" fn paykit_select_method(
+    client: *const PaykitClientHandle,
+    supported_json: *const c_char,
+    amount_sats: u64,
+    preferences_json: *const c_char,
+    out_json: *mut *mut c_char,
+) -> PaykitStatus {
+    ffi_call(|| {
+        let client = handle(client, "client")?;
+        check_out(out_json, "out_json")?;
+        let supported: Vec<PaymentMethodJson> = parse_json(
+            read_str(supported_json, "supported_json")?,
+            "supported_json",
+        )?;
+        let preferences = read_opt_str(preferences_json, "preferences_json")?
+            .map(|json| parse_json::<PreferencesJson>(json, "preferences_json"))
+            .transpose()?;
+
+        let result = client.inner.select_method(
+            supported.into_iter().map(Into::into).collect(),
+            amount_sats,
+            preferences.map(Into::into),
+        )?;
+        write_json(out_json, &SelectionJson::from(result))
+    })
+}
+
+/// Pay `amount_sats` to `endpoint` with the client's executors.
+///
+/// Executors cannot be registered through the C API yet, so the built-in
+/// plugins return simulated results.
+///
+/// `metadata_json` (an object) and `memo` may be NULL. Writes
+/// `{"execution_id", "method_id", "endpoint", "amount_sats", "success",
+/// "executed_at", "execution_data", "error"}`; a failed payment is still
+/// `PAYKIT_STATUS_OK` with `"success": false`.
+///
+/// # Safety
+///
+/// `client` must be a live handle, the strings valid, and `out_json` a
+/// valid, writable pointer.
+#[no_mangle]
+pub unsafe extern "C" fn paykit_execute_payment(
+    client: *const PaykitClientHandle,
+    method_id: *const c_char,
+    endpoint: *const c_char,
+    amount_sats: u64,
+    metadata_json: *const c_char,
+    memo: *const c_char,
+    out_json: *mut *mut c_char,
+) -> PaykitStatus {
+    ffi_call(|| {
+        let client = handle(client, "client")?;
+        check_out(out_json, "out_json")?;
+        let metadata = read_opt_str(metadata_json, "metadata_json")?;
+        if let Some(metadata) = metadata {
+            parse_json::<serde_json::Value>(metadata, "metadata_json")?;
+        }
+
+        let result = client.inner.execute_payment(
+            read_str(method_id, "method_id")?.to_string(),
+            read_str(endpoint, "endpoint")?.to_string(),
+            amount_sats,
+            metadata.map(str::to_string),
+            read_opt_str(memo, "memo")?.map(str::to_string),
+        )?;
+        write_json(out_json, &ExecutionJson::from(result))
+    })
+}
+
+/// Discover the payment methods `owner` publishes.
+///
+/// Writes an array of `{"method_id", "endpoint"}` objects.
+///
+/// # Safety
+///
+/// `client` and `transport` must be live handles, `owner` valid, and
+/// `out_json` a valid, writable pointer.
+#[no_mangle]
+pub unsafe extern "C" fn paykit_fetch_supported_payments(
+    client: *const PaykitClientHandle,
+    transport: *const PaykitTransport,
+    owner: *const c_char,
+    out_json: *mut *mut c_char,
+) -> PaykitStatus {
+    ffi_call(|| {
+        let client = handle(client, "client")?;
+        let transport = handle(transport, "transport")?;
+        check_out(out_json, "out_json")?;
+        let methods = client.inner.fetch_supported_payments(
+            transport.inner.clone(),
+            read_str(owner, "owner")?.to_string(),
+        )?;
+        let methods: Vec<PaymentMethodJson> = methods.into_iter().map(Into::into).collect();
+        write_json(out_json, &methods)
+    })
+}
+
+/// Fetch the endpoint `owner` publishes for `method_id`.
+///
+/// Writes NULL to `out_endpoint` when the method is not published.
+///
+/// # Safety
+///
+/// `client` and `transport` must be live handles, the strings valid, and
+/// `out_endpoint` a valid, writable pointer.
+#[no_mangle]
+pub unsafe extern "C" fn paykit_fetch_payment_endpoint(
+    client: *const PaykitClientHandle,
+    transport: *const PaykitTransport,
+    owner: *const c_char,
+    method_id: *const c_char,
+    out_endpoint: *mut *mut c_char,
+) -> PaykitStatus {
+    ffi_call(|| {
+        let client = handle(client, "client")?;
+        let transport = handle(transport, "transport")?;
+        check_out(out_endpoint, "out_endpoint")?;
+        *out_endpoint = std::ptr::null_mut();
+        let endpoint = client.inner.fetch_payment_endpoint(
+            transport.inner.clone(),
+            read_str(owner, "owner")?.to_string(),
+            read_str(method_id, "method_id")?.to_string(),
+        )?;
+        match endpoint {
+            Some(endpoint) => write_string(out_endpoint, endpoint),
+            None => Ok(()),
+        }
+    })
+}
+
+/// Create a receipt for a payment from `payer` to `payee`.
+///
+/// `amount`, `currency` and `memo` may be NULL. Writes `{"receipt_id",
+/// "payer", "payee", "method_id", "amount", "currency", "created_at",
+/// "metadata"}`.
+///
+/// # Safety
+///
+/// `client` must be a live handle, the strings valid, and `out_json` a
+/// valid, writable pointer.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn paykit_create_receipt(
+    client: *const PaykitClientHandle,
+    payer: *const c_char,
+    payee: *const c_char,
+    method_id: *const c_char,
+    amount: *const c_char,
+    currency: *const c_char,
+    memo: *const c_char,
+    out_json: *mut *mut c_char,
+) -> PaykitStatus {
+    ffi_call(|| {
+        let client = handle(client, "client")?;
+        check_out(out_json, "out_json")?;
+        let receipt = client.inner.create_receipt(
+            read_str(payer, "payer")?.to_string(),
+            read_str(payee, "payee")?.to_string(),
+            read_str(method_id, "method_id")?.to_string(),
+            read_opt_str(amount, "amount")?.map(str::to_string),
+            read_opt_str(currency, "currency")?.map(str::to_string),
+            read_opt_str(memo, "memo")?.map(str::to_string),
+            false,
+        )?;
+        write_json(out_json, &ReceiptJson::from(receipt))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{paykit_string_free, paykit_transport_free, paykit_transport_new_mock};
+    use std::ffi::{CStr, CString};
+    use std::ptr;
+
+    unsafe fn take_string(ptr: *mut c_char) -> String {
+        let value = CStr::from_ptr(ptr).to_str().unwrap().to_string();
+        paykit_string_free(ptr);
+        value
+    }
+
+    #[test]
+    fn test_client_operations() {
+        unsafe {
+            let mut client = ptr::null_mut();
+            assert_eq!(paykit_client_new(&mut client), PaykitStatus::Ok);
+
+            let mut json = ptr::null_mut();
+            assert_eq!(paykit_list_methods(client, &mut json), PaykitStatus::Ok);
+            let methods: Vec<String> = serde_json::from_str(&take_string(json)).unwrap();
+            assert!(methods.contains(&"lightning".to_string()));
+
+            let lightning = CString::new("lightning").unwrap();
+            let invoice = CString::new("lnbc1000n1pj9x7zzpp5").unwrap();
+            let mut valid = false;
+            assert_eq!(
+                paykit_validate_endpoint(client, lightning.as_ptr(), invoice.as_ptr(), &mut valid),
+                PaykitStatus::Ok
+            );
+            let unknown = CString::new("carrier-pigeon").unwrap();
+            assert_eq!(
+                paykit_validate_endpoint(client, unknown.as_ptr(), invoice.as_ptr(), &mut valid),
+                PaykitStatus::NotFound
+            );
+            assert_eq!(
+                paykit_validate_endpoint(client, ptr::null(), invoice.as_ptr(), &mut valid),
+                PaykitStatus::NullPointer
+            );
+
+            let supported = CString::new(
+                r#"[{"method_id":"lightning","endpoint":"lnbc1000n1pj9x7zzpp5"},
+                    {"method_id":"onchain","endpoint":"bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq"}]"#,
+            )
+            .unwrap();
+            let preferences = CString::new(r#"{"excluded_methods":["onchain"]}"#).unwrap();
+            let mut json = ptr::null_mut();
+            assert_eq!(
+                paykit_select_method(
+                    client,
+                    supported.as_ptr(),
+                    10_000,
+                    preferences.as_ptr(),
+                    &mut json
+                ),
+                PaykitStatus::Ok
+            );
+            let selection: serde_json::Value = serde_json::from_str(&take_string(json)).unwrap();
+            assert_eq!(selection["primary_method"], "lightning");
+
+            let broken = CString::new("[").unwrap();
+            assert_eq!(
+                paykit_select_method(client, broken.as_ptr(), 1, ptr::null(), &mut json),
+                PaykitStatus::InvalidJson
+            );
+
+            let mut transport = ptr::null_mut();
+            assert_eq!(paykit_transport_new_mock(&mut transport), PaykitStatus::Ok);
+            let owner = CString::new(
+                paykit_mobile::keys::generate_ed25519_keypair()
+                    .unwrap()
+                    .public_key_z32,
+            )
+            .unwrap();
+            assert_eq!(
+                paykit_fetch_supported_payments(client, transport, owner.as_ptr(), &mut json),
+                PaykitStatus::Ok
+            );
+            assert_eq!(take_string(json), "[]");
+
+            let mut endpoint = ptr::null_mut();
+            assert_eq!(
+                paykit_fetch_payment_endpoint(
+                    client,
+                    transport,
+                    owner.as_ptr(),
+                    lightning.as_ptr(),
+                    &mut endpoint
+                ),
+                PaykitStatus::Ok
+            );
+            assert!(endpoint.is_null());
+
+            let memo = CString::new("thanks").unwrap();
+            assert_eq!(
+                paykit_create_receipt(
+                    client,
+                    owner.as_ptr(),
+                    owner.as_ptr(),
+                    lightning.as_ptr(),
+                    ptr::null(),
+                    ptr::null(),
+                    memo.as_ptr(),
+                    &mut json
+                ),
+                PaykitStatus::Ok
+            );
+            let receipt: serde_json::Value = serde_json::from_str(&take_string(json)).unwrap();
+            assert_eq!(receipt["metadata"]["memo"], "thanks");
+
+            paykit_transport_free(transport);
+            paykit_client_free(client);
+        }
+    }
+
+    #[test]
+    fn test_client_shutdown() {
+        unsafe {
+            let mut client = ptr::null_mut();
+            assert_eq!(paykit_client_new(&mut client), PaykitStatus::Ok);
+            assert_eq!(paykit_client_shutdown(client, 1_000), PaykitStatus::Ok);
+
+            let lightning = CString::new("lightning").unwrap();
+            let invoice = CString::new("lnbc1000n1pj9x7zzpp5").unwrap();
+            let mut valid = false;
+            assert_eq!(
+                paykit_validate_endpoint(client, lightning.as_ptr(), invoice.as_ptr(), &mut valid),
+                PaykitStatus::ClientShutdown
+            );
+            assert_eq!(paykit_client_shutdown(client, 1_000), PaykitStatus::Ok);
+            paykit_client_free(client);
+        }
+    }
+}
@@ -0,0 +1,302 @@
+//! Paykit C API
+//!
+//! A stable `extern "C"` surface over the Paykit client for hosts that cannot
+//! load UniFFI bindings: React Native JSI modules, Flutter `dart:ffi`, and
+//! C/C++ point-of-sale software. The header is `include/paykit.h`.
+//!
+//! # Conventions
+//!
+//! - **Handles**: clients and transports are opaque pointers created by a
+//!   `*_new*` function and released with the matching `*_free` function.
+//!   Handles may be shared between threads.
+//! - **Status codes**: every fallible function returns a [`PaykitStatus`].
+//!   On failure, [`paykit_last_error_message`] describes the error on the
+//!   calling thread.
+//! - **Strings**: inputs are NUL-terminated UTF-8, optional inputs may be
+//!   NULL. Outputs are written to a `char **` out-parameter and must be
+//!   released with [`paykit_string_free`].
+//! - **Structured data** crosses the boundary as JSON; the shapes are
+//!   documented on each function and in the header.
+//!
+//! Nothing panics across the boundary: a panic is reported as
+//! [`PaykitStatus::Panic`]. That is a last resort; `unwrap`, `expect` and
+//! `panic!` are denied outside tests.
+
+#![cfg_attr(
+    not(test),
+    deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)
+)]
+
+use paykit_mobile::PaykitMobileError;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+mod client;
+mod transport;
+mod types;
+
+pub use client::*;
+pub use transport::*;
+
+/// Version of the C ABI. Bumped on any incompatible change to `paykit.h`.
+pub const PAYKIT_CAPI_ABI_VERSION: u32 = 1;
+
+/// Result code returned by every fallible function.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaykitStatus {
+    /// Success.
+    Ok = 0,
+    /// A required pointer argument was NULL.
+    NullPointer = 1,
+    /// A string argument was not valid UTF-8.
+    InvalidUtf8 = 2,
+    /// A JSON argument could not be parsed.
+    InvalidJson = 3,
+    /// Transport layer error (network, I/O).
+    Transport = 10,
+    /// Invalid input.
+    Validation = 11,
+    /// Resource not found.
+    NotFound = 12,
+    /// Serialization error.
+    Serialization = 13,
+    /// Unexpected internal state.
+    Internal = 14,
+    /// Network timeout.
+    Timeout = 15,
+    /// Connection refused or failed.
+    Connection = 16,
+    /// Authentication failed.
+    Authentication = 17,
+    /// Session expired or invalid.
+    Session = 18,
+    /// Rate limit exceeded.
+    RateLimited = 19,
+    /// Permission denied.
+    PermissionDenied = 20,
+    /// Payment held by velocity checks until the user approves it.
+    PaymentHeld = 21,
+    /// Payment queued until enough co-signers approve it.
+    ApprovalRequired = 22,
+    /// The client was shut down with `paykit_client_shutdown`.
+    ClientShutdown = 23,
+    /// Rust code panicked; the handle should not be used again.
+    Panic = 99,
+}
+
+/// Error carried to the C caller as a status and a message.
+#[derive(Debug)]
+pub(crate) struct CapiError {
+    status: PaykitStatus,
+    message: String,
+}
+
+impl CapiError {
+    pub(crate) fn new(status: PaykitStatus, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<PaykitMobileError> for CapiError {
+    fn from(err: PaykitMobileError) -> Self {
+        let status = match &err {
+            PaykitMobileError::Transport { .. } => PaykitStatus::Transport,
+            PaykitMobileError::Validation { .. } => PaykitStatus::Validation,
+            PaykitMobileError::NotFound { .. } => PaykitStatus::NotFound,
+            PaykitMobileError::Serialization { .. } => PaykitStatus::Serialization,
+            PaykitMobileError::Internal { .. } => PaykitStatus::Internal,
+            PaykitMobileError::NetworkTimeout { .. } => PaykitStatus::Timeout,
+            PaykitMobileError::ConnectionError { .. } => PaykitStatus::Connection,
+            PaykitMobileError::AuthenticationError { .. } => PaykitStatus::Authentication,
+            PaykitMobileError::SessionError { .. } => PaykitStatus::Session,
+            PaykitMobileError::RateLimitError { .. } => PaykitStatus::RateLimited,
+            PaykitMobileError::PermissionDenied { .. } => PaykitStatus::PermissionDenied,
+            PaykitMobileError::PaymentHeld { .. } => PaykitStatus::PaymentHeld,
+            PaykitMobileError::ApprovalRequired { .. } => PaykitStatus::ApprovalRequired,
+            PaykitMobileError::AlreadyShutdown { .. } => PaykitStatus::ClientShutdown,
+        };
+        Self::new(status, err.to_string())
+    }
+}
+
+pub(crate) type CapiResult<T> = std::result::Result<T, CapiError>;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: &str) {
+    // Interior NULs would truncate the message; replace them
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Run an FFI body, recording its error and turning panics into a status.
+///
+/// State touched by a panicking body may be inconsistent, which is why
+/// [`PaykitStatus::Panic`] tells callers to drop the handle.
+pub(crate) fn ffi_call<F>(body: F) -> PaykitStatus
+where
+    F: FnOnce() -> CapiResult<()>,
+{
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+    match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => PaykitStatus::Ok,
+        Ok(Err(err)) => {
+            set_last_error(&err.message);
+            err.status
+        }
+        Err(_) => {
+            set_last_error("internal panic");
+            PaykitStatus::Panic
+        }
+    }
+}
+
+/// Borrow a required string argument.
+///
+/// # Safety
+///
+/// `ptr` must be NULL or a valid NUL-terminated string that outlives `'a`.
+pub(crate) unsafe fn read_str<'a>(ptr: *const c_char, name: &str) -> CapiResult<&'a str> {
+    if ptr.is_null() {
+        return Err(CapiError::new(
+            PaykitStatus::NullPointer,
+            format!("{} must not be NULL", name),
+        ));
+    }
+    CStr::from_ptr(ptr).to_str().map_err(|_| {
+        CapiError::new(
+            PaykitStatus::InvalidUtf8,
+            format!("{} is not valid UTF-8", name),
+        )
+    })
+}
+
+/// Borrow an optional string argument, NULL meaning absent.
+///
+/// # Safety
+///
+/// Same as [`read_str`].
+pub(crate) unsafe fn read_opt_str<'a>(
+    ptr: *const c_char,
+    name: &str,
+) -> CapiResult<Option<&'a str>> {
+    if ptr.is_null() {
+        Ok(None)
+    } else {
+        read_str(ptr, name).map(Some)
+    }
+}
+
+/// Parse a JSON argument.
+pub(crate) fn parse_json<T: serde::de::DeserializeOwned>(json: &str, name: &str) -> CapiResult<T> {
+    serde_json::from_str(json).map_err(|e| {
+        CapiError::new(
+            PaykitStatus::InvalidJson,
+            format!("{} is not valid JSON: {}", name, e),
+        )
+    })
+}
+
+/// Check an out-parameter before doing any work.
+pub(crate) fn check_out<T>(out: *mut T, name: &str) -> CapiResult<()> {
+    if out.is_null() {
+        return Err(CapiError::new(
+            PaykitStatus::NullPointer,
+            format!("{} must not be NULL", name),
+        ));
+    }
+    Ok(())
+}
+
+/// Hand a string to the caller through an out-parameter.
+///
+/// # Safety
+///
+/// `out` must be a valid, writable pointer.
+pub(crate) unsafe fn write_string(out: *mut *mut c_char, value: String) -> CapiResult<()> {
+    let value = CString::new(value)
+        .map_err(|_| CapiError::new(PaykitStatus::Serialization, "output contains a NUL byte"))?;
+    *out = value.into_raw();
+    Ok(())
+}
+
+/// Serialize a value to JSON and hand it to the caller.
+///
+/// # Safety
+///
+/// Same as [`write_string`].
+pub(crate) unsafe fn write_json<T: serde::Serialize>(
+    out: *mut *mut c_char,
+    value: &T,
+) -> CapiResult<()> {
+    let json = serde_json::to_string(value)
+        .map_err(|e| CapiError::new(PaykitStatus::Serialization, e.to_string()))?;
+    write_string(out, json)
+}
+
+/// ABI version of this library, to compare with `PAYKIT_CAPI_ABI_VERSION`
+/// from the header the host was compiled against.
+#[no_mangle]
+pub extern "C" fn paykit_abi_version() -> u32 {
+    PAYKIT_CAPI_ABI_VERSION
+}
+
+/// Library version as a static string. Do not free.
+#[no_mangle]
+pub extern "C" fn paykit_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// Message describing the last error on this thread, or NULL.
+///
+/// The pointer stays valid until the next Paykit call on the same thread.
+/// Do not free.
+#[no_mangle]
+pub extern "C" fn paykit_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Free a string returned by this library. NULL is ignored.
+///
+/// # Safety
+///
+/// `value` must be NULL or a string returned through a Paykit out-parameter
+/// that has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn paykit_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_error_and_panics() {
+        let status = ffi_call(|| Err(CapiError::new(PaykitStatus::Validation, "bad input")));
+        assert_eq!(status, PaykitStatus::Validation);
+        let message = unsafe { CStr::from_ptr(paykit_last_error_message()) };
+        assert_eq!(message.to_str().unwrap(), "bad input");
+
+        assert_eq!(ffi_call(|| Ok(())), PaykitStatus::Ok);
+        assert!(paykit_last_error_message().is_null());
+
+        assert_eq!(ffi_call(|| panic!("boom")), PaykitStatus::Panic);
+
+        let version = unsafe { CStr::from_ptr(paykit_version()) };
+        assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+    }
+}
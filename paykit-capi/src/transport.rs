@@ -0,0 +1,302 @@
+//! Read-only directory transports.
+//!
+//! Discovery reads other users' public storage. C hosts either use the
+//! in-memory mock transport or supply a table of callbacks backed by their
+//! own Pubky client.
+
+use crate::{check_out, ffi_call, read_str, CapiError, PaykitStatus};
+use paykit_mobile::{
+    PubkyUnauthenticatedStorageCallback, StorageGetResult, StorageListResult,
+    UnauthenticatedTransportFFI,
+};
+use std::ffi::{c_char, c_void, CString};
+use std::sync::Arc;
+
+/// Opaque read-only transport handle.
+pub struct PaykitTransport {
+    pub(crate) inner: Arc<UnauthenticatedTransportFFI>,
+}
+
+/// Collects the result of a storage callback.
+///
+/// Callbacks fill it with [`paykit_storage_response_set`],
+/// [`paykit_storage_response_push`] or [`paykit_storage_response_set_error`].
+#[derive(Debug, Default)]
+pub struct PaykitStorageResponse {
+    content: Option<String>,
+    entries: Vec<String>,
+    error: Option<String>,
+}
+
+/// Reads `path` from `owner`'s public storage. Leave the response empty when
+/// the file does not exist. Return 0 on success.
+pub type PaykitGetFn = unsafe extern "C" fn(
+    user_data: *mut c_void,
+    owner: *const c_char,
+    path: *const c_char,
+    response: *mut PaykitStorageResponse,
+) -> i32;
+
+/// Lists the files under `prefix` in `owner`'s public storage, pushing one
+/// entry each. Return 0 on success.
+pub type PaykitListFn = unsafe extern "C" fn(
+    user_data: *mut c_void,
+    owner: *const c_char,
+    prefix: *const c_char,
+    response: *mut PaykitStorageResponse,
+) -> i32;
+
+/// Storage callbacks supplied by the host.
+///
+/// The callbacks may be invoked from any thread, concurrently.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct PaykitReadCallbacks {
+    /// Passed back to every callback.
+    pub user_data: *mut c_void,
+    /// Required.
+    pub get: Option<PaykitGetFn>,
+    /// Required.
+    pub list: Option<PaykitListFn>,
+    /// Called once when the transport is freed. May be NULL.
+    pub free_user_data: Option<unsafe extern "C" fn(user_data: *mut c_void)>,
+}
+
+struct CReadCallbacks(PaykitReadCallbacks);
+
+// SAFETY: the header requires callbacks and user_data to be thread-safe.
+unsafe impl Send for CReadCallbacks {}
+unsafe impl Sync for CReadCallbacks {}
+
+impl CReadCallbacks {
+    fn call(
+        &self,
+        callback: Option<PaykitGetFn>,
+        owner: String,
+        path: String,
+    ) -> Result<PaykitStorageResponse, String> {
+        let callback = callback.ok_or("callback not provided")?;
+        let owner = CString::new(owner).map_err(|e| e.to_string())?;
+        let path = CString::new(path).map_err(|e| e.to_string())?;
+        let mut response = PaykitStorageResponse::default();
+        let rc = unsafe {
+            callback(
+                self.0.user_data,
+                owner.as_ptr(),
+                path.as_ptr(),
+                &mut response,
+            )
+        };
+        if rc != 0 {
+            return Err(response
+                .error
+                .unwrap_or_else(|| format!("storage callback failed with code {}", rc)));
+        }
+        Ok(response)
+    }
+}
+
+impl PubkyUnauthenticatedStorageCallback for CReadCallbacks {
+    fn get(&self, owner_pubkey: String, path: String) -> StorageGetResult {
+        match self.call(self.0.get, owner_pubkey, path) {
+            Ok(response) => StorageGetResult::ok(response.content),
+            Err(message) => StorageGetResult::err(message),
+        }
+    }
+
+    fn list(&self, owner_pubkey: String, prefix: String) -> StorageListResult {
+        match self.call(self.0.list, owner_pubkey, prefix) {
+            Ok(response) => StorageListResult::ok(response.entries),
+            Err(message) => StorageListResult::err(message),
+        }
+    }
+}
+
+impl Drop for CReadCallbacks {
+    fn drop(&mut self) {
+        if let Some(free) = self.0.free_user_data {
+            unsafe { free(self.0.user_data) }
+        }
+    }
+}
+
+/// Create an in-memory transport for tests and demos.
+///
+/// # Safety
+///
+/// `out` must be a valid, writable pointer.
+#[no_mangle]
+pub unsafe extern "C" fn paykit_transport_new_mock(out: *mut *mut PaykitTransport) -> PaykitStatus {
+    ffi_call(|| {
+        check_out(out, "out")?;
+        let transport = PaykitTransport {
+            inner: UnauthenticatedTransportFFI::new_mock(),
+        };
+        *out = Box::into_raw(Box::new(transport));
+        Ok(())
+    })
+}
+
+/// Create a transport backed by host callbacks.
+///
+/// # Safety
+///
+/// `out` must be a valid, writable pointer, and the callbacks must stay
+/// callable until the transport is freed.
+#[no_mangle]
+pub unsafe extern "C" fn paykit_transport_new_callbacks(
+    callbacks: PaykitReadCallbacks,
+    out: *mut *mut PaykitTransport,
+) -> PaykitStatus {
+    ffi_call(|| {
+        check_out(out, "out")?;
+        if callbacks.get.is_none() || callbacks.list.is_none() {
+            return Err(CapiError::new(
+                PaykitStatus::NullPointer,
+                "get and list callbacks are required",
+            ));
+        }
+        let transport = PaykitTransport {
+            inner: UnauthenticatedTransportFFI::from_callback(Box::new(CReadCallbacks(callbacks))),
+        };
+        *out = Box::into_raw(Box::new(transport));
+        Ok(())
+    })
+}
+
+/// Free a transport. NULL is ignored.
+///
+/// # Safety
+///
+/// `transport` must be NULL or a handle that has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn paykit_transport_free(transport: *mut PaykitTransport) {
+    if !transport.is_null() {
+        drop(Box::from_raw(transport));
+    }
+}
+
+/// Set the file content from inside a `get` callback. The string is copied.
+///
+/// # Safety
+///
+/// `response` must be the pointer passed to the callback.
+#[no_mangle]
+pub unsafe extern "C" fn paykit_storage_response_set(
+    response: *mut PaykitStorageResponse,
+    content: *const c_char,
+) -> PaykitStatus {
+    ffi_call(|| {
+        check_out(response, "response")?;
+        (*response).content = Some(read_str(content, "content")?.to_string());
+        Ok(())
+    })
+}
+
+/// Add an entry from inside a `list` callback. The string is copied.
+///
+/// # Safety
+///
+/// `response` must be the pointer passed to the callback.
+#[no_mangle]
+pub unsafe extern "C" fn paykit_storage_response_push(
+    response: *mut PaykitStorageResponse,
+    entry: *const c_char,
+) -> PaykitStatus {
+    ffi_call(|| {
+        check_out(response, "response")?;
+        (*response)
+            .entries
+            .push(read_str(entry, "entry")?.to_string());
+        Ok(())
+    })
+}
+
+/// Describe a failure before returning non-zero from a callback.
+///
+/// # Safety
+///
+/// `response` must be the pointer passed to the callback.
+#[no_mangle]
+pub unsafe extern "C" fn paykit_storage_response_set_error(
+    response: *mut PaykitStorageResponse,
+    message: *const c_char,
+) -> PaykitStatus {
+    ffi_call(|| {
+        check_out(response, "response")?;
+        (*response).error = Some(read_str(message, "message")?.to_string());
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static FREED: AtomicBool = AtomicBool::new(false);
+
+    unsafe extern "C" fn get(
+        _user_data: *mut c_void,
+        _owner: *const c_char,
+        path: *const c_char,
+        response: *mut PaykitStorageResponse,
+    ) -> i32 {
+        if CStr::from_ptr(path).to_bytes().ends_with(b"/lightning") {
+            paykit_storage_response_set(response, c"lnbc1...".as_ptr());
+        }
+        0
+    }
+
+    unsafe extern "C" fn list(
+        _user_data: *mut c_void,
+        _owner: *const c_char,
+        _prefix: *const c_char,
+        response: *mut PaykitStorageResponse,
+    ) -> i32 {
+        paykit_storage_response_set_error(response, c"offline".as_ptr());
+        1
+    }
+
+    unsafe extern "C" fn free_user_data(_user_data: *mut c_void) {
+        FREED.store(true, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_callback_transport() {
+        let callbacks = PaykitReadCallbacks {
+            user_data: std::ptr::null_mut(),
+            get: Some(get),
+            list: Some(list),
+            free_user_data: Some(free_user_data),
+        };
+        unsafe {
+            let mut transport = std::ptr::null_mut();
+            assert_eq!(
+                paykit_transport_new_callbacks(callbacks, &mut transport),
+                PaykitStatus::Ok
+            );
+            let reader = &(*transport).inner;
+            assert_eq!(
+                reader
+                    .get("owner".into(), "/pub/paykit.app/v0/lightning".into())
+                    .unwrap(),
+                Some("lnbc1...".to_string())
+            );
+            assert_eq!(
+                reader
+                    .get("owner".into(), "/pub/paykit.app/v0/onchain".into())
+                    .unwrap(),
+                None
+            );
+            let err = reader
+                .list("owner".into(), "/pub/paykit.app/v0/".into())
+                .unwrap_err();
+            assert!(err.to_string().contains("offline"));
+
+            paykit_transport_free(transport);
+            assert!(FREED.load(Ordering::SeqCst));
+        }
+    }
+}
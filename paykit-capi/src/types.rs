@@ -0,0 +1,142 @@
+//! JSON shapes exchanged with C callers.
+
+use paykit_mobile::{
+    PaymentExecutionResult, PaymentMethod, Receipt, SelectionPreferences, SelectionResult,
+    SelectionStrategy,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// `{"method_id": "...", "endpoint": "..."}`
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct PaymentMethodJson {
+    pub method_id: String,
+    pub endpoint: String,
+}
+
+impl From<PaymentMethodJson> for PaymentMethod {
+    fn from(method: PaymentMethodJson) -> Self {
+        Self {
+            method_id: method.method_id,
+            endpoint: method.endpoint,
+        }
+    }
+}
+
+impl From<PaymentMethod> for PaymentMethodJson {
+    fn from(method: PaymentMethod) -> Self {
+        Self {
+            method_id: method.method_id,
+            endpoint: method.endpoint,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum StrategyJson {
+    #[default]
+    Balanced,
+    CostOptimized,
+    SpeedOptimized,
+    PrivacyOptimized,
+}
+
+/// Selection preferences; every field is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct PreferencesJson {
+    pub strategy: StrategyJson,
+    pub excluded_methods: Vec<String>,
+    pub max_fee_sats: Option<u64>,
+    pub max_confirmation_time_secs: Option<u64>,
+}
+
+impl From<PreferencesJson> for SelectionPreferences {
+    fn from(prefs: PreferencesJson) -> Self {
+        Self {
+            strategy: match prefs.strategy {
+                StrategyJson::Balanced => SelectionStrategy::Balanced,
+                StrategyJson::CostOptimized => SelectionStrategy::CostOptimized,
+                StrategyJson::SpeedOptimized => SelectionStrategy::SpeedOptimized,
+                StrategyJson::PrivacyOptimized => SelectionStrategy::PrivacyOptimized,
+            },
+            excluded_methods: prefs.excluded_methods,
+            max_fee_sats: prefs.max_fee_sats,
+            max_confirmation_time_secs: prefs.max_confirmation_time_secs,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct SelectionJson {
+    pub primary_method: String,
+    pub fallback_methods: Vec<String>,
+    pub reason: String,
+}
+
+impl From<SelectionResult> for SelectionJson {
+    fn from(result: SelectionResult) -> Self {
+        Self {
+            primary_method: result.primary_method,
+            fallback_methods: result.fallback_methods,
+            reason: result.reason,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ExecutionJson {
+    pub execution_id: String,
+    pub method_id: String,
+    pub endpoint: String,
+    pub amount_sats: u64,
+    pub success: bool,
+    pub executed_at: i64,
+    pub execution_data: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl From<PaymentExecutionResult> for ExecutionJson {
+    fn from(result: PaymentExecutionResult) -> Self {
+        Self {
+            execution_id: result.execution_id,
+            method_id: result.method_id,
+            endpoint: result.endpoint,
+            amount_sats: result.amount_sats,
+            success: result.success,
+            executed_at: result.executed_at,
+            execution_data: serde_json::from_str(&result.execution_data_json)
+                .unwrap_or(Value::Null),
+            error: result.error,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ReceiptJson {
+    pub receipt_id: String,
+    pub payer: String,
+    pub payee: String,
+    pub method_id: String,
+    pub amount: Option<String>,
+    pub currency: Option<String>,
+    pub created_at: i64,
+    pub metadata: Value,
+}
+
+impl From<Receipt> for ReceiptJson {
+    fn from(receipt: Receipt) -> Self {
+        Self {
+            receipt_id: receipt.receipt_id,
+            payer: receipt.payer,
+            payee: receipt.payee,
+            method_id: receipt.method_id,
+            amount: receipt.amount,
+            currency: receipt.currency,
+            created_at: receipt.created_at,
+            metadata: serde_json::from_str(&receipt.metadata_json).unwrap_or(Value::Null),
+        }
+    }
+}
@@ -6,7 +6,6 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use colored::Colorize;
-use paykit_demo_core::DemoStorage;
 use std::path::Path;
 
 use crate::ui;
@@ -128,7 +127,7 @@ pub async fn run(
 ) -> Result<()> {
     ui::header("Activity Timeline");
 
-    let storage = DemoStorage::new(storage_dir.join("data"));
+    let storage = super::storage::open(storage_dir);
     let mut activities: Vec<ActivityItem> = Vec::new();
 
     // Collect receipts
@@ -347,15 +346,15 @@ pub async fn run(
             activity.counterparty.dimmed(),
             activity.timestamp_display().dimmed()
         );
-        println!("{}", header);
+        ui::line(&header);
 
         // Details line
-        println!(
+        ui::line(&format!(
             "   {} | Status: {} | ID: {}",
             activity.amount_display(),
             format_status(&activity.status),
             &activity.id[..activity.id.len().min(12)]
-        );
+        ));
 
         if verbose {
             if let Some(method) = &activity.method {
@@ -364,7 +363,7 @@ pub async fn run(
             ui::key_value("   Full ID", &activity.id);
         }
 
-        println!();
+        ui::line("");
     }
 
     // Summary
@@ -0,0 +1,102 @@
+//! Payee analytics commands
+//!
+//! Received payments counted by day, method and currency, without payer
+//! identities. Aggregation is opt-in; see `paykit_demo_core::analytics`.
+
+use anyhow::Result;
+use paykit_demo_core::{AnalyticsCoordinator, AnalyticsReport};
+use std::path::Path;
+
+use crate::{output, ui};
+
+/// Start aggregating received receipts
+pub async fn enable(storage_dir: &Path, k_threshold: u64, no_retain_receipts: bool) -> Result<()> {
+    AnalyticsCoordinator::new(storage_dir).enable(k_threshold, !no_retain_receipts)?;
+
+    ui::success("Payee analytics enabled");
+    ui::key_value("Minimum bucket size", &k_threshold.to_string());
+    if no_retain_receipts {
+        ui::warning("Received receipts will not be stored; only aggregates are kept");
+        ui::info("Receipts stored earlier are not deleted");
+    }
+    Ok(())
+}
+
+/// Stop aggregating, keeping the aggregates
+pub async fn disable(storage_dir: &Path) -> Result<()> {
+    AnalyticsCoordinator::new(storage_dir).disable()?;
+    ui::success("Payee analytics disabled; received receipts are stored again");
+    Ok(())
+}
+
+/// Show the aggregates that pass the k-anonymity threshold
+pub async fn show(
+    storage_dir: &Path,
+    from: Option<String>,
+    to: Option<String>,
+    by_method: bool,
+) -> Result<()> {
+    ui::header("Payee Analytics");
+
+    let analytics = AnalyticsCoordinator::new(storage_dir);
+    let settings = analytics.settings()?;
+    let report = analytics.report(from.as_deref(), to.as_deref())?;
+
+    ui::key_value(
+        "Status",
+        match (settings.enabled, settings.retain_receipts) {
+            (false, _) => "disabled",
+            (true, true) => "enabled",
+            (true, false) => "enabled (receipts not retained)",
+        },
+    );
+    ui::key_value("Minimum bucket size", &report.k_threshold.to_string());
+    ui::separator();
+
+    print_rows(&report, by_method);
+    if report.suppressed > 0 {
+        ui::info(&format!(
+            "{} receipt(s) in buckets below the threshold are not shown",
+            report.suppressed
+        ));
+    }
+
+    if by_method {
+        output::emit(&report.by_method());
+    } else {
+        output::emit(&report);
+    }
+    Ok(())
+}
+
+/// Delete the aggregates
+pub async fn clear(storage_dir: &Path) -> Result<()> {
+    AnalyticsCoordinator::new(storage_dir).clear()?;
+    ui::success("Analytics cleared");
+    Ok(())
+}
+
+fn print_rows(report: &AnalyticsReport, by_method: bool) {
+    let rows = if by_method {
+        report.by_method()
+    } else {
+        report.rows.clone()
+    };
+    if rows.is_empty() {
+        ui::info("No aggregates to show");
+        return;
+    }
+
+    for row in rows {
+        let key = if by_method {
+            row.method.clone()
+        } else {
+            format!("{} {}", row.day, row.method)
+        };
+        let mut value = format!("{} payment(s), {} {}", row.count, row.total, row.currency);
+        if row.unpriced > 0 {
+            value.push_str(&format!(" ({} without amount)", row.unpriced));
+        }
+        ui::key_value(&key, &value);
+    }
+}
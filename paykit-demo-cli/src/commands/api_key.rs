@@ -0,0 +1,153 @@
+//! API key commands
+//!
+//! Keys for the local REST / JSON-RPC server, each with a role
+//! (read-only, operator or admin) and a per-minute request limit. See
+//! `paykit_demo_core::api_keys`.
+
+use anyhow::Result;
+use paykit_demo_core::{ApiAuditEntry, ApiKey, ApiKeyCoordinator, ApiRole};
+use serde::Serialize;
+use std::path::Path;
+
+use crate::{output, ui};
+
+/// `data` of `api-key create` and `api-key rotate` with `--output json`
+#[derive(Serialize)]
+struct IssuedToken<'a> {
+    key: &'a ApiKey,
+    token: &'a str,
+}
+
+/// Create a key and show its token once
+pub async fn create(
+    storage_dir: &Path,
+    name: &str,
+    role: &str,
+    rate_limit: u32,
+    expires_days: Option<u32>,
+) -> Result<()> {
+    let role: ApiRole = role.parse()?;
+    let now = now();
+    let expires_at = expires_days.map(|days| now + i64::from(days) * 86_400);
+    let (key, token) =
+        ApiKeyCoordinator::new(storage_dir).create(name, role, rate_limit, expires_at, now)?;
+
+    ui::success(&format!("Created API key {} ({})", key.id, key.name));
+    print_key(&key);
+    ui::separator();
+    ui::key_value("Token", &token);
+    ui::warning("Store the token now; it cannot be shown again");
+    output::emit(&IssuedToken {
+        key: &key,
+        token: &token,
+    });
+    Ok(())
+}
+
+/// List keys without their secrets
+pub async fn list(storage_dir: &Path) -> Result<()> {
+    ui::header("API Keys");
+
+    let keys = ApiKeyCoordinator::new(storage_dir).list()?;
+    output::emit(&keys);
+    if keys.is_empty() {
+        ui::info("No API keys");
+        return Ok(());
+    }
+
+    for key in &keys {
+        ui::separator();
+        ui::key_value("ID", &key.id);
+        ui::key_value("Name", &key.name);
+        print_key(key);
+    }
+    Ok(())
+}
+
+/// Revoke a key immediately
+pub async fn revoke(storage_dir: &Path, id: &str) -> Result<()> {
+    ApiKeyCoordinator::new(storage_dir).revoke(id, now())?;
+    ui::success(&format!("Revoked API key {}", id));
+    Ok(())
+}
+
+/// Issue a new token for a key, keeping the old one valid for a while
+pub async fn rotate(storage_dir: &Path, id: &str, grace_minutes: u64) -> Result<()> {
+    let keys = ApiKeyCoordinator::new(storage_dir);
+    let token = keys.rotate(id, grace_minutes * 60, now())?;
+    let key = keys.get(id)?;
+
+    ui::success(&format!("Rotated API key {}", id));
+    ui::key_value("Token", &token);
+    match key.previous_valid_until() {
+        Some(until) => ui::info(&format!("The old token works until {}", format_time(until))),
+        None => ui::info("The old token no longer works"),
+    }
+    ui::warning("Store the token now; it cannot be shown again");
+    output::emit(&IssuedToken {
+        key: &key,
+        token: &token,
+    });
+    Ok(())
+}
+
+/// Show recent API actions
+pub async fn audit(storage_dir: &Path, key_id: Option<&str>, limit: usize) -> Result<()> {
+    ui::header("API Audit Log");
+
+    let mut entries = ApiKeyCoordinator::new(storage_dir).audit_log(None)?;
+    if let Some(key_id) = key_id {
+        entries.retain(|entry| entry.key_id.as_deref() == Some(key_id));
+    }
+    entries.drain(..entries.len().saturating_sub(limit));
+    output::emit(&entries);
+    if entries.is_empty() {
+        ui::info("No matching entries");
+        return Ok(());
+    }
+
+    for entry in &entries {
+        print_entry(entry);
+    }
+    Ok(())
+}
+
+fn print_key(key: &ApiKey) {
+    ui::key_value("Role", &key.role.to_string());
+    ui::key_value("Rate limit", &format!("{} requests/minute", key.rate_limit));
+    ui::key_value("Created", &format_time(key.created_at));
+    match key.expires_at {
+        Some(expires_at) if key.is_expired(now()) => {
+            ui::key_value("Expires", &format!("{} (expired)", format_time(expires_at)))
+        }
+        Some(expires_at) => ui::key_value("Expires", &format_time(expires_at)),
+        None => ui::key_value("Expires", "never"),
+    }
+    if let Some(rotated_at) = key.rotated_at {
+        ui::key_value("Rotated", &format_time(rotated_at));
+    }
+}
+
+fn print_entry(entry: &ApiAuditEntry) {
+    let mut line = format!(
+        "{} {} {} {}",
+        format_time(entry.timestamp),
+        entry.key_id.as_deref().unwrap_or("-"),
+        if entry.allowed { "allowed" } else { "denied" },
+        entry.action
+    );
+    if let Some(detail) = &entry.detail {
+        line.push_str(&format!(" ({})", detail));
+    }
+    ui::line(&line);
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+fn format_time(secs: i64) -> String {
+    chrono::DateTime::from_timestamp(secs, 0)
+        .map(|time| time.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|| secs.to_string())
+}
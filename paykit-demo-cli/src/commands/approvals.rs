@@ -0,0 +1,364 @@
+//! Multi-signature approval commands
+//!
+//! Payments above the configured threshold are queued until K of the N
+//! co-signers approve them. Co-signers on this machine approve directly;
+//! remote co-signers sign the exported entry with `approvals sign` and the
+//! result is brought back with `approvals import`.
+
+use anyhow::{Context, Result};
+use paykit_lib::approvals::{
+    ApprovalDecision, ApprovalPolicy, ApprovalQueue, ApprovalStatus, PaymentProposal,
+    PendingApproval, SignedApproval,
+};
+use paykit_lib::audit::AuditOperation;
+use std::path::Path;
+
+use crate::{output, ui};
+
+/// Show or update the approval policy
+pub async fn policy(
+    storage_dir: &Path,
+    threshold: Option<u64>,
+    required: Option<u32>,
+    cosigners: Vec<String>,
+    ttl: Option<i64>,
+    _verbose: bool,
+) -> Result<()> {
+    ui::header("Approval Policy");
+
+    let queue = load_queue(storage_dir)?;
+    if threshold.is_none() && required.is_none() && cosigners.is_empty() && ttl.is_none() {
+        match &queue {
+            Some(queue) => show_policy(queue.policy()),
+            None => ui::info("Approvals are disabled"),
+        }
+        return Ok(());
+    }
+
+    let current = queue.as_ref().map(|q| q.policy().clone());
+    let threshold = threshold
+        .or(current.as_ref().map(|p| p.threshold_sats))
+        .context("--threshold is required when enabling approvals")?;
+    let cosigners = if cosigners.is_empty() {
+        current
+            .as_ref()
+            .map(|p| p.cosigners.clone())
+            .unwrap_or_default()
+    } else {
+        cosigners
+    };
+    let required = required
+        .or(current.as_ref().map(|p| p.required))
+        .unwrap_or(1);
+    let mut new_policy = ApprovalPolicy::new(threshold, required, cosigners)?;
+    if let Some(ttl) = ttl.or(current.as_ref().map(|p| p.ttl_secs)) {
+        new_policy = new_policy.with_ttl_secs(ttl);
+        new_policy.validate()?;
+    }
+
+    let queue = match queue {
+        Some(mut queue) => {
+            queue.set_policy(new_policy)?;
+            queue
+        }
+        None => ApprovalQueue::new(new_policy)?,
+    };
+    save_queue(storage_dir, &queue)?;
+
+    ui::success("Approval policy updated");
+    show_policy(queue.policy());
+    Ok(())
+}
+
+/// Turn approvals off and discard the queue
+pub async fn disable(storage_dir: &Path, _verbose: bool) -> Result<()> {
+    let path = queue_path(storage_dir);
+    if path.exists() {
+        if let Some(queue) = load_queue(storage_dir)? {
+            let pending = queue.pending().len();
+            if pending > 0
+                && !ui::confirm(&format!("Discard {} pending payment(s)?", pending), false)?
+            {
+                ui::info("Cancelled");
+                return Ok(());
+            }
+        }
+        std::fs::remove_file(&path).context("Failed to remove approval queue")?;
+    }
+    ui::success("Approvals disabled");
+    Ok(())
+}
+
+/// List queued payments
+pub async fn list(storage_dir: &Path, all: bool, verbose: bool) -> Result<()> {
+    ui::header("Payments Awaiting Approval");
+
+    let Some(mut queue) = load_queue(storage_dir)? else {
+        ui::info("Approvals are disabled");
+        return Ok(());
+    };
+    if !queue.expire(chrono::Utc::now().timestamp()).is_empty() {
+        save_queue(storage_dir, &queue)?;
+    }
+
+    let entries = if all {
+        queue.entries()
+    } else {
+        queue.pending()
+    };
+    if entries.is_empty() {
+        ui::info("Nothing to approve");
+        return Ok(());
+    }
+
+    for entry in entries {
+        show_entry(entry, verbose);
+        ui::separator();
+    }
+    Ok(())
+}
+
+/// Approve or reject a queued payment as the current identity
+pub async fn decide(
+    storage_dir: &Path,
+    approval_id: &str,
+    decision: ApprovalDecision,
+    _verbose: bool,
+) -> Result<()> {
+    let identity = super::load_current_identity(storage_dir).await?;
+    let mut queue = load_queue(storage_dir)?.context("Approvals are disabled")?;
+    let pending = queue
+        .get(approval_id)
+        .with_context(|| format!("No queued payment {}", approval_id))?
+        .clone();
+
+    let now = chrono::Utc::now().timestamp();
+    let approval = SignedApproval::sign(&identity.keypair, &pending, decision, now);
+    super::audit::record(
+        storage_dir,
+        AuditOperation::Sign,
+        Some(&pending.proposal.payee),
+        Some(&format!("approval {}", approval_id)),
+    );
+    let status = queue.apply(&approval, now)?;
+    save_queue(storage_dir, &queue)?;
+
+    report_status(approval_id, status);
+    Ok(())
+}
+
+/// Print a queued payment as JSON for a remote co-signer
+pub async fn export(storage_dir: &Path, approval_id: &str, _verbose: bool) -> Result<()> {
+    let queue = load_queue(storage_dir)?.context("Approvals are disabled")?;
+    let pending = queue
+        .get(approval_id)
+        .with_context(|| format!("No queued payment {}", approval_id))?;
+
+    if output::is_json() {
+        output::emit(pending);
+    } else {
+        println!("{}", serde_json::to_string(pending)?);
+    }
+    ui::info("Send this to the co-signer; they run 'approvals sign' on it");
+    Ok(())
+}
+
+/// Sign a payment exported by another initiator, as a remote co-signer
+pub async fn sign(
+    storage_dir: &Path,
+    pending_json: &str,
+    decision: ApprovalDecision,
+    _verbose: bool,
+) -> Result<()> {
+    let identity = super::load_current_identity(storage_dir).await?;
+    let pending: PendingApproval =
+        serde_json::from_str(&read_json_arg(pending_json)?).context("Invalid queued payment")?;
+
+    show_entry(&pending, false);
+    let verb = match decision {
+        ApprovalDecision::Approve => "Approve",
+        ApprovalDecision::Reject => "Reject",
+    };
+    if !ui::confirm(&format!("{} this payment?", verb), false)? {
+        ui::info("Cancelled");
+        return Ok(());
+    }
+
+    let approval = SignedApproval::sign(
+        &identity.keypair,
+        &pending,
+        decision,
+        chrono::Utc::now().timestamp(),
+    );
+    super::audit::record(
+        storage_dir,
+        AuditOperation::Sign,
+        Some(&pending.proposal.payee),
+        Some(&format!("approval {}", pending.approval_id)),
+    );
+    if output::is_json() {
+        output::emit(&approval);
+    } else {
+        println!("{}", approval.to_json()?);
+    }
+    ui::info("Send this back to the initiator; they run 'approvals import' on it");
+    Ok(())
+}
+
+/// Apply a signed approval from a remote co-signer
+pub async fn import(storage_dir: &Path, approval_json: &str, _verbose: bool) -> Result<()> {
+    let mut queue = load_queue(storage_dir)?.context("Approvals are disabled")?;
+    let approval = SignedApproval::from_json(&read_json_arg(approval_json)?)?;
+
+    let status = queue.apply(&approval, chrono::Utc::now().timestamp())?;
+    save_queue(storage_dir, &queue)?;
+
+    report_status(&approval.approval_id, status);
+    Ok(())
+}
+
+/// Gate a payment on the approval policy.
+///
+/// Returns the approval to mark executed after paying, `None` when no
+/// approval is needed, or an error after queueing the payment. With a policy
+/// configured, payments whose amount is not given in sats are refused.
+pub(crate) fn enforce(
+    storage_dir: &Path,
+    payee: &str,
+    method: &str,
+    amount: Option<&str>,
+    currency: Option<&str>,
+    dry_run: bool,
+) -> Result<Option<String>> {
+    let Some(mut queue) = load_queue(storage_dir)? else {
+        return Ok(None);
+    };
+    // Without an amount in sats the threshold cannot be checked, so refuse
+    let Some(amount_sats) = super::amount_sats(amount, currency) else {
+        if dry_run {
+            ui::warning("A real payment would be refused: the amount must be given in whole sats");
+            return Ok(None);
+        }
+        anyhow::bail!(
+            "An approval policy is configured; give the amount in whole sats so it can be checked"
+        );
+    };
+    if !queue.requires_approval(amount_sats) {
+        return Ok(None);
+    }
+    if let Some(approved) = queue.find_approved(payee, method, amount_sats) {
+        ui::success(&format!(
+            "Approved by co-signers ({})",
+            approved.approval_id
+        ));
+        // A dry run must not consume the approval
+        return Ok((!dry_run).then(|| approved.approval_id.clone()));
+    }
+    if dry_run {
+        ui::warning("A real payment would require co-signer approval");
+        return Ok(None);
+    }
+
+    let pending = queue.submit(
+        PaymentProposal::new(payee, method, amount_sats),
+        chrono::Utc::now().timestamp(),
+    );
+    save_queue(storage_dir, &queue)?;
+
+    ui::warning(&format!(
+        "Payments above {} sats need {} of {} co-signer approvals",
+        queue.policy().threshold_sats,
+        pending.required,
+        pending.cosigners.len()
+    ));
+    ui::key_value("Approval ID", &pending.approval_id);
+    ui::info("Run 'paykit-demo approvals list' to follow progress, then retry the payment");
+    anyhow::bail!("Payment queued for approval")
+}
+
+/// Mark an approved payment as executed so the approval cannot be reused.
+pub(crate) fn complete(storage_dir: &Path, approval_id: &str) -> Result<()> {
+    let mut queue = load_queue(storage_dir)?.context("Approvals are disabled")?;
+    queue.mark_executed(approval_id)?;
+    save_queue(storage_dir, &queue)
+}
+
+fn show_policy(policy: &ApprovalPolicy) {
+    ui::key_value("Threshold", &format!("{} sats", policy.threshold_sats));
+    ui::key_value(
+        "Required",
+        &format!("{} of {}", policy.required, policy.cosigners.len()),
+    );
+    ui::key_value("Expires after", &format!("{} seconds", policy.ttl_secs));
+    for cosigner in &policy.cosigners {
+        ui::info(&format!("  co-signer {}", cosigner));
+    }
+}
+
+fn show_entry(entry: &PendingApproval, verbose: bool) {
+    ui::key_value("ID", &entry.approval_id);
+    ui::key_value("Payee", &entry.proposal.payee);
+    ui::key_value(
+        "Amount",
+        &format!(
+            "{} sats via {}",
+            entry.proposal.amount_sats, entry.proposal.method_id
+        ),
+    );
+    ui::key_value("Status", entry.status.as_str());
+    ui::key_value(
+        "Approvals",
+        &format!("{} of {}", entry.approvals.len(), entry.required),
+    );
+    if verbose {
+        for key in entry.outstanding() {
+            ui::info(&format!("  waiting on {}", key));
+        }
+        for rejection in &entry.rejections {
+            ui::info(&format!("  rejected by {}", rejection.signer));
+        }
+    }
+}
+
+fn report_status(approval_id: &str, status: ApprovalStatus) {
+    match status {
+        ApprovalStatus::Approved => ui::success(&format!(
+            "{} approved - retry the payment to send it",
+            approval_id
+        )),
+        ApprovalStatus::Rejected => ui::error(&format!("{} rejected", approval_id)),
+        other => ui::info(&format!("{} is {}", approval_id, other.as_str())),
+    }
+}
+
+/// Accept either inline JSON or a path to a file containing it.
+fn read_json_arg(arg: &str) -> Result<String> {
+    if arg.trim_start().starts_with('{') {
+        return Ok(arg.to_string());
+    }
+    std::fs::read_to_string(arg).with_context(|| format!("Failed to read {}", arg))
+}
+
+fn queue_path(storage_dir: &Path) -> std::path::PathBuf {
+    storage_dir.join("approvals.json")
+}
+
+/// Load the approval queue, or `None` if approvals are disabled.
+fn load_queue(storage_dir: &Path) -> Result<Option<ApprovalQueue>> {
+    let path = queue_path(storage_dir);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let contents = std::fs::read_to_string(&path).context("Failed to read approval queue")?;
+    let queue = serde_json::from_str(&contents).context("Failed to parse approval queue")?;
+    Ok(Some(queue))
+}
+
+/// Persist the approval queue.
+fn save_queue(storage_dir: &Path, queue: &ApprovalQueue) -> Result<()> {
+    std::fs::create_dir_all(storage_dir)?;
+    let contents =
+        serde_json::to_string_pretty(queue).context("Failed to serialize approval queue")?;
+    std::fs::write(queue_path(storage_dir), contents).context("Failed to save approval queue")
+}
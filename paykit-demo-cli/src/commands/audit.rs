@@ -0,0 +1,139 @@
+//! Key usage audit log commands
+//!
+//! Signing and handshake code paths call [`record`]; the `audit` command
+//! lists, verifies and exports what was recorded.
+
+use anyhow::{Context, Result};
+use paykit_lib::audit::{AuditEntry, AuditLog, AuditOperation, AuditQuery};
+use std::path::Path;
+
+use crate::{output, ui};
+
+/// Record a use of the identity key
+///
+/// Auditing must never block the operation itself, so failures are only
+/// reported as warnings.
+pub fn record(
+    storage_dir: &Path,
+    operation: AuditOperation,
+    counterparty: Option<&str>,
+    detail: Option<&str>,
+) {
+    let storage = super::storage::open(storage_dir);
+    if let Err(e) = storage.record_audit(operation, counterparty, detail) {
+        ui::warning(&format!("Could not write audit log: {:#}", e));
+    }
+}
+
+/// Build a query from command-line filters
+pub fn query(
+    operation: Option<&str>,
+    counterparty: Option<&str>,
+    since_days: Option<u32>,
+    limit: Option<usize>,
+) -> Result<AuditQuery> {
+    let mut query = AuditQuery::new();
+    if let Some(operation) = operation {
+        query = query.operation(operation.parse::<AuditOperation>()?);
+    }
+    if let Some(counterparty) = counterparty {
+        query = query.counterparty(counterparty);
+    }
+    if let Some(days) = since_days {
+        query = query.since(chrono::Utc::now().timestamp() - i64::from(days) * 86_400);
+    }
+    if let Some(limit) = limit {
+        query = query.limit(limit);
+    }
+    Ok(query)
+}
+
+/// List recorded key uses
+pub async fn list(storage_dir: &Path, query: AuditQuery, verbose: bool) -> Result<()> {
+    ui::header("Key Usage Audit Log");
+
+    let log = super::storage::open(storage_dir).load_audit_log()?;
+    let entries = log.query(&query);
+    output::emit(&entries);
+    if entries.is_empty() {
+        ui::info("No matching entries");
+        return Ok(());
+    }
+
+    for entry in &entries {
+        print_entry(entry, verbose);
+    }
+    ui::separator();
+    ui::key_value(
+        "Showing",
+        &format!("{} of {} entries", entries.len(), log.len()),
+    );
+    Ok(())
+}
+
+/// Check the hash chain
+pub async fn verify(storage_dir: &Path, _verbose: bool) -> Result<()> {
+    ui::header("Verify Audit Log");
+
+    // Loading verifies the chain
+    let log = super::storage::open(storage_dir).load_audit_log()?;
+
+    ui::success(&format!("Hash chain intact ({} entries)", log.len()));
+    ui::key_value("Head hash", log.head_hash());
+    ui::info("Keep a copy of the head hash to detect truncation later");
+    Ok(())
+}
+
+/// Export recorded key uses as JSON Lines or CSV
+pub async fn export(
+    storage_dir: &Path,
+    query: AuditQuery,
+    format: &str,
+    output: Option<&str>,
+    _verbose: bool,
+) -> Result<()> {
+    let log = super::storage::open(storage_dir).load_audit_log()?;
+    let entries = log.query(&query);
+
+    let contents = match format {
+        "csv" => AuditLog::to_csv(&entries),
+        "jsonl" | "json" => {
+            let mut out = String::new();
+            for entry in &entries {
+                out.push_str(&serde_json::to_string(entry)?);
+                out.push('\n');
+            }
+            out
+        }
+        other => anyhow::bail!("Unsupported format '{}'; use jsonl or csv", other),
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, contents)
+                .with_context(|| format!("Failed to write audit export to {}", path))?;
+            ui::success(&format!("Exported {} entries to {}", entries.len(), path));
+        }
+        None if output::is_json() => output::emit(&entries),
+        None => print!("{}", contents),
+    }
+    Ok(())
+}
+
+fn print_entry(entry: &AuditEntry, verbose: bool) {
+    let time = chrono::DateTime::from_timestamp(entry.timestamp, 0)
+        .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_else(|| entry.timestamp.to_string());
+
+    let mut line = format!("#{} {} {}", entry.sequence, time, entry.operation);
+    if let Some(counterparty) = &entry.counterparty {
+        line.push_str(&format!(" with {}", counterparty));
+    }
+    if let Some(detail) = &entry.detail {
+        line.push_str(&format!(" ({})", detail));
+    }
+    ui::line(&line);
+    if verbose {
+        ui::line(&format!("    hash {}", entry.hash));
+    }
+}
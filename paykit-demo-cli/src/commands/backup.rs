@@ -6,11 +6,30 @@ use std::path::Path;
 use crate::ui;
 
 /// Export current identity to encrypted backup
-pub async fn export(storage_dir: &Path, output: Option<&str>, verbose: bool) -> Result<()> {
+///
+/// With `keyring` set the secret is read straight from the OS keyring, which
+/// recovers keys whose identity file has gone missing.
+pub async fn export(
+    storage_dir: &Path,
+    output: Option<&str>,
+    keyring: bool,
+    verbose: bool,
+) -> Result<()> {
     ui::header("Export Identity Backup");
 
     // Load current identity
-    let identity = super::load_current_identity(storage_dir).await?;
+    let identity = if keyring {
+        if !paykit_demo_core::identity::keyring_supported() {
+            anyhow::bail!("This build has no keyring support; rebuild with `--features keyring`");
+        }
+        let name = super::get_current_identity(storage_dir)?.ok_or_else(|| {
+            anyhow::anyhow!("No identity configured. Run 'paykit-demo setup' first.")
+        })?;
+        paykit_demo_core::IdentityManager::new(storage_dir.join("identities"))
+            .load_from_keyring(&name)?
+    } else {
+        super::load_current_identity(storage_dir).await?
+    };
 
     ui::info(&format!("Identity: {}", identity.pubky_uri()));
 
@@ -36,24 +55,29 @@ pub async fn export(storage_dir: &Path, output: Option<&str>, verbose: bool) ->
 
     // Create backup
     let backup = identity.export_backup(&password)?;
+    super::audit::record(
+        storage_dir,
+        paykit_lib::audit::AuditOperation::KeyExport,
+        None,
+        Some("encrypted backup"),
+    );
 
     // Determine output path
     let output_path = if let Some(path) = output {
         Path::new(path).to_path_buf()
     } else {
         let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-        Path::new(&format!("paykit_backup_{}.json", timestamp)).to_path_buf()
+        Path::new(&format!("paykit_backup_{}.txt", timestamp)).to_path_buf()
     };
 
-    // Serialize and write
-    let json = backup.to_json()?;
-    std::fs::write(&output_path, &json)
+    // Armor and write
+    std::fs::write(&output_path, backup.to_armored()?)
         .with_context(|| format!("Failed to write backup to {:?}", output_path))?;
 
     ui::success(&format!("Backup saved to: {}", output_path.display()));
     ui::info(&format!(
         "Public key (for verification): {}",
-        backup.public_key_z32
+        backup.public_key_z32()
     ));
     ui::separator();
     ui::warning("IMPORTANT: Store this backup securely and remember your password!");
@@ -63,24 +87,32 @@ pub async fn export(storage_dir: &Path, output: Option<&str>, verbose: bool) ->
 }
 
 /// Import identity from encrypted backup
+///
+/// With `keyring` set the restored secret is stored in the OS keyring.
 pub async fn import(
     storage_dir: &Path,
     input: &str,
     name: Option<&str>,
+    keyring: bool,
     verbose: bool,
 ) -> Result<()> {
     ui::header("Restore Identity from Backup");
 
     // Read backup file
-    let json = std::fs::read_to_string(input)
+    let text = std::fs::read_to_string(input)
         .with_context(|| format!("Failed to read backup file: {}", input))?;
 
-    // Parse backup
-    let backup = paykit_demo_core::KeyBackup::from_json(&json)?;
+    // Parse backup (armored, or JSON from older versions)
+    let backup = paykit_demo_core::KeyBackup::decode(&text).context("Invalid backup file")?;
 
     if verbose {
-        ui::info(&format!("Backup version: {}", backup.version));
-        ui::info(&format!("Public key: {}", backup.public_key_z32));
+        let kdf = backup.kdf_params();
+        ui::info(&format!("Backup version: {}", backup.version()));
+        ui::info(&format!(
+            "Key derivation: Argon2id, {} KiB, {} passes",
+            kdf.memory_kib, kdf.iterations
+        ));
+        ui::info(&format!("Public key: {}", backup.public_key_z32()));
     }
 
     // Get password
@@ -94,7 +126,7 @@ pub async fn import(
 
     // Verify public key matches
     let derived_pubkey = identity.public_key().to_string();
-    if derived_pubkey != backup.public_key_z32 {
+    if derived_pubkey != backup.public_key_z32() {
         anyhow::bail!("Public key mismatch - backup may be corrupted");
     }
 
@@ -106,9 +138,9 @@ pub async fn import(
         n.to_string()
     } else {
         // Prompt for name
-        print!("Enter a name for this identity: ");
+        eprint!("Enter a name for this identity: ");
         use std::io::Write;
-        std::io::stdout().flush()?;
+        std::io::stderr().flush()?;
         let mut input = String::new();
         std::io::stdin().read_line(&mut input)?;
         let trimmed = input.trim();
@@ -120,8 +152,10 @@ pub async fn import(
     };
 
     // Save identity
-    let manager = paykit_demo_core::IdentityManager::new(storage_dir.join("identities"));
-    manager.save(&identity, &identity_name)?;
+    let location = super::save_file_identity(storage_dir, &identity, &identity_name, keyring)?;
+    if verbose {
+        ui::info(&format!("Secret key stored in: {}", location));
+    }
 
     // Set as current identity
     let current_path = storage_dir.join("current_identity");
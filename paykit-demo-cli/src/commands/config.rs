@@ -0,0 +1,57 @@
+//! Config command - show the config file and its settings
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::{output, ui};
+
+/// `data` of `config --output json`
+#[derive(Serialize)]
+struct ConfigOutput<'a> {
+    path: Option<String>,
+    exists: bool,
+    settings: &'a Config,
+}
+
+pub async fn show(_verbose: bool) -> Result<()> {
+    ui::header("Configuration");
+
+    let path = Config::path();
+    let exists = path.as_ref().is_some_and(|p| p.exists());
+    let config = Config::load()?;
+
+    match &path {
+        Some(path) => ui::key_value("Config file", &path.display().to_string()),
+        None => ui::warning("No config directory on this platform"),
+    }
+    if !exists {
+        ui::info("Not found; built-in defaults apply");
+    }
+
+    ui::separator();
+    let unset = "(unset)".to_string();
+    ui::key_value("homeserver", config.homeserver.as_ref().unwrap_or(&unset));
+    ui::key_value("network", config.network.as_ref().unwrap_or(&unset));
+    ui::key_value("output", config.output.as_ref().unwrap_or(&unset));
+    ui::key_value("storage_dir", config.storage_dir.as_ref().unwrap_or(&unset));
+    ui::key_value("proxy", config.proxy.as_ref().unwrap_or(&unset));
+    ui::key_value(
+        "proxy_bypass",
+        &config
+            .proxy_bypass
+            .as_ref()
+            .map_or(unset.clone(), |kinds| kinds.join(",")),
+    );
+    ui::key_value("profile", config.profile.as_ref().unwrap_or(&unset));
+    ui::key_value("path_root", config.path_root.as_ref().unwrap_or(&unset));
+    ui::separator();
+    ui::info("Flags override environment variables, which override this file");
+
+    output::emit(&ConfigOutput {
+        path: path.map(|p| p.display().to_string()),
+        exists,
+        settings: &config,
+    });
+    Ok(())
+}
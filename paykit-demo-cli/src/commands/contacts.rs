@@ -2,10 +2,11 @@
 
 use anyhow::{Context, Result};
 use colored::Colorize;
-use paykit_demo_core::{Contact, DemoStorage};
+use paykit_demo_core::Contact;
+use paykit_lib::contact_verification::{short_auth_string, VerificationChallenge};
 use std::path::Path;
 
-use crate::ui;
+use crate::{output, ui};
 
 pub async fn add(
     storage_dir: &Path,
@@ -32,7 +33,7 @@ pub async fn add(
     }
 
     // Save to storage
-    let storage = DemoStorage::new(storage_dir.join("data"));
+    let storage = super::storage::open(storage_dir);
     storage.init()?;
     storage.save_contact(contact)?;
 
@@ -44,7 +45,7 @@ pub async fn add(
 pub async fn list(storage_dir: &Path, search_query: Option<&str>, _verbose: bool) -> Result<()> {
     ui::header("Contacts");
 
-    let storage = DemoStorage::new(storage_dir.join("data"));
+    let storage = super::storage::open(storage_dir);
     let mut contacts = storage.list_contacts()?;
 
     // Filter by search query if provided
@@ -73,6 +74,7 @@ pub async fn list(storage_dir: &Path, search_query: Option<&str>, _verbose: bool
             ui::info("No contacts found");
             ui::info("Use 'paykit-demo contacts add' to add contacts");
         }
+        output::emit(&contacts);
         return Ok(());
     }
 
@@ -81,8 +83,9 @@ pub async fn list(storage_dir: &Path, search_query: Option<&str>, _verbose: bool
         ui::separator();
     }
 
+    output::emit(&contacts);
     for contact in contacts {
-        println!("\n{}", contact.name.bold());
+        ui::line(&format!("\n{}", contact.name.bold()));
         ui::key_value("  URI", &contact.pubky_uri());
         if let Some(notes) = &contact.notes {
             ui::key_value("  Notes", notes);
@@ -93,6 +96,7 @@ pub async fn list(storage_dir: &Path, search_query: Option<&str>, _verbose: bool
                 .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
                 .unwrap_or_else(|| "Unknown".to_string()),
         );
+        ui::key_value("  Verified", &verified_label(&contact));
     }
 
     Ok(())
@@ -103,7 +107,7 @@ pub async fn remove(storage_dir: &Path, name: &str, verbose: bool) -> Result<()>
         ui::info(&format!("Removing contact: {}", name));
     }
 
-    let storage = DemoStorage::new(storage_dir.join("data"));
+    let storage = super::storage::open(storage_dir);
 
     // Try to find contact by name first
     let contacts = storage.list_contacts()?;
@@ -123,7 +127,7 @@ pub async fn remove(storage_dir: &Path, name: &str, verbose: bool) -> Result<()>
 pub async fn show(storage_dir: &Path, name: &str, _verbose: bool) -> Result<()> {
     ui::header(&format!("Contact: {}", name));
 
-    let storage = DemoStorage::new(storage_dir.join("data"));
+    let storage = super::storage::open(storage_dir);
     let contacts = storage.list_contacts()?;
 
     let contact = contacts
@@ -146,13 +150,81 @@ pub async fn show(storage_dir: &Path, name: &str, _verbose: bool) -> Result<()>
             .unwrap_or_else(|| "Unknown".to_string()),
     );
 
+    ui::key_value("Verified", &verified_label(contact));
+
     // Show QR code
-    println!();
+    ui::line("");
     ui::qr_code(&contact.pubky_uri())?;
 
     Ok(())
 }
 
+/// Verify a contact's key out of band
+///
+/// Without `code`, shows the short authentication string to compare with
+/// the contact and a QR code for them to scan. With `code`, checks a
+/// verification code scanned from the contact's device.
+pub async fn verify(
+    storage_dir: &Path,
+    name: &str,
+    code: Option<&str>,
+    _verbose: bool,
+) -> Result<()> {
+    ui::header(&format!("Verify Contact: {}", name));
+
+    let identity = super::load_current_identity(storage_dir).await?;
+    let storage = super::storage::open(storage_dir);
+    let mut contact = storage
+        .list_contacts()?
+        .into_iter()
+        .find(|c| c.name == name)
+        .ok_or_else(|| anyhow::anyhow!("Contact '{}' not found", name))?;
+
+    let local_key = identity.public_key().to_string();
+    let contact_key = contact.public_key.to_string();
+
+    match code {
+        Some(code) => {
+            VerificationChallenge::parse(code)?.verify(&local_key, &contact_key)?;
+            ui::success("Verification code matches");
+        }
+        None => {
+            let sas = short_auth_string(&local_key, &contact_key)?;
+            ui::info(&format!(
+                "Compare this code with {} in person or over a call:",
+                name
+            ));
+            ui::line(&format!("\n    {}\n", sas.bold()));
+            ui::info("Or have them scan this code and run 'contacts verify --code <code>':");
+            ui::qr_code(&VerificationChallenge::new(&local_key, &contact_key)?.to_code())?;
+
+            if !ui::confirm("Does the code match on both devices?", false)? {
+                ui::warning(
+                    "Contact not verified. A different code means one of the keys was substituted",
+                );
+                return Ok(());
+            }
+        }
+    }
+
+    contact.verified_at = Some(chrono::Utc::now().timestamp());
+    storage.save_contact(contact.clone())?;
+    output::emit(&contact);
+
+    ui::success(&format!("Contact '{}' verified", name));
+
+    Ok(())
+}
+
+fn verified_label(contact: &Contact) -> String {
+    match contact.verified_at {
+        Some(verified_at) => chrono::DateTime::from_timestamp(verified_at, 0)
+            .map(|dt| format!("yes ({})", dt.format("%Y-%m-%d %H:%M:%S")))
+            .unwrap_or_else(|| "yes".to_string()),
+        None => "no".to_string(),
+    }
+}
+
 /// Discover contacts from Pubky follows directory
 pub async fn discover(
     storage_dir: &Path,
@@ -180,7 +252,8 @@ pub async fn discover(
     // Try to get known contacts from the follows path
     // This uses the UnauthenticatedTransport to query the public directory
     let storage = pubky::PublicStorage::new().context("Failed to create PublicStorage")?;
-    let transport = paykit_lib::PubkyUnauthenticatedTransport::new(storage);
+    let transport = paykit_lib::PubkyUnauthenticatedTransport::new(storage)
+        .with_path_roots(super::path_roots());
 
     let discovered = paykit_lib::get_known_contacts(&transport, &identity.public_key())
         .await
@@ -199,7 +272,7 @@ pub async fn discover(
     ui::success(&format!("Found {} contact(s)", discovered.len()));
     ui::separator();
 
-    let demo_storage = DemoStorage::new(storage_dir.join("data"));
+    let demo_storage = super::storage::open(storage_dir);
     demo_storage.init()?;
     let existing_contacts = demo_storage.list_contacts()?;
 
@@ -258,3 +331,69 @@ pub async fn discover(
 
     Ok(())
 }
+
+/// Import contacts from a CSV, vCard or nostr contact list file
+pub async fn import(
+    storage_dir: &Path,
+    file: &Path,
+    format: Option<&str>,
+    dry_run: bool,
+    verbose: bool,
+) -> Result<()> {
+    use paykit_demo_core::contacts::{import_contacts, ImportFormat, SkipReason};
+
+    ui::header("Import Contacts");
+
+    let format = match format {
+        Some(format) => format.parse::<ImportFormat>()?,
+        None => ImportFormat::from_path(file).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Cannot tell the format of {} (use --format)",
+                file.display()
+            )
+        })?,
+    };
+    let input = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read {}", file.display()))?;
+
+    let storage = super::storage::open(storage_dir);
+    storage.init()?;
+    let summary = import_contacts(&storage, format, &input, dry_run)?;
+
+    for contact in &summary.imported {
+        let tag = if dry_run { "[new]" } else { "[imported]" };
+        ui::success(&format!(
+            "  {} {} ({})",
+            tag,
+            contact.name,
+            contact.pubky_uri()
+        ));
+    }
+    for skipped in &summary.skipped {
+        if verbose || !matches!(skipped.reason, SkipReason::AlreadyExists(_)) {
+            ui::warning(&format!(
+                "  [skipped] entry {} {}: {}",
+                skipped.entry,
+                skipped.name.as_deref().unwrap_or("(unnamed)"),
+                skipped.reason
+            ));
+        }
+    }
+
+    ui::separator();
+    ui::key_value("Entries read", &summary.total.to_string());
+    ui::key_value("New contacts", &summary.imported.len().to_string());
+    ui::key_value("Already known", &summary.duplicates().to_string());
+    ui::key_value(
+        "Skipped",
+        &(summary.skipped.len() - summary.duplicates()).to_string(),
+    );
+
+    if dry_run {
+        ui::info("Dry run: nothing was saved");
+    } else {
+        ui::success(&format!("Imported {} contact(s)", summary.imported.len()));
+    }
+
+    Ok(())
+}
@@ -1,7 +1,6 @@
 //! Dashboard command - show summary statistics
 
 use anyhow::Result;
-use paykit_demo_core::DemoStorage;
 use std::path::Path;
 
 use crate::ui;
@@ -49,7 +48,7 @@ pub async fn run(storage_dir: &Path, verbose: bool) -> Result<()> {
     }
 
     // Check contacts
-    let storage = DemoStorage::new(storage_dir.join("data"));
+    let storage = super::storage::open(storage_dir);
     let contacts = storage.list_contacts().unwrap_or_default();
     if !contacts.is_empty() {
         ui::success(&format!("  ✅ {} contact(s) added", contacts.len()));
@@ -81,7 +80,7 @@ pub async fn run(storage_dir: &Path, verbose: bool) -> Result<()> {
     ui::separator();
 
     // Load storage
-    let storage = DemoStorage::new(storage_dir.join("data"));
+    let storage = super::storage::open(storage_dir);
 
     // Contacts summary
     let contacts = storage.list_contacts().unwrap_or_default();
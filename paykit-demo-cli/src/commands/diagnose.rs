@@ -0,0 +1,90 @@
+//! Diagnose command - check reachability of a Noise endpoint
+//!
+//! Probes every address of a payee's Noise endpoint and reports which ones
+//! accept connections and how fast, to help debug failed payments.
+
+use anyhow::{Context, Result};
+use paykit_interactive::dial::{diagnose_endpoint, AddressFamily};
+use paykit_lib::protocol::fetch_noise_endpoint;
+use std::path::Path;
+
+use crate::{output, ui};
+
+#[tracing::instrument(skip(_storage_dir))]
+pub async fn run(_storage_dir: &Path, endpoint: &str, json: bool, verbose: bool) -> Result<()> {
+    let endpoint = resolve_endpoint(endpoint).await?;
+
+    if !json {
+        ui::header("Endpoint Diagnostics");
+        ui::key_value("Endpoint", &endpoint);
+        if super::proxy_for(paykit_lib::proxy::TransportKind::Noise).is_some() {
+            ui::warning("Diagnostics connect directly and bypass the configured proxy");
+        }
+    }
+
+    let spinner = (!json).then(|| ui::spinner("Probing addresses..."));
+    let report = diagnose_endpoint(&endpoint).await;
+    if let Some(spinner) = spinner {
+        spinner.finish_and_clear();
+    }
+
+    if output::is_json() {
+        output::emit(&report);
+        return Ok(());
+    }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    if verbose {
+        ui::key_value("Resolution", &format!("{} ms", report.resolve_ms));
+    }
+    if let Some(error) = &report.resolve_error {
+        ui::error(error);
+        return Ok(());
+    }
+
+    ui::separator();
+    for probe in &report.probes {
+        match (probe.latency_ms, &probe.error) {
+            (Some(latency), _) => ui::success(&format!("{}  {} ms", probe.address, latency)),
+            (None, Some(error)) => ui::error(&format!("{}  {}", probe.address, error)),
+            (None, None) => ui::error(&probe.address.to_string()),
+        }
+    }
+    ui::separator();
+
+    match report.fastest() {
+        Some(fastest) => ui::success(&format!("Reachable; fastest address {}", fastest.address)),
+        None => ui::error("No address is reachable"),
+    }
+    for family in [AddressFamily::Ipv6, AddressFamily::Ipv4] {
+        let has_family = report.probes.iter().any(|probe| probe.family == family);
+        if has_family && !report.family_reachable(family) {
+            ui::warning(&format!("No {:?} address is reachable", family));
+        }
+    }
+
+    Ok(())
+}
+
+/// Accept `host:port` directly, or look up a Pubky URI's published Noise endpoint.
+async fn resolve_endpoint(endpoint: &str) -> Result<String> {
+    let Some(key) = endpoint.strip_prefix("pubky://") else {
+        return Ok(endpoint.to_string());
+    };
+    let owner: paykit_lib::PublicKey = key.parse().context("Invalid Pubky URI")?;
+    let storage = pubky::PublicStorage::new().context("Failed to create PublicStorage")?;
+    let transport = paykit_lib::PubkyUnauthenticatedTransport::new(storage)
+        .with_path_roots(super::path_roots());
+    let record = fetch_noise_endpoint(&transport, &owner)
+        .await
+        .context("Failed to fetch Noise endpoint")?
+        .context("Payee does not publish a Noise endpoint")?;
+    if record.host.contains(':') {
+        Ok(format!("[{}]:{}", record.host, record.port))
+    } else {
+        Ok(format!("{}:{}", record.host, record.port))
+    }
+}
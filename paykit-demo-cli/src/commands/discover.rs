@@ -1,10 +1,23 @@
 //! Discover command - query payment methods from a Pubky URI
 
 use anyhow::{Context, Result};
-use paykit_demo_core::DirectoryClient;
+use serde::Serialize;
 use std::path::Path;
 
-use crate::ui;
+use crate::{output, ui};
+
+/// `data` of `discover --output json`
+#[derive(Serialize)]
+struct DiscoverOutput {
+    public_key: String,
+    methods: Vec<MethodOutput>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct MethodOutput {
+    pub method_id: String,
+    pub endpoint: String,
+}
 
 #[tracing::instrument(skip(_storage_dir))]
 pub async fn run(_storage_dir: &Path, uri: &str, homeserver: &str, verbose: bool) -> Result<()> {
@@ -21,36 +34,45 @@ pub async fn run(_storage_dir: &Path, uri: &str, homeserver: &str, verbose: bool
     }
 
     // Create directory client
-    let client = DirectoryClient::new(homeserver);
+    let client = super::directory_client(homeserver);
 
     // Query methods
     let spinner = ui::spinner("Querying directory...");
 
-    match client.query_methods(&public_key).await {
-        Ok(methods) => {
-            spinner.finish_and_clear();
-
-            if methods.is_empty() {
-                ui::info("No payment methods found");
-            } else {
-                ui::success(&format!("Found {} payment method(s)", methods.len()));
-                ui::separator();
-
-                for method in methods {
-                    ui::key_value(&method.method_id, &method.endpoint);
-                }
-            }
-        }
+    let methods = client.query_methods(&public_key).await;
+    spinner.finish_and_clear();
+    let methods = match methods {
+        Ok(methods) => methods,
         Err(e) => {
-            spinner.finish_and_clear();
-            ui::error(&format!("Failed to query directory: {}", e));
-
             if verbose {
                 ui::info(&format!("Error details: {:?}", e));
             }
+            return Err(e.context("Failed to query directory"));
+        }
+    };
+
+    if methods.is_empty() {
+        ui::info("No payment methods found");
+    } else {
+        ui::success(&format!("Found {} payment method(s)", methods.len()));
+        ui::separator();
+
+        for method in &methods {
+            ui::key_value(&method.method_id, &method.endpoint);
         }
     }
 
+    output::emit(&DiscoverOutput {
+        public_key: public_key.to_string(),
+        methods: methods
+            .into_iter()
+            .map(|method| MethodOutput {
+                method_id: method.method_id,
+                endpoint: method.endpoint,
+            })
+            .collect(),
+    });
+
     Ok(())
 }
 
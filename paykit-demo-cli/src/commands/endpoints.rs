@@ -325,7 +325,7 @@ pub async fn stats(storage_dir: &Path, _verbose: bool) -> Result<()> {
 }
 
 /// Load the private endpoint manager
-fn load_endpoint_manager(storage_dir: &Path) -> Result<PrivateEndpointManager<FileStore>> {
+pub fn load_endpoint_manager(storage_dir: &Path) -> Result<PrivateEndpointManager<FileStore>> {
     let endpoints_dir = storage_dir.join("private_endpoints");
     let key_path = storage_dir.join(".endpoint_key");
 
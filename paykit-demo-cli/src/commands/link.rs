@@ -0,0 +1,277 @@
+//! Payment link commands
+//!
+//! A payment link is a checkout link for a Pubky identity: an amount, an
+//! expiry and a single- or multi-use flag, published in the directory and
+//! shared as `paykit://link/{payee}/{link_id}`. Payers pay it with
+//! `pay <link>`; `receive` consumes the link when it confirms the receipt,
+//! and `link sync` publishes the new use count.
+
+use anyhow::{Context, Result};
+use paykit_demo_core::{PaymentLinkCoordinator, StoredLink};
+use paykit_lib::protocol::{
+    parse_payment_link_uri, payment_link_uri, resolve_payment_link, LinkUsage, PaymentIntent,
+    PaymentLink, SuccessAction,
+};
+use paykit_lib::MethodId;
+use std::path::Path;
+
+use crate::ui;
+
+/// Options for `link create`
+pub struct LinkOptions {
+    pub amount: Option<u64>,
+    pub memo: Option<String>,
+    pub expires_in: u64,
+    pub multi_use: bool,
+    pub max_uses: Option<u32>,
+    pub methods: Vec<String>,
+    pub redirect_url: Option<String>,
+    pub success_message: Option<String>,
+}
+
+/// Create and publish a payment link
+#[tracing::instrument(skip(storage_dir, options))]
+pub async fn create(
+    storage_dir: &Path,
+    id: &str,
+    options: LinkOptions,
+    homeserver: &str,
+    verbose: bool,
+) -> Result<()> {
+    ui::header("New Payment Link");
+
+    let identity = super::load_current_identity(storage_dir).await?;
+    let now = chrono::Utc::now().timestamp();
+
+    let usage = if options.multi_use || options.max_uses.is_some() {
+        LinkUsage::Multi {
+            max_uses: options.max_uses,
+        }
+    } else {
+        LinkUsage::Single
+    };
+    let mut link = PaymentLink::new(id, now)
+        .with_usage(usage)
+        .with_methods(options.methods.into_iter().map(MethodId::new).collect());
+    if let Some(amount) = options.amount {
+        link = link.with_amount(amount);
+    }
+    if let Some(memo) = options.memo {
+        link = link.with_memo(memo);
+    }
+    if options.expires_in > 0 {
+        link = link.with_ttl(options.expires_in);
+    }
+    if options.redirect_url.is_some() || options.success_message.is_some() {
+        link = link.with_success(SuccessAction {
+            redirect_url: options.redirect_url,
+            message: options.success_message,
+        });
+    }
+
+    let links = PaymentLinkCoordinator::new(storage_dir);
+    links.create(link.clone())?;
+    show_link(&link, now);
+
+    let client = super::directory_client(homeserver);
+    let spinner = ui::spinner("Publishing link...");
+    let session = client
+        .create_session(&identity.keypair, true)
+        .await
+        .context("Failed to establish session with homeserver")?;
+    let result = client.publish_link(&session, &link).await;
+    spinner.finish_and_clear();
+    if let Err(e) = result {
+        ui::warning("The link is saved but not published; run `link sync` to retry");
+        return Err(e);
+    }
+    links.mark_published(&link)?;
+
+    let uri = payment_link_uri(&identity.public_key(), id);
+    ui::success("Payment link published");
+    ui::key_value("Link", &uri);
+    if verbose {
+        ui::qr_code(&uri)?;
+    }
+    Ok(())
+}
+
+/// List payment links
+pub async fn list(storage_dir: &Path, _verbose: bool) -> Result<()> {
+    ui::header("Payment Links");
+
+    let links = PaymentLinkCoordinator::new(storage_dir).list()?;
+    if links.is_empty() {
+        ui::info("No payment links");
+        return Ok(());
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    for stored in &links {
+        show_stored(stored, now);
+        ui::separator();
+    }
+    Ok(())
+}
+
+/// Revoke a payment link and remove it from the directory
+#[tracing::instrument(skip(storage_dir))]
+pub async fn revoke(storage_dir: &Path, id: &str, homeserver: &str) -> Result<()> {
+    let links = PaymentLinkCoordinator::new(storage_dir);
+    if !links.revoke(id)? {
+        ui::warning(&format!("Payment link not found: {}", id));
+        return Ok(());
+    }
+    ui::success(&format!("Revoked payment link {}", id));
+
+    let identity = super::load_current_identity(storage_dir).await?;
+    let client = super::directory_client(homeserver);
+    let spinner = ui::spinner("Removing link from directory...");
+    let session = client
+        .create_session(&identity.keypair, true)
+        .await
+        .context("Failed to establish session with homeserver")?;
+    let result = client.remove_link(&session, id).await;
+    spinner.finish_and_clear();
+    result?;
+
+    if let Some(stored) = links.get(id)? {
+        links.mark_published(&stored.link)?;
+    }
+    Ok(())
+}
+
+/// Publish the current state of links used or revoked since last published
+#[tracing::instrument(skip(storage_dir))]
+pub async fn sync(storage_dir: &Path, homeserver: &str) -> Result<()> {
+    ui::header("Sync Payment Links");
+
+    let links = PaymentLinkCoordinator::new(storage_dir);
+    let pending = links.unpublished()?;
+    if pending.is_empty() {
+        ui::info("All payment links are published");
+        return Ok(());
+    }
+
+    let identity = super::load_current_identity(storage_dir).await?;
+    let client = super::directory_client(homeserver);
+    let session = client
+        .create_session(&identity.keypair, true)
+        .await
+        .context("Failed to establish session with homeserver")?;
+
+    for link in pending {
+        let result = if link.revoked {
+            client.remove_link(&session, &link.link_id).await
+        } else {
+            client.publish_link(&session, &link).await
+        };
+        match result {
+            Ok(()) => {
+                links.mark_published(&link)?;
+                ui::success(&format!("{}: {}", link.link_id, link.state(now()).as_str()));
+            }
+            Err(e) => ui::error(&format!("{}: {}", link.link_id, e)),
+        }
+    }
+    Ok(())
+}
+
+/// Resolve a payment link into what paying it would involve
+#[tracing::instrument]
+pub async fn resolve(uri: &str) -> Result<()> {
+    ui::header("Payment Link");
+
+    let intent = fetch_intent(uri).await?;
+    show_intent(&intent);
+    ui::separator();
+    ui::info(&format!("Pay it with: paykit-demo pay {}", uri));
+    Ok(())
+}
+
+/// Fetch the payment intent behind a `paykit://link/...` URI
+pub(crate) async fn fetch_intent(uri: &str) -> Result<PaymentIntent> {
+    let (payee, link_id) = parse_payment_link_uri(uri)?;
+    let payee: paykit_lib::PublicKey = payee.parse().context("Invalid payee public key")?;
+
+    let storage = pubky::PublicStorage::new().context("Failed to create PublicStorage")?;
+    let transport = paykit_lib::PubkyUnauthenticatedTransport::new(storage)
+        .with_path_roots(super::path_roots());
+    let spinner = ui::spinner("Resolving payment link...");
+    let intent = resolve_payment_link(&transport, &payee, link_id, now()).await;
+    spinner.finish_and_clear();
+    Ok(intent?)
+}
+
+pub(crate) fn show_intent(intent: &PaymentIntent) {
+    ui::key_value("Payee", &format!("pubky://{}", intent.payee));
+    ui::key_value("Link", &intent.link_id);
+    match intent.amount_sats {
+        Some(amount) => ui::key_value("Amount", &ui::sats(amount)),
+        None => ui::key_value("Amount", "payer chooses"),
+    }
+    if let Some(memo) = &intent.memo {
+        ui::key_value("For", memo);
+    }
+    if !intent.methods.is_empty() {
+        let methods: Vec<&str> = intent.methods.iter().map(|m| m.0.as_str()).collect();
+        ui::key_value("Methods", &methods.join(", "));
+    }
+    if let Some(expires_at) = intent.expires_at {
+        ui::key_value("Expires", &format_time(expires_at));
+    }
+}
+
+/// What to show the payer once the link is paid
+pub(crate) fn show_success(intent: &PaymentIntent) {
+    let Some(success) = &intent.success else {
+        return;
+    };
+    if let Some(message) = &success.message {
+        ui::info(message);
+    }
+    if let Some(url) = &success.redirect_url {
+        ui::key_value("Continue at", url);
+    }
+}
+
+fn show_stored(stored: &StoredLink, now: i64) {
+    show_link(&stored.link, now);
+    if !stored.receipts.is_empty() {
+        ui::key_value("Receipts", &stored.receipts.join(", "));
+    }
+    if !stored.published {
+        ui::key_value("Directory", "out of date (run `link sync`)");
+    }
+}
+
+fn show_link(link: &PaymentLink, now: i64) {
+    ui::key_value("ID", &link.link_id);
+    ui::key_value("State", link.state(now).as_str());
+    match link.amount_sats {
+        Some(amount) => ui::key_value("Amount", &ui::sats(amount)),
+        None => ui::key_value("Amount", "payer chooses"),
+    }
+    if let Some(memo) = &link.memo {
+        ui::key_value("For", memo);
+    }
+    let uses = match link.usage.max_uses() {
+        Some(max) => format!("{} of {}", link.uses, max),
+        None => format!("{} (unlimited)", link.uses),
+    };
+    ui::key_value("Uses", &uses);
+    match link.expires_at {
+        Some(expires_at) => ui::key_value("Expires", &format_time(expires_at)),
+        None => ui::key_value("Expires", "never"),
+    }
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+fn format_time(secs: i64) -> String {
+    chrono::DateTime::from_timestamp(secs, 0)
+        .map(|time| time.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|| secs.to_string())
+}
@@ -3,9 +3,19 @@
 use anyhow::Result;
 use colored::Colorize;
 use paykit_demo_core::IdentityManager;
+use serde::Serialize;
 use std::path::Path;
 
-use crate::ui;
+use crate::{output, ui};
+
+/// `data` of `list --output json`
+#[derive(Serialize)]
+struct IdentityOutput {
+    name: String,
+    /// `None` when the identity could not be loaded
+    pubky_uri: Option<String>,
+    current: bool,
+}
 
 pub async fn run(storage_dir: &Path, _verbose: bool) -> Result<()> {
     ui::header("Saved Identities");
@@ -26,41 +36,59 @@ pub async fn run(storage_dir: &Path, _verbose: bool) -> Result<()> {
     if identities.is_empty() {
         ui::info("No identities found");
         ui::info("Run 'paykit-demo setup' to create one");
+        output::emit(&Vec::<IdentityOutput>::new());
         return Ok(());
     }
 
     let current = super::get_current_identity(storage_dir)?;
+    let mut listed = Vec::new();
 
     for name in identities {
-        // Load to get details
+        // Load to get details; file identities record their public key, so
+        // keyring and encrypted secrets don't need unlocking just to list them
         let load_result = if using_secure_storage {
             let secure_manager = paykit_demo_core::SecureIdentityManager::new(storage_dir);
-            secure_manager.load(&name).await
+            secure_manager
+                .load(&name)
+                .await
+                .map(|identity| identity.pubky_uri())
         } else {
             let identities_dir = storage_dir.join("identities");
             let identity_manager = IdentityManager::new(&identities_dir);
-            identity_manager.load(&name)
+            match identity_manager.public_key(&name) {
+                Ok(Some(public_key)) => Ok(format!("pubky://{}", public_key)),
+                _ => identity_manager
+                    .load(&name)
+                    .map(|identity| identity.pubky_uri()),
+            }
         };
 
+        let is_current = current.as_ref() == Some(&name);
+        listed.push(IdentityOutput {
+            name: name.clone(),
+            pubky_uri: load_result.as_ref().ok().cloned(),
+            current: is_current,
+        });
+
         match load_result {
-            Ok(identity) => {
-                let marker = if current.as_ref() == Some(&name) {
+            Ok(uri) => {
+                let marker = if is_current {
                     "→ ".to_string()
                 } else {
                     "  ".to_string()
                 };
 
-                println!(
+                ui::line(&format!(
                     "{}{}",
                     marker,
-                    if current.as_ref() == Some(&name) {
+                    if is_current {
                         name.green().bold().to_string()
                     } else {
                         name.clone()
                     }
-                );
+                ));
 
-                ui::key_value("  URI", &identity.pubky_uri());
+                ui::key_value("  URI", &uri);
             }
             Err(e) => {
                 ui::warning(&format!("Failed to load identity '{}': {}", name, e));
@@ -68,5 +96,6 @@ pub async fn run(storage_dir: &Path, _verbose: bool) -> Result<()> {
         }
     }
 
+    output::emit(&listed);
     Ok(())
 }
@@ -0,0 +1,455 @@
+//! Mirror command - keep a local read replica of public Paykit directories
+//!
+//! `mirror run` syncs a set of users' public paths on an interval and serves
+//! the replica over HTTP:
+//!
+//! - `GET /users` - mirrored users with their root hash
+//! - `GET /users/<pubkey>` - one user's manifest (paths and content hashes)
+//! - `GET /users/<pubkey>/pub/paykit.app/v0/...` - a mirrored file, with its
+//!   SHA-256 in the `ETag` and `X-Content-SHA256` headers
+
+use anyhow::{Context, Result};
+use paykit_demo_core::{DirectoryClient, DirectoryMirror, MirroredDirectory, SyncReport};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::{output, ui};
+
+/// `data` of `mirror status --output json`, and the `/users` response
+#[derive(Serialize)]
+struct UserSummary {
+    public_key: String,
+    synced_at: i64,
+    files: usize,
+    root_hash: String,
+}
+
+impl From<&MirroredDirectory> for UserSummary {
+    fn from(directory: &MirroredDirectory) -> Self {
+        Self {
+            public_key: directory.public_key.clone(),
+            synced_at: directory.synced_at,
+            files: directory.entries.len(),
+            root_hash: directory.root_hash(),
+        }
+    }
+}
+
+/// The `/users/<pubkey>` response
+#[derive(Serialize)]
+struct Manifest {
+    public_key: String,
+    synced_at: i64,
+    root_hash: String,
+    files: BTreeMap<String, FileSummary>,
+}
+
+#[derive(Serialize)]
+struct FileSummary {
+    sha256: String,
+    fetched_at: i64,
+    size: usize,
+}
+
+impl From<&MirroredDirectory> for Manifest {
+    fn from(directory: &MirroredDirectory) -> Self {
+        Self {
+            public_key: directory.public_key.clone(),
+            synced_at: directory.synced_at,
+            root_hash: directory.root_hash(),
+            files: directory
+                .entries
+                .iter()
+                .map(|(path, entry)| {
+                    (
+                        path.clone(),
+                        FileSummary {
+                            sha256: entry.sha256.clone(),
+                            fetched_at: entry.fetched_at,
+                            size: entry.content.len(),
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Sync the given users once (all mirrored users if none are given)
+pub async fn sync(storage_dir: &Path, uris: &[String], homeserver: &str) -> Result<()> {
+    ui::header("Sync Directory Mirror");
+
+    let mirror = DirectoryMirror::new(storage_dir);
+    let keys = mirror_keys(&mirror, uris)?;
+    let client = super::directory_client(homeserver);
+
+    let reports = sync_all(&mirror, &client, &keys).await;
+    output::emit(&reports);
+    Ok(())
+}
+
+/// Keep the given users in sync and serve the replica on `port`
+pub async fn run(
+    storage_dir: &Path,
+    uris: &[String],
+    homeserver: &str,
+    interval_secs: u64,
+    port: u16,
+) -> Result<()> {
+    ui::header("Directory Mirror");
+
+    let mirror = DirectoryMirror::new(storage_dir);
+    let keys = mirror_keys(&mirror, uris)?;
+    let client = super::directory_client(homeserver);
+
+    let listener = TcpListener::bind(format!("0.0.0.0:{}", port))
+        .await
+        .context("Failed to bind to port")?;
+
+    ui::info(&format!("Mirroring {} user(s)", keys.len()));
+    ui::info(&format!("Sync interval: {}s", interval_secs));
+    ui::success(&format!("Read replica listening on 0.0.0.0:{}", port));
+    ui::info("Press Ctrl+C to stop");
+    ui::separator();
+
+    // Syncing runs beside the server; files are replaced by rename, so a
+    // request never sees a half-written directory
+    let sync_dir = storage_dir.to_path_buf();
+    let syncer = tokio::spawn(async move {
+        let mirror = DirectoryMirror::new(&sync_dir);
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs.max(1)));
+        loop {
+            interval.tick().await;
+            sync_all(&mirror, &client, &keys).await;
+        }
+    });
+
+    loop {
+        tokio::select! {
+            result = listener.accept() => {
+                match result {
+                    Ok((socket, _)) => handle_request(&mirror, socket).await,
+                    Err(e) => ui::error(&format!("Accept error: {}", e)),
+                }
+            }
+            _ = tokio::signal::ctrl_c() => {
+                ui::info("\nMirror stopped");
+                break;
+            }
+        }
+    }
+
+    syncer.abort();
+    Ok(())
+}
+
+/// Show the mirrored users
+pub async fn status(storage_dir: &Path) -> Result<()> {
+    ui::header("Directory Mirror");
+
+    let mirror = DirectoryMirror::new(storage_dir);
+    let mut users = Vec::new();
+    for public_key in mirror.users()? {
+        if let Some(directory) = mirror.load(&public_key)? {
+            users.push(UserSummary::from(&directory));
+        }
+    }
+
+    if users.is_empty() {
+        ui::info("No users mirrored. Run 'paykit-demo mirror sync <pubkey>...' first.");
+    }
+    for user in &users {
+        ui::key_value("Public key", &user.public_key);
+        ui::key_value("Files", &user.files.to_string());
+        ui::key_value("Root hash", &user.root_hash);
+        ui::key_value("Synced", &format_time(user.synced_at));
+        ui::separator();
+    }
+
+    output::emit(&users);
+    Ok(())
+}
+
+/// Check every mirrored file against its integrity hash
+pub async fn verify(storage_dir: &Path, uris: &[String]) -> Result<()> {
+    ui::header("Verify Directory Mirror");
+
+    let mirror = DirectoryMirror::new(storage_dir);
+    let mut corrupted = BTreeMap::new();
+    for public_key in mirror_keys(&mirror, uris)? {
+        let public_key = public_key.to_string();
+        let paths = mirror.verify(&public_key)?;
+        if paths.is_empty() {
+            ui::success(&format!("{}: intact", public_key));
+        } else {
+            for path in &paths {
+                ui::error(&format!("{}: {} does not match its hash", public_key, path));
+            }
+            corrupted.insert(public_key, paths);
+        }
+    }
+
+    output::emit(&corrupted);
+    if !corrupted.is_empty() {
+        anyhow::bail!("Mirror has corrupted files; run 'paykit-demo mirror sync' to refetch them");
+    }
+    Ok(())
+}
+
+/// Stop mirroring a user and delete the local copy
+pub async fn remove(storage_dir: &Path, uri: &str) -> Result<()> {
+    let public_key = parse_pubky_uri(uri)?.to_string();
+    if DirectoryMirror::new(storage_dir).remove(&public_key)? {
+        ui::success(&format!("Stopped mirroring {}", public_key));
+    } else {
+        ui::warning(&format!("{} is not mirrored", public_key));
+    }
+    Ok(())
+}
+
+async fn sync_all(
+    mirror: &DirectoryMirror,
+    client: &DirectoryClient,
+    keys: &[pubky::PublicKey],
+) -> Vec<SyncReport> {
+    let mut reports = Vec::new();
+    for public_key in keys {
+        match mirror.sync(client, public_key).await {
+            Ok(report) => {
+                if report.has_changes() {
+                    ui::success(&format!(
+                        "{}: {} added, {} changed, {} removed (root {})",
+                        public_key,
+                        report.added,
+                        report.changed,
+                        report.removed,
+                        &report.root_hash[..12]
+                    ));
+                } else {
+                    ui::info(&format!("{}: up to date", public_key));
+                }
+                reports.push(report);
+            }
+            // Keep serving the last good copy
+            Err(e) => ui::warning(&format!("{}: sync failed: {:#}", public_key, e)),
+        }
+    }
+    reports
+}
+
+/// Keys from the command line, or every mirrored user if none are given
+fn mirror_keys(mirror: &DirectoryMirror, uris: &[String]) -> Result<Vec<pubky::PublicKey>> {
+    if uris.is_empty() {
+        let users = mirror.users()?;
+        if users.is_empty() {
+            anyhow::bail!("No users to mirror; pass one or more Pubky URIs");
+        }
+        return users.iter().map(|user| parse_pubky_uri(user)).collect();
+    }
+    uris.iter().map(|uri| parse_pubky_uri(uri)).collect()
+}
+
+/// Largest request head accepted; the replica only serves GETs.
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// Answer one read replica request.
+async fn handle_request(mirror: &DirectoryMirror, mut socket: TcpStream) {
+    let (status, headers, body) = match read_request_line(&mut socket).await {
+        Ok((method, target)) if method == "GET" => respond(mirror, &target),
+        Ok(_) => ("405 Method Not Allowed", Vec::new(), Vec::new()),
+        Err(e) => {
+            ui::warning(&format!("Malformed mirror request: {}", e));
+            ("400 Bad Request", Vec::new(), Vec::new())
+        }
+    };
+
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        body.len()
+    );
+    for (name, value) in headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str("\r\n");
+    let _ = socket.write_all(response.as_bytes()).await;
+    let _ = socket.write_all(&body).await;
+}
+
+type Response = (&'static str, Vec<(&'static str, String)>, Vec<u8>);
+
+/// Route a GET to the replica.
+fn respond(mirror: &DirectoryMirror, target: &str) -> Response {
+    let path = target.split('?').next().unwrap_or_default();
+    let result = match path.trim_end_matches('/').strip_prefix("/users") {
+        Some("") => list_users(mirror).map(Some),
+        Some(rest) => match rest.trim_start_matches('/').split_once('/') {
+            None => user_manifest(mirror, rest.trim_start_matches('/')),
+            Some((public_key, file)) => mirrored_file(mirror, public_key, &format!("/{}", file)),
+        },
+        None => Ok(None),
+    };
+
+    match result {
+        Ok(Some(response)) => response,
+        Ok(None) => ("404 Not Found", Vec::new(), Vec::new()),
+        Err(e) => {
+            ui::warning(&format!("Mirror request failed: {:#}", e));
+            ("500 Internal Server Error", Vec::new(), Vec::new())
+        }
+    }
+}
+
+fn list_users(mirror: &DirectoryMirror) -> Result<Response> {
+    let mut users = Vec::new();
+    for public_key in mirror.users()? {
+        if let Some(directory) = mirror.load(&public_key)? {
+            users.push(UserSummary::from(&directory));
+        }
+    }
+    json_response(&users)
+}
+
+fn user_manifest(mirror: &DirectoryMirror, public_key: &str) -> Result<Option<Response>> {
+    let Ok(Some(directory)) = mirror.load(public_key) else {
+        return Ok(None);
+    };
+    json_response(&Manifest::from(&directory)).map(Some)
+}
+
+fn mirrored_file(
+    mirror: &DirectoryMirror,
+    public_key: &str,
+    path: &str,
+) -> Result<Option<Response>> {
+    let Ok(Some(entry)) = mirror.get(public_key, path) else {
+        return Ok(None);
+    };
+    if !entry.is_intact() {
+        anyhow::bail!("{}{} does not match its hash", public_key, path);
+    }
+    let headers = vec![
+        ("Content-Type", "text/plain; charset=utf-8".to_string()),
+        ("ETag", format!("\"{}\"", entry.sha256)),
+        ("X-Content-SHA256", entry.sha256),
+        ("X-Mirrored-At", entry.fetched_at.to_string()),
+    ];
+    Ok(Some(("200 OK", headers, entry.content.into_bytes())))
+}
+
+fn json_response<T: Serialize>(data: &T) -> Result<Response> {
+    let body = serde_json::to_vec_pretty(data)?;
+    Ok((
+        "200 OK",
+        vec![("Content-Type", "application/json".to_string())],
+        body,
+    ))
+}
+
+/// Read an HTTP request head, returning its method and target.
+pub(crate) async fn read_request_line(socket: &mut TcpStream) -> Result<(String, String)> {
+    let mut buf = Vec::new();
+    loop {
+        if buf.windows(4).any(|w| w == b"\r\n\r\n") {
+            break;
+        }
+        if buf.len() > MAX_REQUEST_BYTES {
+            anyhow::bail!("Headers too large");
+        }
+        let mut chunk = [0u8; 1024];
+        let n = socket.read(&mut chunk).await?;
+        if n == 0 {
+            anyhow::bail!("Connection closed");
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+
+    let head = String::from_utf8_lossy(&buf);
+    let mut parts = head.lines().next().unwrap_or_default().split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => Ok((method.to_string(), target.to_string())),
+        _ => anyhow::bail!("Missing request line"),
+    }
+}
+
+fn format_time(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+fn parse_pubky_uri(uri: &str) -> Result<pubky::PublicKey> {
+    let key_str = uri.strip_prefix("pubky://").unwrap_or(uri);
+    key_str.parse().context("Invalid Pubky URI format")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: &str = "8pinxxgqs41n4aididenw5apqp1urfmzdztr8jt4abrkdn435ewo";
+
+    fn mirror_with_alice(dir: &Path) -> DirectoryMirror {
+        let mirror = DirectoryMirror::new(dir);
+        let files = [(
+            "/pub/paykit.app/v0/lightning".to_string(),
+            "lnurl1".to_string(),
+        )];
+        mirror
+            .apply(ALICE, files.into_iter().collect(), 100)
+            .unwrap();
+        mirror
+    }
+
+    #[test]
+    fn test_serves_mirrored_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mirror = mirror_with_alice(temp_dir.path());
+
+        let (status, headers, body) = respond(
+            &mirror,
+            &format!("/users/{}/pub/paykit.app/v0/lightning", ALICE),
+        );
+        assert_eq!(status, "200 OK");
+        assert_eq!(body, b"lnurl1");
+        let hash = headers
+            .iter()
+            .find(|(name, _)| *name == "X-Content-SHA256")
+            .map(|(_, value)| value.clone())
+            .unwrap();
+        assert_eq!(hash.len(), 64);
+
+        let (status, _, body) = respond(&mirror, &format!("/users/{}", ALICE));
+        assert_eq!(status, "200 OK");
+        let manifest: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            manifest["files"]["/pub/paykit.app/v0/lightning"]["sha256"],
+            hash
+        );
+
+        let (status, _, body) = respond(&mirror, "/users");
+        assert_eq!(status, "200 OK");
+        let users: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(users[0]["public_key"], ALICE);
+    }
+
+    #[test]
+    fn test_unknown_paths_are_not_found() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mirror = mirror_with_alice(temp_dir.path());
+
+        for target in [
+            "/",
+            "/other",
+            "/users/unknown",
+            "/users/../../etc/passwd",
+            &format!("/users/{}/pub/paykit.app/v0/onchain", ALICE),
+        ] {
+            assert_eq!(respond(&mirror, target).0, "404 Not Found", "{}", target);
+        }
+    }
+}
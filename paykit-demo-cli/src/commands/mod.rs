@@ -1,14 +1,24 @@
 //! CLI command implementations
 
 pub mod activity;
+pub mod analytics;
+pub mod api_key;
+pub mod approvals;
+pub mod audit;
 pub mod backup;
+pub mod config;
 pub mod contacts;
 pub mod dashboard;
+pub mod diagnose;
 pub mod discover;
 pub mod endpoints;
+pub mod link;
 pub mod list;
 pub mod migrate;
+pub mod mirror;
 pub mod pay;
+pub mod payments;
+pub mod pos;
 pub mod profile;
 pub mod publish;
 pub mod qr;
@@ -17,8 +27,15 @@ pub mod receive;
 pub mod rotation;
 pub mod setup;
 pub mod smart_checkout;
+pub mod standing;
+pub mod status;
+pub mod storage;
 pub mod subscriptions;
+pub mod sweep;
 pub mod switch;
+pub mod timestamp;
+pub mod trust;
+pub mod voucher;
 pub mod wallet;
 pub mod whoami;
 
@@ -45,21 +62,186 @@ pub fn set_current_identity(storage_dir: &std::path::Path, name: &str) -> anyhow
     Ok(())
 }
 
+/// Environment variable read when `--proxy` is not given
+pub const PROXY_ENV: &str = "PAYKIT_PROXY";
+
+static PROXY: std::sync::OnceLock<paykit_lib::proxy::ProxyConfig> = std::sync::OnceLock::new();
+
+/// Configure the SOCKS5 proxy for every network transport
+///
+/// Must run before any command creates a network client: the Pubky SDK only
+/// picks up its proxy from the environment.
+pub fn init_proxy(proxy: Option<&str>, bypass: &[String]) -> anyhow::Result<()> {
+    use paykit_lib::proxy::{ProxyConfig, Socks5Proxy, TransportKind};
+
+    let proxy = match proxy
+        .map(String::from)
+        .or_else(|| std::env::var(PROXY_ENV).ok())
+    {
+        Some(url) => Socks5Proxy::parse(&url)?,
+        None => return Ok(()),
+    };
+    let mut config = ProxyConfig::new(proxy);
+    for kind in bypass {
+        config = config.with_override(kind.parse::<TransportKind>()?, None);
+    }
+    for (name, value) in config.env_vars(TransportKind::Pubky) {
+        std::env::set_var(name, value);
+    }
+    let _ = PROXY.set(config);
+    Ok(())
+}
+
+/// The proxy configured for a transport, if any
+pub fn proxy_for(kind: paykit_lib::proxy::TransportKind) -> Option<paykit_lib::proxy::Socks5Proxy> {
+    PROXY
+        .get()
+        .and_then(|config| config.for_transport(kind).cloned())
+}
+
+static PATH_ROOTS: std::sync::OnceLock<paykit_lib::protocol::PathRootConfig> =
+    std::sync::OnceLock::new();
+
+/// Publish under a white-label directory root, still discovering `paykit.app`
+pub fn init_path_root(root: Option<&str>) -> anyhow::Result<()> {
+    use paykit_lib::protocol::{PathRoot, PathRootConfig};
+
+    if let Some(root) = root {
+        let root = root
+            .parse::<PathRoot>()
+            .map_err(|e| anyhow::anyhow!("Invalid path root {:?}: {}", root, e))?;
+        let _ = PATH_ROOTS.set(PathRootConfig::white_label(root));
+    }
+    Ok(())
+}
+
+/// Roots directory paths are published under and discovered in
+pub fn path_roots() -> paykit_lib::protocol::PathRootConfig {
+    PATH_ROOTS.get().cloned().unwrap_or_default()
+}
+
+/// Directory client for `homeserver`, using the configured path root
+pub fn directory_client(homeserver: &str) -> paykit_demo_core::DirectoryClient {
+    paykit_demo_core::DirectoryClient::new(homeserver).with_path_roots(path_roots())
+}
+
+/// Read a payment amount as whole sats
+///
+/// Returns `None` when the amount is missing, not an integer, or given in
+/// another currency.
+pub fn amount_sats(amount: Option<&str>, currency: Option<&str>) -> Option<u64> {
+    if currency.is_some_and(|c| !c.eq_ignore_ascii_case("SAT")) {
+        return None;
+    }
+    amount?.trim().parse().ok()
+}
+
+/// Resolve a `user@domain` handle to a verified public key
+///
+/// Lookups go through the Pubky proxy, if one is configured.
+pub async fn resolve_handle(handle: &str) -> anyhow::Result<paykit_lib::handles::ResolvedHandle> {
+    use paykit_lib::handles::{HandleResolver, HttpHandleLookup};
+
+    let lookup = match proxy_for(paykit_lib::proxy::TransportKind::Pubky) {
+        Some(proxy) => HttpHandleLookup::with_proxy(&proxy)?,
+        None => HttpHandleLookup::new()?,
+    };
+    HandleResolver::new(std::sync::Arc::new(lookup))
+        .resolve(handle)
+        .await
+        .map_err(|e| anyhow::anyhow!("Could not resolve {}: {}", handle, e))
+}
+
+/// Environment variable read before prompting for an identity file passphrase
+pub const IDENTITY_PASSPHRASE_ENV: &str = "PAYKIT_IDENTITY_PASSPHRASE";
+
+/// Read the passphrase for encrypted identity files
+pub fn identity_passphrase(confirm: bool) -> anyhow::Result<String> {
+    use anyhow::Context;
+
+    if let Ok(passphrase) = std::env::var(IDENTITY_PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+    let passphrase =
+        rpassword::prompt_password("Identity passphrase: ").context("Failed to read passphrase")?;
+    if confirm {
+        let again = rpassword::prompt_password("Confirm passphrase: ")
+            .context("Failed to read passphrase confirmation")?;
+        if passphrase != again {
+            anyhow::bail!("Passphrases do not match");
+        }
+    }
+    Ok(passphrase)
+}
+
+/// File-based identity manager, unlocked for `name` if its key is encrypted
+pub fn file_identity_manager(
+    storage_dir: &std::path::Path,
+    name: &str,
+) -> anyhow::Result<paykit_demo_core::IdentityManager> {
+    let manager = paykit_demo_core::IdentityManager::new(storage_dir.join("identities"));
+    match manager.location(name) {
+        Ok(paykit_demo_core::SecretLocation::EncryptedFile) => {
+            Ok(manager.with_passphrase(identity_passphrase(false)?))
+        }
+        _ => Ok(manager),
+    }
+}
+
+/// Save an identity under `<storage_dir>/identities`
+///
+/// With `keyring` set the secret goes to the OS keyring, falling back to a
+/// passphrase-encrypted file if the keyring cannot be used.
+pub fn save_file_identity(
+    storage_dir: &std::path::Path,
+    identity: &paykit_demo_core::Identity,
+    name: &str,
+    keyring: bool,
+) -> anyhow::Result<paykit_demo_core::SecretLocation> {
+    let identities_dir = storage_dir.join("identities");
+    if !keyring {
+        return paykit_demo_core::IdentityManager::new(&identities_dir).save(identity, name);
+    }
+    if !paykit_demo_core::identity::keyring_supported() {
+        anyhow::bail!("This build has no keyring support; rebuild with `--features keyring`");
+    }
+
+    match paykit_demo_core::IdentityManager::new(&identities_dir)
+        .with_keyring()
+        .save(identity, name)
+    {
+        Ok(location) => Ok(location),
+        Err(e) => {
+            crate::ui::warning(&format!("{:#}", e));
+            crate::ui::info("Falling back to a passphrase-encrypted identity file");
+            paykit_demo_core::IdentityManager::new(&identities_dir)
+                .with_passphrase(identity_passphrase(true)?)
+                .save(identity, name)
+        }
+    }
+}
+
 /// Load the current identity
 pub async fn load_current_identity(
     storage_dir: &std::path::Path,
 ) -> anyhow::Result<paykit_demo_core::Identity> {
     let name = get_current_identity(storage_dir)?
         .ok_or_else(|| anyhow::anyhow!("No identity configured. Run 'paykit-demo setup' first."))?;
+    load_identity(storage_dir, &name).await
+}
 
+/// Load a saved identity by name
+pub async fn load_identity(
+    storage_dir: &std::path::Path,
+    name: &str,
+) -> anyhow::Result<paykit_demo_core::Identity> {
     // Try secure storage first
     let metadata_path = storage_dir.join("identities_metadata.json");
     if metadata_path.exists() {
         let secure_manager = paykit_demo_core::SecureIdentityManager::new(storage_dir);
-        secure_manager.load(&name).await
-    } else {
-        let identity_manager =
-            paykit_demo_core::IdentityManager::new(storage_dir.join("identities"));
-        identity_manager.load(&name)
+        if secure_manager.list()?.iter().any(|n| n == name) {
+            return secure_manager.load(name).await;
+        }
     }
+    file_identity_manager(storage_dir, name)?.load(name)
 }
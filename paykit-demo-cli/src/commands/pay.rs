@@ -5,19 +5,57 @@
 
 use anyhow::{Context, Result};
 use paykit_demo_core::DemoStorage;
+use paykit_interactive::metadata::{sanitize_memo, set_memo_in_metadata};
+use paykit_interactive::transport::connect_tcp;
 use paykit_interactive::{PaykitNoiseMessage, PaykitReceipt};
+use paykit_lib::audit::AuditOperation;
+use paykit_lib::handles::{Handle, HandleSource};
+use paykit_lib::policy::{
+    EndpointSource, PlannedPayment, PrivacyAnalyzer, PrivacyReport, PrivacySeverity,
+};
 use paykit_lib::prelude::*;
-use paykit_lib::rotation::{EndpointRotationManager, RotationConfig};
+use paykit_lib::protocol::{
+    fetch_noise_endpoint, fetch_payee_status, is_key_mismatch_hint, PaymentIntent,
+    PAYMENT_LINK_URI_PREFIX,
+};
+use paykit_lib::proxy::TransportKind;
+use paykit_lib::rotation::EndpointRotationManager;
 use paykit_lib::MethodId;
 use pubky_noise::datalink_adapter::{client_complete_ik, client_start_ik_direct};
-use pubky_noise::{DummyRing, NoiseClient};
+use pubky_noise::{DummyRing, NoiseClient, NoiseLink};
+use serde::Serialize;
 use std::path::Path;
 use std::sync::Arc;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
 
 use super::wallet::WalletConfig;
-use crate::ui;
+use crate::{output, ui};
+
+/// `data` of `pay --output json`
+#[derive(Serialize)]
+struct PayOutput<'a> {
+    payee: &'a str,
+    method: &'a str,
+    amount: Option<&'a str>,
+    /// "completed", "pending", "dry_run", "cancelled" or "not_executed"
+    status: &'static str,
+    receipt_id: Option<String>,
+    proof: Option<serde_json::Value>,
+}
+
+impl<'a> PayOutput<'a> {
+    fn new(payee: &'a str, method: &'a str, amount: Option<&'a str>, status: &'static str) -> Self {
+        Self {
+            payee,
+            method,
+            amount,
+            status,
+            receipt_id: None,
+            proof: None,
+        }
+    }
+}
 
 #[allow(clippy::too_many_arguments)]
 #[tracing::instrument(skip(storage_dir))]
@@ -28,7 +66,9 @@ pub async fn run(
     currency: Option<String>,
     method: &str,
     strategy: &str,
+    note: Option<String>,
     dry_run: bool,
+    privacy_report: bool,
     verbose: bool,
 ) -> Result<()> {
     ui::header("Initiate Payment");
@@ -42,8 +82,44 @@ pub async fn run(
         tracing::info!("Payer: {}", identity.pubky_uri());
     }
 
+    // A payment link sets the payee, amount, note and allowed methods
+    let (intent, amount, note) = if recipient.starts_with(PAYMENT_LINK_URI_PREFIX) {
+        let intent = super::link::fetch_intent(recipient).await?;
+        super::link::show_intent(&intent);
+        ui::separator();
+        let amount = match (intent.amount_sats, amount) {
+            (Some(fixed), Some(given)) if given != fixed.to_string() => {
+                anyhow::bail!("The link is for {} sats, not {}", fixed, given)
+            }
+            (Some(fixed), _) => Some(fixed.to_string()),
+            (None, given) => given,
+        };
+        if currency.as_deref().is_some_and(|c| c != "SAT") {
+            anyhow::bail!("Payment links are paid in SAT");
+        }
+        let note = note.or_else(|| intent.memo.clone());
+        (Some(intent), amount, note)
+    } else {
+        (None, amount, note)
+    };
+    let method = match &intent {
+        Some(intent) if !intent.methods.is_empty() => {
+            if method.eq_ignore_ascii_case("auto") {
+                intent.methods[0].0.as_str()
+            } else if intent.methods.iter().any(|m| m.0 == method) {
+                method
+            } else {
+                anyhow::bail!("The link does not accept {}", method)
+            }
+        }
+        _ => method,
+    };
+
     // Resolve recipient (could be contact name or URI)
-    let payee_uri = resolve_recipient(storage_dir, recipient)?;
+    let payee_uri = match &intent {
+        Some(intent) => format!("pubky://{}", intent.payee),
+        None => resolve_recipient(storage_dir, recipient).await?,
+    };
 
     ui::info(&format!("Recipient: {}", payee_uri));
 
@@ -60,6 +136,11 @@ pub async fn run(
         "unspecified".to_string()
     };
 
+    let note = note.as_deref().and_then(sanitize_memo);
+    if let Some(note) = &note {
+        ui::info(&format!("Note: {}", note));
+    }
+
     // Determine the payment method to use
     let selected_method = if method.to_lowercase() == "auto" {
         // Auto-select the best method
@@ -78,24 +159,93 @@ pub async fn run(
 
     ui::separator();
 
+    // Warn about privacy leaks before anything is sent
+    let privacy = if privacy_report {
+        let report = analyze_privacy(
+            storage_dir,
+            &payee_uri,
+            &selected_method,
+            amount.as_deref(),
+            note.as_deref(),
+        )
+        .await?;
+        show_privacy_report(&report);
+        Some(report)
+    } else {
+        None
+    };
+
+    // Large payments need co-signer approval first
+    let approval_id = super::approvals::enforce(
+        storage_dir,
+        &payee_uri,
+        &selected_method,
+        amount.as_deref(),
+        currency.as_deref(),
+        dry_run,
+    )?;
+
     // Check if recipient is a Pubky URI - if so, use Noise negotiation
     if payee_uri.starts_with("pubky://") {
-        return execute_noise_payment(
+        if !super::trust::enforce(storage_dir, &payee_uri, dry_run)? {
+            ui::info("Payment cancelled");
+            output::emit(&PayOutput::new(
+                &payee_uri,
+                &selected_method,
+                amount.as_deref(),
+                "cancelled",
+            ));
+            if let Some(report) = &privacy {
+                output::attach("privacy", report);
+            }
+            return Ok(());
+        }
+        super::trust::enforce_verified(
+            storage_dir,
+            &payee_uri,
+            amount.as_deref(),
+            currency.as_deref(),
+            dry_run,
+        )?;
+
+        execute_noise_payment(
             storage_dir,
             &identity,
             &payee_uri,
             amount.as_deref(),
             currency.as_deref(),
             &selected_method,
+            note.as_deref(),
+            intent.as_ref(),
             dry_run,
             verbose,
         )
-        .await;
+        .await?;
+
+        if let Some(report) = &privacy {
+            output::attach("privacy", report);
+        }
+        if let Some(approval_id) = &approval_id {
+            super::approvals::complete(storage_dir, approval_id)?;
+        }
+        return Ok(());
+    }
+
+    if note.is_some() {
+        ui::warning("Notes are only delivered with Noise payments to a Pubky recipient");
     }
 
     // Check wallet configuration for direct payments
     let wallet_config = WalletConfig::load(storage_dir)?;
 
+    // Replaced by the executor once it gets as far as paying
+    output::emit(&PayOutput::new(
+        &payee_uri,
+        &selected_method,
+        amount.as_deref(),
+        "not_executed",
+    ));
+
     match selected_method.to_lowercase().as_str() {
         "lightning" | "ln" | "ln-btc" => {
             execute_lightning_payment(
@@ -120,9 +270,12 @@ pub async fn run(
             .await?;
         }
         _ => {
-            ui::warning(&format!("Unknown payment method: {}", selected_method));
             ui::info("Supported methods: lightning, onchain, auto");
-            return Ok(());
+            return Err(PaykitError::MethodNotSupported(format!(
+                "Unknown payment method: {}",
+                selected_method
+            ))
+            .into());
         }
     }
 
@@ -131,12 +284,144 @@ pub async fn run(
         log_payment_attempt(storage_dir, &payee_uri, &amount_str, &selected_method)?;
     }
 
+    if let Some(report) = &privacy {
+        output::attach("privacy", report);
+    }
+    if let Some(approval_id) = &approval_id {
+        super::approvals::complete(storage_dir, approval_id)?;
+    }
+
     Ok(())
 }
 
+/// Score a payment for privacy leaks before it is executed
+///
+/// Direct addresses and invoices were handed over out of band, so only
+/// endpoints from a Pubky directory count as public. Notes only travel over
+/// Noise, which encrypts them.
+async fn analyze_privacy(
+    storage_dir: &Path,
+    payee_uri: &str,
+    method: &str,
+    amount: Option<&str>,
+    note: Option<&str>,
+) -> Result<PrivacyReport> {
+    let method_id = match method.to_lowercase().as_str() {
+        "lightning" | "ln" | "ln-btc" => MethodId::lightning(),
+        "onchain" | "btc" | "onchain-btc" => MethodId::onchain(),
+        other => MethodId::new(other),
+    };
+    let amount_sats = amount.and_then(|a| a.parse::<u64>().ok());
+
+    let mut prior_payments = std::fs::read_to_string(storage_dir.join("logs/payments.log"))
+        .unwrap_or_default()
+        .lines()
+        .filter(|line| line.contains(&format!("| recipient={} |", payee_uri)))
+        .count() as u32;
+
+    let mut planned = match payee_uri.strip_prefix("pubky://") {
+        Some(payee_pk) => {
+            let payee_pk: paykit_lib::PublicKey = payee_pk
+                .parse()
+                .context("Failed to parse payee public key")?;
+            let private = super::endpoints::load_endpoint_manager(storage_dir)?
+                .get_endpoint(&payee_pk, &method_id)
+                .await?
+                .is_some();
+            prior_payments += super::storage::open(storage_dir)
+                .list_receipts()?
+                .iter()
+                .filter(|receipt| receipt.payee == payee_pk && receipt.method == method_id.0)
+                .count() as u32;
+
+            let source = if private {
+                EndpointSource::Private
+            } else {
+                EndpointSource::Public
+            };
+            let planned = PlannedPayment::new(method_id.clone(), source);
+            match note {
+                Some(note) => planned.with_memo(note, true),
+                None => planned,
+            }
+        }
+        None => PlannedPayment::new(method_id.clone(), EndpointSource::Private),
+    };
+    planned = planned.with_prior_payments(prior_payments);
+    if let Some(amount_sats) = amount_sats {
+        planned = planned.with_amount_sats(amount_sats);
+    }
+
+    // The change pattern is only known once the wallet picks coins
+    #[cfg(feature = "http-executor")]
+    {
+        let direct_onchain =
+            method_id.as_str() == MethodId::ONCHAIN && !payee_uri.starts_with("pubky://");
+        if let (true, Some(amount_sats)) = (direct_onchain, amount_sats) {
+            if let Some(hints) = spend_hints(storage_dir, payee_uri, amount_sats).await {
+                planned = planned.with_spend_hints(hints);
+            }
+        }
+    }
+
+    Ok(PrivacyAnalyzer::default().analyze(&planned))
+}
+
+/// How the configured on-chain wallet would fund a payment, if it can
+#[cfg(feature = "http-executor")]
+async fn spend_hints(
+    storage_dir: &Path,
+    address: &str,
+    amount_sats: u64,
+) -> Option<paykit_lib::methods::SpendHints> {
+    use paykit_lib::executors::{
+        EsploraBitcoinExecutor, EsploraConfig as LibEsploraConfig, EsploraExecutor,
+    };
+
+    let config = WalletConfig::load(storage_dir).ok()??.esplora?;
+    let change_address = config
+        .change_address
+        .as_deref()
+        .or(config.funding_addresses.first().map(String::as_str))?;
+    let executor = EsploraExecutor::new(
+        LibEsploraConfig::new(&config.url).with_proxy(super::proxy_for(TransportKind::Esplora)),
+    )
+    .ok()?;
+    let wallet = EsploraBitcoinExecutor::new(
+        executor,
+        &config.funding_addresses,
+        change_address,
+        Arc::new(PromptSigner),
+    )
+    .ok()?;
+
+    match wallet.spend_hints(address, amount_sats).await {
+        Ok(hints) => Some(hints),
+        Err(e) => {
+            tracing::debug!("No spend hints for privacy report: {}", e);
+            None
+        }
+    }
+}
+
+fn show_privacy_report(report: &PrivacyReport) {
+    ui::key_value("Privacy score", &format!("{}/100", report.score));
+    if report.leaks.is_empty() {
+        ui::success("No privacy leaks found");
+    }
+    for leak in &report.leaks {
+        if leak.severity() >= PrivacySeverity::Medium {
+            ui::warning(&leak.describe());
+        } else {
+            ui::info(&leak.describe());
+        }
+    }
+    ui::separator();
+}
+
 /// Select the best payment method using paykit-lib selection
 async fn select_payment_method(
-    _storage_dir: &Path,
+    storage_dir: &Path,
     payee_uri: &str,
     amount: Option<&str>,
     strategy: &str,
@@ -175,13 +460,19 @@ async fn select_payment_method(
 
     // Query the directory for supported methods
     let storage = pubky::PublicStorage::new().context("Failed to create PublicStorage")?;
-    let transport = paykit_lib::PubkyUnauthenticatedTransport::new(storage);
+    let transport = paykit_lib::PubkyUnauthenticatedTransport::new(storage)
+        .with_path_roots(super::path_roots());
 
     let payee_pk: paykit_lib::PublicKey = payee_pk_str
         .parse()
         .context("Failed to parse payee public key")?;
 
     let supported = paykit_lib::get_payment_list(&transport, &payee_pk).await;
+    let now = chrono::Utc::now().timestamp();
+    let status = fetch_payee_status(&transport, &payee_pk, now)
+        .await
+        .ok()
+        .flatten();
 
     spinner.finish_and_clear();
 
@@ -197,10 +488,30 @@ async fn select_payment_method(
                 }
             }
 
-            // Use the selector
-            let selector = PaymentMethodSelector::with_defaults();
+            // Use the selector, probing Lightning liquidity when a node is configured
+            let mut selector = PaymentMethodSelector::with_defaults();
+            if let Some(status) = status {
+                if let Some(message) = &status.message {
+                    ui::info(&format!("Payee status: {}", message));
+                }
+                if !status.is_accepting(now) {
+                    ui::warning("Payee reports it is not accepting payments right now");
+                }
+                selector = selector.with_payee_status(status, now);
+            }
+            let selection = match route_prober(storage_dir) {
+                Some(prober) => {
+                    let spinner = ui::spinner("Probing Lightning route...");
+                    let result = selector
+                        .select_probed(&methods, &amt, &prefs, &prober)
+                        .await;
+                    spinner.finish_and_clear();
+                    result
+                }
+                None => selector.select(&methods, &amt, &prefs),
+            };
 
-            match selector.select(&methods, &amt, &prefs) {
+            match selection {
                 Ok(result) => {
                     ui::success(&format!(
                         "Selected method: {} (score: {:.2})",
@@ -244,6 +555,84 @@ async fn select_payment_method(
     }
 }
 
+/// Connect an executor to the configured Lightning node, LND before CLN,
+/// pinned to its TLS certificate when one is configured
+#[cfg(feature = "http-executor")]
+fn lightning_executor(
+    config: &WalletConfig,
+) -> paykit_lib::Result<Option<Arc<dyn paykit_lib::methods::LightningExecutor>>> {
+    use paykit_lib::executors::{ClnConfig as LibClnConfig, ClnExecutor};
+    use paykit_lib::executors::{LndConfig as LibLndConfig, LndExecutor};
+
+    let proxy = super::proxy_for(TransportKind::Lnd);
+    if let Some(lnd) = &config.lnd {
+        let mut lib_config = LibLndConfig::new(&lnd.url, &lnd.macaroon).with_proxy(proxy);
+        if let Some(cert) = &lnd.tls_cert {
+            lib_config = lib_config.with_tls_cert(cert);
+        }
+        return Ok(Some(Arc::new(LndExecutor::new(lib_config)?)));
+    }
+    if let Some(cln) = &config.cln {
+        let mut lib_config = LibClnConfig::new(&cln.url, &cln.rune).with_proxy(proxy);
+        if let Some(cert) = &cln.tls_cert {
+            lib_config = lib_config.with_tls_cert(cert);
+        }
+        return Ok(Some(Arc::new(ClnExecutor::new(lib_config)?)));
+    }
+    Ok(None)
+}
+
+/// Build a route prober from the configured Lightning node, if any
+fn route_prober(storage_dir: &Path) -> Option<paykit_lib::selection::RouteProber> {
+    #[cfg(feature = "http-executor")]
+    {
+        let config = WalletConfig::load(storage_dir).ok().flatten()?;
+        let executor = lightning_executor(&config).ok().flatten()?;
+        Some(paykit_lib::selection::RouteProber::new(executor))
+    }
+
+    #[cfg(not(feature = "http-executor"))]
+    {
+        let _ = storage_dir;
+        None
+    }
+}
+
+/// Connect and run the Noise IK handshake against `server_pk`.
+///
+/// Returns `None` when the payee answers with the key-mismatch hint, meaning
+/// `server_pk` is no longer one of its accepted keys.
+async fn noise_handshake(
+    identity: &paykit_demo_core::Identity,
+    connect_addr: &str,
+    server_pk: &[u8; 32],
+) -> Result<Option<(TcpStream, NoiseLink)>> {
+    let seed = identity.keypair.secret_key();
+    let ring = Arc::new(DummyRing::new(seed, "paykit-payer"));
+    let noise_client = NoiseClient::<_, ()>::new_direct("paykit-payer", b"demo-device", ring);
+
+    let mut socket = connect_tcp(
+        connect_addr,
+        super::proxy_for(TransportKind::Noise).as_ref(),
+    )
+    .await
+    .context("Failed to connect to recipient")?;
+
+    let (client_hs, first_msg) = client_start_ik_direct(&noise_client, server_pk, None)
+        .context("Failed to initiate handshake")?;
+    socket.write_all(&first_msg).await?;
+
+    let mut response = vec![0u8; 4096];
+    let n = socket.read(&mut response).await?;
+    response.truncate(n);
+    if is_key_mismatch_hint(&response) {
+        return Ok(None);
+    }
+
+    let link = client_complete_ik(client_hs, &response).context("Failed to complete handshake")?;
+    Ok(Some((socket, link)))
+}
+
 /// Execute payment using Noise protocol to negotiate with recipient
 #[allow(clippy::too_many_arguments)]
 async fn execute_noise_payment(
@@ -253,6 +642,8 @@ async fn execute_noise_payment(
     amount: Option<&str>,
     currency: Option<&str>,
     method: &str,
+    memo: Option<&str>,
+    intent: Option<&PaymentIntent>,
     dry_run: bool,
     verbose: bool,
 ) -> Result<()> {
@@ -271,7 +662,11 @@ async fn execute_noise_payment(
             ui::info(&format!("  Amount: {} {}", amt, currency.unwrap_or("SAT")));
         }
         ui::info(&format!("  Method: {}", method));
+        if let Some(memo) = memo {
+            ui::info(&format!("  Note: {}", memo));
+        }
         ui::info("  No actual connection will be made");
+        output::emit(&PayOutput::new(payee_uri, method, amount, "dry_run"));
         return Ok(());
     }
 
@@ -279,7 +674,7 @@ async fn execute_noise_payment(
     let spinner = ui::spinner("Resolving payment endpoint...");
 
     // Create storage adapter for private endpoint lookup
-    let demo_storage = DemoStorage::new(storage_dir.join("data"));
+    let demo_storage = super::storage::open(storage_dir);
     let endpoint_manager = {
         use paykit_lib::private_endpoints::{encryption, FileStore, PrivateEndpointManager};
         let endpoints_dir = storage_dir.join("private_endpoints");
@@ -404,7 +799,8 @@ async fn execute_noise_payment(
     // Use smart checkout to resolve endpoint
     let method_id = MethodId::new(method);
     let storage = pubky::PublicStorage::new().context("Failed to create PublicStorage")?;
-    let transport = paykit_lib::PubkyUnauthenticatedTransport::new(storage);
+    let transport = paykit_lib::PubkyUnauthenticatedTransport::new(storage)
+        .with_path_roots(super::path_roots());
 
     let checkout_result = paykit_interactive::smart_checkout_detailed(
         &storage_adapter,
@@ -463,35 +859,51 @@ async fn execute_noise_payment(
         let mut server_pk = [0u8; 32];
         server_pk.copy_from_slice(&noise_pk_bytes);
 
-        // Setup Noise client
-        let seed = identity.keypair.secret_key();
-        let ring = Arc::new(DummyRing::new(seed, "paykit-payer"));
-        let noise_client = NoiseClient::<_, ()>::new_direct("paykit-payer", b"demo-device", ring);
-
-        // Connect
-        let mut socket = TcpStream::connect(&connect_addr)
-            .await
-            .context("Failed to connect to recipient")?;
-
         // Perform handshake
         let spinner = ui::spinner("Performing Noise handshake...");
-        let (client_hs, first_msg) = client_start_ik_direct(&noise_client, &server_pk, None)
-            .context("Failed to initiate handshake")?;
-
-        socket.write_all(&first_msg).await?;
-
-        let mut response = vec![0u8; 4096];
-        let n = socket.read(&mut response).await?;
-        response.truncate(n);
-
-        let mut link =
-            client_complete_ik(client_hs, &response).context("Failed to complete handshake")?;
+        let (mut socket, mut link) =
+            match noise_handshake(identity, &connect_addr, &server_pk).await? {
+                Some(session) => session,
+                None => {
+                    // The payee rotated its Noise key; refresh and retry once
+                    spinner.set_message("Noise key rotated, refreshing from directory...");
+                    let storage =
+                        pubky::PublicStorage::new().context("Failed to create PublicStorage")?;
+                    let transport = paykit_lib::PubkyUnauthenticatedTransport::new(storage)
+                        .with_path_roots(super::path_roots());
+                    let record = fetch_noise_endpoint(&transport, &payee_pk)
+                        .await
+                        .context("Failed to refresh Noise endpoint")?
+                        .context("Payee no longer publishes a Noise endpoint")?;
+                    let key = record
+                        .preferred_key(chrono::Utc::now().timestamp())
+                        .context("Payee has no currently valid Noise key")?;
+                    let refreshed: [u8; 32] = hex::decode(&key.pubkey)
+                        .ok()
+                        .and_then(|bytes| bytes.try_into().ok())
+                        .context("Invalid Noise public key in directory")?;
+                    if refreshed == server_pk {
+                        anyhow::bail!("Payee rejected the handshake with its published Noise key");
+                    }
+                    noise_handshake(identity, &connect_addr, &refreshed)
+                        .await?
+                        .context("Payee rejected the refreshed Noise key")?
+                }
+            };
         spinner.finish_and_clear();
+        super::audit::record(
+            storage_dir,
+            AuditOperation::NoiseHandshake,
+            Some(payee_uri),
+            Some("payer"),
+        );
 
         ui::success(&format!("Session established: {}", link.session_id()));
 
         // Create receipt request
         let receipt_id = uuid::Uuid::new_v4().to_string();
+        let mut metadata = intent.map_or_else(|| serde_json::json!({}), |i| i.receipt_metadata());
+        set_memo_in_metadata(&mut metadata, memo);
         let provisional_receipt = PaykitReceipt::new(
             receipt_id.clone(),
             identity.public_key(),
@@ -499,7 +911,7 @@ async fn execute_noise_payment(
             MethodId::new(method),
             amount.map(String::from),
             Some(currency.unwrap_or("SAT").to_string()),
-            serde_json::json!({}),
+            metadata,
         );
 
         let request_msg = PaykitNoiseMessage::RequestReceipt {
@@ -535,9 +947,12 @@ async fn execute_noise_payment(
                     ui::key_value("Amount", amt);
                 }
                 ui::key_value("Method", &receipt.method_id.0);
+                if let Some(memo) = receipt.memo() {
+                    ui::key_value("Note", &memo);
+                }
 
                 // Save receipt with proof if available
-                let demo_storage = DemoStorage::new(storage_dir.join("data"));
+                let demo_storage = super::storage::open(storage_dir);
                 demo_storage.init()?;
 
                 // Convert PaykitReceipt to core Receipt with proof
@@ -558,6 +973,7 @@ async fn execute_noise_payment(
                 // For now, we save the receipt as-is
                 let receipt_json = serde_json::to_string(&receipt)?;
                 demo_storage.save_receipt_json(&receipt.receipt_id, &receipt_json)?;
+                super::timestamp::record(storage_dir, &receipt.receipt_id, &receipt.digest());
 
                 // Also save as core Receipt for consistency
                 demo_storage.save_receipt(core_receipt)?;
@@ -574,12 +990,28 @@ async fn execute_noise_payment(
                     amount.unwrap_or("unspecified"),
                     method,
                 )?;
+
+                if let Some(intent) = intent {
+                    super::link::show_success(intent);
+                }
+
+                output::emit(&PayOutput {
+                    receipt_id: Some(receipt.receipt_id.clone()),
+                    ..PayOutput::new(payee_uri, method, amount, "completed")
+                });
             }
             PaykitNoiseMessage::Error { code, message } => {
-                ui::error(&format!("Payment rejected: {} - {}", code, message));
+                return Err(PaykitError::PaymentRejected {
+                    payment_id: receipt_id,
+                    reason: format!("{} - {}", code, message),
+                }
+                .into());
             }
             _ => {
-                ui::error("Unexpected response from recipient");
+                return Err(PaykitError::Transport(
+                    "Unexpected response from recipient".to_string(),
+                )
+                .into());
             }
         }
     } else {
@@ -605,30 +1037,31 @@ async fn execute_lightning_payment(
 ) -> Result<()> {
     match wallet_config {
         Some(config) if config.has_lightning() => {
-            let lnd_config = config.lnd.as_ref().unwrap();
+            let (backend, node_url) = match (&config.lnd, &config.cln) {
+                (Some(lnd), _) => ("LND", lnd.url.as_str()),
+                (None, Some(cln)) => ("CLN", cln.url.as_str()),
+                (None, None) => unreachable!("has_lightning checked"),
+            };
 
             if dry_run {
                 ui::info("DRY RUN - Would execute Lightning payment:");
-                ui::info(&format!("  LND URL: {}", lnd_config.url));
+                ui::info(&format!("  {} URL: {}", backend, node_url));
                 ui::info(&format!("  Recipient: {}", payee_uri));
                 if let Some(amt) = amount {
                     ui::info(&format!("  Amount: {} sats", amt));
                 }
                 ui::info("  No actual payment will be made");
+                output::emit(&PayOutput::new(payee_uri, "lightning", amount, "dry_run"));
                 return Ok(());
             }
 
             ui::info("Executing Lightning payment...");
 
-            // Create LND executor
             #[cfg(feature = "http-executor")]
             {
-                use paykit_lib::executors::{LndConfig as LibLndConfig, LndExecutor};
-                use paykit_lib::methods::LightningExecutor;
-
-                let lib_config = LibLndConfig::new(&lnd_config.url, &lnd_config.macaroon);
-                let executor =
-                    LndExecutor::new(lib_config).context("Failed to create LND executor")?;
+                let executor = lightning_executor(config)
+                    .with_context(|| format!("Failed to create {} executor", backend))?
+                    .context("No Lightning node configured")?;
 
                 // For now, we expect the payee_uri to contain an invoice
                 // In a full implementation, we'd use Noise to negotiate
@@ -643,6 +1076,7 @@ async fn execute_lightning_payment(
                     ui::info("To pay directly, provide a BOLT11 invoice as the recipient");
                     return Ok(());
                 };
+                config.check_endpoint(&invoice)?;
 
                 // Decode invoice first
                 let decoded = executor
@@ -662,16 +1096,34 @@ async fn execute_lightning_payment(
                     ui::info(&format!("Description: {}", desc));
                 }
                 if decoded.expired {
-                    ui::error("Invoice has expired!");
-                    return Err(anyhow::anyhow!("Invoice expired"));
+                    return Err(PaykitError::InvoiceExpired {
+                        invoice_id: decoded.payment_hash,
+                        expired_at: (decoded.timestamp + decoded.expiry) as i64,
+                    }
+                    .into());
                 }
 
                 // Execute payment
                 let amount_msat = amount.and_then(|s| s.parse::<u64>().ok().map(|a| a * 1000));
-                let result = executor
+                let mut result = executor
                     .pay_invoice(&invoice, amount_msat, None)
                     .await
                     .context("Payment failed")?;
+                if result.status == paykit_lib::methods::LightningPaymentStatus::Pending
+                    && !result.payment_hash.is_empty()
+                {
+                    ui::info("Payment in flight, waiting for it to settle...");
+                    if let Ok(settled) = paykit_lib::executors::wait_for_payment(
+                        executor.as_ref(),
+                        &result.payment_hash,
+                        std::time::Duration::from_secs(1),
+                        std::time::Duration::from_secs(60),
+                    )
+                    .await
+                    {
+                        result = settled;
+                    }
+                }
 
                 ui::separator();
                 match result.status {
@@ -690,7 +1142,7 @@ async fn execute_lightning_payment(
                             serde_json::to_value(&proof).context("Failed to serialize proof")?;
 
                         // Save receipt with proof
-                        let demo_storage = DemoStorage::new(storage_dir.join("data"));
+                        let demo_storage = super::storage::open(storage_dir);
                         demo_storage.init()?;
 
                         let identity = super::load_current_identity(storage_dir).await?;
@@ -705,17 +1157,29 @@ async fn execute_lightning_payment(
                             "lightning".to_string(),
                         )
                         .with_amount(amount.unwrap_or("0").to_string(), "SAT".to_string())
-                        .with_proof(proof_json);
+                        .with_proof(proof_json.clone());
 
+                        let receipt_id = receipt.id.clone();
                         demo_storage.save_receipt(receipt)?;
                         ui::info("Receipt with proof saved");
+
+                        output::emit(&PayOutput {
+                            receipt_id: Some(receipt_id),
+                            proof: Some(proof_json),
+                            ..PayOutput::new(payee_uri, "lightning", amount, "completed")
+                        });
                     }
                     paykit_lib::methods::LightningPaymentStatus::Pending => {
                         ui::warning("Payment pending...");
                         ui::info(&format!("Payment hash: {}", result.payment_hash));
+                        output::emit(&PayOutput::new(payee_uri, "lightning", amount, "pending"));
                     }
                     paykit_lib::methods::LightningPaymentStatus::Failed => {
-                        ui::error("Payment failed");
+                        return Err(PaykitError::Payment {
+                            payment_id: Some(result.payment_hash),
+                            reason: "Lightning payment failed".to_string(),
+                        }
+                        .into());
                     }
                 }
             }
@@ -727,10 +1191,11 @@ async fn execute_lightning_payment(
             }
         }
         Some(_) => {
-            ui::warning("LND not configured for Lightning payments");
+            ui::warning("No Lightning node configured for Lightning payments");
             ui::info(
                 "Configure with: paykit-demo wallet configure-lnd --url <url> --macaroon <hex>",
             );
+            ui::info("   or: paykit-demo wallet configure-cln --url <url> --rune <rune>");
             show_simulation_message();
         }
         None => {
@@ -744,7 +1209,7 @@ async fn execute_lightning_payment(
 }
 
 async fn execute_onchain_payment(
-    _storage_dir: &Path,
+    storage_dir: &Path,
     wallet_config: &Option<WalletConfig>,
     payee_uri: &str,
     amount: Option<&str>,
@@ -754,6 +1219,7 @@ async fn execute_onchain_payment(
     match wallet_config {
         Some(config) if config.has_onchain() => {
             let esplora_config = config.esplora.as_ref().unwrap();
+            config.check_endpoint(payee_uri)?;
 
             if dry_run {
                 ui::info("DRY RUN - Would prepare on-chain payment:");
@@ -763,6 +1229,7 @@ async fn execute_onchain_payment(
                     ui::info(&format!("  Amount: {} sats", amt));
                 }
                 ui::info("  No actual transaction will be created");
+                output::emit(&PayOutput::new(payee_uri, "onchain", amount, "dry_run"));
                 return Ok(());
             }
 
@@ -770,9 +1237,13 @@ async fn execute_onchain_payment(
 
             #[cfg(feature = "http-executor")]
             {
-                use paykit_lib::executors::{EsploraConfig as LibEsploraConfig, EsploraExecutor};
+                use paykit_lib::executors::{
+                    EsploraBitcoinExecutor, EsploraConfig as LibEsploraConfig, EsploraExecutor,
+                    PsbtSigner,
+                };
 
-                let lib_config = LibEsploraConfig::new(&esplora_config.url);
+                let lib_config = LibEsploraConfig::new(&esplora_config.url)
+                    .with_proxy(super::proxy_for(TransportKind::Esplora));
                 let executor = EsploraExecutor::new(lib_config)
                     .context("Failed to create Esplora executor")?;
 
@@ -796,15 +1267,84 @@ async fn execute_onchain_payment(
                     fees.get_rate_for_blocks(144)
                 ));
 
+                if esplora_config.funding_addresses.is_empty() {
+                    ui::separator();
+                    ui::warning("On-chain payment requires funding addresses for signing");
+                    ui::info("Esplora can verify transactions but cannot create them.");
+                    ui::info(
+                        "Add watch-only addresses with: paykit-demo wallet configure-esplora \
+                         --url <url> --funding-address <address>",
+                    );
+                    if let Some(amt) = amount {
+                        ui::info(&format!("Send {} sats to: {}", amt, payee_uri));
+                    } else {
+                        ui::info(&format!("Send to: {}", payee_uri));
+                    }
+                    return Ok(());
+                }
+
+                let amount_sats = amount.and_then(|a| a.parse::<u64>().ok()).ok_or_else(|| {
+                    PaykitError::invalid_data("amount", "Amount in sats required")
+                })?;
+                let change_address = esplora_config
+                    .change_address
+                    .as_deref()
+                    .unwrap_or(&esplora_config.funding_addresses[0]);
+                let wallet = EsploraBitcoinExecutor::new(
+                    executor,
+                    &esplora_config.funding_addresses,
+                    change_address,
+                    Arc::new(PromptSigner),
+                )?;
+
+                let unsigned = wallet
+                    .build_psbt(payee_uri, amount_sats, None)
+                    .await
+                    .context("Failed to build transaction")?;
                 ui::separator();
-                ui::warning("On-chain payment requires a wallet for signing");
-                ui::info("Esplora can verify transactions but cannot create them.");
-                ui::info("Use a wallet like Sparrow or Electrum to create the transaction.");
-                if let Some(amt) = amount {
-                    ui::info(&format!("Send {} sats to: {}", amt, payee_uri));
-                } else {
-                    ui::info(&format!("Send to: {}", payee_uri));
+                ui::info(&format!("Amount: {} sats", unsigned.amount_sats));
+                ui::info(&format!(
+                    "Fee: {} sats ({:.1} sat/vB)",
+                    unsigned.fee_sats, unsigned.fee_rate
+                ));
+                if unsigned.change_sats > 0 {
+                    ui::info(&format!("Change: {} sats", unsigned.change_sats));
                 }
+
+                let signed = PromptSigner.sign_psbt(&unsigned.psbt_base64).await?;
+                let result = wallet
+                    .finalize_and_broadcast(&unsigned, &signed)
+                    .await
+                    .context("Failed to broadcast transaction")?;
+                ui::success(&format!("Transaction broadcast: {}", result.txid));
+
+                let proof = paykit_lib::methods::PaymentProof::bitcoin_txid(&result.txid, None);
+                let proof_json =
+                    serde_json::to_value(&proof).context("Failed to serialize proof")?;
+                let demo_storage = super::storage::open(storage_dir);
+                demo_storage.init()?;
+                let identity = super::load_current_identity(storage_dir).await?;
+                let receipt = paykit_demo_core::Receipt::new(
+                    uuid::Uuid::new_v4().to_string(),
+                    identity.public_key(),
+                    payee_uri.parse().unwrap_or_else(|_| {
+                        "8pinxxgqs41n4aididenw5apqp1urfmzdztr8jt4abrkdn435ewo"
+                            .parse()
+                            .unwrap()
+                    }),
+                    "onchain".to_string(),
+                )
+                .with_amount(amount_sats.to_string(), "SAT".to_string())
+                .with_proof(proof_json.clone());
+                let receipt_id = receipt.id.clone();
+                demo_storage.save_receipt(receipt)?;
+                ui::info("Receipt with txid saved; check confirmations with `receipts show`");
+
+                output::emit(&PayOutput {
+                    receipt_id: Some(receipt_id),
+                    proof: Some(proof_json),
+                    ..PayOutput::new(payee_uri, "onchain", amount, "pending")
+                });
             }
 
             #[cfg(not(feature = "http-executor"))]
@@ -828,6 +1368,40 @@ async fn execute_onchain_payment(
     Ok(())
 }
 
+/// Hands PSBTs to the user's wallet (Sparrow, a hardware wallet via HWI, ...)
+/// through the terminal
+#[cfg(feature = "http-executor")]
+struct PromptSigner;
+
+#[cfg(feature = "http-executor")]
+#[async_trait::async_trait]
+impl paykit_lib::executors::PsbtSigner for PromptSigner {
+    async fn sign_psbt(&self, psbt_base64: &str) -> paykit_lib::Result<String> {
+        use std::io::Write;
+
+        eprintln!();
+        eprintln!("Sign and finalize this PSBT in your wallet:");
+        eprintln!();
+        eprintln!("{}", psbt_base64);
+        eprintln!();
+        eprint!("Paste the signed PSBT (empty to cancel): ");
+        std::io::stderr().flush().ok();
+
+        let mut signed = String::new();
+        std::io::stdin()
+            .read_line(&mut signed)
+            .map_err(|e| PaykitError::Internal(format!("Failed to read PSBT: {}", e)))?;
+        let signed = signed.trim();
+        if signed.is_empty() {
+            return Err(PaykitError::PaymentRejected {
+                payment_id: String::new(),
+                reason: "Signing cancelled".to_string(),
+            });
+        }
+        Ok(signed.to_string())
+    }
+}
+
 fn show_simulation_message() {
     ui::separator();
     ui::info("SIMULATION MODE");
@@ -839,7 +1413,21 @@ fn show_simulation_message() {
     ui::separator();
 }
 
-fn resolve_recipient(storage_dir: &Path, recipient: &str) -> Result<String> {
+async fn resolve_recipient(storage_dir: &Path, recipient: &str) -> Result<String> {
+    // A handle (alice@example.com) names a key through its domain
+    if Handle::is_handle(recipient) {
+        let spinner = ui::spinner(&format!("Resolving {}...", recipient));
+        let resolved = super::resolve_handle(recipient).await;
+        spinner.finish_and_clear();
+        let resolved = resolved?;
+        let source = match resolved.source {
+            HandleSource::WellKnown => "well-known document",
+            HandleSource::Dns => "DNS",
+        };
+        ui::info(&format!("Resolved {} via {}", resolved.handle, source));
+        return Ok(format!("pubky://{}", resolved.public_key));
+    }
+
     // If it looks like a URI, return as-is
     if recipient.starts_with("pubky://")
         || recipient.starts_with("ln")
@@ -851,7 +1439,7 @@ fn resolve_recipient(storage_dir: &Path, recipient: &str) -> Result<String> {
     }
 
     // Otherwise, try to look up as contact name
-    let storage = DemoStorage::new(storage_dir.join("data"));
+    let storage = super::storage::open(storage_dir);
     let contacts = storage.list_contacts()?;
 
     for contact in contacts {
@@ -870,23 +1458,32 @@ async fn handle_endpoint_rotation(
     method_id: &MethodId,
     verbose: bool,
 ) -> Result<()> {
-    // Load or create rotation manager with default config
-    let rotation_config = load_rotation_config(storage_dir)?;
-    let registry = default_registry();
-    let rotation_manager = EndpointRotationManager::new(rotation_config, registry);
-
     // Record payment execution and check if rotation is needed
-    if let Some(new_endpoint) = rotation_manager.on_payment_executed(method_id).await {
-        ui::separator();
-        ui::info(&format!("Endpoint rotated for method: {}", method_id.0));
-        if verbose {
-            ui::key_value("New endpoint", &new_endpoint.0);
-        }
-        ui::warning("New endpoint generated but not yet published to directory");
-        ui::info("Run 'paykit-demo publish' to update the directory with the new endpoint");
+    let mut engine = super::rotation::load_engine(storage_dir)?;
+    let now = chrono::Utc::now().timestamp();
+    let decision = engine.on_payment(method_id, now);
 
-        // Save rotation state for future reference
-        save_rotation_state(storage_dir, method_id, &new_endpoint)?;
+    if let Some(reason) = decision.reason() {
+        let registry = default_registry();
+        let rotation_manager = EndpointRotationManager::new(engine.config().clone(), registry);
+
+        match rotation_manager.rotate(method_id).await {
+            Ok(new_endpoint) => {
+                ui::separator();
+                ui::info(&format!("Endpoint rotated for method: {}", method_id.0));
+                if verbose {
+                    ui::key_value("New endpoint", &new_endpoint.0);
+                }
+                ui::warning("New endpoint generated but not yet published to directory");
+                ui::info("Run 'paykit-demo publish' to update the directory with the new endpoint");
+
+                engine.record_rotation(method_id, reason, Some(new_endpoint.0), now);
+            }
+            Err(e) if verbose => {
+                ui::warning(&format!("Endpoint rotation failed: {}", e));
+            }
+            Err(_) => {}
+        }
     } else if verbose {
         ui::info(&format!(
             "No rotation needed for method: {} (policy check)",
@@ -894,60 +1491,8 @@ async fn handle_endpoint_rotation(
         ));
     }
 
-    Ok(())
-}
-
-/// Load rotation configuration from storage or return default
-fn load_rotation_config(storage_dir: &Path) -> Result<RotationConfig> {
-    let config_path = storage_dir.join("rotation_config.json");
-
-    if config_path.exists() {
-        let config_str =
-            std::fs::read_to_string(&config_path).context("Failed to read rotation config")?;
-        serde_json::from_str(&config_str).context("Failed to parse rotation config")
-    } else {
-        // Return default config (rotate on use for privacy)
-        Ok(RotationConfig::default())
-    }
-}
-
-/// Save rotation state for tracking
-fn save_rotation_state(
-    storage_dir: &Path,
-    method_id: &MethodId,
-    new_endpoint: &paykit_lib::EndpointData,
-) -> Result<()> {
-    let state_path = storage_dir.join("rotation_state.json");
-
-    // Load existing state or create new
-    let mut state: serde_json::Value = if state_path.exists() {
-        let state_str =
-            std::fs::read_to_string(&state_path).context("Failed to read rotation state")?;
-        serde_json::from_str(&state_str).unwrap_or_else(|_| serde_json::json!({}))
-    } else {
-        serde_json::json!({})
-    };
-
-    // Update state for this method
-    let method_key = &method_id.0;
-    if !state[method_key].is_object() {
-        state[method_key] = serde_json::json!({
-            "last_rotated": chrono::Utc::now().timestamp(),
-            "rotations": 0,
-        });
-    }
-
-    state[method_key]["last_rotated"] = serde_json::json!(chrono::Utc::now().timestamp());
-    state[method_key]["rotations"] =
-        serde_json::json!(state[method_key]["rotations"].as_u64().unwrap_or(0) + 1);
-    state[method_key]["pending_endpoint"] = serde_json::json!(new_endpoint.0);
-
-    // Save updated state
-    let state_str =
-        serde_json::to_string_pretty(&state).context("Failed to serialize rotation state")?;
-    std::fs::write(&state_path, state_str).context("Failed to save rotation state")?;
-
-    Ok(())
+    // Save rotation state for future reference
+    super::rotation::save_engine(storage_dir, &engine)
 }
 
 fn log_payment_attempt(
@@ -0,0 +1,290 @@
+//! Scheduled payment commands
+//!
+//! One-off payments set up now and sent later ("pay X 20k sats on the
+//! 1st"). Each payment has an execution window; `payments run` or
+//! `payments watch` sends the due ones, skipping methods the health monitor
+//! reports as down, and records them as ordinary receipts. Payments can be
+//! cancelled until they are sent.
+
+use anyhow::{anyhow, Context, Result};
+use paykit_demo_core::ScheduledPaymentCoordinator;
+use paykit_lib::health::HealthMonitor;
+use paykit_lib::methods::default_registry;
+use paykit_lib::scheduled_payments::{
+    PaymentScheduler, ScheduledOutcome, ScheduledPayment, ScheduledStatus,
+};
+use paykit_lib::MethodId;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::{output, ui};
+
+/// Schedule a payment
+#[allow(clippy::too_many_arguments)]
+pub async fn schedule(
+    storage_dir: &Path,
+    payee: &str,
+    amount: u64,
+    at: Option<String>,
+    delay: Option<i64>,
+    window: Option<i64>,
+    methods: Vec<String>,
+    memo: Option<String>,
+    id: Option<String>,
+    _verbose: bool,
+) -> Result<()> {
+    ui::header("Schedule Payment");
+
+    let now = chrono::Utc::now().timestamp();
+    let not_before = match (at.as_deref(), delay) {
+        (Some(at), None) => parse_time(at)?,
+        (None, Some(delay)) => now + delay,
+        (None, None) => now,
+        (Some(_), Some(_)) => return Err(anyhow!("Specify at most one of --at or --in")),
+    };
+    let payee = super::standing::resolve_payee(storage_dir, payee)?;
+    let id = id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    let mut payment = ScheduledPayment::new(id, payee, amount, not_before, now)?
+        .with_methods(methods.into_iter().map(MethodId::new).collect());
+    if let Some(window) = window {
+        payment = payment.with_deadline(not_before + window);
+    }
+    if let Some(memo) = memo {
+        payment = payment.with_memo(memo);
+    }
+
+    ScheduledPaymentCoordinator::new(storage_dir).add(payment.clone())?;
+    output::emit(&payment);
+    ui::success("Payment scheduled");
+    show_payment(&payment);
+    Ok(())
+}
+
+/// List scheduled payments
+pub async fn list(storage_dir: &Path, all: bool, _verbose: bool) -> Result<()> {
+    ui::header("Scheduled Payments");
+
+    let coordinator = ScheduledPaymentCoordinator::new(storage_dir);
+    let payments: Vec<ScheduledPayment> = coordinator
+        .list()?
+        .into_iter()
+        .filter(|payment| all || !payment.is_settled())
+        .collect();
+    output::emit(&payments);
+    if payments.is_empty() {
+        ui::info("No scheduled payments");
+        return Ok(());
+    }
+
+    let in_progress = coordinator.in_progress()?;
+    for payment in &payments {
+        show_payment(payment);
+        if let Some(claimed_at) = in_progress.get(&payment.payment_id) {
+            ui::key_value("Being sent since", &format_time(*claimed_at));
+        }
+        ui::separator();
+    }
+    Ok(())
+}
+
+/// Cancel a payment that has not been sent yet
+pub async fn cancel(storage_dir: &Path, id: &str, _verbose: bool) -> Result<()> {
+    let now = chrono::Utc::now().timestamp();
+    let payment = ScheduledPaymentCoordinator::new(storage_dir).cancel(id, now)?;
+    output::emit(&payment);
+    ui::success(&format!("Cancelled scheduled payment {}", id));
+    Ok(())
+}
+
+/// Release a payment left claimed by an interrupted run
+pub async fn release(storage_dir: &Path, id: &str, _verbose: bool) -> Result<()> {
+    if !ScheduledPaymentCoordinator::new(storage_dir).release(id)? {
+        ui::info(&format!("Scheduled payment {} is not being sent", id));
+        return Ok(());
+    }
+    ui::success(&format!("Released scheduled payment {}", id));
+    ui::warning("If the interrupted payment went out, the next run sends it again");
+    Ok(())
+}
+
+/// Send every payment that is due now
+pub async fn run(storage_dir: &Path, verbose: bool) -> Result<()> {
+    ui::header("Scheduled Payments");
+
+    let paid = run_due(storage_dir, verbose).await?;
+    if paid == 0 {
+        ui::info("No scheduled payments due");
+    }
+    Ok(())
+}
+
+/// Keep sending due payments until interrupted
+pub async fn watch(storage_dir: &Path, poll: u64, verbose: bool) -> Result<()> {
+    ui::header("Scheduled Payment Watcher");
+
+    ui::info(&format!("Checking every {}s, press Ctrl+C to stop", poll));
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(poll.max(1)));
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                // Failures are reported per payment; keep watching
+                if let Err(e) = run_due(storage_dir, verbose).await {
+                    ui::error(&format!("Scheduled payment run failed: {}", e));
+                }
+            }
+            _ = tokio::signal::ctrl_c() => {
+                ui::info("Stopped");
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Send the due payments and expire overdue ones, returning how many were paid
+async fn run_due(storage_dir: &Path, verbose: bool) -> Result<usize> {
+    let payments = ScheduledPaymentCoordinator::new(storage_dir);
+    let now = chrono::Utc::now().timestamp();
+    let due = payments.due(now)?;
+    let overdue = payments.overdue(now)?;
+    if due.is_empty() && overdue.is_empty() {
+        return Ok(0);
+    }
+
+    let identity = super::load_current_identity(storage_dir).await?;
+    let storage = super::storage::open(storage_dir);
+    storage.init()?;
+    let health = Arc::new(HealthMonitor::with_defaults());
+    health.check_all().await;
+    let scheduler = PaymentScheduler::new(default_registry()).with_health_monitor(health);
+    let public_storage = pubky::PublicStorage::new().context("Failed to create PublicStorage")?;
+    let transport = paykit_lib::PubkyUnauthenticatedTransport::new(public_storage)
+        .with_path_roots(super::path_roots());
+
+    // Overdue payments expire without contacting the payee
+    let empty = paykit_lib::SupportedPayments::default();
+    for payment in overdue {
+        payments
+            .run(
+                &scheduler,
+                &payment.payment_id,
+                &empty,
+                &storage,
+                &identity.public_key(),
+                now,
+            )
+            .await?;
+        ui::warning(&format!(
+            "{}: window closed before it could be paid",
+            payment.payment_id
+        ));
+    }
+
+    let mut paid = 0;
+    for payment in due {
+        let payee: paykit_lib::PublicKey = payment
+            .payee
+            .parse()
+            .map_err(|e| anyhow!("Invalid payee public key: {}", e))?;
+        let supported = match paykit_lib::get_payment_list(&transport, &payee).await {
+            Ok(supported) => supported,
+            Err(e) => {
+                ui::error(&format!(
+                    "{}: could not fetch payee methods: {}",
+                    payment.payment_id, e
+                ));
+                continue;
+            }
+        };
+
+        match payments
+            .run(
+                &scheduler,
+                &payment.payment_id,
+                &supported,
+                &storage,
+                &identity.public_key(),
+                now,
+            )
+            .await
+        {
+            Ok(ScheduledOutcome::Paid(execution)) => {
+                paid += 1;
+                ui::success(&format!(
+                    "{}: paid {} via {}",
+                    payment.payment_id,
+                    ui::sats(payment.amount_sats),
+                    execution.method_id.0
+                ));
+            }
+            Ok(ScheduledOutcome::Failed(execution)) => ui::error(&format!(
+                "{}: payment failed, will retry: {}",
+                payment.payment_id,
+                execution.error.unwrap_or_default()
+            )),
+            Ok(ScheduledOutcome::Unresolved(execution)) => ui::warning(&format!(
+                "{}: payment may have been sent ({}); check the wallet, then release it",
+                payment.payment_id,
+                execution.error.unwrap_or_default()
+            )),
+            Ok(ScheduledOutcome::Held(held)) => ui::warning(&format!(
+                "{}: held by velocity checks ({})",
+                payment.payment_id, held.hold_id
+            )),
+            Ok(ScheduledOutcome::Skipped(skip)) => {
+                if verbose {
+                    ui::info(&format!("{}: {}", payment.payment_id, skip.describe()));
+                }
+            }
+            Err(e) => ui::error(&format!("{}: {}", payment.payment_id, e)),
+        }
+    }
+    Ok(paid)
+}
+
+/// Accept an RFC 3339 timestamp or unix seconds
+fn parse_time(value: &str) -> Result<i64> {
+    if let Ok(secs) = value.parse::<i64>() {
+        return Ok(secs);
+    }
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|time| time.timestamp())
+        .map_err(|_| anyhow!("Invalid time '{}': use RFC 3339 or unix seconds", value))
+}
+
+fn format_time(at: i64) -> String {
+    chrono::DateTime::from_timestamp(at, 0)
+        .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| at.to_string())
+}
+
+fn show_payment(payment: &ScheduledPayment) {
+    ui::key_value("Payment", &payment.payment_id);
+    ui::key_value("Payee", &payment.payee);
+    ui::key_value("Amount", &ui::sats(payment.amount_sats));
+    let window = match payment.not_after {
+        Some(end) => format!(
+            "{} to {}",
+            format_time(payment.not_before),
+            format_time(end)
+        ),
+        None => format!("from {}", format_time(payment.not_before)),
+    };
+    ui::key_value("Window", &window);
+    if !payment.methods.is_empty() {
+        let methods: Vec<&str> = payment.methods.iter().map(|m| m.0.as_str()).collect();
+        ui::key_value("Methods", &methods.join(", "));
+    }
+    if let Some(memo) = &payment.memo {
+        ui::key_value("Memo", memo);
+    }
+    let status = match (payment.status, payment.settled_at) {
+        (ScheduledStatus::Pending, _) => "pending".to_string(),
+        (status, Some(at)) => format!("{} {}", status.as_str(), format_time(at)),
+        (status, None) => status.as_str().to_string(),
+    };
+    ui::key_value("Status", &status);
+    if let Some(error) = &payment.last_error {
+        ui::key_value("Last error", error);
+    }
+}
@@ -0,0 +1,331 @@
+//! Point-of-sale command - merchant checkout loop
+//!
+//! Each sale creates a signed payment request, displays it as a QR code and
+//! waits on the Noise receiver for a matching receipt. Sales are tallied for
+//! the session and exported when the register closes.
+
+use anyhow::{Context, Result};
+use paykit_lib::audit::AuditOperation;
+use paykit_lib::uri::PaymentRequestLink;
+use paykit_lib::MethodId;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+use crate::ui;
+
+/// A completed sale.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PosSale {
+    pub request_id: String,
+    pub receipt_id: String,
+    pub amount_sats: u64,
+    pub method: String,
+    pub payer: String,
+    pub description: Option<String>,
+    pub paid_at: i64,
+}
+
+/// Sales recorded while the register is open.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PosSession {
+    pub opened_at: i64,
+    pub closed_at: Option<i64>,
+    pub sales: Vec<PosSale>,
+}
+
+impl PosSession {
+    fn new() -> Self {
+        Self {
+            opened_at: chrono::Utc::now().timestamp(),
+            closed_at: None,
+            sales: Vec::new(),
+        }
+    }
+
+    /// Total sats collected this session.
+    pub fn total_sats(&self) -> u64 {
+        self.sales.iter().map(|s| s.amount_sats).sum()
+    }
+
+    /// Render the session as CSV, one row per sale.
+    pub fn to_csv(&self) -> String {
+        let mut out =
+            String::from("paid_at,request_id,receipt_id,amount_sats,method,payer,description\n");
+        for sale in &self.sales {
+            let description = sale
+                .description
+                .as_deref()
+                .unwrap_or("")
+                .replace('"', "\"\"");
+            out.push_str(&format!(
+                "{},{},{},{},{},{},\"{}\"\n",
+                sale.paid_at,
+                sale.request_id,
+                sale.receipt_id,
+                sale.amount_sats,
+                sale.method,
+                sale.payer,
+                description
+            ));
+        }
+        out
+    }
+
+    /// Write the session to `path`, as CSV if the extension is `.csv` and JSON otherwise.
+    pub fn export(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let is_csv = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        let contents = if is_csv {
+            self.to_csv()
+        } else {
+            serde_json::to_string_pretty(self)?
+        };
+        std::fs::write(path, contents)
+            .with_context(|| format!("Failed to write session export to {}", path.display()))
+    }
+}
+
+/// Run the point-of-sale loop until the operator closes the register
+#[tracing::instrument(skip(storage_dir, export))]
+pub async fn run(
+    storage_dir: &Path,
+    port: u16,
+    method: &str,
+    timeout_secs: u64,
+    export: Option<PathBuf>,
+    verbose: bool,
+) -> Result<()> {
+    ui::header("Point of Sale");
+
+    let identity = super::load_current_identity(storage_dir).await?;
+    let my_pubkey = identity.public_key();
+    let (servers, static_pks) = super::receive::noise_servers(&identity, &[0])?;
+    let limits = super::receive::receive_limits(Default::default());
+    let manager = super::receive::build_manager(storage_dir)?.with_peer_limits(limits.clone());
+
+    let listener = TcpListener::bind(format!("0.0.0.0:{}", port))
+        .await
+        .context("Failed to bind to port")?;
+
+    ui::info(&format!("Merchant: {}", identity.pubky_uri()));
+    ui::info(&format!("Receiver listening on 0.0.0.0:{}", port));
+    if verbose {
+        ui::info(&format!(
+            "Noise public key: {}",
+            hex::encode(&static_pks[0][..16])
+        ));
+    }
+    ui::info("Enter an amount in sats for each sale; type 'q' to close the register");
+    ui::separator();
+
+    let mut session = PosSession::new();
+
+    loop {
+        let input = ui::input("Amount (sats)")?;
+        let input = input.trim();
+        if input.eq_ignore_ascii_case("q") {
+            break;
+        }
+        let amount_sats: u64 = match input.parse() {
+            Ok(sats) if sats > 0 => sats,
+            _ => {
+                ui::warning("Amount must be a positive whole number of sats");
+                continue;
+            }
+        };
+        let description = ui::input_with_default("Description", "")?;
+        let description = Some(description.trim().to_string()).filter(|d| !d.is_empty());
+
+        // Create the payment intent as a signed request link
+        let request_id = format!("pos_{}", uuid::Uuid::new_v4());
+        let mut link = PaymentRequestLink::new(&request_id, my_pubkey.clone())
+            .with_amount_sats(amount_sats)
+            .with_methods(vec![MethodId::new(method)]);
+        if let Some(desc) = &description {
+            link = link.with_description(desc);
+        }
+        if timeout_secs > 0 {
+            let expires_at = link.created_at + timeout_secs as i64;
+            link = link.with_expires_at(expires_at);
+        }
+        let uri = link.sign(&identity.keypair)?.to_uri();
+        super::audit::record(
+            storage_dir,
+            AuditOperation::Sign,
+            None,
+            Some(&format!("payment link {}", request_id)),
+        );
+
+        ui::separator();
+        ui::key_value("Amount", &ui::sats(amount_sats));
+        if let Some(desc) = &description {
+            ui::key_value("Description", desc);
+        }
+        if verbose {
+            ui::key_value("Request ID", &request_id);
+            ui::key_value("URI", &uri);
+        }
+        ui::line("");
+        ui::qr_code(&uri)?;
+        ui::info("Waiting for payment... (Ctrl+C to cancel this sale)");
+
+        // Watch the receiver for a receipt matching this sale
+        let deadline = (timeout_secs > 0).then(|| Duration::from_secs(timeout_secs));
+        let expiry = async {
+            match deadline {
+                Some(d) => tokio::time::sleep(d).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::pin!(expiry);
+
+        let receipt = loop {
+            tokio::select! {
+                result = listener.accept() => {
+                    let (socket, addr) = match result {
+                        Ok(conn) => conn,
+                        Err(e) => {
+                            ui::error(&format!("Accept error: {}", e));
+                            continue;
+                        }
+                    };
+                    if verbose {
+                        ui::info(&format!("Connection from: {}", addr));
+                    }
+                    let confirmed = super::receive::handle_connection(
+                        storage_dir, socket, &servers, &manager, &limits, &my_pubkey, verbose,
+                    )
+                    .await;
+                    let matched = confirmed.into_iter().find(|r| {
+                        r.amount.as_deref().and_then(|a| a.parse::<u64>().ok())
+                            == Some(amount_sats)
+                    });
+                    match matched {
+                        Some(receipt) => break Some(receipt),
+                        None => ui::warning("Received payment does not match this sale"),
+                    }
+                }
+                _ = &mut expiry => {
+                    ui::warning("Payment request expired");
+                    break None;
+                }
+                _ = tokio::signal::ctrl_c() => {
+                    ui::warning("Sale cancelled");
+                    break None;
+                }
+            }
+        };
+
+        if let Some(receipt) = receipt {
+            let sale = PosSale {
+                request_id,
+                receipt_id: receipt.receipt_id.clone(),
+                amount_sats,
+                method: receipt.method_id.0.clone(),
+                payer: receipt.payer.to_string(),
+                description,
+                paid_at: chrono::Utc::now().timestamp(),
+            };
+
+            ui::separator();
+            ui::success("Payment received");
+            ui::key_value("Receipt", &sale.receipt_id);
+            ui::key_value("Payer", &sale.payer);
+            ui::key_value("Method", &sale.method);
+            ui::key_value("Amount", &ui::sats(sale.amount_sats));
+
+            session.sales.push(sale);
+        }
+
+        ui::separator();
+        ui::key_value(
+            "Session total",
+            &format!(
+                "{} ({} sales)",
+                ui::sats(session.total_sats()),
+                session.sales.len()
+            ),
+        );
+        ui::separator();
+    }
+
+    session.closed_at = Some(chrono::Utc::now().timestamp());
+
+    ui::header("Register Closed");
+    ui::key_value("Sales", &session.sales.len().to_string());
+    ui::key_value("Total", &ui::sats(session.total_sats()));
+
+    if !session.sales.is_empty() {
+        let path = export.unwrap_or_else(|| {
+            storage_dir
+                .join("pos")
+                .join(format!("session-{}.json", session.opened_at))
+        });
+        session.export(&path)?;
+        ui::success(&format!("Session exported to {}", path.display()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn sample_session() -> PosSession {
+        let mut session = PosSession::new();
+        for (i, amount) in [1500u64, 2500].into_iter().enumerate() {
+            session.sales.push(PosSale {
+                request_id: format!("pos_{}", i),
+                receipt_id: format!("rcpt_{}", i),
+                amount_sats: amount,
+                method: "lightning".to_string(),
+                payer: "payer".to_string(),
+                description: Some("Coffee \"large\"".to_string()),
+                paid_at: 1_700_000_000 + i as i64,
+            });
+        }
+        session
+    }
+
+    #[test]
+    fn test_pos_session_total() {
+        assert_eq!(sample_session().total_sats(), 4000);
+        assert_eq!(PosSession::new().total_sats(), 0);
+    }
+
+    #[test]
+    fn test_pos_session_csv() {
+        let csv = sample_session().to_csv();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("paid_at,"));
+        assert!(lines[1].contains(",1500,lightning,"));
+        assert!(lines[1].ends_with("\"Coffee \"\"large\"\"\""));
+    }
+
+    #[test]
+    fn test_pos_session_export() {
+        let dir = tempdir().unwrap();
+        let session = sample_session();
+
+        let json_path = dir.path().join("pos/session.json");
+        session.export(&json_path).unwrap();
+        let loaded: PosSession =
+            serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(loaded.sales.len(), 2);
+
+        let csv_path = dir.path().join("session.CSV");
+        session.export(&csv_path).unwrap();
+        assert!(std::fs::read_to_string(&csv_path)
+            .unwrap()
+            .starts_with("paid_at,"));
+    }
+}
@@ -3,11 +3,10 @@
 //! Provides parity with mobile demo's ProfileImportView functionality.
 
 use anyhow::{Context, Result};
-use paykit_demo_core::DirectoryClient;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
-use crate::ui;
+use crate::{output, ui};
 
 /// Profile data from Pubky directory
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -57,7 +56,7 @@ pub async fn fetch(
         ui::info(&format!("Homeserver: {}", homeserver));
     }
 
-    let client = DirectoryClient::new(homeserver);
+    let client = super::directory_client(homeserver);
     let spinner = if !output_json {
         Some(ui::spinner("Fetching profile..."))
     } else {
@@ -75,14 +74,19 @@ pub async fn fetch(
 
             match serde_json::from_str::<Profile>(&data) {
                 Ok(profile) => {
-                    if output_json {
+                    if output::is_json() {
+                        output::emit(&profile);
+                    } else if output_json {
                         println!("{}", serde_json::to_string_pretty(&profile)?);
                     } else {
                         display_profile(&profile, &public_key.to_string());
                     }
                 }
                 Err(e) => {
-                    if output_json {
+                    if output::is_json() {
+                        return Err(paykit_lib::PaykitError::Serialization(e.to_string()))
+                            .context("Failed to parse profile");
+                    } else if output_json {
                         println!("{{\"error\": \"Failed to parse profile: {}\"}}", e);
                     } else {
                         ui::error(&format!("Failed to parse profile: {}", e));
@@ -97,7 +101,9 @@ pub async fn fetch(
             if let Some(s) = spinner {
                 s.finish_and_clear();
             }
-            if output_json {
+            if output::is_json() {
+                return Err(paykit_lib::PaykitError::not_found("profile", uri).into());
+            } else if output_json {
                 println!("{{\"error\": \"Profile not found\"}}");
             } else {
                 ui::info("No profile found for this public key");
@@ -107,7 +113,9 @@ pub async fn fetch(
             if let Some(s) = spinner {
                 s.finish_and_clear();
             }
-            if output_json {
+            if output::is_json() {
+                return Err(e.context("Failed to fetch profile"));
+            } else if output_json {
                 println!("{{\"error\": \"{}\"}}", e);
             } else {
                 ui::error(&format!("Failed to fetch profile: {}", e));
@@ -158,7 +166,7 @@ pub async fn publish(
     }
     ui::separator();
 
-    let client = DirectoryClient::new(homeserver);
+    let client = super::directory_client(homeserver);
 
     // Create session
     let spinner = ui::spinner("Connecting to homeserver...");
@@ -216,7 +224,7 @@ pub async fn import(
         ui::info(&format!("Importing from: {}", from_uri));
     }
 
-    let client = DirectoryClient::new(homeserver);
+    let client = super::directory_client(homeserver);
 
     // Fetch source profile
     let spinner = ui::spinner("Fetching source profile...");
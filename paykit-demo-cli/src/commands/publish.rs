@@ -1,17 +1,33 @@
 //! Publish command - publish payment methods to directory
 
 use anyhow::{Context, Result};
-use paykit_demo_core::{DirectoryClient, PaymentMethod};
+use paykit_demo_core::{DirectoryClient, DirectoryConflict, PaymentMethod};
 use paykit_lib::prelude::*;
+use pubky::PubkySession;
+use serde::Serialize;
 use std::path::Path;
 
-use crate::ui;
+use super::discover::MethodOutput;
+use crate::{output, ui};
+
+/// Times to retry after merging with a concurrent publish
+const MAX_PUBLISH_ATTEMPTS: usize = 3;
+
+/// `data` of `publish --output json`
+#[derive(Serialize)]
+struct PublishOutput {
+    public_key: String,
+    homeserver: String,
+    methods: Vec<MethodOutput>,
+}
 
 #[tracing::instrument(skip(storage_dir))]
 pub async fn run(
     storage_dir: &Path,
     onchain: Option<String>,
     lightning: Option<String>,
+    ecash: Option<String>,
+    btcpay: bool,
     homeserver: &str,
     verbose: bool,
 ) -> Result<()> {
@@ -42,10 +58,41 @@ pub async fn run(
         methods.push(PaymentMethod::new("lightning".to_string(), invoice, true));
     }
 
+    if let Some(federation) = ecash {
+        methods.push(PaymentMethod::new(
+            paykit_lib::methods::ECASH_METHOD_ID.to_string(),
+            federation,
+            true,
+        ));
+    }
+
+    if btcpay {
+        let config = super::wallet::WalletConfig::load(storage_dir)?
+            .and_then(|wallet| wallet.btcpay)
+            .ok_or_else(|| {
+                anyhow::anyhow!("No BTCPay store configured; run `wallet configure-btcpay`")
+            })?;
+        let receiver = paykit_lib::executors::BtcPayReceiver::new(config.to_receiver_config())?;
+        let spinner = ui::spinner("Fetching BTCPay store endpoints...");
+        let endpoints = receiver.store_endpoints().await;
+        spinner.finish_and_clear();
+        for (method_id, endpoint) in endpoints.context("Failed to fetch BTCPay endpoints")? {
+            // Endpoints given explicitly win over the store's
+            if methods.iter().any(|m| m.method_id == method_id.as_str()) {
+                continue;
+            }
+            methods.push(PaymentMethod::new(
+                method_id.as_str().to_string(),
+                endpoint.as_str().to_string(),
+                true,
+            ));
+        }
+    }
+
     if methods.is_empty() {
-        ui::error("No payment methods specified");
-        ui::info("Use --onchain or --lightning to specify methods");
-        return Ok(());
+        anyhow::bail!(
+            "No payment methods specified; use --onchain, --lightning, --ecash or --btcpay"
+        );
     }
 
     // Show what we'll publish
@@ -59,6 +106,7 @@ pub async fn run(
     ui::info("Validating payment methods...");
 
     let registry = default_registry();
+    registry.register(Box::new(paykit_lib::methods::EcashPlugin::new()));
     let mut all_valid = true;
 
     for method in &methods {
@@ -91,17 +139,29 @@ pub async fn run(
         }
     }
 
+    // Endpoints must be on the wallet profile's network
+    if let Some(wallet) = super::wallet::WalletConfig::load(storage_dir)? {
+        for method in &methods {
+            if let Err(e) = wallet.check_endpoint(&method.endpoint) {
+                all_valid = false;
+                ui::error(&format!("  {} - {}", method.method_id, e));
+            }
+        }
+    }
+
     if !all_valid {
         ui::separator();
-        ui::error("Some payment methods failed validation");
         ui::info("Fix the errors above before publishing");
-        return Ok(());
+        return Err(PaykitError::ValidationFailed(
+            "Some payment methods failed validation".to_string(),
+        )
+        .into());
     }
 
     ui::separator();
 
     // Create directory client
-    let client = DirectoryClient::new(homeserver);
+    let client = super::directory_client(homeserver);
 
     // Create Pubky session
     let spinner = ui::spinner("Connecting to homeserver...");
@@ -130,7 +190,7 @@ pub async fn run(
     // Publish methods
     let spinner = ui::spinner("Publishing payment methods...");
 
-    match client.publish_methods(&session, &methods).await {
+    match publish_checked(&client, &session, &identity.public_key(), &methods).await {
         Ok(()) => {
             spinner.finish_and_clear();
             ui::separator();
@@ -144,6 +204,17 @@ pub async fn run(
                 methods.len(),
                 identity.pubky_uri()
             );
+            output::emit(&PublishOutput {
+                public_key: identity.public_key().to_string(),
+                homeserver: homeserver.to_string(),
+                methods: methods
+                    .into_iter()
+                    .map(|method| MethodOutput {
+                        method_id: method.method_id,
+                        endpoint: method.endpoint,
+                    })
+                    .collect(),
+            });
         }
         Err(e) => {
             spinner.finish_and_clear();
@@ -154,3 +225,49 @@ pub async fn run(
 
     Ok(())
 }
+
+/// Publish `methods` on top of the current list without clobbering
+/// concurrent publishes from other devices
+///
+/// On a conflict the other device's changes are merged in and the write is
+/// retried. If both changed the same method, nothing more is written and
+/// the error names the methods; publishing again then overwrites them.
+async fn publish_checked(
+    client: &DirectoryClient,
+    session: &PubkySession,
+    public_key: &PublicKey,
+    methods: &[PaymentMethod],
+) -> Result<()> {
+    let mut base = client.fetch_snapshot(public_key).await?;
+    let mut ours = base.methods.clone();
+    for method in methods {
+        ours.retain(|m| m.method_id != method.method_id);
+        ours.push(method.clone());
+    }
+
+    for _ in 0..MAX_PUBLISH_ATTEMPTS {
+        let err = match client
+            .replace_methods_checked(session, public_key, &base.version, &ours)
+            .await
+        {
+            Ok(_) => return Ok(()),
+            Err(e) => e,
+        };
+        let Some(conflict) = err.downcast_ref::<DirectoryConflict>() else {
+            return Err(err);
+        };
+
+        let merged = conflict.merge(&base.methods, &ours);
+        if !merged.is_clean() {
+            anyhow::bail!(
+                "{} changed on another device while publishing; run publish again to overwrite",
+                merged.conflicts.join(", ")
+            );
+        }
+        tracing::info!("Merged concurrent publish ({})", conflict);
+        ours = merged.methods;
+        base = conflict.current.clone();
+    }
+
+    anyhow::bail!("Endpoint list kept changing while publishing; try again")
+}
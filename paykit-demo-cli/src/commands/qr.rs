@@ -1,6 +1,9 @@
 //! QR code display and URI parsing commands
 
 use anyhow::Result;
+use paykit_lib::audit::AuditOperation;
+use paykit_lib::uri::{PaymentRequestLink, SignedPaymentRequestLink, SIGNED_REQUEST_PREFIX};
+use paykit_lib::MethodId;
 use std::path::Path;
 
 use crate::ui;
@@ -13,7 +16,7 @@ pub async fn identity(storage_dir: &Path, _verbose: bool) -> Result<()> {
     let uri = identity.pubky_uri();
 
     ui::info(&format!("Identity: {}", uri));
-    println!();
+    ui::line("");
     ui::qr_code(&uri)?;
 
     ui::separator();
@@ -24,11 +27,9 @@ pub async fn identity(storage_dir: &Path, _verbose: bool) -> Result<()> {
 
 /// Display QR code for a contact
 pub async fn contact(storage_dir: &Path, name: &str, _verbose: bool) -> Result<()> {
-    use paykit_demo_core::DemoStorage;
-
     ui::header(&format!("Contact QR: {}", name));
 
-    let storage = DemoStorage::new(storage_dir.join("data"));
+    let storage = super::storage::open(storage_dir);
     let contacts = storage.list_contacts()?;
 
     let contact = contacts
@@ -40,39 +41,49 @@ pub async fn contact(storage_dir: &Path, name: &str, _verbose: bool) -> Result<(
 
     ui::info(&format!("Contact: {}", contact.name));
     ui::info(&format!("URI: {}", uri));
-    println!();
+    ui::line("");
     ui::qr_code(&uri)?;
 
     Ok(())
 }
 
-/// Generate a payment request QR code
+/// Generate a signed payment request QR code
 pub async fn request(
     storage_dir: &Path,
     amount: Option<String>,
     description: Option<String>,
     method: &str,
-    _verbose: bool,
+    expires_in: u64,
+    verbose: bool,
 ) -> Result<()> {
     ui::header("Payment Request QR Code");
 
     let identity = super::load_current_identity(storage_dir).await?;
 
-    // Build paykit URI with parameters
-    let mut uri = format!("paykit://{}/request", identity.public_key());
-
-    let mut params = Vec::new();
+    // Build and sign the request link
+    let request_id = format!("req_{}", uuid::Uuid::new_v4());
+    let mut link = PaymentRequestLink::new(&request_id, identity.keypair.public_key())
+        .with_methods(vec![MethodId::new(method)]);
     if let Some(amt) = &amount {
-        params.push(format!("amount={}", amt));
+        let sats: u64 = amt
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Amount must be a whole number of sats"))?;
+        link = link.with_amount_sats(sats);
     }
     if let Some(desc) = &description {
-        params.push(format!("description={}", urlencoding::encode(desc)));
+        link = link.with_description(desc);
     }
-    params.push(format!("method={}", method));
-
-    if !params.is_empty() {
-        uri = format!("{}?{}", uri, params.join("&"));
+    if expires_in > 0 {
+        let expires_at = link.created_at + expires_in as i64;
+        link = link.with_expires_at(expires_at);
     }
+    let uri = link.sign(&identity.keypair)?.to_uri();
+    super::audit::record(
+        storage_dir,
+        AuditOperation::Sign,
+        None,
+        Some("payment link"),
+    );
 
     ui::info("Payment Request:");
     if let Some(amt) = &amount {
@@ -82,26 +93,36 @@ pub async fn request(
         ui::key_value("  Description", desc);
     }
     ui::key_value("  Method", method);
+    if expires_in > 0 {
+        ui::key_value("  Expires in", &format!("{}s", expires_in));
+    }
+    if verbose {
+        ui::key_value("  Request ID", &request_id);
+    }
     ui::key_value("  URI", &uri);
 
-    println!();
+    ui::line("");
     ui::qr_code(&uri)?;
 
     ui::separator();
-    ui::info("Scan this QR code to pay");
+    ui::info("Scan this QR code or share the link to pay");
 
     Ok(())
 }
 
 /// Parse a scanned QR code or URI
-pub async fn parse(data: &str, verbose: bool) -> Result<()> {
+///
+/// With `pay`, a valid signed payment request goes straight into the pay flow.
+pub async fn parse(storage_dir: &Path, data: &str, pay: bool, verbose: bool) -> Result<()> {
     ui::header("Parse URI");
 
     ui::key_value("Input", data);
     ui::separator();
 
     // Try to parse as different URI types
-    if data.starts_with("pubky://") {
+    if data.starts_with(SIGNED_REQUEST_PREFIX) {
+        parse_signed_request(storage_dir, data, pay, verbose).await?;
+    } else if data.starts_with("pubky://") {
         parse_pubky_uri(data, verbose)?;
     } else if data.starts_with("paykit://") {
         parse_paykit_uri(data, verbose)?;
@@ -123,6 +144,7 @@ pub async fn parse(data: &str, verbose: bool) -> Result<()> {
         ui::info("");
         ui::info("Supported formats:");
         ui::info("  - pubky://... (Pubky identity)");
+        ui::info("  - paykit://request/... (Signed payment request)");
         ui::info("  - paykit://... (Paykit payment request)");
         ui::info("  - lnurl... (Lightning URL)");
         ui::info("  - lnbc.../lntb... (BOLT11 invoice)");
@@ -155,6 +177,75 @@ fn parse_pubky_uri(uri: &str, _verbose: bool) -> Result<()> {
     Ok(())
 }
 
+async fn parse_signed_request(
+    storage_dir: &Path,
+    uri: &str,
+    pay: bool,
+    verbose: bool,
+) -> Result<()> {
+    let link = SignedPaymentRequestLink::decode(uri)?;
+    let request = link.request();
+
+    ui::key_value("Type", "Signed Payment Request");
+    ui::key_value("Request ID", &request.request_id);
+    ui::key_value("From", &format!("pubky://{}", request.from));
+    if let Some(sats) = request.amount_sats {
+        ui::key_value("Amount", &format!("{} sats", sats));
+    }
+    if !request.methods.is_empty() {
+        let methods: Vec<&str> = request.methods.iter().map(|m| m.as_str()).collect();
+        ui::key_value("Methods", &methods.join(", "));
+    }
+    if let Some(desc) = &request.description {
+        ui::key_value("Description", desc);
+    }
+    if let Some(expires_at) = request.expires_at {
+        let expiry = chrono::DateTime::from_timestamp(expires_at, 0)
+            .map(|d| d.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+            .unwrap_or_else(|| expires_at.to_string());
+        ui::key_value("Expires", &expiry);
+    }
+    if verbose {
+        ui::key_value("Created", &request.created_at.to_string());
+    }
+
+    ui::separator();
+    if let Err(e) = link.verify() {
+        ui::error(&format!("Request rejected: {}", e));
+        return Ok(());
+    }
+    ui::success("Signature verified");
+
+    let recipient = format!("pubky://{}", request.from);
+    let method = request
+        .methods
+        .first()
+        .map(|m| m.as_str())
+        .unwrap_or("auto");
+    let amount = request.amount_sats.map(|sats| sats.to_string());
+
+    if pay {
+        return super::pay::run(
+            storage_dir,
+            &recipient,
+            amount,
+            Some("SAT".to_string()),
+            method,
+            "balanced",
+            None,
+            false,
+            false,
+            verbose,
+        )
+        .await;
+    }
+
+    ui::info("To pay this request:");
+    ui::info(&format!("  paykit-demo qr parse --pay \"{}\"", uri));
+
+    Ok(())
+}
+
 fn parse_paykit_uri(uri: &str, verbose: bool) -> Result<()> {
     ui::success("Paykit Protocol URI");
 
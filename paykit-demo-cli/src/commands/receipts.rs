@@ -2,31 +2,65 @@
 
 use anyhow::{Context, Result};
 use colored::Colorize;
-use paykit_demo_core::DemoStorage;
+use paykit_demo_core::render::{self, ReceiptBranding, ReceiptRenderer};
 #[cfg(feature = "http-executor")]
 use paykit_interactive::proof::verifiers::RealBitcoinProofVerifier;
 use paykit_interactive::proof::verifiers::RealLightningProofVerifier;
 use paykit_interactive::proof::{PaymentProof, ProofType, ProofVerifier};
+use paykit_interactive::{
+    AccountMapping, AccountingDialect, AccountingExporter, DateRange, FixedRates, ReceiptQuery,
+};
 #[cfg(feature = "http-executor")]
 use paykit_lib::executors::EsploraConfig;
+use serde::Serialize;
 use std::path::Path;
 
-use crate::ui;
+use crate::{output, ui};
 
-pub async fn run(storage_dir: &Path, verbose: bool) -> Result<()> {
+/// `data` of `receipts --output json`
+#[derive(Serialize)]
+struct ReceiptsOutput<'a> {
+    receipts: &'a [paykit_demo_core::Receipt],
+    next_cursor: Option<&'a str>,
+}
+
+/// `data` of `receipts verify-proof --output json`
+#[derive(Serialize)]
+struct VerifyProofOutput<'a> {
+    receipt_id: &'a str,
+    valid: bool,
+    errors: &'a [String],
+}
+
+pub async fn run(
+    storage_dir: &Path,
+    filters: &[String],
+    limit: Option<usize>,
+    cursor: Option<String>,
+    verbose: bool,
+) -> Result<()> {
     ui::header("Payment Receipts");
 
-    let storage = DemoStorage::new(storage_dir.join("data"));
-    let receipts = storage.list_receipts()?;
+    let storage = super::storage::open(storage_dir);
+    let query = build_query(filters, limit, cursor)?;
+    let page = storage.query_receipts(&query)?;
 
-    if receipts.is_empty() {
-        ui::info("No receipts found");
-        ui::info("Receipts will appear here after completing payments");
+    if page.items.is_empty() {
+        if filters.is_empty() && query.cursor.is_none() {
+            ui::info("No receipts found");
+            ui::info("Receipts will appear here after completing payments");
+        } else {
+            ui::info("No receipts match the given filters");
+        }
+        output::emit(&ReceiptsOutput {
+            receipts: &[],
+            next_cursor: None,
+        });
         return Ok(());
     }
 
-    for receipt in receipts {
-        println!("\n{}", format!("Receipt: {}", receipt.id).bold());
+    for receipt in &page.items {
+        ui::line(&format!("\n{}", format!("Receipt: {}", receipt.id).bold()));
         ui::key_value("  Method", &receipt.method);
 
         if let Some(amount) = &receipt.amount {
@@ -37,6 +71,10 @@ pub async fn run(storage_dir: &Path, verbose: bool) -> Result<()> {
             }
         }
 
+        if let Some(memo) = receipt.memo() {
+            ui::key_value("  Note", &memo);
+        }
+
         ui::key_value(
             "  Timestamp",
             &chrono::DateTime::from_timestamp(receipt.timestamp, 0)
@@ -51,7 +89,7 @@ pub async fn run(storage_dir: &Path, verbose: bool) -> Result<()> {
             } else {
                 "⚠".yellow()
             };
-            println!(
+            ui::line(&format!(
                 "  Proof: {} {}",
                 status_icon,
                 if receipt.proof_verified {
@@ -59,10 +97,10 @@ pub async fn run(storage_dir: &Path, verbose: bool) -> Result<()> {
                 } else {
                     "Unverified"
                 }
-            );
+            ));
 
             if verbose {
-                println!("  Proof details:");
+                ui::line("  Proof details:");
                 ui::json(proof);
                 if let Some(verified_at) = receipt.proof_verified_at {
                     ui::key_value(
@@ -74,7 +112,7 @@ pub async fn run(storage_dir: &Path, verbose: bool) -> Result<()> {
                 }
             }
         } else {
-            println!("  Proof: {}", "✗".red().to_string() + " None");
+            ui::line(&format!("  Proof: {}", "✗".red().to_string() + " None"));
         }
 
         if verbose {
@@ -82,19 +120,92 @@ pub async fn run(storage_dir: &Path, verbose: bool) -> Result<()> {
             ui::key_value("  Payee", &receipt.payee.to_string());
 
             if !receipt.metadata.is_null() {
-                println!("  Metadata:");
+                ui::line("  Metadata:");
                 ui::json(&receipt.metadata);
             }
         }
     }
 
+    if let Some(next) = &page.next_cursor {
+        ui::line("");
+        ui::info(&format!(
+            "More receipts available, continue with: --cursor {}",
+            next
+        ));
+    }
+
+    output::emit(&ReceiptsOutput {
+        receipts: &page.items,
+        next_cursor: page.next_cursor.as_deref(),
+    });
     Ok(())
 }
 
+/// Build a receipt query from `key=value` filter arguments.
+fn build_query(
+    filters: &[String],
+    limit: Option<usize>,
+    cursor: Option<String>,
+) -> Result<ReceiptQuery> {
+    let mut query = ReceiptQuery::new();
+    let mut range = DateRange::default();
+
+    for filter in filters {
+        let (key, value) = filter
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("Invalid filter '{}', expected key=value", filter))?;
+        match key.trim() {
+            "payer" => query.payer = Some(value.to_string()),
+            "payee" => query.payee = Some(value.to_string()),
+            "method" => query.method = Some(value.to_string()),
+            "min_amount" => {
+                query.min_amount = Some(
+                    value
+                        .parse()
+                        .with_context(|| format!("Invalid min_amount: {}", value))?,
+                )
+            }
+            "from" => range.from = Some(parse_date(value, false)?),
+            "to" => range.to = Some(parse_date(value, true)?),
+            "text" => query.text = Some(value.to_string()),
+            other => anyhow::bail!(
+                "Unknown filter '{}' (expected payer, payee, method, min_amount, from, to, text)",
+                other
+            ),
+        }
+    }
+
+    if range.from.is_some() || range.to.is_some() {
+        query.date_range = Some(range);
+    }
+    if let Some(limit) = limit {
+        query.limit = limit;
+    }
+    query.cursor = cursor;
+    Ok(query)
+}
+
+/// Parse a `YYYY-MM-DD` date or unix timestamp. Dates used as an upper
+/// bound cover the whole day.
+fn parse_date(value: &str, end_of_day: bool) -> Result<i64> {
+    if let Ok(ts) = value.parse::<i64>() {
+        return Ok(ts);
+    }
+    let date = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .with_context(|| format!("Invalid date '{}', expected YYYY-MM-DD", value))?;
+    let time = if end_of_day {
+        date.and_hms_opt(23, 59, 59)
+    } else {
+        date.and_hms_opt(0, 0, 0)
+    };
+    time.map(|t| t.and_utc().timestamp())
+        .ok_or_else(|| anyhow::anyhow!("Invalid date '{}'", value))
+}
+
 pub async fn show(storage_dir: &Path, receipt_id: &str, verbose: bool) -> Result<()> {
     ui::header(&format!("Receipt: {}", receipt_id));
 
-    let storage = DemoStorage::new(storage_dir.join("data"));
+    let storage = super::storage::open(storage_dir);
     let receipt = storage
         .get_receipt(receipt_id)
         .context("Failed to load receipt")?
@@ -111,6 +222,10 @@ pub async fn show(storage_dir: &Path, receipt_id: &str, verbose: bool) -> Result
         }
     }
 
+    if let Some(memo) = receipt.memo() {
+        ui::key_value("Note", &memo);
+    }
+
     ui::key_value(
         "Timestamp",
         &chrono::DateTime::from_timestamp(receipt.timestamp, 0)
@@ -124,7 +239,7 @@ pub async fn show(storage_dir: &Path, receipt_id: &str, verbose: bool) -> Result
     // Show proof details
     ui::separator();
     if let Some(proof_json) = &receipt.proof {
-        println!("{}", "Proof:".bold());
+        ui::line(&"Proof:".bold().to_string());
         ui::json(proof_json);
 
         let status_icon = if receipt.proof_verified {
@@ -132,7 +247,7 @@ pub async fn show(storage_dir: &Path, receipt_id: &str, verbose: bool) -> Result
         } else {
             "⚠".yellow()
         };
-        println!(
+        ui::line(&format!(
             "Status: {} {}",
             status_icon,
             if receipt.proof_verified {
@@ -140,7 +255,7 @@ pub async fn show(storage_dir: &Path, receipt_id: &str, verbose: bool) -> Result
             } else {
                 "Unverified"
             }
-        );
+        ));
 
         if let Some(verified_at) = receipt.proof_verified_at {
             ui::key_value(
@@ -151,22 +266,134 @@ pub async fn show(storage_dir: &Path, receipt_id: &str, verbose: bool) -> Result
             );
         }
     } else {
-        println!("{}", "Proof: ".red().to_string() + "None");
+        ui::line(&("Proof: ".red().to_string() + "None"));
     }
 
     if verbose && !receipt.metadata.is_null() {
         ui::separator();
-        println!("{}", "Metadata:".bold());
+        ui::line(&"Metadata:".bold().to_string());
         ui::json(&receipt.metadata);
     }
 
+    output::emit(&receipt);
     Ok(())
 }
 
+/// Render a receipt to an HTML or PDF file.
+pub async fn render(
+    storage_dir: &Path,
+    receipt_id: &str,
+    format: &str,
+    output: Option<&str>,
+    branding: Option<&str>,
+) -> Result<()> {
+    let format = format.to_lowercase();
+    if format != "html" && format != "pdf" {
+        anyhow::bail!("Unknown format '{}' (expected html or pdf)", format);
+    }
+    if format == "pdf" && !render::pdf_supported() {
+        anyhow::bail!("This build has no PDF support; rebuild with --features pdf");
+    }
+
+    let storage = super::storage::open(storage_dir);
+    let receipt = storage
+        .get_receipt(receipt_id)
+        .context("Failed to load receipt")?
+        .ok_or_else(|| anyhow::anyhow!("Receipt not found: {}", receipt_id))?;
+
+    let mut renderer = ReceiptRenderer::new();
+    if let Some(path) = branding {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read branding file {}", path))?;
+        let branding: ReceiptBranding =
+            serde_json::from_str(&json).context("Invalid branding file")?;
+        renderer = renderer.with_branding(branding);
+    }
+
+    let bytes = if format == "pdf" {
+        renderer.render_pdf(&receipt)?
+    } else {
+        renderer.render_html(&receipt).into_bytes()
+    };
+
+    let output = output
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("receipt-{}.{}", receipt.id, format));
+    std::fs::write(&output, bytes).with_context(|| format!("Failed to write {}", output))?;
+
+    ui::success(&format!("Receipt rendered to {}", output));
+    Ok(())
+}
+
+/// Export receipts in an accounting tool's import format.
+pub async fn export(
+    storage_dir: &Path,
+    format: &str,
+    output: Option<&str>,
+    accounts: Option<&str>,
+    currency: Option<&str>,
+    rates: &[String],
+    filters: &[String],
+) -> Result<()> {
+    let dialect: AccountingDialect = format.parse()?;
+    let identity = super::load_current_identity(storage_dir).await?;
+
+    let storage = super::storage::open(storage_dir);
+    let query = build_query(filters, Some(usize::MAX), None)?;
+    let receipts = storage.query_receipts(&query)?.items;
+
+    let mut exporter = AccountingExporter::new(dialect, identity.public_key().to_string());
+    if let Some(path) = accounts {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read accounts file {}", path))?;
+        let accounts: AccountMapping =
+            serde_json::from_str(&json).context("Invalid accounts file")?;
+        exporter = exporter.with_accounts(accounts);
+    }
+    let rates = parse_rates(rates)?;
+    if let Some(currency) = currency {
+        exporter = exporter.with_currency(currency, &rates);
+    }
+    let export = exporter.export(&receipts);
+
+    let output = output
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("receipts-{}.csv", dialect));
+    std::fs::write(&output, &export.content)
+        .with_context(|| format!("Failed to write {}", output))?;
+
+    for skipped in &export.skipped {
+        ui::warning(&format!(
+            "Skipped {}: {}",
+            skipped.receipt_id, skipped.reason
+        ));
+    }
+    output::emit(&export);
+    ui::success(&format!(
+        "Exported {} receipts for {} to {}",
+        export.exported, dialect, output
+    ));
+    Ok(())
+}
+
+/// Parse `FROM/TO=RATE` exchange rate arguments.
+fn parse_rates(rates: &[String]) -> Result<FixedRates> {
+    rates.iter().try_fold(FixedRates::new(), |table, arg| {
+        let parsed = arg.split_once('=').and_then(|(pair, rate)| {
+            let (from, to) = pair.split_once('/')?;
+            Some((from.trim(), to.trim(), rate.trim().parse::<f64>().ok()?))
+        });
+        match parsed {
+            Some((from, to, rate)) if rate > 0.0 => Ok(table.with_rate(from, to, rate)),
+            _ => anyhow::bail!("Invalid rate '{}', expected FROM/TO=RATE", arg),
+        }
+    })
+}
+
 pub async fn verify_proof(storage_dir: &Path, receipt_id: &str, verbose: bool) -> Result<()> {
     ui::header(&format!("Verify Proof: {}", receipt_id));
 
-    let storage = DemoStorage::new(storage_dir.join("data"));
+    let storage = super::storage::open(storage_dir);
     let mut receipt = storage
         .get_receipt(receipt_id)
         .context("Failed to load receipt")?
@@ -198,7 +425,8 @@ pub async fn verify_proof(storage_dir: &Path, receipt_id: &str, verbose: bool) -
                     }
                 } else {
                     EsploraConfig::blockstream_mainnet()
-                };
+                }
+                .with_proxy(super::proxy_for(paykit_lib::proxy::TransportKind::Esplora));
 
                 let verifier =
                     RealBitcoinProofVerifier::with_config(esplora_config).with_min_confirmations(1);
@@ -223,6 +451,12 @@ pub async fn verify_proof(storage_dir: &Path, receipt_id: &str, verbose: bool) -
 
     spinner.finish_and_clear();
 
+    output::emit(&VerifyProofOutput {
+        receipt_id,
+        valid: verification_result.valid,
+        errors: &verification_result.errors,
+    });
+
     if verification_result.valid {
         ui::success("Proof verification succeeded!");
 
@@ -233,7 +467,7 @@ pub async fn verify_proof(storage_dir: &Path, receipt_id: &str, verbose: bool) -
 
         if let Some(details) = verification_result.details {
             if verbose {
-                println!("\nVerification details:");
+                ui::line("\nVerification details:");
                 ui::json(&details);
             }
         }
@@ -1,16 +1,36 @@
 //! Receive command - start payment receiver with Noise protocol
+//!
+//! With a BTCPay store configured, payment requests are billed as BTCPay
+//! invoices and `--webhook-port` accepts the store's webhook deliveries.
+//! Requests that pay one of our payment links are refused once the link has
+//! expired or been used up. With `analytics enable`, confirmed receipts are
+//! also reduced to anonymous aggregates.
+//!
+//! With `--tenant`, one process serves several identities: a handshake is
+//! routed to the tenant whose Noise key accepts it, and each tenant keeps
+//! its own storage under `tenants/<name>/`, its own per-peer limits and its
+//! own metrics (served as JSON on `--metrics-port`).
 
 use anyhow::{Context, Result};
-use paykit_demo_core::DemoStorage;
+use paykit_demo_core::{
+    AnalyticsCoordinator, DemoStorage, PaymentLinkCoordinator, TimestampCoordinator,
+};
+use paykit_interactive::peer_limit::{PeerLimitConfig, PeerLimitEvent, PeerRateLimiter};
+use paykit_interactive::transport::EpochRing;
 use paykit_interactive::{
     PaykitInteractiveManager, PaykitNoiseMessage, PaykitReceipt, PaykitStorage, ReceiptGenerator,
+    Tenant, TenantRouter,
 };
+use paykit_lib::audit::AuditOperation;
+use paykit_lib::executors::{BtcPayInvoiceRequest, BtcPayReceiver, BtcPayWebhookEvent};
+use paykit_lib::protocol::{payment_link_id, KEY_MISMATCH_HINT};
 use pubky_noise::datalink_adapter::{server_accept_ik, server_complete_ik};
-use pubky_noise::{DummyRing, NoiseServer, RingKeyProvider};
-use std::path::Path;
+use pubky_noise::{DummyRing, NoiseLink, NoiseServer, RingKeyProvider};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::TcpListener;
+use tokio::net::{TcpListener, TcpStream};
 
 use crate::ui;
 
@@ -34,9 +54,130 @@ impl ReceiptGenerator for DemoReceiptGenerator {
     }
 }
 
+/// Receipt generator that bills each request as a BTCPay invoice
+///
+/// The invoice's order ID is the receipt ID, so webhook deliveries can be
+/// matched back to the receipt (see [`handle_webhook`]).
+struct BtcPayReceiptGenerator {
+    receiver: BtcPayReceiver,
+}
+
+#[async_trait::async_trait]
+impl ReceiptGenerator for BtcPayReceiptGenerator {
+    async fn generate_receipt(
+        &self,
+        request: &PaykitReceipt,
+    ) -> paykit_interactive::Result<PaykitReceipt> {
+        let to_error = |e: paykit_lib::PaykitError| {
+            paykit_interactive::InteractiveError::Transport(format!("BTCPay: {}", e))
+        };
+
+        let mut invoice_request = match request.amount.as_deref() {
+            Some(amount) => {
+                let sats = amount.parse::<u64>().map_err(|_| {
+                    paykit_interactive::InteractiveError::Protocol(format!(
+                        "BTCPay invoices need an amount in sats, got '{}'",
+                        amount
+                    ))
+                })?;
+                BtcPayInvoiceRequest::new(sats)
+            }
+            None => BtcPayInvoiceRequest::top_up(),
+        }
+        .with_order_id(&request.receipt_id);
+        if let Some(memo) = request.memo() {
+            invoice_request = invoice_request.with_description(memo);
+        }
+
+        let invoice = self
+            .receiver
+            .create_invoice(&invoice_request)
+            .await
+            .map_err(to_error)?;
+        let destination = self
+            .receiver
+            .invoice_endpoints(&invoice.id)
+            .await
+            .map_err(to_error)?
+            .into_iter()
+            .find(|(method, _)| *method == request.method_id)
+            .map(|(_, endpoint)| endpoint.0);
+
+        let mut confirmed = request.clone();
+        confirmed.metadata = serde_json::json!({
+            "confirmed_at": chrono::Utc::now().timestamp(),
+            "original_metadata": request.metadata,
+            "btcpay": {
+                "invoice_id": invoice.id,
+                "checkout_link": invoice.checkout_link,
+                "status": invoice.status,
+                "expires_at": invoice.expiration_time,
+                "destination": destination,
+            },
+        });
+        Ok(confirmed)
+    }
+}
+
+/// Receipt generator that enforces payment links before confirming
+///
+/// Requests tagged with a payment link are refused unless the link can be
+/// paid; confirming one uses the link up. The link is checked before and
+/// consumed after the inner generator runs, so a failed invoice does not
+/// waste a use.
+struct LinkReceiptGenerator {
+    inner: Box<dyn ReceiptGenerator>,
+    links: PaymentLinkCoordinator,
+}
+
+#[async_trait::async_trait]
+impl ReceiptGenerator for LinkReceiptGenerator {
+    async fn generate_receipt(
+        &self,
+        request: &PaykitReceipt,
+    ) -> paykit_interactive::Result<PaykitReceipt> {
+        let Some(link_id) = payment_link_id(&request.metadata) else {
+            return self.inner.generate_receipt(request).await;
+        };
+        let to_error = |e: anyhow::Error| {
+            paykit_interactive::InteractiveError::Protocol(format!("Payment link: {}", e))
+        };
+        let amount_sats = match request.amount.as_deref() {
+            Some(amount) => Some(amount.parse::<u64>().map_err(|_| {
+                paykit_interactive::InteractiveError::Protocol(format!(
+                    "Payment links are paid in sats, got '{}'",
+                    amount
+                ))
+            })?),
+            None => None,
+        };
+
+        let now = chrono::Utc::now().timestamp();
+        self.links
+            .check(link_id, amount_sats, now)
+            .map_err(to_error)?;
+        let confirmed = self.inner.generate_receipt(request).await?;
+        let state = self
+            .links
+            .consume(link_id, &request.receipt_id, amount_sats, now)
+            .map_err(to_error)?;
+        ui::info(&format!(
+            "Payment link {} paid ({})",
+            link_id,
+            state.as_str()
+        ));
+        Ok(confirmed)
+    }
+}
+
 /// Simple storage adapter for the demo
+///
+/// Confirmed receipts are also counted by payee analytics when enabled; with
+/// receipt retention switched off they are counted and not stored.
 struct DemoStorageAdapter {
     storage: DemoStorage,
+    analytics: AnalyticsCoordinator,
+    timestamps: TimestampCoordinator,
     endpoint_manager: Option<
         paykit_lib::private_endpoints::PrivateEndpointManager<
             paykit_lib::private_endpoints::FileStore,
@@ -47,11 +188,35 @@ struct DemoStorageAdapter {
 #[async_trait::async_trait]
 impl PaykitStorage for DemoStorageAdapter {
     async fn save_receipt(&self, receipt: &PaykitReceipt) -> paykit_interactive::Result<()> {
+        let to_error = |e: anyhow::Error| {
+            paykit_interactive::InteractiveError::Transport(format!("Analytics: {}", e))
+        };
+        self.analytics
+            .record(receipt, chrono::Utc::now().timestamp())
+            .map_err(to_error)?;
+        if !self.analytics.retain_receipts().map_err(to_error)? {
+            return Ok(());
+        }
+
         let json = serde_json::to_string(receipt)
             .map_err(|e| paykit_interactive::InteractiveError::Serialization(e.to_string()))?;
         self.storage
             .save_receipt_json(&receipt.receipt_id, &json)
-            .map_err(|e| paykit_interactive::InteractiveError::Transport(e.to_string()))
+            .map_err(|e| paykit_interactive::InteractiveError::Transport(e.to_string()))?;
+
+        // A timestamping problem must not lose the payer's receipt
+        let queued = self.timestamps.record(
+            &receipt.receipt_id,
+            &receipt.digest(),
+            chrono::Utc::now().timestamp(),
+        );
+        if let Err(e) = queued {
+            ui::warning(&format!(
+                "Could not queue receipt for timestamping: {:#}",
+                e
+            ));
+        }
+        Ok(())
     }
 
     async fn get_receipt(
@@ -144,8 +309,45 @@ impl PaykitStorage for DemoStorageAdapter {
     }
 }
 
+/// The BTCPay store configured for the active wallet profile, if any.
+fn btcpay_receiver(storage_dir: &Path) -> Result<Option<BtcPayReceiver>> {
+    let Some(config) = super::wallet::WalletConfig::load(storage_dir)?.and_then(|w| w.btcpay)
+    else {
+        return Ok(None);
+    };
+    let receiver =
+        BtcPayReceiver::new(config.to_receiver_config()).context("Invalid BTCPay configuration")?;
+    Ok(Some(receiver))
+}
+
 #[tracing::instrument(skip(storage_dir))]
-pub async fn run(storage_dir: &Path, port: u16, verbose: bool) -> Result<()> {
+pub async fn run(
+    storage_dir: &Path,
+    port: u16,
+    noise_epochs: &[u32],
+    webhook_port: Option<u16>,
+    max_messages_per_minute: u32,
+    hosting: Hosting,
+    verbose: bool,
+) -> Result<()> {
+    if !hosting.tenants.is_empty() {
+        if webhook_port.is_some() {
+            anyhow::bail!("--webhook-port is not supported with --tenant");
+        }
+        return run_hosted(
+            storage_dir,
+            port,
+            noise_epochs,
+            max_messages_per_minute,
+            hosting,
+            verbose,
+        )
+        .await;
+    }
+    if hosting.metrics_port.is_some() {
+        anyhow::bail!("--metrics-port needs at least one --tenant");
+    }
+
     ui::header("Payment Receiver");
 
     tracing::info!("Starting payment receiver on port {}", port);
@@ -157,25 +359,512 @@ pub async fn run(storage_dir: &Path, port: u16, verbose: bool) -> Result<()> {
     ui::info(&format!("Identity: {}", identity.pubky_uri()));
     ui::info(&format!("Listening on port: {}", port));
 
-    // Setup Noise server using identity's secret key as seed
-    let seed = identity.keypair.secret_key();
-    let ring = Arc::new(DummyRing::new(seed, "paykit-receiver"));
-    let server = NoiseServer::<_, ()>::new_direct("paykit-receiver", b"demo-device", ring.clone());
+    let (servers, static_pks) = noise_servers(&identity, noise_epochs)?;
 
-    // Get server's static public key for clients to connect
-    let server_sk = ring
-        .derive_device_x25519("paykit-receiver", b"demo-device", 0)
-        .context("Failed to derive server key")?;
-    let server_static_pk = pubky_noise::kdf::x25519_pk_from_sk(&server_sk);
+    for (epoch, static_pk) in noise_epochs.iter().zip(&static_pks) {
+        ui::info(&format!(
+            "Noise public key (epoch {}): {}",
+            epoch,
+            hex::encode(&static_pk[..16])
+        ));
+    }
+    ui::separator();
 
+    let limits = receive_limits(PeerLimitConfig {
+        max_messages_per_peer: max_messages_per_minute,
+        ..Default::default()
+    });
+    let manager = build_manager(storage_dir)?.with_peer_limits(limits.clone());
     ui::info(&format!(
-        "Noise public key: {}",
-        hex::encode(&server_static_pk[..16])
+        "Rate limit: {} messages per peer per minute",
+        max_messages_per_minute
     ));
+
+    let webhooks = match webhook_port {
+        Some(webhook_port) => {
+            let receiver = btcpay_receiver(storage_dir)?.ok_or_else(|| {
+                anyhow::anyhow!(
+                    "--webhook-port needs a BTCPay store; run `wallet configure-btcpay`"
+                )
+            })?;
+            if receiver.config().webhook_secret.is_none() {
+                anyhow::bail!("BTCPay webhooks need --webhook-secret in `wallet configure-btcpay`");
+            }
+            let listener = TcpListener::bind(format!("0.0.0.0:{}", webhook_port))
+                .await
+                .context("Failed to bind webhook port")?;
+            ui::info(&format!(
+                "BTCPay store {} (webhooks on port {})",
+                receiver.config().store_id,
+                webhook_port
+            ));
+            Some((listener, receiver))
+        }
+        None => None,
+    };
+
+    // Bind TCP listener
+    let listener = TcpListener::bind(format!("0.0.0.0:{}", port))
+        .await
+        .context("Failed to bind to port")?;
+
+    ui::success(&format!("Receiver listening on 0.0.0.0:{}", port));
+    ui::info("Press Ctrl+C to stop");
     ui::separator();
 
+    // Handle connections
+    loop {
+        tokio::select! {
+            result = listener.accept() => {
+                match result {
+                    Ok((socket, addr)) => {
+                        ui::info(&format!("Connection from: {}", addr));
+                        handle_connection(
+                            storage_dir, socket, &servers, &manager, &limits, &my_pubkey, verbose,
+                        )
+                        .await;
+                    }
+                    Err(e) => {
+                        ui::error(&format!("Accept error: {}", e));
+                    }
+                }
+            }
+            Ok((socket, _)) = accept_optional(webhooks.as_ref().map(|(l, _)| l)) => {
+                if let Some((_, receiver)) = &webhooks {
+                    handle_webhook(storage_dir, receiver, socket).await;
+                }
+            }
+            _ = tokio::signal::ctrl_c() => {
+                ui::info("\nReceiver stopped");
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Multi-tenant options of `receive`.
+#[derive(Debug, Default)]
+pub struct Hosting {
+    /// `name` or `name:messages-per-minute` for each identity to serve
+    pub tenants: Vec<String>,
+    /// Port serving per-tenant metrics as JSON
+    pub metrics_port: Option<u16>,
+}
+
+/// The tenants served by one multi-tenant receiver.
+struct Hosted {
+    router: TenantRouter,
+    /// Every tenant's Noise servers, with the identity each one serves
+    servers: Vec<(NoiseServer<EpochRing<DummyRing>, ()>, paykit_lib::PublicKey)>,
+    /// Storage namespace of each tenant, by identity
+    dirs: HashMap<String, PathBuf>,
+    /// Handshakes are capped across all tenants: the tenant is only known
+    /// once the handshake is done
+    handshakes: Arc<PeerRateLimiter>,
+}
+
+/// Storage namespace of tenant `name`.
+pub(crate) fn tenant_dir(storage_dir: &Path, name: &str) -> PathBuf {
+    storage_dir.join("tenants").join(name)
+}
+
+/// Split a `--tenant` value into the identity name and its message limit.
+fn parse_tenant(spec: &str, default_limit: u32) -> Result<(String, u32)> {
+    let (name, limit) = match spec.split_once(':') {
+        Some((name, limit)) => {
+            let limit = limit
+                .parse()
+                .with_context(|| format!("Invalid message limit in --tenant {}", spec))?;
+            (name, limit)
+        }
+        None => (spec, default_limit),
+    };
+    let valid = !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+    if !valid {
+        anyhow::bail!("Invalid tenant name {:?}", name);
+    }
+    Ok((name.to_string(), limit))
+}
+
+/// Serve every tenant in `hosting` on one port.
+async fn run_hosted(
+    storage_dir: &Path,
+    port: u16,
+    noise_epochs: &[u32],
+    max_messages_per_minute: u32,
+    hosting: Hosting,
+    verbose: bool,
+) -> Result<()> {
+    ui::header("Payment Receiver (multi-tenant)");
+
+    let mut hosted = Hosted {
+        router: TenantRouter::new(),
+        servers: Vec::new(),
+        dirs: HashMap::new(),
+        handshakes: receive_limits(PeerLimitConfig::default()),
+    };
+    for spec in &hosting.tenants {
+        let (name, limit) = parse_tenant(spec, max_messages_per_minute)?;
+        let identity = super::load_identity(storage_dir, &name)
+            .await
+            .with_context(|| format!("Failed to load identity for tenant {}", name))?;
+        let public_key = identity.public_key();
+        let dir = tenant_dir(storage_dir, &name);
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        let (servers, static_pks) = noise_servers(&identity, noise_epochs)?;
+        let tenant = Tenant::new(
+            name.clone(),
+            public_key.clone(),
+            build_manager(&dir)?,
+            PeerLimitConfig {
+                max_messages_per_peer: limit,
+                ..Default::default()
+            },
+        );
+        report_limit_events(tenant.limits(), Some(&name));
+        hosted.router.register(tenant)?;
+
+        ui::key_value(
+            &name,
+            &format!(
+                "{} (Noise {}..., {} messages per peer per minute)",
+                identity.pubky_uri(),
+                hex::encode(&static_pks[0][..8]),
+                limit
+            ),
+        );
+        hosted.servers.extend(
+            servers
+                .into_iter()
+                .map(|server| (server, public_key.clone())),
+        );
+        hosted.dirs.insert(public_key.to_string(), dir);
+    }
+    ui::separator();
+
+    let metrics = match hosting.metrics_port {
+        Some(metrics_port) => {
+            let listener = TcpListener::bind(format!("0.0.0.0:{}", metrics_port))
+                .await
+                .context("Failed to bind metrics port")?;
+            ui::info(&format!("Metrics on port {}", metrics_port));
+            Some(listener)
+        }
+        None => None,
+    };
+    let listener = TcpListener::bind(format!("0.0.0.0:{}", port))
+        .await
+        .context("Failed to bind to port")?;
+
+    ui::success(&format!(
+        "Receiver listening on 0.0.0.0:{} for {} tenant(s)",
+        port,
+        hosted.router.len()
+    ));
+    ui::info("Press Ctrl+C to stop");
+    ui::separator();
+
+    loop {
+        tokio::select! {
+            result = listener.accept() => {
+                match result {
+                    Ok((socket, addr)) => {
+                        ui::info(&format!("Connection from: {}", addr));
+                        handle_hosted_connection(&hosted, socket, verbose).await;
+                    }
+                    Err(e) => {
+                        ui::error(&format!("Accept error: {}", e));
+                    }
+                }
+            }
+            Ok((socket, _)) = accept_optional(metrics.as_ref()) => {
+                serve_metrics(&hosted.router, socket).await;
+            }
+            _ = tokio::signal::ctrl_c() => {
+                ui::info("\nReceiver stopped");
+                break;
+            }
+        }
+    }
+
+    ui::separator();
+    for (name, snapshot) in hosted.router.metrics() {
+        ui::key_value(
+            &name,
+            &format!(
+                "{} session(s), {} receipt(s), {} message(s) rate limited",
+                snapshot.total_connections,
+                snapshot.receipts_generated,
+                snapshot.messages_rate_limited
+            ),
+        );
+    }
+    Ok(())
+}
+
+/// Serve one connection with the tenant whose key accepts its handshake.
+async fn handle_hosted_connection(hosted: &Hosted, mut socket: TcpStream, verbose: bool) {
+    let servers: Vec<_> = hosted.servers.iter().map(|(server, _)| server).collect();
+    let Some(session) = accept_session(&mut socket, &servers, &hosted.handshakes, verbose).await
+    else {
+        return;
+    };
+    let owner = &hosted.servers[session.server].1;
+    let (Some(tenant), Some(dir)) = (
+        hosted.router.get(owner),
+        hosted.dirs.get(&owner.to_string()),
+    ) else {
+        return;
+    };
+
+    let metrics = tenant.metrics();
+    metrics.record_handshake_success();
+    metrics.record_connection_opened();
+    ui::info(&format!("Session for tenant {}", tenant.id()));
+    serve_session(dir, socket, session, tenant, tenant.limits(), verbose).await;
+    metrics.record_connection_closed();
+}
+
+/// Answer a metrics request: `/metrics` for every tenant, `/metrics/<name>`
+/// for one.
+async fn serve_metrics(router: &TenantRouter, mut socket: TcpStream) {
+    let (status, body) = match super::mirror::read_request_line(&mut socket).await {
+        Ok((method, target)) if method == "GET" => metrics_response(router, &target),
+        Ok(_) => ("405 Method Not Allowed", String::new()),
+        Err(_) => ("400 Bad Request", String::new()),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = socket.write_all(response.as_bytes()).await;
+}
+
+fn metrics_response(router: &TenantRouter, target: &str) -> (&'static str, String) {
+    let metrics: BTreeMap<_, _> = router.metrics().into_iter().collect();
+    let path = target.split('?').next().unwrap_or_default();
+    let body = match path.trim_end_matches('/').strip_prefix("/metrics") {
+        Some("") => serde_json::to_string(&metrics).ok(),
+        Some(rest) => metrics
+            .get(rest.trim_start_matches('/'))
+            .and_then(|snapshot| serde_json::to_string(snapshot).ok()),
+        None => None,
+    };
+    match body {
+        Some(body) => ("200 OK", body),
+        None => ("404 Not Found", String::new()),
+    }
+}
+
+/// Accept the next connection on `listener`, or never resolve without one.
+async fn accept_optional(
+    listener: Option<&TcpListener>,
+) -> std::io::Result<(TcpStream, std::net::SocketAddr)> {
+    match listener {
+        Some(listener) => listener.accept().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Largest webhook body accepted; BTCPay events are a few KB.
+const MAX_WEBHOOK_BYTES: usize = 64 * 1024;
+
+/// Answer one BTCPay webhook delivery.
+///
+/// The delivery must carry a valid `BTCPay-Sig`; its invoice status (and, once
+/// settled, its proof) is written to the receipt named by the invoice's order
+/// ID.
+async fn handle_webhook(storage_dir: &Path, receiver: &BtcPayReceiver, mut socket: TcpStream) {
+    let status = match read_webhook(&mut socket).await {
+        Ok((signature, body)) => match receiver.verify_webhook(&body, &signature) {
+            Ok(event) => {
+                apply_webhook(storage_dir, &event);
+                "200 OK"
+            }
+            Err(e) => {
+                ui::warning(&format!("Rejected BTCPay webhook: {}", e));
+                "401 Unauthorized"
+            }
+        },
+        Err(e) => {
+            ui::warning(&format!("Malformed webhook request: {}", e));
+            "400 Bad Request"
+        }
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        status
+    );
+    let _ = socket.write_all(response.as_bytes()).await;
+}
+
+/// Read an HTTP request, returning its `BTCPay-Sig` header and body.
+async fn read_webhook(socket: &mut TcpStream) -> Result<(String, Vec<u8>)> {
+    let mut buf = Vec::new();
+    let header_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if buf.len() > MAX_WEBHOOK_BYTES {
+            anyhow::bail!("Headers too large");
+        }
+        let mut chunk = [0u8; 4096];
+        let n = socket.read(&mut chunk).await?;
+        if n == 0 {
+            anyhow::bail!("Connection closed");
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let header = |name: &str| {
+        head.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim()
+                .eq_ignore_ascii_case(name)
+                .then(|| value.trim().to_string())
+        })
+    };
+    let length: usize = header("content-length")
+        .and_then(|v| v.parse().ok())
+        .context("Missing Content-Length")?;
+    if length > MAX_WEBHOOK_BYTES {
+        anyhow::bail!("Body too large");
+    }
+    let signature = header("btcpay-sig").context("Missing BTCPay-Sig header")?;
+
+    let mut body = buf.split_off(header_end);
+    if body.len() < length {
+        let start = body.len();
+        body.resize(length, 0);
+        socket.read_exact(&mut body[start..]).await?;
+    }
+    body.truncate(length);
+    Ok((signature, body))
+}
+
+/// Record a webhook's status update on the receipt it belongs to.
+fn apply_webhook(storage_dir: &Path, event: &BtcPayWebhookEvent) {
+    let (Some(receipt_id), Some(status)) = (event.order_id(), event.status()) else {
+        return;
+    };
+    let storage = super::storage::open(storage_dir);
+    let mut receipt: PaykitReceipt = match storage.get_receipt_json(receipt_id) {
+        Ok(Some(json)) => match serde_json::from_str(&json) {
+            Ok(receipt) => receipt,
+            Err(e) => {
+                ui::warning(&format!("Unreadable receipt {}: {}", receipt_id, e));
+                return;
+            }
+        },
+        _ => {
+            ui::warning(&format!(
+                "BTCPay invoice for unknown receipt {}",
+                receipt_id
+            ));
+            return;
+        }
+    };
+
+    if !receipt.metadata["btcpay"].is_object() {
+        receipt.metadata["btcpay"] = serde_json::json!({});
+    }
+    receipt.metadata["btcpay"]["status"] = serde_json::json!(status);
+    receipt.metadata["btcpay"]["updated_at"] = event.timestamp.into();
+    if let Some(proof) = event.proof() {
+        receipt.metadata["proof"] = serde_json::json!(proof);
+    }
+
+    let saved = serde_json::to_string(&receipt)
+        .map_err(anyhow::Error::from)
+        .and_then(|json| storage.save_receipt_json(&receipt.receipt_id, &json));
+    match saved {
+        Ok(()) => ui::success(&format!(
+            "Receipt {}: BTCPay invoice {:?}",
+            receipt_id, status
+        )),
+        Err(e) => ui::error(&format!("Failed to update receipt {}: {}", receipt_id, e)),
+    }
+}
+
+/// Set up the receiver's Noise servers, keyed from the identity's secret key.
+///
+/// One server is built per key epoch so handshakes to the previous key keep
+/// working during a rotation overlap. Returns the servers and their static
+/// public keys, in the order of `epochs`.
+pub(crate) fn noise_servers(
+    identity: &paykit_demo_core::Identity,
+    epochs: &[u32],
+) -> Result<(Vec<NoiseServer<EpochRing<DummyRing>, ()>>, Vec<[u8; 32]>)> {
+    if epochs.is_empty() {
+        anyhow::bail!("At least one Noise key epoch is required");
+    }
+    let seed = identity.keypair.secret_key();
+    let ring = Arc::new(DummyRing::new(seed, "paykit-receiver"));
+
+    let mut servers = Vec::with_capacity(epochs.len());
+    let mut static_pks = Vec::with_capacity(epochs.len());
+    for &epoch in epochs {
+        let epoch_ring = Arc::new(EpochRing::new(ring.clone(), epoch));
+        let server_sk = epoch_ring
+            .derive_device_x25519("paykit-receiver", b"demo-device", epoch)
+            .context("Failed to derive server key")?;
+        static_pks.push(pubky_noise::kdf::x25519_pk_from_sk(&server_sk));
+        servers.push(NoiseServer::<_, ()>::new_direct(
+            "paykit-receiver",
+            b"demo-device",
+            epoch_ring,
+        ));
+    }
+
+    Ok((servers, static_pks))
+}
+
+/// Per-peer limits for the receive server, reporting refusals on the console.
+pub(crate) fn receive_limits(config: PeerLimitConfig) -> Arc<PeerRateLimiter> {
+    let limits = PeerRateLimiter::new_shared(config);
+    report_limit_events(&limits, None);
+    limits
+}
+
+/// Report `limits`' refusals on the console, naming the tenant if any.
+fn report_limit_events(limits: &PeerRateLimiter, tenant: Option<&str>) {
+    let prefix = tenant.map(|t| format!("[{}] ", t)).unwrap_or_default();
+    limits.on_event(Arc::new(move |event| match event {
+        PeerLimitEvent::RateLimited { peer } => {
+            ui::warning(&format!("{}Rate limited {}", prefix, peer));
+        }
+        PeerLimitEvent::Greylisted { peer, duration } => {
+            ui::warning(&format!(
+                "{}Greylisted {} for {}s",
+                prefix,
+                peer,
+                duration.as_secs()
+            ));
+        }
+        PeerLimitEvent::HandshakeRejected { in_progress } => {
+            ui::warning(&format!(
+                "{}Refused handshake: {} already in progress",
+                prefix, in_progress
+            ));
+        }
+    }));
+}
+
+/// Build the interactive manager backed by demo storage and the encrypted
+/// private endpoint store. Callers attach their peer limits.
+pub(crate) fn build_manager(storage_dir: &Path) -> Result<PaykitInteractiveManager> {
     // Setup storage and manager
-    let demo_storage = DemoStorage::new(storage_dir.join("data"));
+    let demo_storage = super::storage::open(storage_dir);
     demo_storage.init()?;
 
     // Setup private endpoint storage with encryption
@@ -217,171 +906,319 @@ pub async fn run(storage_dir: &Path, port: u16, verbose: bool) -> Result<()> {
 
     let storage_adapter = Arc::new(Box::new(DemoStorageAdapter {
         storage: demo_storage,
+        analytics: AnalyticsCoordinator::new(storage_dir),
+        timestamps: TimestampCoordinator::new(storage_dir),
         endpoint_manager,
     }) as Box<dyn PaykitStorage>);
-    let generator = Arc::new(Box::new(DemoReceiptGenerator) as Box<dyn ReceiptGenerator>);
-    let manager = PaykitInteractiveManager::new(storage_adapter, generator);
+    let inner: Box<dyn ReceiptGenerator> = match btcpay_receiver(storage_dir)? {
+        Some(receiver) => Box::new(BtcPayReceiptGenerator { receiver }),
+        None => Box::new(DemoReceiptGenerator),
+    };
+    let generator: Box<dyn ReceiptGenerator> = Box::new(LinkReceiptGenerator {
+        inner,
+        links: PaymentLinkCoordinator::new(storage_dir),
+    });
+    Ok(PaykitInteractiveManager::new(
+        storage_adapter,
+        Arc::new(generator),
+    ))
+}
 
-    // Bind TCP listener
-    let listener = TcpListener::bind(format!("0.0.0.0:{}", port))
-        .await
-        .context("Failed to bind to port")?;
+/// Serve a single client connection until it disconnects.
+///
+/// The handshake takes one of `limits`' handshake slots, and the connection
+/// is dropped once the client is greylisted.
+///
+/// Returns the receipts confirmed during the session.
+pub(crate) async fn handle_connection<R: RingKeyProvider>(
+    storage_dir: &Path,
+    mut socket: TcpStream,
+    servers: &[NoiseServer<R, ()>],
+    manager: &PaykitInteractiveManager,
+    limits: &PeerRateLimiter,
+    my_pubkey: &paykit_lib::PublicKey,
+    verbose: bool,
+) -> Vec<PaykitReceipt> {
+    let servers: Vec<_> = servers.iter().collect();
+    let Some(session) = accept_session(&mut socket, &servers, limits, verbose).await else {
+        return Vec::new();
+    };
+    let handler = ManagerHandler { manager, my_pubkey };
+    serve_session(storage_dir, socket, session, &handler, limits, verbose).await
+}
 
-    ui::success(&format!("Receiver listening on 0.0.0.0:{}", port));
-    ui::info("Press Ctrl+C to stop");
-    ui::separator();
+/// Handles the messages of an established session.
+#[async_trait::async_trait]
+trait SessionHandler: Sync {
+    async fn handle(
+        &self,
+        msg: PaykitNoiseMessage,
+        peer: &paykit_lib::PublicKey,
+    ) -> paykit_interactive::Result<Option<PaykitNoiseMessage>>;
+}
 
-    // Handle connections
+/// Sessions of the single identity served by `receive` and `pos`.
+struct ManagerHandler<'a> {
+    manager: &'a PaykitInteractiveManager,
+    my_pubkey: &'a paykit_lib::PublicKey,
+}
+
+#[async_trait::async_trait]
+impl SessionHandler for ManagerHandler<'_> {
+    async fn handle(
+        &self,
+        msg: PaykitNoiseMessage,
+        peer: &paykit_lib::PublicKey,
+    ) -> paykit_interactive::Result<Option<PaykitNoiseMessage>> {
+        self.manager.handle_message(msg, peer, self.my_pubkey).await
+    }
+}
+
+#[async_trait::async_trait]
+impl SessionHandler for Tenant {
+    async fn handle(
+        &self,
+        msg: PaykitNoiseMessage,
+        peer: &paykit_lib::PublicKey,
+    ) -> paykit_interactive::Result<Option<PaykitNoiseMessage>> {
+        self.handle_message(msg, peer).await
+    }
+}
+
+/// A completed handshake.
+struct AcceptedSession {
+    link: NoiseLink,
+    /// The client's Ed25519 key, hex encoded
+    client_hex: String,
+    peer: paykit_lib::PublicKey,
+    /// Index of the server that accepted the handshake
+    server: usize,
+}
+
+/// Complete a Noise handshake against the first of `servers` that accepts it.
+async fn accept_session<R: RingKeyProvider>(
+    socket: &mut TcpStream,
+    servers: &[&NoiseServer<R, ()>],
+    limits: &PeerRateLimiter,
+    verbose: bool,
+) -> Option<AcceptedSession> {
+    let handshake_slot = limits.try_begin_handshake()?;
+
+    // Read first handshake message
+    let mut first_msg = vec![0u8; 4096];
+    let n = match socket.read(&mut first_msg).await {
+        Ok(0) => {
+            ui::warning("Connection closed before handshake");
+            return None;
+        }
+        Ok(n) => n,
+        Err(e) => {
+            ui::error(&format!("Read error: {}", e));
+            return None;
+        }
+    };
+    first_msg.truncate(n);
+
+    // Process handshake against each accepted key
+    let mut accepted = None;
+    let mut last_error = None;
+    for (index, server) in servers.iter().enumerate() {
+        match server_accept_ik(server, &first_msg) {
+            Ok(result) => {
+                accepted = Some((index, result));
+                break;
+            }
+            Err(e) => last_error = Some(e.to_string()),
+        }
+    }
+    let Some((server, (server_hs, client_identity, response))) = accepted else {
+        ui::error(&format!(
+            "Handshake failed: {}",
+            last_error.unwrap_or_default()
+        ));
+        // Tell the client its cached key may be stale so it can refresh
+        let _ = socket.write_all(KEY_MISMATCH_HINT).await;
+        return None;
+    };
+
+    if verbose {
+        ui::info(&format!(
+            "Client identity: {}...",
+            hex::encode(&client_identity.ed25519_pub[..8])
+        ));
+    }
+
+    // Send response
+    if let Err(e) = socket.write_all(&response).await {
+        ui::error(&format!("Write error: {}", e));
+        return None;
+    }
+
+    // Complete handshake
+    let link = match server_complete_ik(server_hs) {
+        Ok(link) => link,
+        Err(e) => {
+            ui::error(&format!("Handshake completion failed: {}", e));
+            return None;
+        }
+    };
+    drop(handshake_slot);
+
+    let client_hex = hex::encode(client_identity.ed25519_pub);
+    let peer: paykit_lib::PublicKey = client_hex.parse().unwrap_or_else(|_| {
+        // Fallback for parsing issues
+        paykit_lib::PublicKey::try_from(client_hex.clone()).unwrap()
+    });
+    Some(AcceptedSession {
+        link,
+        client_hex,
+        peer,
+        server,
+    })
+}
+
+/// Exchange messages over an accepted session until the client leaves.
+///
+/// Returns the receipts confirmed during the session.
+async fn serve_session(
+    storage_dir: &Path,
+    mut socket: TcpStream,
+    session: AcceptedSession,
+    handler: &dyn SessionHandler,
+    limits: &PeerRateLimiter,
+    verbose: bool,
+) -> Vec<PaykitReceipt> {
+    let mut confirmed = Vec::new();
+    let AcceptedSession {
+        mut link,
+        client_hex,
+        peer: peer_pubkey,
+        ..
+    } = session;
+
+    super::audit::record(
+        storage_dir,
+        AuditOperation::NoiseHandshake,
+        Some(&client_hex),
+        Some("receiver"),
+    );
+
+    ui::success(&format!("Session established: {}", link.session_id()));
+
+    let peer_key = peer_pubkey.to_string();
+    if limits.is_greylisted(&peer_key) {
+        ui::warning("Client is greylisted; closing connection");
+        return confirmed;
+    }
+
+    // Handle messages
     loop {
-        tokio::select! {
-            result = listener.accept() => {
-                match result {
-                    Ok((mut socket, addr)) => {
-                        ui::info(&format!("Connection from: {}", addr));
+        // Read length-prefixed message
+        let mut len_buf = [0u8; 4];
+        match socket.read_exact(&mut len_buf).await {
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                ui::info("Client disconnected");
+                break;
+            }
+            Err(e) => {
+                ui::error(&format!("Read error: {}", e));
+                break;
+            }
+        }
+        let len = u32::from_be_bytes(len_buf) as usize;
 
-                        // Read first handshake message
-                        let mut first_msg = vec![0u8; 4096];
-                        let n = match socket.read(&mut first_msg).await {
-                            Ok(0) => {
-                                ui::warning("Connection closed before handshake");
-                                continue;
-                            }
-                            Ok(n) => n,
-                            Err(e) => {
-                                ui::error(&format!("Read error: {}", e));
-                                continue;
-                            }
-                        };
-                        first_msg.truncate(n);
-
-                        // Process handshake
-                        let (server_hs, client_identity, response) = match server_accept_ik(&server, &first_msg) {
-                            Ok(result) => result,
-                            Err(e) => {
-                                ui::error(&format!("Handshake failed: {}", e));
-                                continue;
-                            }
-                        };
-
-                        if verbose {
-                            ui::info(&format!(
-                                "Client identity: {}...",
-                                hex::encode(&client_identity.ed25519_pub[..8])
-                            ));
-                        }
-
-                        // Send response
-                        if let Err(e) = socket.write_all(&response).await {
-                            ui::error(&format!("Write error: {}", e));
-                            continue;
-                        }
-
-                        // Complete handshake
-                        let mut link = match server_complete_ik(server_hs) {
-                            Ok(link) => link,
-                            Err(e) => {
-                                ui::error(&format!("Handshake completion failed: {}", e));
-                                continue;
-                            }
-                        };
-
-                        ui::success(&format!("Session established: {}", link.session_id()));
-
-                        // Handle messages
-                        loop {
-                            // Read length-prefixed message
-                            let mut len_buf = [0u8; 4];
-                            match socket.read_exact(&mut len_buf).await {
-                                Ok(_) => {}
-                                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
-                                    ui::info("Client disconnected");
-                                    break;
-                                }
-                                Err(e) => {
-                                    ui::error(&format!("Read error: {}", e));
-                                    break;
-                                }
-                            }
-                            let len = u32::from_be_bytes(len_buf) as usize;
-
-                            let mut ciphertext = vec![0u8; len];
-                            if let Err(e) = socket.read_exact(&mut ciphertext).await {
-                                ui::error(&format!("Read error: {}", e));
-                                break;
-                            }
-
-                            // Decrypt
-                            let plaintext = match link.decrypt(&ciphertext) {
-                                Ok(pt) => pt,
-                                Err(e) => {
-                                    ui::error(&format!("Decryption failed: {}", e));
-                                    break;
-                                }
-                            };
-
-                            // Parse message
-                            let msg: PaykitNoiseMessage = match serde_json::from_slice(&plaintext) {
-                                Ok(m) => m,
-                                Err(e) => {
-                                    ui::error(&format!("Parse error: {}", e));
-                                    continue;
-                                }
-                            };
-
-                            if verbose {
-                                ui::info(&format!("Received: {:?}", msg));
-                            }
-
-                            // Handle message
-                            let peer_pk = client_identity.ed25519_pub.to_vec();
-                            let peer_pk_str = hex::encode(&peer_pk);
-                            let peer_pubkey: paykit_lib::PublicKey = peer_pk_str.parse().unwrap_or_else(|_| {
-                                // Fallback for parsing issues
-                                paykit_lib::PublicKey::try_from(peer_pk_str).unwrap()
-                            });
-
-                            match manager.handle_message(msg, &peer_pubkey, &my_pubkey).await {
-                                Ok(Some(response_msg)) => {
-                                    let response_json = serde_json::to_vec(&response_msg)
-                                        .expect("Failed to serialize response");
-                                    let encrypted = link
-                                        .encrypt(&response_json)
-                                        .expect("Encryption failed");
-
-                                    let len_bytes = (encrypted.len() as u32).to_be_bytes();
-                                    if let Err(e) = socket.write_all(&len_bytes).await {
-                                        ui::error(&format!("Write error: {}", e));
-                                        break;
-                                    }
-                                    if let Err(e) = socket.write_all(&encrypted).await {
-                                        ui::error(&format!("Write error: {}", e));
-                                        break;
-                                    }
-
-                                    if matches!(response_msg, PaykitNoiseMessage::ConfirmReceipt { .. }) {
-                                        ui::success("Receipt confirmed and sent");
-                                    }
-                                }
-                                Ok(None) => {
-                                    // No response needed
-                                }
-                                Err(e) => {
-                                    ui::error(&format!("Message handling error: {}", e));
-                                }
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        ui::error(&format!("Accept error: {}", e));
-                    }
+        let mut ciphertext = vec![0u8; len];
+        if let Err(e) = socket.read_exact(&mut ciphertext).await {
+            ui::error(&format!("Read error: {}", e));
+            break;
+        }
+
+        // Decrypt
+        let plaintext = match link.decrypt(&ciphertext) {
+            Ok(pt) => pt,
+            Err(e) => {
+                ui::error(&format!("Decryption failed: {}", e));
+                break;
+            }
+        };
+
+        // Parse message
+        let msg: PaykitNoiseMessage = match serde_json::from_slice(&plaintext) {
+            Ok(m) => m,
+            Err(e) => {
+                ui::error(&format!("Parse error: {}", e));
+                continue;
+            }
+        };
+
+        if verbose {
+            ui::info(&format!("Received: {:?}", msg));
+        }
+
+        // Handle message
+        match handler.handle(msg, &peer_pubkey).await {
+            Ok(Some(response_msg)) => {
+                let response_json =
+                    serde_json::to_vec(&response_msg).expect("Failed to serialize response");
+                let encrypted = link.encrypt(&response_json).expect("Encryption failed");
+
+                let len_bytes = (encrypted.len() as u32).to_be_bytes();
+                if let Err(e) = socket.write_all(&len_bytes).await {
+                    ui::error(&format!("Write error: {}", e));
+                    break;
+                }
+                if let Err(e) = socket.write_all(&encrypted).await {
+                    ui::error(&format!("Write error: {}", e));
+                    break;
+                }
+
+                if let PaykitNoiseMessage::ConfirmReceipt { receipt } = response_msg {
+                    ui::success("Receipt confirmed and sent");
+                    confirmed.push(receipt);
                 }
             }
-            _ = tokio::signal::ctrl_c() => {
-                ui::info("\nReceiver stopped");
-                break;
+            Ok(None) => {
+                // No response needed
+            }
+            Err(e) => {
+                ui::error(&format!("Message handling error: {}", e));
             }
         }
+
+        if limits.is_greylisted(&peer_key) {
+            ui::warning("Client greylisted; closing connection");
+            break;
+        }
     }
 
-    Ok(())
+    confirmed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tenant() {
+        assert_eq!(parse_tenant("coffee", 60).unwrap(), ("coffee".into(), 60));
+        assert_eq!(parse_tenant("books:5", 60).unwrap(), ("books".into(), 5));
+        assert!(parse_tenant("books:many", 60).is_err());
+        assert!(parse_tenant("../etc", 60).is_err());
+        assert!(parse_tenant(":5", 60).is_err());
+    }
+
+    #[test]
+    fn test_metrics_routes() {
+        let router = TenantRouter::new();
+        assert_eq!(
+            metrics_response(&router, "/metrics"),
+            ("200 OK", "{}".into())
+        );
+        assert_eq!(
+            metrics_response(&router, "/metrics/coffee").0,
+            "404 Not Found"
+        );
+        assert_eq!(metrics_response(&router, "/").0, "404 Not Found");
+    }
 }
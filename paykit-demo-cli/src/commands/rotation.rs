@@ -4,7 +4,7 @@
 //! policies for privacy-enhancing address reuse prevention.
 
 use anyhow::{Context, Result};
-use paykit_lib::rotation::{RotationConfig, RotationPolicy};
+use paykit_lib::rotation::{RotationConfig, RotationEngine, RotationPolicy, RotationReason};
 use paykit_lib::MethodId;
 use std::path::Path;
 
@@ -14,11 +14,11 @@ use crate::ui;
 pub async fn status(storage_dir: &Path, verbose: bool) -> Result<()> {
     ui::header("Endpoint Rotation Status");
 
-    let config = load_rotation_config(storage_dir)?;
-    let state = load_rotation_state(storage_dir)?;
+    let engine = load_engine(storage_dir)?;
+    let config = engine.config();
 
     // Default policy
-    ui::key_value("Default policy", &format_policy(&config.default_policy));
+    ui::key_value("Default policy", &config.default_policy.describe());
     ui::key_value(
         "Auto-rotate on payment",
         if config.auto_rotate_on_payment {
@@ -36,44 +36,49 @@ pub async fn status(storage_dir: &Path, verbose: bool) -> Result<()> {
     } else {
         ui::info("Per-method policies:");
         for (method, policy) in &config.method_policies {
-            ui::key_value(&format!("  {}", method), &format_policy(policy));
+            ui::key_value(&format!("  {}", method), &policy.describe());
         }
     }
 
     ui::separator();
 
     // Rotation state
-    if let Some(state) = state {
-        ui::info("Rotation history:");
-        if let Some(obj) = state.as_object() {
-            for (method, info) in obj {
-                if let Some(rotations) = info.get("rotations").and_then(|v| v.as_u64()) {
-                    let last_rotated = info
-                        .get("last_rotated")
-                        .and_then(|v| v.as_i64())
-                        .map(|ts| {
-                            chrono::DateTime::from_timestamp(ts, 0)
-                                .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
-                                .unwrap_or_else(|| "unknown".to_string())
-                        })
-                        .unwrap_or_else(|| "never".to_string());
-
-                    ui::key_value(
-                        &format!("  {}", method),
-                        &format!("{} rotations, last: {}", rotations, last_rotated),
-                    );
-
-                    if verbose {
-                        if let Some(pending) = info.get("pending_endpoint").and_then(|v| v.as_str())
-                        {
-                            ui::info(&format!("    Pending endpoint: {}", pending));
-                        }
-                    }
-                }
+    let statuses = engine.status();
+    if statuses.is_empty() {
+        ui::info("No rotation history yet");
+        return Ok(());
+    }
+
+    ui::info("Rotation state:");
+    let now = chrono::Utc::now().timestamp();
+    for status in statuses {
+        let last_rotated = status
+            .last_rotated_at
+            .map(|ts| format_timestamp(ts, "%Y-%m-%d %H:%M"))
+            .unwrap_or_else(|| "never".to_string());
+
+        ui::key_value(
+            &format!("  {}", status.method_id),
+            &format!(
+                "{} uses, {} rotations, last: {}",
+                status.use_count, status.rotations, last_rotated
+            ),
+        );
+
+        let method_id = MethodId::new(&status.method_id);
+        if let Some(reason) = engine.evaluate(&method_id, now).reason() {
+            ui::warning(&format!("    Rotation due ({})", reason.as_str()));
+        }
+
+        if verbose {
+            if let Some(endpoint) = engine
+                .history(Some(&method_id))
+                .last()
+                .and_then(|r| r.new_endpoint.as_deref())
+            {
+                ui::info(&format!("    Pending endpoint: {}", endpoint));
             }
         }
-    } else {
-        ui::info("No rotation history yet");
     }
 
     Ok(())
@@ -90,11 +95,11 @@ pub async fn set_policy(
 
     let mut config = load_rotation_config(storage_dir)?;
 
-    let policy = parse_policy(policy_str)?;
+    let policy = RotationPolicy::parse(policy_str)?;
     let method_id = method.to_string();
 
     ui::info(&format!("Setting policy for method: {}", method));
-    ui::key_value("New policy", &format_policy(&policy));
+    ui::key_value("New policy", &policy.describe());
 
     config.method_policies.insert(method_id, policy);
     save_rotation_config(storage_dir, &config)?;
@@ -110,9 +115,9 @@ pub async fn set_default(storage_dir: &Path, policy_str: &str, _verbose: bool) -
 
     let mut config = load_rotation_config(storage_dir)?;
 
-    let policy = parse_policy(policy_str)?;
+    let policy = RotationPolicy::parse(policy_str)?;
 
-    ui::key_value("New default policy", &format_policy(&policy));
+    ui::key_value("New default policy", &policy.describe());
 
     config.default_policy = policy;
     save_rotation_config(storage_dir, &config)?;
@@ -147,13 +152,14 @@ pub async fn rotate(storage_dir: &Path, method: &str, verbose: bool) -> Result<(
     ui::header("Manual Endpoint Rotation");
 
     let method_id = MethodId::new(method);
-    let config = load_rotation_config(storage_dir)?;
+    let mut engine = load_engine(storage_dir)?;
 
     ui::info(&format!("Rotating endpoint for method: {}", method));
 
-    // Create rotation manager
+    // Create rotation manager to generate the new endpoint
     let registry = paykit_lib::prelude::default_registry();
-    let rotation_manager = paykit_lib::rotation::EndpointRotationManager::new(config, registry);
+    let rotation_manager =
+        paykit_lib::rotation::EndpointRotationManager::new(engine.config().clone(), registry);
 
     // Trigger rotation
     match rotation_manager.rotate(&method_id).await {
@@ -169,7 +175,13 @@ pub async fn rotate(storage_dir: &Path, method: &str, verbose: bool) -> Result<(
             ));
 
             // Update rotation state
-            update_rotation_state(storage_dir, &method_id, &new_endpoint)?;
+            engine.record_rotation(
+                &method_id,
+                RotationReason::Manual,
+                Some(new_endpoint.0),
+                chrono::Utc::now().timestamp(),
+            );
+            save_engine(storage_dir, &engine)?;
         }
         Err(e) => {
             ui::error(&format!("Rotation failed: {}", e));
@@ -184,90 +196,68 @@ pub async fn rotate(storage_dir: &Path, method: &str, verbose: bool) -> Result<(
 pub async fn history(storage_dir: &Path, method: Option<String>, verbose: bool) -> Result<()> {
     ui::header("Rotation History");
 
-    let state = load_rotation_state(storage_dir)?;
-
-    if state.is_none() {
-        ui::info("No rotation history recorded yet.");
-        ui::info(
-            "Rotations are recorded automatically after payments when auto-rotate is enabled.",
-        );
-        return Ok(());
-    }
-
-    let state = state.unwrap();
-    let obj = state
-        .as_object()
-        .ok_or_else(|| anyhow::anyhow!("Invalid rotation state format"))?;
-
-    // Filter by method if specified
-    let methods: Vec<&String> = if let Some(ref filter_method) = method {
-        obj.keys().filter(|k| *k == filter_method).collect()
-    } else {
-        obj.keys().collect()
-    };
+    let engine = load_engine(storage_dir)?;
+    let method_id = method.as_deref().map(MethodId::new);
+    let records = engine.history(method_id.as_ref());
 
-    if methods.is_empty() {
+    if records.is_empty() {
         if let Some(m) = method {
             ui::info(&format!("No rotation history for method: {}", m));
         } else {
             ui::info("No rotation history recorded yet.");
+            ui::info(
+                "Rotations are recorded automatically after payments when auto-rotate is enabled.",
+            );
         }
         return Ok(());
     }
 
     // Summary statistics
-    let mut total_rotations: u64 = 0;
-    for method_key in &methods {
-        if let Some(info) = obj.get(*method_key) {
-            total_rotations += info.get("rotations").and_then(|v| v.as_u64()).unwrap_or(0);
-        }
-    }
+    let mut methods: Vec<&str> = records.iter().map(|r| r.method_id.as_str()).collect();
+    methods.sort_unstable();
+    methods.dedup();
 
-    ui::key_value("Total rotations", &total_rotations.to_string());
+    ui::key_value("Total rotations", &records.len().to_string());
     ui::key_value("Methods tracked", &methods.len().to_string());
     ui::separator();
 
     // Per-method history
     for method_key in methods {
-        if let Some(info) = obj.get(method_key) {
-            let rotations = info.get("rotations").and_then(|v| v.as_u64()).unwrap_or(0);
-            let last_rotated = info
-                .get("last_rotated")
-                .and_then(|v| v.as_i64())
-                .map(|ts| {
-                    chrono::DateTime::from_timestamp(ts, 0)
-                        .map(|d| d.format("%Y-%m-%d %H:%M:%S UTC").to_string())
-                        .unwrap_or_else(|| "unknown".to_string())
-                })
-                .unwrap_or_else(|| "never".to_string());
-
-            ui::key_value(&format!("Method: {}", method_key), "");
-            ui::key_value("  Total rotations", &rotations.to_string());
-            ui::key_value("  Last rotated", &last_rotated);
-
-            if verbose {
-                if let Some(pending) = info.get("pending_endpoint").and_then(|v| v.as_str()) {
-                    ui::key_value("  Pending endpoint", pending);
-                }
-
-                // Show recent rotation events if available
-                if let Some(history) = info.get("history").and_then(|v| v.as_array()) {
-                    if !history.is_empty() {
-                        ui::info("  Recent events:");
-                        for (i, event) in history.iter().rev().take(5).enumerate() {
-                            if let Some(ts) = event.get("timestamp").and_then(|v| v.as_i64()) {
-                                let time = chrono::DateTime::from_timestamp(ts, 0)
-                                    .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
-                                    .unwrap_or_else(|| "unknown".to_string());
-                                ui::info(&format!("    {}. {}", i + 1, time));
-                            }
-                        }
-                    }
-                }
+        let method_records: Vec<_> = records
+            .iter()
+            .filter(|r| r.method_id == method_key)
+            .collect();
+        let last_rotated = method_records
+            .last()
+            .map(|r| format_timestamp(r.rotated_at, "%Y-%m-%d %H:%M:%S UTC"))
+            .unwrap_or_else(|| "never".to_string());
+
+        ui::key_value(&format!("Method: {}", method_key), "");
+        ui::key_value("  Total rotations", &method_records.len().to_string());
+        ui::key_value("  Last rotated", &last_rotated);
+
+        if verbose {
+            if let Some(endpoint) = method_records
+                .last()
+                .and_then(|r| r.new_endpoint.as_deref())
+            {
+                ui::key_value("  Pending endpoint", endpoint);
             }
 
-            ui::separator();
+            // Show recent rotation events
+            ui::info("  Recent events:");
+            for (i, record) in method_records.iter().rev().take(5).enumerate() {
+                ui::info(&format!(
+                    "    {}. {} ({}, {} uses)",
+                    i + 1,
+                    format_timestamp(record.rotated_at, "%Y-%m-%d %H:%M"),
+                    record.reason.as_str(),
+                    record.use_count
+                ));
+            }
         }
+
+        ui::separator();
     }
 
     Ok(())
@@ -277,13 +267,14 @@ pub async fn history(storage_dir: &Path, method: Option<String>, verbose: bool)
 pub async fn clear_history(storage_dir: &Path, _verbose: bool) -> Result<()> {
     ui::header("Clear Rotation History");
 
-    let state_path = storage_dir.join("rotation_state.json");
+    let mut engine = load_engine(storage_dir)?;
 
-    if state_path.exists() {
-        std::fs::remove_file(&state_path).context("Failed to remove rotation state")?;
-        ui::success("Rotation history cleared");
-    } else {
+    if engine.history(None).is_empty() {
         ui::info("No rotation history to clear");
+    } else {
+        engine.clear_history();
+        save_engine(storage_dir, &engine)?;
+        ui::success("Rotation history cleared");
     }
 
     Ok(())
@@ -311,91 +302,36 @@ fn save_rotation_config(storage_dir: &Path, config: &RotationConfig) -> Result<(
     std::fs::write(&config_path, config_str).context("Failed to write rotation config")
 }
 
-fn load_rotation_state(storage_dir: &Path) -> Result<Option<serde_json::Value>> {
-    let state_path = storage_dir.join("rotation_state.json");
-
-    if state_path.exists() {
-        let state_str =
-            std::fs::read_to_string(&state_path).context("Failed to read rotation state")?;
-        let state = serde_json::from_str(&state_str).context("Failed to parse rotation state")?;
-        Ok(Some(state))
-    } else {
-        Ok(None)
-    }
-}
-
-fn update_rotation_state(
-    storage_dir: &Path,
-    method_id: &MethodId,
-    new_endpoint: &paykit_lib::EndpointData,
-) -> Result<()> {
+/// Load the rotation engine, applying the current rotation config.
+///
+/// State written by older versions uses a different format and is ignored.
+pub(crate) fn load_engine(storage_dir: &Path) -> Result<RotationEngine> {
+    let config = load_rotation_config(storage_dir)?;
     let state_path = storage_dir.join("rotation_state.json");
 
-    let mut state: serde_json::Value = if state_path.exists() {
+    let mut engine: RotationEngine = if state_path.exists() {
         let state_str =
             std::fs::read_to_string(&state_path).context("Failed to read rotation state")?;
-        serde_json::from_str(&state_str).unwrap_or_else(|_| serde_json::json!({}))
+        serde_json::from_str(&state_str).unwrap_or_default()
     } else {
-        serde_json::json!({})
+        RotationEngine::default()
     };
+    engine.set_config(config);
 
-    let method_key = &method_id.0;
-    if !state[method_key].is_object() {
-        state[method_key] = serde_json::json!({
-            "last_rotated": chrono::Utc::now().timestamp(),
-            "rotations": 0,
-        });
-    }
-
-    state[method_key]["last_rotated"] = serde_json::json!(chrono::Utc::now().timestamp());
-    state[method_key]["rotations"] =
-        serde_json::json!(state[method_key]["rotations"].as_u64().unwrap_or(0) + 1);
-    state[method_key]["pending_endpoint"] = serde_json::json!(new_endpoint.0);
-
-    let state_str =
-        serde_json::to_string_pretty(&state).context("Failed to serialize rotation state")?;
-    std::fs::write(&state_path, state_str).context("Failed to save rotation state")?;
-
-    Ok(())
+    Ok(engine)
 }
 
-fn parse_policy(policy_str: &str) -> Result<RotationPolicy> {
-    let policy_lower = policy_str.to_lowercase();
-
-    if policy_lower == "on-use" || policy_lower == "onuse" {
-        return Ok(RotationPolicy::RotateOnUse);
-    }
-
-    if policy_lower == "manual" || policy_lower == "never" {
-        return Ok(RotationPolicy::Manual);
-    }
-
-    if let Some(count_str) = policy_lower.strip_prefix("after:") {
-        let count: u32 = count_str.parse().context("Invalid use count in policy")?;
-        return Ok(RotationPolicy::after_uses(count));
-    }
-
-    if let Some(interval_str) = policy_lower.strip_prefix("periodic:") {
-        let seconds: u64 = interval_str.parse().context("Invalid interval in policy")?;
-        return Ok(RotationPolicy::RotatePeriodic {
-            interval_secs: seconds,
-        });
-    }
-
-    anyhow::bail!(
-        "Invalid policy format. Use: on-use, manual, after:<count>, or periodic:<seconds>"
-    )
+/// Persist the rotation engine state.
+pub(crate) fn save_engine(storage_dir: &Path, engine: &RotationEngine) -> Result<()> {
+    std::fs::create_dir_all(storage_dir)?;
+    let state_path = storage_dir.join("rotation_state.json");
+    let state_str =
+        serde_json::to_string_pretty(engine).context("Failed to serialize rotation state")?;
+    std::fs::write(&state_path, state_str).context("Failed to save rotation state")
 }
 
-fn format_policy(policy: &RotationPolicy) -> String {
-    match policy {
-        RotationPolicy::RotateOnUse => "Rotate on every use (best privacy)".to_string(),
-        RotationPolicy::RotateOnThreshold { threshold } => {
-            format!("Rotate after {} uses", threshold)
-        }
-        RotationPolicy::RotatePeriodic { interval_secs } => {
-            format!("Rotate every {} seconds", interval_secs)
-        }
-        RotationPolicy::Manual => "Manual rotation only".to_string(),
-    }
+fn format_timestamp(ts: i64, format: &str) -> String {
+    chrono::DateTime::from_timestamp(ts, 0)
+        .map(|d| d.format(format).to_string())
+        .unwrap_or_else(|| "unknown".to_string())
 }
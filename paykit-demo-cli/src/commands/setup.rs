@@ -1,12 +1,17 @@
 //! Setup command - create a new identity
 
 use anyhow::Result;
-use paykit_demo_core::{Identity, IdentityManager, SecureIdentityManager};
+use paykit_demo_core::{Identity, IdentityManager, SecretLocation, SecureIdentityManager};
 use std::path::Path;
 
 use crate::ui;
 
-pub async fn run(storage_dir: &Path, name: Option<String>, verbose: bool) -> Result<()> {
+pub async fn run(
+    storage_dir: &Path,
+    name: Option<String>,
+    keyring: bool,
+    verbose: bool,
+) -> Result<()> {
     ui::header("Setup New Identity");
 
     // Get or prompt for name
@@ -20,9 +25,10 @@ pub async fn run(storage_dir: &Path, name: Option<String>, verbose: bool) -> Res
         ui::info(&format!("Creating identity '{}'...", name));
     }
 
-    // Try secure storage first (if metadata file exists, we're using secure storage)
+    // Try secure storage first (if metadata file exists, we're using secure storage),
+    // unless the OS keyring was requested explicitly
     let metadata_path = storage_dir.join("identities_metadata.json");
-    let using_secure_storage = metadata_path.exists();
+    let using_secure_storage = metadata_path.exists() && !keyring;
 
     // Check if identity already exists
     let identity_exists = if using_secure_storage {
@@ -30,7 +36,9 @@ pub async fn run(storage_dir: &Path, name: Option<String>, verbose: bool) -> Res
         secure_manager.list()?.contains(&name)
     } else {
         let identities_dir = storage_dir.join("identities");
-        IdentityManager::new(&identities_dir).load(&name).is_ok()
+        IdentityManager::new(&identities_dir)
+            .list()?
+            .contains(&name)
     };
 
     if identity_exists
@@ -49,15 +57,18 @@ pub async fn run(storage_dir: &Path, name: Option<String>, verbose: bool) -> Res
     spinner.finish_and_clear();
 
     // Save identity
-    if using_secure_storage {
+    let location = if using_secure_storage {
         let secure_manager = SecureIdentityManager::new(storage_dir);
         secure_manager.save(&identity, &name).await?;
+        None
     } else {
-        let identities_dir = storage_dir.join("identities");
-        std::fs::create_dir_all(&identities_dir)?;
-        let identity_manager = IdentityManager::new(&identities_dir);
-        identity_manager.save(&identity, &name)?;
-    }
+        Some(super::save_file_identity(
+            storage_dir,
+            &identity,
+            &name,
+            keyring,
+        )?)
+    };
 
     // Set as current
     super::set_current_identity(storage_dir, &name)?;
@@ -68,20 +79,28 @@ pub async fn run(storage_dir: &Path, name: Option<String>, verbose: bool) -> Res
     ui::key_value("Pubky URI", &identity.pubky_uri());
 
     // Show QR code
-    println!();
+    ui::line("");
     ui::info("Scan this QR code to share your Pubky URI:");
     ui::qr_code(&identity.pubky_uri())?;
 
-    if using_secure_storage {
-        ui::info("Identity saved to secure storage (OS keychain/credential manager)");
-    } else {
-        ui::info(&format!(
-            "Identity saved to: {:?}",
-            storage_dir
-                .join("identities")
-                .join(format!("{}.json", name))
-        ));
-        ui::warning("Using plaintext storage. Run 'paykit-demo migrate' to use secure storage.");
+    let identity_path = storage_dir
+        .join("identities")
+        .join(format!("{}.json", name));
+    match location {
+        None => ui::info("Identity saved to secure storage (OS keychain/credential manager)"),
+        Some(SecretLocation::Keyring) => {
+            ui::info("Secret key saved to the OS keyring");
+            ui::info(&format!("Identity metadata saved to: {:?}", identity_path));
+        }
+        Some(SecretLocation::EncryptedFile) => {
+            ui::info(&format!("Identity saved encrypted to: {:?}", identity_path));
+        }
+        Some(SecretLocation::Plaintext) => {
+            ui::info(&format!("Identity saved to: {:?}", identity_path));
+            ui::warning(
+                "Using plaintext storage. Run 'paykit-demo migrate' or use --keyring for secure storage.",
+            );
+        }
     }
 
     Ok(())
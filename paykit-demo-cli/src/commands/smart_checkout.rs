@@ -3,7 +3,6 @@
 //! Provides parity with mobile demo's SmartCheckoutView functionality.
 
 use anyhow::{Context, Result};
-use paykit_demo_core::DirectoryClient;
 use std::path::Path;
 
 use crate::ui;
@@ -94,7 +93,7 @@ pub async fn run(
     ui::key_value("Strategy", &format!("{:?}", strategy));
     ui::separator();
 
-    let client = DirectoryClient::new(homeserver);
+    let client = super::directory_client(homeserver);
 
     // Discover methods
     let spinner = ui::spinner("Discovering payment methods...");
@@ -119,6 +118,23 @@ pub async fn run(
 
     ui::success(&format!("Found {} payment method(s)", methods.len()));
 
+    // The payee's self-reported status, if fresh
+    let now = chrono::Utc::now().timestamp();
+    let status = client.fetch_status(&public_key).await.ok().flatten();
+    if let Some(status) = &status {
+        if let Some(message) = &status.message {
+            ui::key_value("Payee status", message);
+        }
+        if !status.is_accepting(now) {
+            ui::warning("Payee reports it is not accepting payments right now");
+        }
+    }
+    let available = |method_id: &str| {
+        status.as_ref().is_none_or(|status| {
+            status.is_method_available(&paykit_lib::MethodId(method_id.to_string()), now)
+        })
+    };
+
     // Score methods
     let spinner = ui::spinner("Evaluating methods...");
     let scored_methods: Vec<ScoredMethod> = methods
@@ -152,6 +168,8 @@ pub async fn run(
             .partial_cmp(&a.score_for_strategy(strategy))
             .unwrap_or(std::cmp::Ordering::Equal)
     });
+    // Methods the payee marked unavailable go last
+    sorted.sort_by_key(|m| !available(&m.method_id));
 
     // Display ranked methods
     ui::separator();
@@ -159,9 +177,14 @@ pub async fn run(
     for (i, method) in sorted.iter().enumerate() {
         let rank = if i == 0 { "★ " } else { "  " };
         let score = method.score_for_strategy(strategy);
+        let note = if available(&method.method_id) {
+            ""
+        } else {
+            " [unavailable per payee]"
+        };
         ui::info(&format!(
-            "{}{}: {} (score: {:.2})",
-            rank, method.method_id, method.endpoint, score
+            "{}{}: {} (score: {:.2}){}",
+            rank, method.method_id, method.endpoint, score, note
         ));
 
         if verbose {
@@ -0,0 +1,290 @@
+//! Standing order commands
+//!
+//! Recurring payments set up on the payer's side only ("send 50k sats to X
+//! every Friday"), without the payee running any subscription logic. Due
+//! orders are paid by `standing run` or `standing watch` and recorded as
+//! ordinary receipts.
+
+use anyhow::{anyhow, Context, Result};
+use paykit_demo_core::StandingOrderCoordinator;
+use paykit_lib::methods::default_registry;
+use paykit_lib::standing_orders::{
+    OrderOutcome, OrderSchedule, StandingOrder, StandingOrderScheduler,
+};
+use paykit_lib::MethodId;
+use std::path::Path;
+
+use crate::ui;
+
+const WEEKDAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+/// Create a standing order
+#[allow(clippy::too_many_arguments)]
+pub async fn add(
+    storage_dir: &Path,
+    id: &str,
+    payee: &str,
+    amount: u64,
+    every: Option<i64>,
+    weekly: Option<String>,
+    monthly: Option<u8>,
+    methods: Vec<String>,
+    memo: Option<String>,
+    max_runs: Option<u32>,
+    _verbose: bool,
+) -> Result<()> {
+    ui::header("New Standing Order");
+
+    let schedule = parse_schedule(every, weekly.as_deref(), monthly)?;
+    let payee = resolve_payee(storage_dir, payee)?;
+    let now = chrono::Utc::now().timestamp();
+
+    let mut order = StandingOrder::new(id, payee, amount, schedule, now)?
+        .with_methods(methods.into_iter().map(MethodId::new).collect());
+    if let Some(memo) = memo {
+        order = order.with_memo(memo);
+    }
+    if let Some(max_runs) = max_runs {
+        order = order.with_max_runs(max_runs);
+    }
+
+    StandingOrderCoordinator::new(storage_dir).add(order.clone())?;
+    ui::success("Standing order created");
+    show_order(&order);
+    Ok(())
+}
+
+/// List standing orders
+pub async fn list(storage_dir: &Path, _verbose: bool) -> Result<()> {
+    ui::header("Standing Orders");
+
+    let coordinator = StandingOrderCoordinator::new(storage_dir);
+    let orders = coordinator.list()?;
+    if orders.is_empty() {
+        ui::info("No standing orders");
+        return Ok(());
+    }
+
+    let in_progress = coordinator.in_progress()?;
+    for order in &orders {
+        show_order(order);
+        if let Some(claimed_at) = in_progress.get(&order.order_id) {
+            let since = chrono::DateTime::from_timestamp(*claimed_at, 0)
+                .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| claimed_at.to_string());
+            ui::key_value("Being paid since", &since);
+        }
+        ui::separator();
+    }
+    Ok(())
+}
+
+/// Pause or resume a standing order
+pub async fn set_paused(storage_dir: &Path, id: &str, paused: bool, _verbose: bool) -> Result<()> {
+    StandingOrderCoordinator::new(storage_dir).set_paused(id, paused)?;
+    if paused {
+        ui::success(&format!("Paused standing order {}", id));
+    } else {
+        ui::success(&format!("Resumed standing order {}", id));
+    }
+    Ok(())
+}
+
+/// Delete a standing order
+pub async fn remove(storage_dir: &Path, id: &str, _verbose: bool) -> Result<()> {
+    if StandingOrderCoordinator::new(storage_dir).remove(id)? {
+        ui::success(&format!("Removed standing order {}", id));
+    } else {
+        ui::warning(&format!("Standing order not found: {}", id));
+    }
+    Ok(())
+}
+
+/// Release an order left claimed by an interrupted run
+pub async fn release(storage_dir: &Path, id: &str, _verbose: bool) -> Result<()> {
+    if !StandingOrderCoordinator::new(storage_dir).release(id)? {
+        ui::info(&format!("Standing order {} is not being paid", id));
+        return Ok(());
+    }
+    ui::success(&format!("Released standing order {}", id));
+    ui::warning("If the interrupted payment went out, the next run pays it again");
+    Ok(())
+}
+
+/// Pay every order that is due now
+pub async fn run(storage_dir: &Path, verbose: bool) -> Result<()> {
+    ui::header("Standing Orders");
+
+    let paid = run_due(storage_dir, verbose).await?;
+    if paid == 0 {
+        ui::info("No standing orders due");
+    }
+    Ok(())
+}
+
+/// Keep paying due orders until interrupted
+pub async fn watch(storage_dir: &Path, poll: u64, verbose: bool) -> Result<()> {
+    ui::header("Standing Order Watcher");
+
+    ui::info(&format!("Checking every {}s, press Ctrl+C to stop", poll));
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(poll.max(1)));
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                // Failures are reported per order; keep watching
+                if let Err(e) = run_due(storage_dir, verbose).await {
+                    ui::error(&format!("Standing order run failed: {}", e));
+                }
+            }
+            _ = tokio::signal::ctrl_c() => {
+                ui::info("Stopped");
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Pay the due orders, returning how many were paid
+async fn run_due(storage_dir: &Path, verbose: bool) -> Result<usize> {
+    let orders = StandingOrderCoordinator::new(storage_dir);
+    let now = chrono::Utc::now().timestamp();
+    let due = orders.due(now)?;
+    if due.is_empty() {
+        return Ok(0);
+    }
+
+    let identity = super::load_current_identity(storage_dir).await?;
+    let storage = super::storage::open(storage_dir);
+    storage.init()?;
+    let scheduler = StandingOrderScheduler::new(default_registry());
+    let public_storage = pubky::PublicStorage::new().context("Failed to create PublicStorage")?;
+    let transport = paykit_lib::PubkyUnauthenticatedTransport::new(public_storage)
+        .with_path_roots(super::path_roots());
+
+    let mut paid = 0;
+    for order in due {
+        let payee: paykit_lib::PublicKey = order
+            .payee
+            .parse()
+            .map_err(|e| anyhow!("Invalid payee public key: {}", e))?;
+        let supported = match paykit_lib::get_payment_list(&transport, &payee).await {
+            Ok(supported) => supported,
+            Err(e) => {
+                ui::error(&format!(
+                    "{}: could not fetch payee methods: {}",
+                    order.order_id, e
+                ));
+                continue;
+            }
+        };
+
+        match orders
+            .run(
+                &scheduler,
+                &order.order_id,
+                &supported,
+                &storage,
+                &identity.public_key(),
+                now,
+            )
+            .await
+        {
+            Ok(OrderOutcome::Paid(execution)) => {
+                paid += 1;
+                ui::success(&format!(
+                    "{}: paid {} via {}",
+                    order.order_id,
+                    ui::sats(order.amount_sats),
+                    execution.method_id.0
+                ));
+            }
+            Ok(OrderOutcome::Failed(execution)) => ui::error(&format!(
+                "{}: payment failed: {}",
+                order.order_id,
+                execution.error.unwrap_or_default()
+            )),
+            Ok(OrderOutcome::Unresolved(execution)) => ui::warning(&format!(
+                "{}: payment may have been sent ({}); check the wallet, then release it",
+                order.order_id,
+                execution.error.unwrap_or_default()
+            )),
+            Ok(OrderOutcome::Skipped(skip)) => {
+                if verbose {
+                    ui::info(&format!("{}: {}", order.order_id, skip.describe()));
+                }
+            }
+            Err(e) => ui::error(&format!("{}: {}", order.order_id, e)),
+        }
+    }
+    Ok(paid)
+}
+
+fn parse_schedule(
+    every: Option<i64>,
+    weekly: Option<&str>,
+    monthly: Option<u8>,
+) -> Result<OrderSchedule> {
+    match (every, weekly, monthly) {
+        (Some(every_secs), None, None) => Ok(OrderSchedule::Interval { every_secs }),
+        (None, Some(day), None) => {
+            let day = day.to_lowercase();
+            let weekday = WEEKDAYS
+                .iter()
+                .position(|d| day.starts_with(d))
+                .ok_or_else(|| anyhow!("Unknown weekday '{}'", day))?;
+            Ok(OrderSchedule::Weekly {
+                weekday: weekday as u8,
+            })
+        }
+        (None, None, Some(day)) => Ok(OrderSchedule::Monthly { day }),
+        _ => Err(anyhow!(
+            "Specify exactly one of --every, --weekly or --monthly"
+        )),
+    }
+}
+
+/// Accept a contact name, a pubky URI or a bare public key
+pub(crate) fn resolve_payee(storage_dir: &Path, payee: &str) -> Result<String> {
+    if let Some(key) = payee.strip_prefix("pubky://") {
+        return Ok(key.to_string());
+    }
+    let storage = super::storage::open(storage_dir);
+    Ok(storage
+        .list_contacts()?
+        .into_iter()
+        .find(|contact| contact.name == payee)
+        .map(|contact| contact.public_key.to_string())
+        .unwrap_or_else(|| payee.to_string()))
+}
+
+fn show_order(order: &StandingOrder) {
+    ui::key_value("Order", &order.order_id);
+    ui::key_value("Payee", &order.payee);
+    ui::key_value("Amount", &ui::sats(order.amount_sats));
+    let schedule = match &order.schedule {
+        OrderSchedule::Interval { every_secs } => format!("every {} seconds", every_secs),
+        OrderSchedule::Weekly { weekday } => {
+            format!("weekly on {}", WEEKDAYS[*weekday as usize % 7])
+        }
+        OrderSchedule::Monthly { day } => format!("monthly on day {}", day),
+    };
+    ui::key_value("Schedule", &schedule);
+    if !order.methods.is_empty() {
+        let methods: Vec<&str> = order.methods.iter().map(|m| m.0.as_str()).collect();
+        ui::key_value("Methods", &methods.join(", "));
+    }
+    if let Some(memo) = &order.memo {
+        ui::key_value("Memo", memo);
+    }
+    let status = if order.is_finished() {
+        "finished".to_string()
+    } else if order.paused {
+        "paused".to_string()
+    } else {
+        chrono::DateTime::from_timestamp(order.next_due_at, 0)
+            .map(|d| format!("next payment {}", d.format("%Y-%m-%d %H:%M")))
+            .unwrap_or_else(|| "scheduled".to_string())
+    };
+    ui::key_value("Status", &status);
+    ui::key_value("Payments made", &order.runs.to_string());
+}
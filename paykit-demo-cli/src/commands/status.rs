@@ -0,0 +1,175 @@
+//! Status command - publish or view self-reported payee status
+//!
+//! Payees publish whether they are accepting payments, which methods are
+//! temporarily unavailable and any maintenance window. Payers' method
+//! selection deprioritizes unavailable methods until the status expires.
+
+use anyhow::{Context, Result};
+use paykit_lib::protocol::PayeeStatus;
+use paykit_lib::MethodId;
+use std::path::Path;
+
+use crate::ui;
+
+/// Options for `status set`
+pub struct StatusOptions {
+    pub paused: bool,
+    pub unavailable: Vec<String>,
+    pub preferred: Option<String>,
+    pub maintenance_from: Option<String>,
+    pub maintenance_until: Option<String>,
+    pub message: Option<String>,
+    pub ttl_secs: u64,
+}
+
+#[tracing::instrument(skip(storage_dir, options))]
+pub async fn set(
+    storage_dir: &Path,
+    options: StatusOptions,
+    homeserver: &str,
+    verbose: bool,
+) -> Result<()> {
+    ui::header("Publish Payee Status");
+
+    let identity = super::load_current_identity(storage_dir).await?;
+    let now = chrono::Utc::now().timestamp();
+
+    let mut status = PayeeStatus::new(now)
+        .with_accepting(!options.paused)
+        .with_ttl(options.ttl_secs);
+    for method in options.unavailable {
+        status = status.with_unavailable(MethodId(method));
+    }
+    if let Some(method) = options.preferred {
+        status = status.with_preferred(MethodId(method));
+    }
+    match (&options.maintenance_from, &options.maintenance_until) {
+        (Some(from), Some(until)) => {
+            status = status.with_maintenance(parse_time(from)?, parse_time(until)?, None);
+        }
+        (None, None) => {}
+        _ => anyhow::bail!("--maintenance-from and --maintenance-until must be given together"),
+    }
+    if let Some(message) = options.message {
+        status = status.with_message(message);
+    }
+    status.validate()?;
+
+    show_status(&status, now);
+    if verbose {
+        ui::key_value("Homeserver", homeserver);
+    }
+
+    let client = super::directory_client(homeserver);
+    let spinner = ui::spinner("Publishing status...");
+    let session = client
+        .create_session(&identity.keypair, true)
+        .await
+        .context("Failed to establish session with homeserver")?;
+    let result = client.publish_status(&session, &status).await;
+    spinner.finish_and_clear();
+    result?;
+
+    ui::success(&format!(
+        "Status published; payers will honor it until {}",
+        format_time(status.expires_at())
+    ));
+    Ok(())
+}
+
+#[tracing::instrument(skip(storage_dir))]
+pub async fn show(storage_dir: &Path, uri: Option<&str>, homeserver: &str) -> Result<()> {
+    ui::header("Payee Status");
+
+    let uri = match uri {
+        Some(uri) => uri.to_string(),
+        None => super::load_current_identity(storage_dir).await?.pubky_uri(),
+    };
+    let public_key: paykit_lib::PublicKey = uri
+        .strip_prefix("pubky://")
+        .unwrap_or(&uri)
+        .parse()
+        .context("Invalid Pubky URI")?;
+
+    let client = super::directory_client(homeserver);
+    let spinner = ui::spinner("Fetching status...");
+    let status = client.fetch_status(&public_key).await;
+    spinner.finish_and_clear();
+
+    match status? {
+        Some(status) => show_status(&status, chrono::Utc::now().timestamp()),
+        None => ui::info("No current status published (payers use default selection)"),
+    }
+    Ok(())
+}
+
+#[tracing::instrument(skip(storage_dir))]
+pub async fn clear(storage_dir: &Path, homeserver: &str) -> Result<()> {
+    ui::header("Clear Payee Status");
+
+    let identity = super::load_current_identity(storage_dir).await?;
+    let client = super::directory_client(homeserver);
+    let spinner = ui::spinner("Removing status...");
+    let session = client
+        .create_session(&identity.keypair, true)
+        .await
+        .context("Failed to establish session with homeserver")?;
+    let result = client.clear_status(&session).await;
+    spinner.finish_and_clear();
+    result?;
+
+    ui::success("Status removed");
+    Ok(())
+}
+
+fn show_status(status: &PayeeStatus, now: i64) {
+    let accepting = if status.is_accepting(now) {
+        "yes"
+    } else if status.in_maintenance(now) {
+        "no (maintenance)"
+    } else {
+        "no"
+    };
+    ui::key_value("Accepting payments", accepting);
+    if !status.unavailable_methods.is_empty() {
+        let methods: Vec<&str> = status
+            .unavailable_methods
+            .iter()
+            .map(|m| m.0.as_str())
+            .collect();
+        ui::key_value("Unavailable", &methods.join(", "));
+    }
+    if let Some(method) = &status.preferred_method {
+        ui::key_value("Preferred", &method.0);
+    }
+    if let Some(window) = &status.maintenance {
+        ui::key_value(
+            "Maintenance",
+            &format!(
+                "{} - {}",
+                format_time(window.starts_at),
+                format_time(window.ends_at)
+            ),
+        );
+    }
+    if let Some(message) = &status.message {
+        ui::key_value("Message", message);
+    }
+    ui::key_value("Expires", &format_time(status.expires_at()));
+}
+
+/// Parse an RFC 3339 timestamp or unix seconds
+fn parse_time(value: &str) -> Result<i64> {
+    if let Ok(secs) = value.parse::<i64>() {
+        return Ok(secs);
+    }
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|time| time.timestamp())
+        .with_context(|| format!("Invalid time '{}': use RFC 3339 or unix seconds", value))
+}
+
+fn format_time(secs: i64) -> String {
+    chrono::DateTime::from_timestamp(secs, 0)
+        .map(|time| time.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|| secs.to_string())
+}
@@ -0,0 +1,173 @@
+//! Storage encryption commands
+//!
+//! Demo data (contacts, receipts) lives under `<storage_dir>/data`. Once
+//! encryption is enabled the key is unlocked at startup and every command
+//! opens storage through [`open`], so reads and writes are encrypted
+//! transparently.
+
+use anyhow::{Context, Result};
+use paykit_demo_core::{AesGcmCipher, DemoStorage, EncryptionConfig, KeySource, StorageCipher};
+use paykit_lib::secure_storage::DesktopKeyStorage;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+use crate::ui;
+
+/// Environment variable read before prompting for the storage passphrase
+const PASSPHRASE_ENV: &str = "PAYKIT_STORAGE_PASSPHRASE";
+
+/// Keyring namespace, shared with the secure identity manager
+const KEYRING_APP_ID: &str = "paykit-demo";
+
+static CIPHER: OnceLock<Arc<dyn StorageCipher>> = OnceLock::new();
+
+fn data_dir(storage_dir: &Path) -> PathBuf {
+    storage_dir.join("data")
+}
+
+/// Open demo storage, encrypted if the key was unlocked at startup
+pub fn open(storage_dir: &Path) -> DemoStorage {
+    let storage = DemoStorage::new(data_dir(storage_dir));
+    match CIPHER.get() {
+        Some(cipher) => storage.with_cipher(cipher.clone()),
+        None => storage,
+    }
+}
+
+/// Unlock the storage key if encryption is enabled
+pub async fn unlock(storage_dir: &Path) -> Result<()> {
+    let Some(config) = EncryptionConfig::load(&data_dir(storage_dir))? else {
+        return Ok(());
+    };
+    let cipher = load_cipher(&config, false).await?;
+    config.verify(&cipher)?;
+    let _ = CIPHER.set(Arc::new(cipher));
+    Ok(())
+}
+
+/// Show whether demo storage is encrypted
+pub async fn status(storage_dir: &Path, _verbose: bool) -> Result<()> {
+    ui::header("Storage Encryption");
+
+    let data_dir = data_dir(storage_dir);
+    let config = EncryptionConfig::load(&data_dir)?;
+    let status = DemoStorage::new(&data_dir).status()?;
+
+    ui::key_value("Location", &data_dir.display().to_string());
+    match &config {
+        Some(config) => {
+            ui::key_value("Encryption", "enabled (aes-256-gcm)");
+            ui::key_value("Key source", &config.key_source.to_string());
+            if let Some(enabled) = chrono::DateTime::from_timestamp(config.enabled_at, 0) {
+                ui::key_value("Enabled", &enabled.format("%Y-%m-%d %H:%M UTC").to_string());
+            }
+        }
+        None => ui::key_value("Encryption", "disabled"),
+    }
+    ui::key_value("Encrypted files", &status.encrypted_files.to_string());
+    ui::key_value("Plaintext files", &status.plaintext_files.to_string());
+
+    match (&config, status.fully_encrypted()) {
+        (None, _) => {
+            ui::separator();
+            ui::info("Run 'paykit-demo storage encrypt' to encrypt data at rest");
+        }
+        (Some(_), false) => {
+            ui::separator();
+            ui::warning("Some files are still plaintext; run 'paykit-demo storage encrypt'");
+        }
+        (Some(_), true) => {}
+    }
+    Ok(())
+}
+
+/// Enable encryption and migrate existing plaintext files
+pub async fn encrypt(storage_dir: &Path, passphrase: bool, verbose: bool) -> Result<()> {
+    ui::header("Encrypt Storage");
+
+    let data_dir = data_dir(storage_dir);
+    let (config, cipher) = match EncryptionConfig::load(&data_dir)? {
+        Some(config) => {
+            ui::info(&format!(
+                "Encryption already enabled ({})",
+                config.key_source
+            ));
+            let cipher = load_cipher(&config, false).await?;
+            config.verify(&cipher)?;
+            (config, cipher)
+        }
+        None => {
+            let config = if passphrase {
+                EncryptionConfig::passphrase()
+            } else {
+                EncryptionConfig::keyring()
+            };
+            let cipher = load_cipher(&config, true).await?;
+            (config.with_verifier(&cipher)?, cipher)
+        }
+    };
+    config.save(&data_dir)?;
+
+    let storage = DemoStorage::new(&data_dir).with_cipher(Arc::new(cipher));
+    let migrated = storage.encrypt_existing()?;
+
+    if verbose {
+        ui::key_value("Key source", &config.key_source.to_string());
+    }
+    ui::success(&format!(
+        "Storage encrypted ({} file(s) migrated)",
+        migrated
+    ));
+    if let KeySource::Passphrase { .. } = config.key_source {
+        ui::warning("Without the passphrase this data cannot be recovered.");
+        ui::info(&format!(
+            "Set {} to skip the prompt in scripts",
+            PASSPHRASE_ENV
+        ));
+    }
+    Ok(())
+}
+
+/// Build the cipher for a config, prompting for a passphrase if needed
+///
+/// With `create` set, a new keyring key is generated, or a new passphrase
+/// is prompted for twice.
+async fn load_cipher(config: &EncryptionConfig, create: bool) -> Result<AesGcmCipher> {
+    match &config.key_source {
+        KeySource::Keyring { .. } => {
+            let cipher = config
+                .cipher_from_keyring(&DesktopKeyStorage::new(KEYRING_APP_ID), create)
+                .await?;
+            if create {
+                // DesktopKeyStorage silently falls back to memory when the OS
+                // keyring is unavailable; make sure the key really persisted.
+                config
+                    .cipher_from_keyring(&DesktopKeyStorage::new(KEYRING_APP_ID), false)
+                    .await
+                    .context("OS keyring unavailable; use --passphrase instead")?;
+            }
+            Ok(cipher)
+        }
+        KeySource::Passphrase { .. } => {
+            let passphrase = read_passphrase(create)?;
+            config.cipher_from_passphrase(&passphrase)
+        }
+    }
+}
+
+fn read_passphrase(confirm: bool) -> Result<String> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+
+    let passphrase =
+        rpassword::prompt_password("Storage passphrase: ").context("Failed to read passphrase")?;
+    if confirm {
+        let again = rpassword::prompt_password("Confirm passphrase: ")
+            .context("Failed to read passphrase confirmation")?;
+        if passphrase != again {
+            anyhow::bail!("Passphrases do not match");
+        }
+    }
+    Ok(passphrase)
+}
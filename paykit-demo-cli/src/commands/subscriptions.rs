@@ -1,8 +1,13 @@
 //! Subscription and payment request commands
 
 use anyhow::{anyhow, Context, Result};
+use paykit_lib::audit::AuditOperation;
 use paykit_lib::{MethodId, PublicKey};
 use paykit_subscriptions::{
+    calendar::ChargeCalendar,
+    cancellation::{
+        CancelSubscription, CancellationReason, CancellationRecord, SignedCancellation,
+    },
     request::{PaymentRequest, RequestStatus},
     signing,
     storage::{Direction, FileSubscriptionStorage, RequestFilter, SubscriptionStorage},
@@ -11,7 +16,7 @@ use paykit_subscriptions::{
 };
 use std::{path::Path, str::FromStr};
 
-use crate::ui;
+use crate::{output, ui};
 
 /// Create subscription storage
 fn create_subscription_storage(storage_dir: &Path) -> Result<FileSubscriptionStorage> {
@@ -459,6 +464,73 @@ pub async fn accept_subscription(storage_dir: &Path, subscription_id: &str) -> R
     Ok(())
 }
 
+/// Cancel a subscription agreement
+///
+/// Signs a cancellation with a reason code and records it locally. The
+/// cancellation becomes final once the counterparty acknowledges it over
+/// Noise.
+pub async fn cancel_subscription(
+    storage_dir: &Path,
+    subscription_id: &str,
+    reason: &str,
+    note: Option<String>,
+) -> Result<()> {
+    let identity = super::load_current_identity(storage_dir).await?;
+    let storage = create_subscription_storage(storage_dir)?;
+
+    ui::header("Cancel Subscription");
+
+    let reason = CancellationReason::from_str(reason).map_err(|e| {
+        let codes: Vec<&str> = CancellationReason::ALL.iter().map(|r| r.as_str()).collect();
+        anyhow!("{} (expected one of: {})", e, codes.join(", "))
+    })?;
+
+    let signed_subscription = storage
+        .get_signed_subscription(subscription_id)
+        .await?
+        .ok_or_else(|| anyhow!("Subscription {} not found", subscription_id))?;
+
+    if let Some(existing) = storage.get_cancellation(subscription_id).await? {
+        ui::warning(&format!(
+            "Subscription {} was already cancelled ({})",
+            subscription_id, existing.cancellation.cancellation.reason
+        ));
+        return Ok(());
+    }
+
+    let mut cancellation = CancelSubscription::new(
+        &signed_subscription.subscription,
+        &identity.public_key(),
+        reason,
+    )?;
+    cancellation.note = note;
+    let signed = SignedCancellation::sign(cancellation, &identity.keypair)?;
+    let record = CancellationRecord::new(signed);
+    super::audit::record(
+        storage_dir,
+        AuditOperation::Sign,
+        Some(&record.cancellation.cancellation.counterparty.to_z32()),
+        Some(&format!("cancellation {}", subscription_id)),
+    );
+    storage.save_cancellation(&record).await?;
+
+    ui::key_value("Subscription ID", subscription_id);
+    ui::key_value(
+        "Counterparty",
+        &record.cancellation.cancellation.counterparty.to_z32(),
+    );
+    ui::key_value("Reason", reason.as_str());
+    if let Some(note) = &record.cancellation.cancellation.note {
+        ui::key_value("Note", note);
+    }
+
+    tracing::info!("Subscription cancelled: {} ({})", subscription_id, reason);
+    ui::success("Cancellation signed and recorded");
+    ui::info("Awaiting acknowledgment from the counterparty");
+
+    Ok(())
+}
+
 /// List subscription agreements
 pub async fn list_subscriptions(
     storage_dir: &Path,
@@ -479,6 +551,7 @@ pub async fn list_subscriptions(
         storage.list_active_subscriptions().await? // For now, same as active
     };
 
+    output::emit(&subscriptions);
     if subscriptions.is_empty() {
         ui::info("No subscription agreements found.");
         return Ok(());
@@ -528,6 +601,15 @@ pub async fn list_subscriptions(
     Ok(())
 }
 
+/// `data` of `subscriptions show --output json`
+#[derive(Serialize)]
+struct SubscriptionOutput<'a> {
+    subscription: &'a Subscription,
+    /// "proposed", "active", "expired", "pending_start" or "cancelled"
+    status: &'static str,
+    signed: bool,
+}
+
 /// Show subscription details
 pub async fn show_subscription(storage_dir: &Path, subscription_id: &str) -> Result<()> {
     let storage = create_subscription_storage(storage_dir)?;
@@ -576,13 +658,46 @@ pub async fn show_subscription(storage_dir: &Path, subscription_id: &str) -> Res
         ui::info("Signature Info:");
         ui::key_value("Signature Type", "Ed25519 (v0.2)");
 
-        if signed.is_active() {
+        let cancellation = storage.get_cancellation(subscription_id).await?;
+        output::emit(&SubscriptionOutput {
+            subscription,
+            status: if cancellation.is_some() {
+                "cancelled"
+            } else if signed.is_active() {
+                "active"
+            } else if signed.is_expired() {
+                "expired"
+            } else {
+                "pending_start"
+            },
+            signed: true,
+        });
+
+        if let Some(record) = cancellation {
+            let cancellation = &record.cancellation.cancellation;
+            ui::separator();
+            ui::key_value("Cancelled By", &cancellation.cancelled_by.to_z32());
+            ui::key_value("Reason", cancellation.reason.as_str());
+            if let Some(note) = &cancellation.note {
+                ui::key_value("Note", note);
+            }
+            if record.is_acknowledged() {
+                ui::warning("⚠ This subscription has been CANCELLED");
+            } else {
+                ui::warning("⚠ CANCELLED (awaiting acknowledgment)");
+            }
+        } else if signed.is_active() {
             ui::success("✓ This subscription is ACTIVE");
         } else if signed.is_expired() {
             ui::warning("⚠ This subscription has EXPIRED");
         }
     } else if let Some(subscription) = storage.get_subscription(subscription_id).await? {
         // Unsigned proposal
+        output::emit(&SubscriptionOutput {
+            subscription: &subscription,
+            status: "proposed",
+            signed: false,
+        });
         ui::warning("⚠ This is an unsigned proposal (not yet accepted)");
         ui::key_value("Subscription ID", &subscription.subscription_id);
         ui::key_value("Subscriber", &subscription.subscriber.to_z32());
@@ -1282,6 +1397,54 @@ pub async fn show_recent_autopayments(storage_dir: &Path, count: usize) -> Resul
     Ok(())
 }
 
+/// Show charges coming up on active subscriptions
+pub async fn show_upcoming_charges(storage_dir: &Path, days: u32) -> Result<()> {
+    let storage = create_subscription_storage(storage_dir)?;
+
+    ui::header(&format!("Upcoming Charges (next {} days)", days));
+
+    let mut calendar = ChargeCalendar::new(chrono::Utc::now().timestamp(), days)?;
+    for sub in storage.list_active_subscriptions().await? {
+        let id = &sub.subscription.subscription_id;
+        let rule = storage.get_autopay_rule(id).await?;
+        let modifications = storage.list_modifications(id).await?;
+        calendar.add_subscription(&sub.subscription, rule.as_ref(), &modifications);
+    }
+
+    if calendar.is_empty() {
+        ui::info("No charges due in this period.");
+        return Ok(());
+    }
+
+    for (day, charges) in calendar.by_day() {
+        ui::info(&day.format("%Y-%m-%d (%a)").to_string());
+        for charge in charges {
+            let id_short = &charge.subscription_id[..8.min(charge.subscription_id.len())];
+            let mut line = format!(
+                "  {} {}  {}  {}",
+                charge.amount, charge.currency, id_short, charge.description
+            );
+            if charge.modified {
+                line.push_str("  [modified]");
+            }
+            line.push_str(if charge.autopay {
+                "  [auto-pay]"
+            } else {
+                "  [manual]"
+            });
+            ui::line(&line);
+        }
+    }
+
+    ui::separator();
+    for (currency, total) in calendar.totals_by_currency() {
+        ui::key_value(&format!("Total {}", currency), &total.to_string());
+    }
+    ui::info(&format!("{} charges scheduled", calendar.charges.len()));
+
+    Ok(())
+}
+
 /// Calculate proration for subscription changes
 pub async fn calculate_proration(
     current_amount: i64,
@@ -0,0 +1,203 @@
+//! Treasury sweep commands
+//!
+//! Move funds that accumulate on the hot wallet to a cold storage address
+//! once they pass a threshold. The demo has no signing on-chain wallet, so
+//! sweeps run against a simulated wallet holding the `--balance` given.
+
+use anyhow::{Context, Result};
+use paykit_demo_core::TreasuryCoordinator;
+use paykit_lib::methods::{BitcoinExecutor, MockBitcoinExecutor};
+use paykit_lib::treasury::{
+    SweepEvent, SweepOutcome, SweepPolicy, SweepReceipt, SweepSchedule, TreasurySweeper,
+};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::ui;
+
+/// Show or update the sweep policy
+#[allow(clippy::too_many_arguments)]
+pub async fn policy(
+    storage_dir: &Path,
+    destination: Option<String>,
+    threshold: Option<u64>,
+    max_fee: Option<u64>,
+    reserve: Option<u64>,
+    target_blocks: Option<u32>,
+    every: Option<i64>,
+    _verbose: bool,
+) -> Result<()> {
+    ui::header("Treasury Sweep Policy");
+
+    let treasury = TreasuryCoordinator::new(storage_dir);
+    let current = treasury.policy()?;
+    if destination.is_none()
+        && threshold.is_none()
+        && max_fee.is_none()
+        && reserve.is_none()
+        && target_blocks.is_none()
+        && every.is_none()
+    {
+        match &current {
+            Some(policy) => show_policy(policy),
+            None => ui::info("Sweeps are disabled"),
+        }
+        return Ok(());
+    }
+
+    let destination = destination
+        .or(current.as_ref().map(|p| p.destination.clone()))
+        .context("--destination is required when enabling sweeps")?;
+    let threshold = threshold
+        .or(current.as_ref().map(|p| p.threshold_sats))
+        .context("--threshold is required when enabling sweeps")?;
+    let max_fee = max_fee
+        .or(current.as_ref().map(|p| p.max_fee_sats))
+        .context("--max-fee is required when enabling sweeps")?;
+
+    let mut new_policy = SweepPolicy::new(destination, threshold, max_fee).with_reserve_sats(
+        reserve
+            .or(current.as_ref().map(|p| p.reserve_sats))
+            .unwrap_or(0),
+    );
+    if let Some(blocks) = target_blocks.or(current.as_ref().map(|p| p.target_blocks)) {
+        new_policy = new_policy.with_target_blocks(blocks);
+    }
+    new_policy = match every {
+        Some(0) => new_policy.with_schedule(SweepSchedule::Manual),
+        Some(every_secs) => new_policy.with_schedule(SweepSchedule::Interval { every_secs }),
+        None => new_policy.with_schedule(current.map(|p| p.schedule).unwrap_or_default()),
+    };
+    treasury.set_policy(new_policy.clone())?;
+
+    ui::success("Sweep policy updated");
+    show_policy(&new_policy);
+    Ok(())
+}
+
+/// Turn sweeps off, keeping the history
+pub async fn disable(storage_dir: &Path, _verbose: bool) -> Result<()> {
+    TreasuryCoordinator::new(storage_dir).disable()?;
+    ui::success("Sweeps disabled");
+    Ok(())
+}
+
+/// Sweep once, if due (or immediately with `force`)
+pub async fn run(storage_dir: &Path, balance: u64, force: bool, verbose: bool) -> Result<()> {
+    ui::header("Treasury Sweep");
+
+    let treasury = TreasuryCoordinator::new(storage_dir);
+    let sweeper = load_sweeper(&treasury, balance, verbose)?;
+    treasury.run(&sweeper, force).await?;
+    Ok(())
+}
+
+/// Keep sweeping on schedule until interrupted
+pub async fn watch(storage_dir: &Path, balance: u64, poll: u64, verbose: bool) -> Result<()> {
+    ui::header("Treasury Sweep Watcher");
+
+    let treasury = TreasuryCoordinator::new(storage_dir);
+    let sweeper = load_sweeper(&treasury, balance, verbose)?;
+    if sweeper.policy().schedule == SweepSchedule::Manual {
+        anyhow::bail!("Sweeps are manual; set a schedule with 'sweep policy --every <secs>'");
+    }
+
+    ui::info(&format!("Checking every {}s, press Ctrl+C to stop", poll));
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(poll.max(1)));
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                // Failures are reported through the event callback; keep watching
+                if let Ok(SweepOutcome::Skipped(skip)) = treasury.run(&sweeper, false).await {
+                    if verbose {
+                        ui::info(&skip.describe());
+                    }
+                }
+            }
+            _ = tokio::signal::ctrl_c() => {
+                ui::info("Stopped");
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// List completed sweeps
+pub async fn history(storage_dir: &Path, verbose: bool) -> Result<()> {
+    ui::header("Sweep History");
+
+    let sweeps = TreasuryCoordinator::new(storage_dir).history()?;
+    if sweeps.is_empty() {
+        ui::info("No sweeps yet");
+        return Ok(());
+    }
+
+    for receipt in &sweeps {
+        show_receipt(receipt, verbose);
+        ui::separator();
+    }
+    let total: u64 = sweeps.iter().map(|r| r.amount_sats).sum();
+    ui::key_value("Total swept", &ui::sats(total));
+    Ok(())
+}
+
+fn load_sweeper(
+    treasury: &TreasuryCoordinator,
+    balance: u64,
+    verbose: bool,
+) -> Result<TreasurySweeper> {
+    let wallet: Arc<dyn BitcoinExecutor> = Arc::new(MockBitcoinExecutor::with_balance(balance));
+    let sweeper = treasury
+        .sweeper(wallet)?
+        .context("Sweeps are disabled; configure them with 'sweep policy'")?;
+
+    sweeper.on_event(Arc::new(move |event| match event {
+        SweepEvent::Swept(receipt) => {
+            ui::success(&format!("Swept {}", ui::sats(receipt.amount_sats)));
+            show_receipt(receipt, verbose);
+        }
+        SweepEvent::Skipped(skip) => ui::info(&format!("Sweep skipped: {}", skip.describe())),
+        SweepEvent::Failed { error } => ui::error(&format!("Sweep failed: {}", error)),
+        SweepEvent::FeeCeilingExceeded {
+            txid,
+            fee_sats,
+            max_fee_sats,
+        } => ui::warning(&format!(
+            "Sweep {} paid {}, above the {} fee ceiling",
+            txid,
+            ui::sats(*fee_sats),
+            ui::sats(*max_fee_sats)
+        )),
+    }));
+    Ok(sweeper)
+}
+
+fn show_policy(policy: &SweepPolicy) {
+    ui::key_value("Destination", &policy.destination);
+    ui::key_value("Threshold", &ui::sats(policy.threshold_sats));
+    ui::key_value("Reserve", &ui::sats(policy.reserve_sats));
+    ui::key_value("Fee ceiling", &ui::sats(policy.max_fee_sats));
+    ui::key_value("Target", &format!("{} blocks", policy.target_blocks));
+    let schedule = match &policy.schedule {
+        SweepSchedule::Manual => "manual".to_string(),
+        SweepSchedule::Interval { every_secs } => format!("every {} seconds", every_secs),
+    };
+    ui::key_value("Schedule", &schedule);
+}
+
+fn show_receipt(receipt: &SweepReceipt, verbose: bool) {
+    ui::key_value("Sweep", &receipt.sweep_id);
+    ui::key_value(
+        "Swept at",
+        &chrono::DateTime::from_timestamp(receipt.swept_at, 0)
+            .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "unknown".to_string()),
+    );
+    ui::key_value("Amount", &ui::sats(receipt.amount_sats));
+    ui::key_value("Fee", &ui::sats(receipt.fee_sats));
+    ui::key_value("Destination", &receipt.destination);
+    if verbose {
+        ui::key_value("Txid", &receipt.txid);
+        ui::key_value("Balance before", &ui::sats(receipt.balance_before_sats));
+    }
+}
@@ -1,7 +1,7 @@
 //! Switch command - change active identity
 
 use anyhow::Result;
-use paykit_demo_core::{IdentityManager, SecureIdentityManager};
+use paykit_demo_core::SecureIdentityManager;
 use std::path::Path;
 
 use crate::ui;
@@ -12,13 +12,11 @@ pub async fn run(storage_dir: &Path, name: &str, _verbose: bool) -> Result<()> {
     let using_secure_storage = metadata_path.exists();
 
     // Verify identity exists
-    let identity = if using_secure_storage {
-        let secure_manager = SecureIdentityManager::new(storage_dir);
+    let secure_manager = SecureIdentityManager::new(storage_dir);
+    let identity = if using_secure_storage && secure_manager.list()?.contains(&name.to_string()) {
         secure_manager.load(name).await?
     } else {
-        let identities_dir = storage_dir.join("identities");
-        let identity_manager = IdentityManager::new(&identities_dir);
-        identity_manager.load(name)?
+        super::file_identity_manager(storage_dir, name)?.load(name)?
     };
 
     // Set as current
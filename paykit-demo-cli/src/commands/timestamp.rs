@@ -0,0 +1,272 @@
+//! Receipt timestamp commands
+//!
+//! Anchors receipt digests in Bitcoin through OpenTimestamps calendars for
+//! long-term dispute evidence; see `paykit_demo_core::timestamping`.
+//! `submit` and `upgrade` are meant to run periodically (e.g. from cron):
+//! the first batches queued receipts, the second completes proofs once the
+//! calendars' transactions confirm.
+
+use anyhow::{anyhow, Context, Result};
+use paykit_demo_core::{DemoStorage, TimestampCoordinator, TimestampRecord};
+use paykit_interactive::ots::Calendar;
+use paykit_interactive::PaykitReceipt;
+use paykit_lib::executors::{CalendarClient, EsploraConfig, EsploraExecutor};
+use std::path::Path;
+
+use crate::{output, ui};
+
+/// Queue a saved receipt if timestamping is enabled
+///
+/// Timestamping must never block saving a receipt, so failures are only
+/// reported as warnings.
+pub fn record(storage_dir: &Path, receipt_id: &str, digest: &str) {
+    let queued = TimestampCoordinator::new(storage_dir).record(receipt_id, digest, now());
+    if let Err(e) = queued {
+        ui::warning(&format!(
+            "Could not queue receipt for timestamping: {:#}",
+            e
+        ));
+    }
+}
+
+/// Start queueing receipts as they are saved
+pub async fn enable(storage_dir: &Path, calendars: Vec<String>) -> Result<()> {
+    let timestamps = TimestampCoordinator::new(storage_dir);
+    timestamps.enable(calendars)?;
+
+    ui::success("Receipt timestamping enabled");
+    for calendar in timestamps.state()?.settings.calendars {
+        ui::key_value("Calendar", &calendar);
+    }
+    ui::info("Run `timestamp submit` periodically to send queued receipts");
+    Ok(())
+}
+
+/// Stop queueing receipts
+pub async fn disable(storage_dir: &Path) -> Result<()> {
+    TimestampCoordinator::new(storage_dir).disable()?;
+    ui::success("Receipt timestamping disabled; existing proofs are kept");
+    Ok(())
+}
+
+/// Queue stored receipts by ID, or all receipts without a proof
+pub async fn queue(storage_dir: &Path, receipt_ids: Vec<String>, all: bool) -> Result<()> {
+    let storage = super::storage::open(storage_dir);
+    let timestamps = TimestampCoordinator::new(storage_dir);
+    let receipt_ids = if all {
+        let proved = timestamps.state()?.proofs;
+        stored_receipt_ids(&storage)?
+            .into_iter()
+            .filter(|id| !proved.contains_key(id))
+            .collect()
+    } else {
+        receipt_ids
+    };
+    if receipt_ids.is_empty() {
+        ui::info("No receipts to queue");
+        return Ok(());
+    }
+
+    let mut queued = 0;
+    for receipt_id in &receipt_ids {
+        let digest = receipt_digest(&storage, receipt_id)?;
+        if timestamps.queue(receipt_id, &digest, now())? {
+            queued += 1;
+        }
+    }
+    ui::success(&format!("Queued {} receipt(s)", queued));
+    Ok(())
+}
+
+/// Submit queued receipts to the calendars as one batch
+pub async fn submit(storage_dir: &Path) -> Result<()> {
+    let timestamps = TimestampCoordinator::new(storage_dir);
+    let clients = calendars(&timestamps)?;
+    let calendars: Vec<&dyn Calendar> = clients.iter().map(|c| c as &dyn Calendar).collect();
+
+    let spinner = ui::spinner("Submitting to calendars");
+    let submitted = timestamps.submit(&calendars, now()).await;
+    spinner.finish_and_clear();
+    let submitted = submitted?;
+
+    output::emit(&submitted);
+    if submitted.is_empty() {
+        ui::info("No receipts queued");
+    } else {
+        ui::success(&format!(
+            "Submitted {} receipt(s); proofs complete after the next Bitcoin confirmations",
+            submitted.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Complete pending proofs with their Bitcoin attestations
+pub async fn upgrade(storage_dir: &Path) -> Result<()> {
+    let timestamps = TimestampCoordinator::new(storage_dir);
+    let clients = calendars(&timestamps)?;
+    let calendars: Vec<&dyn Calendar> = clients.iter().map(|c| c as &dyn Calendar).collect();
+
+    let spinner = ui::spinner("Checking calendars");
+    let completed = timestamps.upgrade(&calendars).await;
+    spinner.finish_and_clear();
+    let completed = completed?;
+
+    output::emit(&completed);
+    for receipt_id in &completed {
+        ui::success(&format!("Receipt {} is anchored in Bitcoin", receipt_id));
+    }
+    let waiting = timestamps
+        .state()?
+        .proofs
+        .values()
+        .filter(|record| !record.is_complete())
+        .count();
+    if waiting > 0 {
+        ui::info(&format!(
+            "{} proof(s) still waiting for confirmation",
+            waiting
+        ));
+    }
+    Ok(())
+}
+
+/// Check a receipt's proof against the Bitcoin chain
+pub async fn verify(storage_dir: &Path, receipt_id: &str, esplora: Option<&str>) -> Result<()> {
+    ui::header("Verify Receipt Timestamp");
+
+    let storage = super::storage::open(storage_dir);
+    let digest = receipt_digest(&storage, receipt_id)?;
+    let headers = esplora_executor(storage_dir, esplora)?;
+
+    let spinner = ui::spinner("Checking block headers");
+    let verified = TimestampCoordinator::new(storage_dir)
+        .verify(receipt_id, &digest, &headers, now())
+        .await;
+    spinner.finish_and_clear();
+    let verified = verified?;
+
+    output::emit(&verified);
+    ui::success(&format!(
+        "Receipt {} existed before block {}",
+        receipt_id, verified.block_height
+    ));
+    ui::key_value("Block time", &format_time(verified.block_time as i64));
+    ui::key_value("Digest", &verified.digest);
+    if !verified.receipt_unchanged {
+        ui::warning("The stored receipt has changed since it was timestamped");
+    }
+    Ok(())
+}
+
+/// Show settings, the queue and the state of each proof
+pub async fn status(storage_dir: &Path) -> Result<()> {
+    ui::header("Receipt Timestamps");
+
+    let state = TimestampCoordinator::new(storage_dir).state()?;
+    output::emit(&state);
+    ui::key_value(
+        "Status",
+        if state.settings.enabled {
+            "enabled"
+        } else {
+            "disabled"
+        },
+    );
+    ui::key_value("Calendars", &state.settings.calendars.join(", "));
+    ui::key_value("Queued", &state.pending.len().to_string());
+    ui::separator();
+
+    if state.proofs.is_empty() {
+        ui::info("No timestamp proofs");
+        return Ok(());
+    }
+    for (receipt_id, record) in &state.proofs {
+        ui::key_value(receipt_id, &describe(record));
+    }
+    Ok(())
+}
+
+/// Write a receipt's `.ots` proof to a file
+pub async fn export(storage_dir: &Path, receipt_id: &str, output: Option<&str>) -> Result<()> {
+    let bytes = TimestampCoordinator::new(storage_dir)
+        .proof_bytes(receipt_id)?
+        .ok_or_else(|| anyhow!("Receipt {} has no timestamp proof", receipt_id))?;
+    let path = output
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{}.ots", receipt_id));
+    std::fs::write(&path, bytes).with_context(|| format!("Failed to write {}", path))?;
+    ui::success(&format!("Wrote {}", path));
+    Ok(())
+}
+
+/// Digest of a stored receipt, preferring the form the payee confirmed
+fn receipt_digest(storage: &DemoStorage, receipt_id: &str) -> Result<String> {
+    if let Some(json) = storage.get_receipt_json(receipt_id)? {
+        let receipt: PaykitReceipt =
+            serde_json::from_str(&json).context("Failed to parse receipt")?;
+        return Ok(receipt.digest());
+    }
+    storage
+        .get_receipt(receipt_id)?
+        .map(|receipt| receipt.digest())
+        .ok_or_else(|| anyhow!("Receipt not found: {}", receipt_id))
+}
+
+fn stored_receipt_ids(storage: &DemoStorage) -> Result<Vec<String>> {
+    let mut ids: Vec<String> = storage
+        .list_receipt_jsons()?
+        .iter()
+        .filter_map(|json| serde_json::from_str::<PaykitReceipt>(json).ok())
+        .map(|receipt| receipt.receipt_id)
+        .chain(
+            storage
+                .list_receipts()?
+                .into_iter()
+                .map(|receipt| receipt.id),
+        )
+        .collect();
+    ids.sort();
+    ids.dedup();
+    Ok(ids)
+}
+
+fn calendars(timestamps: &TimestampCoordinator) -> Result<Vec<CalendarClient>> {
+    timestamps
+        .state()?
+        .settings
+        .calendars
+        .iter()
+        .map(|url| CalendarClient::new(url.as_str()).map_err(anyhow::Error::from))
+        .collect()
+}
+
+fn esplora_executor(storage_dir: &Path, url: Option<&str>) -> Result<EsploraExecutor> {
+    let configured = super::wallet::WalletConfig::load(storage_dir)?
+        .and_then(|config| config.esplora)
+        .map(|esplora| esplora.url);
+    let config = match url.map(str::to_string).or(configured) {
+        Some(url) => EsploraConfig::new(url),
+        None => EsploraConfig::blockstream_mainnet(),
+    }
+    .with_proxy(super::proxy_for(paykit_lib::proxy::TransportKind::Esplora));
+    Ok(EsploraExecutor::new(config)?)
+}
+
+fn describe(record: &TimestampRecord) -> String {
+    match (record.block_height, record.verified_at) {
+        (Some(height), Some(_)) => format!("verified in block {}", height),
+        (Some(height), None) => format!("anchored in block {}", height),
+        (None, _) => format!("pending since {}", format_time(record.submitted_at)),
+    }
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+fn format_time(secs: i64) -> String {
+    chrono::DateTime::from_timestamp(secs, 0)
+        .map(|time| time.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|| secs.to_string())
+}
@@ -0,0 +1,255 @@
+//! Payee trust policy commands
+//!
+//! Manage the allowlist/blocklist that `pay` consults before sending money:
+//! blocked keys are refused and unknown keys require confirmation. Large
+//! payments can additionally be limited to contacts verified with
+//! `contacts verify`.
+
+use anyhow::{Context, Result};
+use paykit_lib::policy::{TrustDecision, TrustLevel, TrustPolicy};
+use std::path::Path;
+
+use crate::ui;
+
+/// List all trusted and blocked keys
+pub async fn list(storage_dir: &Path, verbose: bool) -> Result<()> {
+    ui::header("Payee Trust Policy");
+
+    let policy = load_policy(storage_dir)?;
+    ui::key_value(
+        "Unknown payees",
+        if policy.confirm_unknown() {
+            "require confirmation"
+        } else {
+            "allowed"
+        },
+    );
+    if let Some(threshold) = policy.require_verified_above() {
+        ui::key_value("Verified contact required above", &ui::sats(threshold));
+    }
+    ui::separator();
+
+    let entries = policy.entries();
+    if entries.is_empty() {
+        ui::info("No trusted or blocked keys");
+        return Ok(());
+    }
+
+    for entry in entries {
+        let label = match entry.level {
+            TrustLevel::Trusted => "trusted",
+            TrustLevel::Blocked => "BLOCKED",
+        };
+        let line = match &entry.reason {
+            Some(reason) => format!("{} ({})", label, reason),
+            None => label.to_string(),
+        };
+        ui::key_value(&format!("  {}", entry.public_key), &line);
+        if verbose {
+            ui::info(&format!(
+                "    updated {}",
+                chrono::DateTime::from_timestamp(entry.updated_at, 0)
+                    .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_else(|| "unknown".to_string())
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Mark a key or contact as trusted or blocked
+pub async fn set(
+    storage_dir: &Path,
+    key: &str,
+    level: TrustLevel,
+    reason: Option<String>,
+    _verbose: bool,
+) -> Result<()> {
+    let public_key = resolve_key(storage_dir, key)?;
+    let mut policy = load_policy(storage_dir)?;
+    let entry = policy.set(&public_key, level, reason.as_deref()).clone();
+    save_policy(storage_dir, &policy)?;
+
+    match level {
+        TrustLevel::Trusted => ui::success(&format!("Trusted {}", entry.public_key)),
+        TrustLevel::Blocked => ui::success(&format!("Blocked {}", entry.public_key)),
+    }
+    if let Some(reason) = &entry.reason {
+        ui::key_value("Reason", reason);
+    }
+
+    Ok(())
+}
+
+/// Remove a key from the policy
+pub async fn remove(storage_dir: &Path, key: &str, _verbose: bool) -> Result<()> {
+    let public_key = resolve_key(storage_dir, key)?;
+    let mut policy = load_policy(storage_dir)?;
+
+    match policy.remove(&public_key) {
+        Some(entry) => {
+            save_policy(storage_dir, &policy)?;
+            ui::success(&format!("Removed {}", entry.public_key));
+        }
+        None => ui::warning(&format!("{} is not in the trust policy", key)),
+    }
+
+    Ok(())
+}
+
+/// Show how a payment to a key would be treated
+pub async fn check(storage_dir: &Path, key: &str, _verbose: bool) -> Result<()> {
+    let public_key = resolve_key(storage_dir, key)?;
+    let policy = load_policy(storage_dir)?;
+
+    match policy.evaluate_key(&public_key) {
+        TrustDecision::Allow => ui::success("Allowed"),
+        TrustDecision::RequireConfirmation => {
+            ui::warning("Unknown payee - payments require confirmation")
+        }
+        TrustDecision::Block { reason } => ui::error(&format!(
+            "Blocked{}",
+            reason.map(|r| format!(": {}", r)).unwrap_or_default()
+        )),
+    }
+
+    Ok(())
+}
+
+/// Enable or disable confirmation for unknown payees
+pub async fn confirm_unknown(storage_dir: &Path, enable: bool, _verbose: bool) -> Result<()> {
+    let mut policy = load_policy(storage_dir)?;
+    policy.set_confirm_unknown(enable);
+    save_policy(storage_dir, &policy)?;
+
+    if enable {
+        ui::success("Unknown payees now require confirmation");
+    } else {
+        ui::success("Unknown payees no longer require confirmation");
+    }
+
+    Ok(())
+}
+
+/// Require verified contacts for payments above an amount
+pub async fn require_verified(
+    storage_dir: &Path,
+    above: Option<u64>,
+    _verbose: bool,
+) -> Result<()> {
+    let mut policy = load_policy(storage_dir)?;
+    policy.set_require_verified_above(above);
+    save_policy(storage_dir, &policy)?;
+
+    match above {
+        Some(threshold) => ui::success(&format!(
+            "Payments above {} now require a verified contact",
+            ui::sats(threshold)
+        )),
+        None => ui::success("Payments no longer require verified contacts"),
+    }
+
+    Ok(())
+}
+
+/// Enforce the trust policy before paying `payee_uri`.
+///
+/// Returns `Ok(false)` when the user declines an unknown payee.
+pub(crate) fn enforce(storage_dir: &Path, payee_uri: &str, dry_run: bool) -> Result<bool> {
+    let policy = load_policy(storage_dir)?;
+
+    match policy.evaluate_key(payee_uri) {
+        TrustDecision::Allow => Ok(true),
+        TrustDecision::Block { reason } => anyhow::bail!(
+            "Payee is blocked{}",
+            reason.map(|r| format!(": {}", r)).unwrap_or_default()
+        ),
+        TrustDecision::RequireConfirmation if dry_run => {
+            ui::warning("Unknown payee - a real payment would require confirmation");
+            Ok(true)
+        }
+        TrustDecision::RequireConfirmation => {
+            ui::warning("You have not paid or trusted this payee before");
+            ui::confirm("Continue with payment?", false)
+        }
+    }
+}
+
+/// Refuse large payments to contacts that have not been verified.
+///
+/// An amount that cannot be read as sats counts as large.
+pub(crate) fn enforce_verified(
+    storage_dir: &Path,
+    payee_uri: &str,
+    amount: Option<&str>,
+    currency: Option<&str>,
+    dry_run: bool,
+) -> Result<()> {
+    let policy = load_policy(storage_dir)?;
+    let Some(threshold) = policy.require_verified_above() else {
+        return Ok(());
+    };
+
+    let payee_key = payee_uri.strip_prefix("pubky://").unwrap_or(payee_uri);
+    let storage = super::storage::open(storage_dir);
+    let verified = storage
+        .list_contacts()?
+        .iter()
+        .any(|c| c.public_key.to_string() == payee_key && c.is_verified());
+
+    let result = match super::amount_sats(amount, currency) {
+        Some(amount_sats) => policy
+            .check_verified_payment(payee_key, amount_sats, verified)
+            .map_err(anyhow::Error::from),
+        None if verified => Ok(()),
+        None => Err(anyhow::anyhow!(
+            "Amount is not in sats, so the {} verification limit applies; {} is not verified",
+            ui::sats(threshold),
+            payee_key
+        )),
+    };
+
+    match result {
+        Err(e) if dry_run => {
+            ui::warning(&format!("{} - a real payment would be refused", e));
+            Ok(())
+        }
+        Err(e) => {
+            ui::info("Verify the contact with 'paykit-demo contacts verify <name>'");
+            Err(e)
+        }
+        Ok(()) => Ok(()),
+    }
+}
+
+/// Load the trust policy, or an empty one if none is saved.
+pub(crate) fn load_policy(storage_dir: &Path) -> Result<TrustPolicy> {
+    let path = storage_dir.join("trust_policy.json");
+    if !path.exists() {
+        return Ok(TrustPolicy::new());
+    }
+
+    let contents = std::fs::read_to_string(&path).context("Failed to read trust policy")?;
+    serde_json::from_str(&contents).context("Failed to parse trust policy")
+}
+
+/// Persist the trust policy.
+pub(crate) fn save_policy(storage_dir: &Path, policy: &TrustPolicy) -> Result<()> {
+    std::fs::create_dir_all(storage_dir)?;
+    let path = storage_dir.join("trust_policy.json");
+    let contents =
+        serde_json::to_string_pretty(policy).context("Failed to serialize trust policy")?;
+    std::fs::write(&path, contents).context("Failed to save trust policy")
+}
+
+/// Resolve a contact name to its public key; other input is used as-is.
+fn resolve_key(storage_dir: &Path, key: &str) -> Result<String> {
+    let storage = super::storage::open(storage_dir);
+    if let Ok(contacts) = storage.list_contacts() {
+        if let Some(contact) = contacts.iter().find(|c| c.name == key) {
+            return Ok(contact.pubky_uri());
+        }
+    }
+    Ok(key.to_string())
+}
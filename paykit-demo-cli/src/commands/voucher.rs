@@ -0,0 +1,248 @@
+//! Voucher commands
+//!
+//! Gift codes signed by the issuer and bound to an amount and, optionally,
+//! a method. The holder checks a code with `voucher show` and hands it back
+//! to the issuer, whose `voucher redeem` pays the holder's published
+//! endpoint from the issuer's wallet. A code is paid out at most once; a
+//! payout that may have gone out keeps the code claimed until the issuer
+//! checks the wallet and runs `voucher release`.
+
+use anyhow::{anyhow, Context, Result};
+use paykit_demo_core::voucher::verify_code;
+use paykit_demo_core::VoucherCoordinator;
+use paykit_lib::methods::default_registry;
+use paykit_lib::vouchers::{Voucher, VoucherRecord, VoucherRedeemer, VoucherStatus};
+use paykit_lib::MethodId;
+use std::path::Path;
+
+use crate::ui;
+
+/// Sign a new voucher and print its code
+#[tracing::instrument(skip(storage_dir, memo))]
+pub async fn issue(
+    storage_dir: &Path,
+    id: &str,
+    amount: u64,
+    method: Option<String>,
+    memo: Option<String>,
+    expires_in: u64,
+    verbose: bool,
+) -> Result<()> {
+    ui::header("New Voucher");
+
+    let identity = super::load_current_identity(storage_dir).await?;
+    let now = now();
+    let mut voucher = Voucher::new(id, identity.public_key(), amount, now);
+    if let Some(method) = method {
+        voucher = voucher.with_method(MethodId::new(method));
+    }
+    if let Some(memo) = memo {
+        voucher = voucher.with_memo(memo);
+    }
+    if expires_in > 0 {
+        voucher = voucher.with_expires_at(now + expires_in as i64);
+    }
+    let signed = voucher.sign(&identity.keypair)?;
+
+    let vouchers = VoucherCoordinator::new(storage_dir);
+    vouchers.issue(&signed)?;
+    let record = vouchers
+        .get(id)?
+        .ok_or_else(|| anyhow!("Voucher not found: {}", id))?;
+    show_record(&record, now);
+
+    let code = signed.to_code();
+    ui::success("Voucher issued");
+    ui::key_value("Code", &code);
+    if verbose {
+        ui::qr_code(&code)?;
+    }
+    ui::info("The holder redeems it by sending you the code and their public key");
+    Ok(())
+}
+
+/// List issued vouchers
+pub async fn list(storage_dir: &Path, _verbose: bool) -> Result<()> {
+    ui::header("Vouchers");
+
+    let vouchers = VoucherCoordinator::new(storage_dir).list()?;
+    if vouchers.is_empty() {
+        ui::info("No vouchers issued");
+        return Ok(());
+    }
+
+    let now = now();
+    for record in &vouchers {
+        show_record(record, now);
+        ui::separator();
+    }
+    Ok(())
+}
+
+/// Show the status of an issued voucher
+pub async fn status(storage_dir: &Path, id: &str, _verbose: bool) -> Result<()> {
+    ui::header("Voucher Status");
+
+    let record = VoucherCoordinator::new(storage_dir)
+        .get(id)?
+        .ok_or_else(|| anyhow!("Voucher not found: {}", id))?;
+    show_record(&record, now());
+    match &record.status {
+        VoucherStatus::Redeeming {
+            redeemer,
+            claimed_at,
+        } => {
+            ui::key_value("Claimed by", redeemer);
+            ui::key_value("Claimed", &format_time(*claimed_at));
+        }
+        VoucherStatus::Redeemed {
+            redeemer,
+            redeemed_at,
+            method_id,
+            ..
+        } => {
+            ui::key_value("Redeemed by", redeemer);
+            ui::key_value("Redeemed", &format_time(*redeemed_at));
+            ui::key_value("Paid via", &method_id.0);
+        }
+        VoucherStatus::Revoked { revoked_at } => {
+            ui::key_value("Revoked", &format_time(*revoked_at));
+        }
+        VoucherStatus::Issued => {}
+    }
+    Ok(())
+}
+
+/// Check a voucher code and show what it is worth
+#[tracing::instrument(skip(code))]
+pub async fn show(code: &str) -> Result<()> {
+    ui::header("Voucher");
+
+    let signed = verify_code(code)?;
+    let voucher = signed.voucher();
+    ui::key_value("Issuer", &format!("pubky://{}", voucher.issuer));
+    ui::key_value("ID", &voucher.voucher_id);
+    ui::key_value("Amount", &ui::sats(voucher.amount_sats));
+    if let Some(method) = &voucher.method {
+        ui::key_value("Paid via", &method.0);
+    }
+    if let Some(memo) = &voucher.memo {
+        ui::key_value("Message", memo);
+    }
+    match voucher.expires_at {
+        Some(expires_at) if voucher.is_expired_at(now()) => {
+            ui::warning(&format!("Expired {}", format_time(expires_at)))
+        }
+        Some(expires_at) => ui::key_value("Expires", &format_time(expires_at)),
+        None => ui::key_value("Expires", "never"),
+    }
+    ui::success("Signature valid");
+    ui::info("Send the code and your public key to the issuer to redeem it");
+    Ok(())
+}
+
+/// Pay out a voucher to its holder
+#[tracing::instrument(skip(storage_dir, code))]
+pub async fn redeem(storage_dir: &Path, code: &str, holder: &str, _verbose: bool) -> Result<()> {
+    ui::header("Redeem Voucher");
+
+    let identity = super::load_current_identity(storage_dir).await?;
+    let signed = verify_code(code)?;
+    if signed.voucher().issuer != identity.public_key() {
+        return Err(anyhow!(
+            "Voucher was issued by pubky://{}, not the current identity",
+            signed.voucher().issuer
+        ));
+    }
+
+    let holder = resolve_holder(storage_dir, holder)?;
+    let holder: paykit_lib::PublicKey = holder
+        .parse()
+        .map_err(|e| anyhow!("Invalid holder public key: {}", e))?;
+    let public_storage = pubky::PublicStorage::new().context("Failed to create PublicStorage")?;
+    let transport = paykit_lib::PubkyUnauthenticatedTransport::new(public_storage)
+        .with_path_roots(super::path_roots());
+    let spinner = ui::spinner("Fetching holder's payment methods...");
+    let supported = paykit_lib::get_payment_list(&transport, &holder).await;
+    spinner.finish_and_clear();
+    let supported = supported.context("Failed to fetch holder's payment methods")?;
+
+    let storage = super::storage::open(storage_dir);
+    storage.init()?;
+    let redeemer = VoucherRedeemer::new(default_registry());
+    let execution = VoucherCoordinator::new(storage_dir)
+        .redeem(&redeemer, code, &holder, &supported, &storage, now())
+        .await?;
+
+    if !execution.success {
+        return Err(anyhow!(
+            "Payout failed: {}",
+            execution.error.unwrap_or_default()
+        ));
+    }
+    ui::success(&format!(
+        "Paid {} to pubky://{} via {}",
+        ui::sats(signed.voucher().amount_sats),
+        holder,
+        execution.method_id.0
+    ));
+    Ok(())
+}
+
+/// Release a voucher left claimed by a payout that may not have gone out
+pub async fn release(storage_dir: &Path, id: &str, _verbose: bool) -> Result<()> {
+    if !VoucherCoordinator::new(storage_dir).release(id)? {
+        ui::info(&format!("Voucher {} is not being redeemed", id));
+        return Ok(());
+    }
+    ui::success(&format!("Released voucher {}", id));
+    ui::warning("If the payout went out, the code can now be paid out again");
+    Ok(())
+}
+
+/// Revoke an unredeemed voucher
+pub async fn revoke(storage_dir: &Path, id: &str, _verbose: bool) -> Result<()> {
+    VoucherCoordinator::new(storage_dir).revoke(id, now())?;
+    ui::success(&format!("Revoked voucher {}", id));
+    Ok(())
+}
+
+/// Accept a contact name, a pubky URI or a bare public key
+fn resolve_holder(storage_dir: &Path, holder: &str) -> Result<String> {
+    if let Some(key) = holder.strip_prefix("pubky://") {
+        return Ok(key.to_string());
+    }
+    let storage = super::storage::open(storage_dir);
+    Ok(storage
+        .list_contacts()?
+        .into_iter()
+        .find(|contact| contact.name == holder)
+        .map(|contact| contact.public_key.to_string())
+        .unwrap_or_else(|| holder.to_string()))
+}
+
+fn show_record(record: &VoucherRecord, now: i64) {
+    ui::key_value("ID", &record.voucher_id);
+    ui::key_value("State", record.state(now).as_str());
+    ui::key_value("Amount", &ui::sats(record.amount_sats));
+    if let Some(method) = &record.method {
+        ui::key_value("Paid via", &method.0);
+    }
+    if let Some(memo) = &record.memo {
+        ui::key_value("Message", memo);
+    }
+    match record.expires_at {
+        Some(expires_at) => ui::key_value("Expires", &format_time(expires_at)),
+        None => ui::key_value("Expires", "never"),
+    }
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+fn format_time(secs: i64) -> String {
+    chrono::DateTime::from_timestamp(secs, 0)
+        .map(|time| time.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|| secs.to_string())
+}
@@ -1,89 +1,18 @@
 //! Wallet configuration commands
 //!
-//! Configure payment execution backends (LND, Esplora) for real payments.
+//! Configure payment execution backends (LND, CLN, Esplora) for real payments,
+//! and a BTCPay Server store for receiving them.
 
-use anyhow::{Context, Result};
-use serde::{Deserialize, Serialize};
+use anyhow::Result;
+use serde::Serialize;
 use std::path::Path;
 
-use crate::ui;
-
-/// Wallet configuration stored on disk
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct WalletConfig {
-    /// LND configuration (optional)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub lnd: Option<LndConfig>,
-    /// Esplora configuration (optional)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub esplora: Option<EsploraConfig>,
-    /// Network (mainnet, testnet, signet, regtest)
-    #[serde(default = "default_network")]
-    pub network: String,
-}
+use crate::{output, ui};
 
-fn default_network() -> String {
-    "testnet".to_string()
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LndConfig {
-    /// REST API URL
-    pub url: String,
-    /// Macaroon in hex format
-    pub macaroon: String,
-    /// TLS certificate (optional, PEM format)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub tls_cert: Option<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct EsploraConfig {
-    /// API base URL
-    pub url: String,
-}
-
-impl WalletConfig {
-    /// Load wallet configuration from disk
-    pub fn load(storage_dir: &Path) -> Result<Option<Self>> {
-        let config_path = storage_dir.join("wallet.json");
-        if !config_path.exists() {
-            return Ok(None);
-        }
-
-        let contents =
-            std::fs::read_to_string(&config_path).context("Failed to read wallet configuration")?;
-        let config: Self =
-            serde_json::from_str(&contents).context("Failed to parse wallet configuration")?;
-        Ok(Some(config))
-    }
-
-    /// Save wallet configuration to disk
-    pub fn save(&self, storage_dir: &Path) -> Result<()> {
-        std::fs::create_dir_all(storage_dir)?;
-        let config_path = storage_dir.join("wallet.json");
-        let contents =
-            serde_json::to_string_pretty(self).context("Failed to serialize wallet config")?;
-        std::fs::write(&config_path, contents).context("Failed to write wallet configuration")?;
-        Ok(())
-    }
-
-    /// Check if any executor is configured
-    #[allow(dead_code)]
-    pub fn is_configured(&self) -> bool {
-        self.lnd.is_some() || self.esplora.is_some()
-    }
-
-    /// Check if Lightning payments are configured
-    pub fn has_lightning(&self) -> bool {
-        self.lnd.is_some()
-    }
-
-    /// Check if on-chain payments are configured
-    pub fn has_onchain(&self) -> bool {
-        self.esplora.is_some()
-    }
-}
+pub use paykit_demo_core::wallet_profile::{
+    BtcPayConfig, ClnConfig, EsploraConfig, LndConfig, WalletConfig,
+};
+use paykit_demo_core::WalletProfiles;
 
 /// Configure LND for Lightning payments
 pub async fn configure_lnd(
@@ -144,10 +73,54 @@ pub async fn configure_lnd(
     Ok(())
 }
 
+/// Configure Core Lightning for Lightning payments
+pub async fn configure_cln(
+    storage_dir: &Path,
+    url: &str,
+    rune: &str,
+    tls_cert: Option<&str>,
+    network: Option<&str>,
+    _verbose: bool,
+) -> Result<()> {
+    ui::header("Configure Core Lightning");
+
+    if url.is_empty() {
+        anyhow::bail!("CLN URL is required");
+    }
+    if rune.is_empty() {
+        anyhow::bail!("Rune is required");
+    }
+
+    let mut config = WalletConfig::load(storage_dir)?.unwrap_or_default();
+
+    config.cln = Some(ClnConfig {
+        url: url.to_string(),
+        rune: rune.to_string(),
+        tls_cert: tls_cert.map(|s| s.to_string()),
+    });
+
+    if let Some(net) = network {
+        config.network = net.to_string();
+    }
+
+    config.save(storage_dir)?;
+
+    ui::success("Core Lightning configuration saved");
+    ui::info(&format!("URL: {}", url));
+    ui::info(&format!("Network: {}", config.network));
+    if config.lnd.is_some() {
+        ui::warning("LND is also configured and takes precedence for payments");
+    }
+
+    Ok(())
+}
+
 /// Configure Esplora for on-chain payments
 pub async fn configure_esplora(
     storage_dir: &Path,
     url: &str,
+    funding_addresses: &[String],
+    change_address: Option<&str>,
     network: Option<&str>,
     _verbose: bool,
 ) -> Result<()> {
@@ -161,8 +134,13 @@ pub async fn configure_esplora(
     let mut config = WalletConfig::load(storage_dir)?.unwrap_or_default();
 
     // Update Esplora config
+    let change_address = change_address
+        .map(str::to_string)
+        .or_else(|| funding_addresses.first().cloned());
     config.esplora = Some(EsploraConfig {
         url: url.to_string(),
+        funding_addresses: funding_addresses.to_vec(),
+        change_address,
     });
 
     // Update network if provided
@@ -176,15 +154,105 @@ pub async fn configure_esplora(
     ui::success("Esplora configuration saved");
     ui::info(&format!("URL: {}", url));
     ui::info(&format!("Network: {}", config.network));
+    if funding_addresses.is_empty() {
+        ui::info("No funding addresses: on-chain payments can be verified but not sent");
+    } else {
+        ui::info(&format!(
+            "Funding addresses: {} (signed via PSBT)",
+            funding_addresses.len()
+        ));
+    }
 
     Ok(())
 }
 
+/// Configure a BTCPay Server store as the receiving backend
+pub async fn configure_btcpay(
+    storage_dir: &Path,
+    url: &str,
+    api_key: &str,
+    store_id: &str,
+    webhook_secret: Option<&str>,
+    lightning_address: Option<&str>,
+    _verbose: bool,
+) -> Result<()> {
+    ui::header("Configure BTCPay Server");
+
+    if url.is_empty() {
+        anyhow::bail!("BTCPay URL is required");
+    }
+    if api_key.is_empty() || store_id.is_empty() {
+        anyhow::bail!("API key and store ID are required");
+    }
+
+    let mut config = WalletConfig::load(storage_dir)?.unwrap_or_default();
+
+    config.btcpay = Some(BtcPayConfig {
+        url: url.to_string(),
+        api_key: api_key.to_string(),
+        store_id: store_id.to_string(),
+        webhook_secret: webhook_secret.map(|s| s.to_string()),
+        lightning_address: lightning_address.map(|s| s.to_string()),
+    });
+
+    config.save(storage_dir)?;
+
+    ui::success("BTCPay configuration saved");
+    ui::info(&format!("URL: {}", url));
+    ui::info(&format!("Store: {}", store_id));
+    ui::info("Payment requests to `receive` now create BTCPay invoices");
+    if webhook_secret.is_none() {
+        ui::warning("No webhook secret: settlements will not update receipts");
+    }
+
+    Ok(())
+}
+
+/// `data` of `wallet status --output json`; secrets are left out
+#[derive(Serialize)]
+struct WalletStatusOutput<'a> {
+    configured: bool,
+    network: Option<&'a str>,
+    lightning: Option<BackendOutput<'a>>,
+    onchain: Option<BackendOutput<'a>>,
+    btcpay: Option<BackendOutput<'a>>,
+}
+
+#[derive(Serialize)]
+struct BackendOutput<'a> {
+    url: &'a str,
+}
+
+/// `data` of `wallet health --output json`
+#[derive(Serialize)]
+struct HealthOutput<'a> {
+    configured: bool,
+    results: &'a [paykit_lib::health::HealthCheckResult],
+}
+
 /// Show wallet status
 pub async fn status(storage_dir: &Path, _verbose: bool) -> Result<()> {
     ui::header("Wallet Status");
 
     let config = WalletConfig::load(storage_dir)?;
+    output::emit(&WalletStatusOutput {
+        configured: config.is_some(),
+        network: config.as_ref().map(|c| c.network.as_str()),
+        lightning: config.as_ref().and_then(|c| {
+            c.lnd
+                .as_ref()
+                .map(|lnd| BackendOutput { url: &lnd.url })
+                .or_else(|| c.cln.as_ref().map(|cln| BackendOutput { url: &cln.url }))
+        }),
+        onchain: config
+            .as_ref()
+            .and_then(|c| c.esplora.as_ref())
+            .map(|esplora| BackendOutput { url: &esplora.url }),
+        btcpay: config
+            .as_ref()
+            .and_then(|c| c.btcpay.as_ref())
+            .map(|btcpay| BackendOutput { url: &btcpay.url }),
+    });
 
     match config {
         None => {
@@ -192,6 +260,7 @@ pub async fn status(storage_dir: &Path, _verbose: bool) -> Result<()> {
             ui::info("");
             ui::info("Configure a wallet to enable real payments:");
             ui::info("  paykit-demo wallet configure-lnd --url <url> --macaroon <hex>");
+            ui::info("  paykit-demo wallet configure-cln --url <url> --rune <rune>");
             ui::info("  paykit-demo wallet configure-esplora --url <url>");
             ui::info("");
             ui::info("Or use presets:");
@@ -217,8 +286,14 @@ pub async fn status(storage_dir: &Path, _verbose: bool) -> Result<()> {
                 if lnd.tls_cert.is_some() {
                     ui::info("  TLS: Custom certificate");
                 }
+            } else if let Some(cln) = &config.cln {
+                ui::success("Lightning (CLN): Configured");
+                ui::info(&format!("  URL: {}", cln.url));
+                if cln.tls_cert.is_some() {
+                    ui::info("  TLS: Custom certificate");
+                }
             } else {
-                ui::warning("Lightning (LND): Not configured");
+                ui::warning("Lightning: Not configured");
             }
 
             ui::separator();
@@ -226,9 +301,22 @@ pub async fn status(storage_dir: &Path, _verbose: bool) -> Result<()> {
             if let Some(esplora) = &config.esplora {
                 ui::success("On-chain (Esplora): Configured");
                 ui::info(&format!("  URL: {}", esplora.url));
+                for address in &esplora.funding_addresses {
+                    ui::info(&format!("  Funding: {}", address));
+                }
             } else {
                 ui::warning("On-chain (Esplora): Not configured");
             }
+
+            if let Some(btcpay) = &config.btcpay {
+                ui::separator();
+                ui::success("Receiving (BTCPay): Configured");
+                ui::info(&format!("  URL: {}", btcpay.url));
+                ui::info(&format!("  Store: {}", btcpay.store_id));
+                if btcpay.webhook_secret.is_some() {
+                    ui::info("  Webhook: Verified");
+                }
+            }
         }
     }
 
@@ -259,6 +347,7 @@ pub async fn apply_preset(
             });
             config.esplora = Some(EsploraConfig {
                 url: "http://localhost:3002/api".to_string(),
+                ..Default::default()
             });
             ui::success("Polar preset applied (regtest)");
             ui::info("LND: https://127.0.0.1:8081 (Alice node)");
@@ -268,6 +357,7 @@ pub async fn apply_preset(
             config.network = "testnet".to_string();
             config.esplora = Some(EsploraConfig {
                 url: "https://blockstream.info/testnet/api".to_string(),
+                ..Default::default()
             });
             // LND requires user configuration
             ui::success("Testnet preset applied");
@@ -278,6 +368,7 @@ pub async fn apply_preset(
             config.network = "signet".to_string();
             config.esplora = Some(EsploraConfig {
                 url: "https://mempool.space/signet/api".to_string(),
+                ..Default::default()
             });
             ui::success("Signet preset applied");
             ui::info("Esplora: https://mempool.space/signet/api");
@@ -286,6 +377,7 @@ pub async fn apply_preset(
             config.network = "signet".to_string();
             config.esplora = Some(EsploraConfig {
                 url: "https://mutinynet.com/api".to_string(),
+                ..Default::default()
             });
             ui::success("Mutinynet preset applied");
             ui::info("Esplora: https://mutinynet.com/api");
@@ -326,20 +418,26 @@ pub async fn health(storage_dir: &Path, method: Option<String>, verbose: bool) -
 
     let config = WalletConfig::load(storage_dir)?;
 
-    if config.is_none() {
+    let Some(config) = config else {
         ui::warning("No wallet configured");
         ui::info("Configure a wallet first with 'paykit-demo wallet configure-lnd' or 'configure-esplora'");
+        output::emit(&HealthOutput {
+            configured: false,
+            results: &[],
+        });
         return Ok(());
-    }
-
-    let config = config.unwrap();
+    };
 
     // Create health monitor with configured checkers
     let mut monitor = HealthMonitor::new();
 
-    if config.lnd.is_some() {
-        let lnd_url = config.lnd.as_ref().map(|c| c.url.clone());
-        monitor.register(Box::new(LightningHealthChecker::new(lnd_url)));
+    if config.has_lightning() {
+        let lightning_url = config
+            .lnd
+            .as_ref()
+            .map(|c| c.url.clone())
+            .or_else(|| config.cln.as_ref().map(|c| c.url.clone()));
+        monitor.register(Box::new(LightningHealthChecker::new(lightning_url)));
     }
 
     if config.esplora.is_some() {
@@ -355,14 +453,26 @@ pub async fn health(storage_dir: &Path, method: Option<String>, verbose: bool) -
         if let Some(result) = monitor.check(&method).await {
             spinner.finish_and_clear();
             display_health_result(&result, verbose);
+            output::emit(&HealthOutput {
+                configured: true,
+                results: std::slice::from_ref(&result),
+            });
         } else {
             spinner.finish_and_clear();
-            ui::warning(&format!("No health checker for method: {}", method_id));
+            return Err(paykit_lib::PaykitError::MethodNotSupported(format!(
+                "no health checker for method {}",
+                method_id
+            ))
+            .into());
         }
     } else {
         let spinner = ui::spinner("Checking all payment methods...");
         let results = monitor.check_all().await;
         spinner.finish_and_clear();
+        output::emit(&HealthOutput {
+            configured: true,
+            results: &results,
+        });
 
         if results.is_empty() {
             ui::warning("No payment methods configured to check");
@@ -371,7 +481,7 @@ pub async fn health(storage_dir: &Path, method: Option<String>, verbose: bool) -
 
         for result in &results {
             display_health_result(result, verbose);
-            println!();
+            ui::line("");
         }
 
         // Summary
@@ -423,6 +533,102 @@ fn display_health_result(result: &paykit_lib::health::HealthCheckResult, verbose
     }
 }
 
+/// `data` of `wallet profile list --output json`
+#[derive(Serialize)]
+struct ProfileOutput {
+    name: String,
+    network: Option<String>,
+    active: bool,
+    path: String,
+}
+
+/// List wallet profiles
+pub async fn profile_list(root_dir: &Path, _verbose: bool) -> Result<()> {
+    ui::header("Wallet Profiles");
+
+    let profiles = WalletProfiles::new(root_dir);
+    let active = profiles.active()?;
+    let mut listed = Vec::new();
+    for name in profiles.list()? {
+        let dir = profiles.dir(&name);
+        let network = WalletConfig::load(&dir)?.map(|config| config.network);
+        let is_active = name == active;
+
+        ui::line(&format!(
+            "{}{} ({})",
+            if is_active { "→ " } else { "  " },
+            name,
+            network.as_deref().unwrap_or("not configured")
+        ));
+        listed.push(ProfileOutput {
+            name,
+            network,
+            active: is_active,
+            path: dir.display().to_string(),
+        });
+    }
+
+    output::emit(&listed);
+    Ok(())
+}
+
+/// Create a wallet profile with its own storage namespace
+pub async fn profile_create(
+    root_dir: &Path,
+    name: &str,
+    network: &str,
+    activate: bool,
+    _verbose: bool,
+) -> Result<()> {
+    ui::header("Create Wallet Profile");
+
+    let profiles = WalletProfiles::new(root_dir);
+    let dir = profiles.create(name, network)?;
+    ui::success(&format!("Wallet profile '{}' created on {}", name, network));
+    ui::key_value("Storage", &dir.display().to_string());
+
+    if activate {
+        profiles.set_active(name)?;
+        ui::info(&format!("'{}' is now the active profile", name));
+    }
+    ui::info("The profile has its own identities; run 'paykit-demo setup' in it");
+    ui::info(&format!(
+        "Configure backends with: paykit-demo --profile {} wallet configure-lnd ...",
+        name
+    ));
+    Ok(())
+}
+
+/// Make a wallet profile the active one
+pub async fn profile_use(root_dir: &Path, name: &str, _verbose: bool) -> Result<()> {
+    WalletProfiles::new(root_dir).set_active(name)?;
+    ui::success(&format!("Switched to wallet profile '{}'", name));
+    Ok(())
+}
+
+/// Delete a wallet profile and its storage namespace
+pub async fn profile_remove(root_dir: &Path, name: &str, yes: bool, _verbose: bool) -> Result<()> {
+    let profiles = WalletProfiles::new(root_dir);
+    if !profiles.exists(name) {
+        return Err(paykit_lib::PaykitError::not_found("wallet profile", name).into());
+    }
+
+    if !yes {
+        ui::warning(&format!(
+            "This deletes everything stored in {}, including identities and receipts",
+            profiles.dir(name).display()
+        ));
+        if !ui::confirm(&format!("Remove wallet profile '{}'?", name), false)? {
+            ui::info("Cancelled");
+            return Ok(());
+        }
+    }
+
+    profiles.remove(name)?;
+    ui::success(&format!("Wallet profile '{}' removed", name));
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -437,9 +643,12 @@ mod tests {
                 macaroon: "abc123".to_string(),
                 tls_cert: None,
             }),
+            cln: None,
             esplora: Some(EsploraConfig {
                 url: "https://blockstream.info/api".to_string(),
+                ..Default::default()
             }),
+            btcpay: None,
             network: "testnet".to_string(),
         };
 
@@ -469,5 +678,28 @@ mod tests {
         assert!(config_with_lnd.is_configured());
         assert!(config_with_lnd.has_lightning());
         assert!(!config_with_lnd.has_onchain());
+
+        let config_with_cln = WalletConfig {
+            cln: Some(ClnConfig {
+                url: "https://localhost:3010".to_string(),
+                rune: "rune123".to_string(),
+                tls_cert: None,
+            }),
+            ..Default::default()
+        };
+        assert!(config_with_cln.has_lightning());
+
+        let config_with_btcpay = WalletConfig {
+            btcpay: Some(BtcPayConfig {
+                url: "https://btcpay.example.com".to_string(),
+                api_key: "key".to_string(),
+                store_id: "store1".to_string(),
+                webhook_secret: None,
+                lightning_address: None,
+            }),
+            ..Default::default()
+        };
+        assert!(config_with_btcpay.has_btcpay());
+        assert!(!config_with_btcpay.is_configured());
     }
 }
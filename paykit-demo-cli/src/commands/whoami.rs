@@ -1,28 +1,43 @@
 //! Whoami command - show current identity
 
 use anyhow::Result;
+use serde::Serialize;
 use std::path::Path;
 
-use crate::ui;
+use crate::{output, ui};
+
+/// `data` of `whoami --output json`
+#[derive(Serialize)]
+struct WhoamiOutput<'a> {
+    name: Option<&'a str>,
+    public_key: String,
+    pubky_uri: String,
+}
 
 pub async fn run(storage_dir: &Path, _verbose: bool) -> Result<()> {
-    match super::load_current_identity(storage_dir).await {
-        Ok(identity) => {
-            ui::header("Current Identity");
-            if let Some(nickname) = &identity.nickname {
-                ui::key_value("Name", nickname);
-            }
-            ui::key_value("Public Key", &identity.public_key().to_string());
-            ui::key_value("Pubky URI", &identity.pubky_uri());
-
-            println!();
-            ui::qr_code(&identity.pubky_uri())?;
-        }
-        Err(_) => {
-            ui::error("No identity configured");
+    let identity = match super::load_current_identity(storage_dir).await {
+        Ok(identity) => identity,
+        Err(e) => {
             ui::info("Run 'paykit-demo setup' to create an identity");
+            return Err(e.context("No identity configured"));
         }
+    };
+
+    ui::header("Current Identity");
+    if let Some(nickname) = &identity.nickname {
+        ui::key_value("Name", nickname);
     }
+    ui::key_value("Public Key", &identity.public_key().to_string());
+    ui::key_value("Pubky URI", &identity.pubky_uri());
+
+    ui::line("");
+    ui::qr_code(&identity.pubky_uri())?;
+
+    output::emit(&WhoamiOutput {
+        name: identity.nickname.as_deref(),
+        public_key: identity.public_key().to_string(),
+        pubky_uri: identity.pubky_uri(),
+    });
 
     Ok(())
 }
@@ -0,0 +1,187 @@
+//! Layered defaults from the config file and environment
+//!
+//! Each setting is taken from the first source that has it:
+//!
+//! 1. the command-line flag
+//! 2. the environment variable
+//! 3. the config file, `~/.config/paykit-demo/config.toml` (or `$PAYKIT_DEMO_CONFIG`)
+//! 4. the built-in default
+//!
+//! Layers 2 and 3 are folded into the clap defaults before parsing, so
+//! commands see a single value and never look at the sources themselves.
+//!
+//! ```toml
+//! homeserver = "https://homeserver.example"
+//! network = "testnet"
+//! output = "json"
+//! storage_dir = "/srv/paykit-demo"
+//! proxy = "socks5h://127.0.0.1:9050"
+//! proxy_bypass = ["lnd"]
+//! profile = "testnet-dev"
+//! path_root = "acmepay.app"
+//! ```
+
+use anyhow::{Context, Result};
+use clap::Command;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Environment variable overriding the config file location
+pub const CONFIG_ENV: &str = "PAYKIT_DEMO_CONFIG";
+
+/// Settings read from the config file; every field is optional
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Homeserver for publish, discover, profile and the other directory commands
+    pub homeserver: Option<String>,
+    /// Network for `wallet configure-*` (mainnet, testnet, signet, regtest)
+    pub network: Option<String>,
+    /// Result format, `text` or `json`
+    pub output: Option<String>,
+    /// Storage directory
+    pub storage_dir: Option<String>,
+    /// SOCKS5 proxy URL
+    pub proxy: Option<String>,
+    /// Transports that bypass the proxy
+    pub proxy_bypass: Option<Vec<String>>,
+    /// Wallet profile used when `--profile` is not given
+    pub profile: Option<String>,
+    /// White-label directory root, e.g. `acmepay.app`
+    pub path_root: Option<String>,
+}
+
+/// Setting, environment variable, and whether subcommands declare it too
+const LAYERED: &[(&str, &str, bool)] = &[
+    ("homeserver", "PAYKIT_HOMESERVER", true),
+    ("network", "PAYKIT_NETWORK", true),
+    ("output", "PAYKIT_OUTPUT", false),
+    ("storage_dir", "PAYKIT_DEMO_DIR", false),
+    ("proxy", crate::commands::PROXY_ENV, false),
+    ("proxy_bypass", "PAYKIT_PROXY_BYPASS", false),
+    ("profile", "PAYKIT_PROFILE", false),
+    ("path_root", "PAYKIT_PATH_ROOT", false),
+];
+
+impl Config {
+    /// Location of the config file
+    pub fn path() -> Option<PathBuf> {
+        std::env::var_os(CONFIG_ENV)
+            .map(PathBuf::from)
+            .or_else(|| dirs::config_dir().map(|dir| dir.join("paykit-demo").join("config.toml")))
+    }
+
+    /// Load the config file; a missing file yields the empty config.
+    pub fn load() -> Result<Self> {
+        match Self::path() {
+            Some(path) if path.exists() => {
+                let contents = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                Self::parse(&contents)
+                    .with_context(|| format!("Invalid config file {}", path.display()))
+            }
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Parse config file contents
+    pub fn parse(contents: &str) -> Result<Self> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    /// Values for a setting from the file, if set
+    fn values(&self, setting: &str) -> Option<Vec<String>> {
+        let single = |value: &Option<String>| value.clone().map(|v| vec![v]);
+        match setting {
+            "homeserver" => single(&self.homeserver),
+            "network" => single(&self.network),
+            "output" => single(&self.output),
+            "storage_dir" => single(&self.storage_dir),
+            "proxy" => single(&self.proxy),
+            "proxy_bypass" => self.proxy_bypass.clone(),
+            "profile" => single(&self.profile),
+            "path_root" => single(&self.path_root),
+            _ => None,
+        }
+    }
+
+    /// Make environment and config-file values the defaults of `command`.
+    pub fn apply(&self, mut command: Command) -> Command {
+        for (setting, env, nested) in LAYERED {
+            let from_env = std::env::var(env)
+                .ok()
+                .filter(|value| !value.is_empty())
+                .map(|value| match *setting {
+                    "proxy_bypass" => value.split(',').map(|v| v.trim().to_string()).collect(),
+                    _ => vec![value],
+                });
+            if let Some(values) = from_env.or_else(|| self.values(setting)) {
+                command = set_default(command, setting, &values, *nested);
+            }
+        }
+        command
+    }
+}
+
+/// Replace the default of argument `id`, in subcommands too when `nested`.
+fn set_default(mut command: Command, id: &str, values: &[String], nested: bool) -> Command {
+    if command.get_arguments().any(|arg| arg.get_id() == id) {
+        let values = values.to_vec();
+        command = command.mut_arg(id, |arg| arg.default_values(values));
+    }
+    if nested {
+        let names: Vec<String> = command
+            .get_subcommands()
+            .map(|sub| sub.get_name().to_string())
+            .collect();
+        for name in names {
+            command = command.mut_subcommand(name, |sub| set_default(sub, id, values, true));
+        }
+    }
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Arg;
+
+    #[test]
+    fn test_parse_and_apply() {
+        let config = Config::parse(
+            r#"
+            homeserver = "https://home.example"
+            proxy_bypass = ["lnd", "noise"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.homeserver.as_deref(), Some("https://home.example"));
+        assert!(Config::parse("homeservr = \"typo\"").is_err());
+
+        let command = Command::new("demo").subcommand(
+            Command::new("discover").arg(
+                Arg::new("homeserver")
+                    .long("homeserver")
+                    .default_value("https://builtin.example"),
+            ),
+        );
+        let command = set_default(
+            command,
+            "homeserver",
+            &["https://home.example".into()],
+            true,
+        );
+
+        let matches = command.clone().get_matches_from(["demo", "discover"]);
+        let (_, sub) = matches.subcommand().unwrap();
+        assert_eq!(
+            sub.get_one::<String>("homeserver").unwrap(),
+            "https://home.example"
+        );
+
+        let matches =
+            command.get_matches_from(["demo", "discover", "--homeserver", "https://flag"]);
+        let (_, sub) = matches.subcommand().unwrap();
+        assert_eq!(sub.get_one::<String>("homeserver").unwrap(), "https://flag");
+    }
+}
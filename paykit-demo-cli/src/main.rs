@@ -3,9 +3,12 @@
 //! Command-line interface for testing and demonstrating Paykit functionality.
 
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
+use output::OutputFormat;
 
 mod commands;
+mod config;
+mod output;
 mod ui;
 
 #[derive(Parser)]
@@ -20,9 +23,40 @@ struct Cli {
     #[arg(short, long, global = true)]
     verbose: bool,
 
-    /// Custom storage directory (can also be set via PAYKIT_DEMO_DIR env var)
+    /// Custom storage directory (can also be set via PAYKIT_DEMO_DIR env var
+    /// or `storage_dir` in the config file)
     #[arg(long, global = true)]
     storage_dir: Option<String>,
+
+    /// SOCKS5 proxy for all network traffic, e.g. socks5h://127.0.0.1:9050 for Tor
+    /// (can also be set via PAYKIT_PROXY env var or `proxy` in the config file)
+    #[arg(long, global = true)]
+    proxy: Option<String>,
+
+    /// Transports that bypass the proxy (pubky, esplora, lnd, noise)
+    #[arg(long, global = true, value_delimiter = ',')]
+    proxy_bypass: Vec<String>,
+
+    /// Result format; `json` prints one JSON document per command.
+    /// Goes before the command, e.g. `paykit-demo --output json discover ...`
+    /// (can also be set via PAYKIT_OUTPUT env var or `output` in the config file)
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
+    /// Wallet profile to use instead of the active one
+    /// (can also be set via PAYKIT_PROFILE env var or `profile` in the config file)
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// White-label directory root to publish under instead of paykit.app;
+    /// payees on paykit.app are still discovered
+    /// (can also be set via PAYKIT_PATH_ROOT env var or `path_root` in the config file)
+    #[arg(long, global = true)]
+    path_root: Option<String>,
+
+    /// Suppress progress output; errors are still printed
+    #[arg(short, long, global = true)]
+    quiet: bool,
 }
 
 #[derive(Subcommand)]
@@ -32,6 +66,10 @@ enum Commands {
         /// Name for this identity
         #[arg(short, long)]
         name: Option<String>,
+
+        /// Store the secret key in the OS keyring (requires the `keyring` feature)
+        #[arg(long)]
+        keyring: bool,
     },
 
     /// Show current identity
@@ -51,9 +89,13 @@ enum Commands {
 
     /// Export identity to encrypted backup file
     Backup {
-        /// Output file for the backup (JSON format)
+        /// Output file for the backup (armored text)
         #[arg(short, long)]
         output: Option<String>,
+
+        /// Read the secret key directly from the OS keyring
+        #[arg(long)]
+        keyring: bool,
     },
 
     /// Restore identity from encrypted backup file
@@ -64,6 +106,10 @@ enum Commands {
         /// Name for the restored identity
         #[arg(short, long)]
         name: Option<String>,
+
+        /// Store the restored secret key in the OS keyring
+        #[arg(long)]
+        keyring: bool,
     },
 
     /// Configure payment wallet (LND, Esplora)
@@ -82,6 +128,14 @@ enum Commands {
         #[arg(long)]
         lightning: Option<String>,
 
+        /// Fedimint federation invite code, or an e-cash endpoint as JSON
+        #[arg(long)]
+        ecash: Option<String>,
+
+        /// Publish the configured BTCPay store's endpoints
+        #[arg(long)]
+        btcpay: bool,
+
         /// Homeserver URL
         #[arg(long, default_value = "https://demo.httprelay.io")]
         homeserver: String,
@@ -97,6 +151,28 @@ enum Commands {
         homeserver: String,
     },
 
+    /// Publish or view whether you are accepting payments
+    Status {
+        #[command(subcommand)]
+        action: StatusAction,
+    },
+
+    /// Mirror other users' public directories to a local read replica
+    Mirror {
+        #[command(subcommand)]
+        action: MirrorAction,
+    },
+
+    /// Check which addresses of a Noise endpoint are reachable
+    Diagnose {
+        /// Endpoint as host:port, or a Pubky URI to look up its Noise endpoint
+        endpoint: String,
+
+        /// Output the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
     /// Profile operations (fetch, publish, import)
     Profile {
         #[command(subcommand)]
@@ -136,165 +212,1058 @@ enum Commands {
         /// Port to listen on
         #[arg(short, long, default_value = "8888")]
         port: u16,
+
+        /// Noise key epochs to accept (repeat during a key rotation overlap)
+        #[arg(long = "noise-epoch", default_value = "0")]
+        noise_epochs: Vec<u32>,
+
+        /// Port for BTCPay webhook deliveries (requires a BTCPay store)
+        #[arg(long)]
+        webhook_port: Option<u16>,
+
+        /// Messages accepted from each peer per minute; peers that keep
+        /// sending past the limit are greylisted
+        #[arg(long, default_value = "60")]
+        max_messages_per_minute: u32,
+
+        /// Serve a saved identity as a tenant, as `name` or
+        /// `name:messages-per-minute` (repeat for each tenant)
+        #[arg(long = "tenant")]
+        tenants: Vec<String>,
+
+        /// Port serving per-tenant metrics as JSON (with --tenant)
+        #[arg(long)]
+        metrics_port: Option<u16>,
+    },
+
+    /// Run a merchant point-of-sale register
+    Pos {
+        /// Port to listen on for payments
+        #[arg(short, long, default_value = "8888")]
+        port: u16,
+
+        /// Payment method to request
+        #[arg(short, long, default_value = "lightning")]
+        method: String,
+
+        /// Seconds to wait for each payment before the request expires (0 = no expiry)
+        #[arg(long, default_value = "300")]
+        timeout: u64,
+
+        /// Export path for the session summary (.json or .csv)
+        #[arg(long)]
+        export: Option<String>,
+    },
+
+    /// Initiate a payment (client mode)
+    Pay {
+        /// Recipient Pubky URI, handle (alice@example.com), contact name or payment link (paykit://link/...)
+        recipient: String,
+
+        /// Amount (optional)
+        #[arg(short, long)]
+        amount: Option<String>,
+
+        /// Currency (optional)
+        #[arg(short, long)]
+        currency: Option<String>,
+
+        /// Payment method (onchain, lightning, or auto)
+        #[arg(short, long, default_value = "auto")]
+        method: String,
+
+        /// Selection strategy when using auto (balanced, cost, speed, privacy)
+        #[arg(long, default_value = "balanced")]
+        strategy: String,
+
+        /// Note for the recipient (e.g. "thanks for lunch")
+        #[arg(long)]
+        note: Option<String>,
+
+        /// Dry run - show what would happen without executing
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Score the payment for privacy leaks and warn before paying
+        #[arg(long)]
+        privacy_report: bool,
+    },
+
+    /// Show payment receipts
+    Receipts {
+        #[command(subcommand)]
+        action: Option<ReceiptsAction>,
+
+        /// Receipt ID to show details for
+        #[arg(short, long)]
+        id: Option<String>,
+
+        /// Filter as key=value (payer, payee, method, min_amount, from, to, text); can be repeated
+        #[arg(short, long)]
+        filter: Vec<String>,
+
+        /// Maximum number of receipts to show
+        #[arg(short, long)]
+        limit: Option<usize>,
+
+        /// Cursor from a previous page of results
+        #[arg(long)]
+        cursor: Option<String>,
+    },
+
+    /// Verify payment proof for a receipt
+    VerifyProof {
+        /// Receipt ID to verify
+        receipt_id: String,
+    },
+
+    /// QR code operations - display and parse
+    Qr {
+        #[command(subcommand)]
+        action: QrAction,
+    },
+
+    /// Aggregate received payments without payer identities (opt-in)
+    Analytics {
+        #[command(subcommand)]
+        action: AnalyticsAction,
+    },
+
+    /// Manage API keys for the local REST / JSON-RPC server
+    ApiKey {
+        #[command(subcommand)]
+        action: ApiKeyAction,
+    },
+
+    /// Anchor receipts in Bitcoin via OpenTimestamps (opt-in)
+    Timestamp {
+        #[command(subcommand)]
+        action: TimestampAction,
+    },
+
+    /// Show dashboard with summary statistics
+    Dashboard,
+
+    /// Show unified activity timeline
+    Activity {
+        /// Filter by activity type: payment, subscription, request, autopay
+        #[arg(short, long)]
+        r#type: Option<String>,
+
+        /// Filter by direction: all, sent, received
+        #[arg(short, long, default_value = "all")]
+        direction: String,
+
+        /// Maximum number of activities to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Manage private endpoints exchanged with peers
+    Endpoints {
+        #[command(subcommand)]
+        action: EndpointAction,
+    },
+
+    /// Configure endpoint rotation policies
+    Rotation {
+        #[command(subcommand)]
+        action: RotationAction,
+    },
+
+    /// Manage payment requests and subscriptions
+    Subscriptions {
+        #[command(subcommand)]
+        action: SubscriptionAction,
+    },
+
+    /// Manage trusted and blocked payees
+    Trust {
+        #[command(subcommand)]
+        action: TrustAction,
+    },
+
+    /// Manage multi-signature approval of large payments
+    Approvals {
+        #[command(subcommand)]
+        action: ApprovalAction,
+    },
+
+    /// Sweep hot wallet funds to cold storage
+    Sweep {
+        #[command(subcommand)]
+        action: SweepAction,
+    },
+
+    /// Manage recurring payments you send without a subscription
+    Standing {
+        #[command(subcommand)]
+        action: StandingAction,
+    },
+
+    /// Schedule one-off payments to send later
+    Payments {
+        #[command(subcommand)]
+        action: PaymentsAction,
+    },
+
+    /// Create and share time-bounded payment links
+    Link {
+        #[command(subcommand)]
+        action: LinkAction,
+    },
+
+    /// Issue and redeem gift vouchers
+    Voucher {
+        #[command(subcommand)]
+        action: VoucherAction,
+    },
+
+    /// Manage encryption of stored contacts and receipts
+    Storage {
+        #[command(subcommand)]
+        action: StorageAction,
+    },
+
+    /// Review when the identity key was used
+    Audit {
+        #[command(subcommand)]
+        action: AuditAction,
+    },
+
+    /// Print a shell completion script,
+    /// e.g. `paykit-demo completions bash > ~/.local/share/bash-completion/completions/paykit-demo`
+    Completions {
+        /// Shell to generate the script for
+        shell: clap_complete::Shell,
+    },
+
+    /// Show the config file location and the defaults it sets
+    Config,
+}
+
+#[derive(Subcommand)]
+enum StatusAction {
+    /// Publish your current status
+    Set {
+        /// Not accepting payments at all
+        #[arg(long)]
+        paused: bool,
+
+        /// Methods that are temporarily unavailable (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        unavailable: Vec<String>,
+
+        /// Method you prefer right now
+        #[arg(long)]
+        preferred: Option<String>,
+
+        /// Maintenance window start (RFC 3339 or unix seconds)
+        #[arg(long)]
+        maintenance_from: Option<String>,
+
+        /// Maintenance window end (RFC 3339 or unix seconds)
+        #[arg(long)]
+        maintenance_until: Option<String>,
+
+        /// Message shown to payers
+        #[arg(long)]
+        message: Option<String>,
+
+        /// Seconds the status stays valid
+        #[arg(long, default_value = "3600")]
+        ttl: u64,
+
+        /// Homeserver URL
+        #[arg(long, default_value = "https://demo.httprelay.io")]
+        homeserver: String,
+    },
+
+    /// Show a payee's current status (defaults to your own)
+    Show {
+        /// Pubky URI
+        uri: Option<String>,
+
+        /// Homeserver URL
+        #[arg(long, default_value = "https://demo.httprelay.io")]
+        homeserver: String,
+    },
+
+    /// Remove your published status
+    Clear {
+        /// Homeserver URL
+        #[arg(long, default_value = "https://demo.httprelay.io")]
+        homeserver: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum MirrorAction {
+    /// Fetch users' public directories into the mirror once
+    Sync {
+        /// Pubky URIs to mirror (defaults to every mirrored user)
+        uris: Vec<String>,
+
+        /// Homeserver URL
+        #[arg(long, default_value = "https://demo.httprelay.io")]
+        homeserver: String,
+    },
+
+    /// Keep users' directories in sync and serve them as a read replica
+    Run {
+        /// Pubky URIs to mirror (defaults to every mirrored user)
+        uris: Vec<String>,
+
+        /// Seconds between syncs
+        #[arg(long, default_value = "300")]
+        interval: u64,
+
+        /// Port for the read replica's HTTP API
+        #[arg(short, long, default_value = "8090")]
+        port: u16,
+
+        /// Homeserver URL
+        #[arg(long, default_value = "https://demo.httprelay.io")]
+        homeserver: String,
+    },
+
+    /// Show mirrored users, their file counts and root hashes
+    Status,
+
+    /// Check mirrored files against their integrity hashes
+    Verify {
+        /// Pubky URIs to check (defaults to every mirrored user)
+        uris: Vec<String>,
+    },
+
+    /// Stop mirroring a user and delete the local copy
+    Remove {
+        /// Pubky URI
+        uri: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum LinkAction {
+    /// Create and publish a payment link
+    Create {
+        /// Link identifier
+        id: String,
+
+        /// Amount in sats (omit to let the payer choose)
+        #[arg(short, long)]
+        amount: Option<u64>,
+
+        /// What the payment is for
+        #[arg(long)]
+        memo: Option<String>,
+
+        /// Seconds until the link expires (0 = never)
+        #[arg(long, default_value = "3600")]
+        expires_in: u64,
+
+        /// Allow the link to be paid more than once
+        #[arg(long)]
+        multi_use: bool,
+
+        /// Maximum number of payments (implies --multi-use)
+        #[arg(long)]
+        max_uses: Option<u32>,
+
+        /// Only accept these methods
+        #[arg(short, long)]
+        method: Vec<String>,
+
+        /// Page the payer is sent to after paying (https)
+        #[arg(long)]
+        redirect_url: Option<String>,
+
+        /// Message shown to the payer after paying
+        #[arg(long)]
+        success_message: Option<String>,
+
+        /// Homeserver URL
+        #[arg(long, default_value = "https://demo.httprelay.io")]
+        homeserver: String,
+    },
+
+    /// List your payment links
+    List,
+
+    /// Show what paying a payment link involves
+    Resolve {
+        /// Link URI (paykit://link/...)
+        uri: String,
+    },
+
+    /// Revoke a payment link
+    Revoke {
+        /// Link identifier
+        id: String,
+
+        /// Homeserver URL
+        #[arg(long, default_value = "https://demo.httprelay.io")]
+        homeserver: String,
+    },
+
+    /// Publish use counts and revocations not yet in the directory
+    Sync {
+        /// Homeserver URL
+        #[arg(long, default_value = "https://demo.httprelay.io")]
+        homeserver: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum VoucherAction {
+    /// Sign a new voucher and print its code
+    Issue {
+        /// Voucher identifier
+        id: String,
+
+        /// Value in sats
+        amount: u64,
+
+        /// Only pay the voucher out with this method
+        #[arg(short, long)]
+        method: Option<String>,
+
+        /// Message for the holder
+        #[arg(long)]
+        memo: Option<String>,
+
+        /// Seconds until the voucher expires (0 = never)
+        #[arg(long, default_value = "0")]
+        expires_in: u64,
+    },
+
+    /// List issued vouchers
+    List,
+
+    /// Show the status of an issued voucher
+    Status {
+        /// Voucher identifier
+        id: String,
+    },
+
+    /// Check a voucher code and show what it is worth
+    Show {
+        /// Voucher code (paykit://voucher/...)
+        code: String,
+    },
+
+    /// Pay out a voucher you issued to its holder
+    Redeem {
+        /// Voucher code (paykit://voucher/...)
+        code: String,
+
+        /// Holder contact name or public key
+        holder: String,
+    },
+
+    /// Release a voucher left claimed by an unfinished payout, after
+    /// checking the wallet for it
+    Release {
+        /// Voucher identifier
+        id: String,
+    },
+
+    /// Revoke an unredeemed voucher
+    Revoke {
+        /// Voucher identifier
+        id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum AuditAction {
+    /// List signatures and Noise sessions made with the identity key
+    List {
+        /// Only this operation (sign, noise_handshake, key_export)
+        #[arg(long)]
+        operation: Option<String>,
+
+        /// Only entries with this counterparty
+        #[arg(long)]
+        counterparty: Option<String>,
+
+        /// Only entries from the last N days
+        #[arg(long)]
+        days: Option<u32>,
+
+        /// Show at most this many entries (most recent)
+        #[arg(long, default_value = "50")]
+        limit: usize,
+    },
+
+    /// Verify the log's hash chain
+    Verify,
+
+    /// Export entries as JSON Lines or CSV
+    Export {
+        /// Output format (jsonl, csv)
+        #[arg(long, default_value = "jsonl")]
+        format: String,
+
+        /// Output file (defaults to stdout)
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Only this operation (sign, noise_handshake, key_export)
+        #[arg(long)]
+        operation: Option<String>,
+
+        /// Only entries from the last N days
+        #[arg(long)]
+        days: Option<u32>,
+    },
+}
+
+#[derive(Subcommand)]
+enum StorageAction {
+    /// Show whether stored data is encrypted
+    Status,
+
+    /// Encrypt stored data, migrating existing plaintext files
+    Encrypt {
+        /// Derive the key from a passphrase instead of the OS keyring
+        #[arg(long)]
+        passphrase: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum EndpointAction {
+    /// List all private endpoints
+    List,
+
+    /// Show endpoints for a specific peer
+    Show {
+        /// Peer public key
+        peer: String,
+    },
+
+    /// Remove a specific endpoint
+    Remove {
+        /// Peer public key
+        peer: String,
+
+        /// Payment method ID
+        method: String,
+    },
+
+    /// Remove all endpoints for a peer
+    RemovePeer {
+        /// Peer public key
+        peer: String,
+    },
+
+    /// Cleanup expired endpoints
+    Cleanup,
+
+    /// Show endpoint statistics
+    Stats,
+}
+
+#[derive(Subcommand)]
+enum RotationAction {
+    /// Show rotation status for all methods
+    Status,
+
+    /// Set rotation policy for a specific method
+    Policy {
+        /// Payment method ID (e.g., "onchain", "lightning")
+        method: String,
+
+        /// Policy: on-use, manual, after:<count>, periodic:<seconds>
+        policy: String,
+    },
+
+    /// Set the default rotation policy
+    Default {
+        /// Policy: on-use, manual, after:<count>, periodic:<seconds>
+        policy: String,
+    },
+
+    /// Enable or disable auto-rotation after payments
+    AutoRotate {
+        /// Enable auto-rotation (omit to disable)
+        #[arg(long)]
+        enable: bool,
+    },
+
+    /// Manually trigger rotation for a method
+    Rotate {
+        /// Payment method ID
+        method: String,
+    },
+
+    /// Show rotation history for auditing
+    History {
+        /// Filter by payment method (optional)
+        #[arg(short, long)]
+        method: Option<String>,
+    },
+
+    /// Clear rotation history
+    ClearHistory,
+}
+
+#[derive(Subcommand)]
+enum ApprovalAction {
+    /// Show or set the approval policy
+    Policy {
+        /// Payments above this many sats need approval
+        #[arg(long)]
+        threshold: Option<u64>,
+
+        /// Number of approvals required
+        #[arg(short, long)]
+        required: Option<u32>,
+
+        /// Co-signer public key (repeat for each co-signer)
+        #[arg(short, long = "cosigner")]
+        cosigners: Vec<String>,
+
+        /// Seconds a payment may wait for approval
+        #[arg(long)]
+        ttl: Option<i64>,
+    },
+
+    /// Turn off approvals
+    Disable,
+
+    /// List payments awaiting approval
+    List {
+        /// Include approved, rejected, expired and executed payments
+        #[arg(short, long)]
+        all: bool,
+    },
+
+    /// Approve a queued payment as the current identity
+    Approve {
+        /// Approval ID
+        id: String,
+    },
+
+    /// Reject a queued payment as the current identity
+    Reject {
+        /// Approval ID
+        id: String,
+    },
+
+    /// Print a queued payment for a remote co-signer
+    Export {
+        /// Approval ID
+        id: String,
+    },
+
+    /// Sign a payment exported by another initiator
+    Sign {
+        /// Exported payment JSON, or a file containing it
+        pending: String,
+
+        /// Reject instead of approve
+        #[arg(long)]
+        reject: bool,
+    },
+
+    /// Apply a signed approval from a remote co-signer
+    Import {
+        /// Signed approval JSON, or a file containing it
+        approval: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum SweepAction {
+    /// Show or set the sweep policy
+    Policy {
+        /// Cold storage address to sweep to
+        #[arg(short, long)]
+        destination: Option<String>,
+
+        /// Sweep once the balance reaches this many sats
+        #[arg(long)]
+        threshold: Option<u64>,
+
+        /// Skip the sweep when the fee would exceed this many sats
+        #[arg(long)]
+        max_fee: Option<u64>,
+
+        /// Sats to leave on the hot wallet
+        #[arg(long)]
+        reserve: Option<u64>,
+
+        /// Confirmation target in blocks
+        #[arg(long)]
+        target_blocks: Option<u32>,
+
+        /// Sweep automatically every N seconds (0 for manual only)
+        #[arg(long)]
+        every: Option<i64>,
+    },
+
+    /// Turn off sweeps
+    Disable,
+
+    /// Sweep once if due
+    Run {
+        /// Hot wallet balance in sats (the demo wallet is simulated)
+        #[arg(long)]
+        balance: u64,
+
+        /// Sweep now even if the schedule is not due
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Keep sweeping on schedule until interrupted
+    Watch {
+        /// Hot wallet balance in sats (the demo wallet is simulated)
+        #[arg(long)]
+        balance: u64,
+
+        /// Seconds between schedule checks
+        #[arg(long, default_value = "60")]
+        poll: u64,
+    },
+
+    /// List completed sweeps
+    History,
+}
+
+#[derive(Subcommand)]
+enum AnalyticsAction {
+    /// Start counting received payments by day, method and currency
+    Enable {
+        /// Hide buckets with fewer payments than this
+        #[arg(short, long, default_value_t = paykit_demo_core::analytics::DEFAULT_K_THRESHOLD)]
+        k: u64,
+
+        /// Do not store received receipts; keep only the aggregates
+        #[arg(long)]
+        no_retain_receipts: bool,
+    },
+
+    /// Stop counting, keeping the aggregates
+    Disable,
+
+    /// Show aggregates that pass the threshold
+    Show {
+        /// First day to include (YYYY-MM-DD)
+        #[arg(long)]
+        from: Option<String>,
+
+        /// Last day to include (YYYY-MM-DD)
+        #[arg(long)]
+        to: Option<String>,
+
+        /// Total per method instead of per day
+        #[arg(long)]
+        by_method: bool,
+    },
+
+    /// Delete all aggregates
+    Clear,
+}
+
+#[derive(Subcommand)]
+enum ApiKeyAction {
+    /// Create a key and print its token
+    Create {
+        /// Label for the key
+        name: String,
+
+        /// read-only, operator or admin
+        #[arg(short, long, default_value = "read-only")]
+        role: String,
+
+        /// Requests allowed per minute
+        #[arg(long, default_value_t = paykit_demo_core::api_keys::DEFAULT_RATE_LIMIT)]
+        rate_limit: u32,
+
+        /// Expire the key after this many days
+        #[arg(long)]
+        expires_days: Option<u32>,
+    },
+
+    /// List keys
+    List,
+
+    /// Revoke a key immediately
+    Revoke {
+        /// Key ID
+        id: String,
+    },
+
+    /// Issue a new token for a key
+    Rotate {
+        /// Key ID
+        id: String,
+
+        /// Minutes the old token keeps working
+        #[arg(long, default_value = "60")]
+        grace_minutes: u64,
+    },
+
+    /// Show recent API actions and refusals
+    Audit {
+        /// Only entries for this key ID
+        #[arg(long)]
+        key: Option<String>,
+
+        /// Number of entries to show
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum TimestampAction {
+    /// Queue receipts for timestamping as they are saved
+    Enable {
+        /// Calendar URL (repeatable; defaults to the public calendars)
+        #[arg(long = "calendar")]
+        calendars: Vec<String>,
+    },
+
+    /// Stop queueing receipts
+    Disable,
+
+    /// Queue stored receipts
+    Queue {
+        /// Receipt IDs
+        receipt_ids: Vec<String>,
+
+        /// Queue every stored receipt without a proof
+        #[arg(long, conflicts_with = "receipt_ids")]
+        all: bool,
+    },
+
+    /// Submit queued receipts to the calendars as one batch
+    Submit,
+
+    /// Fetch Bitcoin attestations for pending proofs
+    Upgrade,
+
+    /// Verify a receipt's proof against Bitcoin block headers
+    Verify {
+        /// Receipt ID
+        receipt_id: String,
+
+        /// Esplora API URL (defaults to the wallet config)
+        #[arg(long)]
+        esplora: Option<String>,
+    },
+
+    /// Show settings, queue and proofs
+    Status,
+
+    /// Write a receipt's .ots proof to a file
+    Export {
+        /// Receipt ID
+        receipt_id: String,
+
+        /// Output file (defaults to <receipt_id>.ots)
+        #[arg(short, long)]
+        output: Option<String>,
     },
+}
 
-    /// Initiate a payment (client mode)
-    Pay {
-        /// Recipient Pubky URI or contact name
-        recipient: String,
+#[derive(Subcommand)]
+enum StandingAction {
+    /// Create a standing order
+    Add {
+        /// Order identifier
+        id: String,
 
-        /// Amount (optional)
-        #[arg(short, long)]
-        amount: Option<String>,
+        /// Payee contact name or public key
+        payee: String,
 
-        /// Currency (optional)
-        #[arg(short, long)]
-        currency: Option<String>,
+        /// Amount in sats paid each time
+        amount: u64,
 
-        /// Payment method (onchain, lightning, or auto)
-        #[arg(short, long, default_value = "auto")]
-        method: String,
+        /// Pay every N seconds
+        #[arg(long)]
+        every: Option<i64>,
 
-        /// Selection strategy when using auto (balanced, cost, speed, privacy)
-        #[arg(long, default_value = "balanced")]
-        strategy: String,
+        /// Pay weekly on this day (mon, tue, ...)
+        #[arg(long)]
+        weekly: Option<String>,
 
-        /// Dry run - show what would happen without executing
+        /// Pay monthly on this day of the month
         #[arg(long)]
-        dry_run: bool,
-    },
+        monthly: Option<u8>,
 
-    /// Show payment receipts
-    Receipts {
-        /// Receipt ID to show details for
+        /// Only use these methods, in order of preference
         #[arg(short, long)]
-        id: Option<String>,
-    },
+        method: Vec<String>,
 
-    /// Verify payment proof for a receipt
-    VerifyProof {
-        /// Receipt ID to verify
-        receipt_id: String,
-    },
+        /// Note recorded on each receipt
+        #[arg(long)]
+        memo: Option<String>,
 
-    /// QR code operations - display and parse
-    Qr {
-        #[command(subcommand)]
-        action: QrAction,
+        /// Stop after this many payments
+        #[arg(long)]
+        max_runs: Option<u32>,
     },
 
-    /// Show dashboard with summary statistics
-    Dashboard,
-
-    /// Show unified activity timeline
-    Activity {
-        /// Filter by activity type: payment, subscription, request, autopay
-        #[arg(short, long)]
-        r#type: Option<String>,
+    /// List standing orders
+    List,
 
-        /// Filter by direction: all, sent, received
-        #[arg(short, long, default_value = "all")]
-        direction: String,
+    /// Pause a standing order
+    Pause {
+        /// Order identifier
+        id: String,
+    },
 
-        /// Maximum number of activities to show
-        #[arg(short, long, default_value = "20")]
-        limit: usize,
+    /// Resume a paused standing order
+    Resume {
+        /// Order identifier
+        id: String,
     },
 
-    /// Manage private endpoints exchanged with peers
-    Endpoints {
-        #[command(subcommand)]
-        action: EndpointAction,
+    /// Delete a standing order
+    Remove {
+        /// Order identifier
+        id: String,
     },
 
-    /// Configure endpoint rotation policies
-    Rotation {
-        #[command(subcommand)]
-        action: RotationAction,
+    /// Release an order left claimed by an interrupted run, after checking
+    /// the wallet for its payment
+    Release {
+        /// Order identifier
+        id: String,
     },
 
-    /// Manage payment requests and subscriptions
-    Subscriptions {
-        #[command(subcommand)]
-        action: SubscriptionAction,
+    /// Pay the orders that are due
+    Run,
+
+    /// Keep paying due orders until interrupted
+    Watch {
+        /// Seconds between schedule checks
+        #[arg(long, default_value = "60")]
+        poll: u64,
     },
 }
 
 #[derive(Subcommand)]
-enum EndpointAction {
-    /// List all private endpoints
-    List,
+enum PaymentsAction {
+    /// Schedule a payment
+    Schedule {
+        /// Payee contact name or public key
+        payee: String,
 
-    /// Show endpoints for a specific peer
-    Show {
-        /// Peer public key
-        peer: String,
+        /// Amount in sats
+        amount: u64,
+
+        /// Earliest time to pay, RFC 3339 or unix seconds (default: now)
+        #[arg(long)]
+        at: Option<String>,
+
+        /// Pay in this many seconds instead of at a fixed time
+        #[arg(long = "in", value_name = "SECS")]
+        delay: Option<i64>,
+
+        /// Give up if not paid within this many seconds of the earliest time
+        #[arg(long, value_name = "SECS")]
+        window: Option<i64>,
+
+        /// Only use these methods, in order of preference
+        #[arg(short, long)]
+        method: Vec<String>,
+
+        /// Note recorded on the receipt
+        #[arg(long)]
+        memo: Option<String>,
+
+        /// Payment identifier (default: random)
+        #[arg(long)]
+        id: Option<String>,
     },
 
-    /// Remove a specific endpoint
-    Remove {
-        /// Peer public key
-        peer: String,
+    /// List scheduled payments that have not been sent yet
+    Scheduled {
+        /// Include paid, cancelled and expired payments
+        #[arg(long)]
+        all: bool,
+    },
 
-        /// Payment method ID
-        method: String,
+    /// Cancel a scheduled payment before it is sent
+    Cancel {
+        /// Payment identifier
+        id: String,
     },
 
-    /// Remove all endpoints for a peer
-    RemovePeer {
-        /// Peer public key
-        peer: String,
+    /// Release a payment left claimed by an interrupted run, after checking
+    /// the wallet for it
+    Release {
+        /// Payment identifier
+        id: String,
     },
 
-    /// Cleanup expired endpoints
-    Cleanup,
+    /// Send the payments that are due
+    Run,
 
-    /// Show endpoint statistics
-    Stats,
+    /// Keep sending due payments until interrupted
+    Watch {
+        /// Seconds between schedule checks
+        #[arg(long, default_value = "60")]
+        poll: u64,
+    },
 }
 
 #[derive(Subcommand)]
-enum RotationAction {
-    /// Show rotation status for all methods
-    Status,
+enum TrustAction {
+    /// List trusted and blocked payees
+    List,
 
-    /// Set rotation policy for a specific method
-    Policy {
-        /// Payment method ID (e.g., "onchain", "lightning")
-        method: String,
+    /// Mark a payee as trusted
+    Add {
+        /// Public key, Pubky URI or contact name
+        key: String,
 
-        /// Policy: on-use, manual, after:<count>, periodic:<seconds>
-        policy: String,
+        /// Why this payee is trusted
+        #[arg(short, long)]
+        reason: Option<String>,
     },
 
-    /// Set the default rotation policy
-    Default {
-        /// Policy: on-use, manual, after:<count>, periodic:<seconds>
-        policy: String,
+    /// Block payments to a payee
+    Block {
+        /// Public key, Pubky URI or contact name
+        key: String,
+
+        /// Why this payee is blocked
+        #[arg(short, long)]
+        reason: Option<String>,
     },
 
-    /// Enable or disable auto-rotation after payments
-    AutoRotate {
-        /// Enable auto-rotation (omit to disable)
-        #[arg(long)]
-        enable: bool,
+    /// Remove a payee from the trust policy
+    Remove {
+        /// Public key, Pubky URI or contact name
+        key: String,
     },
 
-    /// Manually trigger rotation for a method
-    Rotate {
-        /// Payment method ID
-        method: String,
+    /// Show how payments to a payee would be treated
+    Check {
+        /// Public key, Pubky URI or contact name
+        key: String,
     },
 
-    /// Show rotation history for auditing
-    History {
-        /// Filter by payment method (optional)
-        #[arg(short, long)]
-        method: Option<String>,
+    /// Require confirmation before paying unknown payees
+    ConfirmUnknown {
+        /// Require confirmation (omit to disable)
+        #[arg(long)]
+        enable: bool,
     },
 
-    /// Clear rotation history
-    ClearHistory,
+    /// Require verified contacts for large payments
+    RequireVerified {
+        /// Amount in sats above which the payee must be verified (omit to disable)
+        #[arg(long)]
+        above: Option<u64>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -381,6 +1350,21 @@ enum SubscriptionAction {
         subscription_id: String,
     },
 
+    /// Cancel a subscription agreement
+    Cancel {
+        /// Subscription ID
+        subscription_id: String,
+
+        /// Reason code: no_longer_needed, too_expensive, switching_provider,
+        /// payment_issue, service_issue, terms_violation, other
+        #[arg(short, long, default_value = "no_longer_needed")]
+        reason: String,
+
+        /// Free-form note for the counterparty
+        #[arg(short, long)]
+        note: Option<String>,
+    },
+
     /// List subscription agreements
     ListAgreements {
         /// Filter by peer (contact name or public key)
@@ -492,6 +1476,13 @@ enum SubscriptionAction {
         count: usize,
     },
 
+    /// Show charges coming up on active subscriptions
+    Upcoming {
+        /// Number of days to look ahead
+        #[arg(short, long, default_value = "30")]
+        days: u32,
+    },
+
     /// Calculate proration for subscription changes
     Prorate {
         /// Current amount per period (sats)
@@ -547,17 +1538,67 @@ enum WalletAction {
         network: Option<String>,
     },
 
+    /// Configure Core Lightning (clnrest) for Lightning payments
+    ConfigureCln {
+        /// clnrest API URL
+        #[arg(long)]
+        url: String,
+
+        /// Rune authorizing pay, decode, getroute and listpays
+        #[arg(long)]
+        rune: String,
+
+        /// clnrest CA certificate in PEM format (optional)
+        #[arg(long)]
+        tls_cert: Option<String>,
+
+        /// Network (mainnet, testnet, signet, regtest)
+        #[arg(long)]
+        network: Option<String>,
+    },
+
     /// Configure Esplora for on-chain payments
     ConfigureEsplora {
         /// Esplora API URL
         #[arg(long)]
         url: String,
 
+        /// Watch-only address funding on-chain payments (repeatable)
+        #[arg(long = "funding-address")]
+        funding_addresses: Vec<String>,
+
+        /// Change address (defaults to the first funding address)
+        #[arg(long)]
+        change_address: Option<String>,
+
         /// Network (mainnet, testnet, signet, regtest)
         #[arg(long)]
         network: Option<String>,
     },
 
+    /// Configure a BTCPay Server store for receiving payments
+    ConfigureBtcpay {
+        /// BTCPay Server URL
+        #[arg(long)]
+        url: String,
+
+        /// Greenfield API key
+        #[arg(long)]
+        api_key: String,
+
+        /// Store ID
+        #[arg(long)]
+        store_id: String,
+
+        /// Secret of the store webhook pointing at `receive --webhook-port`
+        #[arg(long)]
+        webhook_secret: Option<String>,
+
+        /// Lightning address or LNURL published for the store
+        #[arg(long)]
+        lightning_address: Option<String>,
+    },
+
     /// Apply a preset configuration
     Preset {
         /// Preset name (polar, testnet, signet, mutinynet)
@@ -570,6 +1611,96 @@ enum WalletAction {
 
     /// Clear wallet configuration
     Clear,
+
+    /// Manage named wallet profiles, each with its own network and storage
+    Profile {
+        #[command(subcommand)]
+        action: WalletProfileAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum WalletProfileAction {
+    /// List profiles and show the active one
+    List,
+
+    /// Create a profile
+    Create {
+        /// Profile name, e.g. testnet-dev
+        name: String,
+
+        /// Network (mainnet, testnet, signet, regtest)
+        #[arg(long, default_value = "testnet")]
+        network: String,
+
+        /// Make it the active profile
+        #[arg(long = "use")]
+        activate: bool,
+    },
+
+    /// Make a profile the active one
+    Use {
+        /// Profile name
+        name: String,
+    },
+
+    /// Delete a profile and everything stored in it
+    Remove {
+        /// Profile name
+        name: String,
+
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ReceiptsAction {
+    /// Render a receipt as a document for the payer
+    Render {
+        /// Receipt ID
+        receipt_id: String,
+
+        /// Output format (html, pdf)
+        #[arg(short, long, default_value = "html")]
+        format: String,
+
+        /// Output file (defaults to receipt-<id>.<format>)
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// JSON file with merchant branding
+        #[arg(short, long)]
+        branding: Option<String>,
+    },
+
+    /// Export receipts for an accounting tool
+    Export {
+        /// Output format (csv, quickbooks, xero)
+        #[arg(short, long, default_value = "csv")]
+        format: String,
+
+        /// Output file (defaults to receipts-<format>.csv)
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// JSON file mapping receipts to ledger accounts
+        #[arg(short, long)]
+        accounts: Option<String>,
+
+        /// Convert amounts to this currency (e.g. USD)
+        #[arg(short, long)]
+        currency: Option<String>,
+
+        /// Exchange rate as FROM/TO=RATE (e.g. SAT/USD=0.0006); can be repeated
+        #[arg(short, long)]
+        rate: Vec<String>,
+
+        /// Filter as key=value (payer, payee, method, min_amount, from, to, text); can be repeated
+        #[arg(long)]
+        filter: Vec<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -596,12 +1727,20 @@ enum QrAction {
         /// Payment method (lightning, onchain)
         #[arg(short, long, default_value = "lightning")]
         method: String,
+
+        /// Seconds until the request expires (0 for no expiry)
+        #[arg(long, default_value = "3600")]
+        expires_in: u64,
     },
 
     /// Parse a scanned QR code or URI
     Parse {
         /// The URI or QR data to parse
         data: String,
+
+        /// Pay a valid signed payment request immediately
+        #[arg(long)]
+        pay: bool,
     },
 }
 
@@ -639,6 +1778,16 @@ enum ContactAction {
         name: String,
     },
 
+    /// Verify a contact's key by comparing a short code or scanning a QR code
+    Verify {
+        /// Contact name
+        name: String,
+
+        /// Verification code scanned from the contact's device
+        #[arg(short, long)]
+        code: Option<String>,
+    },
+
     /// Discover contacts from Pubky follows directory
     Discover {
         /// Auto-import discovered contacts
@@ -649,6 +1798,20 @@ enum ContactAction {
         #[arg(long, default_value = "https://demo.httprelay.io")]
         homeserver: String,
     },
+
+    /// Import contacts from a CSV, vCard or nostr contact list file
+    Import {
+        /// File to import
+        file: String,
+
+        /// File format (csv, vcard, nostr); guessed from the extension if omitted
+        #[arg(short, long)]
+        format: Option<String>,
+
+        /// Show what would be imported without saving
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -702,22 +1865,46 @@ enum ProfileAction {
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
+async fn main() {
+    let config = config::Config::load().unwrap_or_else(|e| {
+        eprintln!("Error: {:#}", e);
+        std::process::exit(output::EXIT_FAILURE);
+    });
+    let matches = config.apply(Cli::command()).get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    output::init(cli.output, cli.quiet, command_name(&matches));
+
+    let result = run(cli).await;
+    std::process::exit(output::finish(&result));
+}
+
+/// Full subcommand path, e.g. "wallet health"
+fn command_name(matches: &ArgMatches) -> String {
+    let mut names = Vec::new();
+    let mut current = matches;
+    while let Some((name, sub)) = current.subcommand() {
+        names.push(name);
+        current = sub;
+    }
+    names.join(" ")
+}
 
-    // Initialize tracing
+async fn run(cli: Cli) -> Result<()> {
+    // Initialize tracing; logs go to stderr so stdout stays parseable
     if cli.verbose {
         tracing_subscriber::fmt()
             .with_env_filter("paykit_demo_cli=debug,paykit_lib=debug,paykit_interactive=debug,paykit_subscriptions=debug")
+            .with_writer(std::io::stderr)
             .init();
     } else {
         tracing_subscriber::fmt()
             .with_env_filter("paykit_demo_cli=info,paykit_lib=warn,paykit_interactive=warn")
+            .with_writer(std::io::stderr)
             .init();
     }
 
     // Setup storage directory
-    let storage_dir = if let Some(dir) = cli.storage_dir {
+    let root_dir = if let Some(dir) = cli.storage_dir {
         std::path::PathBuf::from(dir)
     } else {
         dirs::data_local_dir()
@@ -725,10 +1912,40 @@ async fn main() -> Result<()> {
             .join("paykit-demo")
     };
 
+    // Everything but profile management runs inside the profile's namespace
+    let managing_profiles = matches!(
+        cli.command,
+        Commands::Wallet {
+            action: WalletAction::Profile { .. }
+        }
+    );
+    let storage_dir = if managing_profiles {
+        root_dir.clone()
+    } else {
+        let (profile, dir) =
+            paykit_demo_core::WalletProfiles::new(&root_dir).resolve(cli.profile.as_deref())?;
+        if cli.verbose {
+            ui::info(&format!("Wallet profile: {}", profile));
+        }
+        dir
+    };
+
+    // Configure the proxy before any network client is created
+    commands::init_proxy(cli.proxy.as_deref(), &cli.proxy_bypass)?;
+    commands::init_path_root(cli.path_root.as_deref())?;
+
+    // Unlock encrypted storage before any command touches it
+    if !matches!(
+        cli.command,
+        Commands::Storage { .. } | Commands::Completions { .. } | Commands::Config
+    ) {
+        commands::storage::unlock(&storage_dir).await?;
+    }
+
     // Dispatch commands
     match cli.command {
-        Commands::Setup { name } => {
-            commands::setup::run(&storage_dir, name, cli.verbose).await?;
+        Commands::Setup { name, keyring } => {
+            commands::setup::run(&storage_dir, name, keyring, cli.verbose).await?;
         }
         Commands::Whoami => {
             commands::whoami::run(&storage_dir, cli.verbose).await?;
@@ -742,11 +1959,16 @@ async fn main() -> Result<()> {
         Commands::Migrate => {
             commands::migrate::run(&storage_dir, cli.verbose).await?;
         }
-        Commands::Backup { output } => {
-            commands::backup::export(&storage_dir, output.as_deref(), cli.verbose).await?;
+        Commands::Backup { output, keyring } => {
+            commands::backup::export(&storage_dir, output.as_deref(), keyring, cli.verbose).await?;
         }
-        Commands::Restore { input, name } => {
-            commands::backup::import(&storage_dir, &input, name.as_deref(), cli.verbose).await?;
+        Commands::Restore {
+            input,
+            name,
+            keyring,
+        } => {
+            commands::backup::import(&storage_dir, &input, name.as_deref(), keyring, cli.verbose)
+                .await?;
         }
         Commands::Wallet { action } => match action {
             WalletAction::Status => {
@@ -771,15 +1993,56 @@ async fn main() -> Result<()> {
                 )
                 .await?;
             }
-            WalletAction::ConfigureEsplora { url, network } => {
+            WalletAction::ConfigureCln {
+                url,
+                rune,
+                tls_cert,
+                network,
+            } => {
+                commands::wallet::configure_cln(
+                    &storage_dir,
+                    &url,
+                    &rune,
+                    tls_cert.as_deref(),
+                    network.as_deref(),
+                    cli.verbose,
+                )
+                .await?;
+            }
+            WalletAction::ConfigureEsplora {
+                url,
+                funding_addresses,
+                change_address,
+                network,
+            } => {
                 commands::wallet::configure_esplora(
                     &storage_dir,
                     &url,
+                    &funding_addresses,
+                    change_address.as_deref(),
                     network.as_deref(),
                     cli.verbose,
                 )
                 .await?;
             }
+            WalletAction::ConfigureBtcpay {
+                url,
+                api_key,
+                store_id,
+                webhook_secret,
+                lightning_address,
+            } => {
+                commands::wallet::configure_btcpay(
+                    &storage_dir,
+                    &url,
+                    &api_key,
+                    &store_id,
+                    webhook_secret.as_deref(),
+                    lightning_address.as_deref(),
+                    cli.verbose,
+                )
+                .await?;
+            }
             WalletAction::Preset { name, macaroon } => {
                 commands::wallet::apply_preset(
                     &storage_dir,
@@ -789,6 +2052,31 @@ async fn main() -> Result<()> {
                 )
                 .await?;
             }
+            WalletAction::Profile { action } => match action {
+                WalletProfileAction::List => {
+                    commands::wallet::profile_list(&root_dir, cli.verbose).await?;
+                }
+                WalletProfileAction::Create {
+                    name,
+                    network,
+                    activate,
+                } => {
+                    commands::wallet::profile_create(
+                        &root_dir,
+                        &name,
+                        &network,
+                        activate,
+                        cli.verbose,
+                    )
+                    .await?;
+                }
+                WalletProfileAction::Use { name } => {
+                    commands::wallet::profile_use(&root_dir, &name, cli.verbose).await?;
+                }
+                WalletProfileAction::Remove { name, yes } => {
+                    commands::wallet::profile_remove(&root_dir, &name, yes, cli.verbose).await?;
+                }
+            },
             WalletAction::Clear => {
                 commands::wallet::clear(&storage_dir, cli.verbose).await?;
             }
@@ -796,14 +2084,78 @@ async fn main() -> Result<()> {
         Commands::Publish {
             onchain,
             lightning,
+            ecash,
+            btcpay,
             homeserver,
         } => {
-            commands::publish::run(&storage_dir, onchain, lightning, &homeserver, cli.verbose)
-                .await?;
+            commands::publish::run(
+                &storage_dir,
+                onchain,
+                lightning,
+                ecash,
+                btcpay,
+                &homeserver,
+                cli.verbose,
+            )
+            .await?;
         }
         Commands::Discover { uri, homeserver } => {
             commands::discover::run(&storage_dir, &uri, &homeserver, cli.verbose).await?;
         }
+        Commands::Status { action } => match action {
+            StatusAction::Set {
+                paused,
+                unavailable,
+                preferred,
+                maintenance_from,
+                maintenance_until,
+                message,
+                ttl,
+                homeserver,
+            } => {
+                let options = commands::status::StatusOptions {
+                    paused,
+                    unavailable,
+                    preferred,
+                    maintenance_from,
+                    maintenance_until,
+                    message,
+                    ttl_secs: ttl,
+                };
+                commands::status::set(&storage_dir, options, &homeserver, cli.verbose).await?;
+            }
+            StatusAction::Show { uri, homeserver } => {
+                commands::status::show(&storage_dir, uri.as_deref(), &homeserver).await?;
+            }
+            StatusAction::Clear { homeserver } => {
+                commands::status::clear(&storage_dir, &homeserver).await?;
+            }
+        },
+        Commands::Mirror { action } => match action {
+            MirrorAction::Sync { uris, homeserver } => {
+                commands::mirror::sync(&storage_dir, &uris, &homeserver).await?;
+            }
+            MirrorAction::Run {
+                uris,
+                interval,
+                port,
+                homeserver,
+            } => {
+                commands::mirror::run(&storage_dir, &uris, &homeserver, interval, port).await?;
+            }
+            MirrorAction::Status => {
+                commands::mirror::status(&storage_dir).await?;
+            }
+            MirrorAction::Verify { uris } => {
+                commands::mirror::verify(&storage_dir, &uris).await?;
+            }
+            MirrorAction::Remove { uri } => {
+                commands::mirror::remove(&storage_dir, &uri).await?;
+            }
+        },
+        Commands::Diagnose { endpoint, json } => {
+            commands::diagnose::run(&storage_dir, &endpoint, json, cli.verbose).await?;
+        }
         Commands::Profile { action } => match action {
             ProfileAction::Fetch {
                 uri,
@@ -870,13 +2222,66 @@ async fn main() -> Result<()> {
             ContactAction::Show { name } => {
                 commands::contacts::show(&storage_dir, &name, cli.verbose).await?;
             }
+            ContactAction::Verify { name, code } => {
+                commands::contacts::verify(&storage_dir, &name, code.as_deref(), cli.verbose)
+                    .await?;
+            }
             ContactAction::Discover { import, homeserver } => {
                 commands::contacts::discover(&storage_dir, import, &homeserver, cli.verbose)
                     .await?;
             }
+            ContactAction::Import {
+                file,
+                format,
+                dry_run,
+            } => {
+                commands::contacts::import(
+                    &storage_dir,
+                    std::path::Path::new(&file),
+                    format.as_deref(),
+                    dry_run,
+                    cli.verbose,
+                )
+                .await?;
+            }
         },
-        Commands::Receive { port } => {
-            commands::receive::run(&storage_dir, port, cli.verbose).await?;
+        Commands::Receive {
+            port,
+            noise_epochs,
+            webhook_port,
+            max_messages_per_minute,
+            tenants,
+            metrics_port,
+        } => {
+            commands::receive::run(
+                &storage_dir,
+                port,
+                &noise_epochs,
+                webhook_port,
+                max_messages_per_minute,
+                commands::receive::Hosting {
+                    tenants,
+                    metrics_port,
+                },
+                cli.verbose,
+            )
+            .await?;
+        }
+        Commands::Pos {
+            port,
+            method,
+            timeout,
+            export,
+        } => {
+            commands::pos::run(
+                &storage_dir,
+                port,
+                &method,
+                timeout,
+                export.map(std::path::PathBuf::from),
+                cli.verbose,
+            )
+            .await?;
         }
         Commands::Pay {
             recipient,
@@ -884,7 +2289,9 @@ async fn main() -> Result<()> {
             currency,
             method,
             strategy,
+            note,
             dry_run,
+            privacy_report,
         } => {
             commands::pay::run(
                 &storage_dir,
@@ -893,18 +2300,63 @@ async fn main() -> Result<()> {
                 currency,
                 &method,
                 &strategy,
+                note,
                 dry_run,
+                privacy_report,
                 cli.verbose,
             )
             .await?;
         }
-        Commands::Receipts { id } => {
-            if let Some(receipt_id) = id {
-                commands::receipts::show(&storage_dir, &receipt_id, cli.verbose).await?;
-            } else {
-                commands::receipts::run(&storage_dir, cli.verbose).await?;
+        Commands::Receipts {
+            action,
+            id,
+            filter,
+            limit,
+            cursor,
+        } => match action {
+            Some(ReceiptsAction::Render {
+                receipt_id,
+                format,
+                output,
+                branding,
+            }) => {
+                commands::receipts::render(
+                    &storage_dir,
+                    &receipt_id,
+                    &format,
+                    output.as_deref(),
+                    branding.as_deref(),
+                )
+                .await?;
             }
-        }
+            Some(ReceiptsAction::Export {
+                format,
+                output,
+                accounts,
+                currency,
+                rate,
+                filter,
+            }) => {
+                commands::receipts::export(
+                    &storage_dir,
+                    &format,
+                    output.as_deref(),
+                    accounts.as_deref(),
+                    currency.as_deref(),
+                    &rate,
+                    &filter,
+                )
+                .await?;
+            }
+            None => {
+                if let Some(receipt_id) = id {
+                    commands::receipts::show(&storage_dir, &receipt_id, cli.verbose).await?;
+                } else {
+                    commands::receipts::run(&storage_dir, &filter, limit, cursor, cli.verbose)
+                        .await?;
+                }
+            }
+        },
 
         Commands::VerifyProof { receipt_id } => {
             commands::receipts::verify_proof(&storage_dir, &receipt_id, cli.verbose).await?;
@@ -920,12 +2372,93 @@ async fn main() -> Result<()> {
                 amount,
                 description,
                 method,
+                expires_in,
+            } => {
+                commands::qr::request(
+                    &storage_dir,
+                    amount,
+                    description,
+                    &method,
+                    expires_in,
+                    cli.verbose,
+                )
+                .await?;
+            }
+            QrAction::Parse { data, pay } => {
+                commands::qr::parse(&storage_dir, &data, pay, cli.verbose).await?;
+            }
+        },
+        Commands::Analytics { action } => match action {
+            AnalyticsAction::Enable {
+                k,
+                no_retain_receipts,
+            } => {
+                commands::analytics::enable(&storage_dir, k, no_retain_receipts).await?;
+            }
+            AnalyticsAction::Disable => {
+                commands::analytics::disable(&storage_dir).await?;
+            }
+            AnalyticsAction::Show {
+                from,
+                to,
+                by_method,
+            } => {
+                commands::analytics::show(&storage_dir, from, to, by_method).await?;
+            }
+            AnalyticsAction::Clear => {
+                commands::analytics::clear(&storage_dir).await?;
+            }
+        },
+        Commands::ApiKey { action } => match action {
+            ApiKeyAction::Create {
+                name,
+                role,
+                rate_limit,
+                expires_days,
             } => {
-                commands::qr::request(&storage_dir, amount, description, &method, cli.verbose)
+                commands::api_key::create(&storage_dir, &name, &role, rate_limit, expires_days)
                     .await?;
             }
-            QrAction::Parse { data } => {
-                commands::qr::parse(&data, cli.verbose).await?;
+            ApiKeyAction::List => {
+                commands::api_key::list(&storage_dir).await?;
+            }
+            ApiKeyAction::Revoke { id } => {
+                commands::api_key::revoke(&storage_dir, &id).await?;
+            }
+            ApiKeyAction::Rotate { id, grace_minutes } => {
+                commands::api_key::rotate(&storage_dir, &id, grace_minutes).await?;
+            }
+            ApiKeyAction::Audit { key, limit } => {
+                commands::api_key::audit(&storage_dir, key.as_deref(), limit).await?;
+            }
+        },
+        Commands::Timestamp { action } => match action {
+            TimestampAction::Enable { calendars } => {
+                commands::timestamp::enable(&storage_dir, calendars).await?;
+            }
+            TimestampAction::Disable => {
+                commands::timestamp::disable(&storage_dir).await?;
+            }
+            TimestampAction::Queue { receipt_ids, all } => {
+                commands::timestamp::queue(&storage_dir, receipt_ids, all).await?;
+            }
+            TimestampAction::Submit => {
+                commands::timestamp::submit(&storage_dir).await?;
+            }
+            TimestampAction::Upgrade => {
+                commands::timestamp::upgrade(&storage_dir).await?;
+            }
+            TimestampAction::Verify {
+                receipt_id,
+                esplora,
+            } => {
+                commands::timestamp::verify(&storage_dir, &receipt_id, esplora.as_deref()).await?;
+            }
+            TimestampAction::Status => {
+                commands::timestamp::status(&storage_dir).await?;
+            }
+            TimestampAction::Export { receipt_id, output } => {
+                commands::timestamp::export(&storage_dir, &receipt_id, output.as_deref()).await?;
             }
         },
         Commands::Dashboard => {
@@ -988,6 +2521,354 @@ async fn main() -> Result<()> {
                 commands::rotation::clear_history(&storage_dir, cli.verbose).await?;
             }
         },
+        Commands::Trust { action } => match action {
+            TrustAction::List => {
+                commands::trust::list(&storage_dir, cli.verbose).await?;
+            }
+            TrustAction::Add { key, reason } => {
+                commands::trust::set(
+                    &storage_dir,
+                    &key,
+                    paykit_lib::policy::TrustLevel::Trusted,
+                    reason,
+                    cli.verbose,
+                )
+                .await?;
+            }
+            TrustAction::Block { key, reason } => {
+                commands::trust::set(
+                    &storage_dir,
+                    &key,
+                    paykit_lib::policy::TrustLevel::Blocked,
+                    reason,
+                    cli.verbose,
+                )
+                .await?;
+            }
+            TrustAction::Remove { key } => {
+                commands::trust::remove(&storage_dir, &key, cli.verbose).await?;
+            }
+            TrustAction::Check { key } => {
+                commands::trust::check(&storage_dir, &key, cli.verbose).await?;
+            }
+            TrustAction::ConfirmUnknown { enable } => {
+                commands::trust::confirm_unknown(&storage_dir, enable, cli.verbose).await?;
+            }
+            TrustAction::RequireVerified { above } => {
+                commands::trust::require_verified(&storage_dir, above, cli.verbose).await?;
+            }
+        },
+        Commands::Approvals { action } => match action {
+            ApprovalAction::Policy {
+                threshold,
+                required,
+                cosigners,
+                ttl,
+            } => {
+                commands::approvals::policy(
+                    &storage_dir,
+                    threshold,
+                    required,
+                    cosigners,
+                    ttl,
+                    cli.verbose,
+                )
+                .await?;
+            }
+            ApprovalAction::Disable => {
+                commands::approvals::disable(&storage_dir, cli.verbose).await?;
+            }
+            ApprovalAction::List { all } => {
+                commands::approvals::list(&storage_dir, all, cli.verbose).await?;
+            }
+            ApprovalAction::Approve { id } => {
+                commands::approvals::decide(
+                    &storage_dir,
+                    &id,
+                    paykit_lib::approvals::ApprovalDecision::Approve,
+                    cli.verbose,
+                )
+                .await?;
+            }
+            ApprovalAction::Reject { id } => {
+                commands::approvals::decide(
+                    &storage_dir,
+                    &id,
+                    paykit_lib::approvals::ApprovalDecision::Reject,
+                    cli.verbose,
+                )
+                .await?;
+            }
+            ApprovalAction::Export { id } => {
+                commands::approvals::export(&storage_dir, &id, cli.verbose).await?;
+            }
+            ApprovalAction::Sign { pending, reject } => {
+                let decision = if reject {
+                    paykit_lib::approvals::ApprovalDecision::Reject
+                } else {
+                    paykit_lib::approvals::ApprovalDecision::Approve
+                };
+                commands::approvals::sign(&storage_dir, &pending, decision, cli.verbose).await?;
+            }
+            ApprovalAction::Import { approval } => {
+                commands::approvals::import(&storage_dir, &approval, cli.verbose).await?;
+            }
+        },
+        Commands::Sweep { action } => match action {
+            SweepAction::Policy {
+                destination,
+                threshold,
+                max_fee,
+                reserve,
+                target_blocks,
+                every,
+            } => {
+                commands::sweep::policy(
+                    &storage_dir,
+                    destination,
+                    threshold,
+                    max_fee,
+                    reserve,
+                    target_blocks,
+                    every,
+                    cli.verbose,
+                )
+                .await?;
+            }
+            SweepAction::Disable => {
+                commands::sweep::disable(&storage_dir, cli.verbose).await?;
+            }
+            SweepAction::Run { balance, force } => {
+                commands::sweep::run(&storage_dir, balance, force, cli.verbose).await?;
+            }
+            SweepAction::Watch { balance, poll } => {
+                commands::sweep::watch(&storage_dir, balance, poll, cli.verbose).await?;
+            }
+            SweepAction::History => {
+                commands::sweep::history(&storage_dir, cli.verbose).await?;
+            }
+        },
+        Commands::Standing { action } => match action {
+            StandingAction::Add {
+                id,
+                payee,
+                amount,
+                every,
+                weekly,
+                monthly,
+                method,
+                memo,
+                max_runs,
+            } => {
+                commands::standing::add(
+                    &storage_dir,
+                    &id,
+                    &payee,
+                    amount,
+                    every,
+                    weekly,
+                    monthly,
+                    method,
+                    memo,
+                    max_runs,
+                    cli.verbose,
+                )
+                .await?;
+            }
+            StandingAction::List => {
+                commands::standing::list(&storage_dir, cli.verbose).await?;
+            }
+            StandingAction::Pause { id } => {
+                commands::standing::set_paused(&storage_dir, &id, true, cli.verbose).await?;
+            }
+            StandingAction::Resume { id } => {
+                commands::standing::set_paused(&storage_dir, &id, false, cli.verbose).await?;
+            }
+            StandingAction::Remove { id } => {
+                commands::standing::remove(&storage_dir, &id, cli.verbose).await?;
+            }
+            StandingAction::Release { id } => {
+                commands::standing::release(&storage_dir, &id, cli.verbose).await?;
+            }
+            StandingAction::Run => {
+                commands::standing::run(&storage_dir, cli.verbose).await?;
+            }
+            StandingAction::Watch { poll } => {
+                commands::standing::watch(&storage_dir, poll, cli.verbose).await?;
+            }
+        },
+        Commands::Payments { action } => match action {
+            PaymentsAction::Schedule {
+                payee,
+                amount,
+                at,
+                delay,
+                window,
+                method,
+                memo,
+                id,
+            } => {
+                commands::payments::schedule(
+                    &storage_dir,
+                    &payee,
+                    amount,
+                    at,
+                    delay,
+                    window,
+                    method,
+                    memo,
+                    id,
+                    cli.verbose,
+                )
+                .await?;
+            }
+            PaymentsAction::Scheduled { all } => {
+                commands::payments::list(&storage_dir, all, cli.verbose).await?;
+            }
+            PaymentsAction::Cancel { id } => {
+                commands::payments::cancel(&storage_dir, &id, cli.verbose).await?;
+            }
+            PaymentsAction::Release { id } => {
+                commands::payments::release(&storage_dir, &id, cli.verbose).await?;
+            }
+            PaymentsAction::Run => {
+                commands::payments::run(&storage_dir, cli.verbose).await?;
+            }
+            PaymentsAction::Watch { poll } => {
+                commands::payments::watch(&storage_dir, poll, cli.verbose).await?;
+            }
+        },
+        Commands::Link { action } => match action {
+            LinkAction::Create {
+                id,
+                amount,
+                memo,
+                expires_in,
+                multi_use,
+                max_uses,
+                method,
+                redirect_url,
+                success_message,
+                homeserver,
+            } => {
+                let options = commands::link::LinkOptions {
+                    amount,
+                    memo,
+                    expires_in,
+                    multi_use,
+                    max_uses,
+                    methods: method,
+                    redirect_url,
+                    success_message,
+                };
+                commands::link::create(&storage_dir, &id, options, &homeserver, cli.verbose)
+                    .await?;
+            }
+            LinkAction::List => {
+                commands::link::list(&storage_dir, cli.verbose).await?;
+            }
+            LinkAction::Resolve { uri } => {
+                commands::link::resolve(&uri).await?;
+            }
+            LinkAction::Revoke { id, homeserver } => {
+                commands::link::revoke(&storage_dir, &id, &homeserver).await?;
+            }
+            LinkAction::Sync { homeserver } => {
+                commands::link::sync(&storage_dir, &homeserver).await?;
+            }
+        },
+        Commands::Voucher { action } => match action {
+            VoucherAction::Issue {
+                id,
+                amount,
+                method,
+                memo,
+                expires_in,
+            } => {
+                commands::voucher::issue(
+                    &storage_dir,
+                    &id,
+                    amount,
+                    method,
+                    memo,
+                    expires_in,
+                    cli.verbose,
+                )
+                .await?;
+            }
+            VoucherAction::List => {
+                commands::voucher::list(&storage_dir, cli.verbose).await?;
+            }
+            VoucherAction::Status { id } => {
+                commands::voucher::status(&storage_dir, &id, cli.verbose).await?;
+            }
+            VoucherAction::Show { code } => {
+                commands::voucher::show(&code).await?;
+            }
+            VoucherAction::Redeem { code, holder } => {
+                commands::voucher::redeem(&storage_dir, &code, &holder, cli.verbose).await?;
+            }
+            VoucherAction::Release { id } => {
+                commands::voucher::release(&storage_dir, &id, cli.verbose).await?;
+            }
+            VoucherAction::Revoke { id } => {
+                commands::voucher::revoke(&storage_dir, &id, cli.verbose).await?;
+            }
+        },
+        Commands::Storage { action } => match action {
+            StorageAction::Status => {
+                commands::storage::status(&storage_dir, cli.verbose).await?;
+            }
+            StorageAction::Encrypt { passphrase } => {
+                commands::storage::encrypt(&storage_dir, passphrase, cli.verbose).await?;
+            }
+        },
+        Commands::Completions { shell } => {
+            clap_complete::generate(
+                shell,
+                &mut Cli::command(),
+                "paykit-demo",
+                &mut std::io::stdout(),
+            );
+        }
+        Commands::Config => {
+            commands::config::show(cli.verbose).await?;
+        }
+        Commands::Audit { action } => match action {
+            AuditAction::List {
+                operation,
+                counterparty,
+                days,
+                limit,
+            } => {
+                let query = commands::audit::query(
+                    operation.as_deref(),
+                    counterparty.as_deref(),
+                    days,
+                    Some(limit),
+                )?;
+                commands::audit::list(&storage_dir, query, cli.verbose).await?;
+            }
+            AuditAction::Verify => {
+                commands::audit::verify(&storage_dir, cli.verbose).await?;
+            }
+            AuditAction::Export {
+                format,
+                output,
+                operation,
+                days,
+            } => {
+                let query = commands::audit::query(operation.as_deref(), None, days, None)?;
+                commands::audit::export(
+                    &storage_dir,
+                    query,
+                    &format,
+                    output.as_deref(),
+                    cli.verbose,
+                )
+                .await?;
+            }
+        },
         Commands::Subscriptions { action } => match action {
             SubscriptionAction::Request {
                 recipient,
@@ -1047,6 +2928,19 @@ async fn main() -> Result<()> {
                 commands::subscriptions::accept_subscription(&storage_dir, &subscription_id)
                     .await?;
             }
+            SubscriptionAction::Cancel {
+                subscription_id,
+                reason,
+                note,
+            } => {
+                commands::subscriptions::cancel_subscription(
+                    &storage_dir,
+                    &subscription_id,
+                    &reason,
+                    note,
+                )
+                .await?;
+            }
             SubscriptionAction::ListAgreements { peer, active } => {
                 commands::subscriptions::list_subscriptions(&storage_dir, peer, active).await?;
             }
@@ -1129,6 +3023,9 @@ async fn main() -> Result<()> {
             SubscriptionAction::RecentPayments { count } => {
                 commands::subscriptions::show_recent_autopayments(&storage_dir, count).await?;
             }
+            SubscriptionAction::Upcoming { days } => {
+                commands::subscriptions::show_upcoming_charges(&storage_dir, days).await?;
+            }
             SubscriptionAction::Prorate {
                 current_amount,
                 new_amount,
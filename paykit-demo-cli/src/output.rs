@@ -0,0 +1,224 @@
+//! Output modes shared by every command
+//!
+//! Commands report progress through [`crate::ui`] and hand their result to
+//! [`emit`]. With `--output json` the UI helpers stay silent and `main`
+//! prints exactly one JSON document on stdout once the command finishes:
+//!
+//! ```text
+//! {"schema":1,"command":"discover","ok":true,"data":{...}}
+//! {"schema":1,"command":"pay","ok":false,"error":{"code":"transport","paykit_code":2000,"message":"..."}}
+//! ```
+//!
+//! `data` is `null` for commands that produce nothing beyond success.
+//! `--quiet` silences the UI helpers in text mode as well; errors still go
+//! to stderr. Either way the exit code follows the error's
+//! [`PaykitErrorCode`], see [`exit_code`].
+
+use clap::ValueEnum;
+use paykit_lib::{PaykitError, PaykitErrorCode};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::{Mutex, OnceLock};
+
+/// Version of the JSON envelope. Bumped on incompatible changes to it or to
+/// any command's `data`.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Exit code for failures that carry no Paykit error code.
+pub const EXIT_FAILURE: i32 = 1;
+
+/// How command results are printed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// One JSON document on stdout
+    Json,
+}
+
+struct Mode {
+    format: OutputFormat,
+    quiet: bool,
+    command: String,
+}
+
+static MODE: OnceLock<Mode> = OnceLock::new();
+static RESULT: Mutex<Option<Value>> = Mutex::new(None);
+
+/// Select the output mode; called once by `main` before dispatching.
+pub fn init(format: OutputFormat, quiet: bool, command: String) {
+    let _ = MODE.set(Mode {
+        format,
+        quiet,
+        command,
+    });
+}
+
+/// Whether results are printed as JSON
+pub fn is_json() -> bool {
+    MODE.get()
+        .is_some_and(|mode| mode.format == OutputFormat::Json)
+}
+
+/// Whether human-readable progress output is suppressed
+pub fn is_silent() -> bool {
+    MODE.get()
+        .is_some_and(|mode| mode.quiet || mode.format == OutputFormat::Json)
+}
+
+/// Record the command's result for the JSON document.
+///
+/// Text mode ignores it: commands print their own human-readable output.
+pub fn emit<T: Serialize>(data: &T) {
+    if !is_json() {
+        return;
+    }
+    let value = serde_json::to_value(data)
+        .unwrap_or_else(|e| json!({ "serialization_error": e.to_string() }));
+    *RESULT.lock().unwrap_or_else(|e| e.into_inner()) = Some(value);
+}
+
+/// Add a field to the result recorded by the last [`emit`].
+///
+/// For details gathered before the command knows which result it ends with.
+/// Does nothing unless that result is a JSON object.
+pub fn attach<T: Serialize>(key: &str, data: &T) {
+    if !is_json() {
+        return;
+    }
+    let value = serde_json::to_value(data)
+        .unwrap_or_else(|e| json!({ "serialization_error": e.to_string() }));
+    let mut result = RESULT.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(Value::Object(result)) = result.as_mut() {
+        result.insert(key.to_string(), value);
+    }
+}
+
+/// Print the outcome of the command and return the process exit code.
+pub fn finish(result: &anyhow::Result<()>) -> i32 {
+    let code = match result {
+        Ok(()) => 0,
+        Err(err) => exit_code(err),
+    };
+
+    if is_json() {
+        let command = MODE.get().map_or("", |mode| mode.command.as_str());
+        let document = match result {
+            Ok(()) => json!({
+                "schema": SCHEMA_VERSION,
+                "command": command,
+                "ok": true,
+                "data": RESULT.lock().unwrap_or_else(|e| e.into_inner()).take(),
+            }),
+            Err(err) => json!({
+                "schema": SCHEMA_VERSION,
+                "command": command,
+                "ok": false,
+                "error": {
+                    "code": paykit_code(err).map_or("error", code_name),
+                    "paykit_code": paykit_code(err).map(|code| code as i32),
+                    "message": format!("{:#}", err),
+                },
+            }),
+        };
+        println!("{}", document);
+    } else if let Err(err) = result {
+        eprintln!("Error: {:#}", err);
+    }
+
+    code
+}
+
+/// The Paykit error code behind `err`, if any error in its chain has one.
+pub fn paykit_code(err: &anyhow::Error) -> Option<PaykitErrorCode> {
+    err.chain()
+        .find_map(|cause| cause.downcast_ref::<PaykitError>())
+        .map(PaykitError::code)
+}
+
+/// Process exit code for a failed command.
+///
+/// Paykit errors map to `10 * category + detail`, mirroring the
+/// `PaykitErrorCode` numbering (2001 → 21); anything else exits with 1.
+/// 2 is left to clap for usage errors.
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    use PaykitErrorCode::*;
+
+    match paykit_code(err) {
+        None => EXIT_FAILURE,
+        Some(code) => match code {
+            Unimplemented => 10,
+            Transport => 20,
+            ConnectionFailed => 21,
+            ConnectionTimeout => 22,
+            Auth => 30,
+            SessionExpired => 31,
+            InvalidCredentials => 32,
+            NotFound => 40,
+            MethodNotSupported => 41,
+            InvalidData => 50,
+            ValidationFailed => 51,
+            Serialization => 52,
+            Payment => 60,
+            InsufficientFunds => 61,
+            InvoiceExpired => 62,
+            PaymentRejected => 63,
+            PaymentAlreadyCompleted => 64,
+            FeeSpike => 65,
+            Storage => 70,
+            QuotaExceeded => 71,
+            RateLimited => 80,
+            Internal => 99,
+        },
+    }
+}
+
+/// Stable name of a Paykit error code in JSON output
+fn code_name(code: PaykitErrorCode) -> &'static str {
+    use PaykitErrorCode::*;
+
+    match code {
+        Unimplemented => "unimplemented",
+        Transport => "transport",
+        ConnectionFailed => "connection_failed",
+        ConnectionTimeout => "connection_timeout",
+        Auth => "auth",
+        SessionExpired => "session_expired",
+        InvalidCredentials => "invalid_credentials",
+        NotFound => "not_found",
+        MethodNotSupported => "method_not_supported",
+        InvalidData => "invalid_data",
+        ValidationFailed => "validation_failed",
+        Serialization => "serialization",
+        Payment => "payment",
+        InsufficientFunds => "insufficient_funds",
+        InvoiceExpired => "invoice_expired",
+        PaymentRejected => "payment_rejected",
+        PaymentAlreadyCompleted => "payment_already_completed",
+        FeeSpike => "fee_spike",
+        Storage => "storage",
+        QuotaExceeded => "quota_exceeded",
+        RateLimited => "rate_limited",
+        Internal => "internal",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_exit_codes() {
+        let err = Err::<(), _>(PaykitError::Transport("offline".into()))
+            .context("Failed to query directory")
+            .unwrap_err();
+        assert_eq!(paykit_code(&err), Some(PaykitErrorCode::Transport));
+        assert_eq!(exit_code(&err), 20);
+
+        let err = anyhow::anyhow!("no identity");
+        assert_eq!(paykit_code(&err), None);
+        assert_eq!(exit_code(&err), EXIT_FAILURE);
+    }
+}
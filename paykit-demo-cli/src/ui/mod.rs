@@ -1,5 +1,9 @@
 //! Terminal UI utilities
+//!
+//! Everything printed here is human-readable progress, silenced by
+//! `--quiet` and `--output json` (see [`crate::output`]).
 
+use crate::output;
 use colored::Colorize;
 use console::Term;
 use indicatif::{ProgressBar, ProgressStyle};
@@ -7,36 +11,70 @@ use std::time::Duration;
 
 /// Print a success message
 pub fn success(message: &str) {
+    if output::is_silent() {
+        return;
+    }
     println!("{} {}", "✓".green().bold(), message);
 }
 
 /// Print an error message
 pub fn error(message: &str) {
+    if output::is_json() {
+        return;
+    }
     eprintln!("{} {}", "✗".red().bold(), message);
 }
 
 /// Print an info message
 pub fn info(message: &str) {
+    if output::is_silent() {
+        return;
+    }
     println!("{} {}", "ℹ".blue().bold(), message);
 }
 
 /// Print a warning message
 pub fn warning(message: &str) {
+    if output::is_silent() {
+        return;
+    }
     println!("{} {}", "⚠".yellow().bold(), message);
 }
 
 /// Print a section header
 pub fn header(text: &str) {
+    if output::is_silent() {
+        return;
+    }
     println!("\n{}", text.bold().underline());
 }
 
+/// Print a plain line of text
+pub fn line(text: &str) {
+    if output::is_silent() {
+        return;
+    }
+    println!("{}", text);
+}
+
 /// Print a key-value pair
 pub fn key_value(key: &str, value: &str) {
+    if output::is_silent() {
+        return;
+    }
     println!("  {}: {}", key.cyan(), value);
 }
 
+/// Format a satoshi amount for display, e.g. "1,234,567 sats"
+pub fn sats(amount: u64) -> String {
+    paykit_lib::formatting::format_sats(amount, paykit_lib::i18n::Locale::En)
+}
+
 /// Create a spinner progress indicator
 pub fn spinner(message: &str) -> ProgressBar {
+    if output::is_silent() {
+        return ProgressBar::hidden();
+    }
     let pb = ProgressBar::new_spinner();
     pb.set_style(
         ProgressStyle::default_spinner()
@@ -64,7 +102,6 @@ pub fn input(prompt: &str) -> anyhow::Result<String> {
 }
 
 /// Prompt for text input with default
-#[allow(dead_code)]
 pub fn input_with_default(prompt: &str, default: &str) -> anyhow::Result<String> {
     use dialoguer::Input;
     Ok(Input::new()
@@ -77,6 +114,10 @@ pub fn input_with_default(prompt: &str, default: &str) -> anyhow::Result<String>
 pub fn qr_code(data: &str) -> anyhow::Result<()> {
     use qrcode::QrCode;
 
+    if output::is_silent() {
+        return Ok(());
+    }
+
     let code = QrCode::new(data)?;
     let string = code
         .render::<char>()
@@ -97,11 +138,17 @@ pub fn clear() {
 
 /// Print a separator line
 pub fn separator() {
+    if output::is_silent() {
+        return;
+    }
     println!("{}", "─".repeat(60).dimmed());
 }
 
 /// Print JSON prettily
 pub fn json(value: &serde_json::Value) {
+    if output::is_silent() {
+        return;
+    }
     if let Ok(pretty) = serde_json::to_string_pretty(value) {
         println!("{}", pretty);
     }
@@ -102,6 +102,39 @@ fn test_cli_whoami_no_identity() {
     );
 }
 
+/// Test that `--output json` prints a single error document and fails
+#[test]
+fn test_cli_json_output_error() {
+    use tempfile::TempDir;
+
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "-p",
+            "paykit-demo-cli",
+            "--",
+            "--output",
+            "json",
+            "whoami",
+        ])
+        .env("PAYKIT_DEMO_DIR", temp_dir.path())
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let document: serde_json::Value =
+        serde_json::from_str(stdout.trim()).expect("stdout should be one JSON document");
+    assert_eq!(document["schema"], 1);
+    assert_eq!(document["command"], "whoami");
+    assert_eq!(document["ok"], false);
+    assert!(document["error"]["message"].is_string());
+}
+
 #[cfg(test)]
 mod unit_tests {
     //! Unit tests that don't require running the binary
@@ -0,0 +1,403 @@
+//! Opt-in payee analytics without payer identities
+//!
+//! [`AnalyticsCoordinator::record`] reduces each received receipt to a
+//! count and amount in its (day, method, currency) bucket as soon as it is
+//! confirmed; the payer, receipt ID and metadata are never written to the
+//! analytics state. Reports only show buckets with at least `k_threshold`
+//! receipts, so a bucket cannot single out one payment.
+//!
+//! With `retain_receipts` off the receiver does not keep the receipts
+//! themselves either, leaving the aggregates as the only record.
+//!
+//! The only per-receipt data kept is a hash of the receipt ID for today and
+//! yesterday, so a retransmitted request is not counted twice; older hashes
+//! are dropped on the next write.
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, NaiveDate};
+use paykit_interactive::PaykitReceipt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Smallest bucket shown in reports unless configured otherwise
+pub const DEFAULT_K_THRESHOLD: u64 = 5;
+
+/// Currency assumed for receipts that name none
+const DEFAULT_CURRENCY: &str = "SAT";
+
+/// Whether and how received payments are aggregated
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnalyticsSettings {
+    /// Aggregate received receipts
+    pub enabled: bool,
+    /// Buckets with fewer receipts are left out of reports
+    pub k_threshold: u64,
+    /// Keep received receipts in storage; when off, only aggregates remain
+    pub retain_receipts: bool,
+}
+
+impl Default for AnalyticsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            k_threshold: DEFAULT_K_THRESHOLD,
+            retain_receipts: true,
+        }
+    }
+}
+
+/// Received payments in one day, method and currency
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregateRow {
+    /// UTC day, `YYYY-MM-DD`
+    pub day: String,
+    /// Payment method
+    pub method: String,
+    /// Currency of `total`
+    pub currency: String,
+    /// Receipts in the bucket
+    pub count: u64,
+    /// Sum of the integer amounts
+    pub total: u64,
+    /// Receipts without an integer amount, counted but not in `total`
+    pub unpriced: u64,
+}
+
+/// Aggregates that pass the k-anonymity threshold
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalyticsReport {
+    /// Threshold the report was built with
+    pub k_threshold: u64,
+    /// Buckets with at least `k_threshold` receipts, by day then method
+    pub rows: Vec<AggregateRow>,
+    /// Receipts in buckets below the threshold
+    pub suppressed: u64,
+}
+
+impl AnalyticsReport {
+    /// Totals per method and currency over the reported rows
+    pub fn by_method(&self) -> Vec<AggregateRow> {
+        let mut totals: BTreeMap<(String, String), AggregateRow> = BTreeMap::new();
+        for row in &self.rows {
+            let total = totals
+                .entry((row.method.clone(), row.currency.clone()))
+                .or_insert_with(|| AggregateRow {
+                    day: String::new(),
+                    method: row.method.clone(),
+                    currency: row.currency.clone(),
+                    count: 0,
+                    total: 0,
+                    unpriced: 0,
+                });
+            total.count += row.count;
+            total.total = total.total.saturating_add(row.total);
+            total.unpriced += row.unpriced;
+        }
+        totals.into_values().collect()
+    }
+}
+
+/// Persisted analytics settings and aggregates
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnalyticsState {
+    /// Current settings
+    #[serde(default)]
+    pub settings: AnalyticsSettings,
+    /// Aggregates, sorted by day, method and currency
+    #[serde(default)]
+    pub rows: Vec<AggregateRow>,
+    /// Hashed receipt IDs counted recently, by day
+    #[serde(default)]
+    recent: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Coordinates payee analytics for a demo storage directory
+pub struct AnalyticsCoordinator {
+    storage_dir: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl AnalyticsCoordinator {
+    /// Create a coordinator for the given storage directory
+    pub fn new(storage_dir: impl AsRef<Path>) -> Self {
+        Self {
+            storage_dir: storage_dir.as_ref().to_path_buf(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Current settings
+    pub fn settings(&self) -> Result<AnalyticsSettings> {
+        Ok(self.load()?.settings)
+    }
+
+    /// Start aggregating received receipts
+    pub fn enable(&self, k_threshold: u64, retain_receipts: bool) -> Result<()> {
+        if k_threshold == 0 {
+            return Err(anyhow!("k threshold must be at least 1"));
+        }
+        self.update(|state| {
+            state.settings = AnalyticsSettings {
+                enabled: true,
+                k_threshold,
+                retain_receipts,
+            };
+            Ok(())
+        })
+    }
+
+    /// Stop aggregating, keeping the aggregates collected so far
+    pub fn disable(&self) -> Result<()> {
+        self.update(|state| {
+            state.settings.enabled = false;
+            state.settings.retain_receipts = true;
+            state.recent.clear();
+            Ok(())
+        })
+    }
+
+    /// Whether received receipts may be written to storage
+    pub fn retain_receipts(&self) -> Result<bool> {
+        let settings = self.settings()?;
+        Ok(!settings.enabled || settings.retain_receipts)
+    }
+
+    /// Add a received receipt to its bucket
+    ///
+    /// Returns whether it was counted: nothing is recorded while analytics
+    /// are disabled, and a receipt already counted today or yesterday is
+    /// skipped.
+    pub fn record(&self, receipt: &PaykitReceipt, now: i64) -> Result<bool> {
+        self.update(|state| {
+            if !state.settings.enabled {
+                return Ok(false);
+            }
+
+            // Forget receipt hashes once a retransmission is no longer plausible
+            let yesterday = day_of(now - 86_400)?;
+            state.recent.retain(|day, _| *day >= yesterday);
+
+            let day = day_of(receipt.created_at)?;
+            let receipt_hash = hex::encode(Sha256::digest(receipt.receipt_id.as_bytes()));
+            if day >= yesterday
+                && !state
+                    .recent
+                    .entry(day.clone())
+                    .or_default()
+                    .insert(receipt_hash)
+            {
+                return Ok(false);
+            }
+
+            let method = receipt.method_id.0.clone();
+            let currency = receipt
+                .currency
+                .clone()
+                .unwrap_or_else(|| DEFAULT_CURRENCY.to_string());
+            let position = state
+                .rows
+                .binary_search_by(|row| {
+                    (row.day.as_str(), row.method.as_str(), row.currency.as_str()).cmp(&(
+                        day.as_str(),
+                        method.as_str(),
+                        currency.as_str(),
+                    ))
+                })
+                .unwrap_or_else(|position| {
+                    state.rows.insert(
+                        position,
+                        AggregateRow {
+                            day: day.clone(),
+                            method: method.clone(),
+                            currency: currency.clone(),
+                            count: 0,
+                            total: 0,
+                            unpriced: 0,
+                        },
+                    );
+                    position
+                });
+
+            let row = &mut state.rows[position];
+            row.count += 1;
+            match receipt
+                .amount
+                .as_deref()
+                .and_then(|amount| amount.trim().parse::<u64>().ok())
+            {
+                Some(amount) => row.total = row.total.saturating_add(amount),
+                None => row.unpriced += 1,
+            }
+            Ok(true)
+        })
+    }
+
+    /// Aggregates between two days (inclusive, `YYYY-MM-DD`)
+    ///
+    /// Buckets below the k-anonymity threshold are left out and only
+    /// counted in `suppressed`.
+    pub fn report(&self, from: Option<&str>, to: Option<&str>) -> Result<AnalyticsReport> {
+        for day in from.iter().chain(to.iter()) {
+            NaiveDate::parse_from_str(day, "%Y-%m-%d")
+                .with_context(|| format!("Invalid day '{}', expected YYYY-MM-DD", day))?;
+        }
+
+        let state = self.load()?;
+        let k_threshold = state.settings.k_threshold;
+        let mut report = AnalyticsReport {
+            k_threshold,
+            rows: Vec::new(),
+            suppressed: 0,
+        };
+        for row in state.rows {
+            if from.is_some_and(|from| row.day.as_str() < from)
+                || to.is_some_and(|to| row.day.as_str() > to)
+            {
+                continue;
+            }
+            if row.count >= k_threshold {
+                report.rows.push(row);
+            } else {
+                report.suppressed += row.count;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Delete all aggregates, keeping the settings
+    pub fn clear(&self) -> Result<()> {
+        self.update(|state| {
+            state.rows.clear();
+            state.recent.clear();
+            Ok(())
+        })
+    }
+
+    fn update<T>(&self, f: impl FnOnce(&mut AnalyticsState) -> Result<T>) -> Result<T> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut state = self.load()?;
+        let result = f(&mut state)?;
+        self.save(&state)?;
+        Ok(result)
+    }
+
+    fn state_path(&self) -> PathBuf {
+        self.storage_dir.join("analytics.json")
+    }
+
+    fn load(&self) -> Result<AnalyticsState> {
+        let path = self.state_path();
+        if !path.exists() {
+            return Ok(AnalyticsState::default());
+        }
+
+        let json = std::fs::read_to_string(&path).context("Failed to read analytics")?;
+        serde_json::from_str(&json).context("Failed to parse analytics")
+    }
+
+    fn save(&self, state: &AnalyticsState) -> Result<()> {
+        std::fs::create_dir_all(&self.storage_dir)?;
+        let json = serde_json::to_string_pretty(state)?;
+        let tmp = self.state_path().with_extension("json.tmp");
+        std::fs::write(&tmp, json).context("Failed to save analytics")?;
+        std::fs::rename(&tmp, self.state_path()).context("Failed to save analytics")
+    }
+}
+
+/// UTC day of a Unix timestamp, `YYYY-MM-DD`
+fn day_of(timestamp: i64) -> Result<String> {
+    DateTime::from_timestamp(timestamp, 0)
+        .map(|time| time.date_naive().to_string())
+        .ok_or_else(|| anyhow!("Timestamp out of range: {}", timestamp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paykit_lib::MethodId;
+    use pubky::Keypair;
+
+    // 2024-01-02 00:00:00 UTC
+    const DAY: i64 = 1_704_153_600;
+
+    fn receipt(id: &str, method: &str, amount: Option<&str>, created_at: i64) -> PaykitReceipt {
+        let mut receipt = PaykitReceipt::new(
+            id.to_string(),
+            Keypair::random().public_key(),
+            Keypair::random().public_key(),
+            MethodId(method.to_string()),
+            amount.map(str::to_string),
+            None,
+            serde_json::json!({ "memo": "private" }),
+        );
+        receipt.created_at = created_at;
+        receipt
+    }
+
+    #[test]
+    fn test_aggregates_without_payer_data() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let analytics = AnalyticsCoordinator::new(temp_dir.path());
+        let first = receipt("r1", "lightning", Some("1000"), DAY + 60);
+        assert!(!analytics.record(&first, DAY + 60).unwrap());
+
+        analytics.enable(2, false).unwrap();
+        assert!(!analytics.retain_receipts().unwrap());
+        assert!(analytics.record(&first, DAY + 60).unwrap());
+        // A retransmission is not counted again
+        assert!(!analytics.record(&first, DAY + 120).unwrap());
+        analytics
+            .record(&receipt("r2", "lightning", Some("abc"), DAY + 90), DAY + 90)
+            .unwrap();
+        analytics
+            .record(&receipt("r3", "onchain", Some("5000"), DAY + 90), DAY + 90)
+            .unwrap();
+
+        let report = analytics.report(None, None).unwrap();
+        assert_eq!(report.suppressed, 1);
+        assert_eq!(
+            report.rows,
+            vec![AggregateRow {
+                day: "2024-01-02".into(),
+                method: "lightning".into(),
+                currency: "SAT".into(),
+                count: 2,
+                total: 1000,
+                unpriced: 1,
+            }]
+        );
+
+        let stored = std::fs::read_to_string(temp_dir.path().join("analytics.json")).unwrap();
+        assert!(!stored.contains(&first.payer.to_string()));
+        assert!(!stored.contains("r1"));
+        assert!(!stored.contains("private"));
+    }
+
+    #[test]
+    fn test_old_receipt_hashes_are_dropped() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let analytics = AnalyticsCoordinator::new(temp_dir.path());
+        analytics.enable(1, true).unwrap();
+
+        analytics
+            .record(&receipt("r1", "lightning", Some("1"), DAY), DAY)
+            .unwrap();
+        analytics
+            .record(
+                &receipt("r2", "lightning", Some("1"), DAY + 3 * 86_400),
+                DAY + 3 * 86_400,
+            )
+            .unwrap();
+
+        let state = analytics.load().unwrap();
+        assert_eq!(state.recent.len(), 1);
+        assert_eq!(state.rows.len(), 2);
+
+        let report = analytics.report(Some("2024-01-03"), None).unwrap();
+        assert_eq!(report.rows.len(), 1);
+        assert!(analytics.report(Some("Jan 3"), None).is_err());
+    }
+}
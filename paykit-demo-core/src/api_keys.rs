@@ -0,0 +1,583 @@
+//! API keys for a local REST / JSON-RPC server
+//!
+//! Each key has a role that bounds what it may do:
+//!
+//! - [`ApiRole::ReadOnly`] - query receipts, contacts, status
+//! - [`ApiRole::Operator`] - also make payments and create requests
+//! - [`ApiRole::Admin`] - also manage keys
+//!
+//! Only a SHA-256 hash of each secret is stored. A token is
+//! `paykit_<id>_<secret>`: the ID selects the key and the secret is checked
+//! against its hash.
+//!
+//! [`ApiKeyCoordinator::authorize`] checks a token against the role an
+//! action needs and the key's per-minute limit, and appends the decision to
+//! `api_audit.jsonl`. A refused request fails with an [`ApiDenial`], which a
+//! server can recover with `downcast_ref` to pick its status code.
+//!
+//! Rotating a key issues a new secret under the same ID; the old secret
+//! keeps working for a grace period so clients can be updated without
+//! downtime.
+
+use anyhow::{anyhow, Context, Result};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Requests per minute allowed for a key unless configured otherwise
+pub const DEFAULT_RATE_LIMIT: u32 = 60;
+
+/// Prefix of every API token
+const TOKEN_PREFIX: &str = "paykit_";
+
+/// What a key may do; each role includes the ones before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiRole {
+    /// Read state only
+    ReadOnly,
+    /// Read state and move money
+    Operator,
+    /// Everything, including key management
+    Admin,
+}
+
+impl ApiRole {
+    /// Whether this role may perform an action that needs `required`
+    pub fn permits(self, required: ApiRole) -> bool {
+        self >= required
+    }
+}
+
+impl fmt::Display for ApiRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::ReadOnly => "read_only",
+            Self::Operator => "operator",
+            Self::Admin => "admin",
+        })
+    }
+}
+
+impl std::str::FromStr for ApiRole {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().replace('-', "_").as_str() {
+            "read_only" | "readonly" | "read" => Ok(Self::ReadOnly),
+            "operator" => Ok(Self::Operator),
+            "admin" => Ok(Self::Admin),
+            _ => Err(anyhow!(
+                "Unknown role '{}', expected read-only, operator or admin",
+                s
+            )),
+        }
+    }
+}
+
+/// A stored API key, without its secret
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiKey {
+    /// Public identifier, part of the token
+    pub id: String,
+    /// Operator-chosen label
+    pub name: String,
+    /// What the key may do
+    pub role: ApiRole,
+    /// Requests allowed per minute
+    pub rate_limit: u32,
+    /// Unix timestamp of creation
+    pub created_at: i64,
+    /// Unix timestamp after which the key is refused
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+    /// Unix timestamp of the last rotation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotated_at: Option<i64>,
+    /// SHA-256 of the current secret, hex
+    secret_hash: String,
+    /// Secret replaced by the last rotation, accepted until its deadline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    previous: Option<PreviousSecret>,
+}
+
+impl ApiKey {
+    /// Whether the key has expired at `now`
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+
+    /// Unix timestamp until which the pre-rotation secret is accepted
+    pub fn previous_valid_until(&self) -> Option<i64> {
+        self.previous.as_ref().map(|previous| previous.valid_until)
+    }
+
+    fn accepts(&self, secret: &str, now: i64) -> bool {
+        // Hashes are compared, so timing reveals nothing useful about the secret
+        let hash = hash_secret(secret);
+        hash == self.secret_hash
+            || self
+                .previous
+                .as_ref()
+                .is_some_and(|previous| now < previous.valid_until && hash == previous.hash)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct PreviousSecret {
+    hash: String,
+    valid_until: i64,
+}
+
+/// Why a request was refused
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiDenial {
+    /// The token is malformed, unknown or revoked, or its secret is wrong
+    InvalidKey,
+    /// The key has expired
+    Expired,
+    /// The key's role does not permit the action
+    Forbidden {
+        /// The key's role
+        role: ApiRole,
+        /// The role the action needs
+        required: ApiRole,
+    },
+    /// The key used up its requests for this minute
+    RateLimited {
+        /// Seconds until the next window opens
+        retry_after: u64,
+    },
+}
+
+impl ApiDenial {
+    /// HTTP status a server should answer with
+    pub fn http_status(&self) -> u16 {
+        match self {
+            Self::InvalidKey | Self::Expired => 401,
+            Self::Forbidden { .. } => 403,
+            Self::RateLimited { .. } => 429,
+        }
+    }
+}
+
+impl fmt::Display for ApiDenial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidKey => write!(f, "Invalid API key"),
+            Self::Expired => write!(f, "API key has expired"),
+            Self::Forbidden { role, required } => {
+                write!(
+                    f,
+                    "Role {} may not perform an action needing {}",
+                    role, required
+                )
+            }
+            Self::RateLimited { retry_after } => {
+                write!(f, "Rate limit exceeded, retry in {}s", retry_after)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ApiDenial {}
+
+/// One API action or key-management change
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiAuditEntry {
+    /// Unix timestamp
+    pub timestamp: i64,
+    /// Key used, if the token named one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+    /// Action, e.g. `pay` or `keys.rotate`
+    pub action: String,
+    /// Whether it was allowed
+    pub allowed: bool,
+    /// Reason for a refusal, or other context
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Persisted API keys
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApiKeyState {
+    /// Keys by creation order
+    #[serde(default)]
+    pub keys: Vec<ApiKey>,
+}
+
+/// Coordinates API keys for a demo storage directory
+///
+/// Rate-limit windows are kept in memory, so a server should hold one
+/// coordinator for its lifetime.
+pub struct ApiKeyCoordinator {
+    storage_dir: PathBuf,
+    lock: Mutex<()>,
+    windows: Mutex<HashMap<String, (i64, u32)>>,
+}
+
+impl ApiKeyCoordinator {
+    /// Create a coordinator for the given storage directory
+    pub fn new(storage_dir: impl AsRef<Path>) -> Self {
+        Self {
+            storage_dir: storage_dir.as_ref().to_path_buf(),
+            lock: Mutex::new(()),
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Create a key and return it with its token
+    ///
+    /// The token is not stored and cannot be shown again.
+    pub fn create(
+        &self,
+        name: &str,
+        role: ApiRole,
+        rate_limit: u32,
+        expires_at: Option<i64>,
+        now: i64,
+    ) -> Result<(ApiKey, String)> {
+        if rate_limit == 0 {
+            return Err(anyhow!("Rate limit must be at least 1 request per minute"));
+        }
+        let (key, token) = self.update(|state| {
+            let mut id = random_hex(4);
+            while state.keys.iter().any(|key| key.id == id) {
+                id = random_hex(4);
+            }
+            let secret = random_hex(32);
+            let key = ApiKey {
+                id: id.clone(),
+                name: name.to_string(),
+                role,
+                rate_limit,
+                created_at: now,
+                expires_at,
+                rotated_at: None,
+                secret_hash: hash_secret(&secret),
+                previous: None,
+            };
+            state.keys.push(key.clone());
+            Ok((key, format!("{}{}_{}", TOKEN_PREFIX, id, secret)))
+        })?;
+        self.audit(
+            now,
+            Some(&key.id),
+            "keys.create",
+            true,
+            Some(&role.to_string()),
+        )?;
+        Ok((key, token))
+    }
+
+    /// All keys
+    pub fn list(&self) -> Result<Vec<ApiKey>> {
+        Ok(self.load()?.keys)
+    }
+
+    /// The key with `id`
+    pub fn get(&self, id: &str) -> Result<ApiKey> {
+        self.load()?
+            .keys
+            .into_iter()
+            .find(|key| key.id == id)
+            .ok_or_else(|| anyhow!("No API key with ID {}", id))
+    }
+
+    /// Delete a key; its tokens stop working immediately
+    pub fn revoke(&self, id: &str, now: i64) -> Result<()> {
+        self.update(|state| {
+            let before = state.keys.len();
+            state.keys.retain(|key| key.id != id);
+            if state.keys.len() == before {
+                return Err(anyhow!("No API key with ID {}", id));
+            }
+            Ok(())
+        })?;
+        self.windows
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(id);
+        self.audit(now, Some(id), "keys.revoke", true, None)
+    }
+
+    /// Issue a new secret for a key and return the new token
+    ///
+    /// The previous secret is accepted for `grace_secs` more seconds; with
+    /// a grace of zero it stops working at once.
+    pub fn rotate(&self, id: &str, grace_secs: u64, now: i64) -> Result<String> {
+        let token = self.update(|state| {
+            let key = state
+                .keys
+                .iter_mut()
+                .find(|key| key.id == id)
+                .ok_or_else(|| anyhow!("No API key with ID {}", id))?;
+            let secret = random_hex(32);
+            let previous = std::mem::replace(&mut key.secret_hash, hash_secret(&secret));
+            key.previous = (grace_secs > 0).then(|| PreviousSecret {
+                hash: previous,
+                valid_until: now.saturating_add(grace_secs as i64),
+            });
+            key.rotated_at = Some(now);
+            Ok(format!("{}{}_{}", TOKEN_PREFIX, id, secret))
+        })?;
+        self.audit(
+            now,
+            Some(id),
+            "keys.rotate",
+            true,
+            Some(&format!("grace {}s", grace_secs)),
+        )?;
+        Ok(token)
+    }
+
+    /// Check that `token` may perform `action`, which needs `required`
+    ///
+    /// Counts the request against the key's limit and records the decision
+    /// in the audit log. Refusals are [`ApiDenial`] errors.
+    pub fn authorize(
+        &self,
+        token: &str,
+        action: &str,
+        required: ApiRole,
+        now: i64,
+    ) -> Result<ApiKey> {
+        let key_id = parse_token(token).map(|(id, _)| id.to_string());
+        let decision = self.check(token, required, now);
+        let detail = decision.as_ref().err().map(ToString::to_string);
+        self.audit(
+            now,
+            key_id.as_deref(),
+            action,
+            decision.is_ok(),
+            detail.as_deref(),
+        )?;
+        decision.map_err(Into::into)
+    }
+
+    /// Audit entries, oldest first, limited to the last `limit`
+    pub fn audit_log(&self, limit: Option<usize>) -> Result<Vec<ApiAuditEntry>> {
+        let path = self.audit_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let jsonl = std::fs::read_to_string(&path).context("Failed to read API audit log")?;
+        let mut entries = jsonl
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).context("API audit log is corrupted"))
+            .collect::<Result<Vec<ApiAuditEntry>>>()?;
+        if let Some(limit) = limit {
+            entries.drain(..entries.len().saturating_sub(limit));
+        }
+        Ok(entries)
+    }
+
+    fn check(&self, token: &str, required: ApiRole, now: i64) -> Result<ApiKey, ApiDenial> {
+        let (id, secret) = parse_token(token).ok_or(ApiDenial::InvalidKey)?;
+        let key = self
+            .load()
+            .ok()
+            .and_then(|state| state.keys.into_iter().find(|key| key.id == id))
+            .filter(|key| key.accepts(secret, now))
+            .ok_or(ApiDenial::InvalidKey)?;
+        if key.is_expired(now) {
+            return Err(ApiDenial::Expired);
+        }
+        if !key.role.permits(required) {
+            return Err(ApiDenial::Forbidden {
+                role: key.role,
+                required,
+            });
+        }
+
+        let minute = now.div_euclid(60);
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        let window = windows.entry(key.id.clone()).or_insert((minute, 0));
+        if window.0 != minute {
+            *window = (minute, 0);
+        }
+        if window.1 >= key.rate_limit {
+            return Err(ApiDenial::RateLimited {
+                retry_after: (60 - now.rem_euclid(60)) as u64,
+            });
+        }
+        window.1 += 1;
+        Ok(key)
+    }
+
+    fn audit(
+        &self,
+        timestamp: i64,
+        key_id: Option<&str>,
+        action: &str,
+        allowed: bool,
+        detail: Option<&str>,
+    ) -> Result<()> {
+        let entry = ApiAuditEntry {
+            timestamp,
+            key_id: key_id.map(str::to_string),
+            action: action.to_string(),
+            allowed,
+            detail: detail.map(str::to_string),
+        };
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        std::fs::create_dir_all(&self.storage_dir)?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.audit_path())
+            .context("Failed to open API audit log")?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)
+            .context("Failed to write API audit log")
+    }
+
+    fn update<T>(&self, f: impl FnOnce(&mut ApiKeyState) -> Result<T>) -> Result<T> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut state = self.load()?;
+        let result = f(&mut state)?;
+        self.save(&state)?;
+        Ok(result)
+    }
+
+    fn state_path(&self) -> PathBuf {
+        self.storage_dir.join("api_keys.json")
+    }
+
+    fn audit_path(&self) -> PathBuf {
+        self.storage_dir.join("api_audit.jsonl")
+    }
+
+    fn load(&self) -> Result<ApiKeyState> {
+        let path = self.state_path();
+        if !path.exists() {
+            return Ok(ApiKeyState::default());
+        }
+
+        let json = std::fs::read_to_string(&path).context("Failed to read API keys")?;
+        serde_json::from_str(&json).context("Failed to parse API keys")
+    }
+
+    fn save(&self, state: &ApiKeyState) -> Result<()> {
+        std::fs::create_dir_all(&self.storage_dir)?;
+        let json = serde_json::to_string_pretty(state)?;
+        let tmp = self.state_path().with_extension("json.tmp");
+        std::fs::write(&tmp, json).context("Failed to save API keys")?;
+        std::fs::rename(&tmp, self.state_path()).context("Failed to save API keys")
+    }
+}
+
+/// Split a token into key ID and secret
+fn parse_token(token: &str) -> Option<(&str, &str)> {
+    token.trim().strip_prefix(TOKEN_PREFIX)?.split_once('_')
+}
+
+fn hash_secret(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+fn random_hex(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_704_153_600;
+
+    fn denial(result: Result<ApiKey>) -> ApiDenial {
+        result
+            .unwrap_err()
+            .downcast_ref::<ApiDenial>()
+            .cloned()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_roles_and_rate_limits() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let keys = ApiKeyCoordinator::new(temp_dir.path());
+        let (reader, token) = keys
+            .create("dashboard", ApiRole::ReadOnly, 2, None, NOW)
+            .unwrap();
+
+        assert_eq!(
+            keys.authorize(&token, "receipts.list", ApiRole::ReadOnly, NOW)
+                .unwrap()
+                .id,
+            reader.id
+        );
+        assert_eq!(
+            denial(keys.authorize(&token, "pay", ApiRole::Operator, NOW)),
+            ApiDenial::Forbidden {
+                role: ApiRole::ReadOnly,
+                required: ApiRole::Operator
+            }
+        );
+        keys.authorize(&token, "status", ApiRole::ReadOnly, NOW)
+            .unwrap();
+        let limited = denial(keys.authorize(&token, "status", ApiRole::ReadOnly, NOW + 1));
+        assert_eq!(limited.http_status(), 429);
+        // A new minute opens a new window
+        keys.authorize(&token, "status", ApiRole::ReadOnly, NOW + 60)
+            .unwrap();
+
+        let forged = format!("{}{}_{}", TOKEN_PREFIX, reader.id, "00".repeat(32));
+        assert_eq!(
+            denial(keys.authorize(&forged, "status", ApiRole::ReadOnly, NOW)),
+            ApiDenial::InvalidKey
+        );
+
+        // Only the hash is stored, and every decision is audited
+        let stored = std::fs::read_to_string(temp_dir.path().join("api_keys.json")).unwrap();
+        assert!(!stored.contains(token.rsplit('_').next().unwrap()));
+        let log = keys.audit_log(None).unwrap();
+        assert_eq!(log.len(), 7);
+        assert_eq!(log[0].action, "keys.create");
+        assert_eq!(log.iter().filter(|entry| !entry.allowed).count(), 3);
+        assert_eq!(keys.audit_log(Some(2)).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_rotation_grace_and_revocation() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let keys = ApiKeyCoordinator::new(temp_dir.path());
+        let (key, old) = keys
+            .create("pos", ApiRole::Operator, 100, Some(NOW + 3600), NOW)
+            .unwrap();
+
+        let new = keys.rotate(&key.id, 300, NOW).unwrap();
+        assert_ne!(old, new);
+        keys.authorize(&old, "pay", ApiRole::Operator, NOW + 299)
+            .unwrap();
+        assert_eq!(
+            denial(keys.authorize(&old, "pay", ApiRole::Operator, NOW + 300)),
+            ApiDenial::InvalidKey
+        );
+        keys.authorize(&new, "pay", ApiRole::Operator, NOW + 300)
+            .unwrap();
+        assert_eq!(
+            denial(keys.authorize(&new, "pay", ApiRole::Operator, NOW + 3600)),
+            ApiDenial::Expired
+        );
+
+        keys.revoke(&key.id, NOW).unwrap();
+        assert_eq!(
+            denial(keys.authorize(&new, "pay", ApiRole::Operator, NOW)),
+            ApiDenial::InvalidKey
+        );
+        assert!(keys.revoke(&key.id, NOW).is_err());
+        assert_eq!("Read-Only".parse::<ApiRole>().unwrap(), ApiRole::ReadOnly);
+        assert!("root".parse::<ApiRole>().is_err());
+    }
+}
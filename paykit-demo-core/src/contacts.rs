@@ -0,0 +1,677 @@
+//! Contact import
+//!
+//! Reads address books exported from other apps and turns the entries that
+//! carry a Pubky identity into [`Contact`]s. Supported formats:
+//!
+//! - **CSV** with a header row naming the `name`, `pubky` (or `uri`,
+//!   `public_key`) and optional `notes` columns. Files without a recognised
+//!   header are read as `name,pubky,notes`.
+//! - **vCard** (3.0 / 4.0). The Pubky URI is taken from an `X-PUBKY`, `URL`
+//!   or `IMPP` property holding a `pubky://` URI.
+//! - **Nostr** contact lists (NIP-02 kind 3 events, optionally alongside the
+//!   followed profiles' kind 0 metadata) behind the `nostr` feature. Nostr
+//!   keys are not Pubky keys, so a follow is only imported when its petname,
+//!   profile or NIP-39 `i` tags mention a `pubky://` URI.
+//!
+//! Entries whose key is missing or invalid, that are already in the address
+//! book, or that repeat an earlier entry are reported in the
+//! [`ImportSummary`] instead of failing the whole import.
+
+use crate::{Contact, DemoStorage};
+use anyhow::{anyhow, Result};
+use pubky::PublicKey;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Address book formats that can be imported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    /// Comma-separated values with a header row
+    Csv,
+    /// vCard 3.0 / 4.0
+    VCard,
+    /// Nostr contact list events as JSON
+    #[cfg(feature = "nostr")]
+    Nostr,
+}
+
+impl ImportFormat {
+    /// Guess the format from a file extension
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let ext = path.as_ref().extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "csv" => Some(Self::Csv),
+            "vcf" | "vcard" => Some(Self::VCard),
+            #[cfg(feature = "nostr")]
+            "json" => Some(Self::Nostr),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for ImportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "vcard" | "vcf" => Ok(Self::VCard),
+            #[cfg(feature = "nostr")]
+            "nostr" => Ok(Self::Nostr),
+            other => Err(anyhow!("Unknown contact format '{}'", other)),
+        }
+    }
+}
+
+/// One entry read from an address book, before validation
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContactRecord {
+    /// Position in the file (1-based row, card or follow)
+    pub entry: usize,
+    /// Display name, if the entry had one
+    pub name: Option<String>,
+    /// Pubky URI or bare public key, if the entry had one
+    pub uri: Option<String>,
+    /// Notes to keep on the contact
+    pub notes: Option<String>,
+}
+
+/// Why an entry was not imported
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The entry has no Pubky URI
+    MissingKey,
+    /// The Pubky URI does not hold a valid public key
+    InvalidKey(String),
+    /// The key is already in the address book under this name
+    AlreadyExists(String),
+    /// The key appeared earlier in the same file
+    Duplicate,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingKey => write!(f, "no pubky URI"),
+            Self::InvalidKey(e) => write!(f, "invalid pubky URI: {}", e),
+            Self::AlreadyExists(name) => write!(f, "already saved as '{}'", name),
+            Self::Duplicate => write!(f, "duplicate entry"),
+        }
+    }
+}
+
+/// An entry that was not imported
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedContact {
+    /// Position in the file
+    pub entry: usize,
+    /// Display name, if known
+    pub name: Option<String>,
+    /// Why it was skipped
+    pub reason: SkipReason,
+}
+
+/// Result of an import
+#[derive(Debug, Clone, Default)]
+pub struct ImportSummary {
+    /// Entries read from the file
+    pub total: usize,
+    /// Contacts added to the address book
+    pub imported: Vec<Contact>,
+    /// Entries left out, with the reason
+    pub skipped: Vec<SkippedContact>,
+}
+
+impl ImportSummary {
+    /// Entries skipped because they were already known
+    pub fn duplicates(&self) -> usize {
+        self.skipped
+            .iter()
+            .filter(|s| {
+                matches!(
+                    s.reason,
+                    SkipReason::AlreadyExists(_) | SkipReason::Duplicate
+                )
+            })
+            .count()
+    }
+}
+
+/// Read the entries of an address book without validating them
+pub fn parse_contacts(format: ImportFormat, input: &str) -> Result<Vec<ContactRecord>> {
+    match format {
+        ImportFormat::Csv => parse_csv(input),
+        ImportFormat::VCard => parse_vcard(input),
+        #[cfg(feature = "nostr")]
+        ImportFormat::Nostr => nostr::parse_nostr(input),
+    }
+}
+
+/// Parse a `pubky://` URI (any path is ignored) or a bare public key
+pub fn parse_pubky_uri(uri: &str) -> Result<PublicKey> {
+    let uri = uri.trim();
+    let key = uri
+        .strip_prefix("pubky://")
+        .or_else(|| uri.strip_prefix("pubky:"))
+        .unwrap_or(uri);
+    let key = key.split('/').next().unwrap_or_default();
+    key.parse()
+        .map_err(|e| anyhow!("Invalid public key '{}': {}", key, e))
+}
+
+/// Import an address book into `storage`
+///
+/// With `dry_run` the summary is computed but nothing is saved.
+pub fn import_contacts(
+    storage: &DemoStorage,
+    format: ImportFormat,
+    input: &str,
+    dry_run: bool,
+) -> Result<ImportSummary> {
+    let records = parse_contacts(format, input)?;
+    let existing = storage.list_contacts()?;
+    let summary = build_import(records, &existing);
+
+    if !dry_run {
+        for contact in &summary.imported {
+            storage.save_contact(contact.clone())?;
+        }
+    }
+    Ok(summary)
+}
+
+/// Validate and dedupe records against the existing contacts
+fn build_import(records: Vec<ContactRecord>, existing: &[Contact]) -> ImportSummary {
+    let mut summary = ImportSummary {
+        total: records.len(),
+        ..Default::default()
+    };
+    let mut seen = HashSet::new();
+
+    for record in records {
+        let skip = |reason| SkippedContact {
+            entry: record.entry,
+            name: record.name.clone(),
+            reason,
+        };
+        let Some(uri) = record.uri.as_deref().filter(|u| !u.trim().is_empty()) else {
+            summary.skipped.push(skip(SkipReason::MissingKey));
+            continue;
+        };
+        let public_key = match parse_pubky_uri(uri) {
+            Ok(key) => key,
+            Err(e) => {
+                summary
+                    .skipped
+                    .push(skip(SkipReason::InvalidKey(e.to_string())));
+                continue;
+            }
+        };
+
+        let key = public_key.to_string();
+        if let Some(contact) = existing.iter().find(|c| c.public_key.to_string() == key) {
+            summary
+                .skipped
+                .push(skip(SkipReason::AlreadyExists(contact.name.clone())));
+            continue;
+        }
+        if !seen.insert(key.clone()) {
+            summary.skipped.push(skip(SkipReason::Duplicate));
+            continue;
+        }
+
+        let name = record
+            .name
+            .clone()
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or_else(|| format!("contact_{}", &key[..8.min(key.len())]));
+        let mut contact = Contact::new(public_key, name);
+        if let Some(notes) = record.notes.filter(|n| !n.trim().is_empty()) {
+            contact = contact.with_notes(notes);
+        }
+        summary.imported.push(contact);
+    }
+    summary
+}
+
+// ============================================================================
+// CSV
+// ============================================================================
+
+const NAME_COLUMNS: [&str; 4] = ["name", "full name", "display name", "display_name"];
+const KEY_COLUMNS: [&str; 6] = [
+    "pubky",
+    "uri",
+    "pubky_uri",
+    "pubky uri",
+    "public_key",
+    "key",
+];
+const NOTES_COLUMNS: [&str; 2] = ["notes", "note"];
+
+fn parse_csv(input: &str) -> Result<Vec<ContactRecord>> {
+    let rows = csv_rows(input)?;
+    let Some(first) = rows.first() else {
+        return Ok(Vec::new());
+    };
+
+    let column = |names: &[&str]| {
+        first
+            .iter()
+            .position(|c| names.contains(&c.trim().to_lowercase().as_str()))
+    };
+    let (name_col, key_col, notes_col, data) = match column(&KEY_COLUMNS) {
+        Some(key_col) => (
+            column(&NAME_COLUMNS),
+            key_col,
+            column(&NOTES_COLUMNS),
+            &rows[1..],
+        ),
+        None => (Some(0), 1, Some(2), &rows[..]),
+    };
+
+    let cell = |row: &[String], col: Option<usize>| {
+        col.and_then(|c| row.get(c))
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    Ok(data
+        .iter()
+        .filter(|row| row.iter().any(|c| !c.trim().is_empty()))
+        .enumerate()
+        .map(|(i, row)| ContactRecord {
+            entry: i + 1,
+            name: cell(row, name_col),
+            uri: cell(row, Some(key_col)),
+            notes: cell(row, notes_col),
+        })
+        .collect())
+}
+
+/// Split CSV into rows of fields, honouring quoted fields
+fn csv_rows(input: &str) -> Result<Vec<Vec<String>>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = input.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => row.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            (c, _) => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(anyhow!("Unterminated quoted field in CSV"));
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+// ============================================================================
+// vCard
+// ============================================================================
+
+fn parse_vcard(input: &str) -> Result<Vec<ContactRecord>> {
+    // Unfold continuation lines (RFC 6350 section 3.2)
+    let mut lines: Vec<String> = Vec::new();
+    for line in input.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut records = Vec::new();
+    let mut card: Option<ContactRecord> = None;
+    for line in lines {
+        let Some((property, value)) = line.split_once(':') else {
+            continue;
+        };
+        // Drop group prefixes ("item1.URL") and parameters ("URL;TYPE=home")
+        let property = property.split(';').next().unwrap_or_default();
+        let property = property
+            .rsplit('.')
+            .next()
+            .unwrap_or_default()
+            .to_uppercase();
+        let value = unescape_vcard(value.trim());
+
+        match property.as_str() {
+            "BEGIN" if value.eq_ignore_ascii_case("VCARD") => {
+                card = Some(ContactRecord {
+                    entry: records.len() + 1,
+                    ..Default::default()
+                });
+            }
+            "END" if value.eq_ignore_ascii_case("VCARD") => {
+                if let Some(card) = card.take() {
+                    records.push(card);
+                }
+            }
+            _ => {
+                let Some(card) = card.as_mut() else {
+                    continue;
+                };
+                match property.as_str() {
+                    "FN" => card.name = Some(value),
+                    "N" if card.name.is_none() => {
+                        // Family;Given;... -> "Given Family"
+                        let parts: Vec<&str> = value.split(';').collect();
+                        let name = [parts.get(1), parts.first()]
+                            .into_iter()
+                            .flatten()
+                            .filter(|p| !p.is_empty())
+                            .copied()
+                            .collect::<Vec<_>>()
+                            .join(" ");
+                        if !name.is_empty() {
+                            card.name = Some(name);
+                        }
+                    }
+                    "NOTE" => card.notes = Some(value),
+                    "X-PUBKY" => card.uri = Some(value),
+                    "URL" | "IMPP"
+                        if card.uri.is_none() && value.to_lowercase().starts_with("pubky:") =>
+                    {
+                        card.uri = Some(value)
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    if card.is_some() {
+        return Err(anyhow!("vCard is missing END:VCARD"));
+    }
+    Ok(records)
+}
+
+fn unescape_vcard(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n' | 'N')) => {
+                chars.next();
+                out.push('\n');
+            }
+            ('\\', Some(next @ (',' | ';' | '\\'))) => {
+                chars.next();
+                out.push(next);
+            }
+            (c, _) => out.push(c),
+        }
+    }
+    out
+}
+
+// ============================================================================
+// Nostr
+// ============================================================================
+
+#[cfg(feature = "nostr")]
+mod nostr {
+    use super::ContactRecord;
+    use anyhow::{anyhow, Result};
+    use serde::Deserialize;
+    use serde_json::Value;
+    use std::collections::HashMap;
+
+    const KIND_METADATA: u64 = 0;
+    const KIND_CONTACTS: u64 = 3;
+
+    #[derive(Deserialize)]
+    struct Event {
+        kind: u64,
+        #[serde(default)]
+        pubkey: String,
+        #[serde(default)]
+        created_at: i64,
+        #[serde(default)]
+        tags: Vec<Vec<String>>,
+        #[serde(default)]
+        content: String,
+    }
+
+    #[derive(Default)]
+    struct Profile {
+        name: Option<String>,
+        pubky: Option<String>,
+    }
+
+    /// Read a kind 3 contact list, enriched with any kind 0 profiles
+    ///
+    /// Accepts a single event, a JSON array of events or one event per line.
+    pub(super) fn parse_nostr(input: &str) -> Result<Vec<ContactRecord>> {
+        let events = parse_events(input)?;
+
+        let contacts = events
+            .iter()
+            .filter(|e| e.kind == KIND_CONTACTS)
+            .max_by_key(|e| e.created_at)
+            .ok_or_else(|| anyhow!("No nostr contact list (kind 3) event found"))?;
+
+        let profiles: HashMap<&str, Profile> = events
+            .iter()
+            .filter(|e| e.kind == KIND_METADATA)
+            .map(|e| (e.pubkey.as_str(), profile(e)))
+            .collect();
+
+        Ok(contacts
+            .tags
+            .iter()
+            .filter(|tag| tag.first().map(String::as_str) == Some("p"))
+            .filter_map(|tag| tag.get(1).map(|key| (key, tag.get(3))))
+            .enumerate()
+            .map(|(i, (key, petname))| {
+                let profile = profiles.get(key.as_str());
+                let petname = petname.filter(|p| !p.is_empty());
+                // A petname may itself be a pubky URI
+                let (name, uri) = match petname {
+                    Some(p) if p.starts_with("pubky:") => (None, Some(p.clone())),
+                    p => (p.cloned(), None),
+                };
+                ContactRecord {
+                    entry: i + 1,
+                    name: name.or_else(|| profile.and_then(|p| p.name.clone())),
+                    uri: uri.or_else(|| profile.and_then(|p| p.pubky.clone())),
+                    notes: Some(format!("nostr:{}", key)),
+                }
+            })
+            .collect())
+    }
+
+    fn parse_events(input: &str) -> Result<Vec<Event>> {
+        let input = input.trim();
+        if input.starts_with('[') {
+            return serde_json::from_str(input).map_err(|e| anyhow!("Invalid nostr events: {}", e));
+        }
+        input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line).map_err(|e| anyhow!("Invalid nostr event: {}", e))
+            })
+            .collect()
+    }
+
+    fn profile(event: &Event) -> Profile {
+        let metadata: Value = serde_json::from_str(&event.content).unwrap_or(Value::Null);
+        let field = |key: &str| {
+            metadata
+                .get(key)
+                .and_then(Value::as_str)
+                .filter(|v| !v.is_empty())
+        };
+
+        // NIP-39 identity tags first, then any pubky URI in the profile text
+        let from_tags = event.tags.iter().find_map(|tag| match tag.as_slice() {
+            [kind, value, ..] if kind == "i" && value.starts_with("pubky:") => Some(value.clone()),
+            _ => None,
+        });
+        let from_fields = ["pubky", "website", "about"]
+            .into_iter()
+            .filter_map(field)
+            .find_map(find_pubky_uri);
+
+        Profile {
+            name: field("display_name")
+                .or_else(|| field("name"))
+                .map(String::from),
+            pubky: from_tags.or(from_fields),
+        }
+    }
+
+    fn find_pubky_uri(text: &str) -> Option<String> {
+        let start = text.find("pubky://")?;
+        let uri: String = text[start..]
+            .chars()
+            .take_while(|c| !c.is_whitespace() && !matches!(c, '"' | '\'' | ')' | '>' | ','))
+            .collect();
+        // Drop sentence punctuation following the URI
+        Some(uri.trim_end_matches(['.', ';', ':', '!', '?']).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pubky::Keypair;
+
+    fn key() -> String {
+        Keypair::random().public_key().to_string()
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let (alice, bob) = (key(), key());
+        let csv = format!(
+            "Notes,Name,Pubky\n\"Met at \"\"BTC\"\", Prague\",Alice,pubky://{}\n,Bob,{}\n,Carol,\n",
+            alice, bob
+        );
+        let records = parse_contacts(ImportFormat::Csv, &csv).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].name.as_deref(), Some("Alice"));
+        assert_eq!(records[0].notes.as_deref(), Some("Met at \"BTC\", Prague"));
+        assert_eq!(records[1].uri.as_deref(), Some(bob.as_str()));
+        assert_eq!(records[2].uri, None);
+
+        // Headerless files are name,pubky,notes
+        let records = parse_contacts(ImportFormat::Csv, &format!("Dave,{},\n", alice)).unwrap();
+        assert_eq!(records[0].name.as_deref(), Some("Dave"));
+        assert!(parse_contacts(ImportFormat::Csv, "\"open").is_err());
+    }
+
+    #[test]
+    fn test_parse_vcard() {
+        let alice = key();
+        let vcf = format!(
+            "BEGIN:VCARD\r\nVERSION:4.0\r\nN:Liddell;Alice;;;\r\nitem1.URL;TYPE=home:https://example.com\r\n\
+             item2.URL:pubky://{}/pub/\r\nNOTE:line one\\nline two\\, more\r\nEND:VCARD\r\n\
+             BEGIN:VCARD\r\nFN:Bob\r\nX-PUBKY:not\r\n -a-key\r\nEND:VCARD\r\n",
+            alice
+        );
+        let records = parse_contacts(ImportFormat::VCard, &vcf).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].name.as_deref(), Some("Alice Liddell"));
+        assert_eq!(
+            parse_pubky_uri(records[0].uri.as_deref().unwrap())
+                .unwrap()
+                .to_string(),
+            alice
+        );
+        assert_eq!(
+            records[0].notes.as_deref(),
+            Some("line one\nline two, more")
+        );
+        assert_eq!(records[1].uri.as_deref(), Some("not-a-key"));
+        assert!(parse_contacts(ImportFormat::VCard, "BEGIN:VCARD\nFN:Eve\n").is_err());
+    }
+
+    #[test]
+    fn test_import_dedupes_and_reports() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let storage = DemoStorage::new(temp_dir.path());
+        storage.init().unwrap();
+
+        let (alice, bob) = (key(), key());
+        storage
+            .save_contact(Contact::new(alice.parse().unwrap(), "Alice".to_string()))
+            .unwrap();
+
+        let csv = format!(
+            "name,pubky\nAlice Again,pubky://{alice}\nBob,pubky://{bob}\nBobby,{bob}\nEve,nope\nNobody,\n"
+        );
+        let summary = import_contacts(&storage, ImportFormat::Csv, &csv, true).unwrap();
+        assert_eq!(summary.total, 5);
+        assert_eq!(summary.imported.len(), 1);
+        assert_eq!(summary.duplicates(), 2);
+        assert_eq!(storage.list_contacts().unwrap().len(), 1);
+
+        let summary = import_contacts(&storage, ImportFormat::Csv, &csv, false).unwrap();
+        let reasons: Vec<&SkipReason> = summary.skipped.iter().map(|s| &s.reason).collect();
+        assert_eq!(reasons[0], &SkipReason::AlreadyExists("Alice".to_string()));
+        assert_eq!(reasons[1], &SkipReason::Duplicate);
+        assert!(matches!(reasons[2], SkipReason::InvalidKey(_)));
+        assert_eq!(reasons[3], &SkipReason::MissingKey);
+
+        let contacts = storage.list_contacts().unwrap();
+        assert_eq!(contacts.len(), 2);
+        assert!(contacts.iter().any(|c| c.name == "Bob"));
+    }
+
+    #[cfg(feature = "nostr")]
+    #[test]
+    fn test_parse_nostr() {
+        let (alice, bob) = (key(), key());
+        let events = serde_json::json!([
+            {
+                "kind": 3,
+                "pubkey": "me",
+                "created_at": 10,
+                "tags": [
+                    ["p", "aa", "wss://relay.example", "alice"],
+                    ["p", "bb", "", format!("pubky://{}", bob)],
+                    ["p", "cc"]
+                ],
+                "content": ""
+            },
+            {
+                "kind": 0,
+                "pubkey": "aa",
+                "created_at": 5,
+                "tags": [],
+                "content": serde_json::json!({
+                    "name": "Alice",
+                    "about": format!("Pay me at pubky://{}.", alice)
+                }).to_string()
+            }
+        ]);
+
+        let records = parse_contacts(ImportFormat::Nostr, &events.to_string()).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].name.as_deref(), Some("alice"));
+        assert!(records[0].uri.as_deref().unwrap().contains(&alice));
+        assert_eq!(records[1].uri, Some(format!("pubky://{}", bob)));
+        assert_eq!(records[2].uri, None);
+        assert_eq!(records[2].notes.as_deref(), Some("nostr:cc"));
+    }
+}
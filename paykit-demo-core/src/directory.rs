@@ -1,16 +1,40 @@
 //! Directory operations using paykit-lib
+//!
+//! # Concurrent publishers
+//!
+//! Two devices sharing an identity can publish at the same time. The
+//! homeserver has no conditional writes, so [`DirectoryClient`] detects
+//! lost updates by content: [`DirectoryClient::fetch_snapshot`] returns
+//! the endpoint list with a [`DirectoryVersion`] (a hash of its contents),
+//! and [`DirectoryClient::replace_methods_checked`] re-reads the list and
+//! refuses to write with a [`DirectoryConflict`] if the version moved.
+//! [`DirectoryConflict::merge`] then combines both sides' changes.
+//!
+//! The re-read and the writes are not atomic; a publish landing between
+//! them still wins, but the window shrinks from the whole edit to one
+//! round trip.
 
 use crate::models::PaymentMethod;
 use anyhow::{Context, Result};
+use paykit_lib::protocol::{
+    clear_payee_status, fetch_payee_status, publish_payee_status, publish_payment_link,
+    remove_payment_link, resolve_payment_link, PathRootConfig, PayeeStatus, PaymentIntent,
+    PaymentLink,
+};
 use paykit_lib::{
     AuthenticatedTransport, EndpointData, MethodId, PubkyAuthenticatedTransport,
     PubkyUnauthenticatedTransport, PublicKey, UnauthenticatedTransportRead,
 };
 use pubky::{Pubky, PubkySession, PublicStorage};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
 
 /// Client for interacting with the Pubky directory
 pub struct DirectoryClient {
     homeserver: String,
+    roots: PathRootConfig,
 }
 
 impl DirectoryClient {
@@ -18,9 +42,21 @@ impl DirectoryClient {
     pub fn new(homeserver: impl Into<String>) -> Self {
         Self {
             homeserver: homeserver.into(),
+            roots: PathRootConfig::default(),
         }
     }
 
+    /// Publish under a white-label root and discover payees in its read roots
+    pub fn with_path_roots(mut self, roots: PathRootConfig) -> Self {
+        self.roots = roots;
+        self
+    }
+
+    /// The roots paths are written and read under
+    pub fn path_roots(&self) -> &PathRootConfig {
+        &self.roots
+    }
+
     /// Get the homeserver URL
     pub fn homeserver(&self) -> &str {
         &self.homeserver
@@ -32,7 +68,7 @@ impl DirectoryClient {
         session: &PubkySession,
         methods: &[PaymentMethod],
     ) -> Result<()> {
-        let transport = PubkyAuthenticatedTransport::new(session.clone());
+        let transport = self.writer(session);
 
         for method in methods {
             let method_id = MethodId(method.method_id.clone());
@@ -49,8 +85,7 @@ impl DirectoryClient {
 
     /// Query payment methods from a public key
     pub async fn query_methods(&self, public_key: &PublicKey) -> Result<Vec<PaymentMethod>> {
-        let storage = PublicStorage::new().context("Failed to create PublicStorage")?;
-        let transport = PubkyUnauthenticatedTransport::new(storage);
+        let transport = self.reader()?;
 
         let supported = transport
             .fetch_supported_payments(public_key)
@@ -69,9 +104,62 @@ impl DirectoryClient {
         Ok(methods)
     }
 
+    /// Fetch a public key's endpoint list with its version
+    ///
+    /// Pass the version to [`replace_methods_checked`](Self::replace_methods_checked)
+    /// when writing back an edited list.
+    pub async fn fetch_snapshot(&self, public_key: &PublicKey) -> Result<DirectorySnapshot> {
+        Ok(DirectorySnapshot::new(
+            self.query_methods(public_key).await?,
+        ))
+    }
+
+    /// Make the published list exactly `methods`, unless it changed since
+    /// `expected` was read
+    ///
+    /// Only methods whose endpoint differs are written, and methods missing
+    /// from `methods` are removed. Returns the version of the new list.
+    ///
+    /// # Errors
+    ///
+    /// Fails with a [`DirectoryConflict`] (reachable through
+    /// `anyhow::Error::downcast_ref`) if the published list no longer
+    /// matches `expected`; nothing is written in that case.
+    pub async fn replace_methods_checked(
+        &self,
+        session: &PubkySession,
+        public_key: &PublicKey,
+        expected: &DirectoryVersion,
+        methods: &[PaymentMethod],
+    ) -> Result<DirectoryVersion> {
+        let current = self.fetch_snapshot(public_key).await?;
+        if current.version != *expected {
+            return Err(DirectoryConflict {
+                expected: expected.clone(),
+                current,
+            }
+            .into());
+        }
+
+        let changed: Vec<PaymentMethod> = methods
+            .iter()
+            .filter(|method| current.endpoint(&method.method_id) != Some(method.endpoint.as_str()))
+            .cloned()
+            .collect();
+        self.publish_methods(session, &changed).await?;
+
+        for method in &current.methods {
+            if !methods.iter().any(|m| m.method_id == method.method_id) {
+                self.delete_method(session, &method.method_id).await?;
+            }
+        }
+
+        Ok(DirectoryVersion::of(methods))
+    }
+
     /// Delete a payment method from the directory
     pub async fn delete_method(&self, session: &PubkySession, method_id: &str) -> Result<()> {
-        let transport = PubkyAuthenticatedTransport::new(session.clone());
+        let transport = self.writer(session);
         let method_id = MethodId(method_id.to_string());
 
         transport
@@ -82,6 +170,64 @@ impl DirectoryClient {
         Ok(())
     }
 
+    /// Fetch a payee's self-reported status
+    ///
+    /// Returns `None` if the payee publishes no status or it has expired.
+    pub async fn fetch_status(&self, public_key: &PublicKey) -> Result<Option<PayeeStatus>> {
+        let transport = self.reader()?;
+        let now = chrono::Utc::now().timestamp();
+
+        fetch_payee_status(&transport, public_key, now)
+            .await
+            .context("Failed to fetch payee status")
+    }
+
+    /// Publish our status (accepting payments, unavailable methods, maintenance)
+    pub async fn publish_status(&self, session: &PubkySession, status: &PayeeStatus) -> Result<()> {
+        let transport = self.writer(session);
+
+        publish_payee_status(&transport, status)
+            .await
+            .context("Failed to publish payee status")
+    }
+
+    /// Remove our published status
+    pub async fn clear_status(&self, session: &PubkySession) -> Result<()> {
+        let transport = self.writer(session);
+
+        clear_payee_status(&transport)
+            .await
+            .context("Failed to clear payee status")
+    }
+
+    /// Resolve one of a payee's published payment links into a payment intent
+    ///
+    /// Fails if the link is not published, or is expired, used up or revoked.
+    pub async fn resolve_link(&self, payee: &PublicKey, link_id: &str) -> Result<PaymentIntent> {
+        let transport = self.reader()?;
+        let now = chrono::Utc::now().timestamp();
+
+        Ok(resolve_payment_link(&transport, payee, link_id, now).await?)
+    }
+
+    /// Publish or update one of our payment links
+    pub async fn publish_link(&self, session: &PubkySession, link: &PaymentLink) -> Result<()> {
+        let transport = self.writer(session);
+
+        publish_payment_link(&transport, link)
+            .await
+            .context("Failed to publish payment link")
+    }
+
+    /// Remove one of our payment links from the directory
+    pub async fn remove_link(&self, session: &PubkySession, link_id: &str) -> Result<()> {
+        let transport = self.writer(session);
+
+        remove_payment_link(&transport, link_id)
+            .await
+            .context("Failed to remove payment link")
+    }
+
     /// Create a Pubky session for authenticated operations.
     ///
     /// This creates a session by signing in to a homeserver using the Pubky SDK.
@@ -169,6 +315,15 @@ impl DirectoryClient {
     ///
     /// Used for fetching profiles and other arbitrary data from the directory.
     pub async fn get_raw(&self, public_key: &PublicKey, path: &str) -> Result<Option<String>> {
+        for path in self.roots.read_paths(path) {
+            if let Some(content) = self.get_raw_at(public_key, &path).await? {
+                return Ok(Some(content));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn get_raw_at(&self, public_key: &PublicKey, path: &str) -> Result<Option<String>> {
         let storage = PublicStorage::new().context("Failed to create PublicStorage")?;
 
         // Construct the full URL for the resource
@@ -206,13 +361,25 @@ impl DirectoryClient {
         }
     }
 
+    /// List the file names in a directory of a public key's public storage
+    ///
+    /// Subdirectories are not included. A missing directory lists as empty.
+    pub async fn list_raw(&self, public_key: &PublicKey, path: &str) -> Result<Vec<String>> {
+        let transport = self.reader()?;
+
+        transport
+            .list_directory(public_key, path)
+            .await
+            .with_context(|| format!("Failed to list {}", path))
+    }
+
     /// Put raw data to authenticated storage
     ///
     /// Used for publishing profiles and other arbitrary data to the directory.
     pub async fn put_raw(&self, session: &PubkySession, path: &str, content: &str) -> Result<()> {
         session
             .storage()
-            .put(path, content.as_bytes().to_vec())
+            .put(self.roots.rebase(path), content.as_bytes().to_vec())
             .await
             .with_context(|| format!("Failed to put data at {}", path))?;
 
@@ -223,10 +390,244 @@ impl DirectoryClient {
     pub async fn delete_raw(&self, session: &PubkySession, path: &str) -> Result<()> {
         session
             .storage()
-            .delete(path)
+            .delete(self.roots.rebase(path))
             .await
             .with_context(|| format!("Failed to delete {}", path))?;
 
         Ok(())
     }
+
+    fn reader(&self) -> Result<PubkyUnauthenticatedTransport> {
+        let storage = PublicStorage::new().context("Failed to create PublicStorage")?;
+        Ok(PubkyUnauthenticatedTransport::new(storage).with_path_roots(self.roots.clone()))
+    }
+
+    fn writer(&self, session: &PubkySession) -> PubkyAuthenticatedTransport {
+        PubkyAuthenticatedTransport::new(session.clone()).with_path_root(self.roots.root.clone())
+    }
+}
+
+/// Version of a published endpoint list
+///
+/// A SHA-256 over the sorted method IDs and endpoints: two reads return the
+/// same version exactly when the list is unchanged. Timestamps and the
+/// `is_public` flag are not part of it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DirectoryVersion(String);
+
+impl DirectoryVersion {
+    /// Version of an endpoint list
+    pub fn of(methods: &[PaymentMethod]) -> Self {
+        let mut hasher = Sha256::new();
+        for (method_id, endpoint) in endpoint_map(methods) {
+            hasher.update(method_id.as_bytes());
+            hasher.update([0]);
+            hasher.update(endpoint.as_bytes());
+            hasher.update([0]);
+        }
+        Self(hex::encode(hasher.finalize()))
+    }
+
+    /// The version as a hex string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for DirectoryVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Short form, like a git hash
+        write!(f, "{}", &self.0[..12.min(self.0.len())])
+    }
+}
+
+/// An endpoint list as read from the directory
+#[derive(Debug, Clone)]
+pub struct DirectorySnapshot {
+    /// Published methods
+    pub methods: Vec<PaymentMethod>,
+    /// Version of `methods`
+    pub version: DirectoryVersion,
+}
+
+impl DirectorySnapshot {
+    /// Wrap a list read from the directory
+    pub fn new(methods: Vec<PaymentMethod>) -> Self {
+        let version = DirectoryVersion::of(&methods);
+        Self { methods, version }
+    }
+
+    /// The endpoint published for a method
+    pub fn endpoint(&self, method_id: &str) -> Option<&str> {
+        self.methods
+            .iter()
+            .find(|m| m.method_id == method_id)
+            .map(|m| m.endpoint.as_str())
+    }
+}
+
+/// The endpoint list changed between reading it and writing it back
+#[derive(Debug, Clone)]
+pub struct DirectoryConflict {
+    /// Version the write was based on
+    pub expected: DirectoryVersion,
+    /// What is published now
+    pub current: DirectorySnapshot,
+}
+
+impl DirectoryConflict {
+    /// Combine our edit of `base` with the changes published since
+    ///
+    /// See [`merge_methods`].
+    pub fn merge(&self, base: &[PaymentMethod], ours: &[PaymentMethod]) -> MergedMethods {
+        merge_methods(base, ours, &self.current.methods)
+    }
+}
+
+impl fmt::Display for DirectoryConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "endpoint list changed since it was read (expected version {}, found {})",
+            self.expected, self.current.version
+        )
+    }
+}
+
+impl std::error::Error for DirectoryConflict {}
+
+/// Result of [`merge_methods`]
+#[derive(Debug, Clone)]
+pub struct MergedMethods {
+    /// The merged list; where both sides changed a method, ours is kept
+    pub methods: Vec<PaymentMethod>,
+    /// Methods both sides changed differently
+    pub conflicts: Vec<String>,
+}
+
+impl MergedMethods {
+    /// Whether every change merged cleanly
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Three-way merge of endpoint lists
+///
+/// Per method, a side that left `base` unchanged takes the other side's
+/// value, so additions, edits and removals from either side all carry over.
+/// When both sides changed a method to different values it is reported in
+/// `conflicts` and our value is kept.
+pub fn merge_methods(
+    base: &[PaymentMethod],
+    ours: &[PaymentMethod],
+    theirs: &[PaymentMethod],
+) -> MergedMethods {
+    let find = |list: &[PaymentMethod], method_id: &str| -> Option<PaymentMethod> {
+        list.iter().find(|m| m.method_id == method_id).cloned()
+    };
+    let endpoint = |m: &Option<PaymentMethod>| m.as_ref().map(|m| m.endpoint.clone());
+
+    let mut method_ids: Vec<&str> = ours
+        .iter()
+        .chain(theirs)
+        .map(|m| m.method_id.as_str())
+        .collect();
+    method_ids.sort_unstable();
+    method_ids.dedup();
+
+    let mut methods = Vec::new();
+    let mut conflicts = Vec::new();
+    for method_id in method_ids {
+        let base_endpoint = endpoint(&find(base, method_id));
+        let ours = find(ours, method_id);
+        let theirs = find(theirs, method_id);
+
+        let merged = if endpoint(&ours) == base_endpoint {
+            theirs
+        } else {
+            if endpoint(&theirs) != base_endpoint && endpoint(&theirs) != endpoint(&ours) {
+                conflicts.push(method_id.to_string());
+            }
+            ours
+        };
+        methods.extend(merged);
+    }
+
+    MergedMethods { methods, conflicts }
+}
+
+/// Method ID to endpoint, sorted by method ID
+fn endpoint_map(methods: &[PaymentMethod]) -> BTreeMap<&str, &str> {
+    methods
+        .iter()
+        .map(|m| (m.method_id.as_str(), m.endpoint.as_str()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn method(method_id: &str, endpoint: &str) -> PaymentMethod {
+        PaymentMethod::new(method_id.to_string(), endpoint.to_string(), true)
+    }
+
+    fn endpoints(merged: &MergedMethods) -> Vec<(&str, &str)> {
+        merged
+            .methods
+            .iter()
+            .map(|m| (m.method_id.as_str(), m.endpoint.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_version_ignores_order_and_timestamps() {
+        let a = [method("onchain", "bc1qa"), method("lightning", "lnurl1a")];
+        let mut b = [method("lightning", "lnurl1a"), method("onchain", "bc1qa")];
+        b[0].created_at = 0;
+        assert_eq!(DirectoryVersion::of(&a), DirectoryVersion::of(&b));
+        assert_ne!(
+            DirectoryVersion::of(&a),
+            DirectoryVersion::of(&[method("onchain", "bc1qa")])
+        );
+        // Method ID and endpoint boundaries are part of the hash
+        assert_ne!(
+            DirectoryVersion::of(&[method("ab", "c")]),
+            DirectoryVersion::of(&[method("a", "bc")])
+        );
+    }
+
+    #[test]
+    fn test_merge_keeps_both_sides_changes() {
+        let base = [method("onchain", "bc1qa"), method("lightning", "lnurl1a")];
+        // We edit on-chain, they remove Lightning and add e-cash
+        let ours = [method("onchain", "bc1qb"), method("lightning", "lnurl1a")];
+        let theirs = [method("onchain", "bc1qa"), method("ecash", "fed1x")];
+
+        let merged = merge_methods(&base, &ours, &theirs);
+        assert!(merged.is_clean());
+        assert_eq!(
+            endpoints(&merged),
+            vec![("ecash", "fed1x"), ("onchain", "bc1qb")]
+        );
+    }
+
+    #[test]
+    fn test_merge_reports_conflicts() {
+        let base = [method("lightning", "lnurl1a")];
+        let ours = [method("lightning", "lnurl1b")];
+        let theirs = [method("lightning", "lnurl1c")];
+
+        let conflict = DirectoryConflict {
+            expected: DirectoryVersion::of(&base),
+            current: DirectorySnapshot::new(theirs.to_vec()),
+        };
+        let merged = conflict.merge(&base, &ours);
+        assert_eq!(merged.conflicts, vec!["lightning".to_string()]);
+        assert_eq!(endpoints(&merged), vec![("lightning", "lnurl1b")]);
+
+        // The same change on both sides is not a conflict
+        assert!(merge_methods(&base, &ours, &ours).is_clean());
+    }
 }
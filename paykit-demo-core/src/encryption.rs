@@ -0,0 +1,310 @@
+//! Encryption at rest for demo storage
+//!
+//! [`DemoStorage`](crate::DemoStorage) encrypts every file it writes when a
+//! [`StorageCipher`] is attached. Encrypted files start with a short magic
+//! header so plaintext files left over from before encryption was enabled can
+//! still be read and are re-encrypted on the next write.
+//!
+//! The AES-256-GCM key comes either from the OS keyring or from a passphrase
+//! run through Argon2. Which one is recorded in `encryption.json` next to the
+//! data; the file holds only the salt or keyring entry name, never the key.
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use anyhow::{Context, Result};
+use paykit_lib::prelude::{SecureKeyStorage, StoreOptions};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Keyring entry holding the storage key
+pub const KEYRING_KEY_ID: &str = "storage.encryption_key";
+
+/// Header written in front of every encrypted file
+const MAGIC: &[u8] = b"PKENC1";
+
+const CONFIG_FILE: &str = "encryption.json";
+const VERIFIER_PLAINTEXT: &[u8] = b"paykit-demo-storage";
+const NONCE_LEN: usize = 12;
+
+/// Encrypts and decrypts storage files
+pub trait StorageCipher: Send + Sync {
+    /// Short algorithm name shown in status output
+    fn name(&self) -> &str;
+
+    /// Encrypt a file's contents
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>>;
+
+    /// Decrypt a file's contents produced by [`StorageCipher::encrypt`]
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// AES-256-GCM with a random nonce per write
+pub struct AesGcmCipher {
+    cipher: Aes256Gcm,
+}
+
+impl AesGcmCipher {
+    /// Create a cipher from a raw 256-bit key
+    pub fn from_key(key: &[u8; 32]) -> Self {
+        Self {
+            cipher: Aes256Gcm::new(aes_gcm::Key::<Aes256Gcm>::from_slice(key)),
+        }
+    }
+
+    /// Derive the key from a passphrase with Argon2
+    pub fn from_passphrase(passphrase: &str, salt: &[u8]) -> Result<Self> {
+        if passphrase.is_empty() {
+            anyhow::bail!("Passphrase cannot be empty");
+        }
+        let mut key = [0u8; 32];
+        argon2::Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| anyhow::anyhow!("Key derivation failed: {}", e))?;
+        Ok(Self::from_key(&key))
+    }
+
+    /// Generate a random 256-bit key
+    pub fn generate_key() -> [u8; 32] {
+        let mut key = [0u8; 32];
+        rand::rngs::OsRng.fill_bytes(&mut key);
+        key
+    }
+}
+
+impl StorageCipher for AesGcmCipher {
+    fn name(&self) -> &str {
+        "aes-256-gcm"
+    }
+
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext)
+            .map_err(|e| anyhow::anyhow!("Encryption failed: {}", e))?;
+
+        let mut out = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&ciphertext);
+        Ok(out)
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        if ciphertext.len() < NONCE_LEN {
+            anyhow::bail!("Encrypted data is truncated");
+        }
+        let (nonce, data) = ciphertext.split_at(NONCE_LEN);
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), data)
+            .map_err(|_| anyhow::anyhow!("Decryption failed - wrong key or corrupted data"))
+    }
+}
+
+/// Whether file contents carry the encrypted header
+pub fn is_sealed(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// Encrypt file contents and prepend the header
+pub fn seal(cipher: &dyn StorageCipher, plaintext: &[u8]) -> Result<Vec<u8>> {
+    let mut out = MAGIC.to_vec();
+    out.extend(cipher.encrypt(plaintext)?);
+    Ok(out)
+}
+
+/// Strip the header and decrypt file contents
+pub fn open(cipher: &dyn StorageCipher, sealed: &[u8]) -> Result<Vec<u8>> {
+    match sealed.strip_prefix(MAGIC) {
+        Some(ciphertext) => cipher.decrypt(ciphertext),
+        None => anyhow::bail!("Data is not encrypted"),
+    }
+}
+
+/// Where the storage key comes from
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum KeySource {
+    /// Random key kept in the OS keyring
+    Keyring { key_id: String },
+    /// Key derived from a passphrase with Argon2
+    Passphrase { salt_hex: String },
+}
+
+impl std::fmt::Display for KeySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeySource::Keyring { key_id } => write!(f, "OS keyring ({})", key_id),
+            KeySource::Passphrase { .. } => write!(f, "passphrase (Argon2)"),
+        }
+    }
+}
+
+/// Encryption settings stored alongside the data
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EncryptionConfig {
+    /// Config format version
+    pub version: u32,
+    /// Where the key comes from
+    pub key_source: KeySource,
+    /// When encryption was enabled (Unix seconds)
+    pub enabled_at: i64,
+    /// Known value sealed with the key, used to reject a wrong key up front
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verifier_hex: Option<String>,
+}
+
+impl EncryptionConfig {
+    /// Config for a key kept in the OS keyring
+    pub fn keyring() -> Self {
+        Self::with_source(KeySource::Keyring {
+            key_id: KEYRING_KEY_ID.to_string(),
+        })
+    }
+
+    /// Config for a passphrase-derived key with a fresh salt
+    pub fn passphrase() -> Self {
+        let mut salt = [0u8; 16];
+        rand::rngs::OsRng.fill_bytes(&mut salt);
+        Self::with_source(KeySource::Passphrase {
+            salt_hex: hex::encode(salt),
+        })
+    }
+
+    fn with_source(key_source: KeySource) -> Self {
+        Self {
+            version: 1,
+            key_source,
+            enabled_at: chrono::Utc::now().timestamp(),
+            verifier_hex: None,
+        }
+    }
+
+    /// Record a verifier so later unlocks can detect a wrong key
+    pub fn with_verifier(mut self, cipher: &dyn StorageCipher) -> Result<Self> {
+        self.verifier_hex = Some(hex::encode(seal(cipher, VERIFIER_PLAINTEXT)?));
+        Ok(self)
+    }
+
+    /// Check that a cipher holds the key this storage was encrypted with
+    pub fn verify(&self, cipher: &dyn StorageCipher) -> Result<()> {
+        let Some(verifier_hex) = &self.verifier_hex else {
+            return Ok(());
+        };
+        let verifier = hex::decode(verifier_hex).context("Invalid verifier")?;
+        match open(cipher, &verifier) {
+            Ok(plaintext) if plaintext == VERIFIER_PLAINTEXT => Ok(()),
+            _ => anyhow::bail!("Wrong storage key or passphrase"),
+        }
+    }
+
+    /// Path of the config file inside a storage directory
+    pub fn path(storage_dir: &Path) -> PathBuf {
+        storage_dir.join(CONFIG_FILE)
+    }
+
+    /// Load the config, or `None` if encryption was never enabled
+    pub fn load(storage_dir: &Path) -> Result<Option<Self>> {
+        let path = Self::path(storage_dir);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let json = std::fs::read_to_string(&path).context("Failed to read encryption config")?;
+        let config: Self =
+            serde_json::from_str(&json).context("Failed to parse encryption config")?;
+        if config.version != 1 {
+            anyhow::bail!("Unsupported encryption config version: {}", config.version);
+        }
+        Ok(Some(config))
+    }
+
+    /// Write the config into a storage directory
+    pub fn save(&self, storage_dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(storage_dir).context("Failed to create storage directory")?;
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(Self::path(storage_dir), json).context("Failed to write encryption config")
+    }
+
+    /// Build the cipher for a passphrase-based config
+    pub fn cipher_from_passphrase(&self, passphrase: &str) -> Result<AesGcmCipher> {
+        match &self.key_source {
+            KeySource::Passphrase { salt_hex } => {
+                let salt = hex::decode(salt_hex).context("Invalid salt")?;
+                AesGcmCipher::from_passphrase(passphrase, &salt)
+            }
+            KeySource::Keyring { .. } => anyhow::bail!("Storage key is kept in the OS keyring"),
+        }
+    }
+
+    /// Build the cipher for a keyring-based config
+    ///
+    /// With `create` set, a missing key is generated and stored first.
+    pub async fn cipher_from_keyring<S: SecureKeyStorage>(
+        &self,
+        keyring: &S,
+        create: bool,
+    ) -> Result<AesGcmCipher> {
+        let KeySource::Keyring { key_id } = &self.key_source else {
+            anyhow::bail!("Storage key is derived from a passphrase");
+        };
+
+        let stored = keyring
+            .retrieve(key_id)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to read storage key: {}", e))?;
+        let key = match stored {
+            Some(bytes) => bytes
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("Storage key in keyring has the wrong length"))?,
+            None if create => {
+                let key = AesGcmCipher::generate_key();
+                keyring
+                    .store(key_id, &key, StoreOptions::default())
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Failed to store storage key: {}", e))?;
+                key
+            }
+            None => anyhow::bail!("Storage key '{}' not found in keyring", key_id),
+        };
+        Ok(AesGcmCipher::from_key(&key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paykit_lib::secure_storage::InMemoryKeyStorage;
+
+    #[test]
+    fn test_seal_round_trip() {
+        let cipher = AesGcmCipher::from_passphrase("correct horse", b"0123456789abcdef").unwrap();
+        let sealed = seal(&cipher, b"{\"contacts\":{}}").unwrap();
+        assert!(is_sealed(&sealed));
+        assert_eq!(open(&cipher, &sealed).unwrap(), b"{\"contacts\":{}}");
+
+        let wrong = AesGcmCipher::from_passphrase("wrong horse", b"0123456789abcdef").unwrap();
+        assert!(open(&wrong, &sealed).is_err());
+        assert!(!is_sealed(b"{}"));
+
+        let config = EncryptionConfig::passphrase()
+            .with_verifier(&cipher)
+            .unwrap();
+        assert!(config.verify(&cipher).is_ok());
+        assert!(config.verify(&wrong).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_keyring_key_is_reused() {
+        let keyring = InMemoryKeyStorage::new();
+        let config = EncryptionConfig::keyring();
+        assert!(config.cipher_from_keyring(&keyring, false).await.is_err());
+
+        let first = config.cipher_from_keyring(&keyring, true).await.unwrap();
+        let second = config.cipher_from_keyring(&keyring, false).await.unwrap();
+        let sealed = seal(&first, b"data").unwrap();
+        assert_eq!(open(&second, &sealed).unwrap(), b"data");
+    }
+}
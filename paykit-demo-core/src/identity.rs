@@ -41,32 +41,8 @@ where
     Ok(Keypair::from_secret_key(&secret))
 }
 
-/// Encrypted backup of an identity
-#[derive(Clone, Serialize, Deserialize)]
-pub struct KeyBackup {
-    /// Version of the backup format
-    pub version: u32,
-    /// Encrypted secret key data (hex encoded)
-    pub encrypted_data_hex: String,
-    /// Salt used for key derivation (hex encoded)
-    pub salt_hex: String,
-    /// Nonce used for encryption (hex encoded)
-    pub nonce_hex: String,
-    /// Public key for identification (z32 encoded)
-    pub public_key_z32: String,
-}
-
-impl KeyBackup {
-    /// Serialize backup to JSON string
-    pub fn to_json(&self) -> Result<String> {
-        serde_json::to_string(self).context("Failed to serialize backup")
-    }
-
-    /// Deserialize backup from JSON string
-    pub fn from_json(json: &str) -> Result<Self> {
-        serde_json::from_str(json).context("Failed to parse backup")
-    }
-}
+/// Encrypted backup of an identity, in the format shared with web and mobile
+pub use paykit_lib::backup::{KdfParams, KeyBackup};
 
 impl Identity {
     /// Generate a new random identity
@@ -79,97 +55,30 @@ impl Identity {
 
     /// Export identity to encrypted backup
     ///
-    /// Uses Argon2 for password-based key derivation and AES-256-GCM for encryption.
+    /// Uses Argon2id for password-based key derivation and AES-256-GCM for
+    /// encryption; see [`paykit_lib::backup`] for the format.
     pub fn export_backup(&self, password: &str) -> Result<KeyBackup> {
-        use aes_gcm::{
-            aead::{Aead, KeyInit},
-            Aes256Gcm, Nonce,
-        };
-        use argon2::Argon2;
-        use rand::RngCore;
-
-        let secret_bytes = self.keypair.secret_key();
-
-        // Derive encryption key from password using Argon2
-        let mut salt = [0u8; 16];
-        rand::rngs::OsRng.fill_bytes(&mut salt);
-
-        let mut encryption_key = [0u8; 32];
-        Argon2::default()
-            .hash_password_into(password.as_bytes(), &salt, &mut encryption_key)
-            .map_err(|e| anyhow::anyhow!("Key derivation failed: {}", e))?;
-
-        // Encrypt with AES-GCM
-        let cipher = Aes256Gcm::new_from_slice(&encryption_key)
-            .map_err(|e| anyhow::anyhow!("Cipher init failed: {}", e))?;
-
-        let mut nonce_bytes = [0u8; 12];
-        rand::rngs::OsRng.fill_bytes(&mut nonce_bytes);
-        let nonce = Nonce::from_slice(&nonce_bytes);
-
-        let encrypted = cipher
-            .encrypt(nonce, secret_bytes.as_ref())
-            .map_err(|e| anyhow::anyhow!("Encryption failed: {}", e))?;
-
-        Ok(KeyBackup {
-            version: 1,
-            encrypted_data_hex: hex::encode(encrypted),
-            salt_hex: hex::encode(salt),
-            nonce_hex: hex::encode(nonce_bytes),
-            public_key_z32: self.public_key().to_string(),
-        })
+        KeyBackup::seal(
+            &self.keypair.secret_key(),
+            &self.public_key().to_string(),
+            password,
+            KdfParams::default(),
+        )
+        .context("Failed to create backup")
     }
 
     /// Import identity from encrypted backup
     ///
-    /// Uses Argon2 for password-based key derivation and AES-256-GCM for decryption.
+    /// Accepts current and version 1 backups.
     pub fn import_backup(backup: &KeyBackup, password: &str) -> Result<Self> {
-        use aes_gcm::{
-            aead::{Aead, KeyInit},
-            Aes256Gcm, Nonce,
-        };
-        use argon2::Argon2;
-
-        if backup.version != 1 {
-            anyhow::bail!("Unsupported backup version: {}", backup.version);
-        }
-
-        let salt = hex::decode(&backup.salt_hex).context("Invalid salt")?;
-        let nonce_bytes = hex::decode(&backup.nonce_hex).context("Invalid nonce")?;
-        let encrypted =
-            hex::decode(&backup.encrypted_data_hex).context("Invalid encrypted data")?;
-
-        // Derive encryption key from password
-        let mut encryption_key = [0u8; 32];
-        Argon2::default()
-            .hash_password_into(password.as_bytes(), &salt, &mut encryption_key)
-            .map_err(|e| anyhow::anyhow!("Key derivation failed: {}", e))?;
-
-        // Decrypt with AES-GCM
-        let cipher = Aes256Gcm::new_from_slice(&encryption_key)
-            .map_err(|e| anyhow::anyhow!("Cipher init failed: {}", e))?;
-
-        if nonce_bytes.len() != 12 {
-            anyhow::bail!("Invalid nonce length");
-        }
-        let nonce = Nonce::from_slice(&nonce_bytes);
-
-        let decrypted = cipher
-            .decrypt(nonce, encrypted.as_ref())
+        let secret_key = backup
+            .open(password)
             .map_err(|_| anyhow::anyhow!("Decryption failed - wrong password or corrupted data"))?;
-
-        if decrypted.len() != 32 {
-            anyhow::bail!("Invalid decrypted data length");
-        }
-
-        let mut secret_key = [0u8; 32];
-        secret_key.copy_from_slice(&decrypted);
-
         let keypair = Keypair::from_secret_key(&secret_key);
 
         // Verify public key matches
         let derived_public_key = keypair.public_key().to_string();
-        if derived_public_key != backup.public_key_z32 {
+        if derived_public_key != backup.public_key_z32() {
             anyhow::bail!("Public key mismatch - backup may be corrupted");
         }
 
@@ -214,14 +123,60 @@ impl Identity {
     }
 }
 
+/// Where an identity's secret key is kept
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretLocation {
+    /// Hex in the identity file (legacy)
+    #[default]
+    Plaintext,
+    /// Passphrase-encrypted in the identity file
+    EncryptedFile,
+    /// Platform keychain / secret service
+    Keyring,
+}
+
+impl std::fmt::Display for SecretLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SecretLocation::Plaintext => write!(f, "plaintext file"),
+            SecretLocation::EncryptedFile => write!(f, "encrypted file"),
+            SecretLocation::Keyring => write!(f, "OS keyring"),
+        }
+    }
+}
+
+/// Keyring service name for identity secrets
+pub const KEYRING_SERVICE: &str = "paykit-demo";
+
+/// Whether this build can store secrets in the OS keyring
+pub const fn keyring_supported() -> bool {
+    cfg!(all(feature = "keyring", not(target_arch = "wasm32")))
+}
+
 /// Manages identity persistence and loading
+///
+/// By default secrets are written to the identity file in plaintext. Use
+/// [`IdentityManager::with_keyring`] to keep them in the platform keychain and
+/// [`IdentityManager::with_passphrase`] to encrypt them in the file instead,
+/// or as the fallback when the keychain is unavailable.
 pub struct IdentityManager {
     storage_dir: PathBuf,
+    use_keyring: bool,
+    passphrase: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
 struct StoredIdentity {
-    secret_key_hex: String,
+    #[serde(default)]
+    location: SecretLocation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    secret_key_hex: Option<String>,
+    /// Armored [`KeyBackup`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    encrypted: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    public_key_z32: Option<String>,
     nickname: Option<String>,
 }
 
@@ -230,41 +185,103 @@ impl IdentityManager {
     pub fn new(storage_dir: impl AsRef<Path>) -> Self {
         Self {
             storage_dir: storage_dir.as_ref().to_path_buf(),
+            use_keyring: false,
+            passphrase: None,
         }
     }
 
+    /// Store new secrets in the OS keyring
+    ///
+    /// Fails at save time if this build has no keyring support; check
+    /// [`keyring_supported`] first.
+    pub fn with_keyring(mut self) -> Self {
+        self.use_keyring = true;
+        self
+    }
+
+    /// Passphrase for encrypted identity files
+    ///
+    /// Used to encrypt new secrets when the keyring is not in use or fails,
+    /// and to decrypt identities saved that way.
+    pub fn with_passphrase(mut self, passphrase: impl Into<String>) -> Self {
+        self.passphrase = Some(passphrase.into());
+        self
+    }
+
     /// Save an identity to disk
-    pub fn save(&self, identity: &Identity, name: &str) -> Result<()> {
+    ///
+    /// Returns where the secret key ended up.
+    pub fn save(&self, identity: &Identity, name: &str) -> Result<SecretLocation> {
         std::fs::create_dir_all(&self.storage_dir).context("Failed to create storage directory")?;
 
         let path = self.identity_path(name);
-
-        // Convert secret key to hex
         let secret_key_hex = hex::encode(identity.keypair.secret_key());
 
-        let stored = StoredIdentity {
-            secret_key_hex,
+        let mut stored = StoredIdentity {
+            location: SecretLocation::Plaintext,
+            secret_key_hex: None,
+            encrypted: None,
+            public_key_z32: Some(identity.public_key().to_string()),
             nickname: identity.nickname.clone(),
         };
 
+        let keyring_error = if self.use_keyring {
+            match keyring_store(name, &secret_key_hex) {
+                Ok(()) => {
+                    stored.location = SecretLocation::Keyring;
+                    None
+                }
+                Err(e) => Some(e),
+            }
+        } else {
+            None
+        };
+
+        if stored.location != SecretLocation::Keyring {
+            match (&self.passphrase, keyring_error) {
+                (Some(passphrase), _) => {
+                    stored.location = SecretLocation::EncryptedFile;
+                    stored.encrypted = Some(identity.export_backup(passphrase)?.to_armored()?);
+                }
+                (None, Some(e)) => {
+                    return Err(e.context("Keyring unavailable and no passphrase for fallback"))
+                }
+                (None, None) => stored.secret_key_hex = Some(secret_key_hex),
+            }
+        }
+
         let json = serde_json::to_string_pretty(&stored)?;
         std::fs::write(&path, json)
             .with_context(|| format!("Failed to write identity to {:?}", path))?;
 
-        Ok(())
+        Ok(stored.location)
     }
 
     /// Load an identity from disk
     pub fn load(&self, name: &str) -> Result<Identity> {
-        let path = self.identity_path(name);
-
-        let json = std::fs::read_to_string(&path)
-            .with_context(|| format!("Failed to read identity from {:?}", path))?;
-
-        let stored: StoredIdentity = serde_json::from_str(&json)?;
+        let stored = self.read_stored(name)?;
+
+        let secret_key_hex = match stored.location {
+            SecretLocation::Plaintext => stored
+                .secret_key_hex
+                .context("Identity file has no secret key")?,
+            SecretLocation::Keyring => keyring_load(name)?,
+            SecretLocation::EncryptedFile => {
+                let armored = stored
+                    .encrypted
+                    .context("Identity file has no encrypted key")?;
+                let backup = KeyBackup::decode(&armored).context("Invalid encrypted key")?;
+                let passphrase = self.passphrase.as_deref().with_context(|| {
+                    format!("Identity '{}' is encrypted; a passphrase is required", name)
+                })?;
+                let mut identity = Identity::import_backup(&backup, passphrase)?;
+                identity.nickname = stored.nickname;
+                return Ok(identity);
+            }
+        };
 
         // Decode secret key from hex
-        let secret_bytes = hex::decode(&stored.secret_key_hex)?;
+        let secret_bytes = hex::decode(&secret_key_hex)?;
         if secret_bytes.len() != 32 {
             anyhow::bail!("Invalid secret key length: expected 32 bytes");
         }
@@ -275,12 +292,43 @@ impl IdentityManager {
         let _signing_key = SigningKey::from_bytes(&secret_key);
         let keypair = Keypair::from_secret_key(&secret_key);
 
+        if let Some(expected) = &stored.public_key_z32 {
+            if keypair.public_key().to_string() != *expected {
+                anyhow::bail!("Public key mismatch for identity '{}'", name);
+            }
+        }
+
         let mut identity = Identity::from_keypair(keypair);
         identity.nickname = stored.nickname;
 
         Ok(identity)
     }
 
+    /// Load an identity's secret straight from the OS keyring
+    ///
+    /// Recovers keys whose identity file is missing.
+    pub fn load_from_keyring(&self, name: &str) -> Result<Identity> {
+        let secret_bytes = hex::decode(keyring_load(name)?)?;
+        let secret_key: [u8; 32] = secret_bytes
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("Invalid secret key length: expected 32 bytes"))?;
+        Ok(Identity::from_keypair(Keypair::from_secret_key(
+            &secret_key,
+        )))
+    }
+
+    /// Where an identity's secret key is stored
+    pub fn location(&self, name: &str) -> Result<SecretLocation> {
+        Ok(self.read_stored(name)?.location)
+    }
+
+    /// Public key recorded for an identity, without touching the secret
+    ///
+    /// `None` for files written before the public key was recorded.
+    pub fn public_key(&self, name: &str) -> Result<Option<String>> {
+        Ok(self.read_stored(name)?.public_key_z32)
+    }
+
     /// List all saved identities
     pub fn list(&self) -> Result<Vec<String>> {
         if !self.storage_dir.exists() {
@@ -308,6 +356,9 @@ impl IdentityManager {
 
     /// Delete an identity
     pub fn delete(&self, name: &str) -> Result<()> {
+        if let Ok(SecretLocation::Keyring) = self.location(name) {
+            keyring_delete(name)?;
+        }
         let path = self.identity_path(name);
         std::fs::remove_file(&path)
             .with_context(|| format!("Failed to delete identity {:?}", path))?;
@@ -324,11 +375,67 @@ impl IdentityManager {
         Ok(identity)
     }
 
+    fn read_stored(&self, name: &str) -> Result<StoredIdentity> {
+        let path = self.identity_path(name);
+        let json = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read identity from {:?}", path))?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
     fn identity_path(&self, name: &str) -> PathBuf {
         self.storage_dir.join(format!("{}.json", name))
     }
 }
 
+#[cfg(all(feature = "keyring", not(target_arch = "wasm32")))]
+fn keyring_entry(name: &str) -> Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, &format!("identity.{}", name))
+        .map_err(|e| anyhow::anyhow!("Keyring unavailable: {}", e))
+}
+
+#[cfg(all(feature = "keyring", not(target_arch = "wasm32")))]
+fn keyring_store(name: &str, secret_key_hex: &str) -> Result<()> {
+    let entry = keyring_entry(name)?;
+    entry
+        .set_password(secret_key_hex)
+        .map_err(|e| anyhow::anyhow!("Failed to store secret in keyring: {}", e))?;
+    // Some backends accept writes they cannot persist; read back to be sure
+    match entry.get_password() {
+        Ok(stored) if stored == secret_key_hex => Ok(()),
+        _ => anyhow::bail!("Keyring did not persist the secret"),
+    }
+}
+
+#[cfg(all(feature = "keyring", not(target_arch = "wasm32")))]
+fn keyring_load(name: &str) -> Result<String> {
+    keyring_entry(name)?
+        .get_password()
+        .map_err(|e| anyhow::anyhow!("Failed to read secret for '{}' from keyring: {}", name, e))
+}
+
+#[cfg(all(feature = "keyring", not(target_arch = "wasm32")))]
+fn keyring_delete(name: &str) -> Result<()> {
+    match keyring_entry(name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => anyhow::bail!("Failed to delete secret from keyring: {}", e),
+    }
+}
+
+#[cfg(not(all(feature = "keyring", not(target_arch = "wasm32"))))]
+fn keyring_store(_name: &str, _secret_key_hex: &str) -> Result<()> {
+    anyhow::bail!("Built without keyring support (enable the `keyring` feature)")
+}
+
+#[cfg(not(all(feature = "keyring", not(target_arch = "wasm32"))))]
+fn keyring_load(_name: &str) -> Result<String> {
+    anyhow::bail!("Built without keyring support (enable the `keyring` feature)")
+}
+
+#[cfg(not(all(feature = "keyring", not(target_arch = "wasm32"))))]
+fn keyring_delete(_name: &str) -> Result<()> {
+    Ok(())
+}
+
 /// Manages identity persistence using OS secure storage (keychain/credential manager)
 pub struct SecureIdentityManager {
     storage: paykit_lib::secure_storage::DesktopKeyStorage,
@@ -516,4 +623,40 @@ mod tests {
         let key3 = identity.derive_x25519_key(device_id, 1).unwrap();
         assert_ne!(key1, key3);
     }
+
+    #[test]
+    fn test_identity_manager_secret_locations() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let identity = Identity::generate().with_nickname("Alice");
+
+        // Legacy plaintext files without a location still load
+        let legacy = serde_json::json!({
+            "secret_key_hex": hex::encode(identity.keypair.secret_key()),
+            "nickname": "Alice",
+        });
+        std::fs::write(temp_dir.path().join("legacy.json"), legacy.to_string()).unwrap();
+        let plain = IdentityManager::new(temp_dir.path());
+        assert_eq!(plain.location("legacy").unwrap(), SecretLocation::Plaintext);
+        assert_eq!(
+            plain.load("legacy").unwrap().public_key(),
+            identity.public_key()
+        );
+
+        let encrypted = IdentityManager::new(temp_dir.path()).with_passphrase("hunter2");
+        assert_eq!(
+            encrypted.save(&identity, "alice").unwrap(),
+            SecretLocation::EncryptedFile
+        );
+        let json = std::fs::read_to_string(temp_dir.path().join("alice.json")).unwrap();
+        assert!(!json.contains(&hex::encode(identity.keypair.secret_key())));
+
+        let loaded = encrypted.load("alice").unwrap();
+        assert_eq!(loaded.public_key(), identity.public_key());
+        assert_eq!(loaded.nickname.as_deref(), Some("Alice"));
+        assert!(plain.load("alice").is_err());
+        assert!(IdentityManager::new(temp_dir.path())
+            .with_passphrase("wrong")
+            .load("alice")
+            .is_err());
+    }
 }
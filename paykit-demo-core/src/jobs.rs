@@ -0,0 +1,140 @@
+//! Runs persisted payment jobs (standing orders, scheduled payments)
+//!
+//! A run claims the job in its state file before paying and saves the
+//! job's new state right after, before the receipt is written. While a job
+//! is claimed, other runs skip it and edits are refused, so it is never
+//! paid twice and a change made during the run is not lost. A claim left
+//! behind by an interrupted run, or by a payment that failed without
+//! ruling out that it was sent, stays until it is released by hand, once
+//! the wallet shows whether the payment went out.
+
+use crate::state_file::StateFile;
+use crate::{DemoStorage, Receipt};
+use anyhow::{anyhow, Context, Result};
+use paykit_lib::methods::PaymentExecution;
+use paykit_lib::PublicKey;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::future::Future;
+
+/// A state file holding jobs and the claims of runs in progress
+pub(crate) trait JobState: Serialize + DeserializeOwned + Default {
+    type Job: Clone + PartialEq;
+
+    /// What a job is called in messages, e.g. "Standing order"
+    const KIND: &'static str;
+
+    fn jobs(&mut self) -> &mut Vec<Self::Job>;
+
+    /// Claimed job IDs and when they were claimed
+    fn claims(&mut self) -> &mut BTreeMap<String, i64>;
+
+    fn job_id(job: &Self::Job) -> &str;
+
+    fn is_due(job: &Self::Job, now: i64) -> bool;
+}
+
+/// The outcome of running a job
+pub(crate) trait JobOutcome {
+    /// Whether the payment may have been sent even though it failed
+    fn is_unresolved(&self) -> bool;
+}
+
+/// Fail if the job is claimed by a run in progress
+pub(crate) fn ensure_unclaimed<S: JobState>(state: &mut S, job_id: &str) -> Result<()> {
+    match state.claims().get(job_id) {
+        Some(claimed_at) => Err(anyhow!(
+            "{} {} is being paid by a run started at {}",
+            S::KIND,
+            job_id,
+            claimed_at
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Release the claim of an interrupted run, returning whether there was one
+pub(crate) fn release<S: JobState>(file: &StateFile, job_id: &str) -> Result<bool> {
+    file.update(|state: &mut S| Ok(state.claims().remove(job_id).is_some()))
+}
+
+/// Run one job and save its new state
+///
+/// `run` gets a copy of the job and returns it updated along with the
+/// outcome, which are both passed on. A due job is claimed first; if `run`
+/// fails the claim is released and the job is left as it was. An unresolved
+/// outcome keeps the claim.
+pub(crate) async fn run_job<S, O, F, Fut>(
+    file: &StateFile,
+    job_id: &str,
+    now: i64,
+    run: F,
+) -> Result<(S::Job, O)>
+where
+    S: JobState,
+    O: JobOutcome,
+    F: FnOnce(S::Job) -> Fut,
+    Fut: Future<Output = Result<(S::Job, O)>>,
+{
+    let (job, claimed) = file.update(|state: &mut S| {
+        ensure_unclaimed(state, job_id)?;
+        let job = state
+            .jobs()
+            .iter()
+            .find(|job| S::job_id(job) == job_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("{} not found: {}", S::KIND, job_id))?;
+        let claimed = S::is_due(&job, now);
+        if claimed {
+            state.claims().insert(job_id.to_string(), now);
+        }
+        Ok((job, claimed))
+    })?;
+
+    let result = run(job.clone()).await;
+
+    file.update(|state: &mut S| {
+        let unresolved = matches!(&result, Ok((_, outcome)) if outcome.is_unresolved());
+        if claimed && !unresolved {
+            state.claims().remove(job_id);
+        }
+        if let Ok((updated, _)) = &result {
+            if let Some(stored) = state.jobs().iter_mut().find(|j| S::job_id(j) == job_id) {
+                // An unclaimed job may have been edited meanwhile; keep the edit
+                if claimed || *stored == job {
+                    *stored = updated.clone();
+                }
+            }
+        }
+        Ok(())
+    })?;
+    result
+}
+
+/// Record a payment made by a job as an ordinary receipt
+pub(crate) fn save_receipt(
+    storage: &DemoStorage,
+    payer: &PublicKey,
+    payee: &str,
+    amount_sats: u64,
+    execution: &PaymentExecution,
+    metadata: serde_json::Value,
+    now: i64,
+) -> Result<()> {
+    let payee: PublicKey = payee
+        .parse()
+        .map_err(|e| anyhow!("Invalid payee public key: {}", e))?;
+    let mut receipt = Receipt::new(
+        uuid::Uuid::new_v4().to_string(),
+        payer.clone(),
+        payee,
+        execution.method_id.0.clone(),
+    )
+    .with_amount(amount_sats.to_string(), "SAT".to_string())
+    .with_metadata(metadata);
+    receipt.timestamp = now;
+    storage
+        .save_receipt(receipt)
+        .context("The payment was sent and saved, but its receipt could not be written")
+}
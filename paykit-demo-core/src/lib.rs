@@ -1,22 +1,62 @@
 //! Paykit Demo Core Library
 //!
 //! Shared business logic for all Paykit demo applications (CLI, Web, Desktop).
-//! This crate provides identity management, directory operations, payment flows,
-//! subscription management, and storage abstraction.
+//! This crate provides identity management, contact import, directory
+//! operations and mirroring, payment flows, payment links, subscription
+//! management, standing orders, scheduled payments, vouchers, wallet
+//! profiles, payee analytics, API keys, receipt timestamping and storage
+//! abstraction.
 
+pub mod analytics;
+pub mod api_keys;
+pub mod contacts;
 pub mod directory;
+pub mod encryption;
 pub mod identity;
+mod jobs;
+pub mod mirror;
 pub mod models;
 pub mod payment;
+pub mod payment_link;
+#[cfg(feature = "render")]
+pub mod render;
+pub mod scheduled_payment;
+pub mod standing_order;
+mod state_file;
 pub mod storage;
 pub mod subscription;
+pub mod timestamping;
+pub mod treasury;
+pub mod voucher;
+pub mod wallet_profile;
 
-pub use directory::DirectoryClient;
-pub use identity::{Identity, IdentityManager, KeyBackup, SecureIdentityManager};
+pub use analytics::{AggregateRow, AnalyticsCoordinator, AnalyticsReport, AnalyticsSettings};
+pub use api_keys::{ApiAuditEntry, ApiDenial, ApiKey, ApiKeyCoordinator, ApiRole};
+pub use contacts::{import_contacts, ImportFormat, ImportSummary};
+pub use directory::{
+    merge_methods, DirectoryClient, DirectoryConflict, DirectorySnapshot, DirectoryVersion,
+    MergedMethods,
+};
+pub use encryption::{AesGcmCipher, EncryptionConfig, KeySource, StorageCipher};
+pub use identity::{
+    Identity, IdentityManager, KdfParams, KeyBackup, SecretLocation, SecureIdentityManager,
+};
+pub use mirror::{DirectoryMirror, MirroredDirectory, MirroredEntry, SyncReport};
 pub use models::{Contact, PaymentMethod, Receipt};
 pub use payment::PaymentCoordinator;
-pub use storage::DemoStorage;
+pub use payment_link::{PaymentLinkCoordinator, StoredLink};
+#[cfg(feature = "render")]
+pub use render::{ReceiptBranding, ReceiptRenderer, ReceiptTemplate};
+pub use scheduled_payment::ScheduledPaymentCoordinator;
+pub use standing_order::StandingOrderCoordinator;
+pub use storage::{DemoStorage, StorageStatus};
 pub use subscription::{DemoPaymentRequest, DemoSubscription, SubscriptionCoordinator};
+pub use timestamping::{
+    PendingDigest, TimestampCoordinator, TimestampRecord, TimestampSettings, TimestampVerification,
+};
+pub use treasury::TreasuryCoordinator;
+pub use voucher::VoucherCoordinator;
+pub use wallet_profile::{WalletConfig, WalletProfiles, DEFAULT_PROFILE};
 
 /// Result type for demo operations
 pub type Result<T> = anyhow::Result<T>;
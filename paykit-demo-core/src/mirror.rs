@@ -0,0 +1,414 @@
+//! Read replica of other users' public Paykit directories
+//!
+//! [`DirectoryMirror`] keeps a local copy of everything a set of users
+//! publish under `/pub/paykit.app/v0`: endpoints, the Noise endpoint,
+//! status, hints and payment links. Each file is stored with the SHA-256 of
+//! its content, and each directory with a root hash over all of its files,
+//! so a replica can be checked against tampering or disk corruption with
+//! [`DirectoryMirror::verify`] and compared with another replica by root
+//! hash alone.
+//!
+//! Mirrors live in `<storage_dir>/mirror/<pubkey>.json`, one file per user,
+//! replaced by rename on every sync.
+
+use crate::directory::DirectoryClient;
+use anyhow::{anyhow, Context, Result};
+use paykit_lib::protocol::{PAYKIT_V0_PREFIX, PAYMENT_LINKS_SUBPATH};
+use paykit_lib::PublicKey;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// One mirrored file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MirroredEntry {
+    /// File content as published
+    pub content: String,
+    /// Hex SHA-256 of `content`
+    pub sha256: String,
+    /// When this content was first seen (Unix seconds)
+    pub fetched_at: i64,
+}
+
+impl MirroredEntry {
+    fn new(content: String, fetched_at: i64) -> Self {
+        Self {
+            sha256: content_hash(&content),
+            content,
+            fetched_at,
+        }
+    }
+
+    /// Whether `sha256` still matches `content`
+    pub fn is_intact(&self) -> bool {
+        content_hash(&self.content) == self.sha256
+    }
+}
+
+/// The mirrored public directory of one user
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MirroredDirectory {
+    /// z-base-32 public key of the user
+    pub public_key: String,
+    /// Last successful sync (Unix seconds)
+    pub synced_at: i64,
+    /// Files by absolute path, e.g. `/pub/paykit.app/v0/lightning`
+    #[serde(default)]
+    pub entries: BTreeMap<String, MirroredEntry>,
+}
+
+impl MirroredDirectory {
+    /// SHA-256 over the sorted paths and their content hashes
+    ///
+    /// Two replicas of a directory have the same root hash exactly when they
+    /// hold the same files.
+    pub fn root_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        for (path, entry) in &self.entries {
+            hasher.update(path.as_bytes());
+            hasher.update([0]);
+            hasher.update(entry.sha256.as_bytes());
+            hasher.update([0]);
+        }
+        hex::encode(hasher.finalize())
+    }
+
+    /// Paths whose stored hash no longer matches their content
+    pub fn corrupted(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter(|(_, entry)| !entry.is_intact())
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+}
+
+/// What one sync changed in a mirrored directory
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SyncReport {
+    /// z-base-32 public key of the user
+    pub public_key: String,
+    /// Files that were not mirrored before
+    pub added: usize,
+    /// Files whose content changed
+    pub changed: usize,
+    /// Files no longer published
+    pub removed: usize,
+    /// Files that were already up to date
+    pub unchanged: usize,
+    /// Root hash after the sync
+    pub root_hash: String,
+}
+
+impl SyncReport {
+    /// Whether the sync changed anything
+    pub fn has_changes(&self) -> bool {
+        self.added + self.changed + self.removed > 0
+    }
+}
+
+/// Local read replica of public Paykit directories
+pub struct DirectoryMirror {
+    mirror_dir: PathBuf,
+}
+
+impl DirectoryMirror {
+    /// Create a mirror for the given storage directory
+    pub fn new(storage_dir: impl AsRef<Path>) -> Self {
+        Self {
+            mirror_dir: storage_dir.as_ref().join("mirror"),
+        }
+    }
+
+    /// Public keys with a mirrored directory, sorted
+    pub fn users(&self) -> Result<Vec<String>> {
+        if !self.mirror_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut users = Vec::new();
+        for entry in std::fs::read_dir(&self.mirror_dir).context("Failed to read mirror")? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                    users.push(stem.to_string());
+                }
+            }
+        }
+        users.sort();
+        Ok(users)
+    }
+
+    /// The mirrored directory of a user
+    pub fn load(&self, public_key: &str) -> Result<Option<MirroredDirectory>> {
+        let path = self.user_path(public_key)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let json = std::fs::read_to_string(&path).context("Failed to read mirrored directory")?;
+        let directory =
+            serde_json::from_str(&json).context("Failed to parse mirrored directory")?;
+        Ok(Some(directory))
+    }
+
+    /// One mirrored file of a user
+    pub fn get(&self, public_key: &str, path: &str) -> Result<Option<MirroredEntry>> {
+        Ok(self
+            .load(public_key)?
+            .and_then(|mut directory| directory.entries.remove(path)))
+    }
+
+    /// Make the mirror of `public_key` exactly `files` (path to content)
+    ///
+    /// Unchanged files keep their `fetched_at`.
+    pub fn apply(
+        &self,
+        public_key: &str,
+        files: BTreeMap<String, String>,
+        now: i64,
+    ) -> Result<SyncReport> {
+        let previous = self.load(public_key)?.unwrap_or_default();
+        let mut report = SyncReport {
+            public_key: public_key.to_string(),
+            ..Default::default()
+        };
+
+        let mut entries = BTreeMap::new();
+        for (path, content) in files {
+            let entry = match previous.entries.get(&path) {
+                Some(old) if old.content == content && old.is_intact() => {
+                    report.unchanged += 1;
+                    old.clone()
+                }
+                Some(_) => {
+                    report.changed += 1;
+                    MirroredEntry::new(content, now)
+                }
+                None => {
+                    report.added += 1;
+                    MirroredEntry::new(content, now)
+                }
+            };
+            entries.insert(path, entry);
+        }
+        report.removed = previous
+            .entries
+            .keys()
+            .filter(|path| !entries.contains_key(*path))
+            .count();
+
+        let directory = MirroredDirectory {
+            public_key: public_key.to_string(),
+            synced_at: now,
+            entries,
+        };
+        report.root_hash = directory.root_hash();
+        self.save(&directory)?;
+        Ok(report)
+    }
+
+    /// Fetch a user's public Paykit files and mirror them
+    pub async fn sync(
+        &self,
+        client: &DirectoryClient,
+        public_key: &PublicKey,
+    ) -> Result<SyncReport> {
+        let files = fetch_public_files(client, public_key).await?;
+        self.apply(
+            &public_key.to_string(),
+            files,
+            chrono::Utc::now().timestamp(),
+        )
+    }
+
+    /// Paths of a user's mirror whose content fails its integrity hash
+    pub fn verify(&self, public_key: &str) -> Result<Vec<String>> {
+        let directory = self
+            .load(public_key)?
+            .ok_or_else(|| anyhow!("No mirror for {}", public_key))?;
+        Ok(directory.corrupted())
+    }
+
+    /// Stop mirroring a user, deleting the local copy
+    ///
+    /// Returns whether there was a mirror to delete.
+    pub fn remove(&self, public_key: &str) -> Result<bool> {
+        let path = self.user_path(public_key)?;
+        if !path.exists() {
+            return Ok(false);
+        }
+        std::fs::remove_file(&path).context("Failed to remove mirrored directory")?;
+        Ok(true)
+    }
+
+    fn user_path(&self, public_key: &str) -> Result<PathBuf> {
+        // Keys become file names, so only accept z-base-32
+        if public_key.is_empty() || !public_key.bytes().all(|b| b.is_ascii_alphanumeric()) {
+            return Err(anyhow!("Invalid public key: {}", public_key));
+        }
+        Ok(self.mirror_dir.join(format!("{}.json", public_key)))
+    }
+
+    fn save(&self, directory: &MirroredDirectory) -> Result<()> {
+        std::fs::create_dir_all(&self.mirror_dir)?;
+        let path = self.user_path(&directory.public_key)?;
+        let json = serde_json::to_string_pretty(directory)?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json).context("Failed to save mirrored directory")?;
+        std::fs::rename(&tmp, &path).context("Failed to save mirrored directory")
+    }
+}
+
+/// Every file a user publishes under the Paykit prefix, by absolute path
+///
+/// Reads the files directly under `/pub/paykit.app/v0/` (endpoints,
+/// `supported.json`, Noise endpoint, status and hints) and the payment
+/// links. Private areas such as requests and inboxes are not listed.
+pub async fn fetch_public_files(
+    client: &DirectoryClient,
+    public_key: &PublicKey,
+) -> Result<BTreeMap<String, String>> {
+    let mut files = BTreeMap::new();
+    for method in client.query_methods(public_key).await? {
+        files.insert(
+            format!("{}/{}", PAYKIT_V0_PREFIX, method.method_id),
+            method.endpoint,
+        );
+    }
+
+    let links_dir = format!("{}/{}", PAYKIT_V0_PREFIX, PAYMENT_LINKS_SUBPATH);
+    for name in client
+        .list_raw(public_key, &format!("{}/", links_dir))
+        .await?
+    {
+        let path = format!("{}/{}", links_dir, name);
+        if let Some(content) = client.get_raw(public_key, &path).await? {
+            files.insert(path, content);
+        }
+    }
+    Ok(files)
+}
+
+fn content_hash(content: &str) -> String {
+    hex::encode(Sha256::digest(content.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: &str = "8pinxxgqs41n4aididenw5apqp1urfmzdztr8jt4abrkdn435ewo";
+
+    fn files(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(path, content)| (path.to_string(), content.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_apply_tracks_changes() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mirror = DirectoryMirror::new(temp_dir.path());
+
+        let report = mirror
+            .apply(
+                ALICE,
+                files(&[
+                    ("/pub/paykit.app/v0/lightning", "lnurl1"),
+                    ("/pub/paykit.app/v0/onchain", "bc1q"),
+                ]),
+                100,
+            )
+            .unwrap();
+        assert_eq!(report.added, 2);
+        assert_eq!(mirror.users().unwrap(), vec![ALICE.to_string()]);
+
+        let report = mirror
+            .apply(
+                ALICE,
+                files(&[
+                    ("/pub/paykit.app/v0/lightning", "lnurl2"),
+                    ("/pub/paykit.app/v0/links/coffee", "{}"),
+                ]),
+                200,
+            )
+            .unwrap();
+        assert_eq!(
+            (
+                report.added,
+                report.changed,
+                report.removed,
+                report.unchanged
+            ),
+            (1, 1, 1, 0)
+        );
+
+        let entry = mirror
+            .get(ALICE, "/pub/paykit.app/v0/lightning")
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.content, "lnurl2");
+        assert_eq!(entry.fetched_at, 200);
+
+        // Same content again: nothing changes, including the root hash
+        let again = mirror
+            .apply(
+                ALICE,
+                files(&[
+                    ("/pub/paykit.app/v0/lightning", "lnurl2"),
+                    ("/pub/paykit.app/v0/links/coffee", "{}"),
+                ]),
+                300,
+            )
+            .unwrap();
+        assert!(!again.has_changes());
+        assert_eq!(again.root_hash, report.root_hash);
+    }
+
+    #[test]
+    fn test_verify_detects_tampering() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mirror = DirectoryMirror::new(temp_dir.path());
+        mirror
+            .apply(
+                ALICE,
+                files(&[("/pub/paykit.app/v0/lightning", "lnurl1")]),
+                100,
+            )
+            .unwrap();
+        assert!(mirror.verify(ALICE).unwrap().is_empty());
+
+        let path = temp_dir
+            .path()
+            .join("mirror")
+            .join(format!("{}.json", ALICE));
+        let json = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, json.replace("lnurl1", "lnurl-evil")).unwrap();
+        assert_eq!(
+            mirror.verify(ALICE).unwrap(),
+            vec!["/pub/paykit.app/v0/lightning".to_string()]
+        );
+
+        // The next sync replaces the tampered copy
+        let report = mirror
+            .apply(
+                ALICE,
+                files(&[("/pub/paykit.app/v0/lightning", "lnurl-evil")]),
+                200,
+            )
+            .unwrap();
+        assert_eq!(report.changed, 1);
+        assert!(mirror.verify(ALICE).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rejects_path_like_keys() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mirror = DirectoryMirror::new(temp_dir.path());
+        assert!(mirror.load("../secret").is_err());
+        assert!(!mirror.remove(ALICE).unwrap());
+    }
+}
@@ -14,6 +14,9 @@ pub struct Contact {
     pub notes: Option<String>,
     /// Timestamp when added
     pub added_at: i64,
+    /// Timestamp when the key was verified out of band
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verified_at: Option<i64>,
 }
 
 impl Contact {
@@ -23,6 +26,7 @@ impl Contact {
             name,
             notes: None,
             added_at: current_timestamp(),
+            verified_at: None,
         }
     }
 
@@ -34,6 +38,10 @@ impl Contact {
     pub fn pubky_uri(&self) -> String {
         format!("pubky://{}", self.public_key)
     }
+
+    pub fn is_verified(&self) -> bool {
+        self.verified_at.is_some()
+    }
 }
 
 /// A payment method (onchain, lightning, etc.)
@@ -128,6 +136,80 @@ impl Receipt {
         self.proof_verified_at = Some(current_timestamp());
         self
     }
+
+    /// Attach the payer's note (sanitized, stored in the metadata)
+    pub fn with_memo(mut self, memo: &str) -> Self {
+        paykit_interactive::metadata::set_memo_in_metadata(&mut self.metadata, Some(memo));
+        self
+    }
+
+    /// The payer's note, if any
+    pub fn memo(&self) -> Option<String> {
+        paykit_interactive::metadata::memo_from_metadata(&self.metadata)
+    }
+
+    /// Hex SHA-256 of the receipt's canonical JSON
+    ///
+    /// The same digest [`PaykitReceipt::digest`](paykit_interactive::PaykitReceipt::digest)
+    /// gives for the receipt the payee confirmed.
+    pub fn digest(&self) -> String {
+        let mut receipt = paykit_interactive::PaykitReceipt::new(
+            self.id.clone(),
+            self.payer.clone(),
+            self.payee.clone(),
+            paykit_lib::MethodId(self.method.clone()),
+            self.amount.clone(),
+            self.currency.clone(),
+            self.metadata.clone(),
+        );
+        receipt.created_at = self.timestamp;
+        receipt.digest()
+    }
+}
+
+impl paykit_interactive::SearchableReceipt for Receipt {
+    fn receipt_id(&self) -> &str {
+        &self.id
+    }
+
+    fn payer(&self) -> String {
+        self.payer.to_string()
+    }
+
+    fn payee(&self) -> String {
+        self.payee.to_string()
+    }
+
+    fn method_id(&self) -> &str {
+        &self.method
+    }
+
+    fn amount(&self) -> Option<&str> {
+        self.amount.as_deref()
+    }
+
+    fn created_at(&self) -> Option<i64> {
+        Some(self.timestamp)
+    }
+
+    fn search_text(&self) -> String {
+        format!(
+            "{} {} {}",
+            self.id,
+            self.currency.as_deref().unwrap_or_default(),
+            self.metadata
+        )
+    }
+}
+
+impl paykit_interactive::ExportableReceipt for Receipt {
+    fn currency(&self) -> Option<&str> {
+        self.currency.as_deref()
+    }
+
+    fn metadata(&self) -> std::borrow::Cow<'_, serde_json::Value> {
+        std::borrow::Cow::Borrowed(&self.metadata)
+    }
 }
 
 /// Get current Unix timestamp
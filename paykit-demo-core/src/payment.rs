@@ -2,10 +2,15 @@
 
 use crate::models::Receipt;
 use anyhow::{Context, Result};
+use paykit_interactive::metadata::set_memo_in_metadata;
 use paykit_interactive::{
     PaykitInteractiveManager, PaykitNoiseChannel, PaykitNoiseMessage, PaykitReceipt, PaykitStorage,
     ReceiptGenerator,
 };
+use paykit_lib::policy::{
+    ScreeningDirection, ScreeningRequest, ScreeningResult, SharedComplianceScreener,
+    SharedTrustPolicy, TrustDecision,
+};
 use paykit_lib::{MethodId, PublicKey};
 use std::sync::Arc;
 
@@ -13,6 +18,8 @@ use std::sync::Arc;
 pub struct PaymentCoordinator {
     storage: Arc<Box<dyn PaykitStorage>>,
     receipt_generator: Arc<Box<dyn ReceiptGenerator>>,
+    trust_policy: Option<SharedTrustPolicy>,
+    compliance_screener: Option<SharedComplianceScreener>,
 }
 
 impl PaymentCoordinator {
@@ -24,10 +31,68 @@ impl PaymentCoordinator {
         Self {
             storage,
             receipt_generator,
+            trust_policy: None,
+            compliance_screener: None,
+        }
+    }
+
+    /// Enforce a payee trust policy on outgoing payments
+    pub fn with_trust_policy(mut self, policy: SharedTrustPolicy) -> Self {
+        self.trust_policy = Some(policy);
+        self
+    }
+
+    /// Screen counterparties before every payment
+    ///
+    /// Blocked payments are refused; the result of every screening is
+    /// recorded in the receipt metadata.
+    pub fn with_compliance_screener(mut self, screener: SharedComplianceScreener) -> Self {
+        self.compliance_screener = Some(screener);
+        self
+    }
+
+    /// Screen a payment, if a screener is configured
+    async fn screen(&self, request: ScreeningRequest) -> Result<Option<ScreeningResult>> {
+        let Some(screener) = &self.compliance_screener else {
+            return Ok(None);
+        };
+        let result = screener
+            .screen(&request)
+            .await
+            .context("Compliance screening failed")?;
+        Ok(Some(result))
+    }
+
+    /// Evaluate a payee against the trust policy
+    ///
+    /// UIs should ask the user to confirm when this returns
+    /// [`TrustDecision::RequireConfirmation`] before calling `initiate_payment`.
+    pub fn trust_decision(&self, payee: &PublicKey) -> Result<TrustDecision> {
+        match &self.trust_policy {
+            Some(policy) => {
+                let policy = policy
+                    .read()
+                    .map_err(|_| anyhow::anyhow!("Trust policy lock poisoned"))?;
+                Ok(policy.evaluate(payee))
+            }
+            None => Ok(TrustDecision::Allow),
+        }
+    }
+
+    fn manager(&self) -> PaykitInteractiveManager {
+        let manager =
+            PaykitInteractiveManager::new(self.storage.clone(), self.receipt_generator.clone());
+        match &self.trust_policy {
+            Some(policy) => manager.with_trust_policy(policy.clone()),
+            None => manager,
         }
     }
 
     /// Initiate a payment as the payer
+    ///
+    /// `memo` is an optional note for the payee; it is sanitized and sent in
+    /// the receipt metadata.
+    #[allow(clippy::too_many_arguments)]
     pub async fn initiate_payment(
         &self,
         mut channel: impl PaykitNoiseChannel,
@@ -36,12 +101,33 @@ impl PaymentCoordinator {
         method: String,
         amount: Option<String>,
         currency: Option<String>,
+        memo: Option<String>,
     ) -> Result<Receipt> {
-        let manager =
-            PaykitInteractiveManager::new(self.storage.clone(), self.receipt_generator.clone());
+        let manager = self.manager();
 
         let receipt_id = format!("receipt_{}", uuid::Uuid::new_v4());
 
+        let mut metadata = serde_json::json!({});
+        set_memo_in_metadata(&mut metadata, memo.as_deref());
+        let screened_at = chrono::Utc::now().timestamp();
+        let screening = self
+            .screen(ScreeningRequest {
+                direction: ScreeningDirection::Outgoing,
+                payer: payer.to_string(),
+                payee: payee.to_string(),
+                method_id: method.clone(),
+                amount: amount.clone(),
+                currency: currency.clone(),
+                metadata: metadata.clone(),
+            })
+            .await?;
+        if let Some(result) = &screening {
+            if let ScreeningResult::Block { reason } = result {
+                anyhow::bail!("Payment blocked by compliance screening: {}", reason);
+            }
+            result.record_in(&mut metadata, screened_at);
+        }
+
         let provisional_receipt = PaykitReceipt::new(
             receipt_id.clone(),
             payer.clone(),
@@ -49,14 +135,19 @@ impl PaymentCoordinator {
             MethodId(method.clone()),
             amount.clone(),
             currency.clone(),
-            serde_json::json!({}),
+            metadata,
         );
 
-        let final_receipt = manager
+        let mut final_receipt = manager
             .initiate_payment(&mut channel, provisional_receipt)
             .await
             .context("Failed to initiate payment")?;
 
+        // Keep our own screening result; the payee may have recorded theirs
+        if let Some(result) = &screening {
+            result.record_in(&mut final_receipt.metadata, screened_at);
+        }
+
         Ok(Receipt {
             id: final_receipt.receipt_id,
             payer: final_receipt.payer,
@@ -79,15 +170,48 @@ impl PaymentCoordinator {
         payer: PublicKey,
         payee: PublicKey,
     ) -> Result<Option<Receipt>> {
-        let manager =
-            PaykitInteractiveManager::new(self.storage.clone(), self.receipt_generator.clone());
+        let manager = self.manager();
 
         // Receive the request
-        let msg = channel
+        let mut msg = channel
             .recv()
             .await
             .context("Failed to receive payment request")?;
 
+        // Screen the payer before issuing a receipt
+        if let PaykitNoiseMessage::RequestReceipt {
+            provisional_receipt,
+        } = &mut msg
+        {
+            let screening = self
+                .screen(ScreeningRequest {
+                    direction: ScreeningDirection::Incoming,
+                    payer: provisional_receipt.payer.to_string(),
+                    payee: provisional_receipt.payee.to_string(),
+                    method_id: provisional_receipt.method_id.0.clone(),
+                    amount: provisional_receipt.amount.clone(),
+                    currency: provisional_receipt.currency.clone(),
+                    metadata: provisional_receipt.metadata.clone(),
+                })
+                .await?;
+            if let Some(result) = screening {
+                if let ScreeningResult::Block { reason } = &result {
+                    channel
+                        .send(PaykitNoiseMessage::Error {
+                            code: "COMPLIANCE_BLOCKED".to_string(),
+                            message: reason.clone(),
+                        })
+                        .await
+                        .context("Failed to send response")?;
+                    return Ok(None);
+                }
+                result.record_in(
+                    &mut provisional_receipt.metadata,
+                    chrono::Utc::now().timestamp(),
+                );
+            }
+        }
+
         // Handle it
         let response = manager
             .handle_message(msg, &payer, &payee)
@@ -188,6 +312,11 @@ impl PaykitStorage for DemoPaykitStorage {
         Ok(receipts.get(receipt_id).cloned())
     }
 
+    async fn delete_receipt(&self, receipt_id: &str) -> paykit_interactive::Result<()> {
+        self.receipts.lock().await.remove(receipt_id);
+        Ok(())
+    }
+
     async fn save_private_endpoint(
         &self,
         peer: &PublicKey,
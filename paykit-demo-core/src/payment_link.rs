@@ -0,0 +1,263 @@
+//! Payment links for demo wallets
+//!
+//! The payee's copy of each [`PaymentLink`] lives next to the other demo
+//! data and is authoritative: payers see the published copy, but only
+//! [`PaymentLinkCoordinator::consume`] decides whether a payment is
+//! accepted. The check, the use count and the save happen under one lock,
+//! and the state file is replaced by rename, so two payments can never both
+//! take the last use of a link served by the same coordinator.
+
+use anyhow::{anyhow, Context, Result};
+use paykit_lib::protocol::{LinkState, PaymentLink};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// A link and what the payee knows about it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredLink {
+    /// The link as published
+    pub link: PaymentLink,
+    /// Receipts that paid the link, oldest first
+    #[serde(default)]
+    pub receipts: Vec<String>,
+    /// Whether the directory has the current state of the link
+    #[serde(default)]
+    pub published: bool,
+}
+
+/// Persisted payment links
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PaymentLinkState {
+    /// Links in creation order
+    #[serde(default)]
+    pub links: Vec<StoredLink>,
+}
+
+/// Coordinates payment links for a demo storage directory
+pub struct PaymentLinkCoordinator {
+    storage_dir: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl PaymentLinkCoordinator {
+    /// Create a coordinator for the given storage directory
+    pub fn new(storage_dir: impl AsRef<Path>) -> Self {
+        Self {
+            storage_dir: storage_dir.as_ref().to_path_buf(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// All links, in creation order
+    pub fn list(&self) -> Result<Vec<StoredLink>> {
+        Ok(self.load()?.links)
+    }
+
+    /// Get a link by ID
+    pub fn get(&self, link_id: &str) -> Result<Option<StoredLink>> {
+        Ok(self
+            .load()?
+            .links
+            .into_iter()
+            .find(|stored| stored.link.link_id == link_id))
+    }
+
+    /// Add a new, not yet published link
+    pub fn create(&self, link: PaymentLink) -> Result<()> {
+        link.validate()?;
+        self.update(|state| {
+            if state.links.iter().any(|s| s.link.link_id == link.link_id) {
+                return Err(anyhow!("Payment link {} already exists", link.link_id));
+            }
+            state.links.push(StoredLink {
+                link,
+                receipts: Vec::new(),
+                published: false,
+            });
+            Ok(())
+        })
+    }
+
+    /// Fail unless `link_id` can be paid `amount_sats` at `now`
+    ///
+    /// Use before doing work for a payment; [`consume`](Self::consume)
+    /// checks again.
+    pub fn check(&self, link_id: &str, amount_sats: Option<u64>, now: i64) -> Result<()> {
+        let stored = self
+            .get(link_id)?
+            .ok_or_else(|| anyhow!("Payment link not found: {}", link_id))?;
+        check_amount(&stored.link, amount_sats)?;
+        stored.link.check_payable(now)?;
+        Ok(())
+    }
+
+    /// Record `receipt_id` as a payment of `link_id`
+    ///
+    /// Paying with a receipt that was already recorded succeeds without
+    /// using the link again, so a retransmitted request is confirmed twice
+    /// rather than refused.
+    pub fn consume(
+        &self,
+        link_id: &str,
+        receipt_id: &str,
+        amount_sats: Option<u64>,
+        now: i64,
+    ) -> Result<LinkState> {
+        self.update(|state| {
+            let stored = state
+                .links
+                .iter_mut()
+                .find(|s| s.link.link_id == link_id)
+                .ok_or_else(|| anyhow!("Payment link not found: {}", link_id))?;
+            if stored.receipts.iter().any(|r| r == receipt_id) {
+                return Ok(stored.link.state(now));
+            }
+            check_amount(&stored.link, amount_sats)?;
+            let link_state = stored.link.consume(now)?;
+            stored.receipts.push(receipt_id.to_string());
+            stored.published = false;
+            Ok(link_state)
+        })
+    }
+
+    /// Revoke a link, returning whether it existed
+    pub fn revoke(&self, link_id: &str) -> Result<bool> {
+        self.update(|state| {
+            let Some(stored) = state.links.iter_mut().find(|s| s.link.link_id == link_id) else {
+                return Ok(false);
+            };
+            stored.link.revoked = true;
+            stored.published = false;
+            Ok(true)
+        })
+    }
+
+    /// Links whose current state has not been published
+    pub fn unpublished(&self) -> Result<Vec<PaymentLink>> {
+        Ok(self
+            .load()?
+            .links
+            .into_iter()
+            .filter(|stored| !stored.published)
+            .map(|stored| stored.link)
+            .collect())
+    }
+
+    /// Note that the directory has `link` as it is now
+    ///
+    /// Does nothing if the link changed since it was read.
+    pub fn mark_published(&self, link: &PaymentLink) -> Result<()> {
+        self.update(|state| {
+            if let Some(stored) = state.links.iter_mut().find(|s| s.link == *link) {
+                stored.published = true;
+            }
+            Ok(())
+        })
+    }
+
+    fn update<T>(&self, f: impl FnOnce(&mut PaymentLinkState) -> Result<T>) -> Result<T> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut state = self.load()?;
+        let result = f(&mut state)?;
+        self.save(&state)?;
+        Ok(result)
+    }
+
+    fn state_path(&self) -> PathBuf {
+        self.storage_dir.join("payment_links.json")
+    }
+
+    fn load(&self) -> Result<PaymentLinkState> {
+        let path = self.state_path();
+        if !path.exists() {
+            return Ok(PaymentLinkState::default());
+        }
+
+        let json = std::fs::read_to_string(&path).context("Failed to read payment links")?;
+        serde_json::from_str(&json).context("Failed to parse payment links")
+    }
+
+    fn save(&self, state: &PaymentLinkState) -> Result<()> {
+        std::fs::create_dir_all(&self.storage_dir)?;
+        let json = serde_json::to_string_pretty(state)?;
+        let tmp = self.state_path().with_extension("json.tmp");
+        std::fs::write(&tmp, json).context("Failed to save payment links")?;
+        std::fs::rename(&tmp, self.state_path()).context("Failed to save payment links")
+    }
+}
+
+fn check_amount(link: &PaymentLink, amount_sats: Option<u64>) -> Result<()> {
+    match (link.amount_sats, amount_sats) {
+        (Some(expected), Some(paid)) if paid != expected => Err(anyhow!(
+            "Payment link {} is for {} sats, not {}",
+            link.link_id,
+            expected,
+            paid
+        )),
+        (Some(expected), None) => Err(anyhow!(
+            "Payment link {} is for {} sats",
+            link.link_id,
+            expected
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paykit_lib::protocol::LinkUsage;
+
+    #[test]
+    fn test_single_use_link_consumed_once() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let links = PaymentLinkCoordinator::new(temp_dir.path());
+        let link = PaymentLink::new("coffee", 1_000)
+            .with_amount(5_000)
+            .with_ttl(600);
+        links.create(link.clone()).unwrap();
+        assert!(links.create(link).is_err());
+
+        assert!(links.check("coffee", Some(4_000), 1_100).is_err());
+        assert!(links.check("coffee", Some(5_000), 1_700).is_err());
+        links.check("coffee", Some(5_000), 1_100).unwrap();
+
+        let state = links.consume("coffee", "r1", Some(5_000), 1_100).unwrap();
+        assert_eq!(state, LinkState::Consumed);
+        // The same receipt again is confirmed, another one is refused
+        links.consume("coffee", "r1", Some(5_000), 1_101).unwrap();
+        assert!(links.consume("coffee", "r2", Some(5_000), 1_102).is_err());
+
+        let stored = links.get("coffee").unwrap().unwrap();
+        assert_eq!(stored.receipts, vec!["r1"]);
+        assert_eq!(stored.link.uses, 1);
+    }
+
+    #[test]
+    fn test_publish_tracking() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let links = PaymentLinkCoordinator::new(temp_dir.path());
+        links
+            .create(PaymentLink::new("tips", 0).with_usage(LinkUsage::Multi { max_uses: None }))
+            .unwrap();
+
+        let pending = links.unpublished().unwrap();
+        assert_eq!(pending.len(), 1);
+        links.mark_published(&pending[0]).unwrap();
+        assert!(links.unpublished().unwrap().is_empty());
+
+        links.consume("tips", "r1", None, 10).unwrap();
+        assert_eq!(links.unpublished().unwrap()[0].uses, 1);
+        // A stale copy does not mark the new state published
+        links.mark_published(&pending[0]).unwrap();
+        assert_eq!(links.unpublished().unwrap().len(), 1);
+
+        assert!(links.revoke("tips").unwrap());
+        assert!(!links.revoke("missing").unwrap());
+        assert_eq!(
+            links.get("tips").unwrap().unwrap().link.state(10),
+            LinkState::Revoked
+        );
+    }
+}
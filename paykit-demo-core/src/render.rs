@@ -0,0 +1,414 @@
+//! Receipt rendering for end users
+//!
+//! Turns a stored [`Receipt`] into a standalone HTML document, and with the
+//! `pdf` feature on native targets, a single-page PDF. Merchants brand the
+//! output with [`ReceiptBranding`] and can replace whole sections by
+//! implementing [`ReceiptTemplate`].
+
+use crate::models::Receipt;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Merchant branding applied to rendered receipts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReceiptBranding {
+    /// Name shown in the receipt header
+    pub merchant_name: Option<String>,
+    /// Logo image URL for the HTML header
+    pub logo_url: Option<String>,
+    /// Accent color as a CSS color value
+    pub accent_color: String,
+    /// Text shown at the bottom of the receipt
+    pub footer_text: Option<String>,
+    /// Extra CSS appended to the default stylesheet
+    pub custom_css: Option<String>,
+}
+
+impl Default for ReceiptBranding {
+    fn default() -> Self {
+        Self {
+            merchant_name: None,
+            logo_url: None,
+            accent_color: "#f7931a".to_string(),
+            footer_text: None,
+            custom_css: None,
+        }
+    }
+}
+
+/// Receipt fields prepared for display
+#[derive(Debug, Clone)]
+pub struct ReceiptView {
+    pub id: String,
+    pub date: String,
+    pub amount: Option<String>,
+    pub method: String,
+    pub payer: String,
+    pub payee: String,
+    pub proof_status: String,
+    /// Top-level metadata entries as (key, value) pairs
+    pub details: Vec<(String, String)>,
+}
+
+impl ReceiptView {
+    pub fn from_receipt(receipt: &Receipt) -> Self {
+        let amount = receipt
+            .amount
+            .as_ref()
+            .map(|amount| match &receipt.currency {
+                Some(currency) => format!("{} {}", amount, currency),
+                None => amount.clone(),
+            });
+
+        let proof_status = if receipt.proof.is_none() {
+            "No proof"
+        } else if receipt.proof_verified {
+            "Verified"
+        } else {
+            "Unverified"
+        };
+
+        let details = receipt
+            .metadata
+            .as_object()
+            .map(|map| {
+                map.iter()
+                    .map(|(key, value)| {
+                        let value = match value {
+                            serde_json::Value::String(s) => s.clone(),
+                            other => other.to_string(),
+                        };
+                        (key.clone(), value)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            id: receipt.id.clone(),
+            date: format_timestamp(receipt.timestamp),
+            amount,
+            method: receipt.method.clone(),
+            payer: receipt.payer.to_string(),
+            payee: receipt.payee.to_string(),
+            proof_status: proof_status.to_string(),
+            details,
+        }
+    }
+
+    /// Label/value rows in display order
+    pub fn rows(&self) -> Vec<(&str, &str)> {
+        let mut rows = vec![
+            ("Receipt ID", self.id.as_str()),
+            ("Date", self.date.as_str()),
+        ];
+        if let Some(amount) = &self.amount {
+            rows.push(("Amount", amount.as_str()));
+        }
+        rows.push(("Method", self.method.as_str()));
+        rows.push(("From", self.payer.as_str()));
+        rows.push(("To", self.payee.as_str()));
+        rows.push(("Proof", self.proof_status.as_str()));
+        rows
+    }
+}
+
+/// Hooks for customizing rendered HTML
+///
+/// Every method has a default, so templates only override the sections they
+/// change. Returned strings are inserted verbatim; escape user data with
+/// [`escape_html`].
+pub trait ReceiptTemplate: Send + Sync {
+    /// Stylesheet for the document
+    fn styles(&self, branding: &ReceiptBranding) -> String {
+        let mut css = format!(
+            "body{{font-family:-apple-system,Helvetica,Arial,sans-serif;color:#222;margin:0;padding:32px;}}\
+             .receipt{{max-width:640px;margin:0 auto;border:1px solid #ddd;border-radius:8px;overflow:hidden;}}\
+             header{{background:{accent};color:#fff;padding:20px 24px;}}\
+             header img{{max-height:40px;display:block;margin-bottom:8px;}}\
+             h1{{margin:0;font-size:22px;}}\
+             table{{width:100%;border-collapse:collapse;}}\
+             th,td{{text-align:left;padding:8px 24px;border-bottom:1px solid #eee;vertical-align:top;}}\
+             th{{width:30%;color:#666;font-weight:normal;}}\
+             td{{word-break:break-all;}}\
+             .amount td{{font-size:20px;font-weight:bold;color:{accent};}}\
+             footer{{padding:16px 24px;color:#888;font-size:12px;}}",
+            accent = escape_html(&branding.accent_color)
+        );
+        if let Some(custom) = &branding.custom_css {
+            css.push_str(custom);
+        }
+        css
+    }
+
+    /// Header section
+    fn header(&self, _view: &ReceiptView, branding: &ReceiptBranding) -> String {
+        let logo = branding
+            .logo_url
+            .as_ref()
+            .map(|url| format!("<img src=\"{}\" alt=\"\">", escape_html(url)))
+            .unwrap_or_default();
+        let title = branding
+            .merchant_name
+            .as_deref()
+            .map(escape_html)
+            .unwrap_or_else(|| "Payment Receipt".to_string());
+        format!("<header>{}<h1>{}</h1></header>", logo, title)
+    }
+
+    /// Receipt details section
+    fn body(&self, view: &ReceiptView, _branding: &ReceiptBranding) -> String {
+        let mut html = String::from("<table>");
+        for (label, value) in view.rows() {
+            let class = if label == "Amount" {
+                " class=\"amount\""
+            } else {
+                ""
+            };
+            html.push_str(&format!(
+                "<tr{}><th>{}</th><td>{}</td></tr>",
+                class,
+                escape_html(label),
+                escape_html(value)
+            ));
+        }
+        for (key, value) in &view.details {
+            html.push_str(&format!(
+                "<tr><th>{}</th><td>{}</td></tr>",
+                escape_html(key),
+                escape_html(value)
+            ));
+        }
+        html.push_str("</table>");
+        html
+    }
+
+    /// Footer section
+    fn footer(&self, _view: &ReceiptView, branding: &ReceiptBranding) -> String {
+        let text = branding
+            .footer_text
+            .as_deref()
+            .unwrap_or("Paid with Paykit");
+        format!("<footer>{}</footer>", escape_html(text))
+    }
+}
+
+/// The built-in receipt layout
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultTemplate;
+
+impl ReceiptTemplate for DefaultTemplate {}
+
+/// Renders receipts with branding and an optional custom template
+pub struct ReceiptRenderer {
+    branding: ReceiptBranding,
+    template: Box<dyn ReceiptTemplate>,
+}
+
+impl Default for ReceiptRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReceiptRenderer {
+    pub fn new() -> Self {
+        Self {
+            branding: ReceiptBranding::default(),
+            template: Box::new(DefaultTemplate),
+        }
+    }
+
+    pub fn with_branding(mut self, branding: ReceiptBranding) -> Self {
+        self.branding = branding;
+        self
+    }
+
+    pub fn with_template(mut self, template: impl ReceiptTemplate + 'static) -> Self {
+        self.template = Box::new(template);
+        self
+    }
+
+    /// Render a receipt as a standalone HTML document
+    pub fn render_html(&self, receipt: &Receipt) -> String {
+        let view = ReceiptView::from_receipt(receipt);
+        format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+             <title>Receipt {}</title>\n<style>{}</style>\n</head>\n<body>\n\
+             <div class=\"receipt\">{}{}{}</div>\n</body>\n</html>\n",
+            escape_html(&view.id),
+            self.template.styles(&self.branding),
+            self.template.header(&view, &self.branding),
+            self.template.body(&view, &self.branding),
+            self.template.footer(&view, &self.branding),
+        )
+    }
+
+    /// Render a receipt as a single-page A4 PDF
+    ///
+    /// Uses the built-in Helvetica font, so the layout follows the branding
+    /// text but not the HTML template or CSS.
+    #[cfg(all(feature = "pdf", not(target_arch = "wasm32")))]
+    pub fn render_pdf(&self, receipt: &Receipt) -> Result<Vec<u8>> {
+        use printpdf::{BuiltinFont, Mm, PdfDocument};
+
+        let view = ReceiptView::from_receipt(receipt);
+        let (doc, page, layer) = PdfDocument::new(
+            format!("Receipt {}", view.id),
+            Mm(210.0),
+            Mm(297.0),
+            "Receipt",
+        );
+        let regular = doc
+            .add_builtin_font(BuiltinFont::Helvetica)
+            .map_err(|e| anyhow::anyhow!("Failed to load font: {}", e))?;
+        let bold = doc
+            .add_builtin_font(BuiltinFont::HelveticaBold)
+            .map_err(|e| anyhow::anyhow!("Failed to load font: {}", e))?;
+        let layer = doc.get_page(page).get_layer(layer);
+
+        let title = self
+            .branding
+            .merchant_name
+            .as_deref()
+            .unwrap_or("Payment Receipt");
+        layer.use_text(title, 20.0, Mm(20.0), Mm(270.0), &bold);
+
+        let mut y = 250.0;
+        let details = view
+            .details
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()));
+        for (label, value) in view.rows().into_iter().chain(details) {
+            layer.use_text(label, 10.0, Mm(20.0), Mm(y), &bold);
+            // Public keys and long IDs are wrapped to fit the page width
+            for line in wrap(value, 64) {
+                layer.use_text(line, 10.0, Mm(60.0), Mm(y), &regular);
+                y -= 6.0;
+            }
+            y -= 3.0;
+            if y < 30.0 {
+                break;
+            }
+        }
+
+        let footer = self
+            .branding
+            .footer_text
+            .as_deref()
+            .unwrap_or("Paid with Paykit");
+        layer.use_text(footer, 8.0, Mm(20.0), Mm(15.0), &regular);
+
+        doc.save_to_bytes()
+            .map_err(|e| anyhow::anyhow!("Failed to write PDF: {}", e))
+    }
+
+    /// Render a receipt as PDF (unavailable in this build)
+    #[cfg(not(all(feature = "pdf", not(target_arch = "wasm32"))))]
+    pub fn render_pdf(&self, _receipt: &Receipt) -> Result<Vec<u8>> {
+        anyhow::bail!("PDF rendering requires the `pdf` feature on a native target")
+    }
+}
+
+/// Whether this build can render PDFs
+pub fn pdf_supported() -> bool {
+    cfg!(all(feature = "pdf", not(target_arch = "wasm32")))
+}
+
+/// Escape text for inclusion in HTML
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
+
+fn format_timestamp(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_else(|| "Unknown".to_string())
+}
+
+#[cfg(all(feature = "pdf", not(target_arch = "wasm32")))]
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.is_empty() {
+        return vec![String::new()];
+    }
+    chars
+        .chunks(width)
+        .map(|chunk| chunk.iter().collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receipt() -> Receipt {
+        let payer = pubky::Keypair::random().public_key();
+        let payee = pubky::Keypair::random().public_key();
+        Receipt::new(
+            "rcpt_001".to_string(),
+            payer,
+            payee,
+            "lightning".to_string(),
+        )
+        .with_amount("2500".to_string(), "SAT".to_string())
+        .with_metadata(serde_json::json!({ "order": "<script>alert(1)</script>" }))
+    }
+
+    struct PlainFooter;
+
+    impl ReceiptTemplate for PlainFooter {
+        fn footer(&self, view: &ReceiptView, _branding: &ReceiptBranding) -> String {
+            format!("<footer>Thanks! Ref {}</footer>", escape_html(&view.id))
+        }
+    }
+
+    #[test]
+    fn test_render_html_escapes_and_brands() {
+        let branding = ReceiptBranding {
+            merchant_name: Some("Coffee & Co".to_string()),
+            ..Default::default()
+        };
+        let html = ReceiptRenderer::new()
+            .with_branding(branding)
+            .render_html(&receipt());
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<h1>Coffee &amp; Co</h1>"));
+        assert!(html.contains("2500 SAT"));
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("Paid with Paykit"));
+    }
+
+    #[test]
+    fn test_template_overrides_section() {
+        let html = ReceiptRenderer::new()
+            .with_template(PlainFooter)
+            .render_html(&receipt());
+
+        assert!(html.contains("<footer>Thanks! Ref rcpt_001</footer>"));
+        assert!(!html.contains("Paid with Paykit"));
+        assert!(html.contains("<h1>Payment Receipt</h1>"));
+    }
+
+    #[cfg(all(feature = "pdf", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_render_pdf() {
+        let pdf = ReceiptRenderer::new().render_pdf(&receipt()).unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+    }
+}
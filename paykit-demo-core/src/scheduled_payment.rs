@@ -0,0 +1,290 @@
+//! Scheduled payments for demo wallets
+//!
+//! Persists one-off payments to send later next to the other demo data and
+//! sends the due ones through a [`PaymentScheduler`], recording each payment
+//! as an ordinary receipt. A run claims the payment before sending it, so
+//! two processes never send it twice and a cancel cannot race the send.
+
+use crate::jobs::{self, JobOutcome, JobState};
+use crate::state_file::StateFile;
+use crate::DemoStorage;
+use anyhow::{anyhow, Result};
+use paykit_lib::scheduled_payments::{PaymentScheduler, ScheduledOutcome, ScheduledPayment};
+use paykit_lib::{PublicKey, SupportedPayments};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Persisted scheduled payments
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScheduledPaymentState {
+    /// Payments in creation order, including settled ones
+    #[serde(default)]
+    pub payments: Vec<ScheduledPayment>,
+    /// Payments being sent by a run, with when the run claimed them
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub in_progress: BTreeMap<String, i64>,
+}
+
+impl JobState for ScheduledPaymentState {
+    type Job = ScheduledPayment;
+
+    const KIND: &'static str = "Scheduled payment";
+
+    fn jobs(&mut self) -> &mut Vec<ScheduledPayment> {
+        &mut self.payments
+    }
+
+    fn claims(&mut self) -> &mut BTreeMap<String, i64> {
+        &mut self.in_progress
+    }
+
+    fn job_id(payment: &ScheduledPayment) -> &str {
+        &payment.payment_id
+    }
+
+    fn is_due(payment: &ScheduledPayment, now: i64) -> bool {
+        payment.is_due(now)
+    }
+}
+
+impl JobOutcome for ScheduledOutcome {
+    fn is_unresolved(&self) -> bool {
+        matches!(self, ScheduledOutcome::Unresolved(_))
+    }
+}
+
+/// Coordinates scheduled payments for a demo storage directory
+pub struct ScheduledPaymentCoordinator {
+    file: StateFile,
+}
+
+impl ScheduledPaymentCoordinator {
+    /// Create a coordinator for the given storage directory
+    pub fn new(storage_dir: impl AsRef<Path>) -> Self {
+        Self {
+            file: StateFile::new(storage_dir, "scheduled_payments.json", "scheduled payments"),
+        }
+    }
+
+    /// All payments, in creation order
+    pub fn list(&self) -> Result<Vec<ScheduledPayment>> {
+        Ok(self.load()?.payments)
+    }
+
+    /// Get a payment by ID
+    pub fn get(&self, payment_id: &str) -> Result<Option<ScheduledPayment>> {
+        Ok(self
+            .load()?
+            .payments
+            .into_iter()
+            .find(|payment| payment.payment_id == payment_id))
+    }
+
+    /// Schedule a new payment
+    pub fn add(&self, payment: ScheduledPayment) -> Result<()> {
+        payment.validate()?;
+        payment
+            .payee
+            .parse::<PublicKey>()
+            .map_err(|e| anyhow!("Invalid payee public key: {}", e))?;
+
+        self.file.update(|state: &mut ScheduledPaymentState| {
+            if state
+                .payments
+                .iter()
+                .any(|p| p.payment_id == payment.payment_id)
+            {
+                return Err(anyhow!(
+                    "Scheduled payment {} already exists",
+                    payment.payment_id
+                ));
+            }
+            state.payments.push(payment);
+            Ok(())
+        })
+    }
+
+    /// Cancel a payment that has not been sent yet
+    ///
+    /// Fails while a run is sending the payment.
+    pub fn cancel(&self, payment_id: &str, now: i64) -> Result<ScheduledPayment> {
+        self.file.update(|state: &mut ScheduledPaymentState| {
+            jobs::ensure_unclaimed(state, payment_id)?;
+            let payment = state
+                .payments
+                .iter_mut()
+                .find(|payment| payment.payment_id == payment_id)
+                .ok_or_else(|| anyhow!("Scheduled payment not found: {}", payment_id))?;
+            payment.cancel(now)?;
+            Ok(payment.clone())
+        })
+    }
+
+    /// Payments due at `now`, earliest window first, excluding those being sent
+    pub fn due(&self, now: i64) -> Result<Vec<ScheduledPayment>> {
+        let state = self.load()?;
+        let mut due: Vec<ScheduledPayment> = state
+            .payments
+            .into_iter()
+            .filter(|p| p.is_due(now) && !state.in_progress.contains_key(&p.payment_id))
+            .collect();
+        due.sort_by_key(|payment| payment.not_before);
+        Ok(due)
+    }
+
+    /// Pending payments whose window has closed, so the next run expires them
+    pub fn overdue(&self, now: i64) -> Result<Vec<ScheduledPayment>> {
+        Ok(self
+            .load()?
+            .payments
+            .into_iter()
+            .filter(|payment| {
+                !payment.is_settled() && payment.not_after.is_some_and(|end| now > end)
+            })
+            .collect())
+    }
+
+    /// Payments claimed by a run, with when the run started
+    pub fn in_progress(&self) -> Result<BTreeMap<String, i64>> {
+        Ok(self.load()?.in_progress)
+    }
+
+    /// Release the claim an interrupted or unresolved run left on a payment
+    ///
+    /// Only call this once the wallet shows whether the payment was sent;
+    /// a pending payment is sent again on the next run. Returns whether
+    /// there was a claim.
+    pub fn release(&self, payment_id: &str) -> Result<bool> {
+        jobs::release::<ScheduledPaymentState>(&self.file, payment_id)
+    }
+
+    /// Run one payment and record it as a receipt if it was sent
+    ///
+    /// `supported` are the payee's published methods. The payment's status
+    /// is saved whatever the outcome, before the receipt is written. An
+    /// [`Unresolved`](ScheduledOutcome::Unresolved) payment keeps the claim
+    /// until it is released.
+    pub async fn run(
+        &self,
+        scheduler: &PaymentScheduler,
+        payment_id: &str,
+        supported: &SupportedPayments,
+        storage: &DemoStorage,
+        payer: &PublicKey,
+        now: i64,
+    ) -> Result<ScheduledOutcome> {
+        let (payment, outcome) = jobs::run_job::<ScheduledPaymentState, _, _, _>(
+            &self.file,
+            payment_id,
+            now,
+            |mut payment| async move {
+                let outcome = scheduler.run_at(&mut payment, supported, now).await?;
+                Ok((payment, outcome))
+            },
+        )
+        .await?;
+
+        if let ScheduledOutcome::Paid(execution) = &outcome {
+            jobs::save_receipt(
+                storage,
+                payer,
+                &payment.payee,
+                payment.amount_sats,
+                execution,
+                serde_json::json!({
+                    "scheduled_payment": payment.payment_id,
+                    "memo": payment.memo,
+                    "execution": execution.execution_data,
+                }),
+                now,
+            )?;
+        }
+        Ok(outcome)
+    }
+
+    fn load(&self) -> Result<ScheduledPaymentState> {
+        self.file.load()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paykit_lib::methods::default_registry;
+    use paykit_lib::scheduled_payments::{ScheduledSkip, ScheduledStatus};
+    use paykit_lib::{EndpointData, MethodId};
+    use pubky::Keypair;
+
+    #[tokio::test]
+    async fn test_scheduled_payment_run_records_receipt() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let payments = ScheduledPaymentCoordinator::new(temp_dir.path());
+        let storage = DemoStorage::new(temp_dir.path());
+        let payer = Keypair::random().public_key();
+        let payee = Keypair::random().public_key();
+
+        let payment = ScheduledPayment::new("rent", payee.to_string(), 20_000, 1_000, 0)
+            .unwrap()
+            .with_memo("march rent");
+        payments.add(payment.clone()).unwrap();
+        assert!(payments.add(payment).is_err());
+        assert!(payments.due(999).unwrap().is_empty());
+        assert_eq!(payments.due(1_000).unwrap().len(), 1);
+
+        let mut supported = SupportedPayments::default();
+        supported.entries.insert(
+            MethodId::onchain(),
+            EndpointData("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".into()),
+        );
+        let scheduler = PaymentScheduler::new(default_registry());
+
+        let outcome = payments
+            .run(&scheduler, "rent", &supported, &storage, &payer, 1_000)
+            .await
+            .unwrap();
+        assert!(matches!(outcome, ScheduledOutcome::Paid(_)));
+        let receipts = storage.list_receipts().unwrap();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].metadata["scheduled_payment"], "rent");
+
+        // Paid payments are persisted and cannot be sent or cancelled again
+        let stored = payments.get("rent").unwrap().unwrap();
+        assert_eq!(stored.status, ScheduledStatus::Paid);
+        assert!(payments.due(1_001).unwrap().is_empty());
+        assert!(payments.cancel("rent", 1_001).is_err());
+
+        // A cancel cannot race a run that is sending the payment
+        let racing = ScheduledPayment::new("racing", payee.to_string(), 1_000, 1_000, 0).unwrap();
+        payments.add(racing).unwrap();
+        payments
+            .file
+            .update(|state: &mut ScheduledPaymentState| {
+                state.in_progress.insert("racing".to_string(), 1_000);
+                Ok(())
+            })
+            .unwrap();
+        assert!(payments.due(1_000).unwrap().is_empty());
+        assert!(payments.cancel("racing", 1_001).is_err());
+        assert!(payments
+            .run(&scheduler, "racing", &supported, &storage, &payer, 1_001)
+            .await
+            .is_err());
+        assert!(payments.release("racing").unwrap());
+        payments.cancel("racing", 1_002).unwrap();
+
+        let later = ScheduledPayment::new("later", payee.to_string(), 5_000, 9_000, 0).unwrap();
+        payments.add(later).unwrap();
+        let cancelled = payments.cancel("later", 1_002).unwrap();
+        assert_eq!(cancelled.status, ScheduledStatus::Cancelled);
+        let outcome = payments
+            .run(&scheduler, "later", &supported, &storage, &payer, 9_000)
+            .await
+            .unwrap();
+        assert!(matches!(
+            outcome,
+            ScheduledOutcome::Skipped(ScheduledSkip::Cancelled)
+        ));
+        assert_eq!(storage.list_receipts().unwrap().len(), 1);
+    }
+}
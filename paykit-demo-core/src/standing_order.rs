@@ -0,0 +1,302 @@
+//! Standing orders for demo wallets
+//!
+//! Persists sender-side recurring payments next to the other demo data and
+//! pays the due ones through a [`StandingOrderScheduler`], recording each
+//! payment as an ordinary receipt. A run claims the order before paying,
+//! so two processes never pay the same occurrence.
+
+use crate::jobs::{self, JobOutcome, JobState};
+use crate::state_file::StateFile;
+use crate::DemoStorage;
+use anyhow::{anyhow, Result};
+use paykit_lib::standing_orders::{OrderOutcome, StandingOrder, StandingOrderScheduler};
+use paykit_lib::{PublicKey, SupportedPayments};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Persisted standing orders
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StandingOrderState {
+    /// Orders in creation order
+    #[serde(default)]
+    pub orders: Vec<StandingOrder>,
+    /// Orders being paid by a run, with when the run claimed them
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub in_progress: BTreeMap<String, i64>,
+}
+
+impl JobState for StandingOrderState {
+    type Job = StandingOrder;
+
+    const KIND: &'static str = "Standing order";
+
+    fn jobs(&mut self) -> &mut Vec<StandingOrder> {
+        &mut self.orders
+    }
+
+    fn claims(&mut self) -> &mut BTreeMap<String, i64> {
+        &mut self.in_progress
+    }
+
+    fn job_id(order: &StandingOrder) -> &str {
+        &order.order_id
+    }
+
+    fn is_due(order: &StandingOrder, now: i64) -> bool {
+        order.is_due(now)
+    }
+}
+
+impl JobOutcome for OrderOutcome {
+    fn is_unresolved(&self) -> bool {
+        matches!(self, OrderOutcome::Unresolved(_))
+    }
+}
+
+/// Coordinates standing orders for a demo storage directory
+pub struct StandingOrderCoordinator {
+    file: StateFile,
+}
+
+impl StandingOrderCoordinator {
+    /// Create a coordinator for the given storage directory
+    pub fn new(storage_dir: impl AsRef<Path>) -> Self {
+        Self {
+            file: StateFile::new(storage_dir, "standing_orders.json", "standing orders"),
+        }
+    }
+
+    /// All orders, in creation order
+    pub fn list(&self) -> Result<Vec<StandingOrder>> {
+        Ok(self.load()?.orders)
+    }
+
+    /// Get an order by ID
+    pub fn get(&self, order_id: &str) -> Result<Option<StandingOrder>> {
+        Ok(self
+            .load()?
+            .orders
+            .into_iter()
+            .find(|order| order.order_id == order_id))
+    }
+
+    /// Add a new order
+    pub fn add(&self, order: StandingOrder) -> Result<()> {
+        order.validate()?;
+        order
+            .payee
+            .parse::<PublicKey>()
+            .map_err(|e| anyhow!("Invalid payee public key: {}", e))?;
+
+        self.file.update(|state: &mut StandingOrderState| {
+            if state.orders.iter().any(|o| o.order_id == order.order_id) {
+                return Err(anyhow!("Standing order {} already exists", order.order_id));
+            }
+            state.orders.push(order);
+            Ok(())
+        })
+    }
+
+    /// Pause or resume an order
+    pub fn set_paused(&self, order_id: &str, paused: bool) -> Result<()> {
+        self.file.update(|state: &mut StandingOrderState| {
+            jobs::ensure_unclaimed(state, order_id)?;
+            let order = state
+                .orders
+                .iter_mut()
+                .find(|order| order.order_id == order_id)
+                .ok_or_else(|| anyhow!("Standing order not found: {}", order_id))?;
+            order.paused = paused;
+            Ok(())
+        })
+    }
+
+    /// Remove an order, returning whether it existed
+    pub fn remove(&self, order_id: &str) -> Result<bool> {
+        self.file.update(|state: &mut StandingOrderState| {
+            jobs::ensure_unclaimed(state, order_id)?;
+            let before = state.orders.len();
+            state.orders.retain(|order| order.order_id != order_id);
+            Ok(state.orders.len() != before)
+        })
+    }
+
+    /// Orders due at `now`, earliest first, excluding those being paid
+    pub fn due(&self, now: i64) -> Result<Vec<StandingOrder>> {
+        let state = self.load()?;
+        let mut due: Vec<StandingOrder> = state
+            .orders
+            .into_iter()
+            .filter(|order| order.is_due(now) && !state.in_progress.contains_key(&order.order_id))
+            .collect();
+        due.sort_by_key(|order| order.next_due_at);
+        Ok(due)
+    }
+
+    /// Orders claimed by a run, with when the run started
+    pub fn in_progress(&self) -> Result<BTreeMap<String, i64>> {
+        Ok(self.load()?.in_progress)
+    }
+
+    /// Release the claim an interrupted or unresolved run left on an order
+    ///
+    /// Only call this once the wallet shows whether the payment was sent;
+    /// the order is paid again on its next run. Returns whether there was a
+    /// claim.
+    pub fn release(&self, order_id: &str) -> Result<bool> {
+        jobs::release::<StandingOrderState>(&self.file, order_id)
+    }
+
+    /// Run one order and record the payment as a receipt
+    ///
+    /// `supported` are the payee's published methods. The order's schedule
+    /// is saved whatever the outcome, before the receipt is written. An
+    /// [`Unresolved`](OrderOutcome::Unresolved) payment keeps the order
+    /// claimed until it is released.
+    pub async fn run(
+        &self,
+        scheduler: &StandingOrderScheduler,
+        order_id: &str,
+        supported: &SupportedPayments,
+        storage: &DemoStorage,
+        payer: &PublicKey,
+        now: i64,
+    ) -> Result<OrderOutcome> {
+        let (order, outcome) = jobs::run_job::<StandingOrderState, _, _, _>(
+            &self.file,
+            order_id,
+            now,
+            |mut order| async move {
+                let outcome = scheduler.run_at(&mut order, supported, now).await?;
+                Ok((order, outcome))
+            },
+        )
+        .await?;
+
+        if let OrderOutcome::Paid(execution) = &outcome {
+            jobs::save_receipt(
+                storage,
+                payer,
+                &order.payee,
+                order.amount_sats,
+                execution,
+                serde_json::json!({
+                    "standing_order": order.order_id,
+                    "memo": order.memo,
+                    "execution": execution.execution_data,
+                }),
+                now,
+            )?;
+        }
+        Ok(outcome)
+    }
+
+    fn load(&self) -> Result<StandingOrderState> {
+        self.file.load()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paykit_lib::methods::default_registry;
+    use paykit_lib::standing_orders::{OrderSchedule, OrderSkip};
+    use paykit_lib::{EndpointData, MethodId};
+    use pubky::Keypair;
+
+    #[tokio::test]
+    async fn test_standing_order_run_records_receipt() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let orders = StandingOrderCoordinator::new(temp_dir.path());
+        let storage = DemoStorage::new(temp_dir.path());
+        let payer = Keypair::random().public_key();
+        let payee = Keypair::random().public_key();
+
+        let order = StandingOrder::new(
+            "weekly-savings",
+            payee.to_string(),
+            50_000,
+            OrderSchedule::Interval { every_secs: 3600 },
+            1_000,
+        )
+        .unwrap()
+        .with_memo("savings");
+        orders.add(order.clone()).unwrap();
+        assert!(orders.add(order).is_err());
+        assert_eq!(orders.due(1_000).unwrap().len(), 1);
+
+        let mut supported = SupportedPayments::default();
+        supported.entries.insert(
+            MethodId::onchain(),
+            EndpointData("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".into()),
+        );
+        let scheduler = StandingOrderScheduler::new(default_registry());
+
+        let outcome = orders
+            .run(
+                &scheduler,
+                "weekly-savings",
+                &supported,
+                &storage,
+                &payer,
+                1_000,
+            )
+            .await
+            .unwrap();
+        assert!(matches!(outcome, OrderOutcome::Paid(_)));
+        let receipts = storage.list_receipts().unwrap();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].metadata["standing_order"], "weekly-savings");
+
+        // The schedule moved on and was persisted
+        let outcome = orders
+            .run(
+                &scheduler,
+                "weekly-savings",
+                &supported,
+                &storage,
+                &payer,
+                1_001,
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            outcome,
+            OrderOutcome::Skipped(OrderSkip::NotDue { next_due_at: 4_600 })
+        ));
+
+        // An interrupted run leaves its claim; nothing pays or edits the order until released
+        orders
+            .file
+            .update(|state: &mut StandingOrderState| {
+                state
+                    .in_progress
+                    .insert("weekly-savings".to_string(), 4_600);
+                Ok(())
+            })
+            .unwrap();
+        assert!(orders.due(4_600).unwrap().is_empty());
+        assert!(orders
+            .run(
+                &scheduler,
+                "weekly-savings",
+                &supported,
+                &storage,
+                &payer,
+                4_600
+            )
+            .await
+            .is_err());
+        assert!(orders.set_paused("weekly-savings", true).is_err());
+        assert!(orders.remove("weekly-savings").is_err());
+        assert!(orders.release("weekly-savings").unwrap());
+        assert!(!orders.release("weekly-savings").unwrap());
+        assert_eq!(storage.list_receipts().unwrap().len(), 1);
+
+        orders.set_paused("weekly-savings", true).unwrap();
+        assert!(orders.due(4_600).unwrap().is_empty());
+        assert!(orders.remove("weekly-savings").unwrap());
+        assert!(orders.list().unwrap().is_empty());
+    }
+}
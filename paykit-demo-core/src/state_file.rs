@@ -0,0 +1,82 @@
+//! JSON state files shared between processes
+//!
+//! Every change happens under an exclusive lock on a `.lock` file next to
+//! the state, held across the load and the save, so two CLI processes (or a
+//! `watch` and a one-off command) never apply changes on top of each other.
+//! The state itself is replaced by rename, so readers never see a partly
+//! written file.
+
+use anyhow::{Context, Result};
+use fs2::FileExt;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+
+/// A JSON state file in a demo storage directory
+pub(crate) struct StateFile {
+    storage_dir: PathBuf,
+    file_name: &'static str,
+    label: &'static str,
+}
+
+impl StateFile {
+    /// `label` names the contents in error messages, e.g. "standing orders"
+    pub(crate) fn new(
+        storage_dir: impl AsRef<Path>,
+        file_name: &'static str,
+        label: &'static str,
+    ) -> Self {
+        Self {
+            storage_dir: storage_dir.as_ref().to_path_buf(),
+            file_name,
+            label,
+        }
+    }
+
+    /// Read the current state, or the default if there is none yet
+    pub(crate) fn load<T: DeserializeOwned + Default>(&self) -> Result<T> {
+        let path = self.path();
+        if !path.exists() {
+            return Ok(T::default());
+        }
+
+        let json = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", self.label))?;
+        serde_json::from_str(&json).with_context(|| format!("Failed to parse {}", self.label))
+    }
+
+    /// Apply `f` to the state and save it, holding the lock throughout
+    ///
+    /// Nothing is saved if `f` fails.
+    pub(crate) fn update<T, R>(&self, f: impl FnOnce(&mut T) -> Result<R>) -> Result<R>
+    where
+        T: Serialize + DeserializeOwned + Default,
+    {
+        std::fs::create_dir_all(&self.storage_dir)?;
+        let lock = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(self.path().with_extension("lock"))
+            .with_context(|| format!("Failed to lock {}", self.label))?;
+        lock.lock_exclusive()
+            .with_context(|| format!("Failed to lock {}", self.label))?;
+
+        let mut state = self.load()?;
+        let result = f(&mut state)?;
+        self.save(&state)?;
+        Ok(result)
+    }
+
+    fn save<T: Serialize>(&self, state: &T) -> Result<()> {
+        let json = serde_json::to_string_pretty(state)?;
+        let tmp = self.path().with_extension("json.tmp");
+        std::fs::write(&tmp, json).with_context(|| format!("Failed to save {}", self.label))?;
+        std::fs::rename(&tmp, self.path()).with_context(|| format!("Failed to save {}", self.label))
+    }
+
+    fn path(&self) -> PathBuf {
+        self.storage_dir.join(self.file_name)
+    }
+}
@@ -1,14 +1,38 @@
 //! File-based storage for demo data (contacts, receipts, etc.)
+//!
+//! Files are plaintext JSON unless a [`StorageCipher`] is attached with
+//! [`DemoStorage::with_cipher`], in which case every write is encrypted.
 
+use crate::encryption::{self, StorageCipher};
 use crate::models::{Contact, Receipt};
 use anyhow::{Context, Result};
+use paykit_interactive::{ReceiptPage, ReceiptQuery};
+use paykit_lib::audit::{AuditEntry, AuditLog, AuditOperation};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// Simple file-based storage for demo applications
 pub struct DemoStorage {
     storage_dir: PathBuf,
+    cipher: Option<Arc<dyn StorageCipher>>,
+}
+
+/// Encryption state of the files in a storage directory
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StorageStatus {
+    /// Files written with encryption
+    pub encrypted_files: usize,
+    /// Files still in plaintext
+    pub plaintext_files: usize,
+}
+
+impl StorageStatus {
+    /// Whether every file is encrypted
+    pub fn fully_encrypted(&self) -> bool {
+        self.plaintext_files == 0
+    }
 }
 
 #[derive(Serialize, Deserialize, Default)]
@@ -22,9 +46,58 @@ impl DemoStorage {
     pub fn new(storage_dir: impl AsRef<Path>) -> Self {
         Self {
             storage_dir: storage_dir.as_ref().to_path_buf(),
+            cipher: None,
         }
     }
 
+    /// Encrypt all writes with the given cipher
+    ///
+    /// Plaintext files written before encryption was enabled can still be
+    /// read and are encrypted the next time they are saved.
+    pub fn with_cipher(mut self, cipher: Arc<dyn StorageCipher>) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// Whether writes are encrypted
+    pub fn is_encrypted(&self) -> bool {
+        self.cipher.is_some()
+    }
+
+    /// Count encrypted and plaintext files
+    pub fn status(&self) -> Result<StorageStatus> {
+        let mut status = StorageStatus::default();
+        for path in self.data_files()? {
+            let bytes = std::fs::read(&path)?;
+            if encryption::is_sealed(&bytes) {
+                status.encrypted_files += 1;
+            } else {
+                status.plaintext_files += 1;
+            }
+        }
+        Ok(status)
+    }
+
+    /// Encrypt any plaintext files in place
+    ///
+    /// Returns the number of files migrated.
+    pub fn encrypt_existing(&self) -> Result<usize> {
+        if self.cipher.is_none() {
+            anyhow::bail!("No storage cipher configured");
+        }
+        let mut migrated = 0;
+        for path in self.data_files()? {
+            let bytes = std::fs::read(&path)?;
+            if !encryption::is_sealed(&bytes) {
+                let text = String::from_utf8(bytes)
+                    .with_context(|| format!("{} is not valid UTF-8", path.display()))?;
+                self.write_file(&path, &text)?;
+                migrated += 1;
+            }
+        }
+        Ok(migrated)
+    }
+
     /// Initialize storage directory
     pub fn init(&self) -> Result<()> {
         std::fs::create_dir_all(&self.storage_dir).context("Failed to create storage directory")?;
@@ -84,13 +157,18 @@ impl DemoStorage {
         Ok(receipts)
     }
 
+    /// Search receipts and return one page of results, newest first
+    pub fn query_receipts(&self, query: &ReceiptQuery) -> Result<ReceiptPage<Receipt>> {
+        let data = self.load_data()?;
+        Ok(query.apply(data.receipts.into_values())?)
+    }
+
     /// Save a receipt as JSON (for interactive protocol receipts)
     pub fn save_receipt_json(&self, id: &str, json: &str) -> Result<()> {
         let receipts_dir = self.storage_dir.join("interactive_receipts");
         std::fs::create_dir_all(&receipts_dir)?;
         let path = receipts_dir.join(format!("{}.json", id));
-        std::fs::write(&path, json)?;
-        Ok(())
+        self.write_file(&path, json)
     }
 
     /// Get a receipt JSON by ID
@@ -99,11 +177,7 @@ impl DemoStorage {
             .storage_dir
             .join("interactive_receipts")
             .join(format!("{}.json", id));
-        if !path.exists() {
-            return Ok(None);
-        }
-        let json = std::fs::read_to_string(&path)?;
-        Ok(Some(json))
+        self.read_file(&path)
     }
 
     /// List all receipt JSONs
@@ -117,7 +191,7 @@ impl DemoStorage {
             let entry = entry?;
             let path = entry.path();
             if path.extension().map(|e| e == "json").unwrap_or(false) {
-                if let Ok(json) = std::fs::read_to_string(&path) {
+                if let Ok(Some(json)) = self.read_file(&path) {
                     jsons.push(json);
                 }
             }
@@ -125,26 +199,102 @@ impl DemoStorage {
         Ok(jsons)
     }
 
+    /// Load the key usage audit log, verifying its hash chain
+    pub fn load_audit_log(&self) -> Result<AuditLog> {
+        match self.read_file(&self.audit_path())? {
+            Some(jsonl) => AuditLog::from_jsonl(&jsonl).context("Audit log is corrupted"),
+            None => Ok(AuditLog::new()),
+        }
+    }
+
+    /// Record a use of the identity key in the audit log
+    pub fn record_audit(
+        &self,
+        operation: AuditOperation,
+        counterparty: Option<&str>,
+        detail: Option<&str>,
+    ) -> Result<AuditEntry> {
+        let mut log = self.load_audit_log()?;
+        let entry = log
+            .append(
+                operation,
+                counterparty,
+                detail,
+                chrono::Utc::now().timestamp(),
+            )
+            .clone();
+        self.init()?;
+        self.write_file(&self.audit_path(), &log.to_jsonl()?)?;
+        Ok(entry)
+    }
+
+    fn audit_path(&self) -> PathBuf {
+        self.storage_dir.join("audit.jsonl")
+    }
+
     fn data_path(&self) -> PathBuf {
         self.storage_dir.join("data.json")
     }
 
     fn load_data(&self) -> Result<StorageData> {
-        let path = self.data_path();
-        if !path.exists() {
-            return Ok(StorageData::default());
+        match self.read_file(&self.data_path())? {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(StorageData::default()),
         }
-
-        let json = std::fs::read_to_string(&path)?;
-        let data = serde_json::from_str(&json)?;
-        Ok(data)
     }
 
     fn save_data(&self, data: &StorageData) -> Result<()> {
         self.init()?;
-        let path = self.data_path();
         let json = serde_json::to_string_pretty(data)?;
-        std::fs::write(&path, json)?;
+        self.write_file(&self.data_path(), &json)
+    }
+
+    /// All files managed by this storage
+    fn data_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for path in [self.data_path(), self.audit_path()] {
+            if path.exists() {
+                files.push(path);
+            }
+        }
+        let receipts_dir = self.storage_dir.join("interactive_receipts");
+        if receipts_dir.exists() {
+            for entry in std::fs::read_dir(&receipts_dir)? {
+                let path = entry?.path();
+                if path.extension().map(|e| e == "json").unwrap_or(false) {
+                    files.push(path);
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    /// Read a file, decrypting it if needed
+    fn read_file(&self, path: &Path) -> Result<Option<String>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let bytes = std::fs::read(path)?;
+        let bytes = if encryption::is_sealed(&bytes) {
+            let cipher = self
+                .cipher
+                .as_deref()
+                .context("Storage is encrypted; unlock it before reading")?;
+            encryption::open(cipher, &bytes)?
+        } else {
+            bytes
+        };
+        Ok(Some(
+            String::from_utf8(bytes).context("Stored data is not valid UTF-8")?,
+        ))
+    }
+
+    /// Write a file, encrypting it when a cipher is configured
+    fn write_file(&self, path: &Path, contents: &str) -> Result<()> {
+        match self.cipher.as_deref() {
+            Some(cipher) => std::fs::write(path, encryption::seal(cipher, contents.as_bytes())?)?,
+            None => std::fs::write(path, contents)?,
+        }
         Ok(())
     }
 }
@@ -173,4 +323,96 @@ mod tests {
         let contacts = storage.list_contacts().unwrap();
         assert_eq!(contacts.len(), 1);
     }
+
+    #[test]
+    fn test_encrypted_storage_migrates_plaintext() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let plain = DemoStorage::new(temp_dir.path());
+        let keypair = Keypair::random();
+        plain
+            .save_contact(Contact::new(keypair.public_key(), "Alice".to_string()))
+            .unwrap();
+        plain.save_receipt_json("r1", "{\"id\":\"r1\"}").unwrap();
+
+        let cipher = Arc::new(crate::encryption::AesGcmCipher::from_key(&[7u8; 32]));
+        let encrypted = DemoStorage::new(temp_dir.path()).with_cipher(cipher);
+        assert_eq!(encrypted.status().unwrap().plaintext_files, 2);
+        assert_eq!(encrypted.list_contacts().unwrap().len(), 1);
+
+        assert_eq!(encrypted.encrypt_existing().unwrap(), 2);
+        let status = encrypted.status().unwrap();
+        assert!(status.fully_encrypted());
+        assert_eq!(status.encrypted_files, 2);
+
+        let raw = std::fs::read(temp_dir.path().join("data.json")).unwrap();
+        assert!(!String::from_utf8_lossy(&raw).contains("Alice"));
+        assert_eq!(encrypted.list_contacts().unwrap()[0].name, "Alice");
+        assert_eq!(
+            encrypted.get_receipt_json("r1").unwrap().unwrap(),
+            "{\"id\":\"r1\"}"
+        );
+        assert!(plain.list_contacts().is_err());
+    }
+
+    #[test]
+    fn test_audit_log_persists_chain() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let storage = DemoStorage::new(temp_dir.path());
+        assert!(storage.load_audit_log().unwrap().is_empty());
+
+        let first = storage
+            .record_audit(AuditOperation::Sign, None, Some("payment link"))
+            .unwrap();
+        let second = storage
+            .record_audit(AuditOperation::NoiseHandshake, Some("127.0.0.1:9735"), None)
+            .unwrap();
+        assert_eq!(second.prev_hash, first.hash);
+
+        let log = storage.load_audit_log().unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log.head_hash(), second.hash);
+
+        let path = temp_dir.path().join("audit.jsonl");
+        let tampered = std::fs::read_to_string(&path)
+            .unwrap()
+            .replace("payment link", "nothing to see");
+        std::fs::write(&path, tampered).unwrap();
+        assert!(storage.load_audit_log().is_err());
+    }
+
+    #[test]
+    fn test_receipt_query_pagination() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let storage = DemoStorage::new(temp_dir.path());
+
+        let payer = Keypair::random().public_key();
+        let payee = Keypair::random().public_key();
+        for i in 0..5 {
+            let mut receipt = Receipt::new(
+                format!("r{}", i),
+                payer.clone(),
+                payee.clone(),
+                if i % 2 == 0 { "lightning" } else { "onchain" }.to_string(),
+            )
+            .with_amount((i * 1000).to_string(), "SAT".to_string());
+            receipt.timestamp = 1_700_000_000 + i;
+            storage.save_receipt(receipt).unwrap();
+        }
+
+        let query = ReceiptQuery::new().with_method("lightning").with_limit(2);
+        let first = storage.query_receipts(&query).unwrap();
+        assert_eq!(first.items.len(), 2);
+        assert_eq!(first.items[0].id, "r4");
+
+        let next = query.with_cursor(first.next_cursor.unwrap());
+        let second = storage.query_receipts(&next).unwrap();
+        assert_eq!(second.items.len(), 1);
+        assert_eq!(second.items[0].id, "r0");
+        assert!(second.next_cursor.is_none());
+
+        let filtered = storage
+            .query_receipts(&ReceiptQuery::new().with_min_amount(3000))
+            .unwrap();
+        assert_eq!(filtered.items.len(), 2);
+    }
 }
@@ -0,0 +1,472 @@
+//! Opt-in OpenTimestamps attestation of receipts
+//!
+//! Once enabled, receipt digests are queued as receipts are saved.
+//! [`TimestampCoordinator::submit`] batches everything queued into one
+//! Merkle tree and sends its root to the configured calendars, storing each
+//! receipt's `.ots` proof next to the receipt as
+//! `interactive_receipts/<receipt id>.ots`. A few hours later
+//! [`TimestampCoordinator::upgrade`] completes the proofs with the Bitcoin
+//! block the calendars' transactions confirmed in, and
+//! [`TimestampCoordinator::verify`] checks a proof against the chain.
+//!
+//! The proofs are standard `.ots` files, so they can also be checked with
+//! the reference `ots verify` against the receipt's canonical JSON digest.
+//! See `paykit_interactive::ots` for the format and protocol.
+
+use anyhow::{anyhow, Context, Result};
+use paykit_interactive::ots::{self, BlockAttestation, BlockHeaders, Calendar, DetachedTimestamp};
+use paykit_lib::executors::DEFAULT_CALENDARS;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Whether and where receipts are timestamped
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimestampSettings {
+    /// Queue receipt digests as receipts are saved
+    pub enabled: bool,
+    /// Calendar servers to submit to and upgrade from
+    pub calendars: Vec<String>,
+}
+
+impl Default for TimestampSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            calendars: DEFAULT_CALENDARS.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+}
+
+/// A receipt digest waiting for the next batch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingDigest {
+    /// Receipt ID
+    pub receipt_id: String,
+    /// Hex SHA-256 of the receipt's canonical JSON
+    pub digest: String,
+    /// Unix timestamp it was queued
+    pub queued_at: i64,
+}
+
+/// What is known about one receipt's proof
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimestampRecord {
+    /// Hex digest the proof commits to
+    pub digest: String,
+    /// Unix timestamp the batch was submitted
+    pub submitted_at: i64,
+    /// Block the proof is anchored in, once upgraded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_height: Option<u64>,
+    /// Header time of that block, once verified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_time: Option<u64>,
+    /// Unix timestamp of the last successful verification
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verified_at: Option<i64>,
+}
+
+impl TimestampRecord {
+    /// Whether the proof reaches a Bitcoin block
+    pub fn is_complete(&self) -> bool {
+        self.block_height.is_some()
+    }
+}
+
+/// Result of checking a receipt's proof against the chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TimestampVerification {
+    /// Receipt ID
+    pub receipt_id: String,
+    /// Hex digest the proof commits to
+    pub digest: String,
+    /// Block height the digest is anchored in
+    pub block_height: u64,
+    /// Header time of that block; the receipt existed before it
+    pub block_time: u64,
+    /// Whether the receipt still has the digest that was timestamped
+    pub receipt_unchanged: bool,
+}
+
+/// Persisted timestamping settings, queue and proof index
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TimestampState {
+    /// Current settings
+    #[serde(default)]
+    pub settings: TimestampSettings,
+    /// Digests waiting for the next batch
+    #[serde(default)]
+    pub pending: Vec<PendingDigest>,
+    /// Proofs by receipt ID
+    #[serde(default)]
+    pub proofs: BTreeMap<String, TimestampRecord>,
+}
+
+/// Coordinates receipt timestamping for a demo storage directory
+pub struct TimestampCoordinator {
+    storage_dir: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl TimestampCoordinator {
+    /// Create a coordinator for the given storage directory
+    pub fn new(storage_dir: impl AsRef<Path>) -> Self {
+        Self {
+            storage_dir: storage_dir.as_ref().to_path_buf(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Current state
+    pub fn state(&self) -> Result<TimestampState> {
+        self.load()
+    }
+
+    /// Start queueing receipts, submitting to `calendars` (or the defaults)
+    pub fn enable(&self, calendars: Vec<String>) -> Result<()> {
+        self.update(|state| {
+            state.settings.enabled = true;
+            if !calendars.is_empty() {
+                state.settings.calendars = calendars;
+            }
+            Ok(())
+        })
+    }
+
+    /// Stop queueing receipts, keeping the queue and existing proofs
+    pub fn disable(&self) -> Result<()> {
+        self.update(|state| {
+            state.settings.enabled = false;
+            Ok(())
+        })
+    }
+
+    /// Queue a receipt's digest if timestamping is enabled
+    ///
+    /// Returns whether it was queued; a receipt already queued or
+    /// timestamped with the same digest is skipped.
+    pub fn record(&self, receipt_id: &str, digest: &str, now: i64) -> Result<bool> {
+        if !self.load()?.settings.enabled {
+            return Ok(false);
+        }
+        self.queue(receipt_id, digest, now)
+    }
+
+    /// Queue a receipt's digest whether or not timestamping is enabled
+    pub fn queue(&self, receipt_id: &str, digest: &str, now: i64) -> Result<bool> {
+        self.proof_path(receipt_id)?;
+        parse_digest(digest)?;
+        self.update(|state| {
+            let known = state
+                .pending
+                .iter()
+                .any(|p| p.receipt_id == receipt_id && p.digest == digest)
+                || state
+                    .proofs
+                    .get(receipt_id)
+                    .is_some_and(|record| record.digest == digest);
+            if known {
+                return Ok(false);
+            }
+            state.pending.retain(|p| p.receipt_id != receipt_id);
+            state.pending.push(PendingDigest {
+                receipt_id: receipt_id.to_string(),
+                digest: digest.to_string(),
+                queued_at: now,
+            });
+            Ok(true)
+        })
+    }
+
+    /// Submit every queued digest to the calendars as one batch
+    ///
+    /// Returns the receipt IDs that now have a pending proof. Succeeds if
+    /// at least one calendar accepts the batch.
+    pub async fn submit(&self, calendars: &[&dyn Calendar], now: i64) -> Result<Vec<String>> {
+        let pending = self.load()?.pending;
+        if pending.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let digests = pending
+            .iter()
+            .map(|p| parse_digest(&p.digest))
+            .collect::<Result<Vec<_>>>()?;
+        let proofs = ots::stamp(&digests, calendars)
+            .await
+            .context("No calendar accepted the batch")?;
+        for (entry, proof) in pending.iter().zip(&proofs) {
+            self.write_proof(&entry.receipt_id, proof)?;
+        }
+
+        self.update(|state| {
+            state.pending.retain(|p| !pending.contains(p));
+            for entry in &pending {
+                state.proofs.insert(
+                    entry.receipt_id.clone(),
+                    TimestampRecord {
+                        digest: entry.digest.clone(),
+                        submitted_at: now,
+                        block_height: None,
+                        block_time: None,
+                        verified_at: None,
+                    },
+                );
+            }
+            Ok(pending.iter().map(|p| p.receipt_id.clone()).collect())
+        })
+    }
+
+    /// Ask the calendars for the Bitcoin paths of incomplete proofs
+    ///
+    /// Returns the receipt IDs whose proofs are now complete. Calendars
+    /// that are still waiting for confirmation are skipped.
+    pub async fn upgrade(&self, calendars: &[&dyn Calendar]) -> Result<Vec<String>> {
+        let incomplete: Vec<String> = self
+            .load()?
+            .proofs
+            .into_iter()
+            .filter(|(_, record)| !record.is_complete())
+            .map(|(receipt_id, _)| receipt_id)
+            .collect();
+
+        let mut completed = Vec::new();
+        for receipt_id in incomplete {
+            let mut proof = self.read_proof(&receipt_id)?;
+            if !ots::upgrade(&mut proof, calendars).await? {
+                continue;
+            }
+            self.write_proof(&receipt_id, &proof)?;
+            let height = proof
+                .timestamp()
+                .attestations()
+                .into_iter()
+                .filter_map(|(_, attestation)| match attestation {
+                    ots::Attestation::Bitcoin { height } => Some(*height),
+                    _ => None,
+                })
+                .min();
+            self.update(|state| {
+                if let Some(record) = state.proofs.get_mut(&receipt_id) {
+                    record.block_height = height;
+                }
+                Ok(())
+            })?;
+            completed.push(receipt_id);
+        }
+        Ok(completed)
+    }
+
+    /// Check a receipt's proof against Bitcoin block headers
+    ///
+    /// `current_digest` is the receipt's digest now; the result reports
+    /// whether it still matches what was timestamped.
+    pub async fn verify(
+        &self,
+        receipt_id: &str,
+        current_digest: &str,
+        headers: &dyn BlockHeaders,
+        now: i64,
+    ) -> Result<TimestampVerification> {
+        let proof = self.read_proof(receipt_id)?;
+        let attested: BlockAttestation = proof.verify(headers).await?;
+        let digest = hex::encode(proof.digest());
+
+        self.update(|state| {
+            if let Some(record) = state.proofs.get_mut(receipt_id) {
+                record.block_height = Some(attested.height);
+                record.block_time = Some(attested.time);
+                record.verified_at = Some(now);
+            }
+            Ok(())
+        })?;
+        Ok(TimestampVerification {
+            receipt_id: receipt_id.to_string(),
+            receipt_unchanged: digest.eq_ignore_ascii_case(current_digest),
+            digest,
+            block_height: attested.height,
+            block_time: attested.time,
+        })
+    }
+
+    /// A receipt's `.ots` file
+    pub fn proof_bytes(&self, receipt_id: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.proof_path(receipt_id)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(std::fs::read(&path).context("Failed to read proof")?))
+    }
+
+    fn read_proof(&self, receipt_id: &str) -> Result<DetachedTimestamp> {
+        let bytes = self
+            .proof_bytes(receipt_id)?
+            .ok_or_else(|| anyhow!("Receipt {} has no timestamp proof", receipt_id))?;
+        DetachedTimestamp::from_bytes(&bytes)
+            .with_context(|| format!("Proof for receipt {} is corrupted", receipt_id))
+    }
+
+    fn write_proof(&self, receipt_id: &str, proof: &DetachedTimestamp) -> Result<()> {
+        let path = self.proof_path(receipt_id)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("ots.tmp");
+        std::fs::write(&tmp, proof.to_bytes()?).context("Failed to save proof")?;
+        std::fs::rename(&tmp, &path).context("Failed to save proof")
+    }
+
+    fn proof_path(&self, receipt_id: &str) -> Result<PathBuf> {
+        // Receipt IDs become file names
+        if receipt_id.is_empty()
+            || !receipt_id
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        {
+            return Err(anyhow!("Invalid receipt ID: {}", receipt_id));
+        }
+        Ok(self
+            .storage_dir
+            .join("interactive_receipts")
+            .join(format!("{}.ots", receipt_id)))
+    }
+
+    fn update<T>(&self, f: impl FnOnce(&mut TimestampState) -> Result<T>) -> Result<T> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut state = self.load()?;
+        let result = f(&mut state)?;
+        self.save(&state)?;
+        Ok(result)
+    }
+
+    fn state_path(&self) -> PathBuf {
+        self.storage_dir.join("timestamps.json")
+    }
+
+    fn load(&self) -> Result<TimestampState> {
+        let path = self.state_path();
+        if !path.exists() {
+            return Ok(TimestampState::default());
+        }
+
+        let json = std::fs::read_to_string(&path).context("Failed to read timestamps")?;
+        serde_json::from_str(&json).context("Failed to parse timestamps")
+    }
+
+    fn save(&self, state: &TimestampState) -> Result<()> {
+        std::fs::create_dir_all(&self.storage_dir)?;
+        let json = serde_json::to_string_pretty(state)?;
+        let tmp = self.state_path().with_extension("json.tmp");
+        std::fs::write(&tmp, json).context("Failed to save timestamps")?;
+        std::fs::rename(&tmp, self.state_path()).context("Failed to save timestamps")
+    }
+}
+
+fn parse_digest(digest: &str) -> Result<[u8; 32]> {
+    hex::decode(digest)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| anyhow!("Invalid receipt digest: {}", digest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paykit_interactive::ots::{Attestation, BlockHeader, Op, Timestamp};
+
+    const CALENDAR: &str = "https://calendar.example";
+
+    /// Answers every submission with a pending attestation and completes
+    /// it into block 100, whose Merkle root is the commitment itself.
+    struct InstantCalendar;
+
+    #[async_trait::async_trait]
+    impl Calendar for InstantCalendar {
+        fn url(&self) -> &str {
+            CALENDAR
+        }
+
+        async fn submit(&self, digest: &[u8]) -> paykit_interactive::Result<Vec<u8>> {
+            let mut timestamp = Timestamp::new(digest);
+            timestamp
+                .add_op(Op::Sha256)?
+                .add_attestation(Attestation::Pending {
+                    uri: CALENDAR.to_string(),
+                });
+            timestamp.to_bytes()
+        }
+
+        async fn fetch(&self, commitment: &[u8]) -> paykit_interactive::Result<Option<Vec<u8>>> {
+            let mut timestamp = Timestamp::new(commitment);
+            timestamp.add_attestation(Attestation::Bitcoin { height: 100 });
+            Ok(Some(timestamp.to_bytes()?))
+        }
+    }
+
+    struct Headers(Vec<u8>);
+
+    #[async_trait::async_trait]
+    impl BlockHeaders for Headers {
+        async fn block_header(&self, _height: u64) -> paykit_interactive::Result<BlockHeader> {
+            Ok(BlockHeader {
+                merkle_root: self.0.clone().try_into().unwrap(),
+                time: 1_700_000_000,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_queue_submit_upgrade_verify() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let timestamps = TimestampCoordinator::new(temp_dir.path());
+        let digest = hex::encode([1u8; 32]);
+
+        // Nothing is queued until enabled
+        assert!(!timestamps.record("r1", &digest, 10).unwrap());
+        timestamps.enable(vec![CALENDAR.to_string()]).unwrap();
+        assert!(timestamps.record("r1", &digest, 10).unwrap());
+        assert!(!timestamps.record("r1", &digest, 11).unwrap());
+        assert!(timestamps
+            .record("r2", &hex::encode([2u8; 32]), 12)
+            .unwrap());
+        assert!(timestamps.record("../r3", &digest, 12).is_err());
+
+        let submitted = timestamps.submit(&[&InstantCalendar], 20).await.unwrap();
+        assert_eq!(submitted, vec!["r1", "r2"]);
+        let state = timestamps.state().unwrap();
+        assert!(state.pending.is_empty());
+        assert!(!state.proofs["r1"].is_complete());
+        assert!(temp_dir.path().join("interactive_receipts/r1.ots").exists());
+
+        assert_eq!(
+            timestamps.upgrade(&[&InstantCalendar]).await.unwrap(),
+            vec!["r1", "r2"]
+        );
+        assert_eq!(
+            timestamps.state().unwrap().proofs["r1"].block_height,
+            Some(100)
+        );
+
+        // The calendar's commitment is the one the pending attestation names
+        let proof = timestamps.read_proof("r1").unwrap();
+        let commitment = proof.timestamp().pending()[0].1.to_vec();
+        let verified = timestamps
+            .verify("r1", &digest, &Headers(commitment.clone()), 30)
+            .await
+            .unwrap();
+        assert_eq!(verified.block_height, 100);
+        assert!(verified.receipt_unchanged);
+
+        let changed = timestamps
+            .verify("r1", &hex::encode([9u8; 32]), &Headers(commitment), 30)
+            .await
+            .unwrap();
+        assert!(!changed.receipt_unchanged);
+        assert!(timestamps
+            .verify("r1", &digest, &Headers(vec![0u8; 32]), 30)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,158 @@
+//! Treasury sweeps for merchant demos
+//!
+//! Persists the sweep policy and sweep receipts next to the other demo data
+//! and drives [`TreasurySweeper`] runs from it.
+
+use anyhow::{Context, Result};
+use paykit_lib::methods::BitcoinExecutor;
+use paykit_lib::treasury::{SweepOutcome, SweepPolicy, SweepReceipt, TreasurySweeper};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Persisted treasury configuration and sweep history
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TreasuryState {
+    /// Active sweep policy, if sweeps are enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<SweepPolicy>,
+    /// When the last sweep was broadcast
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_sweep_at: Option<i64>,
+    /// Completed sweeps, oldest first
+    #[serde(default)]
+    pub sweeps: Vec<SweepReceipt>,
+}
+
+/// Coordinates treasury sweeps for a demo storage directory
+pub struct TreasuryCoordinator {
+    storage_dir: PathBuf,
+}
+
+impl TreasuryCoordinator {
+    /// Create a coordinator for the given storage directory
+    pub fn new(storage_dir: impl AsRef<Path>) -> Self {
+        Self {
+            storage_dir: storage_dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Get the active sweep policy
+    pub fn policy(&self) -> Result<Option<SweepPolicy>> {
+        Ok(self.load()?.policy)
+    }
+
+    /// Enable sweeps with the given policy, replacing any existing one
+    pub fn set_policy(&self, policy: SweepPolicy) -> Result<()> {
+        policy.validate()?;
+        let mut state = self.load()?;
+        state.policy = Some(policy);
+        self.save(&state)
+    }
+
+    /// Disable sweeps, keeping the history
+    pub fn disable(&self) -> Result<()> {
+        let mut state = self.load()?;
+        state.policy = None;
+        self.save(&state)
+    }
+
+    /// Completed sweeps, newest first
+    pub fn history(&self) -> Result<Vec<SweepReceipt>> {
+        let mut sweeps = self.load()?.sweeps;
+        sweeps.reverse();
+        Ok(sweeps)
+    }
+
+    /// Build a sweeper for the active policy, resuming its schedule
+    ///
+    /// Returns `None` if sweeps are disabled.
+    pub fn sweeper(&self, executor: Arc<dyn BitcoinExecutor>) -> Result<Option<TreasurySweeper>> {
+        let state = self.load()?;
+        let Some(policy) = state.policy else {
+            return Ok(None);
+        };
+        Ok(Some(
+            TreasurySweeper::new(executor, policy)?.with_last_sweep_at(state.last_sweep_at),
+        ))
+    }
+
+    /// Run a sweeper and record the sweep receipt
+    ///
+    /// Scheduled runs only sweep when due; forced runs ignore the schedule.
+    pub async fn run(&self, sweeper: &TreasurySweeper, force: bool) -> Result<SweepOutcome> {
+        let outcome = if force {
+            sweeper.sweep_now().await?
+        } else {
+            sweeper.tick().await?
+        };
+
+        if let SweepOutcome::Swept(receipt) = &outcome {
+            let mut state = self.load()?;
+            state.last_sweep_at = Some(receipt.swept_at);
+            state.sweeps.push(receipt.clone());
+            self.save(&state)?;
+        }
+        Ok(outcome)
+    }
+
+    fn state_path(&self) -> PathBuf {
+        self.storage_dir.join("treasury.json")
+    }
+
+    fn load(&self) -> Result<TreasuryState> {
+        let path = self.state_path();
+        if !path.exists() {
+            return Ok(TreasuryState::default());
+        }
+
+        let json = std::fs::read_to_string(&path).context("Failed to read treasury state")?;
+        serde_json::from_str(&json).context("Failed to parse treasury state")
+    }
+
+    fn save(&self, state: &TreasuryState) -> Result<()> {
+        std::fs::create_dir_all(&self.storage_dir)?;
+        let json = serde_json::to_string_pretty(state)?;
+        std::fs::write(self.state_path(), json).context("Failed to save treasury state")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paykit_lib::methods::MockBitcoinExecutor;
+    use paykit_lib::treasury::{SweepSchedule, SweepSkip};
+
+    #[tokio::test]
+    async fn test_sweep_is_recorded_and_schedule_resumed() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let treasury = TreasuryCoordinator::new(temp_dir.path());
+        let wallet: Arc<dyn BitcoinExecutor> = Arc::new(MockBitcoinExecutor::with_balance(500_000));
+        assert!(treasury.sweeper(wallet.clone()).unwrap().is_none());
+
+        treasury
+            .set_policy(
+                SweepPolicy::new("bc1qcold", 100_000, 1_000)
+                    .with_schedule(SweepSchedule::Interval { every_secs: 3600 }),
+            )
+            .unwrap();
+        let sweeper = treasury.sweeper(wallet.clone()).unwrap().unwrap();
+        assert!(matches!(
+            treasury.run(&sweeper, false).await.unwrap(),
+            SweepOutcome::Swept(_)
+        ));
+        assert_eq!(treasury.history().unwrap().len(), 1);
+
+        // A fresh sweeper picks up the persisted schedule
+        let sweeper = treasury.sweeper(wallet).unwrap().unwrap();
+        assert!(matches!(
+            treasury.run(&sweeper, false).await.unwrap(),
+            SweepOutcome::Skipped(SweepSkip::NotDue { .. })
+        ));
+        assert!(matches!(
+            treasury.run(&sweeper, true).await.unwrap(),
+            SweepOutcome::Swept(_)
+        ));
+        assert_eq!(treasury.history().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,200 @@
+//! Vouchers for demo wallets
+//!
+//! Keeps the issuer's [`VoucherLedger`] next to the other demo data.
+//! Redeeming claims the voucher and saves the claim, under a lock on the
+//! ledger file, before paying, so a code presented twice, even to two
+//! processes, is paid at most once. The payout is recorded as an ordinary
+//! receipt from the issuer to the holder. A payout that may or may not have
+//! gone out keeps its claim until it is released by hand.
+
+use crate::jobs;
+use crate::state_file::StateFile;
+use crate::DemoStorage;
+use anyhow::{anyhow, Result};
+use paykit_lib::methods::PaymentExecution;
+use paykit_lib::vouchers::{
+    SignedVoucher, VoucherLedger, VoucherPayout, VoucherRecord, VoucherRedeemer, VoucherStatus,
+};
+use paykit_lib::{PublicKey, SupportedPayments};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Persisted vouchers
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VoucherLedgerState {
+    /// Issued vouchers, oldest first
+    #[serde(default)]
+    pub vouchers: Vec<VoucherRecord>,
+}
+
+/// Coordinates issued vouchers for a demo storage directory
+pub struct VoucherCoordinator {
+    file: StateFile,
+}
+
+impl VoucherCoordinator {
+    /// Create a coordinator for the given storage directory
+    pub fn new(storage_dir: impl AsRef<Path>) -> Self {
+        Self {
+            file: StateFile::new(storage_dir, "vouchers.json", "vouchers"),
+        }
+    }
+
+    /// All issued vouchers, oldest first
+    pub fn list(&self) -> Result<Vec<VoucherRecord>> {
+        Ok(self.load()?.vouchers)
+    }
+
+    /// Get a voucher by ID
+    pub fn get(&self, voucher_id: &str) -> Result<Option<VoucherRecord>> {
+        Ok(self
+            .load()?
+            .vouchers
+            .into_iter()
+            .find(|record| record.voucher_id == voucher_id))
+    }
+
+    /// Record a newly signed voucher
+    pub fn issue(&self, signed: &SignedVoucher) -> Result<()> {
+        self.update(|ledger| Ok(ledger.issue(signed)?))
+    }
+
+    /// Revoke an unredeemed voucher
+    pub fn revoke(&self, voucher_id: &str, now: i64) -> Result<()> {
+        self.update(|ledger| Ok(ledger.revoke(voucher_id, now)?))
+    }
+
+    /// Release a voucher left claimed by an interrupted or unresolved payout
+    ///
+    /// Only call this once the wallet shows the payout did not go out; the
+    /// code can then be redeemed again. Returns whether there was a claim.
+    pub fn release(&self, voucher_id: &str) -> Result<bool> {
+        self.update(|ledger| {
+            let claimed = ledger
+                .get(voucher_id)
+                .ok_or_else(|| anyhow!("Voucher not found: {}", voucher_id))?
+                .status;
+            ledger.release(voucher_id)?;
+            Ok(matches!(claimed, VoucherStatus::Redeeming { .. }))
+        })
+    }
+
+    /// Pay out `code` to `holder` and record the payment as a receipt
+    ///
+    /// `supported` are the holder's published methods. A failed payout
+    /// releases the claim and is returned as the failed execution. A payout
+    /// that may have been sent keeps the claim and is returned as an error.
+    pub async fn redeem(
+        &self,
+        redeemer: &VoucherRedeemer,
+        code: &str,
+        holder: &PublicKey,
+        supported: &SupportedPayments,
+        storage: &DemoStorage,
+        now: i64,
+    ) -> Result<PaymentExecution> {
+        let voucher = self.update(|ledger| Ok(ledger.claim(code, &holder.to_string(), now)?))?;
+
+        let execution = match redeemer.pay_claimed(&voucher, supported).await {
+            Ok(VoucherPayout::Paid(execution)) => execution,
+            Ok(VoucherPayout::Failed(execution)) => {
+                self.release(&voucher.voucher_id)?;
+                return Ok(execution);
+            }
+            Ok(VoucherPayout::Unresolved(execution)) => {
+                return Err(anyhow!(
+                    "Voucher {} may have been paid out ({}); check the wallet, then release it",
+                    voucher.voucher_id,
+                    execution.error.unwrap_or_default()
+                ));
+            }
+            Err(e) => {
+                self.release(&voucher.voucher_id)?;
+                return Err(e.into());
+            }
+        };
+        self.update(|ledger| Ok(ledger.complete(&voucher.voucher_id, &execution)?))?;
+
+        jobs::save_receipt(
+            storage,
+            &voucher.issuer,
+            &holder.to_string(),
+            voucher.amount_sats,
+            &execution,
+            serde_json::json!({
+                "voucher": voucher.voucher_id,
+                "memo": voucher.memo,
+                "execution": execution.execution_data,
+            }),
+            now,
+        )?;
+        Ok(execution)
+    }
+
+    fn update<T>(&self, f: impl FnOnce(&VoucherLedger) -> Result<T>) -> Result<T> {
+        self.file.update(|state: &mut VoucherLedgerState| {
+            let ledger = VoucherLedger::from_records(std::mem::take(&mut state.vouchers));
+            let result = f(&ledger)?;
+            state.vouchers = ledger.records();
+            Ok(result)
+        })
+    }
+
+    fn load(&self) -> Result<VoucherLedgerState> {
+        self.file.load()
+    }
+}
+
+/// Fail with a readable message if `code` is not a voucher signed by its issuer
+pub fn verify_code(code: &str) -> Result<SignedVoucher> {
+    let signed = SignedVoucher::decode(code)?;
+    signed
+        .verify_signature()
+        .map_err(|_| anyhow!("Voucher signature does not match its issuer"))?;
+    Ok(signed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paykit_lib::methods::default_registry;
+    use paykit_lib::vouchers::{Voucher, VoucherState};
+    use paykit_lib::{EndpointData, MethodId};
+
+    #[tokio::test]
+    async fn test_redeem_once_and_record_receipt() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let vouchers = VoucherCoordinator::new(temp_dir.path());
+        let storage = DemoStorage::new(temp_dir.path());
+
+        let issuer = pubky::Keypair::random();
+        let holder = pubky::Keypair::random().public_key();
+        let signed = Voucher::new("gift", issuer.public_key(), 2_100, 1_000)
+            .sign(&issuer)
+            .unwrap();
+        vouchers.issue(&signed).unwrap();
+        assert!(vouchers.issue(&signed).is_err());
+
+        let mut supported = SupportedPayments::default();
+        supported
+            .entries
+            .insert(MethodId::lightning(), EndpointData("lnbc1holder".into()));
+        let redeemer = VoucherRedeemer::new(default_registry());
+        let code = verify_code(&signed.to_code()).unwrap().to_code();
+
+        let execution = vouchers
+            .redeem(&redeemer, &code, &holder, &supported, &storage, 1_100)
+            .await
+            .unwrap();
+        assert!(execution.success);
+        assert!(vouchers
+            .redeem(&redeemer, &code, &holder, &supported, &storage, 1_200)
+            .await
+            .is_err());
+
+        let record = vouchers.get("gift").unwrap().unwrap();
+        assert_eq!(record.state(1_200), VoucherState::Redeemed);
+        assert_eq!(storage.list_receipts().unwrap().len(), 1);
+        assert!(vouchers.revoke("gift", 1_300).is_err());
+    }
+}
@@ -0,0 +1,435 @@
+//! Wallet configuration and named wallet profiles
+//!
+//! A profile is a separate demo environment: its own payment backends, its
+//! own network and its own storage namespace, so a "testnet-dev" profile
+//! never sees the identities, receipts or wallet of "mainnet-prod".
+//!
+//! The `default` profile lives directly in the storage directory, which
+//! keeps setups from before profiles working unchanged. Every other profile
+//! lives in `profiles/<name>/` below it:
+//!
+//! ```text
+//! <storage>/
+//! ├── wallet.json              # default profile
+//! ├── .wallet_profile          # name of the active profile
+//! └── profiles/
+//!     └── testnet-dev/
+//!         └── wallet.json
+//! ```
+
+use anyhow::{Context, Result};
+use paykit_lib::executors::BitcoinNetwork;
+use paykit_lib::PaykitError;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Name of the profile that lives in the storage directory itself
+pub const DEFAULT_PROFILE: &str = "default";
+
+const WALLET_FILE: &str = "wallet.json";
+const ACTIVE_FILE: &str = ".wallet_profile";
+const PROFILES_DIR: &str = "profiles";
+
+/// Wallet configuration stored on disk
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct WalletConfig {
+    /// LND configuration (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lnd: Option<LndConfig>,
+    /// Core Lightning configuration (optional, used when LND is not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cln: Option<ClnConfig>,
+    /// Esplora configuration (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub esplora: Option<EsploraConfig>,
+    /// BTCPay Server store receiving payments (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub btcpay: Option<BtcPayConfig>,
+    /// Network (mainnet, testnet, signet, regtest)
+    #[serde(default = "default_network")]
+    pub network: String,
+}
+
+fn default_network() -> String {
+    "testnet".to_string()
+}
+
+/// LND REST connection settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LndConfig {
+    /// REST API URL
+    pub url: String,
+    /// Macaroon in hex format
+    pub macaroon: String,
+    /// TLS certificate (optional, PEM format)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls_cert: Option<String>,
+}
+
+/// Core Lightning `clnrest` connection settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClnConfig {
+    /// REST API URL
+    pub url: String,
+    /// Rune authorizing the calls
+    pub rune: String,
+    /// CA certificate of the REST server (optional, PEM format)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls_cert: Option<String>,
+}
+
+/// Esplora connection settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EsploraConfig {
+    /// API base URL
+    pub url: String,
+    /// Watch-only addresses that fund on-chain payments
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub funding_addresses: Vec<String>,
+    /// Address receiving change (defaults to the first funding address)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub change_address: Option<String>,
+}
+
+/// BTCPay Server store settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BtcPayConfig {
+    /// Server URL
+    pub url: String,
+    /// Greenfield API key
+    pub api_key: String,
+    /// Store ID
+    pub store_id: String,
+    /// Secret of the store webhook (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_secret: Option<String>,
+    /// Lightning address or LNURL published for the store (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lightning_address: Option<String>,
+}
+
+impl BtcPayConfig {
+    /// The paykit-lib receiver configuration
+    pub fn to_receiver_config(&self) -> paykit_lib::executors::BtcPayConfig {
+        let mut config =
+            paykit_lib::executors::BtcPayConfig::new(&self.url, &self.api_key, &self.store_id);
+        config.webhook_secret = self.webhook_secret.clone();
+        config.lightning_address = self.lightning_address.clone();
+        config
+    }
+}
+
+impl WalletConfig {
+    /// Load wallet configuration from disk
+    pub fn load(storage_dir: &Path) -> Result<Option<Self>> {
+        let config_path = storage_dir.join(WALLET_FILE);
+        if !config_path.exists() {
+            return Ok(None);
+        }
+
+        let contents =
+            std::fs::read_to_string(&config_path).context("Failed to read wallet configuration")?;
+        let config: Self =
+            serde_json::from_str(&contents).context("Failed to parse wallet configuration")?;
+        Ok(Some(config))
+    }
+
+    /// Save wallet configuration to disk
+    pub fn save(&self, storage_dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(storage_dir)?;
+        let config_path = storage_dir.join(WALLET_FILE);
+        let contents =
+            serde_json::to_string_pretty(self).context("Failed to serialize wallet config")?;
+        std::fs::write(&config_path, contents).context("Failed to write wallet configuration")?;
+        Ok(())
+    }
+
+    /// Check if any executor is configured
+    pub fn is_configured(&self) -> bool {
+        self.has_lightning() || self.esplora.is_some()
+    }
+
+    /// Check if Lightning payments are configured
+    pub fn has_lightning(&self) -> bool {
+        self.lnd.is_some() || self.cln.is_some()
+    }
+
+    /// Check if on-chain payments are configured
+    pub fn has_onchain(&self) -> bool {
+        self.esplora.is_some()
+    }
+
+    /// Check if a BTCPay store receives payments
+    pub fn has_btcpay(&self) -> bool {
+        self.btcpay.is_some()
+    }
+
+    /// The configured network
+    pub fn bitcoin_network(&self) -> Result<BitcoinNetwork> {
+        parse_network(&self.network)
+    }
+
+    /// Refuse to pay `endpoint` if it belongs to another network.
+    ///
+    /// Endpoints whose network cannot be told from their format pass.
+    pub fn check_endpoint(&self, endpoint: &str) -> Result<()> {
+        let network = self.bitcoin_network()?;
+        let candidates = endpoint_networks(endpoint);
+        if candidates.is_empty() || candidates.contains(&network) {
+            return Ok(());
+        }
+        Err(PaykitError::ValidationFailed(format!(
+            "Endpoint is for {}, but this wallet profile is on {}",
+            candidates
+                .iter()
+                .map(BitcoinNetwork::as_str)
+                .collect::<Vec<_>>()
+                .join("/"),
+            network.as_str()
+        ))
+        .into())
+    }
+}
+
+/// Parse a network name
+pub fn parse_network(name: &str) -> Result<BitcoinNetwork> {
+    match name.to_lowercase().as_str() {
+        "mainnet" | "bitcoin" => Ok(BitcoinNetwork::Mainnet),
+        "testnet" => Ok(BitcoinNetwork::Testnet),
+        "signet" => Ok(BitcoinNetwork::Signet),
+        "regtest" => Ok(BitcoinNetwork::Regtest),
+        other => Err(PaykitError::invalid_data(
+            "network",
+            format!(
+                "unknown network '{}' (mainnet, testnet, signet, regtest)",
+                other
+            ),
+        )
+        .into()),
+    }
+}
+
+/// Networks a Lightning invoice or Bitcoin address can belong to.
+///
+/// Testnet and signet share address formats, so addresses may match
+/// several networks. Unrecognized formats match none.
+pub fn endpoint_networks(endpoint: &str) -> Vec<BitcoinNetwork> {
+    use BitcoinNetwork::*;
+
+    let endpoint = endpoint.trim().to_lowercase();
+    let endpoint = endpoint
+        .strip_prefix("lightning:")
+        .or_else(|| endpoint.strip_prefix("bitcoin:"))
+        .unwrap_or(&endpoint);
+    let endpoint = endpoint.split('?').next().unwrap_or_default();
+
+    // Longer prefixes first: "lnbcrt" starts with "lnbc"
+    let prefixes: [(&str, &[BitcoinNetwork]); 7] = [
+        ("lnbcrt", &[Regtest]),
+        ("lnbc", &[Mainnet]),
+        ("lntbs", &[Signet]),
+        ("lntb", &[Testnet]),
+        ("bcrt1", &[Regtest]),
+        ("bc1", &[Mainnet]),
+        ("tb1", &[Testnet, Signet]),
+    ];
+    if let Some((_, networks)) = prefixes
+        .iter()
+        .find(|(prefix, _)| endpoint.starts_with(prefix))
+    {
+        return networks.to_vec();
+    }
+
+    // Base58 addresses
+    if (26..=35).contains(&endpoint.len()) && endpoint.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return match endpoint.chars().next() {
+            Some('1' | '3') => vec![Mainnet],
+            Some('m' | 'n' | '2') => vec![Testnet, Signet, Regtest],
+            _ => Vec::new(),
+        };
+    }
+    Vec::new()
+}
+
+/// Named wallet profiles below a storage directory
+#[derive(Debug, Clone)]
+pub struct WalletProfiles {
+    root: PathBuf,
+}
+
+impl WalletProfiles {
+    /// Profiles stored below `root`
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Storage namespace of a profile; it need not exist yet
+    pub fn dir(&self, name: &str) -> PathBuf {
+        if name == DEFAULT_PROFILE {
+            self.root.clone()
+        } else {
+            self.root.join(PROFILES_DIR).join(name)
+        }
+    }
+
+    /// Whether a profile exists; `default` always does
+    pub fn exists(&self, name: &str) -> bool {
+        name == DEFAULT_PROFILE || self.dir(name).is_dir()
+    }
+
+    /// Profile names, `default` first
+    pub fn list(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        let dir = self.root.join(PROFILES_DIR);
+        if dir.is_dir() {
+            for entry in std::fs::read_dir(&dir)
+                .with_context(|| format!("Failed to read {}", dir.display()))?
+            {
+                let entry = entry?;
+                if entry.file_type()?.is_dir() {
+                    names.push(entry.file_name().to_string_lossy().into_owned());
+                }
+            }
+        }
+        names.sort();
+        names.insert(0, DEFAULT_PROFILE.to_string());
+        Ok(names)
+    }
+
+    /// Create a profile on `network`
+    pub fn create(&self, name: &str, network: &str) -> Result<PathBuf> {
+        validate_name(name)?;
+        if self.exists(name) {
+            anyhow::bail!("Wallet profile '{}' already exists", name);
+        }
+        let network = parse_network(network)?;
+
+        let dir = self.dir(name);
+        WalletConfig {
+            network: network.as_str().to_string(),
+            ..Default::default()
+        }
+        .save(&dir)?;
+        Ok(dir)
+    }
+
+    /// Delete a profile and everything stored in its namespace
+    pub fn remove(&self, name: &str) -> Result<()> {
+        if name == DEFAULT_PROFILE {
+            anyhow::bail!("The default profile cannot be removed");
+        }
+        if self.active()? == name {
+            anyhow::bail!(
+                "Wallet profile '{}' is active; switch to another first",
+                name
+            );
+        }
+        let dir = self.dir(name);
+        if !dir.is_dir() {
+            return Err(PaykitError::not_found("wallet profile", name).into());
+        }
+        std::fs::remove_dir_all(&dir).with_context(|| format!("Failed to remove {}", dir.display()))
+    }
+
+    /// Name of the active profile
+    pub fn active(&self) -> Result<String> {
+        let path = self.root.join(ACTIVE_FILE);
+        if !path.exists() {
+            return Ok(DEFAULT_PROFILE.to_string());
+        }
+        let name = std::fs::read_to_string(&path).context("Failed to read active profile")?;
+        Ok(name.trim().to_string())
+    }
+
+    /// Make a profile the active one
+    pub fn set_active(&self, name: &str) -> Result<()> {
+        if !self.exists(name) {
+            return Err(PaykitError::not_found("wallet profile", name).into());
+        }
+        std::fs::create_dir_all(&self.root)?;
+        std::fs::write(self.root.join(ACTIVE_FILE), name).context("Failed to save active profile")
+    }
+
+    /// Storage namespace of `name`, or of the active profile
+    pub fn resolve(&self, name: Option<&str>) -> Result<(String, PathBuf)> {
+        let name = match name {
+            Some(name) => name.to_string(),
+            None => self.active()?,
+        };
+        if !self.exists(&name) {
+            return Err(PaykitError::not_found("wallet profile", name.as_str()).into());
+        }
+        let dir = self.dir(&name);
+        Ok((name, dir))
+    }
+}
+
+/// Profile names are directory names: letters, digits, '-' and '_'
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || name.len() > 64
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(
+            PaykitError::invalid_data("profile", "use 1-64 letters, digits, '-' or '_'").into(),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_profiles_are_isolated() {
+        let dir = tempdir().unwrap();
+        let profiles = WalletProfiles::new(dir.path());
+        assert_eq!(profiles.active().unwrap(), DEFAULT_PROFILE);
+
+        let dev = profiles.create("testnet-dev", "testnet").unwrap();
+        assert_ne!(dev, profiles.dir(DEFAULT_PROFILE));
+        assert!(profiles.create("testnet-dev", "testnet").is_err());
+        assert!(profiles.create("../escape", "testnet").is_err());
+        assert!(profiles.create("prod", "mainnt").is_err());
+
+        profiles.set_active("testnet-dev").unwrap();
+        let (name, path) = profiles.resolve(None).unwrap();
+        assert_eq!(name, "testnet-dev");
+        assert_eq!(path, dev);
+        assert!(profiles.remove("testnet-dev").is_err());
+        assert!(profiles.resolve(Some("missing")).is_err());
+
+        assert_eq!(profiles.list().unwrap(), vec!["default", "testnet-dev"]);
+    }
+
+    #[test]
+    fn test_endpoint_network_guard() {
+        let mainnet = WalletConfig {
+            network: "mainnet".to_string(),
+            ..Default::default()
+        };
+        let testnet = WalletConfig::default();
+
+        assert!(mainnet.check_endpoint("lnbc10u1p3xyz").is_ok());
+        assert!(mainnet.check_endpoint("lntb10u1p3xyz").is_err());
+        assert!(mainnet
+            .check_endpoint("tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx")
+            .is_err());
+        assert!(testnet
+            .check_endpoint("bitcoin:tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx?amount=1")
+            .is_ok());
+        assert!(testnet
+            .check_endpoint("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq")
+            .is_err());
+        assert!(testnet.check_endpoint("lnbcrt1p3xyz").is_err());
+
+        // Unknown formats are left to the executor
+        assert!(mainnet.check_endpoint("pubky://abc").is_ok());
+    }
+}
@@ -212,6 +212,23 @@ impl WasmContact {
     }
 }
 
+impl WasmContact {
+    /// Plain JS object with the contact's fields, as returned by `list_contacts`
+    pub(crate) fn to_js_object(&self) -> JsValue {
+        let js_obj = js_sys::Object::new();
+        let _ = js_sys::Reflect::set(&js_obj, &"public_key".into(), &self.public_key().into());
+        let _ = js_sys::Reflect::set(&js_obj, &"name".into(), &self.name().into());
+        if let Some(notes) = self.notes() {
+            let _ = js_sys::Reflect::set(&js_obj, &"notes".into(), &notes.into());
+        }
+        let _ = js_sys::Reflect::set(&js_obj, &"added_at".into(), &self.added_at().into());
+        let _ = js_sys::Reflect::set(&js_obj, &"pubky_uri".into(), &self.pubky_uri().into());
+        let history = self.payment_history();
+        let _ = js_sys::Reflect::set(&js_obj, &"payment_history".into(), &history.into());
+        js_obj.into()
+    }
+}
+
 /// Storage manager for contacts in browser localStorage
 ///
 /// Provides CRUD operations for managing contacts with localStorage persistence.
@@ -359,55 +376,13 @@ impl WasmContactStorage {
     /// }
     /// ```
     pub async fn list_contacts(&self) -> Result<Vec<JsValue>, JsValue> {
-        let window = web_sys::window().ok_or("No window")?;
-        let storage = window.local_storage()?.ok_or("No localStorage")?;
-
-        let mut contacts = Vec::new();
-        let prefix = format!("{}:", self.storage_key_prefix);
-        let length = storage
-            .length()
-            .map_err(|e| JsValue::from_str(&format!("{:?}", e)))?;
-
-        for i in 0..length {
-            if let Ok(Some(key)) = storage.key(i) {
-                if key.starts_with(&prefix) {
-                    if let Ok(Some(json)) = storage.get_item(&key) {
-                        if let Ok(contact) = WasmContact::from_json(&json) {
-                            contacts.push(contact);
-                        }
-                    }
-                }
-            }
-        }
+        let mut contacts = self.load_contacts()?;
 
         // Sort by name (case-insensitive)
         contacts.sort_by_key(|a| a.name().to_lowercase());
 
         // Convert to JsValue objects for JavaScript
-        let js_contacts: Vec<JsValue> = contacts
-            .iter()
-            .map(|contact| {
-                let js_obj = js_sys::Object::new();
-                let _ = js_sys::Reflect::set(
-                    &js_obj,
-                    &"public_key".into(),
-                    &contact.public_key().into(),
-                );
-                let _ = js_sys::Reflect::set(&js_obj, &"name".into(), &contact.name().into());
-                if let Some(notes) = contact.notes() {
-                    let _ = js_sys::Reflect::set(&js_obj, &"notes".into(), &notes.into());
-                }
-                let _ =
-                    js_sys::Reflect::set(&js_obj, &"added_at".into(), &contact.added_at().into());
-                let _ =
-                    js_sys::Reflect::set(&js_obj, &"pubky_uri".into(), &contact.pubky_uri().into());
-                let history = contact.payment_history();
-                let _ = js_sys::Reflect::set(&js_obj, &"payment_history".into(), &history.into());
-                js_obj.into()
-            })
-            .collect();
-
-        Ok(js_contacts)
+        Ok(contacts.iter().map(WasmContact::to_js_object).collect())
     }
 
     /// Delete a contact by public key
@@ -703,6 +678,34 @@ impl WasmContactStorage {
     }
 }
 
+impl WasmContactStorage {
+    /// Every stored contact, in localStorage key order
+    pub(crate) fn load_contacts(&self) -> Result<Vec<WasmContact>, JsValue> {
+        let window = web_sys::window().ok_or("No window")?;
+        let storage = window.local_storage()?.ok_or("No localStorage")?;
+
+        let mut contacts = Vec::new();
+        let prefix = format!("{}:", self.storage_key_prefix);
+        let length = storage
+            .length()
+            .map_err(|e| JsValue::from_str(&format!("{:?}", e)))?;
+
+        for i in 0..length {
+            if let Ok(Some(key)) = storage.key(i) {
+                if key.starts_with(&prefix) {
+                    if let Ok(Some(json)) = storage.get_item(&key) {
+                        if let Ok(contact) = WasmContact::from_json(&json) {
+                            contacts.push(contact);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(contacts)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
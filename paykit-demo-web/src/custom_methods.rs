@@ -0,0 +1,554 @@
+//! Custom Payment Methods for Paykit Demo Web
+//!
+//! Lets web wallets add proprietary payment rails (internal ledger
+//! transfers, store credit, ...) from JavaScript. A [`WasmMethodRegistry`]
+//! starts with the built-in methods; custom methods registered on it take
+//! part in selection, execution and proof generation alongside them.
+//!
+//! A custom method is a plain object of callbacks:
+//!
+//! ```javascript
+//! const registry = new WasmMethodRegistry();
+//! registry.register_custom_method("com.acme.ledger", "ACME Ledger", {
+//!     // Problems with the endpoint; return [] (or nothing) if valid
+//!     validate: (endpoint) => endpoint.startsWith("acct:") ? [] : ["Expected an account"],
+//!     // Pay and return execution data (object or JSON string), may be async
+//!     execute: async (endpoint, amountSats, metadataJson) => ({ transfer_id: await ledger.send(endpoint, amountSats) }),
+//!     // Proof data for a successful payment (object or JSON string)
+//!     prove: (executionDataJson) => JSON.parse(executionDataJson),
+//! });
+//! ```
+//!
+//! # Threading
+//!
+//! Payment method plugins must be `Send + Sync`, which JavaScript functions
+//! are not. The callbacks therefore live in a thread-local table and the
+//! registered plugin only holds a handle into it; wasm32 runs everything on
+//! one thread, so the handle always resolves.
+
+use paykit_lib::methods::{
+    default_registry, Amount, CustomMethodHandler, CustomMethodPlugin, MethodWeight,
+    PaymentExecution, PaymentMethodRegistry, PluginCapabilities, RegistryConfig, ValidationResult,
+};
+use paykit_lib::selection::{PaymentMethodSelector, SelectionPreferences};
+use paykit_lib::{EndpointData, MethodId, PaykitError, SupportedPayments};
+use serde::Serialize;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::sync::Arc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+
+use crate::utils::js_error;
+
+/// JavaScript callbacks backing one custom method.
+#[derive(Clone)]
+struct JsCallbacks {
+    validate: js_sys::Function,
+    execute: js_sys::Function,
+    prove: js_sys::Function,
+}
+
+impl JsCallbacks {
+    fn from_object(callbacks: &JsValue) -> Result<Self, JsValue> {
+        let function = |name: &str| -> Result<js_sys::Function, JsValue> {
+            js_sys::Reflect::get(callbacks, &name.into())?
+                .dyn_into::<js_sys::Function>()
+                .map_err(|_| js_error(&format!("Callback '{}' must be a function", name)))
+        };
+        Ok(Self {
+            validate: function("validate")?,
+            execute: function("execute")?,
+            prove: function("prove")?,
+        })
+    }
+}
+
+thread_local! {
+    static CALLBACKS: RefCell<HashMap<u32, JsCallbacks>> = RefCell::new(HashMap::new());
+    static NEXT_HANDLE: Cell<u32> = const { Cell::new(0) };
+}
+
+fn callbacks_for(handle: u32) -> paykit_lib::Result<JsCallbacks> {
+    CALLBACKS
+        .with(|table| table.borrow().get(&handle).cloned())
+        .ok_or_else(|| PaykitError::Transport("Custom method callbacks were dropped".to_string()))
+}
+
+/// Convert a callback result (object or JSON string) into JSON.
+fn js_to_json(value: JsValue) -> paykit_lib::Result<serde_json::Value> {
+    if value.is_undefined() || value.is_null() {
+        return Ok(serde_json::Value::Null);
+    }
+    if let Some(json) = value.as_string() {
+        return serde_json::from_str(&json).map_err(|e| PaykitError::Serialization(e.to_string()));
+    }
+    serde_wasm_bindgen::from_value(value).map_err(|e| PaykitError::Serialization(e.to_string()))
+}
+
+/// Serialize a result with JSON-compatible output, so JSON objects inside
+/// (execution data, proofs) become plain objects rather than `Map`s.
+fn to_js<T: Serialize>(value: &T, what: &str) -> Result<JsValue, JsValue> {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|e| js_error(&format!("Failed to serialize {}: {}", what, e)))
+}
+
+fn js_call_error(e: JsValue) -> PaykitError {
+    let msg = e
+        .dyn_ref::<js_sys::Error>()
+        .map(|err| String::from(err.message()))
+        .or_else(|| e.as_string())
+        .unwrap_or_else(|| "Custom method callback failed".to_string());
+    PaykitError::Transport(msg)
+}
+
+/// Handler forwarding to the JavaScript callbacks stored under `handle`.
+struct JsCustomMethod {
+    handle: u32,
+}
+
+impl Drop for JsCustomMethod {
+    fn drop(&mut self) {
+        let _ = CALLBACKS.try_with(|table| table.borrow_mut().remove(&self.handle));
+    }
+}
+
+impl CustomMethodHandler for JsCustomMethod {
+    fn validate_endpoint(&self, endpoint: &EndpointData) -> ValidationResult {
+        let result = callbacks_for(self.handle).and_then(|cb| {
+            cb.validate
+                .call1(&JsValue::NULL, &endpoint.0.clone().into())
+                .map_err(js_call_error)
+        });
+        match result {
+            Ok(errors) if errors.is_undefined() || errors.is_null() => ValidationResult::valid(),
+            Ok(errors) => {
+                let errors: Vec<String> = js_sys::Array::from(&errors)
+                    .iter()
+                    .filter_map(|e| e.as_string())
+                    .collect();
+                if errors.is_empty() {
+                    ValidationResult::valid()
+                } else {
+                    ValidationResult::invalid(errors)
+                }
+            }
+            Err(e) => ValidationResult::invalid(vec![e.to_string()]),
+        }
+    }
+
+    fn execute_payment(
+        &self,
+        endpoint: &EndpointData,
+        amount: &Amount,
+        metadata: &serde_json::Value,
+    ) -> paykit_lib::Result<serde_json::Value> {
+        let result = call_execute(&callbacks_for(self.handle)?, endpoint, amount, metadata)?;
+        if result.is_instance_of::<js_sys::Promise>() {
+            return Err(PaykitError::Transport(
+                "Async custom methods must be executed with WasmMethodRegistry.execute_payment"
+                    .to_string(),
+            ));
+        }
+        js_to_json(result)
+    }
+
+    fn generate_proof(
+        &self,
+        execution: &PaymentExecution,
+    ) -> paykit_lib::Result<serde_json::Value> {
+        let result = callbacks_for(self.handle)?
+            .prove
+            .call1(&JsValue::NULL, &execution.execution_data.to_string().into())
+            .map_err(js_call_error)?;
+        js_to_json(result)
+    }
+}
+
+fn call_execute(
+    callbacks: &JsCallbacks,
+    endpoint: &EndpointData,
+    amount: &Amount,
+    metadata: &serde_json::Value,
+) -> paykit_lib::Result<JsValue> {
+    let amount_sats = amount
+        .as_u64()
+        .filter(|_| amount.currency.eq_ignore_ascii_case("SAT"))
+        .ok_or_else(|| PaykitError::invalid_data("amount", "Amount must be whole sats"))?;
+    callbacks
+        .execute
+        .call3(
+            &JsValue::NULL,
+            &endpoint.0.clone().into(),
+            &JsValue::from_f64(amount_sats as f64),
+            &metadata.to_string().into(),
+        )
+        .map_err(js_call_error)
+}
+
+/// A payment method registry that accepts custom methods from JavaScript.
+///
+/// # Examples
+///
+/// ```javascript
+/// const registry = new WasmMethodRegistry();
+/// registry.register_custom_method("com.acme.ledger", "ACME Ledger", callbacks);
+///
+/// const [method] = registry.select_method(JSON.stringify({ "com.acme.ledger": "acct:42" }), 500n);
+/// const execution = await registry.execute_payment(method, "acct:42", 500n);
+/// ```
+#[wasm_bindgen]
+pub struct WasmMethodRegistry {
+    registry: Arc<PaymentMethodRegistry>,
+    custom: RefCell<HashMap<String, u32>>,
+}
+
+impl Default for WasmMethodRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl WasmMethodRegistry {
+    /// Create a registry holding the built-in methods.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self {
+            registry: Arc::new(default_registry()),
+            custom: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Register a custom payment method.
+    ///
+    /// `callbacks` must provide `validate`, `execute` and `prove`
+    /// functions. `capabilities` is an optional object shaped like the
+    /// `capabilities` returned by `describe()`. The ID must be namespaced
+    /// (e.g. `com.acme.ledger`); registering an existing custom method ID
+    /// replaces it.
+    pub fn register_custom_method(
+        &self,
+        method_id: &str,
+        display_name: &str,
+        callbacks: JsValue,
+        capabilities: JsValue,
+    ) -> Result<(), JsValue> {
+        let callbacks = JsCallbacks::from_object(&callbacks)?;
+        let capabilities: PluginCapabilities =
+            if capabilities.is_undefined() || capabilities.is_null() {
+                PluginCapabilities::default()
+            } else {
+                serde_wasm_bindgen::from_value(capabilities)
+                    .map_err(|e| js_error(&format!("Invalid capabilities: {}", e)))?
+            };
+
+        let handle = NEXT_HANDLE.with(|next| {
+            let handle = next.get();
+            next.set(handle.wrapping_add(1));
+            handle
+        });
+        let handler = Arc::new(JsCustomMethod { handle });
+        // Registering the callbacks only after validation means a rejected
+        // ID drops the handler without leaving an entry behind.
+        let plugin = CustomMethodPlugin::new(MethodId::new(method_id), display_name, handler)
+            .map_err(|e| js_error(&e.to_string()))?
+            .with_capabilities(capabilities);
+        CALLBACKS.with(|table| table.borrow_mut().insert(handle, callbacks));
+
+        self.registry.replace(Box::new(plugin));
+        self.custom
+            .borrow_mut()
+            .insert(method_id.to_string(), handle);
+        Ok(())
+    }
+
+    /// Remove a custom payment method, returning whether it was registered.
+    pub fn unregister_custom_method(&self, method_id: &str) -> Result<bool, JsValue> {
+        if self.custom.borrow_mut().remove(method_id).is_none() {
+            if !MethodId::new(method_id).is_namespaced() {
+                return Err(js_error(&format!("{} is not a custom method", method_id)));
+            }
+            return Ok(false);
+        }
+        Ok(self
+            .registry
+            .unregister(&MethodId::new(method_id))
+            .is_some())
+    }
+
+    /// Replace the method weighting configuration with one given as JSON.
+    ///
+    /// Weights are added to every method's selection score, whatever the
+    /// strategy: `{"methods": {"lightning": {"weight": 30, "max_amount_sats": 500000}}}`.
+    pub fn load_config(&self, config_json: &str) -> Result<(), JsValue> {
+        let config =
+            RegistryConfig::from_json(config_json).map_err(|e| js_error(&e.to_string()))?;
+        self.registry
+            .set_config(config)
+            .map_err(|e| js_error(&e.to_string()))
+    }
+
+    /// The method weighting configuration as JSON.
+    pub fn config_json(&self) -> Result<String, JsValue> {
+        self.registry
+            .config()
+            .to_json()
+            .map_err(|e| js_error(&e.to_string()))
+    }
+
+    /// Set the selection weight of one method, optionally limited to an amount range.
+    pub fn set_method_weight(
+        &self,
+        method_id: &str,
+        weight: f64,
+        min_amount_sats: Option<u64>,
+        max_amount_sats: Option<u64>,
+    ) -> Result<(), JsValue> {
+        let weight = MethodWeight::new(weight).with_amount_range(min_amount_sats, max_amount_sats);
+        self.registry
+            .set_method_weight(&MethodId::new(method_id), weight)
+            .map_err(|e| js_error(&e.to_string()))
+    }
+
+    /// Remove the selection weight of one method, returning whether it had one.
+    pub fn remove_method_weight(&self, method_id: &str) -> bool {
+        self.registry
+            .remove_method_weight(&MethodId::new(method_id))
+            .is_some()
+    }
+
+    /// Describe the registered methods and their capabilities.
+    pub fn describe(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.registry.describe(), "methods")
+    }
+
+    /// Validate an endpoint for a registered method.
+    pub fn validate_endpoint(&self, method_id: &str, endpoint: &str) -> Result<bool, JsValue> {
+        let plugin = self.plugin(method_id)?;
+        Ok(plugin
+            .validate_endpoint(&EndpointData(endpoint.to_string()))
+            .valid)
+    }
+
+    /// Select methods for paying a payee, best first.
+    ///
+    /// `supported_json` maps method IDs to endpoints, as published by the
+    /// payee. Custom methods are considered alongside the built-ins.
+    pub fn select_method(
+        &self,
+        supported_json: &str,
+        amount_sats: u64,
+    ) -> Result<Vec<JsValue>, JsValue> {
+        let entries: HashMap<String, String> = serde_json::from_str(supported_json)
+            .map_err(|e| js_error(&format!("Invalid supported methods: {}", e)))?;
+        let supported = SupportedPayments {
+            entries: entries
+                .into_iter()
+                .map(|(method, endpoint)| (MethodId(method), EndpointData(endpoint)))
+                .collect(),
+        };
+
+        let result = PaymentMethodSelector::new(self.registry.clone())
+            .select(
+                &supported,
+                &Amount::sats(amount_sats),
+                &SelectionPreferences::balanced(),
+            )
+            .map_err(|e| js_error(&e.to_string()))?;
+        Ok(result
+            .all_methods()
+            .into_iter()
+            .map(|m| JsValue::from_str(&m.0))
+            .collect())
+    }
+
+    /// Execute a payment with a registered method.
+    ///
+    /// Async `execute` callbacks of custom methods are awaited. Returns the
+    /// payment execution as an object (`success`, `execution_data`,
+    /// `error`, ...).
+    pub async fn execute_payment(
+        &self,
+        method_id: &str,
+        endpoint: &str,
+        amount_sats: u64,
+        metadata_json: Option<String>,
+    ) -> Result<JsValue, JsValue> {
+        let plugin = self.plugin(method_id)?;
+        let endpoint = EndpointData(endpoint.to_string());
+        let amount = Amount::sats(amount_sats);
+        let metadata: serde_json::Value = match metadata_json {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| js_error(&format!("Invalid metadata: {}", e)))?,
+            None => serde_json::json!({}),
+        };
+
+        let handle = self.custom.borrow().get(method_id).copied();
+        let execution = match handle {
+            Some(handle) => {
+                if !plugin.supports_amount(&amount) {
+                    return Err(js_error(&format!(
+                        "Amount {} is outside the limits of {}",
+                        amount, method_id
+                    )));
+                }
+                match execute_js(handle, &endpoint, &amount, &metadata).await {
+                    Ok(data) => {
+                        PaymentExecution::success(MethodId::new(method_id), endpoint, amount, data)
+                    }
+                    Err(e) => PaymentExecution::failure(
+                        MethodId::new(method_id),
+                        endpoint,
+                        amount,
+                        e.to_string(),
+                    ),
+                }
+            }
+            None => plugin
+                .execute_payment(&endpoint, &amount, &metadata)
+                .await
+                .map_err(|e| js_error(&e.to_string()))?,
+        };
+
+        to_js(&execution, "execution")
+    }
+
+    /// Generate a payment proof from the `execution_data` of a successful payment.
+    pub fn generate_proof(
+        &self,
+        method_id: &str,
+        endpoint: &str,
+        amount_sats: u64,
+        execution_data_json: &str,
+    ) -> Result<JsValue, JsValue> {
+        let plugin = self.plugin(method_id)?;
+        let data: serde_json::Value = serde_json::from_str(execution_data_json)
+            .map_err(|e| js_error(&format!("Invalid execution data: {}", e)))?;
+        let execution = PaymentExecution::success(
+            MethodId::new(method_id),
+            EndpointData(endpoint.to_string()),
+            Amount::sats(amount_sats),
+            data,
+        );
+        let proof = plugin
+            .generate_proof(&execution)
+            .map_err(|e| js_error(&e.to_string()))?;
+        to_js(&proof, "proof")
+    }
+}
+
+impl WasmMethodRegistry {
+    /// The underlying registry, shared with selectors.
+    pub fn registry(&self) -> &Arc<PaymentMethodRegistry> {
+        &self.registry
+    }
+
+    fn plugin(
+        &self,
+        method_id: &str,
+    ) -> Result<Arc<dyn paykit_lib::methods::PaymentMethodPlugin>, JsValue> {
+        self.registry
+            .get(&MethodId::new(method_id))
+            .ok_or_else(|| js_error(&format!("Payment method not registered: {}", method_id)))
+    }
+}
+
+/// Run a custom method's `execute` callback, awaiting it if it is async.
+async fn execute_js(
+    handle: u32,
+    endpoint: &EndpointData,
+    amount: &Amount,
+    metadata: &serde_json::Value,
+) -> paykit_lib::Result<serde_json::Value> {
+    let mut result = call_execute(&callbacks_for(handle)?, endpoint, amount, metadata)?;
+    if let Some(promise) = result.dyn_ref::<js_sys::Promise>() {
+        result = JsFuture::from(promise.clone())
+            .await
+            .map_err(js_call_error)?;
+    }
+    js_to_json(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn ledger_callbacks() -> JsValue {
+        js_sys::eval(
+            r#"({
+                validate: (e) => e.startsWith("acct:") ? [] : ["Expected acct: prefix"],
+                execute: async (e, sats, meta) => ({ transfer: e + ":" + sats }),
+                prove: (data) => data,
+            })"#,
+        )
+        .unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_method_weights() {
+        let registry = WasmMethodRegistry::new();
+        registry
+            .set_method_weight("onchain", 100.0, Some(5_000), None)
+            .unwrap();
+        let supported = r#"{"onchain":"bc1q...","lightning":"lnbc..."}"#;
+        let methods = registry.select_method(supported, 10_000).unwrap();
+        assert_eq!(methods[0].as_string().unwrap(), "onchain");
+
+        registry.load_config(r#"{"methods":{}}"#).unwrap();
+        let methods = registry.select_method(supported, 10_000).unwrap();
+        assert_eq!(methods[0].as_string().unwrap(), "lightning");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_register_rejects_builtin_ids() {
+        let registry = WasmMethodRegistry::new();
+        assert!(registry
+            .register_custom_method("onchain", "Fake", ledger_callbacks(), JsValue::UNDEFINED)
+            .is_err());
+        assert!(registry.unregister_custom_method("lightning").is_err());
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_custom_method_selection_and_execution() {
+        let registry = WasmMethodRegistry::new();
+        registry
+            .register_custom_method(
+                "org.example.ledger",
+                "Ledger",
+                ledger_callbacks(),
+                JsValue::UNDEFINED,
+            )
+            .unwrap();
+
+        assert!(registry
+            .validate_endpoint("org.example.ledger", "acct:42")
+            .unwrap());
+        assert!(!registry
+            .validate_endpoint("org.example.ledger", "bc1q")
+            .unwrap());
+
+        let methods = registry
+            .select_method(r#"{"org.example.ledger":"acct:42"}"#, 500)
+            .unwrap();
+        assert_eq!(methods[0].as_string().unwrap(), "org.example.ledger");
+
+        let execution = registry
+            .execute_payment("org.example.ledger", "acct:42", 500, None)
+            .await
+            .unwrap();
+        let success = js_sys::Reflect::get(&execution, &"success".into()).unwrap();
+        assert_eq!(success.as_bool(), Some(true));
+
+        assert!(registry
+            .unregister_custom_method("org.example.ledger")
+            .unwrap());
+        assert!(registry
+            .validate_endpoint("org.example.ledger", "acct:42")
+            .is_err());
+    }
+}
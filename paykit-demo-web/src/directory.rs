@@ -29,17 +29,19 @@
 //!
 //! // Publish a method endpoint
 //! const result = await client.publishEndpoint(methodId, endpoint, authToken);
+//!
+//! // White-label deployment: publish under /pub/acmepay.app/v0, and still
+//! // discover payees that publish under paykit.app
+//! client.setPathRoot("acmepay.app", true);
 //! ```
 
+use paykit_lib::protocol::{PathRoot, PathRootConfig};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::JsFuture;
 use web_sys::{Headers, RequestInit, RequestMode, Response};
 
 use crate::utils;
 
-/// Paykit directory path prefix
-const PAYKIT_PATH_PREFIX: &str = "/pub/paykit.app/v0/";
-
 /// Publishing mode for directory operations
 #[wasm_bindgen]
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
@@ -91,6 +93,7 @@ pub struct DirectoryClient {
     homeserver: String,
     proxy_url: Option<String>,
     mode: PublishMode,
+    roots: PathRootConfig,
 }
 
 #[wasm_bindgen]
@@ -109,6 +112,7 @@ impl DirectoryClient {
             homeserver,
             proxy_url: None,
             mode: PublishMode::Direct,
+            roots: PathRootConfig::default(),
         }
     }
 
@@ -125,6 +129,7 @@ impl DirectoryClient {
             homeserver,
             proxy_url: None,
             mode: PublishMode::Mock,
+            roots: PathRootConfig::default(),
         }
     }
 
@@ -140,6 +145,7 @@ impl DirectoryClient {
             homeserver,
             proxy_url: Some(proxy_url),
             mode: PublishMode::Proxy,
+            roots: PathRootConfig::default(),
         }
     }
 
@@ -156,6 +162,7 @@ impl DirectoryClient {
             homeserver,
             proxy_url: None,
             mode: PublishMode::Direct,
+            roots: PathRootConfig::default(),
         }
     }
 
@@ -183,6 +190,28 @@ impl DirectoryClient {
         self.proxy_url.clone()
     }
 
+    /// Use a white-label directory root instead of `paykit.app`
+    ///
+    /// Endpoints are published under `/pub/{app}/v0`. With
+    /// `fallback_to_paykit`, payees with nothing under `app` are also looked
+    /// up under `paykit.app`.
+    #[wasm_bindgen(js_name = setPathRoot)]
+    pub fn set_path_root(&mut self, app: &str, fallback_to_paykit: bool) -> Result<(), JsValue> {
+        let root = PathRoot::new(app).map_err(|e| utils::js_error(&e.to_string()))?;
+        self.roots = if fallback_to_paykit {
+            PathRootConfig::white_label(root)
+        } else {
+            PathRootConfig::new(root)
+        };
+        Ok(())
+    }
+
+    /// The directory root endpoints are published under
+    #[wasm_bindgen(getter, js_name = pathRoot)]
+    pub fn path_root(&self) -> String {
+        self.roots.root.to_string()
+    }
+
     /// Query payment methods for a public key
     ///
     /// This is a read-only operation that usually works without CORS issues.
@@ -190,20 +219,59 @@ impl DirectoryClient {
     pub async fn query_methods(&self, public_key: &str) -> Result<JsValue, JsValue> {
         utils::log(&format!("Querying methods for: {}", public_key));
 
-        // Construct the URL for the public directory
+        for root in self.roots.read_roots() {
+            if let Some(methods) = self.query_methods_in(public_key, root).await? {
+                return Ok(methods);
+            }
+        }
+
+        // No methods published under any root
+        Ok(js_sys::Array::new().into())
+    }
+
+    /// Fetch a specific payment endpoint for a public key and method
+    #[wasm_bindgen(js_name = fetchEndpoint)]
+    pub async fn fetch_endpoint(
+        &self,
+        public_key: &str,
+        method_id: &str,
+    ) -> Result<JsValue, JsValue> {
+        utils::log(&format!(
+            "Fetching endpoint for {} method {}",
+            public_key, method_id
+        ));
+
+        for root in self.roots.read_roots() {
+            let endpoint = self.fetch_endpoint_in(public_key, root, method_id).await?;
+            if !endpoint.is_null() {
+                return Ok(endpoint);
+            }
+        }
+        Ok(JsValue::NULL)
+    }
+
+    /// `{homeserver}/pub/{public_key}/{app}/v0/{file}`, through the proxy if set
+    fn directory_url(&self, public_key: &str, root: &PathRoot, file: &str) -> String {
         let url = format!(
-            "{}{}{}/",
+            "{}/pub/{}/{}/v0/{}",
             self.homeserver,
-            PAYKIT_PATH_PREFIX.replace("/pub/", &format!("/pub/{}/", public_key)),
-            ""
+            public_key,
+            root.app(),
+            file
         );
+        match self.proxy_url {
+            Some(ref proxy) => format!("{}/{}", proxy, url),
+            None => url,
+        }
+    }
 
-        // Use proxy if configured for queries too
-        let fetch_url = if let Some(ref proxy) = self.proxy_url {
-            format!("{}/{}", proxy, url)
-        } else {
-            format!("{}/pub/{}/paykit.app/v0/", self.homeserver, public_key)
-        };
+    /// Methods published under one root; `None` if there are none
+    async fn query_methods_in(
+        &self,
+        public_key: &str,
+        root: &PathRoot,
+    ) -> Result<Option<JsValue>, JsValue> {
+        let fetch_url = self.directory_url(public_key, root, "");
 
         // Make the fetch call
         let window = web_sys::window().ok_or_else(|| utils::js_error("No window object"))?;
@@ -217,8 +285,7 @@ impl DirectoryClient {
 
         if !resp.ok() {
             if resp.status() == 404 {
-                // No methods published - return empty array
-                return Ok(js_sys::Array::new().into());
+                return Ok(None);
             }
             return Err(utils::js_error(&format!("HTTP error: {}", resp.status())));
         }
@@ -228,31 +295,22 @@ impl DirectoryClient {
             .await
             .map_err(|_| utils::js_error("Failed to parse JSON"))?;
 
-        Ok(json)
+        let empty = if js_sys::Array::is_array(&json) {
+            js_sys::Array::from(&json).length() == 0
+        } else {
+            json.is_object() && js_sys::Object::keys(&json.clone().into()).length() == 0
+        };
+        Ok((!empty).then_some(json))
     }
 
-    /// Fetch a specific payment endpoint for a public key and method
-    #[wasm_bindgen(js_name = fetchEndpoint)]
-    pub async fn fetch_endpoint(
+    /// One endpoint under one root; null if it is not published there
+    async fn fetch_endpoint_in(
         &self,
         public_key: &str,
+        root: &PathRoot,
         method_id: &str,
     ) -> Result<JsValue, JsValue> {
-        utils::log(&format!(
-            "Fetching endpoint for {} method {}",
-            public_key, method_id
-        ));
-
-        let url = format!(
-            "{}/pub/{}/paykit.app/v0/{}",
-            self.homeserver, public_key, method_id
-        );
-
-        let fetch_url = if let Some(ref proxy) = self.proxy_url {
-            format!("{}/{}", proxy, url)
-        } else {
-            url
-        };
+        let fetch_url = self.directory_url(public_key, root, method_id);
 
         let window = web_sys::window().ok_or_else(|| utils::js_error("No window object"))?;
         let resp_value = JsFuture::from(window.fetch_with_str(&fetch_url))
@@ -293,42 +351,13 @@ impl DirectoryClient {
     ) -> Result<Vec<JsValue>, JsValue> {
         utils::log(&format!("Listing directory for {} at {}", public_key, path));
 
-        let url = format!("{}/pub/{}{}", self.homeserver, public_key, path);
-
-        let fetch_url = if let Some(ref proxy) = self.proxy_url {
-            format!("{}/{}", proxy, url)
-        } else {
-            url
-        };
-
-        let window = web_sys::window().ok_or_else(|| utils::js_error("No window object"))?;
-        let resp_value = JsFuture::from(window.fetch_with_str(&fetch_url))
-            .await
-            .map_err(|e| utils::js_error(&format!("Fetch failed: {:?}", e)))?;
-
-        let resp: Response = resp_value
-            .dyn_into()
-            .map_err(|_| utils::js_error("Failed to cast to Response"))?;
-
-        if !resp.ok() {
-            if resp.status() == 404 {
-                return Ok(Vec::new());
+        for path in self.roots.read_paths(path) {
+            let entries = self.list_directory_in(public_key, &path).await?;
+            if !entries.is_empty() {
+                return Ok(entries);
             }
-            return Err(utils::js_error(&format!("HTTP error: {}", resp.status())));
-        }
-
-        // Try to parse as JSON array
-        let json = JsFuture::from(resp.json().map_err(|_| utils::js_error("No JSON method"))?)
-            .await
-            .map_err(|_| utils::js_error("Failed to parse JSON"))?;
-
-        // Convert JSON array to Vec<JsValue>
-        if js_sys::Array::is_array(&json) {
-            let arr: js_sys::Array = json.into();
-            Ok(arr.iter().collect())
-        } else {
-            Ok(Vec::new())
         }
+        Ok(Vec::new())
     }
 
     /// Get payment methods list for a public key
@@ -509,8 +538,11 @@ impl DirectoryClient {
         auth_token: Option<String>,
     ) -> Result<PublishResult, JsValue> {
         let url = format!(
-            "{}/pub/{}/paykit.app/v0/{}",
-            self.homeserver, public_key, method_id
+            "{}/pub/{}/{}/v0/{}",
+            self.homeserver,
+            public_key,
+            self.roots.root.app(),
+            method_id
         );
 
         self.http_put(&url, endpoint, auth_token, PublishMode::Direct)
@@ -530,8 +562,11 @@ impl DirectoryClient {
             .ok_or_else(|| utils::js_error("Proxy mode requires proxy_url to be set"))?;
 
         let target_url = format!(
-            "{}/pub/{}/paykit.app/v0/{}",
-            self.homeserver, public_key, method_id
+            "{}/pub/{}/{}/v0/{}",
+            self.homeserver,
+            public_key,
+            self.roots.root.app(),
+            method_id
         );
         let url = format!("{}/{}", proxy, target_url);
 
@@ -546,8 +581,11 @@ impl DirectoryClient {
         auth_token: Option<String>,
     ) -> Result<PublishResult, JsValue> {
         let url = format!(
-            "{}/pub/{}/paykit.app/v0/{}",
-            self.homeserver, public_key, method_id
+            "{}/pub/{}/{}/v0/{}",
+            self.homeserver,
+            public_key,
+            self.roots.root.app(),
+            method_id
         );
 
         self.http_delete(&url, auth_token, PublishMode::Direct)
@@ -566,8 +604,11 @@ impl DirectoryClient {
             .ok_or_else(|| utils::js_error("Proxy mode requires proxy_url to be set"))?;
 
         let target_url = format!(
-            "{}/pub/{}/paykit.app/v0/{}",
-            self.homeserver, public_key, method_id
+            "{}/pub/{}/{}/v0/{}",
+            self.homeserver,
+            public_key,
+            self.roots.root.app(),
+            method_id
         );
         let url = format!("{}/{}", proxy, target_url);
 
@@ -877,6 +918,62 @@ impl DirectoryClient {
     }
 }
 
+impl DirectoryClient {
+    /// The roots paths are read and written under
+    pub(crate) fn path_roots(&self) -> &PathRootConfig {
+        &self.roots
+    }
+
+    /// Read and write under `roots` instead of the current roots
+    pub(crate) fn set_path_roots(&mut self, roots: PathRootConfig) {
+        self.roots = roots;
+    }
+
+    /// List one directory, already moved into a root
+    async fn list_directory_in(
+        &self,
+        public_key: &str,
+        path: &str,
+    ) -> Result<Vec<JsValue>, JsValue> {
+        let url = format!("{}/pub/{}{}", self.homeserver, public_key, path);
+
+        let fetch_url = if let Some(ref proxy) = self.proxy_url {
+            format!("{}/{}", proxy, url)
+        } else {
+            url
+        };
+
+        let window = web_sys::window().ok_or_else(|| utils::js_error("No window object"))?;
+        let resp_value = JsFuture::from(window.fetch_with_str(&fetch_url))
+            .await
+            .map_err(|e| utils::js_error(&format!("Fetch failed: {:?}", e)))?;
+
+        let resp: Response = resp_value
+            .dyn_into()
+            .map_err(|_| utils::js_error("Failed to cast to Response"))?;
+
+        if !resp.ok() {
+            if resp.status() == 404 {
+                return Ok(Vec::new());
+            }
+            return Err(utils::js_error(&format!("HTTP error: {}", resp.status())));
+        }
+
+        // Try to parse as JSON array
+        let json = JsFuture::from(resp.json().map_err(|_| utils::js_error("No JSON method"))?)
+            .await
+            .map_err(|_| utils::js_error("Failed to parse JSON"))?;
+
+        // Convert JSON array to Vec<JsValue>
+        if js_sys::Array::is_array(&json) {
+            let arr: js_sys::Array = json.into();
+            Ok(arr.iter().collect())
+        } else {
+            Ok(Vec::new())
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,13 +1,19 @@
 //! Identity management for WASM
 
+use paykit_lib::backup::{KdfParams, KeyBackup};
 use paykit_lib::PublicKey;
 use pkarr::Keypair;
 use pubky_noise::RingKeyProvider;
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 use wasm_bindgen::prelude::*;
+use web_sys::CryptoKey;
 
+use crate::indexeddb_storage::{self, KEYS_STORE};
+use crate::subscriptions::{WasmSignedSubscription, WasmSubscription};
+use crate::types::{verify_ed25519, PaykitReceipt, SignedSubscription};
 use crate::utils;
+use crate::webcrypto::{self, KeyHandle};
 
 /// Represents a user's identity for WASM
 #[derive(Clone, Serialize, Deserialize)]
@@ -67,16 +73,6 @@ impl CoreIdentity {
     }
 }
 
-/// Encrypted backup format for identity
-#[derive(Clone, Serialize, Deserialize)]
-struct EncryptedBackup {
-    version: u32,
-    encrypted_data_hex: String,
-    salt_hex: String,
-    nonce_hex: String,
-    public_key_z32: String,
-}
-
 /// JavaScript-facing identity wrapper
 #[wasm_bindgen]
 #[derive(Clone)]
@@ -134,119 +130,21 @@ impl Identity {
 
     /// Export identity to encrypted backup
     ///
-    /// Uses Argon2 for password-based key derivation and AES-256-GCM for encryption.
-    /// Returns a JSON string containing the encrypted backup.
+    /// Uses Argon2id for password-based key derivation and AES-256-GCM for encryption.
+    /// Returns armored text in the backup format shared with the CLI and mobile apps.
     #[wasm_bindgen(js_name = exportBackup)]
     pub fn export_backup(&self, password: &str) -> Result<String, JsValue> {
-        use aes_gcm::{
-            aead::{Aead, KeyInit},
-            Aes256Gcm, Nonce,
-        };
-        use argon2::Argon2;
-        use rand::RngCore;
-
-        let secret_bytes = self.inner.keypair.secret_key();
-
-        // Derive encryption key from password using Argon2
-        let mut salt = [0u8; 16];
-        rand::rngs::OsRng.fill_bytes(&mut salt);
-
-        let mut encryption_key = [0u8; 32];
-        Argon2::default()
-            .hash_password_into(password.as_bytes(), &salt, &mut encryption_key)
-            .map_err(|e| utils::js_error(&format!("Key derivation failed: {}", e)))?;
-
-        // Encrypt with AES-GCM
-        let cipher = Aes256Gcm::new_from_slice(&encryption_key)
-            .map_err(|e| utils::js_error(&format!("Cipher init failed: {}", e)))?;
-
-        let mut nonce_bytes = [0u8; 12];
-        rand::rngs::OsRng.fill_bytes(&mut nonce_bytes);
-        let nonce = Nonce::from_slice(&nonce_bytes);
-
-        let encrypted = cipher
-            .encrypt(nonce, secret_bytes.as_ref())
-            .map_err(|e| utils::js_error(&format!("Encryption failed: {}", e)))?;
-
-        let backup = EncryptedBackup {
-            version: 1,
-            encrypted_data_hex: hex::encode(encrypted),
-            salt_hex: hex::encode(salt),
-            nonce_hex: hex::encode(nonce_bytes),
-            public_key_z32: self.public_key(),
-        };
-
-        serde_json::to_string(&backup)
-            .map_err(|e| utils::js_error(&format!("Serialization failed: {}", e)))
+        seal_backup(&self.inner.keypair, password)
     }
 
     /// Import identity from encrypted backup
     ///
-    /// Uses Argon2 for password-based key derivation and AES-256-GCM for decryption.
+    /// Accepts armored backups and the JSON format written by older versions.
     #[wasm_bindgen(js_name = importBackup)]
-    pub fn import_backup(backup_json: &str, password: &str) -> Result<Identity, JsValue> {
-        use aes_gcm::{
-            aead::{Aead, KeyInit},
-            Aes256Gcm, Nonce,
-        };
-        use argon2::Argon2;
-
-        let backup: EncryptedBackup = serde_json::from_str(backup_json)
-            .map_err(|e| utils::js_error(&format!("Invalid backup format: {}", e)))?;
-
-        if backup.version != 1 {
-            return Err(utils::js_error(&format!(
-                "Unsupported backup version: {}",
-                backup.version
-            )));
-        }
-
-        let salt = hex::decode(&backup.salt_hex)
-            .map_err(|e| utils::js_error(&format!("Invalid salt: {}", e)))?;
-        let nonce_bytes = hex::decode(&backup.nonce_hex)
-            .map_err(|e| utils::js_error(&format!("Invalid nonce: {}", e)))?;
-        let encrypted = hex::decode(&backup.encrypted_data_hex)
-            .map_err(|e| utils::js_error(&format!("Invalid encrypted data: {}", e)))?;
-
-        // Derive encryption key from password
-        let mut encryption_key = [0u8; 32];
-        Argon2::default()
-            .hash_password_into(password.as_bytes(), &salt, &mut encryption_key)
-            .map_err(|e| utils::js_error(&format!("Key derivation failed: {}", e)))?;
-
-        // Decrypt with AES-GCM
-        let cipher = Aes256Gcm::new_from_slice(&encryption_key)
-            .map_err(|e| utils::js_error(&format!("Cipher init failed: {}", e)))?;
-
-        if nonce_bytes.len() != 12 {
-            return Err(utils::js_error("Invalid nonce length"));
-        }
-        let nonce = Nonce::from_slice(&nonce_bytes);
-
-        let decrypted = cipher
-            .decrypt(nonce, encrypted.as_ref())
-            .map_err(|_| utils::js_error("Decryption failed - wrong password or corrupted data"))?;
-
-        if decrypted.len() != 32 {
-            return Err(utils::js_error("Invalid decrypted data length"));
-        }
-
-        let mut secret_key = [0u8; 32];
-        secret_key.copy_from_slice(&decrypted);
-
-        let keypair = Keypair::from_secret_key(&secret_key);
-
-        // Verify public key matches
-        let derived_public_key = keypair.public_key().to_z32();
-        if derived_public_key != backup.public_key_z32 {
-            return Err(utils::js_error(
-                "Public key mismatch - backup may be corrupted",
-            ));
-        }
-
+    pub fn import_backup(backup: &str, password: &str) -> Result<Identity, JsValue> {
         Ok(Identity {
             inner: CoreIdentity {
-                keypair,
+                keypair: open_backup(backup, password)?,
                 nickname: None,
             },
         })
@@ -267,22 +165,332 @@ impl Identity {
     }
 }
 
-/// WASM-compatible key provider for Noise protocol
+/// Seal `keypair`'s secret in the shared armored backup format
+fn seal_backup(keypair: &Keypair, password: &str) -> Result<String, JsValue> {
+    let backup = KeyBackup::seal(
+        &keypair.secret_key(),
+        &keypair.public_key().to_z32(),
+        password,
+        KdfParams::default(),
+    )
+    .map_err(|e| utils::js_error(&format!("Backup failed: {}", e)))?;
+    backup
+        .to_armored()
+        .map_err(|e| utils::js_error(&format!("Backup failed: {}", e)))
+}
+
+/// Open a backup written by `seal_backup` or an older JSON backup
+fn open_backup(backup: &str, password: &str) -> Result<Keypair, JsValue> {
+    let backup = KeyBackup::decode(backup)
+        .map_err(|e| utils::js_error(&format!("Invalid backup format: {}", e)))?;
+    let secret_key = backup
+        .open(password)
+        .map_err(|_| utils::js_error("Decryption failed - wrong password or corrupted data"))?;
+
+    let keypair = Keypair::from_secret_key(&secret_key);
+
+    // Verify public key matches
+    let derived_public_key = keypair.public_key().to_z32();
+    if derived_public_key != backup.public_key_z32() {
+        return Err(utils::js_error(
+            "Public key mismatch - backup may be corrupted",
+        ));
+    }
+
+    Ok(keypair)
+}
+
+/// Key provider for Noise and for signing payloads in the browser
 ///
-/// This wraps a pkarr Keypair and provides key derivation for Noise.
+/// Keys come from an [`Identity`] or from WebCrypto. A WebCrypto key
+/// created or imported as non-extractable never leaves the browser's key
+/// store: it can sign payloads, but can't be backed up or used for Noise
+/// handshakes, which derive X25519 keys from the Ed25519 secret.
+#[wasm_bindgen]
 pub struct WasmKeyProvider {
-    keypair: Keypair,
+    /// Secret in wasm memory; `None` for non-extractable WebCrypto keys
+    keypair: Option<Keypair>,
+    public_key: pkarr::PublicKey,
+    /// Private key in WebCrypto, when WebCrypto holds the key
+    crypto_key: Option<KeyHandle>,
 }
 
 impl WasmKeyProvider {
     pub fn new(keypair: Keypair) -> Self {
-        Self { keypair }
+        Self {
+            public_key: keypair.public_key(),
+            keypair: Some(keypair),
+            crypto_key: None,
+        }
     }
 
+    /// Import `keypair` into WebCrypto, keeping it in wasm memory as well
+    /// only if `extractable`
+    ///
+    /// Falls back to a software key if the browser lacks WebCrypto Ed25519.
+    async fn import(keypair: Keypair, extractable: bool) -> Self {
+        match webcrypto::import_seed(&keypair.secret_key(), extractable).await {
+            Ok(key) => Self {
+                public_key: keypair.public_key(),
+                keypair: extractable.then_some(keypair),
+                crypto_key: Some(KeyHandle::new(key)),
+            },
+            Err(e) => {
+                utils::log(&format!("WebCrypto Ed25519 unavailable: {:?}", e));
+                Self::new(keypair)
+            }
+        }
+    }
+
+    /// Build a provider around a WebCrypto private key
+    async fn from_crypto_key(
+        key: CryptoKey,
+        public_key: pkarr::PublicKey,
+    ) -> Result<Self, JsValue> {
+        let keypair = if key.extractable() {
+            let keypair = Keypair::from_secret_key(&webcrypto::export_seed(&key).await?);
+            if keypair.public_key() != public_key {
+                return Err(utils::js_error("Stored key does not match its public key"));
+            }
+            Some(keypair)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            keypair,
+            public_key,
+            crypto_key: Some(KeyHandle::new(key)),
+        })
+    }
+
+    fn keypair(&self) -> Result<&Keypair, JsValue> {
+        self.keypair
+            .as_ref()
+            .ok_or_else(|| utils::js_error("Key is not extractable"))
+    }
+
+    fn paykit_public_key(&self) -> PublicKey {
+        PublicKey::from_str(&self.public_key.to_z32()).unwrap()
+    }
+
+    async fn sign_bytes(&self, message: &[u8]) -> Result<[u8; 64], JsValue> {
+        match &self.crypto_key {
+            Some(handle) => webcrypto::sign(&handle.key()?, message).await,
+            None => Ok(self.keypair()?.sign(message).to_bytes()),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl WasmKeyProvider {
+    /// Generate a new key, in WebCrypto where the browser supports Ed25519
+    ///
+    /// Pass `extractable = false` to keep the secret out of reach of
+    /// JavaScript; such keys can sign but not be exported or used for Noise.
+    pub async fn generate(extractable: bool) -> Result<WasmKeyProvider, JsValue> {
+        match webcrypto::generate(extractable).await {
+            Ok((key, public_key)) => {
+                let public_key = pkarr::PublicKey::try_from(&public_key)
+                    .map_err(|e| utils::js_error(&format!("Invalid public key: {}", e)))?;
+                Self::from_crypto_key(key, public_key).await
+            }
+            Err(e) => {
+                utils::log(&format!("WebCrypto Ed25519 unavailable: {:?}", e));
+                Ok(Self::new(Keypair::random()))
+            }
+        }
+    }
+
+    /// Key provider for an identity's key
+    #[wasm_bindgen(js_name = fromIdentity)]
     pub fn from_identity(identity: &Identity) -> Self {
-        Self {
-            keypair: identity.inner.keypair.clone(),
+        Self::new(identity.inner.keypair.clone())
+    }
+
+    /// Import a key from an encrypted backup into WebCrypto
+    ///
+    /// Accepts the backup format shared with `Identity.exportBackup`, the
+    /// CLI and mobile apps.
+    #[wasm_bindgen(js_name = importBackup)]
+    pub async fn import_backup(
+        backup: &str,
+        password: &str,
+        extractable: bool,
+    ) -> Result<WasmKeyProvider, JsValue> {
+        let keypair = open_backup(backup, password)?;
+        Ok(Self::import(keypair, extractable).await)
+    }
+
+    /// Export the key to an encrypted backup
+    ///
+    /// Fails for non-extractable keys.
+    #[wasm_bindgen(js_name = exportBackup)]
+    pub fn export_backup(&self, password: &str) -> Result<String, JsValue> {
+        seal_backup(self.keypair()?, password)
+    }
+
+    /// Identity for this key, for Noise payment flows
+    ///
+    /// Fails for non-extractable keys.
+    #[wasm_bindgen(js_name = toIdentity)]
+    pub fn to_identity(&self) -> Result<Identity, JsValue> {
+        Ok(Identity {
+            inner: CoreIdentity {
+                keypair: self.keypair()?.clone(),
+                nickname: None,
+            },
+        })
+    }
+
+    /// Get the public key (z-base-32)
+    #[wasm_bindgen(js_name = publicKey)]
+    pub fn public_key(&self) -> String {
+        self.public_key.to_z32()
+    }
+
+    /// Whether WebCrypto holds the key
+    #[wasm_bindgen(js_name = isWebCrypto)]
+    pub fn is_web_crypto(&self) -> bool {
+        self.crypto_key.is_some()
+    }
+
+    /// Whether the secret can be exported
+    #[wasm_bindgen(js_name = isExtractable)]
+    pub fn is_extractable(&self) -> bool {
+        self.keypair.is_some()
+    }
+
+    /// Sign raw bytes, returning the 64-byte Ed25519 signature
+    pub async fn sign(&self, message: &[u8]) -> Result<Vec<u8>, JsValue> {
+        Ok(self.sign_bytes(message).await?.to_vec())
+    }
+
+    /// Sign a subscription as its subscriber
+    #[wasm_bindgen(js_name = signSubscription)]
+    pub async fn sign_subscription(
+        &self,
+        subscription: &WasmSubscription,
+    ) -> Result<WasmSignedSubscription, JsValue> {
+        let subscription = subscription.inner.clone();
+        if subscription.subscriber != self.paykit_public_key() {
+            return Err(utils::js_error(
+                "Only the subscriber can sign a subscription",
+            ));
         }
+
+        let payload = subscription
+            .signing_payload()
+            .map_err(|e| utils::js_error(&e))?;
+        let signature = self.sign_bytes(&payload).await?;
+        Ok(WasmSignedSubscription {
+            inner: SignedSubscription {
+                subscription,
+                subscriber_signature: signature.to_vec(),
+                provider_signature: None,
+            },
+        })
+    }
+
+    /// Countersign a subscriber-signed subscription as its provider
+    #[wasm_bindgen(js_name = countersignSubscription)]
+    pub async fn countersign_subscription(
+        &self,
+        signed: &WasmSignedSubscription,
+    ) -> Result<WasmSignedSubscription, JsValue> {
+        let mut signed = signed.inner.clone();
+        if signed.subscription.provider != self.paykit_public_key() {
+            return Err(utils::js_error(
+                "Only the provider can countersign a subscription",
+            ));
+        }
+
+        let payload = signed
+            .subscription
+            .signing_payload()
+            .map_err(|e| utils::js_error(&e))?;
+        if !verify_ed25519(
+            &signed.subscription.subscriber,
+            &payload,
+            &signed.subscriber_signature,
+        ) {
+            return Err(utils::js_error("Invalid subscriber signature"));
+        }
+
+        signed.provider_signature = Some(self.sign_bytes(&payload).await?.to_vec());
+        Ok(WasmSignedSubscription { inner: signed })
+    }
+
+    /// Sign a receipt as its payer or payee, returning the signature as hex
+    #[wasm_bindgen(js_name = signReceipt)]
+    pub async fn sign_receipt(&self, receipt_json: &str) -> Result<String, JsValue> {
+        let receipt: PaykitReceipt = serde_json::from_str(receipt_json)
+            .map_err(|e| utils::js_error(&format!("Invalid receipt: {}", e)))?;
+        let me = self.paykit_public_key();
+        if receipt.payer != me && receipt.payee != me {
+            return Err(utils::js_error(
+                "Only the payer or payee can sign a receipt",
+            ));
+        }
+
+        let payload = receipt.signing_payload().map_err(|e| utils::js_error(&e))?;
+        Ok(hex::encode(self.sign_bytes(&payload).await?))
+    }
+
+    /// Check a receipt signature made with `signReceipt`
+    #[wasm_bindgen(js_name = verifyReceipt)]
+    pub fn verify_receipt(
+        receipt_json: &str,
+        signature_hex: &str,
+        signer: &str,
+    ) -> Result<bool, JsValue> {
+        let receipt: PaykitReceipt = serde_json::from_str(receipt_json)
+            .map_err(|e| utils::js_error(&format!("Invalid receipt: {}", e)))?;
+        let signer = PublicKey::from_str(signer)
+            .map_err(|e| utils::js_error(&format!("Invalid public key: {}", e)))?;
+        let signature = hex::decode(signature_hex)
+            .map_err(|e| utils::js_error(&format!("Invalid signature hex: {}", e)))?;
+
+        let payload = receipt.signing_payload().map_err(|e| utils::js_error(&e))?;
+        Ok(verify_ed25519(&signer, &payload, &signature))
+    }
+
+    /// Save a WebCrypto key in IndexedDB under `name`
+    ///
+    /// The `CryptoKey` is stored as-is, so a non-extractable key stays
+    /// non-extractable. Keys in wasm memory are saved as identities with
+    /// `BrowserStorage` instead.
+    pub async fn save(&self, name: &str) -> Result<(), JsValue> {
+        let handle = self
+            .crypto_key
+            .as_ref()
+            .ok_or_else(|| utils::js_error("Only WebCrypto keys can be saved"))?;
+
+        let record = js_sys::Object::new();
+        js_sys::Reflect::set(&record, &"public_key".into(), &self.public_key().into())?;
+        js_sys::Reflect::set(&record, &"private_key".into(), &handle.key()?)?;
+        indexeddb_storage::put_record(KEYS_STORE, name, &record).await
+    }
+
+    /// Load a key saved with `save`
+    pub async fn load(name: &str) -> Result<Option<WasmKeyProvider>, JsValue> {
+        let Some(record) = indexeddb_storage::get_record(KEYS_STORE, name).await? else {
+            return Ok(None);
+        };
+
+        let key: CryptoKey = js_sys::Reflect::get(&record, &"private_key".into())?.dyn_into()?;
+        let public_key = js_sys::Reflect::get(&record, &"public_key".into())?
+            .as_string()
+            .ok_or_else(|| utils::js_error("Stored key has no public key"))?;
+        let public_key = pkarr::PublicKey::try_from(public_key.as_str())
+            .map_err(|e| utils::js_error(&format!("Invalid public key: {}", e)))?;
+
+        Self::from_crypto_key(key, public_key).await.map(Some)
+    }
+
+    /// Delete a key saved with `save`
+    pub async fn remove(name: &str) -> Result<(), JsValue> {
+        indexeddb_storage::delete_record(KEYS_STORE, name).await
     }
 }
 
@@ -294,12 +502,12 @@ impl RingKeyProvider for WasmKeyProvider {
         epoch: u32,
     ) -> std::result::Result<[u8; 32], pubky_noise::NoiseError> {
         // Use pubky-noise's KDF for deterministic key derivation
-        let secret = self.keypair.secret_key();
+        let secret = self.noise_keypair()?.secret_key();
         pubky_noise::kdf::derive_x25519_for_device_epoch(&secret, device_id, epoch)
     }
 
     fn ed25519_pubkey(&self, _kid: &str) -> std::result::Result<[u8; 32], pubky_noise::NoiseError> {
-        Ok(self.keypair.public_key().to_bytes())
+        Ok(self.public_key.to_bytes())
     }
 
     fn sign_ed25519(
@@ -307,7 +515,15 @@ impl RingKeyProvider for WasmKeyProvider {
         _kid: &str,
         msg: &[u8],
     ) -> std::result::Result<[u8; 64], pubky_noise::NoiseError> {
-        Ok(self.keypair.sign(msg).to_bytes())
+        Ok(self.noise_keypair()?.sign(msg).to_bytes())
+    }
+}
+
+impl WasmKeyProvider {
+    fn noise_keypair(&self) -> std::result::Result<&Keypair, pubky_noise::NoiseError> {
+        self.keypair.as_ref().ok_or_else(|| {
+            pubky_noise::NoiseError::Ring("Non-extractable keys can't be used for Noise".into())
+        })
     }
 }
 
@@ -337,4 +553,46 @@ mod tests {
         assert_eq!(identity.public_key(), restored.public_key());
         assert_eq!(identity.nickname(), restored.nickname());
     }
+
+    #[wasm_bindgen_test]
+    async fn test_key_provider_shares_identity_backup_format() {
+        let identity = Identity::new().unwrap();
+        let backup = identity.export_backup("correct horse").unwrap();
+
+        let provider = WasmKeyProvider::import_backup(&backup, "correct horse", true)
+            .await
+            .unwrap();
+        assert_eq!(provider.public_key(), identity.public_key());
+
+        let exported = provider.export_backup("correct horse").unwrap();
+        let restored = Identity::import_backup(&exported, "correct horse").unwrap();
+        assert_eq!(restored.public_key(), identity.public_key());
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_non_extractable_key_signs_receipts() {
+        let provider = WasmKeyProvider::generate(false).await.unwrap();
+        let payee = Identity::new().unwrap();
+        let receipt = PaykitReceipt::new(
+            "receipt_1".to_string(),
+            PublicKey::from_str(&provider.public_key()).unwrap(),
+            PublicKey::from_str(&payee.public_key()).unwrap(),
+            paykit_lib::MethodId("lightning".to_string()),
+            Some("1000".to_string()),
+            Some("SAT".to_string()),
+            serde_json::json!({}),
+        );
+        let json = serde_json::to_string(&receipt).unwrap();
+
+        let signature = provider.sign_receipt(&json).await.unwrap();
+        assert!(
+            WasmKeyProvider::verify_receipt(&json, &signature, &provider.public_key()).unwrap()
+        );
+        assert!(!WasmKeyProvider::verify_receipt(&json, &signature, &payee.public_key()).unwrap());
+
+        // Browsers without WebCrypto Ed25519 fall back to a software key
+        if provider.is_web_crypto() {
+            assert!(provider.export_backup("correct horse").is_err());
+        }
+    }
 }
@@ -0,0 +1,672 @@
+//! IndexedDB storage for receipts and contacts
+//!
+//! localStorage is synchronous, shared with every other `paykit_*` key and
+//! capped at a few megabytes, which a busy receipt history outgrows. This
+//! module keeps receipts and contacts in an IndexedDB database instead.
+//!
+//! # Storage Schema
+//!
+//! Database `paykit_demo`, currently at [`SCHEMA_VERSION`]:
+//! - `receipts` - `{ json, created_at }` keyed by receipt ID, indexed by
+//!   `created_at` so listing and eviction run oldest first
+//! - `contacts` - `{ json, name }` keyed by public key, indexed by the
+//!   lowercased `name` so listing is alphabetical
+//! - `keys` - WebCrypto identity keys saved by `WasmKeyProvider`, keyed by
+//!   name; `CryptoKey` objects are stored as-is, so non-extractable keys
+//!   stay non-extractable
+//!
+//! Schema changes are applied in `onupgradeneeded` by running every step in
+//! [`MIGRATIONS`] above the version the browser already has. Shipped steps
+//! must never change; add a new one and bump the version instead. Data left
+//! in localStorage by older releases is moved over by
+//! `StorageMigration.migrateToIndexedDb()`.
+//!
+//! # Quota
+//!
+//! Before each receipt write the store checks `navigator.storage.estimate()`
+//! and, above its usage ratio, evicts the oldest receipts. A write that
+//! still fails with `QuotaExceededError` evicts a batch and retries once.
+//! Contacts are never evicted.
+//!
+//! # Examples
+//!
+//! ```
+//! use paykit_demo_web::WasmIndexedDbReceiptStorage;
+//! use wasm_bindgen_test::*;
+//!
+//! wasm_bindgen_test_configure!(run_in_browser);
+//!
+//! #[wasm_bindgen_test]
+//! async fn example_receipt_usage() {
+//!     let storage = WasmIndexedDbReceiptStorage::new();
+//!     storage.save_receipt("receipt_1", "{}").await.unwrap();
+//!     assert!(storage.get_receipt("receipt_1").await.unwrap().is_some());
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    IdbDatabase, IdbObjectStore, IdbOpenDbRequest, IdbRequest, IdbTransaction, IdbTransactionMode,
+    IdbVersionChangeEvent,
+};
+
+use crate::contacts::{WasmContact, WasmContactStorage};
+use crate::payment::WasmReceiptStorage;
+
+const DB_NAME: &str = "paykit_demo";
+const RECEIPTS_STORE: &str = "receipts";
+const CONTACTS_STORE: &str = "contacts";
+pub(crate) const KEYS_STORE: &str = "keys";
+const CREATED_AT_INDEX: &str = "created_at";
+const NAME_INDEX: &str = "name";
+
+/// Current version of the `paykit_demo` database schema
+pub const SCHEMA_VERSION: u32 = 3;
+
+/// Fraction of the origin's quota receipts may fill before eviction
+const DEFAULT_MAX_USAGE_RATIO: f64 = 0.8;
+
+/// Receipts evicted per pass when over quota
+const EVICTION_BATCH: u32 = 50;
+
+type Migration = fn(&IdbDatabase, &IdbTransaction) -> Result<(), JsValue>;
+
+/// Schema steps; entry `n` upgrades version `n` to `n + 1`
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] =
+    [create_stores, create_indexes, create_key_store];
+
+/// v1: object stores
+fn create_stores(db: &IdbDatabase, _: &IdbTransaction) -> Result<(), JsValue> {
+    db.create_object_store(RECEIPTS_STORE)?;
+    db.create_object_store(CONTACTS_STORE)?;
+    Ok(())
+}
+
+/// v2: ordering indexes
+fn create_indexes(_: &IdbDatabase, tx: &IdbTransaction) -> Result<(), JsValue> {
+    tx.object_store(RECEIPTS_STORE)?
+        .create_index_with_str(CREATED_AT_INDEX, "created_at")?;
+    tx.object_store(CONTACTS_STORE)?
+        .create_index_with_str(NAME_INDEX, "name")?;
+    Ok(())
+}
+
+/// v3: identity keys
+fn create_key_store(db: &IdbDatabase, _: &IdbTransaction) -> Result<(), JsValue> {
+    db.create_object_store(KEYS_STORE)?;
+    Ok(())
+}
+
+/// Receipt persistence, shaped like the receipt half of the native
+/// `DemoStorage`
+#[async_trait::async_trait(?Send)]
+pub trait ReceiptStore {
+    /// Save a receipt's JSON, replacing any receipt with the same ID
+    async fn save_receipt_json(&self, id: &str, json: &str) -> Result<(), JsValue>;
+
+    /// Get a receipt's JSON by ID
+    async fn get_receipt_json(&self, id: &str) -> Result<Option<String>, JsValue>;
+
+    /// JSON of every stored receipt
+    async fn list_receipt_jsons(&self) -> Result<Vec<String>, JsValue>;
+
+    /// Delete a receipt by ID
+    async fn remove_receipt(&self, id: &str) -> Result<(), JsValue>;
+}
+
+/// Contact persistence, shaped like the contact half of the native
+/// `DemoStorage`
+#[async_trait::async_trait(?Send)]
+pub trait ContactStore {
+    /// Save a contact, replacing any contact with the same public key
+    async fn store_contact(&self, contact: &WasmContact) -> Result<(), JsValue>;
+
+    /// Get a contact by public key
+    async fn load_contact(&self, public_key: &str) -> Result<Option<WasmContact>, JsValue>;
+
+    /// Every stored contact
+    async fn all_contacts(&self) -> Result<Vec<WasmContact>, JsValue>;
+
+    /// Delete a contact by public key
+    async fn remove_contact(&self, public_key: &str) -> Result<(), JsValue>;
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredReceipt {
+    json: String,
+    created_at: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredContact {
+    json: String,
+    name: String,
+}
+
+/// Receipt storage in browser IndexedDB
+///
+/// Same core API as `WasmReceiptStorage`, with receipts listed oldest first
+/// and evicted oldest first when the origin runs short of quota.
+#[wasm_bindgen]
+pub struct WasmIndexedDbReceiptStorage {
+    max_usage_ratio: f64,
+}
+
+impl Default for WasmIndexedDbReceiptStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl WasmIndexedDbReceiptStorage {
+    /// Create new receipt storage
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self {
+            max_usage_ratio: DEFAULT_MAX_USAGE_RATIO,
+        }
+    }
+
+    /// Set the fraction of the origin's quota (0 to 1) above which the
+    /// oldest receipts are evicted
+    pub fn set_max_usage_ratio(&mut self, ratio: f64) -> Result<(), JsValue> {
+        if !(ratio > 0.0 && ratio <= 1.0) {
+            return Err(JsValue::from_str("Usage ratio must be between 0 and 1"));
+        }
+        self.max_usage_ratio = ratio;
+        Ok(())
+    }
+
+    /// Save a receipt, evicting old receipts first if storage is nearly full
+    pub async fn save_receipt(&self, receipt_id: &str, receipt_json: &str) -> Result<(), JsValue> {
+        if let Some((usage, quota)) = storage_estimate().await? {
+            if quota > 0.0 && usage / quota > self.max_usage_ratio {
+                self.evict_oldest(EVICTION_BATCH).await?;
+            }
+        }
+
+        match put_receipt(receipt_id, receipt_json).await {
+            Err(e) if is_quota_error(&e) => {
+                if self.evict_oldest(EVICTION_BATCH).await? == 0 {
+                    return Err(e);
+                }
+                put_receipt(receipt_id, receipt_json).await
+            }
+            result => result,
+        }
+        .map_err(|e| JsValue::from_str(&format!("Failed to save receipt: {:?}", e)))
+    }
+
+    /// Get a receipt by ID
+    pub async fn get_receipt(&self, receipt_id: &str) -> Result<Option<String>, JsValue> {
+        let Some(value) = get_record(RECEIPTS_STORE, receipt_id).await? else {
+            return Ok(None);
+        };
+
+        let stored: StoredReceipt = serde_wasm_bindgen::from_value(value)
+            .map_err(|e| JsValue::from_str(&format!("Failed to read receipt: {}", e)))?;
+        Ok(Some(stored.json))
+    }
+
+    /// List all receipts as JSON strings, oldest first
+    pub async fn list_receipts(&self) -> Result<Vec<JsValue>, JsValue> {
+        Ok(self
+            .list_receipt_jsons()
+            .await?
+            .into_iter()
+            .map(|json| JsValue::from_str(&json))
+            .collect())
+    }
+
+    /// Delete a receipt
+    pub async fn delete_receipt(&self, receipt_id: &str) -> Result<(), JsValue> {
+        delete_record(RECEIPTS_STORE, receipt_id)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to delete receipt: {:?}", e)))
+    }
+
+    /// Number of stored receipts
+    pub async fn count(&self) -> Result<u32, JsValue> {
+        let (_, store) = open_store(RECEIPTS_STORE, IdbTransactionMode::Readonly).await?;
+        let count = await_request(store.count()?).await?;
+        Ok(count.as_f64().unwrap_or(0.0) as u32)
+    }
+
+    /// Delete the `count` oldest receipts, returning how many were removed
+    pub async fn evict_oldest(&self, count: u32) -> Result<u32, JsValue> {
+        let (_, store) = open_store(RECEIPTS_STORE, IdbTransactionMode::Readonly).await?;
+        let keys = await_request(
+            store
+                .index(CREATED_AT_INDEX)?
+                .get_all_keys_with_key_and_limit(&JsValue::UNDEFINED, count)?,
+        )
+        .await?;
+        let keys = js_sys::Array::from(&keys);
+        if keys.length() == 0 {
+            return Ok(0);
+        }
+
+        // Deletes go in a second transaction; the first one has finished
+        // by the time its result reaches us.
+        let (tx, store) = open_store(RECEIPTS_STORE, IdbTransactionMode::Readwrite).await?;
+        for key in keys.iter() {
+            store.delete(&key)?;
+        }
+        await_transaction(&tx).await?;
+        Ok(keys.length())
+    }
+
+    /// Storage usage and quota for this origin in bytes, as
+    /// `{ usage, quota }`, or `null` if the browser doesn't report it
+    pub async fn get_storage_estimate(&self) -> Result<JsValue, JsValue> {
+        let Some((usage, quota)) = storage_estimate().await? else {
+            return Ok(JsValue::NULL);
+        };
+
+        let result = js_sys::Object::new();
+        let _ = js_sys::Reflect::set(&result, &"usage".into(), &usage.into());
+        let _ = js_sys::Reflect::set(&result, &"quota".into(), &quota.into());
+        Ok(result.into())
+    }
+
+    /// Clear all receipts
+    pub async fn clear_all(&self) -> Result<(), JsValue> {
+        clear_store(RECEIPTS_STORE).await
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl ReceiptStore for WasmIndexedDbReceiptStorage {
+    async fn save_receipt_json(&self, id: &str, json: &str) -> Result<(), JsValue> {
+        self.save_receipt(id, json).await
+    }
+
+    async fn get_receipt_json(&self, id: &str) -> Result<Option<String>, JsValue> {
+        self.get_receipt(id).await
+    }
+
+    async fn list_receipt_jsons(&self) -> Result<Vec<String>, JsValue> {
+        let (_, store) = open_store(RECEIPTS_STORE, IdbTransactionMode::Readonly).await?;
+        let values = await_request(store.index(CREATED_AT_INDEX)?.get_all()?).await?;
+
+        js_sys::Array::from(&values)
+            .iter()
+            .map(|value| {
+                serde_wasm_bindgen::from_value::<StoredReceipt>(value)
+                    .map(|stored| stored.json)
+                    .map_err(|e| JsValue::from_str(&format!("Failed to read receipt: {}", e)))
+            })
+            .collect()
+    }
+
+    async fn remove_receipt(&self, id: &str) -> Result<(), JsValue> {
+        self.delete_receipt(id).await
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl ReceiptStore for WasmReceiptStorage {
+    async fn save_receipt_json(&self, id: &str, json: &str) -> Result<(), JsValue> {
+        self.save_receipt(id, json).await
+    }
+
+    async fn get_receipt_json(&self, id: &str) -> Result<Option<String>, JsValue> {
+        self.get_receipt(id).await
+    }
+
+    async fn list_receipt_jsons(&self) -> Result<Vec<String>, JsValue> {
+        Ok(self
+            .list_receipts()
+            .await?
+            .into_iter()
+            .filter_map(|json| json.as_string())
+            .collect())
+    }
+
+    async fn remove_receipt(&self, id: &str) -> Result<(), JsValue> {
+        self.delete_receipt(id).await
+    }
+}
+
+/// Contact storage in browser IndexedDB
+///
+/// Same core API as `WasmContactStorage`.
+#[wasm_bindgen]
+pub struct WasmIndexedDbContactStorage {}
+
+impl Default for WasmIndexedDbContactStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl WasmIndexedDbContactStorage {
+    /// Create a new contact storage manager
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Save a contact, overwriting any contact with the same public key
+    pub async fn save_contact(&self, contact: &WasmContact) -> Result<(), JsValue> {
+        let stored = StoredContact {
+            json: contact.to_json()?,
+            name: contact.name().to_lowercase(),
+        };
+        let value = serde_wasm_bindgen::to_value(&stored)
+            .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))?;
+
+        put_record(CONTACTS_STORE, &contact.public_key(), &value)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to save contact: {:?}", e)))
+    }
+
+    /// Get a contact by public key
+    pub async fn get_contact(&self, public_key: &str) -> Result<Option<WasmContact>, JsValue> {
+        let Some(value) = get_record(CONTACTS_STORE, public_key).await? else {
+            return Ok(None);
+        };
+
+        let stored: StoredContact = serde_wasm_bindgen::from_value(value)
+            .map_err(|e| JsValue::from_str(&format!("Deserialization error: {}", e)))?;
+        WasmContact::from_json(&stored.json).map(Some)
+    }
+
+    /// List all contacts, sorted alphabetically by name
+    pub async fn list_contacts(&self) -> Result<Vec<JsValue>, JsValue> {
+        Ok(self
+            .all_contacts()
+            .await?
+            .iter()
+            .map(WasmContact::to_js_object)
+            .collect())
+    }
+
+    /// Delete a contact by public key
+    pub async fn delete_contact(&self, public_key: &str) -> Result<(), JsValue> {
+        delete_record(CONTACTS_STORE, public_key)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to delete contact: {:?}", e)))
+    }
+
+    /// Number of stored contacts
+    pub async fn count(&self) -> Result<u32, JsValue> {
+        let (_, store) = open_store(CONTACTS_STORE, IdbTransactionMode::Readonly).await?;
+        let count = await_request(store.count()?).await?;
+        Ok(count.as_f64().unwrap_or(0.0) as u32)
+    }
+
+    /// Clear all contacts
+    pub async fn clear_all(&self) -> Result<(), JsValue> {
+        clear_store(CONTACTS_STORE).await
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl ContactStore for WasmIndexedDbContactStorage {
+    async fn store_contact(&self, contact: &WasmContact) -> Result<(), JsValue> {
+        self.save_contact(contact).await
+    }
+
+    async fn load_contact(&self, public_key: &str) -> Result<Option<WasmContact>, JsValue> {
+        self.get_contact(public_key).await
+    }
+
+    async fn all_contacts(&self) -> Result<Vec<WasmContact>, JsValue> {
+        let (_, store) = open_store(CONTACTS_STORE, IdbTransactionMode::Readonly).await?;
+        let values = await_request(store.index(NAME_INDEX)?.get_all()?).await?;
+
+        js_sys::Array::from(&values)
+            .iter()
+            .map(|value| {
+                let stored: StoredContact = serde_wasm_bindgen::from_value(value)
+                    .map_err(|e| JsValue::from_str(&format!("Deserialization error: {}", e)))?;
+                WasmContact::from_json(&stored.json)
+            })
+            .collect()
+    }
+
+    async fn remove_contact(&self, public_key: &str) -> Result<(), JsValue> {
+        self.delete_contact(public_key).await
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl ContactStore for WasmContactStorage {
+    async fn store_contact(&self, contact: &WasmContact) -> Result<(), JsValue> {
+        self.save_contact(contact).await
+    }
+
+    async fn load_contact(&self, public_key: &str) -> Result<Option<WasmContact>, JsValue> {
+        self.get_contact(public_key).await
+    }
+
+    async fn all_contacts(&self) -> Result<Vec<WasmContact>, JsValue> {
+        self.load_contacts()
+    }
+
+    async fn remove_contact(&self, public_key: &str) -> Result<(), JsValue> {
+        self.delete_contact(public_key).await
+    }
+}
+
+/// Open the database, running any schema migrations it needs
+async fn open_database() -> Result<IdbDatabase, JsValue> {
+    let window = web_sys::window().ok_or("No window")?;
+    let factory = window.indexed_db()?.ok_or("IndexedDB not available")?;
+    let request = factory.open_with_u32(DB_NAME, SCHEMA_VERSION)?;
+
+    let on_upgrade = Closure::once_into_js(move |event: IdbVersionChangeEvent| {
+        let Some(request) = event
+            .target()
+            .and_then(|target| target.dyn_into::<IdbOpenDbRequest>().ok())
+        else {
+            return;
+        };
+        let (Ok(db), Some(tx)) = (request.result(), request.transaction()) else {
+            return;
+        };
+        let db: IdbDatabase = db.unchecked_into();
+
+        let from = event.old_version() as usize;
+        for migration in MIGRATIONS.iter().skip(from) {
+            if let Err(e) = migration(&db, &tx) {
+                crate::utils::log(&format!("IndexedDB migration failed: {:?}", e));
+                // Aborting the upgrade fails the open request
+                let _ = tx.abort();
+                return;
+            }
+        }
+    });
+    request.set_onupgradeneeded(Some(on_upgrade.unchecked_ref()));
+
+    let db: IdbDatabase = await_request(request.into())
+        .await
+        .map_err(|e| JsValue::from_str(&format!("Failed to open database: {:?}", e)))?
+        .unchecked_into();
+
+    // Let a newer version in another tab upgrade instead of blocking it
+    let on_version_change = Closure::once_into_js({
+        let db = db.clone();
+        move || db.close()
+    });
+    db.set_onversionchange(Some(on_version_change.unchecked_ref()));
+
+    Ok(db)
+}
+
+async fn open_store(
+    name: &str,
+    mode: IdbTransactionMode,
+) -> Result<(IdbTransaction, IdbObjectStore), JsValue> {
+    let db = open_database().await?;
+    let tx = db.transaction_with_str_and_mode(name, mode)?;
+    let store = tx.object_store(name)?;
+    Ok((tx, store))
+}
+
+async fn put_receipt(receipt_id: &str, receipt_json: &str) -> Result<(), JsValue> {
+    let stored = StoredReceipt {
+        json: receipt_json.to_string(),
+        created_at: js_sys::Date::now(),
+    };
+    let value = serde_wasm_bindgen::to_value(&stored)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))?;
+
+    put_record(RECEIPTS_STORE, receipt_id, &value).await
+}
+
+/// Store `value` under `key`, replacing any existing record
+pub(crate) async fn put_record(
+    store_name: &str,
+    key: &str,
+    value: &JsValue,
+) -> Result<(), JsValue> {
+    let (tx, store) = open_store(store_name, IdbTransactionMode::Readwrite).await?;
+    store.put_with_key(value, &JsValue::from_str(key))?;
+    await_transaction(&tx).await
+}
+
+/// The record under `key`, if any
+pub(crate) async fn get_record(store_name: &str, key: &str) -> Result<Option<JsValue>, JsValue> {
+    let (_, store) = open_store(store_name, IdbTransactionMode::Readonly).await?;
+    let value = await_request(store.get(&JsValue::from_str(key))?).await?;
+    Ok(Some(value).filter(|v| !v.is_undefined() && !v.is_null()))
+}
+
+/// Delete the record under `key`
+pub(crate) async fn delete_record(store_name: &str, key: &str) -> Result<(), JsValue> {
+    let (tx, store) = open_store(store_name, IdbTransactionMode::Readwrite).await?;
+    store.delete(&JsValue::from_str(key))?;
+    await_transaction(&tx).await
+}
+
+async fn clear_store(name: &str) -> Result<(), JsValue> {
+    let (tx, store) = open_store(name, IdbTransactionMode::Readwrite).await?;
+    store.clear()?;
+    await_transaction(&tx).await
+}
+
+/// Resolve with a request's result once it succeeds
+async fn await_request(request: IdbRequest) -> Result<JsValue, JsValue> {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        let target = request.clone();
+        let on_success = Closure::once_into_js(move || {
+            let result = target.result().unwrap_or(JsValue::UNDEFINED);
+            let _ = resolve.call1(&JsValue::NULL, &result);
+        });
+        let target = request.clone();
+        let on_error = Closure::once_into_js(move || {
+            let error = js_sys::Reflect::get(&target, &"error".into()).unwrap_or_default();
+            let _ = reject.call1(&JsValue::NULL, &error);
+        });
+        request.set_onsuccess(Some(on_success.unchecked_ref()));
+        request.set_onerror(Some(on_error.unchecked_ref()));
+    });
+    JsFuture::from(promise).await
+}
+
+/// Resolve once a transaction commits
+async fn await_transaction(tx: &IdbTransaction) -> Result<(), JsValue> {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        let on_complete = Closure::once_into_js(move || {
+            let _ = resolve.call0(&JsValue::NULL);
+        });
+        // A failed request fires `error` and then `abort`, so both go
+        // through one reusable handler; rejecting twice is a no-op.
+        let target = tx.clone();
+        let on_error = Closure::<dyn FnMut()>::new(move || {
+            let error = js_sys::Reflect::get(&target, &"error".into()).unwrap_or_default();
+            let _ = reject.call1(&JsValue::NULL, &error);
+        })
+        .into_js_value();
+        tx.set_oncomplete(Some(on_complete.unchecked_ref()));
+        tx.set_onerror(Some(on_error.unchecked_ref()));
+        tx.set_onabort(Some(on_error.unchecked_ref()));
+    });
+    JsFuture::from(promise).await.map(|_| ())
+}
+
+/// `(usage, quota)` in bytes from `navigator.storage.estimate()`
+async fn storage_estimate() -> Result<Option<(f64, f64)>, JsValue> {
+    let navigator = js_sys::Reflect::get(&js_sys::global(), &"navigator".into())?;
+    let storage = js_sys::Reflect::get(&navigator, &"storage".into())?;
+    let estimate = js_sys::Reflect::get(&storage, &"estimate".into()).unwrap_or_default();
+    let Some(estimate) = estimate.dyn_ref::<js_sys::Function>() else {
+        return Ok(None);
+    };
+
+    let promise: js_sys::Promise = estimate.call0(&storage)?.dyn_into()?;
+    let result = JsFuture::from(promise).await?;
+    let field = |name: &str| {
+        js_sys::Reflect::get(&result, &name.into())
+            .ok()
+            .and_then(|v| v.as_f64())
+    };
+
+    Ok(field("usage").zip(field("quota")))
+}
+
+fn is_quota_error(error: &JsValue) -> bool {
+    js_sys::Reflect::get(error, &"name".into())
+        .ok()
+        .and_then(|name| name.as_string())
+        .is_some_and(|name| name == "QuotaExceededError")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::Identity;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    async fn test_receipts_listed_and_evicted_oldest_first() {
+        let storage = WasmIndexedDbReceiptStorage::new();
+        storage.clear_all().await.unwrap();
+
+        for id in ["first", "second", "third"] {
+            let json = format!(r#"{{"receipt_id":"{}"}}"#, id);
+            storage.save_receipt(id, &json).await.unwrap();
+        }
+        let listed = storage.list_receipt_jsons().await.unwrap();
+        assert_eq!(listed.len(), 3);
+        assert!(listed[0].contains("first"));
+
+        assert_eq!(storage.evict_oldest(1).await.unwrap(), 1);
+        assert!(storage.get_receipt("first").await.unwrap().is_none());
+        assert_eq!(storage.count().await.unwrap(), 2);
+
+        storage.clear_all().await.unwrap();
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_contacts_sorted_by_name() {
+        let storage = WasmIndexedDbContactStorage::new();
+        storage.clear_all().await.unwrap();
+
+        let bob =
+            WasmContact::new(Identity::new().unwrap().public_key(), "bob".to_string()).unwrap();
+        let alice =
+            WasmContact::new(Identity::new().unwrap().public_key(), "Alice".to_string()).unwrap();
+        storage.save_contact(&bob).await.unwrap();
+        storage.save_contact(&alice).await.unwrap();
+
+        let names: Vec<String> = storage
+            .all_contacts()
+            .await
+            .unwrap()
+            .iter()
+            .map(WasmContact::name)
+            .collect();
+        assert_eq!(names, vec!["Alice", "bob"]);
+
+        storage.clear_all().await.unwrap();
+    }
+}
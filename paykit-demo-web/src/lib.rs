@@ -7,11 +7,14 @@
 use wasm_bindgen::prelude::*;
 
 mod contacts;
+mod custom_methods;
 mod dashboard;
 mod directory;
 mod identity;
+mod indexeddb_storage;
 mod payment;
 mod payment_methods;
+mod private_endpoint_exchange;
 mod private_endpoints;
 mod storage;
 mod storage_migration;
@@ -19,20 +22,30 @@ mod subscriptions;
 mod types;
 mod utils;
 mod wasm_transport;
+mod webcrypto;
 mod websocket_transport;
 
 pub use contacts::*;
+pub use custom_methods::WasmMethodRegistry;
 pub use dashboard::*;
 pub use directory::*;
 pub use identity::{Identity, WasmKeyProvider};
+pub use indexeddb_storage::{
+    ContactStore, ReceiptStore, WasmIndexedDbContactStorage, WasmIndexedDbReceiptStorage,
+    SCHEMA_VERSION,
+};
 pub use payment::*;
 pub use payment_methods::*;
+pub use private_endpoint_exchange::WasmPrivateEndpointExchange;
 pub use private_endpoints::WasmPrivateEndpointStorage;
 pub use storage::*;
 pub use storage_migration::*;
 pub use subscriptions::*;
 pub use types::*;
-pub use utils::{is_valid_public_key, parse_uri, to_paykit_uri, to_pubky_uri, ParsedUri};
+pub use utils::{
+    btc_to_sats, format_btc, format_compact_sats, format_fiat, format_sats, is_valid_public_key,
+    parse_uri, to_paykit_uri, to_pubky_uri, ParsedUri,
+};
 pub use wasm_transport::WasmUnauthenticatedTransport;
 pub use websocket_transport::*;
 
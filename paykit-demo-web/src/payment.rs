@@ -409,19 +409,8 @@ impl WasmReceiptStorage {
                 preimage,
                 payment_hash,
             } => {
-                // Basic cryptographic verification: SHA256(preimage) == payment_hash
-                use sha2::{Digest, Sha256};
-
-                let preimage_bytes = hex::decode(preimage)
-                    .map_err(|e| utils::js_error(&format!("Invalid preimage hex: {}", e)))?;
-                let hash_bytes = hex::decode(payment_hash)
-                    .map_err(|e| utils::js_error(&format!("Invalid payment_hash hex: {}", e)))?;
-
-                let mut hasher = Sha256::new();
-                hasher.update(&preimage_bytes);
-                let computed = hasher.finalize();
-
-                if computed.as_slice() == hash_bytes.as_slice() {
+                // Same check as the core library and the Node.js bindings
+                if paykit_lib::methods::preimage_matches_hash(&preimage, &payment_hash) {
                     VerificationResult::valid()
                 } else {
                     VerificationResult::invalid(vec!["Preimage hash mismatch".to_string()])
@@ -554,15 +543,6 @@ impl WasmPaymentCoordinator {
         let payee = PublicKey::from_str(payee_pubkey)
             .map_err(|e| JsValue::from_str(&format!("Invalid payee pubkey: {}", e)))?;
 
-        // Parse server key
-        let server_key_bytes = hex::decode(server_static_key_hex)
-            .map_err(|e| JsValue::from_str(&format!("Invalid server key hex: {}", e)))?;
-        let mut server_key = [0u8; 32];
-        if server_key_bytes.len() != 32 {
-            return Err(JsValue::from_str("Server key must be 32 bytes"));
-        }
-        server_key.copy_from_slice(&server_key_bytes);
-
         // Create provisional receipt
         let receipt_id = format!("pay_{}", uuid::Uuid::new_v4());
         let provisional_receipt = PaykitReceipt::new(
@@ -578,19 +558,9 @@ impl WasmPaymentCoordinator {
             }),
         );
 
-        // Create Noise client with WASM key provider
-        let key_provider = WasmKeyProvider::from_identity(&payer_identity);
-        let device_id = b"wasm-browser-v1"; // Fixed device ID for browser
-        let client = NoiseClient::new_direct(
-            "paykit".to_string(),
-            device_id,
-            std::sync::Arc::new(key_provider),
-        );
-
         // Connect and perform handshake
-        let mut channel = WebSocketNoiseChannel::connect(ws_url, &client, &server_key)
-            .await
-            .map_err(|e| JsValue::from_str(&format!("Connection failed: {}", e)))?;
+        let mut channel =
+            connect_noise_channel(&payer_identity, ws_url, server_static_key_hex).await?;
 
         // Send payment request
         channel
@@ -637,6 +607,38 @@ impl WasmPaymentCoordinator {
     }
 }
 
+/// Connect to `ws_url` as `identity` and perform the Noise handshake
+///
+/// `server_static_key_hex` is the peer's Noise static key, as published in
+/// its noise:// endpoint.
+pub(crate) async fn connect_noise_channel(
+    identity: &Identity,
+    ws_url: &str,
+    server_static_key_hex: &str,
+) -> Result<WebSocketNoiseChannel, JsValue> {
+    // Parse server key
+    let server_key_bytes = hex::decode(server_static_key_hex)
+        .map_err(|e| JsValue::from_str(&format!("Invalid server key hex: {}", e)))?;
+    let mut server_key = [0u8; 32];
+    if server_key_bytes.len() != 32 {
+        return Err(JsValue::from_str("Server key must be 32 bytes"));
+    }
+    server_key.copy_from_slice(&server_key_bytes);
+
+    // Create Noise client with WASM key provider
+    let key_provider = WasmKeyProvider::from_identity(identity);
+    let device_id = b"wasm-browser-v1"; // Fixed device ID for browser
+    let client = NoiseClient::new_direct(
+        "paykit".to_string(),
+        device_id,
+        std::sync::Arc::new(key_provider),
+    );
+
+    WebSocketNoiseChannel::connect(ws_url, &client, &server_key)
+        .await
+        .map_err(|e| JsValue::from_str(&format!("Connection failed: {}", e)))
+}
+
 /// Payment receiver for accepting payments
 #[wasm_bindgen]
 pub struct WasmPaymentReceiver {
@@ -982,6 +982,37 @@ impl WasmPaymentMethodStorage {
     }
 }
 
+/// Describe the built-in payment methods and what each supports
+///
+/// Lets the UI decide which actions to offer (pay, prove, probe, rotate)
+/// without hard-coding method IDs.
+///
+/// # Returns
+///
+/// An array of descriptors:
+/// ```json
+/// [{
+///   "method_id": "lightning",
+///   "display_name": "Lightning Network",
+///   "description": "...",
+///   "capabilities": {
+///     "settlement": "off_chain",
+///     "execution": false,
+///     "proofs": true,
+///     "private_endpoints": false,
+///     "route_probing": true,
+///     "min_amount_sats": 1,
+///     "max_amount_sats": 4000000
+///   }
+/// }]
+/// ```
+#[wasm_bindgen]
+pub fn describe_methods() -> Result<JsValue, JsValue> {
+    let descriptors = paykit_lib::methods::default_registry().describe();
+    serde_wasm_bindgen::to_value(&descriptors)
+        .map_err(|e| crate::utils::js_error(&format!("Failed to serialize methods: {}", e)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -989,6 +1020,12 @@ mod tests {
 
     wasm_bindgen_test_configure!(run_in_browser);
 
+    #[wasm_bindgen_test]
+    fn test_describe_methods() {
+        let methods = describe_methods().unwrap();
+        assert_eq!(js_sys::Array::from(&methods).length(), 2);
+    }
+
     #[wasm_bindgen_test]
     fn test_method_creation() {
         let method = WasmPaymentMethodConfig::new(
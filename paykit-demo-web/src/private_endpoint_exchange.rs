@@ -0,0 +1,377 @@
+//! Private endpoint exchange for WASM
+//!
+//! This module lets the browser demo share private payment endpoints with a
+//! peer over WebSocket Noise channels, using the same `OfferPrivateEndpoints`
+//! / `AcceptPrivateEndpoints` messages as the CLI and mobile apps. Endpoints
+//! received from peers are kept in IndexedDB through
+//! [`WasmPrivateEndpointStorage`] and preferred over public directory
+//! endpoints when paying.
+
+use crate::identity::Identity;
+use crate::payment::connect_noise_channel;
+use crate::private_endpoints::WasmPrivateEndpointStorage;
+use crate::types::{PaykitNoiseMessage, PrivateEndpointOffer};
+use crate::utils;
+use paykit_lib::private_endpoints::{PrivateEndpoint, PrivateEndpointStore};
+use paykit_lib::{EndpointData, MethodId, PublicKey};
+use serde::Serialize;
+use std::str::FromStr;
+use wasm_bindgen::prelude::*;
+
+/// A stored private endpoint as returned to JavaScript
+#[derive(Serialize)]
+struct PrivateEndpointInfo {
+    peer: String,
+    method_id: String,
+    endpoint: String,
+    created_at: i64,
+    expires_at: Option<i64>,
+    use_count: u32,
+}
+
+impl From<&PrivateEndpoint> for PrivateEndpointInfo {
+    fn from(endpoint: &PrivateEndpoint) -> Self {
+        Self {
+            peer: endpoint.peer.to_string(),
+            method_id: endpoint.method_id.0.clone(),
+            endpoint: endpoint.endpoint.0.clone(),
+            created_at: endpoint.created_at,
+            expires_at: endpoint.expires_at,
+            use_count: endpoint.use_count,
+        }
+    }
+}
+
+/// Offers, receives and stores private endpoints
+///
+/// # Example
+///
+/// ```javascript
+/// const exchange = new WasmPrivateEndpointExchange();
+///
+/// // Share a dedicated address with a peer
+/// const accepted = await exchange.offer_endpoints(
+///     identity.toJSON(),
+///     endpoint.wsUrl,
+///     peerPubkey,
+///     endpoint.serverKeyHex,
+///     JSON.stringify([{ method_id: 'onchain', endpoint: 'bc1q...', expires_at: null }])
+/// );
+///
+/// // Later, pay the peer at the endpoint it shared with us
+/// const address = await exchange.get_endpoint(peerPubkey, 'onchain');
+/// ```
+#[wasm_bindgen]
+pub struct WasmPrivateEndpointExchange {
+    storage: WasmPrivateEndpointStorage,
+}
+
+impl Default for WasmPrivateEndpointExchange {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl WasmPrivateEndpointExchange {
+    /// Create a new exchange backed by IndexedDB
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self {
+            storage: WasmPrivateEndpointStorage::new(),
+        }
+    }
+
+    /// Offer private endpoints to a peer
+    ///
+    /// Connects to the peer's WebSocket endpoint, sends an
+    /// `OfferPrivateEndpoints` message and waits for the answer.
+    ///
+    /// # Arguments
+    ///
+    /// * `identity_json` - JSON string of our identity (from Identity.toJSON())
+    /// * `ws_url` - The peer's WebSocket URL
+    /// * `peer_pubkey` - The peer's public key
+    /// * `server_static_key_hex` - The peer's Noise static key
+    /// * `offers_json` - JSON array of `{ method_id, endpoint, expires_at }`
+    ///
+    /// # Returns
+    ///
+    /// The method IDs the peer accepted
+    pub async fn offer_endpoints(
+        &self,
+        identity_json: &str,
+        ws_url: &str,
+        peer_pubkey: &str,
+        server_static_key_hex: &str,
+        offers_json: &str,
+    ) -> Result<Vec<String>, JsValue> {
+        let identity = Identity::from_json(identity_json)?;
+        parse_peer(peer_pubkey)?;
+        let methods: Vec<PrivateEndpointOffer> = serde_json::from_str(offers_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid offers: {}", e)))?;
+        if methods.is_empty() {
+            return Err(JsValue::from_str("No endpoints to offer"));
+        }
+
+        let mut channel = connect_noise_channel(&identity, ws_url, server_static_key_hex).await?;
+        channel
+            .send(PaykitNoiseMessage::OfferPrivateEndpoints { methods })
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to send offer: {}", e)))?;
+        let response = channel
+            .recv()
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to receive response: {}", e)));
+        let _ = channel.close();
+
+        match response? {
+            PaykitNoiseMessage::AcceptPrivateEndpoints { method_ids } => {
+                Ok(method_ids.into_iter().map(|m| m.0).collect())
+            }
+            PaykitNoiseMessage::DeclinePrivateEndpoints { reason } => Err(JsValue::from_str(
+                &format!("Peer declined endpoints: {}", reason),
+            )),
+            PaykitNoiseMessage::Error { code, message } => Err(JsValue::from_str(&format!(
+                "Offer error [{}]: {}",
+                code, message
+            ))),
+            _ => Err(JsValue::from_str("Unexpected response from peer")),
+        }
+    }
+
+    /// Connect to a peer and receive the private endpoints it offers
+    ///
+    /// For peers that offer their endpoints as soon as a channel opens. The
+    /// offer is stored and answered the same way as
+    /// [`handle_message`](Self::handle_message).
+    ///
+    /// # Returns
+    ///
+    /// The endpoints stored for the peer, as objects
+    pub async fn receive_offer(
+        &self,
+        identity_json: &str,
+        ws_url: &str,
+        peer_pubkey: &str,
+        server_static_key_hex: &str,
+    ) -> Result<Vec<JsValue>, JsValue> {
+        let identity = Identity::from_json(identity_json)?;
+        let peer = parse_peer(peer_pubkey)?;
+
+        let mut channel = connect_noise_channel(&identity, ws_url, server_static_key_hex).await?;
+        let result = async {
+            let msg = channel
+                .recv()
+                .await
+                .map_err(|e| JsValue::from_str(&format!("Failed to receive offer: {}", e)))?;
+            let reply = self.reply_to_offer(&peer, msg).await?;
+            channel
+                .send(reply)
+                .await
+                .map_err(|e| JsValue::from_str(&format!("Failed to send reply: {}", e)))
+        }
+        .await;
+        let _ = channel.close();
+        result?;
+
+        self.list_endpoints(peer_pubkey).await
+    }
+
+    /// Handle an offer message relayed from a peer
+    ///
+    /// Browsers cannot accept incoming connections, so offers sent to us
+    /// arrive through a WebSocket relay server, like payment requests do
+    /// (see `WasmPaymentServer`). Offered endpoints that have not expired
+    /// are stored.
+    ///
+    /// # Returns
+    ///
+    /// The reply message JSON to relay back: `AcceptPrivateEndpoints` for
+    /// `OfferPrivateEndpoints` and `Ack` for a single `OfferPrivateEndpoint`
+    pub async fn handle_message(
+        &self,
+        peer_pubkey: &str,
+        message_json: &str,
+    ) -> Result<String, JsValue> {
+        let peer = parse_peer(peer_pubkey)?;
+        let msg: PaykitNoiseMessage = serde_json::from_str(message_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid message: {}", e)))?;
+        let reply = self.reply_to_offer(&peer, msg).await?;
+        serde_json::to_string(&reply)
+            .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+    }
+
+    /// Get the private endpoint a peer shared for a payment method
+    ///
+    /// Returns `undefined` if there is none or it has expired. Use it in
+    /// place of the peer's public endpoint.
+    pub async fn get_endpoint(
+        &self,
+        peer_pubkey: &str,
+        method_id: &str,
+    ) -> Result<Option<String>, JsValue> {
+        let peer = parse_peer(peer_pubkey)?;
+        let endpoint = self
+            .storage
+            .get(&peer, &MethodId(method_id.to_string()))
+            .await
+            .map_err(|e| utils::js_error(&e.to_string()))?;
+        Ok(endpoint.filter(|e| e.is_valid()).map(|e| e.endpoint.0))
+    }
+
+    /// List the private endpoints stored for a peer
+    pub async fn list_endpoints(&self, peer_pubkey: &str) -> Result<Vec<JsValue>, JsValue> {
+        let peer = parse_peer(peer_pubkey)?;
+        let endpoints = self
+            .storage
+            .list_for_peer(&peer)
+            .await
+            .map_err(|e| utils::js_error(&e.to_string()))?;
+        endpoints
+            .iter()
+            .map(|e| {
+                serde_wasm_bindgen::to_value(&PrivateEndpointInfo::from(e))
+                    .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+            })
+            .collect()
+    }
+
+    /// List the peers that shared private endpoints with us
+    pub async fn list_peers(&self) -> Result<Vec<String>, JsValue> {
+        let peers = self
+            .storage
+            .list_peers()
+            .await
+            .map_err(|e| utils::js_error(&e.to_string()))?;
+        Ok(peers.iter().map(|p| p.to_string()).collect())
+    }
+
+    /// Remove the private endpoint a peer shared for a payment method
+    pub async fn remove_endpoint(&self, peer_pubkey: &str, method_id: &str) -> Result<(), JsValue> {
+        let peer = parse_peer(peer_pubkey)?;
+        self.storage
+            .remove(&peer, &MethodId(method_id.to_string()))
+            .await
+            .map_err(|e| utils::js_error(&e.to_string()))
+    }
+
+    /// Remove expired endpoints, returning how many were removed
+    pub async fn cleanup_expired(&self) -> Result<usize, JsValue> {
+        self.storage
+            .cleanup_expired()
+            .await
+            .map_err(|e| utils::js_error(&e.to_string()))
+    }
+}
+
+impl WasmPrivateEndpointExchange {
+    /// Store the endpoints in an offer from `peer` and build the reply
+    async fn reply_to_offer(
+        &self,
+        peer: &PublicKey,
+        msg: PaykitNoiseMessage,
+    ) -> Result<PaykitNoiseMessage, JsValue> {
+        match msg {
+            PaykitNoiseMessage::OfferPrivateEndpoints { methods } => {
+                let method_ids = self.store_offers(peer, methods).await?;
+                Ok(PaykitNoiseMessage::AcceptPrivateEndpoints { method_ids })
+            }
+            PaykitNoiseMessage::OfferPrivateEndpoint {
+                method_id,
+                endpoint,
+            } => {
+                let offer = PrivateEndpointOffer {
+                    method_id,
+                    endpoint,
+                    expires_at: None,
+                };
+                self.store_offers(peer, vec![offer]).await?;
+                Ok(PaykitNoiseMessage::Ack)
+            }
+            _ => Err(JsValue::from_str("Expected a private endpoint offer")),
+        }
+    }
+
+    /// Store unexpired offers, returning the method IDs stored
+    async fn store_offers(
+        &self,
+        peer: &PublicKey,
+        offers: Vec<PrivateEndpointOffer>,
+    ) -> Result<Vec<MethodId>, JsValue> {
+        let now = chrono::Utc::now().timestamp();
+        let mut stored = Vec::new();
+        for offer in offers {
+            if offer.expires_at.is_some_and(|at| at <= now) {
+                continue;
+            }
+            let endpoint = PrivateEndpoint::new(
+                peer.clone(),
+                offer.method_id.clone(),
+                EndpointData(offer.endpoint),
+                offer.expires_at,
+            );
+            self.storage
+                .save(endpoint)
+                .await
+                .map_err(|e| utils::js_error(&e.to_string()))?;
+            stored.push(offer.method_id);
+        }
+        Ok(stored)
+    }
+}
+
+/// Parse a peer public key, accepting pubky:// URIs
+fn parse_peer(peer_pubkey: &str) -> Result<PublicKey, JsValue> {
+    let key = peer_pubkey.strip_prefix("pubky://").unwrap_or(peer_pubkey);
+    PublicKey::from_str(key).map_err(|e| JsValue::from_str(&format!("Invalid peer pubkey: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_parse_peer_accepts_pubky_uri() {
+        let key = pkarr::Keypair::random().public_key().to_string();
+        assert!(parse_peer(&key).is_ok());
+        assert!(parse_peer(&format!("pubky://{}", key)).is_ok());
+        assert!(parse_peer("not-a-key").is_err());
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_relayed_offer_is_stored() {
+        let exchange = WasmPrivateEndpointExchange::new();
+        let peer = pkarr::Keypair::random().public_key().to_string();
+        let offer = serde_json::json!({
+            "type": "OfferPrivateEndpoints",
+            "payload": { "methods": [
+                { "method_id": "onchain", "endpoint": "bc1qprivate", "expires_at": null },
+                { "method_id": "lightning", "endpoint": "lnbc1expired", "expires_at": 1 }
+            ]}
+        });
+
+        let reply = exchange
+            .handle_message(&peer, &offer.to_string())
+            .await
+            .unwrap();
+        let reply: serde_json::Value = serde_json::from_str(&reply).unwrap();
+        assert_eq!(reply["type"], "AcceptPrivateEndpoints");
+        assert_eq!(
+            reply["payload"]["method_ids"],
+            serde_json::json!(["onchain"])
+        );
+
+        assert_eq!(
+            exchange.get_endpoint(&peer, "onchain").await.unwrap(),
+            Some("bc1qprivate".to_string())
+        );
+        assert_eq!(
+            exchange.get_endpoint(&peer, "lightning").await.unwrap(),
+            None
+        );
+    }
+}
@@ -1,7 +1,12 @@
-//! Migration from localStorage to WebCryptoStorage
+//! Migration from localStorage to WebCryptoStorage and IndexedDB
 
 use wasm_bindgen::prelude::*;
 
+use crate::contacts::WasmContactStorage;
+use crate::indexeddb_storage::{
+    ContactStore, ReceiptStore, WasmIndexedDbContactStorage, WasmIndexedDbReceiptStorage,
+};
+use crate::payment::WasmReceiptStorage;
 use crate::utils;
 
 #[cfg(target_arch = "wasm32")]
@@ -165,6 +170,27 @@ impl StorageMigration {
         Ok(migrated_count)
     }
 
+    /// Move receipts and contacts from localStorage into IndexedDB
+    ///
+    /// Each record is removed from localStorage once IndexedDB holds it, so
+    /// an interrupted migration resumes where it stopped. Run this before
+    /// `migrate`, which would otherwise move them into secure storage.
+    ///
+    /// # Returns
+    /// Number of records migrated
+    #[wasm_bindgen(js_name = migrateToIndexedDb)]
+    pub async fn migrate_to_indexed_db(&mut self) -> Result<u32, JsValue> {
+        self.status = MigrationStatus::InProgress;
+
+        let result = Self::move_to_indexed_db().await;
+        self.status = match &result {
+            Ok(0) => MigrationStatus::NotNeeded,
+            Ok(_) => MigrationStatus::Completed,
+            Err(_) => MigrationStatus::Failed,
+        };
+        result
+    }
+
     /// Get migration status
     #[wasm_bindgen(js_name = getStatus)]
     pub fn get_status(&self) -> String {
@@ -178,3 +204,47 @@ impl StorageMigration {
         .to_string()
     }
 }
+
+impl StorageMigration {
+    async fn move_to_indexed_db() -> Result<u32, JsValue> {
+        let mut migrated_count = 0u32;
+
+        // Receipt IDs only live in the localStorage keys
+        let window = web_sys::window().ok_or_else(|| utils::js_error("No window"))?;
+        let storage = window
+            .local_storage()?
+            .ok_or_else(|| utils::js_error("No localStorage"))?;
+        let length = storage
+            .length()
+            .map_err(|_| utils::js_error("Failed to get length"))?;
+
+        let mut receipt_ids = Vec::new();
+        for i in 0..length {
+            if let Ok(Some(key)) = storage.key(i) {
+                if let Some(id) = key.strip_prefix("paykit_receipts:") {
+                    receipt_ids.push(id.to_string());
+                }
+            }
+        }
+
+        let receipts = WasmReceiptStorage::new();
+        let receipt_db = WasmIndexedDbReceiptStorage::new();
+        for id in &receipt_ids {
+            if let Some(json) = receipts.get_receipt_json(id).await? {
+                receipt_db.save_receipt_json(id, &json).await?;
+                migrated_count += 1;
+            }
+            receipts.remove_receipt(id).await?;
+        }
+
+        let contacts = WasmContactStorage::new();
+        let contact_db = WasmIndexedDbContactStorage::new();
+        for contact in contacts.all_contacts().await? {
+            contact_db.store_contact(&contact).await?;
+            contacts.remove_contact(&contact.public_key()).await?;
+            migrated_count += 1;
+        }
+
+        Ok(migrated_count)
+    }
+}
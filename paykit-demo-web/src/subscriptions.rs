@@ -310,7 +310,7 @@ pub fn is_valid_pubkey(pubkey: &str) -> bool {
 #[wasm_bindgen]
 #[derive(Clone)]
 pub struct WasmSubscription {
-    inner: Subscription,
+    pub(crate) inner: Subscription,
 }
 
 #[wasm_bindgen]
@@ -435,7 +435,7 @@ impl WasmSubscription {
 #[wasm_bindgen]
 #[derive(Clone)]
 pub struct WasmSignedSubscription {
-    inner: SignedSubscription,
+    pub(crate) inner: SignedSubscription,
 }
 
 #[wasm_bindgen]
@@ -5,6 +5,37 @@
 
 use paykit_lib::{MethodId, PublicKey};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Domain separation for subscription signatures made in the browser
+const SUBSCRIPTION_DOMAIN: &[u8] = b"PAYKIT_WEB_SUBSCRIPTION_V1";
+
+/// Domain separation for receipt signatures made in the browser
+const RECEIPT_DOMAIN: &[u8] = b"PAYKIT_WEB_RECEIPT_V1";
+
+/// SHA-256 over `domain` and the JSON encoding of `value`
+fn signing_payload<T: Serialize>(domain: &[u8], value: &T) -> Result<Vec<u8>, String> {
+    let json = serde_json::to_vec(value).map_err(|e| e.to_string())?;
+    let mut hasher = Sha256::new();
+    hasher.update(domain);
+    hasher.update(json);
+    Ok(hasher.finalize().to_vec())
+}
+
+/// Whether `signature` is `signer`'s Ed25519 signature over `message`
+pub fn verify_ed25519(signer: &PublicKey, message: &[u8], signature: &[u8]) -> bool {
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+    let Ok(signature) = <[u8; 64]>::try_from(signature) else {
+        return false;
+    };
+    VerifyingKey::from_bytes(&signer.to_bytes())
+        .map(|key| {
+            key.verify(message, &Signature::from_bytes(&signature))
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
 
 // ============================================================
 // Amount Type (from paykit-subscriptions)
@@ -219,6 +250,13 @@ pub struct SignedSubscription {
     pub provider_signature: Option<Vec<u8>>,
 }
 
+impl Subscription {
+    /// Bytes the subscriber and provider sign
+    pub fn signing_payload(&self) -> Result<Vec<u8>, String> {
+        signing_payload(SUBSCRIPTION_DOMAIN, self)
+    }
+}
+
 impl SignedSubscription {
     pub fn is_active(&self) -> bool {
         self.subscription.is_active()
@@ -229,9 +267,16 @@ impl SignedSubscription {
     }
 
     pub fn verify_signatures(&self) -> Result<bool, String> {
-        // Simplified verification for WASM
-        // In production, would verify Ed25519 signatures
-        Ok(!self.subscriber_signature.is_empty())
+        let payload = self.subscription.signing_payload()?;
+        let subscriber_ok = verify_ed25519(
+            &self.subscription.subscriber,
+            &payload,
+            &self.subscriber_signature,
+        );
+        let provider_ok = self.provider_signature.as_ref().map_or(true, |sig| {
+            verify_ed25519(&self.subscription.provider, &payload, sig)
+        });
+        Ok(subscriber_ok && provider_ok)
     }
 }
 
@@ -292,6 +337,24 @@ impl PaykitReceipt {
         }
     }
 
+    /// Bytes signed for this receipt
+    ///
+    /// Covers the fields fixed at creation; the proof and its verification
+    /// state can change afterwards without invalidating signatures.
+    pub fn signing_payload(&self) -> Result<Vec<u8>, String> {
+        let fields = (
+            &self.receipt_id,
+            &self.payer,
+            &self.payee,
+            &self.method_id,
+            &self.amount,
+            &self.currency,
+            self.created_at,
+            &self.metadata,
+        );
+        signing_payload(RECEIPT_DOMAIN, &fields)
+    }
+
     pub fn with_proof(mut self, proof: serde_json::Value) -> Self {
         self.proof = Some(proof);
         self
@@ -1,5 +1,6 @@
 //! Utility functions for WASM
 
+use paykit_lib::i18n::Locale;
 use wasm_bindgen::prelude::*;
 
 /// Set up better panic messages in the browser console
@@ -377,3 +378,64 @@ pub fn to_paykit_uri(
 
     uri
 }
+
+fn parse_sats(sats: &str) -> Result<u64, JsValue> {
+    sats.trim()
+        .parse()
+        .map_err(|_| js_error(&format!("Invalid amount: {}", sats)))
+}
+
+/// Format a satoshi amount with digit grouping
+///
+/// Amounts are passed as strings, like everywhere else in this module, so
+/// large values survive the trip from JavaScript.
+///
+/// # Examples
+///
+/// ```javascript
+/// import { formatSats } from 'paykit-demo-web';
+///
+/// formatSats("1234567", "en"); // "1,234,567 sats"
+/// formatSats("1234567", "de"); // "1.234.567 sats"
+/// ```
+#[wasm_bindgen(js_name = formatSats)]
+pub fn format_sats(sats: &str, locale: &str) -> Result<String, JsValue> {
+    Ok(paykit_lib::formatting::format_sats(
+        parse_sats(sats)?,
+        Locale::from_tag(locale),
+    ))
+}
+
+/// Format a satoshi amount as BTC, e.g. "0.01234567 BTC"
+#[wasm_bindgen(js_name = formatBtc)]
+pub fn format_btc(sats: &str, locale: &str) -> Result<String, JsValue> {
+    Ok(paykit_lib::formatting::format_btc(
+        parse_sats(sats)?,
+        Locale::from_tag(locale),
+    ))
+}
+
+/// Format a satoshi amount in short form, e.g. "1.2M sats"
+#[wasm_bindgen(js_name = formatCompactSats)]
+pub fn format_compact_sats(sats: &str, locale: &str) -> Result<String, JsValue> {
+    Ok(paykit_lib::formatting::format_compact_sats(
+        parse_sats(sats)?,
+        Locale::from_tag(locale),
+    ))
+}
+
+/// Format a fiat amount, e.g. "$1,234.50" or "1.234,50 €"
+#[wasm_bindgen(js_name = formatFiat)]
+pub fn format_fiat(amount: f64, currency: &str, locale: &str) -> String {
+    paykit_lib::formatting::format_fiat(amount, currency, Locale::from_tag(locale))
+}
+
+/// Convert a BTC decimal string such as "0.001" into satoshis
+///
+/// Returns the satoshi amount as a string.
+#[wasm_bindgen(js_name = btcToSats)]
+pub fn btc_to_sats(btc: &str) -> Result<String, JsValue> {
+    paykit_lib::formatting::btc_to_sats(btc)
+        .map(|sats| sats.to_string())
+        .map_err(|e| js_error(&e.to_string()))
+}
@@ -5,8 +5,8 @@
 
 use js_sys::{Object, Reflect};
 use paykit_lib::{
-    EndpointData, MethodId, PaykitError, PublicKey, Result, SupportedPayments,
-    UnauthenticatedTransportRead,
+    protocol::PathRootConfig, EndpointData, MethodId, PaykitError, PublicKey, Result,
+    SupportedPayments, UnauthenticatedTransportRead,
 };
 use wasm_bindgen::JsCast;
 
@@ -31,6 +31,12 @@ impl WasmUnauthenticatedTransport {
             directory_client: DirectoryClient::with_proxy(homeserver, proxy_url),
         }
     }
+
+    /// Read under the roots in `roots` instead of `paykit.app` only
+    pub fn with_path_roots(mut self, roots: PathRootConfig) -> Self {
+        self.directory_client.set_path_roots(roots);
+        self
+    }
 }
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
@@ -139,6 +145,53 @@ impl UnauthenticatedTransportRead for WasmUnauthenticatedTransport {
     }
 
     async fn get(&self, owner: &PublicKey, path: &str) -> Result<Option<String>> {
+        for path in self.directory_client.path_roots().read_paths(path) {
+            if let Some(content) = self.get_at(owner, &path).await? {
+                return Ok(Some(content));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn list_directory(&self, owner: &PublicKey, path: &str) -> Result<Vec<String>> {
+        let owner_str = owner.to_string();
+
+        let entries_vec = self
+            .directory_client
+            .list_directory(&owner_str, path)
+            .await
+            .map_err(|e| {
+                let error_msg = if e.is_string() {
+                    e.as_string().unwrap_or_else(|| format!("{:?}", e))
+                } else {
+                    format!("{:?}", e)
+                };
+                PaykitError::Transport(format!("Directory listing failed: {}", error_msg))
+            })?;
+
+        let mut entries = Vec::new();
+
+        // entries_vec is already a Vec<JsValue>
+        for entry_js in entries_vec {
+            if let Some(entry_str) = entry_js.as_string() {
+                entries.push(entry_str);
+            } else if entry_js.is_object() {
+                // Some directory listings return objects, extract name if available
+                if let Ok(name) = Reflect::get(&entry_js.into(), &"name".into()) {
+                    if let Some(name_str) = name.as_string() {
+                        entries.push(name_str);
+                    }
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+}
+
+impl WasmUnauthenticatedTransport {
+    /// Fetch one file, already moved into a root
+    async fn get_at(&self, owner: &PublicKey, path: &str) -> Result<Option<String>> {
         let owner_str = owner.to_string();
 
         // Use the directory client's homeserver to construct URL
@@ -194,41 +247,6 @@ impl UnauthenticatedTransportRead for WasmUnauthenticatedTransport {
 
         Ok(Some(content))
     }
-
-    async fn list_directory(&self, owner: &PublicKey, path: &str) -> Result<Vec<String>> {
-        let owner_str = owner.to_string();
-
-        let entries_vec = self
-            .directory_client
-            .list_directory(&owner_str, path)
-            .await
-            .map_err(|e| {
-                let error_msg = if e.is_string() {
-                    e.as_string().unwrap_or_else(|| format!("{:?}", e))
-                } else {
-                    format!("{:?}", e)
-                };
-                PaykitError::Transport(format!("Directory listing failed: {}", error_msg))
-            })?;
-
-        let mut entries = Vec::new();
-
-        // entries_vec is already a Vec<JsValue>
-        for entry_js in entries_vec {
-            if let Some(entry_str) = entry_js.as_string() {
-                entries.push(entry_str);
-            } else if entry_js.is_object() {
-                // Some directory listings return objects, extract name if available
-                if let Ok(name) = Reflect::get(&entry_js.into(), &"name".into()) {
-                    if let Some(name_str) = name.as_string() {
-                        entries.push(name_str);
-                    }
-                }
-            }
-        }
-
-        Ok(entries)
-    }
 }
 
 use std::str::FromStr;
@@ -0,0 +1,122 @@
+//! Ed25519 keys held by the browser's WebCrypto implementation
+//!
+//! Ed25519 in SubtleCrypto is recent (Chrome 137, Firefox 129, Safari 17),
+//! so every call here can fail with `NotSupportedError`; callers fall back
+//! to keys in wasm memory.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{CryptoKey, SubtleCrypto};
+
+const ALGORITHM: &str = "Ed25519";
+
+/// DER prefix of a PKCS#8 Ed25519 private key; the 32-byte seed follows
+const PKCS8_PREFIX: [u8; 16] = [
+    0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x04, 0x22, 0x04, 0x20,
+];
+
+thread_local! {
+    static KEYS: RefCell<HashMap<u32, CryptoKey>> = RefCell::new(HashMap::new());
+    static NEXT_HANDLE: Cell<u32> = const { Cell::new(0) };
+}
+
+/// A private key kept in this thread's key table
+///
+/// `CryptoKey` is a JS object and so not `Send`; key providers hold this
+/// handle instead so they can still be shared with the Noise client.
+pub(crate) struct KeyHandle(u32);
+
+impl KeyHandle {
+    pub(crate) fn new(key: CryptoKey) -> Self {
+        let id = NEXT_HANDLE.with(|next| {
+            let id = next.get();
+            next.set(id.wrapping_add(1));
+            id
+        });
+        KEYS.with(|keys| keys.borrow_mut().insert(id, key));
+        Self(id)
+    }
+
+    pub(crate) fn key(&self) -> Result<CryptoKey, JsValue> {
+        KEYS.with(|keys| keys.borrow().get(&self.0).cloned())
+            .ok_or_else(|| JsValue::from_str("Key handle used on another thread"))
+    }
+}
+
+impl Drop for KeyHandle {
+    fn drop(&mut self) {
+        KEYS.with(|keys| keys.borrow_mut().remove(&self.0));
+    }
+}
+
+fn subtle() -> Result<SubtleCrypto, JsValue> {
+    let window = web_sys::window().ok_or("No window")?;
+    Ok(window.crypto()?.subtle())
+}
+
+fn sign_usage() -> js_sys::Array {
+    js_sys::Array::of1(&"sign".into())
+}
+
+async fn bytes_of(promise: js_sys::Promise) -> Result<Vec<u8>, JsValue> {
+    let buffer = JsFuture::from(promise).await?;
+    Ok(js_sys::Uint8Array::new(&buffer).to_vec())
+}
+
+fn array_32(bytes: &[u8], what: &str) -> Result<[u8; 32], JsValue> {
+    bytes
+        .try_into()
+        .map_err(|_| JsValue::from_str(&format!("Unexpected {} length: {}", what, bytes.len())))
+}
+
+/// Generate a key pair, returning the private key and raw public key
+pub(crate) async fn generate(extractable: bool) -> Result<(CryptoKey, [u8; 32]), JsValue> {
+    let subtle = subtle()?;
+    let pair =
+        JsFuture::from(subtle.generate_key_with_str(ALGORITHM, extractable, &sign_usage())?)
+            .await?;
+    let private_key: CryptoKey = js_sys::Reflect::get(&pair, &"privateKey".into())?.dyn_into()?;
+    let public_key: CryptoKey = js_sys::Reflect::get(&pair, &"publicKey".into())?.dyn_into()?;
+
+    // Public keys are always exportable
+    let public = bytes_of(subtle.export_key("raw", &public_key)?).await?;
+    Ok((private_key, array_32(&public, "public key")?))
+}
+
+/// Import a private key from its 32-byte seed
+pub(crate) async fn import_seed(seed: &[u8; 32], extractable: bool) -> Result<CryptoKey, JsValue> {
+    let mut pkcs8 = PKCS8_PREFIX.to_vec();
+    pkcs8.extend_from_slice(seed);
+    let data = js_sys::Uint8Array::from(pkcs8.as_slice());
+
+    let key = JsFuture::from(subtle()?.import_key_with_str(
+        "pkcs8",
+        &data,
+        ALGORITHM,
+        extractable,
+        &sign_usage(),
+    )?)
+    .await?;
+    key.dyn_into()
+}
+
+/// The 32-byte seed of an extractable private key
+pub(crate) async fn export_seed(key: &CryptoKey) -> Result<[u8; 32], JsValue> {
+    let pkcs8 = bytes_of(subtle()?.export_key("pkcs8", key)?).await?;
+    match pkcs8.strip_prefix(&PKCS8_PREFIX[..]) {
+        Some(seed) => array_32(seed, "private key"),
+        None => Err(JsValue::from_str("Unexpected PKCS#8 encoding")),
+    }
+}
+
+/// Sign `message` with a private key
+pub(crate) async fn sign(key: &CryptoKey, message: &[u8]) -> Result<[u8; 64], JsValue> {
+    let data = js_sys::Uint8Array::from(message);
+    let signature =
+        bytes_of(subtle()?.sign_with_str_and_buffer_source(ALGORITHM, key, &data)?).await?;
+    signature
+        .try_into()
+        .map_err(|_| JsValue::from_str("Unexpected signature length"))
+}
@@ -14,6 +14,7 @@ import init, {
     WasmPaymentMethodStorage,
     WasmReceiptStorage,
     WasmPaymentCoordinator,
+    WasmPrivateEndpointExchange,
     WasmDashboard,
     WasmAutoPayRule,
     WasmAutoPayRuleStorage,
@@ -33,6 +34,7 @@ let contactStorage;
 let paymentMethodStorage;
 let receiptStorage;
 let paymentCoordinator;
+let privateEndpointExchange;
 let dashboard;
 let autopayRuleStorage;
 let peerLimitStorage;
@@ -60,6 +62,7 @@ async function initializeApp() {
         paymentMethodStorage = new WasmPaymentMethodStorage();
         receiptStorage = new WasmReceiptStorage();
         paymentCoordinator = new WasmPaymentCoordinator();
+        privateEndpointExchange = new WasmPrivateEndpointExchange();
         dashboard = new WasmDashboard();
         
         // Initialize auto-pay and spending limits storage
@@ -99,6 +102,7 @@ async function initializeApp() {
         await updateContactsList();
         await updatePaymentMethodsList();
         await updateReceiptsList();
+        await updatePrivateEndpointsList();
         await updateDashboard();
         
         // Set version
@@ -3974,10 +3978,14 @@ async function runSmartCheckout() {
             return;
         }
         
+        // Prefer endpoints the recipient shared with us privately
+        const privateEndpoints = await loadPrivateEndpoints(pubkey);
+        
         // Score and rank methods
         const scoredMethods = methods.map(m => {
             const methodId = m.method_id || m.methodId || 'unknown';
-            const endpoint = m.endpoint || '';
+            const isPrivate = Boolean(privateEndpoints[methodId]);
+            const endpoint = privateEndpoints[methodId] || m.endpoint || '';
             
             // Calculate scores based on method type
             let costScore = 0.5, speedScore = 0.5, privacyScore = 0.5;
@@ -3995,6 +4003,10 @@ async function runSmartCheckout() {
                 speedScore = 0.9;
                 privacyScore = 1.0;
             }
+            if (isPrivate) {
+                // Not in the public directory, so not linkable to other payers
+                privacyScore = 1.0;
+            }
             
             // Calculate composite score based on strategy
             let score;
@@ -4012,7 +4024,7 @@ async function runSmartCheckout() {
                     score = (costScore + speedScore + privacyScore) / 3;
             }
             
-            return { methodId, endpoint, score, costScore, speedScore, privacyScore };
+            return { methodId, endpoint, isPrivate, score, costScore, speedScore, privacyScore };
         });
         
         // Sort by score (highest first)
@@ -4028,7 +4040,7 @@ async function runSmartCheckout() {
             return `
                 <div class="checkout-method ${isRecommended ? 'recommended' : ''}" data-method="${m.methodId}" data-endpoint="${m.endpoint}">
                     <div class="method-header">
-                        <span class="method-name">${isRecommended ? '⭐ ' : ''}${m.methodId}</span>
+                        <span class="method-name">${isRecommended ? '⭐ ' : ''}${m.methodId}${m.isPrivate ? ' 🔒 private' : ''}</span>
                         <span class="method-score">Score: ${(m.score * 100).toFixed(0)}%</span>
                     </div>
                     <div class="method-endpoint">${truncatedEndpoint}</div>
@@ -4088,6 +4100,154 @@ function proceedWithSmartCheckout() {
     validatePaymentForm();
 }
 
+// ========================================
+// Private Endpoints
+// ========================================
+
+/**
+ * Private endpoints a peer shared with us, keyed by method ID
+ */
+async function loadPrivateEndpoints(pubkey) {
+    const endpoints = {};
+    if (!privateEndpointExchange) return endpoints;
+    try {
+        for (const e of await privateEndpointExchange.list_endpoints(pubkey)) {
+            if (!e.expires_at || e.expires_at > Math.floor(Date.now() / 1000)) {
+                endpoints[e.method_id] = e.endpoint;
+            }
+        }
+    } catch (error) {
+        console.warn('Failed to load private endpoints:', error);
+    }
+    return endpoints;
+}
+
+/**
+ * Resolve a peer's noise:// endpoint for a private endpoint exchange
+ */
+async function resolvePeerChannel(peerInput) {
+    const homeserver = document.getElementById('homeserver-input')?.value || 'https://demo.httprelay.io';
+    const peerPubkey = await extractPayeePubkey(peerInput);
+    const noiseEndpoint = await discoverRecipientEndpoint(peerInput, homeserver);
+    if (!noiseEndpoint) {
+        throw new Error('Peer has not published a noise:// endpoint');
+    }
+    return { peerPubkey, ...parseNoiseEndpoint(noiseEndpoint) };
+}
+
+async function offerPrivateEndpoint() {
+    const peerInput = document.getElementById('private-endpoint-peer').value.trim();
+    const method = document.getElementById('private-endpoint-method').value;
+    const endpoint = document.getElementById('private-endpoint-value').value.trim();
+    const expiryDays = parseInt(document.getElementById('private-endpoint-expiry').value || '0', 10);
+    
+    if (!currentIdentity) {
+        showNotification('Please create or load an identity first', 'error');
+        return;
+    }
+    if (!peerInput || !endpoint) {
+        showNotification('Please enter a peer and an endpoint', 'error');
+        return;
+    }
+    
+    try {
+        const channel = await resolvePeerChannel(peerInput);
+        const expiresAt = expiryDays > 0 ? Math.floor(Date.now() / 1000) + expiryDays * 86400 : null;
+        const accepted = await privateEndpointExchange.offer_endpoints(
+            currentIdentity.toJSON(),
+            channel.wsUrl,
+            channel.peerPubkey,
+            channel.serverKeyHex,
+            JSON.stringify([{ method_id: method, endpoint, expires_at: expiresAt }])
+        );
+        if (accepted.includes(method)) {
+            showNotification(`Peer accepted your private ${method} endpoint`, 'success');
+        } else {
+            showNotification('Peer did not accept the endpoint', 'warning');
+        }
+    } catch (error) {
+        handleError(error, 'offering private endpoint');
+    }
+}
+
+async function receivePrivateEndpoints() {
+    const peerInput = document.getElementById('private-endpoint-peer').value.trim();
+    
+    if (!currentIdentity) {
+        showNotification('Please create or load an identity first', 'error');
+        return;
+    }
+    if (!peerInput) {
+        showNotification('Please enter a peer', 'error');
+        return;
+    }
+    
+    try {
+        const channel = await resolvePeerChannel(peerInput);
+        const endpoints = await privateEndpointExchange.receive_offer(
+            currentIdentity.toJSON(),
+            channel.wsUrl,
+            channel.peerPubkey,
+            channel.serverKeyHex
+        );
+        showNotification(`Stored ${endpoints.length} private endpoint(s) from peer`, 'success');
+        await updatePrivateEndpointsList();
+    } catch (error) {
+        handleError(error, 'receiving private endpoints');
+    }
+}
+
+async function updatePrivateEndpointsList() {
+    const list = document.getElementById('private-endpoints-list');
+    if (!list || !privateEndpointExchange) return;
+    
+    try {
+        await privateEndpointExchange.cleanup_expired();
+        const rows = [];
+        for (const peer of await privateEndpointExchange.list_peers()) {
+            for (const e of await privateEndpointExchange.list_endpoints(peer)) {
+                const expires = e.expires_at ? new Date(e.expires_at * 1000).toLocaleDateString() : 'never';
+                rows.push(`
+                    <div class="method-card">
+                        <div class="method-header">
+                            <div class="method-title">
+                                <span class="method-icon">${getMethodIcon(e.method_id)}</span>
+                                <span class="method-name">${e.method_id}</span>
+                                <span class="private-badge">🔒 ${truncateKey(e.peer)}</span>
+                            </div>
+                            <div class="method-actions">
+                                <button class="btn-sm" onclick="removePrivateEndpoint('${e.peer}', '${e.method_id}')" style="background: var(--error)">Remove</button>
+                            </div>
+                        </div>
+                        <div class="method-body">
+                            <div class="method-endpoint">${truncateEndpoint(e.endpoint)}</div>
+                            <div class="method-meta">Expires: ${expires}</div>
+                        </div>
+                    </div>
+                `);
+            }
+        }
+        list.innerHTML = rows.length > 0
+            ? rows.join('')
+            : '<p class="empty-state">No private endpoints received yet</p>';
+    } catch (error) {
+        console.error('Failed to list private endpoints:', error);
+    }
+}
+
+async function removePrivateEndpoint(peer, methodId) {
+    try {
+        await privateEndpointExchange.remove_endpoint(peer, methodId);
+        showNotification('Private endpoint removed', 'success');
+        await updatePrivateEndpointsList();
+    } catch (error) {
+        handleError(error, 'removing private endpoint');
+    }
+}
+
+// Make private endpoint functions global for HTML onclick handlers
+window.removePrivateEndpoint = removePrivateEndpoint;
+
 // ========================================
 // URL Routing for Deep Links
 // ========================================
@@ -4407,6 +4567,13 @@ document.addEventListener('DOMContentLoaded', () => {
     const smartCheckoutProceedBtn = document.getElementById('smart-checkout-proceed-btn');
     if (smartCheckoutProceedBtn) smartCheckoutProceedBtn.addEventListener('click', proceedWithSmartCheckout);
     
+    // Private endpoint actions
+    const offerPrivateEndpointBtn = document.getElementById('offer-private-endpoint-btn');
+    if (offerPrivateEndpointBtn) offerPrivateEndpointBtn.addEventListener('click', offerPrivateEndpoint);
+    
+    const receivePrivateEndpointsBtn = document.getElementById('receive-private-endpoints-btn');
+    if (receivePrivateEndpointsBtn) receivePrivateEndpointsBtn.addEventListener('click', receivePrivateEndpoints);
+    
     // Initialize app (don't block on errors)
     initializeApp().catch(err => {
         console.error('Failed to initialize app:', err);
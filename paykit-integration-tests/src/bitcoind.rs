@@ -0,0 +1,159 @@
+//! bitcoind JSON-RPC client and PSBT signer.
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use paykit_lib::executors::PsbtSigner;
+use paykit_lib::PaykitError;
+use serde_json::{json, Value};
+
+/// Minimal bitcoind JSON-RPC client.
+///
+/// A client created with [`BitcoindRpc::wallet`] sends wallet calls to that
+/// wallet's endpoint.
+#[derive(Clone)]
+pub struct BitcoindRpc {
+    client: reqwest::Client,
+    url: String,
+    user: String,
+    password: String,
+}
+
+impl BitcoindRpc {
+    /// Create a client for the node at `url`.
+    pub fn new(
+        url: impl Into<String>,
+        user: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.into(),
+            user: user.into(),
+            password: password.into(),
+        }
+    }
+
+    /// A client for the named wallet on the same node.
+    pub fn wallet(&self, name: &str) -> Self {
+        let base = self.url.split("/wallet/").next().unwrap_or(&self.url);
+        Self {
+            url: format!("{}/wallet/{}", base, name),
+            ..self.clone()
+        }
+    }
+
+    /// Call `method` and return its result.
+    pub async fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let response: Value = self
+            .client
+            .post(&self.url)
+            .basic_auth(&self.user, Some(&self.password))
+            .json(&json!({
+                "jsonrpc": "1.0",
+                "id": "paykit-e2e",
+                "method": method,
+                "params": params,
+            }))
+            .send()
+            .await
+            .with_context(|| format!("bitcoind {} failed", method))?
+            .json()
+            .await
+            .with_context(|| format!("bitcoind {} returned invalid JSON", method))?;
+
+        match response.get("error") {
+            Some(error) if !error.is_null() => bail!("bitcoind {}: {}", method, error),
+            _ => Ok(response.get("result").cloned().unwrap_or(Value::Null)),
+        }
+    }
+
+    /// Create a wallet and return a client for it.
+    pub async fn create_wallet(&self, name: &str) -> Result<Self> {
+        self.call("createwallet", json!([name])).await?;
+        Ok(self.wallet(name))
+    }
+
+    /// A fresh bech32 address from this wallet.
+    pub async fn new_address(&self) -> Result<String> {
+        as_string(self.call("getnewaddress", json!(["", "bech32"])).await?)
+    }
+
+    /// Mine `blocks` blocks paying the reward to `address`.
+    pub async fn mine(&self, blocks: u64, address: &str) -> Result<()> {
+        self.call("generatetoaddress", json!([blocks, address]))
+            .await?;
+        Ok(())
+    }
+
+    /// Current block height.
+    pub async fn block_count(&self) -> Result<u64> {
+        self.call("getblockcount", json!([]))
+            .await?
+            .as_u64()
+            .ok_or_else(|| anyhow!("getblockcount returned a non-integer"))
+    }
+
+    /// Send `sats` from this wallet to `address`, returning the txid.
+    pub async fn send_to_address(&self, address: &str, sats: u64) -> Result<String> {
+        let btc = sats as f64 / 100_000_000.0;
+        as_string(self.call("sendtoaddress", json!([address, btc])).await?)
+    }
+
+    /// Satoshis this wallet has received at `address` with at least
+    /// `min_confirmations`.
+    pub async fn received_by_address(&self, address: &str, min_confirmations: u32) -> Result<u64> {
+        let btc = self
+            .call("getreceivedbyaddress", json!([address, min_confirmations]))
+            .await?
+            .as_f64()
+            .ok_or_else(|| anyhow!("getreceivedbyaddress returned a non-number"))?;
+        Ok((btc * 100_000_000.0).round() as u64)
+    }
+}
+
+fn as_string(value: Value) -> Result<String> {
+    value
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("expected a string, got {}", value))
+}
+
+/// [`PsbtSigner`] backed by a bitcoind wallet.
+///
+/// Stands in for the hardware wallet or host app wallet that signs for
+/// `EsploraBitcoinExecutor` in production.
+pub struct BitcoindSigner {
+    wallet: BitcoindRpc,
+}
+
+impl BitcoindSigner {
+    /// Sign with the keys in `wallet`.
+    pub fn new(wallet: BitcoindRpc) -> Self {
+        Self { wallet }
+    }
+}
+
+#[async_trait]
+impl PsbtSigner for BitcoindSigner {
+    async fn sign_psbt(&self, psbt_base64: &str) -> paykit_lib::Result<String> {
+        let result = self
+            .wallet
+            .call(
+                "walletprocesspsbt",
+                json!([psbt_base64, true, "ALL", true, true]),
+            )
+            .await
+            .map_err(|e| PaykitError::Transport(e.to_string()))?;
+
+        if result.get("complete").and_then(Value::as_bool) != Some(true) {
+            return Err(PaykitError::Transport(
+                "bitcoind could not sign every input".to_string(),
+            ));
+        }
+        result
+            .get("psbt")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| PaykitError::Transport("walletprocesspsbt returned no PSBT".to_string()))
+    }
+}
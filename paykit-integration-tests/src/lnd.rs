@@ -0,0 +1,207 @@
+//! LND REST client for node setup and assertions.
+//!
+//! Payments go through [`LndExecutor`]; this client covers what the
+//! executor does not: funding, peering, channels and invoices.
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::Engine;
+use paykit_lib::executors::{BitcoinNetwork, LndConfig, LndExecutor};
+use serde_json::{json, Value};
+
+/// LND runs with `--no-macaroons`; the executor still requires a value.
+const UNUSED_MACAROON: &str = "00";
+
+/// A running LND node.
+pub struct LndNode {
+    /// Container name, which is also the node's host name on the Docker
+    /// network.
+    pub host: String,
+    /// REST URL reachable from the host.
+    pub rest_url: String,
+    /// The node's self-signed TLS certificate.
+    pub tls_cert_pem: String,
+    client: reqwest::Client,
+}
+
+/// An invoice created with [`LndNode::add_invoice`].
+#[derive(Clone, Debug)]
+pub struct LndInvoice {
+    /// BOLT11 payment request.
+    pub payment_request: String,
+    /// Payment hash, hex-encoded.
+    pub payment_hash: String,
+}
+
+impl LndNode {
+    /// Connect to the node at `rest_url`, trusting only `tls_cert_pem`.
+    pub fn new(
+        host: impl Into<String>,
+        rest_url: impl Into<String>,
+        tls_cert_pem: impl Into<String>,
+    ) -> Result<Self> {
+        let tls_cert_pem = tls_cert_pem.into();
+        let client = reqwest::Client::builder()
+            .tls_built_in_root_certs(false)
+            .add_root_certificate(reqwest::Certificate::from_pem(tls_cert_pem.as_bytes())?)
+            .build()?;
+        Ok(Self {
+            host: host.into(),
+            rest_url: rest_url.into(),
+            tls_cert_pem,
+            client,
+        })
+    }
+
+    /// An executor for paying from this node.
+    pub fn executor(&self) -> paykit_lib::Result<LndExecutor> {
+        LndExecutor::new(
+            LndConfig::new(&self.rest_url, UNUSED_MACAROON)
+                .with_tls_cert(&self.tls_cert_pem)
+                .with_network(BitcoinNetwork::Regtest)
+                .with_timeout(30),
+        )
+    }
+
+    async fn request(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> Result<Value> {
+        let url = format!("{}/v1/{}", self.rest_url, path);
+        let mut request = self.client.request(method, &url);
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("{}: {} failed", self.host, path))?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or(Value::Null);
+        if !status.is_success() {
+            bail!("{}: {} returned {}: {}", self.host, path, status, body);
+        }
+        Ok(body)
+    }
+
+    async fn get(&self, path: &str) -> Result<Value> {
+        self.request(reqwest::Method::GET, path, None).await
+    }
+
+    async fn post(&self, path: &str, body: Value) -> Result<Value> {
+        self.request(reqwest::Method::POST, path, Some(body)).await
+    }
+
+    /// The node's public key, hex-encoded.
+    pub async fn identity_pubkey(&self) -> Result<String> {
+        string_field(&self.get("getinfo").await?, "identity_pubkey")
+    }
+
+    /// Whether the node has caught up with the chain tip.
+    pub async fn is_synced(&self) -> Result<bool> {
+        Ok(self.get("getinfo").await?["synced_to_chain"].as_bool() == Some(true))
+    }
+
+    /// Number of active channels.
+    pub async fn active_channels(&self) -> Result<u64> {
+        Ok(self.get("getinfo").await?["num_active_channels"]
+            .as_u64()
+            .unwrap_or(0))
+    }
+
+    /// A fresh P2WPKH address from the node's wallet.
+    pub async fn new_address(&self) -> Result<String> {
+        string_field(&self.get("newaddress?type=0").await?, "address")
+    }
+
+    /// Connect to `peer` over the Docker network.
+    pub async fn connect(&self, peer: &LndNode) -> Result<()> {
+        self.post(
+            "peers",
+            json!({
+                "addr": {
+                    "pubkey": peer.identity_pubkey().await?,
+                    "host": format!("{}:9735", peer.host),
+                },
+                "perm": false,
+            }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Open a channel to `peer`, pushing `push_sats` of it to the peer.
+    pub async fn open_channel(
+        &self,
+        peer: &LndNode,
+        capacity_sats: u64,
+        push_sats: u64,
+    ) -> Result<()> {
+        let pubkey = hex::decode(peer.identity_pubkey().await?)?;
+        self.post(
+            "channels",
+            json!({
+                "node_pubkey": base64::engine::general_purpose::STANDARD.encode(pubkey),
+                "local_funding_amount": capacity_sats.to_string(),
+                "push_sat": push_sats.to_string(),
+            }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Create an invoice. `None` leaves the amount to the payer, as a
+    /// published directory endpoint must.
+    pub async fn add_invoice(&self, sats: Option<u64>, memo: &str) -> Result<LndInvoice> {
+        let mut body = json!({ "memo": memo });
+        if let Some(sats) = sats {
+            body["value"] = json!(sats.to_string());
+        }
+        let response = self.post("invoices", body).await?;
+        let r_hash =
+            base64::engine::general_purpose::STANDARD.decode(string_field(&response, "r_hash")?)?;
+        Ok(LndInvoice {
+            payment_request: string_field(&response, "payment_request")?,
+            payment_hash: hex::encode(r_hash),
+        })
+    }
+
+    /// Satoshis paid to the invoice with `payment_hash`, if it has settled.
+    pub async fn settled_amount(&self, payment_hash: &str) -> Result<Option<u64>> {
+        let invoice = self.get(&format!("invoice/{}", payment_hash)).await?;
+        if invoice["state"].as_str() != Some("SETTLED") {
+            return Ok(None);
+        }
+        Ok(Some(u64_field(&invoice, "amt_paid_sat")?))
+    }
+
+    /// Confirmed on-chain wallet balance in satoshis.
+    pub async fn wallet_balance(&self) -> Result<u64> {
+        u64_field(&self.get("balance/blockchain").await?, "confirmed_balance")
+    }
+
+    /// Local balance across all channels in satoshis.
+    pub async fn channel_balance(&self) -> Result<u64> {
+        let balance = self.get("balance/channels").await?;
+        u64_field(&balance["local_balance"], "sat")
+    }
+}
+
+fn string_field(value: &Value, field: &str) -> Result<String> {
+    value[field]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("missing field {} in {}", field, value))
+}
+
+/// LND encodes 64-bit integers as JSON strings.
+fn u64_field(value: &Value, field: &str) -> Result<u64> {
+    match &value[field] {
+        Value::String(s) => Ok(s.parse()?),
+        Value::Number(n) => n.as_u64().ok_or_else(|| anyhow!("{} is not a u64", field)),
+        // Zero values are omitted from the response
+        Value::Null => Ok(0),
+        other => bail!("unexpected {} value: {}", field, other),
+    }
+}
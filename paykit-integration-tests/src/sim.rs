@@ -0,0 +1,661 @@
+//! Deterministic simulation of a payer paying its providers.
+//!
+//! The Docker tests prove each flow works once against real backends. This
+//! module replays thousands of randomized flows in-process against the real
+//! spending-limit storage, Lightning plugin, invoice scheduler and receipts,
+//! with the network replaced by a [`SimLightningNode`] that refuses, loses
+//! and delays payments. One seed drives every choice, so a failure replays
+//! from its seed alone.
+//!
+//! A scenario runs for [`SimConfig::days`] of virtual time. Each tick:
+//!
+//! 1. Due subscription invoices are issued and checked against autopay
+//!    rules; approved ones get a Lightning invoice from the provider.
+//! 2. Sometimes the payer makes a one-off payment to a provider.
+//! 3. Sometimes the payer restarts and reopens its storage.
+//! 4. The payer works through its outbox: reserve against the peer limit,
+//!    pay, and reconcile with the node when the result is not a clean
+//!    success. Payments in flight keep their reservation until they resolve.
+//!
+//! After the last tick in-flight payments resolve and the invariants are
+//! checked:
+//!
+//! - every settled payment has exactly one receipt, for the same amount and
+//!   payee, with a preimage that matches its hash, and no request is paid
+//!   twice;
+//! - each peer limit has spent exactly what was paid to that peer, and
+//!   never more than the limit;
+//! - every invoice marked paid names the receipt that paid it, and every
+//!   invoice with a receipt is marked paid;
+//! - the node's balance plus what it paid out equals its starting balance.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::{bail, ensure, Context, Result};
+use paykit_interactive::PaykitReceipt;
+use paykit_lib::methods::{
+    Amount as MethodAmount, LightningExecutor, LightningNetwork, LightningPaymentStatus,
+    LightningPlugin, PaymentMethodPlugin,
+};
+use paykit_lib::test_utils::sim::{FaultPlan, SimClock, SimLightningNode, SimRng};
+use paykit_lib::{EndpointData, MethodId, PublicKey};
+use paykit_subscriptions::invoice::{
+    InvoiceItem, InvoiceScheduler, InvoiceStatus, InvoiceTemplate, RecurringInvoice,
+    INVOICE_METADATA_KEY,
+};
+use paykit_subscriptions::{
+    Amount, AutoPayRule, FileSubscriptionStorage, PaymentFrequency, PeerSpendingLimit,
+    ReservationToken, SubscriptionError, SubscriptionStorage,
+};
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+/// 2024-01-01T00:00:00Z.
+const START: i64 = 1_704_067_200;
+const HOUR: u64 = 3600;
+const DAY: i64 = 86_400;
+/// Longest a simulated payment stays in flight.
+const MAX_IN_FLIGHT_SECS: i64 = 6 * 3600;
+/// Attempts per charge before the payer gives up on it.
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Shape of the generated scenarios.
+#[derive(Clone, Debug)]
+pub struct SimConfig {
+    /// Virtual days per scenario. Peer limits are monthly and do not reset
+    /// within a scenario, so keep this under 30.
+    pub days: i64,
+    /// Most providers the payer subscribes to.
+    pub max_providers: u64,
+    /// Chance per tick of a one-off payment.
+    pub one_off_rate: f64,
+    /// Chance per tick of the payer restarting.
+    pub restart_rate: f64,
+    /// Chance per payment of the node refusing it.
+    pub reject_rate: f64,
+    /// Chance per payment of it settling while the payer sees a timeout.
+    pub lost_response_rate: f64,
+    /// Chance per payment of it staying in flight.
+    pub in_flight_rate: f64,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            days: 28,
+            max_providers: 3,
+            one_off_rate: 0.2,
+            restart_rate: 0.05,
+            reject_rate: 0.1,
+            lost_response_rate: 0.05,
+            in_flight_rate: 0.05,
+        }
+    }
+}
+
+impl SimConfig {
+    /// A configuration without injected faults.
+    pub fn fault_free() -> Self {
+        Self {
+            reject_rate: 0.0,
+            lost_response_rate: 0.0,
+            in_flight_rate: 0.0,
+            ..Self::default()
+        }
+    }
+}
+
+/// What happened in a scenario.
+///
+/// Two runs of the same seed and configuration produce equal reports.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SimReport {
+    /// Subscription invoices issued by providers.
+    pub invoices_issued: u64,
+    /// One-off payments started by the payer.
+    pub one_off_payments: u64,
+    /// Invoices refused by the payer's autopay rule.
+    pub declined_by_rule: u64,
+    /// Charges refused because they would exceed the peer limit.
+    pub declined_by_limit: u64,
+    /// Payment attempts after the first for the same charge.
+    pub retries: u64,
+    /// Charges whose payment stayed in flight.
+    pub in_doubt: u64,
+    /// Charges that settled although the payer saw no clean success.
+    pub settled_after_reconcile: u64,
+    /// Charges given up after three failed attempts.
+    pub abandoned: u64,
+    /// Times the payer restarted.
+    pub restarts: u64,
+    /// Payments settled.
+    pub settled: u64,
+    /// Satoshis paid to providers.
+    pub spent_sats: u64,
+    /// Routing fees paid, in millisatoshis.
+    pub fees_msat: u64,
+}
+
+impl SimReport {
+    /// Add `other`'s counts to this report.
+    pub fn add(&mut self, other: &SimReport) {
+        self.invoices_issued += other.invoices_issued;
+        self.one_off_payments += other.one_off_payments;
+        self.declined_by_rule += other.declined_by_rule;
+        self.declined_by_limit += other.declined_by_limit;
+        self.retries += other.retries;
+        self.in_doubt += other.in_doubt;
+        self.settled_after_reconcile += other.settled_after_reconcile;
+        self.abandoned += other.abandoned;
+        self.restarts += other.restarts;
+        self.settled += other.settled;
+        self.spent_sats += other.spent_sats;
+        self.fees_msat += other.fees_msat;
+    }
+}
+
+/// Run the scenario for `seed` and check its invariants.
+///
+/// Errors name the seed, which is all that is needed to replay the run.
+pub async fn run_scenario(seed: u64, config: &SimConfig) -> Result<SimReport> {
+    let mut sim = Simulation::new(seed, config.clone()).await?;
+    async {
+        sim.run().await?;
+        sim.check_invariants().await
+    }
+    .await
+    .with_context(|| format!("scenario failed (replay with PAYKIT_SIM_SEED={})", seed))?;
+    Ok(sim.report)
+}
+
+struct Provider {
+    name: String,
+    key: PublicKey,
+    rule: AutoPayRule,
+}
+
+/// A payment the payer owes, from approval until it settles or is dropped.
+struct Charge {
+    request_id: String,
+    provider: usize,
+    amount_sats: u64,
+    invoice: String,
+    payment_hash: String,
+    metadata: Value,
+    reservation: Option<ReservationToken>,
+    attempts: u32,
+    in_doubt: bool,
+}
+
+struct Simulation {
+    config: SimConfig,
+    rng: SimRng,
+    clock: SimClock,
+    node: Arc<SimLightningNode>,
+    plugin: LightningPlugin,
+    dir: TempDir,
+    storage: FileSubscriptionStorage,
+    scheduler: InvoiceScheduler,
+    payer: PublicKey,
+    providers: Vec<Provider>,
+    outbox: Vec<Charge>,
+    receipts: Vec<PaykitReceipt>,
+    initial_balance_msat: u64,
+    report: SimReport,
+}
+
+fn lightning() -> MethodId {
+    MethodId("lightning".to_string())
+}
+
+fn public_key(rng: &mut SimRng) -> PublicKey {
+    pubky::Keypair::from_secret_key(&rng.bytes32()).public_key()
+}
+
+impl Simulation {
+    async fn new(seed: u64, config: SimConfig) -> Result<Self> {
+        let mut rng = SimRng::new(seed);
+        let clock = SimClock::new(START);
+
+        let dir = tempfile::tempdir()?;
+        let storage = FileSubscriptionStorage::new(dir.path().to_path_buf())?;
+        let scheduler = InvoiceScheduler::new();
+        let payer = public_key(&mut rng);
+
+        let mut providers = Vec::new();
+        let mut total_limits = 0;
+        for i in 0..rng.range(1, config.max_providers + 1) {
+            let name = format!("provider{}", i);
+            let key = public_key(&mut rng);
+            let price = rng.range(500, 20_000);
+            let frequency = if rng.chance(0.5) {
+                PaymentFrequency::Daily
+            } else {
+                PaymentFrequency::Weekly
+            };
+            let template = InvoiceTemplate::new(
+                format!("{}-plan", name),
+                vec![InvoiceItem::new("Plan", 1, Amount::from_sats(price as i64))],
+            );
+            let schedule_id = format!("{}-sub", name);
+            scheduler.add_schedule(RecurringInvoice::new(
+                schedule_id.clone(),
+                template,
+                key.clone(),
+                payer.clone(),
+                "SAT",
+                lightning(),
+                frequency,
+                START,
+            )?)?;
+
+            // The per-charge cap is sometimes below the price, and the
+            // period limit sometimes runs out before the scenario ends
+            let max_payment = price * rng.range(8, 20) / 10;
+            let rule = AutoPayRule::new(schedule_id, key.clone(), lightning())
+                .with_max_payment_amount(Amount::from_sats(max_payment as i64));
+            let limit = price * rng.range(5, 30);
+            total_limits += limit;
+            storage
+                .save_peer_limit(&PeerSpendingLimit::new(
+                    key.clone(),
+                    Amount::from_sats(limit as i64),
+                    "monthly".to_string(),
+                ))
+                .await?;
+            providers.push(Provider { name, key, rule });
+        }
+
+        // Between half and one and a half times what the limits allow, so
+        // some payers run out of funds
+        let balance_sats = total_limits * rng.range(50, 150) / 100;
+        let faults = FaultPlan::new().with_rates(
+            config.reject_rate,
+            config.lost_response_rate,
+            config.in_flight_rate,
+            MAX_IN_FLIGHT_SECS,
+        );
+        let node = Arc::new(
+            SimLightningNode::new(rng.next_u64(), clock.clone(), balance_sats).with_faults(faults),
+        );
+        let plugin =
+            LightningPlugin::with_network_and_executor(LightningNetwork::Regtest, node.clone());
+
+        Ok(Self {
+            config,
+            rng,
+            clock,
+            initial_balance_msat: node.balance_msat(),
+            node,
+            plugin,
+            dir,
+            storage,
+            scheduler,
+            payer,
+            providers,
+            outbox: Vec::new(),
+            receipts: Vec::new(),
+            report: SimReport::default(),
+        })
+    }
+
+    async fn run(&mut self) -> Result<()> {
+        let end = START + self.config.days * DAY;
+        while self.clock.now() < end {
+            self.issue_due().await?;
+            if self.rng.chance(self.config.one_off_rate) {
+                self.queue_one_off().await?;
+            }
+            if self.rng.chance(self.config.restart_rate) {
+                self.restart()?;
+            }
+            self.work_outbox().await?;
+            self.clock.advance(self.rng.range(HOUR, 12 * HOUR) as i64);
+        }
+
+        // Let every in-flight payment resolve, then settle the books
+        self.clock.advance(MAX_IN_FLIGHT_SECS);
+        let outbox = std::mem::take(&mut self.outbox);
+        for mut charge in outbox {
+            if charge.in_doubt && self.reconcile(&mut charge).await? {
+                self.outbox.push(charge);
+            }
+        }
+        Ok(())
+    }
+
+    async fn issue_due(&mut self) -> Result<()> {
+        for request in self.scheduler.generate_due(self.clock.now())? {
+            self.report.invoices_issued += 1;
+            let provider = self
+                .providers
+                .iter()
+                .position(|p| p.key == request.from)
+                .context("invoice from unknown provider")?;
+            if !self.providers[provider]
+                .rule
+                .is_amount_within_limit(&request.amount)
+            {
+                self.report.declined_by_rule += 1;
+                continue;
+            }
+            let memo = request.invoice_number.clone().unwrap_or_default();
+            self.queue(
+                request.request_id,
+                provider,
+                request.amount.as_sats() as u64,
+                &memo,
+                request.metadata,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn queue_one_off(&mut self) -> Result<()> {
+        self.report.one_off_payments += 1;
+        let provider = self.rng.range(0, self.providers.len() as u64) as usize;
+        let amount_sats = self.rng.range(100, 10_000);
+        let request_id = format!("oneoff_{}", self.report.one_off_payments);
+        self.queue(request_id, provider, amount_sats, "one-off", json!({}))
+            .await
+    }
+
+    /// Have the provider create a Lightning invoice and add the charge to
+    /// the outbox.
+    async fn queue(
+        &mut self,
+        request_id: String,
+        provider: usize,
+        amount_sats: u64,
+        memo: &str,
+        metadata: Value,
+    ) -> Result<()> {
+        // Amountless, like an invoice fetched from a published endpoint
+        let invoice = self
+            .node
+            .create_invoice(&self.providers[provider].name, None, memo);
+        let payment_hash = self.node.decode_invoice(&invoice).await?.payment_hash;
+        self.outbox.push(Charge {
+            request_id,
+            provider,
+            amount_sats,
+            invoice,
+            payment_hash,
+            metadata,
+            reservation: None,
+            attempts: 0,
+            in_doubt: false,
+        });
+        Ok(())
+    }
+
+    /// Drop in-memory state and reopen storage, as after a process restart.
+    /// The outbox stands in for the payer's persisted queue.
+    fn restart(&mut self) -> Result<()> {
+        self.report.restarts += 1;
+        self.storage = FileSubscriptionStorage::new(self.dir.path().to_path_buf())?;
+        Ok(())
+    }
+
+    async fn work_outbox(&mut self) -> Result<()> {
+        let outbox = std::mem::take(&mut self.outbox);
+        for mut charge in outbox {
+            let pending = if charge.in_doubt {
+                self.reconcile(&mut charge).await?
+            } else {
+                self.attempt(&mut charge).await?
+            };
+            if pending {
+                self.outbox.push(charge);
+            }
+        }
+        Ok(())
+    }
+
+    /// Reserve and pay a charge. Returns whether it stays in the outbox.
+    async fn attempt(&mut self, charge: &mut Charge) -> Result<bool> {
+        if charge.reservation.is_none() {
+            let peer = &self.providers[charge.provider].key;
+            let amount = Amount::from_sats(charge.amount_sats as i64);
+            match self.storage.try_reserve_spending(peer, &amount).await {
+                Ok(token) => charge.reservation = Some(token),
+                Err(e) if is_limit_exceeded(&e) => {
+                    self.report.declined_by_limit += 1;
+                    return Ok(false);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        charge.attempts += 1;
+        if charge.attempts > 1 {
+            self.report.retries += 1;
+        }
+        let execution = self
+            .plugin
+            .execute_payment(
+                &EndpointData(charge.invoice.clone()),
+                &MethodAmount::sats(charge.amount_sats),
+                &json!({}),
+            )
+            .await?;
+        if execution.success {
+            self.settle(charge).await?;
+            return Ok(false);
+        }
+
+        // A failed or timed-out call does not mean no money moved
+        self.reconcile(charge).await
+    }
+
+    /// Ask the node what became of a charge's payment. Returns whether the
+    /// charge stays in the outbox.
+    async fn reconcile(&mut self, charge: &mut Charge) -> Result<bool> {
+        let status = self
+            .node
+            .get_payment(&charge.payment_hash)
+            .await?
+            .map(|payment| payment.status);
+        match status {
+            Some(LightningPaymentStatus::Succeeded) => {
+                self.report.settled_after_reconcile += 1;
+                self.settle(charge).await?;
+                Ok(false)
+            }
+            Some(LightningPaymentStatus::Pending) => {
+                // Keep the reservation: the money may still leave
+                if !charge.in_doubt {
+                    charge.in_doubt = true;
+                    self.report.in_doubt += 1;
+                }
+                Ok(true)
+            }
+            Some(LightningPaymentStatus::Failed) | None => {
+                charge.in_doubt = false;
+                if let Some(token) = charge.reservation.take() {
+                    self.storage.rollback_spending(token).await?;
+                }
+                if charge.attempts >= MAX_ATTEMPTS {
+                    self.report.abandoned += 1;
+                    return Ok(false);
+                }
+                Ok(true)
+            }
+        }
+    }
+
+    /// Commit a settled charge's reservation and issue its receipt.
+    async fn settle(&mut self, charge: &mut Charge) -> Result<()> {
+        let payment = self
+            .node
+            .get_payment(&charge.payment_hash)
+            .await?
+            .context("settled payment missing from the node")?;
+        let token = charge
+            .reservation
+            .take()
+            .context("charge settled without a reservation")?;
+        self.storage.commit_spending(token).await?;
+
+        let mut metadata = charge.metadata.clone();
+        metadata["payment_hash"] = json!(payment.payment_hash);
+        metadata["preimage"] = json!(payment.preimage);
+        let mut receipt = PaykitReceipt::new(
+            format!("rcpt_{}", charge.request_id),
+            self.payer.clone(),
+            self.providers[charge.provider].key.clone(),
+            lightning(),
+            Some(charge.amount_sats.to_string()),
+            Some("SAT".to_string()),
+            metadata,
+        );
+        receipt.created_at = self.clock.now();
+        self.scheduler.record_receipt(&receipt);
+        self.receipts.push(receipt);
+
+        self.report.settled += 1;
+        self.report.spent_sats += charge.amount_sats;
+        self.report.fees_msat += payment.fee_msat;
+        Ok(())
+    }
+
+    async fn check_invariants(&self) -> Result<()> {
+        ensure!(
+            self.outbox.iter().all(|charge| !charge.in_doubt),
+            "payments still in flight after the last tick"
+        );
+        let payments = self.node.payments();
+        ensure!(
+            payments
+                .iter()
+                .all(|p| p.status != LightningPaymentStatus::Pending),
+            "node still has pending payments"
+        );
+
+        // Receipts and settled payments correspond one to one
+        let payees: HashMap<&str, &PublicKey> = self
+            .providers
+            .iter()
+            .map(|p| (p.name.as_str(), &p.key))
+            .collect();
+        let mut receipt_ids = HashSet::new();
+        let mut by_hash = HashMap::new();
+        for receipt in &self.receipts {
+            ensure!(
+                receipt_ids.insert(&receipt.receipt_id),
+                "request paid twice: {}",
+                receipt.receipt_id
+            );
+            let hash = receipt.metadata["payment_hash"]
+                .as_str()
+                .context("receipt without payment hash")?;
+            ensure!(
+                by_hash.insert(hash, receipt).is_none(),
+                "payment {} has two receipts",
+                hash
+            );
+        }
+        let settled: Vec<_> = payments
+            .iter()
+            .filter(|p| p.status == LightningPaymentStatus::Succeeded)
+            .collect();
+        ensure!(
+            settled.len() == self.receipts.len(),
+            "{} settled payments but {} receipts",
+            settled.len(),
+            self.receipts.len()
+        );
+        for payment in &settled {
+            let receipt = by_hash
+                .get(payment.payment_hash.as_str())
+                .with_context(|| {
+                    format!("settled payment {} has no receipt", payment.payment_hash)
+                })?;
+            ensure!(
+                receipt.amount == Some((payment.amount_msat / 1000).to_string()),
+                "receipt {} does not match the amount paid",
+                receipt.receipt_id
+            );
+            ensure!(
+                payees.get(payment.payee.as_str()) == Some(&&receipt.payee),
+                "receipt {} names the wrong payee",
+                receipt.receipt_id
+            );
+            let preimage = receipt.metadata["preimage"].as_str().unwrap_or_default();
+            ensure!(
+                self.node.verify_preimage(preimage, &payment.payment_hash),
+                "receipt {} carries an invalid preimage",
+                receipt.receipt_id
+            );
+        }
+
+        // Spending limits account for exactly what was paid
+        for provider in &self.providers {
+            let limit = self
+                .storage
+                .get_peer_limit(&provider.key)
+                .await?
+                .with_context(|| format!("limit for {} lost", provider.name))?;
+            let paid: i64 = self
+                .receipts
+                .iter()
+                .filter(|r| r.payee == provider.key)
+                .filter_map(|r| r.amount.as_deref()?.parse::<i64>().ok())
+                .sum();
+            ensure!(
+                limit.current_spent.as_sats() == paid,
+                "limit for {} shows {} sats spent, receipts show {}",
+                provider.name,
+                limit.current_spent.as_sats(),
+                paid
+            );
+            ensure!(
+                limit.current_spent <= limit.total_amount_limit,
+                "limit for {} exceeded",
+                provider.name
+            );
+        }
+
+        // The invoice book agrees with the receipts
+        for invoice in self.scheduler.book().issued {
+            let receipt = self.receipts.iter().find(|r| {
+                r.metadata[INVOICE_METADATA_KEY]["invoice_number"].as_str()
+                    == Some(invoice.invoice_number.as_str())
+            });
+            match (&invoice.status, receipt) {
+                (InvoiceStatus::Paid { receipt_id, .. }, Some(receipt)) => ensure!(
+                    *receipt_id == receipt.receipt_id,
+                    "invoice {} marked paid by the wrong receipt",
+                    invoice.invoice_number
+                ),
+                (InvoiceStatus::Open, None) => {}
+                (status, receipt) => bail!(
+                    "invoice {} is {:?} but its receipt is {:?}",
+                    invoice.invoice_number,
+                    status,
+                    receipt.map(|r| &r.receipt_id)
+                ),
+            }
+        }
+
+        // No money appeared or vanished
+        let paid_out: u64 = settled.iter().map(|p| p.amount_msat + p.fee_msat).sum();
+        ensure!(
+            self.node.balance_msat() + paid_out == self.initial_balance_msat,
+            "balance {} msat plus {} msat paid out does not match the starting {} msat",
+            self.node.balance_msat(),
+            paid_out,
+            self.initial_balance_msat
+        );
+        Ok(())
+    }
+}
+
+fn is_limit_exceeded(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<SubscriptionError>(),
+        Some(SubscriptionError::LimitExceeded)
+    )
+}
@@ -0,0 +1,280 @@
+//! The regtest stack: bitcoind, electrs and two LND nodes.
+
+use crate::{wait_until, BitcoindRpc, LndNode};
+use anyhow::{Context, Result};
+use paykit_lib::executors::{BitcoinNetwork, EsploraConfig, EsploraExecutor};
+use std::time::Duration;
+use testcontainers::core::{ExecCommand, IntoContainerPort, WaitFor};
+use testcontainers::runners::AsyncRunner;
+use testcontainers::{ContainerAsync, GenericImage, ImageExt};
+
+const BITCOIND_IMAGE: (&str, &str) = ("polarlightning/bitcoind", "27.0");
+const ELECTRS_IMAGE: (&str, &str) = ("vulpemventures/electrs", "latest");
+const LND_IMAGE: (&str, &str) = ("polarlightning/lnd", "0.18.3-beta");
+
+const RPC_USER: &str = "paykit";
+const RPC_PASSWORD: &str = "paykit";
+const RPC_PORT: u16 = 18443;
+const ZMQ_BLOCK_PORT: u16 = 28334;
+const ZMQ_TX_PORT: u16 = 28335;
+const ESPLORA_PORT: u16 = 3002;
+const LND_REST_PORT: u16 = 8080;
+const LND_DIR: &str = "/tmp/lnd";
+
+/// Alice's on-chain funding, in satoshis.
+pub const ALICE_FUNDING_SATS: u64 = 10_000_000;
+/// Capacity of the alice → bob channel, in satoshis.
+pub const CHANNEL_CAPACITY_SATS: u64 = 5_000_000;
+/// Part of the channel pushed to bob so bob can pay back, in satoshis.
+pub const CHANNEL_PUSH_SATS: u64 = 2_000_000;
+
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(120);
+const SYNC_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A running regtest stack.
+///
+/// The containers stop when the stack is dropped.
+pub struct RegtestStack {
+    /// RPC client for the miner wallet, which holds the block rewards.
+    pub miner: BitcoindRpc,
+    /// The paying node, with most of the channel balance.
+    pub alice: LndNode,
+    /// The receiving node.
+    pub bob: LndNode,
+    esplora_url: String,
+    mining_address: String,
+    _containers: Vec<ContainerAsync<GenericImage>>,
+}
+
+impl RegtestStack {
+    /// Start the containers, fund alice and open a channel to bob.
+    pub async fn start() -> Result<Self> {
+        let network = format!("paykit-e2e-{:08x}", rand::random::<u32>());
+        let bitcoind_host = format!("{}-bitcoind", network);
+
+        let bitcoind = GenericImage::new(BITCOIND_IMAGE.0, BITCOIND_IMAGE.1)
+            .with_exposed_port(RPC_PORT.tcp())
+            .with_wait_for(WaitFor::message_on_stdout("init message: Done loading"))
+            .with_cmd(bitcoind_args())
+            .with_network(&network)
+            .with_container_name(&bitcoind_host)
+            .start()
+            .await
+            .context("failed to start bitcoind")?;
+        let rpc = BitcoindRpc::new(
+            format!(
+                "http://127.0.0.1:{}",
+                bitcoind.get_host_port_ipv4(RPC_PORT).await?
+            ),
+            RPC_USER,
+            RPC_PASSWORD,
+        );
+
+        let electrs = GenericImage::new(ELECTRS_IMAGE.0, ELECTRS_IMAGE.1)
+            .with_exposed_port(ESPLORA_PORT.tcp())
+            .with_cmd(electrs_args(&bitcoind_host))
+            .with_network(&network)
+            .with_container_name(format!("{}-electrs", network))
+            .start()
+            .await
+            .context("failed to start electrs")?;
+        let esplora_url = format!(
+            "http://127.0.0.1:{}",
+            electrs.get_host_port_ipv4(ESPLORA_PORT).await?
+        );
+
+        let (alice_container, alice) = start_lnd(&network, "alice", &bitcoind_host).await?;
+        let (bob_container, bob) = start_lnd(&network, "bob", &bitcoind_host).await?;
+
+        let miner = rpc.create_wallet("miner").await?;
+        let mining_address = miner.new_address().await?;
+        let stack = Self {
+            miner,
+            alice,
+            bob,
+            esplora_url,
+            mining_address,
+            _containers: vec![bitcoind, electrs, alice_container, bob_container],
+        };
+
+        // Coinbase outputs mature after 100 blocks
+        stack.mine(101).await?;
+        let alice_address = stack.alice.new_address().await?;
+        stack
+            .miner
+            .send_to_address(&alice_address, ALICE_FUNDING_SATS)
+            .await?;
+        stack.mine(1).await?;
+        let s = &stack;
+        wait_until("alice's funding", SYNC_TIMEOUT, || async move {
+            Ok(s.alice.wallet_balance().await? >= ALICE_FUNDING_SATS)
+        })
+        .await?;
+
+        stack.alice.connect(&stack.bob).await?;
+        stack
+            .alice
+            .open_channel(&stack.bob, CHANNEL_CAPACITY_SATS, CHANNEL_PUSH_SATS)
+            .await?;
+        wait_until("the funding transaction", SYNC_TIMEOUT, || async move {
+            let mempool = s.miner.call("getrawmempool", serde_json::json!([])).await?;
+            Ok(mempool.as_array().is_some_and(|txs| !txs.is_empty()))
+        })
+        .await?;
+        stack.mine(6).await?;
+        wait_until("the channel", SYNC_TIMEOUT, || async move {
+            Ok(s.alice.active_channels().await? == 1 && s.bob.active_channels().await? == 1)
+        })
+        .await?;
+
+        Ok(stack)
+    }
+
+    /// Esplora configuration for the electrs container.
+    pub fn esplora_config(&self) -> EsploraConfig {
+        EsploraConfig::new(&self.esplora_url).with_network(BitcoinNetwork::Regtest)
+    }
+
+    /// An Esplora executor for the electrs container.
+    pub fn esplora(&self) -> paykit_lib::Result<EsploraExecutor> {
+        EsploraExecutor::new(self.esplora_config())
+    }
+
+    /// Mine `blocks` blocks and wait until electrs and both nodes see them.
+    pub async fn mine(&self, blocks: u64) -> Result<()> {
+        self.miner.mine(blocks, &self.mining_address).await?;
+        let height = self.miner.block_count().await?;
+        let esplora = &self.esplora()?;
+        wait_until("electrs to index", SYNC_TIMEOUT, || async move {
+            Ok(esplora.get_block_height().await? >= height)
+        })
+        .await?;
+        wait_until("LND to sync", SYNC_TIMEOUT, || async move {
+            Ok(self.alice.is_synced().await? && self.bob.is_synced().await?)
+        })
+        .await
+    }
+
+    /// Create a bitcoind wallet holding one confirmed output of `sats`.
+    ///
+    /// Returns the wallet and the address holding the output, for use as
+    /// an `EsploraBitcoinExecutor` funding address.
+    pub async fn funded_wallet(&self, name: &str, sats: u64) -> Result<(BitcoindRpc, String)> {
+        let wallet = self.miner.create_wallet(name).await?;
+        let address = wallet.new_address().await?;
+        self.miner.send_to_address(&address, sats).await?;
+        self.mine(1).await?;
+        Ok((wallet, address))
+    }
+}
+
+fn bitcoind_args() -> Vec<String> {
+    vec![
+        "bitcoind".to_string(),
+        "-regtest=1".to_string(),
+        "-server=1".to_string(),
+        "-txindex=1".to_string(),
+        "-printtoconsole=1".to_string(),
+        "-fallbackfee=0.0002".to_string(),
+        format!("-rpcuser={}", RPC_USER),
+        format!("-rpcpassword={}", RPC_PASSWORD),
+        "-rpcbind=0.0.0.0".to_string(),
+        "-rpcallowip=0.0.0.0/0".to_string(),
+        format!("-rpcport={}", RPC_PORT),
+        format!("-zmqpubrawblock=tcp://0.0.0.0:{}", ZMQ_BLOCK_PORT),
+        format!("-zmqpubrawtx=tcp://0.0.0.0:{}", ZMQ_TX_PORT),
+    ]
+}
+
+fn electrs_args(bitcoind_host: &str) -> Vec<String> {
+    vec![
+        "electrs".to_string(),
+        "-vvv".to_string(),
+        "--network=regtest".to_string(),
+        "--jsonrpc-import".to_string(),
+        "--daemon-dir=/tmp".to_string(),
+        "--db-dir=/tmp/electrs".to_string(),
+        format!("--daemon-rpc-addr={}:{}", bitcoind_host, RPC_PORT),
+        format!("--cookie={}:{}", RPC_USER, RPC_PASSWORD),
+        format!("--http-addr=0.0.0.0:{}", ESPLORA_PORT),
+        "--electrum-rpc-addr=0.0.0.0:50001".to_string(),
+    ]
+}
+
+fn lnd_args(alias: &str, bitcoind_host: &str) -> Vec<String> {
+    vec![
+        "lnd".to_string(),
+        format!("--lnddir={}", LND_DIR),
+        format!("--alias={}", alias),
+        "--noseedbackup".to_string(),
+        "--no-macaroons".to_string(),
+        "--bitcoin.regtest".to_string(),
+        "--bitcoin.node=bitcoind".to_string(),
+        format!("--bitcoind.rpchost={}:{}", bitcoind_host, RPC_PORT),
+        format!("--bitcoind.rpcuser={}", RPC_USER),
+        format!("--bitcoind.rpcpass={}", RPC_PASSWORD),
+        format!(
+            "--bitcoind.zmqpubrawblock=tcp://{}:{}",
+            bitcoind_host, ZMQ_BLOCK_PORT
+        ),
+        format!(
+            "--bitcoind.zmqpubrawtx=tcp://{}:{}",
+            bitcoind_host, ZMQ_TX_PORT
+        ),
+        "--listen=0.0.0.0:9735".to_string(),
+        format!("--restlisten=0.0.0.0:{}", LND_REST_PORT),
+        "--tlsextradomain=localhost".to_string(),
+        "--tlsextraip=127.0.0.1".to_string(),
+    ]
+}
+
+/// Start an LND node named `alias` and wait for its REST API.
+///
+/// Peers reach the node at `{network}-{alias}:9735`.
+async fn start_lnd(
+    network: &str,
+    alias: &str,
+    bitcoind_host: &str,
+) -> Result<(ContainerAsync<GenericImage>, LndNode)> {
+    let container = GenericImage::new(LND_IMAGE.0, LND_IMAGE.1)
+        .with_exposed_port(LND_REST_PORT.tcp())
+        .with_cmd(lnd_args(alias, bitcoind_host))
+        .with_network(network)
+        .with_container_name(format!("{}-{}", network, alias))
+        .start()
+        .await
+        .with_context(|| format!("failed to start LND {}", alias))?;
+
+    let cert_path = format!("{}/tls.cert", LND_DIR);
+    let container_ref = &container;
+    let cert_path_ref = &cert_path;
+    wait_until("the LND TLS certificate", STARTUP_TIMEOUT, || async move {
+        Ok(read_file(container_ref, cert_path_ref)
+            .await?
+            .contains("BEGIN CERTIFICATE"))
+    })
+    .await?;
+    let tls_cert_pem = read_file(&container, &cert_path).await?;
+
+    let port = container.get_host_port_ipv4(LND_REST_PORT).await?;
+    let node = LndNode::new(
+        format!("{}-{}", network, alias),
+        format!("https://localhost:{}", port),
+        tls_cert_pem,
+    )?;
+    // bitcoind reports initial block download until the first block is
+    // mined, so the node is not synced yet; its REST API answering is enough
+    let node_ref = &node;
+    wait_until("the LND REST API", STARTUP_TIMEOUT, || async move {
+        Ok(!node_ref.identity_pubkey().await?.is_empty())
+    })
+    .await?;
+    Ok((container, node))
+}
+
+async fn read_file(container: &ContainerAsync<GenericImage>, path: &str) -> Result<String> {
+    let mut result = container
+        .exec(ExecCommand::new(["cat".to_string(), path.to_string()]))
+        .await?;
+    Ok(String::from_utf8(result.stdout_to_vec().await?)?)
+}
@@ -0,0 +1,373 @@
+//! End-to-end payment flows against dockerized regtest backends.
+//!
+//! Each test starts a [`RegtestStack`] (bitcoind, electrs and two LND
+//! nodes) plus an ephemeral Pubky testnet, then drives a full flow through
+//! the production executors, plugins and verifiers.
+//!
+//! # Running Tests
+//!
+//! The tests need Docker and are marked `#[ignore]`:
+//!
+//! ```bash
+//! cargo test -p paykit-integration-tests -- --ignored --test-threads=1
+//! ```
+
+use paykit_integration_tests::{BitcoindSigner, RegtestStack};
+use paykit_interactive::proof::verifiers::{RealBitcoinProofVerifier, RealLightningProofVerifier};
+use paykit_interactive::proof::{PaymentProof as InteractiveProof, ProofVerifier};
+use paykit_interactive::PaykitReceipt;
+use paykit_lib::executors::EsploraBitcoinExecutor;
+use paykit_lib::methods::{
+    Amount, BitcoinNetwork, LightningNetwork, LightningPlugin, OnchainPlugin, PaymentMethodPlugin,
+    PaymentMethodRegistry, PaymentProof,
+};
+use paykit_lib::{
+    get_payment_list, set_payment_endpoint, EndpointData, MethodId, PubkyAuthenticatedTransport,
+    PubkyUnauthenticatedTransport, PublicKey,
+};
+use paykit_subscriptions::invoice::{
+    InvoiceItem, InvoiceScheduler, InvoiceTemplate, RecurringInvoice,
+};
+use paykit_subscriptions::{AutoPayRule, PaymentFrequency};
+use pubky_testnet::{pubky::Keypair, EphemeralTestnet};
+use serde_json::json;
+use std::sync::Arc;
+
+fn lightning() -> MethodId {
+    MethodId("lightning".to_string())
+}
+
+fn onchain() -> MethodId {
+    MethodId("onchain".to_string())
+}
+
+/// Publish `endpoints` under a fresh identity and return its public key.
+async fn publish(testnet: &EphemeralTestnet, endpoints: &[(MethodId, EndpointData)]) -> PublicKey {
+    let sdk = testnet.sdk().expect("Failed to get SDK");
+    let keypair = Keypair::random();
+    let session = sdk
+        .signer(keypair.clone())
+        .signup(&testnet.homeserver_app().public_key(), None)
+        .await
+        .expect("Failed to signup");
+    let transport = PubkyAuthenticatedTransport::new(session);
+    for (method, data) in endpoints {
+        set_payment_endpoint(&transport, method.clone(), data.clone())
+            .await
+            .expect("Failed to publish endpoint");
+    }
+    keypair.public_key()
+}
+
+/// Look up `payee`'s endpoint for `method` the way a payer would.
+async fn discover(
+    testnet: &EphemeralTestnet,
+    payee: &PublicKey,
+    method: &MethodId,
+) -> EndpointData {
+    let reader = PubkyUnauthenticatedTransport::new(
+        testnet.sdk().expect("Failed to get SDK").public_storage(),
+    );
+    get_payment_list(&reader, payee)
+        .await
+        .expect("Failed to fetch payment list")
+        .entries
+        .get(method)
+        .cloned()
+        .expect("Endpoint not published")
+}
+
+/// Convert a plugin proof to the interactive proof format verifiers read.
+fn to_interactive(proof: PaymentProof) -> InteractiveProof {
+    match proof {
+        PaymentProof::BitcoinTxid { txid, .. } => InteractiveProof::bitcoin_txid(txid),
+        PaymentProof::LightningPreimage {
+            preimage,
+            payment_hash,
+        } => InteractiveProof::lightning_preimage(preimage, payment_hash),
+        PaymentProof::Custom { method, data } => InteractiveProof::custom(method, data),
+    }
+}
+
+/// A registry whose Lightning plugin pays from alice's node.
+fn alice_registry(stack: &RegtestStack) -> PaymentMethodRegistry {
+    let registry = PaymentMethodRegistry::new();
+    registry.register(Box::new(LightningPlugin::with_network_and_executor(
+        LightningNetwork::Regtest,
+        Arc::new(
+            stack
+                .alice
+                .executor()
+                .expect("Failed to create LND executor"),
+        ),
+    )));
+    registry
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_publish_discover_and_pay_lightning() {
+    let stack = RegtestStack::start()
+        .await
+        .expect("Failed to start regtest stack");
+    let testnet = EphemeralTestnet::start()
+        .await
+        .expect("Failed to start testnet");
+
+    // Bob publishes an amountless invoice; the payer picks the amount
+    let invoice = stack.bob.add_invoice(None, "paykit e2e").await.unwrap();
+    let bob = publish(
+        &testnet,
+        &[(lightning(), EndpointData(invoice.payment_request.clone()))],
+    )
+    .await;
+    let endpoint = discover(&testnet, &bob, &lightning()).await;
+    assert_eq!(endpoint.0, invoice.payment_request);
+
+    let plugin = alice_registry(&stack).get_required(&lightning()).unwrap();
+    let execution = plugin
+        .execute_payment(&endpoint, &Amount::sats(21_000), &json!({}))
+        .await
+        .unwrap();
+    assert!(execution.success, "payment failed: {:?}", execution.error);
+
+    let proof = to_interactive(plugin.generate_proof(&execution).unwrap());
+    let result = RealLightningProofVerifier::new().verify(&proof).await;
+    assert!(result.valid, "proof rejected: {:?}", result.errors);
+    match &proof.proof_type {
+        paykit_interactive::proof::ProofType::LightningPreimage { payment_hash, .. } => {
+            assert_eq!(*payment_hash, invoice.payment_hash)
+        }
+        other => panic!("unexpected proof {:?}", other),
+    }
+
+    assert_eq!(
+        stack
+            .bob
+            .settled_amount(&invoice.payment_hash)
+            .await
+            .unwrap(),
+        Some(21_000)
+    );
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_pay_onchain_with_esplora_proof() {
+    let stack = RegtestStack::start()
+        .await
+        .expect("Failed to start regtest stack");
+    let testnet = EphemeralTestnet::start()
+        .await
+        .expect("Failed to start testnet");
+
+    let payee_wallet = stack.miner.create_wallet("payee").await.unwrap();
+    let payee_address = payee_wallet.new_address().await.unwrap();
+    let payee = publish(
+        &testnet,
+        &[(onchain(), EndpointData(payee_address.clone()))],
+    )
+    .await;
+    let endpoint = discover(&testnet, &payee, &onchain()).await;
+
+    // The payer's keys live in a bitcoind wallet standing in for a
+    // hardware signer; the executor only sees addresses and PSBTs
+    let (payer_wallet, funding_address) = stack.funded_wallet("payer", 1_000_000).await.unwrap();
+    let change_address = payer_wallet.new_address().await.unwrap();
+    let executor = EsploraBitcoinExecutor::new(
+        stack.esplora().unwrap(),
+        &[funding_address],
+        &change_address,
+        Arc::new(BitcoindSigner::new(payer_wallet)),
+    )
+    .unwrap();
+    let plugin =
+        OnchainPlugin::with_network_and_executor(BitcoinNetwork::Regtest, Arc::new(executor));
+
+    let execution = plugin
+        .execute_payment(
+            &endpoint,
+            &Amount::sats(50_000),
+            &json!({ "fee_rate": 2.0 }),
+        )
+        .await
+        .unwrap();
+    assert!(execution.success, "payment failed: {:?}", execution.error);
+    stack.mine(1).await.unwrap();
+
+    let proof = to_interactive(plugin.generate_proof(&execution).unwrap());
+    let result = RealBitcoinProofVerifier::with_config(stack.esplora_config())
+        .verify(&proof)
+        .await;
+    assert!(result.valid, "proof rejected: {:?}", result.errors);
+
+    assert_eq!(
+        payee_wallet
+            .received_by_address(&payee_address, 1)
+            .await
+            .unwrap(),
+        50_000
+    );
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_refund_over_lightning() {
+    let stack = RegtestStack::start()
+        .await
+        .expect("Failed to start regtest stack");
+    let alice_key = Keypair::random().public_key();
+    let bob_key = Keypair::random().public_key();
+
+    let invoice = stack.bob.add_invoice(None, "order 42").await.unwrap();
+    let plugin = alice_registry(&stack).get_required(&lightning()).unwrap();
+    let payment = plugin
+        .execute_payment(
+            &EndpointData(invoice.payment_request),
+            &Amount::sats(30_000),
+            &json!({}),
+        )
+        .await
+        .unwrap();
+    assert!(payment.success, "payment failed: {:?}", payment.error);
+    let receipt = PaykitReceipt::new(
+        "rcpt_order_42".to_string(),
+        alice_key.clone(),
+        bob_key.clone(),
+        lightning(),
+        Some("30000".to_string()),
+        Some("SAT".to_string()),
+        json!({ "payment_hash": invoice.payment_hash }),
+    );
+    let alice_after_payment = stack.alice.channel_balance().await.unwrap();
+
+    // A refund is a payment back to the payer, linked to the original
+    // receipt through metadata
+    let refund_invoice = stack
+        .alice
+        .add_invoice(None, "refund order 42")
+        .await
+        .unwrap();
+    let bob_plugin = LightningPlugin::with_network_and_executor(
+        LightningNetwork::Regtest,
+        Arc::new(stack.bob.executor().unwrap()),
+    );
+    let refund = bob_plugin
+        .execute_payment(
+            &EndpointData(refund_invoice.payment_request),
+            &Amount::sats(30_000),
+            &json!({}),
+        )
+        .await
+        .unwrap();
+    assert!(refund.success, "refund failed: {:?}", refund.error);
+    let refund_receipt = PaykitReceipt::new(
+        "rcpt_refund_42".to_string(),
+        bob_key,
+        alice_key,
+        lightning(),
+        Some("30000".to_string()),
+        Some("SAT".to_string()),
+        json!({
+            "refund_of": receipt.receipt_id,
+            "payment_hash": refund_invoice.payment_hash,
+        }),
+    );
+
+    let proof = to_interactive(bob_plugin.generate_proof(&refund).unwrap());
+    assert!(RealLightningProofVerifier::new().verify(&proof).await.valid);
+    assert_eq!(
+        stack
+            .alice
+            .settled_amount(&refund_invoice.payment_hash)
+            .await
+            .unwrap(),
+        Some(30_000)
+    );
+    assert_eq!(
+        stack.alice.channel_balance().await.unwrap(),
+        alice_after_payment + 30_000
+    );
+    assert_eq!(refund_receipt.metadata["refund_of"], "rcpt_order_42");
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_subscription_invoice_charged_over_lightning() {
+    let stack = RegtestStack::start()
+        .await
+        .expect("Failed to start regtest stack");
+    let provider = Keypair::random().public_key();
+    let subscriber = Keypair::random().public_key();
+
+    // Provider side: a monthly invoice schedule for the subscriber
+    let template = InvoiceTemplate::new(
+        "pro-plan",
+        vec![InvoiceItem::new(
+            "Pro plan",
+            1,
+            paykit_subscriptions::Amount::from_sats(25_000),
+        )],
+    );
+    // 2024-01-01T00:00:00Z, the first billing day
+    let starts_at = 1_704_067_200;
+    let schedule = RecurringInvoice::new(
+        "pro-plan-monthly",
+        template,
+        provider.clone(),
+        subscriber.clone(),
+        "SAT",
+        lightning(),
+        PaymentFrequency::Monthly { day_of_month: 1 },
+        starts_at,
+    )
+    .unwrap();
+    let scheduler = InvoiceScheduler::new();
+    scheduler.add_schedule(schedule).unwrap();
+    let request = scheduler.generate_due(starts_at).unwrap().remove(0);
+    let amount_sats = request.amount.as_sats() as u64;
+
+    // Subscriber side: autopay approves the charge and pays it
+    let rule = AutoPayRule::new(
+        "pro-plan-monthly".to_string(),
+        provider.clone(),
+        lightning(),
+    )
+    .with_max_payment_amount(paykit_subscriptions::Amount::from_sats(50_000));
+    assert!(rule.is_amount_within_limit(&request.amount));
+
+    let invoice = stack.bob.add_invoice(None, "pro plan").await.unwrap();
+    let plugin = alice_registry(&stack).get_required(&lightning()).unwrap();
+    let execution = plugin
+        .execute_payment(
+            &EndpointData(invoice.payment_request),
+            &Amount::sats(amount_sats),
+            &request.metadata,
+        )
+        .await
+        .unwrap();
+    assert!(execution.success, "charge failed: {:?}", execution.error);
+
+    let receipt = PaykitReceipt::new(
+        format!("rcpt_{}", request.request_id),
+        subscriber,
+        provider,
+        lightning(),
+        Some(amount_sats.to_string()),
+        Some("SAT".to_string()),
+        request.metadata.clone(),
+    );
+    let paid = scheduler
+        .record_receipt(&receipt)
+        .expect("receipt did not settle the invoice");
+    assert_eq!(paid.invoice_number, request.invoice_number.clone().unwrap());
+    assert!(scheduler.outstanding().is_empty());
+    assert_eq!(
+        stack
+            .bob
+            .settled_amount(&invoice.payment_hash)
+            .await
+            .unwrap(),
+        Some(amount_sats)
+    );
+}
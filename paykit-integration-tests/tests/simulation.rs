@@ -0,0 +1,78 @@
+//! Randomized payment scenarios under deterministic simulation.
+//!
+//! Each seed generates a payer, one to three subscribed providers and four
+//! weeks of charges over a Lightning node that refuses, loses and delays
+//! payments. See [`paykit_integration_tests::sim`] for the invariants.
+//!
+//! # Running Tests
+//!
+//! ```bash
+//! cargo test -p paykit-integration-tests --test simulation
+//! PAYKIT_SIM_SCENARIOS=5000 cargo test -p paykit-integration-tests --test simulation
+//! PAYKIT_SIM_SEED=1234 cargo test -p paykit-integration-tests --test simulation -- --nocapture
+//! ```
+
+use paykit_integration_tests::{run_scenario, SimConfig, SimReport};
+
+const DEFAULT_SCENARIOS: u64 = 500;
+
+fn env_u64(name: &str) -> Option<u64> {
+    std::env::var(name).ok().map(|value| {
+        value
+            .parse()
+            .unwrap_or_else(|_| panic!("{} must be a number", name))
+    })
+}
+
+async fn run_seeds(seeds: impl Iterator<Item = u64>, config: &SimConfig) -> SimReport {
+    let mut totals = SimReport::default();
+    for seed in seeds {
+        let report = run_scenario(seed, config)
+            .await
+            .unwrap_or_else(|e| panic!("seed {}: {:#}", seed, e));
+        totals.add(&report);
+    }
+    totals
+}
+
+#[tokio::test]
+async fn test_randomized_scenarios_hold_invariants() {
+    let config = SimConfig::default();
+    if let Some(seed) = env_u64("PAYKIT_SIM_SEED") {
+        let report = run_seeds(std::iter::once(seed), &config).await;
+        println!("seed {}: {:#?}", seed, report);
+        return;
+    }
+
+    let scenarios = env_u64("PAYKIT_SIM_SCENARIOS").unwrap_or(DEFAULT_SCENARIOS);
+    let totals = run_seeds(0..scenarios, &config).await;
+
+    // Every recovery path must have been exercised
+    assert!(totals.settled > 0, "{:#?}", totals);
+    assert!(totals.retries > 0, "{:#?}", totals);
+    assert!(totals.in_doubt > 0, "{:#?}", totals);
+    assert!(totals.settled_after_reconcile > 0, "{:#?}", totals);
+    assert!(totals.declined_by_rule > 0, "{:#?}", totals);
+    assert!(totals.declined_by_limit > 0, "{:#?}", totals);
+    assert!(totals.restarts > 0, "{:#?}", totals);
+}
+
+#[tokio::test]
+async fn test_fault_free_scenarios_are_never_in_doubt() {
+    let totals = run_seeds(0..50, &SimConfig::fault_free()).await;
+
+    // Only running out of funds makes a payment fail here
+    assert!(totals.settled > 0);
+    assert_eq!(totals.in_doubt, 0);
+    assert_eq!(totals.settled_after_reconcile, 0);
+}
+
+#[tokio::test]
+async fn test_same_seed_replays_identically() {
+    let config = SimConfig::default();
+    for seed in [7, 42, 1234] {
+        let first = run_scenario(seed, &config).await.unwrap();
+        let second = run_scenario(seed, &config).await.unwrap();
+        assert_eq!(first, second, "seed {} diverged", seed);
+    }
+}
@@ -0,0 +1,116 @@
+//! Receipt and message serialization benchmarks
+//!
+//! Every message on a Noise channel, every stored receipt and every inbox
+//! envelope is JSON. These benchmarks measure encoding and decoding of the
+//! messages exchanged during checkout, with receipts carrying typical
+//! invoice metadata.
+//!
+//! Run with: `cargo bench --bench message_benchmarks`
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use paykit_interactive::{PaykitNoiseMessage, PaykitReceipt, PrivateEndpointOffer};
+use paykit_lib::MethodId;
+use serde_json::json;
+
+fn receipt() -> PaykitReceipt {
+    let payer = pubky::Keypair::from_secret_key(&[1u8; 32]).public_key();
+    let payee = pubky::Keypair::from_secret_key(&[2u8; 32]).public_key();
+    PaykitReceipt::new(
+        "rcpt_1704067200_8f3a2c".to_string(),
+        payer,
+        payee,
+        MethodId("lightning".to_string()),
+        Some("25000".to_string()),
+        Some("SAT".to_string()),
+        json!({
+            "invoice": {
+                "invoice_number": "pro-plan-0001",
+                "schedule_id": "pro-plan-monthly",
+                "cycle": 1,
+            },
+            "order_id": "order-42",
+            "memo": "Pro plan, January",
+        }),
+    )
+}
+
+/// One of each message sent during a typical checkout.
+fn checkout_messages() -> Vec<(&'static str, PaykitNoiseMessage)> {
+    vec![
+        (
+            "offer_private_endpoints",
+            PaykitNoiseMessage::OfferPrivateEndpoints {
+                methods: vec![
+                    PrivateEndpointOffer {
+                        method_id: MethodId("lightning".to_string()),
+                        endpoint:
+                            "lnbc10u1pj9x3qapp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypq"
+                                .to_string(),
+                        expires_at: Some(1_704_070_800),
+                    },
+                    PrivateEndpointOffer {
+                        method_id: MethodId("onchain".to_string()),
+                        endpoint: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+                        expires_at: None,
+                    },
+                ],
+            },
+        ),
+        (
+            "request_receipt",
+            PaykitNoiseMessage::RequestReceipt {
+                provisional_receipt: receipt(),
+            },
+        ),
+        (
+            "confirm_receipt",
+            PaykitNoiseMessage::ConfirmReceipt { receipt: receipt() },
+        ),
+        ("ack", PaykitNoiseMessage::Ack),
+    ]
+}
+
+/// Benchmark encoding and decoding each checkout message
+fn bench_messages(c: &mut Criterion) {
+    let mut encode = c.benchmark_group("message_encode");
+    for (kind, message) in checkout_messages() {
+        let size = serde_json::to_vec(&message).unwrap().len();
+        encode.throughput(Throughput::Bytes(size as u64));
+        encode.bench_with_input(BenchmarkId::from_parameter(kind), &message, |b, message| {
+            b.iter(|| serde_json::to_vec(black_box(message)).unwrap())
+        });
+    }
+    encode.finish();
+
+    let mut decode = c.benchmark_group("message_decode");
+    for (kind, message) in checkout_messages() {
+        let bytes = serde_json::to_vec(&message).unwrap();
+        decode.throughput(Throughput::Bytes(bytes.len() as u64));
+        decode.bench_with_input(BenchmarkId::from_parameter(kind), &bytes, |b, bytes| {
+            b.iter(|| serde_json::from_slice::<PaykitNoiseMessage>(black_box(bytes)).unwrap())
+        });
+    }
+    decode.finish();
+}
+
+/// Benchmark receipt round trips, as done when storing and loading receipts
+fn bench_receipts(c: &mut Criterion) {
+    let receipt = receipt();
+    let json = serde_json::to_string(&receipt).unwrap();
+
+    let mut group = c.benchmark_group("receipt");
+    group.bench_function("serialize", |b| {
+        b.iter(|| serde_json::to_string(black_box(&receipt)).unwrap())
+    });
+    group.bench_function("deserialize", |b| {
+        b.iter(|| serde_json::from_str::<PaykitReceipt>(black_box(&json)).unwrap())
+    });
+    group.bench_function("serialize_batch_100", |b| {
+        let batch = vec![receipt.clone(); 100];
+        b.iter(|| serde_json::to_vec(black_box(&batch)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_messages, bench_receipts);
+criterion_main!(benches);
@@ -0,0 +1,245 @@
+//! Identity attestations and trust-on-first-use pinning.
+//!
+//! An NN handshake authenticates neither side, so after it completes each
+//! party sends a [`PaykitNoiseMessage::Attestation`](crate::PaykitNoiseMessage::Attestation):
+//! its Ed25519 identity key and a signature over the handshake transcript
+//! (the Noise handshake hash). Because the transcript is unique to the
+//! session, the signature cannot be replayed into another channel.
+//!
+//! [`verify_attestation`] checks the signature. [`PinStore`] then remembers
+//! the key the first time a peer is seen and flags any later change, the same
+//! model SSH uses for host keys. A changed key either means the peer rotated
+//! their identity or someone is in the middle; callers decide whether to
+//! prompt the user ([`PinStore::check`]) or refuse outright
+//! ([`PinStore::enforce`]).
+//!
+//! # Example
+//!
+//! ```ignore
+//! use paykit_interactive::attestation::{verify_attestation, PinCheck, PinStore};
+//!
+//! // After the NN handshake, with `transcript` = the Noise handshake hash
+//! verify_attestation(&peer_pk, &signature, &transcript)?;
+//!
+//! match pins.check("bob@example", &peer_pk, now) {
+//!     PinCheck::FirstUse | PinCheck::Match => proceed(),
+//!     PinCheck::Changed { pinned } => ask_user(pinned),
+//! }
+//! ```
+
+use crate::{InteractiveError, Result};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// Domain separator mixed into every attestation signature.
+pub const ATTESTATION_CONTEXT: &[u8] = b"paykit-attestation-v1";
+
+/// Bytes actually signed: the context followed by SHA-256 of the transcript.
+pub fn attestation_message(handshake_transcript: &[u8]) -> Vec<u8> {
+    let mut message = ATTESTATION_CONTEXT.to_vec();
+    message.extend_from_slice(&Sha256::digest(handshake_transcript));
+    message
+}
+
+/// Sign a handshake transcript with an Ed25519 identity secret key.
+pub fn sign_attestation(secret_key: &[u8; 32], handshake_transcript: &[u8]) -> [u8; 64] {
+    SigningKey::from_bytes(secret_key)
+        .sign(&attestation_message(handshake_transcript))
+        .to_bytes()
+}
+
+/// Verify that `ed25519_pk` signed this session's handshake transcript.
+pub fn verify_attestation(
+    ed25519_pk: &[u8; 32],
+    signature: &[u8; 64],
+    handshake_transcript: &[u8],
+) -> Result<()> {
+    let key = VerifyingKey::from_bytes(ed25519_pk)
+        .map_err(|_| InteractiveError::Protocol("Invalid attestation public key".into()))?;
+    key.verify(
+        &attestation_message(handshake_transcript),
+        &Signature::from_bytes(signature),
+    )
+    .map_err(|_| InteractiveError::Protocol("Attestation signature is invalid".into()))
+}
+
+/// Verify an attestation given as hex strings, as carried in the message.
+pub fn verify_attestation_hex(
+    ed25519_pk_hex: &str,
+    signature_hex: &str,
+    handshake_transcript: &[u8],
+) -> Result<[u8; 32]> {
+    let ed25519_pk: [u8; 32] = decode_hex(ed25519_pk_hex, "attestation public key")?;
+    let signature: [u8; 64] = decode_hex(signature_hex, "attestation signature")?;
+    verify_attestation(&ed25519_pk, &signature, handshake_transcript)?;
+    Ok(ed25519_pk)
+}
+
+fn decode_hex<const N: usize>(value: &str, what: &str) -> Result<[u8; N]> {
+    hex::decode(value)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| InteractiveError::Protocol(format!("Invalid {}", what)))
+}
+
+/// An identity key pinned for a peer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinnedKey {
+    /// Application-chosen peer identifier (contact ID, address, ...).
+    pub peer: String,
+    /// Pinned Ed25519 key, hex encoded.
+    pub ed25519_pk_hex: String,
+    /// When the key was first pinned (unix seconds).
+    pub first_seen: i64,
+    /// When the key was last presented (unix seconds).
+    pub last_seen: i64,
+}
+
+/// Outcome of checking a presented key against the pin store.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PinCheck {
+    /// Peer had no pin; the key is now pinned.
+    FirstUse,
+    /// Key matches the pin.
+    Match,
+    /// Key differs from the pin, which is left unchanged.
+    Changed {
+        /// The previously pinned key.
+        pinned: PinnedKey,
+    },
+}
+
+/// Trust-on-first-use pins, keyed by peer.
+///
+/// Serializable so apps can persist it between launches.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PinStore {
+    pins: HashMap<String, PinnedKey>,
+}
+
+impl PinStore {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check a presented key, pinning it if the peer is new.
+    pub fn check(&mut self, peer: &str, ed25519_pk: &[u8; 32], now: i64) -> PinCheck {
+        let presented = hex::encode(ed25519_pk);
+        match self.pins.get_mut(peer) {
+            Some(pin) if pin.ed25519_pk_hex == presented => {
+                pin.last_seen = now;
+                PinCheck::Match
+            }
+            Some(pin) => PinCheck::Changed {
+                pinned: pin.clone(),
+            },
+            None => {
+                self.pin(peer, ed25519_pk, now);
+                PinCheck::FirstUse
+            }
+        }
+    }
+
+    /// Like [`PinStore::check`], but a changed key is an error.
+    pub fn enforce(&mut self, peer: &str, ed25519_pk: &[u8; 32], now: i64) -> Result<()> {
+        match self.check(peer, ed25519_pk, now) {
+            PinCheck::FirstUse | PinCheck::Match => Ok(()),
+            PinCheck::Changed { pinned } => Err(InteractiveError::Protocol(format!(
+                "Identity key for {} changed since {} (pinned {}, presented {})",
+                peer,
+                pinned.first_seen,
+                pinned.ed25519_pk_hex,
+                hex::encode(ed25519_pk)
+            ))),
+        }
+    }
+
+    /// Pin a key, replacing any existing pin (e.g. after the user accepts a change).
+    pub fn pin(&mut self, peer: &str, ed25519_pk: &[u8; 32], now: i64) -> &PinnedKey {
+        self.pins.insert(
+            peer.to_string(),
+            PinnedKey {
+                peer: peer.to_string(),
+                ed25519_pk_hex: hex::encode(ed25519_pk),
+                first_seen: now,
+                last_seen: now,
+            },
+        );
+        &self.pins[peer]
+    }
+
+    /// Forget a peer's pin. Returns the removed pin, if any.
+    pub fn unpin(&mut self, peer: &str) -> Option<PinnedKey> {
+        self.pins.remove(peer)
+    }
+
+    /// The pin for a peer.
+    pub fn get(&self, peer: &str) -> Option<&PinnedKey> {
+        self.pins.get(peer)
+    }
+
+    /// All pins, sorted by peer.
+    pub fn pins(&self) -> Vec<&PinnedKey> {
+        let mut pins: Vec<&PinnedKey> = self.pins.values().collect();
+        pins.sort_by(|a, b| a.peer.cmp(&b.peer));
+        pins
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attestation_binds_transcript() {
+        let secret = [3u8; 32];
+        let public = SigningKey::from_bytes(&secret).verifying_key().to_bytes();
+        let signature = sign_attestation(&secret, b"handshake hash A");
+
+        assert!(verify_attestation(&public, &signature, b"handshake hash A").is_ok());
+        assert!(verify_attestation(&public, &signature, b"handshake hash B").is_err());
+
+        let other = SigningKey::from_bytes(&[4u8; 32])
+            .verifying_key()
+            .to_bytes();
+        assert!(verify_attestation(&other, &signature, b"handshake hash A").is_err());
+
+        let verified = verify_attestation_hex(
+            &hex::encode(public),
+            &hex::encode(signature),
+            b"handshake hash A",
+        )
+        .unwrap();
+        assert_eq!(verified, public);
+        assert!(verify_attestation_hex("zz", &hex::encode(signature), b"").is_err());
+    }
+
+    #[test]
+    fn test_tofu_pinning() {
+        let mut pins = PinStore::new();
+        let original = [1u8; 32];
+        let rotated = [2u8; 32];
+
+        assert_eq!(pins.check("bob", &original, 100), PinCheck::FirstUse);
+        assert_eq!(pins.check("bob", &original, 200), PinCheck::Match);
+        assert_eq!(pins.get("bob").unwrap().last_seen, 200);
+
+        match pins.check("bob", &rotated, 300) {
+            PinCheck::Changed { pinned } => assert_eq!(pinned.first_seen, 100),
+            other => panic!("expected a key change, got {:?}", other),
+        }
+        assert!(pins.enforce("bob", &rotated, 300).is_err());
+        assert_eq!(
+            pins.get("bob").unwrap().ed25519_pk_hex,
+            hex::encode(original)
+        );
+
+        pins.pin("bob", &rotated, 400);
+        assert!(pins.enforce("bob", &rotated, 500).is_ok());
+        assert!(pins.unpin("bob").is_some());
+        assert!(pins.pins().is_empty());
+    }
+}
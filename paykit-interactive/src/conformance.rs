@@ -0,0 +1,671 @@
+//! Wire protocol test vectors and conformance checks.
+//!
+//! Other Paykit implementations (Kotlin, Swift, the JavaScript client) must
+//! produce byte-identical scopes, storage paths, signed payloads, receipt
+//! digests and Noise messages. [`vectors`] returns the canonical vectors for
+//! each of those; [`vectors_json`] renders them as JSON for implementations
+//! that cannot link this crate. The same file is published as
+//! `docs/conformance-vectors.json`.
+//!
+//! [`run`] checks this crate against a set of vectors, so a vector file
+//! produced by another implementation can be verified here as well.
+//!
+//! | Category         | Input                                  | Expected output                          |
+//! |------------------|----------------------------------------|------------------------------------------|
+//! | `scopes`         | pubkey (any case, `pk:` prefix)        | scope hex, or rejected                   |
+//! | `paths`          | builder name and arguments             | storage path, or rejected                |
+//! | `uris`           | URI string                             | parsed kind and fields, or rejected      |
+//! | `signatures`     | scheme, test secret key, input         | signed message and Ed25519 signature     |
+//! | `receipts`       | receipt fields                         | canonical JSON and SHA-256 digest        |
+//! | `noise_messages` | message JSON                           | parses and re-encodes to the same value  |
+//!
+//! The secret keys are test keys (32 bytes of `0x07` and `0x09`); never use
+//! them for anything else.
+//!
+//! # Example
+//!
+//! ```
+//! use paykit_interactive::conformance;
+//!
+//! let report = conformance::run(&conformance::vectors());
+//! assert!(report.is_success(), "{:?}", report.failures);
+//! ```
+
+use crate::attestation::{attestation_message, sign_attestation};
+use crate::{PaykitNoiseMessage, PaykitReceipt, Result};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier};
+use paykit_lib::protocol::{self, PROTOCOL_VERSION};
+use paykit_lib::uri::{parse_uri, PaykitUri, SIGNED_REQUEST_DOMAIN};
+use paykit_lib::{MethodId, PublicKey};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::str::FromStr;
+
+/// Payer identity of the vectors (secret key: 32 bytes of `0x07`).
+pub const PAYER: &str = "7jfgaa9nutjyixzikb7tgmsf9gkwq7iqz498zr1nd5ig1fng4esy";
+
+/// Payee identity of the vectors (secret key: 32 bytes of `0x09`).
+pub const PAYEE: &str = "9wm1eqn4wddis385xdgsym7b5ge9549zpcjhmdszymicopxj6acy";
+
+const INVOICE: &str = "lnbc10u1pj9x3qapp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdq5xysxxatsyp3k7enxv4jsxqzpusp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygs9qyyssq";
+
+const SIGNED_LINK_PAYLOAD: &str = "eyJ2IjoxLCJpIjoicmVxLWxpbmstMSIsImYiOiI3amZnYWE5bnV0anlpeHppa2I3dGdtc2Y5Z2t3cTdpcXo0OTh6cjFuZDVpZzFmbmc0ZXN5IiwiYSI6NTAwMCwibSI6WyJsaWdodG5pbmciLCJvbmNoYWluIl0sImQiOiJDb2ZmZWUiLCJjIjoxNzAwMDAwMDAwLCJlIjoxNzAwMDAzNjAwfQ";
+
+/// The full set of vectors.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TestVectors {
+    /// Protocol version the vectors describe.
+    pub protocol_version: String,
+    pub scopes: Vec<ScopeVector>,
+    pub paths: Vec<PathVector>,
+    pub uris: Vec<UriVector>,
+    pub signatures: Vec<SignatureVector>,
+    pub receipts: Vec<ReceiptVector>,
+    pub noise_messages: Vec<NoiseMessageVector>,
+}
+
+/// Scope derivation: `hex(sha256(utf8(normalize(pubkey))))`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScopeVector {
+    pub pubkey: String,
+    /// Expected scope; `None` if the pubkey must be rejected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+/// A storage path builder applied to `args`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PathVector {
+    /// Builder name, e.g. `payment_request` for `payment_request_path`.
+    pub builder: String,
+    pub args: Vec<String>,
+    /// Expected path; `None` if the arguments must be rejected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+/// A URI and what parsing it must yield.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UriVector {
+    pub uri: String,
+    /// `pubky`, `invoice`, `payment_request`, `signed_request`, `handle` or
+    /// `invalid`.
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+    /// z-base-32 public key carried by the URI.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+/// The exact bytes signed for a scheme, and the resulting signature.
+///
+/// Ed25519 signatures are deterministic, so implementations can check
+/// signing as well as verification. All byte strings are hex.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SignatureVector {
+    /// `payment_request_link` (input: the base64url payload) or
+    /// `attestation` (input: the handshake transcript, hex).
+    pub scheme: String,
+    pub secret_key: String,
+    pub public_key: String,
+    pub input: String,
+    pub message: String,
+    pub signature: String,
+}
+
+/// Receipt fields and their canonical encoding.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReceiptVector {
+    pub name: String,
+    pub receipt_id: String,
+    /// z-base-32 payer key.
+    pub payer: String,
+    /// z-base-32 payee key.
+    pub payee: String,
+    pub method_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    pub created_at: i64,
+    pub metadata: serde_json::Value,
+    pub canonical_json: String,
+    /// Hex SHA-256 of `canonical_json`.
+    pub digest: String,
+}
+
+/// A Noise channel message as sent on the wire.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NoiseMessageVector {
+    pub message_type: String,
+    pub json: String,
+}
+
+/// A vector this implementation does not reproduce.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConformanceFailure {
+    pub category: &'static str,
+    /// Identifies the vector within its category.
+    pub case: String,
+    pub reason: String,
+}
+
+/// Outcome of [`run`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConformanceReport {
+    pub passed: usize,
+    pub failures: Vec<ConformanceFailure>,
+}
+
+impl ConformanceReport {
+    /// Whether every vector passed.
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    fn record(
+        &mut self,
+        category: &'static str,
+        case: impl Into<String>,
+        outcome: std::result::Result<(), String>,
+    ) {
+        match outcome {
+            Ok(()) => self.passed += 1,
+            Err(reason) => self.failures.push(ConformanceFailure {
+                category,
+                case: case.into(),
+                reason,
+            }),
+        }
+    }
+}
+
+/// The canonical vectors for this protocol version.
+pub fn vectors() -> TestVectors {
+    TestVectors {
+        protocol_version: PROTOCOL_VERSION.to_string(),
+        scopes: vec![
+            scope(
+                "ybndrfg8ejkmcpqxot1uwisza345h769ybndrfg8ejkmcpqxot1u",
+                Some("55340b54f918470e1f025a80bb3347934fad3f57189eef303d620e65468cde80"),
+            ),
+            scope(
+                "8pinxxgqs41n4aididenw5apqp1urfmzdztr8jt4abrkdn435ewo",
+                Some("04dc3323da61313c6f5404cf7921af2432ef867afe6cc4c32553858b8ac07f12"),
+            ),
+            scope(
+                "pk:8pinxxgqs41n4aididenw5apqp1urfmzdztr8jt4abrkdn435ewo",
+                Some("04dc3323da61313c6f5404cf7921af2432ef867afe6cc4c32553858b8ac07f12"),
+            ),
+            scope(
+                "YBNDRFG8EJKMCPQXOT1UWISZA345H769YBNDRFG8EJKMCPQXOT1U",
+                Some("55340b54f918470e1f025a80bb3347934fad3f57189eef303d620e65468cde80"),
+            ),
+            scope(
+                "  7jfgaa9nutjyixzikb7tgmsf9gkwq7iqz498zr1nd5ig1fng4esy\n",
+                Some("07ae14c59fe584e315b459c15779469bf69b9bd2ba52271ef55a083b9d64455b"),
+            ),
+            scope("tooshort", None),
+            scope("lbndrfg8ejkmcpqxot1uwisza345h769ybndrfg8ejkmcpqxot1u", None),
+            scope("vbndrfg8ejkmcpqxot1uwisza345h769ybndrfg8ejkmcpqxot1u", None),
+        ],
+        paths: vec![
+            path(
+                "payment_request",
+                &[PAYER, "req-12345"],
+                Some("/pub/paykit.app/v0/requests/07ae14c59fe584e315b459c15779469bf69b9bd2ba52271ef55a083b9d64455b/req-12345"),
+            ),
+            path(
+                "payment_requests_dir",
+                &[PAYER],
+                Some("/pub/paykit.app/v0/requests/07ae14c59fe584e315b459c15779469bf69b9bd2ba52271ef55a083b9d64455b/"),
+            ),
+            path(
+                "subscription_proposal",
+                &[PAYEE, "prop-67890"],
+                Some("/pub/paykit.app/v0/subscriptions/proposals/db4376f99a9b5ce8956dcb88b34f96393d3688fe74d519bbed4bac44757c0c47/prop-67890"),
+            ),
+            path(
+                "subscription_status",
+                &[PAYEE, "sub-1"],
+                Some("/pub/paykit.app/v0/subscriptions/status/db4376f99a9b5ce8956dcb88b34f96393d3688fe74d519bbed4bac44757c0c47/sub-1"),
+            ),
+            path("noise_endpoint", &[], Some("/pub/paykit.app/v0/noise")),
+            path("payee_status", &[], Some("/pub/paykit.app/v0/status")),
+            path("routing_hints", &[], Some("/pub/paykit.app/v0/routing")),
+            path("push_hint", &[], Some("/pub/paykit.app/v0/push")),
+            path(
+                "secure_handoff",
+                &["handoff-abc123"],
+                Some("/pub/paykit.app/v0/handoff/handoff-abc123"),
+            ),
+            path(
+                "attachment",
+                &["86d12f9a45fdf1acb251f42af06eb1e754c6b968b0834242bbb8fffdf0baba08"],
+                Some("/pub/paykit.app/v0/attachments/86d12f9a45fdf1acb251f42af06eb1e754c6b968b0834242bbb8fffdf0baba08"),
+            ),
+            path(
+                "attachment",
+                &["86D12F9A45FDF1ACB251F42AF06EB1E754C6B968B0834242BBB8FFFDF0BABA08"],
+                None,
+            ),
+            path("device_sync", &["phone_01"], Some("/pub/paykit.app/v0/sync/phone_01")),
+            path("device_sync", &["../escape"], None),
+            path(
+                "inbox_message",
+                &[PAYEE, "msg-1"],
+                Some("/pub/paykit.app/v0/inbox/db4376f99a9b5ce8956dcb88b34f96393d3688fe74d519bbed4bac44757c0c47/msg-1"),
+            ),
+            path("inbox_message", &[PAYEE, "msg/1"], None),
+            path(
+                "inbox_receipt",
+                &[PAYER, "msg-1"],
+                Some("/pub/paykit.app/v0/inbox-receipts/07ae14c59fe584e315b459c15779469bf69b9bd2ba52271ef55a083b9d64455b/msg-1"),
+            ),
+            path("payment_link", &["coffee-2024"], Some("/pub/paykit.app/v0/links/coffee-2024")),
+            path("payment_request", &["tooshort", "req-1"], None),
+        ],
+        uris: vec![
+            UriVector {
+                public_key: Some(PAYER.into()),
+                ..uri(&format!("pubky://{}", PAYER), "pubky")
+            },
+            UriVector {
+                public_key: Some(PAYER.into()),
+                ..uri(&format!("pubky://{}/", PAYER), "pubky")
+            },
+            invoice_uri(&format!("lightning:{}", INVOICE), "lightning", INVOICE),
+            invoice_uri(INVOICE, "lightning", INVOICE),
+            invoice_uri(
+                "bitcoin:bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq",
+                "onchain",
+                "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq",
+            ),
+            invoice_uri(
+                "paykit:invoice?method=ecash&data=cashuA%2Bsecret%20token",
+                "ecash",
+                "cashuA+secret token",
+            ),
+            invoice_uri("paykit:invoice?method=onchain&data=bc1q+ar0s", "onchain", "bc1q ar0s"),
+            UriVector {
+                public_key: Some(PAYEE.into()),
+                request_id: Some("req-7".into()),
+                ..uri(
+                    &format!("paykit:request?request_id=req%2D7&from=pubky%3A%2F%2F{}", PAYEE),
+                    "payment_request",
+                )
+            },
+            UriVector {
+                data: Some("alice@example.com".into()),
+                ..uri("Alice@Example.com", "handle")
+            },
+            uri("alice@localhost", "invalid"),
+            UriVector {
+                public_key: Some(PAYER.into()),
+                request_id: Some("req-link-1".into()),
+                ..uri(
+                    &format!(
+                        "paykit://request/{}.LY7hNxjhw3DM8EvCwIRCYaV3lECV41fWFqb9TszSUXm00e03d9a7JucJJFY5QwLlqTEeei6w1ArbLOAXr8S5Aw",
+                        SIGNED_LINK_PAYLOAD
+                    ),
+                    "signed_request",
+                )
+            },
+            // Same link with a corrupted signature
+            uri(
+                &format!(
+                    "paykit://request/{}.LY7hNxjhw3DM8EvCwIRCYaV3lECV41fWFqb9TszSUXm00e03d9a7JucJJFY5QwLlqTEeei6w1ArbLOAXr8AAAA",
+                    SIGNED_LINK_PAYLOAD
+                ),
+                "invalid",
+            ),
+            uri("paykit:unknown?x=1", "invalid"),
+            uri("paykit:invoice?method=lightning", "invalid"),
+        ],
+        signatures: vec![
+            SignatureVector {
+                scheme: "payment_request_link".into(),
+                secret_key: "0707070707070707070707070707070707070707070707070707070707070707".into(),
+                public_key: "ea4a6c63e29c520abef5507b132ec5f9954776aebebe7b92421eea691446d22c".into(),
+                input: SIGNED_LINK_PAYLOAD.into(),
+                message: "5041594b49545f524551554553545f4c494e4b5f56313a65794a32496a6f784c434a70496a6f69636d56784c577870626d73744d534973496d59694f694933616d5a6e59574535626e5630616e6c70654870706132493364476474633259355a3274336354647063586f304f546836636a46755a4456705a7a466d626d63305a584e3549697769595349364e5441774d4377696253493657794a736157646f64473570626d63694c434a76626d4e6f59576c75496c3073496d51694f694a4462325a6d5a5755694c434a6a496a6f784e7a41774d4441774d4441774c434a6c496a6f784e7a41774d44417a4e6a41776651".into(),
+                signature: "2d8ee13718e1c370ccf04bc2c0844261a577944095e357d616a6fd4eccd25179b4d1ed3777d6bb26e7092456394302e5a9311e7a2eb0d40adb2ce017afc4b903".into(),
+            },
+            SignatureVector {
+                scheme: "attestation".into(),
+                secret_key: "0909090909090909090909090909090909090909090909090909090909090909".into(),
+                public_key: "fd1724385aa0c75b64fb78cd602fa1d991fdebf76b13c58ed702eac835e9f618".into(),
+                input: "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f".into(),
+                message: "7061796b69742d6174746573746174696f6e2d7631630dcd2966c4336691125448bbb25b4ff412a49c732db2c8abc1b8581bd710dd".into(),
+                signature: "f05e49e3885f753690b96000461feb66e09079cf1bce01b946a9becd1f884a6b1defb8ff4d06a6aaab6dc3c34f47c1ccba0a187e7072cdb63b01df2f8b51bf00".into(),
+            },
+        ],
+        receipts: vec![
+            ReceiptVector {
+                name: "minimal".into(),
+                receipt_id: "rcpt-0001".into(),
+                payer: PAYER.into(),
+                payee: PAYEE.into(),
+                method_id: "lightning".into(),
+                amount: None,
+                currency: None,
+                created_at: 1_700_000_000,
+                metadata: json!({}),
+                canonical_json: r#"{"created_at":1700000000,"metadata":{},"method_id":"lightning","payee":"9wm1eqn4wddis385xdgsym7b5ge9549zpcjhmdszymicopxj6acy","payer":"7jfgaa9nutjyixzikb7tgmsf9gkwq7iqz498zr1nd5ig1fng4esy","receipt_id":"rcpt-0001"}"#.into(),
+                digest: "bb510d9b8530e38437b40556072544045e4f3408623d287d7aeaaec8cbf54ce0".into(),
+            },
+            ReceiptVector {
+                name: "full".into(),
+                receipt_id: "rcpt-0002".into(),
+                payer: PAYER.into(),
+                payee: PAYEE.into(),
+                method_id: "onchain".into(),
+                amount: Some("150000".into()),
+                currency: Some("SAT".into()),
+                created_at: 1_700_000_123,
+                metadata: json!({
+                    "order_id": "A-17",
+                    "memo": "Café ☕",
+                    "items": [{ "sku": "espresso", "qty": 2 }, { "qty": 1, "sku": "croissant" }],
+                    "attachments": [],
+                }),
+                canonical_json: r#"{"amount":"150000","created_at":1700000123,"currency":"SAT","metadata":{"attachments":[],"items":[{"qty":2,"sku":"espresso"},{"qty":1,"sku":"croissant"}],"memo":"Café ☕","order_id":"A-17"},"method_id":"onchain","payee":"9wm1eqn4wddis385xdgsym7b5ge9549zpcjhmdszymicopxj6acy","payer":"7jfgaa9nutjyixzikb7tgmsf9gkwq7iqz498zr1nd5ig1fng4esy","receipt_id":"rcpt-0002"}"#.into(),
+                digest: "07c82a93cd3085303ab7619e81de6ba17442b59d7252d95c398ed5545f51f000".into(),
+            },
+        ],
+        noise_messages: vec![
+            noise("Ack", r#"{"type":"Ack"}"#),
+            noise(
+                "OfferPrivateEndpoints",
+                r#"{"type":"OfferPrivateEndpoints","payload":{"methods":[{"method_id":"lightning","endpoint":"lnbc1u1p3xyz","expires_at":1700003600},{"method_id":"onchain","endpoint":"bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq","expires_at":null}]}}"#,
+            ),
+            noise(
+                "AcceptPrivateEndpoints",
+                r#"{"type":"AcceptPrivateEndpoints","payload":{"method_ids":["lightning"]}}"#,
+            ),
+            noise(
+                "DeclinePrivateEndpoints",
+                r#"{"type":"DeclinePrivateEndpoints","payload":{"reason":"endpoints expire too soon"}}"#,
+            ),
+            noise(
+                "Error",
+                r#"{"type":"Error","payload":{"code":"RATE_LIMITED","message":"Too many requests"}}"#,
+            ),
+            noise(
+                "Attestation",
+                r#"{"type":"Attestation","payload":{"ed25519_pk":"fd1724385aa0c75b64fb78cd602fa1d991fdebf76b13c58ed702eac835e9f618","signature":"f05e49e3885f753690b96000461feb66e09079cf1bce01b946a9becd1f884a6b1defb8ff4d06a6aaab6dc3c34f47c1ccba0a187e7072cdb63b01df2f8b51bf00"}}"#,
+            ),
+            noise(
+                "SubscriptionStatus",
+                r#"{"type":"SubscriptionStatus","payload":{"notice":{"subscription_id":"sub-1","status":"paused"}}}"#,
+            ),
+        ],
+    }
+}
+
+/// [`vectors`] as pretty-printed JSON, for other implementations.
+pub fn vectors_json() -> Result<String> {
+    Ok(serde_json::to_string_pretty(&vectors())?)
+}
+
+/// Check this implementation against `vectors`.
+pub fn run(vectors: &TestVectors) -> ConformanceReport {
+    let mut report = ConformanceReport::default();
+    if vectors.protocol_version != PROTOCOL_VERSION {
+        report.record(
+            "protocol_version",
+            vectors.protocol_version.clone(),
+            Err(format!("this implementation speaks {}", PROTOCOL_VERSION)),
+        );
+    }
+    for v in &vectors.scopes {
+        let actual = protocol::recipient_scope(&v.pubkey).ok();
+        report.record("scopes", v.pubkey.clone(), expect_eq(&v.scope, &actual));
+    }
+    for v in &vectors.paths {
+        let case = format!("{}({})", v.builder, v.args.join(", "));
+        report.record(
+            "paths",
+            case,
+            build_path(v).and_then(|p| expect_eq(&v.path, &p)),
+        );
+    }
+    for v in &vectors.uris {
+        report.record("uris", v.uri.clone(), check_uri(v));
+    }
+    for v in &vectors.signatures {
+        report.record("signatures", v.scheme.clone(), check_signature(v));
+    }
+    for v in &vectors.receipts {
+        report.record("receipts", v.name.clone(), check_receipt(v));
+    }
+    for v in &vectors.noise_messages {
+        report.record(
+            "noise_messages",
+            v.message_type.clone(),
+            check_noise_message(v),
+        );
+    }
+    report
+}
+
+fn scope(pubkey: &str, scope: Option<&str>) -> ScopeVector {
+    ScopeVector {
+        pubkey: pubkey.into(),
+        scope: scope.map(Into::into),
+    }
+}
+
+fn path(builder: &str, args: &[&str], path: Option<&str>) -> PathVector {
+    PathVector {
+        builder: builder.into(),
+        args: args.iter().map(|a| a.to_string()).collect(),
+        path: path.map(Into::into),
+    }
+}
+
+fn uri(uri: &str, kind: &str) -> UriVector {
+    UriVector {
+        uri: uri.into(),
+        kind: kind.into(),
+        method: None,
+        data: None,
+        public_key: None,
+        request_id: None,
+    }
+}
+
+fn invoice_uri(uri_str: &str, method: &str, data: &str) -> UriVector {
+    UriVector {
+        method: Some(method.into()),
+        data: Some(data.into()),
+        ..uri(uri_str, "invoice")
+    }
+}
+
+fn noise(message_type: &str, json: &str) -> NoiseMessageVector {
+    NoiseMessageVector {
+        message_type: message_type.into(),
+        json: json.into(),
+    }
+}
+
+fn expect_eq<T: PartialEq + std::fmt::Debug>(
+    expected: &T,
+    actual: &T,
+) -> std::result::Result<(), String> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(format!("expected {:?}, got {:?}", expected, actual))
+    }
+}
+
+fn build_path(v: &PathVector) -> std::result::Result<Option<String>, String> {
+    let args: Vec<&str> = v.args.iter().map(String::as_str).collect();
+    let built = match (v.builder.as_str(), args.as_slice()) {
+        ("payment_request", [key, id]) => protocol::payment_request_path(key, id),
+        ("payment_requests_dir", [key]) => protocol::payment_requests_dir(key),
+        ("subscription_proposal", [key, id]) => protocol::subscription_proposal_path(key, id),
+        ("subscription_status", [key, id]) => protocol::subscription_status_path(key, id),
+        ("noise_endpoint", []) => Ok(protocol::noise_endpoint_path().to_string()),
+        ("payee_status", []) => Ok(protocol::payee_status_path().to_string()),
+        ("routing_hints", []) => Ok(protocol::routing_hints_path().to_string()),
+        ("push_hint", []) => Ok(protocol::push_hint_path().to_string()),
+        ("secure_handoff", [id]) => Ok(protocol::secure_handoff_path(id)),
+        ("attachment", [sha256]) => protocol::attachment_path(sha256),
+        ("device_sync", [id]) => protocol::device_sync_path(id),
+        ("inbox_message", [key, id]) => protocol::inbox_message_path(key, id),
+        ("inbox_receipt", [key, id]) => protocol::inbox_receipt_path(key, id),
+        ("payment_link", [id]) => protocol::payment_link_path(id),
+        (builder, args) => {
+            return Err(format!(
+                "unknown builder {} with {} arguments",
+                builder,
+                args.len()
+            ))
+        }
+    };
+    Ok(built.ok())
+}
+
+fn check_uri(v: &UriVector) -> std::result::Result<(), String> {
+    let parsed = match parse_uri(&v.uri) {
+        Ok(parsed) => parsed,
+        Err(_) if v.kind == "invalid" => return Ok(()),
+        Err(e) => return Err(format!("rejected: {}", e)),
+    };
+    let actual = match &parsed {
+        PaykitUri::Pubky { public_key } => UriVector {
+            public_key: Some(public_key.to_z32()),
+            ..uri(&v.uri, "pubky")
+        },
+        PaykitUri::Invoice { method, data } => invoice_uri(&v.uri, &method.0, data),
+        PaykitUri::PaymentRequest { request_id, from } => UriVector {
+            public_key: Some(from.to_z32()),
+            request_id: Some(request_id.clone()),
+            ..uri(&v.uri, "payment_request")
+        },
+        PaykitUri::SignedRequest(link) => UriVector {
+            public_key: Some(link.request().from.to_z32()),
+            request_id: Some(link.request().request_id.clone()),
+            ..uri(&v.uri, "signed_request")
+        },
+        PaykitUri::Handle { handle } => UriVector {
+            data: Some(handle.to_string()),
+            ..uri(&v.uri, "handle")
+        },
+    };
+    expect_eq(v, &actual)
+}
+
+fn check_signature(v: &SignatureVector) -> std::result::Result<(), String> {
+    let secret: [u8; 32] = hex::decode(&v.secret_key)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or("secret_key is not 32 bytes of hex")?;
+    let signing_key = SigningKey::from_bytes(&secret);
+    expect_eq(
+        &v.public_key,
+        &hex::encode(signing_key.verifying_key().to_bytes()),
+    )?;
+
+    let (message, signature) = match v.scheme.as_str() {
+        "payment_request_link" => {
+            let mut message = SIGNED_REQUEST_DOMAIN.to_vec();
+            message.extend_from_slice(v.input.as_bytes());
+            let signature = signing_key.sign(&message).to_bytes();
+            (message, signature)
+        }
+        "attestation" => {
+            let transcript = hex::decode(&v.input).map_err(|e| format!("input: {}", e))?;
+            (
+                attestation_message(&transcript),
+                sign_attestation(&secret, &transcript),
+            )
+        }
+        other => return Err(format!("unknown scheme {}", other)),
+    };
+    expect_eq(&v.message, &hex::encode(&message))?;
+    expect_eq(&v.signature, &hex::encode(signature))?;
+    signing_key
+        .verifying_key()
+        .verify(&message, &Signature::from_bytes(&signature))
+        .map_err(|e| e.to_string())
+}
+
+fn check_receipt(v: &ReceiptVector) -> std::result::Result<(), String> {
+    let key = |z32: &str| PublicKey::from_str(z32).map_err(|e| format!("{}: {}", z32, e));
+    let receipt = PaykitReceipt {
+        receipt_id: v.receipt_id.clone(),
+        payer: key(&v.payer)?,
+        payee: key(&v.payee)?,
+        method_id: MethodId(v.method_id.clone()),
+        amount: v.amount.clone(),
+        currency: v.currency.clone(),
+        created_at: v.created_at,
+        metadata: v.metadata.clone(),
+    };
+    expect_eq(&v.canonical_json, &receipt.canonical_json())?;
+    expect_eq(&v.digest, &receipt.digest())
+}
+
+fn check_noise_message(v: &NoiseMessageVector) -> std::result::Result<(), String> {
+    let expected: serde_json::Value = serde_json::from_str(&v.json).map_err(|e| e.to_string())?;
+    let message: PaykitNoiseMessage =
+        serde_json::from_str(&v.json).map_err(|e| format!("does not parse: {}", e))?;
+    let actual = serde_json::to_value(&message).map_err(|e| e.to_string())?;
+    expect_eq(&expected, &actual)?;
+    expect_eq(&Some(v.message_type.as_str()), &actual["type"].as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_implementation_conforms() {
+        let vectors = vectors();
+        let report = run(&vectors);
+        assert!(report.is_success(), "{:#?}", report.failures);
+
+        let total = vectors.scopes.len()
+            + vectors.paths.len()
+            + vectors.uris.len()
+            + vectors.signatures.len()
+            + vectors.receipts.len()
+            + vectors.noise_messages.len();
+        assert_eq!(report.passed, total);
+    }
+
+    #[test]
+    fn test_published_vectors_match() {
+        let published: TestVectors =
+            serde_json::from_str(include_str!("../../docs/conformance-vectors.json")).unwrap();
+        assert_eq!(published, vectors());
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let json = vectors_json().unwrap();
+        let parsed: TestVectors = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, vectors());
+    }
+
+    #[test]
+    fn test_wrong_vectors_are_reported() {
+        let mut vectors = vectors();
+        vectors.scopes[0].scope = Some("00".repeat(32));
+        vectors.receipts[1].metadata["memo"] = json!("Cafe");
+        vectors.uris[0].kind = "invalid".into();
+
+        let report = run(&vectors);
+        let categories: Vec<_> = report.failures.iter().map(|f| f.category).collect();
+        assert_eq!(categories, vec!["scopes", "uris", "receipts"]);
+    }
+}
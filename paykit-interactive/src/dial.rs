@@ -0,0 +1,350 @@
+//! Dual-stack dialing and reachability diagnostics for Noise endpoints.
+//!
+//! Published Noise endpoints may name a host with both IPv4 and IPv6
+//! addresses, only one of which is reachable from the payer's network.
+//! Dialing them one by one stalls for a full connect timeout on every dead
+//! address, so [`dial`] races them RFC 8305 style ("happy eyeballs"):
+//!
+//! 1. resolve the host and interleave the addresses by family, IPv6 first;
+//! 2. start the first attempt, then start the next one every
+//!    [`DialConfig::attempt_delay`], or immediately when an attempt fails;
+//! 3. keep the first connection that succeeds and abort the others.
+//!
+//! Each attempt is bounded by [`DialConfig::connect_timeout`]. The system
+//! resolver returns A and AAAA records together, so there is no separate
+//! resolution delay.
+//!
+//! [`diagnose_endpoint`] probes every resolved address instead of stopping
+//! at the first success, for support tooling that needs to know which
+//! addresses work and how fast. Diagnostics always connect directly, never
+//! through a proxy, so they reveal the payer's address to the endpoint.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use paykit_interactive::dial::{diagnose_endpoint, dial, DialConfig};
+//!
+//! # async fn example() -> paykit_interactive::Result<()> {
+//! let (stream, addr) = dial("payee.example.com:9735", &DialConfig::default()).await?;
+//! println!("connected to {}", addr);
+//!
+//! let report = diagnose_endpoint("payee.example.com:9735").await;
+//! for probe in &report.probes {
+//!     println!("{} {:?} {:?}", probe.address, probe.latency_ms, probe.error);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{InteractiveError, Result};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::task::JoinSet;
+
+/// Delay between connection attempts recommended by RFC 8305.
+const DEFAULT_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// Per-address connect timeout.
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Timing parameters for [`dial`] and [`diagnose_endpoint_with`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DialConfig {
+    /// How long to wait for an attempt before starting the next address.
+    pub attempt_delay: Duration,
+    /// Upper bound for a single address's TCP connect.
+    pub connect_timeout: Duration,
+}
+
+impl Default for DialConfig {
+    fn default() -> Self {
+        Self {
+            attempt_delay: DEFAULT_ATTEMPT_DELAY,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+        }
+    }
+}
+
+impl DialConfig {
+    /// Set the delay between staggered attempts.
+    pub fn with_attempt_delay(mut self, delay: Duration) -> Self {
+        self.attempt_delay = delay;
+        self
+    }
+
+    /// Set the per-address connect timeout.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+}
+
+/// IP family of a resolved address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressFamily {
+    /// IPv4
+    Ipv4,
+    /// IPv6
+    Ipv6,
+}
+
+impl AddressFamily {
+    /// Family of `addr`.
+    pub fn of(addr: &SocketAddr) -> Self {
+        if addr.is_ipv6() {
+            AddressFamily::Ipv6
+        } else {
+            AddressFamily::Ipv4
+        }
+    }
+}
+
+/// Outcome of probing one resolved address.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressProbe {
+    /// Address that was dialed.
+    pub address: SocketAddr,
+    /// Its IP family.
+    pub family: AddressFamily,
+    /// TCP connect time in milliseconds, if the connection succeeded.
+    pub latency_ms: Option<u64>,
+    /// Why the connection failed, if it did.
+    pub error: Option<String>,
+}
+
+impl AddressProbe {
+    /// Whether the address accepted a TCP connection.
+    pub fn is_reachable(&self) -> bool {
+        self.latency_ms.is_some()
+    }
+}
+
+/// Reachability report for a Noise endpoint.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointDiagnostics {
+    /// Endpoint as given (`host:port`).
+    pub endpoint: String,
+    /// Name resolution time in milliseconds.
+    pub resolve_ms: u64,
+    /// Resolution failure, in which case `probes` is empty.
+    pub resolve_error: Option<String>,
+    /// One probe per resolved address, in dialing order.
+    pub probes: Vec<AddressProbe>,
+}
+
+impl EndpointDiagnostics {
+    /// Whether any address is reachable.
+    pub fn is_reachable(&self) -> bool {
+        self.probes.iter().any(AddressProbe::is_reachable)
+    }
+
+    /// The reachable address with the lowest connect latency.
+    pub fn fastest(&self) -> Option<&AddressProbe> {
+        self.probes
+            .iter()
+            .filter(|probe| probe.is_reachable())
+            .min_by_key(|probe| probe.latency_ms)
+    }
+
+    /// Whether any address of `family` is reachable.
+    pub fn family_reachable(&self, family: AddressFamily) -> bool {
+        self.probes
+            .iter()
+            .any(|probe| probe.family == family && probe.is_reachable())
+    }
+}
+
+/// Order addresses for dialing: alternate families, starting with IPv6,
+/// keeping the resolver's order within each family (RFC 8305 section 4).
+pub fn interleave(addrs: impl IntoIterator<Item = SocketAddr>) -> Vec<SocketAddr> {
+    let (v6, v4): (Vec<_>, Vec<_>) = addrs.into_iter().partition(SocketAddr::is_ipv6);
+    let mut v6 = v6.into_iter();
+    let mut v4 = v4.into_iter();
+    let mut ordered = Vec::with_capacity(v6.len() + v4.len());
+    loop {
+        match (v6.next(), v4.next()) {
+            (None, None) => return ordered,
+            (a, b) => ordered.extend(a.into_iter().chain(b)),
+        }
+    }
+}
+
+async fn resolve(endpoint: &str) -> Result<Vec<SocketAddr>> {
+    let addrs: Vec<_> = tokio::net::lookup_host(endpoint)
+        .await
+        .map_err(|e| InteractiveError::Transport(format!("Cannot resolve {}: {}", endpoint, e)))?
+        .collect();
+    if addrs.is_empty() {
+        return Err(InteractiveError::Transport(format!(
+            "{} resolved to no addresses",
+            endpoint
+        )));
+    }
+    Ok(interleave(addrs))
+}
+
+async fn attempt(
+    address: SocketAddr,
+    timeout: Duration,
+) -> (SocketAddr, Duration, std::result::Result<TcpStream, String>) {
+    let started = Instant::now();
+    let result = match tokio::time::timeout(timeout, TcpStream::connect(address)).await {
+        Ok(Ok(stream)) => Ok(stream),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err(format!("timed out after {:?}", timeout)),
+    };
+    (address, started.elapsed(), result)
+}
+
+/// Connect to `endpoint` (`host:port`), racing its resolved addresses.
+///
+/// Returns the first established stream and the address it connected to.
+/// The error lists every address that failed.
+pub async fn dial(endpoint: &str, config: &DialConfig) -> Result<(TcpStream, SocketAddr)> {
+    let mut queue = resolve(endpoint).await?.into_iter().peekable();
+    let mut attempts = JoinSet::new();
+    let mut failures = Vec::new();
+
+    loop {
+        if let Some(address) = queue.next() {
+            attempts.spawn(attempt(address, config.connect_timeout));
+        }
+        loop {
+            let joined = if queue.peek().is_some() {
+                match tokio::time::timeout(config.attempt_delay, attempts.join_next()).await {
+                    Ok(joined) => joined,
+                    // Attempt delay elapsed: start the next address alongside
+                    Err(_) => break,
+                }
+            } else {
+                attempts.join_next().await
+            };
+            match joined {
+                // Dropping the JoinSet aborts the attempts still in flight
+                Some(Ok((address, _, Ok(stream)))) => return Ok((stream, address)),
+                Some(Ok((address, _, Err(e)))) => {
+                    failures.push(format!("{}: {}", address, e));
+                    if queue.peek().is_some() {
+                        break;
+                    }
+                }
+                Some(Err(e)) => failures.push(e.to_string()),
+                None => break,
+            }
+        }
+        if queue.peek().is_none() && attempts.is_empty() {
+            return Err(InteractiveError::Transport(format!(
+                "Connect to {} failed ({})",
+                endpoint,
+                failures.join("; ")
+            )));
+        }
+    }
+}
+
+/// Probe every address of `endpoint` with the default timeouts.
+pub async fn diagnose_endpoint(endpoint: &str) -> EndpointDiagnostics {
+    diagnose_endpoint_with(endpoint, &DialConfig::default()).await
+}
+
+/// Probe every address of `endpoint` in parallel, recording which ones
+/// accept a TCP connection and how long the connect took.
+///
+/// Only `connect_timeout` applies; all addresses are dialed at once.
+pub async fn diagnose_endpoint_with(endpoint: &str, config: &DialConfig) -> EndpointDiagnostics {
+    let started = Instant::now();
+    let resolved = resolve(endpoint).await;
+    let resolve_ms = started.elapsed().as_millis() as u64;
+
+    let addresses = match resolved {
+        Ok(addresses) => addresses,
+        Err(e) => {
+            return EndpointDiagnostics {
+                endpoint: endpoint.to_string(),
+                resolve_ms,
+                resolve_error: Some(e.to_string()),
+                probes: Vec::new(),
+            }
+        }
+    };
+
+    let mut attempts = JoinSet::new();
+    for address in &addresses {
+        attempts.spawn(attempt(*address, config.connect_timeout));
+    }
+    let mut probes: Vec<AddressProbe> = Vec::with_capacity(addresses.len());
+    while let Some(joined) = attempts.join_next().await {
+        if let Ok((address, elapsed, result)) = joined {
+            probes.push(AddressProbe {
+                address,
+                family: AddressFamily::of(&address),
+                latency_ms: result.as_ref().ok().map(|_| elapsed.as_millis() as u64),
+                error: result.err(),
+            });
+        }
+    }
+    probes.sort_by_key(|probe| addresses.iter().position(|a| *a == probe.address));
+
+    EndpointDiagnostics {
+        endpoint: endpoint.to_string(),
+        resolve_ms,
+        resolve_error: None,
+        probes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_interleave_prefers_ipv6() {
+        let addrs: Vec<SocketAddr> = ["1.1.1.1:1", "2.2.2.2:1", "[::1]:1", "3.3.3.3:1", "[::2]:1"]
+            .iter()
+            .map(|a| a.parse().unwrap())
+            .collect();
+        let ordered: Vec<String> = interleave(addrs).iter().map(|a| a.to_string()).collect();
+        assert_eq!(
+            ordered,
+            ["[::1]:1", "1.1.1.1:1", "[::2]:1", "2.2.2.2:1", "3.3.3.3:1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dial_and_diagnose_local_listener() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = listener.local_addr().unwrap().to_string();
+
+        let (_stream, address) = dial(&endpoint, &DialConfig::default()).await.unwrap();
+        assert_eq!(address.to_string(), endpoint);
+
+        let report = diagnose_endpoint(&endpoint).await;
+        assert!(report.resolve_error.is_none());
+        assert!(report.is_reachable());
+        assert!(report.family_reachable(AddressFamily::Ipv4));
+        assert_eq!(report.fastest().unwrap().address.to_string(), endpoint);
+    }
+
+    #[tokio::test]
+    async fn test_dial_reports_failures() {
+        // Bind then drop to get a port nothing listens on
+        let endpoint = TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .to_string();
+
+        let config = DialConfig::default().with_connect_timeout(Duration::from_secs(1));
+        assert!(dial(&endpoint, &config).await.is_err());
+
+        let report = diagnose_endpoint_with(&endpoint, &config).await;
+        assert!(!report.is_reachable());
+        assert_eq!(report.probes.len(), 1);
+        assert!(report.probes[0].error.is_some());
+    }
+}
@@ -0,0 +1,243 @@
+//! Eviction policies for locally stored receipts.
+//!
+//! Receipts accumulate forever unless something removes them. An
+//! [`EvictionPolicy`] caps how many receipts (and how many serialized bytes)
+//! are kept, and [`evict_receipts`] deletes the oldest receipts whose
+//! payments have reached a terminal state until the store is back under the
+//! caps. Receipts for payments still in progress are never evicted, nor are
+//! receipts younger than [`EvictionPolicy::min_age_secs`].
+//!
+//! Payments the [`PaymentStatusTracker`] does not know about are treated as
+//! settled, since a restarted app only tracks payments it is still watching.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use paykit_interactive::eviction::{evict_receipts, EvictionPolicy};
+//!
+//! let policy = EvictionPolicy {
+//!     max_receipts: Some(5_000),
+//!     ..Default::default()
+//! };
+//! let report = evict_receipts(storage.as_ref().as_ref(), &policy, Some(&tracker), now).await?;
+//! println!("evicted {} receipts, {} bytes", report.evicted.len(), report.bytes_freed);
+//! ```
+
+use crate::status::PaymentStatusTracker;
+use crate::{PaykitReceipt, PaykitStorage, Result};
+use serde::{Deserialize, Serialize};
+
+/// Limits on locally stored receipts.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvictionPolicy {
+    /// Maximum receipts to keep. `None` means unlimited.
+    pub max_receipts: Option<usize>,
+    /// Maximum serialized bytes of receipts to keep. `None` means unlimited.
+    pub max_bytes: Option<u64>,
+    /// Receipts younger than this are never evicted.
+    pub min_age_secs: i64,
+}
+
+impl Default for EvictionPolicy {
+    /// Keep 10,000 receipts, with no byte limit, and never evict receipts
+    /// younger than a week.
+    fn default() -> Self {
+        Self {
+            max_receipts: Some(10_000),
+            max_bytes: None,
+            min_age_secs: 7 * 24 * 60 * 60,
+        }
+    }
+}
+
+impl EvictionPolicy {
+    /// Whether a store with `receipts` receipts totalling `bytes` is over the limits.
+    pub fn is_exceeded(&self, receipts: usize, bytes: u64) -> bool {
+        self.max_receipts.is_some_and(|max| receipts > max)
+            || self.max_bytes.is_some_and(|max| bytes > max)
+    }
+}
+
+/// Outcome of an eviction pass.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvictionReport {
+    /// Receipts in the store before eviction.
+    pub examined: usize,
+    /// IDs of evicted receipts, oldest first.
+    pub evicted: Vec<String>,
+    /// Serialized bytes of the evicted receipts.
+    pub bytes_freed: u64,
+    /// Receipts left in the store.
+    pub remaining: usize,
+    /// Serialized bytes of the receipts left.
+    pub remaining_bytes: u64,
+    /// Whether the store is still over the limits because the remaining
+    /// receipts are too recent or still in progress.
+    pub over_limit: bool,
+}
+
+/// Choose which receipts to evict, without deleting anything.
+///
+/// Returns the report [`evict_receipts`] would produce.
+pub fn plan_eviction(
+    receipts: &[PaykitReceipt],
+    policy: &EvictionPolicy,
+    tracker: Option<&PaymentStatusTracker>,
+    now: i64,
+) -> EvictionReport {
+    let sizes: Vec<u64> = receipts.iter().map(receipt_size).collect();
+    let mut remaining = receipts.len();
+    let mut remaining_bytes: u64 = sizes.iter().sum();
+
+    let mut candidates: Vec<(i64, &str, u64)> = receipts
+        .iter()
+        .zip(&sizes)
+        .filter(|(receipt, _)| now - receipt.created_at >= policy.min_age_secs)
+        .filter(|(receipt, _)| {
+            let status = tracker.and_then(|t| t.get(&receipt.receipt_id));
+            !status.is_some_and(|info| !info.status.is_terminal())
+        })
+        .map(|(receipt, size)| (receipt.created_at, receipt.receipt_id.as_str(), *size))
+        .collect();
+    candidates.sort();
+
+    let mut evicted = Vec::new();
+    let mut bytes_freed = 0;
+    for (_, receipt_id, size) in candidates {
+        if !policy.is_exceeded(remaining, remaining_bytes) {
+            break;
+        }
+        evicted.push(receipt_id.to_string());
+        bytes_freed += size;
+        remaining -= 1;
+        remaining_bytes -= size;
+    }
+
+    EvictionReport {
+        examined: receipts.len(),
+        evicted,
+        bytes_freed,
+        remaining,
+        remaining_bytes,
+        over_limit: policy.is_exceeded(remaining, remaining_bytes),
+    }
+}
+
+/// Delete the oldest settled receipts until `storage` is within `policy`.
+///
+/// # Errors
+///
+/// Fails if the receipts cannot be listed or a delete fails. Receipts
+/// deleted before the failure stay deleted.
+pub async fn evict_receipts(
+    storage: &dyn PaykitStorage,
+    policy: &EvictionPolicy,
+    tracker: Option<&PaymentStatusTracker>,
+    now: i64,
+) -> Result<EvictionReport> {
+    let receipts = storage.list_receipts().await?;
+    let report = plan_eviction(&receipts, policy, tracker, now);
+    for receipt_id in &report.evicted {
+        storage.delete_receipt(receipt_id).await?;
+    }
+
+    #[cfg(feature = "tracing")]
+    if !report.evicted.is_empty() {
+        tracing::info!(
+            evicted = report.evicted.len(),
+            bytes_freed = report.bytes_freed,
+            remaining = report.remaining,
+            "evicted receipts"
+        );
+    }
+
+    Ok(report)
+}
+
+fn receipt_size(receipt: &PaykitReceipt) -> u64 {
+    serde_json::to_vec(receipt).map_or(0, |bytes| bytes.len() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status::PaymentStatus;
+    use paykit_lib::MethodId;
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    fn receipt(id: &str, created_at: i64) -> PaykitReceipt {
+        let key = pubky::Keypair::random().public_key();
+        let mut receipt = PaykitReceipt::new(
+            id.to_string(),
+            key.clone(),
+            key,
+            MethodId("lightning".to_string()),
+            Some("1000".to_string()),
+            Some("SAT".to_string()),
+            serde_json::json!({}),
+        );
+        receipt.created_at = created_at;
+        receipt
+    }
+
+    #[test]
+    fn test_evicts_oldest_first() {
+        let now = 100 * DAY;
+        let receipts = vec![
+            receipt("newer", 50 * DAY),
+            receipt("oldest", 10 * DAY),
+            receipt("older", 20 * DAY),
+        ];
+        let policy = EvictionPolicy {
+            max_receipts: Some(1),
+            max_bytes: None,
+            min_age_secs: DAY,
+        };
+
+        let report = plan_eviction(&receipts, &policy, None, now);
+        assert_eq!(report.evicted, vec!["oldest", "older"]);
+        assert_eq!(report.remaining, 1);
+        assert!(!report.over_limit);
+    }
+
+    #[test]
+    fn test_protects_recent_and_in_progress() {
+        let now = 100 * DAY;
+        let receipts = vec![
+            receipt("pending", 10 * DAY),
+            receipt("failed", 20 * DAY),
+            receipt("fresh", now - 60),
+        ];
+        let tracker = PaymentStatusTracker::new();
+        tracker.track(&receipts[0]);
+        tracker.track(&receipts[1]);
+        tracker.update("failed", PaymentStatus::Failed);
+        let policy = EvictionPolicy {
+            max_receipts: Some(0),
+            max_bytes: None,
+            min_age_secs: DAY,
+        };
+
+        let report = plan_eviction(&receipts, &policy, Some(&tracker), now);
+        assert_eq!(report.evicted, vec!["failed"]);
+        assert_eq!(report.remaining, 2);
+        assert!(report.over_limit);
+    }
+
+    #[test]
+    fn test_byte_limit() {
+        let receipts = vec![receipt("a", 0), receipt("b", 1), receipt("c", 2)];
+        let size = receipt_size(&receipts[0]);
+        let policy = EvictionPolicy {
+            max_receipts: None,
+            max_bytes: Some(size * 2),
+            min_age_secs: 0,
+        };
+
+        let report = plan_eviction(&receipts, &policy, None, 10);
+        assert_eq!(report.evicted, vec!["a"]);
+        assert_eq!(report.bytes_freed, size);
+        assert!(report.remaining_bytes <= size * 2);
+    }
+}
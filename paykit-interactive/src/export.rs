@@ -0,0 +1,635 @@
+//! Receipt export for accounting tools.
+//!
+//! Beyond a plain CSV dump, receipts can be exported in the import formats of
+//! common accounting tools. An [`AccountingExporter`] looks at each receipt
+//! from the owner's side (money in or money out), maps it to ledger accounts
+//! with an [`AccountMapping`], optionally converts it to a reporting currency
+//! through a [`RateProvider`], and writes it in the chosen
+//! [`AccountingDialect`]:
+//!
+//! - [`AccountingDialect::Csv`]: one row per receipt with every field.
+//! - [`AccountingDialect::QuickBooks`]: QuickBooks Online journal entry
+//!   import, one balanced journal per receipt.
+//! - [`AccountingDialect::Xero`]: Xero precoded bank statement import, one
+//!   line per receipt plus one per fee.
+//!
+//! On-chain and Lightning fees recorded in the receipt metadata (`fee_sats`
+//! or `fee_msat`, at the top level or under `execution`) are booked to the
+//! fee account on outgoing payments.
+//!
+//! Receipts that cannot be booked (no date, no amount, not involving the
+//! owner, no exchange rate) are left out and listed in
+//! [`AccountingExport::skipped`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! use paykit_interactive::export::{AccountingDialect, AccountingExporter, FixedRates};
+//!
+//! let rates = FixedRates::new().with_rate("SAT", "USD", 0.0006);
+//! let export = AccountingExporter::new(AccountingDialect::Xero, my_pubkey)
+//!     .with_accounts(accounts)
+//!     .with_currency("USD", &rates)
+//!     .export(&receipts);
+//! std::fs::write("xero.csv", export.content)?;
+//! ```
+
+use crate::metadata::memo_from_metadata;
+use crate::{InteractiveError, PaykitReceipt, Result, SearchableReceipt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Receipt fields an accounting export needs beyond [`SearchableReceipt`].
+pub trait ExportableReceipt: SearchableReceipt {
+    /// Currency code of the amount, if any.
+    fn currency(&self) -> Option<&str>;
+    /// Receipt metadata.
+    fn metadata(&self) -> Cow<'_, Value>;
+}
+
+impl ExportableReceipt for PaykitReceipt {
+    fn currency(&self) -> Option<&str> {
+        self.currency.as_deref()
+    }
+
+    fn metadata(&self) -> Cow<'_, Value> {
+        Cow::Borrowed(&self.metadata)
+    }
+}
+
+/// Output format of an accounting export.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountingDialect {
+    /// Generic CSV with every field.
+    #[default]
+    Csv,
+    /// QuickBooks Online journal entry import.
+    QuickBooks,
+    /// Xero precoded bank statement import.
+    Xero,
+}
+
+impl AccountingDialect {
+    /// Every dialect, for listing choices.
+    pub const ALL: [AccountingDialect; 3] = [
+        AccountingDialect::Csv,
+        AccountingDialect::QuickBooks,
+        AccountingDialect::Xero,
+    ];
+
+    /// Name used on the command line and in settings.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountingDialect::Csv => "csv",
+            AccountingDialect::QuickBooks => "quickbooks",
+            AccountingDialect::Xero => "xero",
+        }
+    }
+
+    fn header(&self) -> &'static str {
+        match self {
+            AccountingDialect::Csv => {
+                "date,receipt_id,direction,counterparty,method,account,amount,fee,currency,memo"
+            }
+            AccountingDialect::QuickBooks => {
+                "JournalNo,JournalDate,Currency,Memo,AccountName,Debits,Credits,Description,Name"
+            }
+            AccountingDialect::Xero => "Date,Amount,Payee,Description,Reference,Account Code",
+        }
+    }
+}
+
+impl fmt::Display for AccountingDialect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AccountingDialect {
+    type Err = InteractiveError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(AccountingDialect::Csv),
+            "quickbooks" | "qbo" => Ok(AccountingDialect::QuickBooks),
+            "xero" => Ok(AccountingDialect::Xero),
+            other => Err(InteractiveError::Protocol(format!(
+                "unknown export format '{}' (expected csv, quickbooks or xero)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Ledger accounts receipts are booked to.
+///
+/// Names for QuickBooks, account codes for Xero.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccountMapping {
+    /// Asset account holding the funds.
+    pub bank_account: String,
+    /// Account credited for money received.
+    pub income_account: String,
+    /// Account debited for money paid.
+    pub expense_account: String,
+    /// Account debited for network and routing fees.
+    pub fee_account: String,
+    /// Asset account per payment method, overriding `bank_account`.
+    pub method_accounts: HashMap<String, String>,
+    /// Income or expense account per counterparty public key.
+    pub counterparty_accounts: HashMap<String, String>,
+    /// Display name per counterparty public key.
+    pub counterparty_names: HashMap<String, String>,
+}
+
+impl Default for AccountMapping {
+    fn default() -> Self {
+        Self {
+            bank_account: "Bitcoin".to_string(),
+            income_account: "Sales".to_string(),
+            expense_account: "Purchases".to_string(),
+            fee_account: "Bank Fees".to_string(),
+            method_accounts: HashMap::new(),
+            counterparty_accounts: HashMap::new(),
+            counterparty_names: HashMap::new(),
+        }
+    }
+}
+
+impl AccountMapping {
+    fn bank_for(&self, method: &str) -> &str {
+        self.method_accounts
+            .get(method)
+            .unwrap_or(&self.bank_account)
+    }
+
+    fn category_for(&self, counterparty: &str, incoming: bool) -> &str {
+        self.counterparty_accounts
+            .get(counterparty)
+            .unwrap_or(if incoming {
+                &self.income_account
+            } else {
+                &self.expense_account
+            })
+    }
+
+    fn name_for<'a>(&'a self, counterparty: &'a str) -> &'a str {
+        self.counterparty_names
+            .get(counterparty)
+            .map(String::as_str)
+            .unwrap_or(counterparty)
+    }
+}
+
+/// Exchange rates for converting receipts to a reporting currency.
+pub trait RateProvider: Send + Sync {
+    /// Units of `to` per unit of `from` at time `at`, if known.
+    fn rate(&self, from: &str, to: &str, at: i64) -> Option<f64>;
+}
+
+/// [`RateProvider`] with fixed rates, whatever the date.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FixedRates {
+    rates: HashMap<String, f64>,
+}
+
+impl FixedRates {
+    /// Create an empty rate table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the rate from `from` to `to`; the inverse is implied.
+    pub fn with_rate(mut self, from: &str, to: &str, rate: f64) -> Self {
+        self.rates.insert(Self::key(from, to), rate);
+        self
+    }
+
+    fn key(from: &str, to: &str) -> String {
+        format!("{}/{}", from.to_ascii_uppercase(), to.to_ascii_uppercase())
+    }
+}
+
+impl RateProvider for FixedRates {
+    fn rate(&self, from: &str, to: &str, _at: i64) -> Option<f64> {
+        if from.eq_ignore_ascii_case(to) {
+            return Some(1.0);
+        }
+        self.rates.get(&Self::key(from, to)).copied().or_else(|| {
+            self.rates
+                .get(&Self::key(to, from))
+                .filter(|rate| **rate != 0.0)
+                .map(|rate| 1.0 / rate)
+        })
+    }
+}
+
+/// A receipt left out of an export, and why.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedReceipt {
+    /// Receipt identifier.
+    pub receipt_id: String,
+    /// Why it was left out.
+    pub reason: String,
+}
+
+/// Result of an accounting export.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountingExport {
+    /// File contents, header row included.
+    pub content: String,
+    /// Number of receipts exported.
+    pub exported: usize,
+    /// Receipts left out.
+    pub skipped: Vec<SkippedReceipt>,
+}
+
+/// Writes receipts in an accounting tool's import format.
+pub struct AccountingExporter<'a> {
+    dialect: AccountingDialect,
+    owner: String,
+    accounts: AccountMapping,
+    currency: Option<(String, &'a dyn RateProvider)>,
+}
+
+/// A receipt from the owner's side, in the export currency.
+struct Entry {
+    receipt_id: String,
+    date: (i64, u32, u32),
+    incoming: bool,
+    counterparty: String,
+    method: String,
+    amount: String,
+    fee: Option<String>,
+    currency: String,
+    memo: String,
+}
+
+impl<'a> AccountingExporter<'a> {
+    /// Export receipts as seen by `owner` (a public key).
+    pub fn new(dialect: AccountingDialect, owner: impl Into<String>) -> Self {
+        Self {
+            dialect,
+            owner: owner.into(),
+            accounts: AccountMapping::default(),
+            currency: None,
+        }
+    }
+
+    /// Set the account mapping.
+    pub fn with_accounts(mut self, accounts: AccountMapping) -> Self {
+        self.accounts = accounts;
+        self
+    }
+
+    /// Convert every amount to `currency` using `rates`.
+    pub fn with_currency(
+        mut self,
+        currency: impl Into<String>,
+        rates: &'a dyn RateProvider,
+    ) -> Self {
+        self.currency = Some((currency.into().to_ascii_uppercase(), rates));
+        self
+    }
+
+    /// Export `receipts`, oldest first.
+    pub fn export<R: ExportableReceipt>(&self, receipts: &[R]) -> AccountingExport {
+        let mut entries = Vec::new();
+        let mut skipped = Vec::new();
+        for receipt in receipts {
+            match self.entry(receipt) {
+                Ok(entry) => entries.push((receipt.created_at().unwrap_or_default(), entry)),
+                Err(reason) => skipped.push(SkippedReceipt {
+                    receipt_id: receipt.receipt_id().to_string(),
+                    reason,
+                }),
+            }
+        }
+        entries.sort_by(|(a, x), (b, y)| a.cmp(b).then_with(|| x.receipt_id.cmp(&y.receipt_id)));
+
+        let mut content = format!("{}\n", self.dialect.header());
+        for (_, entry) in &entries {
+            for row in self.rows(entry) {
+                let fields: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+                content.push_str(&fields.join(","));
+                content.push('\n');
+            }
+        }
+        AccountingExport {
+            content,
+            exported: entries.len(),
+            skipped,
+        }
+    }
+
+    fn entry<R: ExportableReceipt>(&self, receipt: &R) -> std::result::Result<Entry, String> {
+        let at = receipt.created_at().ok_or("no date")?;
+        let (payer, payee) = (receipt.payer(), receipt.payee());
+        let incoming = payee == self.owner;
+        if !incoming && payer != self.owner {
+            return Err("not paid to or by the owner".to_string());
+        }
+        let raw_amount = receipt.amount().ok_or("no amount")?;
+        let amount: f64 = raw_amount
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid amount '{}'", raw_amount))?;
+        let currency = receipt
+            .currency()
+            .unwrap_or(DEFAULT_CURRENCY)
+            .to_ascii_uppercase();
+        let metadata = receipt.metadata();
+
+        // Only the payer bears the fee
+        let fee_sats = if incoming { None } else { fee_sats(&metadata) };
+        let (amount, fee, currency) = match &self.currency {
+            Some((target, rates)) => {
+                let rate = rates
+                    .rate(&currency, target, at)
+                    .ok_or_else(|| format!("no {}/{} rate", currency, target))?;
+                let fee = match fee_sats {
+                    Some(fee) => {
+                        let fee_rate = rates
+                            .rate(DEFAULT_CURRENCY, target, at)
+                            .ok_or_else(|| format!("no {}/{} rate", DEFAULT_CURRENCY, target))?;
+                        Some(format!("{:.2}", fee as f64 * fee_rate))
+                    }
+                    None => None,
+                };
+                (format!("{:.2}", amount * rate), fee, target.clone())
+            }
+            // Without conversion, fees are only booked in sats
+            None => (
+                raw_amount.trim().to_string(),
+                fee_sats
+                    .filter(|_| currency == DEFAULT_CURRENCY)
+                    .map(|fee| fee.to_string()),
+                currency,
+            ),
+        };
+
+        Ok(Entry {
+            receipt_id: receipt.receipt_id().to_string(),
+            date: civil_date(at),
+            incoming,
+            counterparty: if incoming { payer } else { payee },
+            method: receipt.method_id().to_string(),
+            amount,
+            fee,
+            currency,
+            memo: memo_from_metadata(&metadata).unwrap_or_default(),
+        })
+    }
+
+    fn rows(&self, entry: &Entry) -> Vec<Vec<String>> {
+        let accounts = &self.accounts;
+        let bank = accounts.bank_for(&entry.method).to_string();
+        let category = accounts
+            .category_for(&entry.counterparty, entry.incoming)
+            .to_string();
+        let name = accounts.name_for(&entry.counterparty).to_string();
+        let description = if entry.memo.is_empty() {
+            format!("Paykit {} payment", entry.method)
+        } else {
+            entry.memo.clone()
+        };
+        let (year, month, day) = entry.date;
+
+        match self.dialect {
+            AccountingDialect::Csv => vec![vec![
+                format!("{:04}-{:02}-{:02}", year, month, day),
+                entry.receipt_id.clone(),
+                if entry.incoming { "in" } else { "out" }.to_string(),
+                name,
+                entry.method.clone(),
+                category,
+                entry.amount.clone(),
+                entry.fee.clone().unwrap_or_default(),
+                entry.currency.clone(),
+                entry.memo.clone(),
+            ]],
+            AccountingDialect::QuickBooks => {
+                let date = format!("{:02}/{:02}/{:04}", month, day, year);
+                let line = |account: &str, debit: &str, credit: &str| {
+                    vec![
+                        entry.receipt_id.clone(),
+                        date.clone(),
+                        entry.currency.clone(),
+                        entry.memo.clone(),
+                        account.to_string(),
+                        debit.to_string(),
+                        credit.to_string(),
+                        description.clone(),
+                        name.clone(),
+                    ]
+                };
+                let (debit, credit) = if entry.incoming {
+                    (&bank, &category)
+                } else {
+                    (&category, &bank)
+                };
+                let mut rows = vec![
+                    line(debit, &entry.amount, ""),
+                    line(credit, "", &entry.amount),
+                ];
+                if let Some(fee) = &entry.fee {
+                    rows.push(line(&accounts.fee_account, fee, ""));
+                    rows.push(line(&bank, "", fee));
+                }
+                rows
+            }
+            AccountingDialect::Xero => {
+                let date = format!("{:02}/{:02}/{:04}", day, month, year);
+                let sign = if entry.incoming { "" } else { "-" };
+                let mut rows = vec![vec![
+                    date.clone(),
+                    format!("{}{}", sign, entry.amount),
+                    name.clone(),
+                    description,
+                    entry.receipt_id.clone(),
+                    category,
+                ]];
+                if let Some(fee) = &entry.fee {
+                    rows.push(vec![
+                        date,
+                        format!("-{}", fee),
+                        name,
+                        format!("Network fee ({})", entry.method),
+                        entry.receipt_id.clone(),
+                        accounts.fee_account.clone(),
+                    ]);
+                }
+                rows
+            }
+        }
+    }
+}
+
+/// Currency assumed for receipts that do not name one.
+const DEFAULT_CURRENCY: &str = "SAT";
+
+/// Fee in sats recorded in receipt metadata, if any.
+fn fee_sats(metadata: &Value) -> Option<u64> {
+    [
+        Some(metadata),
+        metadata.get("execution"),
+        metadata.get("execution_data"),
+    ]
+    .into_iter()
+    .flatten()
+    .find_map(|value| {
+        value.get("fee_sats").and_then(Value::as_u64).or_else(|| {
+            value
+                .get("fee_msat")
+                .and_then(Value::as_u64)
+                .map(|msat| msat / 1000)
+        })
+    })
+    .filter(|fee| *fee > 0)
+}
+
+/// UTC (year, month, day) of a unix timestamp.
+fn civil_date(timestamp: i64) -> (i64, u32, u32) {
+    // Howard Hinnant's days-to-civil algorithm
+    let days = timestamp.div_euclid(86_400);
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paykit_lib::{MethodId, PublicKey};
+
+    fn pubkey() -> PublicKey {
+        pubky::Keypair::random().public_key()
+    }
+
+    fn receipt(
+        id: &str,
+        payer: &PublicKey,
+        payee: &PublicKey,
+        amount: &str,
+        metadata: Value,
+    ) -> PaykitReceipt {
+        let mut receipt = PaykitReceipt::new(
+            id.to_string(),
+            payer.clone(),
+            payee.clone(),
+            MethodId("lightning".to_string()),
+            Some(amount.to_string()),
+            Some("SAT".to_string()),
+            metadata,
+        );
+        // 2024-03-05 12:00 UTC
+        receipt.created_at = 1_709_640_000;
+        receipt
+    }
+
+    #[test]
+    fn test_civil_date() {
+        assert_eq!(civil_date(0), (1970, 1, 1));
+        assert_eq!(civil_date(1_709_640_000), (2024, 3, 5));
+        assert_eq!(civil_date(951_782_400), (2000, 2, 29));
+        assert_eq!(civil_date(-1), (1969, 12, 31));
+    }
+
+    #[test]
+    fn test_quickbooks_journal_balances_with_fee() {
+        let (me, shop, client) = (pubkey(), pubkey(), pubkey());
+        let receipts = vec![
+            receipt(
+                "r-out",
+                &me,
+                &shop,
+                "10000",
+                serde_json::json!({"memo": "Office, supplies", "execution": {"fee_msat": 21_000}}),
+            ),
+            receipt("r-in", &client, &me, "50000", serde_json::json!({})),
+            receipt("r-other", &client, &shop, "1", serde_json::json!({})),
+        ];
+        let mut accounts = AccountMapping::default();
+        accounts
+            .counterparty_names
+            .insert(shop.to_string(), "Paper Shop".to_string());
+
+        let rates = FixedRates::new().with_rate("USD", "SAT", 2_000.0);
+        let export = AccountingExporter::new(AccountingDialect::QuickBooks, me.to_string())
+            .with_accounts(accounts)
+            .with_currency("usd", &rates)
+            .export(&receipts);
+
+        assert_eq!(export.exported, 2);
+        assert_eq!(export.skipped[0].receipt_id, "r-other");
+        let lines: Vec<&str> = export.content.lines().collect();
+        assert_eq!(lines.len(), 1 + 4 + 2);
+        assert_eq!(
+            lines[1],
+            "r-in,03/05/2024,USD,,Bitcoin,25.00,,Paykit lightning payment,".to_string()
+                + &client.to_string()
+        );
+        assert!(lines[3].starts_with("r-out,03/05/2024,USD,\"Office, supplies\",Purchases,5.00,,"));
+        assert!(lines[3].ends_with(",Paper Shop"));
+        assert!(lines[5].contains(",Bank Fees,0.01,,"));
+        assert!(lines[6].contains(",Bitcoin,,0.01,"));
+    }
+
+    #[test]
+    fn test_xero_statement_lines() {
+        let (me, shop) = (pubkey(), pubkey());
+        let receipts = vec![receipt(
+            "r1",
+            &me,
+            &shop,
+            "10000",
+            serde_json::json!({"fee_sats": 150}),
+        )];
+        let mut accounts = AccountMapping {
+            expense_account: "429".to_string(),
+            fee_account: "404".to_string(),
+            ..AccountMapping::default()
+        };
+        accounts
+            .counterparty_accounts
+            .insert(shop.to_string(), "300".to_string());
+
+        let export = AccountingExporter::new("xero".parse().unwrap(), me.to_string())
+            .with_accounts(accounts)
+            .export(&receipts);
+        let lines: Vec<&str> = export.content.lines().collect();
+        assert_eq!(lines[0], AccountingDialect::Xero.header());
+        assert!(lines[1].starts_with("05/03/2024,-10000,"));
+        assert!(lines[1].ends_with(",r1,300"));
+        assert!(lines[2].starts_with("05/03/2024,-150,"));
+        assert!(lines[2].ends_with(",r1,404"));
+
+        let missing = AccountingExporter::new(AccountingDialect::Csv, me.to_string())
+            .with_currency("EUR", &FixedRates::new())
+            .export(&receipts);
+        assert_eq!(missing.exported, 0);
+        assert_eq!(missing.skipped[0].reason, "no SAT/EUR rate");
+        assert!("sage".parse::<AccountingDialect>().is_err());
+    }
+}
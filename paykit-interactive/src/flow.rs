@@ -0,0 +1,246 @@
+//! Receipt exchange state machine, deadlines and retransmission.
+//!
+//! A receipt exchange is three messages:
+//!
+//! ```text
+//! Payer                          Payee
+//!   |-- RequestReceipt ----------->|   RequestSent / RequestReceived
+//!   |<--------- ConfirmReceipt ----|   ConfirmSent
+//!   |-- Ack ---------------------->|   Completed
+//! ```
+//!
+//! Each side tracks a [`ReceiptFlow`]. Events that make no sense in the
+//! current state, such as a confirmation nobody asked for, are rejected with
+//! [`InteractiveError::IllegalTransition`] instead of being acted on.
+//!
+//! A side waiting for the next message gives up after
+//! [`FlowConfig::exchange_timeout`] and sends its last message again, up to
+//! [`FlowConfig::max_attempts`] sends in total. Retransmissions are safe:
+//! the payee answers a repeated request with the receipt it already issued,
+//! and the payer answers a repeated confirmation with another `Ack`.
+
+use crate::{InteractiveError, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Where a receipt exchange stands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FlowState {
+    /// Nothing sent or received yet.
+    Idle,
+    /// Payer: `RequestReceipt` sent, waiting for the confirmation.
+    RequestSent,
+    /// Payee: `RequestReceipt` received, receipt being generated.
+    RequestReceived,
+    /// Payee: `ConfirmReceipt` sent, waiting for the `Ack`.
+    ConfirmSent,
+    /// The receipt is confirmed (payer) or acknowledged (payee).
+    Completed,
+    /// The peer refused, or answered with the wrong receipt.
+    Failed,
+    /// Every attempt went unanswered. A late confirmation still moves the
+    /// payer to `Completed`.
+    TimedOut,
+}
+
+impl FlowState {
+    /// Whether the exchange is over.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            FlowState::Completed | FlowState::Failed | FlowState::TimedOut
+        )
+    }
+}
+
+/// Something that happened in a receipt exchange.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FlowEvent {
+    /// Payer sent `RequestReceipt`.
+    SendRequest,
+    /// Payee received `RequestReceipt`.
+    ReceiveRequest,
+    /// Payee sent `ConfirmReceipt`.
+    SendConfirm,
+    /// Payer received `ConfirmReceipt`.
+    ReceiveConfirm,
+    /// Payee received the payer's `Ack`.
+    ReceiveAck,
+    /// The last message was sent again after a deadline passed.
+    Retransmit,
+    /// The last deadline passed with no attempts left.
+    Expire,
+    /// The peer sent an error or a mismatched receipt.
+    Fail,
+}
+
+/// Deadlines and retransmission limits for receipt exchanges.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlowConfig {
+    /// How long to wait for each reply.
+    pub exchange_timeout: Duration,
+    /// Sends of the same message before giving up, including the first.
+    pub max_attempts: u32,
+}
+
+impl Default for FlowConfig {
+    /// Three sends ten seconds apart: the same 30 seconds the payer used to
+    /// wait for a single reply.
+    fn default() -> Self {
+        Self {
+            exchange_timeout: Duration::from_secs(10),
+            max_attempts: 3,
+        }
+    }
+}
+
+impl FlowConfig {
+    /// Set how long to wait for each reply.
+    pub fn with_exchange_timeout(mut self, timeout: Duration) -> Self {
+        self.exchange_timeout = timeout;
+        self
+    }
+
+    /// Set the number of sends before giving up (at least 1).
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+}
+
+/// One receipt exchange, seen from one side.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiptFlow {
+    /// The receipt being exchanged.
+    pub receipt_id: String,
+    state: FlowState,
+    attempts: u32,
+}
+
+impl ReceiptFlow {
+    /// Start tracking an exchange of `receipt_id`.
+    pub fn new(receipt_id: impl Into<String>) -> Self {
+        Self {
+            receipt_id: receipt_id.into(),
+            state: FlowState::Idle,
+            attempts: 0,
+        }
+    }
+
+    /// Current state.
+    pub fn state(&self) -> FlowState {
+        self.state
+    }
+
+    /// Sends of the current message so far.
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// Whether another retransmission is allowed under `config`.
+    pub fn can_retransmit(&self, config: &FlowConfig) -> bool {
+        self.attempts < config.max_attempts
+    }
+
+    /// Apply `event`, returning the new state.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InteractiveError::IllegalTransition`] if `event` cannot
+    /// happen in the current state; the state is left unchanged.
+    pub fn apply(&mut self, event: FlowEvent) -> Result<FlowState> {
+        use FlowEvent as E;
+        use FlowState as S;
+
+        let next = match (self.state, event) {
+            (S::Idle, E::SendRequest) => S::RequestSent,
+            (S::Idle, E::ReceiveRequest) => S::RequestReceived,
+            (S::RequestReceived, E::SendConfirm) => S::ConfirmSent,
+            // A confirmation arriving after we gave up still completes it
+            (S::RequestSent | S::TimedOut, E::ReceiveConfirm) => S::Completed,
+            (S::ConfirmSent, E::ReceiveAck) => S::Completed,
+            // The payer retransmitted before our confirmation arrived
+            (S::ConfirmSent, E::ReceiveRequest) => S::ConfirmSent,
+            // The payee retransmitted because our Ack was lost
+            (S::Completed, E::ReceiveConfirm) => S::Completed,
+            (S::RequestSent | S::ConfirmSent, E::Retransmit) => self.state,
+            (S::RequestSent | S::RequestReceived | S::ConfirmSent, E::Expire) => S::TimedOut,
+            (state, E::Fail) if !state.is_terminal() => S::Failed,
+            (from, event) => return Err(InteractiveError::IllegalTransition { from, event }),
+        };
+
+        match event {
+            E::SendRequest | E::SendConfirm => self.attempts = 1,
+            E::Retransmit => self.attempts += 1,
+            _ => {}
+        }
+        self.state = next;
+        Ok(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payer_and_payee_paths() {
+        let mut payer = ReceiptFlow::new("r1");
+        assert_eq!(
+            payer.apply(FlowEvent::SendRequest).unwrap(),
+            FlowState::RequestSent
+        );
+        payer.apply(FlowEvent::Retransmit).unwrap();
+        assert_eq!(payer.attempts(), 2);
+        assert_eq!(
+            payer.apply(FlowEvent::ReceiveConfirm).unwrap(),
+            FlowState::Completed
+        );
+        // A repeated confirmation is answered, not rejected
+        assert_eq!(
+            payer.apply(FlowEvent::ReceiveConfirm).unwrap(),
+            FlowState::Completed
+        );
+
+        let mut payee = ReceiptFlow::new("r1");
+        payee.apply(FlowEvent::ReceiveRequest).unwrap();
+        payee.apply(FlowEvent::SendConfirm).unwrap();
+        assert_eq!(
+            payee.apply(FlowEvent::ReceiveRequest).unwrap(),
+            FlowState::ConfirmSent
+        );
+        assert_eq!(
+            payee.apply(FlowEvent::ReceiveAck).unwrap(),
+            FlowState::Completed
+        );
+    }
+
+    #[test]
+    fn test_illegal_transitions() {
+        let mut flow = ReceiptFlow::new("r1");
+        let err = flow.apply(FlowEvent::ReceiveConfirm).unwrap_err();
+        assert!(matches!(
+            err,
+            InteractiveError::IllegalTransition {
+                from: FlowState::Idle,
+                event: FlowEvent::ReceiveConfirm
+            }
+        ));
+        assert_eq!(flow.state(), FlowState::Idle);
+
+        flow.apply(FlowEvent::SendRequest).unwrap();
+        flow.apply(FlowEvent::Expire).unwrap();
+        assert!(flow.apply(FlowEvent::Retransmit).is_err());
+        assert!(flow.apply(FlowEvent::Fail).is_err());
+    }
+
+    #[test]
+    fn test_retransmit_budget() {
+        let config = FlowConfig::default().with_max_attempts(2);
+        let mut flow = ReceiptFlow::new("r1");
+        flow.apply(FlowEvent::SendRequest).unwrap();
+        assert!(flow.can_retransmit(&config));
+        flow.apply(FlowEvent::Retransmit).unwrap();
+        assert!(!flow.can_retransmit(&config));
+    }
+}
@@ -0,0 +1,374 @@
+//! Asynchronous messages for peers that are offline.
+//!
+//! Noise needs both peers online. [`PeerInbox`] carries the same
+//! [`PaykitNoiseMessage`]s through the homeserver instead, so a payer can
+//! request a receipt, or a provider propose a subscription, while the other
+//! side is away:
+//!
+//! 1. The sender seals each message to the recipient's X25519 Noise key
+//!    (Sealed Blob v1) and writes it to the recipient's inbox directory,
+//!    `/pub/paykit.app/v0/inbox/{recipient_scope}/{message_id}`, on the
+//!    sender's own storage.
+//! 2. The recipient polls its contacts with [`PeerInbox::receive`] and,
+//!    once a message is handled, writes a read receipt to
+//!    `/pub/paykit.app/v0/inbox-receipts/{sender_scope}/{message_id}` on its
+//!    storage with [`PeerInbox::mark_read`].
+//! 3. The sender picks up receipts with [`PeerInbox::collect_receipts`],
+//!    which deletes the messages that were read. The recipient's next poll
+//!    then deletes receipts whose message is gone.
+//!
+//! Homeservers only let owners write, so each side deletes from its own
+//! storage. Only the storage owner can write to those directories, which is
+//! what authenticates the sender of a message and of a receipt.
+//!
+//! [`PaykitInteractiveManager::process_inbox`](crate::PaykitInteractiveManager::process_inbox)
+//! handles received messages and sends the replies back the same way.
+//!
+//! Recipients that publish a [`PushHintRecord`] can be woken rather than
+//! waiting for their next poll: after [`PeerInbox::send`], fetch the hint
+//! with [`fetch_push_hint`](paykit_lib::protocol::fetch_push_hint) and POST
+//! [`PushHintRecord::wake_up`] to its relay. [`seal_push_registration`]
+//! builds the hint's token and [`open_push_registration`] is the relay side.
+
+use crate::{InteractiveError, PaykitNoiseMessage, Result};
+use paykit_lib::protocol::{
+    inbox_dir, inbox_message_aad, inbox_message_path, inbox_receipt_path, inbox_receipts_dir,
+    push_token_aad, PushHintRecord, PushRegistration, WakeUpRequest, PURPOSE_INBOX,
+    PURPOSE_PUSH_TOKEN,
+};
+use paykit_lib::{AuthenticatedTransport, PublicKey, UnauthenticatedTransportRead};
+use pubky_noise::sealed_blob::{is_sealed_blob, sealed_blob_decrypt, sealed_blob_encrypt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// A message read from a peer's storage.
+#[derive(Clone, Debug)]
+pub struct InboxMessage {
+    /// ID to pass to [`PeerInbox::mark_read`].
+    pub message_id: String,
+    /// The peer whose storage held the message.
+    pub sender: PublicKey,
+    /// When the sender wrote it (unix seconds).
+    pub sent_at: i64,
+    /// The message itself.
+    pub message: PaykitNoiseMessage,
+}
+
+/// A recipient's confirmation that it read a message.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadReceipt {
+    /// The message that was read.
+    pub message_id: String,
+    /// When it was read (unix seconds).
+    pub read_at: i64,
+}
+
+/// What gets sealed: the message with its ID, so it cannot be moved to
+/// another ID in the same inbox.
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    message_id: String,
+    sent_at: i64,
+    message: PaykitNoiseMessage,
+}
+
+/// One identity's view of the inboxes it shares with its peers.
+pub struct PeerInbox {
+    owner: PublicKey,
+    owner_z32: String,
+    noise_sk: [u8; 32],
+}
+
+impl PeerInbox {
+    /// Create an inbox for `owner`, who decrypts with its Noise secret key.
+    pub fn new(owner: PublicKey, noise_sk: [u8; 32]) -> Self {
+        Self {
+            owner_z32: owner.to_string(),
+            owner,
+            noise_sk,
+        }
+    }
+
+    /// Seal `message` to `recipient` and write it to its inbox on our storage.
+    ///
+    /// Returns the message ID, which the recipient's read receipt refers to.
+    pub async fn send<T>(
+        &self,
+        transport: &T,
+        recipient: &PublicKey,
+        recipient_noise_pk: &[u8; 32],
+        message: PaykitNoiseMessage,
+    ) -> Result<String>
+    where
+        T: AuthenticatedTransport + Sync,
+    {
+        let envelope = Envelope {
+            message_id: hex::encode(rand::random::<[u8; 16]>()),
+            sent_at: crate::chrono_now(),
+            message,
+        };
+        let recipient_z32 = recipient.to_string();
+        let path =
+            inbox_message_path(&recipient_z32, &envelope.message_id).map_err(protocol_error)?;
+        let aad = inbox_message_aad(&self.owner_z32, &recipient_z32, &envelope.message_id)
+            .map_err(protocol_error)?;
+
+        let plaintext = serde_json::to_vec(&envelope)?;
+        let sealed = sealed_blob_encrypt(recipient_noise_pk, &plaintext, &aad, Some(PURPOSE_INBOX))
+            .map_err(|e| InteractiveError::Protocol(format!("Seal inbox message: {}", e)))?;
+
+        transport
+            .put(&path, &sealed)
+            .await
+            .map_err(|e| InteractiveError::Transport(e.to_string()))?;
+        Ok(envelope.message_id)
+    }
+
+    /// Read the unread messages `sender` has left for us, oldest first.
+    ///
+    /// Messages that are not sealed to our key are skipped. Receipts for
+    /// messages the sender has already deleted are cleaned up on the way.
+    pub async fn receive<T, R>(
+        &self,
+        transport: &T,
+        reader: &R,
+        sender: &PublicKey,
+    ) -> Result<Vec<InboxMessage>>
+    where
+        T: AuthenticatedTransport + Sync,
+        R: UnauthenticatedTransportRead + Sync,
+    {
+        let sender_z32 = sender.to_string();
+        let inbox = inbox_dir(&self.owner_z32).map_err(protocol_error)?;
+        let receipts = inbox_receipts_dir(&sender_z32).map_err(protocol_error)?;
+        let message_ids = list_or_empty(reader, sender, &inbox).await;
+        let receipted = list_or_empty(reader, &self.owner, &receipts).await;
+
+        let pending: HashSet<&String> = message_ids.iter().collect();
+        for message_id in receipted.iter().filter(|id| !pending.contains(id)) {
+            let path = inbox_receipt_path(&sender_z32, message_id).map_err(protocol_error)?;
+            transport
+                .delete(&path)
+                .await
+                .map_err(|e| InteractiveError::Transport(e.to_string()))?;
+        }
+
+        let read: HashSet<&String> = receipted.iter().collect();
+        let mut messages = Vec::new();
+        for message_id in message_ids.iter().filter(|id| !read.contains(id)) {
+            let Ok(path) = inbox_message_path(&self.owner_z32, message_id) else {
+                continue;
+            };
+            let Some(content) = reader
+                .get(sender, &path)
+                .await
+                .map_err(|e| InteractiveError::Transport(e.to_string()))?
+            else {
+                continue;
+            };
+            if let Some(envelope) = self.open(&sender_z32, message_id, &content) {
+                messages.push(InboxMessage {
+                    message_id: envelope.message_id,
+                    sender: sender.clone(),
+                    sent_at: envelope.sent_at,
+                    message: envelope.message,
+                });
+            }
+        }
+        messages.sort_by_key(|m| m.sent_at);
+        Ok(messages)
+    }
+
+    /// Tell `sender` we have read `message_id`, so it can delete the message.
+    pub async fn mark_read<T>(
+        &self,
+        transport: &T,
+        sender: &PublicKey,
+        message_id: &str,
+    ) -> Result<()>
+    where
+        T: AuthenticatedTransport + Sync,
+    {
+        let path = inbox_receipt_path(&sender.to_string(), message_id).map_err(protocol_error)?;
+        let receipt = ReadReceipt {
+            message_id: message_id.to_string(),
+            read_at: crate::chrono_now(),
+        };
+        transport
+            .put(&path, &serde_json::to_string(&receipt)?)
+            .await
+            .map_err(|e| InteractiveError::Transport(e.to_string()))
+    }
+
+    /// Collect `recipient`'s read receipts and delete the messages they cover.
+    ///
+    /// Each receipt is returned once: the first time it is seen, while the
+    /// message still exists.
+    pub async fn collect_receipts<T, R>(
+        &self,
+        transport: &T,
+        reader: &R,
+        recipient: &PublicKey,
+    ) -> Result<Vec<ReadReceipt>>
+    where
+        T: AuthenticatedTransport + Sync,
+        R: UnauthenticatedTransportRead + Sync,
+    {
+        let recipient_z32 = recipient.to_string();
+        let receipts = inbox_receipts_dir(&self.owner_z32).map_err(protocol_error)?;
+        let receipted = list_or_empty(reader, recipient, &receipts).await;
+
+        let mut receipts = Vec::new();
+        for message_id in receipted {
+            let Ok(message_path) = inbox_message_path(&recipient_z32, &message_id) else {
+                continue;
+            };
+            let exists = transport
+                .get(&message_path)
+                .await
+                .map_err(|e| InteractiveError::Transport(e.to_string()))?
+                .is_some();
+            if !exists {
+                continue;
+            }
+
+            let receipt_path =
+                inbox_receipt_path(&self.owner_z32, &message_id).map_err(protocol_error)?;
+            let receipt = reader
+                .get(recipient, &receipt_path)
+                .await
+                .map_err(|e| InteractiveError::Transport(e.to_string()))?
+                .and_then(|content| serde_json::from_str::<ReadReceipt>(&content).ok())
+                .filter(|receipt| receipt.message_id == message_id);
+            let Some(receipt) = receipt else {
+                continue;
+            };
+
+            transport
+                .delete(&message_path)
+                .await
+                .map_err(|e| InteractiveError::Transport(e.to_string()))?;
+            receipts.push(receipt);
+        }
+        Ok(receipts)
+    }
+
+    /// Decrypt a message, binding it to its sender and ID.
+    fn open(&self, sender_z32: &str, message_id: &str, content: &str) -> Option<Envelope> {
+        if !is_sealed_blob(content) {
+            return None;
+        }
+        let aad = inbox_message_aad(sender_z32, &self.owner_z32, message_id).ok()?;
+        let plaintext = sealed_blob_decrypt(&self.noise_sk, content, &aad).ok()?;
+        let envelope: Envelope = serde_json::from_slice(&plaintext).ok()?;
+        (envelope.message_id == message_id).then_some(envelope)
+    }
+}
+
+/// Seal `registration` to a notification relay for `owner`'s push hint.
+///
+/// The result goes in [`PushHintRecord::sealed_token`].
+pub fn seal_push_registration(
+    owner: &PublicKey,
+    relay_pk: &[u8; 32],
+    registration: &PushRegistration,
+) -> Result<String> {
+    let aad = push_token_aad(&owner.to_string());
+    let plaintext = serde_json::to_vec(registration)?;
+    sealed_blob_encrypt(relay_pk, &plaintext, &aad, Some(PURPOSE_PUSH_TOKEN))
+        .map_err(|e| InteractiveError::Protocol(format!("Seal push token: {}", e)))
+}
+
+/// Relay side: open the push registration in a wake-up request.
+pub fn open_push_registration(
+    relay_sk: &[u8; 32],
+    request: &WakeUpRequest,
+) -> Result<PushRegistration> {
+    let aad = push_token_aad(&request.recipient);
+    let plaintext = sealed_blob_decrypt(relay_sk, &request.sealed_token, &aad)
+        .map_err(|e| InteractiveError::Protocol(format!("Open push token: {}", e)))?;
+    Ok(serde_json::from_slice(&plaintext)?)
+}
+
+/// List a directory, treating a failed listing as empty: the directory does
+/// not exist until the first entry is written.
+async fn list_or_empty<R>(reader: &R, owner: &PublicKey, dir: &str) -> Vec<String>
+where
+    R: UnauthenticatedTransportRead + Sync,
+{
+    reader.list_directory(owner, dir).await.unwrap_or_default()
+}
+
+fn protocol_error(e: paykit_lib::PaykitError) -> InteractiveError {
+    InteractiveError::Protocol(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn pubkey() -> PublicKey {
+        let keypair = pubky::Keypair::random();
+        PublicKey::from_str(&keypair.public_key().to_z32()).unwrap()
+    }
+
+    #[test]
+    fn test_open_binds_sender_and_id() {
+        let alice = pubkey();
+        let bob = pubkey();
+        let bob_sk = [9u8; 32];
+        let bob_pk = pubky_noise::kdf::x25519_pk_from_sk(&bob_sk);
+        let inbox = PeerInbox::new(bob.clone(), bob_sk);
+
+        let envelope = Envelope {
+            message_id: "msg-1".into(),
+            sent_at: 1_700_000_000,
+            message: PaykitNoiseMessage::Ack,
+        };
+        let aad = inbox_message_aad(&alice.to_string(), &bob.to_string(), "msg-1").unwrap();
+        let sealed = sealed_blob_encrypt(
+            &bob_pk,
+            &serde_json::to_vec(&envelope).unwrap(),
+            &aad,
+            Some(PURPOSE_INBOX),
+        )
+        .unwrap();
+
+        let opened = inbox.open(&alice.to_string(), "msg-1", &sealed).unwrap();
+        assert_eq!(opened.sent_at, 1_700_000_000);
+        // Copied to another sender's storage, or to another ID
+        assert!(inbox.open(&bob.to_string(), "msg-1", &sealed).is_none());
+        assert!(inbox.open(&alice.to_string(), "msg-2", &sealed).is_none());
+        assert!(inbox.open(&alice.to_string(), "msg-1", "{}").is_none());
+    }
+
+    #[test]
+    fn test_push_registration_roundtrip() {
+        use paykit_lib::protocol::PushPlatform;
+
+        let alice = pubkey();
+        let relay_sk = [5u8; 32];
+        let relay_pk = pubky_noise::kdf::x25519_pk_from_sk(&relay_sk);
+        let registration = PushRegistration {
+            platform: PushPlatform::Fcm,
+            token: "fcm-token".into(),
+            app_id: Some("app.paykit".into()),
+        };
+        let hint = PushHintRecord {
+            relay_url: "https://push.example.com/wake".into(),
+            relay_pubkey: hex::encode(relay_pk),
+            sealed_token: seal_push_registration(&alice, &relay_pk, &registration).unwrap(),
+            updated_at: 1_700_000_000,
+        };
+
+        let request = hint.wake_up(&alice);
+        assert_eq!(
+            open_push_registration(&relay_sk, &request).unwrap(),
+            registration
+        );
+        // Republished under someone else's hint
+        let stolen = hint.wake_up(&pubkey());
+        assert!(open_push_registration(&relay_sk, &stolen).is_err());
+    }
+}
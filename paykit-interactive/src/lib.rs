@@ -3,8 +3,10 @@
 //! This crate implements the interactive payment flows and receipt exchange for Paykit,
 //! designed to run over encrypted channels (like Pubky Noise).
 
+use paykit_lib::protocol::{add_attachment_to_metadata, attachments_from_metadata, AttachmentRef};
 use paykit_lib::{MethodId, PublicKey};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 /// A cryptographic receipt shared between two peers for a payment.
 ///
@@ -55,6 +57,64 @@ impl PaykitReceipt {
             metadata,
         }
     }
+
+    /// Attach the payer's note, sanitized with [`sanitize_memo`].
+    ///
+    /// The note is stored under `memo` in the metadata and travels to the
+    /// payee with the `RequestReceipt` message.
+    pub fn with_memo(mut self, memo: impl AsRef<str>) -> Self {
+        metadata::set_memo_in_metadata(&mut self.metadata, Some(memo.as_ref()));
+        self
+    }
+
+    /// The payer's note, if any, sanitized for display.
+    pub fn memo(&self) -> Option<String> {
+        metadata::memo_from_metadata(&self.metadata)
+    }
+
+    /// Reference an attachment (an invoice PDF, a photo) from this receipt.
+    ///
+    /// Only the reference lives in the metadata; stored attachments must be
+    /// published with [`paykit_lib::protocol::attach`] first.
+    pub fn add_attachment(&mut self, attachment: AttachmentRef) -> paykit_lib::Result<()> {
+        add_attachment_to_metadata(&mut self.metadata, attachment)
+    }
+
+    /// Attachments referenced by this receipt.
+    pub fn attachments(&self) -> Vec<AttachmentRef> {
+        attachments_from_metadata(&self.metadata)
+    }
+
+    /// The canonical JSON form that [`digest`](Self::digest) commits to.
+    ///
+    /// Keys are sorted at every level, keys are z-base-32 strings, absent
+    /// optional fields are omitted and there is no whitespace, so any
+    /// implementation can reproduce it byte for byte.
+    pub fn canonical_json(&self) -> String {
+        let mut doc = serde_json::Map::new();
+        doc.insert("receipt_id".into(), self.receipt_id.clone().into());
+        doc.insert("payer".into(), self.payer.to_z32().into());
+        doc.insert("payee".into(), self.payee.to_z32().into());
+        doc.insert("method_id".into(), self.method_id.0.clone().into());
+        if let Some(amount) = &self.amount {
+            doc.insert("amount".into(), amount.clone().into());
+        }
+        if let Some(currency) = &self.currency {
+            doc.insert("currency".into(), currency.clone().into());
+        }
+        doc.insert("created_at".into(), self.created_at.into());
+        doc.insert("metadata".into(), self.metadata.clone());
+        // serde_json maps are ordered by key
+        serde_json::Value::Object(doc).to_string()
+    }
+
+    /// Hex SHA-256 of [`canonical_json`](Self::canonical_json).
+    ///
+    /// Both peers compute the same digest for the same receipt, so it can
+    /// be signed, pinned in an audit log or compared across devices.
+    pub fn digest(&self) -> String {
+        hex::encode(Sha256::digest(self.canonical_json().as_bytes()))
+    }
 }
 
 fn chrono_now() -> i64 {
@@ -92,6 +152,66 @@ pub enum PaykitNoiseMessage {
     Ack,
     /// Error reporting.
     Error { code: String, message: String },
+    /// Ask a co-signer to approve or reject a payment held for approval.
+    RequestApproval {
+        pending: paykit_lib::approvals::PendingApproval,
+    },
+    /// A co-signer's signed decision on a held payment.
+    SubmitApproval {
+        approval: paykit_lib::approvals::SignedApproval,
+    },
+    /// A provider's signed subscription status notice.
+    ///
+    /// Carried as JSON because the notice type lives in `paykit-subscriptions`;
+    /// the receiver must verify it before acting on it.
+    SubscriptionStatus { notice: serde_json::Value },
+    /// A provider's subscription proposal.
+    ///
+    /// Carried as JSON like `SubscriptionStatus`, typically through a
+    /// [`inbox::PeerInbox`] while the subscriber is offline; the subscription
+    /// layer records it for the subscriber to accept or decline.
+    ProposeSubscription { proposal: serde_json::Value },
+    /// A party's signed request to cancel a subscription.
+    ///
+    /// Carried as JSON like `SubscriptionStatus`; the counterparty answers
+    /// with `CancelSubscriptionAck` once it has verified and recorded it.
+    CancelSubscription { cancellation: serde_json::Value },
+    /// The counterparty's signed acknowledgment of a cancellation.
+    CancelSubscriptionAck { ack: serde_json::Value },
+    /// Proof of identity after an unauthenticated (NN) handshake.
+    ///
+    /// `signature` is over the channel's handshake hash; verify it with
+    /// [`attestation::verify_attestation_hex`] before trusting `ed25519_pk`.
+    Attestation {
+        /// Sender's Ed25519 identity key, hex encoded.
+        ed25519_pk: String,
+        /// Signature from [`attestation::sign_attestation`], hex encoded.
+        signature: String,
+    },
+    /// Ask another device of the same identity for the sync records that
+    /// `clock` has not seen.
+    SyncRequest {
+        device_id: String,
+        clock: sync::VectorClock,
+    },
+    /// Sync records from another device of the same identity, with the
+    /// sender's clock so the receiver can send back what it is missing.
+    SyncChanges {
+        device_id: String,
+        clock: sync::VectorClock,
+        records: Vec<sync::SyncRecord>,
+    },
+    /// Open a session by advertising the sender's protocol version and
+    /// features; see [`negotiation`].
+    Hello {
+        version: u32,
+        features: negotiation::Features,
+    },
+    /// The responder's version and features, answering `Hello`.
+    HelloAck {
+        version: u32,
+        features: negotiation::Features,
+    },
 }
 
 /// Private endpoint offer with optional expiration.
@@ -116,28 +236,62 @@ pub trait PaykitNoiseChannel {
     async fn recv(&mut self) -> Result<PaykitNoiseMessage>;
 }
 
+pub mod attestation;
+pub mod conformance;
 pub mod connection_limit;
+#[cfg(feature = "tcp-transport")]
+pub mod dial;
+pub mod eviction;
+pub mod export;
+pub mod flow;
+pub mod inbox;
 pub mod manager;
 pub mod metadata;
 pub mod metrics;
+pub mod negotiation;
+pub mod ots;
+pub mod peer_limit;
+pub mod privacy;
 pub mod proof;
+pub mod proximity;
+pub mod query;
 pub mod rate_limit;
+pub mod rules;
+#[cfg(feature = "sessions")]
+pub mod session;
 pub mod status;
 pub mod storage;
+pub mod sync;
+pub mod tenant;
 pub mod transport;
 
-pub use manager::{PaykitInteractiveManager, ReceiptGenerator};
+pub use export::{
+    AccountMapping, AccountingDialect, AccountingExport, AccountingExporter, ExportableReceipt,
+    FixedRates, RateProvider,
+};
+pub use flow::{FlowConfig, FlowEvent, FlowState, ReceiptFlow};
+pub use inbox::{InboxMessage, PeerInbox, ReadReceipt};
+pub use manager::{ApprovalHandler, PaykitInteractiveManager, ReceiptGenerator};
+pub use metadata::tax::{TaxCalculator, TaxContext, TaxRateRule, TaxRulesTable};
 pub use metadata::{
-    MetadataItem, MetadataValidator, OrderMetadata, PaymentMetadata, ShippingMetadata, TaxMetadata,
+    sanitize_memo, MetadataItem, MetadataValidator, OrderMetadata, PaymentMetadata,
+    ShippingMetadata, TaxMetadata,
 };
+pub use negotiation::{Features, Negotiated};
 pub use proof::{
     PaymentProof, ProofType, ProofVerifier, ProofVerifierRegistry, VerificationResult,
 };
-pub use status::{PaymentStatus, PaymentStatusInfo, PaymentStatusTracker};
+pub use proximity::{LoopbackTransport, ProximityNoiseChannel, ProximityTransport};
+pub use query::{DateRange, ReceiptPage, ReceiptQuery, SearchableReceipt};
+pub use rules::{InboundRules, RuleAction, RuleDecision, RulesEngine};
+pub use status::{
+    ConfirmationPolicy, ConfirmationTier, PaymentStatus, PaymentStatusInfo, PaymentStatusTracker,
+};
 pub use storage::{
     smart_checkout, smart_checkout_all_methods, smart_checkout_detailed, CheckoutResult,
     PaykitStorage, StorageAdapter,
 };
+pub use tenant::{Tenant, TenantRouter};
 
 /// Result type for interactive operations.
 pub type Result<T> = std::result::Result<T, InteractiveError>;
@@ -152,6 +306,22 @@ pub enum InteractiveError {
     Unimplemented,
     #[error("serialization error: {0}")]
     Serialization(String),
+    /// The server accepts none of the static keys tried; refetch its Noise
+    /// endpoint record and retry.
+    #[error("noise key mismatch: server static key has rotated")]
+    KeyMismatch,
+    /// The peer did not answer before the last deadline.
+    #[error("timed out: {0}")]
+    Timeout(String),
+    /// A session, queue or memory limit was reached.
+    #[error("limit exceeded: {0}")]
+    LimitExceeded(String),
+    /// A message arrived that the receipt flow cannot accept in its state.
+    #[error("illegal transition: {event:?} in state {from:?}")]
+    IllegalTransition {
+        from: flow::FlowState,
+        event: flow::FlowEvent,
+    },
 }
 
 impl From<serde_json::Error> for InteractiveError {
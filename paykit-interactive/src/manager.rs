@@ -1,7 +1,19 @@
+use crate::flow::{FlowConfig, FlowEvent, FlowState, ReceiptFlow};
+use crate::inbox::{InboxMessage, PeerInbox};
+use crate::negotiation::{self, Features, Hello, Negotiated, VERSION_UNSUPPORTED};
+use crate::peer_limit::{PeerRateLimiter, PeerVerdict};
+use crate::rules::{PeerTrust, RuleAction, RulesEngine};
+use crate::status::PaymentStatusTracker;
+use crate::sync::{self, MergeReport, SharedSyncState, SyncRecord};
 use crate::{
-    InteractiveError, PaykitNoiseChannel, PaykitNoiseMessage, PaykitReceipt, PaykitStorage, Result,
+    chrono_now, InteractiveError, PaykitNoiseChannel, PaykitNoiseMessage, PaykitReceipt,
+    PaykitStorage, Result,
 };
-use paykit_lib::{MethodId, PublicKey};
+use parking_lot::Mutex;
+use paykit_lib::approvals::{PendingApproval, SharedApprovalQueue, SignedApproval};
+use paykit_lib::policy::SharedTrustPolicy;
+use paykit_lib::{AuthenticatedTransport, MethodId, PublicKey, UnauthenticatedTransportRead};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 /// Trait for generating/finalizing receipts (e.g. creating Lightning invoices).
@@ -16,10 +28,36 @@ pub trait ReceiptGenerator: Send + Sync {
     async fn generate_receipt(&self, request: &PaykitReceipt) -> Result<PaykitReceipt>;
 }
 
+/// Trait for co-signers reviewing payments held for approval.
+#[async_trait::async_trait]
+pub trait ApprovalHandler: Send + Sync {
+    /// Review a payment that `requester` wants approved.
+    ///
+    /// Return a signed approval or rejection, or `None` to defer (for
+    /// example while waiting for the user to decide).
+    async fn review(
+        &self,
+        pending: &PendingApproval,
+        requester: &PublicKey,
+    ) -> Result<Option<SignedApproval>>;
+}
+
 /// Manages interactive Paykit flows over a secure channel.
 pub struct PaykitInteractiveManager {
     storage: Arc<Box<dyn PaykitStorage>>,
     generator: Arc<Box<dyn ReceiptGenerator>>,
+    trust_policy: Option<SharedTrustPolicy>,
+    approval_queue: Option<SharedApprovalQueue>,
+    approval_handler: Option<Arc<dyn ApprovalHandler>>,
+    device_sync: Option<SharedSyncState>,
+    inbound_rules: Option<Arc<RulesEngine>>,
+    peer_limits: Option<Arc<PeerRateLimiter>>,
+    status_tracker: Option<Arc<PaymentStatusTracker>>,
+    flow_config: FlowConfig,
+    flows: Arc<Mutex<HashMap<String, ReceiptFlow>>>,
+    advertised_features: Option<Features>,
+    min_protocol_version: u32,
+    negotiated: Arc<Mutex<HashMap<String, Negotiated>>>,
 }
 
 impl PaykitInteractiveManager {
@@ -27,7 +65,459 @@ impl PaykitInteractiveManager {
         storage: Arc<Box<dyn PaykitStorage>>,
         generator: Arc<Box<dyn ReceiptGenerator>>,
     ) -> Self {
-        Self { storage, generator }
+        Self {
+            storage,
+            generator,
+            trust_policy: None,
+            approval_queue: None,
+            approval_handler: None,
+            device_sync: None,
+            inbound_rules: None,
+            peer_limits: None,
+            status_tracker: None,
+            flow_config: FlowConfig::default(),
+            flows: Arc::new(Mutex::new(HashMap::new())),
+            advertised_features: None,
+            min_protocol_version: negotiation::LEGACY_PROTOCOL_VERSION,
+            negotiated: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Set the deadlines and retransmission limits of receipt exchanges.
+    pub fn with_flow_config(mut self, config: FlowConfig) -> Self {
+        self.flow_config = config;
+        self
+    }
+
+    /// State of the receipt exchange for `receipt_id`, for progress UIs.
+    ///
+    /// `None` if this manager has not seen the receipt.
+    pub fn flow_state(&self, receipt_id: &str) -> Option<FlowState> {
+        self.flows.lock().get(receipt_id).map(ReceiptFlow::state)
+    }
+
+    /// Stop tracking exchanges that are over.
+    ///
+    /// Long-running managers should call this once finished states have
+    /// been shown, so the flow table does not grow without bound.
+    pub fn clear_finished_flows(&self) {
+        self.flows
+            .lock()
+            .retain(|_, flow| !flow.state().is_terminal());
+    }
+
+    /// Apply `event` to the flow for `receipt_id`.
+    fn advance(&self, receipt_id: &str, event: FlowEvent) -> Result<FlowState> {
+        self.flows
+            .lock()
+            .entry(receipt_id.to_string())
+            .or_insert_with(|| ReceiptFlow::new(receipt_id))
+            .apply(event)
+    }
+
+    /// Track a new exchange of `receipt_id`, replacing any earlier one.
+    fn start_flow(&self, receipt_id: &str) {
+        self.flows
+            .lock()
+            .insert(receipt_id.to_string(), ReceiptFlow::new(receipt_id));
+    }
+
+    fn can_retransmit(&self, receipt_id: &str) -> bool {
+        self.flows
+            .lock()
+            .get(receipt_id)
+            .is_some_and(|flow| flow.can_retransmit(&self.flow_config))
+    }
+
+    /// Wait for the next message until the exchange deadline.
+    ///
+    /// `Ok(None)` means the deadline passed.
+    async fn recv_before_deadline<C: PaykitNoiseChannel>(
+        &self,
+        channel: &mut C,
+    ) -> Result<Option<PaykitNoiseMessage>> {
+        #[cfg(feature = "timeout")]
+        let msg =
+            match tokio::time::timeout(self.flow_config.exchange_timeout, channel.recv()).await {
+                Ok(msg) => Some(msg?),
+                Err(_) => None,
+            };
+
+        #[cfg(not(feature = "timeout"))]
+        let msg = Some(channel.recv().await?);
+
+        Ok(msg)
+    }
+
+    /// Refuse outgoing payments to payees blocked by the trust policy.
+    ///
+    /// Confirmation for unknown payees is left to the caller, which should
+    /// check [`TrustPolicy::evaluate`](paykit_lib::policy::TrustPolicy::evaluate)
+    /// before initiating the payment.
+    pub fn with_trust_policy(mut self, policy: SharedTrustPolicy) -> Self {
+        self.trust_policy = Some(policy);
+        self
+    }
+
+    /// Apply signed approvals received from co-signers to this queue.
+    pub fn with_approval_queue(mut self, queue: SharedApprovalQueue) -> Self {
+        self.approval_queue = Some(queue);
+        self
+    }
+
+    /// Answer approval requests from peers as a co-signer.
+    pub fn with_approval_handler(mut self, handler: Arc<dyn ApprovalHandler>) -> Self {
+        self.approval_handler = Some(handler);
+        self
+    }
+
+    /// Decide receipt requests with `engine` before generating receipts.
+    ///
+    /// Requests the rules queue are answered with a `QUEUED_FOR_REVIEW`
+    /// error and released with
+    /// [`approve_queued_request`](Self::approve_queued_request); rejected
+    /// ones get `REQUEST_REJECTED`. The trust level the rules see comes from
+    /// the [trust policy](Self::with_trust_policy), if one is set.
+    pub fn with_inbound_rules(mut self, engine: Arc<RulesEngine>) -> Self {
+        self.inbound_rules = Some(engine);
+        self
+    }
+
+    /// Limit how many messages each peer may send us.
+    ///
+    /// [`handle_message`](Self::handle_message) answers messages over the
+    /// limit with a `RATE_LIMITED` or `GREYLISTED` error instead of handling
+    /// them, and [`process_inbox`](Self::process_inbox) leaves them unread
+    /// for a later poll. Servers should share the limiter with their accept
+    /// loop to cap concurrent handshakes and drop greylisted peers.
+    pub fn with_peer_limits(mut self, limiter: Arc<PeerRateLimiter>) -> Self {
+        self.peer_limits = Some(limiter);
+        self
+    }
+
+    /// Track the status of every receipt confirmed with a peer.
+    ///
+    /// The confirmations each payment needs are fixed from the tracker's
+    /// [confirmation policy](PaymentStatusTracker::confirmation_policy) when
+    /// the receipt is confirmed; the peer counts as trusted if the
+    /// [trust policy](Self::with_trust_policy) trusts it. Report what the
+    /// payment watcher sees with
+    /// [`update_confirmations`](PaymentStatusTracker::update_confirmations).
+    pub fn with_status_tracker(mut self, tracker: Arc<PaymentStatusTracker>) -> Self {
+        self.status_tracker = Some(tracker);
+        self
+    }
+
+    /// Start tracking a confirmed receipt exchanged with `peer`.
+    ///
+    /// A receipt already tracked, for example one confirmed again after a
+    /// lost `Ack`, keeps its status.
+    fn track_receipt(&self, receipt: &PaykitReceipt, peer: &PublicKey) -> Result<()> {
+        let Some(tracker) = &self.status_tracker else {
+            return Ok(());
+        };
+        if tracker.get(&receipt.receipt_id).is_none() {
+            let trusted = self.peer_trust(peer)? == PeerTrust::Trusted;
+            tracker.track_from_peer(receipt, trusted);
+        }
+        Ok(())
+    }
+
+    /// How the trust policy, if any, classifies `peer`.
+    fn peer_trust(&self, peer: &PublicKey) -> Result<PeerTrust> {
+        Ok(match &self.trust_policy {
+            Some(policy) => {
+                let policy = policy
+                    .read()
+                    .map_err(|_| InteractiveError::Protocol("Trust policy lock poisoned".into()))?;
+                PeerTrust::from_policy(&policy, peer)
+            }
+            None => PeerTrust::Unknown,
+        })
+    }
+
+    /// The refusal to send if `peer` is over its message limit.
+    fn check_peer_limit(&self, peer: &PublicKey) -> Option<PaykitNoiseMessage> {
+        let limiter = self.peer_limits.as_ref()?;
+        let verdict = limiter.check_message(&peer.to_string());
+        let code = match verdict {
+            PeerVerdict::Allow => return None,
+            PeerVerdict::RateLimited { .. } => "RATE_LIMITED",
+            PeerVerdict::Greylisted { .. } => "GREYLISTED",
+        };
+        Some(PaykitNoiseMessage::Error {
+            code: code.into(),
+            message: format!(
+                "Too many messages; retry after {}s",
+                verdict.retry_after().as_secs().max(1)
+            ),
+        })
+    }
+
+    /// Issue the receipt for a request the inbound rules queued.
+    ///
+    /// Records an accept override, then generates and saves the receipt.
+    /// Deliver it with a `ConfirmReceipt` message (through the
+    /// [inbox](crate::inbox) if the payer is offline); a payer retrying the
+    /// request also gets it. Reject a queued request with
+    /// [`RulesEngine::override_decision`].
+    ///
+    /// Returns `None` if no request with this ID is queued.
+    pub async fn approve_queued_request(&self, receipt_id: &str) -> Result<Option<PaykitReceipt>> {
+        let engine = self
+            .inbound_rules
+            .as_ref()
+            .ok_or_else(|| InteractiveError::Protocol("Inbound rules not configured".into()))?;
+        if engine.queued_request(receipt_id).is_none() {
+            return Ok(None);
+        }
+        let Some(queued) = engine.override_decision(receipt_id, RuleAction::Accept, chrono_now())
+        else {
+            return Ok(None);
+        };
+
+        self.start_flow(receipt_id);
+        self.advance(receipt_id, FlowEvent::ReceiveRequest)?;
+        self.issue_receipt(queued.request).await.map(Some)
+    }
+
+    /// The refusal to send if the inbound rules do not accept `request`.
+    fn check_inbound_rules(
+        &self,
+        request: &PaykitReceipt,
+        peer: &PublicKey,
+    ) -> Result<Option<PaykitNoiseMessage>> {
+        let Some(engine) = &self.inbound_rules else {
+            return Ok(None);
+        };
+        let trust = self.peer_trust(peer)?;
+        let decision = engine.decide(request, peer, trust, chrono_now());
+        Ok(match decision.action {
+            RuleAction::Accept => None,
+            RuleAction::Queue => Some(PaykitNoiseMessage::Error {
+                code: "QUEUED_FOR_REVIEW".into(),
+                message: "Receipt request is held for review; retry later".into(),
+            }),
+            RuleAction::Reject => Some(PaykitNoiseMessage::Error {
+                code: "REQUEST_REJECTED".into(),
+                message: "Receipt request refused by the payee".into(),
+            }),
+        })
+    }
+
+    /// Generate, save and track the receipt for an accepted request.
+    async fn issue_receipt(&self, mut provisional_receipt: PaykitReceipt) -> Result<PaykitReceipt> {
+        let receipt_id = provisional_receipt.receipt_id.clone();
+
+        // Never store the payer's note as sent
+        let memo = provisional_receipt.memo();
+        crate::metadata::set_memo_in_metadata(&mut provisional_receipt.metadata, memo.as_deref());
+
+        let confirmed_receipt = match self.generator.generate_receipt(&provisional_receipt).await {
+            Ok(receipt) => receipt,
+            Err(e) => {
+                self.advance(&receipt_id, FlowEvent::Fail)?;
+                return Err(e);
+            }
+        };
+
+        self.storage.save_receipt(&confirmed_receipt).await?;
+        self.track_receipt(&confirmed_receipt, &confirmed_receipt.payer)?;
+        self.advance(&receipt_id, FlowEvent::SendConfirm)?;
+        Ok(confirmed_receipt)
+    }
+
+    /// Sync receipts and private endpoints with the identity's other devices.
+    ///
+    /// Sync requests are only answered for peers with our own identity key.
+    pub fn with_device_sync(mut self, state: SharedSyncState) -> Self {
+        self.device_sync = Some(state);
+        self
+    }
+
+    /// Exchange sync records with another device of the same identity.
+    ///
+    /// `channel` must be connected to one of our own devices; the other side
+    /// answers only if its peer key is its own. Records received are merged
+    /// and written to storage.
+    pub async fn sync_with_device<C: PaykitNoiseChannel>(
+        &self,
+        channel: &mut C,
+    ) -> Result<MergeReport> {
+        let state = self.sync_state()?;
+        self.refresh_sync_state(state).await?;
+
+        let (device_id, clock) = {
+            let state = state.lock();
+            (state.device_id().to_string(), state.clock().clone())
+        };
+        channel
+            .send(PaykitNoiseMessage::SyncRequest {
+                device_id: device_id.clone(),
+                clock,
+            })
+            .await?;
+
+        let (their_clock, records) = match channel.recv().await? {
+            PaykitNoiseMessage::SyncChanges { clock, records, .. } => (clock, records),
+            PaykitNoiseMessage::Error { code, message } => {
+                return Err(InteractiveError::Protocol(format!(
+                    "Peer error {}: {}",
+                    code, message
+                )))
+            }
+            msg => {
+                return Err(InteractiveError::Protocol(format!(
+                    "Unexpected message: {:?}",
+                    msg
+                )))
+            }
+        };
+        let report = self.merge_sync_records(state, records).await?;
+
+        // Send back what the other device is missing
+        let (clock, records) = {
+            let state = state.lock();
+            (state.clock().clone(), state.changes_since(&their_clock))
+        };
+        channel
+            .send(PaykitNoiseMessage::SyncChanges {
+                device_id,
+                clock,
+                records,
+            })
+            .await?;
+        match channel.recv().await? {
+            PaykitNoiseMessage::Ack => Ok(report),
+            msg => Err(InteractiveError::Protocol(format!(
+                "Unexpected message: {:?}",
+                msg
+            ))),
+        }
+    }
+
+    fn sync_state(&self) -> Result<&SharedSyncState> {
+        self.device_sync
+            .as_ref()
+            .ok_or_else(|| InteractiveError::Protocol("Device sync not configured".into()))
+    }
+
+    /// Record changes the app made to storage since the last sync.
+    async fn refresh_sync_state(&self, state: &SharedSyncState) -> Result<()> {
+        let peers = state.lock().endpoint_peers();
+        let snapshot = sync::snapshot_storage(&**self.storage, &peers).await?;
+        state.lock().observe(&snapshot);
+        Ok(())
+    }
+
+    async fn merge_sync_records(
+        &self,
+        state: &SharedSyncState,
+        records: Vec<SyncRecord>,
+    ) -> Result<MergeReport> {
+        let report = state.lock().merge(records);
+        sync::apply_to_storage(&**self.storage, &report.applied).await?;
+        Ok(report)
+    }
+
+    /// Handle the messages `sender` left in our inbox while we were offline.
+    ///
+    /// Each message goes through [`handle_message`](Self::handle_message);
+    /// replies other than `Ack` are sealed to `sender_noise_pk` and left in
+    /// the sender's inbox, and the message is then marked read. A message
+    /// whose handling fails stays unread and is retried on the next call.
+    ///
+    /// With [peer limits](Self::with_peer_limits), a greylisted sender's
+    /// inbox is not read at all, and messages over the sender's limit stay
+    /// unread until a later call.
+    ///
+    /// Returns the messages handled.
+    pub async fn process_inbox<T, R>(
+        &self,
+        inbox: &PeerInbox,
+        transport: &T,
+        reader: &R,
+        sender: &PublicKey,
+        sender_noise_pk: &[u8; 32],
+        my_pubkey: &PublicKey,
+    ) -> Result<Vec<InboxMessage>>
+    where
+        T: AuthenticatedTransport + Sync,
+        R: UnauthenticatedTransportRead + Sync,
+    {
+        let sender_key = sender.to_string();
+        if let Some(limiter) = &self.peer_limits {
+            if limiter.is_greylisted(&sender_key) {
+                return Ok(Vec::new());
+            }
+        }
+
+        let mut handled = Vec::new();
+        for message in inbox.receive(transport, reader, sender).await? {
+            if let Some(limiter) = &self.peer_limits {
+                if !limiter.check_message(&sender_key).is_allowed() {
+                    break;
+                }
+            }
+            let reply = self
+                .dispatch_message(message.message.clone(), sender, my_pubkey)
+                .await?;
+            // The read receipt already tells the sender we got it
+            if let Some(reply) = reply.filter(|r| !matches!(r, PaykitNoiseMessage::Ack)) {
+                inbox
+                    .send(transport, sender, sender_noise_pk, reply)
+                    .await?;
+            }
+            inbox
+                .mark_read(transport, sender, &message.message_id)
+                .await?;
+            handled.push(message);
+        }
+        Ok(handled)
+    }
+
+    /// Ask a remote co-signer to approve a held payment.
+    ///
+    /// The returned decision is also applied to the approval queue, if one
+    /// is configured.
+    pub async fn request_approval<C: PaykitNoiseChannel>(
+        &self,
+        channel: &mut C,
+        pending: PendingApproval,
+    ) -> Result<SignedApproval> {
+        let approval_id = pending.approval_id.clone();
+        channel
+            .send(PaykitNoiseMessage::RequestApproval { pending })
+            .await?;
+
+        match channel.recv().await? {
+            PaykitNoiseMessage::SubmitApproval { approval } => {
+                if approval.approval_id != approval_id {
+                    return Err(InteractiveError::Protocol("Approval ID mismatch".into()));
+                }
+                self.apply_approval(&approval)?;
+                Ok(approval)
+            }
+            PaykitNoiseMessage::Error { code, message } => Err(InteractiveError::Protocol(
+                format!("Peer error {}: {}", code, message),
+            )),
+            msg => Err(InteractiveError::Protocol(format!(
+                "Unexpected message: {:?}",
+                msg
+            ))),
+        }
+    }
+
+    fn apply_approval(&self, approval: &SignedApproval) -> Result<()> {
+        if let Some(queue) = &self.approval_queue {
+            let mut queue = queue
+                .write()
+                .map_err(|_| InteractiveError::Protocol("Approval queue lock poisoned".into()))?;
+            queue
+                .apply(approval, chrono_now())
+                .map_err(|e| InteractiveError::Protocol(e.to_string()))?;
+        }
+        Ok(())
     }
 
     /// Initiate a payment flow by requesting a receipt from a peer.
@@ -35,53 +525,155 @@ impl PaykitInteractiveManager {
     /// * `channel`: The established Noise channel to the peer.
     /// * `provisional_receipt`: The receipt request details.
     ///
+    /// The confirmed receipt is acknowledged with `Ack`.
+    ///
     /// # Timeout
-    /// This function will timeout after 30 seconds if no response is received.
+    /// With the `timeout` feature, the request is sent again each time
+    /// [`FlowConfig::exchange_timeout`] passes without an answer, and the
+    /// call fails with [`InteractiveError::Timeout`] after
+    /// [`FlowConfig::max_attempts`] sends (30 seconds by default).
     pub async fn initiate_payment<C: PaykitNoiseChannel>(
         &self,
         channel: &mut C,
         provisional_receipt: PaykitReceipt,
     ) -> Result<PaykitReceipt> {
-        use std::time::Duration;
-
-        // 1. Send RequestReceipt
-        channel
-            .send(PaykitNoiseMessage::RequestReceipt {
-                provisional_receipt: provisional_receipt.clone(),
-            })
-            .await?;
+        // 1. Refuse blocked payees
+        if let Some(policy) = &self.trust_policy {
+            let policy = policy
+                .read()
+                .map_err(|_| InteractiveError::Protocol("Trust policy lock poisoned".into()))?;
+            policy
+                .check_payment(&provisional_receipt.payee, true)
+                .map_err(|e| InteractiveError::Protocol(e.to_string()))?;
+        }
 
-        // 2. Wait for response with timeout (30 seconds)
-        #[cfg(feature = "timeout")]
-        let msg = {
-            tokio::time::timeout(Duration::from_secs(30), channel.recv())
-                .await
-                .map_err(|_| {
-                    InteractiveError::Transport("Receipt confirmation timed out".into())
-                })??
+        // 2. Send RequestReceipt
+        let receipt_id = provisional_receipt.receipt_id.clone();
+        let request = PaykitNoiseMessage::RequestReceipt {
+            provisional_receipt,
         };
+        self.start_flow(&receipt_id);
+        channel.send(request.clone()).await?;
+        self.advance(&receipt_id, FlowEvent::SendRequest)?;
 
-        #[cfg(not(feature = "timeout"))]
-        let msg = channel.recv().await?;
+        // 3. Wait for the confirmation, retransmitting at each deadline
+        let msg = loop {
+            match self.recv_before_deadline(channel).await {
+                Ok(Some(msg)) => break msg,
+                Ok(None) if self.can_retransmit(&receipt_id) => {
+                    channel.send(request.clone()).await?;
+                    self.advance(&receipt_id, FlowEvent::Retransmit)?;
+                }
+                Ok(None) => {
+                    self.advance(&receipt_id, FlowEvent::Expire)?;
+                    return Err(InteractiveError::Timeout(
+                        "Receipt confirmation timed out".into(),
+                    ));
+                }
+                Err(e) => {
+                    self.advance(&receipt_id, FlowEvent::Fail)?;
+                    return Err(e);
+                }
+            }
+        };
 
         match msg {
             PaykitNoiseMessage::ConfirmReceipt { receipt } => {
-                // 3. Validate receipt matches request ID
-                if receipt.receipt_id != provisional_receipt.receipt_id {
+                // 4. Validate receipt matches request ID
+                if receipt.receipt_id != receipt_id {
+                    self.advance(&receipt_id, FlowEvent::Fail)?;
                     return Err(InteractiveError::Protocol("Receipt ID mismatch".into()));
                 }
 
-                // 4. Save confirmed receipt
+                // 5. Save and track confirmed receipt
                 self.storage.save_receipt(&receipt).await?;
+                self.track_receipt(&receipt, &receipt.payee)?;
+                self.advance(&receipt_id, FlowEvent::ReceiveConfirm)?;
+
+                // 6. Stop the payee retransmitting; it may already be gone
+                let _ = channel.send(PaykitNoiseMessage::Ack).await;
                 Ok(receipt)
             }
-            PaykitNoiseMessage::Error { code, message } => Err(InteractiveError::Protocol(
-                format!("Peer error {}: {}", code, message),
-            )),
-            msg => Err(InteractiveError::Protocol(format!(
+            PaykitNoiseMessage::Error { code, message } => {
+                self.advance(&receipt_id, FlowEvent::Fail)?;
+                Err(InteractiveError::Protocol(format!(
+                    "Peer error {}: {}",
+                    code, message
+                )))
+            }
+            msg => {
+                self.advance(&receipt_id, FlowEvent::Fail)?;
+                Err(InteractiveError::Protocol(format!(
+                    "Unexpected message: {:?}",
+                    msg
+                )))
+            }
+        }
+    }
+
+    /// Answer one receipt request from `peer` and wait for its `Ack`.
+    ///
+    /// The payee side of [`initiate_payment`](Self::initiate_payment). The
+    /// confirmation is sent again at each deadline, and whenever the payer
+    /// repeats its request, until the `Ack` arrives. Fails with
+    /// [`InteractiveError::Timeout`] after [`FlowConfig::max_attempts`]
+    /// sends; the receipt is saved by then, so the payer may still have it.
+    ///
+    /// Returns the confirmed receipt, or `None` if the request was refused
+    /// (the refusal is sent to the payer).
+    pub async fn serve_receipt_request<C: PaykitNoiseChannel>(
+        &self,
+        channel: &mut C,
+        peer: &PublicKey,
+        my_pubkey: &PublicKey,
+    ) -> Result<Option<PaykitReceipt>> {
+        let msg = channel.recv().await?;
+        if !matches!(msg, PaykitNoiseMessage::RequestReceipt { .. }) {
+            return Err(InteractiveError::Protocol(format!(
                 "Unexpected message: {:?}",
                 msg
-            ))),
+            )));
+        }
+        let receipt = match self.handle_message(msg, peer, my_pubkey).await? {
+            Some(PaykitNoiseMessage::ConfirmReceipt { receipt }) => receipt,
+            Some(refusal) => {
+                channel.send(refusal).await?;
+                return Ok(None);
+            }
+            None => return Ok(None),
+        };
+        let reply = PaykitNoiseMessage::ConfirmReceipt {
+            receipt: receipt.clone(),
+        };
+        channel.send(reply.clone()).await?;
+
+        loop {
+            match self.recv_before_deadline(channel).await? {
+                Some(PaykitNoiseMessage::Ack) => {
+                    self.advance(&receipt.receipt_id, FlowEvent::ReceiveAck)?;
+                    return Ok(Some(receipt));
+                }
+                // Our confirmation crossed a retransmitted request
+                Some(repeat @ PaykitNoiseMessage::RequestReceipt { .. }) => {
+                    self.handle_message(repeat, peer, my_pubkey).await?;
+                    channel.send(reply.clone()).await?;
+                }
+                Some(msg) => {
+                    self.advance(&receipt.receipt_id, FlowEvent::Fail)?;
+                    return Err(InteractiveError::Protocol(format!(
+                        "Unexpected message: {:?}",
+                        msg
+                    )));
+                }
+                None if self.can_retransmit(&receipt.receipt_id) => {
+                    channel.send(reply.clone()).await?;
+                    self.advance(&receipt.receipt_id, FlowEvent::Retransmit)?;
+                }
+                None => {
+                    self.advance(&receipt.receipt_id, FlowEvent::Expire)?;
+                    return Err(InteractiveError::Timeout("Receipt ack timed out".into()));
+                }
+            }
         }
     }
 
@@ -91,12 +683,27 @@ impl PaykitInteractiveManager {
     /// * `peer`: The public key of the sender.
     /// * `my_pubkey`: The public key of the receiver (self).
     ///
-    /// Returns an optional response message to be sent back.
+    /// Returns an optional response message to be sent back. With
+    /// [peer limits](Self::with_peer_limits), a message over the peer's
+    /// limit is not handled and the response is an error.
     pub async fn handle_message(
         &self,
         msg: PaykitNoiseMessage,
         peer: &PublicKey,
         my_pubkey: &PublicKey,
+    ) -> Result<Option<PaykitNoiseMessage>> {
+        if let Some(refusal) = self.check_peer_limit(peer) {
+            return Ok(Some(refusal));
+        }
+        self.dispatch_message(msg, peer, my_pubkey).await
+    }
+
+    /// Handle a message that passed the peer limits.
+    async fn dispatch_message(
+        &self,
+        msg: PaykitNoiseMessage,
+        peer: &PublicKey,
+        my_pubkey: &PublicKey,
     ) -> Result<Option<PaykitNoiseMessage>> {
         match msg {
             PaykitNoiseMessage::OfferPrivateEndpoint {
@@ -107,6 +714,7 @@ impl PaykitInteractiveManager {
                 self.storage
                     .save_private_endpoint(peer, &method_id, &endpoint)
                     .await?;
+                self.note_private_endpoint(peer, &method_id, &endpoint);
                 Ok(Some(PaykitNoiseMessage::Ack))
             }
             PaykitNoiseMessage::OfferPrivateEndpoints { methods } => {
@@ -117,6 +725,7 @@ impl PaykitInteractiveManager {
                     self.storage
                         .save_private_endpoint(peer, &offer.method_id, &offer.endpoint)
                         .await?;
+                    self.note_private_endpoint(peer, &offer.method_id, &offer.endpoint);
                 }
                 // Accept all endpoints (could be made configurable)
                 Ok(Some(PaykitNoiseMessage::AcceptPrivateEndpoints {
@@ -144,14 +753,28 @@ impl PaykitInteractiveManager {
                     }));
                 }
 
-                // 2. Generate receipt using the generator (app logic)
-                let confirmed_receipt = self
-                    .generator
-                    .generate_receipt(&provisional_receipt)
-                    .await?;
+                // A retransmitted request gets the receipt we already issued
+                let receipt_id = provisional_receipt.receipt_id.clone();
+                if let Some(existing) = self.storage.get_receipt(&receipt_id).await? {
+                    if is_same_request(&existing, &provisional_receipt) {
+                        if self.flow_state(&receipt_id) == Some(FlowState::ConfirmSent) {
+                            self.advance(&receipt_id, FlowEvent::ReceiveRequest)?;
+                        }
+                        return Ok(Some(PaykitNoiseMessage::ConfirmReceipt {
+                            receipt: existing,
+                        }));
+                    }
+                }
 
-                // 3. Save locally
-                self.storage.save_receipt(&confirmed_receipt).await?;
+                // 2. Apply the payee's inbound rules
+                if let Some(refusal) = self.check_inbound_rules(&provisional_receipt, peer)? {
+                    return Ok(Some(refusal));
+                }
+                self.start_flow(&receipt_id);
+                self.advance(&receipt_id, FlowEvent::ReceiveRequest)?;
+
+                // 3. Generate receipt using the generator (app logic) and save it
+                let confirmed_receipt = self.issue_receipt(provisional_receipt).await?;
 
                 // 4. Respond with confirmation
                 Ok(Some(PaykitNoiseMessage::ConfirmReceipt {
@@ -159,8 +782,18 @@ impl PaykitInteractiveManager {
                 }))
             }
             PaykitNoiseMessage::ConfirmReceipt { receipt } => {
-                // Handle unsolicited confirmation or late arrival
+                // Handle unsolicited confirmation or late arrival; a repeat
+                // of one we accepted means our Ack was lost
+                if self.flow_state(&receipt.receipt_id).is_some() {
+                    if let Err(e) = self.advance(&receipt.receipt_id, FlowEvent::ReceiveConfirm) {
+                        return Ok(Some(PaykitNoiseMessage::Error {
+                            code: "UNEXPECTED_CONFIRM".into(),
+                            message: e.to_string(),
+                        }));
+                    }
+                }
                 self.storage.save_receipt(&receipt).await?;
+                self.track_receipt(&receipt, peer)?;
                 Ok(Some(PaykitNoiseMessage::Ack))
             }
             PaykitNoiseMessage::Ack => {
@@ -171,9 +804,237 @@ impl PaykitInteractiveManager {
                 // Log error?
                 Ok(None)
             }
+            PaykitNoiseMessage::RequestApproval { pending } => {
+                let Some(handler) = &self.approval_handler else {
+                    return Ok(Some(PaykitNoiseMessage::Error {
+                        code: "APPROVALS_UNSUPPORTED".into(),
+                        message: "Not configured as a co-signer".into(),
+                    }));
+                };
+                match handler.review(&pending, peer).await? {
+                    Some(approval) => Ok(Some(PaykitNoiseMessage::SubmitApproval { approval })),
+                    None => Ok(Some(PaykitNoiseMessage::Error {
+                        code: "APPROVAL_DEFERRED".into(),
+                        message: "Co-signer has not decided yet".into(),
+                    })),
+                }
+            }
+            PaykitNoiseMessage::SubmitApproval { approval } => {
+                // Co-signer sent a decision later, outside request_approval
+                match self.apply_approval(&approval) {
+                    Ok(()) => Ok(Some(PaykitNoiseMessage::Ack)),
+                    Err(e) => Ok(Some(PaykitNoiseMessage::Error {
+                        code: "APPROVAL_INVALID".into(),
+                        message: e.to_string(),
+                    })),
+                }
+            }
+            PaykitNoiseMessage::SubscriptionStatus { .. } => {
+                // Verified and applied by the subscription layer
+                Ok(Some(PaykitNoiseMessage::Ack))
+            }
+            PaykitNoiseMessage::ProposeSubscription { .. } => {
+                // Recorded by the subscription layer for the user to decide
+                Ok(None)
+            }
+            PaykitNoiseMessage::CancelSubscription { .. } => {
+                // The subscription layer records it and replies with a signed ack
+                Ok(None)
+            }
+            PaykitNoiseMessage::CancelSubscriptionAck { .. } => Ok(None),
+            PaykitNoiseMessage::Attestation { .. } => {
+                // Verified by the transport, which holds the handshake hash
+                Ok(None)
+            }
+            PaykitNoiseMessage::SyncRequest { clock, .. } => {
+                if let Some(error) = self.check_sync_peer(peer, my_pubkey) {
+                    return Ok(Some(error));
+                }
+                let state = self.sync_state()?;
+                self.refresh_sync_state(state).await?;
+                let state = state.lock();
+                Ok(Some(PaykitNoiseMessage::SyncChanges {
+                    device_id: state.device_id().to_string(),
+                    clock: state.clock().clone(),
+                    records: state.changes_since(&clock),
+                }))
+            }
+            PaykitNoiseMessage::SyncChanges { records, .. } => {
+                if let Some(error) = self.check_sync_peer(peer, my_pubkey) {
+                    return Ok(Some(error));
+                }
+                self.merge_sync_records(self.sync_state()?, records).await?;
+                Ok(Some(PaykitNoiseMessage::Ack))
+            }
+            PaykitNoiseMessage::Hello { version, features } => {
+                let ours = self.local_hello();
+                let theirs = Hello { version, features };
+                match negotiation::negotiate(&ours, &theirs, self.min_protocol_version) {
+                    Ok(negotiated) => {
+                        self.negotiated.lock().insert(peer.to_string(), negotiated);
+                        Ok(Some(PaykitNoiseMessage::HelloAck {
+                            version: ours.version,
+                            features: ours.features,
+                        }))
+                    }
+                    Err(e) => Ok(Some(PaykitNoiseMessage::Error {
+                        code: VERSION_UNSUPPORTED.into(),
+                        message: e.to_string(),
+                    })),
+                }
+            }
+            PaykitNoiseMessage::HelloAck { .. } => {
+                // Only meaningful as the answer awaited by negotiate
+                Ok(None)
+            }
         }
     }
 
+    /// Advertise exactly `features` when negotiating.
+    ///
+    /// By default the manager advertises what it is configured for:
+    /// receipts, private endpoints, subscriptions and attestation, plus
+    /// approvals with an [approval handler](Self::with_approval_handler)
+    /// and device sync with [device sync](Self::with_device_sync).
+    pub fn with_advertised_features(mut self, features: Features) -> Self {
+        self.advertised_features = Some(features);
+        self
+    }
+
+    /// Refuse peers that negotiate a protocol version below `version`.
+    ///
+    /// The default accepts every version, including peers that predate
+    /// negotiation.
+    pub fn with_min_protocol_version(mut self, version: u32) -> Self {
+        self.min_protocol_version = version;
+        self
+    }
+
+    /// The features this manager advertises in its hellos.
+    pub fn local_features(&self) -> Features {
+        if let Some(features) = self.advertised_features {
+            return features;
+        }
+        let mut features = Features::LEGACY | Features::SUBSCRIPTIONS | Features::ATTESTATION;
+        if self.approval_handler.is_some() {
+            features = features | Features::APPROVALS;
+        }
+        if self.device_sync.is_some() {
+            features = features | Features::DEVICE_SYNC;
+        }
+        features
+    }
+
+    fn local_hello(&self) -> Hello {
+        Hello::new(self.local_features())
+    }
+
+    /// Negotiate the protocol version and features with `peer`.
+    ///
+    /// Call this first on a newly opened channel; the peer answers through
+    /// [`handle_message`](Self::handle_message). The result is remembered
+    /// for [`peer_supports`](Self::peer_supports). A peer that answers with
+    /// an error predates negotiation and is recorded as
+    /// [`Negotiated::legacy`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InteractiveError::Protocol`] if either side refuses the
+    /// other's protocol version or the peer answers with anything else.
+    pub async fn negotiate<C: PaykitNoiseChannel>(
+        &self,
+        channel: &mut C,
+        peer: &PublicKey,
+    ) -> Result<Negotiated> {
+        let ours = self.local_hello();
+        channel
+            .send(PaykitNoiseMessage::Hello {
+                version: ours.version,
+                features: ours.features,
+            })
+            .await?;
+
+        let negotiated = match channel.recv().await? {
+            PaykitNoiseMessage::HelloAck { version, features } => negotiation::negotiate(
+                &ours,
+                &Hello { version, features },
+                self.min_protocol_version,
+            )?,
+            PaykitNoiseMessage::Error { code, message } if code == VERSION_UNSUPPORTED => {
+                return Err(InteractiveError::Protocol(format!(
+                    "Peer error {}: {}",
+                    code, message
+                )))
+            }
+            PaykitNoiseMessage::Error { .. } => {
+                let legacy = Negotiated::legacy();
+                negotiation::check_version(legacy.version, self.min_protocol_version)?;
+                legacy
+            }
+            msg => {
+                return Err(InteractiveError::Protocol(format!(
+                    "Unexpected message: {:?}",
+                    msg
+                )))
+            }
+        };
+        self.negotiated.lock().insert(peer.to_string(), negotiated);
+        Ok(negotiated)
+    }
+
+    /// The version and features negotiated with `peer`, if any.
+    pub fn negotiated(&self, peer: &PublicKey) -> Option<Negotiated> {
+        self.negotiated.lock().get(&peer.to_string()).copied()
+    }
+
+    /// Whether `peer` understands every feature in `features`.
+    ///
+    /// Peers that have not negotiated are assumed to support only
+    /// [`Features::LEGACY`], so check this before sending anything newer.
+    pub fn peer_supports(&self, peer: &PublicKey, features: Features) -> bool {
+        self.negotiated(peer)
+            .unwrap_or_else(Negotiated::legacy)
+            .supports(features)
+    }
+
+    /// Forget what was negotiated with `peer`, e.g. when its session closes.
+    pub fn forget_negotiation(&self, peer: &PublicKey) {
+        self.negotiated.lock().remove(&peer.to_string());
+    }
+
+    /// Record a private endpoint for device sync.
+    ///
+    /// `PaykitStorage` cannot list peers, so endpoints are recorded as they
+    /// arrive rather than found by the next refresh.
+    fn note_private_endpoint(&self, peer: &PublicKey, method_id: &MethodId, endpoint: &str) {
+        if let Some(state) = &self.device_sync {
+            state
+                .lock()
+                .put_private_endpoint(peer.clone(), method_id.clone(), endpoint);
+        }
+    }
+
+    /// An error reply if `peer` may not sync with us, `None` if it may.
+    fn check_sync_peer(
+        &self,
+        peer: &PublicKey,
+        my_pubkey: &PublicKey,
+    ) -> Option<PaykitNoiseMessage> {
+        if self.device_sync.is_none() {
+            return Some(PaykitNoiseMessage::Error {
+                code: "SYNC_UNSUPPORTED".into(),
+                message: "Device sync not configured".into(),
+            });
+        }
+        if peer != my_pubkey {
+            return Some(PaykitNoiseMessage::Error {
+                code: "NOT_OWN_DEVICE".into(),
+                message: "Only devices of the same identity can sync".into(),
+            });
+        }
+        None
+    }
+
     /// Send a private endpoint offer to a peer.
     pub async fn offer_private_endpoint<C: PaykitNoiseChannel>(
         &self,
@@ -189,3 +1050,12 @@ impl PaykitInteractiveManager {
             .await
     }
 }
+
+/// Whether `request` repeats the request `existing` was issued for.
+fn is_same_request(existing: &PaykitReceipt, request: &PaykitReceipt) -> bool {
+    existing.payer == request.payer
+        && existing.payee == request.payee
+        && existing.method_id == request.method_id
+        && existing.amount == request.amount
+        && existing.currency == request.currency
+}
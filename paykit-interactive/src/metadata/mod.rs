@@ -10,6 +10,18 @@
 //! - **TaxMetadata**: Tax information (rate, amount, jurisdiction)
 //! - **CustomMetadata**: Extensible key-value pairs
 //!
+//! Tax can be calculated from the order's items instead of entered by hand;
+//! see the [`tax`] module.
+//!
+//! # Payment Notes
+//!
+//! A payer can attach a short human note ("thanks for lunch") that travels
+//! to the payee in the `memo` field of the receipt metadata. Memos come from
+//! peers, so they are always passed through [`sanitize_memo`] before being
+//! stored or displayed: control and bidirectional-override characters are
+//! removed, whitespace is collapsed and the text is capped at
+//! [`MAX_MEMO_BYTES`].
+//!
 //! # Example
 //!
 //! ```ignore
@@ -28,9 +40,84 @@
 //! let json = metadata.to_json();
 //! ```
 
+pub mod tax;
+
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use tax::TaxLine;
+
+/// Metadata key holding the payer's note.
+pub const MEMO_KEY: &str = "memo";
+
+/// Longest memo kept, in UTF-8 bytes.
+pub const MAX_MEMO_BYTES: usize = 280;
+
+/// Clean up a payment note for storage and display.
+///
+/// Removes control characters and bidirectional overrides (which can make a
+/// note render misleadingly), collapses whitespace to single spaces and
+/// truncates to [`MAX_MEMO_BYTES`] on a character boundary. Returns `None`
+/// when nothing is left.
+pub fn sanitize_memo(memo: &str) -> Option<String> {
+    let collapsed = memo
+        .split(char::is_whitespace)
+        .map(|word| {
+            word.chars()
+                .filter(|c| !is_unsafe_memo_char(*c))
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut end = collapsed.len().min(MAX_MEMO_BYTES);
+    while !collapsed.is_char_boundary(end) {
+        end -= 1;
+    }
+    let memo = collapsed[..end].trim_end();
+    (!memo.is_empty()).then(|| memo.to_string())
+}
+
+fn is_unsafe_memo_char(c: char) -> bool {
+    c.is_control()
+        || matches!(
+            c,
+            '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}' | '\u{FEFF}'
+        )
+}
+
+/// Read the sanitized memo from receipt metadata.
+pub fn memo_from_metadata(metadata: &Value) -> Option<String> {
+    metadata
+        .get(MEMO_KEY)
+        .and_then(Value::as_str)
+        .and_then(sanitize_memo)
+}
+
+/// Set (or with `None`, remove) the memo in receipt metadata.
+///
+/// The memo is sanitized first; non-object metadata is replaced by an
+/// object holding only the memo.
+pub fn set_memo_in_metadata(metadata: &mut Value, memo: Option<&str>) {
+    let memo = memo.and_then(sanitize_memo);
+    if !metadata.is_object() {
+        if memo.is_none() {
+            return;
+        }
+        *metadata = Value::Object(Default::default());
+    }
+    if let Some(map) = metadata.as_object_mut() {
+        match memo {
+            Some(memo) => {
+                map.insert(MEMO_KEY.to_string(), Value::String(memo));
+            }
+            None => {
+                map.remove(MEMO_KEY);
+            }
+        }
+    }
+}
 
 /// Item in an order for metadata purposes.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -250,6 +337,9 @@ pub struct TaxMetadata {
     /// Tax ID / VAT number.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tax_id: Option<String>,
+    /// Per-item calculation, when the tax was calculated.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub breakdown: Vec<TaxLine>,
 }
 
 impl TaxMetadata {
@@ -304,6 +394,9 @@ pub struct PaymentMetadata {
     /// Tax metadata.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tax: Option<TaxMetadata>,
+    /// Payer's note to the payee.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
     /// Custom key-value pairs.
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub custom: HashMap<String, Value>,
@@ -333,6 +426,12 @@ impl PaymentMetadata {
         self
     }
 
+    /// Set the payer's note, sanitized with [`sanitize_memo`].
+    pub fn with_memo(mut self, memo: impl AsRef<str>) -> Self {
+        self.memo = sanitize_memo(memo.as_ref());
+        self
+    }
+
     /// Add a custom field.
     pub fn with_custom(mut self, key: impl Into<String>, value: Value) -> Self {
         self.custom.insert(key.into(), value);
@@ -352,6 +451,9 @@ impl PaymentMetadata {
         if other.tax.is_some() {
             self.tax = other.tax;
         }
+        if other.memo.is_some() {
+            self.memo = other.memo;
+        }
         self.custom.extend(other.custom);
         self
     }
@@ -371,6 +473,7 @@ impl PaymentMetadata {
         self.order.is_none()
             && self.shipping.is_none()
             && self.tax.is_none()
+            && self.memo.is_none()
             && self.custom.is_empty()
     }
 }
@@ -551,4 +654,28 @@ mod tests {
         assert!(validator.validate(&valid).is_ok());
         assert!(validator.validate(&invalid).is_err());
     }
+
+    #[test]
+    fn test_memo_sanitization() {
+        assert_eq!(
+            sanitize_memo("  thanks\tfor\n\nlunch \u{202E}!\u{0007} "),
+            Some("thanks for lunch !".to_string())
+        );
+        assert_eq!(sanitize_memo(" \n\u{200F} "), None);
+
+        let long = sanitize_memo(&"é".repeat(MAX_MEMO_BYTES)).unwrap();
+        assert!(long.len() <= MAX_MEMO_BYTES);
+        assert_eq!(long.chars().count(), MAX_MEMO_BYTES / 2);
+
+        let mut metadata = serde_json::json!({"order_id": "A1"});
+        set_memo_in_metadata(&mut metadata, Some("coffee\r\n"));
+        assert_eq!(memo_from_metadata(&metadata), Some("coffee".to_string()));
+        set_memo_in_metadata(&mut metadata, None);
+        assert!(metadata.get(MEMO_KEY).is_none());
+        assert_eq!(metadata["order_id"], "A1");
+
+        let parsed = PaymentMetadata::from_json(&serde_json::json!({"memo": "hi"})).unwrap();
+        assert_eq!(parsed.memo.as_deref(), Some("hi"));
+        assert!(!PaymentMetadata::new().with_memo("hi").is_empty());
+    }
 }
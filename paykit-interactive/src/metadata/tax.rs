@@ -0,0 +1,397 @@
+//! Tax Calculation
+//!
+//! A [`TaxCalculator`] turns the line items of an order into [`TaxMetadata`]
+//! when a request, intent or invoice is built, instead of the tax being
+//! filled in by hand. The calculation is recorded line by line in
+//! [`TaxMetadata::breakdown`], so an export can show how the tax was reached
+//! and check that the lines add up to the total ([`TaxMetadata::is_reconciled`]).
+//!
+//! [`TaxRulesTable`] is a simple calculator: one rate per jurisdiction,
+//! picked from the payer's (destination) or payee's (origin) jurisdiction,
+//! with optional SKU exemptions. Anything more involved implements
+//! [`TaxCalculator`] directly.
+//!
+//! Amounts are integers in the currency's smallest unit (e.g. sats), and
+//! rates are in basis points (825 = 8.25%). Tax is rounded half up per line.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use paykit_interactive::metadata::tax::{TaxContext, TaxRateRule, TaxRulesTable};
+//!
+//! let table = TaxRulesTable::new()
+//!     .with_rule(TaxRateRule::new("US-CA", "Sales Tax", 825))
+//!     .with_rule(TaxRateRule::new("DE", "VAT", 1900).with_exempt_sku("BOOK"));
+//!
+//! let metadata = PaymentMetadata::new()
+//!     .with_order(order)
+//!     .with_calculated_tax(&table, &TaxContext::new("SAT").with_payer_jurisdiction("US-CA"))?;
+//! ```
+
+use super::{MetadataItem, PaymentMetadata, TaxMetadata};
+use crate::{InteractiveError, Result};
+use serde::{Deserialize, Serialize};
+
+const BASIS_POINTS: u128 = 10_000;
+
+/// Who and what is being taxed.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaxContext {
+    /// Currency of the line items.
+    pub currency: String,
+    /// Payer's jurisdiction (e.g. "US-CA", "DE").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payer_jurisdiction: Option<String>,
+    /// Payee's jurisdiction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payee_jurisdiction: Option<String>,
+}
+
+impl TaxContext {
+    /// Create a context for items priced in `currency`.
+    pub fn new(currency: impl Into<String>) -> Self {
+        Self {
+            currency: currency.into(),
+            payer_jurisdiction: None,
+            payee_jurisdiction: None,
+        }
+    }
+
+    /// Set the payer's jurisdiction.
+    pub fn with_payer_jurisdiction(mut self, jurisdiction: impl Into<String>) -> Self {
+        self.payer_jurisdiction = Some(jurisdiction.into());
+        self
+    }
+
+    /// Set the payee's jurisdiction.
+    pub fn with_payee_jurisdiction(mut self, jurisdiction: impl Into<String>) -> Self {
+        self.payee_jurisdiction = Some(jurisdiction.into());
+        self
+    }
+}
+
+/// How the tax on one line item was calculated.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaxLine {
+    /// Item description.
+    pub description: String,
+    /// Item SKU.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sku: Option<String>,
+    /// Quantity times unit price, as string.
+    pub taxable_amount: String,
+    /// Rate applied, in basis points.
+    pub rate_bps: u32,
+    /// Tax on the line, as string.
+    pub tax_amount: String,
+    /// Why the line was not taxed, if it was exempt.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exemption: Option<String>,
+}
+
+/// Calculates the tax due on an order.
+pub trait TaxCalculator: Send + Sync {
+    /// Tax on `items`, or `None` if no tax applies in this context.
+    ///
+    /// The returned metadata should carry a breakdown whose lines add up to
+    /// its amount.
+    fn calculate(
+        &self,
+        items: &[MetadataItem],
+        context: &TaxContext,
+    ) -> Result<Option<TaxMetadata>>;
+}
+
+/// Which party's jurisdiction decides the rate.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaxBasis {
+    /// Where the payer is, falling back to the payee.
+    #[default]
+    Destination,
+    /// Where the payee is, falling back to the payer.
+    Origin,
+}
+
+/// Tax rate for one jurisdiction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaxRateRule {
+    /// Jurisdiction the rule applies to (e.g. "US-CA", "DE").
+    pub jurisdiction: String,
+    /// Tax description (e.g. "Sales Tax", "VAT").
+    pub description: String,
+    /// Rate in basis points.
+    pub rate_bps: u32,
+    /// SKUs not taxed under this rule.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exempt_skus: Vec<String>,
+    /// Payee's tax ID / VAT number in this jurisdiction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tax_id: Option<String>,
+}
+
+impl TaxRateRule {
+    /// Create a rule taxing everything in `jurisdiction` at `rate_bps`.
+    pub fn new(
+        jurisdiction: impl Into<String>,
+        description: impl Into<String>,
+        rate_bps: u32,
+    ) -> Self {
+        Self {
+            jurisdiction: jurisdiction.into(),
+            description: description.into(),
+            rate_bps,
+            exempt_skus: Vec::new(),
+            tax_id: None,
+        }
+    }
+
+    /// Exempt a SKU.
+    pub fn with_exempt_sku(mut self, sku: impl Into<String>) -> Self {
+        self.exempt_skus.push(sku.into());
+        self
+    }
+
+    /// Set the tax ID.
+    pub fn with_tax_id(mut self, id: impl Into<String>) -> Self {
+        self.tax_id = Some(id.into());
+        self
+    }
+}
+
+/// Rules-table [`TaxCalculator`]: one rate per jurisdiction.
+///
+/// A jurisdiction without its own rule uses its country's rule, so "US-NY"
+/// falls back to "US".
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaxRulesTable {
+    /// Which party's jurisdiction decides the rate.
+    #[serde(default)]
+    pub basis: TaxBasis,
+    /// Rules by jurisdiction.
+    pub rules: Vec<TaxRateRule>,
+}
+
+impl TaxRulesTable {
+    /// Create an empty table taxing by destination.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule, replacing any rule for the same jurisdiction.
+    pub fn with_rule(mut self, rule: TaxRateRule) -> Self {
+        self.rules.retain(|r| r.jurisdiction != rule.jurisdiction);
+        self.rules.push(rule);
+        self
+    }
+
+    /// Set the tax basis.
+    pub fn with_basis(mut self, basis: TaxBasis) -> Self {
+        self.basis = basis;
+        self
+    }
+
+    /// Rule applying in `context`, if any.
+    pub fn rule_for(&self, context: &TaxContext) -> Option<&TaxRateRule> {
+        let (first, second) = match self.basis {
+            TaxBasis::Destination => (&context.payer_jurisdiction, &context.payee_jurisdiction),
+            TaxBasis::Origin => (&context.payee_jurisdiction, &context.payer_jurisdiction),
+        };
+        let jurisdiction = first.as_ref().or(second.as_ref())?;
+        self.lookup(jurisdiction).or_else(|| {
+            let (country, _) = jurisdiction.split_once('-')?;
+            self.lookup(country)
+        })
+    }
+
+    fn lookup(&self, jurisdiction: &str) -> Option<&TaxRateRule> {
+        self.rules
+            .iter()
+            .find(|r| r.jurisdiction.eq_ignore_ascii_case(jurisdiction))
+    }
+}
+
+impl TaxCalculator for TaxRulesTable {
+    fn calculate(
+        &self,
+        items: &[MetadataItem],
+        context: &TaxContext,
+    ) -> Result<Option<TaxMetadata>> {
+        let Some(rule) = self.rule_for(context) else {
+            return Ok(None);
+        };
+
+        let mut total_tax: u128 = 0;
+        let mut breakdown = Vec::with_capacity(items.len());
+        for item in items {
+            let taxable = line_amount(item, &context.currency)?;
+            let exempt = item
+                .sku
+                .as_ref()
+                .is_some_and(|sku| rule.exempt_skus.contains(sku));
+            let (rate_bps, tax) = if exempt {
+                (0, 0)
+            } else {
+                let rate = u128::from(rule.rate_bps);
+                (
+                    rule.rate_bps,
+                    (taxable * rate + BASIS_POINTS / 2) / BASIS_POINTS,
+                )
+            };
+            total_tax += tax;
+            breakdown.push(TaxLine {
+                description: item.description.clone(),
+                sku: item.sku.clone(),
+                taxable_amount: taxable.to_string(),
+                rate_bps,
+                tax_amount: tax.to_string(),
+                exemption: exempt.then(|| format!("{} exempt", rule.jurisdiction)),
+            });
+        }
+
+        let mut tax = TaxMetadata::new()
+            .with_description(rule.description.clone())
+            .with_rate(f64::from(rule.rate_bps) / 100.0)
+            .with_amount(total_tax.to_string(), context.currency.clone())
+            .with_jurisdiction(rule.jurisdiction.clone());
+        if let Some(tax_id) = &rule.tax_id {
+            tax = tax.with_tax_id(tax_id.clone());
+        }
+        tax.breakdown = breakdown;
+        Ok(Some(tax))
+    }
+}
+
+impl TaxMetadata {
+    /// Whether the breakdown adds up to the tax amount.
+    ///
+    /// Tax entered by hand has no breakdown and is taken as is.
+    pub fn is_reconciled(&self) -> bool {
+        if self.breakdown.is_empty() {
+            return true;
+        }
+        let lines = self
+            .breakdown
+            .iter()
+            .map(|line| line.tax_amount.parse::<u128>().ok())
+            .sum::<Option<u128>>();
+        let amount = self.amount.as_deref().and_then(|a| a.parse::<u128>().ok());
+        lines.is_some() && lines == amount
+    }
+}
+
+impl PaymentMetadata {
+    /// Calculate the tax on the order's items and record it.
+    ///
+    /// Replaces any tax already set; leaves it unset when no tax applies.
+    pub fn with_calculated_tax(
+        mut self,
+        calculator: &dyn TaxCalculator,
+        context: &TaxContext,
+    ) -> Result<Self> {
+        let items = self
+            .order
+            .as_ref()
+            .map(|order| order.items.as_slice())
+            .unwrap_or_default();
+        self.tax = calculator.calculate(items, context)?;
+        Ok(self)
+    }
+}
+
+fn line_amount(item: &MetadataItem, currency: &str) -> Result<u128> {
+    if item.currency != currency {
+        return Err(InteractiveError::Protocol(format!(
+            "item '{}' is priced in {}, not {}",
+            item.description, item.currency, currency
+        )));
+    }
+    let unit_price: u64 = item.unit_price.parse().map_err(|_| {
+        InteractiveError::Protocol(format!(
+            "item '{}' has invalid unit price '{}'",
+            item.description, item.unit_price
+        ))
+    })?;
+    Ok(u128::from(unit_price) * u128::from(item.quantity))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::OrderMetadata;
+
+    fn table() -> TaxRulesTable {
+        TaxRulesTable::new()
+            .with_rule(TaxRateRule::new("US", "Sales Tax", 500))
+            .with_rule(TaxRateRule::new("US-CA", "Sales Tax", 825).with_tax_id("CA-123"))
+            .with_rule(TaxRateRule::new("DE", "VAT", 1900).with_exempt_sku("BOOK"))
+    }
+
+    #[test]
+    fn test_rules_table_picks_jurisdiction() {
+        let items = vec![MetadataItem::new("Widget", 3, "333", "SAT")];
+        let ctx = TaxContext::new("SAT").with_payee_jurisdiction("DE");
+
+        // Destination first, then country fallback, then the payee
+        let ca = table()
+            .calculate(&items, &ctx.clone().with_payer_jurisdiction("US-CA"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(ca.amount.as_deref(), Some("82"));
+        assert_eq!(ca.jurisdiction.as_deref(), Some("US-CA"));
+        assert_eq!(ca.tax_id.as_deref(), Some("CA-123"));
+
+        let ny = table()
+            .calculate(&items, &ctx.clone().with_payer_jurisdiction("US-NY"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(ny.jurisdiction.as_deref(), Some("US"));
+        assert_eq!(ny.amount.as_deref(), Some("50"));
+
+        let de = table().calculate(&items, &ctx).unwrap().unwrap();
+        assert_eq!(de.rate, Some(19.0));
+
+        let origin = table().with_basis(TaxBasis::Origin);
+        let ctx = ctx.with_payer_jurisdiction("US-CA");
+        assert_eq!(origin.rule_for(&ctx).unwrap().jurisdiction, "DE");
+
+        let nowhere = TaxContext::new("SAT").with_payer_jurisdiction("JP");
+        assert!(table().calculate(&items, &nowhere).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_breakdown_reconciles_in_metadata() {
+        let order = OrderMetadata::new()
+            .add_item(MetadataItem::new("Widget", 2, "1005", "SAT"))
+            .add_item(MetadataItem::new("Manual", 1, "2000", "SAT").with_sku("BOOK"))
+            .add_item(MetadataItem::new("Cable", 3, "17", "SAT"));
+        let ctx = TaxContext::new("SAT").with_payer_jurisdiction("DE");
+
+        let metadata = PaymentMetadata::new()
+            .with_order(order)
+            .with_calculated_tax(&table(), &ctx)
+            .unwrap();
+        let tax = metadata.tax.as_ref().unwrap();
+
+        // 2010 * 19% = 381.9 -> 382, book exempt, 51 * 19% = 9.69 -> 10
+        let lines: Vec<&str> = tax
+            .breakdown
+            .iter()
+            .map(|l| l.tax_amount.as_str())
+            .collect();
+        assert_eq!(lines, vec!["382", "0", "10"]);
+        assert_eq!(tax.amount.as_deref(), Some("392"));
+        assert!(tax.breakdown[1].exemption.is_some());
+        assert!(tax.is_reconciled());
+
+        let parsed = PaymentMetadata::from_json(&metadata.to_json()).unwrap();
+        assert_eq!(parsed.tax.unwrap().breakdown, tax.breakdown);
+
+        let mut tampered = tax.clone();
+        tampered.amount = Some("400".to_string());
+        assert!(!tampered.is_reconciled());
+
+        let euros = vec![MetadataItem::new("Widget", 1, "10", "EUR")];
+        assert!(table().calculate(&euros, &ctx).is_err());
+    }
+}
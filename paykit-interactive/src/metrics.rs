@@ -44,6 +44,8 @@ pub struct Metrics {
     messages_received: AtomicU64,
     bytes_sent: AtomicU64,
     bytes_received: AtomicU64,
+    messages_rate_limited: AtomicU64,
+    peers_greylisted: AtomicU64,
 
     // Connection metrics
     active_connections: AtomicU64,
@@ -83,6 +85,8 @@ impl Metrics {
             messages_received: AtomicU64::new(0),
             bytes_sent: AtomicU64::new(0),
             bytes_received: AtomicU64::new(0),
+            messages_rate_limited: AtomicU64::new(0),
+            peers_greylisted: AtomicU64::new(0),
             active_connections: AtomicU64::new(0),
             total_connections: AtomicU64::new(0),
             connection_rejections: AtomicU64::new(0),
@@ -133,6 +137,16 @@ impl Metrics {
         self.bytes_received.fetch_add(bytes, Ordering::Relaxed);
     }
 
+    /// Record a message refused by a per-peer rate limit.
+    pub fn record_message_rate_limited(&self) {
+        self.messages_rate_limited.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a peer greylisted after repeatedly exceeding its rate limit.
+    pub fn record_peer_greylisted(&self) {
+        self.peers_greylisted.fetch_add(1, Ordering::Relaxed);
+    }
+
     // === Connection Metrics ===
 
     /// Record a new connection.
@@ -207,6 +221,8 @@ impl Metrics {
             messages_received: self.messages_received.load(Ordering::Relaxed),
             bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
             bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            messages_rate_limited: self.messages_rate_limited.load(Ordering::Relaxed),
+            peers_greylisted: self.peers_greylisted.load(Ordering::Relaxed),
 
             active_connections: self.active_connections.load(Ordering::Relaxed),
             total_connections: self.total_connections.load(Ordering::Relaxed),
@@ -234,6 +250,8 @@ impl Metrics {
         self.messages_received.store(0, Ordering::Relaxed);
         self.bytes_sent.store(0, Ordering::Relaxed);
         self.bytes_received.store(0, Ordering::Relaxed);
+        self.messages_rate_limited.store(0, Ordering::Relaxed);
+        self.peers_greylisted.store(0, Ordering::Relaxed);
 
         // Don't reset active_connections as it represents current state
         self.total_connections.store(0, Ordering::Relaxed);
@@ -267,6 +285,8 @@ pub struct MetricsSnapshot {
     pub messages_received: u64,
     pub bytes_sent: u64,
     pub bytes_received: u64,
+    pub messages_rate_limited: u64,
+    pub peers_greylisted: u64,
 
     // Connection metrics
     pub active_connections: u64,
@@ -339,6 +359,14 @@ paykit_bytes_sent_total {}
 # TYPE paykit_bytes_received_total counter
 paykit_bytes_received_total {}
 
+# HELP paykit_messages_rate_limited_total Messages refused by per-peer rate limits
+# TYPE paykit_messages_rate_limited_total counter
+paykit_messages_rate_limited_total {}
+
+# HELP paykit_peers_greylisted_total Peers greylisted after repeated rate limit violations
+# TYPE paykit_peers_greylisted_total counter
+paykit_peers_greylisted_total {}
+
 # HELP paykit_active_connections Current active connections
 # TYPE paykit_active_connections gauge
 paykit_active_connections {}
@@ -382,6 +410,8 @@ paykit_errors_total{{type="protocol"}} {}
             self.messages_received,
             self.bytes_sent,
             self.bytes_received,
+            self.messages_rate_limited,
+            self.peers_greylisted,
             self.active_connections,
             self.total_connections,
             self.connection_rejections,
@@ -407,7 +437,7 @@ impl serde::Serialize for MetricsSnapshot {
         S: serde::Serializer,
     {
         use serde::ser::SerializeStruct;
-        let mut state = serializer.serialize_struct("MetricsSnapshot", 19)?;
+        let mut state = serializer.serialize_struct("MetricsSnapshot", 21)?;
         state.serialize_field("uptime_secs", &self.uptime_secs)?;
         state.serialize_field("handshake_attempts", &self.handshake_attempts)?;
         state.serialize_field("handshake_successes", &self.handshake_successes)?;
@@ -417,6 +447,8 @@ impl serde::Serialize for MetricsSnapshot {
         state.serialize_field("messages_received", &self.messages_received)?;
         state.serialize_field("bytes_sent", &self.bytes_sent)?;
         state.serialize_field("bytes_received", &self.bytes_received)?;
+        state.serialize_field("messages_rate_limited", &self.messages_rate_limited)?;
+        state.serialize_field("peers_greylisted", &self.peers_greylisted)?;
         state.serialize_field("active_connections", &self.active_connections)?;
         state.serialize_field("total_connections", &self.total_connections)?;
         state.serialize_field("connection_rejections", &self.connection_rejections)?;
@@ -0,0 +1,279 @@
+//! Protocol version and feature negotiation.
+//!
+//! Peers running different releases of this crate may not understand each
+//! other's messages: a `RequestApproval` sent to a peer that predates
+//! co-signing fails to deserialize on the other side. To avoid that, the
+//! initiator of a session opens with a [`PaykitNoiseMessage::Hello`]
+//! carrying its protocol version and the features it supports, and the
+//! responder answers with a [`PaykitNoiseMessage::HelloAck`] carrying its
+//! own. Both sides then run [`negotiate`] on the two hellos and arrive at
+//! the same [`Negotiated`] result.
+//!
+//! # Downgrade Rules
+//!
+//! - The session runs at the lower of the two protocol versions.
+//! - The feature set is the intersection of both advertised sets, limited
+//!   to the features that version defines ([`Features::for_version`]).
+//! - A version below the local minimum is refused with a
+//!   `VERSION_UNSUPPORTED` error.
+//! - A peer that answers the hello with an error predates negotiation; it
+//!   is treated as version 0 with [`Features::LEGACY`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! let negotiated = manager.negotiate(&mut channel, &peer).await?;
+//! if manager.peer_supports(&peer, Features::APPROVALS) {
+//!     manager.request_approval(&mut channel, pending).await?;
+//! }
+//! ```
+//!
+//! [`PaykitNoiseMessage::Hello`]: crate::PaykitNoiseMessage::Hello
+//! [`PaykitNoiseMessage::HelloAck`]: crate::PaykitNoiseMessage::HelloAck
+
+use crate::{InteractiveError, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::{BitAnd, BitOr};
+
+/// Protocol version spoken by this release.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Version assumed for peers that do not negotiate.
+pub const LEGACY_PROTOCOL_VERSION: u32 = 0;
+
+/// Error code sent to a peer whose protocol version is refused.
+pub const VERSION_UNSUPPORTED: &str = "VERSION_UNSUPPORTED";
+
+/// Set of optional protocol features, as bit flags.
+///
+/// Serialized as a plain integer. Bits this release does not know are kept
+/// when parsing so that newer peers round-trip, but never survive
+/// [`negotiate`].
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Features(u64);
+
+impl Features {
+    /// Receipt request and confirmation.
+    pub const RECEIPTS: Features = Features(1 << 0);
+    /// Private endpoint offers.
+    pub const PRIVATE_ENDPOINTS: Features = Features(1 << 1);
+    /// Co-signer approval requests.
+    pub const APPROVALS: Features = Features(1 << 2);
+    /// Subscription proposals, status notices and cancellations.
+    pub const SUBSCRIPTIONS: Features = Features(1 << 3);
+    /// Identity attestation after NN handshakes.
+    pub const ATTESTATION: Features = Features(1 << 4);
+    /// Sync between devices of the same identity.
+    pub const DEVICE_SYNC: Features = Features(1 << 5);
+
+    /// Features every peer is assumed to support, negotiated or not.
+    pub const LEGACY: Features = Features(Self::RECEIPTS.0 | Self::PRIVATE_ENDPOINTS.0);
+
+    /// Every feature this release knows.
+    pub const ALL: Features = Features(
+        Self::RECEIPTS.0
+            | Self::PRIVATE_ENDPOINTS.0
+            | Self::APPROVALS.0
+            | Self::SUBSCRIPTIONS.0
+            | Self::ATTESTATION.0
+            | Self::DEVICE_SYNC.0,
+    );
+
+    const NAMES: [(Features, &'static str); 6] = [
+        (Self::RECEIPTS, "receipts"),
+        (Self::PRIVATE_ENDPOINTS, "private_endpoints"),
+        (Self::APPROVALS, "approvals"),
+        (Self::SUBSCRIPTIONS, "subscriptions"),
+        (Self::ATTESTATION, "attestation"),
+        (Self::DEVICE_SYNC, "device_sync"),
+    ];
+
+    /// The empty set.
+    pub const fn empty() -> Self {
+        Features(0)
+    }
+
+    /// Features from raw bits, unknown bits included.
+    pub const fn from_bits(bits: u64) -> Self {
+        Features(bits)
+    }
+
+    /// The raw bits.
+    pub const fn bits(self) -> u64 {
+        self.0
+    }
+
+    /// Whether every feature in `other` is in this set.
+    pub const fn contains(self, other: Features) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Whether the set is empty.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Features that protocol `version` defines.
+    pub fn for_version(version: u32) -> Self {
+        match version {
+            LEGACY_PROTOCOL_VERSION => Self::LEGACY,
+            _ => Self::ALL,
+        }
+    }
+
+    /// Names of the known features in the set, for logs and diagnostics.
+    pub fn names(self) -> Vec<&'static str> {
+        Self::NAMES
+            .iter()
+            .filter(|(feature, _)| self.contains(*feature))
+            .map(|(_, name)| *name)
+            .collect()
+    }
+}
+
+impl BitOr for Features {
+    type Output = Features;
+
+    fn bitor(self, rhs: Features) -> Features {
+        Features(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for Features {
+    type Output = Features;
+
+    fn bitand(self, rhs: Features) -> Features {
+        Features(self.0 & rhs.0)
+    }
+}
+
+impl fmt::Debug for Features {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Features({})", self.names().join(" | "))
+    }
+}
+
+/// What one side advertises in a `Hello` or `HelloAck`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Hello {
+    /// The sender's protocol version.
+    pub version: u32,
+    /// The features the sender supports.
+    pub features: Features,
+}
+
+impl Hello {
+    /// Hello for this release with the given features.
+    pub fn new(features: Features) -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            features,
+        }
+    }
+}
+
+/// The version and features agreed for a session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Negotiated {
+    /// Protocol version both sides speak.
+    pub version: u32,
+    /// Features both sides support.
+    pub features: Features,
+}
+
+impl Negotiated {
+    /// What is assumed of a peer that has not negotiated.
+    pub const fn legacy() -> Self {
+        Self {
+            version: LEGACY_PROTOCOL_VERSION,
+            features: Features::LEGACY,
+        }
+    }
+
+    /// Whether the peer understands every feature in `features`.
+    pub fn supports(&self, features: Features) -> bool {
+        self.features.contains(features)
+    }
+}
+
+/// Agree on a version and feature set from both sides' hellos.
+///
+/// The result is the same whichever side is `ours`, so both peers reach it
+/// without a further round trip.
+///
+/// # Errors
+///
+/// Returns [`InteractiveError::Protocol`] if the agreed version is below
+/// `min_version`.
+pub fn negotiate(ours: &Hello, theirs: &Hello, min_version: u32) -> Result<Negotiated> {
+    let version = ours.version.min(theirs.version);
+    check_version(version, min_version)?;
+    Ok(Negotiated {
+        version,
+        features: ours.features & theirs.features & Features::for_version(version),
+    })
+}
+
+/// Refuse `version` if it is below `min_version`.
+pub(crate) fn check_version(version: u32, min_version: u32) -> Result<()> {
+    if version < min_version {
+        return Err(InteractiveError::Protocol(format!(
+            "Protocol version {} is below the minimum {}",
+            version, min_version
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_intersects_features() {
+        let ours = Hello::new(Features::ALL);
+        let theirs = Hello::new(Features::LEGACY | Features::APPROVALS);
+
+        let negotiated = negotiate(&ours, &theirs, 0).unwrap();
+        assert_eq!(negotiated, negotiate(&theirs, &ours, 0).unwrap());
+        assert_eq!(negotiated.version, PROTOCOL_VERSION);
+        assert!(negotiated.supports(Features::APPROVALS));
+        assert!(!negotiated.supports(Features::DEVICE_SYNC));
+    }
+
+    #[test]
+    fn test_newer_peer_downgrades() {
+        let ours = Hello::new(Features::ALL);
+        // A future release with a feature we do not know
+        let theirs = Hello {
+            version: PROTOCOL_VERSION + 1,
+            features: Features::from_bits(Features::ALL.bits() | (1 << 40)),
+        };
+
+        let negotiated = negotiate(&ours, &theirs, 0).unwrap();
+        assert_eq!(negotiated.version, PROTOCOL_VERSION);
+        assert_eq!(negotiated.features, Features::ALL);
+    }
+
+    #[test]
+    fn test_version_zero_limited_to_legacy_features() {
+        let ours = Hello::new(Features::ALL);
+        let theirs = Hello {
+            version: LEGACY_PROTOCOL_VERSION,
+            features: Features::ALL,
+        };
+
+        let negotiated = negotiate(&ours, &theirs, 0).unwrap();
+        assert_eq!(negotiated, Negotiated::legacy());
+        assert!(negotiate(&ours, &theirs, 1).is_err());
+    }
+
+    #[test]
+    fn test_features_serialize_as_integer() {
+        let features = Features::RECEIPTS | Features::DEVICE_SYNC;
+        assert_eq!(serde_json::to_string(&features).unwrap(), "33");
+        assert_eq!(features.names(), vec!["receipts", "device_sync"]);
+    }
+}
@@ -0,0 +1,944 @@
+//! OpenTimestamps proofs for receipt digests.
+//!
+//! A receipt's [`digest`](crate::PaykitReceipt::digest) can be anchored in
+//! Bitcoin so that, years later, either party can show the receipt existed
+//! at a point in time without trusting the other or a server.
+//!
+//! The flow follows the OpenTimestamps protocol:
+//!
+//! 1. [`stamp`] batches digests into a Merkle tree, each leaf salted with a
+//!    random nonce so a proof reveals nothing about the other receipts in
+//!    its batch, and submits the root to one or more calendars.
+//! 2. The resulting [`DetachedTimestamp`]s end in pending attestations and
+//!    are stored as standard `.ots` files.
+//! 3. Once a calendar's transaction confirms, [`upgrade`] fetches the path
+//!    from the pending commitment to a Bitcoin block header.
+//! 4. [`DetachedTimestamp::verify`] replays the operations and checks the
+//!    result against that block's Merkle root.
+//!
+//! The `.ots` encoding is the one the reference `ots` client reads and
+//! writes, so proofs can also be checked with third-party tools. Only the
+//! operations calendars use (SHA-256, append, prepend, reverse and hexlify)
+//! are supported.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let calendar = CalendarClient::new(DEFAULT_CALENDARS[0])?;
+//! let mut proofs = ots::stamp(&[digest], &[&calendar]).await?;
+//! std::fs::write("receipt.ots", proofs[0].to_bytes()?)?;
+//!
+//! // hours later
+//! ots::upgrade(&mut proofs[0], &[&calendar]).await?;
+//! let attested = proofs[0].verify(&esplora).await?;
+//! println!("existed by block {}", attested.height);
+//! ```
+
+use crate::{InteractiveError, Result};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+/// Magic bytes that open every `.ots` file.
+pub const HEADER_MAGIC: &[u8] =
+    b"\x00OpenTimestamps\x00\x00Proof\x00\xbf\x89\xe2\xe8\x84\xe8\x92\x94";
+
+/// Major version of the `.ots` format written.
+const MAJOR_VERSION: u64 = 1;
+
+const TAG_ATTESTATION: u8 = 0x00;
+const TAG_SHA256: u8 = 0x08;
+const TAG_APPEND: u8 = 0xf0;
+const TAG_PREPEND: u8 = 0xf1;
+const TAG_REVERSE: u8 = 0xf2;
+const TAG_HEXLIFY: u8 = 0xf3;
+const TAG_FORK: u8 = 0xff;
+
+const PENDING_TAG: [u8; 8] = [0x83, 0xdf, 0xe3, 0x0d, 0x2e, 0xf9, 0x0c, 0x8e];
+const BITCOIN_TAG: [u8; 8] = [0x05, 0x88, 0x96, 0x0d, 0x73, 0xd7, 0x19, 0x01];
+
+/// Longest message an operation may produce.
+const MAX_MSG_LENGTH: usize = 4096;
+/// Longest attestation payload accepted.
+const MAX_PAYLOAD_LENGTH: usize = 8192;
+/// Longest calendar URI accepted.
+const MAX_URI_LENGTH: usize = 1000;
+/// Deepest operation chain accepted.
+const MAX_DEPTH: usize = 256;
+/// Nonce appended to each digest before it joins a batch.
+const NONCE_LENGTH: usize = 16;
+
+/// One step from a message towards an attestation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op {
+    /// SHA-256 of the message
+    Sha256,
+    /// The message followed by these bytes
+    Append(Vec<u8>),
+    /// These bytes followed by the message
+    Prepend(Vec<u8>),
+    /// The message with its bytes reversed
+    Reverse,
+    /// The message as lowercase hex
+    Hexlify,
+}
+
+impl Op {
+    /// Apply the operation to `msg`.
+    pub fn apply(&self, msg: &[u8]) -> Result<Vec<u8>> {
+        let result = match self {
+            Self::Sha256 => Sha256::digest(msg).to_vec(),
+            Self::Append(suffix) => [msg, suffix.as_slice()].concat(),
+            Self::Prepend(prefix) => [prefix.as_slice(), msg].concat(),
+            Self::Reverse => msg.iter().rev().copied().collect(),
+            Self::Hexlify => hex::encode(msg).into_bytes(),
+        };
+        if result.len() > MAX_MSG_LENGTH {
+            return Err(ots_error("operation result is too long"));
+        }
+        Ok(result)
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        match self {
+            Self::Sha256 => out.push(TAG_SHA256),
+            Self::Append(arg) => {
+                out.push(TAG_APPEND);
+                write_varbytes(out, arg);
+            }
+            Self::Prepend(arg) => {
+                out.push(TAG_PREPEND);
+                write_varbytes(out, arg);
+            }
+            Self::Reverse => out.push(TAG_REVERSE),
+            Self::Hexlify => out.push(TAG_HEXLIFY),
+        }
+    }
+
+    fn read(tag: u8, reader: &mut Reader<'_>) -> Result<Self> {
+        match tag {
+            TAG_SHA256 => Ok(Self::Sha256),
+            TAG_APPEND => Ok(Self::Append(reader.varbytes(MAX_MSG_LENGTH)?)),
+            TAG_PREPEND => Ok(Self::Prepend(reader.varbytes(MAX_MSG_LENGTH)?)),
+            TAG_REVERSE => Ok(Self::Reverse),
+            TAG_HEXLIFY => Ok(Self::Hexlify),
+            _ => Err(ots_error(&format!("unsupported operation 0x{:02x}", tag))),
+        }
+    }
+}
+
+/// A claim about when a message existed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Attestation {
+    /// Submitted to the calendar at `uri`, not yet in a block
+    Pending {
+        /// Calendar to ask for the completed timestamp
+        uri: String,
+    },
+    /// The message is the Merkle root of the Bitcoin block at `height`
+    Bitcoin {
+        /// Block height
+        height: u64,
+    },
+    /// An attestation this implementation does not know, kept as is
+    Unknown {
+        /// Attestation type tag
+        tag: [u8; 8],
+        /// Raw payload
+        payload: Vec<u8>,
+    },
+}
+
+impl Attestation {
+    fn write(&self, out: &mut Vec<u8>) {
+        let mut payload = Vec::new();
+        let tag = match self {
+            Self::Pending { uri } => {
+                write_varbytes(&mut payload, uri.as_bytes());
+                PENDING_TAG
+            }
+            Self::Bitcoin { height } => {
+                write_varuint(&mut payload, *height);
+                BITCOIN_TAG
+            }
+            Self::Unknown { tag, payload: raw } => {
+                payload.extend_from_slice(raw);
+                *tag
+            }
+        };
+        out.extend_from_slice(&tag);
+        write_varbytes(out, &payload);
+    }
+
+    fn read(reader: &mut Reader<'_>) -> Result<Self> {
+        let tag: [u8; 8] = reader
+            .bytes(8)?
+            .try_into()
+            .expect("reader returned eight bytes");
+        let payload = reader.varbytes(MAX_PAYLOAD_LENGTH)?;
+        let mut inner = Reader::new(&payload);
+        let attestation = match tag {
+            PENDING_TAG => {
+                let uri = String::from_utf8(inner.varbytes(MAX_URI_LENGTH)?)
+                    .map_err(|_| ots_error("calendar URI is not UTF-8"))?;
+                if !uri.bytes().all(|b| b.is_ascii_graphic()) {
+                    return Err(ots_error("calendar URI has invalid characters"));
+                }
+                Self::Pending { uri }
+            }
+            BITCOIN_TAG => Self::Bitcoin {
+                height: inner.varuint()?,
+            },
+            _ => return Ok(Self::Unknown { tag, payload }),
+        };
+        inner.finish()?;
+        Ok(attestation)
+    }
+}
+
+/// Operations from a message to its attestations, as a tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timestamp {
+    msg: Vec<u8>,
+    attestations: Vec<Attestation>,
+    ops: Vec<(Op, Timestamp)>,
+}
+
+impl Timestamp {
+    /// An empty timestamp for `msg`.
+    pub fn new(msg: impl Into<Vec<u8>>) -> Self {
+        Self {
+            msg: msg.into(),
+            attestations: Vec::new(),
+            ops: Vec::new(),
+        }
+    }
+
+    /// Parse a serialized timestamp for `msg`, such as a calendar response.
+    pub fn from_bytes(msg: impl Into<Vec<u8>>, bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader::new(bytes);
+        let timestamp = Self::read(&mut reader, msg.into(), MAX_DEPTH)?;
+        reader.finish()?;
+        Ok(timestamp)
+    }
+
+    /// The message this timestamp starts from.
+    pub fn msg(&self) -> &[u8] {
+        &self.msg
+    }
+
+    /// Every attestation in the tree, with the message it attests.
+    pub fn attestations(&self) -> Vec<(&[u8], &Attestation)> {
+        let mut all: Vec<_> = self
+            .attestations
+            .iter()
+            .map(|attestation| (self.msg.as_slice(), attestation))
+            .collect();
+        for (_, child) in &self.ops {
+            all.extend(child.attestations());
+        }
+        all
+    }
+
+    /// Calendars still to be asked, with the commitment each one holds.
+    pub fn pending(&self) -> Vec<(&str, &[u8])> {
+        self.attestations()
+            .into_iter()
+            .filter_map(|(msg, attestation)| match attestation {
+                Attestation::Pending { uri } => Some((uri.as_str(), msg)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Whether any path ends in a Bitcoin block.
+    pub fn is_complete(&self) -> bool {
+        self.attestations()
+            .iter()
+            .any(|(_, attestation)| matches!(attestation, Attestation::Bitcoin { .. }))
+    }
+
+    /// Add `op` after this message, or return the branch that already has it.
+    pub fn add_op(&mut self, op: Op) -> Result<&mut Timestamp> {
+        let position = match self.ops.iter().position(|(existing, _)| *existing == op) {
+            Some(position) => position,
+            None => {
+                let child = Timestamp::new(op.apply(&self.msg)?);
+                self.ops.push((op, child));
+                self.ops.len() - 1
+            }
+        };
+        Ok(&mut self.ops[position].1)
+    }
+
+    /// Attach an attestation to this message.
+    pub fn add_attestation(&mut self, attestation: Attestation) {
+        if !self.attestations.contains(&attestation) {
+            self.attestations.push(attestation);
+        }
+    }
+
+    /// Merge `other` into the node whose message is `other`'s message.
+    ///
+    /// Returns whether such a node was found.
+    pub fn merge(&mut self, other: &Timestamp) -> bool {
+        if self.msg == other.msg {
+            for attestation in &other.attestations {
+                self.add_attestation(attestation.clone());
+            }
+            for (op, child) in &other.ops {
+                match self.ops.iter_mut().find(|(existing, _)| existing == op) {
+                    Some((_, existing)) => {
+                        existing.merge(child);
+                    }
+                    None => self.ops.push((op.clone(), child.clone())),
+                }
+            }
+            return true;
+        }
+        self.ops.iter_mut().any(|(_, child)| child.merge(other))
+    }
+
+    /// Serialize without the message.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        self.write(&mut out)?;
+        Ok(out)
+    }
+
+    fn write(&self, out: &mut Vec<u8>) -> Result<()> {
+        let branches = self.attestations.len() + self.ops.len();
+        if branches == 0 {
+            return Err(ots_error("timestamp has no attestations"));
+        }
+        let mut written = 0;
+        for attestation in &self.attestations {
+            written += 1;
+            if written < branches {
+                out.push(TAG_FORK);
+            }
+            out.push(TAG_ATTESTATION);
+            attestation.write(out);
+        }
+        for (op, child) in &self.ops {
+            written += 1;
+            if written < branches {
+                out.push(TAG_FORK);
+            }
+            op.write(out);
+            child.write(out)?;
+        }
+        Ok(())
+    }
+
+    fn read(reader: &mut Reader<'_>, msg: Vec<u8>, depth: usize) -> Result<Self> {
+        if depth == 0 {
+            return Err(ots_error("timestamp is nested too deeply"));
+        }
+        let mut timestamp = Self::new(msg);
+        loop {
+            let tag = reader.byte()?;
+            let (tag, more) = match tag {
+                TAG_FORK => (reader.byte()?, true),
+                tag => (tag, false),
+            };
+            if tag == TAG_ATTESTATION {
+                timestamp.attestations.push(Attestation::read(reader)?);
+            } else {
+                let op = Op::read(tag, reader)?;
+                let child = Self::read(reader, op.apply(&timestamp.msg)?, depth - 1)?;
+                timestamp.ops.push((op, child));
+            }
+            if !more {
+                return Ok(timestamp);
+            }
+        }
+    }
+}
+
+/// A timestamp for a SHA-256 digest, as stored in a `.ots` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetachedTimestamp {
+    timestamp: Timestamp,
+}
+
+impl DetachedTimestamp {
+    /// Wrap a timestamp whose message is a SHA-256 digest.
+    pub fn new(timestamp: Timestamp) -> Result<Self> {
+        if timestamp.msg.len() != 32 {
+            return Err(ots_error(
+                "detached timestamps must start from a SHA-256 digest",
+            ));
+        }
+        Ok(Self { timestamp })
+    }
+
+    /// Parse a `.ots` file.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader::new(bytes);
+        if reader.bytes(HEADER_MAGIC.len())? != HEADER_MAGIC {
+            return Err(ots_error("not an OpenTimestamps proof"));
+        }
+        let version = reader.varuint()?;
+        if version != MAJOR_VERSION {
+            return Err(ots_error(&format!("unsupported version {}", version)));
+        }
+        if reader.byte()? != TAG_SHA256 {
+            return Err(ots_error("only SHA-256 file digests are supported"));
+        }
+        let digest = reader.bytes(32)?.to_vec();
+        let timestamp = Timestamp::read(&mut reader, digest, MAX_DEPTH)?;
+        reader.finish()?;
+        Ok(Self { timestamp })
+    }
+
+    /// Serialize as a `.ots` file.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut out = HEADER_MAGIC.to_vec();
+        write_varuint(&mut out, MAJOR_VERSION);
+        out.push(TAG_SHA256);
+        out.extend_from_slice(&self.timestamp.msg);
+        self.timestamp.write(&mut out)?;
+        Ok(out)
+    }
+
+    /// The digest this proof is for.
+    pub fn digest(&self) -> &[u8] {
+        &self.timestamp.msg
+    }
+
+    /// The operation tree.
+    pub fn timestamp(&self) -> &Timestamp {
+        &self.timestamp
+    }
+
+    /// Whether the proof reaches a Bitcoin block.
+    pub fn is_complete(&self) -> bool {
+        self.timestamp.is_complete()
+    }
+
+    /// Check the proof's Bitcoin attestations against block headers.
+    ///
+    /// Returns the earliest block whose Merkle root matches. Fails if the
+    /// proof has no Bitcoin attestation yet, or none of them match.
+    pub async fn verify(&self, headers: &dyn BlockHeaders) -> Result<BlockAttestation> {
+        let mut attested: Vec<(u64, &[u8])> = self
+            .timestamp
+            .attestations()
+            .into_iter()
+            .filter_map(|(msg, attestation)| match attestation {
+                Attestation::Bitcoin { height } => Some((*height, msg)),
+                _ => None,
+            })
+            .collect();
+        if attested.is_empty() {
+            return Err(ots_error("timestamp is not anchored in Bitcoin yet"));
+        }
+        attested.sort_by_key(|(height, _)| *height);
+
+        let mut last_error = None;
+        for (height, msg) in attested {
+            match headers.block_header(height).await {
+                Ok(header) if header.merkle_root.as_slice() == msg => {
+                    return Ok(BlockAttestation {
+                        height,
+                        time: header.time,
+                    })
+                }
+                Ok(_) => {
+                    last_error = Some(ots_error(&format!(
+                        "commitment does not match the Merkle root of block {}",
+                        height
+                    )))
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.expect("at least one attestation was checked"))
+    }
+}
+
+/// A Bitcoin block a proof was verified against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockAttestation {
+    /// Block height
+    pub height: u64,
+    /// Block header time (unix epoch); the digest existed before it
+    pub time: u64,
+}
+
+/// The parts of a block header verification needs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockHeader {
+    /// Merkle root in header (internal) byte order
+    pub merkle_root: [u8; 32],
+    /// Header time (unix epoch)
+    pub time: u64,
+}
+
+/// A source of Bitcoin block headers, such as an Esplora server.
+#[async_trait::async_trait]
+pub trait BlockHeaders: Send + Sync {
+    /// The header of the block at `height` on the best chain.
+    async fn block_header(&self, height: u64) -> Result<BlockHeader>;
+}
+
+/// An OpenTimestamps calendar server.
+#[async_trait::async_trait]
+pub trait Calendar: Send + Sync {
+    /// The calendar's URL, as it appears in its pending attestations.
+    fn url(&self) -> &str;
+
+    /// Submit a digest; returns the serialized timestamp for it.
+    async fn submit(&self, digest: &[u8]) -> Result<Vec<u8>>;
+
+    /// Fetch the completed timestamp for a commitment, if confirmed yet.
+    async fn fetch(&self, commitment: &[u8]) -> Result<Option<Vec<u8>>>;
+}
+
+#[cfg(feature = "http-executor")]
+#[async_trait::async_trait]
+impl BlockHeaders for paykit_lib::executors::EsploraExecutor {
+    async fn block_header(&self, height: u64) -> Result<BlockHeader> {
+        let hash = self.get_block_hash_at(height).await.map_err(lib_error)?;
+        let block = self.get_block(&hash).await.map_err(lib_error)?;
+        let mut merkle_root: [u8; 32] = hex::decode(&block.merkle_root)
+            .ok()
+            .and_then(|root| root.try_into().ok())
+            .ok_or_else(|| ots_error("explorer returned an invalid Merkle root"))?;
+        // Explorers display the root reversed
+        merkle_root.reverse();
+        Ok(BlockHeader {
+            merkle_root,
+            time: block.timestamp,
+        })
+    }
+}
+
+#[cfg(feature = "http-executor")]
+#[async_trait::async_trait]
+impl Calendar for paykit_lib::executors::CalendarClient {
+    fn url(&self) -> &str {
+        paykit_lib::executors::CalendarClient::url(self)
+    }
+
+    async fn submit(&self, digest: &[u8]) -> Result<Vec<u8>> {
+        paykit_lib::executors::CalendarClient::submit(self, digest)
+            .await
+            .map_err(lib_error)
+    }
+
+    async fn fetch(&self, commitment: &[u8]) -> Result<Option<Vec<u8>>> {
+        paykit_lib::executors::CalendarClient::fetch(self, commitment)
+            .await
+            .map_err(lib_error)
+    }
+}
+
+/// Digests batched into one Merkle tree, awaiting calendar timestamps.
+pub struct Batch {
+    root: [u8; 32],
+    leaves: Vec<Timestamp>,
+}
+
+impl Batch {
+    /// Salt each digest with a random nonce and build the tree.
+    pub fn new(digests: &[[u8; 32]]) -> Result<Self> {
+        if digests.is_empty() {
+            return Err(ots_error("nothing to timestamp"));
+        }
+
+        let mut leaves: Vec<Timestamp> = digests.iter().map(|d| Timestamp::new(*d)).collect();
+        let mut paths: Vec<Vec<Op>> = Vec::with_capacity(digests.len());
+        let mut level: Vec<Vec<u8>> = Vec::with_capacity(digests.len());
+        for digest in digests {
+            let mut nonce = [0u8; NONCE_LENGTH];
+            rand::rngs::OsRng.fill_bytes(&mut nonce);
+            let path = vec![Op::Append(nonce.to_vec()), Op::Sha256];
+            level.push(apply_all(digest, &path)?);
+            paths.push(path);
+        }
+
+        // Position of each leaf's ancestor in the current level
+        let mut positions: Vec<usize> = (0..digests.len()).collect();
+        while level.len() > 1 {
+            let mut next = Vec::with_capacity(level.len().div_ceil(2));
+            for pair in level.chunks(2) {
+                next.push(match pair {
+                    [left, right] => {
+                        Sha256::digest([left.as_slice(), right.as_slice()].concat()).to_vec()
+                    }
+                    [single] => single.clone(),
+                    _ => unreachable!("chunks(2) yields one or two nodes"),
+                });
+            }
+            for (path, position) in paths.iter_mut().zip(positions.iter_mut()) {
+                let sibling = *position ^ 1;
+                if sibling < level.len() {
+                    if *position % 2 == 0 {
+                        path.push(Op::Append(level[sibling].clone()));
+                    } else {
+                        path.push(Op::Prepend(level[sibling].clone()));
+                    }
+                    path.push(Op::Sha256);
+                }
+                *position /= 2;
+            }
+            level = next;
+        }
+
+        for (leaf, path) in leaves.iter_mut().zip(paths) {
+            let mut node = leaf;
+            for op in path {
+                node = node.add_op(op)?;
+            }
+        }
+        let root = level[0]
+            .clone()
+            .try_into()
+            .expect("SHA-256 output is 32 bytes");
+        Ok(Self { root, leaves })
+    }
+
+    /// The digest to submit to calendars.
+    pub fn root(&self) -> [u8; 32] {
+        self.root
+    }
+
+    /// Attach a calendar's timestamp for the root to every leaf.
+    pub fn attach(&mut self, timestamp: &Timestamp) -> Result<()> {
+        if timestamp.msg != self.root {
+            return Err(ots_error("calendar timestamp is for a different digest"));
+        }
+        for leaf in &mut self.leaves {
+            leaf.merge(timestamp);
+        }
+        Ok(())
+    }
+
+    /// One proof per digest, in the order given to [`Batch::new`].
+    pub fn into_proofs(self) -> Result<Vec<DetachedTimestamp>> {
+        self.leaves
+            .into_iter()
+            .map(DetachedTimestamp::new)
+            .collect()
+    }
+}
+
+/// Timestamp `digests` with every calendar that answers.
+///
+/// Fails only if no calendar accepts the batch.
+pub async fn stamp(
+    digests: &[[u8; 32]],
+    calendars: &[&dyn Calendar],
+) -> Result<Vec<DetachedTimestamp>> {
+    let mut batch = Batch::new(digests)?;
+    let root = batch.root();
+
+    let mut accepted = 0;
+    let mut last_error = None;
+    for calendar in calendars {
+        let attached = match calendar.submit(&root).await {
+            Ok(bytes) => Timestamp::from_bytes(root, &bytes).and_then(|t| batch.attach(&t)),
+            Err(e) => Err(e),
+        };
+        match attached {
+            Ok(()) => accepted += 1,
+            Err(e) => last_error = Some(e),
+        }
+    }
+    if accepted == 0 {
+        return Err(last_error.unwrap_or_else(|| ots_error("no calendars configured")));
+    }
+    batch.into_proofs()
+}
+
+/// Ask the calendars a proof is pending with for their completed paths.
+///
+/// Only calendars in `calendars` are contacted, whatever URI a pending
+/// attestation names. Returns whether the proof now reaches Bitcoin; a
+/// proof that already does is left unchanged.
+pub async fn upgrade(proof: &mut DetachedTimestamp, calendars: &[&dyn Calendar]) -> Result<bool> {
+    if proof.is_complete() {
+        return Ok(true);
+    }
+
+    let pending: Vec<(String, Vec<u8>)> = proof
+        .timestamp
+        .pending()
+        .into_iter()
+        .map(|(uri, commitment)| (uri.trim_end_matches('/').to_string(), commitment.to_vec()))
+        .collect();
+    for (uri, commitment) in pending {
+        let Some(calendar) = calendars
+            .iter()
+            .find(|calendar| calendar.url().trim_end_matches('/') == uri)
+        else {
+            continue;
+        };
+        if let Some(bytes) = calendar.fetch(&commitment).await? {
+            proof
+                .timestamp
+                .merge(&Timestamp::from_bytes(commitment, &bytes)?);
+        }
+    }
+    Ok(proof.is_complete())
+}
+
+fn apply_all(msg: &[u8], ops: &[Op]) -> Result<Vec<u8>> {
+    ops.iter().try_fold(msg.to_vec(), |msg, op| op.apply(&msg))
+}
+
+fn ots_error(reason: &str) -> InteractiveError {
+    InteractiveError::Protocol(format!("OpenTimestamps: {}", reason))
+}
+
+#[cfg(feature = "http-executor")]
+fn lib_error(e: paykit_lib::PaykitError) -> InteractiveError {
+    InteractiveError::Transport(e.to_string())
+}
+
+fn write_varuint(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn write_varbytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_varuint(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| ots_error("proof is truncated"))?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn varuint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(ots_error("integer is too large"))
+    }
+
+    fn varbytes(&mut self, max_len: usize) -> Result<Vec<u8>> {
+        let len = usize::try_from(self.varuint()?).unwrap_or(usize::MAX);
+        if len > max_len {
+            return Err(ots_error("field is too long"));
+        }
+        Ok(self.bytes(len)?.to_vec())
+    }
+
+    fn finish(&self) -> Result<()> {
+        if self.position != self.bytes.len() {
+            return Err(ots_error("trailing bytes after proof"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    const CALENDAR: &str = "https://calendar.example";
+    const HEIGHT: u64 = 840_000;
+
+    /// Stamps with a pending attestation, then completes every commitment
+    /// into one block.
+    struct MockCalendar {
+        commitments: Mutex<Vec<Vec<u8>>>,
+    }
+
+    impl MockCalendar {
+        fn new() -> Self {
+            Self {
+                commitments: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn completion() -> Vec<Op> {
+            vec![Op::Prepend(b"tx".to_vec()), Op::Sha256, Op::Reverse]
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Calendar for MockCalendar {
+        fn url(&self) -> &str {
+            CALENDAR
+        }
+
+        async fn submit(&self, digest: &[u8]) -> Result<Vec<u8>> {
+            let mut timestamp = Timestamp::new(digest);
+            let node = timestamp
+                .add_op(Op::Append(b"calendar".to_vec()))?
+                .add_op(Op::Sha256)?;
+            self.commitments.lock().unwrap().push(node.msg.clone());
+            node.add_attestation(Attestation::Pending {
+                uri: CALENDAR.to_string(),
+            });
+            timestamp.to_bytes()
+        }
+
+        async fn fetch(&self, commitment: &[u8]) -> Result<Option<Vec<u8>>> {
+            if !self
+                .commitments
+                .lock()
+                .unwrap()
+                .contains(&commitment.to_vec())
+            {
+                return Ok(None);
+            }
+            let mut timestamp = Timestamp::new(commitment);
+            let mut node = &mut timestamp;
+            for op in Self::completion() {
+                node = node.add_op(op)?;
+            }
+            node.add_attestation(Attestation::Bitcoin { height: HEIGHT });
+            Ok(Some(timestamp.to_bytes()?))
+        }
+    }
+
+    struct MockHeaders(HashMap<u64, [u8; 32]>);
+
+    #[async_trait::async_trait]
+    impl BlockHeaders for MockHeaders {
+        async fn block_header(&self, height: u64) -> Result<BlockHeader> {
+            let merkle_root = *self
+                .0
+                .get(&height)
+                .ok_or_else(|| InteractiveError::Transport("unknown block".into()))?;
+            Ok(BlockHeader {
+                merkle_root,
+                time: 1_713_571_767,
+            })
+        }
+    }
+
+    fn digest(data: &[u8]) -> [u8; 32] {
+        Sha256::digest(data).into()
+    }
+
+    #[test]
+    fn test_varuint_round_trip() {
+        for value in [0, 1, 127, 128, 300, HEIGHT, u64::MAX] {
+            let mut out = Vec::new();
+            write_varuint(&mut out, value);
+            assert_eq!(Reader::new(&out).varuint().unwrap(), value);
+        }
+        let mut out = Vec::new();
+        write_varuint(&mut out, 300);
+        assert_eq!(out, vec![0xac, 0x02]);
+    }
+
+    #[test]
+    fn test_batch_paths_reach_the_root() {
+        let digests: Vec<[u8; 32]> = (0u8..5).map(|i| digest(&[i])).collect();
+        let mut batch = Batch::new(&digests).unwrap();
+        let root = batch.root();
+
+        let mut calendar = Timestamp::new(root);
+        calendar.add_attestation(Attestation::Pending {
+            uri: CALENDAR.to_string(),
+        });
+        batch.attach(&calendar).unwrap();
+        assert!(batch.attach(&Timestamp::new([0u8; 32])).is_err());
+
+        let proofs = batch.into_proofs().unwrap();
+        assert_eq!(proofs.len(), 5);
+        for (proof, digest) in proofs.iter().zip(&digests) {
+            assert_eq!(proof.digest(), digest);
+            assert_eq!(proof.timestamp().pending(), vec![(CALENDAR, &root[..])]);
+            // Each proof survives the .ots encoding unchanged
+            let bytes = proof.to_bytes().unwrap();
+            assert!(bytes.starts_with(HEADER_MAGIC));
+            assert_eq!(&DetachedTimestamp::from_bytes(&bytes).unwrap(), proof);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stamp_upgrade_and_verify() {
+        let calendar = MockCalendar::new();
+        let receipt = digest(b"receipt");
+        let mut proofs = stamp(&[receipt, digest(b"other")], &[&calendar])
+            .await
+            .unwrap();
+        let mut proof = proofs.remove(0);
+        assert!(!proof.is_complete());
+
+        let headers = MockHeaders(HashMap::new());
+        assert!(proof.verify(&headers).await.is_err());
+
+        assert!(upgrade(&mut proof, &[&calendar]).await.unwrap());
+        let commitment = calendar.commitments.lock().unwrap()[0].clone();
+        let merkle_root: [u8; 32] = apply_all(&commitment, &MockCalendar::completion())
+            .unwrap()
+            .try_into()
+            .unwrap();
+
+        let headers = MockHeaders(HashMap::from([(HEIGHT, merkle_root)]));
+        let restored = DetachedTimestamp::from_bytes(&proof.to_bytes().unwrap()).unwrap();
+        assert_eq!(
+            restored.verify(&headers).await.unwrap(),
+            BlockAttestation {
+                height: HEIGHT,
+                time: 1_713_571_767,
+            }
+        );
+
+        // A different block does not verify
+        let headers = MockHeaders(HashMap::from([(HEIGHT, [7u8; 32])]));
+        assert!(restored.verify(&headers).await.is_err());
+    }
+
+    #[test]
+    fn test_rejects_malformed_proofs() {
+        assert!(DetachedTimestamp::from_bytes(b"not a proof").is_err());
+
+        let mut timestamp = Timestamp::new(digest(b"x"));
+        timestamp.add_attestation(Attestation::Bitcoin { height: 1 });
+        let mut bytes = DetachedTimestamp::new(timestamp)
+            .unwrap()
+            .to_bytes()
+            .unwrap();
+        bytes.push(0);
+        assert!(DetachedTimestamp::from_bytes(&bytes).is_err());
+        bytes.truncate(bytes.len() - 3);
+        assert!(DetachedTimestamp::from_bytes(&bytes).is_err());
+
+        // An empty timestamp cannot be written
+        assert!(Timestamp::new(digest(b"x")).to_bytes().is_err());
+    }
+}
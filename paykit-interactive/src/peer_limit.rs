@@ -0,0 +1,510 @@
+//! Per-peer message rate limits and greylisting for the receive path.
+//!
+//! [`HandshakeRateLimiter`](crate::rate_limit::HandshakeRateLimiter) and
+//! [`ConnectionLimiter`](crate::connection_limit::ConnectionLimiter) work on
+//! IP addresses before a peer has proven who it is. Once a Noise session is
+//! up, or a message has been read from a peer's inbox, the peer's key is
+//! known, and a peer spamming receipt requests can be limited no matter
+//! which addresses it connects from.
+//!
+//! [`PeerRateLimiter`] counts messages per peer key in a fixed window. A
+//! peer that keeps sending after reaching its limit is greylisted: all of
+//! its messages are refused until [`PeerLimitConfig::greylist_duration`]
+//! has passed. It also caps the handshakes in progress across all peers.
+//!
+//! Refusals are reported through [`on_event`](PeerRateLimiter::on_event)
+//! callbacks and, if attached, [`Metrics`].
+//!
+//! # Example
+//!
+//! ```
+//! use paykit_interactive::peer_limit::{PeerLimitConfig, PeerRateLimiter, PeerVerdict};
+//!
+//! let limiter = PeerRateLimiter::new(PeerLimitConfig {
+//!     max_messages_per_peer: 2,
+//!     greylist_after: 1,
+//!     ..Default::default()
+//! });
+//!
+//! assert!(limiter.check_message("peer").is_allowed());
+//! assert!(limiter.check_message("peer").is_allowed());
+//! // The third message in the window is one too many
+//! assert!(matches!(limiter.check_message("peer"), PeerVerdict::Greylisted { .. }));
+//! assert!(limiter.is_greylisted("peer"));
+//!
+//! // Handshakes are capped globally
+//! let guard = limiter.try_begin_handshake().expect("slot available");
+//! assert_eq!(limiter.handshakes_in_progress(), 1);
+//! drop(guard);
+//! ```
+
+use crate::metrics::Metrics;
+use parking_lot::{Mutex, RwLock};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Callback for rate limit events.
+pub type PeerLimitCallback = Arc<dyn Fn(&PeerLimitEvent) + Send + Sync>;
+
+/// Limits for the receive path.
+#[derive(Clone, Debug)]
+pub struct PeerLimitConfig {
+    /// Messages accepted from one peer within the window.
+    pub max_messages_per_peer: u32,
+    /// Window for per-peer message counts (default: 60 seconds).
+    pub window: Duration,
+    /// Refused messages within one window before the peer is greylisted;
+    /// 0 never greylists.
+    pub greylist_after: u32,
+    /// How long a greylisted peer's messages are refused.
+    pub greylist_duration: Duration,
+    /// Handshakes in progress at once, across all peers.
+    pub max_concurrent_handshakes: usize,
+    /// Maximum tracked peers to prevent memory exhaustion.
+    pub max_tracked_peers: usize,
+}
+
+impl Default for PeerLimitConfig {
+    fn default() -> Self {
+        Self {
+            max_messages_per_peer: 60,
+            window: Duration::from_secs(60),
+            greylist_after: 10,
+            greylist_duration: Duration::from_secs(15 * 60),
+            max_concurrent_handshakes: 64,
+            max_tracked_peers: 10_000,
+        }
+    }
+}
+
+impl PeerLimitConfig {
+    /// Strict limits for high-security deployments.
+    pub fn strict() -> Self {
+        Self {
+            max_messages_per_peer: 20,
+            greylist_after: 3,
+            greylist_duration: Duration::from_secs(60 * 60),
+            max_concurrent_handshakes: 16,
+            ..Self::default()
+        }
+    }
+
+    /// Relaxed limits for development/testing.
+    pub fn relaxed() -> Self {
+        Self {
+            max_messages_per_peer: 1_000,
+            greylist_after: 0,
+            max_concurrent_handshakes: 1_000,
+            ..Self::default()
+        }
+    }
+}
+
+/// Outcome of checking a message against the limits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PeerVerdict {
+    /// The message may be handled.
+    Allow,
+    /// The peer reached its limit for this window.
+    RateLimited {
+        /// Time until the window resets.
+        retry_after: Duration,
+    },
+    /// The peer is greylisted.
+    Greylisted {
+        /// Time until the greylisting ends.
+        retry_after: Duration,
+    },
+}
+
+impl PeerVerdict {
+    /// Check whether the message may be handled.
+    pub fn is_allowed(&self) -> bool {
+        matches!(self, PeerVerdict::Allow)
+    }
+
+    /// Time until the peer may send again; zero if allowed.
+    pub fn retry_after(&self) -> Duration {
+        match self {
+            PeerVerdict::Allow => Duration::ZERO,
+            PeerVerdict::RateLimited { retry_after } | PeerVerdict::Greylisted { retry_after } => {
+                *retry_after
+            }
+        }
+    }
+}
+
+/// Something a limit refused.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PeerLimitEvent {
+    /// A message from `peer` was refused by its rate limit.
+    RateLimited {
+        /// The peer.
+        peer: String,
+    },
+    /// `peer` kept sending past its limit and is greylisted.
+    Greylisted {
+        /// The peer.
+        peer: String,
+        /// How long its messages will be refused.
+        duration: Duration,
+    },
+    /// A handshake was refused because too many were in progress.
+    HandshakeRejected {
+        /// Handshakes in progress at the time.
+        in_progress: usize,
+    },
+}
+
+#[derive(Debug)]
+struct PeerRecord {
+    window_start: Instant,
+    count: u32,
+    violations: u32,
+    greylisted_until: Option<Instant>,
+}
+
+impl PeerRecord {
+    fn new(now: Instant) -> Self {
+        Self {
+            window_start: now,
+            count: 0,
+            violations: 0,
+            greylisted_until: None,
+        }
+    }
+
+    fn is_stale(&self, now: Instant, window: Duration) -> bool {
+        !self.greylisted_until.is_some_and(|until| now < until)
+            && now.duration_since(self.window_start) >= window
+    }
+}
+
+/// RAII guard for a handshake slot; the slot is released when dropped.
+pub struct HandshakeGuard {
+    in_progress: Arc<AtomicUsize>,
+}
+
+impl Drop for HandshakeGuard {
+    fn drop(&mut self) {
+        self.in_progress.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Per-peer message rate limiter with greylisting.
+///
+/// Peers are identified by any stable string, normally their public key.
+pub struct PeerRateLimiter {
+    config: PeerLimitConfig,
+    peers: Mutex<HashMap<String, PeerRecord>>,
+    handshakes: Arc<AtomicUsize>,
+    callbacks: RwLock<Vec<PeerLimitCallback>>,
+    metrics: Option<Arc<Metrics>>,
+}
+
+impl Default for PeerRateLimiter {
+    fn default() -> Self {
+        Self::new(PeerLimitConfig::default())
+    }
+}
+
+impl PeerRateLimiter {
+    /// Create a limiter with the given configuration.
+    pub fn new(config: PeerLimitConfig) -> Self {
+        Self {
+            config,
+            peers: Mutex::new(HashMap::new()),
+            handshakes: Arc::new(AtomicUsize::new(0)),
+            callbacks: RwLock::new(Vec::new()),
+            metrics: None,
+        }
+    }
+
+    /// Create a limiter wrapped in an Arc for sharing across tasks.
+    pub fn new_shared(config: PeerLimitConfig) -> Arc<Self> {
+        Arc::new(Self::new(config))
+    }
+
+    /// Count refusals in `metrics`.
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Get the configuration.
+    pub fn config(&self) -> &PeerLimitConfig {
+        &self.config
+    }
+
+    /// Register a callback for limit events.
+    pub fn on_event(&self, callback: PeerLimitCallback) {
+        self.callbacks.write().push(callback);
+    }
+
+    /// Check a message from `peer` and count it if allowed.
+    ///
+    /// Each refused message is a violation; after
+    /// [`PeerLimitConfig::greylist_after`] violations in one window the peer
+    /// is greylisted.
+    pub fn check_message(&self, peer: &str) -> PeerVerdict {
+        let now = Instant::now();
+        let (verdict, event) = {
+            let mut peers = self.peers.lock();
+            if peers.len() >= self.config.max_tracked_peers && !peers.contains_key(peer) {
+                let window = self.config.window;
+                peers.retain(|_, record| !record.is_stale(now, window));
+            }
+            let record = peers
+                .entry(peer.to_string())
+                .or_insert_with(|| PeerRecord::new(now));
+            self.check_record(record, peer, now)
+        };
+
+        if let Some(event) = event {
+            self.emit(&event);
+        }
+        verdict
+    }
+
+    fn check_record(
+        &self,
+        record: &mut PeerRecord,
+        peer: &str,
+        now: Instant,
+    ) -> (PeerVerdict, Option<PeerLimitEvent>) {
+        match record.greylisted_until {
+            Some(until) if now < until => {
+                return (
+                    PeerVerdict::Greylisted {
+                        retry_after: until - now,
+                    },
+                    None,
+                );
+            }
+            Some(_) => *record = PeerRecord::new(now),
+            None => {}
+        }
+
+        let elapsed = now.duration_since(record.window_start);
+        if elapsed >= self.config.window {
+            *record = PeerRecord::new(now);
+        }
+        if record.count < self.config.max_messages_per_peer {
+            record.count += 1;
+            return (PeerVerdict::Allow, None);
+        }
+
+        record.violations += 1;
+        if self.config.greylist_after > 0 && record.violations >= self.config.greylist_after {
+            let duration = self.config.greylist_duration;
+            record.greylisted_until = Some(now + duration);
+            return (
+                PeerVerdict::Greylisted {
+                    retry_after: duration,
+                },
+                Some(PeerLimitEvent::Greylisted {
+                    peer: peer.to_string(),
+                    duration,
+                }),
+            );
+        }
+        (
+            PeerVerdict::RateLimited {
+                retry_after: self.config.window.saturating_sub(elapsed),
+            },
+            Some(PeerLimitEvent::RateLimited {
+                peer: peer.to_string(),
+            }),
+        )
+    }
+
+    /// Check whether `peer` is greylisted, without counting a message.
+    pub fn is_greylisted(&self, peer: &str) -> bool {
+        let now = Instant::now();
+        self.peers
+            .lock()
+            .get(peer)
+            .and_then(|record| record.greylisted_until)
+            .is_some_and(|until| now < until)
+    }
+
+    /// Greylisted peers and the time left for each.
+    pub fn greylisted(&self) -> Vec<(String, Duration)> {
+        let now = Instant::now();
+        let mut peers: Vec<_> = self
+            .peers
+            .lock()
+            .iter()
+            .filter_map(|(peer, record)| {
+                let until = record.greylisted_until.filter(|until| now < *until)?;
+                Some((peer.clone(), until - now))
+            })
+            .collect();
+        peers.sort();
+        peers
+    }
+
+    /// Greylist `peer` for `duration`, e.g. after abuse found elsewhere.
+    pub fn greylist(&self, peer: &str, duration: Duration) {
+        let now = Instant::now();
+        self.peers
+            .lock()
+            .entry(peer.to_string())
+            .or_insert_with(|| PeerRecord::new(now))
+            .greylisted_until = Some(now + duration);
+        self.emit(&PeerLimitEvent::Greylisted {
+            peer: peer.to_string(),
+            duration,
+        });
+    }
+
+    /// Forget `peer`'s counts and lift any greylisting.
+    pub fn reset(&self, peer: &str) {
+        self.peers.lock().remove(peer);
+    }
+
+    /// Get current number of tracked peers.
+    pub fn tracked_count(&self) -> usize {
+        self.peers.lock().len()
+    }
+
+    /// Reserve a handshake slot.
+    ///
+    /// Returns `None` if [`PeerLimitConfig::max_concurrent_handshakes`] are
+    /// already in progress. Hold the guard until the handshake completes
+    /// or fails.
+    pub fn try_begin_handshake(&self) -> Option<HandshakeGuard> {
+        let max = self.config.max_concurrent_handshakes;
+        let reserved = self
+            .handshakes
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                (n < max).then_some(n + 1)
+            });
+        match reserved {
+            Ok(_) => Some(HandshakeGuard {
+                in_progress: self.handshakes.clone(),
+            }),
+            Err(in_progress) => {
+                self.emit(&PeerLimitEvent::HandshakeRejected { in_progress });
+                None
+            }
+        }
+    }
+
+    /// Handshakes currently in progress.
+    pub fn handshakes_in_progress(&self) -> usize {
+        self.handshakes.load(Ordering::Acquire)
+    }
+
+    fn emit(&self, event: &PeerLimitEvent) {
+        if let Some(metrics) = &self.metrics {
+            match event {
+                PeerLimitEvent::RateLimited { .. } => metrics.record_message_rate_limited(),
+                PeerLimitEvent::Greylisted { .. } => {
+                    metrics.record_message_rate_limited();
+                    metrics.record_peer_greylisted();
+                }
+                PeerLimitEvent::HandshakeRejected { .. } => metrics.record_handshake_rate_limited(),
+            }
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::warn!(?event, "receive limit triggered");
+
+        for callback in self.callbacks.read().iter() {
+            callback(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(max: u32, greylist_after: u32) -> PeerLimitConfig {
+        PeerLimitConfig {
+            max_messages_per_peer: max,
+            greylist_after,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_per_peer_limit() {
+        let limiter = PeerRateLimiter::new(config(2, 0));
+
+        assert!(limiter.check_message("alice").is_allowed());
+        assert!(limiter.check_message("alice").is_allowed());
+        let verdict = limiter.check_message("alice");
+        assert!(matches!(verdict, PeerVerdict::RateLimited { .. }));
+        assert!(verdict.retry_after() <= Duration::from_secs(60));
+
+        // Other peers are unaffected, and greylisting is off
+        assert!(limiter.check_message("bob").is_allowed());
+        for _ in 0..10 {
+            limiter.check_message("alice");
+        }
+        assert!(!limiter.is_greylisted("alice"));
+    }
+
+    #[test]
+    fn test_greylisting_and_events() {
+        let metrics = Arc::new(Metrics::new());
+        let limiter = PeerRateLimiter::new(PeerLimitConfig {
+            greylist_duration: Duration::from_millis(50),
+            ..config(1, 2)
+        })
+        .with_metrics(metrics.clone());
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        limiter.on_event(Arc::new(move |event| sink.lock().push(event.clone())));
+
+        assert!(limiter.check_message("spammer").is_allowed());
+        assert!(matches!(
+            limiter.check_message("spammer"),
+            PeerVerdict::RateLimited { .. }
+        ));
+        assert!(matches!(
+            limiter.check_message("spammer"),
+            PeerVerdict::Greylisted { .. }
+        ));
+        assert!(matches!(
+            limiter.check_message("spammer"),
+            PeerVerdict::Greylisted { .. }
+        ));
+        assert_eq!(limiter.greylisted().len(), 1);
+        assert_eq!(
+            events.lock()[1],
+            PeerLimitEvent::Greylisted {
+                peer: "spammer".into(),
+                duration: Duration::from_millis(50),
+            }
+        );
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.messages_rate_limited, 2);
+        assert_eq!(snapshot.peers_greylisted, 1);
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(!limiter.is_greylisted("spammer"));
+        assert!(limiter.check_message("spammer").is_allowed());
+    }
+
+    #[test]
+    fn test_concurrent_handshakes() {
+        let limiter = PeerRateLimiter::new(PeerLimitConfig {
+            max_concurrent_handshakes: 2,
+            ..Default::default()
+        });
+
+        let first = limiter.try_begin_handshake().unwrap();
+        let _second = limiter.try_begin_handshake().unwrap();
+        assert!(limiter.try_begin_handshake().is_none());
+
+        drop(first);
+        assert_eq!(limiter.handshakes_in_progress(), 1);
+        assert!(limiter.try_begin_handshake().is_some());
+    }
+}
@@ -0,0 +1,281 @@
+//! Message padding and cover traffic for Noise channels.
+//!
+//! Noise hides message contents but not their sizes or timing. A receipt
+//! request, a confirmation and an error all have recognisable lengths, so an
+//! observer of the TCP stream can tell how far a payment got. A
+//! [`PrivacyProfile`] selected per connection pads every plaintext to a fixed
+//! bucket size before encryption and, in the strict profile, asks the caller
+//! to send cover frames while idle.
+//!
+//! # Frame Format
+//!
+//! With padding enabled, each encrypted plaintext is
+//! `u32 BE payload length || payload || zero padding`, sized to the smallest
+//! bucket that fits. A frame with payload length zero is cover traffic and is
+//! discarded by the receiver. With [`PrivacyProfile::Off`] the payload is sent
+//! as-is, so both peers must choose the same profile.
+//!
+//! # Example
+//!
+//! ```rust
+//! use paykit_interactive::privacy::{pad, unpad, Frame, PrivacyProfile};
+//!
+//! let frame = pad(b"{\"type\":\"Ack\"}", PrivacyProfile::Basic);
+//! assert_eq!(frame.len(), 256);
+//! assert_eq!(
+//!     unpad(&frame, PrivacyProfile::Basic).unwrap(),
+//!     Frame::Message(b"{\"type\":\"Ack\"}".to_vec())
+//! );
+//! ```
+
+use crate::{InteractiveError, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Length of the payload length prefix inside a padded frame.
+const LENGTH_PREFIX: usize = 4;
+
+/// Bucket sizes for [`PrivacyProfile::Basic`].
+const BASIC_BUCKETS: &[usize] = &[256, 1024, 4096, 16384];
+
+/// Frame size for [`PrivacyProfile::Strict`]; larger payloads use a multiple.
+const STRICT_FRAME: usize = 4096;
+
+/// Idle interval after which a strict-profile channel should send cover traffic.
+const STRICT_COVER_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Padding and cover-traffic policy for one connection.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrivacyProfile {
+    /// No padding, no cover traffic. Wire-compatible with older peers.
+    #[default]
+    Off,
+    /// Pad to the nearest of a few bucket sizes (256 B to 16 KiB).
+    Basic,
+    /// Pad every message to a multiple of 4 KiB and send cover frames while idle.
+    Strict,
+}
+
+impl PrivacyProfile {
+    /// Whether messages are framed and padded.
+    pub fn pads(&self) -> bool {
+        !matches!(self, PrivacyProfile::Off)
+    }
+
+    /// How long a channel may stay silent before the caller should send a
+    /// cover frame, if the profile uses cover traffic.
+    pub fn cover_interval(&self) -> Option<Duration> {
+        match self {
+            PrivacyProfile::Strict => Some(STRICT_COVER_INTERVAL),
+            _ => None,
+        }
+    }
+
+    /// Size of the padded frame carrying `payload_len` bytes.
+    pub fn padded_len(&self, payload_len: usize) -> usize {
+        let framed = payload_len + LENGTH_PREFIX;
+        match self {
+            PrivacyProfile::Off => payload_len,
+            PrivacyProfile::Basic => BASIC_BUCKETS
+                .iter()
+                .copied()
+                .find(|bucket| framed <= *bucket)
+                .unwrap_or_else(|| framed.next_multiple_of(STRICT_FRAME)),
+            PrivacyProfile::Strict => framed.next_multiple_of(STRICT_FRAME),
+        }
+    }
+}
+
+impl std::fmt::Display for PrivacyProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PrivacyProfile::Off => "off",
+            PrivacyProfile::Basic => "basic",
+            PrivacyProfile::Strict => "strict",
+        })
+    }
+}
+
+impl std::str::FromStr for PrivacyProfile {
+    type Err = InteractiveError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" | "none" => Ok(PrivacyProfile::Off),
+            "basic" => Ok(PrivacyProfile::Basic),
+            "strict" => Ok(PrivacyProfile::Strict),
+            other => Err(InteractiveError::Protocol(format!(
+                "Unknown privacy profile '{}'",
+                other
+            ))),
+        }
+    }
+}
+
+/// A decoded plaintext frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Frame {
+    /// A real message payload.
+    Message(Vec<u8>),
+    /// Cover traffic, to be discarded.
+    Cover,
+}
+
+/// Frame and pad a payload for encryption.
+pub fn pad(payload: &[u8], profile: PrivacyProfile) -> Vec<u8> {
+    if !profile.pads() {
+        return payload.to_vec();
+    }
+    let mut frame = Vec::with_capacity(profile.padded_len(payload.len()));
+    frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    frame.extend_from_slice(payload);
+    frame.resize(profile.padded_len(payload.len()), 0);
+    frame
+}
+
+/// Build a cover frame for the profile.
+///
+/// Returns `None` if the profile does not pad, since an unpadded peer could
+/// not tell cover traffic from a message.
+pub fn cover_frame(profile: PrivacyProfile) -> Option<Vec<u8>> {
+    profile.pads().then(|| pad(&[], profile))
+}
+
+/// Strip padding from a decrypted frame.
+pub fn unpad(frame: &[u8], profile: PrivacyProfile) -> Result<Frame> {
+    if !profile.pads() {
+        return Ok(Frame::Message(frame.to_vec()));
+    }
+    let prefix: [u8; LENGTH_PREFIX] = frame
+        .get(..LENGTH_PREFIX)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| InteractiveError::Protocol("Padded frame too short".into()))?;
+    let len = u32::from_be_bytes(prefix) as usize;
+    if len == 0 {
+        return Ok(Frame::Cover);
+    }
+    LENGTH_PREFIX
+        .checked_add(len)
+        .and_then(|end| frame.get(LENGTH_PREFIX..end))
+        .map(|payload| Frame::Message(payload.to_vec()))
+        .ok_or_else(|| InteractiveError::Protocol("Padded frame length exceeds frame".into()))
+}
+
+/// Traffic counters for one channel, including privacy overhead.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelStats {
+    /// Profile in effect.
+    pub profile: PrivacyProfile,
+    /// Real messages sent.
+    pub messages_sent: u64,
+    /// Real messages received.
+    pub messages_received: u64,
+    /// Cover frames sent.
+    pub cover_sent: u64,
+    /// Cover frames received and discarded.
+    pub cover_received: u64,
+    /// Serialized message bytes sent, before padding.
+    pub payload_bytes_sent: u64,
+    /// Bytes written to the wire, including padding, cover, encryption and framing.
+    pub wire_bytes_sent: u64,
+    /// Serialized message bytes received, after unpadding.
+    pub payload_bytes_received: u64,
+    /// Bytes read from the wire.
+    pub wire_bytes_received: u64,
+}
+
+impl ChannelStats {
+    /// Stats for a new channel using `profile`.
+    pub fn new(profile: PrivacyProfile) -> Self {
+        Self {
+            profile,
+            ..Self::default()
+        }
+    }
+
+    /// Bytes sent beyond the payload (padding, cover traffic, encryption, framing).
+    pub fn overhead_bytes_sent(&self) -> u64 {
+        self.wire_bytes_sent.saturating_sub(self.payload_bytes_sent)
+    }
+
+    /// Wire bytes sent per payload byte; 1.0 means no overhead.
+    pub fn overhead_ratio(&self) -> f64 {
+        if self.payload_bytes_sent == 0 {
+            return if self.wire_bytes_sent == 0 {
+                1.0
+            } else {
+                f64::INFINITY
+            };
+        }
+        self.wire_bytes_sent as f64 / self.payload_bytes_sent as f64
+    }
+
+    pub(crate) fn record_sent(&mut self, payload: usize, wire: usize) {
+        self.messages_sent += 1;
+        self.payload_bytes_sent += payload as u64;
+        self.wire_bytes_sent += wire as u64;
+    }
+
+    pub(crate) fn record_cover_sent(&mut self, wire: usize) {
+        self.cover_sent += 1;
+        self.wire_bytes_sent += wire as u64;
+    }
+
+    pub(crate) fn record_received(&mut self, frame: &Frame, wire: usize) {
+        self.wire_bytes_received += wire as u64;
+        match frame {
+            Frame::Message(payload) => {
+                self.messages_received += 1;
+                self.payload_bytes_received += payload.len() as u64;
+            }
+            Frame::Cover => self.cover_received += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_padding_buckets() {
+        assert_eq!(PrivacyProfile::Off.padded_len(10), 10);
+        assert_eq!(PrivacyProfile::Basic.padded_len(10), 256);
+        assert_eq!(PrivacyProfile::Basic.padded_len(300), 1024);
+        assert_eq!(PrivacyProfile::Basic.padded_len(20_000), 20_480);
+        assert_eq!(PrivacyProfile::Strict.padded_len(10), 4096);
+        assert_eq!(PrivacyProfile::Strict.padded_len(4093), 8192);
+    }
+
+    #[test]
+    fn test_pad_roundtrip_and_cover() {
+        for profile in [PrivacyProfile::Basic, PrivacyProfile::Strict] {
+            let frame = pad(b"hello", profile);
+            assert_eq!(frame.len(), profile.padded_len(5));
+            assert_eq!(
+                unpad(&frame, profile).unwrap(),
+                Frame::Message(b"hello".to_vec())
+            );
+
+            let cover = cover_frame(profile).unwrap();
+            assert_eq!(unpad(&cover, profile).unwrap(), Frame::Cover);
+        }
+        assert!(cover_frame(PrivacyProfile::Off).is_none());
+        assert!(unpad(&[0, 0, 1, 0, 1], PrivacyProfile::Basic).is_err());
+        assert!(unpad(&[0xff, 0xff, 0xff, 0xff, 1], PrivacyProfile::Basic).is_err());
+    }
+
+    #[test]
+    fn test_stats_overhead() {
+        let mut stats = ChannelStats::new(PrivacyProfile::Basic);
+        stats.record_sent(100, 272);
+        stats.record_cover_sent(272);
+        assert_eq!(stats.overhead_bytes_sent(), 444);
+        assert!((stats.overhead_ratio() - 5.44).abs() < 1e-9);
+        assert_eq!(
+            "strict".parse::<PrivacyProfile>().unwrap(),
+            PrivacyProfile::Strict
+        );
+    }
+}
@@ -0,0 +1,409 @@
+//! Proximity Transport
+//!
+//! Abstraction over short-range links (Bluetooth LE, AWDL, Wi-Fi Direct) so two
+//! devices can run the Noise handshake and exchange receipts and private
+//! endpoints while both are offline.
+//!
+//! Host apps implement [`ProximityTransport`] on top of the platform radio API;
+//! Paykit takes care of fragmenting messages into frames that fit the link MTU
+//! and of running the Noise_IK handshake over it via [`ProximityNoiseChannel`].
+//!
+//! # Framing
+//!
+//! Every frame starts with a one-byte header. [`FRAME_MORE`] marks that further
+//! fragments of the same message follow; the final fragment has it cleared.
+//! Frames are expected to be delivered reliably and in order, which holds for
+//! BLE L2CAP channels and GATT write-with-response.
+//!
+//! # Example
+//!
+//! ```ignore
+//! // Payer (client)
+//! let transport = MyBleTransport::connect(peripheral).await?;
+//! let mut channel = ProximityNoiseChannel::connect(&client, transport, &payee_static_pk).await?;
+//! manager.initiate_payment(&mut channel, provisional_receipt).await?;
+//!
+//! // Payee (server)
+//! let transport = MyBleTransport::accept(central).await?;
+//! let (mut channel, identity) = ProximityNoiseChannel::accept(&server, transport).await?;
+//! let msg = channel.recv().await?;
+//! ```
+
+use crate::{InteractiveError, PaykitNoiseChannel, PaykitNoiseMessage, Result};
+use async_trait::async_trait;
+use pubky_noise::identity_payload::IdentityPayload;
+use pubky_noise::{NoiseClient, NoiseLink, NoiseServer, RingKeyProvider};
+use std::collections::VecDeque;
+use std::future::poll_fn;
+use std::sync::{Arc, Mutex};
+use std::task::{Poll, Waker};
+
+/// Default maximum frame size, sized for a BLE link with a 247-byte ATT MTU.
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 244;
+
+/// Frame header flag: more fragments of the current message follow.
+pub const FRAME_MORE: u8 = 0x01;
+
+/// Upper bound on a reassembled message, to bound memory use.
+pub const MAX_MESSAGE_SIZE: usize = 64 * 1024;
+
+/// A reliable, ordered, frame-oriented link between two nearby devices.
+///
+/// Implementations wrap a platform radio API. Frames passed to
+/// [`send_frame`](Self::send_frame) never exceed
+/// [`max_frame_size`](Self::max_frame_size).
+#[async_trait]
+pub trait ProximityTransport: Send {
+    /// Largest frame the link can carry in one write.
+    fn max_frame_size(&self) -> usize {
+        DEFAULT_MAX_FRAME_SIZE
+    }
+
+    /// Send a single frame.
+    async fn send_frame(&mut self, frame: Vec<u8>) -> Result<()>;
+
+    /// Receive the next frame, waiting until one is available.
+    async fn recv_frame(&mut self) -> Result<Vec<u8>>;
+}
+
+/// Send a message over a proximity transport, fragmenting as needed.
+pub async fn send_message<T: ProximityTransport + ?Sized>(
+    transport: &mut T,
+    message: &[u8],
+) -> Result<()> {
+    if message.len() > MAX_MESSAGE_SIZE {
+        return Err(InteractiveError::Transport(format!(
+            "Message of {} bytes exceeds proximity limit of {}",
+            message.len(),
+            MAX_MESSAGE_SIZE
+        )));
+    }
+
+    let chunk_size = transport.max_frame_size().saturating_sub(1);
+    if chunk_size == 0 {
+        return Err(InteractiveError::Transport(
+            "Proximity frame size too small".to_string(),
+        ));
+    }
+
+    let mut chunks = message.chunks(chunk_size).peekable();
+    if chunks.peek().is_none() {
+        return transport.send_frame(vec![0]).await;
+    }
+
+    while let Some(chunk) = chunks.next() {
+        let header = if chunks.peek().is_some() {
+            FRAME_MORE
+        } else {
+            0
+        };
+        let mut frame = Vec::with_capacity(chunk.len() + 1);
+        frame.push(header);
+        frame.extend_from_slice(chunk);
+        transport.send_frame(frame).await?;
+    }
+
+    Ok(())
+}
+
+/// Receive a message from a proximity transport, reassembling fragments.
+pub async fn recv_message<T: ProximityTransport + ?Sized>(transport: &mut T) -> Result<Vec<u8>> {
+    let mut message = Vec::new();
+
+    loop {
+        let frame = transport.recv_frame().await?;
+        let (&header, body) = frame
+            .split_first()
+            .ok_or_else(|| InteractiveError::Transport("Empty proximity frame".to_string()))?;
+
+        if message.len() + body.len() > MAX_MESSAGE_SIZE {
+            return Err(InteractiveError::Transport(
+                "Proximity message exceeds size limit".to_string(),
+            ));
+        }
+        message.extend_from_slice(body);
+
+        if header & FRAME_MORE == 0 {
+            return Ok(message);
+        }
+    }
+}
+
+// ============================================================================
+// Loopback Transport
+// ============================================================================
+
+#[derive(Default)]
+struct Queue {
+    frames: VecDeque<Vec<u8>>,
+    waker: Option<Waker>,
+    closed: bool,
+}
+
+/// In-memory [`ProximityTransport`] connecting two endpoints.
+///
+/// Useful for tests and for simulating proximity exchanges on a single device.
+/// Dropping one end closes the link for the other.
+pub struct LoopbackTransport {
+    inbox: Arc<Mutex<Queue>>,
+    outbox: Arc<Mutex<Queue>>,
+    max_frame_size: usize,
+}
+
+impl LoopbackTransport {
+    /// Create a connected pair of endpoints.
+    pub fn pair() -> (Self, Self) {
+        Self::pair_with_frame_size(DEFAULT_MAX_FRAME_SIZE)
+    }
+
+    /// Create a connected pair with a custom maximum frame size.
+    pub fn pair_with_frame_size(max_frame_size: usize) -> (Self, Self) {
+        let a = Arc::new(Mutex::new(Queue::default()));
+        let b = Arc::new(Mutex::new(Queue::default()));
+        (
+            Self {
+                inbox: a.clone(),
+                outbox: b.clone(),
+                max_frame_size,
+            },
+            Self {
+                inbox: b,
+                outbox: a,
+                max_frame_size,
+            },
+        )
+    }
+}
+
+impl Drop for LoopbackTransport {
+    fn drop(&mut self) {
+        for queue in [&self.inbox, &self.outbox] {
+            let mut queue = queue.lock().unwrap_or_else(|e| e.into_inner());
+            queue.closed = true;
+            if let Some(waker) = queue.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ProximityTransport for LoopbackTransport {
+    fn max_frame_size(&self) -> usize {
+        self.max_frame_size
+    }
+
+    async fn send_frame(&mut self, frame: Vec<u8>) -> Result<()> {
+        if frame.len() > self.max_frame_size {
+            return Err(InteractiveError::Transport(format!(
+                "Frame of {} bytes exceeds link MTU of {}",
+                frame.len(),
+                self.max_frame_size
+            )));
+        }
+
+        let mut queue = self.outbox.lock().unwrap_or_else(|e| e.into_inner());
+        if queue.closed {
+            return Err(InteractiveError::Transport("Peer disconnected".to_string()));
+        }
+        queue.frames.push_back(frame);
+        if let Some(waker) = queue.waker.take() {
+            waker.wake();
+        }
+        Ok(())
+    }
+
+    async fn recv_frame(&mut self) -> Result<Vec<u8>> {
+        poll_fn(|cx| {
+            let mut queue = self.inbox.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(frame) = queue.frames.pop_front() {
+                Poll::Ready(Ok(frame))
+            } else if queue.closed {
+                Poll::Ready(Err(InteractiveError::Transport(
+                    "Peer disconnected".to_string(),
+                )))
+            } else {
+                queue.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+// ============================================================================
+// Noise over Proximity
+// ============================================================================
+
+/// A [`PaykitNoiseChannel`] running the Noise protocol over a
+/// [`ProximityTransport`].
+///
+/// This mirrors [`PubkyNoiseChannel`](crate::transport::PubkyNoiseChannel),
+/// with length prefixes replaced by proximity framing.
+pub struct ProximityNoiseChannel<T> {
+    transport: T,
+    link: NoiseLink,
+}
+
+impl<T: ProximityTransport> ProximityNoiseChannel<T> {
+    /// Perform a client-side Noise_IK handshake over the transport.
+    ///
+    /// * `client`: The initialized NoiseClient.
+    /// * `transport`: A connected proximity link.
+    /// * `server_static_pub`: The server's static public key (32 bytes).
+    pub async fn connect<R: RingKeyProvider>(
+        client: &NoiseClient<R, ()>,
+        mut transport: T,
+        server_static_pub: &[u8; 32],
+    ) -> Result<Self> {
+        let (hs, first_msg) =
+            pubky_noise::datalink_adapter::client_start_ik_direct(client, server_static_pub, None)
+                .map_err(|e| {
+                    InteractiveError::Transport(format!("Handshake build failed: {}", e))
+                })?;
+
+        send_message(&mut transport, &first_msg).await?;
+        let response = recv_message(&mut transport).await?;
+
+        let link =
+            pubky_noise::datalink_adapter::client_complete_ik(hs, &response).map_err(|e| {
+                InteractiveError::Transport(format!("Failed to complete handshake: {}", e))
+            })?;
+
+        Ok(Self { transport, link })
+    }
+
+    /// Accept a client-side handshake over the transport.
+    ///
+    /// Returns the channel and the authenticated client identity.
+    pub async fn accept<R: RingKeyProvider>(
+        server: &NoiseServer<R, ()>,
+        mut transport: T,
+    ) -> Result<(Self, IdentityPayload)> {
+        let first_msg = recv_message(&mut transport).await?;
+
+        let (hs, identity, response) =
+            pubky_noise::datalink_adapter::server_accept_ik(server, &first_msg)
+                .map_err(|e| InteractiveError::Transport(format!("Handshake failed: {}", e)))?;
+
+        send_message(&mut transport, &response).await?;
+
+        let link = pubky_noise::datalink_adapter::server_complete_ik(hs).map_err(|e| {
+            InteractiveError::Transport(format!("Failed to complete handshake: {}", e))
+        })?;
+
+        Ok((Self { transport, link }, identity))
+    }
+
+    /// Consume the channel and return the underlying transport.
+    pub fn into_transport(self) -> T {
+        self.transport
+    }
+}
+
+#[async_trait]
+impl<T: ProximityTransport> PaykitNoiseChannel for ProximityNoiseChannel<T> {
+    async fn send(&mut self, msg: PaykitNoiseMessage) -> Result<()> {
+        let json_bytes = serde_json::to_vec(&msg)?;
+        let ciphertext = self
+            .link
+            .encrypt(&json_bytes)
+            .map_err(|e| InteractiveError::Transport(format!("Encryption failed: {}", e)))?;
+        send_message(&mut self.transport, &ciphertext).await
+    }
+
+    async fn recv(&mut self) -> Result<PaykitNoiseMessage> {
+        let ciphertext = recv_message(&mut self.transport).await?;
+        let plaintext = self
+            .link
+            .decrypt(&ciphertext)
+            .map_err(|e| InteractiveError::Transport(format!("Decryption failed: {}", e)))?;
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn test_loopback_frames() {
+        block_on(async {
+            let (mut a, mut b) = LoopbackTransport::pair();
+            a.send_frame(vec![1, 2, 3]).await.unwrap();
+            b.send_frame(vec![4]).await.unwrap();
+
+            assert_eq!(b.recv_frame().await.unwrap(), vec![1, 2, 3]);
+            assert_eq!(a.recv_frame().await.unwrap(), vec![4]);
+        });
+    }
+
+    #[test]
+    fn test_loopback_rejects_oversized_frame() {
+        block_on(async {
+            let (mut a, _b) = LoopbackTransport::pair_with_frame_size(8);
+            assert!(a.send_frame(vec![0; 9]).await.is_err());
+        });
+    }
+
+    #[test]
+    fn test_loopback_disconnect() {
+        block_on(async {
+            let (mut a, b) = LoopbackTransport::pair();
+            drop(b);
+            assert!(a.recv_frame().await.is_err());
+            assert!(a.send_frame(vec![1]).await.is_err());
+        });
+    }
+
+    #[test]
+    fn test_message_fragmentation() {
+        block_on(async {
+            let (mut a, mut b) = LoopbackTransport::pair_with_frame_size(20);
+            let message: Vec<u8> = (0..=255).collect();
+
+            send_message(&mut a, &message).await.unwrap();
+            send_message(&mut a, &[]).await.unwrap();
+
+            assert_eq!(recv_message(&mut b).await.unwrap(), message);
+            assert!(recv_message(&mut b).await.unwrap().is_empty());
+        });
+    }
+
+    #[test]
+    fn test_message_concurrent_exchange() {
+        block_on(async {
+            let (mut a, mut b) = LoopbackTransport::pair_with_frame_size(16);
+
+            let ping = async {
+                send_message(&mut a, b"ping over a tiny mtu").await.unwrap();
+                recv_message(&mut a).await.unwrap()
+            };
+            let pong = async {
+                let msg = recv_message(&mut b).await.unwrap();
+                send_message(&mut b, b"pong").await.unwrap();
+                msg
+            };
+
+            let (reply, request) = tokio::join!(ping, pong);
+            assert_eq!(request, b"ping over a tiny mtu");
+            assert_eq!(reply, b"pong");
+        });
+    }
+
+    #[test]
+    fn test_message_size_limit() {
+        block_on(async {
+            let (mut a, _b) = LoopbackTransport::pair();
+            let message = vec![0u8; MAX_MESSAGE_SIZE + 1];
+            assert!(send_message(&mut a, &message).await.is_err());
+        });
+    }
+}
@@ -0,0 +1,463 @@
+//! Receipt search and pagination.
+//!
+//! [`ReceiptQuery`] describes a filter over stored receipts plus a page size and
+//! an opaque cursor. Stores apply it through [`ReceiptQuery::apply`], which works
+//! for any receipt type implementing [`SearchableReceipt`], so the interactive
+//! [`PaykitReceipt`](crate::PaykitReceipt), the demo file store, and the mobile
+//! FFI records all page through results the same way.
+//!
+//! Results are ordered newest first (ties broken by receipt ID) and cursors are
+//! keyset based: a cursor encodes the position of the last returned receipt, so
+//! inserting new receipts between calls never duplicates or skips older entries.
+
+use crate::{InteractiveError, PaykitReceipt, Result};
+use serde::{Deserialize, Serialize};
+
+/// Default number of receipts returned per page.
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+
+/// Inclusive range of unix timestamps (seconds).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DateRange {
+    /// Earliest timestamp to include.
+    pub from: Option<i64>,
+    /// Latest timestamp to include.
+    pub to: Option<i64>,
+}
+
+impl DateRange {
+    /// Create a range between two timestamps (both inclusive).
+    pub fn new(from: Option<i64>, to: Option<i64>) -> Self {
+        Self { from, to }
+    }
+
+    /// Check whether a timestamp falls inside the range.
+    pub fn contains(&self, timestamp: i64) -> bool {
+        self.from.is_none_or(|from| timestamp >= from) && self.to.is_none_or(|to| timestamp <= to)
+    }
+}
+
+/// Read-only view of the receipt fields that can be searched.
+///
+/// Implemented for every receipt representation that supports
+/// [`ReceiptQuery`].
+pub trait SearchableReceipt {
+    /// Unique receipt identifier.
+    fn receipt_id(&self) -> &str;
+    /// Payer public key in its string form.
+    fn payer(&self) -> String;
+    /// Payee public key in its string form.
+    fn payee(&self) -> String;
+    /// Payment method identifier.
+    fn method_id(&self) -> &str;
+    /// Raw amount string, if any.
+    fn amount(&self) -> Option<&str>;
+    /// Creation timestamp (unix seconds), if the representation tracks one.
+    fn created_at(&self) -> Option<i64>;
+    /// Free text matched by [`ReceiptQuery::text`] (memo, metadata, etc.).
+    fn search_text(&self) -> String;
+}
+
+impl SearchableReceipt for PaykitReceipt {
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn payer(&self) -> String {
+        self.payer.to_string()
+    }
+
+    fn payee(&self) -> String {
+        self.payee.to_string()
+    }
+
+    fn method_id(&self) -> &str {
+        &self.method_id.0
+    }
+
+    fn amount(&self) -> Option<&str> {
+        self.amount.as_deref()
+    }
+
+    fn created_at(&self) -> Option<i64> {
+        Some(self.created_at)
+    }
+
+    fn search_text(&self) -> String {
+        format!(
+            "{} {} {}",
+            self.receipt_id,
+            self.currency.as_deref().unwrap_or_default(),
+            self.metadata
+        )
+    }
+}
+
+/// Filter and pagination parameters for receipt searches.
+///
+/// All filters are optional and combined with AND semantics.
+///
+/// # Example
+///
+/// ```
+/// use paykit_interactive::query::ReceiptQuery;
+///
+/// let query = ReceiptQuery::new()
+///     .with_method("lightning")
+///     .with_min_amount(1_000)
+///     .with_limit(20);
+/// assert_eq!(query.limit, 20);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiptQuery {
+    /// Only receipts paid by this public key.
+    pub payer: Option<String>,
+    /// Only receipts paid to this public key.
+    pub payee: Option<String>,
+    /// Only receipts for this payment method.
+    pub method: Option<String>,
+    /// Only receipts whose integer amount is at least this value.
+    ///
+    /// Receipts without an amount, or with a non-integer amount, are excluded
+    /// when this filter is set.
+    pub min_amount: Option<u64>,
+    /// Only receipts created within this range.
+    pub date_range: Option<DateRange>,
+    /// Case-insensitive substring matched against receipt ID, method and metadata.
+    pub text: Option<String>,
+    /// Maximum number of receipts per page.
+    pub limit: usize,
+    /// Cursor returned by a previous page, or `None` for the first page.
+    pub cursor: Option<String>,
+}
+
+impl Default for ReceiptQuery {
+    fn default() -> Self {
+        Self {
+            payer: None,
+            payee: None,
+            method: None,
+            min_amount: None,
+            date_range: None,
+            text: None,
+            limit: DEFAULT_PAGE_SIZE,
+            cursor: None,
+        }
+    }
+}
+
+impl ReceiptQuery {
+    /// Create a query that matches every receipt.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Filter by payer public key.
+    pub fn with_payer(mut self, payer: impl Into<String>) -> Self {
+        self.payer = Some(payer.into());
+        self
+    }
+
+    /// Filter by payee public key.
+    pub fn with_payee(mut self, payee: impl Into<String>) -> Self {
+        self.payee = Some(payee.into());
+        self
+    }
+
+    /// Filter by payment method.
+    pub fn with_method(mut self, method: impl Into<String>) -> Self {
+        self.method = Some(method.into());
+        self
+    }
+
+    /// Filter by minimum amount.
+    pub fn with_min_amount(mut self, min_amount: u64) -> Self {
+        self.min_amount = Some(min_amount);
+        self
+    }
+
+    /// Filter by creation date.
+    pub fn with_date_range(mut self, date_range: DateRange) -> Self {
+        self.date_range = Some(date_range);
+        self
+    }
+
+    /// Filter by free text.
+    pub fn with_text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// Set the page size.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Continue from a previous page.
+    pub fn with_cursor(mut self, cursor: impl Into<String>) -> Self {
+        self.cursor = Some(cursor.into());
+        self
+    }
+
+    /// Check whether a single receipt satisfies every filter.
+    ///
+    /// Pagination fields are ignored.
+    pub fn matches<R: SearchableReceipt>(&self, receipt: &R) -> bool {
+        if let Some(payer) = &self.payer {
+            if &receipt.payer() != payer {
+                return false;
+            }
+        }
+        if let Some(payee) = &self.payee {
+            if &receipt.payee() != payee {
+                return false;
+            }
+        }
+        if let Some(method) = &self.method {
+            if receipt.method_id() != method.as_str() {
+                return false;
+            }
+        }
+        if let Some(min_amount) = self.min_amount {
+            match receipt.amount().and_then(|a| a.trim().parse::<u64>().ok()) {
+                Some(amount) if amount >= min_amount => {}
+                _ => return false,
+            }
+        }
+        if let Some(range) = &self.date_range {
+            match receipt.created_at() {
+                Some(ts) if range.contains(ts) => {}
+                _ => return false,
+            }
+        }
+        if let Some(text) = &self.text {
+            let needle = text.to_lowercase();
+            let haystack = format!(
+                "{} {} {}",
+                receipt.method_id(),
+                receipt.amount().unwrap_or_default(),
+                receipt.search_text()
+            )
+            .to_lowercase();
+            if !haystack.contains(&needle) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Filter, order and paginate a set of receipts.
+    ///
+    /// Returns an error if the cursor is malformed.
+    pub fn apply<R, I>(&self, receipts: I) -> Result<ReceiptPage<R>>
+    where
+        R: SearchableReceipt,
+        I: IntoIterator<Item = R>,
+    {
+        let after = self.cursor.as_deref().map(decode_cursor).transpose()?;
+
+        let mut matched: Vec<R> = receipts.into_iter().filter(|r| self.matches(r)).collect();
+        matched.sort_by(|a, b| sort_key(a).cmp(&sort_key(b)));
+
+        let start = match &after {
+            Some(position) => matched.partition_point(|r| &sort_key(r) <= position),
+            None => 0,
+        };
+        let limit = self.limit.max(1);
+        let total_remaining = matched.len() - start;
+
+        let items: Vec<R> = matched.into_iter().skip(start).take(limit).collect();
+        let next_cursor = if total_remaining > limit {
+            items.last().map(|r| encode_cursor(&sort_key(r)))
+        } else {
+            None
+        };
+
+        Ok(ReceiptPage { items, next_cursor })
+    }
+}
+
+/// One page of receipt search results.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReceiptPage<R> {
+    /// Receipts on this page, newest first.
+    pub items: Vec<R>,
+    /// Cursor for the next page, or `None` when this is the last page.
+    pub next_cursor: Option<String>,
+}
+
+impl<R> ReceiptPage<R> {
+    /// Whether more results are available.
+    pub fn has_more(&self) -> bool {
+        self.next_cursor.is_some()
+    }
+
+    /// Convert the page items into another representation.
+    pub fn map<T>(self, f: impl FnMut(R) -> T) -> ReceiptPage<T> {
+        ReceiptPage {
+            items: self.items.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+        }
+    }
+}
+
+/// Ordering key: newest first, then by ID. Receipts without a timestamp sort last.
+type SortKey = (std::cmp::Reverse<i64>, String);
+
+fn sort_key<R: SearchableReceipt>(receipt: &R) -> SortKey {
+    (
+        std::cmp::Reverse(receipt.created_at().unwrap_or(i64::MIN)),
+        receipt.receipt_id().to_string(),
+    )
+}
+
+fn encode_cursor(key: &SortKey) -> String {
+    format!("{}:{}", key.0 .0, hex::encode(key.1.as_bytes()))
+}
+
+fn decode_cursor(cursor: &str) -> Result<SortKey> {
+    let invalid = || InteractiveError::Protocol(format!("invalid receipt cursor: {}", cursor));
+    let (ts, id_hex) = cursor.split_once(':').ok_or_else(invalid)?;
+    let ts: i64 = ts.parse().map_err(|_| invalid())?;
+    let id_bytes = hex::decode(id_hex).map_err(|_| invalid())?;
+    let id = String::from_utf8(id_bytes).map_err(|_| invalid())?;
+    Ok((std::cmp::Reverse(ts), id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct TestReceipt {
+        id: String,
+        payer: String,
+        method: String,
+        amount: Option<String>,
+        created_at: i64,
+        memo: String,
+    }
+
+    impl SearchableReceipt for TestReceipt {
+        fn receipt_id(&self) -> &str {
+            &self.id
+        }
+        fn payer(&self) -> String {
+            self.payer.clone()
+        }
+        fn payee(&self) -> String {
+            "payee".to_string()
+        }
+        fn method_id(&self) -> &str {
+            &self.method
+        }
+        fn amount(&self) -> Option<&str> {
+            self.amount.as_deref()
+        }
+        fn created_at(&self) -> Option<i64> {
+            Some(self.created_at)
+        }
+        fn search_text(&self) -> String {
+            self.memo.clone()
+        }
+    }
+
+    fn receipt(id: &str, method: &str, amount: Option<&str>, created_at: i64) -> TestReceipt {
+        TestReceipt {
+            id: id.to_string(),
+            payer: "alice".to_string(),
+            method: method.to_string(),
+            amount: amount.map(String::from),
+            created_at,
+            memo: format!("order for {}", id),
+        }
+    }
+
+    fn sample() -> Vec<TestReceipt> {
+        vec![
+            receipt("r1", "lightning", Some("500"), 100),
+            receipt("r2", "onchain", Some("50000"), 200),
+            receipt("r3", "lightning", Some("2000"), 300),
+            receipt("r4", "lightning", None, 400),
+            receipt("r5", "onchain", Some("abc"), 500),
+        ]
+    }
+
+    #[test]
+    fn test_query_filters() {
+        let page = ReceiptQuery::new()
+            .with_method("lightning")
+            .with_min_amount(1000)
+            .apply(sample())
+            .unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].id, "r3");
+
+        let page = ReceiptQuery::new()
+            .with_date_range(DateRange::new(Some(200), Some(400)))
+            .apply(sample())
+            .unwrap();
+        let ids: Vec<_> = page.items.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["r4", "r3", "r2"]);
+
+        let page = ReceiptQuery::new()
+            .with_text("ORDER FOR R2")
+            .apply(sample())
+            .unwrap();
+        assert_eq!(page.items.len(), 1);
+
+        let page = ReceiptQuery::new()
+            .with_payer("bob")
+            .apply(sample())
+            .unwrap();
+        assert!(page.items.is_empty());
+        assert!(!page.has_more());
+    }
+
+    #[test]
+    fn test_query_pagination_walks_all_pages() {
+        let query = ReceiptQuery::new().with_limit(2);
+        let first = query.apply(sample()).unwrap();
+        assert_eq!(first.items.len(), 2);
+        assert_eq!(first.items[0].id, "r5");
+
+        let second = query
+            .clone()
+            .with_cursor(first.next_cursor.clone().unwrap())
+            .apply(sample())
+            .unwrap();
+        assert_eq!(second.items[0].id, "r3");
+
+        let third = query
+            .clone()
+            .with_cursor(second.next_cursor.clone().unwrap())
+            .apply(sample())
+            .unwrap();
+        assert_eq!(third.items.len(), 1);
+        assert_eq!(third.items[0].id, "r1");
+        assert!(third.next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_query_cursor_stable_after_insert() {
+        let query = ReceiptQuery::new().with_limit(2);
+        let first = query.apply(sample()).unwrap();
+
+        let mut grown = sample();
+        grown.push(receipt("r6", "lightning", Some("1"), 600));
+        let second = query
+            .with_cursor(first.next_cursor.unwrap())
+            .apply(grown)
+            .unwrap();
+        assert_eq!(second.items[0].id, "r3");
+    }
+
+    #[test]
+    fn test_query_invalid_cursor() {
+        let result = ReceiptQuery::new()
+            .with_cursor("not-a-cursor")
+            .apply(sample());
+        assert!(result.is_err());
+    }
+}
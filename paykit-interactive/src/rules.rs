@@ -0,0 +1,610 @@
+//! Rules for answering inbound receipt requests.
+//!
+//! A payee can decide automatically what happens to each `RequestReceipt`:
+//! confirm it, hold it for review, or refuse it. Rules are checked in order
+//! and the first whose conditions all match decides; requests no rule
+//! matches get [`InboundRules::default_action`].
+//!
+//! Every decision is logged, and queued requests wait in the engine until
+//! someone [overrides](RulesEngine::override_decision) them. An override
+//! also applies when the payer retries the same request.
+//!
+//! # Example
+//!
+//! ```
+//! use paykit_interactive::rules::{InboundRules, PeerTrust, RuleAction, RulesEngine};
+//! use paykit_interactive::PaykitReceipt;
+//! use paykit_lib::MethodId;
+//!
+//! // Auto-confirm receipts under 10k sats from known contacts, queue the rest
+//! let rules = InboundRules::from_json(
+//!     r#"{
+//!         "rules": [
+//!             {
+//!                 "name": "small-from-contacts",
+//!                 "when": { "max_amount_sats": 9999, "trust": ["trusted"] },
+//!                 "action": "accept"
+//!             }
+//!         ],
+//!         "default_action": "queue"
+//!     }"#,
+//! )
+//! .unwrap();
+//! let engine = RulesEngine::new(rules);
+//!
+//! let payer = pubky::Keypair::random().public_key();
+//! let payee = pubky::Keypair::random().public_key();
+//! let request = PaykitReceipt::new(
+//!     "r1".into(),
+//!     payer.clone(),
+//!     payee,
+//!     MethodId("lightning".into()),
+//!     Some("5000".into()),
+//!     Some("SAT".into()),
+//!     serde_json::json!({}),
+//! );
+//!
+//! let decision = engine.decide(&request, &payer, PeerTrust::Trusted, 0);
+//! assert_eq!(decision.action, RuleAction::Accept);
+//! let decision = engine.decide(&request, &payer, PeerTrust::Unknown, 0);
+//! assert_eq!(decision.action, RuleAction::Queue);
+//! assert_eq!(engine.queued().len(), 1);
+//! ```
+
+use crate::{InteractiveError, PaykitReceipt, Result};
+use parking_lot::{Mutex, RwLock};
+use paykit_lib::policy::{TrustLevel, TrustPolicy};
+use paykit_lib::PublicKey;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Decisions kept in the log before the oldest are dropped.
+pub const MAX_LOGGED_DECISIONS: usize = 1000;
+
+/// What to do with an inbound receipt request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleAction {
+    /// Generate and confirm the receipt.
+    Accept,
+    /// Hold the request until someone overrides the decision.
+    Queue,
+    /// Refuse the request.
+    Reject,
+}
+
+impl RuleAction {
+    /// Get the action as a string.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleAction::Accept => "accept",
+            RuleAction::Queue => "queue",
+            RuleAction::Reject => "reject",
+        }
+    }
+}
+
+/// How the payee's trust policy treats the requesting peer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerTrust {
+    /// The peer is on the allowlist.
+    Trusted,
+    /// The peer has no trust entry.
+    Unknown,
+    /// The peer is on the blocklist.
+    Blocked,
+}
+
+impl PeerTrust {
+    /// Look `peer` up in `policy`.
+    pub fn from_policy(policy: &TrustPolicy, peer: &PublicKey) -> Self {
+        match policy.entry(&peer.to_string()).map(|e| e.level) {
+            Some(TrustLevel::Trusted) => PeerTrust::Trusted,
+            Some(TrustLevel::Blocked) => PeerTrust::Blocked,
+            None => PeerTrust::Unknown,
+        }
+    }
+}
+
+/// Conditions a request must meet for a rule to apply.
+///
+/// Unset conditions match anything; an empty condition matches every
+/// request. Amount bounds only match requests whose amount is a whole number
+/// of satoshis (currency unset, `SAT` or `SATS`).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RuleCondition {
+    /// Smallest amount matched, in satoshis (inclusive).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_amount_sats: Option<u64>,
+    /// Largest amount matched, in satoshis (inclusive).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_amount_sats: Option<u64>,
+    /// Payment methods matched.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub methods: Vec<String>,
+    /// Peers matched, as z-base-32 keys with or without `pubky://`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub peers: Vec<String>,
+    /// Trust levels matched.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub trust: Vec<PeerTrust>,
+}
+
+impl RuleCondition {
+    /// Whether a request from `peer` at `trust` meets every condition.
+    pub fn matches(&self, request: &PaykitReceipt, peer: &PublicKey, trust: PeerTrust) -> bool {
+        if self.min_amount_sats.is_some() || self.max_amount_sats.is_some() {
+            let Some(amount) = amount_sats(request) else {
+                return false;
+            };
+            if self.min_amount_sats.is_some_and(|min| amount < min)
+                || self.max_amount_sats.is_some_and(|max| amount > max)
+            {
+                return false;
+            }
+        }
+        if !self.methods.is_empty() && !self.methods.contains(&request.method_id.0) {
+            return false;
+        }
+        if !self.peers.is_empty() {
+            let peer = peer.to_string();
+            if !self.peers.iter().any(|p| strip_pubky_prefix(p) == peer) {
+                return false;
+            }
+        }
+        self.trust.is_empty() || self.trust.contains(&trust)
+    }
+}
+
+/// One rule: conditions and the action taken when they match.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InboundRule {
+    /// Name recorded with each decision the rule makes.
+    pub name: String,
+    /// Conditions the request must meet.
+    #[serde(default)]
+    pub when: RuleCondition,
+    /// Action taken when the conditions match.
+    pub action: RuleAction,
+}
+
+fn default_action() -> RuleAction {
+    RuleAction::Queue
+}
+
+/// An ordered rule set, usually loaded from JSON.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InboundRules {
+    /// Rules checked in order; the first match decides.
+    #[serde(default)]
+    pub rules: Vec<InboundRule>,
+    /// Action for requests no rule matches (queue by default).
+    #[serde(default = "default_action")]
+    pub default_action: RuleAction,
+}
+
+impl Default for InboundRules {
+    /// No rules: every request is queued for review.
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            default_action: default_action(),
+        }
+    }
+}
+
+impl InboundRules {
+    /// Parse a rule set from JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InteractiveError::Serialization`] for malformed JSON or
+    /// unknown fields, and [`InteractiveError::Protocol`] if two rules share
+    /// a name or a rule's amount bounds are inverted.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let rules: Self = serde_json::from_str(json)?;
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    /// Serialize the rule set to JSON.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Check that rule names are unique and amount bounds are ordered.
+    pub fn validate(&self) -> Result<()> {
+        let mut names = std::collections::HashSet::new();
+        for rule in &self.rules {
+            if !names.insert(rule.name.as_str()) {
+                return Err(InteractiveError::Protocol(format!(
+                    "Duplicate rule name '{}'",
+                    rule.name
+                )));
+            }
+            if let (Some(min), Some(max)) = (rule.when.min_amount_sats, rule.when.max_amount_sats) {
+                if min > max {
+                    return Err(InteractiveError::Protocol(format!(
+                        "Rule '{}' has min_amount_sats above max_amount_sats",
+                        rule.name
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The first rule matching the request, if any.
+    pub fn first_match(
+        &self,
+        request: &PaykitReceipt,
+        peer: &PublicKey,
+        trust: PeerTrust,
+    ) -> Option<&InboundRule> {
+        self.rules
+            .iter()
+            .find(|rule| rule.when.matches(request, peer, trust))
+    }
+}
+
+/// Why a decision was made.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "name", rename_all = "snake_case")]
+pub enum DecisionSource {
+    /// The named rule matched.
+    Rule(String),
+    /// No rule matched.
+    Default,
+    /// Someone overrode the decision for this receipt.
+    Override,
+}
+
+/// A decision on one receipt request.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleDecision {
+    /// The receipt requested.
+    pub receipt_id: String,
+    /// The requesting peer (z-base-32).
+    pub peer: String,
+    /// The action taken.
+    pub action: RuleAction,
+    /// What decided it.
+    pub source: DecisionSource,
+    /// Unix timestamp of the decision.
+    pub decided_at: i64,
+}
+
+/// A request held for review.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QueuedRequest {
+    /// The provisional receipt as the payer sent it.
+    pub request: PaykitReceipt,
+    /// The requesting peer.
+    pub peer: PublicKey,
+    /// The decision that queued it.
+    pub decision: RuleDecision,
+}
+
+#[derive(Default)]
+struct EngineState {
+    log: VecDeque<RuleDecision>,
+    queue: HashMap<String, QueuedRequest>,
+    overrides: HashMap<String, RuleAction>,
+}
+
+/// Applies an [`InboundRules`] set and keeps the decision log and review
+/// queue.
+///
+/// Share it with [`PaykitInteractiveManager::with_inbound_rules`](crate::PaykitInteractiveManager::with_inbound_rules)
+/// and keep a handle for the review UI.
+pub struct RulesEngine {
+    rules: RwLock<InboundRules>,
+    state: Mutex<EngineState>,
+}
+
+impl Default for RulesEngine {
+    fn default() -> Self {
+        Self::new(InboundRules::default())
+    }
+}
+
+impl RulesEngine {
+    /// Create an engine applying `rules`.
+    pub fn new(rules: InboundRules) -> Self {
+        Self {
+            rules: RwLock::new(rules),
+            state: Mutex::new(EngineState::default()),
+        }
+    }
+
+    /// Get the current rule set.
+    pub fn rules(&self) -> InboundRules {
+        self.rules.read().clone()
+    }
+
+    /// Replace the rule set.
+    ///
+    /// Queued requests and overrides are kept.
+    pub fn set_rules(&self, rules: InboundRules) -> Result<()> {
+        rules.validate()?;
+        *self.rules.write() = rules;
+        Ok(())
+    }
+
+    /// Decide what to do with a receipt request from `peer`.
+    ///
+    /// An override for the receipt ID wins over the rules. The decision is
+    /// logged, and queued requests are kept until overridden; a retried
+    /// request that is still queued is not queued twice.
+    pub fn decide(
+        &self,
+        request: &PaykitReceipt,
+        peer: &PublicKey,
+        trust: PeerTrust,
+        now: i64,
+    ) -> RuleDecision {
+        let mut state = self.state.lock();
+        let (action, source) = match state.overrides.get(&request.receipt_id) {
+            Some(action) => (*action, DecisionSource::Override),
+            None => {
+                let rules = self.rules.read();
+                match rules.first_match(request, peer, trust) {
+                    Some(rule) => (rule.action, DecisionSource::Rule(rule.name.clone())),
+                    None => (rules.default_action, DecisionSource::Default),
+                }
+            }
+        };
+
+        let decision = RuleDecision {
+            receipt_id: request.receipt_id.clone(),
+            peer: peer.to_string(),
+            action,
+            source,
+            decided_at: now,
+        };
+        if action == RuleAction::Queue {
+            state
+                .queue
+                .entry(request.receipt_id.clone())
+                .or_insert_with(|| QueuedRequest {
+                    request: request.clone(),
+                    peer: peer.clone(),
+                    decision: decision.clone(),
+                });
+        }
+        log_decision(&mut state, decision.clone());
+        decision
+    }
+
+    /// Override the decision for `receipt_id`.
+    ///
+    /// Removes the request from the review queue and returns it if it was
+    /// queued. The override is remembered, so a retry of the same request
+    /// gets `action` instead of the rules' verdict.
+    pub fn override_decision(
+        &self,
+        receipt_id: &str,
+        action: RuleAction,
+        now: i64,
+    ) -> Option<QueuedRequest> {
+        let mut state = self.state.lock();
+        state.overrides.insert(receipt_id.to_string(), action);
+        let queued = state.queue.remove(receipt_id);
+        log_decision(
+            &mut state,
+            RuleDecision {
+                receipt_id: receipt_id.to_string(),
+                peer: queued
+                    .as_ref()
+                    .map(|q| q.peer.to_string())
+                    .unwrap_or_default(),
+                action,
+                source: DecisionSource::Override,
+                decided_at: now,
+            },
+        );
+        queued
+    }
+
+    /// Forget the override for `receipt_id`, returning it if there was one.
+    pub fn clear_override(&self, receipt_id: &str) -> Option<RuleAction> {
+        self.state.lock().overrides.remove(receipt_id)
+    }
+
+    /// Get a queued request.
+    pub fn queued_request(&self, receipt_id: &str) -> Option<QueuedRequest> {
+        self.state.lock().queue.get(receipt_id).cloned()
+    }
+
+    /// List requests awaiting review, oldest first.
+    pub fn queued(&self) -> Vec<QueuedRequest> {
+        let mut queued: Vec<_> = self.state.lock().queue.values().cloned().collect();
+        queued.sort_by(|a, b| {
+            a.decision
+                .decided_at
+                .cmp(&b.decision.decided_at)
+                .then_with(|| a.decision.receipt_id.cmp(&b.decision.receipt_id))
+        });
+        queued
+    }
+
+    /// Logged decisions, oldest first; at most [`MAX_LOGGED_DECISIONS`].
+    pub fn decisions(&self) -> Vec<RuleDecision> {
+        self.state.lock().log.iter().cloned().collect()
+    }
+
+    /// Logged decisions for one receipt, oldest first.
+    pub fn decisions_for(&self, receipt_id: &str) -> Vec<RuleDecision> {
+        self.state
+            .lock()
+            .log
+            .iter()
+            .filter(|d| d.receipt_id == receipt_id)
+            .cloned()
+            .collect()
+    }
+}
+
+fn log_decision(state: &mut EngineState, decision: RuleDecision) {
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        receipt_id = %decision.receipt_id,
+        peer = %decision.peer,
+        action = decision.action.as_str(),
+        source = ?decision.source,
+        "inbound receipt request decided"
+    );
+
+    if state.log.len() == MAX_LOGGED_DECISIONS {
+        state.log.pop_front();
+    }
+    state.log.push_back(decision);
+}
+
+/// The request's amount in whole satoshis, if it is denominated in them.
+fn amount_sats(request: &PaykitReceipt) -> Option<u64> {
+    match request.currency.as_deref() {
+        None => {}
+        Some(c) if c.eq_ignore_ascii_case("SAT") || c.eq_ignore_ascii_case("SATS") => {}
+        Some(_) => return None,
+    }
+    request.amount.as_deref()?.trim().parse().ok()
+}
+
+fn strip_pubky_prefix(key: &str) -> &str {
+    let key = key.trim();
+    key.strip_prefix("pubky://").unwrap_or(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paykit_lib::MethodId;
+
+    fn request(id: &str, payer: &PublicKey, amount: &str, currency: Option<&str>) -> PaykitReceipt {
+        PaykitReceipt::new(
+            id.into(),
+            payer.clone(),
+            pubky::Keypair::random().public_key(),
+            MethodId("lightning".into()),
+            Some(amount.into()),
+            currency.map(Into::into),
+            serde_json::json!({}),
+        )
+    }
+
+    #[test]
+    fn test_first_matching_rule_decides() {
+        let vip = pubky::Keypair::random().public_key();
+        let stranger = pubky::Keypair::random().public_key();
+        let rules = InboundRules {
+            rules: vec![
+                InboundRule {
+                    name: "vip".into(),
+                    when: RuleCondition {
+                        peers: vec![format!("pubky://{}", vip)],
+                        ..Default::default()
+                    },
+                    action: RuleAction::Accept,
+                },
+                InboundRule {
+                    name: "blocked".into(),
+                    when: RuleCondition {
+                        trust: vec![PeerTrust::Blocked],
+                        ..Default::default()
+                    },
+                    action: RuleAction::Reject,
+                },
+                InboundRule {
+                    name: "small".into(),
+                    when: RuleCondition {
+                        max_amount_sats: Some(9_999),
+                        methods: vec!["lightning".into()],
+                        ..Default::default()
+                    },
+                    action: RuleAction::Accept,
+                },
+            ],
+            default_action: RuleAction::Queue,
+        };
+        let engine = RulesEngine::new(rules);
+
+        let big = request("r1", &vip, "50000", Some("SAT"));
+        let decision = engine.decide(&big, &vip, PeerTrust::Blocked, 1);
+        assert_eq!(decision.action, RuleAction::Accept);
+        assert_eq!(decision.source, DecisionSource::Rule("vip".into()));
+
+        let small = request("r2", &stranger, "500", None);
+        let decision = engine.decide(&small, &stranger, PeerTrust::Blocked, 2);
+        assert_eq!(decision.source, DecisionSource::Rule("blocked".into()));
+        assert_eq!(
+            engine
+                .decide(&small, &stranger, PeerTrust::Unknown, 3)
+                .action,
+            RuleAction::Accept
+        );
+
+        // Amount bounds never match other currencies
+        let usd = request("r3", &stranger, "5", Some("USD"));
+        let decision = engine.decide(&usd, &stranger, PeerTrust::Unknown, 4);
+        assert_eq!(decision.source, DecisionSource::Default);
+        assert_eq!(decision.action, RuleAction::Queue);
+        assert_eq!(engine.decisions().len(), 4);
+    }
+
+    #[test]
+    fn test_queue_and_override() {
+        let peer = pubky::Keypair::random().public_key();
+        let engine = RulesEngine::default();
+        let req = request("r1", &peer, "20000", None);
+
+        engine.decide(&req, &peer, PeerTrust::Unknown, 1);
+        engine.decide(&req, &peer, PeerTrust::Unknown, 2);
+        let queued = engine.queued();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].decision.decided_at, 1);
+
+        let released = engine
+            .override_decision("r1", RuleAction::Accept, 3)
+            .unwrap();
+        assert_eq!(released.request, req);
+        assert!(engine.queued().is_empty());
+
+        // The retried request now follows the override
+        let decision = engine.decide(&req, &peer, PeerTrust::Unknown, 4);
+        assert_eq!(decision.action, RuleAction::Accept);
+        assert_eq!(decision.source, DecisionSource::Override);
+        assert_eq!(engine.decisions_for("r1").len(), 4);
+    }
+
+    #[test]
+    fn test_rules_from_json() {
+        let rules = InboundRules::from_json(
+            r#"{"rules": [{"name": "any", "when": {}, "action": "reject"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(rules.default_action, RuleAction::Queue);
+        assert_eq!(
+            InboundRules::from_json(&rules.to_json().unwrap()).unwrap(),
+            rules
+        );
+
+        assert!(InboundRules::from_json(
+            r#"{"rules": [{"name": "a", "when": {"max_amount": 1}, "action": "accept"}]}"#
+        )
+        .is_err());
+        assert!(InboundRules::from_json(
+            r#"{"rules": [
+                {"name": "a", "action": "accept"},
+                {"name": "a", "action": "reject"}
+            ]}"#
+        )
+        .is_err());
+        assert!(InboundRules::from_json(
+            r#"{"rules": [{"name": "a", "when": {"min_amount_sats": 5, "max_amount_sats": 1}, "action": "accept"}]}"#
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,640 @@
+//! Registry of concurrent peer sessions.
+//!
+//! A merchant receive server holds many Noise channels at once: payers
+//! connecting in, and its own connections out to suppliers or co-signers.
+//! The [`SessionRegistry`] keeps track of them by peer and session ID,
+//! enforces [`SessionLimits`], and gives every session a send queue so other
+//! tasks can push messages (status notices, endpoint offers) to a peer
+//! without owning its channel.
+//!
+//! The connection task owns the channel through a [`PeerSession`] and serves
+//! it; any task can reach it through a cloned [`SessionHandle`]. Queued
+//! messages are sent between exchanges, never in the middle of one, so a
+//! push cannot land where the peer expects a `ConfirmReceipt`.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let registry = SessionRegistry::new_shared(SessionLimits::default());
+//!
+//! // Connection task, for each accepted channel
+//! let mut session = registry.open(peer, new_session_id(), SessionDirection::Incoming, channel)?;
+//! let handle = session.handle();
+//! tokio::select! {
+//!     result = session.run(&manager, &my_pubkey) => result?,
+//!     _ = handle.closed() => {}
+//! }
+//!
+//! // Anywhere else
+//! registry.send_to_peer(&peer, PaykitNoiseMessage::SubscriptionStatus { notice })?;
+//! registry.close_peer(&peer);
+//! ```
+
+use crate::manager::PaykitInteractiveManager;
+use crate::{chrono_now, InteractiveError, PaykitNoiseChannel, PaykitNoiseMessage, Result};
+use parking_lot::Mutex;
+use paykit_lib::PublicKey;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Limits on the sessions a registry holds.
+#[derive(Clone, Debug)]
+pub struct SessionLimits {
+    /// Maximum open sessions.
+    pub max_sessions: usize,
+    /// Maximum open sessions with one peer.
+    pub max_sessions_per_peer: usize,
+    /// Maximum serialized bytes waiting in one session's send queue.
+    pub max_queued_bytes_per_session: usize,
+    /// Maximum serialized bytes waiting in all send queues together.
+    pub max_queued_bytes: usize,
+}
+
+impl Default for SessionLimits {
+    fn default() -> Self {
+        Self {
+            max_sessions: 1000,
+            max_sessions_per_peer: 4,
+            max_queued_bytes_per_session: 256 * 1024,
+            max_queued_bytes: 64 * 1024 * 1024,
+        }
+    }
+}
+
+/// Who opened a session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SessionDirection {
+    /// The peer connected to us.
+    Incoming,
+    /// We connected to the peer.
+    Outgoing,
+}
+
+/// Snapshot of one session, for dashboards and logs.
+#[derive(Clone, Debug)]
+pub struct SessionInfo {
+    /// The peer on the other end.
+    pub peer: PublicKey,
+    /// The session ID.
+    pub session_id: String,
+    /// Who opened the session.
+    pub direction: SessionDirection,
+    /// When the session was opened (unix seconds).
+    pub opened_at: i64,
+    /// Messages waiting in the send queue.
+    pub queued_messages: usize,
+    /// Serialized size of the queued messages.
+    pub queued_bytes: usize,
+    /// Messages sent, replies and queued messages alike.
+    pub messages_sent: u64,
+    /// Messages received from the peer.
+    pub messages_received: u64,
+}
+
+/// Generate a random session ID.
+pub fn new_session_id() -> String {
+    hex::encode(rand::random::<[u8; 16]>())
+}
+
+type SessionKey = (String, String);
+
+struct SessionState {
+    peer: PublicKey,
+    session_id: String,
+    direction: SessionDirection,
+    opened_at: i64,
+    queue: Mutex<VecDeque<(PaykitNoiseMessage, usize)>>,
+    queued_bytes: AtomicUsize,
+    messages_sent: AtomicU64,
+    messages_received: AtomicU64,
+    closed: AtomicBool,
+    close_notify: Notify,
+}
+
+impl SessionState {
+    fn key(&self) -> SessionKey {
+        (self.peer.to_string(), self.session_id.clone())
+    }
+}
+
+struct RegistryInner {
+    limits: SessionLimits,
+    sessions: Mutex<HashMap<SessionKey, Arc<SessionState>>>,
+    queued_bytes: AtomicUsize,
+}
+
+impl RegistryInner {
+    /// Mark `state` closed, drop its queue and unregister it.
+    fn close(&self, state: &Arc<SessionState>) {
+        if state.closed.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let dropped: usize = state.queue.lock().drain(..).map(|(_, size)| size).sum();
+        state.queued_bytes.fetch_sub(dropped, Ordering::SeqCst);
+        self.queued_bytes.fetch_sub(dropped, Ordering::SeqCst);
+
+        let mut sessions = self.sessions.lock();
+        if sessions
+            .get(&state.key())
+            .is_some_and(|current| Arc::ptr_eq(current, state))
+        {
+            sessions.remove(&state.key());
+        }
+        drop(sessions);
+        state.close_notify.notify_waiters();
+    }
+}
+
+/// Registry of open peer sessions.
+pub struct SessionRegistry {
+    inner: Arc<RegistryInner>,
+}
+
+impl SessionRegistry {
+    /// Create a registry with the given limits.
+    pub fn new(limits: SessionLimits) -> Self {
+        Self {
+            inner: Arc::new(RegistryInner {
+                limits,
+                sessions: Mutex::new(HashMap::new()),
+                queued_bytes: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Create a registry wrapped in an Arc for sharing.
+    pub fn new_shared(limits: SessionLimits) -> Arc<Self> {
+        Arc::new(Self::new(limits))
+    }
+
+    /// Register a session with `peer` over `channel`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InteractiveError::LimitExceeded`] if the registry or the
+    /// peer is at its session limit, and [`InteractiveError::Protocol`] if
+    /// the peer already has a session with this ID.
+    pub fn open<C: PaykitNoiseChannel>(
+        &self,
+        peer: PublicKey,
+        session_id: impl Into<String>,
+        direction: SessionDirection,
+        channel: C,
+    ) -> Result<PeerSession<C>> {
+        let state = Arc::new(SessionState {
+            peer,
+            session_id: session_id.into(),
+            direction,
+            opened_at: chrono_now(),
+            queue: Mutex::new(VecDeque::new()),
+            queued_bytes: AtomicUsize::new(0),
+            messages_sent: AtomicU64::new(0),
+            messages_received: AtomicU64::new(0),
+            closed: AtomicBool::new(false),
+            close_notify: Notify::new(),
+        });
+        let key = state.key();
+
+        let mut sessions = self.inner.sessions.lock();
+        let limits = &self.inner.limits;
+        if sessions.len() >= limits.max_sessions {
+            return Err(InteractiveError::LimitExceeded(format!(
+                "{} sessions open",
+                sessions.len()
+            )));
+        }
+        let with_peer = sessions.keys().filter(|(peer, _)| *peer == key.0).count();
+        if with_peer >= limits.max_sessions_per_peer {
+            return Err(InteractiveError::LimitExceeded(format!(
+                "{} sessions open with {}",
+                with_peer, key.0
+            )));
+        }
+        if sessions.contains_key(&key) {
+            return Err(InteractiveError::Protocol(format!(
+                "Session {} already open with {}",
+                key.1, key.0
+            )));
+        }
+        sessions.insert(key, state.clone());
+        drop(sessions);
+
+        Ok(PeerSession {
+            handle: SessionHandle {
+                state,
+                registry: self.inner.clone(),
+            },
+            channel,
+        })
+    }
+
+    /// Handle to the session with `peer` and `session_id`, if open.
+    pub fn get(&self, peer: &PublicKey, session_id: &str) -> Option<SessionHandle> {
+        let key = (peer.to_string(), session_id.to_string());
+        self.inner
+            .sessions
+            .lock()
+            .get(&key)
+            .map(|state| SessionHandle {
+                state: state.clone(),
+                registry: self.inner.clone(),
+            })
+    }
+
+    /// Handles to every open session with `peer`, oldest first.
+    pub fn peer_sessions(&self, peer: &PublicKey) -> Vec<SessionHandle> {
+        let peer = peer.to_string();
+        let mut handles: Vec<SessionHandle> = self
+            .inner
+            .sessions
+            .lock()
+            .iter()
+            .filter(|((p, _), _)| *p == peer)
+            .map(|(_, state)| SessionHandle {
+                state: state.clone(),
+                registry: self.inner.clone(),
+            })
+            .collect();
+        handles.sort_by_key(|h| h.state.opened_at);
+        handles
+    }
+
+    /// Queue `msg` on the newest open session with `peer`.
+    ///
+    /// Returns the session ID it was queued on.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InteractiveError::Transport`] if the peer has no open
+    /// session, and [`InteractiveError::LimitExceeded`] if the queue is full.
+    pub fn send_to_peer(&self, peer: &PublicKey, msg: PaykitNoiseMessage) -> Result<String> {
+        let handle = self
+            .peer_sessions(peer)
+            .pop()
+            .ok_or_else(|| InteractiveError::Transport(format!("No open session with {}", peer)))?;
+        handle.enqueue(msg)?;
+        Ok(handle.session_id().to_string())
+    }
+
+    /// Snapshots of every open session.
+    pub fn sessions(&self) -> Vec<SessionInfo> {
+        self.inner
+            .sessions
+            .lock()
+            .values()
+            .map(|state| info(state))
+            .collect()
+    }
+
+    /// Number of open sessions.
+    pub fn len(&self) -> usize {
+        self.inner.sessions.lock().len()
+    }
+
+    /// Whether no sessions are open.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Serialized bytes waiting in all send queues.
+    pub fn queued_bytes(&self) -> usize {
+        self.inner.queued_bytes.load(Ordering::SeqCst)
+    }
+
+    /// Close the session with `peer` and `session_id`.
+    ///
+    /// Returns whether it was open.
+    pub fn close(&self, peer: &PublicKey, session_id: &str) -> bool {
+        match self.get(peer, session_id) {
+            Some(handle) => {
+                handle.close();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Close every session with `peer`, returning how many were open.
+    pub fn close_peer(&self, peer: &PublicKey) -> usize {
+        let handles = self.peer_sessions(peer);
+        for handle in &handles {
+            handle.close();
+        }
+        handles.len()
+    }
+
+    /// Close every session, e.g. on shutdown, returning how many were open.
+    pub fn close_all(&self) -> usize {
+        let states: Vec<Arc<SessionState>> = self.inner.sessions.lock().values().cloned().collect();
+        for state in &states {
+            self.inner.close(state);
+        }
+        states.len()
+    }
+}
+
+fn info(state: &SessionState) -> SessionInfo {
+    SessionInfo {
+        peer: state.peer.clone(),
+        session_id: state.session_id.clone(),
+        direction: state.direction,
+        opened_at: state.opened_at,
+        queued_messages: state.queue.lock().len(),
+        queued_bytes: state.queued_bytes.load(Ordering::SeqCst),
+        messages_sent: state.messages_sent.load(Ordering::SeqCst),
+        messages_received: state.messages_received.load(Ordering::SeqCst),
+    }
+}
+
+/// Shared handle to a session, for queueing messages and closing it.
+#[derive(Clone)]
+pub struct SessionHandle {
+    state: Arc<SessionState>,
+    registry: Arc<RegistryInner>,
+}
+
+impl SessionHandle {
+    /// The peer on the other end.
+    pub fn peer(&self) -> &PublicKey {
+        &self.state.peer
+    }
+
+    /// The session ID.
+    pub fn session_id(&self) -> &str {
+        &self.state.session_id
+    }
+
+    /// Snapshot of the session.
+    pub fn info(&self) -> SessionInfo {
+        info(&self.state)
+    }
+
+    /// Queue `msg` to be sent after the current exchange.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InteractiveError::Transport`] if the session is closed, and
+    /// [`InteractiveError::LimitExceeded`] if the session's queue or the
+    /// registry's total queue would exceed its byte limit.
+    pub fn enqueue(&self, msg: PaykitNoiseMessage) -> Result<()> {
+        let size = serde_json::to_vec(&msg)?.len();
+        let limits = &self.registry.limits;
+
+        let mut queue = self.state.queue.lock();
+        if self.is_closed() {
+            return Err(InteractiveError::Transport("Session closed".into()));
+        }
+        if self.state.queued_bytes.load(Ordering::SeqCst) + size
+            > limits.max_queued_bytes_per_session
+        {
+            return Err(InteractiveError::LimitExceeded(
+                "Session send queue full".into(),
+            ));
+        }
+        let total = self.registry.queued_bytes.fetch_add(size, Ordering::SeqCst);
+        if total + size > limits.max_queued_bytes {
+            self.registry.queued_bytes.fetch_sub(size, Ordering::SeqCst);
+            return Err(InteractiveError::LimitExceeded(
+                "Send queues use too much memory".into(),
+            ));
+        }
+        self.state.queued_bytes.fetch_add(size, Ordering::SeqCst);
+        queue.push_back((msg, size));
+        Ok(())
+    }
+
+    /// Close the session: drop its queue and unregister it.
+    ///
+    /// The [`PeerSession`] stops at its next exchange boundary; tasks
+    /// awaiting [`closed`](Self::closed) are woken at once.
+    pub fn close(&self) {
+        self.registry.close(&self.state);
+    }
+
+    /// Whether the session has been closed.
+    pub fn is_closed(&self) -> bool {
+        self.state.closed.load(Ordering::SeqCst)
+    }
+
+    /// Wait until the session is closed.
+    pub async fn closed(&self) {
+        loop {
+            let notified = self.state.close_notify.notified();
+            if self.is_closed() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    fn pop(&self) -> Option<PaykitNoiseMessage> {
+        let (msg, size) = self.state.queue.lock().pop_front()?;
+        self.state.queued_bytes.fetch_sub(size, Ordering::SeqCst);
+        self.registry.queued_bytes.fetch_sub(size, Ordering::SeqCst);
+        Some(msg)
+    }
+}
+
+/// A registered session and its channel, owned by the connection task.
+///
+/// Dropping it closes the session.
+pub struct PeerSession<C> {
+    handle: SessionHandle,
+    channel: C,
+}
+
+impl<C: PaykitNoiseChannel> PeerSession<C> {
+    /// A handle other tasks can use to reach this session.
+    pub fn handle(&self) -> SessionHandle {
+        self.handle.clone()
+    }
+
+    /// The channel, for flows this side starts such as
+    /// [`PaykitInteractiveManager::initiate_payment`].
+    ///
+    /// Call [`send_queued`](Self::send_queued) after the flow completes.
+    pub fn channel(&mut self) -> &mut C {
+        &mut self.channel
+    }
+
+    /// Negotiate the protocol version and features with the peer; see
+    /// [`PaykitInteractiveManager::negotiate`].
+    ///
+    /// Outgoing sessions call this before anything else. Incoming sessions
+    /// answer the peer's hello in [`run`](Self::run).
+    pub async fn negotiate(
+        &mut self,
+        manager: &PaykitInteractiveManager,
+    ) -> Result<crate::negotiation::Negotiated> {
+        let peer = self.handle.state.peer.clone();
+        let negotiated = manager.negotiate(&mut self.channel, &peer).await?;
+        self.handle
+            .state
+            .messages_sent
+            .fetch_add(1, Ordering::SeqCst);
+        self.handle
+            .state
+            .messages_received
+            .fetch_add(1, Ordering::SeqCst);
+        Ok(negotiated)
+    }
+
+    /// Send every queued message, returning how many were sent.
+    pub async fn send_queued(&mut self) -> Result<usize> {
+        let mut sent = 0;
+        while let Some(msg) = self.handle.pop() {
+            self.channel.send(msg).await?;
+            self.handle
+                .state
+                .messages_sent
+                .fetch_add(1, Ordering::SeqCst);
+            sent += 1;
+        }
+        Ok(sent)
+    }
+
+    /// Serve one message from the peer with `manager`.
+    ///
+    /// Queued messages are sent before waiting and after replying. Returns
+    /// `false` once the session is closed.
+    pub async fn serve_next(
+        &mut self,
+        manager: &PaykitInteractiveManager,
+        my_pubkey: &PublicKey,
+    ) -> Result<bool> {
+        self.send_queued().await?;
+        if self.handle.is_closed() {
+            return Ok(false);
+        }
+
+        let msg = self.channel.recv().await?;
+        self.handle
+            .state
+            .messages_received
+            .fetch_add(1, Ordering::SeqCst);
+        let peer = self.handle.state.peer.clone();
+        if let Some(reply) = manager.handle_message(msg, &peer, my_pubkey).await? {
+            self.channel.send(reply).await?;
+            self.handle
+                .state
+                .messages_sent
+                .fetch_add(1, Ordering::SeqCst);
+        }
+
+        self.send_queued().await?;
+        Ok(!self.handle.is_closed())
+    }
+
+    /// Serve the peer until the session is closed or the channel fails.
+    pub async fn run(
+        &mut self,
+        manager: &PaykitInteractiveManager,
+        my_pubkey: &PublicKey,
+    ) -> Result<()> {
+        while self.serve_next(manager, my_pubkey).await? {}
+        Ok(())
+    }
+}
+
+impl<C> Drop for PeerSession<C> {
+    fn drop(&mut self) {
+        self.handle.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    struct NullChannel;
+
+    #[async_trait]
+    impl PaykitNoiseChannel for NullChannel {
+        async fn send(&mut self, _msg: PaykitNoiseMessage) -> Result<()> {
+            Ok(())
+        }
+
+        async fn recv(&mut self) -> Result<PaykitNoiseMessage> {
+            Err(InteractiveError::Transport("Channel closed".into()))
+        }
+    }
+
+    fn peer() -> PublicKey {
+        pubky::Keypair::random().public_key()
+    }
+
+    #[test]
+    fn test_session_limits() {
+        let registry = SessionRegistry::new(SessionLimits {
+            max_sessions: 3,
+            max_sessions_per_peer: 2,
+            ..Default::default()
+        });
+        let (alice, bob) = (peer(), peer());
+
+        let _a1 = registry
+            .open(alice.clone(), "a1", SessionDirection::Incoming, NullChannel)
+            .unwrap();
+        assert!(matches!(
+            registry.open(alice.clone(), "a1", SessionDirection::Incoming, NullChannel),
+            Err(InteractiveError::Protocol(_))
+        ));
+        let a2 = registry
+            .open(alice.clone(), "a2", SessionDirection::Outgoing, NullChannel)
+            .unwrap();
+        assert!(matches!(
+            registry.open(alice.clone(), "a3", SessionDirection::Incoming, NullChannel),
+            Err(InteractiveError::LimitExceeded(_))
+        ));
+        let _b1 = registry
+            .open(bob.clone(), "b1", SessionDirection::Incoming, NullChannel)
+            .unwrap();
+        assert!(matches!(
+            registry.open(bob, "b2", SessionDirection::Incoming, NullChannel),
+            Err(InteractiveError::LimitExceeded(_))
+        ));
+
+        // Dropping a session frees its slot
+        drop(a2);
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.close_peer(&alice), 1);
+        assert_eq!(registry.close_all(), 1);
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn test_queue_memory_limits() {
+        let registry = SessionRegistry::new(SessionLimits {
+            max_queued_bytes_per_session: 64,
+            ..Default::default()
+        });
+        let alice = peer();
+        let session = registry
+            .open(alice.clone(), "a1", SessionDirection::Incoming, NullChannel)
+            .unwrap();
+
+        // {"type":"Ack"} is 14 bytes
+        for _ in 0..4 {
+            registry
+                .send_to_peer(&alice, PaykitNoiseMessage::Ack)
+                .unwrap();
+        }
+        assert!(matches!(
+            registry.send_to_peer(&alice, PaykitNoiseMessage::Ack),
+            Err(InteractiveError::LimitExceeded(_))
+        ));
+        assert_eq!(registry.queued_bytes(), 56);
+
+        // Closing releases the queued bytes and refuses new messages
+        let handle = session.handle();
+        handle.close();
+        assert_eq!(registry.queued_bytes(), 0);
+        assert!(handle.enqueue(PaykitNoiseMessage::Ack).is_err());
+        assert!(registry
+            .send_to_peer(&alice, PaykitNoiseMessage::Ack)
+            .is_err());
+    }
+}
@@ -5,15 +5,20 @@
 //!
 //! # Thread Safety
 //!
-//! The status tracker uses `RwLock` for thread-safe access. Public methods
-//! will panic if the internal lock is poisoned (which only happens if a thread
-//! panics while holding the lock).
+//! The status tracker uses `parking_lot::RwLock`, which cannot be poisoned.
+//! Locks are released before callbacks run, so a callback may safely call
+//! back into the tracker.
+
+mod policy;
+
+pub use policy::{amount_in_sats, ConfirmationPolicy, ConfirmationTier};
 
 use crate::PaykitReceipt;
+use parking_lot::RwLock;
 use paykit_lib::MethodId;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::sync::Arc;
 
 /// Payment status states.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -101,6 +106,8 @@ impl PaymentStatusInfo {
     }
 
     /// Update confirmations.
+    ///
+    /// A `required` of zero finalizes the payment as soon as it is observed.
     pub fn update_confirmations(&mut self, confirmations: u64, required: u64) {
         self.confirmations = Some(confirmations);
         self.required_confirmations = Some(required);
@@ -126,9 +133,10 @@ impl PaymentStatusInfo {
             PaymentStatus::Pending => 0.0,
             PaymentStatus::Processing => 25.0,
             PaymentStatus::Confirmed => {
-                if let (Some(confs), Some(required)) =
-                    (self.confirmations, self.required_confirmations)
-                {
+                if let (Some(confs), Some(required)) = (
+                    self.confirmations,
+                    self.required_confirmations.filter(|r| *r > 0),
+                ) {
                     50.0 + (confs as f64 / required as f64) * 50.0
                 } else {
                     75.0
@@ -149,29 +157,65 @@ pub struct PaymentStatusTracker {
     statuses: RwLock<HashMap<String, PaymentStatusInfo>>,
     /// Callbacks for status changes.
     callbacks: RwLock<Vec<StatusCallback>>,
+    /// Policy deciding when payments become final.
+    confirmation_policy: RwLock<ConfirmationPolicy>,
 }
 
 impl PaymentStatusTracker {
-    /// Create a new tracker.
+    /// Create a new tracker with the default confirmation policy.
     pub fn new() -> Self {
+        Self::with_confirmation_policy(ConfirmationPolicy::default())
+    }
+
+    /// Create a new tracker with a custom confirmation policy.
+    pub fn with_confirmation_policy(policy: ConfirmationPolicy) -> Self {
         Self {
             statuses: RwLock::new(HashMap::new()),
             callbacks: RwLock::new(Vec::new()),
+            confirmation_policy: RwLock::new(policy),
         }
     }
 
+    /// Replace the confirmation policy.
+    ///
+    /// Only affects payments tracked after the call.
+    pub fn set_confirmation_policy(&self, policy: ConfirmationPolicy) {
+        let mut current = self.confirmation_policy.write();
+        *current = policy;
+    }
+
+    /// Get a copy of the current confirmation policy.
+    pub fn confirmation_policy(&self) -> ConfirmationPolicy {
+        self.confirmation_policy.read().clone()
+    }
+
     /// Register a callback for status changes.
     pub fn on_status_change(&self, callback: StatusCallback) {
-        let mut callbacks = self.callbacks.write().unwrap_or_else(|e| e.into_inner());
+        let mut callbacks = self.callbacks.write();
         callbacks.push(callback);
     }
 
-    /// Create a new pending status for a receipt.
+    /// Create a new pending status for a receipt from an untrusted peer.
     pub fn track(&self, receipt: &PaykitReceipt) {
-        let status = PaymentStatusInfo::pending(&receipt.receipt_id, receipt.method_id.clone());
+        self.track_from_peer(receipt, false);
+    }
+
+    /// Create a new pending status for a receipt.
+    ///
+    /// The required confirmations are fixed from the confirmation policy at
+    /// this point, using the receipt amount and whether the peer is trusted.
+    pub fn track_from_peer(&self, receipt: &PaykitReceipt, trusted: bool) {
+        let mut status = PaymentStatusInfo::pending(&receipt.receipt_id, receipt.method_id.clone());
+        let amount_sats = amount_in_sats(receipt.amount.as_deref(), receipt.currency.as_deref());
+        let required = self.confirmation_policy.read().required_confirmations(
+            &receipt.method_id,
+            amount_sats,
+            trusted,
+        );
+        status.required_confirmations = Some(required);
 
         {
-            let mut statuses = self.statuses.write().unwrap_or_else(|e| e.into_inner());
+            let mut statuses = self.statuses.write();
             statuses.insert(receipt.receipt_id.clone(), status.clone());
         }
 
@@ -180,13 +224,13 @@ impl PaymentStatusTracker {
 
     /// Get status for a receipt.
     pub fn get(&self, receipt_id: &str) -> Option<PaymentStatusInfo> {
-        let statuses = self.statuses.read().unwrap_or_else(|e| e.into_inner());
+        let statuses = self.statuses.read();
         statuses.get(receipt_id).cloned()
     }
 
     /// Update status for a receipt.
     pub fn update(&self, receipt_id: &str, new_status: PaymentStatus) -> Option<PaymentStatusInfo> {
-        let mut statuses = self.statuses.write().unwrap_or_else(|e| e.into_inner());
+        let mut statuses = self.statuses.write();
 
         if let Some(status) = statuses.get_mut(receipt_id) {
             status.update(new_status);
@@ -199,16 +243,25 @@ impl PaymentStatusTracker {
         }
     }
 
-    /// Update confirmations for a receipt.
+    /// Record observed confirmations, finalizing according to the policy.
+    ///
+    /// Uses the requirement fixed when the payment was tracked, falling back
+    /// to the policy default for the method if none was recorded.
     pub fn update_confirmations(
         &self,
         receipt_id: &str,
         confirmations: u64,
-        required: u64,
     ) -> Option<PaymentStatusInfo> {
-        let mut statuses = self.statuses.write().unwrap_or_else(|e| e.into_inner());
+        let mut statuses = self.statuses.write();
 
         if let Some(status) = statuses.get_mut(receipt_id) {
+            let required = status.required_confirmations.unwrap_or_else(|| {
+                self.confirmation_policy.read().required_confirmations(
+                    &status.method_id,
+                    None,
+                    false,
+                )
+            });
             status.update_confirmations(confirmations, required);
             let status_clone = status.clone();
             drop(statuses);
@@ -225,7 +278,7 @@ impl PaymentStatusTracker {
         receipt_id: &str,
         error: impl Into<String>,
     ) -> Option<PaymentStatusInfo> {
-        let mut statuses = self.statuses.write().unwrap_or_else(|e| e.into_inner());
+        let mut statuses = self.statuses.write();
 
         if let Some(status) = statuses.get_mut(receipt_id) {
             status.mark_failed(error);
@@ -240,7 +293,7 @@ impl PaymentStatusTracker {
 
     /// Get all pending/in-progress payments.
     pub fn get_in_progress(&self) -> Vec<PaymentStatusInfo> {
-        let statuses = self.statuses.read().unwrap_or_else(|e| e.into_inner());
+        let statuses = self.statuses.read();
         statuses
             .values()
             .filter(|s| s.status.is_in_progress())
@@ -250,7 +303,7 @@ impl PaymentStatusTracker {
 
     /// Get all payments by status.
     pub fn get_by_status(&self, status: PaymentStatus) -> Vec<PaymentStatusInfo> {
-        let statuses = self.statuses.read().unwrap_or_else(|e| e.into_inner());
+        let statuses = self.statuses.read();
         statuses
             .values()
             .filter(|s| s.status == status)
@@ -258,9 +311,19 @@ impl PaymentStatusTracker {
             .collect()
     }
 
+    /// Number of payments being tracked, terminal ones included.
+    pub fn len(&self) -> usize {
+        self.statuses.read().len()
+    }
+
+    /// Whether no payments are being tracked.
+    pub fn is_empty(&self) -> bool {
+        self.statuses.read().is_empty()
+    }
+
     /// Remove completed/terminal statuses older than the given timestamp.
     pub fn cleanup_old(&self, before_timestamp: i64) -> usize {
-        let mut statuses = self.statuses.write().unwrap_or_else(|e| e.into_inner());
+        let mut statuses = self.statuses.write();
         let count = statuses.len();
         statuses.retain(|_, status| {
             !status.status.is_terminal() || status.updated_at >= before_timestamp
@@ -269,8 +332,9 @@ impl PaymentStatusTracker {
     }
 
     fn notify(&self, status: &PaymentStatusInfo) {
-        let callbacks = self.callbacks.read().unwrap_or_else(|e| e.into_inner());
-        for callback in callbacks.iter() {
+        // Snapshot the callbacks so none runs under the lock
+        let callbacks = self.callbacks.read().clone();
+        for callback in &callbacks {
             callback(status);
         }
     }
@@ -375,15 +439,63 @@ mod tests {
 
     #[test]
     fn test_tracker_confirmations() {
-        let tracker = PaymentStatusTracker::new();
+        let tracker = PaymentStatusTracker::with_confirmation_policy(ConfirmationPolicy::new(6));
         let receipt = test_receipt();
 
         tracker.track(&receipt);
-        tracker.update_confirmations(&receipt.receipt_id, 3, 6);
+        tracker.update_confirmations(&receipt.receipt_id, 3);
 
         let status = tracker.get(&receipt.receipt_id).unwrap();
         assert_eq!(status.status, PaymentStatus::Confirmed);
         assert_eq!(status.confirmations, Some(3));
+        assert_eq!(status.required_confirmations, Some(6));
+    }
+
+    #[test]
+    fn test_tracker_confirmation_policy() {
+        let tracker = PaymentStatusTracker::new();
+
+        let mut small = test_receipt();
+        small.method_id = MethodId::onchain();
+        small.amount = Some("10000".to_string());
+        tracker.track_from_peer(&small, true);
+        assert_eq!(
+            tracker
+                .get(&small.receipt_id)
+                .unwrap()
+                .required_confirmations,
+            Some(0)
+        );
+        let status = tracker.update_confirmations(&small.receipt_id, 0).unwrap();
+        assert_eq!(status.status, PaymentStatus::Finalized);
+
+        let mut large = test_receipt();
+        large.receipt_id = "large".to_string();
+        large.method_id = MethodId::onchain();
+        large.amount = Some("2000000".to_string());
+        tracker.track(&large);
+        let status = tracker.update_confirmations(&large.receipt_id, 1).unwrap();
+        assert_eq!(status.status, PaymentStatus::Confirmed);
+        let status = tracker.update_confirmations(&large.receipt_id, 6).unwrap();
+        assert_eq!(status.status, PaymentStatus::Finalized);
+
+        assert!(tracker.update_confirmations("missing", 1).is_none());
+    }
+
+    #[test]
+    fn test_tracker_custom_policy() {
+        let tracker = PaymentStatusTracker::with_confirmation_policy(ConfirmationPolicy::new(2));
+        let receipt = test_receipt();
+        tracker.track(&receipt);
+
+        let status = tracker
+            .update_confirmations(&receipt.receipt_id, 1)
+            .unwrap();
+        assert_eq!(status.status, PaymentStatus::Confirmed);
+        let status = tracker
+            .update_confirmations(&receipt.receipt_id, 2)
+            .unwrap();
+        assert_eq!(status.status, PaymentStatus::Finalized);
     }
 
     #[test]
@@ -404,6 +516,24 @@ mod tests {
         assert!(called.load(Ordering::SeqCst));
     }
 
+    #[test]
+    fn test_tracker_callback_can_reenter() {
+        let tracker = Arc::new(PaymentStatusTracker::new());
+        let inner = Arc::downgrade(&tracker);
+
+        // Registering from inside a callback must not deadlock
+        tracker.on_status_change(Arc::new(move |status| {
+            if let Some(tracker) = inner.upgrade() {
+                assert!(tracker.get(&status.receipt_id).is_some());
+                tracker.on_status_change(Arc::new(|_| {}));
+            }
+        }));
+
+        let receipt = test_receipt();
+        tracker.track(&receipt);
+        tracker.update(&receipt.receipt_id, PaymentStatus::Processing);
+    }
+
     #[test]
     fn test_get_in_progress() {
         let tracker = PaymentStatusTracker::new();
@@ -0,0 +1,204 @@
+//! Confirmation Policies
+//!
+//! Decides how many confirmations a payment needs before it is considered
+//! final, based on the payment method, the amount and whether the counterparty
+//! is trusted.
+//!
+//! # Example
+//!
+//! ```
+//! use paykit_interactive::status::ConfirmationPolicy;
+//! use paykit_lib::MethodId;
+//!
+//! let policy = ConfirmationPolicy::default();
+//! let onchain = MethodId::onchain();
+//!
+//! // Small payment from a trusted peer: accept unconfirmed
+//! assert_eq!(policy.required_confirmations(&onchain, Some(10_000), true), 0);
+//! // Same amount from an unknown peer: one confirmation
+//! assert_eq!(policy.required_confirmations(&onchain, Some(10_000), false), 1);
+//! // Large payment: six confirmations
+//! assert_eq!(policy.required_confirmations(&onchain, Some(5_000_000), false), 6);
+//! ```
+
+use paykit_lib::MethodId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// One amount band of a confirmation policy.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfirmationTier {
+    /// Upper bound (exclusive) in satoshis, or `None` for "any amount".
+    pub below_sats: Option<u64>,
+    /// Confirmations required for payments in this band.
+    pub confirmations: u64,
+    /// Only apply this tier when the counterparty is trusted.
+    #[serde(default)]
+    pub trusted_only: bool,
+}
+
+impl ConfirmationTier {
+    /// Create a tier for payments below `below_sats`.
+    pub fn below(below_sats: u64, confirmations: u64) -> Self {
+        Self {
+            below_sats: Some(below_sats),
+            confirmations,
+            trusted_only: false,
+        }
+    }
+
+    /// Create a catch-all tier.
+    pub fn any(confirmations: u64) -> Self {
+        Self {
+            below_sats: None,
+            confirmations,
+            trusted_only: false,
+        }
+    }
+
+    /// Restrict the tier to trusted counterparties.
+    pub fn trusted(mut self) -> Self {
+        self.trusted_only = true;
+        self
+    }
+
+    fn applies(&self, amount_sats: Option<u64>, trusted: bool) -> bool {
+        if self.trusted_only && !trusted {
+            return false;
+        }
+        match (self.below_sats, amount_sats) {
+            (None, _) => true,
+            (Some(limit), Some(amount)) => amount < limit,
+            // Unknown amounts only match catch-all tiers
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// Per-method confirmation requirements.
+///
+/// Tiers are evaluated in order and the first matching tier wins. Methods
+/// without configured tiers, or amounts no tier matches, fall back to
+/// [`default_confirmations`](Self::default_confirmations).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfirmationPolicy {
+    /// Tiers keyed by method ID.
+    pub methods: HashMap<String, Vec<ConfirmationTier>>,
+    /// Confirmations required when nothing else matches.
+    pub default_confirmations: u64,
+}
+
+impl Default for ConfirmationPolicy {
+    /// On-chain: 0-conf below 50k sats from trusted peers, 1 conf below 1M sats,
+    /// 6 confs otherwise. Lightning payments are final once settled.
+    fn default() -> Self {
+        Self::new(6)
+            .with_method(
+                MethodId::onchain(),
+                vec![
+                    ConfirmationTier::below(50_000, 0).trusted(),
+                    ConfirmationTier::below(1_000_000, 1),
+                    ConfirmationTier::any(6),
+                ],
+            )
+            .with_method(MethodId::lightning(), vec![ConfirmationTier::any(0)])
+    }
+}
+
+impl ConfirmationPolicy {
+    /// Create an empty policy that always requires `default_confirmations`.
+    pub fn new(default_confirmations: u64) -> Self {
+        Self {
+            methods: HashMap::new(),
+            default_confirmations,
+        }
+    }
+
+    /// Set the tiers for a method, replacing any existing ones.
+    pub fn with_method(mut self, method: MethodId, tiers: Vec<ConfirmationTier>) -> Self {
+        self.set_method(method, tiers);
+        self
+    }
+
+    /// Set the tiers for a method, replacing any existing ones.
+    pub fn set_method(&mut self, method: MethodId, tiers: Vec<ConfirmationTier>) {
+        self.methods.insert(method.0, tiers);
+    }
+
+    /// Number of confirmations required before a payment is final.
+    pub fn required_confirmations(
+        &self,
+        method: &MethodId,
+        amount_sats: Option<u64>,
+        trusted: bool,
+    ) -> u64 {
+        self.methods
+            .get(method.as_str())
+            .and_then(|tiers| tiers.iter().find(|t| t.applies(amount_sats, trusted)))
+            .map(|t| t.confirmations)
+            .unwrap_or(self.default_confirmations)
+    }
+}
+
+/// Interpret a receipt amount as satoshis.
+///
+/// Only amounts without a currency or denominated in sats are understood;
+/// anything else is treated as unknown so the strictest catch-all tier applies.
+pub fn amount_in_sats(amount: Option<&str>, currency: Option<&str>) -> Option<u64> {
+    let is_sats =
+        currency.is_none_or(|c| c.eq_ignore_ascii_case("sat") || c.eq_ignore_ascii_case("sats"));
+    if !is_sats {
+        return None;
+    }
+    amount.and_then(|a| a.trim().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_policy_tiers() {
+        let policy = ConfirmationPolicy::default();
+        let onchain = MethodId::onchain();
+
+        assert_eq!(
+            policy.required_confirmations(&onchain, Some(49_999), true),
+            0
+        );
+        assert_eq!(
+            policy.required_confirmations(&onchain, Some(50_000), true),
+            1
+        );
+        assert_eq!(
+            policy.required_confirmations(&onchain, Some(999_999), false),
+            1
+        );
+        assert_eq!(
+            policy.required_confirmations(&onchain, Some(1_000_000), false),
+            6
+        );
+        assert_eq!(policy.required_confirmations(&onchain, None, true), 6);
+        assert_eq!(
+            policy.required_confirmations(&MethodId::lightning(), Some(1), false),
+            0
+        );
+    }
+
+    #[test]
+    fn test_unknown_method_uses_default() {
+        let policy = ConfirmationPolicy::new(3);
+        assert_eq!(
+            policy.required_confirmations(&MethodId::new("custom"), Some(1), true),
+            3
+        );
+    }
+
+    #[test]
+    fn test_amount_in_sats() {
+        assert_eq!(amount_in_sats(Some("1000"), Some("SAT")), Some(1000));
+        assert_eq!(amount_in_sats(Some("1000"), None), Some(1000));
+        assert_eq!(amount_in_sats(Some("10"), Some("USD")), None);
+        assert_eq!(amount_in_sats(Some("abc"), Some("SAT")), None);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::query::{ReceiptPage, ReceiptQuery};
 use crate::{PaykitReceipt, Result};
 use paykit_lib::private_endpoints::PrivateEndpoint;
 use paykit_lib::{EndpointData, MethodId, PublicKey};
@@ -16,6 +17,25 @@ pub trait PaykitStorage: Send + Sync {
     /// List all receipts.
     async fn list_receipts(&self) -> Result<Vec<PaykitReceipt>>;
 
+    /// Search receipts and return one page of results.
+    ///
+    /// The default implementation filters [`list_receipts`](Self::list_receipts)
+    /// in memory. Stores backed by an indexed database should override it.
+    async fn query_receipts(&self, query: &ReceiptQuery) -> Result<ReceiptPage<PaykitReceipt>> {
+        let receipts = self.list_receipts().await?;
+        query.apply(receipts)
+    }
+
+    /// Delete a receipt. Deleting a receipt that does not exist is not an error.
+    ///
+    /// Needed for [`evict_receipts`](crate::eviction::evict_receipts). The
+    /// default implementation returns [`InteractiveError::Unimplemented`].
+    ///
+    /// [`InteractiveError::Unimplemented`]: crate::InteractiveError::Unimplemented
+    async fn delete_receipt(&self, _receipt_id: &str) -> Result<()> {
+        Err(crate::InteractiveError::Unimplemented)
+    }
+
     /// Save a private endpoint offered by a peer.
     ///
     /// * `peer`: The public key of the peer who offered the endpoint.
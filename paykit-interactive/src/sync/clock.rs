@@ -0,0 +1,124 @@
+//! Vector clocks for ordering changes made on different devices.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// How two vector clocks relate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClockOrdering {
+    /// Both clocks have seen the same changes.
+    Equal,
+    /// The first clock has seen a subset of the second's changes.
+    Before,
+    /// The first clock has seen a superset of the second's changes.
+    After,
+    /// Each clock has seen changes the other has not.
+    Concurrent,
+}
+
+/// A vector clock: one change counter per device.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct VectorClock(BTreeMap<String, u64>);
+
+impl VectorClock {
+    /// An empty clock, which is before every other clock.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The counter for `device_id`, 0 if it has made no changes.
+    pub fn get(&self, device_id: &str) -> u64 {
+        self.0.get(device_id).copied().unwrap_or(0)
+    }
+
+    /// Count a change on `device_id` and return the new counter.
+    pub fn increment(&mut self, device_id: &str) -> u64 {
+        let counter = self.0.entry(device_id.to_string()).or_insert(0);
+        *counter += 1;
+        *counter
+    }
+
+    /// Raise the counter for `device_id` to at least `counter`.
+    pub fn observe(&mut self, device_id: &str, counter: u64) {
+        let entry = self.0.entry(device_id.to_string()).or_insert(0);
+        *entry = (*entry).max(counter);
+    }
+
+    /// Take the element-wise maximum with `other`.
+    pub fn merge(&mut self, other: &VectorClock) {
+        for (device_id, counter) in &other.0 {
+            self.observe(device_id, *counter);
+        }
+    }
+
+    /// Compare with `other`.
+    pub fn compare(&self, other: &VectorClock) -> ClockOrdering {
+        let mut ordering = Ordering::Equal;
+        for device_id in self.0.keys().chain(other.0.keys()) {
+            match (self.get(device_id).cmp(&other.get(device_id)), ordering) {
+                (Ordering::Equal, _) => {}
+                (Ordering::Less, Ordering::Greater) | (Ordering::Greater, Ordering::Less) => {
+                    return ClockOrdering::Concurrent
+                }
+                (side, _) => ordering = side,
+            }
+        }
+        match ordering {
+            Ordering::Equal => ClockOrdering::Equal,
+            Ordering::Less => ClockOrdering::Before,
+            Ordering::Greater => ClockOrdering::After,
+        }
+    }
+
+    /// Whether everything this clock has seen, `other` has seen too.
+    pub fn is_covered_by(&self, other: &VectorClock) -> bool {
+        matches!(
+            self.compare(other),
+            ClockOrdering::Equal | ClockOrdering::Before
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock(entries: &[(&str, u64)]) -> VectorClock {
+        let mut clock = VectorClock::new();
+        for (device_id, counter) in entries {
+            clock.observe(device_id, *counter);
+        }
+        clock
+    }
+
+    #[test]
+    fn test_compare() {
+        let a = clock(&[("phone", 2), ("desktop", 1)]);
+        assert_eq!(a.compare(&a.clone()), ClockOrdering::Equal);
+        assert_eq!(
+            a.compare(&clock(&[("phone", 3), ("desktop", 1)])),
+            ClockOrdering::Before
+        );
+        assert_eq!(a.compare(&clock(&[("phone", 2)])), ClockOrdering::After);
+        assert_eq!(
+            a.compare(&clock(&[("phone", 1), ("desktop", 2)])),
+            ClockOrdering::Concurrent
+        );
+        assert!(VectorClock::new().is_covered_by(&a));
+    }
+
+    #[test]
+    fn test_merge_dominates_both() {
+        let mut a = clock(&[("phone", 2)]);
+        let b = clock(&[("desktop", 3)]);
+        assert_eq!(a.compare(&b), ClockOrdering::Concurrent);
+
+        let before = a.clone();
+        a.merge(&b);
+        assert_eq!(a.compare(&before), ClockOrdering::After);
+        assert_eq!(a.compare(&b), ClockOrdering::After);
+        assert_eq!(a.increment("phone"), 3);
+    }
+}
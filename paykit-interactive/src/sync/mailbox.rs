@@ -0,0 +1,166 @@
+//! Encrypted sync mailbox on the identity's homeserver storage.
+//!
+//! Each device writes its records, encrypted, to
+//! `/pub/paykit.app/v0/sync/{device_id}` on the identity's storage and
+//! reads the other devices' bundles from the same directory. The mailbox
+//! key is derived from the identity secret, which all of the identity's
+//! devices hold and nobody else does.
+//!
+//! The storage is public: the device IDs, bundle sizes and update times
+//! are visible to anyone, only the contents are hidden.
+
+use super::{SyncRecord, SyncState, VectorClock};
+use crate::{InteractiveError, Result};
+use paykit_lib::private_endpoints::encryption::EncryptionContext;
+use paykit_lib::protocol::{device_sync_path, DEVICE_SYNC_SUBPATH, PAYKIT_V0_PREFIX};
+use paykit_lib::{AuthenticatedTransport, PublicKey, UnauthenticatedTransportRead};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Domain separator for the mailbox key.
+const MAILBOX_KEY_CONTEXT: &[u8] = b"paykit-device-sync-v1";
+
+/// One device's records as stored in the mailbox.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SyncBundle {
+    /// Device that wrote the bundle.
+    pub device_id: String,
+    /// Everything that device had seen when writing.
+    pub clock: VectorClock,
+    /// All of the device's records.
+    pub records: Vec<SyncRecord>,
+}
+
+/// Encrypted mailbox shared by an identity's devices.
+pub struct SyncMailbox {
+    owner: PublicKey,
+    encryption: EncryptionContext,
+}
+
+impl SyncMailbox {
+    /// Open the mailbox of `owner` with a key from [`derive_key`](Self::derive_key).
+    pub fn new(owner: PublicKey, mailbox_key: [u8; 32]) -> Self {
+        Self {
+            owner,
+            encryption: EncryptionContext::new(mailbox_key),
+        }
+    }
+
+    /// Derive the mailbox key from the identity's Ed25519 secret key.
+    pub fn derive_key(identity_secret: &[u8; 32]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(MAILBOX_KEY_CONTEXT);
+        hasher.update(identity_secret);
+        hasher.finalize().into()
+    }
+
+    /// Write this device's records to the mailbox.
+    pub async fn publish<T>(&self, transport: &T, state: &SyncState) -> Result<()>
+    where
+        T: AuthenticatedTransport + Sync,
+    {
+        let bundle = SyncBundle {
+            device_id: state.device_id().to_string(),
+            clock: state.clock().clone(),
+            records: state.changes_since(&VectorClock::new()),
+        };
+        let path = device_sync_path(&bundle.device_id)
+            .map_err(|e| InteractiveError::Protocol(e.to_string()))?;
+        let plaintext = serde_json::to_vec(&bundle)?;
+        let ciphertext = self
+            .encryption
+            .encrypt(&plaintext, bundle.device_id.as_bytes())
+            .map_err(|e| InteractiveError::Protocol(format!("Encrypt sync bundle: {}", e)))?;
+
+        transport
+            .put(&path, &hex::encode(ciphertext))
+            .await
+            .map_err(|e| InteractiveError::Transport(e.to_string()))
+    }
+
+    /// Read the other devices' bundles.
+    ///
+    /// Bundles that fail to decrypt (written with another key) are skipped.
+    /// Merge each bundle's records with [`SyncState::merge`].
+    pub async fn fetch<R>(&self, reader: &R, own_device_id: &str) -> Result<Vec<SyncBundle>>
+    where
+        R: UnauthenticatedTransportRead + Sync,
+    {
+        let dir = format!("{}/{}/", PAYKIT_V0_PREFIX, DEVICE_SYNC_SUBPATH);
+        let device_ids = reader
+            .list_directory(&self.owner, &dir)
+            .await
+            .map_err(|e| InteractiveError::Transport(e.to_string()))?;
+
+        let mut bundles = Vec::new();
+        for device_id in device_ids {
+            if device_id == own_device_id {
+                continue;
+            }
+            let Ok(path) = device_sync_path(&device_id) else {
+                continue;
+            };
+            let Some(content) = reader
+                .get(&self.owner, &path)
+                .await
+                .map_err(|e| InteractiveError::Transport(e.to_string()))?
+            else {
+                continue;
+            };
+            if let Some(bundle) = self.open(&device_id, &content) {
+                bundles.push(bundle);
+            }
+        }
+        Ok(bundles)
+    }
+
+    /// Decrypt a bundle, binding it to the device it was stored under.
+    fn open(&self, device_id: &str, content: &str) -> Option<SyncBundle> {
+        let ciphertext = hex::decode(content.trim()).ok()?;
+        let plaintext = self
+            .encryption
+            .decrypt(&ciphertext, device_id.as_bytes())
+            .ok()?;
+        let bundle: SyncBundle = serde_json::from_slice(&plaintext).ok()?;
+        (bundle.device_id == device_id).then_some(bundle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paykit_lib::MethodId;
+    use std::str::FromStr;
+
+    fn owner() -> PublicKey {
+        let keypair = pubky::Keypair::random();
+        PublicKey::from_str(&keypair.public_key().to_z32()).unwrap()
+    }
+
+    #[test]
+    fn test_bundle_roundtrip_is_bound_to_device() {
+        let owner = owner();
+        let key = SyncMailbox::derive_key(&[7; 32]);
+        let mailbox = SyncMailbox::new(owner.clone(), key);
+
+        let mut state = SyncState::new("phone");
+        state.put_private_endpoint(owner.clone(), MethodId::new("onchain"), "bc1qa");
+        let bundle = SyncBundle {
+            device_id: "phone".into(),
+            clock: state.clock().clone(),
+            records: state.changes_since(&VectorClock::new()),
+        };
+        let ciphertext = mailbox
+            .encryption
+            .encrypt(&serde_json::to_vec(&bundle).unwrap(), b"phone")
+            .unwrap();
+        let content = hex::encode(ciphertext);
+
+        let opened = mailbox.open("phone", &content).unwrap();
+        assert_eq!(opened.records, bundle.records);
+        // Moved to another device's slot, or read with another key
+        assert!(mailbox.open("desktop", &content).is_none());
+        let other = SyncMailbox::new(owner, SyncMailbox::derive_key(&[8; 32]));
+        assert!(other.open("phone", &content).is_none());
+    }
+}
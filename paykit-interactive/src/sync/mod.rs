@@ -0,0 +1,479 @@
+//! Multi-Device Sync
+//!
+//! Keeps the receipt store and private endpoint store of one identity's
+//! devices (say a phone and a desktop) in step.
+//!
+//! Each device keeps a [`SyncState`]: every stored receipt and private
+//! endpoint as a [`SyncRecord`] stamped with a [`VectorClock`]. Devices
+//! exchange the records the other has not seen and [merge](SyncState::merge)
+//! them; a record wins over another if its clock is later, and concurrent
+//! edits (each device changed it without seeing the other's change) go to
+//! the most recently modified one, so every device settles on the same
+//! value.
+//!
+//! Records travel either
+//!
+//! - directly between devices over Noise, with
+//!   [`PaykitInteractiveManager::sync_with_device`](crate::PaykitInteractiveManager::sync_with_device), or
+//! - through an encrypted mailbox on the identity's own homeserver storage
+//!   ([`mailbox::SyncMailbox`], `sync-mailbox` feature), for devices that
+//!   are rarely online at the same time.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use paykit_interactive::sync::{snapshot_storage, apply_to_storage, SyncState};
+//!
+//! let mut state = SyncState::new("phone");
+//! // Pick up what the app stored since the last sync
+//! let snapshot = snapshot_storage(&storage, &state.endpoint_peers()).await?;
+//! state.observe(&snapshot);
+//!
+//! // Records received from another device
+//! let report = state.merge(records);
+//! apply_to_storage(&storage, &report.applied).await?;
+//! ```
+
+mod clock;
+#[cfg(feature = "sync-mailbox")]
+pub mod mailbox;
+
+pub use clock::{ClockOrdering, VectorClock};
+
+use crate::{chrono_now, PaykitReceipt, PaykitStorage, Result};
+use paykit_lib::{MethodId, PublicKey};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// A sync state shared between the manager and the host app.
+pub type SharedSyncState = Arc<parking_lot::Mutex<SyncState>>;
+
+/// What a record holds.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SyncKey {
+    /// A receipt, by ID.
+    Receipt { receipt_id: String },
+    /// The private endpoint a peer offered for a method.
+    PrivateEndpoint {
+        peer: PublicKey,
+        method_id: MethodId,
+    },
+}
+
+impl SyncKey {
+    /// Stable identifier of the key.
+    pub fn id(&self) -> String {
+        match self {
+            Self::Receipt { receipt_id } => format!("receipt/{}", receipt_id),
+            Self::PrivateEndpoint { peer, method_id } => {
+                format!("endpoint/{}/{}", peer, method_id.0)
+            }
+        }
+    }
+}
+
+/// The synced value of a record.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum SyncValue {
+    /// A receipt.
+    Receipt(PaykitReceipt),
+    /// Private endpoint data.
+    PrivateEndpoint(String),
+}
+
+/// One synced entry.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SyncRecord {
+    /// What the record holds.
+    pub key: SyncKey,
+    /// The value, or `None` once it has been removed.
+    pub value: Option<SyncValue>,
+    /// Changes to this record that have been seen.
+    pub clock: VectorClock,
+    /// When the record was last changed (unix seconds).
+    pub modified_at: i64,
+    /// Device that made the last change.
+    pub device_id: String,
+}
+
+impl SyncRecord {
+    /// Whether this record wins a concurrent edit against `other`.
+    ///
+    /// The later change wins; ties go to the higher device ID so that all
+    /// devices pick the same winner.
+    fn wins_over(&self, other: &SyncRecord) -> bool {
+        (self.modified_at, &self.device_id) > (other.modified_at, &other.device_id)
+    }
+}
+
+/// Outcome of [`SyncState::merge`].
+#[derive(Clone, Debug, Default)]
+pub struct MergeReport {
+    /// Remote records that changed the local state; write them to storage
+    /// with [`apply_to_storage`].
+    pub applied: Vec<SyncRecord>,
+    /// Concurrent edits resolved by modification time.
+    pub conflicts: usize,
+}
+
+/// The stores' contents as read by [`snapshot_storage`].
+#[derive(Clone, Debug, Default)]
+pub struct StorageSnapshot {
+    /// All receipts.
+    pub receipts: Vec<PaykitReceipt>,
+    /// Private endpoints of the peers that were listed.
+    pub endpoints: Vec<(PublicKey, MethodId, String)>,
+    /// Peers whose private endpoints were listed.
+    pub peers: Vec<PublicKey>,
+}
+
+/// One device's view of the synced stores.
+///
+/// Serializable, so the host app can persist it between runs.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SyncState {
+    device_id: String,
+    /// Everything this device has seen.
+    clock: VectorClock,
+    records: BTreeMap<String, SyncRecord>,
+}
+
+impl SyncState {
+    /// Create the state of a device with no records.
+    ///
+    /// `device_id` must be unique among the identity's devices and stable
+    /// across restarts.
+    pub fn new(device_id: impl Into<String>) -> Self {
+        Self {
+            device_id: device_id.into(),
+            clock: VectorClock::new(),
+            records: BTreeMap::new(),
+        }
+    }
+
+    /// This device's ID.
+    pub fn device_id(&self) -> &str {
+        &self.device_id
+    }
+
+    /// Every change this device has seen.
+    pub fn clock(&self) -> &VectorClock {
+        &self.clock
+    }
+
+    /// Number of records, including removed ones.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Whether there are no records.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// The current value for a key.
+    pub fn get(&self, key: &SyncKey) -> Option<&SyncValue> {
+        self.records
+            .get(&key.id())
+            .and_then(|record| record.value.as_ref())
+    }
+
+    /// Peers with a private endpoint record, removed ones included.
+    pub fn endpoint_peers(&self) -> Vec<PublicKey> {
+        let mut peers: Vec<PublicKey> = Vec::new();
+        for record in self.records.values() {
+            if let SyncKey::PrivateEndpoint { peer, .. } = &record.key {
+                if !peers.contains(peer) {
+                    peers.push(peer.clone());
+                }
+            }
+        }
+        peers
+    }
+
+    /// Record a receipt stored on this device.
+    ///
+    /// Returns `false` if the record already held this receipt.
+    pub fn put_receipt(&mut self, receipt: PaykitReceipt) -> bool {
+        let key = SyncKey::Receipt {
+            receipt_id: receipt.receipt_id.clone(),
+        };
+        self.record_local(key, Some(SyncValue::Receipt(receipt)))
+    }
+
+    /// Record a private endpoint stored on this device.
+    pub fn put_private_endpoint(
+        &mut self,
+        peer: PublicKey,
+        method_id: MethodId,
+        endpoint: impl Into<String>,
+    ) -> bool {
+        let key = SyncKey::PrivateEndpoint { peer, method_id };
+        self.record_local(key, Some(SyncValue::PrivateEndpoint(endpoint.into())))
+    }
+
+    /// Record a private endpoint removed on this device.
+    pub fn remove_private_endpoint(&mut self, peer: PublicKey, method_id: MethodId) -> bool {
+        let key = SyncKey::PrivateEndpoint { peer, method_id };
+        if !self.records.contains_key(&key.id()) {
+            return false;
+        }
+        self.record_local(key, None)
+    }
+
+    /// Record what changed in the stores since the last call.
+    ///
+    /// Private endpoints of peers missing from `snapshot.peers` are left
+    /// alone; endpoints of listed peers that are gone are recorded as
+    /// removed. Receipts are never removed. Returns the number of changes.
+    pub fn observe(&mut self, snapshot: &StorageSnapshot) -> usize {
+        let mut changes = 0;
+        for receipt in &snapshot.receipts {
+            changes += usize::from(self.put_receipt(receipt.clone()));
+        }
+        for (peer, method_id, endpoint) in &snapshot.endpoints {
+            changes += usize::from(self.put_private_endpoint(
+                peer.clone(),
+                method_id.clone(),
+                endpoint.clone(),
+            ));
+        }
+
+        let removed: Vec<SyncKey> = self
+            .records
+            .values()
+            .filter(|record| record.value.is_some())
+            .filter_map(|record| match &record.key {
+                SyncKey::PrivateEndpoint { peer, method_id }
+                    if snapshot.peers.contains(peer)
+                        && !snapshot
+                            .endpoints
+                            .iter()
+                            .any(|(p, m, _)| p == peer && m == method_id) =>
+                {
+                    Some(record.key.clone())
+                }
+                _ => None,
+            })
+            .collect();
+        for key in removed {
+            changes += usize::from(self.record_local(key, None));
+        }
+        changes
+    }
+
+    /// Records with changes `clock` has not seen.
+    ///
+    /// Pass the other device's [`clock`](Self::clock) to get what it is
+    /// missing, or an empty clock for everything.
+    pub fn changes_since(&self, clock: &VectorClock) -> Vec<SyncRecord> {
+        self.records
+            .values()
+            .filter(|record| !record.clock.is_covered_by(clock))
+            .cloned()
+            .collect()
+    }
+
+    /// Merge records from another device.
+    pub fn merge(&mut self, remote: Vec<SyncRecord>) -> MergeReport {
+        let mut report = MergeReport::default();
+        for record in remote {
+            self.clock.merge(&record.clock);
+            let id = record.key.id();
+            let Some(local) = self.records.get_mut(&id) else {
+                report.applied.push(record.clone());
+                self.records.insert(id, record);
+                continue;
+            };
+
+            match record.clock.compare(&local.clock) {
+                ClockOrdering::After => {
+                    *local = record.clone();
+                    report.applied.push(record);
+                }
+                ClockOrdering::Before | ClockOrdering::Equal => {}
+                ClockOrdering::Concurrent => {
+                    report.conflicts += 1;
+                    let mut clock = local.clock.clone();
+                    clock.merge(&record.clock);
+                    if record.wins_over(local) {
+                        *local = record;
+                        report.applied.push(local.clone());
+                    }
+                    // Either way the merged clock supersedes both edits
+                    local.clock = clock;
+                }
+            }
+        }
+        report
+    }
+
+    fn record_local(&mut self, key: SyncKey, value: Option<SyncValue>) -> bool {
+        let id = key.id();
+        let mut clock = match self.records.get(&id) {
+            Some(record) if record.value == value => return false,
+            Some(record) => record.clock.clone(),
+            None => VectorClock::new(),
+        };
+        let counter = self.clock.increment(&self.device_id);
+        clock.observe(&self.device_id, counter);
+        self.records.insert(
+            id,
+            SyncRecord {
+                key,
+                value,
+                clock,
+                modified_at: chrono_now(),
+                device_id: self.device_id.clone(),
+            },
+        );
+        true
+    }
+}
+
+/// Read the receipts and the private endpoints of `peers` from storage.
+///
+/// `PaykitStorage` cannot list peers, so pass every peer the app knows
+/// about (at least [`SyncState::endpoint_peers`]) for new endpoints and
+/// removals to be noticed.
+pub async fn snapshot_storage(
+    storage: &dyn PaykitStorage,
+    peers: &[PublicKey],
+) -> Result<StorageSnapshot> {
+    let mut snapshot = StorageSnapshot {
+        receipts: storage.list_receipts().await?,
+        ..StorageSnapshot::default()
+    };
+    for peer in peers {
+        for (method_id, endpoint) in storage.list_private_endpoints_for_peer(peer).await? {
+            snapshot.endpoints.push((peer.clone(), method_id, endpoint));
+        }
+        snapshot.peers.push(peer.clone());
+    }
+    Ok(snapshot)
+}
+
+/// Write merged records to storage.
+pub async fn apply_to_storage(storage: &dyn PaykitStorage, records: &[SyncRecord]) -> Result<()> {
+    for record in records {
+        match (&record.key, &record.value) {
+            (SyncKey::Receipt { .. }, Some(SyncValue::Receipt(receipt))) => {
+                storage.save_receipt(receipt).await?;
+            }
+            (SyncKey::PrivateEndpoint { peer, method_id }, Some(SyncValue::PrivateEndpoint(e))) => {
+                storage.save_private_endpoint(peer, method_id, e).await?;
+            }
+            (SyncKey::PrivateEndpoint { peer, method_id }, None) => {
+                storage.remove_private_endpoint(peer, method_id).await?;
+            }
+            // Receipts are never removed, and mismatched kinds are ignored
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn peer() -> PublicKey {
+        let keypair = pubky::Keypair::random();
+        PublicKey::from_str(&keypair.public_key().to_z32()).unwrap()
+    }
+
+    /// Exchange changes both ways, as `sync_with_device` does.
+    fn sync(a: &mut SyncState, b: &mut SyncState) {
+        let to_a = b.changes_since(a.clock());
+        let to_b = a.changes_since(b.clock());
+        a.merge(to_a);
+        b.merge(to_b);
+    }
+
+    #[test]
+    fn test_changes_propagate_both_ways() {
+        let peer = peer();
+        let mut phone = SyncState::new("phone");
+        let mut desktop = SyncState::new("desktop");
+
+        phone.put_private_endpoint(peer.clone(), MethodId::new("lightning"), "lnurl1a");
+        desktop.put_private_endpoint(peer.clone(), MethodId::new("onchain"), "bc1qa");
+        sync(&mut phone, &mut desktop);
+
+        assert_eq!(phone.len(), 2);
+        assert_eq!(desktop.len(), 2);
+        assert!(phone.changes_since(desktop.clock()).is_empty());
+
+        // A removal on one device reaches the other
+        desktop.remove_private_endpoint(peer.clone(), MethodId::new("lightning"));
+        let report = phone.merge(desktop.changes_since(phone.clock()));
+        assert_eq!(report.applied.len(), 1);
+        assert_eq!(report.applied[0].value, None);
+        let key = SyncKey::PrivateEndpoint {
+            peer,
+            method_id: MethodId::new("lightning"),
+        };
+        assert_eq!(phone.get(&key), None);
+    }
+
+    #[test]
+    fn test_concurrent_edits_converge() {
+        let peer = peer();
+        let method_id = MethodId::new("lightning");
+        let mut phone = SyncState::new("phone");
+        let mut desktop = SyncState::new("desktop");
+        phone.put_private_endpoint(peer.clone(), method_id.clone(), "lnurl1a");
+        sync(&mut phone, &mut desktop);
+
+        phone.put_private_endpoint(peer.clone(), method_id.clone(), "lnurl1phone");
+        desktop.put_private_endpoint(peer.clone(), method_id.clone(), "lnurl1desktop");
+        for state in [&mut phone, &mut desktop] {
+            for record in state.records.values_mut() {
+                record.modified_at = 1_700_000_000;
+            }
+        }
+        let to_phone = desktop.changes_since(phone.clock());
+        let to_desktop = phone.changes_since(desktop.clock());
+        assert_eq!(phone.merge(to_phone).conflicts, 1);
+        assert_eq!(desktop.merge(to_desktop).conflicts, 1);
+
+        let key = SyncKey::PrivateEndpoint { peer, method_id };
+        assert_eq!(phone.get(&key), desktop.get(&key));
+        // Same modification time, so the higher device ID wins
+        assert_eq!(
+            phone.get(&key),
+            Some(&SyncValue::PrivateEndpoint("lnurl1phone".into()))
+        );
+
+        // Nothing left to exchange
+        sync(&mut phone, &mut desktop);
+        assert!(phone.changes_since(desktop.clock()).is_empty());
+        assert!(desktop.changes_since(phone.clock()).is_empty());
+    }
+
+    #[test]
+    fn test_observe_records_removals_of_listed_peers() {
+        let peer = peer();
+        let mut state = SyncState::new("phone");
+        let snapshot = StorageSnapshot {
+            receipts: Vec::new(),
+            endpoints: vec![(peer.clone(), MethodId::new("onchain"), "bc1qa".into())],
+            peers: vec![peer.clone()],
+        };
+        assert_eq!(state.observe(&snapshot), 1);
+        assert_eq!(state.observe(&snapshot), 0);
+
+        // Unlisted peers are left alone
+        assert_eq!(state.observe(&StorageSnapshot::default()), 0);
+        let emptied = StorageSnapshot {
+            peers: vec![peer],
+            ..StorageSnapshot::default()
+        };
+        assert_eq!(state.observe(&emptied), 1);
+        assert_eq!(state.changes_since(&VectorClock::new())[0].value, None);
+    }
+}
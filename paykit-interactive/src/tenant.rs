@@ -0,0 +1,281 @@
+//! Serving several payee identities from one process.
+//!
+//! A hosting provider runs one receive server for many merchants. Each
+//! merchant is a [`Tenant`]: its own identity key, its own
+//! [`PaykitInteractiveManager`] (and so its own storage), its own
+//! [`PeerRateLimiter`] and its own [`Metrics`]. A busy or abused tenant
+//! cannot use up another tenant's message budget, and every counter can be
+//! reported per merchant.
+//!
+//! [`TenantRouter`] finds the tenant by recipient key:
+//!
+//! - A Noise session is addressed to the key the payer dialled; the server
+//!   learns it from whichever tenant's Noise key accepted the handshake and
+//!   passes that tenant's identity to [`TenantRouter::handle_message`].
+//! - Inbox messages are addressed to the inbox owner, so
+//!   [`TenantRouter::process_inbox`] reads with the owner's [`PeerInbox`].
+//!
+//! Messages for a key that is not registered are refused rather than
+//! handled by some other tenant.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut router = TenantRouter::new();
+//! router.register(Tenant::new("coffee", coffee_pk, coffee_manager, PeerLimitConfig::default()))?;
+//! router.register(Tenant::new("books", books_pk, books_manager, PeerLimitConfig::strict()))?;
+//!
+//! let reply = router.handle_message(&books_pk, msg, &payer_pk).await?;
+//! let per_tenant = router.metrics();
+//! ```
+
+use crate::inbox::{InboxMessage, PeerInbox};
+use crate::metrics::{Metrics, MetricsSnapshot};
+use crate::peer_limit::{PeerLimitConfig, PeerRateLimiter};
+use crate::{InteractiveError, PaykitInteractiveManager, PaykitNoiseMessage, Result};
+use paykit_lib::{AuthenticatedTransport, PublicKey, UnauthenticatedTransportRead};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// One payee identity served by a shared receive server.
+pub struct Tenant {
+    id: String,
+    public_key: PublicKey,
+    manager: PaykitInteractiveManager,
+    limits: Arc<PeerRateLimiter>,
+    metrics: Arc<Metrics>,
+    inbox: Option<PeerInbox>,
+}
+
+impl Tenant {
+    /// Serve `public_key` with `manager`, limiting each peer by `limits`.
+    ///
+    /// The tenant gets its own limiter and metrics; the limiter is attached
+    /// to `manager`, replacing any it already had.
+    pub fn new(
+        id: impl Into<String>,
+        public_key: PublicKey,
+        manager: PaykitInteractiveManager,
+        limits: PeerLimitConfig,
+    ) -> Self {
+        let metrics = Arc::new(Metrics::new());
+        let limits = Arc::new(PeerRateLimiter::new(limits).with_metrics(metrics.clone()));
+        Self {
+            id: id.into(),
+            manager: manager.with_peer_limits(limits.clone()),
+            public_key,
+            limits,
+            metrics,
+            inbox: None,
+        }
+    }
+
+    /// Also read the tenant's inbox, decrypting with its Noise secret key.
+    pub fn with_inbox(mut self, noise_sk: [u8; 32]) -> Self {
+        self.inbox = Some(PeerInbox::new(self.public_key.clone(), noise_sk));
+        self
+    }
+
+    /// Operator-chosen name, used in logs and metrics.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The identity payers address.
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    /// The tenant's manager.
+    pub fn manager(&self) -> &PaykitInteractiveManager {
+        &self.manager
+    }
+
+    /// The tenant's per-peer limits, shared with its manager.
+    pub fn limits(&self) -> &Arc<PeerRateLimiter> {
+        &self.limits
+    }
+
+    /// The tenant's counters.
+    pub fn metrics(&self) -> &Arc<Metrics> {
+        &self.metrics
+    }
+
+    /// Handle a message from `peer` addressed to this tenant.
+    pub async fn handle_message(
+        &self,
+        msg: PaykitNoiseMessage,
+        peer: &PublicKey,
+    ) -> Result<Option<PaykitNoiseMessage>> {
+        self.count_incoming(&msg);
+        let reply = self
+            .manager
+            .handle_message(msg, peer, &self.public_key)
+            .await;
+        self.count_reply(&reply);
+        reply
+    }
+
+    /// Handle the messages `sender` left in this tenant's inbox.
+    ///
+    /// See [`PaykitInteractiveManager::process_inbox`]; `transport` must
+    /// write to the tenant's own storage.
+    pub async fn process_inbox<T, R>(
+        &self,
+        transport: &T,
+        reader: &R,
+        sender: &PublicKey,
+        sender_noise_pk: &[u8; 32],
+    ) -> Result<Vec<InboxMessage>>
+    where
+        T: AuthenticatedTransport + Sync,
+        R: UnauthenticatedTransportRead + Sync,
+    {
+        let inbox = self.inbox.as_ref().ok_or_else(|| {
+            InteractiveError::Protocol(format!("Tenant {} has no inbox key", self.id))
+        })?;
+        let handled = self
+            .manager
+            .process_inbox(
+                inbox,
+                transport,
+                reader,
+                sender,
+                sender_noise_pk,
+                &self.public_key,
+            )
+            .await;
+        match &handled {
+            Ok(messages) => messages
+                .iter()
+                .for_each(|m| self.count_incoming(&m.message)),
+            Err(_) => self.metrics.record_protocol_error(),
+        }
+        handled
+    }
+
+    fn count_incoming(&self, msg: &PaykitNoiseMessage) {
+        self.metrics.record_message_received(0);
+        if matches!(msg, PaykitNoiseMessage::RequestReceipt { .. }) {
+            self.metrics.record_payment_request_received();
+        }
+    }
+
+    fn count_reply(&self, reply: &Result<Option<PaykitNoiseMessage>>) {
+        match reply {
+            Ok(Some(PaykitNoiseMessage::ConfirmReceipt { .. })) => {
+                self.metrics.record_receipt_generated()
+            }
+            Ok(_) => {}
+            Err(_) => self.metrics.record_protocol_error(),
+        }
+    }
+}
+
+/// Tenants of a shared receive server, keyed by identity.
+#[derive(Default)]
+pub struct TenantRouter {
+    tenants: HashMap<String, Tenant>,
+}
+
+impl TenantRouter {
+    /// Create a router with no tenants.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a tenant.
+    ///
+    /// Fails if its identity or its ID is already registered.
+    pub fn register(&mut self, tenant: Tenant) -> Result<()> {
+        let key = tenant.public_key.to_string();
+        if self.tenants.contains_key(&key) {
+            return Err(InteractiveError::Protocol(format!(
+                "Tenant for {} is already registered",
+                key
+            )));
+        }
+        if self.tenants.values().any(|t| t.id == tenant.id) {
+            return Err(InteractiveError::Protocol(format!(
+                "Tenant ID {} is already in use",
+                tenant.id
+            )));
+        }
+        self.tenants.insert(key, tenant);
+        Ok(())
+    }
+
+    /// Remove the tenant serving `public_key`.
+    pub fn unregister(&mut self, public_key: &PublicKey) -> Option<Tenant> {
+        self.tenants.remove(&public_key.to_string())
+    }
+
+    /// The tenant serving `recipient`, if any.
+    pub fn get(&self, recipient: &PublicKey) -> Option<&Tenant> {
+        self.tenants.get(&recipient.to_string())
+    }
+
+    /// The tenant with operator ID `id`, if any.
+    pub fn by_id(&self, id: &str) -> Option<&Tenant> {
+        self.tenants.values().find(|t| t.id == id)
+    }
+
+    /// All tenants, in no particular order.
+    pub fn tenants(&self) -> impl Iterator<Item = &Tenant> {
+        self.tenants.values()
+    }
+
+    /// Number of tenants.
+    pub fn len(&self) -> usize {
+        self.tenants.len()
+    }
+
+    /// Whether there are no tenants.
+    pub fn is_empty(&self) -> bool {
+        self.tenants.is_empty()
+    }
+
+    /// Handle a message from `peer` with the tenant serving `recipient`.
+    pub async fn handle_message(
+        &self,
+        recipient: &PublicKey,
+        msg: PaykitNoiseMessage,
+        peer: &PublicKey,
+    ) -> Result<Option<PaykitNoiseMessage>> {
+        self.tenant(recipient)?.handle_message(msg, peer).await
+    }
+
+    /// Handle the messages `sender` left in `recipient`'s inbox.
+    pub async fn process_inbox<T, R>(
+        &self,
+        recipient: &PublicKey,
+        transport: &T,
+        reader: &R,
+        sender: &PublicKey,
+        sender_noise_pk: &[u8; 32],
+    ) -> Result<Vec<InboxMessage>>
+    where
+        T: AuthenticatedTransport + Sync,
+        R: UnauthenticatedTransportRead + Sync,
+    {
+        self.tenant(recipient)?
+            .process_inbox(transport, reader, sender, sender_noise_pk)
+            .await
+    }
+
+    /// Each tenant's counters, sorted by tenant ID.
+    pub fn metrics(&self) -> Vec<(String, MetricsSnapshot)> {
+        let mut snapshots: Vec<_> = self
+            .tenants
+            .values()
+            .map(|t| (t.id.clone(), t.metrics.snapshot()))
+            .collect();
+        snapshots.sort_by(|a, b| a.0.cmp(&b.0));
+        snapshots
+    }
+
+    fn tenant(&self, recipient: &PublicKey) -> Result<&Tenant> {
+        self.get(recipient)
+            .ok_or_else(|| InteractiveError::Protocol(format!("No tenant serves {}", recipient)))
+    }
+}
@@ -1,15 +1,114 @@
+use crate::privacy::{self, ChannelStats, Frame, PrivacyProfile};
 use crate::{InteractiveError, PaykitNoiseChannel, PaykitNoiseMessage, Result};
 use async_trait::async_trait;
+use paykit_lib::protocol::{is_key_mismatch_hint, KEY_MISMATCH_HINT};
+#[cfg(feature = "tcp-transport")]
+use paykit_lib::proxy::Socks5Proxy;
 use pubky_noise::identity_payload::IdentityPayload;
 use pubky_noise::{NoiseClient, NoiseLink, NoiseServer, RingKeyProvider};
+use std::sync::Arc;
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
+/// Open the TCP connection for an outgoing Noise session.
+///
+/// Without a proxy, the host's addresses are raced with
+/// [`dial`](crate::dial::dial) so an unreachable IPv6 or IPv4 address does
+/// not stall the connection.
+///
+/// With a proxy, the connection is tunnelled through SOCKS5; when the proxy
+/// resolves names remotely (`socks5h`, as Tor does), `addr` is passed to it
+/// unresolved so neither DNS nor the payee's address leaks locally. Pass the
+/// proxy from `ProxyConfig::for_transport(TransportKind::Noise)`.
+#[cfg(feature = "tcp-transport")]
+pub async fn connect_tcp(addr: &str, proxy: Option<&Socks5Proxy>) -> Result<tokio::net::TcpStream> {
+    use tokio_socks::tcp::Socks5Stream;
+
+    let Some(proxy) = proxy else {
+        let (stream, _) = crate::dial::dial(addr, &crate::dial::DialConfig::default()).await?;
+        return Ok(stream);
+    };
+
+    let proxy_err = |e: tokio_socks::Error| {
+        InteractiveError::Transport(format!("SOCKS5 connect to {} failed: {}", addr, e))
+    };
+    let target = if proxy.remote_dns {
+        addr.to_string()
+    } else {
+        tokio::net::lookup_host(addr)
+            .await
+            .ok()
+            .and_then(|mut addrs| addrs.next())
+            .ok_or_else(|| InteractiveError::Transport(format!("Cannot resolve {}", addr)))?
+            .to_string()
+    };
+    let stream = match (&proxy.username, &proxy.password) {
+        (Some(username), password) => Socks5Stream::connect_with_password(
+            proxy.address().as_str(),
+            target.as_str(),
+            username,
+            password.as_deref().unwrap_or_default(),
+        )
+        .await
+        .map_err(proxy_err)?,
+        (None, _) => Socks5Stream::connect(proxy.address().as_str(), target.as_str())
+            .await
+            .map_err(proxy_err)?,
+    };
+    Ok(stream.into_inner())
+}
+
+/// Key provider that derives Noise static keys for one fixed epoch.
+///
+/// A server rotating its static key builds one `NoiseServer` per accepted
+/// epoch, each over an `EpochRing`, and passes them all to
+/// [`PubkyNoiseChannel::accept_any`].
+pub struct EpochRing<R> {
+    inner: Arc<R>,
+    epoch: u32,
+}
+
+impl<R: RingKeyProvider> EpochRing<R> {
+    /// Wrap `inner`, pinning key derivation to `epoch`.
+    pub fn new(inner: Arc<R>, epoch: u32) -> Self {
+        Self { inner, epoch }
+    }
+
+    /// The pinned epoch.
+    pub fn epoch(&self) -> u32 {
+        self.epoch
+    }
+}
+
+impl<R: RingKeyProvider> RingKeyProvider for EpochRing<R> {
+    fn derive_device_x25519(
+        &self,
+        kid: &str,
+        device_id: &[u8],
+        _epoch: u32,
+    ) -> std::result::Result<[u8; 32], pubky_noise::NoiseError> {
+        self.inner.derive_device_x25519(kid, device_id, self.epoch)
+    }
+
+    fn ed25519_pubkey(&self, kid: &str) -> std::result::Result<[u8; 32], pubky_noise::NoiseError> {
+        self.inner.ed25519_pubkey(kid)
+    }
+
+    fn sign_ed25519(
+        &self,
+        kid: &str,
+        msg: &[u8],
+    ) -> std::result::Result<[u8; 64], pubky_noise::NoiseError> {
+        self.inner.sign_ed25519(kid, msg)
+    }
+}
+
 /// A concrete implementation of `PaykitNoiseChannel` using `pubky-noise`.
 ///
 /// It wraps an underlying byte stream (`T`) and handles the Noise protocol encryption/decryption.
 pub struct PubkyNoiseChannel<S> {
     stream: S,
     link: NoiseLink,
+    stats: ChannelStats,
 }
 
 impl<S> PubkyNoiseChannel<S>
@@ -18,11 +117,71 @@ where
 {
     /// Create a new channel from an established Noise Link and an underlying stream.
     pub fn new(stream: S, link: NoiseLink) -> Self {
-        Self { stream, link }
+        Self {
+            stream,
+            link,
+            stats: ChannelStats::default(),
+        }
+    }
+
+    /// Pad messages and allow cover traffic according to `profile`.
+    ///
+    /// Both peers must use the same profile on a connection; set it right
+    /// after the handshake, before the first message.
+    pub fn with_privacy_profile(mut self, profile: PrivacyProfile) -> Self {
+        self.stats.profile = profile;
+        self
+    }
+
+    /// The privacy profile in effect.
+    pub fn privacy_profile(&self) -> PrivacyProfile {
+        self.stats.profile
+    }
+
+    /// Traffic counters, including padding and cover-traffic overhead.
+    pub fn stats(&self) -> &ChannelStats {
+        &self.stats
+    }
+
+    /// Send a cover frame, which the peer silently discards.
+    ///
+    /// Call when the channel has been idle for
+    /// [`PrivacyProfile::cover_interval`]. Does nothing if the profile does
+    /// not pad.
+    pub async fn send_cover(&mut self) -> Result<()> {
+        let Some(frame) = privacy::cover_frame(self.stats.profile) else {
+            return Ok(());
+        };
+        let wire = self.write_frame(&frame).await?;
+        self.stats.record_cover_sent(wire);
+        Ok(())
+    }
+
+    /// Encrypt and write one length-prefixed frame, returning the bytes written.
+    async fn write_frame(&mut self, plaintext: &[u8]) -> Result<usize> {
+        let ciphertext = self
+            .link
+            .encrypt(plaintext)
+            .map_err(|e| InteractiveError::Transport(format!("Encryption failed: {}", e)))?;
+
+        let len = (ciphertext.len() as u32).to_be_bytes();
+        self.stream
+            .write_all(&len)
+            .await
+            .map_err(|e| InteractiveError::Transport(format!("Write failed: {}", e)))?;
+        self.stream
+            .write_all(&ciphertext)
+            .await
+            .map_err(|e| InteractiveError::Transport(format!("Write failed: {}", e)))?;
+
+        Ok(len.len() + ciphertext.len())
     }
 
     /// Perform a client-side handshake and return a new channel.
     ///
+    /// Fails with [`InteractiveError::KeyMismatch`] if the server no longer
+    /// accepts `server_static_pub`.
+    ///
     /// * `client`: The initialized NoiseClient.
     /// * `stream`: The underlying transport stream (TCP, etc.).
     /// * `server_static_pub`: The server's static public key (32 bytes).
@@ -69,6 +228,9 @@ where
         stream.read_exact(&mut response).await.map_err(|e| {
             InteractiveError::Transport(format!("Failed to read handshake response: {}", e))
         })?;
+        if is_key_mismatch_hint(&response) {
+            return Err(InteractiveError::KeyMismatch);
+        }
 
         // 4. Complete the handshake
         let link =
@@ -77,7 +239,7 @@ where
             })?;
 
         // 5. Channel is now ready for encrypted transport messages
-        Ok(Self { stream, link })
+        Ok(Self::new(stream, link))
     }
 
     /// Accept an incoming client connection (server-side handshake).
@@ -98,6 +260,19 @@ where
     /// the authenticated client identity (Ed25519 public key, etc.).
     pub async fn accept<R: RingKeyProvider>(
         server: &NoiseServer<R, ()>,
+        stream: S,
+    ) -> Result<(Self, IdentityPayload)> {
+        Self::accept_any(std::slice::from_ref(server), stream).await
+    }
+
+    /// Accept an incoming connection addressed to any of several static keys.
+    ///
+    /// Used while a Noise key rotation is in its overlap window: pass one
+    /// server per accepted key epoch. If none of them can process the
+    /// handshake, the client is sent [`KEY_MISMATCH_HINT`] so it knows to
+    /// refetch the endpoint record.
+    pub async fn accept_any<R: RingKeyProvider>(
+        servers: &[NoiseServer<R, ()>],
         mut stream: S,
     ) -> Result<(Self, IdentityPayload)> {
         // 1. Read length-prefixed client handshake initiation message
@@ -114,9 +289,28 @@ where
             .map_err(|e| InteractiveError::Transport(format!("Failed to read handshake: {}", e)))?;
 
         // 2. Process the handshake - validates client identity and prepares response
-        let (hs, identity, response) =
-            pubky_noise::datalink_adapter::server_accept_ik(server, &first_msg)
-                .map_err(|e| InteractiveError::Transport(format!("Handshake failed: {}", e)))?;
+        let mut last_error = None;
+        let mut accepted = None;
+        for server in servers {
+            match pubky_noise::datalink_adapter::server_accept_ik(server, &first_msg) {
+                Ok(result) => {
+                    accepted = Some(result);
+                    break;
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+        let Some((hs, identity, response)) = accepted else {
+            // Most failures here are a client encrypting to a retired key.
+            // The hint reveals nothing, and a client retries at most once.
+            let len = (KEY_MISMATCH_HINT.len() as u32).to_be_bytes();
+            let _ = stream.write_all(&len).await;
+            let _ = stream.write_all(KEY_MISMATCH_HINT).await;
+            return Err(InteractiveError::Transport(format!(
+                "Handshake failed: {}",
+                last_error.map(|e| e.to_string()).unwrap_or_default()
+            )));
+        };
 
         // 3. Send length-prefixed handshake response
         let len = (response.len() as u32).to_be_bytes();
@@ -133,7 +327,7 @@ where
         })?;
 
         // 5. Channel is now ready for encrypted transport messages
-        Ok((Self { stream, link }, identity))
+        Ok((Self::new(stream, link), identity))
     }
 }
 
@@ -147,52 +341,47 @@ where
         let json_bytes =
             serde_json::to_vec(&msg).map_err(|e| InteractiveError::Serialization(e.to_string()))?;
 
-        // 2. Encrypt
-        let ciphertext = self
-            .link
-            .encrypt(&json_bytes)
-            .map_err(|e| InteractiveError::Transport(format!("Encryption failed: {}", e)))?;
-
-        // 3. Send length-prefixed
-        let len = (ciphertext.len() as u32).to_be_bytes();
-        self.stream
-            .write_all(&len)
-            .await
-            .map_err(|e| InteractiveError::Transport(format!("Write failed: {}", e)))?;
-        self.stream
-            .write_all(&ciphertext)
-            .await
-            .map_err(|e| InteractiveError::Transport(format!("Write failed: {}", e)))?;
+        // 2. Pad, encrypt and send length-prefixed
+        let frame = privacy::pad(&json_bytes, self.stats.profile);
+        let wire = self.write_frame(&frame).await?;
+        self.stats.record_sent(json_bytes.len(), wire);
 
         Ok(())
     }
 
     async fn recv(&mut self) -> Result<PaykitNoiseMessage> {
-        // 1. Read length
-        let mut len_bytes = [0u8; 4];
-        self.stream
-            .read_exact(&mut len_bytes)
-            .await
-            .map_err(|e| InteractiveError::Transport(format!("Read failed: {}", e)))?;
-        let len = u32::from_be_bytes(len_bytes) as usize;
+        loop {
+            // 1. Read length
+            let mut len_bytes = [0u8; 4];
+            self.stream
+                .read_exact(&mut len_bytes)
+                .await
+                .map_err(|e| InteractiveError::Transport(format!("Read failed: {}", e)))?;
+            let len = u32::from_be_bytes(len_bytes) as usize;
 
-        // 2. Read ciphertext
-        let mut ciphertext = vec![0u8; len];
-        self.stream
-            .read_exact(&mut ciphertext)
-            .await
-            .map_err(|e| InteractiveError::Transport(format!("Read failed: {}", e)))?;
+            // 2. Read ciphertext
+            let mut ciphertext = vec![0u8; len];
+            self.stream
+                .read_exact(&mut ciphertext)
+                .await
+                .map_err(|e| InteractiveError::Transport(format!("Read failed: {}", e)))?;
 
-        // 3. Decrypt
-        let plaintext = self
-            .link
-            .decrypt(&ciphertext)
-            .map_err(|e| InteractiveError::Transport(format!("Decryption failed: {}", e)))?;
+            // 3. Decrypt and strip padding, skipping cover traffic
+            let plaintext = self
+                .link
+                .decrypt(&ciphertext)
+                .map_err(|e| InteractiveError::Transport(format!("Decryption failed: {}", e)))?;
+            let frame = privacy::unpad(&plaintext, self.stats.profile)?;
+            self.stats.record_received(&frame, len_bytes.len() + len);
+            let Frame::Message(payload) = frame else {
+                continue;
+            };
 
-        // 4. Deserialize
-        let msg = serde_json::from_slice(&plaintext)
-            .map_err(|e| InteractiveError::Serialization(e.to_string()))?;
+            // 4. Deserialize
+            let msg = serde_json::from_slice(&payload)
+                .map_err(|e| InteractiveError::Serialization(e.to_string()))?;
 
-        Ok(msg)
+            return Ok(msg);
+        }
     }
 }
@@ -4,8 +4,10 @@
 //! TCP connections and Noise_IK handshakes (not mocks).
 
 use paykit_interactive::{
-    transport::PubkyNoiseChannel, PaykitInteractiveManager, PaykitNoiseChannel, PaykitNoiseMessage,
-    PaykitReceipt, PaykitStorage, ReceiptGenerator,
+    proximity::{LoopbackTransport, ProximityNoiseChannel},
+    transport::PubkyNoiseChannel,
+    PaykitInteractiveManager, PaykitNoiseChannel, PaykitNoiseMessage, PaykitReceipt, PaykitStorage,
+    ReceiptGenerator,
 };
 use paykit_lib::MethodId;
 use pubky_noise::{NoiseClient, NoiseServer, RingKeyProvider};
@@ -463,3 +465,68 @@ async fn test_complete_payment_flow_encrypted() {
 
     println!("✅ Complete payment flow over real Noise test passed");
 }
+
+#[tokio::test]
+async fn test_proximity_noise_channel_loopback() {
+    // Test 4: Noise handshake and messages over a small-MTU proximity link
+
+    let server_seed = generate_test_seed("server_proximity");
+    let server_ring = Arc::new(DummyRing::new(server_seed));
+    let server_pk = get_server_pubkey(&server_ring, b"server_device_id");
+    let server = Arc::new(NoiseServer::<DummyRing, ()>::new_direct(
+        "server_kid4",
+        b"server_device_id",
+        server_ring,
+    ));
+
+    // Tiny frames force the handshake and transport messages to fragment
+    let (client_link, server_link) = LoopbackTransport::pair_with_frame_size(32);
+
+    let server_handle = tokio::spawn(async move {
+        let (mut channel, _identity) = ProximityNoiseChannel::accept(&server, server_link)
+            .await
+            .expect("Failed to accept proximity connection");
+
+        let msg = channel.recv().await.expect("Failed to receive message");
+        match msg {
+            PaykitNoiseMessage::OfferPrivateEndpoint {
+                method_id,
+                endpoint,
+            } => {
+                assert_eq!(method_id.0, "onchain");
+                assert_eq!(endpoint, "bc1qproximity");
+            }
+            _ => panic!("Unexpected message type"),
+        }
+
+        channel
+            .send(PaykitNoiseMessage::Ack)
+            .await
+            .expect("Failed to send response");
+    });
+
+    let client_seed = generate_test_seed("client_proximity");
+    let client_ring = Arc::new(DummyRing::new(client_seed));
+    let client =
+        NoiseClient::<DummyRing, ()>::new_direct("client_kid", b"client_device_id", client_ring);
+
+    let mut channel = ProximityNoiseChannel::connect(&client, client_link, &server_pk)
+        .await
+        .expect("Failed to connect proximity channel");
+
+    channel
+        .send(PaykitNoiseMessage::OfferPrivateEndpoint {
+            method_id: MethodId("onchain".to_string()),
+            endpoint: "bc1qproximity".to_string(),
+        })
+        .await
+        .expect("Failed to send message");
+
+    let msg = channel.recv().await.expect("Failed to receive response");
+    assert!(matches!(msg, PaykitNoiseMessage::Ack));
+
+    timeout(Duration::from_secs(5), server_handle)
+        .await
+        .expect("Server timeout")
+        .expect("Server panicked");
+}
@@ -163,6 +163,49 @@ async fn test_wrong_payee_error() {
     }
 }
 
+#[tokio::test]
+async fn test_payer_memo_sanitized_by_payee() {
+    let payer_pk = test_pubkey("payer");
+    let payee_pk = test_pubkey("payee");
+
+    let storage =
+        Arc::new(Box::new(MockStorage::new()) as Box<dyn paykit_interactive::PaykitStorage>);
+    let generator = Arc::new(
+        Box::new(MockReceiptGenerator::new()) as Box<dyn paykit_interactive::ReceiptGenerator>
+    );
+    let manager = PaykitInteractiveManager::new(storage, generator);
+
+    // A raw memo, as a misbehaving payer might send it
+    let receipt = PaykitReceipt::new(
+        "receipt_memo".to_string(),
+        payer_pk.clone(),
+        payee_pk.clone(),
+        MethodId("lightning".to_string()),
+        Some("1000".to_string()),
+        Some("SAT".to_string()),
+        json!({"memo": "thanks\u{202E} for\n lunch\u{0000}"}),
+    );
+
+    let response = manager
+        .handle_message(
+            PaykitNoiseMessage::RequestReceipt {
+                provisional_receipt: receipt,
+            },
+            &payer_pk,
+            &payee_pk,
+        )
+        .await
+        .unwrap();
+
+    match response {
+        Some(PaykitNoiseMessage::ConfirmReceipt { receipt }) => {
+            assert_eq!(receipt.metadata["memo"], "thanks for lunch");
+            assert_eq!(receipt.memo().as_deref(), Some("thanks for lunch"));
+        }
+        _ => panic!("Expected confirmation"),
+    }
+}
+
 #[tokio::test]
 async fn test_offer_private_endpoint_api() {
     let (mut channel1, mut channel2) = MockNoiseChannel::pair();
@@ -250,3 +293,1073 @@ async fn test_receipt_id_mismatch_error() {
         assert!(e.to_string().contains("Receipt ID mismatch"));
     }
 }
+
+#[tokio::test]
+async fn test_blocked_payee_refused() {
+    let payer_pk = test_pubkey("payer");
+    let payee_pk = test_pubkey("payee");
+
+    let mut policy = paykit_lib::policy::TrustPolicy::new();
+    policy.block(&payee_pk.to_string(), Some("reported scam"));
+
+    let storage =
+        Arc::new(Box::new(MockStorage::new()) as Box<dyn paykit_interactive::PaykitStorage>);
+    let generator = Arc::new(
+        Box::new(MockReceiptGenerator::new()) as Box<dyn paykit_interactive::ReceiptGenerator>
+    );
+    let manager =
+        PaykitInteractiveManager::new(storage, generator).with_trust_policy(policy.shared());
+
+    let (mut payer_channel, _payee_channel) = MockNoiseChannel::pair();
+
+    let provisional_receipt = PaykitReceipt::new(
+        "receipt_blocked".to_string(),
+        payer_pk,
+        payee_pk,
+        MethodId("lightning".to_string()),
+        Some("1000".to_string()),
+        Some("SAT".to_string()),
+        json!({}),
+    );
+
+    let err = manager
+        .initiate_payment(&mut payer_channel, provisional_receipt)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("reported scam"));
+}
+
+struct AutoApprove(pubky::Keypair);
+
+#[async_trait::async_trait]
+impl paykit_interactive::ApprovalHandler for AutoApprove {
+    async fn review(
+        &self,
+        pending: &paykit_lib::approvals::PendingApproval,
+        _requester: &PublicKey,
+    ) -> paykit_interactive::Result<Option<paykit_lib::approvals::SignedApproval>> {
+        Ok(Some(paykit_lib::approvals::SignedApproval::sign(
+            &self.0,
+            pending,
+            paykit_lib::approvals::ApprovalDecision::Approve,
+            pending.created_at,
+        )))
+    }
+}
+
+#[tokio::test]
+async fn test_remote_cosigner_approval() {
+    use paykit_lib::approvals::{ApprovalPolicy, ApprovalQueue, ApprovalStatus, PaymentProposal};
+
+    let initiator_pk = test_pubkey("initiator");
+    let cosigner = pubky::Keypair::random();
+    let cosigner_pk = cosigner.public_key();
+
+    let policy = ApprovalPolicy::new(10_000, 1, vec![cosigner_pk.to_string()]).unwrap();
+    let queue = ApprovalQueue::new(policy).unwrap().shared();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let pending = queue
+        .write()
+        .unwrap()
+        .submit(PaymentProposal::new("lnbc1...", "lightning", 50_000), now);
+
+    let new_manager = || {
+        let storage =
+            Arc::new(Box::new(MockStorage::new()) as Box<dyn paykit_interactive::PaykitStorage>);
+        let generator =
+            Arc::new(Box::new(MockReceiptGenerator::new())
+                as Box<dyn paykit_interactive::ReceiptGenerator>);
+        PaykitInteractiveManager::new(storage, generator)
+    };
+    let initiator = new_manager().with_approval_queue(queue.clone());
+    let cosigner_manager = new_manager().with_approval_handler(Arc::new(AutoApprove(cosigner)));
+
+    let (mut initiator_channel, mut cosigner_channel) = MockNoiseChannel::pair();
+    let cosigner_handle = tokio::spawn(async move {
+        let msg = cosigner_channel.recv().await.unwrap();
+        let response = cosigner_manager
+            .handle_message(msg, &initiator_pk, &cosigner_pk)
+            .await
+            .unwrap();
+        cosigner_channel.send(response.unwrap()).await.unwrap();
+    });
+
+    let approval = initiator
+        .request_approval(&mut initiator_channel, pending.clone())
+        .await
+        .unwrap();
+    cosigner_handle.await.unwrap();
+
+    assert_eq!(approval.approval_id, pending.approval_id);
+    let queue = queue.read().unwrap();
+    assert_eq!(
+        queue.get(&pending.approval_id).unwrap().status,
+        ApprovalStatus::Approved
+    );
+}
+
+#[tokio::test]
+async fn test_device_sync_over_noise() {
+    use paykit_interactive::sync::SyncState;
+
+    let my_pk = test_pubkey("me");
+    let merchant_pk = test_pubkey("merchant");
+
+    let phone_storage =
+        Arc::new(Box::new(MockStorage::new()) as Box<dyn paykit_interactive::PaykitStorage>);
+    let desktop_storage =
+        Arc::new(Box::new(MockStorage::new()) as Box<dyn paykit_interactive::PaykitStorage>);
+    let new_manager = |storage: Arc<Box<dyn paykit_interactive::PaykitStorage>>, device| {
+        let generator =
+            Arc::new(Box::new(MockReceiptGenerator::new())
+                as Box<dyn paykit_interactive::ReceiptGenerator>);
+        let state = Arc::new(parking_lot::Mutex::new(SyncState::new(device)));
+        PaykitInteractiveManager::new(storage, generator).with_device_sync(state)
+    };
+    let phone = new_manager(phone_storage.clone(), "phone");
+    let desktop = new_manager(desktop_storage.clone(), "desktop");
+
+    // The phone paid the merchant; the desktop got a private endpoint
+    let receipt = PaykitReceipt::new(
+        "receipt_sync".to_string(),
+        my_pk.clone(),
+        merchant_pk.clone(),
+        MethodId("lightning".to_string()),
+        Some("1000".to_string()),
+        Some("SAT".to_string()),
+        json!({}),
+    );
+    phone_storage.save_receipt(&receipt).await.unwrap();
+    desktop
+        .handle_message(
+            PaykitNoiseMessage::OfferPrivateEndpoint {
+                method_id: MethodId("onchain".to_string()),
+                endpoint: "bc1qprivate".to_string(),
+            },
+            &merchant_pk,
+            &my_pk,
+        )
+        .await
+        .unwrap();
+
+    let (mut phone_channel, mut desktop_channel) = MockNoiseChannel::pair();
+    let my_pk_clone = my_pk.clone();
+    let desktop_handle = tokio::spawn(async move {
+        for _ in 0..2 {
+            let msg = desktop_channel.recv().await.unwrap();
+            let response = desktop
+                .handle_message(msg, &my_pk_clone, &my_pk_clone)
+                .await
+                .unwrap();
+            desktop_channel.send(response.unwrap()).await.unwrap();
+        }
+    });
+
+    let report = phone.sync_with_device(&mut phone_channel).await.unwrap();
+    desktop_handle.await.unwrap();
+
+    assert_eq!(report.applied.len(), 1);
+    assert_eq!(
+        phone_storage
+            .get_private_endpoint(&merchant_pk, &MethodId("onchain".to_string()))
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("bc1qprivate")
+    );
+    assert_eq!(
+        desktop_storage.get_receipt("receipt_sync").await.unwrap(),
+        Some(receipt)
+    );
+}
+
+#[tokio::test]
+async fn test_device_sync_refused_for_other_identity() {
+    use paykit_interactive::sync::{SyncState, VectorClock};
+
+    let storage =
+        Arc::new(Box::new(MockStorage::new()) as Box<dyn paykit_interactive::PaykitStorage>);
+    let generator = Arc::new(
+        Box::new(MockReceiptGenerator::new()) as Box<dyn paykit_interactive::ReceiptGenerator>
+    );
+    let state = Arc::new(parking_lot::Mutex::new(SyncState::new("phone")));
+    let manager = PaykitInteractiveManager::new(storage, generator).with_device_sync(state);
+
+    let response = manager
+        .handle_message(
+            PaykitNoiseMessage::SyncRequest {
+                device_id: "laptop".to_string(),
+                clock: VectorClock::new(),
+            },
+            &test_pubkey("stranger"),
+            &test_pubkey("me"),
+        )
+        .await
+        .unwrap();
+    assert!(matches!(
+        response,
+        Some(PaykitNoiseMessage::Error { code, .. }) if code == "NOT_OWN_DEVICE"
+    ));
+}
+
+/// In-memory homeserver: every identity's files, readable by anyone and
+/// writable by their owner through [`HomeserverSession`].
+#[derive(Clone, Default)]
+struct MockHomeserver {
+    files: Arc<std::sync::Mutex<std::collections::BTreeMap<(String, String), String>>>,
+}
+
+struct HomeserverSession {
+    server: MockHomeserver,
+    owner: String,
+}
+
+impl MockHomeserver {
+    fn session(&self, owner: &PublicKey) -> HomeserverSession {
+        HomeserverSession {
+            server: self.clone(),
+            owner: owner.to_string(),
+        }
+    }
+
+    fn count(&self, owner: &PublicKey, prefix: &str) -> usize {
+        let owner = owner.to_string();
+        let files = self.files.lock().unwrap();
+        files
+            .keys()
+            .filter(|(o, path)| *o == owner && path.starts_with(prefix))
+            .count()
+    }
+}
+
+#[async_trait::async_trait]
+impl paykit_lib::UnauthenticatedTransportRead for MockHomeserver {
+    async fn fetch_supported_payments(
+        &self,
+        _payee: &PublicKey,
+    ) -> paykit_lib::Result<paykit_lib::SupportedPayments> {
+        Ok(Default::default())
+    }
+
+    async fn fetch_payment_endpoint(
+        &self,
+        _payee: &PublicKey,
+        _method: &MethodId,
+    ) -> paykit_lib::Result<Option<paykit_lib::EndpointData>> {
+        Ok(None)
+    }
+
+    async fn fetch_known_contacts(&self, _owner: &PublicKey) -> paykit_lib::Result<Vec<PublicKey>> {
+        Ok(Vec::new())
+    }
+
+    async fn get(&self, owner: &PublicKey, path: &str) -> paykit_lib::Result<Option<String>> {
+        let files = self.files.lock().unwrap();
+        Ok(files.get(&(owner.to_string(), path.to_string())).cloned())
+    }
+
+    async fn list_directory(
+        &self,
+        owner: &PublicKey,
+        path: &str,
+    ) -> paykit_lib::Result<Vec<String>> {
+        let owner = owner.to_string();
+        let files = self.files.lock().unwrap();
+        Ok(files
+            .keys()
+            .filter(|(o, _)| *o == owner)
+            .filter_map(|(_, p)| p.strip_prefix(path).map(str::to_string))
+            .collect())
+    }
+}
+
+#[async_trait::async_trait]
+impl paykit_lib::AuthenticatedTransport for HomeserverSession {
+    async fn upsert_payment_endpoint(
+        &self,
+        _method: &MethodId,
+        _data: &paykit_lib::EndpointData,
+    ) -> paykit_lib::Result<()> {
+        Ok(())
+    }
+
+    async fn remove_payment_endpoint(&self, _method: &MethodId) -> paykit_lib::Result<()> {
+        Ok(())
+    }
+
+    async fn put(&self, path: &str, content: &str) -> paykit_lib::Result<()> {
+        let mut files = self.server.files.lock().unwrap();
+        files.insert((self.owner.clone(), path.to_string()), content.to_string());
+        Ok(())
+    }
+
+    async fn get(&self, path: &str) -> paykit_lib::Result<Option<String>> {
+        let files = self.server.files.lock().unwrap();
+        Ok(files.get(&(self.owner.clone(), path.to_string())).cloned())
+    }
+
+    async fn delete(&self, path: &str) -> paykit_lib::Result<()> {
+        let mut files = self.server.files.lock().unwrap();
+        files.remove(&(self.owner.clone(), path.to_string()));
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_receipt_request_through_inbox() {
+    use paykit_interactive::PeerInbox;
+
+    let payer_pk = test_pubkey("payer");
+    let payee_pk = test_pubkey("payee");
+    let payer_noise_sk = [1u8; 32];
+    let payee_noise_sk = [2u8; 32];
+    let payer_noise_pk = pubky_noise::kdf::x25519_pk_from_sk(&payer_noise_sk);
+    let payee_noise_pk = pubky_noise::kdf::x25519_pk_from_sk(&payee_noise_sk);
+
+    let server = MockHomeserver::default();
+    let payer_session = server.session(&payer_pk);
+    let payee_session = server.session(&payee_pk);
+    let payer_inbox = PeerInbox::new(payer_pk.clone(), payer_noise_sk);
+    let payee_inbox = PeerInbox::new(payee_pk.clone(), payee_noise_sk);
+
+    let new_manager = || {
+        let storage =
+            Arc::new(Box::new(MockStorage::new()) as Box<dyn paykit_interactive::PaykitStorage>);
+        let generator =
+            Arc::new(Box::new(MockReceiptGenerator::new())
+                as Box<dyn paykit_interactive::ReceiptGenerator>);
+        (
+            storage.clone(),
+            PaykitInteractiveManager::new(storage, generator),
+        )
+    };
+    let (payer_storage, payer) = new_manager();
+    let (_, payee) = new_manager();
+
+    // Payer asks for a receipt while the payee is offline
+    let provisional = PaykitReceipt::new(
+        "receipt_inbox".to_string(),
+        payer_pk.clone(),
+        payee_pk.clone(),
+        MethodId("lightning".to_string()),
+        Some("1000".to_string()),
+        Some("SAT".to_string()),
+        json!({}),
+    );
+    let request_id = payer_inbox
+        .send(
+            &payer_session,
+            &payee_pk,
+            &payee_noise_pk,
+            PaykitNoiseMessage::RequestReceipt {
+                provisional_receipt: provisional,
+            },
+        )
+        .await
+        .unwrap();
+
+    // Payee comes online, confirms and leaves the receipt in the payer's inbox
+    let handled = payee
+        .process_inbox(
+            &payee_inbox,
+            &payee_session,
+            &server,
+            &payer_pk,
+            &payer_noise_pk,
+            &payee_pk,
+        )
+        .await
+        .unwrap();
+    assert_eq!(handled.len(), 1);
+    assert_eq!(handled[0].message_id, request_id);
+    assert!(payee_inbox
+        .receive(&payee_session, &server, &payer_pk)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Payer sees the read receipt, which deletes the request
+    let receipts = payer_inbox
+        .collect_receipts(&payer_session, &server, &payee_pk)
+        .await
+        .unwrap();
+    assert_eq!(receipts.len(), 1);
+    assert_eq!(receipts[0].message_id, request_id);
+    assert_eq!(server.count(&payer_pk, "/pub/paykit.app/v0/inbox/"), 0);
+
+    // Payer picks up the confirmed receipt
+    let handled = payer
+        .process_inbox(
+            &payer_inbox,
+            &payer_session,
+            &server,
+            &payee_pk,
+            &payee_noise_pk,
+            &payer_pk,
+        )
+        .await
+        .unwrap();
+    assert!(matches!(
+        &handled[0].message,
+        PaykitNoiseMessage::ConfirmReceipt { receipt } if receipt.receipt_id == "receipt_inbox"
+    ));
+    assert!(payer_storage
+        .get_receipt("receipt_inbox")
+        .await
+        .unwrap()
+        .is_some());
+
+    // The payee's next poll cleans up its receipt for the deleted request
+    payee_inbox
+        .receive(&payee_session, &server, &payer_pk)
+        .await
+        .unwrap();
+    assert_eq!(
+        server.count(&payee_pk, "/pub/paykit.app/v0/inbox-receipts/"),
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_lost_request_is_retransmitted() {
+    use paykit_interactive::{FlowConfig, FlowState};
+    use std::time::Duration;
+
+    let payer_pk = test_pubkey("payer");
+    let payee_pk = test_pubkey("payee");
+    let config = FlowConfig::default().with_exchange_timeout(Duration::from_millis(50));
+
+    let payer_manager = PaykitInteractiveManager::new(
+        Arc::new(Box::new(MockStorage::new()) as Box<dyn paykit_interactive::PaykitStorage>),
+        Arc::new(
+            Box::new(MockReceiptGenerator::new()) as Box<dyn paykit_interactive::ReceiptGenerator>
+        ),
+    )
+    .with_flow_config(config);
+    let payee_manager = Arc::new(
+        PaykitInteractiveManager::new(
+            Arc::new(Box::new(MockStorage::new()) as Box<dyn paykit_interactive::PaykitStorage>),
+            Arc::new(Box::new(MockReceiptGenerator::new())
+                as Box<dyn paykit_interactive::ReceiptGenerator>),
+        )
+        .with_flow_config(config),
+    );
+
+    let (mut payer_channel, mut payee_channel) = MockNoiseChannel::pair();
+    let receipt = PaykitReceipt::new(
+        "receipt_retry".to_string(),
+        payer_pk.clone(),
+        payee_pk.clone(),
+        MethodId("lightning".to_string()),
+        Some("1000".to_string()),
+        Some("SAT".to_string()),
+        json!({}),
+    );
+
+    let payee = payee_manager.clone();
+    let (payer, me) = (payer_pk.clone(), payee_pk.clone());
+    let payee_handle = tokio::spawn(async move {
+        // The first request is lost
+        let _ = payee_channel.recv().await.unwrap();
+        payee
+            .serve_receipt_request(&mut payee_channel, &payer, &me)
+            .await
+    });
+
+    let confirmed = payer_manager
+        .initiate_payment(&mut payer_channel, receipt)
+        .await
+        .unwrap();
+    let served = payee_handle.await.unwrap().unwrap().unwrap();
+
+    assert_eq!(confirmed, served);
+    assert_eq!(
+        payer_manager.flow_state("receipt_retry"),
+        Some(FlowState::Completed)
+    );
+    assert_eq!(
+        payee_manager.flow_state("receipt_retry"),
+        Some(FlowState::Completed)
+    );
+
+    // A late duplicate of the request gets the same receipt again
+    let provisional = confirmed.clone();
+    let reply = payee_manager
+        .handle_message(
+            PaykitNoiseMessage::RequestReceipt {
+                provisional_receipt: provisional,
+            },
+            &payer_pk,
+            &payee_pk,
+        )
+        .await
+        .unwrap();
+    match reply {
+        Some(PaykitNoiseMessage::ConfirmReceipt { receipt }) => assert_eq!(receipt, confirmed),
+        other => panic!("Expected ConfirmReceipt, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_unanswered_request_times_out() {
+    use paykit_interactive::{FlowConfig, FlowState, InteractiveError};
+    use std::time::Duration;
+
+    let payer_manager = PaykitInteractiveManager::new(
+        Arc::new(Box::new(MockStorage::new()) as Box<dyn paykit_interactive::PaykitStorage>),
+        Arc::new(
+            Box::new(MockReceiptGenerator::new()) as Box<dyn paykit_interactive::ReceiptGenerator>
+        ),
+    )
+    .with_flow_config(
+        FlowConfig::default()
+            .with_exchange_timeout(Duration::from_millis(20))
+            .with_max_attempts(2),
+    );
+
+    // Keep the other end open but silent
+    let (mut payer_channel, mut payee_channel) = MockNoiseChannel::pair();
+    let receipt = PaykitReceipt::new(
+        "receipt_silent".to_string(),
+        test_pubkey("payer"),
+        test_pubkey("payee"),
+        MethodId("lightning".to_string()),
+        None,
+        None,
+        json!({}),
+    );
+
+    let result = payer_manager
+        .initiate_payment(&mut payer_channel, receipt)
+        .await;
+    assert!(matches!(result, Err(InteractiveError::Timeout(_))));
+    assert_eq!(
+        payer_manager.flow_state("receipt_silent"),
+        Some(FlowState::TimedOut)
+    );
+
+    // Sent twice
+    for _ in 0..2 {
+        assert!(matches!(
+            payee_channel.recv().await.unwrap(),
+            PaykitNoiseMessage::RequestReceipt { .. }
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_concurrent_peer_sessions() {
+    use paykit_interactive::session::{SessionDirection, SessionLimits, SessionRegistry};
+
+    let payee_pk = test_pubkey("payee");
+    let payee_manager = Arc::new(PaykitInteractiveManager::new(
+        Arc::new(Box::new(MockStorage::new()) as Box<dyn paykit_interactive::PaykitStorage>),
+        Arc::new(
+            Box::new(MockReceiptGenerator::new()) as Box<dyn paykit_interactive::ReceiptGenerator>
+        ),
+    ));
+    let registry = SessionRegistry::new_shared(SessionLimits::default());
+
+    let mut payers = Vec::new();
+    let mut servers = Vec::new();
+    for i in 0..3 {
+        let payer_pk = test_pubkey("payer");
+        let (payer_channel, payee_channel) = MockNoiseChannel::pair();
+        let mut session = registry
+            .open(
+                payer_pk.clone(),
+                format!("session_{}", i),
+                SessionDirection::Incoming,
+                payee_channel,
+            )
+            .unwrap();
+        let manager = payee_manager.clone();
+        let me = payee_pk.clone();
+        servers.push(tokio::spawn(async move {
+            // Serve the request and its Ack
+            session.serve_next(&manager, &me).await.unwrap();
+            session.serve_next(&manager, &me).await.unwrap();
+            session
+        }));
+        payers.push((payer_pk, payer_channel));
+    }
+    assert_eq!(registry.len(), 3);
+
+    let mut payer_handles = Vec::new();
+    for (i, (payer_pk, mut channel)) in payers.into_iter().enumerate() {
+        let payee_pk = payee_pk.clone();
+        payer_handles.push(tokio::spawn(async move {
+            let manager = PaykitInteractiveManager::new(
+                Arc::new(
+                    Box::new(MockStorage::new()) as Box<dyn paykit_interactive::PaykitStorage>
+                ),
+                Arc::new(Box::new(MockReceiptGenerator::new())
+                    as Box<dyn paykit_interactive::ReceiptGenerator>),
+            );
+            let receipt = PaykitReceipt::new(
+                format!("session_receipt_{}", i),
+                payer_pk.clone(),
+                payee_pk,
+                MethodId("lightning".to_string()),
+                Some("1000".to_string()),
+                Some("SAT".to_string()),
+                json!({}),
+            );
+            manager.initiate_payment(&mut channel, receipt).await.unwrap();
+            (payer_pk, channel)
+        }));
+    }
+
+    let mut payers = Vec::new();
+    for handle in payer_handles {
+        payers.push(handle.await.unwrap());
+    }
+    let mut sessions = Vec::new();
+    for server in servers {
+        sessions.push(server.await.unwrap());
+    }
+
+    // A push queued for one peer goes out on its session only
+    let (payer_pk, channel) = &mut payers[0];
+    registry
+        .send_to_peer(payer_pk, PaykitNoiseMessage::Ack)
+        .unwrap();
+    let session = sessions
+        .iter_mut()
+        .find(|s| s.handle().peer() == payer_pk)
+        .unwrap();
+    assert_eq!(session.send_queued().await.unwrap(), 1);
+    assert!(matches!(
+        channel.recv().await.unwrap(),
+        PaykitNoiseMessage::Ack
+    ));
+    assert_eq!(session.handle().info().messages_received, 2);
+
+    // Teardown
+    assert_eq!(registry.close_all(), 3);
+    drop(sessions);
+    assert!(registry.is_empty());
+}
+
+#[tokio::test]
+async fn test_inbound_rules_queue_and_approve() {
+    use paykit_interactive::rules::DecisionSource;
+    use paykit_interactive::{InboundRules, RuleAction, RulesEngine};
+
+    let contact = test_pubkey("contact");
+    let stranger = test_pubkey("stranger");
+    let payee_pk = test_pubkey("payee");
+
+    let mut policy = paykit_lib::policy::TrustPolicy::new();
+    policy.trust(&contact.to_string(), None);
+    let rules = InboundRules::from_json(
+        r#"{"rules": [{"name": "small-from-contacts",
+                       "when": {"max_amount_sats": 9999, "trust": ["trusted"]},
+                       "action": "accept"}]}"#,
+    )
+    .unwrap();
+    let engine = Arc::new(RulesEngine::new(rules));
+
+    let storage =
+        Arc::new(Box::new(MockStorage::new()) as Box<dyn paykit_interactive::PaykitStorage>);
+    let generator = Arc::new(
+        Box::new(MockReceiptGenerator::new()) as Box<dyn paykit_interactive::ReceiptGenerator>
+    );
+    let manager = PaykitInteractiveManager::new(storage.clone(), generator)
+        .with_trust_policy(policy.shared())
+        .with_inbound_rules(engine.clone());
+
+    let request = |id: &str, payer: &PublicKey| PaykitNoiseMessage::RequestReceipt {
+        provisional_receipt: PaykitReceipt::new(
+            id.to_string(),
+            payer.clone(),
+            payee_pk.clone(),
+            MethodId("lightning".to_string()),
+            Some("5000".to_string()),
+            Some("SAT".to_string()),
+            json!({}),
+        ),
+    };
+
+    // A small request from a contact is confirmed right away
+    let reply = manager
+        .handle_message(request("r_contact", &contact), &contact, &payee_pk)
+        .await
+        .unwrap();
+    assert!(matches!(
+        reply,
+        Some(PaykitNoiseMessage::ConfirmReceipt { .. })
+    ));
+
+    // The same request from a stranger waits for review
+    let reply = manager
+        .handle_message(request("r_stranger", &stranger), &stranger, &payee_pk)
+        .await
+        .unwrap();
+    match reply {
+        Some(PaykitNoiseMessage::Error { code, .. }) => assert_eq!(code, "QUEUED_FOR_REVIEW"),
+        other => panic!("Expected QUEUED_FOR_REVIEW, got {:?}", other),
+    }
+    assert!(storage.get_receipt("r_stranger").await.unwrap().is_none());
+    assert_eq!(engine.queued().len(), 1);
+
+    // Approving it issues the receipt, which a retry then receives
+    let receipt = manager
+        .approve_queued_request("r_stranger")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(engine.queued().is_empty());
+    let reply = manager
+        .handle_message(request("r_stranger", &stranger), &stranger, &payee_pk)
+        .await
+        .unwrap();
+    match reply {
+        Some(PaykitNoiseMessage::ConfirmReceipt { receipt: again }) => assert_eq!(again, receipt),
+        other => panic!("Expected ConfirmReceipt, got {:?}", other),
+    }
+
+    // A rejected request stays rejected when retried
+    manager
+        .handle_message(request("r_rejected", &stranger), &stranger, &payee_pk)
+        .await
+        .unwrap();
+    engine.override_decision("r_rejected", RuleAction::Reject, 0);
+    let reply = manager
+        .handle_message(request("r_rejected", &stranger), &stranger, &payee_pk)
+        .await
+        .unwrap();
+    match reply {
+        Some(PaykitNoiseMessage::Error { code, .. }) => assert_eq!(code, "REQUEST_REJECTED"),
+        other => panic!("Expected REQUEST_REJECTED, got {:?}", other),
+    }
+
+    let decisions = engine.decisions_for("r_contact");
+    assert_eq!(
+        decisions[0].source,
+        DecisionSource::Rule("small-from-contacts".into())
+    );
+}
+
+#[tokio::test]
+async fn test_peer_limits_refuse_spam() {
+    use paykit_interactive::peer_limit::{PeerLimitConfig, PeerRateLimiter};
+
+    let spammer = test_pubkey("spammer");
+    let other = test_pubkey("other");
+    let payee_pk = test_pubkey("payee");
+
+    let limits = PeerRateLimiter::new_shared(PeerLimitConfig {
+        max_messages_per_peer: 2,
+        greylist_after: 2,
+        ..Default::default()
+    });
+    let manager = PaykitInteractiveManager::new(
+        Arc::new(Box::new(MockStorage::new()) as Box<dyn paykit_interactive::PaykitStorage>),
+        Arc::new(
+            Box::new(MockReceiptGenerator::new()) as Box<dyn paykit_interactive::ReceiptGenerator>
+        ),
+    )
+    .with_peer_limits(limits.clone());
+
+    let offer = || PaykitNoiseMessage::OfferPrivateEndpoint {
+        method_id: MethodId("lightning".to_string()),
+        endpoint: "lnbc1...".to_string(),
+    };
+    let error_code = |reply: Option<PaykitNoiseMessage>| match reply {
+        Some(PaykitNoiseMessage::Error { code, .. }) => Some(code),
+        _ => None,
+    };
+
+    for _ in 0..2 {
+        let reply = manager
+            .handle_message(offer(), &spammer, &payee_pk)
+            .await
+            .unwrap();
+        assert!(matches!(reply, Some(PaykitNoiseMessage::Ack)));
+    }
+    let reply = manager
+        .handle_message(offer(), &spammer, &payee_pk)
+        .await
+        .unwrap();
+    assert_eq!(error_code(reply).as_deref(), Some("RATE_LIMITED"));
+    let reply = manager
+        .handle_message(offer(), &spammer, &payee_pk)
+        .await
+        .unwrap();
+    assert_eq!(error_code(reply).as_deref(), Some("GREYLISTED"));
+    assert!(limits.is_greylisted(&spammer.to_string()));
+
+    // Other peers are still served
+    let reply = manager
+        .handle_message(offer(), &other, &payee_pk)
+        .await
+        .unwrap();
+    assert!(matches!(reply, Some(PaykitNoiseMessage::Ack)));
+}
+
+fn negotiation_manager() -> PaykitInteractiveManager {
+    let storage =
+        Arc::new(Box::new(MockStorage::new()) as Box<dyn paykit_interactive::PaykitStorage>);
+    let generator = Arc::new(
+        Box::new(MockReceiptGenerator::new()) as Box<dyn paykit_interactive::ReceiptGenerator>
+    );
+    PaykitInteractiveManager::new(storage, generator)
+}
+
+#[tokio::test]
+async fn test_hello_negotiates_common_features() {
+    use paykit_interactive::Features;
+
+    let initiator_pk = test_pubkey("initiator");
+    let responder_pk = test_pubkey("responder");
+    let initiator = negotiation_manager();
+    let responder = Arc::new(
+        negotiation_manager().with_advertised_features(Features::LEGACY | Features::APPROVALS),
+    );
+
+    let (mut initiator_channel, mut responder_channel) = MockNoiseChannel::pair();
+    let responder_task = responder.clone();
+    let (initiator_clone, responder_clone) = (initiator_pk.clone(), responder_pk.clone());
+    let responder_handle = tokio::spawn(async move {
+        let msg = responder_channel.recv().await.unwrap();
+        let reply = responder_task
+            .handle_message(msg, &initiator_clone, &responder_clone)
+            .await
+            .unwrap();
+        responder_channel.send(reply.unwrap()).await.unwrap();
+    });
+
+    // Before negotiating only legacy features are assumed
+    assert!(!initiator.peer_supports(&responder_pk, Features::SUBSCRIPTIONS));
+
+    let negotiated = initiator
+        .negotiate(&mut initiator_channel, &responder_pk)
+        .await
+        .unwrap();
+    responder_handle.await.unwrap();
+
+    // The initiator has no approval handler, so approvals are not common
+    assert_eq!(negotiated.features, Features::LEGACY);
+    assert_eq!(responder.negotiated(&initiator_pk), Some(negotiated));
+    assert!(initiator.peer_supports(&responder_pk, Features::RECEIPTS));
+    assert!(!initiator.peer_supports(&responder_pk, Features::APPROVALS));
+
+    initiator.forget_negotiation(&responder_pk);
+    assert!(initiator.negotiated(&responder_pk).is_none());
+}
+
+#[tokio::test]
+async fn test_hello_downgrades_to_legacy_peer() {
+    use paykit_interactive::negotiation::LEGACY_PROTOCOL_VERSION;
+    use paykit_interactive::{Features, Negotiated};
+
+    let peer_pk = test_pubkey("legacy");
+    let manager = negotiation_manager();
+
+    // A peer that predates negotiation cannot parse the hello
+    let (mut channel, mut legacy_channel) = MockNoiseChannel::pair();
+    let legacy_handle = tokio::spawn(async move {
+        legacy_channel.recv().await.unwrap();
+        legacy_channel
+            .send(PaykitNoiseMessage::Error {
+                code: "INVALID_MESSAGE".into(),
+                message: "unknown variant `Hello`".into(),
+            })
+            .await
+            .unwrap();
+    });
+
+    let negotiated = manager.negotiate(&mut channel, &peer_pk).await.unwrap();
+    legacy_handle.await.unwrap();
+    assert_eq!(negotiated, Negotiated::legacy());
+    assert_eq!(negotiated.version, LEGACY_PROTOCOL_VERSION);
+    assert!(!manager.peer_supports(&peer_pk, Features::ATTESTATION));
+}
+
+#[tokio::test]
+async fn test_hello_below_min_version_refused() {
+    use paykit_interactive::negotiation::{PROTOCOL_VERSION, VERSION_UNSUPPORTED};
+    use paykit_interactive::Features;
+
+    let peer_pk = test_pubkey("peer");
+    let my_pk = test_pubkey("me");
+    let manager = negotiation_manager().with_min_protocol_version(PROTOCOL_VERSION);
+
+    let reply = manager
+        .handle_message(
+            PaykitNoiseMessage::Hello {
+                version: 0,
+                features: Features::LEGACY,
+            },
+            &peer_pk,
+            &my_pk,
+        )
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        reply,
+        Some(PaykitNoiseMessage::Error { code, .. }) if code == VERSION_UNSUPPORTED
+    ));
+    assert!(manager.negotiated(&peer_pk).is_none());
+}
+
+#[tokio::test]
+async fn test_tenants_routed_by_recipient() {
+    use paykit_interactive::peer_limit::PeerLimitConfig;
+    use paykit_interactive::{PaykitStorage, ReceiptGenerator, Tenant, TenantRouter};
+
+    let payer_pk = test_pubkey("payer");
+    let coffee_pk = test_pubkey("coffee");
+    let books_pk = test_pubkey("books");
+
+    let new_tenant = |id: &str, pk: &PublicKey, limits: PeerLimitConfig| {
+        let storage = Arc::new(Box::new(MockStorage::new()) as Box<dyn PaykitStorage>);
+        let generator =
+            Arc::new(Box::new(MockReceiptGenerator::new()) as Box<dyn ReceiptGenerator>);
+        let manager = PaykitInteractiveManager::new(storage.clone(), generator);
+        (storage, Tenant::new(id, pk.clone(), manager, limits))
+    };
+    let (coffee_storage, coffee) = new_tenant("coffee", &coffee_pk, PeerLimitConfig::default());
+    let (books_storage, books) = new_tenant(
+        "books",
+        &books_pk,
+        PeerLimitConfig {
+            max_messages_per_peer: 1,
+            ..Default::default()
+        },
+    );
+
+    let mut router = TenantRouter::new();
+    router.register(coffee).unwrap();
+    router.register(books).unwrap();
+    let (_, again) = new_tenant("coffee-2", &coffee_pk, PeerLimitConfig::default());
+    assert!(router.register(again).is_err());
+
+    let request = |id: &str, payee: &PublicKey| PaykitNoiseMessage::RequestReceipt {
+        provisional_receipt: PaykitReceipt::new(
+            id.to_string(),
+            payer_pk.clone(),
+            payee.clone(),
+            MethodId("lightning".to_string()),
+            Some("1000".to_string()),
+            Some("SAT".to_string()),
+            json!({}),
+        ),
+    };
+
+    let reply = router
+        .handle_message(&books_pk, request("r1", &books_pk), &payer_pk)
+        .await
+        .unwrap();
+    assert!(matches!(
+        reply,
+        Some(PaykitNoiseMessage::ConfirmReceipt { .. })
+    ));
+    assert!(books_storage.get_receipt("r1").await.unwrap().is_some());
+    assert!(coffee_storage.get_receipt("r1").await.unwrap().is_none());
+
+    // The payer is over its limit with books but not with coffee
+    let reply = router
+        .handle_message(&books_pk, request("r2", &books_pk), &payer_pk)
+        .await
+        .unwrap();
+    assert!(matches!(reply, Some(PaykitNoiseMessage::Error { .. })));
+    let reply = router
+        .handle_message(&coffee_pk, request("r3", &coffee_pk), &payer_pk)
+        .await
+        .unwrap();
+    assert!(matches!(
+        reply,
+        Some(PaykitNoiseMessage::ConfirmReceipt { .. })
+    ));
+
+    // Nobody serves the payer's own key
+    assert!(router
+        .handle_message(&payer_pk, request("r4", &payer_pk), &payer_pk)
+        .await
+        .is_err());
+
+    let metrics = router.metrics();
+    assert_eq!(metrics[0].0, "books");
+    assert_eq!(metrics[0].1.receipts_generated, 1);
+    assert_eq!(metrics[0].1.messages_rate_limited, 1);
+    assert_eq!(metrics[1].0, "coffee");
+    assert_eq!(metrics[1].1.receipts_generated, 1);
+    assert_eq!(metrics[1].1.messages_rate_limited, 0);
+}
+
+#[tokio::test]
+async fn test_confirmed_receipts_tracked_with_peer_trust() {
+    use paykit_interactive::{PaymentStatus, PaymentStatusTracker};
+
+    let payer_pk = test_pubkey("payer");
+    let payee_pk = test_pubkey("payee");
+
+    let payer_tracker = Arc::new(PaymentStatusTracker::new());
+    let payer_manager = PaykitInteractiveManager::new(
+        Arc::new(Box::new(MockStorage::new()) as Box<dyn paykit_interactive::PaykitStorage>),
+        Arc::new(
+            Box::new(MockReceiptGenerator::new()) as Box<dyn paykit_interactive::ReceiptGenerator>
+        ),
+    )
+    .with_status_tracker(payer_tracker.clone());
+
+    // The payee trusts the payer, so a small on-chain payment needs no confirmations
+    let mut policy = paykit_lib::policy::TrustPolicy::new();
+    policy.trust(&payer_pk.to_string(), None);
+    let payee_tracker = Arc::new(PaymentStatusTracker::new());
+    let payee_manager = PaykitInteractiveManager::new(
+        Arc::new(Box::new(MockStorage::new()) as Box<dyn paykit_interactive::PaykitStorage>),
+        Arc::new(
+            Box::new(MockReceiptGenerator::new()) as Box<dyn paykit_interactive::ReceiptGenerator>
+        ),
+    )
+    .with_trust_policy(policy.shared())
+    .with_status_tracker(payee_tracker.clone());
+
+    let (mut payer_channel, mut payee_channel) = MockNoiseChannel::pair();
+    let provisional_receipt = PaykitReceipt::new(
+        "receipt_tracked".to_string(),
+        payer_pk.clone(),
+        payee_pk.clone(),
+        MethodId::onchain(),
+        Some("10000".to_string()),
+        Some("SAT".to_string()),
+        json!({}),
+    );
+
+    let payer_pk_clone = payer_pk.clone();
+    let payee_pk_clone = payee_pk.clone();
+    let payee_handle = tokio::spawn(async move {
+        let msg = payee_channel.recv().await.unwrap();
+        let response = payee_manager
+            .handle_message(msg, &payer_pk_clone, &payee_pk_clone)
+            .await
+            .unwrap();
+        if let Some(response_msg) = response {
+            payee_channel.send(response_msg).await.unwrap();
+        }
+    });
+    payer_manager
+        .initiate_payment(&mut payer_channel, provisional_receipt)
+        .await
+        .unwrap();
+    payee_handle.await.unwrap();
+
+    let payee_status = payee_tracker.get("receipt_tracked").unwrap();
+    assert_eq!(payee_status.required_confirmations, Some(0));
+    let payee_status = payee_tracker
+        .update_confirmations("receipt_tracked", 0)
+        .unwrap();
+    assert_eq!(payee_status.status, PaymentStatus::Finalized);
+
+    // The payer has no trust entry for the payee
+    let payer_status = payer_tracker.get("receipt_tracked").unwrap();
+    assert_eq!(payer_status.status, PaymentStatus::Pending);
+    assert_eq!(payer_status.required_confirmations, Some(1));
+}
@@ -48,6 +48,15 @@ impl PaykitStorage for MockStorage {
         Ok(receipts.get(receipt_id).cloned())
     }
 
+    async fn delete_receipt(&self, receipt_id: &str) -> Result<()> {
+        let mut receipts = self
+            .receipts
+            .lock()
+            .map_err(|e| InteractiveError::Transport(format!("Mutex poisoned: {}", e)))?;
+        receipts.remove(receipt_id);
+        Ok(())
+    }
+
     async fn save_private_endpoint(
         &self,
         peer: &PublicKey,
@@ -0,0 +1,162 @@
+//! Payment method selection benchmarks
+//!
+//! These benchmarks compare selecting a method with a selector built over a
+//! shared `Arc<PaymentMethodRegistry>` against rebuilding the selector from a
+//! cloned registry on every call, as checkout used to do.
+//!
+//! They also measure how selection scales when the registry and the payee's
+//! supported methods grow to a thousand custom methods.
+//!
+//! Allocations per selection are counted and printed before the timed runs.
+//!
+//! Run with: `cargo bench --bench selection_benchmarks`
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use paykit_lib::methods::{
+    default_registry, Amount, CustomMethodHandler, CustomMethodPlugin, PaymentExecution,
+    PaymentMethodRegistry, ValidationResult,
+};
+use paykit_lib::selection::{PaymentMethodSelector, SelectionPreferences, SelectionResult};
+use paykit_lib::{EndpointData, MethodId, Result, SupportedPayments};
+use serde_json::Value;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// System allocator that counts allocations.
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn supported() -> SupportedPayments {
+    let mut entries = HashMap::new();
+    entries.insert(MethodId("onchain".into()), EndpointData("bc1q...".into()));
+    entries.insert(MethodId("lightning".into()), EndpointData("lnbc...".into()));
+    SupportedPayments { entries }
+}
+
+fn select_cloned(
+    registry: &PaymentMethodRegistry,
+    supported: &SupportedPayments,
+) -> Result<SelectionResult> {
+    PaymentMethodSelector::new(registry.clone()).select(
+        supported,
+        &Amount::sats(10_000),
+        &SelectionPreferences::balanced(),
+    )
+}
+
+fn select_shared(
+    registry: &Arc<PaymentMethodRegistry>,
+    supported: &SupportedPayments,
+) -> Result<SelectionResult> {
+    PaymentMethodSelector::new(registry.clone()).select(
+        supported,
+        &Amount::sats(10_000),
+        &SelectionPreferences::balanced(),
+    )
+}
+
+fn allocations_per_call<T>(rounds: usize, mut f: impl FnMut() -> T) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    for _ in 0..rounds {
+        drop(black_box(f()));
+    }
+    (ALLOCATIONS.load(Ordering::Relaxed) - before) / rounds
+}
+
+/// Benchmark selection with and without cloning the registry
+fn bench_selection(c: &mut Criterion) {
+    let registry = default_registry();
+    let shared = Arc::new(default_registry());
+    let supported = supported();
+
+    println!(
+        "allocations per selection: cloned registry = {}, shared registry = {}",
+        allocations_per_call(1_000, || select_cloned(&registry, &supported)),
+        allocations_per_call(1_000, || select_shared(&shared, &supported)),
+    );
+
+    let mut group = c.benchmark_group("select_method");
+    group.bench_function("cloned_registry", |b| {
+        b.iter(|| select_cloned(&registry, black_box(&supported)))
+    });
+    group.bench_function("shared_registry", |b| {
+        b.iter(|| select_shared(&shared, black_box(&supported)))
+    });
+    group.finish();
+}
+
+/// Custom method that accepts every endpoint, so only selection is measured.
+struct NoopHandler;
+
+impl CustomMethodHandler for NoopHandler {
+    fn validate_endpoint(&self, _endpoint: &EndpointData) -> ValidationResult {
+        ValidationResult::valid()
+    }
+
+    fn execute_payment(
+        &self,
+        _endpoint: &EndpointData,
+        _amount: &Amount,
+        _metadata: &Value,
+    ) -> Result<Value> {
+        Ok(Value::Null)
+    }
+
+    fn generate_proof(&self, _execution: &PaymentExecution) -> Result<Value> {
+        Ok(Value::Null)
+    }
+}
+
+/// The default registry plus `extra` custom methods, and a payee that
+/// supports every one of them.
+fn large_registry(extra: usize) -> (Arc<PaymentMethodRegistry>, SupportedPayments) {
+    let registry = default_registry();
+    let handler: Arc<dyn CustomMethodHandler> = Arc::new(NoopHandler);
+    let mut supported = supported();
+    for i in 0..extra {
+        let method_id = MethodId(format!("com.bench.method{}", i));
+        let plugin =
+            CustomMethodPlugin::new(method_id.clone(), format!("Method {}", i), handler.clone())
+                .expect("valid custom method ID");
+        registry.register(Box::new(plugin));
+        supported
+            .entries
+            .insert(method_id, EndpointData(format!("acct:{}", i)));
+    }
+    (Arc::new(registry), supported)
+}
+
+/// Benchmark selection as the registry and the payee's methods grow
+fn bench_large_registry(c: &mut Criterion) {
+    let mut group = c.benchmark_group("select_method_large_registry");
+    for size in [10, 100, 1_000] {
+        let (registry, supported) = large_registry(size);
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(size),
+            &supported,
+            |b, supported| b.iter(|| select_shared(&registry, black_box(supported))),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_selection, bench_large_registry);
+criterion_main!(benches);
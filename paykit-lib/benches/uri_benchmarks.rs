@@ -0,0 +1,82 @@
+//! URI and QR payload parsing benchmarks
+//!
+//! Every scanned QR code and tapped deep link goes through `parse_uri`
+//! before checkout can start. These benchmarks cover each URI form,
+//! including signed payment request links, whose signature is verified
+//! during parsing.
+//!
+//! Run with: `cargo bench --bench uri_benchmarks`
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use paykit_lib::uri::{parse_uri, PaymentRequestLink, SignedPaymentRequestLink};
+use paykit_lib::MethodId;
+
+const INVOICE: &str = "lnbc10u1pj9x3qapp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdq5xysxxatsyp3k7enxv4jsxqzpusp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygs9qyyssq";
+
+/// One URI of each kind a payer can scan.
+fn sample_uris() -> Vec<(&'static str, String)> {
+    let keypair = pubky::Keypair::from_secret_key(&[7u8; 32]);
+    let public_key = keypair.public_key();
+    let signed = PaymentRequestLink::new("req-bench", public_key.clone())
+        .with_amount_sats(5_000)
+        .with_methods(vec![MethodId::lightning(), MethodId("onchain".to_string())])
+        .with_description("Coffee")
+        .sign(&keypair)
+        .expect("signing succeeds");
+
+    vec![
+        ("pubky", format!("pubky://{}", public_key.to_z32())),
+        ("lightning", format!("lightning:{}", INVOICE)),
+        (
+            "bitcoin",
+            "bitcoin:bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+        ),
+        (
+            "paykit_invoice",
+            "paykit:invoice?method=lightning&data=lnbc1u1p3xyz".to_string(),
+        ),
+        (
+            "paykit_request",
+            format!(
+                "paykit:request?request_id=req-1&from=pubky://{}",
+                public_key.to_z32()
+            ),
+        ),
+        ("signed_request", signed.to_uri()),
+    ]
+}
+
+/// Benchmark parsing each URI form
+fn bench_parse_uri(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_uri");
+    for (kind, uri) in sample_uris() {
+        assert!(parse_uri(&uri).is_ok(), "sample {} must parse", kind);
+        group.bench_with_input(BenchmarkId::from_parameter(kind), &uri, |b, uri| {
+            b.iter(|| parse_uri(black_box(uri)))
+        });
+    }
+    group.finish();
+}
+
+/// Benchmark decoding a signed request link with and without verification
+fn bench_signed_request_link(c: &mut Criterion) {
+    let (_, uri) = sample_uris()
+        .into_iter()
+        .find(|(kind, _)| *kind == "signed_request")
+        .expect("signed request sample");
+
+    let mut group = c.benchmark_group("signed_request_link");
+    group.bench_function("decode", |b| {
+        b.iter(|| SignedPaymentRequestLink::decode(black_box(&uri)))
+    });
+    group.bench_function("decode_and_verify", |b| {
+        b.iter(|| {
+            let link = SignedPaymentRequestLink::decode(black_box(&uri)).unwrap();
+            link.verify_signature()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse_uri, bench_signed_request_link);
+criterion_main!(benches);
@@ -57,7 +57,7 @@ impl DemoPaymentPlugin {
 #[async_trait]
 impl PaymentMethodPlugin for DemoPaymentPlugin {
     fn method_id(&self) -> MethodId {
-        MethodId("demo".to_string())
+        MethodId("org.example.demo".to_string())
     }
 
     fn display_name(&self) -> &str {
@@ -130,7 +130,7 @@ impl PaymentMethodPlugin for DemoPaymentPlugin {
 
     fn format_receipt_metadata(&self, execution: &PaymentExecution) -> Value {
         serde_json::json!({
-            "method": "demo",
+            "method": "org.example.demo",
             "demo_tx_id": execution.execution_data.get("demo_tx_id"),
             "simulated": true,
             "executed_at": execution.executed_at,
@@ -168,9 +168,13 @@ fn current_timestamp() -> i64 {
 async fn main() {
     println!("=== Custom Payment Method Plugin Example ===\n");
 
-    // Create a registry and register our custom plugin
+    // Create a registry and register our custom plugin. Third-party methods
+    // use a namespaced ID, and `try_register` refuses to replace a method
+    // that is already registered.
     let registry = PaymentMethodRegistry::new();
-    registry.register(Box::new(DemoPaymentPlugin::new()));
+    registry
+        .try_register(Box::new(DemoPaymentPlugin::new()))
+        .expect("demo method ID is free");
 
     // List all registered methods
     println!("Registered methods:");
@@ -182,7 +186,7 @@ async fn main() {
     println!();
 
     // Get our demo plugin
-    let demo = registry.get(&MethodId("demo".into())).unwrap();
+    let demo = registry.get(&MethodId("org.example.demo".into())).unwrap();
 
     // Test endpoint validation
     println!("Testing endpoint validation:");
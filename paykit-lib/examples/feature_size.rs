@@ -0,0 +1,69 @@
+//! Feature Size Probe
+//!
+//! Touches every subsystem enabled in this build so that none of them is
+//! stripped by the linker. CI builds it in release mode with different
+//! feature sets and reports the binary sizes:
+//!
+//! ```bash
+//! cargo build -p paykit-lib --release --example feature_size --no-default-features
+//! cargo build -p paykit-lib --release --example feature_size --no-default-features --features selection
+//! cargo build -p paykit-lib --release --example feature_size
+//! ```
+
+use paykit_lib::methods::default_registry;
+use paykit_lib::{MethodId, SupportedPayments};
+use std::hint::black_box;
+
+fn main() {
+    let registry = default_registry();
+
+    let _ = black_box(SupportedPayments::default().to_list());
+    let _ = black_box(MethodId::lightning().validate());
+
+    #[cfg(feature = "health")]
+    let _ = black_box(paykit_lib::health::HealthMonitor::new());
+
+    #[cfg(feature = "routing")]
+    let _ = black_box(paykit_lib::routing::RoutingHintGenerator::new(
+        registry.clone(),
+    ));
+
+    #[cfg(feature = "rotation")]
+    let _ = black_box(paykit_lib::rotation::EndpointRotationManager::new(
+        Default::default(),
+        registry.clone(),
+    ));
+
+    #[cfg(feature = "private-endpoints")]
+    {
+        use paykit_lib::private_endpoints::{InMemoryStore, PrivateEndpointManager};
+        let _ = black_box(PrivateEndpointManager::new(InMemoryStore::new()));
+    }
+
+    #[cfg(feature = "selection")]
+    {
+        use paykit_lib::methods::Amount;
+        use paykit_lib::selection::{PaymentMethodSelector, SelectionPreferences};
+        let selector = PaymentMethodSelector::new(registry.clone());
+        let _ = black_box(selector.select(
+            &SupportedPayments::default(),
+            &Amount::sats(1_000),
+            &SelectionPreferences::balanced(),
+        ));
+    }
+
+    drop(registry);
+
+    let enabled: Vec<&str> = [
+        ("pubky", cfg!(feature = "pubky")),
+        ("health", cfg!(feature = "health")),
+        ("routing", cfg!(feature = "routing")),
+        ("rotation", cfg!(feature = "rotation")),
+        ("private-endpoints", cfg!(feature = "private-endpoints")),
+        ("selection", cfg!(feature = "selection")),
+    ]
+    .into_iter()
+    .filter_map(|(name, on)| on.then_some(name))
+    .collect();
+    println!("paykit-lib features: directory {}", enabled.join(" "));
+}
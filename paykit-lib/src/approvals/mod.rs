@@ -0,0 +1,52 @@
+//! Multi-Signature Payment Approvals
+//!
+//! Large payments can require sign-off from co-signers before they reach an
+//! executor. An [`ApprovalPolicy`] sets the amount threshold and the K-of-N
+//! co-signers; payments above the threshold are queued in an
+//! [`ApprovalQueue`] as a [`PendingApproval`] and proceed only once K
+//! co-signers have approved.
+//!
+//! Co-signers decide by producing a [`SignedApproval`]: an Ed25519 signature
+//! over the queued payment's digest. Approvals can be signed locally (a
+//! co-signer key on the same device) or by a remote co-signer and delivered
+//! over a Noise channel.
+//!
+//! # Lifecycle
+//!
+//! - **Pending**: waiting for approvals
+//! - **Approved**: K approvals collected; the payment may be executed
+//! - **Rejected**: so many rejections that K approvals are impossible
+//! - **Expired**: not approved within the policy's TTL
+//! - **Executed**: approved and paid; cannot be replayed
+//!
+//! # Example
+//!
+//! ```ignore
+//! use paykit_lib::approvals::{
+//!     ApprovalDecision, ApprovalPolicy, ApprovalQueue, PaymentProposal, SignedApproval,
+//! };
+//!
+//! let policy = ApprovalPolicy::new(1_000_000, 2, vec![alice, bob, carol])?;
+//! let mut queue = ApprovalQueue::new(policy)?;
+//!
+//! if queue.requires_approval(amount_sats) {
+//!     let pending = queue.submit(PaymentProposal::new(invoice, "lightning", amount_sats), now);
+//!     // Send `pending` to co-signers; each returns a SignedApproval
+//!     let approval = SignedApproval::sign(&alice_keypair, &pending, ApprovalDecision::Approve, now);
+//!     queue.apply(&approval, now)?;
+//! }
+//!
+//! // Before executing
+//! queue.ensure_approved(&approval_id)?;
+//! execute().await?;
+//! queue.mark_executed(&approval_id)?;
+//! ```
+
+mod queue;
+mod types;
+
+pub use queue::{ApprovalQueue, SharedApprovalQueue};
+pub use types::{
+    ApprovalDecision, ApprovalPolicy, ApprovalStatus, PaymentProposal, PendingApproval,
+    SignedApproval, DEFAULT_APPROVAL_TTL_SECS,
+};
@@ -0,0 +1,338 @@
+//! Approval queue: tracks payments through K-of-N approval.
+
+use super::types::{
+    ApprovalDecision, ApprovalPolicy, ApprovalStatus, PaymentProposal, PendingApproval,
+    SignedApproval,
+};
+use crate::policy::normalize_key;
+use crate::{PaykitError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// An approval queue shared between the components that enforce it.
+pub type SharedApprovalQueue = Arc<RwLock<ApprovalQueue>>;
+
+/// Queue of payments awaiting co-signer approval.
+///
+/// Serializable so apps can persist it between launches.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalQueue {
+    policy: ApprovalPolicy,
+    #[serde(default)]
+    entries: HashMap<String, PendingApproval>,
+}
+
+impl ApprovalQueue {
+    /// Create an empty queue enforcing `policy`.
+    pub fn new(policy: ApprovalPolicy) -> Result<Self> {
+        policy.validate()?;
+        Ok(Self {
+            policy,
+            entries: HashMap::new(),
+        })
+    }
+
+    /// Wrap the queue for sharing between threads.
+    pub fn shared(self) -> SharedApprovalQueue {
+        Arc::new(RwLock::new(self))
+    }
+
+    /// The current policy.
+    pub fn policy(&self) -> &ApprovalPolicy {
+        &self.policy
+    }
+
+    /// Replace the policy. Payments already queued keep their K-of-N.
+    pub fn set_policy(&mut self, policy: ApprovalPolicy) -> Result<()> {
+        policy.validate()?;
+        self.policy = policy;
+        Ok(())
+    }
+
+    /// Whether a payment of `amount_sats` needs approval.
+    pub fn requires_approval(&self, amount_sats: u64) -> bool {
+        self.policy.requires_approval(amount_sats)
+    }
+
+    /// Queue a payment for approval.
+    ///
+    /// Submitting a payment that is already pending returns the existing
+    /// entry instead of queueing it twice.
+    pub fn submit(&mut self, proposal: PaymentProposal, now: i64) -> PendingApproval {
+        let digest = proposal.digest();
+        if let Some(existing) = self.entries.values().find(|e| {
+            e.status == ApprovalStatus::Pending
+                && !e.is_expired_at(now)
+                && e.proposal.digest() == digest
+        }) {
+            return existing.clone();
+        }
+
+        let pending = PendingApproval {
+            approval_id: format!("apr_{}_{}", now, &digest[..12]),
+            proposal,
+            required: self.policy.required,
+            cosigners: self.policy.cosigners.clone(),
+            approvals: Vec::new(),
+            rejections: Vec::new(),
+            status: ApprovalStatus::Pending,
+            created_at: now,
+            expires_at: now + self.policy.ttl_secs,
+        };
+        self.entries
+            .insert(pending.approval_id.clone(), pending.clone());
+        pending
+    }
+
+    /// Apply a co-signer's signed decision and return the resulting status.
+    pub fn apply(&mut self, approval: &SignedApproval, now: i64) -> Result<ApprovalStatus> {
+        let entry = self
+            .entries
+            .get_mut(&approval.approval_id)
+            .ok_or_else(|| not_found(&approval.approval_id))?;
+
+        if entry.status == ApprovalStatus::Pending && entry.is_expired_at(now) {
+            entry.status = ApprovalStatus::Expired;
+        }
+        if entry.status != ApprovalStatus::Pending {
+            return Err(PaykitError::ValidationFailed(format!(
+                "Payment {} is {}, not pending",
+                entry.approval_id,
+                entry.status.as_str()
+            )));
+        }
+
+        let signer = normalize_key(&approval.signer);
+        if !entry.cosigners.contains(&signer) {
+            return Err(PaykitError::ValidationFailed(format!(
+                "{} is not a co-signer for payment {}",
+                signer, entry.approval_id
+            )));
+        }
+        if entry.has_decided(&signer) {
+            return Err(PaykitError::ValidationFailed(format!(
+                "{} has already decided on payment {}",
+                signer, entry.approval_id
+            )));
+        }
+        if approval.proposal_digest != entry.proposal.digest() {
+            return Err(PaykitError::ValidationFailed(
+                "Approval was signed for a different payment".to_string(),
+            ));
+        }
+        approval.verify_signature()?;
+
+        match approval.decision {
+            ApprovalDecision::Approve => entry.approvals.push(approval.clone()),
+            ApprovalDecision::Reject => entry.rejections.push(approval.clone()),
+        }
+
+        let needed = entry.required as usize;
+        let max_rejections = entry.cosigners.len().saturating_sub(needed);
+        if entry.approvals.len() >= needed {
+            entry.status = ApprovalStatus::Approved;
+        } else if entry.rejections.len() > max_rejections {
+            entry.status = ApprovalStatus::Rejected;
+        }
+        Ok(entry.status)
+    }
+
+    /// Get a queued payment.
+    pub fn get(&self, approval_id: &str) -> Option<&PendingApproval> {
+        self.entries.get(approval_id)
+    }
+
+    /// All queued payments, oldest first.
+    pub fn entries(&self) -> Vec<&PendingApproval> {
+        let mut entries: Vec<_> = self.entries.values().collect();
+        entries.sort_by(|a, b| {
+            a.created_at
+                .cmp(&b.created_at)
+                .then_with(|| a.approval_id.cmp(&b.approval_id))
+        });
+        entries
+    }
+
+    /// Payments still waiting for approvals, oldest first.
+    pub fn pending(&self) -> Vec<&PendingApproval> {
+        self.entries()
+            .into_iter()
+            .filter(|e| e.status == ApprovalStatus::Pending)
+            .collect()
+    }
+
+    /// Find an approved, not yet executed entry for this payment.
+    pub fn find_approved(
+        &self,
+        payee: &str,
+        method_id: &str,
+        amount_sats: u64,
+    ) -> Option<&PendingApproval> {
+        self.entries().into_iter().find(|e| {
+            e.status == ApprovalStatus::Approved
+                && e.proposal.matches(payee, method_id, amount_sats)
+        })
+    }
+
+    /// Fail unless the payment has collected enough approvals.
+    pub fn ensure_approved(&self, approval_id: &str) -> Result<&PendingApproval> {
+        let entry = self
+            .get(approval_id)
+            .ok_or_else(|| not_found(approval_id))?;
+        if entry.status != ApprovalStatus::Approved {
+            return Err(PaykitError::ValidationFailed(format!(
+                "Payment {} is {} ({} of {} approvals)",
+                approval_id,
+                entry.status.as_str(),
+                entry.approvals.len(),
+                entry.required
+            )));
+        }
+        Ok(entry)
+    }
+
+    /// Mark an approved payment as executed so it cannot be replayed.
+    pub fn mark_executed(&mut self, approval_id: &str) -> Result<()> {
+        self.ensure_approved(approval_id)?;
+        if let Some(entry) = self.entries.get_mut(approval_id) {
+            entry.status = ApprovalStatus::Executed;
+        }
+        Ok(())
+    }
+
+    /// Remove a payment from the queue.
+    pub fn cancel(&mut self, approval_id: &str) -> Option<PendingApproval> {
+        self.entries.remove(approval_id)
+    }
+
+    /// Mark pending payments past their deadline as expired.
+    ///
+    /// Returns the identifiers that expired.
+    pub fn expire(&mut self, now: i64) -> Vec<String> {
+        let mut expired = Vec::new();
+        for entry in self.entries.values_mut() {
+            if entry.status == ApprovalStatus::Pending && entry.is_expired_at(now) {
+                entry.status = ApprovalStatus::Expired;
+                expired.push(entry.approval_id.clone());
+            }
+        }
+        expired.sort();
+        expired
+    }
+}
+
+fn not_found(approval_id: &str) -> PaykitError {
+    PaykitError::NotFound {
+        resource_type: "approval".to_string(),
+        identifier: approval_id.to_string(),
+    }
+}
+
+#[cfg(all(test, feature = "pubky"))]
+mod tests {
+    use super::*;
+    use pubky::Keypair;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn setup(required: u32, n: usize) -> (ApprovalQueue, Vec<Keypair>) {
+        let signers: Vec<Keypair> = (0..n).map(|_| Keypair::random()).collect();
+        let policy = ApprovalPolicy::new(
+            100_000,
+            required,
+            signers.iter().map(|k| k.public_key().to_string()).collect(),
+        )
+        .unwrap();
+        (ApprovalQueue::new(policy).unwrap(), signers)
+    }
+
+    fn proposal() -> PaymentProposal {
+        PaymentProposal::new("lnbc1...", "lightning", 500_000)
+    }
+
+    #[test]
+    fn test_policy_validation() {
+        assert!(ApprovalPolicy::new(0, 0, vec!["a".to_string()]).is_err());
+        assert!(ApprovalPolicy::new(0, 2, vec!["a".to_string()]).is_err());
+        assert!(ApprovalPolicy::new(0, 1, vec!["a".to_string(), "a".to_string()]).is_err());
+
+        let policy = ApprovalPolicy::new(1000, 1, vec!["pubky://a".to_string()]).unwrap();
+        assert!(policy.is_cosigner("a"));
+        assert!(!policy.requires_approval(1000));
+        assert!(policy.requires_approval(1001));
+    }
+
+    #[test]
+    fn test_two_of_three_approval() {
+        let (mut queue, signers) = setup(2, 3);
+        let pending = queue.submit(proposal(), NOW);
+        assert_eq!(
+            queue.submit(proposal(), NOW + 1).approval_id,
+            pending.approval_id
+        );
+
+        let first = SignedApproval::sign(&signers[0], &pending, ApprovalDecision::Approve, NOW);
+        assert_eq!(queue.apply(&first, NOW).unwrap(), ApprovalStatus::Pending);
+        assert!(queue.apply(&first, NOW).is_err(), "duplicate signer");
+        assert!(queue.ensure_approved(&pending.approval_id).is_err());
+
+        let second = SignedApproval::sign(&signers[2], &pending, ApprovalDecision::Approve, NOW);
+        assert_eq!(queue.apply(&second, NOW).unwrap(), ApprovalStatus::Approved);
+        assert!(queue
+            .find_approved("lnbc1...", "lightning", 500_000)
+            .is_some());
+
+        queue.mark_executed(&pending.approval_id).unwrap();
+        assert!(queue.mark_executed(&pending.approval_id).is_err());
+        assert!(queue
+            .find_approved("lnbc1...", "lightning", 500_000)
+            .is_none());
+    }
+
+    #[test]
+    fn test_rejections_and_outsiders() {
+        let (mut queue, signers) = setup(2, 3);
+        let pending = queue.submit(proposal(), NOW);
+
+        let outsider = Keypair::random();
+        let forged = SignedApproval::sign(&outsider, &pending, ApprovalDecision::Approve, NOW);
+        assert!(queue.apply(&forged, NOW).is_err());
+
+        let mut tampered =
+            SignedApproval::sign(&signers[0], &pending, ApprovalDecision::Reject, NOW);
+        tampered.decision = ApprovalDecision::Approve;
+        assert!(queue.apply(&tampered, NOW).is_err());
+
+        for signer in &signers[..2] {
+            let reject = SignedApproval::sign(signer, &pending, ApprovalDecision::Reject, NOW);
+            queue.apply(&reject, NOW).unwrap();
+        }
+        assert_eq!(
+            queue.get(&pending.approval_id).unwrap().status,
+            ApprovalStatus::Rejected
+        );
+    }
+
+    #[test]
+    fn test_expiry_and_serialization() {
+        let (mut queue, signers) = setup(1, 1);
+        let pending = queue.submit(proposal(), NOW);
+        let late_at = pending.expires_at;
+
+        let restored: ApprovalQueue =
+            serde_json::from_str(&serde_json::to_string(&queue).unwrap()).unwrap();
+        assert_eq!(restored, queue);
+
+        let approval = SignedApproval::sign(&signers[0], &pending, ApprovalDecision::Approve, NOW);
+        assert!(queue.apply(&approval, late_at).is_err());
+        assert_eq!(
+            queue.get(&pending.approval_id).unwrap().status,
+            ApprovalStatus::Expired
+        );
+
+        let again = queue.submit(proposal(), late_at);
+        assert_ne!(again.approval_id, pending.approval_id);
+        assert_eq!(queue.expire(again.expires_at), vec![again.approval_id]);
+    }
+}
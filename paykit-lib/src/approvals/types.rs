@@ -0,0 +1,347 @@
+//! Approval policy, proposal and signed approval types.
+
+use crate::policy::normalize_key;
+use crate::{PaykitError, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Domain separation prefix for approval signatures.
+const APPROVAL_DOMAIN: &[u8] = b"PAYKIT_APPROVAL_V1:";
+
+/// Default time a payment may wait for approvals (24 hours).
+pub const DEFAULT_APPROVAL_TTL_SECS: i64 = 24 * 60 * 60;
+
+fn default_ttl_secs() -> i64 {
+    DEFAULT_APPROVAL_TTL_SECS
+}
+
+/// When payments need co-signer approval, and from whom.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalPolicy {
+    /// Payments strictly above this amount need approval.
+    pub threshold_sats: u64,
+    /// Number of approvals required (K).
+    pub required: u32,
+    /// Co-signer public keys (N), z-base-32.
+    pub cosigners: Vec<String>,
+    /// How long a payment may wait for approvals, in seconds.
+    #[serde(default = "default_ttl_secs")]
+    pub ttl_secs: i64,
+}
+
+impl ApprovalPolicy {
+    /// Create a K-of-N policy for payments above `threshold_sats`.
+    pub fn new(threshold_sats: u64, required: u32, cosigners: Vec<String>) -> Result<Self> {
+        let policy = Self {
+            threshold_sats,
+            required,
+            cosigners: cosigners.iter().map(|c| normalize_key(c)).collect(),
+            ttl_secs: DEFAULT_APPROVAL_TTL_SECS,
+        };
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    /// Set how long payments may wait for approvals.
+    pub fn with_ttl_secs(mut self, ttl_secs: i64) -> Self {
+        self.ttl_secs = ttl_secs;
+        self
+    }
+
+    /// Check that K-of-N is satisfiable.
+    pub fn validate(&self) -> Result<()> {
+        if self.required == 0 {
+            return Err(PaykitError::ValidationFailed(
+                "Approval policy must require at least one approval".to_string(),
+            ));
+        }
+        let mut unique = self.cosigners.clone();
+        unique.sort();
+        unique.dedup();
+        if unique.len() != self.cosigners.len() {
+            return Err(PaykitError::ValidationFailed(
+                "Approval policy lists a co-signer more than once".to_string(),
+            ));
+        }
+        if self.required as usize > self.cosigners.len() {
+            return Err(PaykitError::ValidationFailed(format!(
+                "Approval policy requires {} approvals but has {} co-signers",
+                self.required,
+                self.cosigners.len()
+            )));
+        }
+        if self.ttl_secs <= 0 {
+            return Err(PaykitError::ValidationFailed(
+                "Approval TTL must be positive".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether a payment of `amount_sats` needs approval.
+    pub fn requires_approval(&self, amount_sats: u64) -> bool {
+        amount_sats > self.threshold_sats
+    }
+
+    /// Whether `public_key` is one of the co-signers.
+    pub fn is_cosigner(&self, public_key: &str) -> bool {
+        let key = normalize_key(public_key);
+        self.cosigners.iter().any(|c| *c == key)
+    }
+}
+
+/// A payment waiting for approval.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaymentProposal {
+    /// Recipient (public key or payment endpoint).
+    pub payee: String,
+    /// Payment method identifier.
+    pub method_id: String,
+    /// Amount in satoshis.
+    pub amount_sats: u64,
+    /// What the payment is for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl PaymentProposal {
+    /// Create a proposal.
+    pub fn new(payee: impl Into<String>, method_id: impl Into<String>, amount_sats: u64) -> Self {
+        Self {
+            payee: payee.into(),
+            method_id: method_id.into(),
+            amount_sats,
+            description: None,
+        }
+    }
+
+    /// Set the description.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Hex SHA-256 digest that approvals commit to.
+    pub fn digest(&self) -> String {
+        // Struct fields serialize in declaration order, so this is canonical
+        let bytes = serde_json::to_vec(self).unwrap_or_default();
+        hex::encode(Sha256::digest(&bytes))
+    }
+
+    /// Whether this proposal describes the given payment.
+    pub fn matches(&self, payee: &str, method_id: &str, amount_sats: u64) -> bool {
+        self.payee == payee && self.method_id == method_id && self.amount_sats == amount_sats
+    }
+}
+
+/// A co-signer's decision.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalDecision {
+    /// Allow the payment.
+    Approve,
+    /// Refuse the payment.
+    Reject,
+}
+
+/// Lifecycle of a payment under approval.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalStatus {
+    /// Waiting for approvals.
+    Pending,
+    /// Enough approvals collected; the payment may be executed.
+    Approved,
+    /// Enough rejections that approval is no longer possible.
+    Rejected,
+    /// Not approved before the deadline.
+    Expired,
+    /// Approved and executed.
+    Executed,
+}
+
+impl ApprovalStatus {
+    /// Get the status as a string.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApprovalStatus::Pending => "pending",
+            ApprovalStatus::Approved => "approved",
+            ApprovalStatus::Rejected => "rejected",
+            ApprovalStatus::Expired => "expired",
+            ApprovalStatus::Executed => "executed",
+        }
+    }
+}
+
+/// A payment in the approval queue.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingApproval {
+    /// Queue identifier.
+    pub approval_id: String,
+    /// The payment awaiting approval.
+    pub proposal: PaymentProposal,
+    /// Approvals required (K), fixed when the payment was queued.
+    pub required: u32,
+    /// Co-signers (N), fixed when the payment was queued.
+    pub cosigners: Vec<String>,
+    /// Signed approvals collected so far.
+    #[serde(default)]
+    pub approvals: Vec<SignedApproval>,
+    /// Signed rejections collected so far.
+    #[serde(default)]
+    pub rejections: Vec<SignedApproval>,
+    /// Current status.
+    pub status: ApprovalStatus,
+    /// Unix timestamp when the payment was queued.
+    pub created_at: i64,
+    /// Unix timestamp after which the payment can no longer be approved.
+    pub expires_at: i64,
+}
+
+impl PendingApproval {
+    /// Whether the approval window has passed at `now`.
+    pub fn is_expired_at(&self, now: i64) -> bool {
+        now >= self.expires_at
+    }
+
+    /// Co-signers who have not yet decided.
+    pub fn outstanding(&self) -> Vec<&str> {
+        self.cosigners
+            .iter()
+            .filter(|c| !self.has_decided(c))
+            .map(|c| c.as_str())
+            .collect()
+    }
+
+    /// Whether `signer` has already approved or rejected.
+    pub fn has_decided(&self, signer: &str) -> bool {
+        let signer = normalize_key(signer);
+        self.approvals
+            .iter()
+            .chain(&self.rejections)
+            .any(|a| normalize_key(&a.signer) == signer)
+    }
+}
+
+/// A co-signer's signed decision on a [`PendingApproval`].
+///
+/// Exchanged as JSON, either locally or over a Noise channel.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedApproval {
+    /// The queued payment this decision is for.
+    pub approval_id: String,
+    /// Digest of the proposal that was reviewed.
+    pub proposal_digest: String,
+    /// Co-signer public key (z-base-32).
+    pub signer: String,
+    /// Approve or reject.
+    pub decision: ApprovalDecision,
+    /// Unix timestamp of the decision.
+    pub signed_at: i64,
+    /// Base64url Ed25519 signature.
+    pub signature: String,
+}
+
+impl SignedApproval {
+    /// Sign a decision with the co-signer's keypair.
+    #[cfg(feature = "pubky")]
+    pub fn sign(
+        keypair: &pubky::Keypair,
+        pending: &PendingApproval,
+        decision: ApprovalDecision,
+        now: i64,
+    ) -> Self {
+        Self::sign_with_secret_key(&keypair.secret_key(), pending, decision, now)
+    }
+
+    /// Sign a decision with the co-signer's Ed25519 secret key.
+    #[cfg(feature = "pubky")]
+    pub fn sign_with_secret_key(
+        secret_key: &[u8; 32],
+        pending: &PendingApproval,
+        decision: ApprovalDecision,
+        now: i64,
+    ) -> Self {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let signer = normalize_key(
+            &pubky::Keypair::from_secret_key(secret_key)
+                .public_key()
+                .to_string(),
+        );
+        let mut approval = Self {
+            approval_id: pending.approval_id.clone(),
+            proposal_digest: pending.proposal.digest(),
+            signer,
+            decision,
+            signed_at: now,
+            signature: String::new(),
+        };
+        let signature = SigningKey::from_bytes(secret_key).sign(&approval.signing_message());
+        approval.signature = URL_SAFE_NO_PAD.encode(signature.to_bytes());
+        approval
+    }
+
+    /// Verify the signature against the signer's public key.
+    ///
+    /// Requires the `pubky` feature; without it verification always fails.
+    pub fn verify_signature(&self) -> Result<()> {
+        #[cfg(feature = "pubky")]
+        {
+            use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+            use std::str::FromStr;
+
+            let invalid =
+                || PaykitError::ValidationFailed("Invalid approval signature".to_string());
+            let signer = crate::PublicKey::from_str(&self.signer).map_err(|_| invalid())?;
+            let bytes = URL_SAFE_NO_PAD
+                .decode(&self.signature)
+                .map_err(|_| invalid())?;
+            let bytes: [u8; 64] = bytes.try_into().map_err(|_| invalid())?;
+            let key = VerifyingKey::from_bytes(&signer.to_bytes()).map_err(|_| invalid())?;
+
+            key.verify(&self.signing_message(), &Signature::from_bytes(&bytes))
+                .map_err(|_| invalid())
+        }
+
+        #[cfg(not(feature = "pubky"))]
+        {
+            Err(PaykitError::Unimplemented(
+                "approval signature verification requires the pubky feature",
+            ))
+        }
+    }
+
+    fn signing_message(&self) -> Vec<u8> {
+        let mut message = APPROVAL_DOMAIN.to_vec();
+        message.extend_from_slice(
+            format!(
+                "{}|{}|{}|{}|{}",
+                self.approval_id,
+                self.proposal_digest,
+                self.signer,
+                match self.decision {
+                    ApprovalDecision::Approve => "approve",
+                    ApprovalDecision::Reject => "reject",
+                },
+                self.signed_at
+            )
+            .as_bytes(),
+        );
+        message
+    }
+
+    /// Encode as JSON for transfer to the initiator.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self)
+            .map_err(|e| PaykitError::Serialization(format!("signed approval: {}", e)))
+    }
+
+    /// Decode from JSON produced by [`to_json`](Self::to_json).
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| PaykitError::Serialization(format!("signed approval: {}", e)))
+    }
+}
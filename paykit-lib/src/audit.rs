@@ -0,0 +1,398 @@
+//! Key usage audit log.
+//!
+//! Records every use of the identity key — signatures and Noise handshakes —
+//! so users can review when and with whom their key was used.
+//!
+//! Entries are hash-chained: each entry stores the hash of the one before it,
+//! and its own hash covers its fields plus that link. Editing, reordering or
+//! deleting an entry in the middle of the log breaks the chain, which
+//! [`AuditLog::verify`] reports. (Truncating the tail cannot be detected from
+//! the log alone; compare [`AuditLog::head_hash`] with a copy kept elsewhere.)
+//!
+//! The log itself is storage-agnostic. Front-ends persist it with
+//! [`AuditLog::to_jsonl`] / [`AuditLog::from_jsonl`], one entry per line.
+//!
+//! # Example
+//!
+//! ```
+//! use paykit_lib::audit::{AuditLog, AuditOperation, AuditQuery};
+//!
+//! let mut log = AuditLog::new();
+//! log.append(AuditOperation::Sign, Some("pk:alice"), Some("payment link"), 1_700_000_000);
+//! log.append(AuditOperation::NoiseHandshake, Some("pk:bob"), None, 1_700_000_060);
+//! log.verify()?;
+//!
+//! let handshakes = log.query(&AuditQuery::new().operation(AuditOperation::NoiseHandshake));
+//! assert_eq!(handshakes.len(), 1);
+//!
+//! let restored = AuditLog::from_jsonl(&log.to_jsonl()?)?;
+//! assert_eq!(restored.head_hash(), log.head_hash());
+//! # Ok::<(), paykit_lib::PaykitError>(())
+//! ```
+
+use crate::{PaykitError, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Previous-hash value of the first entry.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// What the identity key was used for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOperation {
+    /// Signed a message (payment link, approval, cancellation, ...).
+    Sign,
+    /// Completed a Noise handshake as initiator or responder.
+    NoiseHandshake,
+    /// Exported the secret key, e.g. to an encrypted backup.
+    KeyExport,
+}
+
+impl AuditOperation {
+    /// Stable name, as used in exports.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditOperation::Sign => "sign",
+            AuditOperation::NoiseHandshake => "noise_handshake",
+            AuditOperation::KeyExport => "key_export",
+        }
+    }
+}
+
+impl std::fmt::Display for AuditOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for AuditOperation {
+    type Err = PaykitError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "sign" => Ok(AuditOperation::Sign),
+            "noise_handshake" | "handshake" => Ok(AuditOperation::NoiseHandshake),
+            "key_export" | "export" => Ok(AuditOperation::KeyExport),
+            _ => Err(PaykitError::invalid_data(
+                "operation",
+                format!("unknown audit operation '{}'", s),
+            )),
+        }
+    }
+}
+
+/// One recorded key use.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Position in the log, starting at 0.
+    pub sequence: u64,
+    /// When the key was used (unix seconds).
+    pub timestamp: i64,
+    /// What the key was used for.
+    pub operation: AuditOperation,
+    /// Public key or address of the other party, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counterparty: Option<String>,
+    /// Free-form detail, e.g. what kind of message was signed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Hash of the previous entry (hex), or [`GENESIS_HASH`].
+    pub prev_hash: String,
+    /// Hash of this entry (hex).
+    pub hash: String,
+}
+
+impl AuditEntry {
+    /// Hash over every field except `hash` itself.
+    fn compute_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.prev_hash.as_bytes());
+        hasher.update(self.sequence.to_be_bytes());
+        hasher.update(self.timestamp.to_be_bytes());
+        hash_str(&mut hasher, self.operation.as_str());
+        hash_str(&mut hasher, self.counterparty.as_deref().unwrap_or(""));
+        hash_str(&mut hasher, self.detail.as_deref().unwrap_or(""));
+        hex::encode(hasher.finalize())
+    }
+}
+
+/// Length-prefix strings so field boundaries are unambiguous.
+fn hash_str(hasher: &mut Sha256, value: &str) {
+    hasher.update((value.len() as u64).to_be_bytes());
+    hasher.update(value.as_bytes());
+}
+
+/// Filter for [`AuditLog::query`]. Empty fields match everything.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditQuery {
+    /// Only this operation.
+    pub operation: Option<AuditOperation>,
+    /// Only entries with this counterparty.
+    pub counterparty: Option<String>,
+    /// Only entries at or after this time (unix seconds).
+    pub since: Option<i64>,
+    /// Only entries at or before this time (unix seconds).
+    pub until: Option<i64>,
+    /// Return at most this many entries (the most recent ones).
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    /// Query matching every entry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restrict to one operation.
+    pub fn operation(mut self, operation: AuditOperation) -> Self {
+        self.operation = Some(operation);
+        self
+    }
+
+    /// Restrict to one counterparty.
+    pub fn counterparty(mut self, counterparty: impl Into<String>) -> Self {
+        self.counterparty = Some(counterparty.into());
+        self
+    }
+
+    /// Restrict to entries at or after a time.
+    pub fn since(mut self, timestamp: i64) -> Self {
+        self.since = Some(timestamp);
+        self
+    }
+
+    /// Restrict to entries at or before a time.
+    pub fn until(mut self, timestamp: i64) -> Self {
+        self.until = Some(timestamp);
+        self
+    }
+
+    /// Keep only the most recent `limit` matches.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        self.operation.is_none_or(|op| entry.operation == op)
+            && self
+                .counterparty
+                .as_deref()
+                .is_none_or(|cp| entry.counterparty.as_deref() == Some(cp))
+            && self.since.is_none_or(|t| entry.timestamp >= t)
+            && self.until.is_none_or(|t| entry.timestamp <= t)
+    }
+}
+
+/// Append-only, hash-chained log of key uses.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AuditLog {
+    entries: Vec<AuditEntry>,
+}
+
+impl AuditLog {
+    /// Create an empty log.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuild a log from stored entries, verifying the chain.
+    pub fn from_entries(entries: Vec<AuditEntry>) -> Result<Self> {
+        let log = Self { entries };
+        log.verify()?;
+        Ok(log)
+    }
+
+    /// Record a key use and return the new entry.
+    pub fn append(
+        &mut self,
+        operation: AuditOperation,
+        counterparty: Option<&str>,
+        detail: Option<&str>,
+        timestamp: i64,
+    ) -> &AuditEntry {
+        let mut entry = AuditEntry {
+            sequence: self.entries.len() as u64,
+            timestamp,
+            operation,
+            counterparty: counterparty.map(str::to_string),
+            detail: detail.map(str::to_string),
+            prev_hash: self.head_hash().to_string(),
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash();
+        self.entries.push(entry);
+        self.entries.last().expect("entry was just pushed")
+    }
+
+    /// Check that every entry's hash and chain link are intact.
+    pub fn verify(&self) -> Result<()> {
+        let mut prev_hash = GENESIS_HASH;
+        for (index, entry) in self.entries.iter().enumerate() {
+            let broken = |reason: &str| {
+                PaykitError::invalid_data("audit log", format!("entry {}: {}", index, reason))
+            };
+            if entry.sequence != index as u64 {
+                return Err(broken("out of sequence"));
+            }
+            if entry.prev_hash != prev_hash {
+                return Err(broken("does not link to the previous entry"));
+            }
+            if entry.hash != entry.compute_hash() {
+                return Err(broken("contents do not match its hash"));
+            }
+            prev_hash = &entry.hash;
+        }
+        Ok(())
+    }
+
+    /// Hash of the latest entry, or [`GENESIS_HASH`] for an empty log.
+    pub fn head_hash(&self) -> &str {
+        self.entries
+            .last()
+            .map_or(GENESIS_HASH, |entry| entry.hash.as_str())
+    }
+
+    /// All entries, oldest first.
+    pub fn entries(&self) -> &[AuditEntry] {
+        &self.entries
+    }
+
+    /// Number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether nothing has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Entries matching a query, oldest first.
+    pub fn query(&self, query: &AuditQuery) -> Vec<&AuditEntry> {
+        let matches: Vec<&AuditEntry> = self.entries.iter().filter(|e| query.matches(e)).collect();
+        match query.limit {
+            Some(limit) if matches.len() > limit => matches[matches.len() - limit..].to_vec(),
+            _ => matches,
+        }
+    }
+
+    /// Serialize as JSON Lines, one entry per line.
+    pub fn to_jsonl(&self) -> Result<String> {
+        let mut out = String::new();
+        for entry in &self.entries {
+            out.push_str(&serde_json::to_string(entry)?);
+            out.push('\n');
+        }
+        Ok(out)
+    }
+
+    /// Parse JSON Lines written by [`AuditLog::to_jsonl`], verifying the chain.
+    pub fn from_jsonl(jsonl: &str) -> Result<Self> {
+        let entries = jsonl
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(PaykitError::from))
+            .collect::<Result<Vec<AuditEntry>>>()?;
+        Self::from_entries(entries)
+    }
+
+    /// Export entries as CSV with a header row.
+    pub fn to_csv(entries: &[&AuditEntry]) -> String {
+        let mut out = String::from("sequence,timestamp,operation,counterparty,detail,hash\n");
+        for entry in entries {
+            out.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                entry.sequence,
+                entry.timestamp,
+                entry.operation,
+                csv_field(entry.counterparty.as_deref().unwrap_or("")),
+                csv_field(entry.detail.as_deref().unwrap_or("")),
+                entry.hash
+            ));
+        }
+        out
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_log() -> AuditLog {
+        let mut log = AuditLog::new();
+        log.append(
+            AuditOperation::Sign,
+            Some("pk:alice"),
+            Some("approval"),
+            100,
+        );
+        log.append(AuditOperation::NoiseHandshake, Some("pk:bob"), None, 200);
+        log.append(
+            AuditOperation::Sign,
+            Some("pk:bob"),
+            Some("payment link"),
+            300,
+        );
+        log
+    }
+
+    #[test]
+    fn test_chain_links_entries() {
+        let log = sample_log();
+        assert_eq!(log.entries()[0].prev_hash, GENESIS_HASH);
+        assert_eq!(log.entries()[1].prev_hash, log.entries()[0].hash);
+        assert_eq!(log.head_hash(), log.entries()[2].hash);
+        assert!(log.verify().is_ok());
+
+        let restored = AuditLog::from_jsonl(&log.to_jsonl().unwrap()).unwrap();
+        assert_eq!(restored, log);
+    }
+
+    #[test]
+    fn test_tampering_is_detected() {
+        let mut edited = sample_log().entries().to_vec();
+        edited[1].counterparty = Some("pk:mallory".to_string());
+        assert!(AuditLog::from_entries(edited).is_err());
+
+        let mut removed = sample_log().entries().to_vec();
+        removed.remove(1);
+        assert!(AuditLog::from_entries(removed).is_err());
+
+        let mut swapped = sample_log().entries().to_vec();
+        swapped.swap(0, 2);
+        assert!(AuditLog::from_entries(swapped).is_err());
+    }
+
+    #[test]
+    fn test_query_and_csv() {
+        let log = sample_log();
+        assert_eq!(
+            log.query(&AuditQuery::new().operation(AuditOperation::Sign))
+                .len(),
+            2
+        );
+        assert_eq!(
+            log.query(&AuditQuery::new().counterparty("pk:bob")).len(),
+            2
+        );
+        assert_eq!(log.query(&AuditQuery::new().since(150).until(250)).len(), 1);
+
+        let latest = log.query(&AuditQuery::new().limit(1));
+        assert_eq!(latest[0].sequence, 2);
+
+        let csv = AuditLog::to_csv(&log.query(&AuditQuery::new()));
+        assert_eq!(csv.lines().count(), 4);
+        assert!(csv.contains(",payment link,"));
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+    }
+}
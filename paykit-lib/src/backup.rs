@@ -0,0 +1,607 @@
+//! Portable, passphrase-protected identity backups.
+//!
+//! One export format shared by the CLI, web and mobile front-ends so a backup
+//! made in one can be restored in any other. The secret key is encrypted with
+//! AES-256-GCM under a key derived from the passphrase with Argon2id; the KDF
+//! parameters travel with the backup so they can be raised later without
+//! breaking old files.
+//!
+//! # Binary layout (version 2)
+//!
+//! | Field        | Size            | Notes                                  |
+//! |--------------|-----------------|----------------------------------------|
+//! | magic        | 8               | `PAYKITBK`                             |
+//! | version      | 1               | `2`                                    |
+//! | kdf          | 1               | `1` = Argon2id v1.3                    |
+//! | memory_kib   | 4               | big-endian                             |
+//! | iterations   | 4               | big-endian                             |
+//! | parallelism  | 4               | big-endian                             |
+//! | salt         | 1 + n           | length-prefixed, 16..=64 bytes         |
+//! | cipher       | 1               | `1` = AES-256-GCM                      |
+//! | nonce        | 12              |                                        |
+//! | public key   | 1 + n           | length-prefixed z-base32, UTF-8        |
+//! | ciphertext   | 2 + n           | length-prefixed, secret key + GCM tag  |
+//! | checksum     | 4               | first 4 bytes of SHA-256 of the above  |
+//!
+//! Everything before the ciphertext is authenticated as associated data, so
+//! tampering with the KDF parameters or public key fails decryption. The
+//! checksum catches truncated or mistyped backups before running the KDF.
+//!
+//! Backups are exchanged as text, either armored (see
+//! [`KeyBackup::to_armored`]) or as the version 1 JSON object older releases
+//! wrote, which [`KeyBackup::decode`] still accepts.
+//!
+//! # Example
+//!
+//! ```
+//! use paykit_lib::backup::{KdfParams, KeyBackup};
+//!
+//! let secret = [7u8; 32];
+//! let backup = KeyBackup::seal(&secret, "8pinxxgq...", "hunter2", KdfParams::low_memory())?;
+//! let text = backup.to_armored()?;
+//!
+//! let restored = KeyBackup::decode(&text)?;
+//! assert_eq!(restored.public_key_z32(), "8pinxxgq...");
+//! assert_eq!(*restored.open("hunter2")?, secret);
+//! # Ok::<(), paykit_lib::PaykitError>(())
+//! ```
+
+use crate::{PaykitError, Result};
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Nonce,
+};
+use base64::Engine;
+use rand::RngCore;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+/// Current backup format version.
+pub const BACKUP_FORMAT_VERSION: u8 = 2;
+
+/// Version of the JSON format written before the binary format existed.
+pub const LEGACY_FORMAT_VERSION: u8 = 1;
+
+const MAGIC: &[u8; 8] = b"PAYKITBK";
+const KDF_ARGON2ID: u8 = 1;
+const CIPHER_AES_256_GCM: u8 = 1;
+const NONCE_LEN: usize = 12;
+const SALT_LEN: usize = 16;
+const SECRET_LEN: usize = 32;
+const TAG_LEN: usize = 16;
+const CHECKSUM_LEN: usize = 4;
+
+const ARMOR_BEGIN: &str = "-----BEGIN PAYKIT KEY BACKUP-----";
+const ARMOR_END: &str = "-----END PAYKIT KEY BACKUP-----";
+const ARMOR_LINE_LEN: usize = 64;
+
+/// Argon2id cost parameters stored in a backup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    /// Memory cost in KiB
+    pub memory_kib: u32,
+    /// Number of passes
+    pub iterations: u32,
+    /// Degree of parallelism
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    /// 64 MiB, 3 passes, 4 lanes — for desktop and browser exports.
+    fn default() -> Self {
+        Self {
+            memory_kib: 65536,
+            iterations: 3,
+            parallelism: 4,
+        }
+    }
+}
+
+impl KdfParams {
+    /// 19 MiB, 4 passes, 2 lanes — for mobile devices.
+    pub fn mobile() -> Self {
+        Self {
+            memory_kib: 19456,
+            iterations: 4,
+            parallelism: 2,
+        }
+    }
+
+    /// 4 MiB, 6 passes — for tests and very constrained environments only.
+    pub fn low_memory() -> Self {
+        Self {
+            memory_kib: 4096,
+            iterations: 6,
+            parallelism: 1,
+        }
+    }
+
+    /// Parameters of `argon2::Argon2::default()`, used by version 1 backups.
+    fn legacy() -> Self {
+        Self {
+            memory_kib: 19456,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+
+    /// Reject parameters that are too weak or expensive enough to be abuse.
+    fn validate(&self) -> Result<()> {
+        let invalid = |reason: &str| PaykitError::invalid_data("kdf params", reason);
+        if !(1..=16).contains(&self.parallelism) {
+            return Err(invalid("parallelism must be between 1 and 16"));
+        }
+        if self.memory_kib < 8 * self.parallelism || self.memory_kib > 4 * 1024 * 1024 {
+            return Err(invalid("memory must be between 8 KiB per lane and 4 GiB"));
+        }
+        if !(1..=64).contains(&self.iterations) {
+            return Err(invalid("iterations must be between 1 and 64"));
+        }
+        Ok(())
+    }
+
+    fn derive_key(&self, passphrase: &str, salt: &[u8]) -> Result<Zeroizing<[u8; 32]>> {
+        use argon2::{Algorithm, Argon2, Params, Version};
+
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, Some(32))
+            .map_err(|e| PaykitError::invalid_data("kdf params", e.to_string()))?;
+
+        let mut key = Zeroizing::new([0u8; 32]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), salt, &mut *key)
+            .map_err(|e| PaykitError::Internal(format!("Key derivation failed: {}", e)))?;
+        Ok(key)
+    }
+}
+
+/// An encrypted identity backup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyBackup {
+    version: u8,
+    kdf: KdfParams,
+    salt: Vec<u8>,
+    nonce: [u8; NONCE_LEN],
+    public_key_z32: String,
+    ciphertext: Vec<u8>,
+}
+
+/// JSON written by version 1 exports.
+#[derive(Deserialize)]
+struct LegacyBackup {
+    version: u32,
+    encrypted_data_hex: String,
+    salt_hex: String,
+    nonce_hex: String,
+    public_key_z32: String,
+}
+
+impl KeyBackup {
+    /// Encrypt a 32-byte Ed25519 secret key under a passphrase.
+    ///
+    /// `public_key_z32` is stored in the clear so the backup can be
+    /// identified without the passphrase.
+    pub fn seal(
+        secret_key: &[u8; 32],
+        public_key_z32: &str,
+        passphrase: &str,
+        kdf: KdfParams,
+    ) -> Result<Self> {
+        if passphrase.is_empty() {
+            return Err(PaykitError::invalid_data(
+                "passphrase",
+                "passphrase cannot be empty",
+            ));
+        }
+        if public_key_z32.is_empty() || public_key_z32.len() > u8::MAX as usize {
+            return Err(PaykitError::invalid_data(
+                "public_key_z32",
+                "expected a z-base32 public key",
+            ));
+        }
+        kdf.validate()?;
+
+        let mut salt = vec![0u8; SALT_LEN];
+        rand::rngs::OsRng.fill_bytes(&mut salt);
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+
+        let mut backup = Self {
+            version: BACKUP_FORMAT_VERSION,
+            kdf,
+            salt,
+            nonce,
+            public_key_z32: public_key_z32.to_string(),
+            ciphertext: Vec::new(),
+        };
+
+        let key = kdf.derive_key(passphrase, &backup.salt)?;
+        let aad = backup.header_bytes();
+        backup.ciphertext = cipher(&key)
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: secret_key,
+                    aad: &aad,
+                },
+            )
+            .map_err(|e| PaykitError::Internal(format!("Encryption failed: {}", e)))?;
+        Ok(backup)
+    }
+
+    /// Decrypt the secret key.
+    ///
+    /// Fails with [`PaykitError::InvalidCredentials`] for a wrong passphrase.
+    pub fn open(&self, passphrase: &str) -> Result<Zeroizing<[u8; 32]>> {
+        let key = self.kdf.derive_key(passphrase, &self.salt)?;
+        let aad = match self.version {
+            LEGACY_FORMAT_VERSION => Vec::new(),
+            _ => self.header_bytes(),
+        };
+
+        let plaintext = Zeroizing::new(
+            cipher(&key)
+                .decrypt(
+                    Nonce::from_slice(&self.nonce),
+                    Payload {
+                        msg: &self.ciphertext,
+                        aad: &aad,
+                    },
+                )
+                .map_err(|_| {
+                    PaykitError::InvalidCredentials(
+                        "wrong passphrase or corrupted backup".to_string(),
+                    )
+                })?,
+        );
+
+        if plaintext.len() != SECRET_LEN {
+            return Err(PaykitError::invalid_data(
+                "backup",
+                "decrypted key has the wrong length",
+            ));
+        }
+        let mut secret = Zeroizing::new([0u8; 32]);
+        secret.copy_from_slice(&plaintext);
+        Ok(secret)
+    }
+
+    /// Format version this backup was written with.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Public key of the backed-up identity (z-base32).
+    pub fn public_key_z32(&self) -> &str {
+        &self.public_key_z32
+    }
+
+    /// Argon2id parameters needed to open this backup.
+    pub fn kdf_params(&self) -> KdfParams {
+        self.kdf
+    }
+
+    /// Encode in the binary format.
+    ///
+    /// Version 1 backups cannot be re-encoded because their ciphertext is
+    /// not bound to the header; open and seal them again instead.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        if self.version != BACKUP_FORMAT_VERSION {
+            return Err(PaykitError::invalid_data(
+                "backup",
+                "only current-version backups can be encoded",
+            ));
+        }
+        let mut out = self.header_bytes();
+        out.extend_from_slice(&(self.ciphertext.len() as u16).to_be_bytes());
+        out.extend_from_slice(&self.ciphertext);
+        let checksum = checksum(&out);
+        out.extend_from_slice(&checksum);
+        Ok(out)
+    }
+
+    /// Decode the binary format.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let invalid = |reason: &str| PaykitError::invalid_data("backup", reason);
+
+        if bytes.len() < MAGIC.len() + CHECKSUM_LEN || !bytes.starts_with(MAGIC) {
+            return Err(invalid("not a Paykit key backup"));
+        }
+        let (body, expected) = bytes.split_at(bytes.len() - CHECKSUM_LEN);
+        if checksum(body) != expected {
+            return Err(invalid(
+                "checksum mismatch; the backup is damaged or incomplete",
+            ));
+        }
+
+        let mut reader = Reader {
+            bytes: &body[MAGIC.len()..],
+        };
+        let version = reader.u8()?;
+        if version != BACKUP_FORMAT_VERSION {
+            return Err(invalid(&format!("unsupported backup version {}", version)));
+        }
+        if reader.u8()? != KDF_ARGON2ID {
+            return Err(invalid("unsupported key derivation function"));
+        }
+        let kdf = KdfParams {
+            memory_kib: reader.u32()?,
+            iterations: reader.u32()?,
+            parallelism: reader.u32()?,
+        };
+        kdf.validate()?;
+
+        let salt_len = reader.u8()? as usize;
+        if !(SALT_LEN..=64).contains(&salt_len) {
+            return Err(invalid("salt must be between 16 and 64 bytes"));
+        }
+        let salt = reader.take(salt_len)?.to_vec();
+
+        if reader.u8()? != CIPHER_AES_256_GCM {
+            return Err(invalid("unsupported cipher"));
+        }
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce.copy_from_slice(reader.take(NONCE_LEN)?);
+
+        let key_len = reader.u8()? as usize;
+        let public_key_z32 = String::from_utf8(reader.take(key_len)?.to_vec())
+            .map_err(|_| invalid("public key is not valid UTF-8"))?;
+
+        let ciphertext_len = u16::from_be_bytes([reader.u8()?, reader.u8()?]) as usize;
+        if ciphertext_len != SECRET_LEN + TAG_LEN {
+            return Err(invalid("unexpected ciphertext length"));
+        }
+        let ciphertext = reader.take(ciphertext_len)?.to_vec();
+        if !reader.bytes.is_empty() {
+            return Err(invalid("trailing data after ciphertext"));
+        }
+
+        Ok(Self {
+            version,
+            kdf,
+            salt,
+            nonce,
+            public_key_z32,
+            ciphertext,
+        })
+    }
+
+    /// Encode as base64 between BEGIN/END lines, for files and copy-paste.
+    ///
+    /// Fails for version 1 backups, which can only be re-exported after
+    /// opening and sealing them again.
+    pub fn to_armored(&self) -> Result<String> {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(self.to_bytes()?);
+
+        let mut out = String::from(ARMOR_BEGIN);
+        out.push('\n');
+        for line in encoded.as_bytes().chunks(ARMOR_LINE_LEN) {
+            // Base64 is ASCII, so this never substitutes anything
+            out.push_str(&String::from_utf8_lossy(line));
+            out.push('\n');
+        }
+        out.push_str(ARMOR_END);
+        out.push('\n');
+        Ok(out)
+    }
+
+    /// Decode any supported text form: armored, bare base64, or version 1 JSON.
+    pub fn decode(input: &str) -> Result<Self> {
+        let input = input.trim();
+        if input.starts_with('{') {
+            return Self::from_legacy_json(input);
+        }
+
+        let body = match input.strip_prefix(ARMOR_BEGIN) {
+            Some(rest) => rest
+                .trim_end()
+                .strip_suffix(ARMOR_END)
+                .ok_or_else(|| PaykitError::invalid_data("backup", "missing END line"))?,
+            None => input,
+        };
+        let encoded: String = body.chars().filter(|c| !c.is_whitespace()).collect();
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|_| PaykitError::invalid_data("backup", "invalid base64"))?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Parse the JSON object written by version 1 exports.
+    pub fn from_legacy_json(json: &str) -> Result<Self> {
+        let legacy: LegacyBackup = serde_json::from_str(json)
+            .map_err(|e| PaykitError::invalid_data("backup", e.to_string()))?;
+        if legacy.version != LEGACY_FORMAT_VERSION as u32 {
+            return Err(PaykitError::invalid_data(
+                "backup",
+                format!("unsupported backup version {}", legacy.version),
+            ));
+        }
+
+        Self::from_legacy_parts(
+            &legacy.encrypted_data_hex,
+            &legacy.salt_hex,
+            &legacy.nonce_hex,
+            &legacy.public_key_z32,
+        )
+    }
+
+    /// Rebuild a version 1 backup from its hex-encoded fields.
+    ///
+    /// For callers that stored the fields of the old JSON object separately.
+    pub fn from_legacy_parts(
+        encrypted_data_hex: &str,
+        salt_hex: &str,
+        nonce_hex: &str,
+        public_key_z32: &str,
+    ) -> Result<Self> {
+        let hex_field = |field: &str, value: &str| {
+            hex::decode(value).map_err(|e| PaykitError::invalid_data(field, e.to_string()))
+        };
+        let nonce: [u8; NONCE_LEN] = hex_field("nonce_hex", nonce_hex)?
+            .try_into()
+            .map_err(|_| PaykitError::invalid_data("nonce_hex", "expected 12 bytes"))?;
+
+        Ok(Self {
+            version: LEGACY_FORMAT_VERSION,
+            kdf: KdfParams::legacy(),
+            salt: hex_field("salt_hex", salt_hex)?,
+            nonce,
+            public_key_z32: public_key_z32.to_string(),
+            ciphertext: hex_field("encrypted_data_hex", encrypted_data_hex)?,
+        })
+    }
+
+    /// Everything up to the ciphertext; authenticated as associated data.
+    fn header_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(64 + self.public_key_z32.len());
+        out.extend_from_slice(MAGIC);
+        out.push(self.version);
+        out.push(KDF_ARGON2ID);
+        out.extend_from_slice(&self.kdf.memory_kib.to_be_bytes());
+        out.extend_from_slice(&self.kdf.iterations.to_be_bytes());
+        out.extend_from_slice(&self.kdf.parallelism.to_be_bytes());
+        out.push(self.salt.len() as u8);
+        out.extend_from_slice(&self.salt);
+        out.push(CIPHER_AES_256_GCM);
+        out.extend_from_slice(&self.nonce);
+        out.push(self.public_key_z32.len() as u8);
+        out.extend_from_slice(self.public_key_z32.as_bytes());
+        out
+    }
+}
+
+fn cipher(key: &[u8; 32]) -> Aes256Gcm {
+    Aes256Gcm::new(aes_gcm::Key::<Aes256Gcm>::from_slice(key))
+}
+
+fn checksum(bytes: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let digest = Sha256::digest(bytes);
+    let mut out = [0u8; CHECKSUM_LEN];
+    out.copy_from_slice(&digest[..CHECKSUM_LEN]);
+    out
+}
+
+/// Minimal cursor over the binary format.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.bytes.len() < len {
+            return Err(PaykitError::invalid_data(
+                "backup",
+                "unexpected end of data",
+            ));
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUBKEY: &str = "8pinxxgqs41n4aididenw5apqp1urfmzdztr8jt4abrkdn435ewo";
+
+    fn sealed() -> KeyBackup {
+        KeyBackup::seal(
+            &[42u8; 32],
+            PUBKEY,
+            "correct horse",
+            KdfParams::low_memory(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_armored_round_trip() {
+        let backup = sealed();
+        let armored = backup.to_armored().unwrap();
+        assert!(armored.starts_with(ARMOR_BEGIN));
+
+        let decoded = KeyBackup::decode(&armored).unwrap();
+        assert_eq!(decoded, backup);
+        assert_eq!(decoded.version(), BACKUP_FORMAT_VERSION);
+        assert_eq!(decoded.public_key_z32(), PUBKEY);
+        assert_eq!(*decoded.open("correct horse").unwrap(), [42u8; 32]);
+
+        assert!(matches!(
+            decoded.open("wrong horse"),
+            Err(PaykitError::InvalidCredentials(_))
+        ));
+    }
+
+    #[test]
+    fn test_tampering_is_detected() {
+        let mut bytes = sealed().to_bytes().unwrap();
+
+        // A flipped bit anywhere fails the checksum
+        bytes[20] ^= 1;
+        assert!(KeyBackup::from_bytes(&bytes).is_err());
+
+        // Header changes with a recomputed checksum still fail authentication
+        bytes[20] ^= 1;
+        let last = bytes.len() - CHECKSUM_LEN;
+        let pubkey_at = last - 2 - (SECRET_LEN + TAG_LEN) - PUBKEY.len();
+        bytes[pubkey_at] = b'y';
+        let sum = checksum(&bytes[..last]);
+        bytes[last..].copy_from_slice(&sum);
+        let tampered = KeyBackup::from_bytes(&bytes).unwrap();
+        assert!(tampered.open("correct horse").is_err());
+
+        assert!(KeyBackup::decode("not a backup").is_err());
+        assert!(KeyBackup::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_legacy_json_backup() {
+        use argon2::Argon2;
+
+        let salt = [1u8; 16];
+        let nonce = [2u8; 12];
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(b"legacy", &salt, &mut key)
+            .unwrap();
+        let encrypted = cipher(&key)
+            .encrypt(Nonce::from_slice(&nonce), [9u8; 32].as_ref())
+            .unwrap();
+        let json = serde_json::json!({
+            "version": 1,
+            "encrypted_data_hex": hex::encode(encrypted),
+            "salt_hex": hex::encode(salt),
+            "nonce_hex": hex::encode(nonce),
+            "public_key_z32": PUBKEY,
+        })
+        .to_string();
+
+        let backup = KeyBackup::decode(&json).unwrap();
+        assert_eq!(backup.version(), LEGACY_FORMAT_VERSION);
+        assert_eq!(*backup.open("legacy").unwrap(), [9u8; 32]);
+        assert!(backup.to_bytes().is_err());
+        assert!(backup.to_armored().is_err());
+    }
+
+    #[test]
+    fn test_rejects_bad_params() {
+        let weak = KdfParams {
+            memory_kib: 4096,
+            iterations: 0,
+            parallelism: 1,
+        };
+        assert!(KeyBackup::seal(&[0u8; 32], PUBKEY, "pw", weak).is_err());
+        assert!(KeyBackup::seal(&[0u8; 32], PUBKEY, "", KdfParams::low_memory()).is_err());
+    }
+}
@@ -0,0 +1,219 @@
+//! Contact Verification
+//!
+//! A contact's public key usually arrives over a channel an attacker could
+//! tamper with (a chat message, a web page, a handle lookup). To rule out a
+//! substituted key, both parties compare a short authentication string (SAS)
+//! out of band, in person or over a call, before marking the contact verified.
+//!
+//! The SAS is derived from both public keys and is the same on both devices,
+//! whichever side computes it. If an attacker swapped either key, the two
+//! devices show different codes.
+//!
+//! Instead of reading the code aloud, one party can show a
+//! [`VerificationChallenge`] as a QR code (`paykit://verify/...`) and the
+//! other scans it. The challenge carries the full fingerprint of both keys,
+//! so scanning is stronger than comparing the 12-digit code.
+//!
+//! Verification itself is stored by the app with its contacts (as a
+//! `verified_at` timestamp); [`TrustPolicy`](crate::policy::TrustPolicy) can
+//! then require verified contacts for large payments.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use paykit_lib::contact_verification::{short_auth_string, VerificationChallenge};
+//!
+//! // Both devices show the same code
+//! println!("Compare: {}", short_auth_string(&my_key, &contact_key)?);
+//!
+//! // Or: Alice shows a QR code...
+//! let code = VerificationChallenge::new(&alice_key, &bob_key)?.to_code();
+//! // ...and Bob scans it
+//! VerificationChallenge::parse(&scanned)?.verify(&bob_key, &alice_key)?;
+//! contacts.mark_verified(&alice_key, now)?;
+//! ```
+
+use crate::policy::normalize_key;
+use crate::{PaykitError, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use sha2::{Digest, Sha256};
+
+/// Prefix of verification challenge codes.
+pub const VERIFY_PREFIX: &str = "paykit://verify/";
+
+/// Number of decimal digits in a short authentication string.
+pub const SAS_DIGITS: usize = 12;
+
+/// Domain separation tag prepended to the keys before hashing.
+const SAS_DOMAIN: &[u8] = b"PAYKIT_CONTACT_SAS_V1:";
+
+/// Length of a z-base-32 encoded public key.
+const PUBLIC_KEY_LEN: usize = 52;
+
+fn validate_key(public_key: &str) -> Result<String> {
+    let key = normalize_key(public_key);
+    if key.len() != PUBLIC_KEY_LEN || !key.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(PaykitError::invalid_data(
+            "public_key",
+            format!("'{}' is not a z-base-32 public key", public_key),
+        ));
+    }
+    Ok(key)
+}
+
+/// Fingerprint of a pair of public keys.
+///
+/// The keys are sorted before hashing, so both parties get the same value.
+pub fn fingerprint(local_key: &str, contact_key: &str) -> Result<[u8; 32]> {
+    let local = validate_key(local_key)?;
+    let contact = validate_key(contact_key)?;
+    if local == contact {
+        return Err(PaykitError::ValidationFailed(
+            "Cannot verify a contact against its own key".to_string(),
+        ));
+    }
+    let (lo, hi) = if local < contact {
+        (local, contact)
+    } else {
+        (contact, local)
+    };
+
+    let mut hasher = Sha256::new();
+    hasher.update(SAS_DOMAIN);
+    hasher.update(lo.as_bytes());
+    hasher.update(b":");
+    hasher.update(hi.as_bytes());
+    Ok(hasher.finalize().into())
+}
+
+/// Short authentication string for a pair of keys, e.g. `0482 7731 5590`.
+///
+/// Both parties compute it from their own key and the key they have for the
+/// other; matching codes mean neither key was substituted.
+pub fn short_auth_string(local_key: &str, contact_key: &str) -> Result<String> {
+    let digest = fingerprint(local_key, contact_key)?;
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&digest[..8]);
+    let value = u64::from_be_bytes(prefix) % 10u64.pow(SAS_DIGITS as u32);
+
+    let digits = format!("{:0width$}", value, width = SAS_DIGITS);
+    let groups: Vec<&str> = (0..SAS_DIGITS)
+        .step_by(4)
+        .map(|start| &digits[start..start + 4])
+        .collect();
+    Ok(groups.join(" "))
+}
+
+/// A scannable verification code binding the shower's key to both keys.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerificationChallenge {
+    /// Public key of the party showing the code (z-base-32).
+    pub public_key: String,
+    /// Fingerprint of both keys, as computed by that party.
+    pub fingerprint: [u8; 32],
+}
+
+impl VerificationChallenge {
+    /// Create the challenge `local_key` shows to `contact_key`.
+    pub fn new(local_key: &str, contact_key: &str) -> Result<Self> {
+        Ok(Self {
+            public_key: validate_key(local_key)?,
+            fingerprint: fingerprint(local_key, contact_key)?,
+        })
+    }
+
+    /// Encode as `paykit://verify/<public_key>.<fingerprint>`.
+    pub fn to_code(&self) -> String {
+        format!(
+            "{}{}.{}",
+            VERIFY_PREFIX,
+            self.public_key,
+            URL_SAFE_NO_PAD.encode(self.fingerprint)
+        )
+    }
+
+    /// Decode a scanned challenge code.
+    pub fn parse(code: &str) -> Result<Self> {
+        let body = code.trim().strip_prefix(VERIFY_PREFIX).ok_or_else(|| {
+            PaykitError::invalid_data("code", format!("expected {} prefix", VERIFY_PREFIX))
+        })?;
+        let (public_key, encoded) = body
+            .split_once('.')
+            .ok_or_else(|| PaykitError::invalid_data("code", "missing fingerprint"))?;
+
+        let bytes = URL_SAFE_NO_PAD
+            .decode(encoded)
+            .map_err(|e| PaykitError::invalid_data("fingerprint", e.to_string()))?;
+        let fingerprint: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| PaykitError::invalid_data("fingerprint", "expected 32 bytes"))?;
+
+        Ok(Self {
+            public_key: validate_key(public_key)?,
+            fingerprint,
+        })
+    }
+
+    /// Check a scanned challenge against the keys the scanner has.
+    ///
+    /// Succeeds when the code was shown by `contact_key` and both parties
+    /// hold the same pair of keys.
+    pub fn verify(&self, local_key: &str, contact_key: &str) -> Result<()> {
+        let contact = validate_key(contact_key)?;
+        if self.public_key != contact {
+            return Err(PaykitError::ValidationFailed(format!(
+                "Verification code belongs to {}, not {}",
+                self.public_key, contact
+            )));
+        }
+        if self.fingerprint != fingerprint(local_key, contact_key)? {
+            return Err(PaykitError::ValidationFailed(format!(
+                "Verification code does not match; the key for {} may have been substituted",
+                contact
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: &str = "8pinxxgqs41n4aididenw5apqp1urfmzdztr8jt4abrkdn435ewo";
+    const BOB: &str = "o1gg96ewuojmopcjbz8895478wdtxtzzuxnfjjz8o8e77csa1ngo";
+    const MALLORY: &str = "ybndrfg8ejkmcpqxot1uwisza345h769ybndrfg8ejkmcpqxot1u";
+
+    #[test]
+    fn test_short_auth_string_is_symmetric() {
+        let sas = short_auth_string(ALICE, BOB).unwrap();
+        assert_eq!(
+            sas,
+            short_auth_string(BOB, &format!("pubky://{}", ALICE)).unwrap()
+        );
+        assert_eq!(sas.len(), SAS_DIGITS + 2);
+        assert!(sas.split(' ').all(|g| g.len() == 4));
+
+        assert_ne!(sas, short_auth_string(ALICE, MALLORY).unwrap());
+        assert!(short_auth_string(ALICE, ALICE).is_err());
+        assert!(short_auth_string(ALICE, "not-a-key").is_err());
+    }
+
+    #[test]
+    fn test_verification_challenge_roundtrip() {
+        let code = VerificationChallenge::new(ALICE, BOB).unwrap().to_code();
+        assert!(code.starts_with(VERIFY_PREFIX));
+
+        let challenge = VerificationChallenge::parse(&code).unwrap();
+        assert!(challenge.verify(BOB, ALICE).is_ok());
+
+        // Bob holds a substituted key for Alice
+        assert!(challenge.verify(BOB, MALLORY).is_err());
+        // Alice was given Mallory's key instead of Bob's
+        let forged = VerificationChallenge::new(ALICE, MALLORY).unwrap();
+        assert!(forged.verify(BOB, ALICE).is_err());
+
+        assert!(VerificationChallenge::parse("paykit://verify/abc").is_err());
+    }
+}
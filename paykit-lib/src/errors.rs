@@ -3,6 +3,7 @@
 //! This module provides structured error types for the Paykit library,
 //! enabling precise error handling and recovery strategies.
 
+use crate::i18n::LocalizedMessage;
 use std::fmt;
 
 /// Error codes for FFI and mobile integration.
@@ -43,6 +44,8 @@ pub enum PaykitErrorCode {
     PaymentRejected = 6003,
     /// Payment already completed
     PaymentAlreadyCompleted = 6004,
+    /// Fee above the payer's guardrails
+    FeeSpike = 6005,
     /// Storage error
     Storage = 7000,
     /// Quota exceeded
@@ -152,6 +155,16 @@ pub enum PaykitError {
         payment_id: String,
     },
 
+    /// Fee is above the payer's guardrails; the user must confirm paying it.
+    FeeSpike {
+        /// Payment method
+        method: String,
+        /// Estimated fee (fee rate or percentage)
+        fee: String,
+        /// Configured limit
+        limit: String,
+    },
+
     /// Storage operation failed.
     Storage(String),
 
@@ -194,6 +207,7 @@ impl PaykitError {
             Self::InvoiceExpired { .. } => PaykitErrorCode::InvoiceExpired,
             Self::PaymentRejected { .. } => PaykitErrorCode::PaymentRejected,
             Self::PaymentAlreadyCompleted { .. } => PaykitErrorCode::PaymentAlreadyCompleted,
+            Self::FeeSpike { .. } => PaykitErrorCode::FeeSpike,
             Self::Storage(_) => PaykitErrorCode::Storage,
             Self::QuotaExceeded { .. } => PaykitErrorCode::QuotaExceeded,
             Self::RateLimited { .. } => PaykitErrorCode::RateLimited,
@@ -206,6 +220,83 @@ impl PaykitError {
         self.to_string()
     }
 
+    /// Get the error as a message code with parameters, for localized display.
+    ///
+    /// Formats to the same English text as `Display`.
+    pub fn localized(&self) -> LocalizedMessage {
+        let message = |code: &str| LocalizedMessage::new(format!("error.{}", code));
+        match self {
+            Self::Unimplemented(label) => message("unimplemented").with_param("feature", label),
+            Self::Transport(msg) => message("transport").with_param("detail", msg),
+            Self::ConnectionFailed { target, reason } => message("connection_failed")
+                .with_param("target", target)
+                .with_param("reason", reason),
+            Self::ConnectionTimeout {
+                operation,
+                timeout_ms,
+            } => message("connection_timeout")
+                .with_param("operation", operation)
+                .with_param("timeout_ms", timeout_ms),
+            Self::Auth(msg) => message("auth").with_param("detail", msg),
+            Self::SessionExpired => message("session_expired"),
+            Self::InvalidCredentials(msg) => {
+                message("invalid_credentials").with_param("detail", msg)
+            }
+            Self::NotFound {
+                resource_type,
+                identifier,
+            } => message("not_found")
+                .with_param("resource_type", resource_type)
+                .with_param("identifier", identifier),
+            Self::MethodNotSupported(method) => {
+                message("method_not_supported").with_param("method", method)
+            }
+            Self::InvalidData { field, reason } => message("invalid_data")
+                .with_param("field", field)
+                .with_param("reason", reason),
+            Self::ValidationFailed(msg) => message("validation_failed").with_param("detail", msg),
+            Self::Serialization(msg) => message("serialization").with_param("detail", msg),
+            Self::Payment { payment_id, reason } => match payment_id {
+                Some(id) => message("payment_with_id")
+                    .with_param("payment_id", id)
+                    .with_param("reason", reason),
+                None => message("payment").with_param("reason", reason),
+            },
+            Self::InsufficientFunds {
+                required,
+                available,
+                currency,
+            } => message("insufficient_funds")
+                .with_param("required", required)
+                .with_param("available", available)
+                .with_param("currency", currency),
+            Self::InvoiceExpired {
+                invoice_id,
+                expired_at,
+            } => message("invoice_expired")
+                .with_param("invoice_id", invoice_id)
+                .with_param("expired_at", expired_at),
+            Self::PaymentRejected { payment_id, reason } => message("payment_rejected")
+                .with_param("payment_id", payment_id)
+                .with_param("reason", reason),
+            Self::PaymentAlreadyCompleted { payment_id } => {
+                message("payment_already_completed").with_param("payment_id", payment_id)
+            }
+            Self::FeeSpike { method, fee, limit } => message("fee_spike")
+                .with_param("method", method)
+                .with_param("fee", fee)
+                .with_param("limit", limit),
+            Self::Storage(msg) => message("storage").with_param("detail", msg),
+            Self::QuotaExceeded { used, limit } => message("quota_exceeded")
+                .with_param("used", used)
+                .with_param("limit", limit),
+            Self::RateLimited { retry_after_ms } => {
+                message("rate_limited").with_param("retry_after_ms", retry_after_ms)
+            }
+            Self::Internal(msg) => message("internal").with_param("detail", msg),
+        }
+    }
+
     /// Returns true if this error is potentially recoverable by retrying.
     pub fn is_retryable(&self) -> bool {
         matches!(
@@ -317,6 +408,9 @@ impl fmt::Display for PaykitError {
             Self::PaymentAlreadyCompleted { payment_id } => {
                 write!(f, "payment {} already completed", payment_id)
             }
+            Self::FeeSpike { method, fee, limit } => {
+                write!(f, "{} fee {} is above the limit of {}", method, fee, limit)
+            }
             Self::Storage(msg) => write!(f, "storage error: {}", msg),
             Self::QuotaExceeded { used, limit } => {
                 write!(f, "quota exceeded: using {} of {} allowed", used, limit)
@@ -362,6 +456,43 @@ mod tests {
         assert!(err.to_string().contains("SAT"));
     }
 
+    #[test]
+    fn test_localized_matches_display() {
+        use crate::i18n::Locale;
+
+        let errors = [
+            PaykitError::Unimplemented("Refunds"),
+            PaykitError::SessionExpired,
+            PaykitError::not_found("endpoint", "lightning"),
+            PaykitError::Payment {
+                payment_id: Some("pay_1".to_string()),
+                reason: "no route".to_string(),
+            },
+            PaykitError::InsufficientFunds {
+                required: "1000".to_string(),
+                available: "500".to_string(),
+                currency: "SAT".to_string(),
+            },
+            PaykitError::RateLimited {
+                retry_after_ms: 250,
+            },
+            PaykitError::FeeSpike {
+                method: "onchain".to_string(),
+                fee: "120.0 sat/vB".to_string(),
+                limit: "50.0 sat/vB".to_string(),
+            },
+        ];
+        for err in &errors {
+            assert_eq!(err.localized().to_string(), err.to_string());
+        }
+
+        let err = PaykitError::QuotaExceeded { used: 9, limit: 8 };
+        assert_eq!(
+            err.localized().format(Locale::Es),
+            "cuota superada: se usan 9 de 8 permitidos"
+        );
+    }
+
     #[test]
     fn test_helper_constructors() {
         let err = PaykitError::not_found("endpoint", "lightning");
@@ -0,0 +1,636 @@
+//! BTCPay Server receiving backend.
+//!
+//! [`BtcPayReceiver`] puts Paykit discovery in front of an existing BTCPay
+//! store. Payment requests become BTCPay invoices created through the
+//! Greenfield API, the store's endpoints are published to the Pubky
+//! directory, and webhook deliveries are turned into invoice status updates
+//! and receipt proofs:
+//!
+//! ```text
+//! payer ─► Paykit request ─► create_invoice ─► BTCPay checkout
+//! BTCPay webhook ─► verify_webhook ─► BtcPayWebhookEvent::status / proof
+//! ```
+//!
+//! Invoices carry the Paykit receipt ID as their `orderId`, so a webhook can
+//! be matched back to the receipt it settles.
+//!
+//! # Feature Flags
+//!
+//! API calls and webhook verification require the `http-executor` feature.
+//! Without it, API calls return an `Unimplemented` error.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use paykit_lib::executors::{BtcPayConfig, BtcPayInvoiceRequest, BtcPayReceiver};
+//!
+//! let config = BtcPayConfig::new("https://btcpay.example.com", "api_key", "store_id")
+//!     .with_webhook_secret("whsec");
+//! let receiver = BtcPayReceiver::new(config)?;
+//!
+//! let invoice = receiver
+//!     .create_invoice(&BtcPayInvoiceRequest::new(21_000).with_order_id("receipt_123"))
+//!     .await?;
+//! println!("Pay at {}", invoice.checkout_link);
+//!
+//! // In the webhook handler
+//! let event = receiver.verify_webhook(&body, &headers["BTCPay-Sig"])?;
+//! if event.status() == Some(BtcPayInvoiceStatus::Settled) { /* ... */ }
+//! ```
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "http-executor")]
+use std::time::Duration;
+
+use super::config::BtcPayConfig;
+use crate::methods::PaymentProof;
+use crate::{AuthenticatedTransport, EndpointData, MethodId, PaykitError, Result};
+
+/// Currency code BTCPay uses for satoshi amounts.
+const SATS: &str = "SATS";
+
+/// BTCPay payment method ID of the store's on-chain wallet.
+const ONCHAIN_PAYMENT_METHOD: &str = "BTC-CHAIN";
+
+/// Lifecycle state of a BTCPay invoice.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BtcPayInvoiceStatus {
+    /// Created, nothing paid yet.
+    New,
+    /// Paid, waiting for confirmations.
+    Processing,
+    /// Paid and confirmed according to the store's speed policy.
+    Settled,
+    /// Expired unpaid (or underpaid).
+    Expired,
+    /// Marked invalid, for example after a double spend.
+    Invalid,
+}
+
+impl BtcPayInvoiceStatus {
+    /// Whether the invoice can no longer change state.
+    pub fn is_final(&self) -> bool {
+        matches!(self, Self::Settled | Self::Expired | Self::Invalid)
+    }
+}
+
+/// Parameters of an invoice to create.
+#[derive(Clone, Debug, Default)]
+pub struct BtcPayInvoiceRequest {
+    /// Amount in satoshis; `None` creates a top-up invoice.
+    pub amount_sats: Option<u64>,
+    /// Order ID, set to the Paykit receipt ID to match webhooks back.
+    pub order_id: Option<String>,
+    /// Description shown on the checkout page.
+    pub description: Option<String>,
+    /// Additional invoice metadata.
+    pub metadata: Option<serde_json::Value>,
+}
+
+impl BtcPayInvoiceRequest {
+    /// Request an invoice for `amount_sats`.
+    pub fn new(amount_sats: u64) -> Self {
+        Self {
+            amount_sats: Some(amount_sats),
+            ..Self::default()
+        }
+    }
+
+    /// Request a top-up invoice, which accepts any amount.
+    pub fn top_up() -> Self {
+        Self::default()
+    }
+
+    /// Set the order ID.
+    pub fn with_order_id(mut self, order_id: impl Into<String>) -> Self {
+        self.order_id = Some(order_id.into());
+        self
+    }
+
+    /// Set the description.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Attach additional metadata.
+    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Greenfield `CreateInvoiceRequest` body.
+    fn to_body(&self, expiration_minutes: Option<u32>) -> serde_json::Value {
+        let mut metadata = match &self.metadata {
+            Some(serde_json::Value::Object(map)) => map.clone(),
+            _ => serde_json::Map::new(),
+        };
+        if let Some(order_id) = &self.order_id {
+            metadata.insert("orderId".to_string(), order_id.clone().into());
+        }
+        if let Some(description) = &self.description {
+            metadata.insert("itemDesc".to_string(), description.clone().into());
+        }
+
+        let mut body = serde_json::json!({
+            "currency": SATS,
+            "metadata": metadata,
+        });
+        if let Some(amount) = self.amount_sats {
+            body["amount"] = amount.to_string().into();
+        }
+        if let Some(minutes) = expiration_minutes {
+            body["checkout"] = serde_json::json!({ "expirationMinutes": minutes });
+        }
+        body
+    }
+}
+
+/// An invoice as returned by the Greenfield API.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BtcPayInvoice {
+    /// Invoice ID.
+    pub id: String,
+    /// URL of the hosted checkout page.
+    pub checkout_link: String,
+    /// Current status.
+    pub status: BtcPayInvoiceStatus,
+    /// Amount in `currency` (empty for top-up invoices).
+    #[serde(default)]
+    pub amount: String,
+    /// Currency of `amount`.
+    pub currency: String,
+    /// Creation time (unix seconds).
+    pub created_time: i64,
+    /// Expiration time (unix seconds).
+    pub expiration_time: i64,
+    /// Invoice metadata, including `orderId`.
+    #[serde(default)]
+    pub metadata: serde_json::Value,
+}
+
+impl BtcPayInvoice {
+    /// The order ID the invoice was created with.
+    pub fn order_id(&self) -> Option<&str> {
+        self.metadata.get("orderId").and_then(|v| v.as_str())
+    }
+}
+
+/// One way to pay an invoice (an address, a BOLT11 invoice or an LNURL).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BtcPayPaymentMethod {
+    /// BTCPay payment method ID (`BTC-CHAIN`, `BTC-LN`, `BTC-LNURL`, or the
+    /// pre-2.0 `BTC`, `BTC-LightningNetwork`, `BTC-LNURLPAY`).
+    pub payment_method_id: String,
+    /// Address, BOLT11 invoice or LNURL to pay.
+    pub destination: String,
+    /// BIP21 or `lightning:` link.
+    #[serde(default)]
+    pub payment_link: Option<String>,
+    /// Amount due in BTC.
+    #[serde(default)]
+    pub due: Option<String>,
+}
+
+impl BtcPayPaymentMethod {
+    /// The Paykit method this payment method maps to, if any.
+    pub fn method_id(&self) -> Option<MethodId> {
+        match self.payment_method_id.as_str() {
+            "BTC" | "BTC-CHAIN" | "BTC-OnChain" => Some(MethodId::onchain()),
+            "BTC-LN" | "BTC-LightningNetwork" | "BTC-LNURL" | "BTC-LNURLPAY" => {
+                Some(MethodId::lightning())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A webhook delivery from the store.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BtcPayWebhookEvent {
+    /// Delivery ID (a redelivery gets a new one).
+    pub delivery_id: String,
+    /// Event type (`InvoiceSettled`, `InvoiceExpired`, ...).
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// Event time (unix seconds).
+    pub timestamp: i64,
+    /// Store the event belongs to.
+    pub store_id: String,
+    /// Invoice the event is about.
+    #[serde(default)]
+    pub invoice_id: Option<String>,
+    /// Invoice metadata, including `orderId`.
+    #[serde(default)]
+    pub metadata: serde_json::Value,
+}
+
+impl BtcPayWebhookEvent {
+    /// Parse a webhook body without checking its signature.
+    ///
+    /// Use [`BtcPayReceiver::verify_webhook`] for deliveries from the network.
+    pub fn parse(body: &[u8]) -> Result<Self> {
+        serde_json::from_slice(body).map_err(|e| {
+            PaykitError::Serialization(format!("Failed to parse BTCPay webhook: {}", e))
+        })
+    }
+
+    /// The order ID of the invoice, normally a Paykit receipt ID.
+    pub fn order_id(&self) -> Option<&str> {
+        self.metadata.get("orderId").and_then(|v| v.as_str())
+    }
+
+    /// The invoice status this event moves to, or `None` for events that
+    /// do not change it.
+    ///
+    /// `InvoicePaymentSettled` confirms a single payment, not the invoice,
+    /// so it maps to `Processing`.
+    pub fn status(&self) -> Option<BtcPayInvoiceStatus> {
+        match self.event_type.as_str() {
+            "InvoiceCreated" => Some(BtcPayInvoiceStatus::New),
+            "InvoiceReceivedPayment" | "InvoiceProcessing" | "InvoicePaymentSettled" => {
+                Some(BtcPayInvoiceStatus::Processing)
+            }
+            "InvoiceSettled" => Some(BtcPayInvoiceStatus::Settled),
+            "InvoiceExpired" => Some(BtcPayInvoiceStatus::Expired),
+            "InvoiceInvalid" => Some(BtcPayInvoiceStatus::Invalid),
+            _ => None,
+        }
+    }
+
+    /// Proof of payment for a settled invoice, to attach to its receipt.
+    pub fn proof(&self) -> Option<PaymentProof> {
+        if self.status() != Some(BtcPayInvoiceStatus::Settled) {
+            return None;
+        }
+        Some(PaymentProof::Custom {
+            method: MethodId::new("btcpay"),
+            data: serde_json::json!({
+                "store_id": self.store_id,
+                "invoice_id": self.invoice_id,
+                "delivery_id": self.delivery_id,
+                "settled_at": self.timestamp,
+            }),
+        })
+    }
+}
+
+/// BTCPay Server store used as a Paykit receiving backend.
+///
+/// # Security
+///
+/// The API key is sent with each request. Use HTTPS and a key restricted
+/// to the permissions listed on [`BtcPayConfig`].
+#[derive(Debug)]
+pub struct BtcPayReceiver {
+    config: BtcPayConfig,
+    #[cfg(feature = "http-executor")]
+    client: reqwest::Client,
+}
+
+impl BtcPayReceiver {
+    /// Create a receiver for the configured store.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidData` if the server URL, API key or store ID is
+    /// empty, or (with `http-executor`) if the HTTP client cannot be built.
+    pub fn new(config: BtcPayConfig) -> Result<Self> {
+        for (field, value) in [
+            ("server_url", &config.server_url),
+            ("api_key", &config.api_key),
+            ("store_id", &config.store_id),
+        ] {
+            if value.is_empty() {
+                return Err(PaykitError::invalid_data(
+                    field,
+                    format!("{} cannot be empty", field),
+                ));
+            }
+        }
+
+        #[cfg(feature = "http-executor")]
+        let client = {
+            let mut builder =
+                reqwest::Client::builder().timeout(Duration::from_secs(config.timeout_secs));
+            if let Some(proxy) = &config.proxy {
+                builder = builder.proxy(proxy.to_reqwest()?);
+            }
+            builder
+                .build()
+                .map_err(|e| PaykitError::Internal(format!("Failed to build HTTP client: {}", e)))?
+        };
+
+        Ok(Self {
+            config,
+            #[cfg(feature = "http-executor")]
+            client,
+        })
+    }
+
+    /// Get the configuration.
+    pub fn config(&self) -> &BtcPayConfig {
+        &self.config
+    }
+
+    /// Create an invoice.
+    pub async fn create_invoice(&self, request: &BtcPayInvoiceRequest) -> Result<BtcPayInvoice> {
+        let body = request.to_body(self.config.invoice_expiration_minutes);
+        self.send("invoices", Some(&body)).await
+    }
+
+    /// Fetch an invoice.
+    pub async fn get_invoice(&self, invoice_id: &str) -> Result<BtcPayInvoice> {
+        self.send(&format!("invoices/{}", invoice_id), None).await
+    }
+
+    /// Ways to pay an invoice.
+    pub async fn payment_methods(&self, invoice_id: &str) -> Result<Vec<BtcPayPaymentMethod>> {
+        self.send(&format!("invoices/{}/payment-methods", invoice_id), None)
+            .await
+    }
+
+    /// An invoice's destinations as Paykit endpoints, one per method.
+    pub async fn invoice_endpoints(
+        &self,
+        invoice_id: &str,
+    ) -> Result<Vec<(MethodId, EndpointData)>> {
+        let mut endpoints: Vec<(MethodId, EndpointData)> = Vec::new();
+        for payment_method in self.payment_methods(invoice_id).await? {
+            let Some(method) = payment_method.method_id() else {
+                continue;
+            };
+            if !endpoints.iter().any(|(m, _)| *m == method) {
+                endpoints.push((method, EndpointData::new(payment_method.destination)));
+            }
+        }
+        Ok(endpoints)
+    }
+
+    /// The store's reusable endpoints: a fresh address from its on-chain
+    /// wallet and the configured Lightning address.
+    ///
+    /// BOLT11 invoices are single-use, so Lightning is only published when
+    /// [`BtcPayConfig::lightning_address`] is set.
+    pub async fn store_endpoints(&self) -> Result<Vec<(MethodId, EndpointData)>> {
+        let mut endpoints = Vec::new();
+        let address: BtcPayWalletAddress = self
+            .send(
+                &format!("payment-methods/{}/wallet/address", ONCHAIN_PAYMENT_METHOD),
+                None,
+            )
+            .await?;
+        endpoints.push((MethodId::onchain(), EndpointData::new(address.address)));
+        if let Some(lightning) = &self.config.lightning_address {
+            endpoints.push((MethodId::lightning(), EndpointData::new(lightning.clone())));
+        }
+        Ok(endpoints)
+    }
+
+    /// Publish [`store_endpoints`](Self::store_endpoints) to the directory.
+    ///
+    /// Returns the methods published.
+    pub async fn publish_endpoints<S>(&self, transport: &S) -> Result<Vec<MethodId>>
+    where
+        S: AuthenticatedTransport,
+    {
+        let mut published = Vec::new();
+        for (method, data) in self.store_endpoints().await? {
+            crate::set_payment_endpoint(transport, method.clone(), data).await?;
+            published.push(method);
+        }
+        Ok(published)
+    }
+
+    /// Verify a webhook delivery against the store's webhook secret.
+    ///
+    /// `signature` is the `BTCPay-Sig` header (`sha256=<hex HMAC>`).
+    ///
+    /// # Errors
+    ///
+    /// Returns `Auth` if no secret is configured or the signature does not
+    /// match, and `InvalidData` if the event is for another store.
+    #[cfg(feature = "http-executor")]
+    pub fn verify_webhook(&self, body: &[u8], signature: &str) -> Result<BtcPayWebhookEvent> {
+        use hmac::{Hmac, Mac};
+
+        let secret =
+            self.config.webhook_secret.as_deref().ok_or_else(|| {
+                PaykitError::Auth("No BTCPay webhook secret configured".to_string())
+            })?;
+        let expected = signature
+            .strip_prefix("sha256=")
+            .and_then(|sig| hex::decode(sig).ok())
+            .ok_or_else(|| PaykitError::Auth("Malformed BTCPay-Sig header".to_string()))?;
+
+        let mut mac = Hmac::<sha2::Sha256>::new_from_slice(secret.as_bytes())
+            .map_err(|e| PaykitError::Internal(format!("Invalid webhook secret: {}", e)))?;
+        mac.update(body);
+        mac.verify_slice(&expected)
+            .map_err(|_| PaykitError::Auth("BTCPay webhook signature mismatch".to_string()))?;
+
+        let event = BtcPayWebhookEvent::parse(body)?;
+        if event.store_id != self.config.store_id {
+            return Err(PaykitError::invalid_data(
+                "store_id",
+                format!("Webhook is for store {}", event.store_id),
+            ));
+        }
+        Ok(event)
+    }
+
+    /// Build the full URL for a store API path.
+    #[cfg(any(feature = "http-executor", test))]
+    fn url(&self, path: &str) -> String {
+        format!(
+            "{}/api/v1/stores/{}/{}",
+            self.config.server_url.trim_end_matches('/'),
+            self.config.store_id,
+            path
+        )
+    }
+
+    /// Call the store API, POSTing `body` if given and GETting otherwise.
+    #[cfg(feature = "http-executor")]
+    async fn send<T: for<'de> Deserialize<'de>>(
+        &self,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<T> {
+        let url = self.url(path);
+        let request = match body {
+            Some(body) => self.client.post(url).json(body),
+            None => self.client.get(url),
+        }
+        .header("Authorization", format!("token {}", self.config.api_key));
+        let response = request
+            .send()
+            .await
+            .map_err(|e| self.map_reqwest_error(e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(map_error_response(status.as_u16(), &body));
+        }
+
+        response.json::<T>().await.map_err(|e| {
+            PaykitError::Serialization(format!("Failed to parse BTCPay response: {}", e))
+        })
+    }
+
+    /// Call the store API (stub when feature disabled).
+    #[cfg(not(feature = "http-executor"))]
+    async fn send<T: for<'de> Deserialize<'de>>(
+        &self,
+        _path: &str,
+        _body: Option<&serde_json::Value>,
+    ) -> Result<T> {
+        Err(PaykitError::Unimplemented(
+            "BTCPay HTTP client not compiled - enable the 'http-executor' feature",
+        ))
+    }
+
+    /// Map reqwest errors to PaykitError.
+    #[cfg(feature = "http-executor")]
+    fn map_reqwest_error(&self, e: reqwest::Error) -> PaykitError {
+        if e.is_timeout() {
+            PaykitError::ConnectionTimeout {
+                operation: "BTCPay request".to_string(),
+                timeout_ms: self.config.timeout_secs * 1000,
+            }
+        } else if e.is_connect() {
+            PaykitError::ConnectionFailed {
+                target: self.config.server_url.clone(),
+                reason: e.to_string(),
+            }
+        } else {
+            PaykitError::Transport(format!("BTCPay request failed: {}", e))
+        }
+    }
+}
+
+/// Response of the store wallet's address endpoint.
+#[derive(Deserialize)]
+struct BtcPayWalletAddress {
+    address: String,
+}
+
+/// Greenfield error body.
+#[derive(Deserialize)]
+#[cfg(any(feature = "http-executor", test))]
+struct BtcPayApiError {
+    #[serde(default)]
+    code: Option<String>,
+    message: String,
+}
+
+/// Map a non-2xx Greenfield response to a [`PaykitError`].
+#[cfg(any(feature = "http-executor", test))]
+fn map_error_response(status: u16, body: &str) -> PaykitError {
+    let error = serde_json::from_str::<BtcPayApiError>(body).ok();
+    let message = error
+        .as_ref()
+        .map(|e| e.message.clone())
+        .unwrap_or_else(|| body.to_string());
+
+    match status {
+        401 | 403 => PaykitError::Auth(format!("BTCPay API key rejected: {}", message)),
+        404 => PaykitError::NotFound {
+            resource_type: "BTCPay resource".to_string(),
+            identifier: error.and_then(|e| e.code).unwrap_or(message),
+        },
+        400 | 422 => PaykitError::ValidationFailed(format!("BTCPay rejected request: {}", message)),
+        429 => PaykitError::RateLimited {
+            retry_after_ms: 5000,
+        },
+        500..=599 => {
+            PaykitError::Internal(format!("BTCPay server error ({}): {}", status, message))
+        }
+        _ => PaykitError::Transport(format!("BTCPay request failed ({}): {}", status, message)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receiver() -> BtcPayReceiver {
+        BtcPayReceiver::new(
+            BtcPayConfig::new("https://btcpay.example.com/", "key", "store1")
+                .with_webhook_secret("whsec"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_btcpay_receiver_validation() {
+        assert_eq!(
+            receiver().url("invoices"),
+            "https://btcpay.example.com/api/v1/stores/store1/invoices"
+        );
+        let err = BtcPayReceiver::new(BtcPayConfig::new("https://btcpay", "key", "")).unwrap_err();
+        assert!(err.to_string().contains("store_id"));
+    }
+
+    #[test]
+    fn test_invoice_request_body() {
+        let body = BtcPayInvoiceRequest::new(21_000)
+            .with_order_id("receipt_1")
+            .with_description("Coffee")
+            .to_body(Some(15));
+        assert_eq!(body["amount"], "21000");
+        assert_eq!(body["currency"], "SATS");
+        assert_eq!(body["metadata"]["orderId"], "receipt_1");
+        assert_eq!(body["checkout"]["expirationMinutes"], 15);
+
+        let top_up = BtcPayInvoiceRequest::top_up().to_body(None);
+        assert!(top_up.get("amount").is_none());
+    }
+
+    #[test]
+    fn test_webhook_event_mapping() {
+        let event = BtcPayWebhookEvent::parse(
+            br#"{"deliveryId":"d1","type":"InvoiceSettled","timestamp":1700000000,
+                "storeId":"store1","invoiceId":"inv1","metadata":{"orderId":"receipt_1"}}"#,
+        )
+        .unwrap();
+        assert_eq!(event.status(), Some(BtcPayInvoiceStatus::Settled));
+        assert_eq!(event.order_id(), Some("receipt_1"));
+        assert!(matches!(event.proof(), Some(PaymentProof::Custom { .. })));
+
+        let payment = BtcPayWebhookEvent {
+            event_type: "InvoicePaymentSettled".to_string(),
+            ..event
+        };
+        assert_eq!(payment.status(), Some(BtcPayInvoiceStatus::Processing));
+        assert!(payment.proof().is_none());
+
+        let err = map_error_response(403, r#"{"code":"missing-permission","message":"No"}"#);
+        assert!(matches!(err, PaykitError::Auth(_)));
+    }
+
+    #[cfg(feature = "http-executor")]
+    #[test]
+    fn test_verify_webhook_signature() {
+        use hmac::{Hmac, Mac};
+
+        let body =
+            br#"{"deliveryId":"d1","type":"InvoiceExpired","timestamp":1,"storeId":"store1"}"#;
+        let mut mac = Hmac::<sha2::Sha256>::new_from_slice(b"whsec").unwrap();
+        mac.update(body);
+        let signature = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+
+        let event = receiver().verify_webhook(body, &signature).unwrap();
+        assert_eq!(event.status(), Some(BtcPayInvoiceStatus::Expired));
+
+        let tampered = body.to_vec().into_iter().rev().collect::<Vec<_>>();
+        assert!(matches!(
+            receiver().verify_webhook(&tampered, &signature),
+            Err(PaykitError::Auth(_))
+        ));
+    }
+}
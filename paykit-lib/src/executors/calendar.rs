@@ -0,0 +1,198 @@
+//! OpenTimestamps calendar server client.
+//!
+//! A calendar aggregates the digests it is sent into one Bitcoin
+//! transaction. [`CalendarClient::submit`] hands it a 32-byte digest and
+//! returns the serialized timestamp it answers with, which ends in a pending
+//! attestation. Once the transaction has confirmed (usually a few hours),
+//! [`CalendarClient::fetch`] returns the operations from that pending
+//! commitment to a Bitcoin block header.
+//!
+//! Both calls deal in raw OpenTimestamps bytes; parsing and verifying them
+//! is left to the caller (see `paykit_interactive::ots`).
+//!
+//! # Feature Flags
+//!
+//! Requests need the `http-executor` feature. Without it, all requests
+//! return an `Unimplemented` error.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use paykit_lib::executors::CalendarClient;
+//!
+//! let calendar = CalendarClient::new("https://alice.btc.calendar.opentimestamps.org")?;
+//! let pending = calendar.submit(&digest).await?;
+//! // ...hours later
+//! if let Some(upgrade) = calendar.fetch(&commitment).await? {
+//!     // merge `upgrade` into the stored timestamp
+//! }
+//! ```
+
+#[cfg(feature = "http-executor")]
+use std::time::Duration;
+
+#[cfg(feature = "http-executor")]
+use crate::PaykitError;
+use crate::Result;
+
+/// Public calendars run by the OpenTimestamps project and others.
+pub const DEFAULT_CALENDARS: &[&str] = &[
+    "https://alice.btc.calendar.opentimestamps.org",
+    "https://bob.btc.calendar.opentimestamps.org",
+    "https://finney.calendar.eternitywall.com",
+];
+
+/// Largest response accepted from a calendar, in bytes.
+#[cfg(feature = "http-executor")]
+const MAX_RESPONSE_SIZE: usize = 10_000;
+
+/// Request timeout in seconds.
+#[cfg(feature = "http-executor")]
+const TIMEOUT_SECS: u64 = 30;
+
+/// Client for one OpenTimestamps calendar server.
+pub struct CalendarClient {
+    url: String,
+    #[cfg(feature = "http-executor")]
+    client: reqwest::Client,
+}
+
+impl CalendarClient {
+    /// Create a client for the calendar at `url`.
+    #[cfg(feature = "http-executor")]
+    pub fn new(url: impl Into<String>) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(TIMEOUT_SECS))
+            .build()
+            .map_err(|e| PaykitError::Transport(format!("Failed to create HTTP client: {}", e)))?;
+        Ok(Self {
+            url: url.into().trim_end_matches('/').to_string(),
+            client,
+        })
+    }
+
+    /// Create a client for the calendar at `url` (stub when feature disabled).
+    #[cfg(not(feature = "http-executor"))]
+    pub fn new(url: impl Into<String>) -> Result<Self> {
+        Ok(Self {
+            url: url.into().trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// The calendar's base URL.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Submit a digest; returns the serialized timestamp for it.
+    #[cfg(feature = "http-executor")]
+    pub async fn submit(&self, digest: &[u8]) -> Result<Vec<u8>> {
+        let response = self
+            .client
+            .post(format!("{}/digest", self.url))
+            .header("Accept", "application/vnd.opentimestamps.v1")
+            .body(digest.to_vec())
+            .send()
+            .await
+            .map_err(|e| self.map_reqwest_error(e))?;
+        self.read_body(response)
+            .await?
+            .ok_or_else(|| PaykitError::NotFound {
+                resource_type: "Calendar endpoint".to_string(),
+                identifier: self.url.clone(),
+            })
+    }
+
+    /// Submit a digest (stub when feature disabled).
+    #[cfg(not(feature = "http-executor"))]
+    pub async fn submit(&self, _digest: &[u8]) -> Result<Vec<u8>> {
+        Err(crate::PaykitError::Unimplemented(
+            "Calendar HTTP client not compiled - enable the 'http-executor' feature",
+        ))
+    }
+
+    /// Fetch the completed timestamp for a pending commitment.
+    ///
+    /// Returns `None` while the calendar's transaction is unconfirmed.
+    #[cfg(feature = "http-executor")]
+    pub async fn fetch(&self, commitment: &[u8]) -> Result<Option<Vec<u8>>> {
+        let response = self
+            .client
+            .get(format!(
+                "{}/timestamp/{}",
+                self.url,
+                hex::encode(commitment)
+            ))
+            .header("Accept", "application/vnd.opentimestamps.v1")
+            .send()
+            .await
+            .map_err(|e| self.map_reqwest_error(e))?;
+        self.read_body(response).await
+    }
+
+    /// Fetch a completed timestamp (stub when feature disabled).
+    #[cfg(not(feature = "http-executor"))]
+    pub async fn fetch(&self, _commitment: &[u8]) -> Result<Option<Vec<u8>>> {
+        Err(crate::PaykitError::Unimplemented(
+            "Calendar HTTP client not compiled - enable the 'http-executor' feature",
+        ))
+    }
+
+    /// Read a response body, mapping 404 to `None`.
+    #[cfg(feature = "http-executor")]
+    async fn read_body(&self, response: reqwest::Response) -> Result<Option<Vec<u8>>> {
+        let status = response.status();
+        if status.as_u16() == 404 {
+            return Ok(None);
+        }
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| self.map_reqwest_error(e))?;
+        if !status.is_success() {
+            return Err(PaykitError::Transport(format!(
+                "Calendar {} answered {}: {}",
+                self.url,
+                status.as_u16(),
+                String::from_utf8_lossy(&body)
+            )));
+        }
+        if body.len() > MAX_RESPONSE_SIZE {
+            return Err(PaykitError::InvalidData {
+                field: "calendar response".to_string(),
+                reason: format!("{} bytes exceeds {}", body.len(), MAX_RESPONSE_SIZE),
+            });
+        }
+        Ok(Some(body.to_vec()))
+    }
+
+    /// Map reqwest errors to PaykitError.
+    #[cfg(feature = "http-executor")]
+    fn map_reqwest_error(&self, e: reqwest::Error) -> PaykitError {
+        if e.is_timeout() {
+            PaykitError::ConnectionTimeout {
+                operation: "Calendar request".to_string(),
+                timeout_ms: TIMEOUT_SECS * 1000,
+            }
+        } else if e.is_connect() {
+            PaykitError::ConnectionFailed {
+                target: self.url.clone(),
+                reason: e.to_string(),
+            }
+        } else {
+            PaykitError::Transport(format!("Calendar request failed: {}", e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calendar_url_is_normalized() {
+        let calendar =
+            CalendarClient::new("https://alice.btc.calendar.opentimestamps.org/").unwrap();
+        assert_eq!(calendar.url(), DEFAULT_CALENDARS[0]);
+    }
+}
@@ -0,0 +1,562 @@
+//! Core Lightning (CLN) REST API executor implementation.
+//!
+//! Connects to CLN nodes through the `clnrest` plugin. Every call is a JSON-RPC
+//! command posted to `/v1/<command>` and authorized by the `Rune` header, so
+//! a rune restricted to `pay`, `decode`, `getroute` and `listpays` is enough.
+//!
+//! When the `clnrest` CA certificate is configured the connection is pinned
+//! to it. Payments still in flight are reported as
+//! [`LightningPaymentStatus::Pending`]; `ClnExecutor::wait_for_payment` polls
+//! `listpays` until they settle.
+//!
+//! # Feature Flags
+//!
+//! This module requires the `http-executor` feature flag to be enabled for actual
+//! HTTP requests. Without it, all requests return an `Unimplemented` error.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use paykit_lib::executors::{ClnConfig, ClnExecutor};
+//! use paykit_lib::methods::LightningExecutor;
+//!
+//! let config = ClnConfig::new("https://localhost:3010", "your_rune")
+//!     .with_tls_cert(std::fs::read_to_string("/home/bitcoin/.lightning/bitcoin/ca.pem")?);
+//! let executor = ClnExecutor::new(config)?;
+//!
+//! let decoded = executor.decode_invoice("lnbc...").await?;
+//! let fee = executor.estimate_fee("lnbc...").await?;
+//! let result = executor.pay_invoice("lnbc...", None, None).await?;
+//! ```
+
+use async_trait::async_trait;
+use serde::{Deserialize, Deserializer, Serialize};
+#[cfg(feature = "http-executor")]
+use std::time::Duration;
+
+use super::config::ClnConfig;
+use crate::methods::{
+    DecodedInvoice, LightningExecutor, LightningPaymentResult, LightningPaymentStatus, RouteProbe,
+};
+use crate::{PaykitError, Result};
+
+/// CLN error code when no route to the destination exists.
+#[cfg(any(feature = "http-executor", test))]
+const PAY_ROUTE_NOT_FOUND: i64 = 205;
+
+/// CLN REST API executor for Lightning payments.
+///
+/// # Security
+///
+/// The rune is sent with each request. Use HTTPS, pin the `clnrest` CA
+/// certificate via [`ClnConfig::tls_cert_pem`], and restrict the rune to
+/// the commands this executor needs.
+#[derive(Debug)]
+pub struct ClnExecutor {
+    config: ClnConfig,
+    #[cfg(feature = "http-executor")]
+    client: reqwest::Client,
+}
+
+/// Alias for [`ClnExecutor`], named after the trait it implements.
+pub type ClnLightningExecutor = ClnExecutor;
+
+impl ClnExecutor {
+    /// Create a new CLN executor with the given configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The REST URL is empty
+    /// - The rune is empty
+    /// - (With `http-executor` feature) The TLS certificate is not valid PEM
+    /// - (With `http-executor` feature) The HTTP client cannot be built
+    pub fn new(config: ClnConfig) -> Result<Self> {
+        if config.rest_url.is_empty() {
+            return Err(PaykitError::InvalidData {
+                field: "rest_url".to_string(),
+                reason: "REST URL cannot be empty".to_string(),
+            });
+        }
+        if config.rune.is_empty() {
+            return Err(PaykitError::InvalidData {
+                field: "rune".to_string(),
+                reason: "Rune cannot be empty".to_string(),
+            });
+        }
+
+        #[cfg(feature = "http-executor")]
+        let client = {
+            let mut builder =
+                reqwest::Client::builder().timeout(Duration::from_secs(config.timeout_secs));
+            if let Some(pem) = &config.tls_cert_pem {
+                let cert = reqwest::Certificate::from_pem(pem.as_bytes()).map_err(|e| {
+                    PaykitError::InvalidData {
+                        field: "tls_cert_pem".to_string(),
+                        reason: format!("Invalid TLS certificate: {}", e),
+                    }
+                })?;
+                // Trust the node's CA and nothing else
+                builder = builder
+                    .tls_built_in_root_certs(false)
+                    .add_root_certificate(cert);
+            }
+            if let Some(proxy) = &config.proxy {
+                builder = builder.proxy(proxy.to_reqwest()?);
+            }
+            builder
+                .build()
+                .map_err(|e| PaykitError::Internal(format!("Failed to build HTTP client: {}", e)))?
+        };
+
+        Ok(Self {
+            config,
+            #[cfg(feature = "http-executor")]
+            client,
+        })
+    }
+
+    /// Get the configuration.
+    pub fn config(&self) -> &ClnConfig {
+        &self.config
+    }
+
+    /// Poll the node until a payment succeeds or fails.
+    ///
+    /// Checks every [`ClnConfig::poll_interval_ms`]; see
+    /// [`wait_for_payment`](super::wait_for_payment).
+    #[cfg(feature = "http-executor")]
+    pub async fn wait_for_payment(
+        &self,
+        payment_hash: &str,
+        timeout: Duration,
+    ) -> Result<LightningPaymentResult> {
+        let interval = Duration::from_millis(self.config.poll_interval_ms);
+        super::poll::wait_for_payment(self, payment_hash, interval, timeout).await
+    }
+
+    /// Build the full URL for a command.
+    #[cfg(any(feature = "http-executor", test))]
+    fn url(&self, command: &str) -> String {
+        format!(
+            "{}/v1/{}",
+            self.config.rest_url.trim_end_matches('/'),
+            command
+        )
+    }
+
+    /// Run a command on the node.
+    #[cfg(feature = "http-executor")]
+    async fn call<T: for<'de> Deserialize<'de>, P: Serialize>(
+        &self,
+        command: &str,
+        params: &P,
+    ) -> Result<T> {
+        let response = self
+            .client
+            .post(self.url(command))
+            .header("Rune", &self.config.rune)
+            .json(params)
+            .send()
+            .await
+            .map_err(|e| self.map_reqwest_error(e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(map_error_response(status.as_u16(), &body));
+        }
+
+        response
+            .json::<T>()
+            .await
+            .map_err(|e| PaykitError::Serialization(format!("Failed to parse CLN response: {}", e)))
+    }
+
+    /// Run a command on the node (stub when feature disabled).
+    #[cfg(not(feature = "http-executor"))]
+    async fn call<T: for<'de> Deserialize<'de>, P: Serialize>(
+        &self,
+        _command: &str,
+        _params: &P,
+    ) -> Result<T> {
+        Err(PaykitError::Unimplemented(
+            "CLN HTTP client not compiled - enable the 'http-executor' feature",
+        ))
+    }
+
+    /// Map reqwest errors to PaykitError.
+    #[cfg(feature = "http-executor")]
+    fn map_reqwest_error(&self, e: reqwest::Error) -> PaykitError {
+        if e.is_timeout() {
+            PaykitError::ConnectionTimeout {
+                operation: "CLN request".to_string(),
+                timeout_ms: self.config.timeout_secs * 1000,
+            }
+        } else if e.is_connect() {
+            PaykitError::ConnectionFailed {
+                target: self.config.rest_url.clone(),
+                reason: e.to_string(),
+            }
+        } else {
+            PaykitError::Transport(format!("CLN request failed: {}", e))
+        }
+    }
+
+    /// Find a route to `destination` with `getroute`.
+    async fn get_route(&self, destination: &str, amount_msat: u64) -> Result<Vec<ClnRouteHop>> {
+        let request = ClnGetRouteRequest {
+            id: destination.to_string(),
+            amount_msat,
+            riskfactor: 1,
+        };
+        let response: ClnGetRouteResponse = self.call("getroute", &request).await?;
+        Ok(response.route)
+    }
+}
+
+#[async_trait]
+impl LightningExecutor for ClnExecutor {
+    async fn pay_invoice(
+        &self,
+        invoice: &str,
+        amount_msat: Option<u64>,
+        max_fee_msat: Option<u64>,
+    ) -> Result<LightningPaymentResult> {
+        let request = ClnPayRequest {
+            bolt11: invoice.to_string(),
+            amount_msat,
+            // An absolute limit wins over the configured percentage
+            maxfeepercent: max_fee_msat
+                .is_none()
+                .then_some(self.config.max_fee_percent),
+            maxfee: max_fee_msat,
+            retry_for: Some(self.config.timeout_secs),
+        };
+        let response: ClnPayResponse = self.call("pay", &request).await?;
+
+        let amount = response.amount_msat.or(amount_msat).unwrap_or(0);
+        Ok(LightningPaymentResult {
+            preimage: response.payment_preimage,
+            payment_hash: response.payment_hash,
+            amount_msat: amount,
+            fee_msat: response
+                .amount_sent_msat
+                .map(|sent| sent.saturating_sub(amount))
+                .unwrap_or(0),
+            hops: 0, // Not available in pay response
+            status: payment_status(&response.status),
+        })
+    }
+
+    async fn decode_invoice(&self, invoice: &str) -> Result<DecodedInvoice> {
+        let request = ClnDecodeRequest {
+            string: invoice.to_string(),
+        };
+        let response: ClnDecodeResponse = self.call("decode", &request).await?;
+
+        if !response.valid || !response.kind.starts_with("bolt11") {
+            return Err(PaykitError::invalid_data(
+                "invoice",
+                format!("Not a valid BOLT11 invoice ({})", response.kind),
+            ));
+        }
+
+        let expiry = response.expiry.unwrap_or(3600);
+        let current_time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        Ok(DecodedInvoice {
+            payment_hash: response.payment_hash,
+            amount_msat: response.amount_msat,
+            description: response.description,
+            description_hash: response.description_hash,
+            payee: response.payee,
+            expiry,
+            timestamp: response.created_at,
+            expired: current_time > response.created_at + expiry,
+        })
+    }
+
+    async fn estimate_fee(&self, invoice: &str) -> Result<u64> {
+        let decoded = self.decode_invoice(invoice).await?;
+        let amount_msat = decoded.amount_msat.unwrap_or(0);
+
+        let route = self.get_route(&decoded.payee, amount_msat).await?;
+        Ok(route_fee_msat(&route, amount_msat))
+    }
+
+    async fn probe_route(&self, destination: &str, amount_msat: u64) -> Result<RouteProbe> {
+        // Accept either an invoice or a node public key
+        let payee = if destination.starts_with("ln") {
+            self.decode_invoice(destination).await?.payee
+        } else {
+            destination.to_string()
+        };
+
+        let route = match self.get_route(&payee, amount_msat).await {
+            Ok(route) => route,
+            Err(PaykitError::NotFound { .. }) => return Ok(RouteProbe::no_route()),
+            Err(e) => return Err(e),
+        };
+        if route.is_empty() {
+            return Ok(RouteProbe::no_route());
+        }
+
+        // getroute reports no success probability; a found route is the
+        // best signal available without attempting the payment
+        Ok(RouteProbe::found(
+            1.0,
+            route_fee_msat(&route, amount_msat),
+            route.len() as u32,
+        ))
+    }
+
+    async fn get_payment(&self, payment_hash: &str) -> Result<Option<LightningPaymentResult>> {
+        let request = ClnListPaysRequest {
+            payment_hash: payment_hash.to_string(),
+        };
+        let response: ClnListPaysResponse = self.call("listpays", &request).await?;
+
+        Ok(response.pays.into_iter().next().map(|p| {
+            let amount = p.amount_msat.unwrap_or(0);
+            LightningPaymentResult {
+                preimage: p.preimage,
+                payment_hash: p.payment_hash,
+                amount_msat: amount,
+                fee_msat: p
+                    .amount_sent_msat
+                    .map(|sent| sent.saturating_sub(amount))
+                    .unwrap_or(0),
+                hops: 0, // Not available in list response
+                status: payment_status(&p.status),
+            }
+        }))
+    }
+}
+
+/// Map a CLN payment status to [`LightningPaymentStatus`].
+fn payment_status(status: &str) -> LightningPaymentStatus {
+    match status {
+        "complete" => LightningPaymentStatus::Succeeded,
+        "pending" => LightningPaymentStatus::Pending,
+        _ => LightningPaymentStatus::Failed,
+    }
+}
+
+/// Routing fee of a `getroute` route: what the first hop receives minus the
+/// amount delivered.
+fn route_fee_msat(route: &[ClnRouteHop], amount_msat: u64) -> u64 {
+    route
+        .first()
+        .and_then(|hop| hop.amount_msat)
+        .map(|sent| sent.saturating_sub(amount_msat))
+        .unwrap_or(0)
+}
+
+/// Map a failed `clnrest` response to PaykitError.
+///
+/// `clnrest` passes JSON-RPC errors through as `{"code": .., "message": ..}`.
+#[cfg(any(feature = "http-executor", test))]
+fn map_error_response(status: u16, body: &str) -> PaykitError {
+    let rpc = serde_json::from_str::<ClnRpcError>(body).ok();
+    let message = rpc
+        .as_ref()
+        .map(|e| e.message.clone())
+        .unwrap_or_else(|| body.to_string());
+
+    match (status, rpc.and_then(|e| e.code)) {
+        (401 | 403, _) => PaykitError::Auth(format!("CLN rune rejected: {}", message)),
+        (_, Some(PAY_ROUTE_NOT_FOUND)) => PaykitError::NotFound {
+            resource_type: "Lightning route".to_string(),
+            identifier: message,
+        },
+        // Payment errors are numbered 200-219
+        (_, Some(200..=219)) => PaykitError::Payment {
+            payment_id: None,
+            reason: message,
+        },
+        (_, Some(_)) => PaykitError::Transport(format!("CLN command failed: {}", message)),
+        (429, None) => PaykitError::RateLimited {
+            retry_after_ms: 5000,
+        },
+        (500..=599, None) => {
+            PaykitError::Internal(format!("CLN server error ({}): {}", status, message))
+        }
+        (_, None) => {
+            PaykitError::Transport(format!("CLN request failed ({}): {}", status, message))
+        }
+    }
+}
+
+/// Millisatoshi amounts are plain numbers in current CLN releases and
+/// `"<n>msat"` strings in older ones.
+fn de_msat<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<u64>, D::Error> {
+    Ok(
+        match Option::<serde_json::Value>::deserialize(deserializer)? {
+            Some(serde_json::Value::Number(n)) => n.as_u64(),
+            Some(serde_json::Value::String(s)) => s.trim_end_matches("msat").parse().ok(),
+            _ => None,
+        },
+    )
+}
+
+// ============================================================================
+// CLN REST API Types
+// ============================================================================
+
+#[cfg(any(feature = "http-executor", test))]
+#[derive(Deserialize)]
+struct ClnRpcError {
+    #[serde(default)]
+    code: Option<i64>,
+    #[serde(default)]
+    message: String,
+}
+
+#[derive(Serialize)]
+struct ClnPayRequest {
+    bolt11: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    amount_msat: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    maxfeepercent: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    maxfee: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retry_for: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct ClnPayResponse {
+    #[serde(default)]
+    payment_preimage: String,
+    #[serde(default)]
+    payment_hash: String,
+    #[serde(default, deserialize_with = "de_msat")]
+    amount_msat: Option<u64>,
+    #[serde(default, deserialize_with = "de_msat")]
+    amount_sent_msat: Option<u64>,
+    #[serde(default)]
+    status: String,
+}
+
+#[derive(Serialize)]
+struct ClnDecodeRequest {
+    string: String,
+}
+
+#[derive(Deserialize)]
+struct ClnDecodeResponse {
+    #[serde(rename = "type", default)]
+    kind: String,
+    #[serde(default)]
+    valid: bool,
+    #[serde(default)]
+    payee: String,
+    #[serde(default)]
+    payment_hash: String,
+    #[serde(default, deserialize_with = "de_msat")]
+    amount_msat: Option<u64>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    description_hash: Option<String>,
+    #[serde(default)]
+    created_at: u64,
+    #[serde(default)]
+    expiry: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct ClnGetRouteRequest {
+    id: String,
+    amount_msat: u64,
+    riskfactor: u32,
+}
+
+#[derive(Deserialize)]
+struct ClnGetRouteResponse {
+    #[serde(default)]
+    route: Vec<ClnRouteHop>,
+}
+
+#[derive(Deserialize)]
+struct ClnRouteHop {
+    #[serde(default, deserialize_with = "de_msat")]
+    amount_msat: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct ClnListPaysRequest {
+    payment_hash: String,
+}
+
+#[derive(Deserialize)]
+struct ClnListPaysResponse {
+    #[serde(default)]
+    pays: Vec<ClnPay>,
+}
+
+#[derive(Deserialize)]
+struct ClnPay {
+    #[serde(default)]
+    payment_hash: String,
+    #[serde(default)]
+    preimage: String,
+    #[serde(default, deserialize_with = "de_msat")]
+    amount_msat: Option<u64>,
+    #[serde(default, deserialize_with = "de_msat")]
+    amount_sent_msat: Option<u64>,
+    #[serde(default)]
+    status: String,
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cln_executor_validation() {
+        let executor = ClnExecutor::new(ClnConfig::new("https://localhost:3010", "rune")).unwrap();
+        assert_eq!(executor.url("pay"), "https://localhost:3010/v1/pay");
+
+        let err = ClnExecutor::new(ClnConfig::new("https://localhost:3010", "")).unwrap_err();
+        assert!(err.to_string().contains("Rune"));
+        assert!(ClnExecutor::new(ClnConfig::new("", "rune")).is_err());
+    }
+
+    #[test]
+    fn test_msat_formats() {
+        let pay: ClnPay = serde_json::from_str(
+            r#"{"payment_hash":"h","amount_msat":1000,"amount_sent_msat":"1010msat","status":"complete"}"#,
+        )
+        .unwrap();
+        assert_eq!(pay.amount_msat, Some(1000));
+        assert_eq!(pay.amount_sent_msat, Some(1010));
+        assert_eq!(
+            payment_status(&pay.status),
+            LightningPaymentStatus::Succeeded
+        );
+    }
+
+    #[test]
+    fn test_error_mapping() {
+        let err = map_error_response(500, r#"{"code":205,"message":"Could not find a route"}"#);
+        assert!(matches!(err, PaykitError::NotFound { .. }));
+
+        let err = map_error_response(500, r#"{"code":210,"message":"All attempts failed"}"#);
+        assert!(matches!(err, PaykitError::Payment { .. }));
+
+        let err = map_error_response(401, "Not authorized: Failed");
+        assert!(matches!(err, PaykitError::Auth(_)));
+    }
+}
@@ -1,6 +1,9 @@
 //! Configuration types for payment executors.
 
+use crate::proxy::Socks5Proxy;
+use crate::{PaykitError, Result};
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 
 /// Bitcoin network selection.
 #[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
@@ -46,7 +49,10 @@ pub struct LndConfig {
     /// Macaroon for authentication (hex-encoded).
     pub macaroon_hex: String,
 
-    /// TLS certificate (PEM format, optional for self-signed).
+    /// The node's TLS certificate (PEM format).
+    ///
+    /// When set, the connection is pinned to it: only this certificate is
+    /// trusted and the system roots are ignored.
     pub tls_cert_pem: Option<String>,
 
     /// Network the node is on.
@@ -60,6 +66,15 @@ pub struct LndConfig {
     /// Maximum fee allowed as percentage of payment amount.
     #[serde(default = "default_max_fee_percent")]
     pub max_fee_percent: f64,
+
+    /// Delay between payment status checks while waiting for an in-flight
+    /// payment, in milliseconds.
+    #[serde(default = "default_poll_interval")]
+    pub poll_interval_ms: u64,
+
+    /// SOCKS5 proxy for API requests (e.g. Tor for a remote node).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<Socks5Proxy>,
 }
 
 fn default_timeout() -> u64 {
@@ -70,6 +85,10 @@ fn default_max_fee_percent() -> f64 {
     1.0 // 1% max fee
 }
 
+fn default_poll_interval() -> u64 {
+    1000
+}
+
 impl LndConfig {
     /// Create a new LND configuration.
     pub fn new(rest_url: impl Into<String>, macaroon_hex: impl Into<String>) -> Self {
@@ -80,7 +99,34 @@ impl LndConfig {
             network: BitcoinNetwork::default(),
             timeout_secs: default_timeout(),
             max_fee_percent: default_max_fee_percent(),
+            poll_interval_ms: default_poll_interval(),
+            proxy: None,
+        }
+    }
+
+    /// Create a configuration from the files LND writes to its data directory.
+    ///
+    /// `macaroon_path` is the binary macaroon (e.g. `admin.macaroon`) and
+    /// `tls_cert_path` the node's `tls.cert`, which the connection is then
+    /// pinned to.
+    pub fn from_files(
+        rest_url: impl Into<String>,
+        macaroon_path: impl AsRef<Path>,
+        tls_cert_path: Option<&Path>,
+    ) -> Result<Self> {
+        let read_error = |path: &Path, e: std::io::Error| {
+            PaykitError::Storage(format!("Failed to read {}: {}", path.display(), e))
+        };
+
+        let macaroon_path = macaroon_path.as_ref();
+        let macaroon = std::fs::read(macaroon_path).map_err(|e| read_error(macaroon_path, e))?;
+        let mut config = Self::new(rest_url, hex::encode(macaroon));
+
+        if let Some(path) = tls_cert_path {
+            let pem = std::fs::read_to_string(path).map_err(|e| read_error(path, e))?;
+            config = config.with_tls_cert(pem);
         }
+        Ok(config)
     }
 
     /// Set the TLS certificate.
@@ -106,6 +152,267 @@ impl LndConfig {
         self.max_fee_percent = percent;
         self
     }
+
+    /// Set the delay between payment status checks.
+    pub fn with_poll_interval(mut self, millis: u64) -> Self {
+        self.poll_interval_ms = millis;
+        self
+    }
+
+    /// Route API requests through a SOCKS5 proxy.
+    pub fn with_proxy(mut self, proxy: Option<Socks5Proxy>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+}
+
+/// Configuration for Core Lightning (CLN) REST API executor.
+///
+/// Connects through the `clnrest` plugin, which authorizes each call with a
+/// rune the same way `commando` does.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ClnConfig {
+    /// REST API endpoint URL (e.g., "https://localhost:3010").
+    pub rest_url: String,
+
+    /// Rune authorizing the calls (create one with `lightning-cli createrune`).
+    pub rune: String,
+
+    /// CA certificate of the `clnrest` server (PEM format, `ca.pem`).
+    ///
+    /// When set, the connection is pinned to it: only this certificate is
+    /// trusted and the system roots are ignored.
+    pub tls_cert_pem: Option<String>,
+
+    /// Network the node is on.
+    #[serde(default)]
+    pub network: BitcoinNetwork,
+
+    /// Request timeout in seconds.
+    #[serde(default = "default_timeout")]
+    pub timeout_secs: u64,
+
+    /// Maximum fee allowed as percentage of payment amount.
+    #[serde(default = "default_max_fee_percent")]
+    pub max_fee_percent: f64,
+
+    /// Delay between payment status checks while waiting for an in-flight
+    /// payment, in milliseconds.
+    #[serde(default = "default_poll_interval")]
+    pub poll_interval_ms: u64,
+
+    /// SOCKS5 proxy for API requests (e.g. Tor for a remote node).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<Socks5Proxy>,
+}
+
+impl ClnConfig {
+    /// Create a new CLN configuration.
+    pub fn new(rest_url: impl Into<String>, rune: impl Into<String>) -> Self {
+        Self {
+            rest_url: rest_url.into(),
+            rune: rune.into(),
+            tls_cert_pem: None,
+            network: BitcoinNetwork::default(),
+            timeout_secs: default_timeout(),
+            max_fee_percent: default_max_fee_percent(),
+            poll_interval_ms: default_poll_interval(),
+            proxy: None,
+        }
+    }
+
+    /// Set the TLS CA certificate.
+    pub fn with_tls_cert(mut self, cert_pem: impl Into<String>) -> Self {
+        self.tls_cert_pem = Some(cert_pem.into());
+        self
+    }
+
+    /// Set the network.
+    pub fn with_network(mut self, network: BitcoinNetwork) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Set the request timeout.
+    pub fn with_timeout(mut self, secs: u64) -> Self {
+        self.timeout_secs = secs;
+        self
+    }
+
+    /// Set maximum fee percentage.
+    pub fn with_max_fee_percent(mut self, percent: f64) -> Self {
+        self.max_fee_percent = percent;
+        self
+    }
+
+    /// Set the delay between payment status checks.
+    pub fn with_poll_interval(mut self, millis: u64) -> Self {
+        self.poll_interval_ms = millis;
+        self
+    }
+
+    /// Route API requests through a SOCKS5 proxy.
+    pub fn with_proxy(mut self, proxy: Option<Socks5Proxy>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+}
+
+/// Configuration for a BTCPay Server store used as a receiving backend.
+///
+/// Talks to the Greenfield API with an API key; the key needs
+/// `btcpay.store.cancreateinvoice`, `btcpay.store.canviewinvoices` and, to
+/// publish on-chain addresses, `btcpay.store.canmodifystoresettings`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BtcPayConfig {
+    /// Server URL (e.g., "https://btcpay.example.com").
+    pub server_url: String,
+
+    /// Greenfield API key.
+    pub api_key: String,
+
+    /// Store receiving the payments.
+    pub store_id: String,
+
+    /// Secret of the store webhook, used to verify `BTCPay-Sig`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_secret: Option<String>,
+
+    /// Lightning address or LNURL of the store, published as its Lightning
+    /// endpoint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lightning_address: Option<String>,
+
+    /// Minutes before a created invoice expires (the store default if unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub invoice_expiration_minutes: Option<u32>,
+
+    /// Request timeout in seconds.
+    #[serde(default = "default_timeout")]
+    pub timeout_secs: u64,
+
+    /// SOCKS5 proxy for API requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<Socks5Proxy>,
+}
+
+impl BtcPayConfig {
+    /// Create a new BTCPay configuration.
+    pub fn new(
+        server_url: impl Into<String>,
+        api_key: impl Into<String>,
+        store_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            server_url: server_url.into(),
+            api_key: api_key.into(),
+            store_id: store_id.into(),
+            webhook_secret: None,
+            lightning_address: None,
+            invoice_expiration_minutes: None,
+            timeout_secs: default_timeout(),
+            proxy: None,
+        }
+    }
+
+    /// Set the webhook secret.
+    pub fn with_webhook_secret(mut self, secret: impl Into<String>) -> Self {
+        self.webhook_secret = Some(secret.into());
+        self
+    }
+
+    /// Set the store's Lightning address or LNURL.
+    pub fn with_lightning_address(mut self, address: impl Into<String>) -> Self {
+        self.lightning_address = Some(address.into());
+        self
+    }
+
+    /// Set the invoice expiration.
+    pub fn with_invoice_expiration(mut self, minutes: u32) -> Self {
+        self.invoice_expiration_minutes = Some(minutes);
+        self
+    }
+
+    /// Set the request timeout.
+    pub fn with_timeout(mut self, secs: u64) -> Self {
+        self.timeout_secs = secs;
+        self
+    }
+
+    /// Route API requests through a SOCKS5 proxy.
+    pub fn with_proxy(mut self, proxy: Option<Socks5Proxy>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+}
+
+/// Configuration for a Fedimint client daemon (`fedimint-clientd`).
+///
+/// The daemon holds the e-cash and does the federation consensus work; the
+/// executor only calls its v2 REST API, authenticated with the daemon
+/// password.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FedimintConfig {
+    /// Daemon URL (e.g., "http://127.0.0.1:3333").
+    pub clientd_url: String,
+
+    /// Daemon password, sent as a bearer token.
+    pub password: String,
+
+    /// Federation used for spends and gateway payments when the caller does
+    /// not name one (the daemon's active federation if unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub federation_id: Option<String>,
+
+    /// Lightning gateway to pay through (the daemon picks one if unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gateway_id: Option<String>,
+
+    /// Request timeout in seconds.
+    #[serde(default = "default_timeout")]
+    pub timeout_secs: u64,
+
+    /// SOCKS5 proxy for API requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<Socks5Proxy>,
+}
+
+impl FedimintConfig {
+    /// Create a new Fedimint configuration.
+    pub fn new(clientd_url: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            clientd_url: clientd_url.into(),
+            password: password.into(),
+            federation_id: None,
+            gateway_id: None,
+            timeout_secs: default_timeout(),
+            proxy: None,
+        }
+    }
+
+    /// Set the default federation.
+    pub fn with_federation(mut self, federation_id: impl Into<String>) -> Self {
+        self.federation_id = Some(federation_id.into());
+        self
+    }
+
+    /// Set the Lightning gateway.
+    pub fn with_gateway(mut self, gateway_id: impl Into<String>) -> Self {
+        self.gateway_id = Some(gateway_id.into());
+        self
+    }
+
+    /// Set the request timeout.
+    pub fn with_timeout(mut self, secs: u64) -> Self {
+        self.timeout_secs = secs;
+        self
+    }
+
+    /// Route API requests through a SOCKS5 proxy.
+    pub fn with_proxy(mut self, proxy: Option<Socks5Proxy>) -> Self {
+        self.proxy = proxy;
+        self
+    }
 }
 
 /// Configuration for Electrum server executor.
@@ -166,6 +473,10 @@ pub struct EsploraConfig {
     /// Rate limiting: minimum delay between requests in milliseconds.
     #[serde(default = "default_rate_limit")]
     pub rate_limit_ms: u64,
+
+    /// SOCKS5 proxy for API requests (e.g. Tor).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<Socks5Proxy>,
 }
 
 fn default_rate_limit() -> u64 {
@@ -180,6 +491,7 @@ impl EsploraConfig {
             network: BitcoinNetwork::default(),
             timeout_secs: default_timeout(),
             rate_limit_ms: default_rate_limit(),
+            proxy: None,
         }
     }
 
@@ -214,6 +526,12 @@ impl EsploraConfig {
         self.timeout_secs = secs;
         self
     }
+
+    /// Route API requests through a SOCKS5 proxy.
+    pub fn with_proxy(mut self, proxy: Option<Socks5Proxy>) -> Self {
+        self.proxy = proxy;
+        self
+    }
 }
 
 /// Unified executor configuration.
@@ -222,6 +540,8 @@ impl EsploraConfig {
 pub enum ExecutorConfig {
     /// LND REST API for Lightning.
     Lnd(LndConfig),
+    /// Core Lightning REST API for Lightning.
+    Cln(ClnConfig),
     /// Electrum for on-chain.
     Electrum(ElectrumConfig),
     /// Esplora for on-chain.
@@ -243,6 +563,41 @@ mod tests {
         assert_eq!(config.timeout_secs, 60);
     }
 
+    #[test]
+    fn test_lnd_config_from_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let macaroon = dir.path().join("admin.macaroon");
+        let cert = dir.path().join("tls.cert");
+        std::fs::write(&macaroon, [0x02, 0x01, 0x03]).unwrap();
+        std::fs::write(&cert, "-----BEGIN CERTIFICATE-----").unwrap();
+
+        let config =
+            LndConfig::from_files("https://localhost:8080", &macaroon, Some(cert.as_path()))
+                .unwrap();
+        assert_eq!(config.macaroon_hex, "020103");
+        assert!(config.tls_cert_pem.unwrap().starts_with("-----BEGIN"));
+
+        let missing = dir.path().join("missing.macaroon");
+        assert!(LndConfig::from_files("https://localhost:8080", missing, None).is_err());
+    }
+
+    #[test]
+    fn test_cln_config_in_executor_config() {
+        let config = ExecutorConfig::Cln(
+            ClnConfig::new("https://localhost:3010", "rune123")
+                .with_network(BitcoinNetwork::Regtest),
+        );
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["type"], "cln");
+        assert_eq!(json["rune"], "rune123");
+
+        let ExecutorConfig::Cln(parsed) = serde_json::from_value(json).unwrap() else {
+            panic!("expected a CLN config");
+        };
+        assert_eq!(parsed.network, BitcoinNetwork::Regtest);
+        assert_eq!(parsed.timeout_secs, 30);
+    }
+
     #[test]
     fn test_esplora_presets() {
         let mainnet = EsploraConfig::blockstream_mainnet();
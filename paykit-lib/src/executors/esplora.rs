@@ -36,7 +36,8 @@
 //!
 //! Note: This executor can query and verify transactions, but cannot
 //! create transactions. For full send capability, pair with a wallet
-//! that implements the BitcoinExecutor trait.
+//! that implements the BitcoinExecutor trait, or use
+//! `EsploraBitcoinExecutor` (`psbt` feature) with an external PSBT signer.
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -71,8 +72,12 @@ impl EsploraExecutor {
     /// Create a new Esplora executor with the given configuration.
     #[cfg(feature = "http-executor")]
     pub fn new(config: EsploraConfig) -> Result<Self> {
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(config.timeout_secs))
+        let mut builder =
+            reqwest::Client::builder().timeout(Duration::from_secs(config.timeout_secs));
+        if let Some(proxy) = &config.proxy {
+            builder = builder.proxy(proxy.to_reqwest()?);
+        }
+        let client = builder
             .build()
             .map_err(|e| PaykitError::Internal(format!("Failed to build HTTP client: {}", e)))?;
 
@@ -139,6 +144,39 @@ impl EsploraExecutor {
         ))
     }
 
+    /// Make a GET request to an endpoint that answers in plain text.
+    #[cfg(feature = "http-executor")]
+    async fn get_text(&self, path: &str) -> Result<String> {
+        let url = self.url(path);
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| self.map_reqwest_error(e))?;
+
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| PaykitError::Serialization(format!("Failed to read response: {}", e)))?;
+
+        if !status.is_success() {
+            return Err(self.map_status_error(status.as_u16(), &text));
+        }
+
+        Ok(text.trim().to_string())
+    }
+
+    /// Make a plain-text GET request (stub when feature disabled).
+    #[cfg(not(feature = "http-executor"))]
+    async fn get_text(&self, _path: &str) -> Result<String> {
+        Err(PaykitError::Unimplemented(
+            "Esplora HTTP client not compiled - enable the 'http-executor' feature",
+        ))
+    }
+
     /// Make a POST request with text body.
     #[cfg(feature = "http-executor")]
     async fn post_text(&self, path: &str, body: &str) -> Result<String> {
@@ -322,6 +360,16 @@ impl EsploraExecutor {
     pub async fn get_tx_status(&self, txid: &str) -> Result<TxStatus> {
         self.get(&format!("tx/{}/status", txid)).await
     }
+
+    /// Get the hash of the block at `height` on the best chain.
+    pub async fn get_block_hash_at(&self, height: u64) -> Result<String> {
+        self.get_text(&format!("block-height/{}", height)).await
+    }
+
+    /// Get a block header summary by hash.
+    pub async fn get_block(&self, hash: &str) -> Result<EsploraBlock> {
+        self.get(&format!("block/{}", hash)).await
+    }
 }
 
 #[async_trait]
@@ -537,6 +585,19 @@ pub struct TxStatus {
     pub block_time: Option<u64>,
 }
 
+/// Block header summary from Esplora API.
+#[derive(Clone, Debug, Deserialize)]
+pub struct EsploraBlock {
+    /// Block hash.
+    pub id: String,
+    /// Block height.
+    pub height: u64,
+    /// Header timestamp (unix epoch).
+    pub timestamp: u64,
+    /// Merkle root, hex in display (reversed) byte order.
+    pub merkle_root: String,
+}
+
 /// Transaction from Esplora API.
 #[derive(Clone, Debug, Deserialize)]
 pub struct EsploraTx {
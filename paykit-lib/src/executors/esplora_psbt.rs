@@ -0,0 +1,679 @@
+//! Esplora-backed on-chain executor with PSBT handoff.
+//!
+//! [`EsploraBitcoinExecutor`] sends on-chain payments without holding keys.
+//! It selects coins from a set of watched funding addresses, builds an
+//! unsigned PSBT and hands it to a [`PsbtSigner`] (a hardware wallet or the
+//! host app's own wallet). It then checks that the signed PSBT still spends
+//! what was proposed, broadcasts it through Esplora and tracks
+//! confirmations:
+//!
+//! ```text
+//! build_psbt ─► PsbtSigner::sign_psbt ─► finalize_and_broadcast ─► wait_for_confirmations
+//! ```
+//!
+//! `send_to_address` runs the whole flow. Apps that sign out of band, such
+//! as a QR round trip to an air-gapped device, call
+//! [`EsploraBitcoinExecutor::build_psbt`] and
+//! [`EsploraBitcoinExecutor::finalize_and_broadcast`] separately.
+//!
+//! The signer must return a finalized PSBT (final script witness or script
+//! sig on every input), as hardware wallets and wallet libraries do.
+//! Funding addresses must be P2WPKH, P2TR, P2SH-wrapped P2WPKH or P2PKH;
+//! the address type sets the input size used for fee estimation.
+//!
+//! # Feature Flags
+//!
+//! Requires the `psbt` feature, which also enables `http-executor`.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use paykit_lib::executors::{EsploraBitcoinExecutor, EsploraExecutor, PsbtSigner};
+//! use paykit_lib::methods::BitcoinExecutor;
+//!
+//! let executor = EsploraBitcoinExecutor::new(
+//!     EsploraExecutor::blockstream_testnet()?,
+//!     &["tb1q...".to_string()],
+//!     "tb1q...change",
+//!     Arc::new(my_hardware_wallet),
+//! )?;
+//!
+//! let result = executor.send_to_address("tb1q...payee", 50_000, None).await?;
+//! let confirmed = executor
+//!     .wait_for_confirmations(&result.txid, 1, Duration::from_secs(30), Duration::from_secs(3600))
+//!     .await?;
+//! ```
+
+use async_trait::async_trait;
+use base64::Engine;
+use bitcoin::absolute::LockTime;
+use bitcoin::address::AddressType;
+use bitcoin::consensus::encode;
+use bitcoin::transaction::Version;
+use bitcoin::{
+    Address, Amount, Network, OutPoint, Psbt, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid,
+    Witness,
+};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::config::BitcoinNetwork;
+use super::esplora::EsploraExecutor;
+use crate::methods::{BitcoinExecutor, BitcoinTxResult, SpendHints};
+use crate::{PaykitError, Result};
+
+/// Outputs below this are uneconomical to spend; smaller change goes to fees.
+const DUST_LIMIT_SATS: u64 = 546;
+
+/// Version, lock time, input and output counts and the segwit marker.
+const TX_OVERHEAD_VBYTES: u64 = 11;
+
+/// Default confirmation target for fee estimation, in blocks.
+const DEFAULT_CONFIRMATION_TARGET: u32 = 6;
+
+/// Signs PSBTs for [`EsploraBitcoinExecutor`].
+///
+/// PSBTs are exchanged as base64 (BIP 174), the format hardware wallet
+/// tooling and mobile hosts already speak.
+#[async_trait]
+pub trait PsbtSigner: Send + Sync {
+    /// Sign and finalize `psbt_base64`, returning the finalized PSBT.
+    ///
+    /// Return `PaymentRejected` if the user declines to sign.
+    async fn sign_psbt(&self, psbt_base64: &str) -> Result<String>;
+}
+
+/// An unsigned transaction waiting for the signer.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UnsignedPsbt {
+    /// The PSBT, base64-encoded.
+    pub psbt_base64: String,
+    /// Index of the payment output.
+    pub recipient_vout: u32,
+    /// Amount paid to the recipient in satoshis.
+    pub amount_sats: u64,
+    /// Fee in satoshis.
+    pub fee_sats: u64,
+    /// Fee rate in sat/vB.
+    pub fee_rate: f64,
+    /// Change returned to the change address in satoshis (0 for none).
+    pub change_sats: u64,
+}
+
+/// On-chain executor that builds PSBTs from Esplora data and broadcasts
+/// them once an external signer has signed them.
+pub struct EsploraBitcoinExecutor {
+    esplora: EsploraExecutor,
+    network: Network,
+    funding: Vec<Address>,
+    change: Address,
+    signer: Arc<dyn PsbtSigner>,
+    confirmation_target: u32,
+    allow_unconfirmed: bool,
+}
+
+impl EsploraBitcoinExecutor {
+    /// Create an executor spending from `funding_addresses`.
+    ///
+    /// The network is taken from the Esplora configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidData` if no funding address is given, if an address
+    /// does not parse or belongs to another network, or if a funding
+    /// address has an unsupported type.
+    pub fn new(
+        esplora: EsploraExecutor,
+        funding_addresses: &[String],
+        change_address: &str,
+        signer: Arc<dyn PsbtSigner>,
+    ) -> Result<Self> {
+        let network = to_network(&esplora.config().network);
+        if funding_addresses.is_empty() {
+            return Err(PaykitError::invalid_data(
+                "funding_addresses",
+                "At least one funding address is required",
+            ));
+        }
+        let funding = funding_addresses
+            .iter()
+            .map(|address| parse_address(address, network))
+            .collect::<Result<Vec<_>>>()?;
+        for address in &funding {
+            input_vbytes(address)?;
+        }
+        let change = parse_address(change_address, network)?;
+
+        Ok(Self {
+            esplora,
+            network,
+            funding,
+            change,
+            signer,
+            confirmation_target: DEFAULT_CONFIRMATION_TARGET,
+            allow_unconfirmed: false,
+        })
+    }
+
+    /// Set the confirmation target used when no fee rate is given.
+    pub fn with_confirmation_target(mut self, blocks: u32) -> Self {
+        self.confirmation_target = blocks;
+        self
+    }
+
+    /// Allow spending unconfirmed outputs (off by default).
+    pub fn with_unconfirmed_inputs(mut self, allow: bool) -> Self {
+        self.allow_unconfirmed = allow;
+        self
+    }
+
+    /// The underlying Esplora client.
+    pub fn esplora(&self) -> &EsploraExecutor {
+        &self.esplora
+    }
+
+    /// Build an unsigned PSBT paying `amount_sats` to `address`.
+    ///
+    /// Uses `fee_rate` (sat/vB) if given, otherwise the Esplora estimate for
+    /// the confirmation target. Inputs signal replace-by-fee.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidData` for a bad address or a dust amount and
+    /// `InsufficientFunds` if the funding addresses cannot cover the payment.
+    pub async fn build_psbt(
+        &self,
+        address: &str,
+        amount_sats: u64,
+        fee_rate: Option<f64>,
+    ) -> Result<UnsignedPsbt> {
+        let recipient = parse_address(address, self.network)?;
+        if amount_sats < DUST_LIMIT_SATS {
+            return Err(PaykitError::invalid_data(
+                "amount_sats",
+                format!("Amount is below the dust limit of {} sats", DUST_LIMIT_SATS),
+            ));
+        }
+        let fee_rate = self.fee_rate(fee_rate, self.confirmation_target).await?;
+
+        let recipient_script = recipient.script_pubkey();
+        let change_script = self.change.script_pubkey();
+        let coins = self.coins().await?;
+        let selection = select_coins(
+            &coins,
+            amount_sats,
+            fee_rate,
+            TX_OVERHEAD_VBYTES + output_vbytes(&recipient_script),
+            output_vbytes(&change_script),
+        )?;
+
+        let mut output = vec![TxOut {
+            value: Amount::from_sat(amount_sats),
+            script_pubkey: recipient_script,
+        }];
+        if selection.change_sats > 0 {
+            output.push(TxOut {
+                value: Amount::from_sat(selection.change_sats),
+                script_pubkey: change_script,
+            });
+        }
+        let input = selection
+            .coins
+            .iter()
+            .map(|&i| TxIn {
+                previous_output: coins[i].outpoint,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            })
+            .collect();
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input,
+            output,
+        };
+
+        let mut psbt = Psbt::from_unsigned_tx(tx)
+            .map_err(|e| PaykitError::Internal(format!("Failed to build PSBT: {}", e)))?;
+        for (psbt_input, &i) in psbt.inputs.iter_mut().zip(&selection.coins) {
+            let coin = &coins[i];
+            if coin.segwit {
+                psbt_input.witness_utxo = Some(TxOut {
+                    value: Amount::from_sat(coin.value),
+                    script_pubkey: coin.script_pubkey.clone(),
+                });
+            } else {
+                // Legacy inputs commit to the whole previous transaction
+                psbt_input.non_witness_utxo = Some(self.previous_tx(&coin.outpoint.txid).await?);
+            }
+        }
+
+        Ok(UnsignedPsbt {
+            psbt_base64: encode_psbt(&psbt),
+            recipient_vout: 0,
+            amount_sats,
+            fee_sats: selection.fee_sats,
+            fee_rate,
+            change_sats: selection.change_sats,
+        })
+    }
+
+    /// Check a signed PSBT against the proposal and broadcast it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ValidationFailed` if the signer changed the transaction or
+    /// did not finalize every input, and the Esplora error if the broadcast
+    /// is rejected.
+    pub async fn finalize_and_broadcast(
+        &self,
+        unsigned: &UnsignedPsbt,
+        signed_base64: &str,
+    ) -> Result<BitcoinTxResult> {
+        let proposed = decode_psbt(&unsigned.psbt_base64)?;
+        let signed = decode_psbt(signed_base64)?;
+        let tx = extract_signed_tx(&proposed, signed)?;
+
+        let raw_tx = encode::serialize_hex(&tx);
+        let txid = self.esplora.broadcast_tx(&raw_tx).await?;
+
+        Ok(BitcoinTxResult {
+            txid,
+            raw_tx: Some(raw_tx),
+            vout: unsigned.recipient_vout,
+            fee_sats: unsigned.fee_sats,
+            fee_rate: unsigned.fee_rate,
+            block_height: None,
+            confirmations: 0,
+        })
+    }
+
+    /// Poll Esplora until `txid` has at least `confirmations` confirmations.
+    ///
+    /// If `timeout` elapses first, the transaction's current state is
+    /// returned so the caller can keep tracking it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NotFound` if Esplora never sees the transaction.
+    pub async fn wait_for_confirmations(
+        &self,
+        txid: &str,
+        confirmations: u64,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<BitcoinTxResult> {
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        loop {
+            let tx = self.esplora.get_transaction(txid).await?;
+            if let Some(tx) = &tx {
+                if tx.confirmations >= confirmations {
+                    return Ok(tx.clone());
+                }
+            }
+
+            if tokio::time::Instant::now() + poll_interval > deadline {
+                return tx.ok_or_else(|| PaykitError::not_found("Bitcoin transaction", txid));
+            }
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+
+    /// Spendable outputs of the funding addresses.
+    async fn coins(&self) -> Result<Vec<Coin>> {
+        let mut coins = Vec::new();
+        for address in &self.funding {
+            let input_vbytes = input_vbytes(address)?;
+            for utxo in self.esplora.get_address_utxos(&address.to_string()).await? {
+                if !utxo.status.confirmed && !self.allow_unconfirmed {
+                    continue;
+                }
+                let txid = Txid::from_str(&utxo.txid).map_err(|e| {
+                    PaykitError::Serialization(format!("Invalid txid from Esplora: {}", e))
+                })?;
+                coins.push(Coin {
+                    outpoint: OutPoint::new(txid, utxo.vout),
+                    value: utxo.value,
+                    script_pubkey: address.script_pubkey(),
+                    input_vbytes,
+                    segwit: is_segwit(address),
+                });
+            }
+        }
+        Ok(coins)
+    }
+
+    /// The given fee rate, or the Esplora estimate for `target_blocks`.
+    async fn fee_rate(&self, fee_rate: Option<f64>, target_blocks: u32) -> Result<f64> {
+        let rate = match fee_rate {
+            Some(rate) => rate,
+            None => self
+                .esplora
+                .get_fee_estimates()
+                .await?
+                .get_rate_for_blocks(target_blocks),
+        };
+        // Nodes do not relay below 1 sat/vB
+        Ok(rate.max(1.0))
+    }
+
+    /// Fetch a previous transaction for a legacy input.
+    async fn previous_tx(&self, txid: &Txid) -> Result<Transaction> {
+        let tx_hex = self.esplora.get_tx_hex(&txid.to_string()).await?;
+        let bytes = hex::decode(tx_hex.trim())
+            .map_err(|e| PaykitError::Serialization(format!("Invalid transaction hex: {}", e)))?;
+        encode::deserialize(&bytes)
+            .map_err(|e| PaykitError::Serialization(format!("Invalid transaction: {}", e)))
+    }
+}
+
+#[async_trait]
+impl BitcoinExecutor for EsploraBitcoinExecutor {
+    async fn send_to_address(
+        &self,
+        address: &str,
+        amount_sats: u64,
+        fee_rate: Option<f64>,
+    ) -> Result<BitcoinTxResult> {
+        let unsigned = self.build_psbt(address, amount_sats, fee_rate).await?;
+        let signed = self.signer.sign_psbt(&unsigned.psbt_base64).await?;
+        self.finalize_and_broadcast(&unsigned, &signed).await
+    }
+
+    async fn estimate_fee(
+        &self,
+        address: &str,
+        amount_sats: u64,
+        target_blocks: u32,
+    ) -> Result<u64> {
+        let recipient = parse_address(address, self.network)?;
+        let fee_rate = self.fee_rate(None, target_blocks).await?;
+        let coins = self.coins().await?;
+        let selection = select_coins(
+            &coins,
+            amount_sats,
+            fee_rate,
+            TX_OVERHEAD_VBYTES + output_vbytes(&recipient.script_pubkey()),
+            output_vbytes(&self.change.script_pubkey()),
+        )?;
+        Ok(selection.fee_sats)
+    }
+
+    async fn get_transaction(&self, txid: &str) -> Result<Option<BitcoinTxResult>> {
+        self.esplora.get_transaction(txid).await
+    }
+
+    async fn verify_transaction(
+        &self,
+        txid: &str,
+        address: &str,
+        amount_sats: u64,
+    ) -> Result<bool> {
+        self.esplora
+            .verify_transaction(txid, address, amount_sats)
+            .await
+    }
+
+    async fn spendable_balance(&self) -> Result<u64> {
+        Ok(self.coins().await?.iter().map(|coin| coin.value).sum())
+    }
+
+    async fn spend_hints(&self, address: &str, amount_sats: u64) -> Result<SpendHints> {
+        let recipient = parse_address(address, self.network)?;
+        let fee_rate = self.fee_rate(None, self.confirmation_target).await?;
+        let coins = self.coins().await?;
+        let selection = select_coins(
+            &coins,
+            amount_sats,
+            fee_rate,
+            TX_OVERHEAD_VBYTES + output_vbytes(&recipient.script_pubkey()),
+            output_vbytes(&self.change.script_pubkey()),
+        )?;
+
+        let mut scripts: Vec<&ScriptBuf> = selection
+            .coins
+            .iter()
+            .map(|&i| &coins[i].script_pubkey)
+            .collect();
+        scripts.sort();
+        scripts.dedup();
+        Ok(SpendHints {
+            input_count: selection.coins.len() as u32,
+            input_addresses: scripts.len() as u32,
+            change_sats: (selection.change_sats > 0).then_some(selection.change_sats),
+            change_type_matches: recipient.address_type() == self.change.address_type(),
+        })
+    }
+}
+
+/// A spendable output of a funding address.
+struct Coin {
+    outpoint: OutPoint,
+    value: u64,
+    script_pubkey: ScriptBuf,
+    input_vbytes: u64,
+    segwit: bool,
+}
+
+/// Coins chosen for a payment.
+#[derive(Debug)]
+struct Selection {
+    /// Indexes into the candidate coins.
+    coins: Vec<usize>,
+    fee_sats: u64,
+    change_sats: u64,
+}
+
+/// Largest-first coin selection.
+///
+/// `base_vbytes` covers the transaction overhead and the payment output,
+/// `change_vbytes` the change output. Change below the dust limit is left
+/// to the fee instead of creating an output.
+fn select_coins(
+    coins: &[Coin],
+    amount_sats: u64,
+    fee_rate: f64,
+    base_vbytes: u64,
+    change_vbytes: u64,
+) -> Result<Selection> {
+    let fee_for = |vbytes: u64| (vbytes as f64 * fee_rate).ceil() as u64;
+
+    let mut order: Vec<usize> = (0..coins.len()).collect();
+    order.sort_by(|&a, &b| coins[b].value.cmp(&coins[a].value));
+
+    let mut selected = Vec::new();
+    let mut total = 0u64;
+    let mut vbytes = base_vbytes;
+    for i in order {
+        selected.push(i);
+        total += coins[i].value;
+        vbytes += coins[i].input_vbytes;
+
+        if total < amount_sats + fee_for(vbytes) {
+            continue;
+        }
+        let fee_with_change = fee_for(vbytes + change_vbytes);
+        let change = total.saturating_sub(amount_sats + fee_with_change);
+        return Ok(if change >= DUST_LIMIT_SATS {
+            Selection {
+                coins: selected,
+                fee_sats: fee_with_change,
+                change_sats: change,
+            }
+        } else {
+            Selection {
+                coins: selected,
+                fee_sats: total - amount_sats,
+                change_sats: 0,
+            }
+        });
+    }
+
+    Err(PaykitError::InsufficientFunds {
+        required: (amount_sats + fee_for(vbytes)).to_string(),
+        available: total.to_string(),
+        currency: "SAT".to_string(),
+    })
+}
+
+/// Check the signer's PSBT against the proposal and extract the final
+/// transaction.
+fn extract_signed_tx(proposed: &Psbt, signed: Psbt) -> Result<Transaction> {
+    if signed.unsigned_tx != proposed.unsigned_tx {
+        return Err(PaykitError::ValidationFailed(
+            "Signer returned a PSBT for a different transaction".to_string(),
+        ));
+    }
+    let finalized = signed
+        .inputs
+        .iter()
+        .all(|input| input.final_script_witness.is_some() || input.final_script_sig.is_some());
+    if !finalized {
+        return Err(PaykitError::ValidationFailed(
+            "Signer did not finalize every input".to_string(),
+        ));
+    }
+    signed
+        .extract_tx()
+        .map_err(|e| PaykitError::ValidationFailed(format!("Invalid signed PSBT: {}", e)))
+}
+
+/// Map the executor network to the `bitcoin` crate's.
+fn to_network(network: &BitcoinNetwork) -> Network {
+    match network {
+        BitcoinNetwork::Mainnet => Network::Bitcoin,
+        BitcoinNetwork::Testnet => Network::Testnet,
+        BitcoinNetwork::Signet => Network::Signet,
+        BitcoinNetwork::Regtest => Network::Regtest,
+    }
+}
+
+/// Parse an address and check it belongs to `network`.
+fn parse_address(address: &str, network: Network) -> Result<Address> {
+    Address::from_str(address)
+        .and_then(|address| address.require_network(network))
+        .map_err(|e| PaykitError::invalid_data("address", format!("{}: {}", address, e)))
+}
+
+/// Size of an input spending from `address`, in vbytes.
+fn input_vbytes(address: &Address) -> Result<u64> {
+    match address.address_type() {
+        Some(AddressType::P2wpkh) => Ok(68),
+        Some(AddressType::P2tr) => Ok(58),
+        // Assumed to wrap P2WPKH
+        Some(AddressType::P2sh) => Ok(91),
+        Some(AddressType::P2pkh) => Ok(148),
+        _ => Err(PaykitError::invalid_data(
+            "funding_addresses",
+            format!("Unsupported funding address type: {}", address),
+        )),
+    }
+}
+
+/// Whether spends from `address` carry a witness.
+fn is_segwit(address: &Address) -> bool {
+    !matches!(address.address_type(), Some(AddressType::P2pkh))
+}
+
+/// Size of an output paying to `script`, in vbytes.
+fn output_vbytes(script: &ScriptBuf) -> u64 {
+    // Value, script length prefix and the script itself
+    8 + 1 + script.len() as u64
+}
+
+fn encode_psbt(psbt: &Psbt) -> String {
+    base64::engine::general_purpose::STANDARD.encode(psbt.serialize())
+}
+
+fn decode_psbt(psbt_base64: &str) -> Result<Psbt> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(psbt_base64.trim())
+        .map_err(|e| PaykitError::invalid_data("psbt", format!("Invalid base64: {}", e)))?;
+    Psbt::deserialize(&bytes)
+        .map_err(|e| PaykitError::invalid_data("psbt", format!("Invalid PSBT: {}", e)))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coin(value: u64) -> Coin {
+        Coin {
+            outpoint: OutPoint::null(),
+            value,
+            script_pubkey: ScriptBuf::new(),
+            input_vbytes: 68,
+            segwit: true,
+        }
+    }
+
+    #[test]
+    fn test_select_coins_with_change() {
+        let coins = [coin(10_000), coin(100_000), coin(50_000)];
+        let selection = select_coins(&coins, 60_000, 2.0, 43, 31).unwrap();
+
+        // Largest coin alone covers the payment
+        assert_eq!(selection.coins, vec![1]);
+        assert_eq!(
+            selection.fee_sats,
+            ((43 + 68 + 31) as f64 * 2.0).ceil() as u64
+        );
+        assert_eq!(selection.change_sats, 100_000 - 60_000 - selection.fee_sats);
+    }
+
+    #[test]
+    fn test_select_coins_dust_change_goes_to_fee() {
+        let coins = [coin(60_500)];
+        let selection = select_coins(&coins, 60_000, 1.0, 43, 31).unwrap();
+        assert_eq!(selection.change_sats, 0);
+        assert_eq!(selection.fee_sats, 500);
+    }
+
+    #[test]
+    fn test_select_coins_insufficient_funds() {
+        let coins = [coin(10_000), coin(20_000)];
+        let err = select_coins(&coins, 30_000, 1.0, 43, 31).unwrap_err();
+        assert!(matches!(err, PaykitError::InsufficientFunds { .. }));
+    }
+
+    #[test]
+    fn test_address_network_and_type() {
+        let address = "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq";
+        assert!(parse_address(address, Network::Bitcoin).is_ok());
+        assert!(parse_address(address, Network::Testnet).is_err());
+
+        let parsed = parse_address(address, Network::Bitcoin).unwrap();
+        assert_eq!(input_vbytes(&parsed).unwrap(), 68);
+        assert!(is_segwit(&parsed));
+    }
+
+    #[test]
+    fn test_signed_psbt_must_match_proposal() {
+        let tx = |value: u64| Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn::default()],
+            output: vec![TxOut {
+                value: Amount::from_sat(value),
+                script_pubkey: ScriptBuf::new(),
+            }],
+        };
+        let proposed = Psbt::from_unsigned_tx(tx(1_000)).unwrap();
+
+        let tampered = Psbt::from_unsigned_tx(tx(2_000)).unwrap();
+        assert!(extract_signed_tx(&proposed, tampered).is_err());
+
+        // Same transaction, but nothing signed yet
+        let unsigned = decode_psbt(&encode_psbt(&proposed)).unwrap();
+        let err = extract_signed_tx(&proposed, unsigned).unwrap_err();
+        assert!(err.to_string().contains("finalize"));
+    }
+}
@@ -0,0 +1,308 @@
+//! Fedimint e-cash executor.
+//!
+//! [`FedimintExecutor`] implements [`EcashExecutor`] over the v2 REST API of
+//! `fedimint-clientd`, which holds the wallet's notes for one or more
+//! federations:
+//!
+//! | Operation | Endpoint |
+//! |-----------|----------|
+//! | Joined federations | `GET /v2/admin/federation-ids` |
+//! | Balances | `GET /v2/admin/info` |
+//! | Spend notes | `POST /v2/mint/spend` |
+//! | Redeem notes | `POST /v2/mint/reissue` |
+//! | Gateway payment | `POST /v2/ln/pay` |
+//!
+//! Requires the `fedimint` feature.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use paykit_lib::executors::{FedimintConfig, FedimintExecutor};
+//! use paykit_lib::methods::EcashPlugin;
+//! use std::sync::Arc;
+//!
+//! let config = FedimintConfig::new("http://127.0.0.1:3333", "password")
+//!     .with_federation(federation_id);
+//! let plugin = EcashPlugin::with_executor(Arc::new(FedimintExecutor::new(config)?));
+//! ```
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use super::config::FedimintConfig;
+use crate::methods::{
+    EcashExecutor, EcashReceive, EcashSpend, LightningPaymentResult, LightningPaymentStatus,
+};
+use crate::{PaykitError, Result};
+
+/// Seconds a spend stays reclaimable before the notes are reissued to the
+/// wallet if the payee never redeems them.
+const SPEND_TIMEOUT_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// Fedimint client daemon executor.
+#[derive(Debug)]
+pub struct FedimintExecutor {
+    config: FedimintConfig,
+    client: reqwest::Client,
+}
+
+impl FedimintExecutor {
+    /// Create a new executor.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidData` if the URL or password is empty, or `Internal`
+    /// if the HTTP client cannot be built.
+    pub fn new(config: FedimintConfig) -> Result<Self> {
+        if config.clientd_url.is_empty() {
+            return Err(PaykitError::invalid_data(
+                "clientd_url",
+                "clientd_url cannot be empty",
+            ));
+        }
+        if config.password.is_empty() {
+            return Err(PaykitError::invalid_data(
+                "password",
+                "password cannot be empty",
+            ));
+        }
+
+        let mut builder =
+            reqwest::Client::builder().timeout(Duration::from_secs(config.timeout_secs));
+        if let Some(proxy) = &config.proxy {
+            builder = builder.proxy(proxy.to_reqwest()?);
+        }
+        let client = builder
+            .build()
+            .map_err(|e| PaykitError::Internal(format!("Failed to build HTTP client: {}", e)))?;
+
+        Ok(Self { config, client })
+    }
+
+    /// Get the configuration.
+    pub fn config(&self) -> &FedimintConfig {
+        &self.config
+    }
+
+    /// Build the full URL for an API path.
+    fn url(&self, path: &str) -> String {
+        format!(
+            "{}/v2/{}",
+            self.config.clientd_url.trim_end_matches('/'),
+            path
+        )
+    }
+
+    /// Call the daemon API, POSTing `body` if given and GETting otherwise.
+    async fn send<T: for<'de> Deserialize<'de>>(
+        &self,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<T> {
+        let url = self.url(path);
+        let request = match body {
+            Some(body) => self.client.post(url).json(body),
+            None => self.client.get(url),
+        }
+        .bearer_auth(&self.config.password);
+        let response = request
+            .send()
+            .await
+            .map_err(|e| self.map_reqwest_error(e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(map_error_response(status.as_u16(), &body));
+        }
+
+        response.json::<T>().await.map_err(|e| {
+            PaykitError::Serialization(format!("Failed to parse fedimint-clientd response: {}", e))
+        })
+    }
+
+    /// Map reqwest errors to PaykitError.
+    fn map_reqwest_error(&self, e: reqwest::Error) -> PaykitError {
+        if e.is_timeout() {
+            PaykitError::ConnectionTimeout {
+                operation: "fedimint-clientd request".to_string(),
+                timeout_ms: self.config.timeout_secs * 1000,
+            }
+        } else if e.is_connect() {
+            PaykitError::ConnectionFailed {
+                target: self.config.clientd_url.clone(),
+                reason: e.to_string(),
+            }
+        } else {
+            PaykitError::Transport(format!("fedimint-clientd request failed: {}", e))
+        }
+    }
+}
+
+#[async_trait]
+impl EcashExecutor for FedimintExecutor {
+    async fn federation_ids(&self) -> Result<Vec<String>> {
+        let response: FederationIdsResponse = self.send("admin/federation-ids", None).await?;
+        Ok(response.federation_ids)
+    }
+
+    async fn spend_notes(&self, federation_id: &str, amount_msat: u64) -> Result<EcashSpend> {
+        let body = serde_json::json!({
+            "federationId": federation_id,
+            "amountMsat": amount_msat,
+            "allowOverpay": false,
+            "timeout": SPEND_TIMEOUT_SECS,
+            "includeInvite": false,
+        });
+        let response: SpendResponse = self.send("mint/spend", Some(&body)).await?;
+        Ok(EcashSpend {
+            federation_id: federation_id.to_string(),
+            operation_id: response.operation,
+            notes: response.notes,
+            amount_msat,
+        })
+    }
+
+    async fn receive_notes(&self, notes: &str) -> Result<EcashReceive> {
+        let body = serde_json::json!({ "notes": notes.trim() });
+        let response: ReissueResponse = self.send("mint/reissue", Some(&body)).await?;
+        Ok(EcashReceive {
+            operation_id: response.operation_id.unwrap_or_default(),
+            amount_msat: response.amount_msat,
+        })
+    }
+
+    /// Pay through the federation's gateway.
+    ///
+    /// `fedimint-clientd` identifies the outgoing contract rather than the
+    /// payment hash, so the result's `payment_hash` carries the contract ID
+    /// and `preimage` is empty unless the daemon returns it.
+    async fn pay_invoice(
+        &self,
+        federation_id: Option<&str>,
+        invoice: &str,
+        amount_msat: Option<u64>,
+    ) -> Result<LightningPaymentResult> {
+        let body = serde_json::json!({
+            "paymentInfo": invoice,
+            "amountMsat": amount_msat,
+            "gatewayId": self.config.gateway_id,
+            "federationId": federation_id.or(self.config.federation_id.as_deref()),
+        });
+        let response: LnPayResponse = self.send("ln/pay", Some(&body)).await?;
+        Ok(LightningPaymentResult {
+            preimage: response.preimage.unwrap_or_default(),
+            payment_hash: response.contract_id,
+            amount_msat: amount_msat.unwrap_or_default(),
+            fee_msat: response.fee,
+            hops: 0,
+            status: LightningPaymentStatus::Succeeded,
+        })
+    }
+
+    async fn balance_msat(&self, federation_id: &str) -> Result<u64> {
+        let info: HashMap<String, FederationInfo> = self.send("admin/info", None).await?;
+        info.get(federation_id)
+            .map(|federation| federation.total_amount_msat)
+            .ok_or_else(|| PaykitError::not_found("federation", federation_id))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FederationIdsResponse {
+    federation_ids: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FederationInfo {
+    total_amount_msat: u64,
+}
+
+#[derive(Deserialize)]
+struct SpendResponse {
+    operation: String,
+    notes: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReissueResponse {
+    amount_msat: u64,
+    #[serde(default)]
+    operation_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LnPayResponse {
+    contract_id: String,
+    #[serde(default)]
+    fee: u64,
+    #[serde(default)]
+    preimage: Option<String>,
+}
+
+/// Map a non-2xx daemon response to a [`PaykitError`].
+fn map_error_response(status: u16, body: &str) -> PaykitError {
+    let message = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|v| v.get("error").and_then(|e| e.as_str()).map(str::to_string))
+        .unwrap_or_else(|| body.to_string());
+
+    match status {
+        401 | 403 => PaykitError::Auth(format!("fedimint-clientd password rejected: {}", message)),
+        404 => PaykitError::NotFound {
+            resource_type: "fedimint-clientd resource".to_string(),
+            identifier: message,
+        },
+        400 | 422 if message.to_lowercase().contains("insufficient") => PaykitError::Payment {
+            payment_id: None,
+            reason: format!("Insufficient e-cash balance: {}", message),
+        },
+        400 | 422 => {
+            PaykitError::ValidationFailed(format!("fedimint-clientd rejected request: {}", message))
+        }
+        500..=599 => {
+            PaykitError::Internal(format!("fedimint-clientd error ({}): {}", status, message))
+        }
+        _ => PaykitError::Transport(format!(
+            "fedimint-clientd request failed ({}): {}",
+            status, message
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fedimint_config_validation() {
+        assert!(FedimintExecutor::new(FedimintConfig::new("", "pw")).is_err());
+        assert!(FedimintExecutor::new(FedimintConfig::new("http://localhost:3333", "")).is_err());
+
+        let executor =
+            FedimintExecutor::new(FedimintConfig::new("http://localhost:3333/", "pw")).unwrap();
+        assert_eq!(
+            executor.url("mint/spend"),
+            "http://localhost:3333/v2/mint/spend"
+        );
+    }
+
+    #[test]
+    fn test_fedimint_error_mapping() {
+        assert!(matches!(map_error_response(401, ""), PaykitError::Auth(_)));
+        assert!(matches!(
+            map_error_response(500, r#"{"error":"Insufficient balance"}"#),
+            PaykitError::Internal(_)
+        ));
+        assert!(matches!(
+            map_error_response(400, r#"{"error":"Insufficient balance"}"#),
+            PaykitError::Payment { .. }
+        ));
+    }
+}
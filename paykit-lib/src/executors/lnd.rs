@@ -2,6 +2,14 @@
 //!
 //! Connects to LND nodes via their REST API for Lightning payments.
 //!
+//! Requests authenticate with the node's macaroon. When the node's TLS
+//! certificate is configured the connection is pinned to it, so a
+//! self-signed `tls.cert` works without trusting anything else.
+//!
+//! Payments that are still in flight when the REST call returns (or times
+//! out) are reported as [`LightningPaymentStatus::Pending`];
+//! `LndExecutor::wait_for_payment` polls the node until they settle.
+//!
 //! # Feature Flags
 //!
 //! This module requires the `http-executor` feature flag to be enabled for actual
@@ -17,8 +25,14 @@
 //! ```rust,ignore
 //! use paykit_lib::executors::{LndConfig, LndExecutor};
 //! use paykit_lib::methods::LightningExecutor;
+//! use std::path::Path;
+//! use std::time::Duration;
 //!
-//! let config = LndConfig::new("https://localhost:8080", "your_macaroon_hex");
+//! let config = LndConfig::from_files(
+//!     "https://localhost:8080",
+//!     "/home/bitcoin/.lnd/data/chain/bitcoin/mainnet/admin.macaroon",
+//!     Some(Path::new("/home/bitcoin/.lnd/tls.cert")),
+//! )?;
 //! let executor = LndExecutor::new(config)?;
 //!
 //! // Decode an invoice
@@ -28,6 +42,11 @@
 //! // Pay an invoice
 //! let result = executor.pay_invoice("lnbc...", None, None).await?;
 //! println!("Payment preimage: {}", result.preimage);
+//!
+//! // Wait for a payment that was still in flight
+//! let settled = executor
+//!     .wait_for_payment(&result.payment_hash, Duration::from_secs(60))
+//!     .await?;
 //! ```
 
 use async_trait::async_trait;
@@ -37,7 +56,7 @@ use std::time::Duration;
 
 use super::config::LndConfig;
 use crate::methods::{
-    DecodedInvoice, LightningExecutor, LightningPaymentResult, LightningPaymentStatus,
+    DecodedInvoice, LightningExecutor, LightningPaymentResult, LightningPaymentStatus, RouteProbe,
 };
 use crate::{PaykitError, Result};
 
@@ -50,7 +69,8 @@ use crate::{PaykitError, Result};
 ///
 /// The macaroon is sent with each request for authentication. Ensure you use
 /// HTTPS in production and consider using a restricted macaroon with minimal
-/// permissions needed for your use case.
+/// permissions needed for your use case. Setting
+/// [`LndConfig::tls_cert_pem`] pins the connection to the node's certificate.
 #[derive(Debug)]
 pub struct LndExecutor {
     config: LndConfig,
@@ -58,6 +78,9 @@ pub struct LndExecutor {
     client: reqwest::Client,
 }
 
+/// Alias for [`LndExecutor`], named after the trait it implements.
+pub type LndLightningExecutor = LndExecutor;
+
 impl LndExecutor {
     /// Create a new LND executor with the given configuration.
     ///
@@ -66,6 +89,7 @@ impl LndExecutor {
     /// Returns an error if:
     /// - The REST URL is empty
     /// - The macaroon is empty
+    /// - (With `http-executor` feature) The TLS certificate is not valid PEM
     /// - (With `http-executor` feature) The HTTP client cannot be built
     pub fn new(config: LndConfig) -> Result<Self> {
         // Validate configuration
@@ -83,11 +107,28 @@ impl LndExecutor {
         }
 
         #[cfg(feature = "http-executor")]
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(config.timeout_secs))
-            .danger_accept_invalid_certs(config.tls_cert_pem.is_some()) // Accept self-signed if cert provided
-            .build()
-            .map_err(|e| PaykitError::Internal(format!("Failed to build HTTP client: {}", e)))?;
+        let client = {
+            let mut builder =
+                reqwest::Client::builder().timeout(Duration::from_secs(config.timeout_secs));
+            if let Some(pem) = &config.tls_cert_pem {
+                let cert = reqwest::Certificate::from_pem(pem.as_bytes()).map_err(|e| {
+                    PaykitError::InvalidData {
+                        field: "tls_cert_pem".to_string(),
+                        reason: format!("Invalid TLS certificate: {}", e),
+                    }
+                })?;
+                // Trust the node's certificate and nothing else
+                builder = builder
+                    .tls_built_in_root_certs(false)
+                    .add_root_certificate(cert);
+            }
+            if let Some(proxy) = &config.proxy {
+                builder = builder.proxy(proxy.to_reqwest()?);
+            }
+            builder
+                .build()
+                .map_err(|e| PaykitError::Internal(format!("Failed to build HTTP client: {}", e)))?
+        };
 
         Ok(Self {
             config,
@@ -101,6 +142,20 @@ impl LndExecutor {
         &self.config
     }
 
+    /// Poll the node until a payment succeeds or fails.
+    ///
+    /// Checks every [`LndConfig::poll_interval_ms`]; see
+    /// [`wait_for_payment`](super::wait_for_payment).
+    #[cfg(feature = "http-executor")]
+    pub async fn wait_for_payment(
+        &self,
+        payment_hash: &str,
+        timeout: Duration,
+    ) -> Result<LightningPaymentResult> {
+        let interval = Duration::from_millis(self.config.poll_interval_ms);
+        super::poll::wait_for_payment(self, payment_hash, interval, timeout).await
+    }
+
     /// Build the full URL for an API endpoint.
     #[cfg(feature = "http-executor")]
     fn url(&self, path: &str) -> String {
@@ -251,8 +306,19 @@ impl LightningExecutor for LndExecutor {
             no_inflight_updates: Some(true),
         };
 
-        // Send payment
-        let response: LndPayResponse = self.post("channels/transactions", &pay_req).await?;
+        // Send payment. If the node does not answer in time the payment may
+        // still be in flight, so look it up instead of reporting a failure.
+        let response: LndPayResponse = match self.post("channels/transactions", &pay_req).await {
+            Ok(response) => response,
+            Err(e @ PaykitError::ConnectionTimeout { .. }) => {
+                let payment_hash = self.decode_invoice(invoice).await?.payment_hash;
+                return match self.get_payment(&payment_hash).await? {
+                    Some(payment) => Ok(payment),
+                    None => Err(e),
+                };
+            }
+            Err(e) => return Err(e),
+        };
 
         // Check for error
         if !response.payment_error.is_empty() {
@@ -262,21 +328,31 @@ impl LightningExecutor for LndExecutor {
             });
         }
 
+        // No preimage and no error: the payment has not settled yet
         let status = if response.payment_preimage.is_empty() {
-            LightningPaymentStatus::Failed
+            LightningPaymentStatus::Pending
         } else {
             LightningPaymentStatus::Succeeded
         };
 
+        let fee_msat = response
+            .payment_route
+            .as_ref()
+            .map(|r| r.total_fees_msat.parse().unwrap_or(0))
+            .unwrap_or(0);
+        // Zero-amount invoices take the amount from the route
+        let route_amount_msat = response.payment_route.as_ref().and_then(|r| {
+            r.total_amt_msat
+                .parse::<u64>()
+                .ok()
+                .map(|total| total.saturating_sub(fee_msat))
+        });
+
         Ok(LightningPaymentResult {
-            preimage: response.payment_preimage,
-            payment_hash: response.payment_hash,
-            amount_msat: amount_msat.unwrap_or(0),
-            fee_msat: response
-                .payment_route
-                .as_ref()
-                .map(|r| r.total_fees_msat.parse().unwrap_or(0))
-                .unwrap_or(0),
+            preimage: bytes_to_hex(response.payment_preimage),
+            payment_hash: bytes_to_hex(response.payment_hash),
+            amount_msat: amount_msat.or(route_amount_msat).unwrap_or(0),
+            fee_msat,
             hops: response
                 .payment_route
                 .as_ref()
@@ -342,9 +418,39 @@ impl LightningExecutor for LndExecutor {
         Ok(fee)
     }
 
+    async fn probe_route(&self, destination: &str, amount_msat: u64) -> Result<RouteProbe> {
+        // Accept either an invoice or a node public key
+        let pub_key = if destination.starts_with("ln") {
+            self.decode_invoice(destination).await?.payee
+        } else {
+            destination.to_string()
+        };
+
+        let query = LndQueryRoutesRequest {
+            pub_key,
+            amt_msat: amount_msat.to_string(),
+        };
+        let response: LndQueryRoutesResponse = match self.post("graph/routes", &query).await {
+            Ok(response) => response,
+            // LND reports "unable to find a path" as a not found error
+            Err(PaykitError::NotFound { .. }) => return Ok(RouteProbe::no_route()),
+            Err(e) => return Err(e),
+        };
+
+        Ok(match response.routes.first() {
+            Some(route) => RouteProbe::found(
+                response.success_prob,
+                route.total_fees_msat.parse().unwrap_or(0),
+                route.hops.len() as u32,
+            ),
+            None => RouteProbe::no_route(),
+        })
+    }
+
     async fn get_payment(&self, payment_hash: &str) -> Result<Option<LightningPaymentResult>> {
-        // List payments and find matching one
-        let response: LndListPaymentsResponse = self.get("payments").await?;
+        // List payments, including in-flight ones, and find the matching one
+        let response: LndListPaymentsResponse =
+            self.get("payments?include_incomplete=true").await?;
 
         let payment = response
             .payments
@@ -357,15 +463,33 @@ impl LightningExecutor for LndExecutor {
             amount_msat: p.value_msat.parse().unwrap_or(0),
             fee_msat: p.fee_msat.parse().unwrap_or(0),
             hops: 0, // Not available in list response
-            status: match p.status.as_str() {
-                "SUCCEEDED" => LightningPaymentStatus::Succeeded,
-                "IN_FLIGHT" => LightningPaymentStatus::Pending,
-                _ => LightningPaymentStatus::Failed,
-            },
+            status: payment_status(&p.status),
         }))
     }
 }
 
+/// Map an LND payment status to [`LightningPaymentStatus`].
+fn payment_status(status: &str) -> LightningPaymentStatus {
+    match status {
+        "SUCCEEDED" => LightningPaymentStatus::Succeeded,
+        "IN_FLIGHT" | "INITIATED" => LightningPaymentStatus::Pending,
+        _ => LightningPaymentStatus::Failed,
+    }
+}
+
+/// Convert a 32-byte `bytes` field to hex.
+///
+/// The REST gateway encodes `bytes` fields of the send response as base64,
+/// while proofs and `get_payment` use hex. Other values pass through.
+fn bytes_to_hex(value: String) -> String {
+    use base64::Engine;
+
+    match base64::engine::general_purpose::STANDARD.decode(&value) {
+        Ok(bytes) if bytes.len() == 32 => hex::encode(bytes),
+        _ => value,
+    }
+}
+
 // ============================================================================
 // LND REST API Types
 // ============================================================================
@@ -399,6 +523,8 @@ struct LndRoute {
     #[serde(default)]
     total_fees_msat: String,
     #[serde(default)]
+    total_amt_msat: String,
+    #[serde(default)]
     hops: Vec<LndHop>,
 }
 
@@ -438,6 +564,8 @@ struct LndQueryRoutesRequest {
 struct LndQueryRoutesResponse {
     #[serde(default)]
     routes: Vec<LndRoute>,
+    #[serde(default)]
+    success_prob: f64,
 }
 
 #[derive(Deserialize)]
@@ -513,4 +641,37 @@ mod tests {
             "https://localhost:8080/v1/payreq/lnbc123"
         );
     }
+
+    #[test]
+    fn test_payment_status_mapping() {
+        assert_eq!(
+            payment_status("SUCCEEDED"),
+            LightningPaymentStatus::Succeeded
+        );
+        assert_eq!(payment_status("IN_FLIGHT"), LightningPaymentStatus::Pending);
+        assert_eq!(payment_status("INITIATED"), LightningPaymentStatus::Pending);
+        assert_eq!(payment_status("FAILED"), LightningPaymentStatus::Failed);
+    }
+
+    #[test]
+    fn test_bytes_to_hex() {
+        let hash = "ab".repeat(32);
+        assert_eq!(
+            bytes_to_hex("Q6urq6urq6urq6urq6urq6urq6urq6urq6urq6urq6s=".to_string()),
+            hash
+        );
+        assert_eq!(bytes_to_hex(hash.clone()), hash);
+        assert_eq!(bytes_to_hex(String::new()), "");
+    }
+
+    #[cfg(feature = "http-executor")]
+    #[test]
+    fn test_lnd_executor_rejects_invalid_tls_cert() {
+        let config = LndConfig::new("https://localhost:8080", "macaroon123")
+            .with_tls_cert("not a certificate");
+        let result = LndExecutor::new(config);
+        assert!(
+            matches!(result, Err(PaykitError::InvalidData { ref field, .. }) if field == "tls_cert_pem")
+        );
+    }
 }
@@ -15,12 +15,28 @@
 //! ## Supported Backends
 //!
 //! ### Lightning
-//! - **LND REST API** - Connect to LND nodes via REST
-//! - **CLN (Core Lightning)** - Coming soon
+//! - **LND REST API** - Connect to LND nodes via REST (macaroon auth, TLS
+//!   pinning, payment status polling)
+//! - **CLN (Core Lightning)** - Connect to CLN nodes via the `clnrest` plugin
+//!   (rune auth, TLS pinning)
+//!
+//! ### E-Cash
+//! - **Fedimint** - Spends and redeems federation notes and pays through the
+//!   federation's Lightning gateway via `fedimint-clientd` (`fedimint` feature)
+//!
+//! ### Receiving
+//! - **BTCPay Server** - Creates invoices on a BTCPay store, publishes its
+//!   endpoints and maps webhook settlements to receipts
 //!
 //! ### On-chain
 //! - **Electrum** - Connect to Electrum servers (planned)
 //! - **Esplora** - Block explorer API (Blockstream, mempool.space)
+//! - **Esplora + PSBT signer** - Sends payments by handing unsigned PSBTs to a
+//!   hardware wallet or host app (`psbt` feature)
+//!
+//! ### Timestamping
+//! - **OpenTimestamps calendars** - Submits digests for Bitcoin-anchored
+//!   timestamps and fetches the completed proofs
 //!
 //! ## Usage
 //!
@@ -58,14 +74,38 @@
 //! let config = get_lnd_config_from_env();
 //! ```
 
+mod btcpay;
+mod calendar;
+mod cln;
 mod config;
 mod esplora;
+#[cfg(feature = "psbt")]
+mod esplora_psbt;
+#[cfg(feature = "fedimint")]
+mod fedimint;
 mod lnd;
+#[cfg(feature = "http-executor")]
+mod poll;
 pub mod testnet;
 
-pub use config::{BitcoinNetwork, ElectrumConfig, EsploraConfig, ExecutorConfig, LndConfig};
+pub use btcpay::{
+    BtcPayInvoice, BtcPayInvoiceRequest, BtcPayInvoiceStatus, BtcPayPaymentMethod, BtcPayReceiver,
+    BtcPayWebhookEvent,
+};
+pub use calendar::{CalendarClient, DEFAULT_CALENDARS};
+pub use cln::{ClnExecutor, ClnLightningExecutor};
+pub use config::{
+    BitcoinNetwork, BtcPayConfig, ClnConfig, ElectrumConfig, EsploraConfig, ExecutorConfig,
+    FedimintConfig, LndConfig,
+};
 pub use esplora::{
-    AddressInfo, AddressStats, EsploraExecutor, EsploraTx, EsploraTxInput, EsploraTxOutput,
-    FeeEstimates, TxStatus, Utxo,
+    AddressInfo, AddressStats, EsploraBlock, EsploraExecutor, EsploraTx, EsploraTxInput,
+    EsploraTxOutput, FeeEstimates, TxStatus, Utxo,
 };
-pub use lnd::LndExecutor;
+#[cfg(feature = "psbt")]
+pub use esplora_psbt::{EsploraBitcoinExecutor, PsbtSigner, UnsignedPsbt};
+#[cfg(feature = "fedimint")]
+pub use fedimint::FedimintExecutor;
+pub use lnd::{LndExecutor, LndLightningExecutor};
+#[cfg(feature = "http-executor")]
+pub use poll::wait_for_payment;
@@ -0,0 +1,42 @@
+//! Waiting for in-flight Lightning payments to settle.
+
+use std::time::Duration;
+
+use crate::methods::{LightningExecutor, LightningPaymentResult, LightningPaymentStatus};
+use crate::{PaykitError, Result};
+
+/// Poll `executor` until a payment succeeds or fails.
+///
+/// Checks every `poll_interval`. If the payment is still in flight when
+/// `timeout` elapses, its pending state is returned so the caller can keep
+/// tracking it.
+///
+/// # Errors
+///
+/// Returns `NotFound` if the node never reports the payment, or any error
+/// from querying the node.
+pub async fn wait_for_payment<E: LightningExecutor + ?Sized>(
+    executor: &E,
+    payment_hash: &str,
+    poll_interval: Duration,
+    timeout: Duration,
+) -> Result<LightningPaymentResult> {
+    let deadline = tokio::time::Instant::now() + timeout;
+
+    loop {
+        let payment = executor.get_payment(payment_hash).await?;
+        if let Some(payment) = &payment {
+            if payment.status != LightningPaymentStatus::Pending {
+                return Ok(payment.clone());
+            }
+        }
+
+        if tokio::time::Instant::now() + poll_interval > deadline {
+            return payment.ok_or_else(|| PaykitError::NotFound {
+                resource_type: "Lightning payment".to_string(),
+                identifier: payment_hash.to_string(),
+            });
+        }
+        tokio::time::sleep(poll_interval).await;
+    }
+}
@@ -0,0 +1,252 @@
+//! Amount and currency formatting for display.
+//!
+//! Shared by the CLI, web and mobile front-ends so amounts render the same
+//! everywhere. Bitcoin amounts are handled as integer satoshis; conversions
+//! to and from BTC strings never go through floating point.
+//!
+//! Digit grouping and the decimal mark follow the [`Locale`]: English uses
+//! `1,234.56`, Spanish and German use `1.234,56`.
+//!
+//! # Example
+//!
+//! ```
+//! use paykit_lib::formatting::{format_btc, format_compact_sats, format_fiat, format_sats};
+//! use paykit_lib::i18n::Locale;
+//!
+//! assert_eq!(format_sats(1_234_567, Locale::En), "1,234,567 sats");
+//! assert_eq!(format_btc(1_234_567, Locale::De), "0,01234567 BTC");
+//! assert_eq!(format_compact_sats(1_250_000, Locale::En), "1.3M sats");
+//! assert_eq!(format_fiat(1234.5, "EUR", Locale::De), "1.234,50 €");
+//! ```
+
+use crate::i18n::Locale;
+use crate::{PaykitError, Result};
+
+/// Satoshis in one bitcoin.
+pub const SATS_PER_BTC: u64 = 100_000_000;
+
+/// Decimal places in a BTC amount.
+pub const BTC_DECIMALS: usize = 8;
+
+/// Thousands separator and decimal mark for a locale.
+fn separators(locale: Locale) -> (char, char) {
+    match locale {
+        Locale::En => (',', '.'),
+        Locale::Es | Locale::De => ('.', ','),
+    }
+}
+
+/// Insert a thousands separator into a run of ASCII digits.
+fn group_digits(digits: &str, separator: char) -> String {
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            out.push(separator);
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Group an integer with the locale's thousands separator.
+pub fn format_number(value: u64, locale: Locale) -> String {
+    group_digits(&value.to_string(), separators(locale).0)
+}
+
+/// Format satoshis with grouping, e.g. `"1,234,567 sats"` or `"1 sat"`.
+pub fn format_sats(sats: u64, locale: Locale) -> String {
+    let unit = if sats == 1 { "sat" } else { "sats" };
+    format!("{} {}", format_number(sats, locale), unit)
+}
+
+/// Format satoshis as BTC with all eight decimals, e.g. `"0.01234567 BTC"`.
+pub fn format_btc(sats: u64, locale: Locale) -> String {
+    let (group, decimal) = separators(locale);
+    format!(
+        "{}{}{:0width$} BTC",
+        group_digits(&(sats / SATS_PER_BTC).to_string(), group),
+        decimal,
+        sats % SATS_PER_BTC,
+        width = BTC_DECIMALS
+    )
+}
+
+/// Satoshis as a plain BTC decimal string (`"0.00100000"`), for URIs and APIs.
+pub fn sats_to_btc_string(sats: u64) -> String {
+    format!(
+        "{}.{:0width$}",
+        sats / SATS_PER_BTC,
+        sats % SATS_PER_BTC,
+        width = BTC_DECIMALS
+    )
+}
+
+/// Parse a plain BTC decimal string such as `"0.001"` into satoshis.
+///
+/// Rejects more than eight decimal places, signs, separators and exponents.
+pub fn btc_to_sats(btc: &str) -> Result<u64> {
+    let invalid = |reason: &str| PaykitError::invalid_data("btc amount", reason);
+    let btc = btc.trim();
+    let (whole, fraction) = btc.split_once('.').unwrap_or((btc, ""));
+
+    if whole.is_empty() && fraction.is_empty() {
+        return Err(invalid("empty amount"));
+    }
+    if !whole.chars().all(|c| c.is_ascii_digit()) || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid("expected digits with an optional decimal point"));
+    }
+    if fraction.len() > BTC_DECIMALS {
+        return Err(invalid("more than 8 decimal places"));
+    }
+
+    let whole: u64 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().map_err(|_| invalid("amount too large"))?
+    };
+    let fraction: u64 = format!("{:0<width$}", fraction, width = BTC_DECIMALS)
+        .parse()
+        .map_err(|_| invalid("invalid fraction"))?;
+
+    whole
+        .checked_mul(SATS_PER_BTC)
+        .and_then(|sats| sats.checked_add(fraction))
+        .ok_or_else(|| invalid("amount too large"))
+}
+
+/// Short form for tight layouts, e.g. `"950 sats"`, `"1.2K sats"`, `"3.4M sats"`.
+///
+/// Rounds to one decimal and drops a trailing zero.
+pub fn format_compact_sats(sats: u64, locale: Locale) -> String {
+    const SUFFIXES: [(u64, &str); 3] = [(1_000_000_000, "B"), (1_000_000, "M"), (1_000, "K")];
+
+    let (_, decimal) = separators(locale);
+    for (scale, suffix) in SUFFIXES {
+        // Round to tenths of the unit first so 999_950 becomes "1M", not "1000K"
+        let tenths = (u128::from(sats) * 10 + u128::from(scale) / 2) / u128::from(scale);
+        if tenths >= 10 {
+            let whole = tenths / 10;
+            let fraction = tenths % 10;
+            return if fraction == 0 {
+                format!("{}{} sats", whole, suffix)
+            } else {
+                format!("{}{}{}{} sats", whole, decimal, fraction, suffix)
+            };
+        }
+    }
+    format_sats(sats, locale)
+}
+
+/// Display conventions for a fiat currency.
+struct FiatStyle {
+    symbol: Option<&'static str>,
+    decimals: u32,
+}
+
+fn fiat_style(currency: &str) -> FiatStyle {
+    let (symbol, decimals) = match currency {
+        "USD" => (Some("$"), 2),
+        "EUR" => (Some("€"), 2),
+        "GBP" => (Some("£"), 2),
+        "JPY" => (Some("¥"), 0),
+        "CHF" => (None, 2),
+        "CAD" => (Some("CA$"), 2),
+        "AUD" => (Some("A$"), 2),
+        _ => (None, 2),
+    };
+    FiatStyle { symbol, decimals }
+}
+
+/// Format a fiat amount, e.g. `"$1,234.50"` or `"1.234,50 €"`.
+///
+/// English puts known symbols before the number; Spanish and German put them
+/// after. Currencies without a symbol show their code. Amounts are rounded to
+/// the currency's minor unit.
+pub fn format_fiat(amount: f64, currency: &str, locale: Locale) -> String {
+    let currency = currency.to_ascii_uppercase();
+    let style = fiat_style(&currency);
+    let (group, decimal) = separators(locale);
+
+    let scale = 10u64.pow(style.decimals);
+    let minor = (amount.abs() * scale as f64).round() as u64;
+    let mut number = group_digits(&(minor / scale).to_string(), group);
+    if style.decimals > 0 {
+        number.push(decimal);
+        number.push_str(&format!(
+            "{:0width$}",
+            minor % scale,
+            width = style.decimals as usize
+        ));
+    }
+    let sign = if amount < 0.0 && minor > 0 { "-" } else { "" };
+
+    match (style.symbol, locale) {
+        (Some(symbol), Locale::En) => format!("{}{}{}", sign, symbol, number),
+        (Some(symbol), _) => format!("{}{} {}", sign, number, symbol),
+        (None, _) => format!("{}{} {}", sign, number, currency),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_sats() {
+        assert_eq!(format_sats(0, Locale::En), "0 sats");
+        assert_eq!(format_sats(1, Locale::En), "1 sat");
+        assert_eq!(format_sats(999, Locale::En), "999 sats");
+        assert_eq!(format_sats(1_000, Locale::En), "1,000 sats");
+        assert_eq!(format_sats(21_000_000, Locale::Es), "21.000.000 sats");
+    }
+
+    #[test]
+    fn test_btc_round_trip() {
+        assert_eq!(format_btc(150_000_000, Locale::En), "1.50000000 BTC");
+        assert_eq!(
+            format_btc(2_100_000_000_000_000, Locale::En),
+            "21,000,000.00000000 BTC"
+        );
+        assert_eq!(sats_to_btc_string(100_000), "0.00100000");
+
+        assert_eq!(btc_to_sats("0.001").unwrap(), 100_000);
+        assert_eq!(btc_to_sats("1").unwrap(), SATS_PER_BTC);
+        assert_eq!(btc_to_sats(".5").unwrap(), 50_000_000);
+        assert_eq!(
+            btc_to_sats(&sats_to_btc_string(123_456_789)).unwrap(),
+            123_456_789
+        );
+
+        for bad in [
+            "",
+            ".",
+            "-1",
+            "1e3",
+            "0.000000001",
+            "1,5",
+            "99999999999999999999",
+        ] {
+            assert!(btc_to_sats(bad).is_err(), "{} should fail", bad);
+        }
+    }
+
+    #[test]
+    fn test_format_compact_sats() {
+        assert_eq!(format_compact_sats(950, Locale::En), "950 sats");
+        assert_eq!(format_compact_sats(1_200, Locale::En), "1.2K sats");
+        assert_eq!(format_compact_sats(1_000_000, Locale::En), "1M sats");
+        assert_eq!(format_compact_sats(999_950, Locale::En), "1M sats");
+        assert_eq!(format_compact_sats(1_250_000, Locale::De), "1,3M sats");
+        assert_eq!(format_compact_sats(3_400_000_000, Locale::En), "3.4B sats");
+    }
+
+    #[test]
+    fn test_format_fiat() {
+        assert_eq!(format_fiat(1234.5, "USD", Locale::En), "$1,234.50");
+        assert_eq!(format_fiat(1234.5, "eur", Locale::De), "1.234,50 €");
+        assert_eq!(format_fiat(-0.126, "GBP", Locale::En), "-£0.13");
+        assert_eq!(format_fiat(1500.0, "JPY", Locale::En), "¥1,500");
+        assert_eq!(format_fiat(10.0, "CHF", Locale::Es), "10,00 CHF");
+        assert_eq!(format_fiat(-0.001, "USD", Locale::En), "$0.00");
+    }
+}
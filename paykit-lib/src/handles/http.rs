@@ -0,0 +1,172 @@
+//! HTTPS and DNS-over-HTTPS handle lookups
+
+use super::HandleLookup;
+use crate::proxy::Socks5Proxy;
+use crate::{PaykitError, Result};
+use std::time::Duration;
+
+/// DNS-over-HTTPS endpoint used for TXT lookups by default.
+pub const DEFAULT_DOH_URL: &str = "https://cloudflare-dns.com/dns-query";
+
+/// Request timeout for handle lookups.
+const LOOKUP_TIMEOUT_SECS: u64 = 10;
+
+/// DNS record type of TXT records.
+const DNS_TYPE_TXT: u16 = 16;
+
+/// DNS response code for a name that does not exist.
+const DNS_NXDOMAIN: u16 = 3;
+
+/// [`HandleLookup`] over HTTPS, with TXT records read through a
+/// DNS-over-HTTPS JSON endpoint.
+pub struct HttpHandleLookup {
+    client: reqwest::Client,
+    doh_url: String,
+}
+
+impl HttpHandleLookup {
+    /// Create a lookup using [`DEFAULT_DOH_URL`].
+    pub fn new() -> Result<Self> {
+        Self::build(None)
+    }
+
+    /// Create a lookup sending all requests through a SOCKS5 proxy.
+    pub fn with_proxy(proxy: &Socks5Proxy) -> Result<Self> {
+        Self::build(Some(proxy))
+    }
+
+    fn build(proxy: Option<&Socks5Proxy>) -> Result<Self> {
+        let mut builder =
+            reqwest::Client::builder().timeout(Duration::from_secs(LOOKUP_TIMEOUT_SECS));
+        if let Some(proxy) = proxy {
+            builder = builder.proxy(proxy.to_reqwest()?);
+        }
+        let client = builder
+            .build()
+            .map_err(|e| PaykitError::Internal(format!("Failed to build HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            doh_url: DEFAULT_DOH_URL.to_string(),
+        })
+    }
+
+    /// Read TXT records through another DNS-over-HTTPS JSON endpoint.
+    pub fn with_doh_url(mut self, doh_url: impl Into<String>) -> Self {
+        self.doh_url = doh_url.into();
+        self
+    }
+
+    fn map_reqwest_error(&self, target: &str, e: reqwest::Error) -> PaykitError {
+        if e.is_timeout() {
+            PaykitError::ConnectionTimeout {
+                operation: "Handle lookup".to_string(),
+                timeout_ms: LOOKUP_TIMEOUT_SECS * 1000,
+            }
+        } else if e.is_connect() {
+            PaykitError::ConnectionFailed {
+                target: target.to_string(),
+                reason: e.to_string(),
+            }
+        } else {
+            PaykitError::Transport(format!("Handle lookup failed: {}", e))
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct DohResponse {
+    #[serde(rename = "Status")]
+    status: u16,
+    #[serde(rename = "Answer", default)]
+    answer: Vec<DohAnswer>,
+}
+
+#[derive(serde::Deserialize)]
+struct DohAnswer {
+    #[serde(rename = "type")]
+    record_type: u16,
+    data: String,
+}
+
+/// Join the quoted character-strings of a TXT answer (`"a" "b"` is `ab`).
+fn txt_value(data: &str) -> String {
+    if !data.starts_with('"') {
+        return data.to_string();
+    }
+    data.split('"')
+        .skip(1)
+        .step_by(2)
+        .collect::<Vec<_>>()
+        .concat()
+}
+
+#[async_trait::async_trait]
+impl HandleLookup for HttpHandleLookup {
+    async fn fetch_document(&self, url: &str) -> Result<Option<String>> {
+        let response = self
+            .client
+            .get(url)
+            .header("Accept", "application/json")
+            .send()
+            .await
+            .map_err(|e| self.map_reqwest_error(url, e))?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(PaykitError::Transport(format!(
+                "Handle document {} returned HTTP {}",
+                url,
+                status.as_u16()
+            )));
+        }
+        let text = response
+            .text()
+            .await
+            .map_err(|e| PaykitError::Serialization(format!("Failed to read response: {}", e)))?;
+        Ok(Some(text))
+    }
+
+    async fn lookup_txt(&self, name: &str) -> Result<Vec<String>> {
+        let response = self
+            .client
+            .get(&self.doh_url)
+            .query(&[("name", name), ("type", "TXT")])
+            .header("Accept", "application/dns-json")
+            .send()
+            .await
+            .map_err(|e| self.map_reqwest_error(&self.doh_url, e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(PaykitError::Transport(format!(
+                "DNS lookup of {} returned HTTP {}",
+                name,
+                status.as_u16()
+            )));
+        }
+        let text = response
+            .text()
+            .await
+            .map_err(|e| PaykitError::Serialization(format!("Failed to read response: {}", e)))?;
+        let dns: DohResponse = serde_json::from_str(&text)
+            .map_err(|e| PaykitError::Serialization(format!("Invalid DNS response: {}", e)))?;
+
+        match dns.status {
+            0 => Ok(dns
+                .answer
+                .iter()
+                .filter(|answer| answer.record_type == DNS_TYPE_TXT)
+                .map(|answer| txt_value(&answer.data))
+                .collect()),
+            DNS_NXDOMAIN => Ok(Vec::new()),
+            code => Err(PaykitError::Transport(format!(
+                "DNS lookup of {} failed with code {}",
+                name, code
+            ))),
+        }
+    }
+}
@@ -0,0 +1,648 @@
+//! Human-Readable Handles
+//!
+//! Pubky public keys are 52 characters of z-base32, which nobody wants to
+//! type. A handle such as `alice@example.com` names a key the way an email
+//! address names a mailbox: the domain vouches for the user part by
+//! publishing a [`HandleRecord`], either as a JSON document at
+//! `https://example.com/.well-known/paykit/alice` or as a DNS TXT record at
+//! `alice._paykit.example.com`.
+//!
+//! Every record is signed by the key it names, over the handle. A domain can
+//! therefore only point a handle at a key whose owner agreed to it, and a key
+//! owner cannot claim a handle on a domain that does not publish it.
+//!
+//! [`HandleResolver`] tries the well-known document first and falls back to
+//! DNS, ignoring records that fail verification, and caches verified results
+//! for a TTL. The network is reached through a [`HandleLookup`], so wallets
+//! can bring their own HTTP and DNS stack; the `http-handles` feature
+//! provides [`HttpHandleLookup`], which uses HTTPS and DNS-over-HTTPS.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use paykit_lib::handles::{HandleRecord, HandleResolver, HttpHandleLookup};
+//!
+//! // Publisher: sign a record and serve it from the domain
+//! let record = HandleRecord::sign(&"alice@example.com".parse()?, &keypair, now, None)?;
+//! serve("/.well-known/paykit/alice", record.to_json()?);
+//!
+//! // Payer
+//! let resolver = HandleResolver::new(Arc::new(HttpHandleLookup::new()?));
+//! let resolved = resolver.resolve("alice@example.com").await?;
+//! let supported = get_payment_list(&reader, &resolved.public_key).await?;
+//! ```
+
+#[cfg(feature = "http-handles")]
+mod http;
+
+#[cfg(feature = "http-handles")]
+pub use http::{HttpHandleLookup, DEFAULT_DOH_URL};
+
+use crate::{PaykitError, PublicKey, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+/// Path of well-known handle documents; the user part is appended.
+pub const HANDLE_WELL_KNOWN_PATH: &str = "/.well-known/paykit/";
+
+/// DNS label between the user part and the domain of TXT record names.
+pub const HANDLE_DNS_LABEL: &str = "_paykit";
+
+/// Version tag every handle TXT record starts with.
+pub const HANDLE_TXT_VERSION: &str = "v=paykit1";
+
+/// How long a verified handle is cached by default (1 hour).
+pub const DEFAULT_HANDLE_TTL_SECS: i64 = 3_600;
+
+/// Domain separation tag prepended to the record before signing.
+#[cfg(feature = "pubky")]
+const HANDLE_DOMAIN: &[u8] = b"PAYKIT_HANDLE_V1:";
+
+/// Maximum length of the user part.
+const MAX_USER_LEN: usize = 64;
+
+/// Maximum length of the domain.
+const MAX_DOMAIN_LEN: usize = 253;
+
+/// A human-readable name for a public key, `user@domain`.
+///
+/// Handles are case-insensitive and stored lowercased.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Handle {
+    user: String,
+    domain: String,
+}
+
+impl Handle {
+    /// Parse and normalize a handle.
+    pub fn parse(value: &str) -> Result<Self> {
+        let invalid = |reason: &str| PaykitError::invalid_data("handle", reason);
+        let value = value.trim().to_ascii_lowercase();
+        let (user, domain) = value
+            .split_once('@')
+            .ok_or_else(|| invalid("expected user@domain"))?;
+
+        if user.is_empty() || user.len() > MAX_USER_LEN {
+            return Err(invalid("user part must be 1 to 64 characters"));
+        }
+        if !is_valid_name(user, |c| c == '_' || c == '-') {
+            return Err(invalid(
+                "user part may only contain a-z, 0-9, '.', '_' and '-'",
+            ));
+        }
+        if domain.len() > MAX_DOMAIN_LEN || !domain.contains('.') {
+            return Err(invalid("domain must be a fully qualified name"));
+        }
+        if !is_valid_name(domain, |c| c == '-') {
+            return Err(invalid("domain may only contain a-z, 0-9, '.' and '-'"));
+        }
+
+        Ok(Self {
+            user: user.to_string(),
+            domain: domain.to_string(),
+        })
+    }
+
+    /// Whether `value` looks like a handle rather than a key or URI.
+    pub fn is_handle(value: &str) -> bool {
+        Self::parse(value).is_ok()
+    }
+
+    /// The part before the `@`.
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+
+    /// The part after the `@`.
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    /// URL of the well-known handle document.
+    pub fn well_known_url(&self) -> String {
+        format!(
+            "https://{}{}{}",
+            self.domain, HANDLE_WELL_KNOWN_PATH, self.user
+        )
+    }
+
+    /// Name of the DNS TXT record.
+    pub fn dns_name(&self) -> String {
+        format!("{}.{}.{}", self.user, HANDLE_DNS_LABEL, self.domain)
+    }
+}
+
+impl fmt::Display for Handle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.user, self.domain)
+    }
+}
+
+impl FromStr for Handle {
+    type Err = PaykitError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        Self::parse(value)
+    }
+}
+
+/// Dot-separated labels of `a-z`, `0-9` and the extra characters, with no
+/// empty labels and no label starting or ending with `-`.
+fn is_valid_name(name: &str, extra: impl Fn(char) -> bool) -> bool {
+    name.split('.').all(|label| {
+        !label.is_empty()
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || extra(c))
+    })
+}
+
+/// A signed binding of a handle to a public key, as published by the domain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HandleRecord {
+    /// The handle, `user@domain`.
+    pub handle: String,
+    /// Public key the handle names (z-base32).
+    pub public_key: String,
+    /// When the record was signed (Unix seconds).
+    pub created_at: i64,
+    /// When the record stops being valid (Unix seconds).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+    /// Base64url Ed25519 signature by `public_key`.
+    pub signature: String,
+}
+
+impl HandleRecord {
+    /// Sign a record naming the keypair's public key.
+    #[cfg(feature = "pubky")]
+    pub fn sign(
+        handle: &Handle,
+        keypair: &pubky::Keypair,
+        created_at: i64,
+        expires_at: Option<i64>,
+    ) -> Result<Self> {
+        use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+        use base64::Engine as _;
+        use ed25519_dalek::{Signer, SigningKey};
+
+        if expires_at.is_some_and(|exp| exp <= created_at) {
+            return Err(PaykitError::invalid_data(
+                "expires_at",
+                "must be after created_at",
+            ));
+        }
+
+        let mut record = Self {
+            handle: handle.to_string(),
+            public_key: keypair.public_key().to_string(),
+            created_at,
+            expires_at,
+            signature: String::new(),
+        };
+        let signing_key = SigningKey::from_bytes(&keypair.secret_key());
+        let signature = signing_key.sign(&record.signing_message());
+        record.signature = URL_SAFE_NO_PAD.encode(signature.to_bytes());
+        Ok(record)
+    }
+
+    /// Check that the record names `handle`, is valid at `now` and is signed
+    /// by the key it names. Returns that key.
+    ///
+    /// Requires the `pubky` feature; without it verification always fails.
+    pub fn verify_at(&self, handle: &Handle, now: i64) -> Result<PublicKey> {
+        let names = Handle::parse(&self.handle).is_ok_and(|named| named == *handle);
+        if !names {
+            return Err(PaykitError::ValidationFailed(format!(
+                "Handle record is for {}, not {}",
+                self.handle, handle
+            )));
+        }
+        if self.expires_at.is_some_and(|exp| now >= exp) {
+            return Err(PaykitError::ValidationFailed(format!(
+                "Handle record for {} has expired",
+                handle
+            )));
+        }
+
+        #[cfg(feature = "pubky")]
+        {
+            use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+            use base64::Engine as _;
+            use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+            let invalid = || {
+                PaykitError::ValidationFailed(format!("Invalid handle signature for {}", handle))
+            };
+            let public_key: PublicKey = self.public_key.parse().map_err(|_| invalid())?;
+            let bytes = URL_SAFE_NO_PAD
+                .decode(&self.signature)
+                .map_err(|_| invalid())?;
+            let bytes: [u8; 64] = bytes.try_into().map_err(|_| invalid())?;
+            let key = VerifyingKey::from_bytes(&public_key.to_bytes()).map_err(|_| invalid())?;
+
+            key.verify(&self.signing_message(), &Signature::from_bytes(&bytes))
+                .map_err(|_| invalid())?;
+            Ok(public_key)
+        }
+
+        #[cfg(not(feature = "pubky"))]
+        {
+            Err(PaykitError::Unimplemented(
+                "handle signature verification requires the pubky feature",
+            ))
+        }
+    }
+
+    /// Encode as a well-known JSON document.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self)
+            .map_err(|e| PaykitError::Serialization(format!("handle record: {}", e)))
+    }
+
+    /// Decode a well-known JSON document.
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| PaykitError::invalid_data("handle", format!("bad document: {}", e)))
+    }
+
+    /// Encode as a DNS TXT record value.
+    ///
+    /// The handle is implied by the record name and left out.
+    pub fn to_txt(&self) -> String {
+        let mut txt = format!(
+            "{};pk={};ts={}",
+            HANDLE_TXT_VERSION, self.public_key, self.created_at
+        );
+        if let Some(expires_at) = self.expires_at {
+            txt.push_str(&format!(";exp={}", expires_at));
+        }
+        txt.push_str(&format!(";sig={}", self.signature));
+        txt
+    }
+
+    /// Decode a DNS TXT record value found at `handle`'s record name.
+    pub fn from_txt(handle: &Handle, txt: &str) -> Result<Self> {
+        let invalid = |reason: &str| PaykitError::invalid_data("handle", reason);
+        let mut fields = txt.split(';').map(str::trim);
+        if fields.next() != Some(HANDLE_TXT_VERSION) {
+            return Err(invalid("not a paykit handle record"));
+        }
+
+        let fields: HashMap<&str, &str> = fields.filter_map(|f| f.split_once('=')).collect();
+        let field = |name: &str| {
+            fields
+                .get(name)
+                .copied()
+                .ok_or_else(|| invalid(&format!("TXT record is missing {}", name)))
+        };
+        let timestamp = |value: &str| {
+            value
+                .parse::<i64>()
+                .map_err(|_| invalid("TXT record has a bad timestamp"))
+        };
+
+        Ok(Self {
+            handle: handle.to_string(),
+            public_key: field("pk")?.to_string(),
+            created_at: timestamp(field("ts")?)?,
+            expires_at: fields.get("exp").map(|exp| timestamp(exp)).transpose()?,
+            signature: field("sig")?.to_string(),
+        })
+    }
+
+    #[cfg(feature = "pubky")]
+    fn signing_message(&self) -> Vec<u8> {
+        let mut message = HANDLE_DOMAIN.to_vec();
+        message.extend_from_slice(
+            format!(
+                "{}\n{}\n{}\n{}",
+                self.handle,
+                self.public_key,
+                self.created_at,
+                self.expires_at.map(|e| e.to_string()).unwrap_or_default()
+            )
+            .as_bytes(),
+        );
+        message
+    }
+}
+
+/// Network access used to resolve handles.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+pub trait HandleLookup: Send + Sync {
+    /// Fetch the document at an HTTPS URL, or `None` if there is none (404).
+    async fn fetch_document(&self, url: &str) -> Result<Option<String>>;
+
+    /// All TXT record values at a DNS name, empty if there are none.
+    async fn lookup_txt(&self, name: &str) -> Result<Vec<String>>;
+}
+
+/// Where a handle was resolved from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HandleSource {
+    /// The domain's well-known document
+    WellKnown,
+    /// The domain's DNS TXT record
+    Dns,
+}
+
+/// A handle resolved to a verified public key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolvedHandle {
+    /// The normalized handle.
+    pub handle: Handle,
+    /// The key the handle names.
+    pub public_key: PublicKey,
+    /// Where the record was found.
+    pub source: HandleSource,
+    /// When the record was fetched (Unix seconds).
+    pub resolved_at: i64,
+    /// When the cached result must be fetched again (Unix seconds).
+    pub cached_until: i64,
+}
+
+/// Resolves handles to public keys, caching verified results.
+pub struct HandleResolver {
+    lookup: Arc<dyn HandleLookup>,
+    ttl_secs: i64,
+    cache: RwLock<HashMap<Handle, ResolvedHandle>>,
+}
+
+impl HandleResolver {
+    /// Create a resolver with the default TTL.
+    pub fn new(lookup: Arc<dyn HandleLookup>) -> Self {
+        Self {
+            lookup,
+            ttl_secs: DEFAULT_HANDLE_TTL_SECS,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Cache verified handles for `ttl_secs` instead of the default.
+    pub fn with_ttl(mut self, ttl_secs: i64) -> Self {
+        self.ttl_secs = ttl_secs.max(0);
+        self
+    }
+
+    /// Resolve a handle now.
+    pub async fn resolve(&self, handle: &str) -> Result<ResolvedHandle> {
+        self.resolve_at(handle, chrono::Utc::now().timestamp())
+            .await
+    }
+
+    /// Resolve a handle as of `now`, from the cache if it is still fresh.
+    ///
+    /// Tries the well-known document, then DNS. Records that do not verify
+    /// are skipped; if none verifies, the first problem is returned, or
+    /// [`PaykitError::NotFound`] if nothing was published.
+    pub async fn resolve_at(&self, handle: &str, now: i64) -> Result<ResolvedHandle> {
+        let handle = Handle::parse(handle)?;
+        if let Some(cached) = self.cached(&handle, now) {
+            return Ok(cached);
+        }
+
+        let mut failure = None;
+        let Some((public_key, expires_at, source)) = self.fetch(&handle, now, &mut failure).await
+        else {
+            return Err(
+                failure.unwrap_or_else(|| PaykitError::not_found("handle", handle.to_string()))
+            );
+        };
+
+        let mut cached_until = now + self.ttl_secs;
+        if let Some(expires_at) = expires_at {
+            cached_until = cached_until.min(expires_at);
+        }
+        let resolved = ResolvedHandle {
+            handle: handle.clone(),
+            public_key,
+            source,
+            resolved_at: now,
+            cached_until,
+        };
+        self.cache
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(handle, resolved.clone());
+        Ok(resolved)
+    }
+
+    /// Drop a handle from the cache so the next resolve fetches it.
+    pub fn invalidate(&self, handle: &str) {
+        if let Ok(handle) = Handle::parse(handle) {
+            self.cache
+                .write()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(&handle);
+        }
+    }
+
+    /// Drop every cached handle.
+    pub fn clear_cache(&self) {
+        self.cache
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    fn cached(&self, handle: &Handle, now: i64) -> Option<ResolvedHandle> {
+        self.cache
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(handle)
+            .filter(|cached| now < cached.cached_until)
+            .cloned()
+    }
+
+    /// The first verified key, its record expiry and where it was found,
+    /// noting the first problem met along the way in `failure`.
+    async fn fetch(
+        &self,
+        handle: &Handle,
+        now: i64,
+        failure: &mut Option<PaykitError>,
+    ) -> Option<(PublicKey, Option<i64>, HandleSource)> {
+        let verify = |record: Result<HandleRecord>| {
+            record.and_then(|record| Ok((record.verify_at(handle, now)?, record.expires_at)))
+        };
+
+        match self.lookup.fetch_document(&handle.well_known_url()).await {
+            Ok(Some(body)) => match verify(HandleRecord::from_json(&body)) {
+                Ok((key, expires_at)) => return Some((key, expires_at, HandleSource::WellKnown)),
+                Err(e) => {
+                    failure.get_or_insert(e);
+                }
+            },
+            Ok(None) => {}
+            Err(e) => {
+                failure.get_or_insert(e);
+            }
+        }
+
+        match self.lookup.lookup_txt(&handle.dns_name()).await {
+            Ok(values) => {
+                for txt in values
+                    .iter()
+                    .filter(|txt| txt.starts_with(HANDLE_TXT_VERSION))
+                {
+                    match verify(HandleRecord::from_txt(handle, txt)) {
+                        Ok((key, expires_at)) => return Some((key, expires_at, HandleSource::Dns)),
+                        Err(e) => {
+                            failure.get_or_insert(e);
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                failure.get_or_insert(e);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(all(test, feature = "pubky"))]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct MockLookup {
+        documents: HashMap<String, String>,
+        txt: HashMap<String, Vec<String>>,
+        calls: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl HandleLookup for MockLookup {
+        async fn fetch_document(&self, url: &str) -> Result<Option<String>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(self.documents.get(url).cloned())
+        }
+
+        async fn lookup_txt(&self, name: &str) -> Result<Vec<String>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(self.txt.get(name).cloned().unwrap_or_default())
+        }
+    }
+
+    #[test]
+    fn test_handle_parse() {
+        let handle = Handle::parse(" Alice.B@Example.COM ").unwrap();
+        assert_eq!(handle.to_string(), "alice.b@example.com");
+        assert_eq!(
+            handle.well_known_url(),
+            "https://example.com/.well-known/paykit/alice.b"
+        );
+        assert_eq!(handle.dns_name(), "alice.b._paykit.example.com");
+
+        for bad in [
+            "alice",
+            "@example.com",
+            "alice@localhost",
+            "a b@x.com",
+            "a@-x.com",
+        ] {
+            assert!(!Handle::is_handle(bad), "{} should be rejected", bad);
+        }
+    }
+
+    #[test]
+    fn test_record_roundtrip_and_tampering() {
+        let keypair = pubky::Keypair::random();
+        let handle = Handle::parse("alice@example.com").unwrap();
+        let record = HandleRecord::sign(&handle, &keypair, 1_000, Some(5_000)).unwrap();
+
+        let from_json = HandleRecord::from_json(&record.to_json().unwrap()).unwrap();
+        assert_eq!(
+            from_json.verify_at(&handle, 2_000).unwrap(),
+            keypair.public_key()
+        );
+        let from_txt = HandleRecord::from_txt(&handle, &record.to_txt()).unwrap();
+        assert_eq!(from_txt, record);
+
+        // Expired, reused for another handle, or pointed at another key
+        assert!(record.verify_at(&handle, 5_000).is_err());
+        let bob = Handle::parse("bob@example.com").unwrap();
+        assert!(record.verify_at(&bob, 2_000).is_err());
+        let swapped = HandleRecord {
+            public_key: pubky::Keypair::random().public_key().to_string(),
+            ..record.clone()
+        };
+        assert!(swapped.verify_at(&handle, 2_000).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resolver_falls_back_to_dns_and_caches() {
+        let alice = pubky::Keypair::random();
+        let bob = pubky::Keypair::random();
+        let alice_handle = Handle::parse("alice@example.com").unwrap();
+        let bob_handle = Handle::parse("bob@example.com").unwrap();
+
+        let mut lookup = MockLookup::default();
+        lookup.documents.insert(
+            alice_handle.well_known_url(),
+            HandleRecord::sign(&alice_handle, &alice, 0, None)
+                .unwrap()
+                .to_json()
+                .unwrap(),
+        );
+        // Bob's domain serves a record signed by the wrong key, then a good one
+        let forged = HandleRecord {
+            public_key: bob.public_key().to_string(),
+            ..HandleRecord::sign(&bob_handle, &alice, 0, None).unwrap()
+        };
+        lookup.txt.insert(
+            bob_handle.dns_name(),
+            vec![
+                "unrelated".to_string(),
+                forged.to_txt(),
+                HandleRecord::sign(&bob_handle, &bob, 0, Some(1_500))
+                    .unwrap()
+                    .to_txt(),
+            ],
+        );
+        let lookup = Arc::new(lookup);
+        let resolver = HandleResolver::new(lookup.clone()).with_ttl(600);
+
+        let resolved = resolver
+            .resolve_at("Alice@example.com", 1_000)
+            .await
+            .unwrap();
+        assert_eq!(resolved.public_key, alice.public_key());
+        assert_eq!(resolved.source, HandleSource::WellKnown);
+
+        let resolved = resolver.resolve_at("bob@example.com", 1_000).await.unwrap();
+        assert_eq!(resolved.public_key, bob.public_key());
+        assert_eq!(resolved.source, HandleSource::Dns);
+        assert_eq!(resolved.cached_until, 1_500);
+
+        // Served from the cache until the TTL or the record expiry
+        let calls = lookup.calls.load(Ordering::SeqCst);
+        resolver
+            .resolve_at("alice@example.com", 1_599)
+            .await
+            .unwrap();
+        resolver.resolve_at("bob@example.com", 1_499).await.unwrap();
+        assert_eq!(lookup.calls.load(Ordering::SeqCst), calls);
+        resolver
+            .resolve_at("alice@example.com", 1_600)
+            .await
+            .unwrap();
+        assert!(lookup.calls.load(Ordering::SeqCst) > calls);
+        assert!(resolver.resolve_at("bob@example.com", 1_500).await.is_err());
+
+        assert!(matches!(
+            resolver.resolve_at("carol@example.com", 1_000).await,
+            Err(PaykitError::NotFound { .. })
+        ));
+    }
+}
@@ -5,16 +5,18 @@
 //!
 //! # Thread Safety
 //!
-//! The health monitor uses `RwLock` for thread-safe cache access. Public methods
-//! will panic if the internal lock is poisoned (which only happens if a thread
-//! panics while holding the lock).
+//! The health monitor caches results behind a `parking_lot::RwLock`, which
+//! cannot be poisoned. The lock is only taken to read or store a finished
+//! result and is never held across a checker's `.await`, so checks running on
+//! one task never stall cache readers on another.
 
 use crate::methods::PaymentMethodRegistry;
 use crate::MethodId;
 use async_trait::async_trait;
+use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::sync::Arc;
 
 /// Health status of a payment method.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -228,16 +230,22 @@ impl HealthMonitor {
 
     /// Get cached status for a method.
     pub fn get_status(&self, method_id: &MethodId) -> Option<HealthStatus> {
-        let cache = self.cache.read().unwrap_or_else(|e| e.into_inner());
+        let cache = self.cache.read();
         cache.get(&method_id.0).map(|r| r.status)
     }
 
     /// Get cached result for a method.
     pub fn get_result(&self, method_id: &MethodId) -> Option<HealthCheckResult> {
-        let cache = self.cache.read().unwrap_or_else(|e| e.into_inner());
+        let cache = self.cache.read();
         cache.get(&method_id.0).cloned()
     }
 
+    /// Get all cached results without running any checks.
+    pub fn cached_results(&self) -> Vec<HealthCheckResult> {
+        let cache = self.cache.read();
+        cache.values().cloned().collect()
+    }
+
     /// Check if a method is usable.
     pub fn is_usable(&self, method_id: &MethodId) -> bool {
         self.get_status(method_id)
@@ -258,7 +266,7 @@ impl HealthMonitor {
 
         // Update cache
         {
-            let mut cache = self.cache.write().unwrap_or_else(|e| e.into_inner());
+            let mut cache = self.cache.write();
             cache.insert(method_id.0.clone(), result.clone());
         }
 
@@ -274,7 +282,7 @@ impl HealthMonitor {
 
             // Update cache
             {
-                let mut cache = self.cache.write().unwrap_or_else(|e| e.into_inner());
+                let mut cache = self.cache.write();
                 cache.insert(result.method_id.0.clone(), result.clone());
             }
 
@@ -286,7 +294,7 @@ impl HealthMonitor {
 
     /// Get all healthy methods.
     pub fn get_healthy_methods(&self) -> Vec<MethodId> {
-        let cache = self.cache.read().unwrap_or_else(|e| e.into_inner());
+        let cache = self.cache.read();
         cache
             .iter()
             .filter(|(_, r)| r.status.is_healthy())
@@ -296,7 +304,7 @@ impl HealthMonitor {
 
     /// Get all usable methods.
     pub fn get_usable_methods(&self) -> Vec<MethodId> {
-        let cache = self.cache.read().unwrap_or_else(|e| e.into_inner());
+        let cache = self.cache.read();
         cache
             .iter()
             .filter(|(_, r)| r.status.is_usable())
@@ -306,7 +314,7 @@ impl HealthMonitor {
 
     /// Check if cache is stale for a method.
     pub fn is_stale(&self, method_id: &MethodId) -> bool {
-        let cache = self.cache.read().unwrap_or_else(|e| e.into_inner());
+        let cache = self.cache.read();
         if let Some(result) = cache.get(&method_id.0) {
             let now = current_timestamp();
             (now - result.checked_at) > self.cache_ttl_secs
@@ -328,13 +336,19 @@ pub struct HealthAwareSelector {
     monitor: Arc<HealthMonitor>,
     /// The method registry.
     #[allow(dead_code)]
-    registry: PaymentMethodRegistry,
+    registry: Arc<PaymentMethodRegistry>,
 }
 
 impl HealthAwareSelector {
     /// Create a new health-aware selector.
-    pub fn new(monitor: Arc<HealthMonitor>, registry: PaymentMethodRegistry) -> Self {
-        Self { monitor, registry }
+    pub fn new(
+        monitor: Arc<HealthMonitor>,
+        registry: impl Into<Arc<PaymentMethodRegistry>>,
+    ) -> Self {
+        Self {
+            monitor,
+            registry: registry.into(),
+        }
     }
 
     /// Filter methods by health status.
@@ -418,6 +432,15 @@ mod tests {
         assert_eq!(status, Some(HealthStatus::Healthy));
     }
 
+    #[tokio::test]
+    async fn test_cached_results() {
+        let monitor = HealthMonitor::with_defaults();
+        assert!(monitor.cached_results().is_empty());
+
+        monitor.check_all().await;
+        assert_eq!(monitor.cached_results().len(), 2);
+    }
+
     #[tokio::test]
     async fn test_get_usable_methods() {
         let monitor = HealthMonitor::with_defaults();
@@ -439,4 +462,95 @@ mod tests {
         let usable = selector.filter_usable(&methods);
         assert_eq!(usable.len(), 2);
     }
+
+    /// Reports a method healthy only while it is registered.
+    struct RegistryChecker {
+        registry: Arc<PaymentMethodRegistry>,
+        method_id: MethodId,
+    }
+
+    #[async_trait]
+    impl HealthChecker for RegistryChecker {
+        fn method_id(&self) -> MethodId {
+            self.method_id.clone()
+        }
+
+        async fn check(&self) -> HealthCheckResult {
+            let registered = self.registry.has_method(&self.method_id);
+            tokio::task::yield_now().await;
+            if registered {
+                HealthCheckResult::healthy(self.method_id.clone())
+            } else {
+                HealthCheckResult::unhealthy(self.method_id.clone(), "not registered")
+            }
+        }
+    }
+
+    #[test]
+    fn test_registry_and_monitor_under_contention() {
+        use std::sync::Barrier;
+        use std::thread;
+
+        const ROUNDS: usize = 200;
+        let onchain = MethodId("onchain".into());
+        let registry = Arc::new(PaymentMethodRegistry::with_defaults());
+        let mut monitor = HealthMonitor::new();
+        monitor.register(Box::new(RegistryChecker {
+            registry: registry.clone(),
+            method_id: onchain.clone(),
+        }));
+        let monitor = Arc::new(monitor);
+        let barrier = Arc::new(Barrier::new(4));
+
+        // Churns the registry while the other threads read it.
+        let writer = {
+            let (registry, barrier) = (registry.clone(), barrier.clone());
+            thread::spawn(move || {
+                barrier.wait();
+                for _ in 0..ROUNDS {
+                    registry.register(Box::new(crate::methods::OnchainPlugin::new()));
+                    registry.unregister(&MethodId("onchain".into()));
+                }
+                registry.register(Box::new(crate::methods::OnchainPlugin::new()));
+            })
+        };
+
+        // Runs checks from inside `block_on`, as the mobile client does.
+        let checkers: Vec<_> = (0..2)
+            .map(|_| {
+                let (monitor, barrier) = (monitor.clone(), barrier.clone());
+                thread::spawn(move || {
+                    let runtime = tokio::runtime::Builder::new_current_thread()
+                        .build()
+                        .unwrap();
+                    barrier.wait();
+                    for _ in 0..ROUNDS {
+                        runtime.block_on(monitor.check_all());
+                    }
+                })
+            })
+            .collect();
+
+        // Filters registered plugins by cached health, nesting both locks.
+        barrier.wait();
+        for _ in 0..ROUNDS {
+            let usable = registry.filter(|p| monitor.is_usable(&p.method_id()));
+            assert!(usable.len() <= 2);
+            assert!(monitor
+                .get_result(&onchain)
+                .is_none_or(|r| r.method_id == onchain));
+        }
+
+        writer.join().unwrap();
+        for checker in checkers {
+            checker.join().unwrap();
+        }
+
+        // Once the churn settles, a fresh check sees the final registration.
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(monitor.check_all());
+        assert_eq!(monitor.get_status(&onchain), Some(HealthStatus::Healthy));
+    }
 }
@@ -0,0 +1,375 @@
+//! Localized user-facing messages.
+//!
+//! Selection reasons and errors carry a [`LocalizedMessage`]: a stable code
+//! plus named parameters. Apps keep showing the English text they already
+//! get, or format the message for the user's [`Locale`] from the built-in
+//! catalog (English, Spanish, German).
+//!
+//! Parameters are substituted verbatim. Free-text parameters such as
+//! underlying error details are not translated.
+//!
+//! # Example
+//!
+//! ```
+//! use paykit_lib::i18n::{Locale, LocalizedMessage};
+//!
+//! let message = LocalizedMessage::new("selection.cost_optimized").with_param("method", "Lightning");
+//! assert_eq!(message.format(Locale::En), "Selected Lightning for lowest fees");
+//! assert_eq!(
+//!     message.format(Locale::from_tag("es-MX")),
+//!     "Se seleccionó Lightning por tener las comisiones más bajas"
+//! );
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Languages with a built-in catalog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    /// English (the fallback).
+    #[default]
+    En,
+    /// Spanish.
+    Es,
+    /// German.
+    De,
+}
+
+impl Locale {
+    /// All supported locales.
+    pub const ALL: [Locale; 3] = [Locale::En, Locale::Es, Locale::De];
+
+    /// Resolve a BCP 47 tag such as `"de-AT"` or `"es_ES"`.
+    ///
+    /// Unsupported languages fall back to English.
+    pub fn from_tag(tag: &str) -> Self {
+        let language = tag
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match language.as_str() {
+            "es" => Locale::Es,
+            "de" => Locale::De,
+            _ => Locale::En,
+        }
+    }
+
+    /// Two-letter language code.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Es => "es",
+            Locale::De => "de",
+        }
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A message code with named parameters.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalizedMessage {
+    /// Stable code, e.g. `"selection.balanced"` or `"error.not_found"`.
+    pub code: String,
+    /// Values substituted into `{name}` placeholders.
+    #[serde(default)]
+    pub params: BTreeMap<String, String>,
+}
+
+impl LocalizedMessage {
+    /// Create a message without parameters.
+    pub fn new(code: impl Into<String>) -> Self {
+        Self {
+            code: code.into(),
+            params: BTreeMap::new(),
+        }
+    }
+
+    /// Add a parameter.
+    pub fn with_param(mut self, name: impl Into<String>, value: impl ToString) -> Self {
+        self.params.insert(name.into(), value.to_string());
+        self
+    }
+
+    /// Format the message for `locale`.
+    ///
+    /// Falls back to English when the locale has no entry, and to the code
+    /// itself for unknown codes.
+    pub fn format(&self, locale: Locale) -> String {
+        let template =
+            match template(&self.code, locale).or_else(|| template(&self.code, Locale::En)) {
+                Some(template) => template,
+                None => return self.code.clone(),
+            };
+
+        let mut out = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            match after.find('}') {
+                Some(end) => {
+                    let name = &after[..end];
+                    match self.params.get(name) {
+                        Some(value) => out.push_str(value),
+                        None => out.push_str(&rest[start..start + end + 2]),
+                    }
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    out.push_str(&rest[start..]);
+                    rest = "";
+                }
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+impl fmt::Display for LocalizedMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.format(Locale::En))
+    }
+}
+
+/// Format several messages as one sentence, space-separated.
+pub fn format_all(messages: &[LocalizedMessage], locale: Locale) -> String {
+    messages
+        .iter()
+        .map(|m| m.format(locale))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Look up the catalog entry for a code.
+fn template(code: &str, locale: Locale) -> Option<&'static str> {
+    use Locale::*;
+
+    let text = match (code, locale) {
+        // Selection reasons
+        ("selection.balanced", En) => "Selected {method} as best balanced option",
+        ("selection.balanced", Es) => "Se seleccionó {method} como la opción más equilibrada",
+        ("selection.balanced", De) => "{method} als ausgewogenste Option ausgewählt",
+        ("selection.cost_optimized", En) => "Selected {method} for lowest fees",
+        ("selection.cost_optimized", Es) => {
+            "Se seleccionó {method} por tener las comisiones más bajas"
+        }
+        ("selection.cost_optimized", De) => "{method} wegen der niedrigsten Gebühren ausgewählt",
+        ("selection.speed_optimized", En) => "Selected {method} for fastest confirmation",
+        ("selection.speed_optimized", Es) => {
+            "Se seleccionó {method} por la confirmación más rápida"
+        }
+        ("selection.speed_optimized", De) => {
+            "{method} wegen der schnellsten Bestätigung ausgewählt"
+        }
+        ("selection.privacy_optimized", En) => "Selected {method} for best privacy",
+        ("selection.privacy_optimized", Es) => {
+            "Se seleccionó {method} por ofrecer la mejor privacidad"
+        }
+        ("selection.privacy_optimized", De) => "{method} wegen des besten Datenschutzes ausgewählt",
+        ("selection.priority_list", En) => "Selected {method} from priority list",
+        ("selection.priority_list", Es) => "Se seleccionó {method} de la lista de prioridades",
+        ("selection.priority_list", De) => "{method} aus der Prioritätenliste ausgewählt",
+        ("selection.probe_no_route", En) => "(no reliable Lightning route found)",
+        ("selection.probe_no_route", Es) => "(no se encontró una ruta Lightning fiable)",
+        ("selection.probe_no_route", De) => "(keine zuverlässige Lightning-Route gefunden)",
+        ("selection.probe_route", En) => "(route probe: {success}% success, ~{fee_sats} sat fee)",
+        ("selection.probe_route", Es) => {
+            "(sondeo de ruta: {success}% de éxito, comisión de ~{fee_sats} sat)"
+        }
+        ("selection.probe_route", De) => "(Routentest: {success}% Erfolg, ~{fee_sats} sat Gebühr)",
+        ("selection.payee_preferred", En) => "(preferred by the payee right now)",
+        ("selection.payee_preferred", Es) => "(preferido por el beneficiario en este momento)",
+        ("selection.payee_preferred", De) => "(derzeit vom Empfänger bevorzugt)",
+        ("selection.payee_unavailable", En) => "(payee marked {method} unavailable)",
+        ("selection.payee_unavailable", Es) => "(el beneficiario marcó {method} como no disponible)",
+        ("selection.payee_unavailable", De) => "(Empfänger hat {method} als nicht verfügbar markiert)",
+        ("selection.payee_paused", En) => "(payee is not accepting payments right now)",
+        ("selection.payee_paused", Es) => "(el beneficiario no acepta pagos en este momento)",
+        ("selection.payee_paused", De) => "(Empfänger nimmt derzeit keine Zahlungen an)",
+        ("selection.payee_hint", En) => "(ranked first by the payee)",
+        ("selection.payee_hint", Es) => "(el beneficiario lo indica como primera opción)",
+        ("selection.payee_hint", De) => "(vom Empfänger an erster Stelle empfohlen)",
+
+        // Errors
+        ("error.unimplemented", En) => "{feature} is not implemented yet",
+        ("error.unimplemented", Es) => "{feature} aún no está implementado",
+        ("error.unimplemented", De) => "{feature} ist noch nicht implementiert",
+        ("error.transport", En) => "transport error: {detail}",
+        ("error.transport", Es) => "error de transporte: {detail}",
+        ("error.transport", De) => "Transportfehler: {detail}",
+        ("error.connection_failed", En) => "connection to {target} failed: {reason}",
+        ("error.connection_failed", Es) => "falló la conexión con {target}: {reason}",
+        ("error.connection_failed", De) => "Verbindung zu {target} fehlgeschlagen: {reason}",
+        ("error.connection_timeout", En) => "{operation} timed out after {timeout_ms}ms",
+        ("error.connection_timeout", Es) => {
+            "{operation} superó el tiempo de espera tras {timeout_ms} ms"
+        }
+        ("error.connection_timeout", De) => "Zeitüberschreitung bei {operation} nach {timeout_ms} ms",
+        ("error.auth", En) => "authentication error: {detail}",
+        ("error.auth", Es) => "error de autenticación: {detail}",
+        ("error.auth", De) => "Authentifizierungsfehler: {detail}",
+        ("error.session_expired", En) => "session expired, please re-authenticate",
+        ("error.session_expired", Es) => "la sesión caducó, vuelve a autenticarte",
+        ("error.session_expired", De) => "Sitzung abgelaufen, bitte erneut anmelden",
+        ("error.invalid_credentials", En) => "invalid credentials: {detail}",
+        ("error.invalid_credentials", Es) => "credenciales no válidas: {detail}",
+        ("error.invalid_credentials", De) => "ungültige Anmeldedaten: {detail}",
+        ("error.not_found", En) => "{resource_type} not found: {identifier}",
+        ("error.not_found", Es) => "{resource_type} no encontrado: {identifier}",
+        ("error.not_found", De) => "{resource_type} nicht gefunden: {identifier}",
+        ("error.method_not_supported", En) => "payment method not supported: {method}",
+        ("error.method_not_supported", Es) => "método de pago no compatible: {method}",
+        ("error.method_not_supported", De) => "Zahlungsmethode nicht unterstützt: {method}",
+        ("error.invalid_data", En) => "invalid {field}: {reason}",
+        ("error.invalid_data", Es) => "{field} no válido: {reason}",
+        ("error.invalid_data", De) => "ungültiges Feld {field}: {reason}",
+        ("error.validation_failed", En) => "validation failed: {detail}",
+        ("error.validation_failed", Es) => "la validación falló: {detail}",
+        ("error.validation_failed", De) => "Validierung fehlgeschlagen: {detail}",
+        ("error.serialization", En) => "serialization error: {detail}",
+        ("error.serialization", Es) => "error de serialización: {detail}",
+        ("error.serialization", De) => "Serialisierungsfehler: {detail}",
+        ("error.payment", En) => "payment failed: {reason}",
+        ("error.payment", Es) => "el pago falló: {reason}",
+        ("error.payment", De) => "Zahlung fehlgeschlagen: {reason}",
+        ("error.payment_with_id", En) => "payment {payment_id} failed: {reason}",
+        ("error.payment_with_id", Es) => "el pago {payment_id} falló: {reason}",
+        ("error.payment_with_id", De) => "Zahlung {payment_id} fehlgeschlagen: {reason}",
+        ("error.insufficient_funds", En) => {
+            "insufficient funds: need {required} {currency}, have {available} {currency}"
+        }
+        ("error.insufficient_funds", Es) => {
+            "fondos insuficientes: se necesitan {required} {currency}, hay {available} {currency}"
+        }
+        ("error.insufficient_funds", De) => {
+            "unzureichendes Guthaben: benötigt {required} {currency}, verfügbar {available} {currency}"
+        }
+        ("error.invoice_expired", En) => "invoice {invoice_id} expired at timestamp {expired_at}",
+        ("error.invoice_expired", Es) => {
+            "la factura {invoice_id} caducó en la marca de tiempo {expired_at}"
+        }
+        ("error.invoice_expired", De) => {
+            "Rechnung {invoice_id} ist zum Zeitstempel {expired_at} abgelaufen"
+        }
+        ("error.payment_rejected", En) => "payment {payment_id} rejected: {reason}",
+        ("error.payment_rejected", Es) => "pago {payment_id} rechazado: {reason}",
+        ("error.payment_rejected", De) => "Zahlung {payment_id} abgelehnt: {reason}",
+        ("error.payment_already_completed", En) => "payment {payment_id} already completed",
+        ("error.payment_already_completed", Es) => "el pago {payment_id} ya se completó",
+        ("error.payment_already_completed", De) => "Zahlung {payment_id} bereits abgeschlossen",
+        ("error.fee_spike", En) => "{method} fee {fee} is above the limit of {limit}",
+        ("error.fee_spike", Es) => "la comisión {method} de {fee} supera el límite de {limit}",
+        ("error.fee_spike", De) => "{method}-Gebühr {fee} liegt über dem Limit von {limit}",
+        ("error.storage", En) => "storage error: {detail}",
+        ("error.storage", Es) => "error de almacenamiento: {detail}",
+        ("error.storage", De) => "Speicherfehler: {detail}",
+        ("error.quota_exceeded", En) => "quota exceeded: using {used} of {limit} allowed",
+        ("error.quota_exceeded", Es) => "cuota superada: se usan {used} de {limit} permitidos",
+        ("error.quota_exceeded", De) => "Kontingent überschritten: {used} von {limit} belegt",
+        ("error.rate_limited", En) => "rate limited, retry after {retry_after_ms}ms",
+        ("error.rate_limited", Es) => {
+            "límite de solicitudes alcanzado, reintenta en {retry_after_ms} ms"
+        }
+        ("error.rate_limited", De) => {
+            "Anfragelimit erreicht, erneut versuchen in {retry_after_ms} ms"
+        }
+        ("error.internal", En) => "internal error: {detail}",
+        ("error.internal", Es) => "error interno: {detail}",
+        ("error.internal", De) => "interner Fehler: {detail}",
+        _ => return None,
+    };
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_from_tag() {
+        assert_eq!(Locale::from_tag("de-AT"), Locale::De);
+        assert_eq!(Locale::from_tag("es_ES"), Locale::Es);
+        assert_eq!(Locale::from_tag("EN"), Locale::En);
+        assert_eq!(Locale::from_tag("fr-FR"), Locale::En);
+        assert_eq!(Locale::from_tag(""), Locale::En);
+    }
+
+    #[test]
+    fn test_format_substitutes_params() {
+        let message = LocalizedMessage::new("error.not_found")
+            .with_param("resource_type", "endpoint")
+            .with_param("identifier", "lightning");
+        assert_eq!(message.to_string(), "endpoint not found: lightning");
+        assert_eq!(
+            message.format(Locale::De),
+            "endpoint nicht gefunden: lightning"
+        );
+
+        // Missing params stay visible rather than vanishing
+        let partial = LocalizedMessage::new("error.not_found").with_param("identifier", "x");
+        assert_eq!(partial.to_string(), "{resource_type} not found: x");
+
+        // Unknown codes format as the code
+        assert_eq!(LocalizedMessage::new("nope").format(Locale::Es), "nope");
+    }
+
+    #[test]
+    fn test_every_locale_covers_english_codes() {
+        let codes = [
+            "selection.balanced",
+            "selection.cost_optimized",
+            "selection.speed_optimized",
+            "selection.privacy_optimized",
+            "selection.priority_list",
+            "selection.probe_no_route",
+            "selection.probe_route",
+            "selection.payee_preferred",
+            "selection.payee_unavailable",
+            "selection.payee_paused",
+            "selection.payee_hint",
+            "error.unimplemented",
+            "error.transport",
+            "error.connection_failed",
+            "error.connection_timeout",
+            "error.auth",
+            "error.session_expired",
+            "error.invalid_credentials",
+            "error.not_found",
+            "error.method_not_supported",
+            "error.invalid_data",
+            "error.validation_failed",
+            "error.serialization",
+            "error.payment",
+            "error.payment_with_id",
+            "error.insufficient_funds",
+            "error.invoice_expired",
+            "error.payment_rejected",
+            "error.payment_already_completed",
+            "error.fee_spike",
+            "error.storage",
+            "error.quota_exceeded",
+            "error.rate_limited",
+            "error.internal",
+        ];
+        for code in codes {
+            for locale in Locale::ALL {
+                assert!(
+                    template(code, locale).is_some(),
+                    "{} missing {}",
+                    locale,
+                    code
+                );
+            }
+        }
+    }
+}
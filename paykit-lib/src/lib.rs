@@ -9,6 +9,30 @@
 //! - **Payment Method Plugins**: Extensible system for adding new payment methods
 //! - **Transport Abstraction**: Trait-based design for custom transport implementations
 //!
+//! # Cargo Features
+//!
+//! The directory protocol, payment method plugins and transports are always
+//! built. Optional subsystems are enabled by default and can be dropped with
+//! `default-features = false` to slim down embedded and wasm builds:
+//!
+//! | Feature             | Modules                                                                  |
+//! |---------------------|--------------------------------------------------------------------------|
+//! | `pubky`             | Pubky transport adapters                                                 |
+//! | `health`            | [`health`]                                                               |
+//! | `routing`           | [`routing`]                                                              |
+//! | `rotation`          | [`rotation`]                                                             |
+//! | `private-endpoints` | [`private_endpoints`], [`prefetch`]                                      |
+//! | `selection`         | [`selection`], [`standing_orders`], [`scheduled_payments`], [`vouchers`] |
+//!
+//! The subsystems are independent of each other, so any combination builds.
+//! Common choices:
+//!
+//! - `default-features = false`: directory reads and writes over a custom
+//!   transport, the smallest build
+//! - `features = ["selection"]`: a payer picking methods without Pubky
+//! - `features = ["pubky", "private-endpoints"]`: a payee wallet sharing
+//!   per-contact endpoints
+//!
 //! # Example
 //!
 //! ```ignore
@@ -35,19 +59,45 @@ pub use pubky::PublicKey;
 #[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub struct PublicKey(pub String);
 
+pub mod approvals;
+pub mod audit;
+#[cfg(feature = "key-backup")]
+pub mod backup;
+pub mod contact_verification;
 pub mod errors;
 pub mod executors;
+pub mod formatting;
+pub mod handles;
+#[cfg(feature = "health")]
 pub mod health;
+pub mod i18n;
 pub mod methods;
+pub mod policy;
+#[cfg(feature = "private-endpoints")]
+pub mod prefetch;
 pub mod prelude;
+#[cfg(feature = "private-endpoints")]
 pub mod private_endpoints;
 pub mod protocol;
+pub mod proxy;
+pub mod quota;
+pub mod rescue;
+#[cfg(feature = "rotation")]
 pub mod rotation;
+#[cfg(feature = "routing")]
 pub mod routing;
+#[cfg(feature = "selection")]
+pub mod scheduled_payments;
 pub mod secure_storage;
+#[cfg(feature = "selection")]
 pub mod selection;
+#[cfg(feature = "selection")]
+pub mod standing_orders;
 mod transport;
+pub mod treasury;
 pub mod uri;
+#[cfg(feature = "selection")]
+pub mod vouchers;
 
 /// Test utilities for payment testing.
 ///
@@ -57,6 +107,7 @@ pub mod test_utils;
 
 pub use errors::{PaykitError, PaykitErrorCode};
 pub use transport::{AuthenticatedTransport, UnauthenticatedTransportRead};
+pub use transport::{ContactPager, DirectoryPager, Page, PaymentPager, DEFAULT_PAGE_LIMIT};
 pub use uri::{parse_uri, PaykitUri};
 
 /// Pubky adapters are only exposed when the default `pubky` feature is enabled.
@@ -113,6 +164,64 @@ impl MethodId {
     pub fn lightning() -> Self {
         Self::new(Self::LIGHTNING)
     }
+
+    /// Separator between the segments of a namespaced method ID.
+    pub const NAMESPACE_SEPARATOR: char = '.';
+
+    /// Longest accepted method ID, in bytes.
+    pub const MAX_LEN: usize = 128;
+
+    /// Whether this is one of the built-in method IDs.
+    pub fn is_builtin(&self) -> bool {
+        self.0 == Self::ONCHAIN || self.0 == Self::LIGHTNING
+    }
+
+    /// Whether the ID is namespaced, e.g. `org.example.voucher`.
+    ///
+    /// Un-namespaced IDs are reserved for methods defined by Paykit itself;
+    /// third-party methods should use a reverse-domain namespace they own.
+    pub fn is_namespaced(&self) -> bool {
+        self.0.contains(Self::NAMESPACE_SEPARATOR)
+    }
+
+    /// The namespace of the ID (`org.example` for `org.example.voucher`).
+    pub fn namespace(&self) -> Option<&str> {
+        self.0
+            .rsplit_once(Self::NAMESPACE_SEPARATOR)
+            .map(|(namespace, _)| namespace)
+    }
+
+    /// Check the ID's syntax.
+    ///
+    /// IDs are at most [`MAX_LEN`](Self::MAX_LEN) bytes of `.`-separated,
+    /// non-empty segments of lowercase ASCII letters, digits, `-` and `_`.
+    pub fn validate(&self) -> Result<()> {
+        if self.0.is_empty() {
+            return Err(PaykitError::invalid_data(
+                "method_id",
+                "Method ID must not be empty",
+            ));
+        }
+        if self.0.len() > Self::MAX_LEN {
+            return Err(PaykitError::invalid_data(
+                "method_id",
+                format!("Method ID is longer than {} bytes", Self::MAX_LEN),
+            ));
+        }
+        let valid_segment = |segment: &str| {
+            !segment.is_empty()
+                && segment
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-' || b == b'_')
+        };
+        if !self.0.split(Self::NAMESPACE_SEPARATOR).all(valid_segment) {
+            return Err(PaykitError::invalid_data(
+                "method_id",
+                format!("Invalid method ID: {}", self.0),
+            ));
+        }
+        Ok(())
+    }
 }
 
 impl From<&str> for MethodId {
@@ -404,11 +513,11 @@ where
 {
     match reader.get(payee, SUPPORTED_SNAPSHOT_PATH).await {
         Ok(Some(content)) => {
-            let entries: Vec<SupportedPaymentEntry> =
-                serde_json::from_str(&content).map_err(|e| PaykitError::InvalidData {
-                    field: "supported.json".into(),
-                    reason: format!("failed to parse snapshot: {}", e),
-                })?;
+            let entries: Vec<SupportedPaymentEntry> = protocol::parse_document(
+                "supported.json",
+                content.as_bytes(),
+                protocol::MAX_DOCUMENT_BYTES,
+            )?;
             Ok(Some(entries))
         }
         Ok(None) => Ok(None),
@@ -492,6 +601,89 @@ where
         .map_err(|err| map_transport_error("get_known_contacts", err))
 }
 
+/// Returns one page of the contacts a given public key follows.
+///
+/// Pass the previous page's `next_cursor` to continue. Use [`ContactPager`]
+/// to walk all pages lazily.
+///
+/// # Examples
+/// ```
+/// # use paykit_lib::{get_known_contacts_page, PublicKey};
+/// # use paykit_lib::UnauthenticatedTransportRead;
+/// # async fn contacts(reader: &impl UnauthenticatedTransportRead, pk: &PublicKey) -> paykit_lib::Result<()> {
+/// let mut cursor = None;
+/// loop {
+///     let page = get_known_contacts_page(reader, pk, cursor.as_deref(), 500).await?;
+///     for contact in &page.items {
+///         println!("known contact: {}", contact);
+///     }
+///     match page.next_cursor {
+///         Some(next) => cursor = Some(next),
+///         None => break,
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(feature = "tracing", tracing::instrument(skip(reader)))]
+pub async fn get_known_contacts_page<R>(
+    reader: &R,
+    key: &PublicKey,
+    cursor: Option<&str>,
+    limit: u16,
+) -> Result<Page<PublicKey>>
+where
+    R: UnauthenticatedTransportRead,
+{
+    reader
+        .list_directory_page(key, transport::paging::FOLLOWS_DIR, cursor, limit)
+        .await
+        .map(|page| Page {
+            items: page
+                .items
+                .iter()
+                .filter_map(|name| transport::paging::parse_contact(name))
+                .collect(),
+            next_cursor: page.next_cursor,
+        })
+        .map_err(|err| map_transport_error("get_known_contacts_page", err))
+}
+
+/// Returns one page of the payment endpoints `payee` publishes.
+///
+/// Endpoints are fetched only for the entries in this page. Use
+/// [`PaymentPager`] to walk all pages lazily.
+#[cfg_attr(feature = "tracing", tracing::instrument(skip(reader)))]
+pub async fn get_payment_list_page<R>(
+    reader: &R,
+    payee: &PublicKey,
+    cursor: Option<&str>,
+    limit: u16,
+) -> Result<Page<(MethodId, EndpointData)>>
+where
+    R: UnauthenticatedTransportRead,
+{
+    let page = reader
+        .list_directory_page(payee, transport::paging::PAYMENTS_DIR, cursor, limit)
+        .await
+        .map_err(|err| map_transport_error("get_payment_list_page", err))?;
+    let mut items = Vec::with_capacity(page.items.len());
+    for name in page.items {
+        let method = MethodId(name);
+        if let Some(endpoint) = reader
+            .fetch_payment_endpoint(payee, &method)
+            .await
+            .map_err(|err| map_transport_error("get_payment_list_page", err))?
+        {
+            items.push((method, endpoint));
+        }
+    }
+    Ok(Page {
+        items,
+        next_cursor: page.next_cursor,
+    })
+}
+
 fn map_transport_error(label: &'static str, err: PaykitError) -> PaykitError {
     match err {
         PaykitError::Transport(msg) => PaykitError::Transport(format!("{label}: {msg}")),
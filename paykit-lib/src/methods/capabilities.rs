@@ -0,0 +1,160 @@
+//! Plugin Capability Descriptors
+//!
+//! Plugins describe what they can do with a [`PluginCapabilities`] value,
+//! so callers can branch on features (can it execute? probe routes? does it
+//! settle on-chain?) instead of comparing method IDs against "onchain" or
+//! "lightning". Third-party plugins get sensible behavior in selection by
+//! declaring their capabilities rather than being treated as unknown.
+
+use super::traits::Amount;
+use crate::MethodId;
+use serde::{Deserialize, Serialize};
+
+/// Where a payment method settles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettlementKind {
+    /// Settles in a public ledger (e.g. Bitcoin transactions).
+    OnChain,
+    /// Settles off-chain with no public record (e.g. Lightning).
+    OffChain,
+    /// Anything else, or not declared.
+    Other,
+}
+
+/// What a payment method plugin supports.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginCapabilities {
+    /// Where payments settle.
+    pub settlement: SettlementKind,
+    /// Whether `execute_payment` moves real funds (false for placeholder results).
+    pub execution: bool,
+    /// Whether `generate_proof` produces a verifiable proof.
+    pub proofs: bool,
+    /// Whether `generate_endpoint` can mint fresh private endpoints.
+    pub private_endpoints: bool,
+    /// Whether endpoints can be probed for a route before paying.
+    pub route_probing: bool,
+    /// Smallest payable amount in sats, if limited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_amount_sats: Option<u64>,
+    /// Largest payable amount in sats, if limited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_amount_sats: Option<u64>,
+}
+
+impl PluginCapabilities {
+    /// Capabilities of a plugin that executes payments and produces proofs.
+    pub fn new(settlement: SettlementKind) -> Self {
+        Self {
+            settlement,
+            execution: true,
+            proofs: true,
+            private_endpoints: false,
+            route_probing: false,
+            min_amount_sats: None,
+            max_amount_sats: None,
+        }
+    }
+
+    /// Set whether payments are really executed.
+    pub fn with_execution(mut self, execution: bool) -> Self {
+        self.execution = execution;
+        self
+    }
+
+    /// Set whether verifiable proofs are produced.
+    pub fn with_proofs(mut self, proofs: bool) -> Self {
+        self.proofs = proofs;
+        self
+    }
+
+    /// Declare support for generating private endpoints.
+    pub fn with_private_endpoints(mut self) -> Self {
+        self.private_endpoints = true;
+        self
+    }
+
+    /// Declare support for route probing.
+    pub fn with_route_probing(mut self) -> Self {
+        self.route_probing = true;
+        self
+    }
+
+    /// Limit the payable amount range in sats.
+    pub fn with_amount_range(mut self, min_sats: Option<u64>, max_sats: Option<u64>) -> Self {
+        self.min_amount_sats = min_sats;
+        self.max_amount_sats = max_sats;
+        self
+    }
+
+    /// Whether the payment settles on-chain.
+    pub fn is_on_chain(&self) -> bool {
+        self.settlement == SettlementKind::OnChain
+    }
+
+    /// Whether the payment settles off-chain.
+    pub fn is_off_chain(&self) -> bool {
+        self.settlement == SettlementKind::OffChain
+    }
+
+    /// Whether `amount` falls inside the declared range.
+    ///
+    /// Amounts not denominated in sats are always allowed.
+    pub fn allows_amount(&self, amount: &Amount) -> bool {
+        if amount.currency.to_uppercase() != "SAT" {
+            return true;
+        }
+        let Some(sats) = amount.as_u64() else {
+            return true;
+        };
+        self.min_amount_sats.is_none_or(|min| sats >= min)
+            && self.max_amount_sats.is_none_or(|max| sats <= max)
+    }
+}
+
+impl Default for PluginCapabilities {
+    fn default() -> Self {
+        Self::new(SettlementKind::Other)
+    }
+}
+
+/// A registered method and what it can do.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MethodDescriptor {
+    /// The method ID.
+    pub method_id: MethodId,
+    /// Human-readable name.
+    pub display_name: String,
+    /// Description of the method.
+    pub description: String,
+    /// The plugin's capabilities.
+    pub capabilities: PluginCapabilities,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities_amount_range() {
+        let caps = PluginCapabilities::new(SettlementKind::OffChain)
+            .with_amount_range(Some(10), Some(1_000));
+
+        assert!(caps.allows_amount(&Amount::sats(10)));
+        assert!(caps.allows_amount(&Amount::sats(1_000)));
+        assert!(!caps.allows_amount(&Amount::sats(9)));
+        assert!(!caps.allows_amount(&Amount::sats(1_001)));
+        assert!(caps.allows_amount(&Amount::new("5", "USD")));
+        assert!(caps.is_off_chain());
+    }
+
+    #[test]
+    fn test_capabilities_serialization() {
+        let caps = PluginCapabilities::new(SettlementKind::OnChain).with_route_probing();
+        let json = serde_json::to_value(&caps).unwrap();
+        assert_eq!(json["settlement"], "on_chain");
+        assert_eq!(json["route_probing"], true);
+        assert!(json.get("min_amount_sats").is_none());
+    }
+}
@@ -0,0 +1,227 @@
+//! Registry Weighting Configuration
+//!
+//! Applications tune the default ordering of payment methods with a
+//! [`RegistryConfig`]: a base weight per method, optionally limited to an
+//! amount range. The selector adds the applicable weight to every method's
+//! score after the strategy has scored it, so the configuration acts as a
+//! layer independent of the strategy; among equal scores it also decides
+//! the order.
+//!
+//! The configuration lives on the [`PaymentMethodRegistry`](super::PaymentMethodRegistry),
+//! so changing it at runtime affects every selector sharing the registry.
+//!
+//! # Example
+//!
+//! Always prefer Lightning unless the amount exceeds 500k sats:
+//!
+//! ```json
+//! {
+//!   "methods": {
+//!     "lightning": { "weight": 30, "max_amount_sats": 500000 },
+//!     "onchain": { "weight": 30, "min_amount_sats": 500001 }
+//!   }
+//! }
+//! ```
+//!
+//! The same configuration in TOML (with the `toml-config` feature):
+//!
+//! ```toml
+//! [methods.lightning]
+//! weight = 30
+//! max_amount_sats = 500000
+//! ```
+
+use super::traits::Amount;
+use crate::{MethodId, PaykitError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Base weight of one method.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MethodWeight {
+    /// Points added to the method's selection score (may be negative).
+    pub weight: f64,
+    /// Only apply the weight from this amount in sats upwards.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_amount_sats: Option<u64>,
+    /// Only apply the weight up to this amount in sats.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_amount_sats: Option<u64>,
+}
+
+impl MethodWeight {
+    /// A weight that applies to every amount.
+    pub fn new(weight: f64) -> Self {
+        Self {
+            weight,
+            min_amount_sats: None,
+            max_amount_sats: None,
+        }
+    }
+
+    /// Limit the weight to an amount range in sats.
+    pub fn with_amount_range(mut self, min_sats: Option<u64>, max_sats: Option<u64>) -> Self {
+        self.min_amount_sats = min_sats;
+        self.max_amount_sats = max_sats;
+        self
+    }
+
+    /// Reject a non-finite weight or an inverted amount range for `method_id`.
+    pub fn validate(&self, method_id: &MethodId) -> Result<()> {
+        if !self.weight.is_finite() {
+            return Err(PaykitError::invalid_data(
+                "weight",
+                format!("Weight of {} must be a finite number", method_id.0),
+            ));
+        }
+        if let (Some(min), Some(max)) = (self.min_amount_sats, self.max_amount_sats) {
+            if min > max {
+                return Err(PaykitError::invalid_data(
+                    "min_amount_sats",
+                    format!("Amount range of {} is empty", method_id.0),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The weight for paying `amount`; zero outside the amount range.
+    ///
+    /// Amounts that are not whole sats get the weight only if no range is set.
+    pub fn weight_for(&self, amount: &Amount) -> f64 {
+        let sats = amount
+            .as_u64()
+            .filter(|_| amount.currency.eq_ignore_ascii_case("SAT"));
+        let applies = match sats {
+            Some(sats) => {
+                self.min_amount_sats.is_none_or(|min| sats >= min)
+                    && self.max_amount_sats.is_none_or(|max| sats <= max)
+            }
+            None => self.min_amount_sats.is_none() && self.max_amount_sats.is_none(),
+        };
+        if applies {
+            self.weight
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Per-method weights applied on top of the selection strategy.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RegistryConfig {
+    /// Weights keyed by method ID.
+    #[serde(default)]
+    pub methods: BTreeMap<String, MethodWeight>,
+}
+
+impl RegistryConfig {
+    /// An empty configuration; every method weighs zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a configuration from JSON.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let config: Self =
+            serde_json::from_str(json).map_err(|e| PaykitError::Serialization(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Serialize the configuration to JSON.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(|e| PaykitError::Serialization(e.to_string()))
+    }
+
+    /// Parse a configuration from TOML.
+    #[cfg(feature = "toml-config")]
+    pub fn from_toml(toml: &str) -> Result<Self> {
+        let config: Self =
+            toml::from_str(toml).map_err(|e| PaykitError::Serialization(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Serialize the configuration to TOML.
+    #[cfg(feature = "toml-config")]
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string(self).map_err(|e| PaykitError::Serialization(e.to_string()))
+    }
+
+    /// Set the weight of a method, replacing any previous one.
+    pub fn set_weight(&mut self, method_id: &MethodId, weight: MethodWeight) {
+        self.methods.insert(method_id.0.clone(), weight);
+    }
+
+    /// Builder form of [`set_weight`](Self::set_weight).
+    pub fn with_weight(mut self, method_id: &MethodId, weight: MethodWeight) -> Self {
+        self.set_weight(method_id, weight);
+        self
+    }
+
+    /// Remove the weight of a method, returning it if there was one.
+    pub fn remove_weight(&mut self, method_id: &MethodId) -> Option<MethodWeight> {
+        self.methods.remove(&method_id.0)
+    }
+
+    /// The weight of a method for paying `amount`.
+    pub fn weight_for(&self, method_id: &MethodId, amount: &Amount) -> f64 {
+        self.methods
+            .get(&method_id.0)
+            .map_or(0.0, |weight| weight.weight_for(amount))
+    }
+
+    /// Reject non-finite weights and inverted amount ranges.
+    pub fn validate(&self) -> Result<()> {
+        self.methods
+            .iter()
+            .try_for_each(|(method_id, weight)| weight.validate(&MethodId::new(method_id.as_str())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weight_amount_range() {
+        let config = RegistryConfig::new().with_weight(
+            &MethodId::lightning(),
+            MethodWeight::new(30.0).with_amount_range(None, Some(500_000)),
+        );
+
+        assert_eq!(
+            config.weight_for(&MethodId::lightning(), &Amount::sats(1_000)),
+            30.0
+        );
+        assert_eq!(
+            config.weight_for(&MethodId::lightning(), &Amount::sats(500_001)),
+            0.0
+        );
+        assert_eq!(
+            config.weight_for(&MethodId::onchain(), &Amount::sats(1_000)),
+            0.0
+        );
+        assert_eq!(
+            config.weight_for(&MethodId::lightning(), &Amount::new("5", "USD")),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_config_json_roundtrip() {
+        let json = r#"{"methods":{"lightning":{"weight":30,"max_amount_sats":500000}}}"#;
+        let config = RegistryConfig::from_json(json).unwrap();
+        assert_eq!(config.methods["lightning"].max_amount_sats, Some(500_000));
+        assert_eq!(
+            RegistryConfig::from_json(&config.to_json().unwrap()).unwrap(),
+            config
+        );
+
+        assert!(RegistryConfig::from_json(
+            r#"{"methods":{"onchain":{"weight":1,"min_amount_sats":10,"max_amount_sats":5}}}"#
+        )
+        .is_err());
+    }
+}
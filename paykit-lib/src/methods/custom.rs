@@ -0,0 +1,282 @@
+//! Custom Payment Methods
+//!
+//! Wallets can add proprietary rails (internal ledger transfers, store
+//! credit, ...) without forking Paykit: implement [`CustomMethodHandler`]
+//! and register a [`CustomMethodPlugin`] wrapping it. The plugin takes part
+//! in selection and execution like the built-in methods.
+//!
+//! Handlers are synchronous and exchange plain JSON, so they can be backed
+//! by UniFFI callback interfaces or JavaScript functions.
+//!
+//! # Example
+//!
+//! ```ignore
+//! struct LedgerTransfer { /* ... */ }
+//!
+//! impl CustomMethodHandler for LedgerTransfer {
+//!     fn validate_endpoint(&self, endpoint: &EndpointData) -> ValidationResult { /* ... */ }
+//!     fn execute_payment(&self, endpoint: &EndpointData, amount: &Amount, metadata: &Value) -> Result<Value> {
+//!         let transfer_id = self.ledger.transfer(&endpoint.0, amount)?;
+//!         Ok(serde_json::json!({ "transfer_id": transfer_id }))
+//!     }
+//!     fn generate_proof(&self, execution: &PaymentExecution) -> Result<Value> { /* ... */ }
+//! }
+//!
+//! let plugin = CustomMethodPlugin::new(MethodId::new("com.acme.ledger"), "ACME Ledger", Arc::new(handler))?
+//!     .with_confirmation_time(1);
+//! registry.try_register(Box::new(plugin))?;
+//! ```
+
+use super::capabilities::PluginCapabilities;
+use super::traits::{
+    Amount, PaymentExecution, PaymentMethodPlugin, PaymentProof, ValidationResult,
+};
+use crate::{EndpointData, MethodId, PaykitError, Result};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Callbacks implementing a custom payment method.
+pub trait CustomMethodHandler: Send + Sync {
+    /// Check that an endpoint is usable with this method.
+    fn validate_endpoint(&self, endpoint: &EndpointData) -> ValidationResult;
+
+    /// Pay `amount` to `endpoint`, returning method-specific execution data.
+    ///
+    /// Errors are reported as a failed [`PaymentExecution`].
+    fn execute_payment(
+        &self,
+        endpoint: &EndpointData,
+        amount: &Amount,
+        metadata: &Value,
+    ) -> Result<Value>;
+
+    /// Produce proof data for a successful execution.
+    fn generate_proof(&self, execution: &PaymentExecution) -> Result<Value>;
+}
+
+/// A payment method plugin backed by a [`CustomMethodHandler`].
+pub struct CustomMethodPlugin {
+    method_id: MethodId,
+    display_name: String,
+    description: String,
+    capabilities: PluginCapabilities,
+    confirmation_time_secs: Option<u64>,
+    handler: Arc<dyn CustomMethodHandler>,
+}
+
+impl CustomMethodPlugin {
+    /// Create a plugin for `method_id`.
+    ///
+    /// The ID must be namespaced (e.g. `com.acme.ledger`), which also keeps
+    /// it clear of the built-in methods; use the executor hooks to customize
+    /// those instead.
+    pub fn new(
+        method_id: MethodId,
+        display_name: impl Into<String>,
+        handler: Arc<dyn CustomMethodHandler>,
+    ) -> Result<Self> {
+        method_id.validate()?;
+        if !method_id.is_namespaced() {
+            return Err(PaykitError::invalid_data(
+                "method_id",
+                format!("Custom method {} must be namespaced", method_id.0),
+            ));
+        }
+        let display_name = display_name.into();
+        Ok(Self {
+            description: format!("Custom payment method: {}", display_name),
+            method_id,
+            display_name,
+            capabilities: PluginCapabilities::default(),
+            confirmation_time_secs: None,
+            handler,
+        })
+    }
+
+    /// Set the description.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Declare the method's capabilities.
+    pub fn with_capabilities(mut self, capabilities: PluginCapabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Set the typical confirmation time in seconds.
+    pub fn with_confirmation_time(mut self, secs: u64) -> Self {
+        self.confirmation_time_secs = Some(secs);
+        self
+    }
+}
+
+#[async_trait]
+impl PaymentMethodPlugin for CustomMethodPlugin {
+    fn method_id(&self) -> MethodId {
+        self.method_id.clone()
+    }
+
+    fn display_name(&self) -> &str {
+        &self.display_name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn capabilities(&self) -> PluginCapabilities {
+        self.capabilities.clone()
+    }
+
+    fn validate_endpoint(&self, data: &EndpointData) -> ValidationResult {
+        self.handler.validate_endpoint(data)
+    }
+
+    async fn execute_payment(
+        &self,
+        endpoint: &EndpointData,
+        amount: &Amount,
+        metadata: &Value,
+    ) -> Result<PaymentExecution> {
+        if !self.supports_amount(amount) {
+            return Err(PaykitError::Transport(format!(
+                "Amount {} is outside the limits of {}",
+                amount, self.method_id.0
+            )));
+        }
+        let execution = match self.handler.execute_payment(endpoint, amount, metadata) {
+            Ok(data) => {
+                PaymentExecution::success(self.method_id(), endpoint.clone(), amount.clone(), data)
+            }
+            Err(e) => PaymentExecution::failure(
+                self.method_id(),
+                endpoint.clone(),
+                amount.clone(),
+                e.to_string(),
+            ),
+        };
+        Ok(execution)
+    }
+
+    fn generate_proof(&self, execution: &PaymentExecution) -> Result<PaymentProof> {
+        if !execution.success {
+            return Err(PaykitError::Transport(
+                "Cannot generate proof for failed payment".to_string(),
+            ));
+        }
+        let data = self.handler.generate_proof(execution)?;
+        Ok(PaymentProof::custom(self.method_id(), data))
+    }
+
+    fn format_receipt_metadata(&self, execution: &PaymentExecution) -> Value {
+        serde_json::json!({
+            "method": self.method_id.0,
+            "execution": execution.execution_data,
+            "executed_at": execution.executed_at,
+        })
+    }
+
+    fn supports_amount(&self, amount: &Amount) -> bool {
+        self.capabilities.allows_amount(amount)
+    }
+
+    fn estimated_confirmation_time(&self) -> Option<u64> {
+        self.confirmation_time_secs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::methods::{PaymentMethodRegistry, SettlementKind};
+
+    struct Ledger;
+
+    impl CustomMethodHandler for Ledger {
+        fn validate_endpoint(&self, endpoint: &EndpointData) -> ValidationResult {
+            if endpoint.0.starts_with("acct:") {
+                ValidationResult::valid()
+            } else {
+                ValidationResult::invalid(vec!["Expected acct: prefix".into()])
+            }
+        }
+
+        fn execute_payment(
+            &self,
+            endpoint: &EndpointData,
+            amount: &Amount,
+            _metadata: &Value,
+        ) -> Result<Value> {
+            if endpoint.0 == "acct:frozen" {
+                return Err(PaykitError::Transport("account frozen".into()));
+            }
+            Ok(serde_json::json!({ "transfer": format!("{}:{}", endpoint.0, amount.value) }))
+        }
+
+        fn generate_proof(&self, execution: &PaymentExecution) -> Result<Value> {
+            Ok(execution.execution_data.clone())
+        }
+    }
+
+    fn ledger_plugin() -> CustomMethodPlugin {
+        CustomMethodPlugin::new(
+            MethodId::new("org.example.ledger"),
+            "Ledger",
+            Arc::new(Ledger),
+        )
+        .unwrap()
+        .with_capabilities(
+            PluginCapabilities::new(SettlementKind::OffChain).with_amount_range(None, Some(1_000)),
+        )
+    }
+
+    #[test]
+    fn test_custom_method_requires_namespaced_id() {
+        assert!(CustomMethodPlugin::new(MethodId::onchain(), "x", Arc::new(Ledger)).is_err());
+        assert!(CustomMethodPlugin::new(MethodId::new("ledger"), "x", Arc::new(Ledger)).is_err());
+        assert!(CustomMethodPlugin::new(MethodId::new(" "), "x", Arc::new(Ledger)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_custom_method_execute_and_prove() {
+        let registry = PaymentMethodRegistry::new();
+        registry.try_register(Box::new(ledger_plugin())).unwrap();
+        let plugin = registry.get(&MethodId::new("org.example.ledger")).unwrap();
+
+        assert!(
+            plugin
+                .validate_endpoint(&EndpointData("acct:1".into()))
+                .valid
+        );
+        assert!(!plugin.validate_endpoint(&EndpointData("bc1q".into())).valid);
+        assert!(!plugin.supports_amount(&Amount::sats(2_000)));
+
+        let endpoint = EndpointData("acct:1".into());
+        let execution = plugin
+            .execute_payment(&endpoint, &Amount::sats(500), &Value::Null)
+            .await
+            .unwrap();
+        assert!(execution.success);
+        match plugin.generate_proof(&execution).unwrap() {
+            PaymentProof::Custom { method, data } => {
+                assert_eq!(method.0, "org.example.ledger");
+                assert_eq!(data["transfer"], "acct:1:500");
+            }
+            other => panic!("unexpected proof: {:?}", other),
+        }
+
+        let frozen = plugin
+            .execute_payment(
+                &EndpointData("acct:frozen".into()),
+                &Amount::sats(1),
+                &Value::Null,
+            )
+            .await
+            .unwrap();
+        assert!(!frozen.success);
+        assert!(plugin.generate_proof(&frozen).is_err());
+    }
+}
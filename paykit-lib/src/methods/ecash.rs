@@ -0,0 +1,589 @@
+//! Fedimint E-Cash Payment Method Plugin
+//!
+//! Communities running a Fedimint federation publish an `ecash` endpoint
+//! naming their federation. Payers who are members of the same federation
+//! pay by spending e-cash notes; everyone else pays through the
+//! federation's Lightning gateway using the invoice or LNURL in the
+//! endpoint.
+//!
+//! Spent notes are bearer instruments: the plugin returns them in the
+//! execution data and the caller must hand them to the payee over a private
+//! channel (for example the Noise receipt exchange). Proofs carry only a
+//! hash of the notes, so a published receipt cannot be redeemed.
+//!
+//! # Endpoint Format
+//!
+//! Either a bare federation invite code (`fed1...`) or JSON:
+//!
+//! ```json
+//! {
+//!   "federation_id": "15db8cb4f1ec8e484d73b889372bec94812580f929e8148b7437d359af422cd3",
+//!   "invite_code": "fed11qgqzc2nhwden5te0vejkg6tdd9h8gepwvejkg6tdd9h8garhduhx6at5d9h8jmn9wshxxmmd9uqqzgxg6s3evnr6m9zdxr6hxkdkukexpcs3mn7mj3g5pc5dfh63l4tj6g9zk4er",
+//!   "lnurl": "lnurl1..."
+//! }
+//! ```
+//!
+//! # Wallet Integration
+//!
+//! ```ignore
+//! use paykit_lib::methods::{EcashPlugin, MockEcashExecutor};
+//! use std::sync::Arc;
+//!
+//! let plugin = EcashPlugin::with_executor(Arc::new(MockEcashExecutor::new(federation_id)));
+//! ```
+//!
+//! With the `fedimint` feature, `paykit_lib::executors::FedimintExecutor`
+//! connects a `fedimint-clientd` instance.
+
+use super::capabilities::{PluginCapabilities, SettlementKind};
+use super::executor::{LightningPaymentResult, LightningPaymentStatus};
+use super::traits::{
+    Amount, PaymentExecution, PaymentMethodPlugin, PaymentProof, ValidationResult,
+};
+use crate::protocol::{parse_document, MAX_ENDPOINT_BYTES};
+use crate::{EndpointData, MethodId, PaykitError, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+/// Method ID of the e-cash plugin.
+pub const ECASH_METHOD_ID: &str = "ecash";
+
+/// Human-readable part of Fedimint invite codes.
+const INVITE_PREFIX: &str = "fed1";
+
+/// Notes spent from a federation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EcashSpend {
+    /// Federation the notes belong to (hex).
+    pub federation_id: String,
+    /// Client operation ID of the spend.
+    pub operation_id: String,
+    /// Out-of-band notes, to be delivered to the payee.
+    pub notes: String,
+    /// Value of the notes in millisatoshis.
+    pub amount_msat: u64,
+}
+
+impl EcashSpend {
+    /// The transcript of this spend, safe to share.
+    pub fn transcript(&self) -> EcashSpendTranscript {
+        EcashSpendTranscript {
+            federation_id: self.federation_id.clone(),
+            operation_id: self.operation_id.clone(),
+            amount_msat: self.amount_msat,
+            notes_hash: notes_hash(&self.notes),
+            spent_at: current_timestamp(),
+        }
+    }
+}
+
+/// Notes redeemed into the wallet.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EcashReceive {
+    /// Client operation ID of the reissue.
+    pub operation_id: String,
+    /// Amount credited in millisatoshis.
+    pub amount_msat: u64,
+}
+
+/// Record of a note spend without the notes themselves.
+///
+/// This is the proof of an e-cash payment: the payee, who received the
+/// notes, can check them against `notes_hash` with [`verify_ecash_proof`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EcashSpendTranscript {
+    /// Federation the notes belong to (hex).
+    pub federation_id: String,
+    /// Client operation ID of the spend.
+    pub operation_id: String,
+    /// Value of the notes in millisatoshis.
+    pub amount_msat: u64,
+    /// SHA-256 of the out-of-band notes string (hex).
+    pub notes_hash: String,
+    /// When the notes were spent (unix seconds).
+    pub spent_at: i64,
+}
+
+impl EcashSpendTranscript {
+    /// The payment proof for this spend.
+    pub fn to_proof(&self) -> PaymentProof {
+        PaymentProof::custom(
+            MethodId::new(ECASH_METHOD_ID),
+            serde_json::to_value(self).unwrap_or(Value::Null),
+        )
+    }
+
+    /// Read a transcript back from a payment proof.
+    pub fn from_proof(proof: &PaymentProof) -> Result<Self> {
+        match proof {
+            PaymentProof::Custom { method, data } if method.as_str() == ECASH_METHOD_ID => {
+                serde_json::from_value(data.clone())
+                    .map_err(|e| PaykitError::Serialization(format!("Invalid e-cash proof: {}", e)))
+            }
+            _ => Err(PaykitError::invalid_data(
+                "proof",
+                "expected an e-cash spend transcript",
+            )),
+        }
+    }
+}
+
+/// Check that `notes` are the ones the proof's transcript was made for.
+///
+/// The payee calls this with the notes it received; it does not check
+/// that the notes are still unspent, which only redeeming them does.
+pub fn verify_ecash_proof(proof: &PaymentProof, notes: &str) -> Result<bool> {
+    let transcript = EcashSpendTranscript::from_proof(proof)?;
+    Ok(transcript.notes_hash == notes_hash(notes))
+}
+
+/// Executor trait for Fedimint e-cash wallets.
+///
+/// Implement this trait to connect a Fedimint client to Paykit.
+#[async_trait]
+pub trait EcashExecutor: Send + Sync {
+    /// Federations the wallet has joined (hex federation IDs).
+    async fn federation_ids(&self) -> Result<Vec<String>>;
+
+    /// Spend notes worth `amount_msat` from `federation_id`.
+    async fn spend_notes(&self, federation_id: &str, amount_msat: u64) -> Result<EcashSpend>;
+
+    /// Redeem out-of-band notes into the wallet.
+    async fn receive_notes(&self, notes: &str) -> Result<EcashReceive>;
+
+    /// Pay a BOLT11 invoice or LNURL through a federation's Lightning gateway.
+    async fn pay_invoice(
+        &self,
+        federation_id: Option<&str>,
+        invoice: &str,
+        amount_msat: Option<u64>,
+    ) -> Result<LightningPaymentResult>;
+
+    /// E-cash balance held in `federation_id`, in millisatoshis.
+    async fn balance_msat(&self, federation_id: &str) -> Result<u64> {
+        let _ = federation_id;
+        Err(PaykitError::Unimplemented(
+            "balance queries are not supported by this wallet",
+        ))
+    }
+}
+
+/// A parsed `ecash` endpoint.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EcashEndpoint {
+    /// Federation ID (hex).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub federation_id: Option<String>,
+    /// Invite code for joining the federation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub invite_code: Option<String>,
+    /// BOLT11 invoice payable through the federation's gateway.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bolt11: Option<String>,
+    /// LNURL payable through the federation's gateway.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lnurl: Option<String>,
+}
+
+impl EcashEndpoint {
+    /// Parse endpoint data.
+    pub fn parse(data: &EndpointData) -> Result<Self> {
+        let data = data.as_str().trim();
+        if data.starts_with('{') {
+            return parse_document("endpoint", data.as_bytes(), MAX_ENDPOINT_BYTES);
+        }
+        Ok(Self {
+            invite_code: Some(data.to_string()),
+            ..Self::default()
+        })
+    }
+
+    /// Encode as endpoint data.
+    pub fn to_endpoint_data(&self) -> EndpointData {
+        EndpointData::new(serde_json::to_string(self).unwrap_or_default())
+    }
+
+    /// The Lightning destination for payers outside the federation.
+    pub fn lightning(&self) -> Option<&str> {
+        self.bolt11.as_deref().or(self.lnurl.as_deref())
+    }
+
+    fn validate(&self) -> ValidationResult {
+        let mut errors = Vec::new();
+        if let Some(id) = &self.federation_id {
+            if id.len() != 64 || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+                errors.push("Federation ID must be 64 hex characters".to_string());
+            }
+        }
+        if let Some(invite) = &self.invite_code {
+            let invite = invite.to_lowercase();
+            if !invite.starts_with(INVITE_PREFIX) || invite.len() < 32 {
+                errors.push(format!(
+                    "Invalid invite code: must start with '{}'",
+                    INVITE_PREFIX
+                ));
+            }
+        }
+        if self.federation_id.is_none() && self.invite_code.is_none() {
+            errors.push("Endpoint must name a federation ID or invite code".to_string());
+        }
+        if !errors.is_empty() {
+            return ValidationResult::invalid(errors);
+        }
+
+        let result = ValidationResult::valid();
+        if self.federation_id.is_none() {
+            result.with_warning("No federation ID; only members who joined via the invite can pay")
+        } else if self.lightning().is_none() {
+            result.with_warning("No Lightning destination; only federation members can pay")
+        } else {
+            result
+        }
+    }
+}
+
+/// Fedimint e-cash payment method plugin.
+pub struct EcashPlugin {
+    executor: Option<Arc<dyn EcashExecutor>>,
+}
+
+impl EcashPlugin {
+    /// Create a plugin without an executor; payments fail until one is set.
+    pub fn new() -> Self {
+        Self { executor: None }
+    }
+
+    /// Create a plugin paying through `executor`.
+    pub fn with_executor(executor: Arc<dyn EcashExecutor>) -> Self {
+        Self {
+            executor: Some(executor),
+        }
+    }
+
+    /// Returns whether this plugin has an executor configured.
+    pub fn has_executor(&self) -> bool {
+        self.executor.is_some()
+    }
+
+    /// Federation to spend notes from, if the wallet is a member of the
+    /// payee's federation.
+    async fn shared_federation(
+        executor: &dyn EcashExecutor,
+        endpoint: &EcashEndpoint,
+    ) -> Result<Option<String>> {
+        let Some(federation_id) = &endpoint.federation_id else {
+            return Ok(None);
+        };
+        let joined = executor.federation_ids().await?;
+        Ok(joined
+            .into_iter()
+            .find(|id| id.eq_ignore_ascii_case(federation_id)))
+    }
+}
+
+impl Default for EcashPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl PaymentMethodPlugin for EcashPlugin {
+    fn method_id(&self) -> MethodId {
+        MethodId::new(ECASH_METHOD_ID)
+    }
+
+    fn display_name(&self) -> &str {
+        "Fedimint E-Cash"
+    }
+
+    fn description(&self) -> &str {
+        "Pay members of a Fedimint federation with e-cash notes, or through the federation's Lightning gateway."
+    }
+
+    fn capabilities(&self) -> PluginCapabilities {
+        PluginCapabilities::new(SettlementKind::OffChain)
+            .with_execution(self.has_executor())
+            .with_amount_range(Some(1), None)
+    }
+
+    fn validate_endpoint(&self, data: &EndpointData) -> ValidationResult {
+        match EcashEndpoint::parse(data) {
+            Ok(endpoint) => endpoint.validate(),
+            Err(e) => ValidationResult::invalid(vec![e.to_string()]),
+        }
+    }
+
+    async fn execute_payment(
+        &self,
+        endpoint: &EndpointData,
+        amount: &Amount,
+        _metadata: &Value,
+    ) -> Result<PaymentExecution> {
+        let parsed = EcashEndpoint::parse(endpoint)?;
+        let validation = parsed.validate();
+        if !validation.valid {
+            return Err(PaykitError::invalid_data(
+                "endpoint",
+                validation.errors.join(", "),
+            ));
+        }
+        let executor = self.executor.as_deref().ok_or_else(|| {
+            PaykitError::MethodNotSupported("No e-cash wallet configured".to_string())
+        })?;
+        let amount_msat = amount
+            .as_u64()
+            .map(|sats| sats * 1000)
+            .ok_or_else(|| PaykitError::invalid_data("amount", "Amount in sats required"))?;
+
+        if let Some(federation_id) = Self::shared_federation(executor, &parsed).await? {
+            let spend = executor.spend_notes(&federation_id, amount_msat).await?;
+            let transcript = spend.transcript();
+            return Ok(PaymentExecution::success(
+                self.method_id(),
+                endpoint.clone(),
+                amount.clone(),
+                serde_json::json!({
+                    "type": "notes",
+                    "notes": spend.notes,
+                    "transcript": transcript,
+                }),
+            ));
+        }
+
+        let invoice = parsed.lightning().ok_or_else(|| {
+            PaykitError::MethodNotSupported(
+                "Not a member of the payee's federation and no Lightning destination".to_string(),
+            )
+        })?;
+        let result = executor
+            .pay_invoice(None, invoice, Some(amount_msat))
+            .await?;
+        if result.status != LightningPaymentStatus::Succeeded {
+            return Ok(PaymentExecution::failure(
+                self.method_id(),
+                endpoint.clone(),
+                amount.clone(),
+                format!("Gateway payment {:?}", result.status),
+            ));
+        }
+        Ok(PaymentExecution::success(
+            self.method_id(),
+            endpoint.clone(),
+            amount.clone(),
+            serde_json::json!({
+                "type": "gateway",
+                "preimage": result.preimage,
+                "payment_hash": result.payment_hash,
+                "fee_msat": result.fee_msat,
+            }),
+        ))
+    }
+
+    fn generate_proof(&self, execution: &PaymentExecution) -> Result<PaymentProof> {
+        let data = &execution.execution_data;
+        match data.get("type").and_then(Value::as_str) {
+            Some("notes") => {
+                let transcript: EcashSpendTranscript =
+                    serde_json::from_value(data["transcript"].clone()).map_err(|e| {
+                        PaykitError::Serialization(format!("Invalid spend transcript: {}", e))
+                    })?;
+                Ok(transcript.to_proof())
+            }
+            Some("gateway") => {
+                let field = |name: &str| data.get(name).and_then(Value::as_str).unwrap_or("");
+                if field("preimage").is_empty() {
+                    // Gateways that do not hand back the preimage still
+                    // identify the payment, which is better than no proof
+                    return Ok(PaymentProof::custom(self.method_id(), data.clone()));
+                }
+                Ok(PaymentProof::lightning_preimage(
+                    field("preimage"),
+                    field("payment_hash"),
+                ))
+            }
+            _ => Err(PaykitError::invalid_data(
+                "execution_data",
+                "not an e-cash payment",
+            )),
+        }
+    }
+
+    fn format_receipt_metadata(&self, execution: &PaymentExecution) -> Value {
+        let data = &execution.execution_data;
+        // Never copy the notes themselves into a receipt
+        serde_json::json!({
+            "method": ECASH_METHOD_ID,
+            "type": data.get("type"),
+            "transcript": data.get("transcript"),
+            "payment_hash": data.get("payment_hash"),
+        })
+    }
+
+    fn estimated_confirmation_time(&self) -> Option<u64> {
+        Some(1)
+    }
+}
+
+/// Mock e-cash executor for testing.
+pub struct MockEcashExecutor {
+    federation_id: String,
+    /// Whether to simulate failures.
+    pub simulate_failure: bool,
+}
+
+impl MockEcashExecutor {
+    /// Create a mock wallet that has joined `federation_id`.
+    pub fn new(federation_id: impl Into<String>) -> Self {
+        Self {
+            federation_id: federation_id.into(),
+            simulate_failure: false,
+        }
+    }
+}
+
+#[async_trait]
+impl EcashExecutor for MockEcashExecutor {
+    async fn federation_ids(&self) -> Result<Vec<String>> {
+        Ok(vec![self.federation_id.clone()])
+    }
+
+    async fn spend_notes(&self, federation_id: &str, amount_msat: u64) -> Result<EcashSpend> {
+        if self.simulate_failure {
+            return Err(PaykitError::Transport("Simulated failure".to_string()));
+        }
+        Ok(EcashSpend {
+            federation_id: federation_id.to_string(),
+            operation_id: notes_hash(&format!("op:{}", amount_msat)),
+            notes: format!("mocknotes{}{}", federation_id, amount_msat),
+            amount_msat,
+        })
+    }
+
+    async fn receive_notes(&self, notes: &str) -> Result<EcashReceive> {
+        Ok(EcashReceive {
+            operation_id: notes_hash(notes),
+            amount_msat: 1000,
+        })
+    }
+
+    async fn pay_invoice(
+        &self,
+        _federation_id: Option<&str>,
+        invoice: &str,
+        amount_msat: Option<u64>,
+    ) -> Result<LightningPaymentResult> {
+        if self.simulate_failure {
+            return Err(PaykitError::Transport("Simulated failure".to_string()));
+        }
+        let preimage = notes_hash(invoice);
+        let payment_hash = hex::encode(Sha256::digest(hex::decode(&preimage).unwrap_or_default()));
+        Ok(LightningPaymentResult::success(
+            preimage,
+            payment_hash,
+            amount_msat.unwrap_or(1000),
+            0,
+        ))
+    }
+}
+
+/// SHA-256 of an out-of-band notes string, hex encoded.
+fn notes_hash(notes: &str) -> String {
+    hex::encode(Sha256::digest(notes.trim().as_bytes()))
+}
+
+/// Get current timestamp.
+fn current_timestamp() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEDERATION: &str = "15db8cb4f1ec8e484d73b889372bec94812580f929e8148b7437d359af422cd3";
+    const INVITE: &str = "fed11qgqzc2nhwden5te0vejkg6tdd9h8gepwvejkg6tdd9h8garhduhx6at5d9h8jmn9wshxxmmd9uqqzgxg6s3evnr6m9zdxr6hxkdkukexpcs3mn7mj3g5pc5dfh63l4tj6g9zk4er";
+
+    fn endpoint(lnurl: Option<&str>) -> EndpointData {
+        EcashEndpoint {
+            federation_id: Some(FEDERATION.to_string()),
+            invite_code: Some(INVITE.to_string()),
+            bolt11: None,
+            lnurl: lnurl.map(str::to_string),
+        }
+        .to_endpoint_data()
+    }
+
+    #[test]
+    fn test_ecash_endpoint_validation() {
+        let plugin = EcashPlugin::new();
+        assert!(plugin.validate_endpoint(&EndpointData::new(INVITE)).valid);
+        assert!(plugin.validate_endpoint(&endpoint(None)).valid);
+        assert!(
+            !plugin
+                .validate_endpoint(&EndpointData::new("lnbc1notaninvite"))
+                .valid
+        );
+        assert!(
+            !plugin
+                .validate_endpoint(&EndpointData::new(r#"{"federation_id":"abc"}"#))
+                .valid
+        );
+    }
+
+    #[tokio::test]
+    async fn test_member_pays_with_notes() {
+        let plugin = EcashPlugin::with_executor(Arc::new(MockEcashExecutor::new(FEDERATION)));
+        let execution = plugin
+            .execute_payment(&endpoint(None), &Amount::sats(2100), &Value::Null)
+            .await
+            .unwrap();
+        assert!(execution.success);
+        assert_eq!(execution.execution_data["type"], "notes");
+
+        let proof = plugin.generate_proof(&execution).unwrap();
+        let notes = execution.execution_data["notes"].as_str().unwrap();
+        assert!(verify_ecash_proof(&proof, notes).unwrap());
+        assert!(!verify_ecash_proof(&proof, "othernotes").unwrap());
+        assert_eq!(
+            EcashSpendTranscript::from_proof(&proof)
+                .unwrap()
+                .amount_msat,
+            2_100_000
+        );
+
+        let metadata = plugin.format_receipt_metadata(&execution);
+        assert!(!metadata.to_string().contains(notes));
+    }
+
+    #[tokio::test]
+    async fn test_non_member_pays_through_gateway() {
+        let plugin = EcashPlugin::with_executor(Arc::new(MockEcashExecutor::new("00".repeat(32))));
+        let err = plugin
+            .execute_payment(&endpoint(None), &Amount::sats(100), &Value::Null)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, PaykitError::MethodNotSupported(_)));
+
+        let execution = plugin
+            .execute_payment(
+                &endpoint(Some("lnurl1dp68gurn8ghj7")),
+                &Amount::sats(100),
+                &Value::Null,
+            )
+            .await
+            .unwrap();
+        assert_eq!(execution.execution_data["type"], "gateway");
+        assert!(matches!(
+            plugin.generate_proof(&execution).unwrap(),
+            PaymentProof::LightningPreimage { .. }
+        ));
+    }
+}
@@ -51,6 +51,7 @@
 use crate::{PaykitError, Result};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 /// Result of a Bitcoin on-chain transaction.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -91,6 +92,160 @@ impl BitcoinTxResult {
     }
 }
 
+/// Strategy used to speed up an unconfirmed on-chain transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FeeBumpStrategy {
+    /// Replace-by-fee: re-sign the original transaction with a higher fee (BIP 125).
+    Rbf,
+    /// Child-pays-for-parent: spend an output of the stuck transaction with a
+    /// high-fee child so miners include both.
+    Cpfp,
+}
+
+/// How a wallet would build the transaction for a payment.
+///
+/// Used to score the payment's privacy before it is sent; see
+/// [`crate::policy::PrivacyAnalyzer`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpendHints {
+    /// Number of inputs spent.
+    pub input_count: u32,
+    /// Number of distinct addresses the inputs come from.
+    pub input_addresses: u32,
+    /// Change returned to the wallet, if any.
+    pub change_sats: Option<u64>,
+    /// Whether the change output uses the same address type as the payment.
+    pub change_type_matches: bool,
+}
+
+/// Payment metadata key holding [`CoinSelection`] hints.
+pub const COIN_SELECTION_METADATA_KEY: &str = "coin_selection";
+
+/// What the wallet should do with change.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangePreference {
+    /// Leave it to the wallet.
+    #[default]
+    Default,
+    /// Prefer inputs that need no change output, adding leftovers to the fee.
+    Avoid,
+    /// Send change to an address of the same type as the payment, so it
+    /// cannot be told apart.
+    MatchPaymentType,
+}
+
+/// Payer preferences for which coins fund an on-chain payment.
+///
+/// Carried in payment metadata under [`COIN_SELECTION_METADATA_KEY`] and
+/// passed to [`BitcoinExecutor::send_with_coin_selection`]. Wallets honor
+/// them as far as their coin control allows.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CoinSelection {
+    /// Outputs (`txid:vout`) never to spend, such as tainted or dust-attack coins.
+    pub avoid_outpoints: Vec<String>,
+    /// Only spend confirmed outputs.
+    pub confirmed_only: bool,
+    /// Maximum number of inputs.
+    pub max_inputs: Option<u32>,
+    /// What to do with change.
+    pub change: ChangePreference,
+}
+
+impl CoinSelection {
+    /// Read hints from payment metadata.
+    ///
+    /// Returns `None` when the metadata carries no hints.
+    pub fn from_metadata(metadata: &Value) -> Result<Option<Self>> {
+        let Some(value) = metadata.get(COIN_SELECTION_METADATA_KEY) else {
+            return Ok(None);
+        };
+        if value.is_null() {
+            return Ok(None);
+        }
+        let selection: Self = serde_json::from_value(value.clone())
+            .map_err(|e| PaykitError::invalid_data(COIN_SELECTION_METADATA_KEY, e.to_string()))?;
+        selection.validate()?;
+        Ok(Some(selection))
+    }
+
+    /// Check the hints are well-formed.
+    pub fn validate(&self) -> Result<()> {
+        if self.max_inputs == Some(0) {
+            return Err(PaykitError::invalid_data(
+                COIN_SELECTION_METADATA_KEY,
+                "max_inputs must be at least 1",
+            ));
+        }
+        for outpoint in &self.avoid_outpoints {
+            let valid = match outpoint.split_once(':') {
+                Some((txid, vout)) => {
+                    txid.len() == 64
+                        && txid.chars().all(|c| c.is_ascii_hexdigit())
+                        && vout.parse::<u32>().is_ok()
+                }
+                None => false,
+            };
+            if !valid {
+                return Err(PaykitError::invalid_data(
+                    COIN_SELECTION_METADATA_KEY,
+                    format!("invalid outpoint '{}', expected txid:vout", outpoint),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Result of a fee bump.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FeeBumpResult {
+    /// The stuck transaction that was bumped.
+    pub original_txid: String,
+    /// The new transaction: the replacement for RBF, the child for CPFP.
+    pub bump_txid: String,
+    /// Strategy that was used.
+    pub strategy: FeeBumpStrategy,
+    /// Effective fee rate in sat/vB after the bump.
+    pub fee_rate: f64,
+    /// Additional fee paid in satoshis.
+    pub fee_sats: u64,
+    /// Timestamp of the bump (unix epoch seconds).
+    pub bumped_at: i64,
+}
+
+impl FeeBumpResult {
+    /// Create a new fee bump result timestamped now.
+    pub fn new(
+        original_txid: impl Into<String>,
+        bump_txid: impl Into<String>,
+        strategy: FeeBumpStrategy,
+        fee_rate: f64,
+        fee_sats: u64,
+    ) -> Self {
+        Self {
+            original_txid: original_txid.into(),
+            bump_txid: bump_txid.into(),
+            strategy,
+            fee_rate,
+            fee_sats,
+            bumped_at: current_timestamp(),
+        }
+    }
+
+    /// The txid that now carries the payment.
+    ///
+    /// With RBF the original is evicted, so the replacement pays the payee.
+    /// With CPFP the original still pays the payee and the child only adds fees.
+    pub fn payment_txid(&self) -> &str {
+        match self.strategy {
+            FeeBumpStrategy::Rbf => &self.bump_txid,
+            FeeBumpStrategy::Cpfp => &self.original_txid,
+        }
+    }
+}
+
 /// Result of a Lightning payment.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LightningPaymentResult {
@@ -138,6 +293,41 @@ impl LightningPaymentResult {
     }
 }
 
+/// Result of probing a Lightning route before paying.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RouteProbe {
+    /// Whether any route to the destination was found.
+    pub route_found: bool,
+    /// Estimated probability (0.0 to 1.0) that a payment over the route succeeds.
+    pub success_probability: f64,
+    /// Expected routing fee in millisatoshis.
+    pub expected_fee_msat: u64,
+    /// Number of hops in the probed route.
+    pub hops: u32,
+}
+
+impl RouteProbe {
+    /// A probe that found a route.
+    pub fn found(success_probability: f64, expected_fee_msat: u64, hops: u32) -> Self {
+        Self {
+            route_found: true,
+            success_probability: success_probability.clamp(0.0, 1.0),
+            expected_fee_msat,
+            hops,
+        }
+    }
+
+    /// A probe that found no route.
+    pub fn no_route() -> Self {
+        Self {
+            route_found: false,
+            success_probability: 0.0,
+            expected_fee_msat: 0,
+            hops: 0,
+        }
+    }
+}
+
 /// Executor trait for Bitcoin on-chain payments.
 ///
 /// Implement this trait to integrate your Bitcoin wallet with Paykit.
@@ -203,6 +393,92 @@ pub trait BitcoinExecutor: Send + Sync {
     /// True if the transaction matches.
     async fn verify_transaction(&self, txid: &str, address: &str, amount_sats: u64)
         -> Result<bool>;
+
+    /// Fee bump strategies this wallet can perform.
+    ///
+    /// Defaults to none. Wallets that implement [`fee_bump`](Self::fee_bump)
+    /// should list the strategies they support, in order of preference.
+    fn fee_bump_strategies(&self) -> Vec<FeeBumpStrategy> {
+        Vec::new()
+    }
+
+    /// Bump the fee of an unconfirmed transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `txid` - The stuck transaction
+    /// * `fee_rate` - Target effective fee rate in sat/vB
+    /// * `strategy` - Whether to replace the transaction or spend it with a child
+    ///
+    /// # Returns
+    ///
+    /// Details of the bump, including the new txid.
+    async fn fee_bump(
+        &self,
+        txid: &str,
+        fee_rate: f64,
+        strategy: FeeBumpStrategy,
+    ) -> Result<FeeBumpResult> {
+        let _ = (txid, fee_rate, strategy);
+        Err(PaykitError::Unimplemented(
+            "fee bumping is not supported by this wallet",
+        ))
+    }
+
+    /// Get the wallet's spendable on-chain balance.
+    ///
+    /// Defaults to unsupported. Wallets must implement this, and
+    /// [`spend_hints`](Self::spend_hints), to be used for treasury sweeps.
+    ///
+    /// # Returns
+    ///
+    /// Confirmed balance available to spend, in satoshis.
+    async fn spendable_balance(&self) -> Result<u64> {
+        Err(PaykitError::Unimplemented(
+            "balance queries are not supported by this wallet",
+        ))
+    }
+
+    /// Describe the transaction the wallet would build for a payment.
+    ///
+    /// Defaults to unsupported, in which case privacy reports skip the
+    /// change and input checks and treasury sweeps are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The destination address
+    /// * `amount_sats` - The amount to send
+    async fn spend_hints(&self, address: &str, amount_sats: u64) -> Result<SpendHints> {
+        let _ = (address, amount_sats);
+        Err(PaykitError::Unimplemented(
+            "spend hints are not supported by this wallet",
+        ))
+    }
+
+    /// Send Bitcoin to an address, honoring the payer's coin-selection hints.
+    ///
+    /// Defaults to unsupported, in which case the on-chain plugin falls back
+    /// to [`send_to_address`](Self::send_to_address) and records the hints as
+    /// not honored.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The destination Bitcoin address
+    /// * `amount_sats` - The amount to send in satoshis
+    /// * `fee_rate` - Optional fee rate in sat/vB (uses wallet default if None)
+    /// * `selection` - Which coins to spend and what to do with change
+    async fn send_with_coin_selection(
+        &self,
+        address: &str,
+        amount_sats: u64,
+        fee_rate: Option<f64>,
+        selection: &CoinSelection,
+    ) -> Result<BitcoinTxResult> {
+        let _ = (address, amount_sats, fee_rate, selection);
+        Err(PaykitError::Unimplemented(
+            "coin selection is not supported by this wallet",
+        ))
+    }
 }
 
 /// Executor trait for Lightning Network payments.
@@ -250,6 +526,27 @@ pub trait LightningExecutor: Send + Sync {
     /// Estimated fee in millisatoshis.
     async fn estimate_fee(&self, invoice: &str) -> Result<u64>;
 
+    /// Probe for a route before paying, without moving funds.
+    ///
+    /// Defaults to unsupported. Nodes that can query their channel graph
+    /// should implement this so method selection can avoid Lightning when
+    /// liquidity is missing.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - A BOLT11 invoice or the destination node public key
+    /// * `amount_msat` - Amount to route in millisatoshis
+    ///
+    /// # Returns
+    ///
+    /// Route availability, success probability and expected fee.
+    async fn probe_route(&self, destination: &str, amount_msat: u64) -> Result<RouteProbe> {
+        let _ = (destination, amount_msat);
+        Err(PaykitError::Unimplemented(
+            "route probing is not supported by this node",
+        ))
+    }
+
     /// Check the status of a payment by payment hash.
     ///
     /// # Arguments
@@ -322,6 +619,8 @@ pub struct MockBitcoinExecutor {
     pub simulate_failure: bool,
     /// Fixed txid to return.
     pub mock_txid: Option<String>,
+    /// Spendable balance to report.
+    pub mock_balance_sats: Option<u64>,
 }
 
 impl MockBitcoinExecutor {
@@ -335,6 +634,7 @@ impl MockBitcoinExecutor {
         Self {
             simulate_failure: true,
             mock_txid: None,
+            mock_balance_sats: None,
         }
     }
 
@@ -343,6 +643,16 @@ impl MockBitcoinExecutor {
         Self {
             simulate_failure: false,
             mock_txid: Some(txid.into()),
+            mock_balance_sats: None,
+        }
+    }
+
+    /// Create a mock wallet holding `balance_sats`.
+    pub fn with_balance(balance_sats: u64) -> Self {
+        Self {
+            simulate_failure: false,
+            mock_txid: None,
+            mock_balance_sats: Some(balance_sats),
         }
     }
 }
@@ -415,6 +725,65 @@ impl BitcoinExecutor for MockBitcoinExecutor {
     ) -> Result<bool> {
         Ok(!self.simulate_failure)
     }
+
+    fn fee_bump_strategies(&self) -> Vec<FeeBumpStrategy> {
+        vec![FeeBumpStrategy::Rbf, FeeBumpStrategy::Cpfp]
+    }
+
+    async fn fee_bump(
+        &self,
+        txid: &str,
+        fee_rate: f64,
+        strategy: FeeBumpStrategy,
+    ) -> Result<FeeBumpResult> {
+        if self.simulate_failure {
+            return Err(PaykitError::Transport("Simulated failure".to_string()));
+        }
+
+        let bump_txid = format!(
+            "{:064x}",
+            simple_hash(&format!("bump:{}:{:?}:{}", txid, strategy, fee_rate))
+        );
+        let fee_sats = (fee_rate * 140.0) as u64;
+
+        Ok(FeeBumpResult::new(
+            txid, bump_txid, strategy, fee_rate, fee_sats,
+        ))
+    }
+
+    async fn spendable_balance(&self) -> Result<u64> {
+        if self.simulate_failure {
+            return Err(PaykitError::Transport("Simulated failure".to_string()));
+        }
+        Ok(self.mock_balance_sats.unwrap_or(0))
+    }
+
+    async fn spend_hints(&self, _address: &str, amount_sats: u64) -> Result<SpendHints> {
+        if self.simulate_failure {
+            return Err(PaykitError::Transport("Simulated failure".to_string()));
+        }
+        // One coin holding the whole balance
+        let change_sats = self
+            .mock_balance_sats
+            .unwrap_or(0)
+            .saturating_sub(amount_sats);
+        Ok(SpendHints {
+            input_count: 1,
+            input_addresses: 1,
+            change_sats: (change_sats > 0).then_some(change_sats),
+            change_type_matches: true,
+        })
+    }
+
+    async fn send_with_coin_selection(
+        &self,
+        address: &str,
+        amount_sats: u64,
+        fee_rate: Option<f64>,
+        _selection: &CoinSelection,
+    ) -> Result<BitcoinTxResult> {
+        self.send_to_address(address, amount_sats, fee_rate).await
+    }
 }
 
 /// Mock Lightning executor for testing.
@@ -496,6 +865,14 @@ impl LightningExecutor for MockLightningExecutor {
         Ok(1000)
     }
 
+    async fn probe_route(&self, _destination: &str, amount_msat: u64) -> Result<RouteProbe> {
+        if self.simulate_failure {
+            // A failing node behaves as if it has no outbound liquidity
+            return Ok(RouteProbe::no_route());
+        }
+        Ok(RouteProbe::found(0.95, amount_msat / 1000 + 1000, 3))
+    }
+
     async fn get_payment(&self, payment_hash: &str) -> Result<Option<LightningPaymentResult>> {
         let preimage = self.mock_preimage.clone().unwrap_or_else(|| {
             format!(
@@ -597,6 +974,54 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_mock_bitcoin_fee_bump() {
+        let executor = MockBitcoinExecutor::new();
+
+        let rbf = executor
+            .fee_bump("abc123", 20.0, FeeBumpStrategy::Rbf)
+            .await
+            .unwrap();
+        assert_eq!(rbf.original_txid, "abc123");
+        assert_ne!(rbf.bump_txid, "abc123");
+        assert_eq!(rbf.payment_txid(), rbf.bump_txid);
+
+        let cpfp = executor
+            .fee_bump("abc123", 20.0, FeeBumpStrategy::Cpfp)
+            .await
+            .unwrap();
+        assert_eq!(cpfp.payment_txid(), "abc123");
+
+        let failing = MockBitcoinExecutor::failing();
+        assert!(failing
+            .fee_bump("abc123", 20.0, FeeBumpStrategy::Rbf)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_coin_selection_from_metadata() {
+        let metadata = serde_json::json!({
+            "coin_selection": {
+                "avoid_outpoints": [format!("{}:1", "ab".repeat(32))],
+                "confirmed_only": true,
+                "change": "avoid",
+            }
+        });
+        let selection = CoinSelection::from_metadata(&metadata).unwrap().unwrap();
+        assert!(selection.confirmed_only);
+        assert_eq!(selection.max_inputs, None);
+        assert_eq!(selection.change, ChangePreference::Avoid);
+
+        assert!(CoinSelection::from_metadata(&serde_json::json!({}))
+            .unwrap()
+            .is_none());
+        let bad = serde_json::json!({ "coin_selection": { "avoid_outpoints": ["abc"] } });
+        assert!(CoinSelection::from_metadata(&bad).is_err());
+        let bad = serde_json::json!({ "coin_selection": { "max_inputs": 0 } });
+        assert!(CoinSelection::from_metadata(&bad).is_err());
+    }
+
     #[tokio::test]
     async fn test_mock_lightning_executor() {
         let executor = MockLightningExecutor::new();
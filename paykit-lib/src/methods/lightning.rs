@@ -19,11 +19,21 @@
 //! impl LightningExecutor for MyLndNode { /* ... */ }
 //! let plugin = LightningPlugin::with_executor(Arc::new(MyLndNode::new()));
 //! ```
+//!
+//! # Fee Guardrails
+//!
+//! When the plugin's [`FeeGuard`](crate::policy::FeeGuard) limits the
+//! routing fee, BOLT11 payments are estimated first and fail with
+//! [`PaykitError::FeeSpike`] above the limit, unless `"allow_fee_spike": true`
+//! is set in the metadata.
 
+use super::capabilities::{PluginCapabilities, SettlementKind};
 use super::executor::{LightningExecutor, LightningPaymentStatus, MockLightningExecutor};
 use super::traits::{
     Amount, PaymentExecution, PaymentMethodPlugin, PaymentProof, ValidationResult,
 };
+use crate::policy::{fee_spike_allowed, FeeGuard};
+use crate::protocol::EndpointFields;
 use crate::{EndpointData, MethodId, PaykitError, Result};
 use async_trait::async_trait;
 use serde_json::Value;
@@ -47,6 +57,8 @@ pub struct LightningPlugin {
     network: LightningNetwork,
     /// Optional executor for actual payments.
     executor: Option<Arc<dyn LightningExecutor>>,
+    /// Fee guardrails.
+    fee_guard: Arc<FeeGuard>,
 }
 
 /// Lightning network types.
@@ -69,6 +81,7 @@ impl LightningPlugin {
         Self {
             network: LightningNetwork::Mainnet,
             executor: None,
+            fee_guard: Arc::default(),
         }
     }
 
@@ -77,6 +90,7 @@ impl LightningPlugin {
         Self {
             network,
             executor: None,
+            fee_guard: Arc::default(),
         }
     }
 
@@ -87,6 +101,7 @@ impl LightningPlugin {
         Self {
             network: LightningNetwork::Mainnet,
             executor: Some(executor),
+            fee_guard: Arc::default(),
         }
     }
 
@@ -98,6 +113,7 @@ impl LightningPlugin {
         Self {
             network,
             executor: Some(executor),
+            fee_guard: Arc::default(),
         }
     }
 
@@ -106,9 +122,17 @@ impl LightningPlugin {
         Self {
             network: LightningNetwork::Mainnet,
             executor: Some(Arc::new(MockLightningExecutor::new())),
+            fee_guard: Arc::default(),
         }
     }
 
+    /// Shares a fee guard with other plugins, e.g. one holding the user's
+    /// guardrails.
+    pub fn with_fee_guard(mut self, fee_guard: Arc<FeeGuard>) -> Self {
+        self.fee_guard = fee_guard;
+        self
+    }
+
     /// Returns the network this plugin is configured for.
     pub fn network(&self) -> LightningNetwork {
         self.network
@@ -119,6 +143,28 @@ impl LightningPlugin {
         self.executor.is_some()
     }
 
+    /// Refuses routing fees above the guardrails.
+    ///
+    /// If the node cannot estimate the fee, the payment is not blocked.
+    async fn check_fee_guardrails(
+        &self,
+        executor: &Arc<dyn LightningExecutor>,
+        invoice: &str,
+        amount_msat: Option<u64>,
+    ) -> Result<()> {
+        let guardrails = self.fee_guard.guardrails();
+        let Some(amount_msat) = amount_msat else {
+            return Ok(());
+        };
+        if guardrails.max_lightning_fee_percent.is_none() {
+            return Ok(());
+        }
+        match executor.estimate_fee(invoice).await {
+            Ok(fee_msat) => guardrails.check_lightning(amount_msat, fee_msat),
+            Err(_) => Ok(()),
+        }
+    }
+
     /// Validates a BOLT11 invoice.
     fn validate_bolt11(&self, invoice: &str) -> ValidationResult {
         let invoice = invoice.trim().to_lowercase();
@@ -211,17 +257,16 @@ impl LightningPlugin {
 
         // Try parsing as JSON first
         if data_str.starts_with('{') {
-            let json: serde_json::Value = serde_json::from_str(data_str)
-                .map_err(|e| PaykitError::Transport(format!("Invalid JSON: {}", e)))?;
+            let fields = EndpointFields::parse(data_str)?;
 
             // Look for BOLT11 invoice
-            if let Some(bolt11) = json.get("bolt11").and_then(|v| v.as_str()) {
-                return Ok(PaymentData::Bolt11(bolt11.to_string()));
+            if let Some(bolt11) = fields.bolt11 {
+                return Ok(PaymentData::Bolt11(bolt11.into_owned()));
             }
 
             // Look for LNURL
-            if let Some(lnurl) = json.get("lnurl").and_then(|v| v.as_str()) {
-                return Ok(PaymentData::Lnurl(lnurl.to_string()));
+            if let Some(lnurl) = fields.lnurl {
+                return Ok(PaymentData::Lnurl(lnurl.into_owned()));
             }
 
             return Err(PaykitError::Transport(
@@ -300,6 +345,21 @@ pub fn verify_lightning_proof(
     }
 }
 
+/// Check that `preimage` hashes to `payment_hash`, both hex encoded.
+///
+/// This is the complete check for a Lightning receipt: it needs no node,
+/// so the web and Node.js bindings use it to verify proofs locally.
+pub fn preimage_matches_hash(preimage: &str, payment_hash: &str) -> bool {
+    use sha2::{Digest, Sha256};
+
+    match (hex::decode(preimage), hex::decode(payment_hash)) {
+        (Ok(preimage), Ok(payment_hash)) => {
+            preimage.len() == 32 && Sha256::digest(&preimage).as_slice() == payment_hash
+        }
+        _ => false,
+    }
+}
+
 #[async_trait]
 impl PaymentMethodPlugin for LightningPlugin {
     fn method_id(&self) -> MethodId {
@@ -314,6 +374,16 @@ impl PaymentMethodPlugin for LightningPlugin {
         "Pay using the Lightning Network for instant, low-fee Bitcoin payments. Supports BOLT11 invoices and LNURL."
     }
 
+    fn capabilities(&self) -> PluginCapabilities {
+        // Lightning has practical limits
+        // Minimum: 1 sat (some nodes require higher)
+        // Maximum: depends on channel capacity, but ~0.04 BTC is common limit
+        PluginCapabilities::new(SettlementKind::OffChain)
+            .with_execution(self.has_executor())
+            .with_route_probing()
+            .with_amount_range(Some(1), Some(4_000_000))
+    }
+
     fn validate_endpoint(&self, data: &EndpointData) -> ValidationResult {
         match self.extract_payment_data(data) {
             Ok(PaymentData::Bolt11(invoice)) => self.validate_bolt11(&invoice),
@@ -358,6 +428,11 @@ impl PaymentMethodPlugin for LightningPlugin {
         if let Some(executor) = &self.executor {
             match &payment_data {
                 PaymentData::Bolt11(invoice) => {
+                    if !fee_spike_allowed(metadata) {
+                        self.check_fee_guardrails(executor, invoice, amount_msat)
+                            .await?;
+                    }
+
                     match executor
                         .pay_invoice(invoice, amount_msat, max_fee_msat)
                         .await
@@ -508,16 +583,7 @@ impl PaymentMethodPlugin for LightningPlugin {
     }
 
     fn supports_amount(&self, amount: &Amount) -> bool {
-        // Lightning has practical limits
-        // Minimum: 1 sat (some nodes require higher)
-        // Maximum: depends on channel capacity, but ~0.04 BTC is common limit
-        if amount.currency.to_uppercase() == "SAT" {
-            if let Some(sats) = amount.as_u64() {
-                // Minimum 1 sat, max ~4,000,000 sats (0.04 BTC)
-                return (1..=4_000_000).contains(&sats);
-            }
-        }
-        true
+        self.capabilities().allows_amount(amount)
     }
 
     fn estimated_confirmation_time(&self) -> Option<u64> {
@@ -538,6 +604,7 @@ fn current_timestamp() -> i64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::policy::FeeGuardrails;
 
     #[test]
     fn test_plugin_id() {
@@ -554,6 +621,16 @@ mod tests {
         assert!(!plugin_no_exec.has_executor());
     }
 
+    #[test]
+    fn test_capabilities() {
+        let caps = LightningPlugin::with_mock_executor().capabilities();
+        assert_eq!(caps.settlement, SettlementKind::OffChain);
+        assert!(caps.execution && caps.route_probing);
+        assert_eq!(caps.max_amount_sats, Some(4_000_000));
+
+        assert!(!LightningPlugin::new().capabilities().execution);
+    }
+
     #[test]
     fn test_validate_bolt11_mainnet() {
         let plugin = LightningPlugin::new();
@@ -664,6 +741,35 @@ mod tests {
         assert!(result.execution_data.get("payment_hash").is_some());
     }
 
+    #[tokio::test]
+    async fn test_execute_payment_fee_guardrails() {
+        let guard = Arc::new(FeeGuard::new(
+            FeeGuardrails::default().with_max_lightning_fee_percent(0.5),
+        ));
+        let plugin = LightningPlugin::with_mock_executor().with_fee_guard(guard.clone());
+
+        let invoice = "lnbc1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdpl2pkx2ctnv5sxxmmwwd5kgetjypeh2ursdae8g6twvus8g6rfwvs8qun0dfjkxaq8rkx3yf5tcsyz3d73gafnh3cax9rn449d9p5uxz9ezhhypd0elx87sjle52x86fux2ypatgddc6k63n7erqz25le42c4u4ecky03ylcqca784w";
+        let endpoint = EndpointData(invoice.to_string());
+        let metadata = serde_json::json!({});
+
+        // The mock estimates a 1 sat fee: 0.1% of 1000 sats, 1% of 100 sats
+        let result = plugin
+            .execute_payment(&endpoint, &Amount::sats(1000), &metadata)
+            .await;
+        assert!(result.unwrap().success);
+
+        let result = plugin
+            .execute_payment(&endpoint, &Amount::sats(100), &metadata)
+            .await;
+        assert!(matches!(result, Err(PaykitError::FeeSpike { .. })));
+
+        guard.set_guardrails(FeeGuardrails::default());
+        let result = plugin
+            .execute_payment(&endpoint, &Amount::sats(100), &metadata)
+            .await;
+        assert!(result.unwrap().success);
+    }
+
     #[tokio::test]
     async fn test_execute_payment_without_executor() {
         let plugin = LightningPlugin::new();
@@ -759,4 +865,13 @@ mod tests {
         let result = verify_lightning_proof(&pending_proof, None).unwrap();
         assert!(!result);
     }
+
+    #[test]
+    fn test_preimage_matches_hash() {
+        let preimage = "00".repeat(32);
+        let payment_hash = "66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925";
+        assert!(preimage_matches_hash(&preimage, payment_hash));
+        assert!(!preimage_matches_hash(&"01".repeat(32), payment_hash));
+        assert!(!preimage_matches_hash("zz", payment_hash));
+    }
 }
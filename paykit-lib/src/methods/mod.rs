@@ -10,6 +10,8 @@
 //! - **Traits**: Core abstractions for payment methods (`PaymentMethodPlugin`)
 //! - **Registry**: Dynamic registration and lookup of plugins (`PaymentMethodRegistry`)
 //! - **Built-in Plugins**: Default implementations for Bitcoin on-chain and Lightning
+//! - **E-Cash**: `EcashPlugin` for Fedimint federations, registered by wallets
+//!   that connect an `EcashExecutor`
 //!
 //! # Example
 //!
@@ -81,6 +83,10 @@
 //! }
 //! ```
 
+mod capabilities;
+mod config;
+mod custom;
+mod ecash;
 mod executor;
 mod lightning;
 mod onchain;
@@ -90,17 +96,36 @@ mod traits;
 // Re-export core traits and types
 pub use traits::{Amount, PaymentExecution, PaymentMethodPlugin, PaymentProof, ValidationResult};
 
+// Re-export capability descriptors
+pub use capabilities::{MethodDescriptor, PluginCapabilities, SettlementKind};
+
+// Re-export registry weighting configuration
+pub use config::{MethodWeight, RegistryConfig};
+
+// Re-export custom method support
+pub use custom::{CustomMethodHandler, CustomMethodPlugin};
+
 // Re-export registry
-pub use registry::{global, PaymentMethodRegistry};
+pub use registry::{global, PaymentMethodRegistry, RegistryEvent, RegistryListener};
 
 // Re-export built-in plugins
-pub use lightning::{verify_lightning_proof, LightningNetwork, LightningPlugin};
+pub use lightning::{
+    preimage_matches_hash, verify_lightning_proof, LightningNetwork, LightningPlugin,
+};
 pub use onchain::{verify_bitcoin_proof, BitcoinNetwork, OnchainPlugin};
 
+// Re-export the Fedimint e-cash plugin (not in the default registry)
+pub use ecash::{
+    verify_ecash_proof, EcashEndpoint, EcashExecutor, EcashPlugin, EcashReceive, EcashSpend,
+    EcashSpendTranscript, MockEcashExecutor, ECASH_METHOD_ID,
+};
+
 // Re-export executor traits and types
 pub use executor::{
-    BitcoinExecutor, BitcoinTxResult, DecodedInvoice, LightningExecutor, LightningPaymentResult,
-    LightningPaymentStatus, MockBitcoinExecutor, MockLightningExecutor,
+    BitcoinExecutor, BitcoinTxResult, ChangePreference, CoinSelection, DecodedInvoice,
+    FeeBumpResult, FeeBumpStrategy, LightningExecutor, LightningPaymentResult,
+    LightningPaymentStatus, MockBitcoinExecutor, MockLightningExecutor, RouteProbe, SpendHints,
+    COIN_SELECTION_METADATA_KEY,
 };
 
 /// Convenience function to create a registry with all built-in plugins.
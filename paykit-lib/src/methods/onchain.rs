@@ -19,11 +19,36 @@
 //! impl BitcoinExecutor for MyWallet { /* ... */ }
 //! let plugin = OnchainPlugin::with_executor(Arc::new(MyWallet::new()));
 //! ```
-
-use super::executor::{BitcoinExecutor, MockBitcoinExecutor};
+//!
+//! # Coin Selection
+//!
+//! Payers can steer which coins fund a payment by putting a
+//! [`CoinSelection`](super::CoinSelection) under `"coin_selection"` in the
+//! payment metadata:
+//!
+//! ```json
+//! { "coin_selection": { "avoid_outpoints": ["<txid>:0"], "confirmed_only": true,
+//!                       "max_inputs": 3, "change": "match_payment_type" } }
+//! ```
+//!
+//! The hints go to `BitcoinExecutor::send_with_coin_selection`. The execution
+//! data records them along with whether the wallet honored them.
+//!
+//! # Fee Guardrails
+//!
+//! Fee estimates are cached by the plugin's
+//! [`FeeGuard`](crate::policy::FeeGuard). Before sending, the fee rate (the
+//! `"fee_rate"` metadata, or the estimate for a typical transaction) is
+//! checked against its guardrails; a payment above the limit fails with
+//! [`PaykitError::FeeSpike`] unless `"allow_fee_spike": true` is set.
+
+use super::capabilities::{PluginCapabilities, SettlementKind};
+use super::executor::{BitcoinExecutor, CoinSelection, MockBitcoinExecutor};
 use super::traits::{
     Amount, PaymentExecution, PaymentMethodPlugin, PaymentProof, ValidationResult,
 };
+use crate::policy::{fee_spike_allowed, FeeGuard};
+use crate::protocol::EndpointFields;
 use crate::{EndpointData, MethodId, PaykitError, Result};
 use async_trait::async_trait;
 use serde_json::Value;
@@ -47,8 +72,16 @@ pub struct OnchainPlugin {
     network: BitcoinNetwork,
     /// Optional executor for actual payments.
     executor: Option<Arc<dyn BitcoinExecutor>>,
+    /// Fee estimate cache and guardrails.
+    fee_guard: Arc<FeeGuard>,
 }
 
+/// Confirmation target used for fee estimates.
+const FEE_TARGET_BLOCKS: u32 = 6;
+
+/// Size of a typical one-input, two-output transaction, in vbytes.
+const TYPICAL_TX_VBYTES: f64 = 140.0;
+
 /// Bitcoin network types.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum BitcoinNetwork {
@@ -69,6 +102,7 @@ impl OnchainPlugin {
         Self {
             network: BitcoinNetwork::Mainnet,
             executor: None,
+            fee_guard: Arc::default(),
         }
     }
 
@@ -77,6 +111,7 @@ impl OnchainPlugin {
         Self {
             network,
             executor: None,
+            fee_guard: Arc::default(),
         }
     }
 
@@ -87,6 +122,7 @@ impl OnchainPlugin {
         Self {
             network: BitcoinNetwork::Mainnet,
             executor: Some(executor),
+            fee_guard: Arc::default(),
         }
     }
 
@@ -98,6 +134,7 @@ impl OnchainPlugin {
         Self {
             network,
             executor: Some(executor),
+            fee_guard: Arc::default(),
         }
     }
 
@@ -106,9 +143,17 @@ impl OnchainPlugin {
         Self {
             network: BitcoinNetwork::Mainnet,
             executor: Some(Arc::new(MockBitcoinExecutor::new())),
+            fee_guard: Arc::default(),
         }
     }
 
+    /// Shares a fee guard with other plugins, e.g. one holding the user's
+    /// guardrails.
+    pub fn with_fee_guard(mut self, fee_guard: Arc<FeeGuard>) -> Self {
+        self.fee_guard = fee_guard;
+        self
+    }
+
     /// Returns the network this plugin is configured for.
     pub fn network(&self) -> BitcoinNetwork {
         self.network
@@ -119,6 +164,48 @@ impl OnchainPlugin {
         self.executor.is_some()
     }
 
+    /// Estimates the fee in sats, reusing a recent estimate when possible.
+    async fn cached_fee_estimate(
+        &self,
+        executor: &Arc<dyn BitcoinExecutor>,
+        address: &str,
+        amount_sats: u64,
+    ) -> Result<u64> {
+        self.fee_guard
+            .estimate(&self.method_id(), FEE_TARGET_BLOCKS, amount_sats, || {
+                executor.estimate_fee(address, amount_sats, FEE_TARGET_BLOCKS)
+            })
+            .await
+    }
+
+    /// Refuses fee rates above the guardrails.
+    ///
+    /// Without an explicit fee rate, the cached estimate is converted to a
+    /// rate; if the wallet cannot estimate, the payment is not blocked.
+    async fn check_fee_guardrails(
+        &self,
+        executor: &Arc<dyn BitcoinExecutor>,
+        address: &str,
+        amount_sats: u64,
+        fee_rate: Option<f64>,
+    ) -> Result<()> {
+        let guardrails = self.fee_guard.guardrails();
+        if guardrails.max_onchain_fee_rate.is_none() {
+            return Ok(());
+        }
+        let fee_rate = match fee_rate {
+            Some(rate) => rate,
+            None => match self
+                .cached_fee_estimate(executor, address, amount_sats)
+                .await
+            {
+                Ok(fee) => fee as f64 / TYPICAL_TX_VBYTES,
+                Err(_) => return Ok(()),
+            },
+        };
+        guardrails.check_onchain(fee_rate)
+    }
+
     /// Validates a Bitcoin address.
     fn validate_address(&self, address: &str) -> ValidationResult {
         let address = address.trim();
@@ -221,14 +308,11 @@ impl OnchainPlugin {
 
         // Try parsing as JSON first
         if data_str.starts_with('{') {
-            let json: serde_json::Value = serde_json::from_str(data_str)
-                .map_err(|e| PaykitError::Transport(format!("Invalid JSON: {}", e)))?;
+            let fields = EndpointFields::parse(data_str)?;
 
             // Look for common address field names
-            for key in ["address", "p2wpkh", "p2tr", "p2sh", "p2pkh"] {
-                if let Some(addr) = json.get(key).and_then(|v| v.as_str()) {
-                    return Ok(addr.to_string());
-                }
+            if let Some(addr) = fields.onchain_address() {
+                return Ok(addr.to_string());
             }
 
             return Err(PaykitError::Transport(
@@ -305,6 +389,14 @@ impl PaymentMethodPlugin for OnchainPlugin {
         "Pay using standard Bitcoin on-chain transactions. Supports legacy, SegWit, and Taproot addresses."
     }
 
+    fn capabilities(&self) -> PluginCapabilities {
+        // On-chain has dust limit (~546 sats for P2PKH, ~294 for P2WPKH)
+        // Use a conservative minimum of 546 sats
+        PluginCapabilities::new(SettlementKind::OnChain)
+            .with_execution(self.has_executor())
+            .with_amount_range(Some(546), None)
+    }
+
     fn validate_endpoint(&self, data: &EndpointData) -> ValidationResult {
         match self.extract_address(data) {
             Ok(address) => self.validate_address(&address),
@@ -338,14 +430,40 @@ impl PaymentMethodPlugin for OnchainPlugin {
             )));
         }
 
+        let coin_selection = CoinSelection::from_metadata(metadata)?;
+
         // Execute payment via executor if available
         if let Some(executor) = &self.executor {
             let fee_rate = metadata.get("fee_rate").and_then(|v| v.as_f64());
+            if !fee_spike_allowed(metadata) {
+                self.check_fee_guardrails(executor, &address, amount_sats, fee_rate)
+                    .await?;
+            }
 
-            match executor
-                .send_to_address(&address, amount_sats, fee_rate)
-                .await
-            {
+            // Wallets without coin control still pay; the hints are recorded
+            // as not honored
+            let mut honored = coin_selection.is_some();
+            let sent = match &coin_selection {
+                Some(selection) => match executor
+                    .send_with_coin_selection(&address, amount_sats, fee_rate, selection)
+                    .await
+                {
+                    Err(PaykitError::Unimplemented(_)) => {
+                        honored = false;
+                        executor
+                            .send_to_address(&address, amount_sats, fee_rate)
+                            .await
+                    }
+                    sent => sent,
+                },
+                None => {
+                    executor
+                        .send_to_address(&address, amount_sats, fee_rate)
+                        .await
+                }
+            };
+
+            match sent {
                 Ok(tx_result) => {
                     return Ok(PaymentExecution {
                         method_id: self.method_id(),
@@ -363,6 +481,10 @@ impl PaymentMethodPlugin for OnchainPlugin {
                             "block_height": tx_result.block_height,
                             "confirmations": tx_result.confirmations,
                             "raw_tx": tx_result.raw_tx,
+                            "coin_selection": coin_selection.map(|selection| serde_json::json!({
+                                "requested": selection,
+                                "honored": honored,
+                            })),
                             "metadata": metadata,
                         }),
                         error: None,
@@ -437,8 +559,10 @@ impl PaymentMethodPlugin for OnchainPlugin {
     async fn estimate_fee(&self, amount: &Amount) -> Option<Amount> {
         if let Some(executor) = &self.executor {
             let amount_sats = amount.as_u64()?;
-            // Use 6 blocks as default target
-            if let Ok(fee) = executor.estimate_fee("bc1qtest", amount_sats, 6).await {
+            if let Ok(fee) = self
+                .cached_fee_estimate(executor, "bc1qtest", amount_sats)
+                .await
+            {
                 return Some(Amount::sats(fee));
             }
         }
@@ -468,14 +592,7 @@ impl PaymentMethodPlugin for OnchainPlugin {
     }
 
     fn supports_amount(&self, amount: &Amount) -> bool {
-        // On-chain has dust limit (~546 sats for P2PKH, ~294 for P2WPKH)
-        // Use a conservative minimum of 546 sats
-        if amount.currency.to_uppercase() == "SAT" {
-            if let Some(sats) = amount.as_u64() {
-                return sats >= 546;
-            }
-        }
-        true
+        self.capabilities().allows_amount(amount)
     }
 
     fn estimated_confirmation_time(&self) -> Option<u64> {
@@ -496,6 +613,7 @@ fn current_timestamp() -> i64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::policy::FeeGuardrails;
 
     #[test]
     fn test_plugin_id() {
@@ -604,6 +722,60 @@ mod tests {
         assert!(result.execution_data.get("fee_sats").is_some());
     }
 
+    #[tokio::test]
+    async fn test_execute_payment_records_coin_selection() {
+        let plugin = OnchainPlugin::with_mock_executor();
+
+        let endpoint = EndpointData("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string());
+        let amount = Amount::sats(10000);
+        let metadata = serde_json::json!({
+            "coin_selection": { "confirmed_only": true, "max_inputs": 2 }
+        });
+
+        let result = plugin
+            .execute_payment(&endpoint, &amount, &metadata)
+            .await
+            .unwrap();
+
+        let recorded = &result.execution_data["coin_selection"];
+        assert_eq!(recorded["honored"], true);
+        assert_eq!(recorded["requested"]["max_inputs"], 2);
+
+        let bad = serde_json::json!({ "coin_selection": { "max_inputs": "two" } });
+        assert!(plugin
+            .execute_payment(&endpoint, &amount, &bad)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_execute_payment_fee_guardrails() {
+        let guard = Arc::new(FeeGuard::new(
+            FeeGuardrails::default().with_max_onchain_fee_rate(5.0),
+        ));
+        let plugin = OnchainPlugin::with_mock_executor().with_fee_guard(guard);
+
+        let endpoint = EndpointData("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string());
+        let amount = Amount::sats(10000);
+
+        // The mock estimates 2 sat/vB for six blocks
+        let result = plugin
+            .execute_payment(&endpoint, &amount, &serde_json::json!({}))
+            .await;
+        assert!(result.unwrap().success);
+
+        let spike = serde_json::json!({ "fee_rate": 80.0 });
+        let result = plugin.execute_payment(&endpoint, &amount, &spike).await;
+        assert!(matches!(result, Err(PaykitError::FeeSpike { .. })));
+
+        let confirmed = serde_json::json!({ "fee_rate": 80.0, "allow_fee_spike": true });
+        let result = plugin
+            .execute_payment(&endpoint, &amount, &confirmed)
+            .await
+            .unwrap();
+        assert!(result.success);
+    }
+
     #[tokio::test]
     async fn test_execute_payment_without_executor() {
         let plugin = OnchainPlugin::new();
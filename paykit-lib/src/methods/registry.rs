@@ -5,17 +5,69 @@
 //!
 //! # Thread Safety
 //!
-//! The registry uses `RwLock` for thread-safe access. All public methods will panic
-//! if the internal lock is poisoned (which only happens if a thread panics while
-//! holding the lock). In well-tested applications, this should never occur.
+//! The registry uses a `parking_lot::RwLock`, which cannot be poisoned, so a
+//! panicking plugin cannot take the registry down with it. Lookups clone the
+//! plugin `Arc` out and release the lock before returning; no lock is ever
+//! held while a plugin runs, so the registry is safe to use from async code
+//! and from inside `block_on`.
 //!
 //! If you need fallible access, use [`get_required`](PaymentMethodRegistry::get_required)
 //! which returns a `Result`.
+//!
+//! # Method IDs and Collisions
+//!
+//! Un-namespaced IDs (`onchain`, `lightning`) are reserved for methods
+//! defined by Paykit; third-party plugins use a reverse-domain namespace
+//! such as `org.example.voucher`. Three registration APIs make the
+//! collision behavior explicit:
+//!
+//! - [`try_register`](PaymentMethodRegistry::try_register) validates the ID
+//!   and fails if the method is already registered.
+//! - [`replace`](PaymentMethodRegistry::replace) installs the plugin and
+//!   returns the one it replaced, e.g. to swap in an executor-backed plugin.
+//! - [`register`](PaymentMethodRegistry::register) replaces silently, as it
+//!   always has.
+//!
+//! Every change is reported to listeners added with
+//! [`on_change`](PaymentMethodRegistry::on_change), so replacements are
+//! observable whichever API made them.
+//!
+//! # Weighting
+//!
+//! The registry also carries a [`RegistryConfig`] of per-method weights that
+//! selectors add on top of their strategy. It can be replaced at runtime with
+//! [`set_config`](PaymentMethodRegistry::set_config).
 
+use super::capabilities::MethodDescriptor;
+use super::config::{MethodWeight, RegistryConfig};
 use super::traits::PaymentMethodPlugin;
 use crate::{MethodId, PaykitError, Result};
+use parking_lot::RwLock;
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::sync::Arc;
+
+/// A change to the set of registered plugins.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RegistryEvent {
+    /// A plugin was registered under a new method ID.
+    Registered(MethodId),
+    /// A plugin replaced the one registered under the same method ID.
+    Replaced(MethodId),
+    /// A plugin was removed.
+    Unregistered(MethodId),
+}
+
+impl RegistryEvent {
+    /// The method ID the event is about.
+    pub fn method_id(&self) -> &MethodId {
+        match self {
+            Self::Registered(id) | Self::Replaced(id) | Self::Unregistered(id) => id,
+        }
+    }
+}
+
+/// Listener for registry changes.
+pub type RegistryListener = Arc<dyn Fn(&RegistryEvent) + Send + Sync>;
 
 /// Registry for payment method plugins.
 ///
@@ -35,6 +87,8 @@ use std::sync::{Arc, RwLock};
 /// ```
 pub struct PaymentMethodRegistry {
     plugins: RwLock<HashMap<String, Arc<dyn PaymentMethodPlugin>>>,
+    listeners: RwLock<Vec<RegistryListener>>,
+    config: RwLock<RegistryConfig>,
 }
 
 impl PaymentMethodRegistry {
@@ -42,6 +96,8 @@ impl PaymentMethodRegistry {
     pub fn new() -> Self {
         Self {
             plugins: RwLock::new(HashMap::new()),
+            listeners: RwLock::new(Vec::new()),
+            config: RwLock::new(RegistryConfig::default()),
         }
     }
 
@@ -58,24 +114,120 @@ impl PaymentMethodRegistry {
 
     /// Registers a payment method plugin.
     ///
-    /// If a plugin with the same method ID already exists, it will be replaced.
+    /// If a plugin with the same method ID already exists, it will be replaced
+    /// and listeners see [`RegistryEvent::Replaced`]. Use
+    /// [`try_register`](Self::try_register) to fail on collisions instead.
     pub fn register(&self, plugin: Box<dyn PaymentMethodPlugin>) {
-        let method_id = plugin.method_id().0.clone();
-        let mut plugins = self.plugins.write().unwrap_or_else(|e| e.into_inner());
-        plugins.insert(method_id, Arc::from(plugin));
+        self.replace(plugin);
+    }
+
+    /// Registers a plugin under a method ID that is not taken yet.
+    ///
+    /// Fails if the ID is malformed, is an un-namespaced ID other than a
+    /// built-in one, or is already registered.
+    pub fn try_register(&self, plugin: Box<dyn PaymentMethodPlugin>) -> Result<()> {
+        let method_id = plugin.method_id();
+        method_id.validate()?;
+        if !method_id.is_builtin() && !method_id.is_namespaced() {
+            return Err(PaykitError::invalid_data(
+                "method_id",
+                format!(
+                    "{} must be namespaced, e.g. org.example.{}",
+                    method_id.0, method_id.0
+                ),
+            ));
+        }
+
+        {
+            let mut plugins = self.plugins.write();
+            if plugins.contains_key(&method_id.0) {
+                return Err(PaykitError::invalid_data(
+                    "method_id",
+                    format!("{} is already registered", method_id.0),
+                ));
+            }
+            plugins.insert(method_id.0.clone(), Arc::from(plugin));
+        }
+
+        self.notify(&RegistryEvent::Registered(method_id));
+        Ok(())
+    }
+
+    /// Registers a plugin, replacing any plugin with the same method ID.
+    ///
+    /// Returns the replaced plugin, if there was one.
+    pub fn replace(
+        &self,
+        plugin: Box<dyn PaymentMethodPlugin>,
+    ) -> Option<Arc<dyn PaymentMethodPlugin>> {
+        let method_id = plugin.method_id();
+        let previous = self
+            .plugins
+            .write()
+            .insert(method_id.0.clone(), Arc::from(plugin));
+
+        let event = if previous.is_some() {
+            RegistryEvent::Replaced(method_id)
+        } else {
+            RegistryEvent::Registered(method_id)
+        };
+        self.notify(&event);
+        previous
     }
 
     /// Unregisters a payment method plugin.
     ///
     /// Returns the removed plugin if it existed.
     pub fn unregister(&self, method_id: &MethodId) -> Option<Arc<dyn PaymentMethodPlugin>> {
-        let mut plugins = self.plugins.write().unwrap_or_else(|e| e.into_inner());
-        plugins.remove(&method_id.0)
+        let removed = self.plugins.write().remove(&method_id.0);
+        if removed.is_some() {
+            self.notify(&RegistryEvent::Unregistered(method_id.clone()));
+        }
+        removed
+    }
+
+    /// Adds a listener for registrations, replacements and removals.
+    ///
+    /// Listeners run after the registry lock is released, so they may use
+    /// the registry themselves.
+    pub fn on_change(&self, listener: RegistryListener) {
+        self.listeners.write().push(listener);
+    }
+
+    /// Returns a snapshot of the weighting configuration.
+    pub fn config(&self) -> RegistryConfig {
+        self.config.read().clone()
+    }
+
+    /// Replaces the weighting configuration.
+    pub fn set_config(&self, config: RegistryConfig) -> Result<()> {
+        config.validate()?;
+        *self.config.write() = config;
+        Ok(())
+    }
+
+    /// Sets the weight of one method, keeping the rest of the configuration.
+    pub fn set_method_weight(&self, method_id: &MethodId, weight: MethodWeight) -> Result<()> {
+        weight.validate(method_id)?;
+        self.config.write().set_weight(method_id, weight);
+        Ok(())
+    }
+
+    /// Removes the weight of one method, returning it if there was one.
+    pub fn remove_method_weight(&self, method_id: &MethodId) -> Option<MethodWeight> {
+        self.config.write().remove_weight(method_id)
+    }
+
+    fn notify(&self, event: &RegistryEvent) {
+        let listeners = self.listeners.read().clone();
+        for listener in &listeners {
+            listener(event);
+        }
     }
 
     /// Gets a payment method plugin by its ID.
     pub fn get(&self, method_id: &MethodId) -> Option<Arc<dyn PaymentMethodPlugin>> {
-        let plugins = self.plugins.read().unwrap_or_else(|e| e.into_inner());
+        let plugins = self.plugins.read();
         plugins.get(&method_id.0).cloned()
     }
 
@@ -88,13 +240,13 @@ impl PaymentMethodRegistry {
 
     /// Returns all registered method IDs.
     pub fn list_methods(&self) -> Vec<MethodId> {
-        let plugins = self.plugins.read().unwrap_or_else(|e| e.into_inner());
+        let plugins = self.plugins.read();
         plugins.keys().map(|k| MethodId(k.clone())).collect()
     }
 
     /// Returns the number of registered plugins.
     pub fn len(&self) -> usize {
-        let plugins = self.plugins.read().unwrap_or_else(|e| e.into_inner());
+        let plugins = self.plugins.read();
         plugins.len()
     }
 
@@ -105,7 +257,7 @@ impl PaymentMethodRegistry {
 
     /// Checks if a method is registered.
     pub fn has_method(&self, method_id: &MethodId) -> bool {
-        let plugins = self.plugins.read().unwrap_or_else(|e| e.into_inner());
+        let plugins = self.plugins.read();
         plugins.contains_key(&method_id.0)
     }
 
@@ -116,19 +268,35 @@ impl PaymentMethodRegistry {
         &self,
         method_ids: &[MethodId],
     ) -> Vec<(MethodId, Arc<dyn PaymentMethodPlugin>)> {
-        let plugins = self.plugins.read().unwrap_or_else(|e| e.into_inner());
+        let plugins = self.plugins.read();
         method_ids
             .iter()
             .filter_map(|id| plugins.get(&id.0).map(|p| (id.clone(), p.clone())))
             .collect()
     }
 
+    /// Describes every registered method, sorted by method ID.
+    pub fn describe(&self) -> Vec<MethodDescriptor> {
+        let plugins: Vec<_> = self.plugins.read().values().cloned().collect();
+        let mut descriptors: Vec<MethodDescriptor> = plugins
+            .iter()
+            .map(|plugin| MethodDescriptor {
+                method_id: plugin.method_id(),
+                display_name: plugin.display_name().to_string(),
+                description: plugin.description().to_string(),
+                capabilities: plugin.capabilities(),
+            })
+            .collect();
+        descriptors.sort_by(|a, b| a.method_id.0.cmp(&b.method_id.0));
+        descriptors
+    }
+
     /// Filters methods by a predicate.
     pub fn filter<F>(&self, predicate: F) -> Vec<Arc<dyn PaymentMethodPlugin>>
     where
         F: Fn(&dyn PaymentMethodPlugin) -> bool,
     {
-        let plugins = self.plugins.read().unwrap_or_else(|e| e.into_inner());
+        let plugins = self.plugins.read();
         plugins
             .values()
             .filter(|p| predicate(p.as_ref()))
@@ -143,11 +311,15 @@ impl Default for PaymentMethodRegistry {
     }
 }
 
+/// Clones the registered plugins and weighting configuration; listeners are
+/// not carried over to the new registry.
 impl Clone for PaymentMethodRegistry {
     fn clone(&self) -> Self {
-        let plugins = self.plugins.read().unwrap_or_else(|e| e.into_inner());
+        let plugins = self.plugins.read();
         Self {
             plugins: RwLock::new(plugins.clone()),
+            listeners: RwLock::new(Vec::new()),
+            config: RwLock::new(self.config()),
         }
     }
 }
@@ -185,6 +357,7 @@ pub mod global {
 
 #[cfg(test)]
 mod tests {
+    use super::super::capabilities::PluginCapabilities;
     use super::super::traits::{Amount, PaymentExecution, PaymentProof, ValidationResult};
     use super::*;
     use async_trait::async_trait;
@@ -295,6 +468,77 @@ mod tests {
         assert_eq!(results.len(), 2);
     }
 
+    #[test]
+    fn test_registry_describe() {
+        let registry = crate::methods::default_registry();
+        registry.register(Box::new(MockPlugin::new("custom")));
+
+        let descriptors = registry.describe();
+        let ids: Vec<_> = descriptors.iter().map(|d| d.method_id.0.as_str()).collect();
+        assert_eq!(ids, vec!["custom", "lightning", "onchain"]);
+
+        // Plugins that do not override `capabilities` get the defaults
+        assert_eq!(descriptors[0].capabilities, PluginCapabilities::default());
+        assert!(descriptors[1].capabilities.route_probing);
+        assert!(descriptors[2].capabilities.is_on_chain());
+    }
+
+    #[test]
+    fn test_registry_try_register_collisions() {
+        let registry = crate::methods::default_registry();
+
+        assert!(registry
+            .try_register(Box::new(MockPlugin::new("org.example.voucher")))
+            .is_ok());
+        assert!(registry
+            .try_register(Box::new(MockPlugin::new("org.example.voucher")))
+            .is_err());
+        assert!(registry
+            .try_register(Box::new(MockPlugin::new("onchain")))
+            .is_err());
+
+        // Un-namespaced and malformed IDs are rejected
+        assert!(registry
+            .try_register(Box::new(MockPlugin::new("voucher")))
+            .is_err());
+        assert!(registry
+            .try_register(Box::new(MockPlugin::new("org..voucher")))
+            .is_err());
+        assert!(registry
+            .try_register(Box::new(MockPlugin::new("Org.Example.Voucher")))
+            .is_err());
+
+        let previous = registry.replace(Box::new(MockPlugin::new("org.example.voucher")));
+        assert!(previous.is_some());
+        assert_eq!(
+            MethodId::new("org.example.voucher").namespace(),
+            Some("org.example")
+        );
+    }
+
+    #[test]
+    fn test_registry_events() {
+        let registry = PaymentMethodRegistry::new();
+        let events = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let seen = events.clone();
+        registry.on_change(Arc::new(move |event| seen.lock().push(event.clone())));
+
+        let id = MethodId::new("org.example.voucher");
+        registry.register(Box::new(MockPlugin::new(&id.0)));
+        registry.register(Box::new(MockPlugin::new(&id.0)));
+        registry.unregister(&id);
+        registry.unregister(&id);
+
+        assert_eq!(
+            *events.lock(),
+            vec![
+                RegistryEvent::Registered(id.clone()),
+                RegistryEvent::Replaced(id.clone()),
+                RegistryEvent::Unregistered(id),
+            ]
+        );
+    }
+
     #[test]
     fn test_registry_clone() {
         let registry = PaymentMethodRegistry::new();
@@ -4,6 +4,7 @@
 //! Any payment method (onchain, lightning, ethereum, etc.) can implement
 //! these traits to integrate with Paykit.
 
+use super::capabilities::PluginCapabilities;
 use crate::{EndpointData, MethodId, PaykitError, Result};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -214,6 +215,15 @@ pub trait PaymentMethodPlugin: Send + Sync {
     /// Returns a description of this payment method.
     fn description(&self) -> &str;
 
+    /// Describes what this plugin supports.
+    ///
+    /// Selection and payment flows branch on these rather than on the method
+    /// ID. The default declares an executing, proof-producing plugin with an
+    /// unspecified settlement layer.
+    fn capabilities(&self) -> PluginCapabilities {
+        PluginCapabilities::default()
+    }
+
     /// Validates endpoint data for this payment method.
     ///
     /// Returns a `ValidationResult` indicating whether the endpoint is valid.
@@ -0,0 +1,173 @@
+//! Counterparty compliance screening.
+
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Receipt metadata key under which screening results are recorded.
+pub const COMPLIANCE_METADATA_KEY: &str = "compliance";
+
+/// A screener shared between the components that enforce it.
+pub type SharedComplianceScreener = Arc<dyn ComplianceScreener>;
+
+/// Which side of the payment is being screened.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreeningDirection {
+    /// We are paying the payee.
+    Outgoing,
+    /// The payer is paying us.
+    Incoming,
+}
+
+/// Payment details passed to a [`ComplianceScreener`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScreeningRequest {
+    /// Which side of the payment we are on.
+    pub direction: ScreeningDirection,
+    /// Payer public key (z-base-32).
+    pub payer: String,
+    /// Payee public key (z-base-32).
+    pub payee: String,
+    /// Payment method identifier.
+    pub method_id: String,
+    /// Amount, if known.
+    pub amount: Option<String>,
+    /// Currency code, if known.
+    pub currency: Option<String>,
+    /// Payment metadata.
+    pub metadata: serde_json::Value,
+}
+
+/// Outcome of screening a payment.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum ScreeningResult {
+    /// No match; the payment may proceed.
+    Pass,
+    /// Possible match; the payment proceeds but is flagged for review.
+    Review {
+        /// Why the payment was flagged.
+        reason: String,
+    },
+    /// Confirmed match; the payment must not proceed.
+    Block {
+        /// Why the payment was blocked.
+        reason: String,
+    },
+}
+
+impl ScreeningResult {
+    /// Get the outcome as a string.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScreeningResult::Pass => "pass",
+            ScreeningResult::Review { .. } => "review",
+            ScreeningResult::Block { .. } => "block",
+        }
+    }
+
+    /// Reason given by the screener, if any.
+    pub fn reason(&self) -> Option<&str> {
+        match self {
+            ScreeningResult::Pass => None,
+            ScreeningResult::Review { reason } | ScreeningResult::Block { reason } => Some(reason),
+        }
+    }
+
+    /// Whether the payment must be refused.
+    pub fn is_blocked(&self) -> bool {
+        matches!(self, ScreeningResult::Block { .. })
+    }
+
+    /// Record this result in receipt metadata under [`COMPLIANCE_METADATA_KEY`].
+    ///
+    /// Non-object metadata is replaced by an object holding only the result.
+    pub fn record_in(&self, metadata: &mut serde_json::Value, screened_at: i64) {
+        if !metadata.is_object() {
+            *metadata = serde_json::json!({});
+        }
+        let mut record = serde_json::json!({
+            "result": self.as_str(),
+            "screened_at": screened_at,
+        });
+        if let Some(reason) = self.reason() {
+            record["reason"] = serde_json::Value::String(reason.to_string());
+        }
+        if let Some(obj) = metadata.as_object_mut() {
+            obj.insert(COMPLIANCE_METADATA_KEY.to_string(), record);
+        }
+    }
+
+    /// Read a result previously recorded with [`record_in`](Self::record_in).
+    pub fn from_metadata(metadata: &serde_json::Value) -> Option<Self> {
+        let record = metadata.get(COMPLIANCE_METADATA_KEY)?;
+        let reason = record
+            .get("reason")
+            .and_then(|r| r.as_str())
+            .unwrap_or_default()
+            .to_string();
+        match record.get("result")?.as_str()? {
+            "pass" => Some(ScreeningResult::Pass),
+            "review" => Some(ScreeningResult::Review { reason }),
+            "block" => Some(ScreeningResult::Block { reason }),
+            _ => None,
+        }
+    }
+}
+
+/// Screens payment counterparties before a payment is executed.
+///
+/// Implementations typically call out to a sanctions list or a compliance
+/// provider. Returning an error fails the payment closed.
+#[async_trait::async_trait]
+pub trait ComplianceScreener: Send + Sync {
+    /// Screen a payment.
+    async fn screen(&self, request: &ScreeningRequest) -> Result<ScreeningResult>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_read_back() {
+        let mut metadata = serde_json::json!({ "invoice": "INV-1" });
+        let result = ScreeningResult::Review {
+            reason: "name similarity".to_string(),
+        };
+        result.record_in(&mut metadata, 1_700_000_000);
+
+        assert_eq!(metadata["invoice"], "INV-1");
+        assert_eq!(metadata["compliance"]["result"], "review");
+        assert_eq!(metadata["compliance"]["screened_at"], 1_700_000_000);
+        assert_eq!(ScreeningResult::from_metadata(&metadata), Some(result));
+    }
+
+    #[test]
+    fn test_record_replaces_non_object_metadata() {
+        let mut metadata = serde_json::Value::Null;
+        ScreeningResult::Pass.record_in(&mut metadata, 1);
+
+        assert_eq!(metadata["compliance"]["result"], "pass");
+        assert!(metadata["compliance"].get("reason").is_none());
+        assert_eq!(
+            ScreeningResult::from_metadata(&metadata),
+            Some(ScreeningResult::Pass)
+        );
+    }
+
+    #[test]
+    fn test_result_serialization() {
+        let json = serde_json::to_value(ScreeningResult::Block {
+            reason: "sanctions list".to_string(),
+        })
+        .unwrap();
+        assert_eq!(json["result"], "block");
+        assert_eq!(json["reason"], "sanctions list");
+        assert!(ScreeningResult::Block {
+            reason: String::new()
+        }
+        .is_blocked());
+    }
+}
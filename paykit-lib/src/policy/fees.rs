@@ -0,0 +1,259 @@
+//! Fee estimate caching and fee-spike guardrails.
+
+use crate::{MethodId, PaykitError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// Payment metadata key that skips the guardrails once the user confirmed
+/// paying the higher fee.
+pub const ALLOW_FEE_SPIKE_METADATA_KEY: &str = "allow_fee_spike";
+
+/// Limits on the fees a payment may pay without confirmation.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeeGuardrails {
+    /// Highest on-chain fee rate, in sat/vB.
+    pub max_onchain_fee_rate: Option<f64>,
+    /// Highest Lightning routing fee, as a percentage of the amount.
+    pub max_lightning_fee_percent: Option<f64>,
+}
+
+impl FeeGuardrails {
+    /// Abort on-chain payments above `sat_per_vb`.
+    pub fn with_max_onchain_fee_rate(mut self, sat_per_vb: f64) -> Self {
+        self.max_onchain_fee_rate = Some(sat_per_vb);
+        self
+    }
+
+    /// Abort Lightning payments whose fee exceeds `percent` of the amount.
+    pub fn with_max_lightning_fee_percent(mut self, percent: f64) -> Self {
+        self.max_lightning_fee_percent = Some(percent);
+        self
+    }
+
+    /// Check an on-chain fee rate against the limit.
+    pub fn check_onchain(&self, fee_rate: f64) -> Result<()> {
+        match self.max_onchain_fee_rate {
+            Some(limit) if fee_rate > limit => Err(PaykitError::FeeSpike {
+                method: MethodId::ONCHAIN.to_string(),
+                fee: format!("{:.1} sat/vB", fee_rate),
+                limit: format!("{:.1} sat/vB", limit),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check a Lightning routing fee against the limit.
+    pub fn check_lightning(&self, amount_msat: u64, fee_msat: u64) -> Result<()> {
+        let Some(limit) = self.max_lightning_fee_percent else {
+            return Ok(());
+        };
+        if amount_msat == 0 {
+            return Ok(());
+        }
+        let percent = fee_msat as f64 * 100.0 / amount_msat as f64;
+        if percent > limit {
+            return Err(PaykitError::FeeSpike {
+                method: MethodId::LIGHTNING.to_string(),
+                fee: format!("{:.2}%", percent),
+                limit: format!("{:.2}%", limit),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Fee estimates and guardrails shared by the payment plugins.
+///
+/// Estimates are cached for a short time per method, confirmation target
+/// and amount, so method selection and execution do not query the wallet
+/// for every candidate. Expired entries are dropped whenever a fresh
+/// estimate is stored.
+pub struct FeeGuard {
+    guardrails: RwLock<FeeGuardrails>,
+    ttl: Duration,
+    estimates: Mutex<HashMap<EstimateKey, (u64, Instant)>>,
+}
+
+/// Cache key: method, confirmation target and amount in sats.
+type EstimateKey = (MethodId, u32, u64);
+
+impl Default for FeeGuard {
+    fn default() -> Self {
+        Self::new(FeeGuardrails::default())
+    }
+}
+
+impl std::fmt::Debug for FeeGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FeeGuard")
+            .field("guardrails", &self.guardrails())
+            .field("ttl", &self.ttl)
+            .finish()
+    }
+}
+
+impl FeeGuard {
+    /// How long fee estimates are reused by default.
+    pub const DEFAULT_TTL: Duration = Duration::from_secs(30);
+
+    /// Create a guard enforcing `guardrails`.
+    pub fn new(guardrails: FeeGuardrails) -> Self {
+        Self {
+            guardrails: RwLock::new(guardrails),
+            ttl: Self::DEFAULT_TTL,
+            estimates: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Reuse fee estimates for `ttl`.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Get the current limits.
+    pub fn guardrails(&self) -> FeeGuardrails {
+        self.guardrails
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Replace the limits.
+    pub fn set_guardrails(&self, guardrails: FeeGuardrails) {
+        *self.guardrails.write().unwrap_or_else(|e| e.into_inner()) = guardrails;
+    }
+
+    /// Get a cached fee estimate for paying `amount_sats`, or fetch and
+    /// cache a fresh one.
+    pub async fn estimate<F, Fut>(
+        &self,
+        method: &MethodId,
+        target: u32,
+        amount_sats: u64,
+        fetch: F,
+    ) -> Result<u64>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<u64>>,
+    {
+        let key = (method.clone(), target, amount_sats);
+        let cached = self.lock().get(&key).copied();
+        if let Some((fee, at)) = cached {
+            if at.elapsed() < self.ttl {
+                return Ok(fee);
+            }
+        }
+
+        let fee = fetch().await?;
+        let mut estimates = self.lock();
+        estimates.retain(|_, (_, at)| at.elapsed() < self.ttl);
+        estimates.insert(key, (fee, Instant::now()));
+        Ok(fee)
+    }
+
+    /// Drop all cached estimates.
+    pub fn clear_cache(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<EstimateKey, (u64, Instant)>> {
+        self.estimates.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Whether the user already confirmed paying above the guardrails.
+pub(crate) fn fee_spike_allowed(metadata: &serde_json::Value) -> bool {
+    metadata
+        .get(ALLOW_FEE_SPIKE_METADATA_KEY)
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[tokio::test]
+    async fn test_estimates_are_cached_per_target_and_amount() {
+        let guard = FeeGuard::default();
+        let calls = AtomicU32::new(0);
+        let counter = &calls;
+        let fetch = move || async move {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(420)
+        };
+
+        let onchain = MethodId::onchain();
+        assert_eq!(
+            guard.estimate(&onchain, 6, 1_000, fetch).await.unwrap(),
+            420
+        );
+        assert_eq!(
+            guard.estimate(&onchain, 6, 1_000, fetch).await.unwrap(),
+            420
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        guard.estimate(&onchain, 1, 1_000, fetch).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // A larger payment may need more inputs, so it gets its own estimate
+        let large = guard.estimate(&onchain, 6, 5_000_000, || async { Ok(1_260) });
+        assert_eq!(large.await.unwrap(), 1_260);
+        assert_eq!(
+            guard.estimate(&onchain, 6, 1_000, fetch).await.unwrap(),
+            420
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        let expired = FeeGuard::default().with_ttl(Duration::ZERO);
+        expired.estimate(&onchain, 6, 1_000, fetch).await.unwrap();
+        expired.estimate(&onchain, 6, 1_000, fetch).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_expired_estimates_are_evicted() {
+        let guard = FeeGuard::default().with_ttl(Duration::from_millis(200));
+        let onchain = MethodId::onchain();
+        for amount in 0..50 {
+            guard
+                .estimate(&onchain, 6, amount, || async { Ok(420) })
+                .await
+                .unwrap();
+        }
+        assert_eq!(guard.lock().len(), 50);
+
+        // Storing a fresh estimate drops the ones that have expired
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        guard
+            .estimate(&onchain, 6, 1_000, || async { Ok(420) })
+            .await
+            .unwrap();
+        assert_eq!(guard.lock().len(), 1);
+    }
+
+    #[test]
+    fn test_guardrails() {
+        let guardrails = FeeGuardrails::default()
+            .with_max_onchain_fee_rate(50.0)
+            .with_max_lightning_fee_percent(1.0);
+
+        assert!(guardrails.check_onchain(20.0).is_ok());
+        let err = guardrails.check_onchain(120.0).unwrap_err();
+        assert!(matches!(err, PaykitError::FeeSpike { .. }));
+
+        assert!(guardrails.check_lightning(100_000_000, 500_000).is_ok());
+        assert!(guardrails.check_lightning(100_000_000, 2_000_000).is_err());
+
+        assert!(FeeGuardrails::default().check_onchain(1_000.0).is_ok());
+        assert!(fee_spike_allowed(
+            &serde_json::json!({ "allow_fee_spike": true })
+        ));
+    }
+}
@@ -0,0 +1,158 @@
+//! Payment Policy Enforcement
+//!
+//! This module holds user-defined rules and checks that gate outgoing
+//! payments before any executor is called.
+//!
+//! # Trust Policy
+//!
+//! [`TrustPolicy`] lets users mark payee public keys as trusted or blocked,
+//! each with an optional reason. Evaluating a payee yields a
+//! [`TrustDecision`]:
+//!
+//! - **Allow**: the payee is trusted (or unknown payees need no confirmation)
+//! - **RequireConfirmation**: the payee is unknown and the user must confirm
+//! - **Block**: the payee is blocked; payments must not proceed
+//!
+//! # Example
+//!
+//! ```ignore
+//! use paykit_lib::policy::{TrustDecision, TrustPolicy};
+//!
+//! let mut policy = TrustPolicy::new();
+//! policy.block("8pinxxgqs41n4aididenw5apqp1urfmzdztr8jt4abrkdn435ewo", Some("scam report"));
+//!
+//! match policy.evaluate(&payee) {
+//!     TrustDecision::Allow => pay().await?,
+//!     TrustDecision::RequireConfirmation => {
+//!         if ask_user("First payment to this key, continue?") {
+//!             pay().await?;
+//!         }
+//!     }
+//!     TrustDecision::Block { reason } => println!("Blocked: {:?}", reason),
+//! }
+//! ```
+//!
+//! Payments above [`TrustPolicy::set_require_verified_above`] additionally
+//! require the payee to be a contact verified out of band (see
+//! [`contact_verification`](crate::contact_verification)):
+//!
+//! ```ignore
+//! policy.set_require_verified_above(Some(100_000));
+//! policy.check_verified_payment(&payee_key, amount_sats, contact.verified_at.is_some())?;
+//! ```
+//!
+//! # Velocity Checks
+//!
+//! [`VelocityGuard`] tracks the outgoing payment rate and holds payments that
+//! look anomalous (a burst of small payments, a sudden large payment to a new
+//! peer, too much sent within a short window) until the user confirms them.
+//!
+//! ```ignore
+//! use paykit_lib::policy::{VelocityGuard, VelocityVerdict};
+//!
+//! let guard = VelocityGuard::default();
+//! guard.on_event(Arc::new(|event| notify_user(event)));
+//!
+//! match guard.check(&payee, amount_sats, now) {
+//!     VelocityVerdict::Allow => {
+//!         pay().await?;
+//!         guard.record(&payee, amount_sats, now);
+//!     }
+//!     VelocityVerdict::Hold(held) => {
+//!         // Later, after the user confirms: guard.approve(&held.hold_id, now)
+//!     }
+//! }
+//! ```
+//!
+//! # Compliance Screening
+//!
+//! Businesses can plug in a [`ComplianceScreener`] that checks payer and
+//! payee before a payment is executed. A [`ScreeningResult`] of `Block`
+//! refuses the payment, `Review` lets it proceed flagged, and every result is
+//! recorded in the receipt metadata under [`COMPLIANCE_METADATA_KEY`].
+//!
+//! ```ignore
+//! use paykit_lib::policy::{ComplianceScreener, ScreeningRequest, ScreeningResult};
+//!
+//! struct SanctionsList(HashSet<String>);
+//!
+//! #[async_trait::async_trait]
+//! impl ComplianceScreener for SanctionsList {
+//!     async fn screen(&self, request: &ScreeningRequest) -> Result<ScreeningResult> {
+//!         if self.0.contains(&request.payee) {
+//!             return Ok(ScreeningResult::Block { reason: "sanctions list".into() });
+//!         }
+//!         Ok(ScreeningResult::Pass)
+//!     }
+//! }
+//! ```
+//!
+//! # Privacy Scoring
+//!
+//! [`PrivacyAnalyzer`] scores a [`PlannedPayment`] from 0 to 100 before it is
+//! executed and lists the [`PrivacyLeak`]s found: paying a reused or public
+//! on-chain address, change outputs that stand out (from the wallet's
+//! [`SpendHints`](crate::methods::SpendHints)), and memos sent in cleartext.
+//!
+//! ```ignore
+//! use paykit_lib::policy::{EndpointSource, PlannedPayment, PrivacyAnalyzer};
+//!
+//! let payment = PlannedPayment::new(MethodId::onchain(), EndpointSource::Public)
+//!     .with_amount_sats(100_000)
+//!     .with_prior_payments(receipts_to(&address))
+//!     .with_spend_hints(wallet.spend_hints(&address, 100_000).await?);
+//!
+//! let report = PrivacyAnalyzer::default().analyze(&payment);
+//! for leak in &report.leaks {
+//!     println!("{:?}: {}", leak.severity(), leak.describe());
+//! }
+//! ```
+//!
+//! # Fee Guardrails
+//!
+//! A [`FeeGuard`] caches fee estimates for a short time per method,
+//! confirmation target and amount, and holds the user's [`FeeGuardrails`].
+//! Plugins built with it refuse to pay above the limits with
+//! [`PaykitError::FeeSpike`](crate::PaykitError::FeeSpike); once the user
+//! confirms, the payment is retried with [`ALLOW_FEE_SPIKE_METADATA_KEY`] set.
+//!
+//! ```ignore
+//! use paykit_lib::policy::{FeeGuard, FeeGuardrails};
+//!
+//! let guard = Arc::new(FeeGuard::new(
+//!     FeeGuardrails::default()
+//!         .with_max_onchain_fee_rate(50.0)
+//!         .with_max_lightning_fee_percent(1.0),
+//! ));
+//! let plugin = OnchainPlugin::with_executor(executor).with_fee_guard(guard);
+//!
+//! match plugin.execute_payment(&endpoint, &amount, &metadata).await {
+//!     Err(PaykitError::FeeSpike { fee, limit, .. }) if ask_user(&fee, &limit) => {
+//!         metadata["allow_fee_spike"] = true.into();
+//!         plugin.execute_payment(&endpoint, &amount, &metadata).await?
+//!     }
+//!     result => result?,
+//! }
+//! ```
+
+mod compliance;
+mod fees;
+mod privacy;
+mod trust;
+mod velocity;
+
+pub use compliance::{
+    ComplianceScreener, ScreeningDirection, ScreeningRequest, ScreeningResult,
+    SharedComplianceScreener, COMPLIANCE_METADATA_KEY,
+};
+pub(crate) use fees::fee_spike_allowed;
+pub use fees::{FeeGuard, FeeGuardrails, ALLOW_FEE_SPIKE_METADATA_KEY};
+pub use privacy::{
+    EndpointSource, PlannedPayment, PrivacyAnalyzer, PrivacyLeak, PrivacyReport, PrivacySeverity,
+};
+pub(crate) use trust::normalize_key;
+pub use trust::{SharedTrustPolicy, TrustDecision, TrustEntry, TrustLevel, TrustPolicy};
+pub use velocity::{
+    Anomaly, HeldPayment, VelocityCallback, VelocityConfig, VelocityEvent, VelocityGuard,
+    VelocityVerdict,
+};
@@ -0,0 +1,291 @@
+//! Privacy scoring for planned payments.
+
+use crate::methods::SpendHints;
+use crate::MethodId;
+use serde::{Deserialize, Serialize};
+
+/// Where the payee endpoint of a payment came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndpointSource {
+    /// Published in the payee's public directory.
+    Public,
+    /// Shared privately with the payer.
+    Private,
+}
+
+/// A payment about to be made, as far as privacy is concerned.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PlannedPayment {
+    /// Payment method.
+    pub method: MethodId,
+    /// Where the endpoint came from.
+    pub source: EndpointSource,
+    /// Amount in satoshis, if known.
+    pub amount_sats: Option<u64>,
+    /// Memo sent with the payment, if any.
+    pub memo: Option<String>,
+    /// Whether the memo only travels over an encrypted channel.
+    pub memo_encrypted: bool,
+    /// Earlier payments made to the same endpoint.
+    pub prior_payments_to_endpoint: u32,
+    /// How the wallet would build the transaction (on-chain only).
+    pub spend: Option<SpendHints>,
+}
+
+impl PlannedPayment {
+    /// Describe a payment over `method` to an endpoint from `source`.
+    pub fn new(method: MethodId, source: EndpointSource) -> Self {
+        Self {
+            method,
+            source,
+            amount_sats: None,
+            memo: None,
+            memo_encrypted: false,
+            prior_payments_to_endpoint: 0,
+            spend: None,
+        }
+    }
+
+    /// Set the amount.
+    pub fn with_amount_sats(mut self, amount_sats: u64) -> Self {
+        self.amount_sats = Some(amount_sats);
+        self
+    }
+
+    /// Set the memo and whether it is only sent encrypted.
+    pub fn with_memo(mut self, memo: impl Into<String>, encrypted: bool) -> Self {
+        self.memo = Some(memo.into());
+        self.memo_encrypted = encrypted;
+        self
+    }
+
+    /// Set how many times the endpoint was paid before.
+    pub fn with_prior_payments(mut self, count: u32) -> Self {
+        self.prior_payments_to_endpoint = count;
+        self
+    }
+
+    /// Attach the wallet's description of the transaction it would build.
+    pub fn with_spend_hints(mut self, hints: SpendHints) -> Self {
+        self.spend = Some(hints);
+        self
+    }
+
+    fn is_onchain(&self) -> bool {
+        self.method.as_str() == MethodId::ONCHAIN
+    }
+}
+
+/// How much a leak matters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrivacySeverity {
+    /// Minor; worth knowing.
+    Low,
+    /// Links the payment to other activity under some assumptions.
+    Medium,
+    /// Publicly links the payment to the payee or to other payments.
+    High,
+}
+
+/// Something about a payment that reveals more than it needs to.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PrivacyLeak {
+    /// The on-chain address was paid before.
+    AddressReuse { prior_payments: u32 },
+    /// The endpoint is published in the payee's public directory.
+    PublicEndpoint,
+    /// The change output can be told apart from the payment output.
+    IdentifiableChange,
+    /// A round amount marks which output is the payment.
+    RoundAmount { amount_sats: u64 },
+    /// Inputs from several addresses are spent together, linking them.
+    InputMerge { addresses: u32 },
+    /// The memo is readable by parties other than the payee.
+    CleartextMemo,
+}
+
+impl PrivacyLeak {
+    /// How much the leak matters.
+    pub fn severity(&self) -> PrivacySeverity {
+        match self {
+            PrivacyLeak::AddressReuse { .. } => PrivacySeverity::High,
+            PrivacyLeak::PublicEndpoint => PrivacySeverity::Medium,
+            PrivacyLeak::IdentifiableChange => PrivacySeverity::Medium,
+            PrivacyLeak::RoundAmount { .. } => PrivacySeverity::Low,
+            PrivacyLeak::InputMerge { .. } => PrivacySeverity::Medium,
+            PrivacyLeak::CleartextMemo => PrivacySeverity::Medium,
+        }
+    }
+
+    /// Points taken off the score.
+    pub fn penalty(&self) -> u8 {
+        match self {
+            PrivacyLeak::AddressReuse { .. } => 35,
+            PrivacyLeak::PublicEndpoint => 20,
+            PrivacyLeak::IdentifiableChange => 15,
+            PrivacyLeak::RoundAmount { .. } => 5,
+            PrivacyLeak::InputMerge { .. } => 15,
+            PrivacyLeak::CleartextMemo => 10,
+        }
+    }
+
+    /// Human-readable description.
+    pub fn describe(&self) -> String {
+        match self {
+            PrivacyLeak::AddressReuse { prior_payments } => format!(
+                "address already paid {} time(s); chain observers can link these payments",
+                prior_payments
+            ),
+            PrivacyLeak::PublicEndpoint => {
+                "endpoint is public; anyone can link this payment to the payee".to_string()
+            }
+            PrivacyLeak::IdentifiableChange => {
+                "change uses a different address type than the payment, revealing which output is change"
+                    .to_string()
+            }
+            PrivacyLeak::RoundAmount { amount_sats } => format!(
+                "round amount ({} sats) reveals which output is the payment",
+                amount_sats
+            ),
+            PrivacyLeak::InputMerge { addresses } => format!(
+                "spends coins from {} addresses together, linking them to one wallet",
+                addresses
+            ),
+            PrivacyLeak::CleartextMemo => {
+                "memo is sent in cleartext and readable outside the payment channel".to_string()
+            }
+        }
+    }
+}
+
+/// Result of analyzing a planned payment.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrivacyReport {
+    /// 100 for no known leaks, lower for more and worse leaks.
+    pub score: u8,
+    /// Leaks found, worst first.
+    pub leaks: Vec<PrivacyLeak>,
+}
+
+impl PrivacyReport {
+    /// Worst severity found, if any.
+    pub fn worst(&self) -> Option<PrivacySeverity> {
+        self.leaks.iter().map(PrivacyLeak::severity).max()
+    }
+
+    /// Check whether any leak should be confirmed by the user before paying.
+    pub fn has_warnings(&self) -> bool {
+        self.worst() >= Some(PrivacySeverity::Medium)
+    }
+}
+
+/// Scores planned payments for privacy leaks.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PrivacyAnalyzer {
+    /// On-chain amounts that are multiples of this are "round".
+    pub round_amount_sats: u64,
+}
+
+impl Default for PrivacyAnalyzer {
+    fn default() -> Self {
+        Self {
+            round_amount_sats: 10_000,
+        }
+    }
+}
+
+impl PrivacyAnalyzer {
+    /// Analyze a payment before it is executed.
+    pub fn analyze(&self, payment: &PlannedPayment) -> PrivacyReport {
+        let mut leaks = Vec::new();
+
+        if payment.is_onchain() && payment.prior_payments_to_endpoint > 0 {
+            leaks.push(PrivacyLeak::AddressReuse {
+                prior_payments: payment.prior_payments_to_endpoint,
+            });
+        }
+        if payment.source == EndpointSource::Public {
+            leaks.push(PrivacyLeak::PublicEndpoint);
+        }
+
+        if let (true, Some(spend)) = (payment.is_onchain(), &payment.spend) {
+            if spend.change_sats.is_some() {
+                if !spend.change_type_matches {
+                    leaks.push(PrivacyLeak::IdentifiableChange);
+                }
+                match payment.amount_sats {
+                    Some(amount_sats)
+                        if self.round_amount_sats > 0
+                            && amount_sats % self.round_amount_sats == 0 =>
+                    {
+                        leaks.push(PrivacyLeak::RoundAmount { amount_sats });
+                    }
+                    _ => {}
+                }
+            }
+            if spend.input_addresses > 1 {
+                leaks.push(PrivacyLeak::InputMerge {
+                    addresses: spend.input_addresses,
+                });
+            }
+        }
+
+        let has_memo = payment
+            .memo
+            .as_deref()
+            .is_some_and(|m| !m.trim().is_empty());
+        if has_memo && !payment.memo_encrypted {
+            leaks.push(PrivacyLeak::CleartextMemo);
+        }
+
+        leaks.sort_by_key(|leak| std::cmp::Reverse(leak.severity()));
+        let penalty: u32 = leaks.iter().map(|leak| u32::from(leak.penalty())).sum();
+        PrivacyReport {
+            score: 100u32.saturating_sub(penalty) as u8,
+            leaks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_private_lightning_payment_is_clean() {
+        let payment = PlannedPayment::new(MethodId::lightning(), EndpointSource::Private)
+            .with_amount_sats(50_000)
+            .with_memo("lunch", true)
+            .with_prior_payments(3);
+        let report = PrivacyAnalyzer::default().analyze(&payment);
+        assert_eq!(report.score, 100);
+        assert!(report.leaks.is_empty());
+        assert!(!report.has_warnings());
+    }
+
+    #[test]
+    fn test_reused_public_address_with_change_leaks() {
+        let payment = PlannedPayment::new(MethodId::onchain(), EndpointSource::Public)
+            .with_amount_sats(100_000)
+            .with_memo("invoice 42", false)
+            .with_prior_payments(2)
+            .with_spend_hints(SpendHints {
+                input_count: 2,
+                input_addresses: 2,
+                change_sats: Some(12_345),
+                change_type_matches: false,
+            });
+        let report = PrivacyAnalyzer::default().analyze(&payment);
+
+        assert_eq!(
+            report.leaks[0],
+            PrivacyLeak::AddressReuse { prior_payments: 2 }
+        );
+        assert_eq!(report.leaks.len(), 6);
+        assert_eq!(report.worst(), Some(PrivacySeverity::High));
+        assert_eq!(report.score, 0);
+    }
+}
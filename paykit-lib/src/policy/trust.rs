@@ -0,0 +1,374 @@
+//! Payee allowlist/blocklist.
+
+use crate::{PaykitError, PublicKey, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// A trust policy shared between the components that enforce it.
+pub type SharedTrustPolicy = Arc<RwLock<TrustPolicy>>;
+
+/// How a public key is treated as a payee.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrustLevel {
+    /// Payments proceed without extra confirmation.
+    Trusted,
+    /// Outgoing payments are refused.
+    Blocked,
+}
+
+impl TrustLevel {
+    /// Get the level as a string.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrustLevel::Trusted => "trusted",
+            TrustLevel::Blocked => "blocked",
+        }
+    }
+}
+
+impl std::str::FromStr for TrustLevel {
+    type Err = PaykitError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "trusted" => Ok(TrustLevel::Trusted),
+            "blocked" => Ok(TrustLevel::Blocked),
+            other => Err(PaykitError::ValidationFailed(format!(
+                "Unknown trust level '{}'. Expected trusted or blocked",
+                other
+            ))),
+        }
+    }
+}
+
+/// A user-defined trust entry for one public key.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrustEntry {
+    /// Public key (z-base-32, without `pubky://` prefix).
+    pub public_key: String,
+    /// Trust level.
+    pub level: TrustLevel,
+    /// Why the key was trusted or blocked.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Unix timestamp of the last change.
+    pub updated_at: i64,
+}
+
+/// Outcome of evaluating a payee against the policy.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TrustDecision {
+    /// Payment may proceed.
+    Allow,
+    /// Payee is unknown; the user must confirm before paying.
+    RequireConfirmation,
+    /// Payee is blocked.
+    Block {
+        /// Reason recorded when the key was blocked.
+        reason: Option<String>,
+    },
+}
+
+impl TrustDecision {
+    /// Check whether the payment may proceed without user interaction.
+    pub fn is_allowed(&self) -> bool {
+        matches!(self, TrustDecision::Allow)
+    }
+
+    /// Check whether the payee is blocked.
+    pub fn is_blocked(&self) -> bool {
+        matches!(self, TrustDecision::Block { .. })
+    }
+
+    /// Check whether the user must confirm the payment.
+    pub fn requires_confirmation(&self) -> bool {
+        matches!(self, TrustDecision::RequireConfirmation)
+    }
+}
+
+fn default_confirm_unknown() -> bool {
+    true
+}
+
+/// Allowlist/blocklist of payee public keys.
+///
+/// Keys are stored in z-base-32 form; a leading `pubky://` or `pubky` prefix
+/// is stripped when keys are added or looked up.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TrustPolicy {
+    entries: HashMap<String, TrustEntry>,
+    /// Require confirmation before paying keys with no entry.
+    #[serde(default = "default_confirm_unknown")]
+    confirm_unknown: bool,
+    /// Payments above this many satoshis require a verified contact.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    require_verified_above: Option<u64>,
+}
+
+impl Default for TrustPolicy {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            confirm_unknown: true,
+            require_verified_above: None,
+        }
+    }
+}
+
+impl TrustPolicy {
+    /// Create an empty policy that requires confirmation for unknown keys.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wrap the policy for sharing between enforcement points.
+    pub fn shared(self) -> SharedTrustPolicy {
+        Arc::new(RwLock::new(self))
+    }
+
+    /// Whether unknown keys require confirmation.
+    pub fn confirm_unknown(&self) -> bool {
+        self.confirm_unknown
+    }
+
+    /// Set whether unknown keys require confirmation.
+    pub fn set_confirm_unknown(&mut self, required: bool) {
+        self.confirm_unknown = required;
+    }
+
+    /// Amount above which payees must be verified contacts, if any.
+    pub fn require_verified_above(&self) -> Option<u64> {
+        self.require_verified_above
+    }
+
+    /// Require verified contacts for payments above `amount_sats`, or lift
+    /// the requirement with `None`.
+    ///
+    /// See [`contact_verification`](crate::contact_verification).
+    pub fn set_require_verified_above(&mut self, amount_sats: Option<u64>) {
+        self.require_verified_above = amount_sats;
+    }
+
+    /// Whether a payment of `amount_sats` requires a verified contact.
+    pub fn requires_verification(&self, amount_sats: u64) -> bool {
+        self.require_verified_above
+            .is_some_and(|threshold| amount_sats > threshold)
+    }
+
+    /// Mark a key as trusted.
+    pub fn trust(&mut self, public_key: &str, reason: Option<&str>) -> &TrustEntry {
+        self.set(public_key, TrustLevel::Trusted, reason)
+    }
+
+    /// Mark a key as blocked.
+    pub fn block(&mut self, public_key: &str, reason: Option<&str>) -> &TrustEntry {
+        self.set(public_key, TrustLevel::Blocked, reason)
+    }
+
+    /// Set the trust level for a key, replacing any existing entry.
+    pub fn set(
+        &mut self,
+        public_key: &str,
+        level: TrustLevel,
+        reason: Option<&str>,
+    ) -> &TrustEntry {
+        let key = normalize_key(public_key);
+        let entry = TrustEntry {
+            public_key: key.clone(),
+            level,
+            reason: reason.map(String::from),
+            updated_at: current_timestamp(),
+        };
+        self.entries.insert(key.clone(), entry);
+        &self.entries[&key]
+    }
+
+    /// Remove the entry for a key, returning it if present.
+    pub fn remove(&mut self, public_key: &str) -> Option<TrustEntry> {
+        self.entries.remove(&normalize_key(public_key))
+    }
+
+    /// Get the entry for a key.
+    pub fn entry(&self, public_key: &str) -> Option<&TrustEntry> {
+        self.entries.get(&normalize_key(public_key))
+    }
+
+    /// List all entries, sorted by key.
+    pub fn entries(&self) -> Vec<&TrustEntry> {
+        let mut entries: Vec<_> = self.entries.values().collect();
+        entries.sort_by(|a, b| a.public_key.cmp(&b.public_key));
+        entries
+    }
+
+    /// Evaluate a payee given as a string.
+    pub fn evaluate_key(&self, public_key: &str) -> TrustDecision {
+        match self.entry(public_key) {
+            Some(entry) if entry.level == TrustLevel::Blocked => TrustDecision::Block {
+                reason: entry.reason.clone(),
+            },
+            Some(_) => TrustDecision::Allow,
+            None if self.confirm_unknown => TrustDecision::RequireConfirmation,
+            None => TrustDecision::Allow,
+        }
+    }
+
+    /// Evaluate a payee.
+    pub fn evaluate(&self, payee: &PublicKey) -> TrustDecision {
+        self.evaluate_key(&peer_to_string(payee))
+    }
+
+    /// Enforce the policy for an outgoing payment.
+    ///
+    /// Blocked payees are always refused. Unknown payees are refused unless
+    /// `confirmed` is set, meaning the user already approved this payment.
+    pub fn check_payment(&self, payee: &PublicKey, confirmed: bool) -> Result<()> {
+        self.check_payment_key(&peer_to_string(payee), confirmed)
+    }
+
+    /// Enforce the policy for an outgoing payment to a payee given as a string.
+    pub fn check_payment_key(&self, public_key: &str, confirmed: bool) -> Result<()> {
+        match self.evaluate_key(public_key) {
+            TrustDecision::Allow => Ok(()),
+            TrustDecision::RequireConfirmation if confirmed => Ok(()),
+            TrustDecision::RequireConfirmation => Err(PaykitError::ValidationFailed(format!(
+                "Payee {} is not trusted; confirmation required",
+                normalize_key(public_key)
+            ))),
+            TrustDecision::Block { reason } => Err(PaykitError::ValidationFailed(match reason {
+                Some(reason) => {
+                    format!("Payee {} is blocked: {}", normalize_key(public_key), reason)
+                }
+                None => format!("Payee {} is blocked", normalize_key(public_key)),
+            })),
+        }
+    }
+
+    /// Enforce the verified-contact requirement for a payment.
+    ///
+    /// `verified` is whether the app has verified the payee as a contact.
+    pub fn check_verified_payment(
+        &self,
+        public_key: &str,
+        amount_sats: u64,
+        verified: bool,
+    ) -> Result<()> {
+        if verified || !self.requires_verification(amount_sats) {
+            return Ok(());
+        }
+        Err(PaykitError::ValidationFailed(format!(
+            "Payee {} is not a verified contact; payments above {} sats require verification",
+            normalize_key(public_key),
+            self.require_verified_above.unwrap_or_default()
+        )))
+    }
+}
+
+pub(crate) fn normalize_key(public_key: &str) -> String {
+    let key = public_key.trim().trim_end_matches('/');
+    let key = key.strip_prefix("pubky://").unwrap_or(key);
+    // Bare `pubky<z32>` form; only strip when a full 52-char key remains
+    match key.strip_prefix("pubky") {
+        Some(rest) if rest.len() == 52 => rest.to_string(),
+        _ => key.to_string(),
+    }
+}
+
+#[cfg(feature = "pubky")]
+fn peer_to_string(peer: &PublicKey) -> String {
+    peer.to_string()
+}
+
+#[cfg(not(feature = "pubky"))]
+fn peer_to_string(peer: &PublicKey) -> String {
+    peer.0.clone()
+}
+
+fn current_timestamp() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "8pinxxgqs41n4aididenw5apqp1urfmzdztr8jt4abrkdn435ewo";
+
+    #[test]
+    fn test_trust_policy_decisions() {
+        let mut policy = TrustPolicy::new();
+        assert_eq!(policy.evaluate_key(KEY), TrustDecision::RequireConfirmation);
+
+        policy.trust(KEY, Some("friend"));
+        assert_eq!(policy.evaluate_key(KEY), TrustDecision::Allow);
+
+        policy.block(KEY, Some("scam"));
+        assert_eq!(
+            policy.evaluate_key(KEY),
+            TrustDecision::Block {
+                reason: Some("scam".to_string())
+            }
+        );
+
+        assert!(policy.remove(KEY).is_some());
+        policy.set_confirm_unknown(false);
+        assert_eq!(policy.evaluate_key(KEY), TrustDecision::Allow);
+    }
+
+    #[test]
+    fn test_trust_policy_check_payment() {
+        let mut policy = TrustPolicy::new();
+        assert!(policy.check_payment_key(KEY, false).is_err());
+        assert!(policy.check_payment_key(KEY, true).is_ok());
+
+        policy.block(KEY, None);
+        let err = policy.check_payment_key(KEY, true).unwrap_err();
+        assert!(err.to_string().contains("blocked"));
+    }
+
+    #[test]
+    fn test_trust_policy_requires_verified_contacts() {
+        let mut policy = TrustPolicy::new();
+        assert!(policy.check_verified_payment(KEY, 1_000_000, false).is_ok());
+
+        policy.set_require_verified_above(Some(50_000));
+        assert!(policy.check_verified_payment(KEY, 50_000, false).is_ok());
+        assert!(policy.check_verified_payment(KEY, 50_001, true).is_ok());
+        let err = policy
+            .check_verified_payment(KEY, 50_001, false)
+            .unwrap_err();
+        assert!(err.to_string().contains("verified"));
+
+        let json = serde_json::to_string(&policy).unwrap();
+        let restored: TrustPolicy = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.require_verified_above(), Some(50_000));
+    }
+
+    #[test]
+    fn test_trust_policy_normalizes_keys() {
+        let mut policy = TrustPolicy::new();
+        policy.block(&format!("pubky://{}/", KEY), None);
+
+        assert!(policy.evaluate_key(KEY).is_blocked());
+        assert!(policy.evaluate_key(&format!("pubky{}", KEY)).is_blocked());
+        assert_eq!(policy.entries()[0].public_key, KEY);
+    }
+
+    #[test]
+    fn test_trust_policy_serialization() {
+        let mut policy = TrustPolicy::new();
+        policy.trust(KEY, Some("friend"));
+
+        let json = serde_json::to_string(&policy).unwrap();
+        let restored: TrustPolicy = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, policy);
+
+        let legacy: TrustPolicy = serde_json::from_str(r#"{"entries":{}}"#).unwrap();
+        assert!(legacy.confirm_unknown());
+    }
+}
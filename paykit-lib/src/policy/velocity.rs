@@ -0,0 +1,532 @@
+//! Velocity checks and anomaly detection for outgoing payments.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Callback type for velocity events.
+pub type VelocityCallback = Arc<dyn Fn(&VelocityEvent) + Send + Sync>;
+
+/// Thresholds for flagging unusual outgoing payment patterns.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VelocityConfig {
+    /// Sliding window for rate checks, in seconds.
+    pub window_secs: i64,
+    /// Maximum payments within the window.
+    pub max_payments_per_window: u32,
+    /// Payments at or below this amount count as "small".
+    pub small_payment_sats: u64,
+    /// Maximum small payments within the window.
+    pub max_small_payments_per_window: u32,
+    /// Maximum total sent within the window, if limited.
+    pub max_amount_per_window_sats: Option<u64>,
+    /// A first payment to a new peer is a spike when it exceeds the average
+    /// payment by this factor.
+    pub new_peer_spike_multiplier: f64,
+    /// First payments to new peers below this amount are never spikes.
+    pub new_peer_spike_min_sats: u64,
+    /// How long payment history is kept for peer and average tracking, in seconds.
+    pub history_secs: i64,
+    /// How long an approved hold stays valid, in seconds.
+    pub approval_ttl_secs: i64,
+}
+
+impl Default for VelocityConfig {
+    fn default() -> Self {
+        Self {
+            window_secs: 60,
+            max_payments_per_window: 10,
+            small_payment_sats: 1_000,
+            max_small_payments_per_window: 5,
+            max_amount_per_window_sats: None,
+            new_peer_spike_multiplier: 5.0,
+            new_peer_spike_min_sats: 50_000,
+            history_secs: 30 * 24 * 60 * 60,
+            approval_ttl_secs: 10 * 60,
+        }
+    }
+}
+
+/// An unusual pattern detected for a payment.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Anomaly {
+    /// Too many payments within the window.
+    RateExceeded { count: u32, window_secs: i64 },
+    /// Too many small payments within the window.
+    SmallPaymentBurst { count: u32, window_secs: i64 },
+    /// Total sent within the window exceeds the limit.
+    WindowAmountExceeded { total_sats: u64, limit_sats: u64 },
+    /// Large first payment to a peer never paid before.
+    NewPeerSpike { amount_sats: u64, typical_sats: u64 },
+}
+
+impl Anomaly {
+    /// Human-readable description.
+    pub fn describe(&self) -> String {
+        match self {
+            Anomaly::RateExceeded { count, window_secs } => {
+                format!("{} payments within {}s", count, window_secs)
+            }
+            Anomaly::SmallPaymentBurst { count, window_secs } => {
+                format!("{} small payments within {}s", count, window_secs)
+            }
+            Anomaly::WindowAmountExceeded {
+                total_sats,
+                limit_sats,
+            } => format!(
+                "{} sats sent recently exceeds the {} sat limit",
+                total_sats, limit_sats
+            ),
+            Anomaly::NewPeerSpike {
+                amount_sats,
+                typical_sats,
+            } => format!(
+                "{} sats to a new peer (typical payment {} sats)",
+                amount_sats, typical_sats
+            ),
+        }
+    }
+}
+
+/// A payment held until the user confirms it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeldPayment {
+    /// Identifier used to approve or reject the hold.
+    pub hold_id: String,
+    /// Counterparty (public key or endpoint).
+    pub peer: String,
+    /// Amount in satoshis.
+    pub amount_sats: u64,
+    /// Why the payment was held.
+    pub anomalies: Vec<Anomaly>,
+    /// Unix timestamp when the hold was created.
+    pub created_at: i64,
+}
+
+/// Result of checking a payment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VelocityVerdict {
+    /// The payment may proceed.
+    Allow,
+    /// The payment is held for user confirmation.
+    Hold(HeldPayment),
+}
+
+impl VelocityVerdict {
+    /// Check whether the payment may proceed.
+    pub fn is_allowed(&self) -> bool {
+        matches!(self, VelocityVerdict::Allow)
+    }
+}
+
+/// Events emitted by the [`VelocityGuard`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VelocityEvent {
+    /// A payment was held for confirmation.
+    Held(HeldPayment),
+    /// The user approved a held payment; retrying it will be allowed.
+    Approved(HeldPayment),
+    /// The user rejected a held payment.
+    Rejected(HeldPayment),
+}
+
+#[derive(Clone, Debug)]
+struct PaymentRecord {
+    peer: String,
+    amount_sats: u64,
+    at: i64,
+}
+
+#[derive(Default)]
+struct VelocityState {
+    history: VecDeque<PaymentRecord>,
+    held: HashMap<String, HeldPayment>,
+    approved: Vec<(HeldPayment, i64)>,
+    next_hold: u64,
+}
+
+/// Tracks outgoing payment rate and holds anomalous payments for confirmation.
+///
+/// Call [`check`](Self::check) before executing a payment and
+/// [`record`](Self::record) after it succeeds. Held payments are surfaced
+/// through [`on_event`](Self::on_event) callbacks; once the user approves a
+/// hold, checking the same peer and amount again is allowed until the payment
+/// is recorded or the approval expires.
+pub struct VelocityGuard {
+    config: RwLock<VelocityConfig>,
+    state: Mutex<VelocityState>,
+    callbacks: RwLock<Vec<VelocityCallback>>,
+}
+
+impl Default for VelocityGuard {
+    fn default() -> Self {
+        Self::new(VelocityConfig::default())
+    }
+}
+
+impl VelocityGuard {
+    /// Create a guard with the given thresholds.
+    pub fn new(config: VelocityConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            state: Mutex::new(VelocityState::default()),
+            callbacks: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Get the current thresholds.
+    pub fn config(&self) -> VelocityConfig {
+        self.config
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Replace the thresholds.
+    pub fn set_config(&self, config: VelocityConfig) {
+        *self.config.write().unwrap_or_else(|e| e.into_inner()) = config;
+    }
+
+    /// Register a callback for velocity events.
+    ///
+    /// If the lock is poisoned, the callback is silently ignored.
+    pub fn on_event(&self, callback: VelocityCallback) {
+        if let Ok(mut callbacks) = self.callbacks.write() {
+            callbacks.push(callback);
+        }
+    }
+
+    /// Remove every registered callback.
+    pub fn clear_callbacks(&self) {
+        self.callbacks
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    /// Detect anomalies for a prospective payment without creating a hold.
+    ///
+    /// Returns no anomalies if a matching hold was approved.
+    pub fn assess(&self, peer: &str, amount_sats: u64, now: i64) -> Vec<Anomaly> {
+        let config = self.config();
+        let mut state = self.lock_state();
+        prune(&mut state, &config, now);
+
+        if find_approval(&state, peer, amount_sats).is_some() {
+            return Vec::new();
+        }
+        detect(&state, &config, peer, amount_sats, now)
+    }
+
+    /// Check a payment before executing it.
+    ///
+    /// Anomalous payments are held and a [`VelocityEvent::Held`] is emitted.
+    /// Checking the same peer and amount while a hold is pending returns the
+    /// existing hold without emitting another event.
+    pub fn check(&self, peer: &str, amount_sats: u64, now: i64) -> VelocityVerdict {
+        let config = self.config();
+        let held = {
+            let mut state = self.lock_state();
+            prune(&mut state, &config, now);
+
+            if find_approval(&state, peer, amount_sats).is_some() {
+                return VelocityVerdict::Allow;
+            }
+
+            let anomalies = detect(&state, &config, peer, amount_sats, now);
+            if anomalies.is_empty() {
+                return VelocityVerdict::Allow;
+            }
+
+            if let Some(existing) = state
+                .held
+                .values()
+                .find(|h| h.peer == peer && h.amount_sats == amount_sats)
+            {
+                return VelocityVerdict::Hold(existing.clone());
+            }
+
+            state.next_hold += 1;
+            let held = HeldPayment {
+                hold_id: format!("hold_{}_{}", now, state.next_hold),
+                peer: peer.to_string(),
+                amount_sats,
+                anomalies,
+                created_at: now,
+            };
+            state.held.insert(held.hold_id.clone(), held.clone());
+            held
+        };
+
+        self.emit(&VelocityEvent::Held(held.clone()));
+        VelocityVerdict::Hold(held)
+    }
+
+    /// Record a completed outgoing payment.
+    ///
+    /// Consumes a matching approval, if any.
+    pub fn record(&self, peer: &str, amount_sats: u64, now: i64) {
+        let config = self.config();
+        let mut state = self.lock_state();
+        prune(&mut state, &config, now);
+
+        if let Some(index) = find_approval(&state, peer, amount_sats) {
+            state.approved.remove(index);
+        }
+        state.history.push_back(PaymentRecord {
+            peer: peer.to_string(),
+            amount_sats,
+            at: now,
+        });
+    }
+
+    /// Approve a held payment so it is allowed when retried.
+    pub fn approve(&self, hold_id: &str, now: i64) -> Option<HeldPayment> {
+        let held = {
+            let mut state = self.lock_state();
+            let held = state.held.remove(hold_id)?;
+            state.approved.push((held.clone(), now));
+            held
+        };
+        self.emit(&VelocityEvent::Approved(held.clone()));
+        Some(held)
+    }
+
+    /// Reject a held payment.
+    pub fn reject(&self, hold_id: &str) -> Option<HeldPayment> {
+        let held = self.lock_state().held.remove(hold_id)?;
+        self.emit(&VelocityEvent::Rejected(held.clone()));
+        Some(held)
+    }
+
+    /// List payments awaiting confirmation, oldest first.
+    pub fn held_payments(&self) -> Vec<HeldPayment> {
+        let mut held: Vec<_> = self.lock_state().held.values().cloned().collect();
+        held.sort_by(|a, b| {
+            a.created_at
+                .cmp(&b.created_at)
+                .then_with(|| a.hold_id.cmp(&b.hold_id))
+        });
+        held
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, VelocityState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn emit(&self, event: &VelocityEvent) {
+        if let Ok(callbacks) = self.callbacks.read() {
+            for callback in callbacks.iter() {
+                callback(event);
+            }
+        }
+    }
+}
+
+fn prune(state: &mut VelocityState, config: &VelocityConfig, now: i64) {
+    while state
+        .history
+        .front()
+        .is_some_and(|r| now - r.at > config.history_secs)
+    {
+        state.history.pop_front();
+    }
+    state
+        .approved
+        .retain(|(_, approved_at)| now - approved_at <= config.approval_ttl_secs);
+}
+
+fn find_approval(state: &VelocityState, peer: &str, amount_sats: u64) -> Option<usize> {
+    state
+        .approved
+        .iter()
+        .position(|(h, _)| h.peer == peer && h.amount_sats == amount_sats)
+}
+
+fn detect(
+    state: &VelocityState,
+    config: &VelocityConfig,
+    peer: &str,
+    amount_sats: u64,
+    now: i64,
+) -> Vec<Anomaly> {
+    let mut anomalies = Vec::new();
+
+    let recent: Vec<_> = state
+        .history
+        .iter()
+        .filter(|r| now - r.at < config.window_secs)
+        .collect();
+
+    let count = recent.len() as u32 + 1;
+    if count > config.max_payments_per_window {
+        anomalies.push(Anomaly::RateExceeded {
+            count,
+            window_secs: config.window_secs,
+        });
+    }
+
+    if amount_sats <= config.small_payment_sats {
+        let small = recent
+            .iter()
+            .filter(|r| r.amount_sats <= config.small_payment_sats)
+            .count() as u32
+            + 1;
+        if small > config.max_small_payments_per_window {
+            anomalies.push(Anomaly::SmallPaymentBurst {
+                count: small,
+                window_secs: config.window_secs,
+            });
+        }
+    }
+
+    if let Some(limit_sats) = config.max_amount_per_window_sats {
+        let total_sats = recent
+            .iter()
+            .map(|r| r.amount_sats)
+            .fold(amount_sats, u64::saturating_add);
+        if total_sats > limit_sats {
+            anomalies.push(Anomaly::WindowAmountExceeded {
+                total_sats,
+                limit_sats,
+            });
+        }
+    }
+
+    let is_new_peer = !state.history.iter().any(|r| r.peer == peer);
+    if is_new_peer && !state.history.is_empty() && amount_sats >= config.new_peer_spike_min_sats {
+        let total: u128 = state.history.iter().map(|r| r.amount_sats as u128).sum();
+        let typical_sats = (total / state.history.len() as u128) as u64;
+        if amount_sats as f64 > typical_sats as f64 * config.new_peer_spike_multiplier {
+            anomalies.push(Anomaly::NewPeerSpike {
+                amount_sats,
+                typical_sats,
+            });
+        }
+    }
+
+    anomalies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_velocity_allows_normal_payments() {
+        let guard = VelocityGuard::default();
+        for i in 0..5 {
+            assert!(guard.check("alice", 10_000, 1_000 + i * 30).is_allowed());
+            guard.record("alice", 10_000, 1_000 + i * 30);
+        }
+        assert!(guard.held_payments().is_empty());
+    }
+
+    #[test]
+    fn test_velocity_small_payment_burst() {
+        let guard = VelocityGuard::new(VelocityConfig {
+            max_small_payments_per_window: 3,
+            ..Default::default()
+        });
+        for i in 0..3 {
+            guard.record("bob", 100, 1_000 + i);
+        }
+
+        match guard.check("bob", 100, 1_010) {
+            VelocityVerdict::Hold(held) => {
+                assert_eq!(
+                    held.anomalies,
+                    vec![Anomaly::SmallPaymentBurst {
+                        count: 4,
+                        window_secs: 60
+                    }]
+                );
+            }
+            VelocityVerdict::Allow => panic!("expected hold"),
+        }
+
+        // Outside the window the burst is over
+        assert!(guard.check("bob", 100, 1_100).is_allowed());
+    }
+
+    #[test]
+    fn test_velocity_new_peer_spike() {
+        let guard = VelocityGuard::default();
+        for i in 0..4 {
+            guard.record("regular", 20_000, 1_000 + i * 3600);
+        }
+
+        let anomalies = guard.assess("stranger", 500_000, 20_000);
+        assert_eq!(
+            anomalies,
+            vec![Anomaly::NewPeerSpike {
+                amount_sats: 500_000,
+                typical_sats: 20_000
+            }]
+        );
+        assert!(guard.assess("regular", 500_000, 20_000).is_empty());
+        assert!(guard.held_payments().is_empty());
+    }
+
+    #[test]
+    fn test_velocity_hold_approval_flow() {
+        let guard = VelocityGuard::new(VelocityConfig {
+            max_payments_per_window: 1,
+            ..Default::default()
+        });
+        let events = Arc::new(AtomicUsize::new(0));
+        let counter = events.clone();
+        guard.on_event(Arc::new(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }));
+
+        guard.record("carol", 5_000, 1_000);
+        let held = match guard.check("carol", 5_000, 1_001) {
+            VelocityVerdict::Hold(held) => held,
+            VelocityVerdict::Allow => panic!("expected hold"),
+        };
+
+        // Re-checking returns the same hold without a new event
+        assert_eq!(
+            guard.check("carol", 5_000, 1_002),
+            VelocityVerdict::Hold(held.clone())
+        );
+        assert_eq!(events.load(Ordering::SeqCst), 1);
+
+        guard.approve(&held.hold_id, 1_003).unwrap();
+        assert_eq!(events.load(Ordering::SeqCst), 2);
+        assert!(guard.held_payments().is_empty());
+        assert!(guard.check("carol", 5_000, 1_004).is_allowed());
+
+        // Recording the payment consumes the approval
+        guard.record("carol", 5_000, 1_005);
+        assert!(!guard.check("carol", 5_000, 1_006).is_allowed());
+    }
+
+    #[test]
+    fn test_velocity_reject_and_window_limit() {
+        let guard = VelocityGuard::new(VelocityConfig {
+            max_amount_per_window_sats: Some(100_000),
+            ..Default::default()
+        });
+        guard.record("dave", 80_000, 1_000);
+
+        let held = match guard.check("dave", 30_000, 1_010) {
+            VelocityVerdict::Hold(held) => held,
+            VelocityVerdict::Allow => panic!("expected hold"),
+        };
+        assert!(matches!(
+            held.anomalies[0],
+            Anomaly::WindowAmountExceeded {
+                total_sats: 110_000,
+                limit_sats: 100_000
+            }
+        ));
+
+        assert!(guard.reject(&held.hold_id).is_some());
+        assert!(guard.reject(&held.hold_id).is_none());
+        assert!(guard.approve(&held.hold_id, 1_020).is_none());
+    }
+}
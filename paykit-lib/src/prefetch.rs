@@ -0,0 +1,385 @@
+//! Payee Prefetch
+//!
+//! Checkout needs several independent reads about a payee: the supported
+//! payments list, the payee's status document, its Noise endpoint, and any
+//! private endpoints it gave us. Issued one after another, each homeserver
+//! round trip adds to the time before the payer sees payment options.
+//!
+//! [`prefetch_payee`] issues all of them at once and returns a consolidated
+//! [`PayeeSnapshot`]. Apps can call it as soon as a payee is scanned or
+//! selected ("warming up" the payee) and keep the result in a
+//! [`SnapshotCache`] so the checkout screen reads from memory.
+//!
+//! A failed read does not fail the prefetch: the snapshot records which
+//! parts are missing in [`PayeeSnapshot::failures`] and checkout can retry
+//! just those.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use paykit_lib::prefetch::{prefetch_payee, SnapshotCache};
+//!
+//! let cache = SnapshotCache::new();
+//! let snapshot = cache.get_or_prefetch(&reader, &payee, now).await;
+//! if let Some(status) = &snapshot.status {
+//!     selector = selector.with_payee_status(status.clone(), now);
+//! }
+//! ```
+
+use crate::private_endpoints::{PrivateEndpoint, PrivateEndpointManager, PrivateEndpointStore};
+use crate::protocol::{fetch_noise_endpoint, fetch_payee_status, NoiseEndpointRecord, PayeeStatus};
+use crate::{
+    get_payment_list, EndpointData, MethodId, PublicKey, SupportedPayments,
+    UnauthenticatedTransportRead,
+};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Default time a snapshot is reused, in seconds.
+pub const DEFAULT_SNAPSHOT_TTL_SECS: i64 = 120;
+
+/// A piece of payee data fetched by [`prefetch_payee`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SnapshotPart {
+    /// Published payment endpoints.
+    SupportedPayments,
+    /// The payee status document.
+    Status,
+    /// The Noise endpoint record.
+    NoiseEndpoint,
+    /// Locally stored private endpoints.
+    PrivateEndpoints,
+}
+
+/// Everything checkout needs to know about a payee, fetched together.
+#[derive(Clone, Debug)]
+pub struct PayeeSnapshot {
+    /// The payee.
+    pub payee: PublicKey,
+    /// Published payment endpoints.
+    pub supported: SupportedPayments,
+    /// The payee's status, if one is published and still fresh.
+    pub status: Option<PayeeStatus>,
+    /// The payee's Noise endpoint, if published.
+    pub noise_endpoint: Option<NoiseEndpointRecord>,
+    /// Unexpired private endpoints the payee offered us.
+    pub private_endpoints: Vec<PrivateEndpoint>,
+    /// When the snapshot was taken (unix seconds).
+    pub fetched_at: i64,
+    /// Parts that could not be fetched, with the error message.
+    pub failures: Vec<(SnapshotPart, String)>,
+}
+
+impl PayeeSnapshot {
+    fn empty(payee: &PublicKey, now: i64) -> Self {
+        Self {
+            payee: payee.clone(),
+            supported: SupportedPayments::default(),
+            status: None,
+            noise_endpoint: None,
+            private_endpoints: Vec::new(),
+            fetched_at: now,
+            failures: Vec::new(),
+        }
+    }
+
+    /// Whether every part was fetched.
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Whether `part` failed to fetch.
+    pub fn failed(&self, part: SnapshotPart) -> bool {
+        self.failures.iter().any(|(failed, _)| *failed == part)
+    }
+
+    /// Endpoint to pay `method` with, preferring a private endpoint over
+    /// the public one.
+    pub fn endpoint(&self, method: &MethodId) -> Option<&EndpointData> {
+        self.private_endpoints
+            .iter()
+            .find(|private| &private.method_id == method)
+            .map(|private| &private.endpoint)
+            .or_else(|| self.supported.entries.get(method))
+    }
+
+    /// Every method with a public or private endpoint.
+    pub fn methods(&self) -> Vec<MethodId> {
+        let mut methods: Vec<MethodId> = self.supported.entries.keys().cloned().collect();
+        for private in &self.private_endpoints {
+            if !methods.contains(&private.method_id) {
+                methods.push(private.method_id.clone());
+            }
+        }
+        methods
+    }
+
+    /// Whether the snapshot is younger than `ttl_secs` at `now`.
+    pub fn is_fresh(&self, now: i64, ttl_secs: i64) -> bool {
+        now - self.fetched_at < ttl_secs
+    }
+}
+
+/// Fetch a payee's public data concurrently.
+///
+/// The supported payments list, status document and Noise endpoint are
+/// requested at the same time. Failures are recorded in the snapshot.
+pub async fn prefetch_payee<R>(reader: &R, payee: &PublicKey, now: i64) -> PayeeSnapshot
+where
+    R: UnauthenticatedTransportRead,
+{
+    let mut snapshot = PayeeSnapshot::empty(payee, now);
+    let (supported, status, noise) = tokio::join!(
+        get_payment_list(reader, payee),
+        fetch_payee_status(reader, payee, now),
+        fetch_noise_endpoint(reader, payee),
+    );
+    match supported {
+        Ok(supported) => snapshot.supported = supported,
+        Err(e) => snapshot
+            .failures
+            .push((SnapshotPart::SupportedPayments, e.to_string())),
+    }
+    match status {
+        Ok(status) => snapshot.status = status,
+        Err(e) => snapshot
+            .failures
+            .push((SnapshotPart::Status, e.to_string())),
+    }
+    match noise {
+        Ok(noise) => snapshot.noise_endpoint = noise,
+        Err(e) => snapshot
+            .failures
+            .push((SnapshotPart::NoiseEndpoint, e.to_string())),
+    }
+    snapshot
+}
+
+/// Fetch a payee's public data and our private endpoints for it concurrently.
+pub async fn prefetch_payee_with_private<R, S>(
+    reader: &R,
+    manager: &PrivateEndpointManager<S>,
+    payee: &PublicKey,
+    now: i64,
+) -> PayeeSnapshot
+where
+    R: UnauthenticatedTransportRead,
+    S: PrivateEndpointStore,
+{
+    let (mut snapshot, private) = tokio::join!(
+        prefetch_payee(reader, payee, now),
+        manager.get_endpoints_for_peer(payee),
+    );
+    match private {
+        Ok(private) => snapshot.private_endpoints = private,
+        Err(e) => snapshot
+            .failures
+            .push((SnapshotPart::PrivateEndpoints, e.to_string())),
+    }
+    snapshot
+}
+
+/// Recently prefetched payee snapshots.
+///
+/// Incomplete snapshots are cached too, so a payee whose homeserver is
+/// partly down is not refetched on every read; call
+/// [`SnapshotCache::invalidate`] to force a refetch.
+pub struct SnapshotCache {
+    ttl_secs: i64,
+    snapshots: Mutex<HashMap<String, PayeeSnapshot>>,
+}
+
+impl Default for SnapshotCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SnapshotCache {
+    /// Create an empty cache with the default TTL.
+    pub fn new() -> Self {
+        Self {
+            ttl_secs: DEFAULT_SNAPSHOT_TTL_SECS,
+            snapshots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Set how long snapshots are reused.
+    pub fn with_ttl_secs(mut self, ttl_secs: i64) -> Self {
+        self.ttl_secs = ttl_secs;
+        self
+    }
+
+    /// Get a cached snapshot that is still fresh at `now`.
+    pub fn get(&self, payee: &PublicKey, now: i64) -> Option<PayeeSnapshot> {
+        let mut snapshots = self.lock_snapshots();
+        snapshots.retain(|_, snapshot| snapshot.is_fresh(now, self.ttl_secs));
+        snapshots.get(&peer_to_string(payee)).cloned()
+    }
+
+    /// Store a snapshot, replacing any older one for the same payee.
+    pub fn insert(&self, snapshot: PayeeSnapshot) {
+        self.lock_snapshots()
+            .insert(peer_to_string(&snapshot.payee), snapshot);
+    }
+
+    /// Return the cached snapshot, or prefetch and cache a new one.
+    pub async fn get_or_prefetch<R>(&self, reader: &R, payee: &PublicKey, now: i64) -> PayeeSnapshot
+    where
+        R: UnauthenticatedTransportRead,
+    {
+        if let Some(snapshot) = self.get(payee, now) {
+            return snapshot;
+        }
+        let snapshot = prefetch_payee(reader, payee, now).await;
+        self.insert(snapshot.clone());
+        snapshot
+    }
+
+    /// Forget the snapshot for `payee`.
+    pub fn invalidate(&self, payee: &PublicKey) {
+        self.lock_snapshots().remove(&peer_to_string(payee));
+    }
+
+    /// Forget all snapshots.
+    pub fn clear(&self) {
+        self.lock_snapshots().clear();
+    }
+
+    fn lock_snapshots(&self) -> std::sync::MutexGuard<'_, HashMap<String, PayeeSnapshot>> {
+        self.snapshots.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(feature = "pubky")]
+fn peer_to_string(peer: &PublicKey) -> String {
+    peer.to_string()
+}
+
+#[cfg(not(feature = "pubky"))]
+fn peer_to_string(peer: &PublicKey) -> String {
+    peer.0.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::private_endpoints::InMemoryStore;
+    use crate::protocol::{noise_endpoint_path, payee_status_path};
+    use crate::{PaykitError, Result};
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Serves fixed files and counts reads so cache hits can be observed.
+    #[derive(Default)]
+    struct FixedReader {
+        files: HashMap<String, String>,
+        reads: AtomicU32,
+    }
+
+    #[async_trait]
+    impl UnauthenticatedTransportRead for FixedReader {
+        async fn fetch_supported_payments(&self, _payee: &PublicKey) -> Result<SupportedPayments> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            let mut supported = SupportedPayments::default();
+            supported.entries.insert(
+                MethodId("lightning".into()),
+                EndpointData("lnurl1public".into()),
+            );
+            Ok(supported)
+        }
+
+        async fn fetch_payment_endpoint(
+            &self,
+            _payee: &PublicKey,
+            _method: &MethodId,
+        ) -> Result<Option<EndpointData>> {
+            Ok(None)
+        }
+
+        async fn fetch_known_contacts(&self, _owner: &PublicKey) -> Result<Vec<PublicKey>> {
+            Ok(Vec::new())
+        }
+
+        async fn get(&self, _owner: &PublicKey, path: &str) -> Result<Option<String>> {
+            if path == noise_endpoint_path() {
+                return Err(PaykitError::Transport("homeserver unavailable".into()));
+            }
+            Ok(self.files.get(path).cloned())
+        }
+
+        async fn list_directory(&self, _owner: &PublicKey, _path: &str) -> Result<Vec<String>> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn test_pubkey() -> PublicKey {
+        #[cfg(feature = "pubky")]
+        {
+            pubky::Keypair::random().public_key()
+        }
+        #[cfg(not(feature = "pubky"))]
+        {
+            PublicKey("test_pubkey_123".to_string())
+        }
+    }
+
+    fn reader() -> FixedReader {
+        let status = PayeeStatus::new(1_000).with_unavailable(MethodId("onchain".into()));
+        let mut reader = FixedReader::default();
+        reader.files.insert(
+            payee_status_path().to_string(),
+            serde_json::to_string(&status).unwrap(),
+        );
+        reader
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_records_partial_failure() {
+        let payee = test_pubkey();
+        let snapshot = prefetch_payee(&reader(), &payee, 1_000).await;
+
+        assert_eq!(snapshot.supported.entries.len(), 1);
+        assert!(snapshot.status.is_some());
+        assert!(snapshot.noise_endpoint.is_none());
+        assert!(snapshot.failed(SnapshotPart::NoiseEndpoint));
+        assert!(!snapshot.is_complete());
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_prefers_private_endpoint() {
+        let payee = test_pubkey();
+        let lightning = MethodId("lightning".into());
+        let manager = PrivateEndpointManager::new(InMemoryStore::new());
+        manager
+            .store_endpoint(
+                payee.clone(),
+                lightning.clone(),
+                EndpointData("lnurl1private".into()),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let snapshot = prefetch_payee_with_private(&reader(), &manager, &payee, 1_000).await;
+        assert_eq!(snapshot.endpoint(&lightning).unwrap().0, "lnurl1private");
+        assert_eq!(snapshot.methods(), vec![lightning]);
+    }
+
+    #[tokio::test]
+    async fn test_cache_reuses_fresh_snapshot() {
+        let payee = test_pubkey();
+        let reader = reader();
+        let cache = SnapshotCache::new().with_ttl_secs(60);
+
+        cache.get_or_prefetch(&reader, &payee, 1_000).await;
+        cache.get_or_prefetch(&reader, &payee, 1_030).await;
+        assert_eq!(reader.reads.load(Ordering::SeqCst), 1);
+
+        cache.get_or_prefetch(&reader, &payee, 1_060).await;
+        assert_eq!(reader.reads.load(Ordering::SeqCst), 2);
+
+        cache.invalidate(&payee);
+        assert!(cache.get(&payee, 1_060).is_none());
+    }
+}
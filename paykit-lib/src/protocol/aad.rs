@@ -12,7 +12,10 @@
 //! - `path` is the full storage path
 //! - `id` is the object identifier
 
-use super::paths::{payment_request_path, secure_handoff_path, subscription_proposal_path};
+use super::paths::{
+    inbox_message_path, payment_request_path, secure_handoff_path, subscription_proposal_path,
+    subscription_status_path,
+};
 use crate::Result;
 
 /// AAD prefix for all Paykit v0 sealed blobs.
@@ -24,9 +27,15 @@ pub const PURPOSE_REQUEST: &str = "request";
 /// Purpose label for subscription proposals.
 pub const PURPOSE_SUBSCRIPTION_PROPOSAL: &str = "subscription_proposal";
 
+/// Purpose label for subscription status notices.
+pub const PURPOSE_SUBSCRIPTION_STATUS: &str = "subscription_status";
+
 /// Purpose label for secure handoff payloads.
 pub const PURPOSE_HANDOFF: &str = "handoff";
 
+/// Purpose label for asynchronous peer-to-peer messages.
+pub const PURPOSE_INBOX: &str = "inbox";
+
 /// Build AAD for a payment request.
 ///
 /// Format: `paykit:v0:request:{path}:{request_id}`
@@ -91,6 +100,32 @@ pub fn subscription_proposal_aad(subscriber_pubkey_z32: &str, proposal_id: &str)
     ))
 }
 
+/// Build AAD for a subscription status notice.
+///
+/// Format: `paykit:v0:subscription_status:{path}:{subscription_id}`
+///
+/// # Example
+///
+/// ```
+/// use paykit_lib::protocol::subscription_status_aad;
+///
+/// let aad = subscription_status_aad(
+///     "8pinxxgqs41n4aididenw5apqp1urfmzdztr8jt4abrkdn435ewo",
+///     "sub-789"
+/// ).unwrap();
+/// assert!(aad.starts_with("paykit:v0:subscription_status:"));
+/// ```
+pub fn subscription_status_aad(
+    subscriber_pubkey_z32: &str,
+    subscription_id: &str,
+) -> Result<String> {
+    let path = subscription_status_path(subscriber_pubkey_z32, subscription_id)?;
+    Ok(format!(
+        "{}:{}:{}:{}",
+        AAD_PREFIX, PURPOSE_SUBSCRIPTION_STATUS, path, subscription_id
+    ))
+}
+
 /// Build AAD for a secure handoff payload.
 ///
 /// Format: `paykit:v0:handoff:{path}:{request_id}`
@@ -127,6 +162,37 @@ pub fn secure_handoff_aad(owner_pubkey_z32: &str, request_id: &str) -> String {
     )
 }
 
+/// Build AAD for an asynchronous peer-to-peer message.
+///
+/// Format: `paykit:v0:inbox:{sender}:{path}:{message_id}`
+///
+/// The path only names the recipient, so the sender is bound in as well:
+/// a message copied to another sender's storage fails to decrypt.
+///
+/// # Example
+///
+/// ```
+/// use paykit_lib::protocol::inbox_message_aad;
+///
+/// let aad = inbox_message_aad(
+///     "ybndrfg8ejkmcpqxot1uwisza345h769ybndrfg8ejkmcpqxot1u",
+///     "8pinxxgqs41n4aididenw5apqp1urfmzdztr8jt4abrkdn435ewo",
+///     "msg-1"
+/// ).unwrap();
+/// assert!(aad.starts_with("paykit:v0:inbox:ybndrfg8"));
+/// ```
+pub fn inbox_message_aad(
+    sender_pubkey_z32: &str,
+    recipient_pubkey_z32: &str,
+    message_id: &str,
+) -> Result<String> {
+    let path = inbox_message_path(recipient_pubkey_z32, message_id)?;
+    Ok(format!(
+        "{}:{}:{}:{}:{}",
+        AAD_PREFIX, PURPOSE_INBOX, sender_pubkey_z32, path, message_id
+    ))
+}
+
 /// Build AAD from explicit path and ID.
 ///
 /// This is the low-level builder for cases where you already have the path.
@@ -164,6 +230,14 @@ mod tests {
         assert!(aad.ends_with(":prop-456"));
     }
 
+    #[test]
+    fn subscription_status_aad_format() {
+        let aad = subscription_status_aad(TEST_PUBKEY, "sub-789").unwrap();
+        assert!(aad
+            .starts_with("paykit:v0:subscription_status:/pub/paykit.app/v0/subscriptions/status/"));
+        assert!(aad.ends_with(":sub-789"));
+    }
+
     #[test]
     fn secure_handoff_aad_format() {
         let aad = secure_handoff_aad(TEST_PUBKEY, "handoff-789");
@@ -173,6 +247,16 @@ mod tests {
         assert!(aad.ends_with(":handoff-789"));
     }
 
+    #[test]
+    fn inbox_message_aad_binds_sender() {
+        let pubkey2 = "8pinxxgqs41n4aididenw5apqp1urfmzdztr8jt4abrkdn435ewo";
+        let aad = inbox_message_aad(TEST_PUBKEY, pubkey2, "msg-1").unwrap();
+        assert!(aad.starts_with("paykit:v0:inbox:"));
+        assert!(aad.contains("/pub/paykit.app/v0/inbox/"));
+        assert!(aad.ends_with(":msg-1"));
+        assert_ne!(aad, inbox_message_aad(pubkey2, pubkey2, "msg-1").unwrap());
+    }
+
     #[test]
     fn build_aad_produces_correct_format() {
         let aad = build_aad("custom", "/some/path", "id-123");
@@ -0,0 +1,402 @@
+//! Receipt attachments.
+//!
+//! Merchants can attach small files (a PDF invoice, a photo of the goods)
+//! to a receipt. The receipt metadata only carries an [`AttachmentRef`]
+//! under [`ATTACHMENTS_METADATA_KEY`]: the file's name, type, size and
+//! SHA-256. The bytes themselves are either
+//!
+//! - **inline**, base64 encoded in the reference, for files up to
+//!   [`MAX_INLINE_ATTACHMENT_BYTES`], or
+//! - **stored** content-addressed at [`attachment_path`] on the attaching
+//!   party's storage, for files up to [`MAX_ATTACHMENT_BYTES`].
+//!
+//! [`fetch_attachment`] always checks the size and hash against the
+//! reference, so a stored blob cannot be swapped after the receipt was
+//! issued. Only a few document and image types are accepted, and the file
+//! contents must match the declared type.
+
+use super::document::check_document_size;
+use super::paths::attachment_path;
+use crate::{AuthenticatedTransport, PaykitError, PublicKey, Result, UnauthenticatedTransportRead};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// Largest attachment carried inside the receipt itself (8 KiB).
+pub const MAX_INLINE_ATTACHMENT_BYTES: usize = 8 * 1024;
+
+/// Largest attachment accepted (2 MiB).
+pub const MAX_ATTACHMENT_BYTES: usize = 2 * 1024 * 1024;
+
+/// Most attachments on one receipt.
+pub const MAX_ATTACHMENTS: usize = 8;
+
+/// Longest attachment file name, in bytes.
+pub const MAX_ATTACHMENT_NAME_BYTES: usize = 128;
+
+/// Receipt metadata key holding the list of [`AttachmentRef`]s.
+pub const ATTACHMENTS_METADATA_KEY: &str = "attachments";
+
+/// MIME types that can be attached.
+pub const ALLOWED_ATTACHMENT_TYPES: &[&str] = &[
+    "application/pdf",
+    "image/png",
+    "image/jpeg",
+    "image/webp",
+    "text/plain",
+];
+
+/// A file to attach to a receipt.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Attachment {
+    /// File name shown to the user.
+    pub name: String,
+    /// MIME type, one of [`ALLOWED_ATTACHMENT_TYPES`].
+    pub mime_type: String,
+    /// File contents.
+    pub data: Vec<u8>,
+}
+
+impl Attachment {
+    /// Create an attachment, checking its name, type and size.
+    pub fn new(
+        name: impl Into<String>,
+        mime_type: impl Into<String>,
+        data: Vec<u8>,
+    ) -> Result<Self> {
+        let attachment = Self {
+            name: name.into(),
+            mime_type: mime_type.into().to_ascii_lowercase(),
+            data,
+        };
+        attachment.validate()?;
+        Ok(attachment)
+    }
+
+    /// Check the name, type and size, and that the contents match the type.
+    pub fn validate(&self) -> Result<()> {
+        validate_name(&self.name)?;
+        validate_mime_type(&self.mime_type)?;
+        if self.data.is_empty() || self.data.len() > MAX_ATTACHMENT_BYTES {
+            return Err(PaykitError::invalid_data(
+                "attachment",
+                format!(
+                    "attachment must be between 1 and {} bytes",
+                    MAX_ATTACHMENT_BYTES
+                ),
+            ));
+        }
+        check_content_type(&self.mime_type, &self.data)
+    }
+
+    /// Lowercase hex SHA-256 of the contents.
+    pub fn sha256(&self) -> String {
+        hex::encode(Sha256::digest(&self.data))
+    }
+
+    /// Whether the file is small enough to travel inline.
+    pub fn fits_inline(&self) -> bool {
+        self.data.len() <= MAX_INLINE_ATTACHMENT_BYTES
+    }
+}
+
+/// Reference to an attachment, stored in the receipt metadata.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttachmentRef {
+    /// File name shown to the user.
+    pub name: String,
+    /// MIME type.
+    pub mime_type: String,
+    /// Size of the contents in bytes.
+    pub size: u64,
+    /// Lowercase hex SHA-256 of the contents.
+    pub sha256: String,
+    /// Base64 contents for inline attachments; `None` when stored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inline: Option<String>,
+}
+
+impl AttachmentRef {
+    /// Reference carrying the attachment inline.
+    pub fn inline(attachment: &Attachment) -> Result<Self> {
+        attachment.validate()?;
+        if !attachment.fits_inline() {
+            return Err(PaykitError::invalid_data(
+                "attachment",
+                format!(
+                    "inline attachments are limited to {} bytes",
+                    MAX_INLINE_ATTACHMENT_BYTES
+                ),
+            ));
+        }
+        Ok(Self {
+            inline: Some(base64::engine::general_purpose::STANDARD.encode(&attachment.data)),
+            ..Self::stored(attachment)
+        })
+    }
+
+    /// Reference to an attachment stored at [`attachment_path`].
+    pub fn stored(attachment: &Attachment) -> Self {
+        Self {
+            name: attachment.name.clone(),
+            mime_type: attachment.mime_type.clone(),
+            size: attachment.data.len() as u64,
+            sha256: attachment.sha256(),
+            inline: None,
+        }
+    }
+
+    /// Whether the contents travel inside the reference.
+    pub fn is_inline(&self) -> bool {
+        self.inline.is_some()
+    }
+
+    /// Check the reference is well-formed.
+    pub fn validate(&self) -> Result<()> {
+        validate_name(&self.name)?;
+        validate_mime_type(&self.mime_type)?;
+        attachment_path(&self.sha256)?;
+        if self.size == 0 || self.size > MAX_ATTACHMENT_BYTES as u64 {
+            return Err(PaykitError::invalid_data(
+                "attachment.size",
+                format!("size must be between 1 and {} bytes", MAX_ATTACHMENT_BYTES),
+            ));
+        }
+        if self.is_inline() && self.size > MAX_INLINE_ATTACHMENT_BYTES as u64 {
+            return Err(PaykitError::invalid_data(
+                "attachment.inline",
+                format!(
+                    "inline attachments are limited to {} bytes",
+                    MAX_INLINE_ATTACHMENT_BYTES
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check that `data` is the referenced file.
+    pub fn verify(&self, data: &[u8]) -> Result<()> {
+        if data.len() as u64 != self.size {
+            return Err(PaykitError::invalid_data(
+                "attachment",
+                format!("expected {} bytes, got {}", self.size, data.len()),
+            ));
+        }
+        if hex::encode(Sha256::digest(data)) != self.sha256 {
+            return Err(PaykitError::invalid_data(
+                "attachment",
+                "contents do not match the SHA-256 in the receipt",
+            ));
+        }
+        check_content_type(&self.mime_type, data)
+    }
+}
+
+/// Attach a file: inline if small enough, otherwise stored on `client`.
+pub async fn attach<S>(client: &S, attachment: &Attachment) -> Result<AttachmentRef>
+where
+    S: AuthenticatedTransport,
+{
+    if attachment.fits_inline() {
+        AttachmentRef::inline(attachment)
+    } else {
+        publish_attachment(client, attachment).await
+    }
+}
+
+/// Store an attachment at its content address and return its reference.
+pub async fn publish_attachment<S>(client: &S, attachment: &Attachment) -> Result<AttachmentRef>
+where
+    S: AuthenticatedTransport,
+{
+    attachment.validate()?;
+    let reference = AttachmentRef::stored(attachment);
+    let encoded = base64::engine::general_purpose::STANDARD.encode(&attachment.data);
+    client
+        .put(&attachment_path(&reference.sha256)?, &encoded)
+        .await?;
+    Ok(reference)
+}
+
+/// Remove a stored attachment.
+///
+/// Receipts that still reference it will fail to fetch it.
+pub async fn remove_attachment<S>(client: &S, sha256: &str) -> Result<()>
+where
+    S: AuthenticatedTransport,
+{
+    client.delete(&attachment_path(sha256)?).await
+}
+
+/// Get the contents of an attachment, verified against its reference.
+///
+/// Stored attachments are read from `owner`'s storage: the party that
+/// attached the file, usually the payee who issued the receipt.
+pub async fn fetch_attachment<R>(
+    reader: &R,
+    owner: &PublicKey,
+    reference: &AttachmentRef,
+) -> Result<Vec<u8>>
+where
+    R: UnauthenticatedTransportRead,
+{
+    reference.validate()?;
+    let encoded = match &reference.inline {
+        Some(inline) => inline.clone(),
+        None => reader
+            .get(owner, &attachment_path(&reference.sha256)?)
+            .await?
+            .ok_or_else(|| PaykitError::not_found("attachment", reference.sha256.clone()))?,
+    };
+    check_document_size("attachment", encoded.len(), encoded_len(reference.size))?;
+    let data = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| PaykitError::invalid_data("attachment", format!("invalid base64: {}", e)))?;
+    reference.verify(&data)?;
+    Ok(data)
+}
+
+/// Attachment references in receipt metadata, skipping malformed entries.
+pub fn attachments_from_metadata(metadata: &Value) -> Vec<AttachmentRef> {
+    metadata
+        .get(ATTACHMENTS_METADATA_KEY)
+        .and_then(Value::as_array)
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|entry| serde_json::from_value::<AttachmentRef>(entry.clone()).ok())
+                .filter(|reference| reference.validate().is_ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Add a reference to receipt metadata, replacing one with the same hash.
+pub fn add_attachment_to_metadata(metadata: &mut Value, reference: AttachmentRef) -> Result<()> {
+    reference.validate()?;
+    let mut attachments = attachments_from_metadata(metadata);
+    attachments.retain(|a| a.sha256 != reference.sha256);
+    if attachments.len() >= MAX_ATTACHMENTS {
+        return Err(PaykitError::invalid_data(
+            ATTACHMENTS_METADATA_KEY,
+            format!("at most {} attachments are allowed", MAX_ATTACHMENTS),
+        ));
+    }
+    attachments.push(reference);
+
+    if !metadata.is_object() {
+        *metadata = Value::Object(Default::default());
+    }
+    if let Some(map) = metadata.as_object_mut() {
+        map.insert(
+            ATTACHMENTS_METADATA_KEY.to_string(),
+            serde_json::to_value(attachments)?,
+        );
+    }
+    Ok(())
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.trim().is_empty()
+        || name.len() > MAX_ATTACHMENT_NAME_BYTES
+        || name
+            .chars()
+            .any(|c| c.is_control() || c == '/' || c == '\\')
+    {
+        return Err(PaykitError::invalid_data(
+            "attachment.name",
+            format!(
+                "name must be 1 to {} bytes without path separators or control characters",
+                MAX_ATTACHMENT_NAME_BYTES
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_mime_type(mime_type: &str) -> Result<()> {
+    if !ALLOWED_ATTACHMENT_TYPES.contains(&mime_type) {
+        return Err(PaykitError::invalid_data(
+            "attachment.mime_type",
+            format!(
+                "{} is not allowed (expected one of {})",
+                mime_type,
+                ALLOWED_ATTACHMENT_TYPES.join(", ")
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Check the file starts like its declared type.
+fn check_content_type(mime_type: &str, data: &[u8]) -> Result<()> {
+    let matches = match mime_type {
+        "application/pdf" => data.starts_with(b"%PDF-"),
+        "image/png" => data.starts_with(b"\x89PNG\r\n\x1a\n"),
+        "image/jpeg" => data.starts_with(&[0xFF, 0xD8, 0xFF]),
+        "image/webp" => data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP",
+        "text/plain" => std::str::from_utf8(data).is_ok(),
+        _ => false,
+    };
+    if !matches {
+        return Err(PaykitError::invalid_data(
+            "attachment",
+            format!("contents are not {}", mime_type),
+        ));
+    }
+    Ok(())
+}
+
+/// Base64 length of `size` bytes, plus room for a trailing newline.
+fn encoded_len(size: u64) -> usize {
+    (size as usize).div_ceil(3) * 4 + 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pdf(len: usize) -> Vec<u8> {
+        let mut data = b"%PDF-1.7\n".to_vec();
+        data.resize(len, b' ');
+        data
+    }
+
+    #[test]
+    fn attachment_validation() {
+        assert!(Attachment::new("invoice.pdf", "application/pdf", pdf(100)).is_ok());
+        assert!(Attachment::new("invoice.pdf", "APPLICATION/PDF", pdf(100)).is_ok());
+        // Contents must match the type
+        assert!(Attachment::new("invoice.pdf", "image/png", pdf(100)).is_err());
+        assert!(Attachment::new("run.sh", "application/x-sh", b"#!/bin/sh".to_vec()).is_err());
+        assert!(Attachment::new("../invoice.pdf", "application/pdf", pdf(100)).is_err());
+        assert!(Attachment::new("empty.txt", "text/plain", Vec::new()).is_err());
+        assert!(
+            Attachment::new("big.pdf", "application/pdf", pdf(MAX_ATTACHMENT_BYTES + 1)).is_err()
+        );
+    }
+
+    #[test]
+    fn inline_reference_roundtrip() {
+        let small = Attachment::new("note.txt", "text/plain", b"thank you".to_vec()).unwrap();
+        let reference = AttachmentRef::inline(&small).unwrap();
+        reference.validate().unwrap();
+        reference.verify(&small.data).unwrap();
+        assert!(reference.verify(b"thank yoU").is_err());
+
+        let large = Attachment::new("invoice.pdf", "application/pdf", pdf(10_000)).unwrap();
+        assert!(!large.fits_inline());
+        assert!(AttachmentRef::inline(&large).is_err());
+        assert!(!AttachmentRef::stored(&large).is_inline());
+
+        let mut metadata = serde_json::json!({"memo": "lunch"});
+        add_attachment_to_metadata(&mut metadata, reference.clone()).unwrap();
+        add_attachment_to_metadata(&mut metadata, reference.clone()).unwrap();
+        add_attachment_to_metadata(&mut metadata, AttachmentRef::stored(&large)).unwrap();
+        let attachments = attachments_from_metadata(&metadata);
+        assert_eq!(attachments.len(), 2);
+        assert_eq!(attachments[0], reference);
+        assert_eq!(metadata["memo"], "lunch");
+    }
+}
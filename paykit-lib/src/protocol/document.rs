@@ -0,0 +1,120 @@
+//! Bounded, typed parsing of directory documents.
+//!
+//! Directory documents are written by the payee, so their size is under
+//! someone else's control. Parsing them into `serde_json::Value` allocates
+//! a node per value and a `String` per string, which is wasteful for a
+//! large document when the caller only needs a few fields, and on low-end
+//! devices can exhaust memory outright.
+//!
+//! Instead, documents are checked against a size limit *before* parsing
+//! and deserialized straight into typed structs. Structs that borrow from
+//! the input (`Cow<'a, str>` with `#[serde(borrow)]`) copy nothing unless a
+//! string contains escapes, and fields the struct does not name are
+//! skipped without being materialized.
+//!
+//! Payment endpoints are limited to [`MAX_ENDPOINT_BYTES`]; the Noise
+//! endpoint record, payee status and supported snapshot to
+//! [`MAX_DOCUMENT_BYTES`].
+
+use crate::{PaykitError, Result};
+use serde::Deserialize;
+use std::borrow::Cow;
+
+/// Largest accepted payment endpoint document (16 KiB).
+pub const MAX_ENDPOINT_BYTES: usize = 16 * 1024;
+
+/// Largest accepted structured directory document (256 KiB).
+pub const MAX_DOCUMENT_BYTES: usize = 256 * 1024;
+
+/// Fail if `len` exceeds `max` bytes.
+pub fn check_document_size(field: &str, len: usize, max: usize) -> Result<()> {
+    if len > max {
+        return Err(PaykitError::invalid_data(
+            field,
+            format!("document is {} bytes, limit is {}", len, max),
+        ));
+    }
+    Ok(())
+}
+
+/// Deserialize a document after checking its size.
+///
+/// `T` may borrow from `bytes`, in which case no string is copied unless
+/// it contains escape sequences.
+pub fn parse_document<'de, T>(field: &str, bytes: &'de [u8], max: usize) -> Result<T>
+where
+    T: Deserialize<'de>,
+{
+    check_document_size(field, bytes.len(), max)?;
+    serde_json::from_slice(bytes)
+        .map_err(|e| PaykitError::invalid_data(field, format!("invalid JSON: {}", e)))
+}
+
+/// Address and invoice fields a JSON payment endpoint may carry.
+///
+/// Every field borrows from the endpoint text; unknown fields are ignored.
+#[derive(Debug, Default, Deserialize)]
+pub struct EndpointFields<'a> {
+    /// Generic on-chain address.
+    #[serde(borrow, default)]
+    pub address: Option<Cow<'a, str>>,
+    /// Native SegWit address.
+    #[serde(borrow, default)]
+    pub p2wpkh: Option<Cow<'a, str>>,
+    /// Taproot address.
+    #[serde(borrow, default)]
+    pub p2tr: Option<Cow<'a, str>>,
+    /// Nested SegWit address.
+    #[serde(borrow, default)]
+    pub p2sh: Option<Cow<'a, str>>,
+    /// Legacy address.
+    #[serde(borrow, default)]
+    pub p2pkh: Option<Cow<'a, str>>,
+    /// BOLT11 invoice.
+    #[serde(borrow, default)]
+    pub bolt11: Option<Cow<'a, str>>,
+    /// LNURL.
+    #[serde(borrow, default)]
+    pub lnurl: Option<Cow<'a, str>>,
+}
+
+impl<'a> EndpointFields<'a> {
+    /// Parse a JSON endpoint document.
+    pub fn parse(json: &'a str) -> Result<Self> {
+        parse_document("endpoint", json.as_bytes(), MAX_ENDPOINT_BYTES)
+    }
+
+    /// First on-chain address, in order of preference.
+    pub fn onchain_address(&self) -> Option<&str> {
+        [
+            &self.address,
+            &self.p2wpkh,
+            &self.p2tr,
+            &self.p2sh,
+            &self.p2pkh,
+        ]
+        .into_iter()
+        .find_map(|field| field.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_fields_borrow() {
+        let json = r#"{"p2tr":"bc1p...","label":{"nested":[1,2,3]},"bolt11":"lnbc1"}"#;
+        let fields = EndpointFields::parse(json).unwrap();
+        assert_eq!(fields.onchain_address(), Some("bc1p..."));
+        assert!(matches!(fields.bolt11, Some(Cow::Borrowed("lnbc1"))));
+        assert!(fields.lnurl.is_none());
+    }
+
+    #[test]
+    fn test_document_size_limit() {
+        let json = format!(r#"{{"address":"{}"}}"#, "a".repeat(MAX_ENDPOINT_BYTES));
+        assert!(EndpointFields::parse(&json).is_err());
+        assert!(check_document_size("doc", 10, 10).is_ok());
+    }
+}
@@ -2,8 +2,15 @@
 //!
 //! This module defines the single source of truth for:
 //! - Pubkey normalization and scope hashing
-//! - Storage path construction
+//! - Storage path construction, and white-label path roots
 //! - AAD (Additional Authenticated Data) formats for Sealed Blob v1
+//! - The Noise endpoint record and its key rotation rules
+//! - The self-reported payee status document
+//! - The published routing hints document
+//! - Content-addressed receipt attachments
+//! - Push-notification wake-up hints
+//! - Time-bounded payment links
+//! - Size limits and typed parsing for directory documents
 //!
 //! All Paykit clients (Rust, Kotlin, Swift) must implement equivalent logic
 //! and pass the same test vectors.
@@ -14,9 +21,21 @@
 //! |----------------------|------------------------------------------------------------------|-----------------|
 //! | Supported payment    | `/pub/paykit.app/v0/{method_id}`                                 | payee           |
 //! | Noise endpoint       | `/pub/paykit.app/v0/noise`                                       | payee           |
+//! | Payee status         | `/pub/paykit.app/v0/status`                                      | payee           |
+//! | Routing hints        | `/pub/paykit.app/v0/routing`                                     | payee           |
+//! | Push hint            | `/pub/paykit.app/v0/push`                                        | user            |
 //! | Payment request      | `/pub/paykit.app/v0/requests/{recipient_scope}/{request_id}`     | sender          |
 //! | Subscription proposal| `/pub/paykit.app/v0/subscriptions/proposals/{subscriber_scope}/{proposal_id}` | provider |
+//! | Subscription status  | `/pub/paykit.app/v0/subscriptions/status/{subscriber_scope}/{subscription_id}` | provider |
 //! | Secure handoff       | `/pub/paykit.app/v0/handoff/{request_id}`                        | Ring user       |
+//! | Receipt attachment   | `/pub/paykit.app/v0/attachments/{sha256}`                        | attaching party |
+//! | Device sync bundle   | `/pub/paykit.app/v0/sync/{device_id}`                            | identity        |
+//! | Inbox message        | `/pub/paykit.app/v0/inbox/{recipient_scope}/{message_id}`        | sender          |
+//! | Inbox read receipt   | `/pub/paykit.app/v0/inbox-receipts/{sender_scope}/{message_id}`  | recipient       |
+//! | Payment link         | `/pub/paykit.app/v0/links/{link_id}`                             | payee           |
+//!
+//! Deployments with their own namespace replace `paykit.app` through a
+//! [`PathRootConfig`]; the templates above stay the canonical form.
 //!
 //! # Scope Derivation
 //!
@@ -28,11 +47,27 @@
 //! - Works across all platforms (no z32 decode required)
 
 mod aad;
+mod attachments;
+mod document;
+mod noise_endpoint;
 mod paths;
+mod payee_status;
+mod payment_link;
+mod push_hint;
+mod root;
+mod routing_hints;
 mod scope;
 
 pub use aad::*;
+pub use attachments::*;
+pub use document::*;
+pub use noise_endpoint::*;
 pub use paths::*;
+pub use payee_status::*;
+pub use payment_link::*;
+pub use push_hint::*;
+pub use root::*;
+pub use routing_hints::*;
 pub use scope::*;
 
 /// Protocol version string.
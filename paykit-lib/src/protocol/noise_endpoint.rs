@@ -0,0 +1,270 @@
+//! Noise endpoint record and static key rotation.
+//!
+//! A payee publishes its Noise server address and X25519 static key at
+//! [`noise_endpoint_path`]. Rotating that key used to strand every payer
+//! holding the old one, so the record now carries a list of keys, each with
+//! a validity window:
+//!
+//! - the payee schedules the next key ahead of time with
+//!   [`NoiseEndpointRecord::schedule_rotation`], which also bounds the current
+//!   key to an overlap window after the switch;
+//! - the server accepts handshakes for every key in
+//!   [`NoiseEndpointRecord::accepted_keys`];
+//! - clients connect with [`NoiseEndpointRecord::preferred_key`], and when a
+//!   server answers with [`KEY_MISMATCH_HINT`] they refetch the record and
+//!   retry once.
+//!
+//! The top-level `pubkey` field always holds the preferred key so clients
+//! that predate rotation keep working.
+
+use super::document::{parse_document, MAX_DOCUMENT_BYTES};
+use super::paths::noise_endpoint_path;
+use crate::{AuthenticatedTransport, PaykitError, PublicKey, Result, UnauthenticatedTransportRead};
+use serde::{Deserialize, Serialize};
+
+/// Plaintext reply a server sends when a handshake targets none of its
+/// accepted static keys.
+///
+/// It carries no secret; it only tells the client its cached key is stale.
+pub const KEY_MISMATCH_HINT: &[u8] = b"paykit:v0:noise-key-mismatch";
+
+/// Whether a handshake response is the key-mismatch hint.
+pub fn is_key_mismatch_hint(response: &[u8]) -> bool {
+    response == KEY_MISMATCH_HINT
+}
+
+/// One published X25519 static key.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoiseKey {
+    /// X25519 public key, hex encoded.
+    pub pubkey: String,
+    /// Derivation epoch of the key.
+    pub epoch: u32,
+    /// First moment the key is accepted (unix seconds).
+    pub valid_from: i64,
+    /// Last moment the key is accepted (unix seconds), if bounded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_until: Option<i64>,
+}
+
+impl NoiseKey {
+    /// Whether the key is accepted at `now`.
+    pub fn is_valid_at(&self, now: i64) -> bool {
+        self.valid_from <= now && self.valid_until.is_none_or(|until| now <= until)
+    }
+}
+
+/// The record stored at [`noise_endpoint_path`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoiseEndpointRecord {
+    /// Host the Noise server listens on.
+    pub host: String,
+    /// Port the Noise server listens on.
+    pub port: u16,
+    /// Preferred static key (hex), kept for clients without rotation support.
+    pub pubkey: String,
+    /// Optional application metadata.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<String>,
+    /// Published keys with validity windows. Empty in records written before
+    /// rotation support, in which case `pubkey` is the only key.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<NoiseKey>,
+}
+
+impl NoiseEndpointRecord {
+    /// Create a record with a single key valid from `now`.
+    pub fn new(
+        host: impl Into<String>,
+        port: u16,
+        pubkey: impl Into<String>,
+        epoch: u32,
+        now: i64,
+    ) -> Self {
+        let pubkey = pubkey.into();
+        Self {
+            host: host.into(),
+            port,
+            pubkey: pubkey.clone(),
+            metadata: None,
+            keys: vec![NoiseKey {
+                pubkey,
+                epoch,
+                valid_from: now,
+                valid_until: None,
+            }],
+        }
+    }
+
+    /// Attach metadata.
+    pub fn with_metadata(mut self, metadata: impl Into<String>) -> Self {
+        self.metadata = Some(metadata.into());
+        self
+    }
+
+    /// Published keys, treating a legacy record as a single epoch-0 key.
+    pub fn all_keys(&self) -> Vec<NoiseKey> {
+        if self.keys.is_empty() {
+            vec![NoiseKey {
+                pubkey: self.pubkey.clone(),
+                epoch: 0,
+                valid_from: i64::MIN,
+                valid_until: None,
+            }]
+        } else {
+            self.keys.clone()
+        }
+    }
+
+    /// Keys a server should accept handshakes for at `now`.
+    pub fn accepted_keys(&self, now: i64) -> Vec<NoiseKey> {
+        self.all_keys()
+            .into_iter()
+            .filter(|key| key.is_valid_at(now))
+            .collect()
+    }
+
+    /// Key a client should connect with at `now`: the newest valid one.
+    pub fn preferred_key(&self, now: i64) -> Option<NoiseKey> {
+        self.accepted_keys(now)
+            .into_iter()
+            .max_by_key(|key| (key.valid_from, key.epoch))
+    }
+
+    /// Publish `next_pubkey` to take over at `activate_at`.
+    ///
+    /// Keys currently open-ended stay accepted until `activate_at +
+    /// overlap_secs`, giving payers with a cached key time to refresh. Any
+    /// previously scheduled key that would activate after `activate_at` is
+    /// dropped.
+    pub fn schedule_rotation(
+        &mut self,
+        next_pubkey: impl Into<String>,
+        next_epoch: u32,
+        activate_at: i64,
+        overlap_secs: i64,
+    ) -> Result<()> {
+        if overlap_secs < 0 {
+            return Err(PaykitError::invalid_data(
+                "overlap_secs",
+                "Overlap window cannot be negative",
+            ));
+        }
+        let next_pubkey = next_pubkey.into();
+        let mut keys = self.all_keys();
+        if keys
+            .iter()
+            .any(|key| key.epoch == next_epoch || key.pubkey == next_pubkey)
+        {
+            return Err(PaykitError::invalid_data(
+                "next_epoch",
+                "Key or epoch is already published",
+            ));
+        }
+
+        keys.retain(|key| key.valid_from <= activate_at);
+        let retire_at = activate_at.saturating_add(overlap_secs);
+        for key in &mut keys {
+            if key.valid_until.is_none_or(|until| until > retire_at) {
+                key.valid_until = Some(retire_at);
+            }
+        }
+        keys.push(NoiseKey {
+            pubkey: next_pubkey,
+            epoch: next_epoch,
+            valid_from: activate_at,
+            valid_until: None,
+        });
+        self.keys = keys;
+        Ok(())
+    }
+
+    /// Drop keys that expired before `now` and refresh the legacy `pubkey`
+    /// field. Call before republishing.
+    pub fn prune(&mut self, now: i64) {
+        self.keys = self.all_keys();
+        self.keys
+            .retain(|key| key.valid_until.is_none_or(|until| now <= until));
+        if let Some(preferred) = self.preferred_key(now) {
+            self.pubkey = preferred.pubkey;
+        }
+    }
+}
+
+/// Fetch and parse a payee's Noise endpoint record.
+pub async fn fetch_noise_endpoint<R>(
+    reader: &R,
+    owner: &PublicKey,
+) -> Result<Option<NoiseEndpointRecord>>
+where
+    R: UnauthenticatedTransportRead,
+{
+    match reader.get(owner, noise_endpoint_path()).await? {
+        Some(json) => Ok(Some(parse_document(
+            "noise_endpoint",
+            json.as_bytes(),
+            MAX_DOCUMENT_BYTES,
+        )?)),
+        None => Ok(None),
+    }
+}
+
+/// Publish this payee's Noise endpoint record.
+pub async fn publish_noise_endpoint<S>(client: &S, record: &NoiseEndpointRecord) -> Result<()>
+where
+    S: AuthenticatedTransport,
+{
+    client
+        .put(noise_endpoint_path(), &serde_json::to_string(record)?)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_record_is_single_key() {
+        let json = r#"{"host":"127.0.0.1","port":9000,"pubkey":"aa"}"#;
+        let record: NoiseEndpointRecord = serde_json::from_str(json).unwrap();
+        assert_eq!(record.accepted_keys(0).len(), 1);
+        assert_eq!(record.preferred_key(0).unwrap().pubkey, "aa");
+        assert_eq!(serde_json::to_string(&record).unwrap(), json);
+    }
+
+    #[test]
+    fn rotation_overlap_window() {
+        let mut record = NoiseEndpointRecord::new("127.0.0.1", 9000, "aa", 0, 100);
+        record.schedule_rotation("bb", 1, 1_000, 500).unwrap();
+
+        // Before activation only the current key is accepted
+        assert_eq!(record.accepted_keys(999).len(), 1);
+        assert_eq!(record.preferred_key(999).unwrap().pubkey, "aa");
+
+        // During the overlap both are accepted and clients move to the new key
+        assert_eq!(record.accepted_keys(1_200).len(), 2);
+        assert_eq!(record.preferred_key(1_200).unwrap().pubkey, "bb");
+
+        // After the overlap the old key is gone
+        let accepted = record.accepted_keys(1_501);
+        assert_eq!(accepted.len(), 1);
+        assert_eq!(accepted[0].epoch, 1);
+
+        record.prune(1_501);
+        assert_eq!(record.keys.len(), 1);
+        assert_eq!(record.pubkey, "bb");
+    }
+
+    #[test]
+    fn rotation_rejects_reused_epoch() {
+        let mut record = NoiseEndpointRecord::new("127.0.0.1", 9000, "aa", 0, 100);
+        assert!(record.schedule_rotation("bb", 0, 1_000, 60).is_err());
+        assert!(record.schedule_rotation("bb", 1, 1_000, -1).is_err());
+    }
+
+    #[test]
+    fn mismatch_hint() {
+        assert!(is_key_mismatch_hint(KEY_MISMATCH_HINT));
+        assert!(!is_key_mismatch_hint(b"\x00\x01"));
+    }
+}
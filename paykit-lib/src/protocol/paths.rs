@@ -15,12 +15,39 @@ pub const REQUESTS_SUBPATH: &str = "requests";
 /// Path suffix for subscription proposals directory.
 pub const SUBSCRIPTION_PROPOSALS_SUBPATH: &str = "subscriptions/proposals";
 
+/// Path suffix for subscription status notices directory.
+pub const SUBSCRIPTION_STATUS_SUBPATH: &str = "subscriptions/status";
+
 /// Path for Noise endpoint.
 pub const NOISE_ENDPOINT_SUBPATH: &str = "noise";
 
+/// Path for the payee status document.
+pub const PAYEE_STATUS_SUBPATH: &str = "status";
+
+/// Path for the published routing hints document.
+pub const ROUTING_HINTS_SUBPATH: &str = "routing";
+
 /// Path suffix for secure handoff directory.
 pub const HANDOFF_SUBPATH: &str = "handoff";
 
+/// Path suffix for content-addressed receipt attachments.
+pub const ATTACHMENTS_SUBPATH: &str = "attachments";
+
+/// Path suffix for encrypted multi-device sync bundles.
+pub const DEVICE_SYNC_SUBPATH: &str = "sync";
+
+/// Path suffix for asynchronous peer-to-peer messages.
+pub const INBOX_SUBPATH: &str = "inbox";
+
+/// Path suffix for read receipts of asynchronous peer-to-peer messages.
+pub const INBOX_RECEIPTS_SUBPATH: &str = "inbox-receipts";
+
+/// Path for the push-notification wake-up hint.
+pub const PUSH_HINT_SUBPATH: &str = "push";
+
+/// Path suffix for published payment links.
+pub const PAYMENT_LINKS_SUBPATH: &str = "links";
+
 /// Build the storage path for a payment request.
 ///
 /// Path format: `/pub/paykit.app/v0/requests/{recipient_scope}/{request_id}`
@@ -138,6 +165,41 @@ pub fn subscription_proposals_dir(subscriber_pubkey_z32: &str) -> Result<String>
     ))
 }
 
+/// Build the storage path for a subscription status notice.
+///
+/// Path format: `/pub/paykit.app/v0/subscriptions/status/{subscriber_scope}/{subscription_id}`
+///
+/// This path is used on the **provider's** storage. Each subscription has a
+/// single notice that is overwritten when its status changes.
+///
+/// # Arguments
+///
+/// * `subscriber_pubkey_z32` - The subscriber's z-base-32 encoded pubkey
+/// * `subscription_id` - The subscription the notice is about
+///
+/// # Example
+///
+/// ```
+/// use paykit_lib::protocol::subscription_status_path;
+///
+/// let path = subscription_status_path(
+///     "8pinxxgqs41n4aididenw5apqp1urfmzdztr8jt4abrkdn435ewo",
+///     "sub-789"
+/// ).unwrap();
+/// assert!(path.starts_with("/pub/paykit.app/v0/subscriptions/status/"));
+/// assert!(path.ends_with("/sub-789"));
+/// ```
+pub fn subscription_status_path(
+    subscriber_pubkey_z32: &str,
+    subscription_id: &str,
+) -> Result<String> {
+    let scope = subscriber_scope(subscriber_pubkey_z32)?;
+    Ok(format!(
+        "{}/{}/{}/{}",
+        PAYKIT_V0_PREFIX, SUBSCRIPTION_STATUS_SUBPATH, scope, subscription_id
+    ))
+}
+
 /// Build the storage path for a Noise endpoint.
 ///
 /// Path format: `/pub/paykit.app/v0/noise`
@@ -147,6 +209,33 @@ pub fn noise_endpoint_path() -> &'static str {
     concat!("/pub/paykit.app/v0/", "noise")
 }
 
+/// Build the storage path for the payee status document.
+///
+/// Path format: `/pub/paykit.app/v0/status`
+///
+/// This is a fixed path on the payee's own storage.
+pub fn payee_status_path() -> &'static str {
+    concat!("/pub/paykit.app/v0/", "status")
+}
+
+/// Build the storage path for the published routing hints document.
+///
+/// Path format: `/pub/paykit.app/v0/routing`
+///
+/// This is a fixed path on the payee's own storage.
+pub fn routing_hints_path() -> &'static str {
+    concat!("/pub/paykit.app/v0/", "routing")
+}
+
+/// Build the storage path for the push-notification wake-up hint.
+///
+/// Path format: `/pub/paykit.app/v0/push`
+///
+/// This is a fixed path on the user's own storage.
+pub fn push_hint_path() -> &'static str {
+    concat!("/pub/paykit.app/v0/", "push")
+}
+
 /// Build the storage path for a secure handoff payload.
 ///
 /// Path format: `/pub/paykit.app/v0/handoff/{request_id}`
@@ -165,6 +254,164 @@ pub fn secure_handoff_path(request_id: &str) -> String {
     format!("{}/{}/{}", PAYKIT_V0_PREFIX, HANDOFF_SUBPATH, request_id)
 }
 
+/// Build the storage path for a receipt attachment.
+///
+/// Path format: `/pub/paykit.app/v0/attachments/{sha256}`
+///
+/// Attachments are content-addressed: the last segment is the lowercase hex
+/// SHA-256 of the attachment bytes, so the same file is only stored once.
+///
+/// # Arguments
+///
+/// * `sha256_hex` - Lowercase hex SHA-256 of the attachment (64 chars)
+pub fn attachment_path(sha256_hex: &str) -> Result<String> {
+    if sha256_hex.len() != 64
+        || !sha256_hex
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+    {
+        return Err(crate::PaykitError::invalid_data(
+            "sha256",
+            "expected 64 lowercase hex characters",
+        ));
+    }
+    Ok(format!(
+        "{}/{}/{}",
+        PAYKIT_V0_PREFIX, ATTACHMENTS_SUBPATH, sha256_hex
+    ))
+}
+
+/// Build the storage path for one device's encrypted sync bundle.
+///
+/// Path format: `/pub/paykit.app/v0/sync/{device_id}`
+///
+/// Each device of an identity writes its own bundle on the identity's
+/// storage; the other devices list the directory and read the rest.
+///
+/// # Arguments
+///
+/// * `device_id` - 1 to 64 ASCII letters, digits, `-` or `_`
+pub fn device_sync_path(device_id: &str) -> Result<String> {
+    if device_id.is_empty()
+        || device_id.len() > 64
+        || !device_id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+    {
+        return Err(crate::PaykitError::invalid_data(
+            "device_id",
+            "expected 1-64 letters, digits, '-' or '_'",
+        ));
+    }
+    Ok(format!(
+        "{}/{}/{}",
+        PAYKIT_V0_PREFIX, DEVICE_SYNC_SUBPATH, device_id
+    ))
+}
+
+/// Check a mailbox message ID: 1 to 64 ASCII letters, digits, `-` or `_`.
+fn check_message_id(message_id: &str) -> Result<()> {
+    if message_id.is_empty()
+        || message_id.len() > 64
+        || !message_id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+    {
+        return Err(crate::PaykitError::invalid_data(
+            "message_id",
+            "expected 1-64 letters, digits, '-' or '_'",
+        ));
+    }
+    Ok(())
+}
+
+/// Build the storage path for an asynchronous message to a peer.
+///
+/// Path format: `/pub/paykit.app/v0/inbox/{recipient_scope}/{message_id}`
+///
+/// This path is used on the **sender's** storage: the recipient's inbox is
+/// the directory scoped to it on each of its contacts' storage.
+///
+/// # Arguments
+///
+/// * `recipient_pubkey_z32` - The recipient's z-base-32 encoded pubkey
+/// * `message_id` - 1 to 64 ASCII letters, digits, `-` or `_`
+pub fn inbox_message_path(recipient_pubkey_z32: &str, message_id: &str) -> Result<String> {
+    check_message_id(message_id)?;
+    let scope = recipient_scope(recipient_pubkey_z32)?;
+    Ok(format!(
+        "{}/{}/{}/{}",
+        PAYKIT_V0_PREFIX, INBOX_SUBPATH, scope, message_id
+    ))
+}
+
+/// Build the directory path for listing asynchronous messages to a recipient.
+///
+/// Path format: `/pub/paykit.app/v0/inbox/{recipient_scope}/`
+pub fn inbox_dir(recipient_pubkey_z32: &str) -> Result<String> {
+    let scope = recipient_scope(recipient_pubkey_z32)?;
+    Ok(format!("{}/{}/{}/", PAYKIT_V0_PREFIX, INBOX_SUBPATH, scope))
+}
+
+/// Build the storage path for the read receipt of an asynchronous message.
+///
+/// Path format: `/pub/paykit.app/v0/inbox-receipts/{sender_scope}/{message_id}`
+///
+/// This path is used on the **recipient's** storage. The sender deletes the
+/// message once it sees the receipt.
+///
+/// # Arguments
+///
+/// * `sender_pubkey_z32` - The message sender's z-base-32 encoded pubkey
+/// * `message_id` - The ID of the message that was read
+pub fn inbox_receipt_path(sender_pubkey_z32: &str, message_id: &str) -> Result<String> {
+    check_message_id(message_id)?;
+    let scope = recipient_scope(sender_pubkey_z32)?;
+    Ok(format!(
+        "{}/{}/{}/{}",
+        PAYKIT_V0_PREFIX, INBOX_RECEIPTS_SUBPATH, scope, message_id
+    ))
+}
+
+/// Build the directory path for listing read receipts addressed to a sender.
+///
+/// Path format: `/pub/paykit.app/v0/inbox-receipts/{sender_scope}/`
+pub fn inbox_receipts_dir(sender_pubkey_z32: &str) -> Result<String> {
+    let scope = recipient_scope(sender_pubkey_z32)?;
+    Ok(format!(
+        "{}/{}/{}/",
+        PAYKIT_V0_PREFIX, INBOX_RECEIPTS_SUBPATH, scope
+    ))
+}
+
+/// Build the storage path for a payment link.
+///
+/// Path format: `/pub/paykit.app/v0/links/{link_id}`
+///
+/// This path is used on the **payee's** storage. Anyone holding the link ID
+/// can read it.
+///
+/// # Arguments
+///
+/// * `link_id` - 1 to 64 ASCII letters, digits, `-` or `_`
+pub fn payment_link_path(link_id: &str) -> Result<String> {
+    if link_id.is_empty()
+        || link_id.len() > 64
+        || !link_id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+    {
+        return Err(crate::PaykitError::invalid_data(
+            "link_id",
+            "expected 1-64 letters, digits, '-' or '_'",
+        ));
+    }
+    Ok(format!(
+        "{}/{}/{}",
+        PAYKIT_V0_PREFIX, PAYMENT_LINKS_SUBPATH, link_id
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -206,12 +453,37 @@ mod tests {
         assert!(dir.ends_with('/'));
     }
 
+    #[test]
+    fn subscription_status_path_format() {
+        let path = subscription_status_path(TEST_PUBKEY, "sub-789").unwrap();
+        assert!(path.starts_with("/pub/paykit.app/v0/subscriptions/status/"));
+        assert!(path.ends_with("/sub-789"));
+        let parts: Vec<&str> = path.split('/').collect();
+        assert_eq!(parts.len(), 8);
+        assert_eq!(parts[6].len(), 64);
+    }
+
     #[test]
     fn noise_endpoint_path_is_fixed() {
         let path = noise_endpoint_path();
         assert_eq!(path, "/pub/paykit.app/v0/noise");
     }
 
+    #[test]
+    fn payee_status_path_is_fixed() {
+        assert_eq!(payee_status_path(), "/pub/paykit.app/v0/status");
+    }
+
+    #[test]
+    fn routing_hints_path_is_fixed() {
+        assert_eq!(routing_hints_path(), "/pub/paykit.app/v0/routing");
+    }
+
+    #[test]
+    fn push_hint_path_is_fixed() {
+        assert_eq!(push_hint_path(), "/pub/paykit.app/v0/push");
+    }
+
     #[test]
     fn secure_handoff_path_format() {
         let path = secure_handoff_path("handoff-789");
@@ -240,4 +512,53 @@ mod tests {
         let scope2 = path2.split('/').nth(5).unwrap();
         assert_ne!(scope1, scope2);
     }
+
+    #[test]
+    fn attachment_path_format() {
+        let hash = "ab".repeat(32);
+        assert_eq!(
+            attachment_path(&hash).unwrap(),
+            format!("/pub/paykit.app/v0/attachments/{}", hash)
+        );
+        assert!(attachment_path("AB").is_err());
+        assert!(attachment_path(&"AB".repeat(32)).is_err());
+        assert!(attachment_path(&format!("../{}", &hash[3..])).is_err());
+    }
+
+    #[test]
+    fn device_sync_path_format() {
+        assert_eq!(
+            device_sync_path("phone-1").unwrap(),
+            "/pub/paykit.app/v0/sync/phone-1"
+        );
+        assert!(device_sync_path("").is_err());
+        assert!(device_sync_path("../status").is_err());
+        assert!(device_sync_path(&"a".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn inbox_paths_format() {
+        let path = inbox_message_path(TEST_PUBKEY, "msg-1").unwrap();
+        assert!(path.starts_with(&inbox_dir(TEST_PUBKEY).unwrap()));
+        assert!(path.ends_with("/msg-1"));
+        let parts: Vec<&str> = path.split('/').collect();
+        assert_eq!(parts.len(), 7);
+        assert_eq!(parts[5].len(), 64);
+
+        let receipt = inbox_receipt_path(TEST_PUBKEY, "msg-1").unwrap();
+        assert!(receipt.starts_with("/pub/paykit.app/v0/inbox-receipts/"));
+        assert!(receipt.starts_with(&inbox_receipts_dir(TEST_PUBKEY).unwrap()));
+        assert!(inbox_message_path(TEST_PUBKEY, "../noise").is_err());
+        assert!(inbox_receipt_path(TEST_PUBKEY, "").is_err());
+    }
+
+    #[test]
+    fn payment_link_path_format() {
+        assert_eq!(
+            payment_link_path("coffee-42").unwrap(),
+            "/pub/paykit.app/v0/links/coffee-42"
+        );
+        assert!(payment_link_path("").is_err());
+        assert!(payment_link_path("../status").is_err());
+    }
 }
@@ -0,0 +1,271 @@
+//! Self-reported payee status.
+//!
+//! A payee can publish a small status document at [`payee_status_path`]
+//! telling payers whether it is accepting payments right now, which methods
+//! are temporarily unavailable, which one it would prefer, and any scheduled
+//! maintenance window. Payers fetch it with [`fetch_payee_status`] and hand
+//! it to [`PaymentMethodSelector::with_payee_status`], which demotes the
+//! methods the payee marked unavailable rather than dropping them.
+//!
+//! Statuses carry a TTL. A payee that goes offline without clearing a
+//! "paused" status would otherwise block payments indefinitely, so an
+//! expired status is treated as absent.
+//!
+//! [`PaymentMethodSelector::with_payee_status`]: crate::selection::PaymentMethodSelector::with_payee_status
+
+use super::document::{parse_document, MAX_DOCUMENT_BYTES};
+use super::paths::payee_status_path;
+use crate::{
+    AuthenticatedTransport, MethodId, PaykitError, PublicKey, Result, UnauthenticatedTransportRead,
+};
+use serde::{Deserialize, Serialize};
+
+/// Default status lifetime: one hour.
+pub const DEFAULT_STATUS_TTL_SECS: u64 = 3600;
+
+/// Longest accepted status lifetime: one week.
+pub const MAX_STATUS_TTL_SECS: u64 = 7 * 24 * 3600;
+
+/// A scheduled period during which the payee does not accept payments.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaintenanceWindow {
+    /// Start of the window (unix seconds).
+    pub starts_at: i64,
+    /// End of the window (unix seconds).
+    pub ends_at: i64,
+    /// Optional note shown to payers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl MaintenanceWindow {
+    /// Whether `now` falls inside the window.
+    pub fn contains(&self, now: i64) -> bool {
+        self.starts_at <= now && now < self.ends_at
+    }
+}
+
+/// The status document stored at [`payee_status_path`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayeeStatus {
+    /// Whether the payee is accepting payments at all.
+    pub accepting_payments: bool,
+    /// Methods that are temporarily unavailable.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unavailable_methods: Vec<MethodId>,
+    /// Method the payee would prefer right now.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preferred_method: Option<MethodId>,
+    /// Scheduled maintenance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maintenance: Option<MaintenanceWindow>,
+    /// Free-form message for payers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// When the status was published (unix seconds).
+    pub updated_at: i64,
+    /// Seconds after `updated_at` for which the status is valid.
+    #[serde(default = "default_ttl")]
+    pub ttl_secs: u64,
+}
+
+fn default_ttl() -> u64 {
+    DEFAULT_STATUS_TTL_SECS
+}
+
+impl PayeeStatus {
+    /// A status accepting payments on every method, published at `now`.
+    pub fn new(now: i64) -> Self {
+        Self {
+            accepting_payments: true,
+            unavailable_methods: Vec::new(),
+            preferred_method: None,
+            maintenance: None,
+            message: None,
+            updated_at: now,
+            ttl_secs: DEFAULT_STATUS_TTL_SECS,
+        }
+    }
+
+    /// Set whether payments are accepted at all.
+    pub fn with_accepting(mut self, accepting: bool) -> Self {
+        self.accepting_payments = accepting;
+        self
+    }
+
+    /// Mark a method as temporarily unavailable.
+    pub fn with_unavailable(mut self, method: MethodId) -> Self {
+        if !self.unavailable_methods.contains(&method) {
+            self.unavailable_methods.push(method);
+        }
+        self
+    }
+
+    /// Set the preferred method.
+    pub fn with_preferred(mut self, method: MethodId) -> Self {
+        self.preferred_method = Some(method);
+        self
+    }
+
+    /// Schedule a maintenance window.
+    pub fn with_maintenance(mut self, starts_at: i64, ends_at: i64, note: Option<String>) -> Self {
+        self.maintenance = Some(MaintenanceWindow {
+            starts_at,
+            ends_at,
+            note,
+        });
+        self
+    }
+
+    /// Attach a message for payers.
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// Set the lifetime of the status.
+    pub fn with_ttl(mut self, ttl_secs: u64) -> Self {
+        self.ttl_secs = ttl_secs;
+        self
+    }
+
+    /// Moment after which the status no longer applies.
+    pub fn expires_at(&self) -> i64 {
+        self.updated_at
+            .saturating_add(self.ttl_secs.min(i64::MAX as u64) as i64)
+    }
+
+    /// Whether the status still applies at `now`.
+    pub fn is_fresh(&self, now: i64) -> bool {
+        now <= self.expires_at()
+    }
+
+    /// Whether `now` falls inside the maintenance window.
+    pub fn in_maintenance(&self, now: i64) -> bool {
+        self.maintenance
+            .as_ref()
+            .is_some_and(|window| window.contains(now))
+    }
+
+    /// Whether the payee accepts payments at `now`.
+    pub fn is_accepting(&self, now: i64) -> bool {
+        self.accepting_payments && !self.in_maintenance(now)
+    }
+
+    /// Whether `method` can be used at `now`.
+    pub fn is_method_available(&self, method: &MethodId, now: i64) -> bool {
+        self.is_accepting(now) && !self.unavailable_methods.contains(method)
+    }
+
+    /// Check the status is well-formed before publishing.
+    pub fn validate(&self) -> Result<()> {
+        if self.ttl_secs == 0 || self.ttl_secs > MAX_STATUS_TTL_SECS {
+            return Err(PaykitError::invalid_data(
+                "ttl_secs",
+                format!("TTL must be between 1 and {} seconds", MAX_STATUS_TTL_SECS),
+            ));
+        }
+        if let Some(window) = &self.maintenance {
+            if window.ends_at <= window.starts_at {
+                return Err(PaykitError::invalid_data(
+                    "maintenance",
+                    "Maintenance window must end after it starts",
+                ));
+            }
+        }
+        if let Some(preferred) = &self.preferred_method {
+            if self.unavailable_methods.contains(preferred) {
+                return Err(PaykitError::invalid_data(
+                    "preferred_method",
+                    "Preferred method is marked unavailable",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Fetch a payee's status, ignoring it if it has expired at `now`.
+pub async fn fetch_payee_status<R>(
+    reader: &R,
+    owner: &PublicKey,
+    now: i64,
+) -> Result<Option<PayeeStatus>>
+where
+    R: UnauthenticatedTransportRead,
+{
+    match reader.get(owner, payee_status_path()).await? {
+        Some(json) => {
+            let status: PayeeStatus =
+                parse_document("payee_status", json.as_bytes(), MAX_DOCUMENT_BYTES)?;
+            Ok(status.is_fresh(now).then_some(status))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Publish this payee's status.
+pub async fn publish_payee_status<S>(client: &S, status: &PayeeStatus) -> Result<()>
+where
+    S: AuthenticatedTransport,
+{
+    status.validate()?;
+    client
+        .put(payee_status_path(), &serde_json::to_string(status)?)
+        .await
+}
+
+/// Remove this payee's status, returning payers to default selection.
+pub async fn clear_payee_status<S>(client: &S) -> Result<()>
+where
+    S: AuthenticatedTransport,
+{
+    client.delete(payee_status_path()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_ttl_and_maintenance() {
+        let lightning = MethodId("lightning".into());
+        let onchain = MethodId("onchain".into());
+        let status = PayeeStatus::new(1_000)
+            .with_unavailable(lightning.clone())
+            .with_maintenance(2_000, 3_000, Some("node upgrade".into()))
+            .with_ttl(5_000);
+        status.validate().unwrap();
+
+        assert!(status.is_method_available(&onchain, 1_500));
+        assert!(!status.is_method_available(&lightning, 1_500));
+        assert!(!status.is_accepting(2_500));
+        assert!(status.is_accepting(3_000));
+
+        assert!(status.is_fresh(6_000));
+        assert!(!status.is_fresh(6_001));
+    }
+
+    #[test]
+    fn status_validation() {
+        assert!(PayeeStatus::new(0).with_ttl(0).validate().is_err());
+        assert!(PayeeStatus::new(0)
+            .with_maintenance(10, 10, None)
+            .validate()
+            .is_err());
+        let lightning = MethodId("lightning".into());
+        assert!(PayeeStatus::new(0)
+            .with_unavailable(lightning.clone())
+            .with_preferred(lightning)
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn status_defaults_ttl() {
+        let json = r#"{"accepting_payments":false,"updated_at":100}"#;
+        let status: PayeeStatus = serde_json::from_str(json).unwrap();
+        assert_eq!(status.ttl_secs, DEFAULT_STATUS_TTL_SECS);
+        assert!(!status.is_accepting(100));
+    }
+}
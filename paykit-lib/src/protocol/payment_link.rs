@@ -0,0 +1,496 @@
+//! Time-bounded payment links.
+//!
+//! A payment link is the Paykit equivalent of a checkout link: the payee
+//! publishes a [`PaymentLink`] at [`payment_link_path`] with an amount, an
+//! expiry and whether it can be paid once or several times, and shares
+//! `paykit://link/{payee}/{link_id}` (see [`payment_link_uri`]). The payer
+//! resolves the link into a [`PaymentIntent`] with [`resolve_payment_link`]
+//! and pays it like any other payment, tagging the receipt with the link ID
+//! so the payee can [consume](PaymentLink::consume) the link.
+//!
+//! The published document is only what payers see. The payee's own copy is
+//! authoritative: it must refuse payments for links that are expired,
+//! revoked or used up even if the directory copy has not caught up yet, and
+//! consume the link in the same step that confirms the receipt.
+
+use super::document::{parse_document, MAX_DOCUMENT_BYTES};
+use super::paths::payment_link_path;
+use crate::{
+    AuthenticatedTransport, MethodId, PaykitError, PublicKey, Result, UnauthenticatedTransportRead,
+};
+use serde::{Deserialize, Serialize};
+
+/// URI scheme prefix of payment links.
+pub const PAYMENT_LINK_URI_PREFIX: &str = "paykit://link/";
+
+/// Receipt metadata key carrying the ID of the link being paid.
+pub const PAYMENT_LINK_METADATA_KEY: &str = "payment_link";
+
+/// Longest accepted memo or success message, in bytes.
+pub const MAX_LINK_TEXT_BYTES: usize = 280;
+
+/// How many times a link can be paid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LinkUsage {
+    /// Paid once, then consumed.
+    Single,
+    /// Paid up to `max_uses` times, or without limit.
+    Multi {
+        /// Maximum number of payments, if any.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_uses: Option<u32>,
+    },
+}
+
+impl LinkUsage {
+    /// Maximum number of payments, or `None` for unlimited.
+    pub fn max_uses(&self) -> Option<u32> {
+        match self {
+            Self::Single => Some(1),
+            Self::Multi { max_uses } => *max_uses,
+        }
+    }
+}
+
+/// What the payer's app shows or does once the link is paid.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SuccessAction {
+    /// Page to open after paying (https).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redirect_url: Option<String>,
+    /// Message to show after paying.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Whether a link can be paid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkState {
+    /// Can be paid.
+    Active,
+    /// Past its expiry.
+    Expired,
+    /// Paid as many times as allowed.
+    Consumed,
+    /// Withdrawn by the payee.
+    Revoked,
+}
+
+impl LinkState {
+    /// Lowercase name of the state.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Expired => "expired",
+            Self::Consumed => "consumed",
+            Self::Revoked => "revoked",
+        }
+    }
+}
+
+/// The document stored at [`payment_link_path`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaymentLink {
+    /// Link identifier, unique per payee.
+    pub link_id: String,
+    /// Amount to pay in sats; `None` lets the payer choose.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount_sats: Option<u64>,
+    /// What the payment is for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+    /// Methods accepted for this link; empty means any published method.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub methods: Vec<MethodId>,
+    /// When the link was created (unix seconds).
+    pub created_at: i64,
+    /// When the link stops accepting payments (unix seconds).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+    /// How many times the link can be paid.
+    pub usage: LinkUsage,
+    /// Payments made so far.
+    #[serde(default)]
+    pub uses: u32,
+    /// Whether the payee withdrew the link.
+    #[serde(default)]
+    pub revoked: bool,
+    /// What happens after paying.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success: Option<SuccessAction>,
+}
+
+impl PaymentLink {
+    /// A single-use link for any amount, created at `now`, that never expires.
+    pub fn new(link_id: impl Into<String>, now: i64) -> Self {
+        Self {
+            link_id: link_id.into(),
+            amount_sats: None,
+            memo: None,
+            methods: Vec::new(),
+            created_at: now,
+            expires_at: None,
+            usage: LinkUsage::Single,
+            uses: 0,
+            revoked: false,
+            success: None,
+        }
+    }
+
+    /// Set the amount to pay.
+    pub fn with_amount(mut self, amount_sats: u64) -> Self {
+        self.amount_sats = Some(amount_sats);
+        self
+    }
+
+    /// Describe what the payment is for.
+    pub fn with_memo(mut self, memo: impl Into<String>) -> Self {
+        self.memo = Some(memo.into());
+        self
+    }
+
+    /// Accept only these methods.
+    pub fn with_methods(mut self, methods: Vec<MethodId>) -> Self {
+        self.methods = methods;
+        self
+    }
+
+    /// Stop accepting payments `ttl_secs` after creation.
+    pub fn with_ttl(mut self, ttl_secs: u64) -> Self {
+        self.expires_at = Some(
+            self.created_at
+                .saturating_add(ttl_secs.min(i64::MAX as u64) as i64),
+        );
+        self
+    }
+
+    /// Set how many times the link can be paid.
+    pub fn with_usage(mut self, usage: LinkUsage) -> Self {
+        self.usage = usage;
+        self
+    }
+
+    /// Set what happens after paying.
+    pub fn with_success(mut self, success: SuccessAction) -> Self {
+        self.success = Some(success);
+        self
+    }
+
+    /// Whether the link can be paid at `now`.
+    ///
+    /// A revoked link reports [`LinkState::Revoked`] and a used-up one
+    /// [`LinkState::Consumed`], whatever its expiry.
+    pub fn state(&self, now: i64) -> LinkState {
+        if self.revoked {
+            LinkState::Revoked
+        } else if self.usage.max_uses().is_some_and(|max| self.uses >= max) {
+            LinkState::Consumed
+        } else if self.expires_at.is_some_and(|at| now >= at) {
+            LinkState::Expired
+        } else {
+            LinkState::Active
+        }
+    }
+
+    /// Payments left, or `None` for unlimited.
+    pub fn remaining_uses(&self) -> Option<u32> {
+        self.usage
+            .max_uses()
+            .map(|max| max.saturating_sub(self.uses))
+    }
+
+    /// Fail unless the link can be paid at `now`.
+    ///
+    /// # Errors
+    ///
+    /// - [`PaykitError::InvoiceExpired`] if the link has expired
+    /// - [`PaykitError::PaymentAlreadyCompleted`] if it is used up
+    /// - [`PaykitError::PaymentRejected`] if it was revoked
+    pub fn check_payable(&self, now: i64) -> Result<()> {
+        match self.state(now) {
+            LinkState::Active => Ok(()),
+            LinkState::Expired => Err(PaykitError::InvoiceExpired {
+                invoice_id: self.link_id.clone(),
+                expired_at: self.expires_at.unwrap_or_default(),
+            }),
+            LinkState::Consumed => Err(PaykitError::PaymentAlreadyCompleted {
+                payment_id: self.link_id.clone(),
+            }),
+            LinkState::Revoked => Err(PaykitError::PaymentRejected {
+                payment_id: self.link_id.clone(),
+                reason: "Payment link was revoked".to_string(),
+            }),
+        }
+    }
+
+    /// Record one payment at `now`, returning the link's new state.
+    ///
+    /// Callers must hold whatever lock guards their copy of the link from
+    /// the check through the save, so two payments cannot both take the last
+    /// use.
+    ///
+    /// # Errors
+    ///
+    /// As [`check_payable`](Self::check_payable); the link is unchanged.
+    pub fn consume(&mut self, now: i64) -> Result<LinkState> {
+        self.check_payable(now)?;
+        self.uses += 1;
+        Ok(self.state(now))
+    }
+
+    /// Check the link is well-formed before publishing.
+    pub fn validate(&self) -> Result<()> {
+        payment_link_path(&self.link_id)?;
+        if self.amount_sats == Some(0) {
+            return Err(PaykitError::invalid_data(
+                "amount_sats",
+                "Amount must be positive",
+            ));
+        }
+        if self
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= self.created_at)
+        {
+            return Err(PaykitError::invalid_data(
+                "expires_at",
+                "Link must expire after it is created",
+            ));
+        }
+        if self.usage.max_uses() == Some(0) {
+            return Err(PaykitError::invalid_data(
+                "usage",
+                "A link must allow at least one payment",
+            ));
+        }
+        check_text("memo", self.memo.as_deref())?;
+        if let Some(success) = &self.success {
+            check_text("success.message", success.message.as_deref())?;
+            if success
+                .redirect_url
+                .as_deref()
+                .is_some_and(|url| !url.starts_with("https://"))
+            {
+                return Err(PaykitError::invalid_data(
+                    "success.redirect_url",
+                    "Redirect URL must use https",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// What a payer needs to pay this link at `now`.
+    ///
+    /// # Errors
+    ///
+    /// As [`check_payable`](Self::check_payable).
+    pub fn intent(&self, payee: &PublicKey, now: i64) -> Result<PaymentIntent> {
+        self.check_payable(now)?;
+        Ok(PaymentIntent {
+            payee: payee.clone(),
+            link_id: self.link_id.clone(),
+            amount_sats: self.amount_sats,
+            memo: self.memo.clone(),
+            methods: self.methods.clone(),
+            expires_at: self.expires_at,
+            success: self.success.clone(),
+        })
+    }
+}
+
+fn check_text(field: &str, text: Option<&str>) -> Result<()> {
+    if text.is_some_and(|text| text.len() > MAX_LINK_TEXT_BYTES) {
+        return Err(PaykitError::invalid_data(
+            field,
+            format!("must be at most {} bytes", MAX_LINK_TEXT_BYTES),
+        ));
+    }
+    Ok(())
+}
+
+/// A resolved payment link, ready to pay.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PaymentIntent {
+    /// Who to pay.
+    pub payee: PublicKey,
+    /// The link being paid.
+    pub link_id: String,
+    /// Amount to pay in sats; `None` lets the payer choose.
+    pub amount_sats: Option<u64>,
+    /// What the payment is for.
+    pub memo: Option<String>,
+    /// Methods the payee accepts for this link; empty means any.
+    pub methods: Vec<MethodId>,
+    /// When the link stops accepting payments (unix seconds).
+    pub expires_at: Option<i64>,
+    /// What happens after paying.
+    pub success: Option<SuccessAction>,
+}
+
+impl PaymentIntent {
+    /// Receipt metadata that tells the payee which link is being paid.
+    pub fn receipt_metadata(&self) -> serde_json::Value {
+        serde_json::json!({ PAYMENT_LINK_METADATA_KEY: self.link_id })
+    }
+}
+
+/// The link ID a receipt's metadata says it pays, if any.
+pub fn payment_link_id(metadata: &serde_json::Value) -> Option<&str> {
+    metadata.get(PAYMENT_LINK_METADATA_KEY)?.as_str()
+}
+
+/// Shareable URI for a payee's link: `paykit://link/{payee}/{link_id}`.
+pub fn payment_link_uri(payee: &PublicKey, link_id: &str) -> String {
+    format!("{}{}/{}", PAYMENT_LINK_URI_PREFIX, payee, link_id)
+}
+
+/// Split a payment link URI into the payee's z-base-32 key and the link ID.
+///
+/// The key is not checked beyond being non-empty; parse it with the
+/// transport's public key type.
+pub fn parse_payment_link_uri(uri: &str) -> Result<(&str, &str)> {
+    let invalid = || PaykitError::invalid_data("uri", "expected paykit://link/{payee}/{link_id}");
+    let rest = uri
+        .trim()
+        .strip_prefix(PAYMENT_LINK_URI_PREFIX)
+        .ok_or_else(invalid)?;
+    let (payee, link_id) = rest.split_once('/').ok_or_else(invalid)?;
+    if payee.is_empty() {
+        return Err(invalid());
+    }
+    payment_link_path(link_id)?;
+    Ok((payee, link_id))
+}
+
+/// Fetch one of `owner`'s payment links as published.
+pub async fn fetch_payment_link<R>(
+    reader: &R,
+    owner: &PublicKey,
+    link_id: &str,
+) -> Result<Option<PaymentLink>>
+where
+    R: UnauthenticatedTransportRead,
+{
+    match reader.get(owner, &payment_link_path(link_id)?).await? {
+        Some(json) => {
+            let link: PaymentLink =
+                parse_document("payment_link", json.as_bytes(), MAX_DOCUMENT_BYTES)?;
+            if link.link_id != link_id {
+                return Err(PaykitError::invalid_data(
+                    "link_id",
+                    "Published link ID does not match its path",
+                ));
+            }
+            Ok(Some(link))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Fetch one of `owner`'s payment links and turn it into a payment intent.
+///
+/// # Errors
+///
+/// [`PaykitError::NotFound`] if the link is not published, otherwise as
+/// [`PaymentLink::check_payable`].
+pub async fn resolve_payment_link<R>(
+    reader: &R,
+    owner: &PublicKey,
+    link_id: &str,
+    now: i64,
+) -> Result<PaymentIntent>
+where
+    R: UnauthenticatedTransportRead,
+{
+    fetch_payment_link(reader, owner, link_id)
+        .await?
+        .ok_or_else(|| PaykitError::not_found("payment_link", link_id))?
+        .intent(owner, now)
+}
+
+/// Publish or update a payment link.
+pub async fn publish_payment_link<S>(client: &S, link: &PaymentLink) -> Result<()>
+where
+    S: AuthenticatedTransport,
+{
+    link.validate()?;
+    client
+        .put(
+            &payment_link_path(&link.link_id)?,
+            &serde_json::to_string(link)?,
+        )
+        .await
+}
+
+/// Remove a published payment link.
+pub async fn remove_payment_link<S>(client: &S, link_id: &str) -> Result<()>
+where
+    S: AuthenticatedTransport,
+{
+    client.delete(&payment_link_path(link_id)?).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_use_link_is_consumed_once() {
+        let mut link = PaymentLink::new("coffee", 1_000)
+            .with_amount(5_000)
+            .with_ttl(600);
+        link.validate().unwrap();
+        assert_eq!(link.state(1_500), LinkState::Active);
+        assert_eq!(link.state(1_600), LinkState::Expired);
+
+        assert_eq!(link.consume(1_100).unwrap(), LinkState::Consumed);
+        assert!(matches!(
+            link.consume(1_200),
+            Err(PaykitError::PaymentAlreadyCompleted { .. })
+        ));
+        assert_eq!(link.uses, 1);
+    }
+
+    #[test]
+    fn multi_use_link_counts_down() {
+        let mut link =
+            PaymentLink::new("donate", 0).with_usage(LinkUsage::Multi { max_uses: Some(2) });
+        assert_eq!(link.consume(1).unwrap(), LinkState::Active);
+        assert_eq!(link.remaining_uses(), Some(1));
+        assert_eq!(link.consume(2).unwrap(), LinkState::Consumed);
+
+        let mut unlimited =
+            PaymentLink::new("tips", 0).with_usage(LinkUsage::Multi { max_uses: None });
+        for now in 0..10 {
+            unlimited.consume(now).unwrap();
+        }
+        assert_eq!(unlimited.remaining_uses(), None);
+
+        link.revoked = true;
+        assert_eq!(link.state(3), LinkState::Revoked);
+    }
+
+    #[test]
+    fn link_validation_and_uri() {
+        let link = PaymentLink::new("coffee", 1_000).with_success(SuccessAction {
+            redirect_url: Some("http://shop.example/thanks".into()),
+            message: None,
+        });
+        assert!(link.validate().is_err());
+        assert!(PaymentLink::new("coffee", 1_000)
+            .with_amount(0)
+            .validate()
+            .is_err());
+        assert!(PaymentLink::new("../noise", 1_000).validate().is_err());
+
+        let (payee, link_id) = parse_payment_link_uri("paykit://link/abc123/coffee").unwrap();
+        assert_eq!((payee, link_id), ("abc123", "coffee"));
+        assert!(parse_payment_link_uri("paykit://link/abc123").is_err());
+        assert!(parse_payment_link_uri("paykit://request/abc123/coffee").is_err());
+
+        let metadata = serde_json::json!({ "payment_link": "coffee" });
+        assert_eq!(payment_link_id(&metadata), Some("coffee"));
+    }
+}
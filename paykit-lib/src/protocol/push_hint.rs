@@ -0,0 +1,192 @@
+//! Push-notification wake-up hints.
+//!
+//! Messages left in a peer's inbox sit there until the peer polls. A mobile
+//! user can publish a push hint at [`push_hint_path`] so senders can wake
+//! the app instead:
+//!
+//! - the user picks a notification relay and seals a [`PushRegistration`]
+//!   (its APNS or FCM token) to the relay's X25519 key, with
+//!   [`push_token_aad`] as AAD, so only that relay can read the token;
+//! - a sender that drops a message in the user's inbox fetches the hint and
+//!   POSTs [`PushHintRecord::wake_up`] to the relay's URL;
+//! - the relay opens the token and sends a content-free push.
+//!
+//! The hint is optional and carries no message content. Anyone can ask the
+//! relay to wake the user, so relays should rate-limit per token.
+
+use super::aad::build_aad;
+use super::document::{parse_document, MAX_DOCUMENT_BYTES};
+use super::paths::push_hint_path;
+use crate::{AuthenticatedTransport, PaykitError, PublicKey, Result, UnauthenticatedTransportRead};
+use serde::{Deserialize, Serialize};
+
+/// Purpose label for push tokens sealed to a notification relay.
+pub const PURPOSE_PUSH_TOKEN: &str = "push_token";
+
+/// Push service a token belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PushPlatform {
+    /// Apple Push Notification service.
+    Apns,
+    /// Firebase Cloud Messaging.
+    Fcm,
+}
+
+/// The plaintext a user seals to its notification relay.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PushRegistration {
+    /// Push service the token belongs to.
+    pub platform: PushPlatform,
+    /// Device token issued by the push service.
+    pub token: String,
+    /// App bundle or package ID the token was issued for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_id: Option<String>,
+}
+
+/// The record stored at [`push_hint_path`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PushHintRecord {
+    /// Relay endpoint senders POST wake-up requests to (https).
+    pub relay_url: String,
+    /// Relay's X25519 public key, hex encoded.
+    pub relay_pubkey: String,
+    /// [`PushRegistration`] sealed to the relay (Sealed Blob v1).
+    pub sealed_token: String,
+    /// When the hint was published (unix seconds).
+    pub updated_at: i64,
+}
+
+/// Body a sender POSTs to the relay to wake a user.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WakeUpRequest {
+    /// The user to wake, so the relay can rebuild the AAD.
+    pub recipient: String,
+    /// The sealed token from the user's hint.
+    pub sealed_token: String,
+}
+
+impl PushHintRecord {
+    /// Check the hint is well-formed before publishing.
+    pub fn validate(&self) -> Result<()> {
+        if !self.relay_url.starts_with("https://") {
+            return Err(PaykitError::invalid_data(
+                "relay_url",
+                "Relay URL must use https",
+            ));
+        }
+        let key_ok = self.relay_pubkey.len() == 64
+            && self.relay_pubkey.bytes().all(|b| b.is_ascii_hexdigit());
+        if !key_ok {
+            return Err(PaykitError::invalid_data(
+                "relay_pubkey",
+                "expected 64 hex characters",
+            ));
+        }
+        if self.sealed_token.is_empty() {
+            return Err(PaykitError::invalid_data(
+                "sealed_token",
+                "sealed token cannot be empty",
+            ));
+        }
+        Ok(())
+    }
+
+    /// The request that wakes `recipient`, the owner of this hint.
+    pub fn wake_up(&self, recipient: &PublicKey) -> WakeUpRequest {
+        WakeUpRequest {
+            recipient: recipient.to_string(),
+            sealed_token: self.sealed_token.clone(),
+        }
+    }
+}
+
+/// AAD for a push token sealed by `owner_pubkey_z32`.
+///
+/// Format: `paykit:v0:push_token:{path}:{owner}`
+///
+/// Binding the owner stops a token from being republished under another
+/// identity's hint.
+pub fn push_token_aad(owner_pubkey_z32: &str) -> String {
+    build_aad(PURPOSE_PUSH_TOKEN, push_hint_path(), owner_pubkey_z32)
+}
+
+/// Fetch a user's push hint, if it published one.
+pub async fn fetch_push_hint<R>(reader: &R, owner: &PublicKey) -> Result<Option<PushHintRecord>>
+where
+    R: UnauthenticatedTransportRead,
+{
+    match reader.get(owner, push_hint_path()).await? {
+        Some(json) => Ok(Some(parse_document(
+            "push_hint",
+            json.as_bytes(),
+            MAX_DOCUMENT_BYTES,
+        )?)),
+        None => Ok(None),
+    }
+}
+
+/// Publish this user's push hint, replacing any earlier one.
+pub async fn publish_push_hint<S>(client: &S, record: &PushHintRecord) -> Result<()>
+where
+    S: AuthenticatedTransport,
+{
+    record.validate()?;
+    client
+        .put(push_hint_path(), &serde_json::to_string(record)?)
+        .await
+}
+
+/// Remove this user's push hint; senders fall back to the user polling.
+pub async fn clear_push_hint<S>(client: &S) -> Result<()>
+where
+    S: AuthenticatedTransport,
+{
+    client.delete(push_hint_path()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record() -> PushHintRecord {
+        PushHintRecord {
+            relay_url: "https://push.example.com/wake".into(),
+            relay_pubkey: "ab".repeat(32),
+            sealed_token: "sealed".into(),
+            updated_at: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn push_hint_validation() {
+        record().validate().unwrap();
+        let mut hint = record();
+        hint.relay_url = "http://push.example.com".into();
+        assert!(hint.validate().is_err());
+        let mut hint = record();
+        hint.relay_pubkey = "zz".repeat(32);
+        assert!(hint.validate().is_err());
+        let mut hint = record();
+        hint.sealed_token.clear();
+        assert!(hint.validate().is_err());
+    }
+
+    #[test]
+    fn push_registration_format() {
+        let registration = PushRegistration {
+            platform: PushPlatform::Apns,
+            token: "device-token".into(),
+            app_id: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&registration).unwrap(),
+            r#"{"platform":"apns","token":"device-token"}"#
+        );
+        assert_eq!(
+            push_token_aad("owner"),
+            "paykit:v0:push_token:/pub/paykit.app/v0/push:owner"
+        );
+    }
+}
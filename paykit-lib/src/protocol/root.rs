@@ -0,0 +1,258 @@
+//! Configurable directory roots for white-label deployments.
+//!
+//! Every path builder in this module produces paths under the canonical
+//! root, `/pub/paykit.app/v0`. A deployment that wants its own namespace
+//! (say `/pub/acmepay.app/v0`) does not build different paths: it gives its
+//! transports a [`PathRootConfig`], and they move each canonical path into
+//! the configured root with [`PathRoot::rebase`] on the way to storage.
+//!
+//! Reads also try the config's fallback roots, in order, when nothing is
+//! found under the primary root, so a white-label client still discovers
+//! payees that publish under `paykit.app` (and the reverse). Writes only
+//! ever go to the primary root.
+//!
+//! AAD strings are always built from the canonical path, so an object
+//! sealed by a client on one root opens on a client using another.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::paths::PAYKIT_V0_PREFIX;
+use crate::{PaykitError, Result};
+
+/// Application name of the canonical Paykit root.
+pub const DEFAULT_APP_ROOT: &str = "paykit.app";
+
+/// The application directory Paykit data lives under, e.g. `paykit.app`.
+///
+/// The name must be 1 to 63 lowercase ASCII letters, digits, `.` or `-`,
+/// not starting or ending with `.`, so it is always a single path segment.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PathRoot(String);
+
+impl PathRoot {
+    /// Validate and wrap an application name.
+    pub fn new(app: impl Into<String>) -> Result<Self> {
+        let app = app.into();
+        let valid = !app.is_empty()
+            && app.len() <= 63
+            && !app.starts_with('.')
+            && !app.ends_with('.')
+            && !app.contains("..")
+            && app
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'.' || b == b'-');
+        if !valid {
+            return Err(PaykitError::invalid_data(
+                "path_root",
+                "expected 1-63 lowercase letters, digits, '.' or '-'",
+            ));
+        }
+        Ok(Self(app))
+    }
+
+    /// The canonical `paykit.app` root.
+    pub fn paykit() -> Self {
+        Self(DEFAULT_APP_ROOT.to_string())
+    }
+
+    /// The application name, e.g. `acmepay.app`.
+    pub fn app(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether this is the canonical `paykit.app` root.
+    pub fn is_default(&self) -> bool {
+        self.0 == DEFAULT_APP_ROOT
+    }
+
+    /// The v0 prefix under this root, e.g. `/pub/acmepay.app/v0`.
+    pub fn prefix(&self) -> String {
+        format!("/pub/{}/v0", self.0)
+    }
+
+    /// Move a canonical Paykit path into this root.
+    ///
+    /// Paths outside `/pub/paykit.app/v0` (such as follows under
+    /// `/pub/pubky.app`) are returned unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use paykit_lib::protocol::{noise_endpoint_path, PathRoot};
+    ///
+    /// let root = PathRoot::new("acmepay.app").unwrap();
+    /// assert_eq!(root.rebase(noise_endpoint_path()), "/pub/acmepay.app/v0/noise");
+    /// assert_eq!(root.rebase("/pub/pubky.app/follows/"), "/pub/pubky.app/follows/");
+    /// ```
+    pub fn rebase(&self, path: &str) -> String {
+        match path.strip_prefix(PAYKIT_V0_PREFIX) {
+            Some(rest) if !self.is_default() && (rest.is_empty() || rest.starts_with('/')) => {
+                format!("{}{}", self.prefix(), rest)
+            }
+            _ => path.to_string(),
+        }
+    }
+}
+
+impl Default for PathRoot {
+    fn default() -> Self {
+        Self::paykit()
+    }
+}
+
+impl fmt::Display for PathRoot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::str::FromStr for PathRoot {
+    type Err = PaykitError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::new(s)
+    }
+}
+
+impl TryFrom<String> for PathRoot {
+    type Error = PaykitError;
+
+    fn try_from(app: String) -> Result<Self> {
+        Self::new(app)
+    }
+}
+
+impl From<PathRoot> for String {
+    fn from(root: PathRoot) -> Self {
+        root.0
+    }
+}
+
+/// Which root a client writes to and which roots it reads from.
+///
+/// The default config writes and reads only `paykit.app`, which is the
+/// behaviour of clients that predate configurable roots.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathRootConfig {
+    /// Root all writes go to, and the first one read.
+    #[serde(default)]
+    pub root: PathRoot,
+    /// Roots read, in order, when nothing is found under `root`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallbacks: Vec<PathRoot>,
+}
+
+impl PathRootConfig {
+    /// Read and write only `root`.
+    pub fn new(root: PathRoot) -> Self {
+        Self {
+            root,
+            fallbacks: Vec::new(),
+        }
+    }
+
+    /// Publish under `root`, and still discover payees on `paykit.app`.
+    pub fn white_label(root: PathRoot) -> Self {
+        Self::new(root).with_fallback(PathRoot::paykit())
+    }
+
+    /// Also read `root` when nothing is found under the earlier roots.
+    pub fn with_fallback(mut self, root: PathRoot) -> Self {
+        self.fallbacks.push(root);
+        self
+    }
+
+    /// Roots to read, primary first, without repeats.
+    pub fn read_roots(&self) -> Vec<&PathRoot> {
+        let mut roots: Vec<&PathRoot> = Vec::with_capacity(1 + self.fallbacks.len());
+        for root in std::iter::once(&self.root).chain(&self.fallbacks) {
+            if !roots.contains(&root) {
+                roots.push(root);
+            }
+        }
+        roots
+    }
+
+    /// Move a canonical path into the primary root.
+    pub fn rebase(&self, path: &str) -> String {
+        self.root.rebase(path)
+    }
+
+    /// A canonical path moved into each root to read, primary first.
+    pub fn read_paths(&self, path: &str) -> Vec<String> {
+        let mut paths: Vec<String> = Vec::new();
+        for root in self.read_roots() {
+            let rebased = root.rebase(path);
+            if !paths.contains(&rebased) {
+                paths.push(rebased);
+            }
+        }
+        paths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{payment_link_path, subscription_proposals_dir};
+
+    #[test]
+    fn test_rebase_moves_only_paykit_paths() {
+        let root = PathRoot::new("acmepay.app").unwrap();
+        assert_eq!(
+            root.rebase(&payment_link_path("coffee").unwrap()),
+            "/pub/acmepay.app/v0/links/coffee"
+        );
+        assert_eq!(root.rebase(PAYKIT_V0_PREFIX), "/pub/acmepay.app/v0");
+        // A sibling directory sharing the prefix text is not a Paykit path
+        assert_eq!(
+            root.rebase("/pub/paykit.app/v0x/foo"),
+            "/pub/paykit.app/v0x/foo"
+        );
+
+        let dir =
+            subscription_proposals_dir("8pinxxgqs41n4aididenw5apqp1urfmzdztr8jt4abrkdn435ewo")
+                .unwrap();
+        assert_eq!(PathRoot::paykit().rebase(&dir), dir);
+    }
+
+    #[test]
+    fn test_root_names_are_single_segments() {
+        for bad in [
+            "",
+            "Acme.app",
+            "acme/app",
+            "..",
+            ".acme",
+            "acme..app",
+            "acme app",
+        ] {
+            assert!(PathRoot::new(bad).is_err(), "{bad}");
+        }
+        assert!("acme-pay.app".parse::<PathRoot>().is_ok());
+        assert!(serde_json::from_str::<PathRoot>("\"../etc\"").is_err());
+    }
+
+    #[test]
+    fn test_white_label_reads_fall_back_to_paykit() {
+        let config = PathRootConfig::white_label(PathRoot::new("acmepay.app").unwrap());
+        assert_eq!(
+            config.read_paths("/pub/paykit.app/v0/noise"),
+            vec!["/pub/acmepay.app/v0/noise", "/pub/paykit.app/v0/noise"]
+        );
+        assert_eq!(
+            config.rebase("/pub/paykit.app/v0/noise"),
+            "/pub/acmepay.app/v0/noise"
+        );
+
+        // The default config reads one root, and the fallback never repeats it
+        let config = PathRootConfig::default().with_fallback(PathRoot::paykit());
+        assert_eq!(config.read_roots().len(), 1);
+
+        let json = serde_json::to_string(&PathRootConfig::default()).unwrap();
+        assert_eq!(json, r#"{"root":"paykit.app"}"#);
+    }
+}
@@ -0,0 +1,299 @@
+//! Published routing hints.
+//!
+//! A payee can publish a routing hints document at [`routing_hints_path`]
+//! next to its endpoints, listing the order in which it would like its
+//! methods tried and what each is expected to cost and take. Endpoints are
+//! not repeated here: they stay at their own method paths, and hints for
+//! methods the payee does not publish are simply ignored by payers.
+//!
+//! Payers fetch the document with [`fetch_routing_hints`] and hand it to
+//! [`PaymentMethodSelector::with_payee_hints`]. Hints are advice, merged with
+//! the payer's own preferences according to its
+//! [`PayeeHintPolicy`](crate::selection::PayeeHintPolicy); the payer always
+//! has the last word.
+//!
+//! Like the payee status, hints carry a TTL so that stale cost estimates
+//! stop influencing payers once the payee stops refreshing them.
+//!
+//! [`PaymentMethodSelector::with_payee_hints`]: crate::selection::PaymentMethodSelector::with_payee_hints
+
+use super::document::{parse_document, MAX_DOCUMENT_BYTES};
+use super::paths::routing_hints_path;
+#[cfg(feature = "routing")]
+use crate::routing::RoutingHint;
+use crate::{
+    AuthenticatedTransport, MethodId, PaykitError, PublicKey, Result, UnauthenticatedTransportRead,
+};
+use serde::{Deserialize, Serialize};
+
+/// Default hints lifetime: one day.
+pub const DEFAULT_HINTS_TTL_SECS: u64 = 24 * 3600;
+
+/// Longest accepted hints lifetime: thirty days.
+pub const MAX_HINTS_TTL_SECS: u64 = 30 * 24 * 3600;
+
+/// Most hints accepted in one document.
+pub const MAX_ROUTING_HINTS: usize = 32;
+
+/// The payee's advice for one method.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayeeRoutingHint {
+    /// The payment method.
+    pub method: MethodId,
+    /// Priority (lower = higher priority).
+    pub priority: u8,
+    /// Estimated cost to the payer in satoshis.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_cost_sats: Option<u64>,
+    /// Estimated confirmation time in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_time_secs: Option<u64>,
+}
+
+impl PayeeRoutingHint {
+    /// Create a hint for `method`.
+    pub fn new(method: MethodId, priority: u8) -> Self {
+        Self {
+            method,
+            priority,
+            estimated_cost_sats: None,
+            estimated_time_secs: None,
+        }
+    }
+
+    /// Set estimated cost.
+    pub fn with_estimated_cost(mut self, cost_sats: u64) -> Self {
+        self.estimated_cost_sats = Some(cost_sats);
+        self
+    }
+
+    /// Set estimated time.
+    pub fn with_estimated_time(mut self, time_secs: u64) -> Self {
+        self.estimated_time_secs = Some(time_secs);
+        self
+    }
+}
+
+#[cfg(feature = "routing")]
+impl From<&RoutingHint> for PayeeRoutingHint {
+    fn from(hint: &RoutingHint) -> Self {
+        Self {
+            method: hint.method.clone(),
+            priority: hint.priority,
+            estimated_cost_sats: hint.estimated_cost_sats,
+            estimated_time_secs: hint.estimated_time_secs,
+        }
+    }
+}
+
+/// The routing hints document stored at [`routing_hints_path`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayeeRoutingHints {
+    /// One hint per method, in any order.
+    #[serde(default)]
+    pub hints: Vec<PayeeRoutingHint>,
+    /// When the hints were published (unix seconds).
+    pub updated_at: i64,
+    /// Seconds after `updated_at` for which the hints are valid.
+    #[serde(default = "default_ttl")]
+    pub ttl_secs: u64,
+}
+
+fn default_ttl() -> u64 {
+    DEFAULT_HINTS_TTL_SECS
+}
+
+impl PayeeRoutingHints {
+    /// An empty hints document published at `now`.
+    pub fn new(now: i64) -> Self {
+        Self {
+            hints: Vec::new(),
+            updated_at: now,
+            ttl_secs: DEFAULT_HINTS_TTL_SECS,
+        }
+    }
+
+    /// Build the document from locally generated routing hints.
+    #[cfg(feature = "routing")]
+    pub fn from_routing_hints(hints: &[RoutingHint], now: i64) -> Self {
+        hints
+            .iter()
+            .fold(Self::new(now), |doc, hint| doc.with_hint(hint.into()))
+    }
+
+    /// Add a hint, replacing any previous hint for the same method.
+    pub fn with_hint(mut self, hint: PayeeRoutingHint) -> Self {
+        self.hints.retain(|h| h.method != hint.method);
+        self.hints.push(hint);
+        self
+    }
+
+    /// Set the lifetime of the hints.
+    pub fn with_ttl(mut self, ttl_secs: u64) -> Self {
+        self.ttl_secs = ttl_secs;
+        self
+    }
+
+    /// The hint for `method`, if any.
+    pub fn hint_for(&self, method: &MethodId) -> Option<&PayeeRoutingHint> {
+        self.hints.iter().find(|h| &h.method == method)
+    }
+
+    /// Methods in the payee's preferred order (ties keep document order).
+    pub fn ordered_methods(&self) -> Vec<MethodId> {
+        let mut hints: Vec<&PayeeRoutingHint> = self.hints.iter().collect();
+        hints.sort_by_key(|h| h.priority);
+        hints.into_iter().map(|h| h.method.clone()).collect()
+    }
+
+    /// Position of `method` in [`ordered_methods`](Self::ordered_methods).
+    pub fn rank_of(&self, method: &MethodId) -> Option<usize> {
+        self.ordered_methods().iter().position(|m| m == method)
+    }
+
+    /// Moment after which the hints no longer apply.
+    pub fn expires_at(&self) -> i64 {
+        self.updated_at
+            .saturating_add(self.ttl_secs.min(i64::MAX as u64) as i64)
+    }
+
+    /// Whether the hints still apply at `now`.
+    pub fn is_fresh(&self, now: i64) -> bool {
+        now <= self.expires_at()
+    }
+
+    /// Check the document is well-formed before publishing.
+    pub fn validate(&self) -> Result<()> {
+        if self.ttl_secs == 0 || self.ttl_secs > MAX_HINTS_TTL_SECS {
+            return Err(PaykitError::invalid_data(
+                "ttl_secs",
+                format!("TTL must be between 1 and {} seconds", MAX_HINTS_TTL_SECS),
+            ));
+        }
+        if self.hints.len() > MAX_ROUTING_HINTS {
+            return Err(PaykitError::invalid_data(
+                "hints",
+                format!("At most {} routing hints are allowed", MAX_ROUTING_HINTS),
+            ));
+        }
+        for (i, hint) in self.hints.iter().enumerate() {
+            hint.method.validate()?;
+            if self.hints[..i].iter().any(|h| h.method == hint.method) {
+                return Err(PaykitError::invalid_data(
+                    "hints",
+                    format!("Duplicate routing hint for {}", hint.method.0),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Fetch a payee's routing hints, ignoring them if they have expired at `now`.
+pub async fn fetch_routing_hints<R>(
+    reader: &R,
+    owner: &PublicKey,
+    now: i64,
+) -> Result<Option<PayeeRoutingHints>>
+where
+    R: UnauthenticatedTransportRead,
+{
+    match reader.get(owner, routing_hints_path()).await? {
+        Some(json) => {
+            let hints: PayeeRoutingHints =
+                parse_document("routing_hints", json.as_bytes(), MAX_DOCUMENT_BYTES)?;
+            Ok(hints.is_fresh(now).then_some(hints))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Publish this payee's routing hints.
+pub async fn publish_routing_hints<S>(client: &S, hints: &PayeeRoutingHints) -> Result<()>
+where
+    S: AuthenticatedTransport,
+{
+    hints.validate()?;
+    client
+        .put(routing_hints_path(), &serde_json::to_string(hints)?)
+        .await
+}
+
+/// Remove this payee's routing hints, leaving selection to payers alone.
+pub async fn clear_routing_hints<S>(client: &S) -> Result<()>
+where
+    S: AuthenticatedTransport,
+{
+    client.delete(routing_hints_path()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "routing")]
+    use crate::EndpointData;
+
+    #[test]
+    fn hints_order_and_ttl() {
+        let lightning = MethodId::lightning();
+        let onchain = MethodId::onchain();
+        let hints = PayeeRoutingHints::new(1_000)
+            .with_hint(PayeeRoutingHint::new(onchain.clone(), 2).with_estimated_time(3_600))
+            .with_hint(PayeeRoutingHint::new(lightning.clone(), 1).with_estimated_cost(2))
+            .with_ttl(500);
+        hints.validate().unwrap();
+
+        assert_eq!(
+            hints.ordered_methods(),
+            vec![lightning.clone(), onchain.clone()]
+        );
+        assert_eq!(hints.rank_of(&onchain), Some(1));
+        assert_eq!(
+            hints.hint_for(&lightning).unwrap().estimated_cost_sats,
+            Some(2)
+        );
+        assert!(hints.is_fresh(1_500));
+        assert!(!hints.is_fresh(1_501));
+    }
+
+    #[test]
+    fn hints_validation() {
+        assert!(PayeeRoutingHints::new(0).with_ttl(0).validate().is_err());
+
+        let mut duplicate =
+            PayeeRoutingHints::new(0).with_hint(PayeeRoutingHint::new(MethodId::lightning(), 1));
+        duplicate
+            .hints
+            .push(PayeeRoutingHint::new(MethodId::lightning(), 2));
+        assert!(duplicate.validate().is_err());
+
+        let too_many = (0..=MAX_ROUTING_HINTS).fold(PayeeRoutingHints::new(0), |doc, i| {
+            doc.with_hint(PayeeRoutingHint::new(
+                MethodId::new(format!("org.example.m{}", i)),
+                0,
+            ))
+        });
+        assert!(too_many.validate().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "routing")]
+    fn hints_from_local_routing_hints() {
+        let local =
+            vec![
+                RoutingHint::new(MethodId::lightning(), EndpointData("lnbc1...".into()), 1)
+                    .with_estimated_time(5),
+            ];
+        let hints = PayeeRoutingHints::from_routing_hints(&local, 100);
+        assert_eq!(hints.hints.len(), 1);
+        assert_eq!(hints.hints[0].estimated_time_secs, Some(5));
+
+        let json = serde_json::to_string(&hints).unwrap();
+        assert!(!json.contains("lnbc1"));
+        let parsed: PayeeRoutingHints = serde_json::from_str(
+            r#"{"hints":[{"method":"lightning","priority":0}],"updated_at":1}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.ttl_secs, DEFAULT_HINTS_TTL_SECS);
+    }
+}
@@ -0,0 +1,674 @@
+//! Homeserver storage quotas for directory writes.
+//!
+//! Homeservers refuse writes once an account is over its storage quota, and
+//! the write that fails is usually not the one that used the space. The
+//! [`QuotaManager`] keeps a local tally of the bytes written under each
+//! configured path prefix so the app can see it coming:
+//!
+//! - writes that would take a prefix past its limit fail early with
+//!   [`PaykitError::QuotaExceeded`],
+//! - a [`QuotaEvent::ThresholdCrossed`] is emitted when usage first reaches
+//!   each warning threshold,
+//! - documents written with an expiry (payment requests, status notices) can
+//!   be pruned once expired, automatically when a write would not fit.
+//!
+//! Wrap the app's transport in a [`QuotaTransport`] to keep the tally
+//! current. Files written before the manager existed are unknown to it; add
+//! them with [`QuotaTransport::scan`] or [`QuotaManager::record_existing`].
+//!
+//! # Example
+//!
+//! ```
+//! use paykit_lib::quota::{PrefixQuota, QuotaConfig, QuotaManager};
+//!
+//! let manager = QuotaManager::new(QuotaConfig {
+//!     prefixes: vec![PrefixQuota::new("/pub/paykit.app/v0/requests", 1_000)],
+//!     ..Default::default()
+//! });
+//!
+//! manager.record_write("/pub/paykit.app/v0/requests/alice/r1", 600, Some(100));
+//! assert!(manager.check_write("/pub/paykit.app/v0/requests/alice/r2", 600).is_err());
+//!
+//! // Once r1 has expired it can be pruned to make room
+//! assert_eq!(manager.expired(200), vec!["/pub/paykit.app/v0/requests/alice/r1"]);
+//! ```
+
+use crate::protocol::PAYKIT_V0_PREFIX;
+use crate::UnauthenticatedTransportRead;
+use crate::{AuthenticatedTransport, EndpointData, MethodId, PaykitError, PublicKey, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Callback type for quota events.
+pub type QuotaCallback = Arc<dyn Fn(&QuotaEvent) + Send + Sync>;
+
+/// A storage limit for the files under one path prefix.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrefixQuota {
+    /// Path prefix, e.g. `/pub/paykit.app/v0/requests`.
+    pub prefix: String,
+    /// Maximum bytes under the prefix.
+    pub limit_bytes: u64,
+}
+
+impl PrefixQuota {
+    /// Create a quota for `prefix`.
+    pub fn new(prefix: impl Into<String>, limit_bytes: u64) -> Self {
+        Self {
+            prefix: prefix.into(),
+            limit_bytes,
+        }
+    }
+}
+
+/// Quota limits, warning thresholds and pruning behavior.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaConfig {
+    /// Limits per prefix. Prefixes may nest; a write must fit in every
+    /// prefix containing it.
+    pub prefixes: Vec<PrefixQuota>,
+    /// Usage percentages that emit a warning when first reached.
+    pub warn_at_percent: Vec<u8>,
+    /// Delete expired documents when a write would otherwise not fit.
+    pub auto_prune: bool,
+}
+
+impl Default for QuotaConfig {
+    /// A 10 MiB limit on everything under the Paykit prefix, warnings at 80%
+    /// and 95%, and auto-pruning.
+    fn default() -> Self {
+        Self {
+            prefixes: vec![PrefixQuota::new(PAYKIT_V0_PREFIX, 10 * 1024 * 1024)],
+            warn_at_percent: vec![80, 95],
+            auto_prune: true,
+        }
+    }
+}
+
+/// Something the quota manager noticed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QuotaEvent {
+    /// Usage under `prefix` reached `percent` of its limit.
+    ThresholdCrossed {
+        /// The prefix.
+        prefix: String,
+        /// The threshold reached.
+        percent: u8,
+        /// Bytes used.
+        used: u64,
+        /// The prefix's limit.
+        limit: u64,
+    },
+    /// A write was refused because it would not fit.
+    WriteRefused {
+        /// The path written.
+        path: String,
+        /// The prefix that would overflow.
+        prefix: String,
+        /// Bytes that would be used.
+        used: u64,
+        /// The prefix's limit.
+        limit: u64,
+    },
+    /// An expired document was deleted.
+    Pruned {
+        /// The deleted path.
+        path: String,
+        /// Bytes freed.
+        bytes: u64,
+    },
+}
+
+/// Usage of one prefix.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrefixUsage {
+    /// The prefix.
+    pub prefix: String,
+    /// Bytes used by tracked files under the prefix.
+    pub used_bytes: u64,
+    /// The prefix's limit.
+    pub limit_bytes: u64,
+    /// Tracked files under the prefix.
+    pub files: usize,
+    /// Tracked files under the prefix that have an expiry.
+    pub expiring_files: usize,
+}
+
+impl PrefixUsage {
+    /// Usage as a percentage of the limit (may exceed 100).
+    pub fn percent(&self) -> u8 {
+        percent_of(self.used_bytes, self.limit_bytes)
+    }
+}
+
+/// Usage of every configured prefix.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaReport {
+    /// One entry per configured prefix, in configuration order.
+    pub prefixes: Vec<PrefixUsage>,
+    /// Bytes used by all tracked files.
+    pub total_bytes: u64,
+    /// All tracked files.
+    pub total_files: usize,
+}
+
+impl QuotaReport {
+    /// Prefixes at or over their limit.
+    pub fn full(&self) -> Vec<&PrefixUsage> {
+        self.prefixes
+            .iter()
+            .filter(|p| p.used_bytes >= p.limit_bytes)
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+struct TrackedFile {
+    bytes: u64,
+    expires_at: Option<i64>,
+}
+
+#[derive(Default)]
+struct QuotaState {
+    files: HashMap<String, TrackedFile>,
+    /// Highest threshold already reported per prefix.
+    warned: HashMap<String, u8>,
+}
+
+impl QuotaState {
+    fn used(&self, prefix: &str) -> u64 {
+        self.files
+            .iter()
+            .filter(|(path, _)| under_prefix(path, prefix))
+            .map(|(_, file)| file.bytes)
+            .sum()
+    }
+}
+
+/// Local tally of directory storage per path prefix.
+pub struct QuotaManager {
+    config: RwLock<QuotaConfig>,
+    state: Mutex<QuotaState>,
+    callbacks: RwLock<Vec<QuotaCallback>>,
+}
+
+impl Default for QuotaManager {
+    fn default() -> Self {
+        Self::new(QuotaConfig::default())
+    }
+}
+
+impl QuotaManager {
+    /// Create a manager with the given limits.
+    pub fn new(config: QuotaConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            state: Mutex::new(QuotaState::default()),
+            callbacks: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Get the current configuration.
+    pub fn config(&self) -> QuotaConfig {
+        self.config
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Replace the configuration.
+    ///
+    /// Warnings already emitted are forgotten, so thresholds still exceeded
+    /// are reported again on the next write.
+    pub fn set_config(&self, config: QuotaConfig) {
+        *self.config.write().unwrap_or_else(|e| e.into_inner()) = config;
+        self.lock_state().warned.clear();
+    }
+
+    /// Register a callback for quota events.
+    ///
+    /// If the lock is poisoned, the callback is silently ignored.
+    pub fn on_event(&self, callback: QuotaCallback) {
+        if let Ok(mut callbacks) = self.callbacks.write() {
+            callbacks.push(callback);
+        }
+    }
+
+    /// Check whether writing `bytes` to `path` fits every prefix containing it.
+    ///
+    /// The file's current size, if tracked, is replaced rather than added.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PaykitError::QuotaExceeded`] for the first prefix that
+    /// would overflow.
+    pub fn check_write(&self, path: &str, bytes: u64) -> Result<()> {
+        let config = self.config();
+        let state = self.lock_state();
+        let previous = state.files.get(path).map_or(0, |f| f.bytes);
+        for quota in config
+            .prefixes
+            .iter()
+            .filter(|q| under_prefix(path, &q.prefix))
+        {
+            let used = state.used(&quota.prefix) - previous + bytes;
+            if used > quota.limit_bytes {
+                return Err(PaykitError::QuotaExceeded {
+                    used,
+                    limit: quota.limit_bytes,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Record a successful write of `bytes` to `path`.
+    ///
+    /// `expires_at` (unix seconds) marks the document as prunable after
+    /// that time.
+    pub fn record_write(&self, path: &str, bytes: u64, expires_at: Option<i64>) {
+        self.lock_state()
+            .files
+            .insert(path.to_string(), TrackedFile { bytes, expires_at });
+        self.warn_thresholds(path);
+    }
+
+    /// Record a file that already existed, without emitting warnings.
+    pub fn record_existing(&self, path: &str, bytes: u64, expires_at: Option<i64>) {
+        self.lock_state()
+            .files
+            .insert(path.to_string(), TrackedFile { bytes, expires_at });
+    }
+
+    /// Record a successful delete of `path`.
+    pub fn record_delete(&self, path: &str) {
+        let config = self.config();
+        let mut state = self.lock_state();
+        state.files.remove(path);
+
+        // Re-arm warnings for prefixes that dropped below their thresholds
+        for quota in &config.prefixes {
+            let percent = percent_of(state.used(&quota.prefix), quota.limit_bytes);
+            if let Some(warned) = state.warned.get_mut(&quota.prefix) {
+                *warned = config
+                    .warn_at_percent
+                    .iter()
+                    .copied()
+                    .filter(|t| *t <= percent)
+                    .max()
+                    .unwrap_or(0);
+            }
+        }
+    }
+
+    /// Bytes used by tracked files under `prefix`.
+    pub fn usage(&self, prefix: &str) -> u64 {
+        self.lock_state().used(prefix)
+    }
+
+    /// Tracked documents under `prefix` that expired before `now`, oldest
+    /// expiry first.
+    pub fn expired_under(&self, prefix: &str, now: i64) -> Vec<String> {
+        let state = self.lock_state();
+        let mut expired: Vec<_> = state
+            .files
+            .iter()
+            .filter(|(path, _)| under_prefix(path, prefix))
+            .filter_map(|(path, file)| {
+                let at = file.expires_at.filter(|at| *at < now)?;
+                Some((at, path.clone()))
+            })
+            .collect();
+        expired.sort();
+        expired.into_iter().map(|(_, path)| path).collect()
+    }
+
+    /// All tracked documents that expired before `now`, oldest expiry first.
+    pub fn expired(&self, now: i64) -> Vec<String> {
+        self.expired_under("", now)
+    }
+
+    /// Usage of every configured prefix.
+    pub fn report(&self) -> QuotaReport {
+        let config = self.config();
+        let state = self.lock_state();
+        let prefixes = config
+            .prefixes
+            .iter()
+            .map(|quota| {
+                let files: Vec<_> = state
+                    .files
+                    .iter()
+                    .filter(|(path, _)| under_prefix(path, &quota.prefix))
+                    .map(|(_, file)| file)
+                    .collect();
+                PrefixUsage {
+                    prefix: quota.prefix.clone(),
+                    used_bytes: files.iter().map(|f| f.bytes).sum(),
+                    limit_bytes: quota.limit_bytes,
+                    files: files.len(),
+                    expiring_files: files.iter().filter(|f| f.expires_at.is_some()).count(),
+                }
+            })
+            .collect();
+        QuotaReport {
+            prefixes,
+            total_bytes: state.files.values().map(|f| f.bytes).sum(),
+            total_files: state.files.len(),
+        }
+    }
+
+    fn warn_thresholds(&self, path: &str) {
+        let config = self.config();
+        let mut events = Vec::new();
+        {
+            let mut state = self.lock_state();
+            for quota in config
+                .prefixes
+                .iter()
+                .filter(|q| under_prefix(path, &q.prefix))
+            {
+                let used = state.used(&quota.prefix);
+                let percent = percent_of(used, quota.limit_bytes);
+                let warned = state.warned.get(&quota.prefix).copied().unwrap_or(0);
+                let Some(threshold) = config
+                    .warn_at_percent
+                    .iter()
+                    .copied()
+                    .filter(|t| *t > warned && *t <= percent)
+                    .max()
+                else {
+                    continue;
+                };
+                state.warned.insert(quota.prefix.clone(), threshold);
+                events.push(QuotaEvent::ThresholdCrossed {
+                    prefix: quota.prefix.clone(),
+                    percent: threshold,
+                    used,
+                    limit: quota.limit_bytes,
+                });
+            }
+        }
+        for event in &events {
+            self.emit(event);
+        }
+    }
+
+    /// The configured prefix a write of `bytes` to `path` would overflow.
+    fn overflowing_prefix(&self, path: &str, bytes: u64) -> Option<(String, u64, u64)> {
+        let config = self.config();
+        let state = self.lock_state();
+        let previous = state.files.get(path).map_or(0, |f| f.bytes);
+        config
+            .prefixes
+            .iter()
+            .filter(|q| under_prefix(path, &q.prefix))
+            .find_map(|quota| {
+                let used = state.used(&quota.prefix) - previous + bytes;
+                (used > quota.limit_bytes).then(|| (quota.prefix.clone(), used, quota.limit_bytes))
+            })
+    }
+
+    fn emit(&self, event: &QuotaEvent) {
+        if let Ok(callbacks) = self.callbacks.read() {
+            for callback in callbacks.iter() {
+                callback(event);
+            }
+        }
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, QuotaState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// An [`AuthenticatedTransport`] that keeps a [`QuotaManager`] current.
+///
+/// Writes that would not fit are refused before reaching the homeserver,
+/// after pruning expired documents if [`QuotaConfig::auto_prune`] is set.
+pub struct QuotaTransport<T> {
+    inner: T,
+    quota: Arc<QuotaManager>,
+}
+
+impl<T: AuthenticatedTransport> QuotaTransport<T> {
+    /// Wrap `inner`, tallying its writes in `quota`.
+    pub fn new(inner: T, quota: Arc<QuotaManager>) -> Self {
+        Self { inner, quota }
+    }
+
+    /// The quota manager.
+    pub fn quota(&self) -> &Arc<QuotaManager> {
+        &self.quota
+    }
+
+    /// The wrapped transport.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Write a document that may be pruned after `expires_at` (unix seconds).
+    pub async fn put_expiring(&self, path: &str, content: &str, expires_at: i64) -> Result<()> {
+        self.put_tracked(path, content, Some(expires_at)).await
+    }
+
+    /// Delete the documents that expired before `now`, returning their paths.
+    ///
+    /// A document whose delete fails stays tracked and is retried on the next
+    /// call.
+    pub async fn prune_expired(&self, now: i64) -> Result<Vec<String>> {
+        self.prune_under("", now).await
+    }
+
+    /// Add the files directly under `dir` in `owner`'s storage to the tally.
+    ///
+    /// Subdirectories (names ending in `/`) are not descended into. Returns
+    /// the number of files added.
+    pub async fn scan<R>(&self, reader: &R, owner: &PublicKey, dir: &str) -> Result<usize>
+    where
+        R: UnauthenticatedTransportRead,
+    {
+        let dir = dir.trim_end_matches('/');
+        let mut added = 0;
+        for name in reader.list_directory(owner, dir).await? {
+            if name.ends_with('/') {
+                continue;
+            }
+            let path = format!("{}/{}", dir, name);
+            if let Some(content) = reader.get(owner, &path).await? {
+                self.quota
+                    .record_existing(&path, content.len() as u64, None);
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
+    async fn prune_under(&self, prefix: &str, now: i64) -> Result<Vec<String>> {
+        let mut pruned = Vec::new();
+        for path in self.quota.expired_under(prefix, now) {
+            let bytes = self
+                .quota
+                .lock_state()
+                .files
+                .get(&path)
+                .map_or(0, |f| f.bytes);
+            if self.inner.delete(&path).await.is_ok() {
+                self.quota.record_delete(&path);
+                self.quota.emit(&QuotaEvent::Pruned {
+                    path: path.clone(),
+                    bytes,
+                });
+                pruned.push(path);
+            }
+        }
+        Ok(pruned)
+    }
+
+    async fn put_tracked(&self, path: &str, content: &str, expires_at: Option<i64>) -> Result<()> {
+        let bytes = content.len() as u64;
+        if let Some((prefix, _, _)) = self.quota.overflowing_prefix(path, bytes) {
+            if self.quota.config().auto_prune {
+                self.prune_under(&prefix, current_timestamp()).await?;
+            }
+            if let Some((prefix, used, limit)) = self.quota.overflowing_prefix(path, bytes) {
+                self.quota.emit(&QuotaEvent::WriteRefused {
+                    path: path.to_string(),
+                    prefix,
+                    used,
+                    limit,
+                });
+                return Err(PaykitError::QuotaExceeded { used, limit });
+            }
+        }
+
+        self.inner.put(path, content).await?;
+        self.quota.record_write(path, bytes, expires_at);
+        Ok(())
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl<T: AuthenticatedTransport + Send + Sync> AuthenticatedTransport for QuotaTransport<T> {
+    async fn upsert_payment_endpoint(&self, method: &MethodId, data: &EndpointData) -> Result<()> {
+        let path = endpoint_path(method);
+        let bytes = data.0.len() as u64;
+        self.quota.check_write(&path, bytes)?;
+        self.inner.upsert_payment_endpoint(method, data).await?;
+        self.quota.record_write(&path, bytes, None);
+        Ok(())
+    }
+
+    async fn remove_payment_endpoint(&self, method: &MethodId) -> Result<()> {
+        self.inner.remove_payment_endpoint(method).await?;
+        self.quota.record_delete(&endpoint_path(method));
+        Ok(())
+    }
+
+    async fn put(&self, path: &str, content: &str) -> Result<()> {
+        self.put_tracked(path, content, None).await
+    }
+
+    async fn get(&self, path: &str) -> Result<Option<String>> {
+        self.inner.get(path).await
+    }
+
+    async fn delete(&self, path: &str) -> Result<()> {
+        self.inner.delete(path).await?;
+        self.quota.record_delete(path);
+        Ok(())
+    }
+}
+
+fn endpoint_path(method: &MethodId) -> String {
+    format!("{}/{}", PAYKIT_V0_PREFIX, method.0)
+}
+
+/// Whether `path` is `prefix` itself or lies under it.
+fn under_prefix(path: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches('/');
+    prefix.is_empty()
+        || path == prefix
+        || path
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+fn percent_of(used: u64, limit: u64) -> u8 {
+    if limit == 0 {
+        return u8::MAX;
+    }
+    (used.saturating_mul(100) / limit).min(u8::MAX as u64) as u8
+}
+
+fn current_timestamp() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REQUESTS: &str = "/pub/paykit.app/v0/requests";
+
+    fn manager(limit: u64) -> QuotaManager {
+        QuotaManager::new(QuotaConfig {
+            prefixes: vec![PrefixQuota::new(REQUESTS, limit)],
+            warn_at_percent: vec![50, 90],
+            auto_prune: true,
+        })
+    }
+
+    #[test]
+    fn test_check_write_replaces_existing_size() {
+        let quota = manager(100);
+        quota.record_write(&format!("{}/a", REQUESTS), 80, None);
+
+        assert!(quota.check_write(&format!("{}/b", REQUESTS), 30).is_err());
+        // Rewriting the same file only counts the difference
+        assert!(quota.check_write(&format!("{}/a", REQUESTS), 100).is_ok());
+        // Other prefixes are unlimited
+        assert!(quota
+            .check_write("/pub/paykit.app/v0/status", 1_000)
+            .is_ok());
+        // A sibling that merely shares the text prefix is not under it
+        assert!(quota
+            .check_write("/pub/paykit.app/v0/requests-old/x", 1_000)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_threshold_warnings_fire_once() {
+        let quota = manager(100);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        quota.on_event(Arc::new(move |event| {
+            sink.lock().unwrap().push(event.clone())
+        }));
+
+        quota.record_write(&format!("{}/a", REQUESTS), 40, None);
+        quota.record_write(&format!("{}/b", REQUESTS), 20, None);
+        quota.record_write(&format!("{}/c", REQUESTS), 5, None);
+        quota.record_write(&format!("{}/d", REQUESTS), 30, None);
+
+        let percents: Vec<u8> = events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|e| match e {
+                QuotaEvent::ThresholdCrossed { percent, .. } => Some(*percent),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(percents, vec![50, 90]);
+
+        let report = quota.report();
+        assert_eq!(report.prefixes[0].used_bytes, 95);
+        assert_eq!(report.prefixes[0].percent(), 95);
+        assert!(report.full().is_empty());
+    }
+
+    #[test]
+    fn test_expired_documents() {
+        let quota = manager(100);
+        quota.record_write(&format!("{}/late", REQUESTS), 10, Some(300));
+        quota.record_write(&format!("{}/early", REQUESTS), 10, Some(100));
+        quota.record_write(&format!("{}/forever", REQUESTS), 10, None);
+
+        assert_eq!(
+            quota.expired(400),
+            vec![format!("{}/early", REQUESTS), format!("{}/late", REQUESTS)]
+        );
+        assert_eq!(quota.expired(200).len(), 1);
+
+        quota.record_delete(&format!("{}/early", REQUESTS));
+        assert_eq!(quota.usage(REQUESTS), 20);
+        assert_eq!(quota.report().prefixes[0].expiring_files, 1);
+    }
+}
@@ -0,0 +1,516 @@
+//! Stuck On-Chain Payment Rescue
+//!
+//! This module detects on-chain payments that are stuck in the mempool and
+//! helps get them confirmed through the wallet's fee bump support
+//! ([`BitcoinExecutor::fee_bump`]).
+//!
+//! A payment is considered stuck when it is still unconfirmed and either:
+//! - its fee rate has fallen well below what the market currently requires, or
+//! - it has been waiting longer than the configured maximum since it was
+//!   broadcast or last bumped.
+//!
+//! Every bump is recorded on the [`TrackedTransaction`], so the full proof
+//! chain (original txid followed by each replacement or child) can be attached
+//! to the receipt.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use paykit_lib::rescue::{PaymentRescuer, RescuePolicy, TrackedTransaction};
+//!
+//! let rescuer = PaymentRescuer::new(wallet, RescuePolicy::default());
+//! let mut tracked = TrackedTransaction::new(txid, "bc1q...", 50_000, broadcast_at);
+//!
+//! // Called periodically by the payment watcher
+//! if let Some(bump) = rescuer.rescue(&mut tracked).await? {
+//!     println!("bumped via {:?}, now tracking {}", bump.strategy, tracked.current_txid());
+//!     receipt_metadata["fee_bumps"] = tracked.proof_chain_metadata();
+//! }
+//! ```
+
+use crate::methods::{BitcoinExecutor, FeeBumpResult, FeeBumpStrategy, PaymentProof};
+use crate::{PaykitError, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Virtual size the executors assume when quoting fee estimates.
+const DEFAULT_TX_VSIZE: f64 = 140.0;
+
+/// Thresholds for deciding when an unconfirmed payment needs help.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RescuePolicy {
+    /// Confirmation target (in blocks) used to estimate the market fee rate.
+    pub target_blocks: u32,
+    /// A payment is stuck when its fee rate is below this fraction of the market rate.
+    pub low_fee_ratio: f64,
+    /// A payment is stuck when unconfirmed for longer than this many seconds
+    /// after its broadcast or its last bump.
+    pub max_unconfirmed_secs: i64,
+    /// Minimum increase over the current fee rate when bumping (sat/vB).
+    pub min_bump_increment: f64,
+    /// Never bump above this fee rate (sat/vB).
+    pub max_fee_rate: f64,
+    /// Strategies to try, in order of preference.
+    pub preferred_strategies: Vec<FeeBumpStrategy>,
+}
+
+impl Default for RescuePolicy {
+    fn default() -> Self {
+        Self {
+            target_blocks: 3,
+            low_fee_ratio: 0.5,
+            max_unconfirmed_secs: 6 * 60 * 60,
+            min_bump_increment: 1.0,
+            max_fee_rate: 500.0,
+            preferred_strategies: vec![FeeBumpStrategy::Rbf, FeeBumpStrategy::Cpfp],
+        }
+    }
+}
+
+/// An on-chain payment being watched for confirmation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TrackedTransaction {
+    /// The txid originally broadcast for the payment.
+    pub original_txid: String,
+    /// Destination address of the payment.
+    pub address: String,
+    /// Amount paid in satoshis.
+    pub amount_sats: u64,
+    /// When the original transaction was broadcast (unix epoch seconds).
+    pub broadcast_at: i64,
+    /// Fee bumps applied so far, oldest first.
+    #[serde(default)]
+    pub bumps: Vec<FeeBumpResult>,
+}
+
+impl TrackedTransaction {
+    /// Start tracking a broadcast payment.
+    pub fn new(
+        original_txid: impl Into<String>,
+        address: impl Into<String>,
+        amount_sats: u64,
+        broadcast_at: i64,
+    ) -> Self {
+        Self {
+            original_txid: original_txid.into(),
+            address: address.into(),
+            amount_sats,
+            broadcast_at,
+            bumps: Vec::new(),
+        }
+    }
+
+    /// The txid that currently carries the payment to the payee.
+    pub fn current_txid(&self) -> &str {
+        self.bumps
+            .iter()
+            .rev()
+            .find(|b| b.strategy == FeeBumpStrategy::Rbf)
+            .map(|b| b.bump_txid.as_str())
+            .unwrap_or(&self.original_txid)
+    }
+
+    /// When the payment was last broadcast or bumped (unix epoch seconds).
+    pub fn last_broadcast_at(&self) -> i64 {
+        self.bumps
+            .last()
+            .map(|b| b.bumped_at)
+            .unwrap_or(self.broadcast_at)
+    }
+
+    /// Effective fee rate of the payment given the current transaction's own
+    /// rate: CPFP children added since then raise it to their package rate.
+    pub fn effective_fee_rate(&self, tx_fee_rate: f64) -> f64 {
+        self.bumps
+            .iter()
+            .rev()
+            .take_while(|b| b.strategy == FeeBumpStrategy::Cpfp)
+            .map(|b| b.fee_rate)
+            .fold(tx_fee_rate, f64::max)
+    }
+
+    /// Proofs for every transaction in the chain, starting with the original.
+    pub fn proof_chain(&self) -> Vec<PaymentProof> {
+        std::iter::once(self.original_txid.as_str())
+            .chain(self.bumps.iter().map(|b| b.bump_txid.as_str()))
+            .map(|txid| PaymentProof::bitcoin_txid(txid, None))
+            .collect()
+    }
+
+    /// Fee bump history formatted for inclusion in receipt metadata.
+    pub fn proof_chain_metadata(&self) -> serde_json::Value {
+        serde_json::json!({
+            "original_txid": self.original_txid,
+            "current_txid": self.current_txid(),
+            "bumps": self.bumps,
+        })
+    }
+
+    fn record(&mut self, bump: FeeBumpResult) {
+        self.bumps.push(bump);
+    }
+}
+
+/// Why a payment was judged stuck.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StuckReason {
+    /// Fee rate is far below the current market rate.
+    LowFeeRate,
+    /// Unconfirmed for longer than the policy allows.
+    TooLongUnconfirmed,
+}
+
+/// Outcome of checking a tracked payment.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RescueAssessment {
+    /// The payment has at least one confirmation.
+    Confirmed {
+        /// Current confirmation count.
+        confirmations: u64,
+    },
+    /// Unconfirmed but expected to confirm without help.
+    Pending {
+        /// Fee rate of the payment (sat/vB).
+        fee_rate: f64,
+        /// Market fee rate for the policy's confirmation target (sat/vB).
+        market_fee_rate: f64,
+    },
+    /// Unconfirmed and needs a fee bump.
+    Stuck {
+        /// Why the payment is considered stuck.
+        reason: StuckReason,
+        /// Fee rate of the payment (sat/vB).
+        fee_rate: f64,
+        /// Fee rate a bump should target (sat/vB).
+        suggested_fee_rate: f64,
+        /// Strategies the wallet can use, in policy order.
+        strategies: Vec<FeeBumpStrategy>,
+    },
+    /// The wallet does not know the transaction (evicted or never broadcast).
+    NotFound,
+}
+
+/// Detects stuck on-chain payments and bumps their fees.
+///
+/// Polling is the caller's responsibility; call [`assess`](Self::assess) or
+/// [`rescue`](Self::rescue) from whatever watcher loop drives payment status.
+pub struct PaymentRescuer {
+    executor: Arc<dyn BitcoinExecutor>,
+    policy: RescuePolicy,
+}
+
+impl PaymentRescuer {
+    /// Create a rescuer backed by a wallet executor.
+    pub fn new(executor: Arc<dyn BitcoinExecutor>, policy: RescuePolicy) -> Self {
+        Self { executor, policy }
+    }
+
+    /// Get the rescue policy.
+    pub fn policy(&self) -> &RescuePolicy {
+        &self.policy
+    }
+
+    /// Check whether a tracked payment is stuck.
+    pub async fn assess(&self, tracked: &TrackedTransaction) -> Result<RescueAssessment> {
+        self.assess_at(tracked, current_timestamp()).await
+    }
+
+    /// Check whether a tracked payment is stuck, as of `now`.
+    pub async fn assess_at(
+        &self,
+        tracked: &TrackedTransaction,
+        now: i64,
+    ) -> Result<RescueAssessment> {
+        let tx = match self
+            .executor
+            .get_transaction(tracked.current_txid())
+            .await?
+        {
+            Some(tx) => tx,
+            None => return Ok(RescueAssessment::NotFound),
+        };
+        if tx.confirmations > 0 {
+            return Ok(RescueAssessment::Confirmed {
+                confirmations: tx.confirmations,
+            });
+        }
+
+        // Wallet estimates are quoted for a typical single-payment transaction
+        let market_fee = self
+            .executor
+            .estimate_fee(
+                &tracked.address,
+                tracked.amount_sats,
+                self.policy.target_blocks,
+            )
+            .await?;
+        let market_fee_rate = market_fee as f64 / DEFAULT_TX_VSIZE;
+
+        // A CPFP bump leaves the parent's own rate unchanged
+        let fee_rate = tracked.effective_fee_rate(tx.fee_rate);
+        let reason = if fee_rate < market_fee_rate * self.policy.low_fee_ratio {
+            Some(StuckReason::LowFeeRate)
+        } else if now - tracked.last_broadcast_at() >= self.policy.max_unconfirmed_secs {
+            Some(StuckReason::TooLongUnconfirmed)
+        } else {
+            None
+        };
+
+        let Some(reason) = reason else {
+            return Ok(RescueAssessment::Pending {
+                fee_rate,
+                market_fee_rate,
+            });
+        };
+
+        let suggested_fee_rate = market_fee_rate
+            .max(fee_rate + self.policy.min_bump_increment)
+            .min(self.policy.max_fee_rate);
+        let supported = self.executor.fee_bump_strategies();
+        let strategies = self
+            .policy
+            .preferred_strategies
+            .iter()
+            .copied()
+            .filter(|s| supported.contains(s))
+            .collect();
+
+        Ok(RescueAssessment::Stuck {
+            reason,
+            fee_rate,
+            suggested_fee_rate,
+            strategies,
+        })
+    }
+
+    /// Bump a payment explicitly and record the result.
+    pub async fn bump(
+        &self,
+        tracked: &mut TrackedTransaction,
+        fee_rate: f64,
+        strategy: FeeBumpStrategy,
+    ) -> Result<FeeBumpResult> {
+        if fee_rate > self.policy.max_fee_rate {
+            return Err(PaykitError::ValidationFailed(format!(
+                "fee rate {} sat/vB exceeds policy maximum of {} sat/vB",
+                fee_rate, self.policy.max_fee_rate
+            )));
+        }
+        let bump = self
+            .executor
+            .fee_bump(tracked.current_txid(), fee_rate, strategy)
+            .await?;
+        tracked.record(bump.clone());
+        Ok(bump)
+    }
+
+    /// Assess a payment and bump it if it is stuck.
+    ///
+    /// Returns `Ok(None)` if no bump was needed. Fails if the payment is stuck
+    /// but the wallet supports none of the policy's strategies.
+    pub async fn rescue(&self, tracked: &mut TrackedTransaction) -> Result<Option<FeeBumpResult>> {
+        match self.assess(tracked).await? {
+            RescueAssessment::Stuck {
+                suggested_fee_rate,
+                strategies,
+                ..
+            } => {
+                let strategy = strategies
+                    .first()
+                    .copied()
+                    .ok_or(PaykitError::Unimplemented(
+                        "wallet supports none of the configured fee bump strategies",
+                    ))?;
+                self.bump(tracked, suggested_fee_rate, strategy)
+                    .await
+                    .map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+fn current_timestamp() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::methods::{BitcoinTxResult, MockBitcoinExecutor};
+    use async_trait::async_trait;
+
+    /// Wallet whose transactions never confirm.
+    struct MempoolWallet {
+        fee_rate: f64,
+        strategies: Vec<FeeBumpStrategy>,
+        inner: MockBitcoinExecutor,
+    }
+
+    impl MempoolWallet {
+        fn new(fee_rate: f64) -> Self {
+            Self {
+                fee_rate,
+                strategies: vec![FeeBumpStrategy::Rbf, FeeBumpStrategy::Cpfp],
+                inner: MockBitcoinExecutor::new(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl BitcoinExecutor for MempoolWallet {
+        async fn send_to_address(
+            &self,
+            address: &str,
+            amount_sats: u64,
+            fee_rate: Option<f64>,
+        ) -> Result<BitcoinTxResult> {
+            self.inner
+                .send_to_address(address, amount_sats, fee_rate)
+                .await
+        }
+
+        async fn estimate_fee(&self, _: &str, _: u64, _: u32) -> Result<u64> {
+            // 10 sat/vB market rate
+            Ok(1400)
+        }
+
+        async fn get_transaction(&self, txid: &str) -> Result<Option<BitcoinTxResult>> {
+            Ok(Some(BitcoinTxResult::new(
+                txid,
+                0,
+                (self.fee_rate * 140.0) as u64,
+                self.fee_rate,
+            )))
+        }
+
+        async fn verify_transaction(&self, _: &str, _: &str, _: u64) -> Result<bool> {
+            Ok(true)
+        }
+
+        fn fee_bump_strategies(&self) -> Vec<FeeBumpStrategy> {
+            self.strategies.clone()
+        }
+
+        async fn fee_bump(
+            &self,
+            txid: &str,
+            fee_rate: f64,
+            strategy: FeeBumpStrategy,
+        ) -> Result<FeeBumpResult> {
+            self.inner.fee_bump(txid, fee_rate, strategy).await
+        }
+    }
+
+    fn tracked(now: i64) -> TrackedTransaction {
+        TrackedTransaction::new("orig", "bc1qtest", 50_000, now)
+    }
+
+    #[tokio::test]
+    async fn test_rescue_confirmed_payment() {
+        let rescuer = PaymentRescuer::new(
+            Arc::new(MockBitcoinExecutor::new()),
+            RescuePolicy::default(),
+        );
+        let assessment = rescuer.assess(&tracked(current_timestamp())).await.unwrap();
+        assert_eq!(assessment, RescueAssessment::Confirmed { confirmations: 6 });
+    }
+
+    #[tokio::test]
+    async fn test_rescue_detects_low_fee() {
+        let rescuer =
+            PaymentRescuer::new(Arc::new(MempoolWallet::new(2.0)), RescuePolicy::default());
+        let now = current_timestamp();
+
+        match rescuer.assess_at(&tracked(now), now).await.unwrap() {
+            RescueAssessment::Stuck {
+                reason,
+                suggested_fee_rate,
+                strategies,
+                ..
+            } => {
+                assert_eq!(reason, StuckReason::LowFeeRate);
+                assert_eq!(suggested_fee_rate, 10.0);
+                assert_eq!(
+                    strategies,
+                    vec![FeeBumpStrategy::Rbf, FeeBumpStrategy::Cpfp]
+                );
+            }
+            other => panic!("expected stuck, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rescue_detects_age() {
+        let rescuer =
+            PaymentRescuer::new(Arc::new(MempoolWallet::new(8.0)), RescuePolicy::default());
+        let now = current_timestamp();
+
+        let fresh = rescuer.assess_at(&tracked(now), now).await.unwrap();
+        assert!(matches!(fresh, RescueAssessment::Pending { .. }));
+
+        let old = rescuer
+            .assess_at(&tracked(now - 7 * 60 * 60), now)
+            .await
+            .unwrap();
+        assert!(matches!(
+            old,
+            RescueAssessment::Stuck {
+                reason: StuckReason::TooLongUnconfirmed,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rescue_records_proof_chain() {
+        let rescuer =
+            PaymentRescuer::new(Arc::new(MempoolWallet::new(2.0)), RescuePolicy::default());
+        let mut tx = tracked(current_timestamp());
+
+        let bump = rescuer.rescue(&mut tx).await.unwrap().unwrap();
+        assert_eq!(bump.strategy, FeeBumpStrategy::Rbf);
+        assert_eq!(tx.current_txid(), bump.bump_txid);
+        assert_eq!(tx.proof_chain().len(), 2);
+        assert_eq!(tx.proof_chain_metadata()["original_txid"], "orig");
+    }
+
+    #[tokio::test]
+    async fn test_rescue_does_not_rebump_pending_bump() {
+        let mut wallet = MempoolWallet::new(2.0);
+        wallet.strategies = vec![FeeBumpStrategy::Cpfp];
+        let rescuer = PaymentRescuer::new(Arc::new(wallet), RescuePolicy::default());
+        let mut tx = tracked(current_timestamp() - 7 * 60 * 60);
+
+        let bump = rescuer.rescue(&mut tx).await.unwrap().unwrap();
+        assert_eq!(bump.strategy, FeeBumpStrategy::Cpfp);
+        // The parent still reports 2 sat/vB, but the package pays the market rate
+        assert!(rescuer.rescue(&mut tx).await.unwrap().is_none());
+        assert_eq!(tx.bumps.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rescue_without_supported_strategy() {
+        let mut wallet = MempoolWallet::new(2.0);
+        wallet.strategies.clear();
+        let rescuer = PaymentRescuer::new(Arc::new(wallet), RescuePolicy::default());
+
+        let result = rescuer.rescue(&mut tracked(current_timestamp())).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_bump_respects_max_fee_rate() {
+        let rescuer =
+            PaymentRescuer::new(Arc::new(MempoolWallet::new(2.0)), RescuePolicy::default());
+        let mut tx = tracked(current_timestamp());
+
+        let result = rescuer.bump(&mut tx, 1000.0, FeeBumpStrategy::Cpfp).await;
+        assert!(result.is_err());
+        assert!(tx.bumps.is_empty());
+    }
+}
@@ -0,0 +1,423 @@
+//! Endpoint Rotation Engine
+//!
+//! A synchronous, I/O-free evaluator for rotation policies. The engine consumes
+//! payment events, decides when an endpoint must be rotated and keeps a history
+//! of rotations for auditing. Generating and publishing the new endpoint is left
+//! to the caller, which makes the engine usable from the CLI, mobile bindings
+//! and [`EndpointRotationManager`](super::EndpointRotationManager) alike.
+//!
+//! The engine is serializable so callers can persist it between runs.
+//!
+//! # Example
+//!
+//! ```
+//! use paykit_lib::rotation::{RotationConfig, RotationEngine, RotationPolicy};
+//! use paykit_lib::MethodId;
+//!
+//! let onchain = MethodId::onchain();
+//! let config =
+//!     RotationConfig::default().set_policy(onchain.clone(), RotationPolicy::after_uses(2));
+//! let mut engine = RotationEngine::new(config);
+//!
+//! assert!(!engine.on_payment(&onchain, 100).should_rotate());
+//! let decision = engine.on_payment(&onchain, 200);
+//! assert!(decision.should_rotate());
+//!
+//! // After publishing a new endpoint, record it
+//! engine.record_rotation(&onchain, decision.reason().unwrap(), Some("bc1q...".into()), 300);
+//! assert_eq!(engine.history(Some(&onchain)).len(), 1);
+//! ```
+
+use super::manager::RotationConfig;
+use super::policies::{EndpointTracker, RotationPolicy};
+use crate::{MethodId, PaykitError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Maximum number of history records retained by the engine.
+pub const MAX_ROTATION_HISTORY: usize = 500;
+
+/// Why an endpoint is (or was) rotated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationReason {
+    /// The usage threshold of the policy was reached.
+    Usage,
+    /// The policy's rotation interval elapsed.
+    Periodic,
+    /// Rotation was requested explicitly.
+    Manual,
+}
+
+impl RotationReason {
+    /// Short string form of the reason.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Usage => "usage",
+            Self::Periodic => "periodic",
+            Self::Manual => "manual",
+        }
+    }
+}
+
+impl FromStr for RotationReason {
+    type Err = PaykitError;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        match s.to_lowercase().as_str() {
+            "usage" => Ok(Self::Usage),
+            "periodic" => Ok(Self::Periodic),
+            "manual" => Ok(Self::Manual),
+            other => Err(PaykitError::ValidationFailed(format!(
+                "Unknown rotation reason: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Outcome of evaluating a method against its rotation policy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RotationDecision {
+    /// The current endpoint can stay in use.
+    Keep,
+    /// The endpoint should be rotated now.
+    RotateNow(RotationReason),
+}
+
+impl RotationDecision {
+    /// Whether the endpoint should be rotated.
+    pub fn should_rotate(&self) -> bool {
+        matches!(self, Self::RotateNow(_))
+    }
+
+    /// The rotation reason, if rotation is due.
+    pub fn reason(&self) -> Option<RotationReason> {
+        match self {
+            Self::Keep => None,
+            Self::RotateNow(reason) => Some(*reason),
+        }
+    }
+}
+
+/// A completed rotation, kept for auditing.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RotationRecord {
+    /// Method whose endpoint was rotated.
+    pub method_id: String,
+    /// Why the rotation happened.
+    pub reason: RotationReason,
+    /// Number of uses of the replaced endpoint.
+    pub use_count: u32,
+    /// Timestamp of the rotation.
+    pub rotated_at: i64,
+    /// The new endpoint, if the caller chose to record it.
+    pub new_endpoint: Option<String>,
+}
+
+/// Rotation status of a single method.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MethodRotationStatus {
+    /// Method ID.
+    pub method_id: String,
+    /// Effective policy for the method.
+    pub policy: RotationPolicy,
+    /// Uses of the current endpoint.
+    pub use_count: u32,
+    /// When the current endpoint came into use.
+    pub created_at: i64,
+    /// Last time the current endpoint was used.
+    pub last_used_at: Option<i64>,
+    /// Whether a rotation has been requested but not yet recorded.
+    pub rotation_pending: bool,
+    /// Total rotations recorded in history.
+    pub rotations: u32,
+    /// Timestamp of the most recent rotation.
+    pub last_rotated_at: Option<i64>,
+}
+
+/// Policy evaluation engine for endpoint rotation.
+///
+/// Every method takes an explicit `now` timestamp so decisions are
+/// deterministic and easy to test.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RotationEngine {
+    config: RotationConfig,
+    #[serde(default)]
+    trackers: HashMap<String, EndpointTracker>,
+    #[serde(default)]
+    history: Vec<RotationRecord>,
+}
+
+impl RotationEngine {
+    /// Create an engine with the given configuration.
+    pub fn new(config: RotationConfig) -> Self {
+        Self {
+            config,
+            trackers: HashMap::new(),
+            history: Vec::new(),
+        }
+    }
+
+    /// Current configuration.
+    pub fn config(&self) -> &RotationConfig {
+        &self.config
+    }
+
+    /// Replace the configuration, keeping trackers and history.
+    pub fn set_config(&mut self, config: RotationConfig) {
+        self.config = config;
+    }
+
+    /// Set the policy for a specific method.
+    pub fn set_policy(&mut self, method_id: &MethodId, policy: RotationPolicy) {
+        self.config
+            .method_policies
+            .insert(method_id.0.clone(), policy);
+    }
+
+    /// Remove a method-specific policy so the default applies again.
+    pub fn clear_policy(&mut self, method_id: &MethodId) -> Option<RotationPolicy> {
+        self.config.method_policies.remove(&method_id.0)
+    }
+
+    /// Set the default policy.
+    pub fn set_default_policy(&mut self, policy: RotationPolicy) {
+        self.config.default_policy = policy;
+    }
+
+    /// Enable or disable rotation decisions on payment events.
+    pub fn set_auto_rotate(&mut self, enabled: bool) {
+        self.config.auto_rotate_on_payment = enabled;
+    }
+
+    /// Record a payment that used the method's current endpoint.
+    ///
+    /// Returns [`RotationDecision::Keep`] when auto-rotation is disabled; the
+    /// use is still counted so status stays accurate.
+    pub fn on_payment(&mut self, method_id: &MethodId, now: i64) -> RotationDecision {
+        self.tracker_mut(method_id, now).record_use_at(now);
+
+        if !self.config.auto_rotate_on_payment {
+            return RotationDecision::Keep;
+        }
+        self.evaluate(method_id, now)
+    }
+
+    /// Evaluate a method against its policy without recording anything.
+    pub fn evaluate(&self, method_id: &MethodId, now: i64) -> RotationDecision {
+        let Some(tracker) = self.trackers.get(&method_id.0) else {
+            return RotationDecision::Keep;
+        };
+        let policy = self.config.policy_for(method_id);
+
+        if tracker.rotation_pending {
+            RotationDecision::RotateNow(RotationReason::Manual)
+        } else if tracker.use_count > 0 && policy.should_rotate_on_use(tracker.use_count) {
+            RotationDecision::RotateNow(RotationReason::Usage)
+        } else if policy.should_rotate_on_time(tracker.created_at, now) {
+            RotationDecision::RotateNow(RotationReason::Periodic)
+        } else {
+            RotationDecision::Keep
+        }
+    }
+
+    /// All tracked methods whose endpoints are due for rotation.
+    ///
+    /// Intended to be polled periodically to pick up time-based rotations.
+    pub fn due(&self, now: i64) -> Vec<(MethodId, RotationReason)> {
+        let mut due: Vec<_> = self
+            .trackers
+            .keys()
+            .map(|key| MethodId(key.clone()))
+            .filter_map(|method| {
+                let reason = self.evaluate(&method, now).reason()?;
+                Some((method, reason))
+            })
+            .collect();
+        due.sort_by(|a, b| a.0 .0.cmp(&b.0 .0));
+        due
+    }
+
+    /// Request a rotation regardless of policy.
+    pub fn request_rotation(&mut self, method_id: &MethodId, now: i64) {
+        self.tracker_mut(method_id, now).mark_pending();
+    }
+
+    /// Record that the caller rotated a method's endpoint.
+    ///
+    /// Resets the method's tracker and appends a history record. The oldest
+    /// records are dropped beyond [`MAX_ROTATION_HISTORY`].
+    pub fn record_rotation(
+        &mut self,
+        method_id: &MethodId,
+        reason: RotationReason,
+        new_endpoint: Option<String>,
+        now: i64,
+    ) -> RotationRecord {
+        let tracker = self.tracker_mut(method_id, now);
+        let record = RotationRecord {
+            method_id: method_id.0.clone(),
+            reason,
+            use_count: tracker.use_count,
+            rotated_at: now,
+            new_endpoint,
+        };
+        tracker.reset_at(now);
+
+        self.history.push(record.clone());
+        if self.history.len() > MAX_ROTATION_HISTORY {
+            let excess = self.history.len() - MAX_ROTATION_HISTORY;
+            self.history.drain(..excess);
+        }
+        record
+    }
+
+    /// Status of a single method, if it has been tracked.
+    pub fn status_for(&self, method_id: &MethodId) -> Option<MethodRotationStatus> {
+        let tracker = self.trackers.get(&method_id.0)?;
+        let records = self.history.iter().filter(|r| r.method_id == method_id.0);
+
+        Some(MethodRotationStatus {
+            method_id: method_id.0.clone(),
+            policy: self.config.policy_for(method_id).clone(),
+            use_count: tracker.use_count,
+            created_at: tracker.created_at,
+            last_used_at: tracker.last_used_at,
+            rotation_pending: tracker.rotation_pending,
+            rotations: records.clone().count() as u32,
+            last_rotated_at: records.map(|r| r.rotated_at).max(),
+        })
+    }
+
+    /// Status of every tracked method, sorted by method ID.
+    pub fn status(&self) -> Vec<MethodRotationStatus> {
+        let mut methods: Vec<_> = self.trackers.keys().cloned().collect();
+        methods.sort();
+        methods
+            .into_iter()
+            .filter_map(|m| self.status_for(&MethodId(m)))
+            .collect()
+    }
+
+    /// Rotation history, oldest first, optionally filtered by method.
+    pub fn history(&self, method_id: Option<&MethodId>) -> Vec<&RotationRecord> {
+        self.history
+            .iter()
+            .filter(|r| method_id.is_none_or(|m| r.method_id == m.0))
+            .collect()
+    }
+
+    /// Clear the rotation history.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    fn tracker_mut(&mut self, method_id: &MethodId, now: i64) -> &mut EndpointTracker {
+        self.trackers
+            .entry(method_id.0.clone())
+            .or_insert_with(|| EndpointTracker::starting_at(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn onchain() -> MethodId {
+        MethodId::onchain()
+    }
+
+    #[test]
+    fn test_engine_rotate_on_use() {
+        let mut engine = RotationEngine::default();
+        let decision = engine.on_payment(&onchain(), 100);
+        assert_eq!(decision, RotationDecision::RotateNow(RotationReason::Usage));
+    }
+
+    #[test]
+    fn test_engine_threshold() {
+        let config = RotationConfig::default().set_policy(onchain(), RotationPolicy::after_uses(3));
+        let mut engine = RotationEngine::new(config);
+
+        assert!(!engine.on_payment(&onchain(), 1).should_rotate());
+        assert!(!engine.on_payment(&onchain(), 2).should_rotate());
+        assert!(engine.on_payment(&onchain(), 3).should_rotate());
+
+        let record = engine.record_rotation(&onchain(), RotationReason::Usage, None, 4);
+        assert_eq!(record.use_count, 3);
+        assert!(!engine.evaluate(&onchain(), 5).should_rotate());
+    }
+
+    #[test]
+    fn test_engine_periodic_due() {
+        let config =
+            RotationConfig::default().set_policy(onchain(), RotationPolicy::every_hours(1));
+        let mut engine = RotationEngine::new(config);
+
+        assert!(!engine.on_payment(&onchain(), 0).should_rotate());
+        assert!(engine.due(1800).is_empty());
+        assert_eq!(
+            engine.due(3600),
+            vec![(onchain(), RotationReason::Periodic)]
+        );
+    }
+
+    #[test]
+    fn test_engine_auto_rotate_disabled() {
+        let mut engine = RotationEngine::default();
+        engine.set_auto_rotate(false);
+
+        assert_eq!(engine.on_payment(&onchain(), 1), RotationDecision::Keep);
+        assert_eq!(engine.status_for(&onchain()).unwrap().use_count, 1);
+    }
+
+    #[test]
+    fn test_engine_manual_request() {
+        let mut engine = RotationEngine::default();
+        engine.set_default_policy(RotationPolicy::Manual);
+        engine.request_rotation(&onchain(), 10);
+
+        assert_eq!(
+            engine.evaluate(&onchain(), 10),
+            RotationDecision::RotateNow(RotationReason::Manual)
+        );
+    }
+
+    #[test]
+    fn test_engine_status_and_history() {
+        let mut engine = RotationEngine::default();
+        let lightning = MethodId::lightning();
+
+        engine.on_payment(&onchain(), 1);
+        engine.record_rotation(&onchain(), RotationReason::Usage, Some("addr2".into()), 2);
+        engine.on_payment(&lightning, 3);
+        engine.record_rotation(&lightning, RotationReason::Usage, None, 4);
+        engine.on_payment(&onchain(), 5);
+        engine.record_rotation(&onchain(), RotationReason::Usage, Some("addr3".into()), 6);
+
+        let status = engine.status();
+        assert_eq!(status.len(), 2);
+        assert_eq!(status[0].method_id, "lightning");
+        assert_eq!(status[1].rotations, 2);
+        assert_eq!(status[1].last_rotated_at, Some(6));
+        assert_eq!(engine.history(Some(&onchain())).len(), 2);
+        assert_eq!(engine.history(None).len(), 3);
+
+        engine.clear_history();
+        assert!(engine.history(None).is_empty());
+    }
+
+    #[test]
+    fn test_engine_serialization_roundtrip() {
+        let mut engine = RotationEngine::default();
+        engine.on_payment(&onchain(), 1);
+        engine.record_rotation(&onchain(), RotationReason::Usage, None, 2);
+
+        let json = serde_json::to_string(&engine).unwrap();
+        let restored: RotationEngine = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.history(None).len(), 1);
+        assert_eq!(restored.status(), engine.status());
+    }
+}
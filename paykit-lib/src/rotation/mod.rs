@@ -10,6 +10,12 @@
 //! - **RotatePeriodic**: Rotate on a time interval
 //! - **Manual**: No automatic rotation
 //!
+//! Policies can be parsed from their short string form (`on-use`, `manual`,
+//! `after:<uses>`, `periodic:<seconds>`) with [`RotationPolicy::parse`].
+//!
+//! [`RotationEngine`] evaluates policies against payment events without doing
+//! any I/O, and is shared by the CLI and mobile bindings.
+//!
 //! # Example
 //!
 //! ```ignore
@@ -39,8 +45,13 @@
 //! - Lightning: Rotate invoices for each payment (typically automatic)
 //! - Private endpoints: Use unique endpoints per peer
 
+mod engine;
 mod manager;
 mod policies;
 
+pub use engine::{
+    MethodRotationStatus, RotationDecision, RotationEngine, RotationReason, RotationRecord,
+    MAX_ROTATION_HISTORY,
+};
 pub use manager::{EndpointRotationManager, RotationCallback, RotationConfig};
 pub use policies::{EndpointTracker, RotationPolicy};
@@ -2,7 +2,10 @@
 //!
 //! This module defines policies for when and how to rotate payment endpoints.
 
+use crate::{PaykitError, Result};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 
 /// Policy for endpoint rotation.
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
@@ -52,6 +55,71 @@ impl RotationPolicy {
         }
     }
 
+    /// Parse a policy from its short string form.
+    ///
+    /// Accepted forms (case-insensitive): `on-use`, `manual`, `after:<uses>`
+    /// and `periodic:<seconds>`.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim().to_lowercase();
+
+        match spec.as_str() {
+            "on-use" | "onuse" => return Ok(Self::RotateOnUse),
+            "manual" | "never" => return Ok(Self::Manual),
+            _ => {}
+        }
+
+        if let Some(count) = spec.strip_prefix("after:") {
+            let threshold: u32 = count.parse().map_err(|_| {
+                PaykitError::ValidationFailed(format!("Invalid use count in policy: {}", count))
+            })?;
+            if threshold == 0 {
+                return Err(PaykitError::ValidationFailed(
+                    "Use count must be at least 1".to_string(),
+                ));
+            }
+            return Ok(Self::after_uses(threshold));
+        }
+
+        if let Some(interval) = spec.strip_prefix("periodic:") {
+            let interval_secs: u64 = interval.parse().map_err(|_| {
+                PaykitError::ValidationFailed(format!("Invalid interval in policy: {}", interval))
+            })?;
+            if interval_secs == 0 {
+                return Err(PaykitError::ValidationFailed(
+                    "Interval must be at least 1 second".to_string(),
+                ));
+            }
+            return Ok(Self::RotatePeriodic { interval_secs });
+        }
+
+        Err(PaykitError::ValidationFailed(format!(
+            "Invalid policy '{}'. Use: on-use, manual, after:<count>, or periodic:<seconds>",
+            spec
+        )))
+    }
+
+    /// Short string form accepted by [`parse`](Self::parse).
+    pub fn to_spec(&self) -> String {
+        match self {
+            Self::RotateOnUse => "on-use".to_string(),
+            Self::RotateOnThreshold { threshold } => format!("after:{}", threshold),
+            Self::RotatePeriodic { interval_secs } => format!("periodic:{}", interval_secs),
+            Self::Manual => "manual".to_string(),
+        }
+    }
+
+    /// Human-readable description of the policy.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::RotateOnUse => "Rotate on every use (best privacy)".to_string(),
+            Self::RotateOnThreshold { threshold } => format!("Rotate after {} uses", threshold),
+            Self::RotatePeriodic { interval_secs } => {
+                format!("Rotate every {} seconds", interval_secs)
+            }
+            Self::Manual => "Manual rotation only".to_string(),
+        }
+    }
+
     /// Check if rotation is needed based on usage count.
     pub fn should_rotate_on_use(&self, use_count: u32) -> bool {
         match self {
@@ -73,6 +141,20 @@ impl RotationPolicy {
     }
 }
 
+impl FromStr for RotationPolicy {
+    type Err = PaykitError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for RotationPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_spec())
+    }
+}
+
 /// Tracking information for an endpoint.
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct EndpointTracker {
@@ -103,14 +185,31 @@ impl EndpointTracker {
         self.last_used_at = Some(current_timestamp());
     }
 
+    /// Create a tracker that starts at the given timestamp.
+    pub fn starting_at(now: i64) -> Self {
+        Self {
+            created_at: now,
+            ..Self::default()
+        }
+    }
+
+    /// Record a use of the endpoint at the given timestamp.
+    pub fn record_use_at(&mut self, now: i64) {
+        self.use_count = self.use_count.saturating_add(1);
+        self.last_used_at = Some(now);
+    }
+
     /// Check if rotation is needed based on policy.
     pub fn needs_rotation(&self, policy: &RotationPolicy) -> bool {
+        self.needs_rotation_at(policy, current_timestamp())
+    }
+
+    /// Check if rotation is needed based on policy at the given timestamp.
+    pub fn needs_rotation_at(&self, policy: &RotationPolicy, now: i64) -> bool {
         if self.rotation_pending {
             return true;
         }
 
-        let now = current_timestamp();
-
         policy.should_rotate_on_use(self.use_count)
             || policy.should_rotate_on_time(self.created_at, now)
     }
@@ -122,8 +221,13 @@ impl EndpointTracker {
 
     /// Reset after rotation.
     pub fn reset(&mut self) {
+        self.reset_at(current_timestamp());
+    }
+
+    /// Reset after a rotation performed at the given timestamp.
+    pub fn reset_at(&mut self, now: i64) {
         self.use_count = 0;
-        self.created_at = current_timestamp();
+        self.created_at = now;
         self.last_used_at = None;
         self.rotation_pending = false;
     }
@@ -184,6 +288,41 @@ mod tests {
         assert!(policy.should_rotate_on_time(now, now + 7200));
     }
 
+    #[test]
+    fn test_parse_policy() {
+        assert_eq!(
+            RotationPolicy::parse("on-use").unwrap(),
+            RotationPolicy::RotateOnUse
+        );
+        assert_eq!(
+            RotationPolicy::parse("MANUAL").unwrap(),
+            RotationPolicy::Manual
+        );
+        assert_eq!(
+            RotationPolicy::parse("after:5").unwrap(),
+            RotationPolicy::after_uses(5)
+        );
+        assert_eq!(
+            "periodic:3600".parse::<RotationPolicy>().unwrap(),
+            RotationPolicy::every_hours(1)
+        );
+        assert!(RotationPolicy::parse("after:0").is_err());
+        assert!(RotationPolicy::parse("after:x").is_err());
+        assert!(RotationPolicy::parse("sometimes").is_err());
+    }
+
+    #[test]
+    fn test_policy_spec_roundtrip() {
+        for policy in [
+            RotationPolicy::RotateOnUse,
+            RotationPolicy::after_uses(3),
+            RotationPolicy::every_days(1),
+            RotationPolicy::Manual,
+        ] {
+            assert_eq!(RotationPolicy::parse(&policy.to_spec()).unwrap(), policy);
+        }
+    }
+
     #[test]
     fn test_endpoint_tracker() {
         let mut tracker = EndpointTracker::new();
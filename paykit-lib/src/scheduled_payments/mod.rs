@@ -0,0 +1,520 @@
+//! Scheduled Payments
+//!
+//! A scheduled payment is a one-off payment the payer sets up to be sent
+//! later ("pay X 20k sats on the 1st"). It holds the payee, the amount, an
+//! execution window and an optional method preference, and can be cancelled
+//! until it is paid.
+//!
+//! The host application persists its [`ScheduledPayment`]s and calls
+//! [`PaymentScheduler::run_at`] periodically. A payment is sent on the first
+//! run inside its window, through the regular method registry, trying the
+//! payer's preferred methods first. The scheduler can be given a
+//! [`HealthMonitor`](crate::health::HealthMonitor), whose unusable methods
+//! are skipped, and a [`VelocityGuard`], whose holds keep the payment
+//! waiting. A failed or held payment stays pending, so the next run inside
+//! the window retries it; once the window closes it expires. A payment
+//! whose outcome is unknown because a plugin failed mid-payment is reported
+//! as [`ScheduledOutcome::Unresolved`] and should not be run again until
+//! the wallet shows whether it went out.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use paykit_lib::scheduled_payments::{PaymentScheduler, ScheduledOutcome, ScheduledPayment};
+//!
+//! let mut payment = ScheduledPayment::new("rent-march", payee_z32, 20_000, march_1, now)?
+//!     .with_deadline(march_1 + 3 * 86_400)
+//!     .with_methods(vec![MethodId::lightning()]);
+//! let scheduler = PaymentScheduler::new(registry).with_velocity_guard(guard);
+//!
+//! // Called periodically by the app's scheduler
+//! let supported = get_payment_list(&reader, &payee).await?;
+//! if let ScheduledOutcome::Paid(execution) = scheduler.run_at(&mut payment, &supported, now).await? {
+//!     println!("paid via {}", execution.method_id.0);
+//! }
+//! ```
+
+use crate::methods::{Amount, PaymentExecution, PaymentMethodRegistry};
+use crate::policy::{HeldPayment, VelocityGuard, VelocityVerdict};
+use crate::selection::{pay_with_fallback, Fallback, SelectionPreferences};
+use crate::{MethodId, PaykitError, Result, SupportedPayments};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Where a scheduled payment is in its lifecycle.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduledStatus {
+    /// Waiting for its window, or retrying inside it.
+    #[default]
+    Pending,
+    /// Sent.
+    Paid,
+    /// Cancelled by the payer before it was sent.
+    Cancelled,
+    /// The window closed before the payment could be sent.
+    Expired,
+}
+
+impl ScheduledStatus {
+    /// Lowercase name, as serialized.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScheduledStatus::Pending => "pending",
+            ScheduledStatus::Paid => "paid",
+            ScheduledStatus::Cancelled => "cancelled",
+            ScheduledStatus::Expired => "expired",
+        }
+    }
+}
+
+/// A one-off payment to send later.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduledPayment {
+    /// Unique payment identifier.
+    pub payment_id: String,
+    /// Payee public key (z-base-32).
+    pub payee: String,
+    /// Amount to pay, in satoshis.
+    pub amount_sats: u64,
+    /// Preferred methods, highest priority first. When set, only these
+    /// methods are used; otherwise any method the payee supports is.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub methods: Vec<MethodId>,
+    /// Note recorded with the payment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+    /// Earliest moment to pay (unix seconds).
+    pub not_before: i64,
+    /// Latest moment to pay, if limited; the payment expires after it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_after: Option<i64>,
+    /// When the payment was scheduled.
+    pub created_at: i64,
+    /// Lifecycle status.
+    #[serde(default)]
+    pub status: ScheduledStatus,
+    /// Payment attempts made so far.
+    #[serde(default)]
+    pub attempts: u32,
+    /// Error of the last failed attempt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// When the payment was paid, cancelled or expired.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settled_at: Option<i64>,
+}
+
+impl ScheduledPayment {
+    /// Schedule a payment for `not_before` or later.
+    pub fn new(
+        payment_id: impl Into<String>,
+        payee: impl Into<String>,
+        amount_sats: u64,
+        not_before: i64,
+        created_at: i64,
+    ) -> Result<Self> {
+        let payment = Self {
+            payment_id: payment_id.into(),
+            payee: payee.into(),
+            amount_sats,
+            methods: Vec::new(),
+            memo: None,
+            not_before,
+            not_after: None,
+            created_at,
+            status: ScheduledStatus::Pending,
+            attempts: 0,
+            last_error: None,
+            settled_at: None,
+        };
+        payment.validate()?;
+        Ok(payment)
+    }
+
+    /// Restrict the payment to these methods, highest priority first.
+    pub fn with_methods(mut self, methods: Vec<MethodId>) -> Self {
+        self.methods = methods;
+        self
+    }
+
+    /// Set the note recorded with the payment.
+    pub fn with_memo(mut self, memo: impl Into<String>) -> Self {
+        self.memo = Some(memo.into());
+        self
+    }
+
+    /// Give up if the payment could not be sent by `not_after`.
+    pub fn with_deadline(mut self, not_after: i64) -> Self {
+        self.not_after = Some(not_after);
+        self
+    }
+
+    /// Check the payment can ever be sent.
+    pub fn validate(&self) -> Result<()> {
+        if self.payment_id.trim().is_empty() {
+            return Err(PaykitError::ValidationFailed(
+                "Scheduled payment ID must not be empty".to_string(),
+            ));
+        }
+        if self.payee.trim().is_empty() {
+            return Err(PaykitError::ValidationFailed(
+                "Scheduled payment payee must not be empty".to_string(),
+            ));
+        }
+        if self.amount_sats == 0 {
+            return Err(PaykitError::ValidationFailed(
+                "Scheduled payment amount must be positive".to_string(),
+            ));
+        }
+        if self.not_after.is_some_and(|end| end < self.not_before) {
+            return Err(PaykitError::ValidationFailed(
+                "Scheduled payment window must end after it starts".to_string(),
+            ));
+        }
+        self.methods.iter().try_for_each(MethodId::validate)
+    }
+
+    /// Whether the payment will never be attempted again.
+    pub fn is_settled(&self) -> bool {
+        self.status != ScheduledStatus::Pending
+    }
+
+    /// Why the payment should not be sent at `now`, or `None` if it is due.
+    pub fn skip_reason(&self, now: i64) -> Option<ScheduledSkip> {
+        match self.status {
+            ScheduledStatus::Paid => Some(ScheduledSkip::Paid),
+            ScheduledStatus::Cancelled => Some(ScheduledSkip::Cancelled),
+            ScheduledStatus::Expired => Some(ScheduledSkip::Expired),
+            ScheduledStatus::Pending if now < self.not_before => Some(ScheduledSkip::NotDue {
+                not_before: self.not_before,
+            }),
+            ScheduledStatus::Pending if self.not_after.is_some_and(|end| now > end) => {
+                Some(ScheduledSkip::Expired)
+            }
+            ScheduledStatus::Pending => None,
+        }
+    }
+
+    /// Whether the payment should be sent at `now`.
+    pub fn is_due(&self, now: i64) -> bool {
+        self.skip_reason(now).is_none()
+    }
+
+    /// Cancel the payment. Fails once it has been paid.
+    pub fn cancel(&mut self, now: i64) -> Result<()> {
+        match self.status {
+            ScheduledStatus::Paid => Err(PaykitError::PaymentAlreadyCompleted {
+                payment_id: self.payment_id.clone(),
+            }),
+            ScheduledStatus::Cancelled => Ok(()),
+            ScheduledStatus::Pending | ScheduledStatus::Expired => {
+                self.status = ScheduledStatus::Cancelled;
+                self.settled_at = Some(now);
+                Ok(())
+            }
+        }
+    }
+
+    fn settle(&mut self, status: ScheduledStatus, now: i64) {
+        self.status = status;
+        self.settled_at = Some(now);
+    }
+}
+
+/// Why a scheduled payment was not sent.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum ScheduledSkip {
+    /// The window has not opened yet.
+    NotDue {
+        /// When the window opens.
+        not_before: i64,
+    },
+    /// Every method the payment could use is unhealthy.
+    NoHealthyMethod,
+    /// The payment was already sent.
+    Paid,
+    /// The payment was cancelled.
+    Cancelled,
+    /// The window closed before the payment could be sent.
+    Expired,
+}
+
+impl ScheduledSkip {
+    /// Human-readable description.
+    pub fn describe(&self) -> String {
+        match self {
+            ScheduledSkip::NotDue { not_before } => format!("not due before {}", not_before),
+            ScheduledSkip::NoHealthyMethod => "no healthy payment method".to_string(),
+            ScheduledSkip::Paid => "already paid".to_string(),
+            ScheduledSkip::Cancelled => "payment was cancelled".to_string(),
+            ScheduledSkip::Expired => "payment window has closed".to_string(),
+        }
+    }
+}
+
+/// Result of running a scheduled payment.
+#[derive(Clone, Debug)]
+pub enum ScheduledOutcome {
+    /// The payment succeeded.
+    Paid(PaymentExecution),
+    /// Every usable method failed; the payment stays pending. Holds the last attempt.
+    Failed(PaymentExecution),
+    /// A method failed in a way that does not rule out the payment having
+    /// been sent, and no other method was tried. The payment stays pending;
+    /// check the wallet before running it again. Holds that attempt.
+    Unresolved(PaymentExecution),
+    /// The velocity guard held the payment; it stays pending until approved.
+    Held(HeldPayment),
+    /// No payment was attempted.
+    Skipped(ScheduledSkip),
+}
+
+/// Sends due scheduled payments through a method registry.
+pub struct PaymentScheduler {
+    registry: Arc<PaymentMethodRegistry>,
+    #[cfg(feature = "health")]
+    health: Option<Arc<crate::health::HealthMonitor>>,
+    velocity_guard: Option<Arc<VelocityGuard>>,
+}
+
+impl PaymentScheduler {
+    /// Create a scheduler paying through `registry`.
+    pub fn new(registry: impl Into<Arc<PaymentMethodRegistry>>) -> Self {
+        Self {
+            registry: registry.into(),
+            #[cfg(feature = "health")]
+            health: None,
+            velocity_guard: None,
+        }
+    }
+
+    /// Skip methods the monitor reports as unusable.
+    #[cfg(feature = "health")]
+    pub fn with_health_monitor(mut self, health: Arc<crate::health::HealthMonitor>) -> Self {
+        self.health = Some(health);
+        self
+    }
+
+    /// Check payments against the guard's limits before sending them.
+    pub fn with_velocity_guard(mut self, guard: Arc<VelocityGuard>) -> Self {
+        self.velocity_guard = Some(guard);
+        self
+    }
+
+    /// Payments due at `now`, earliest window first.
+    pub fn due<'a>(&self, payments: &'a [ScheduledPayment], now: i64) -> Vec<&'a ScheduledPayment> {
+        let mut due: Vec<&ScheduledPayment> = payments.iter().filter(|p| p.is_due(now)).collect();
+        due.sort_by_key(|payment| payment.not_before);
+        due
+    }
+
+    fn is_usable(&self, method_id: &MethodId) -> bool {
+        #[cfg(feature = "health")]
+        if let Some(health) = &self.health {
+            return health.is_usable(method_id);
+        }
+        let _ = method_id;
+        true
+    }
+
+    /// Send `payment` if it is due at `now`, using the payee's `supported` methods.
+    ///
+    /// Methods are tried in selection order until one succeeds, stopping
+    /// early at an error that may have come after sending. A payment whose
+    /// window has closed is marked expired.
+    pub async fn run_at(
+        &self,
+        payment: &mut ScheduledPayment,
+        supported: &SupportedPayments,
+        now: i64,
+    ) -> Result<ScheduledOutcome> {
+        if let Some(skip) = payment.skip_reason(now) {
+            if skip == ScheduledSkip::Expired && !payment.is_settled() {
+                payment.settle(ScheduledStatus::Expired, now);
+            }
+            return Ok(ScheduledOutcome::Skipped(skip));
+        }
+
+        let allowed: Vec<&MethodId> = supported
+            .entries
+            .keys()
+            .filter(|method_id| payment.methods.is_empty() || payment.methods.contains(method_id))
+            .collect();
+        let usable: Vec<&MethodId> = allowed
+            .iter()
+            .copied()
+            .filter(|method_id| self.is_usable(method_id))
+            .collect();
+        if usable.is_empty() && !allowed.is_empty() {
+            return Ok(ScheduledOutcome::Skipped(ScheduledSkip::NoHealthyMethod));
+        }
+
+        if let Some(guard) = &self.velocity_guard {
+            if let VelocityVerdict::Hold(held) =
+                guard.check(&payment.payee, payment.amount_sats, now)
+            {
+                return Ok(ScheduledOutcome::Held(held));
+            }
+        }
+
+        let amount = Amount::sats(payment.amount_sats);
+        let mut preferences = SelectionPreferences::with_priority_list(payment.methods.clone());
+        for method_id in supported.entries.keys() {
+            if !usable.contains(&method_id) {
+                preferences = preferences.exclude_method(method_id.clone());
+            }
+        }
+        let metadata = serde_json::json!({
+            "scheduled_payment": payment.payment_id,
+            "memo": payment.memo,
+        });
+        let fallback = pay_with_fallback(
+            &self.registry,
+            supported,
+            &amount,
+            &preferences,
+            &metadata,
+            || payment.attempts += 1,
+        )
+        .await?;
+
+        match fallback {
+            Fallback::Paid(execution) => {
+                payment.last_error = None;
+                payment.settle(ScheduledStatus::Paid, now);
+                if let Some(guard) = &self.velocity_guard {
+                    guard.record(&payment.payee, payment.amount_sats, now);
+                }
+                Ok(ScheduledOutcome::Paid(execution))
+            }
+            Fallback::Failed(execution) => {
+                payment.last_error = execution.error.clone();
+                Ok(ScheduledOutcome::Failed(execution))
+            }
+            Fallback::Unresolved(execution) => {
+                payment.last_error = execution.error.clone();
+                Ok(ScheduledOutcome::Unresolved(execution))
+            }
+            Fallback::Unavailable => Err(PaykitError::Transport(
+                "No payment methods available for scheduled payment".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EndpointData;
+
+    fn supported() -> SupportedPayments {
+        let mut supported = SupportedPayments::default();
+        supported.entries.insert(
+            MethodId::onchain(),
+            EndpointData("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".into()),
+        );
+        supported
+            .entries
+            .insert(MethodId::lightning(), EndpointData("lnbc1payee".into()));
+        supported
+    }
+
+    #[test]
+    fn test_payment_window_and_cancellation() {
+        let mut payment = ScheduledPayment::new("rent", "payee", 20_000, 1_000, 0)
+            .unwrap()
+            .with_deadline(2_000);
+        assert_eq!(
+            payment.skip_reason(999),
+            Some(ScheduledSkip::NotDue { not_before: 1_000 })
+        );
+        assert!(payment.is_due(1_000));
+        assert!(payment.is_due(2_000));
+        assert_eq!(payment.skip_reason(2_001), Some(ScheduledSkip::Expired));
+
+        payment.cancel(1_500).unwrap();
+        assert_eq!(payment.status, ScheduledStatus::Cancelled);
+        assert_eq!(payment.skip_reason(1_500), Some(ScheduledSkip::Cancelled));
+
+        payment.status = ScheduledStatus::Paid;
+        assert!(payment.cancel(1_600).is_err());
+
+        assert!(ScheduledPayment::new("x", "payee", 0, 1_000, 0).is_err());
+        assert!(ScheduledPayment::new("x", "payee", 1, 1_000, 0)
+            .unwrap()
+            .with_deadline(999)
+            .validate()
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_pays_once_inside_window() {
+        let scheduler = PaymentScheduler::new(crate::methods::default_registry());
+        let mut payment = ScheduledPayment::new("rent", "payee", 20_000, 1_000, 0)
+            .unwrap()
+            .with_methods(vec![MethodId::onchain()]);
+
+        assert!(matches!(
+            scheduler
+                .run_at(&mut payment, &supported(), 999)
+                .await
+                .unwrap(),
+            ScheduledOutcome::Skipped(ScheduledSkip::NotDue { .. })
+        ));
+        assert_eq!(
+            scheduler.due(std::slice::from_ref(&payment), 1_000).len(),
+            1
+        );
+
+        match scheduler
+            .run_at(&mut payment, &supported(), 1_000)
+            .await
+            .unwrap()
+        {
+            ScheduledOutcome::Paid(execution) => {
+                assert_eq!(execution.method_id, MethodId::onchain())
+            }
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+        assert_eq!(payment.status, ScheduledStatus::Paid);
+        assert_eq!(payment.settled_at, Some(1_000));
+        assert!(matches!(
+            scheduler
+                .run_at(&mut payment, &supported(), 1_001)
+                .await
+                .unwrap(),
+            ScheduledOutcome::Skipped(ScheduledSkip::Paid)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_expires_and_holds() {
+        let mut late = ScheduledPayment::new("late", "payee", 20_000, 1_000, 0)
+            .unwrap()
+            .with_deadline(1_100);
+        let scheduler = PaymentScheduler::new(crate::methods::default_registry());
+        scheduler
+            .run_at(&mut late, &supported(), 5_000)
+            .await
+            .unwrap();
+        assert_eq!(late.status, ScheduledStatus::Expired);
+
+        let guard = Arc::new(VelocityGuard::new(crate::policy::VelocityConfig {
+            max_amount_per_window_sats: Some(10_000),
+            ..Default::default()
+        }));
+        let scheduler =
+            PaymentScheduler::new(crate::methods::default_registry()).with_velocity_guard(guard);
+        let mut payment = ScheduledPayment::new("big", "payee", 20_000, 1_000, 0).unwrap();
+        assert!(matches!(
+            scheduler
+                .run_at(&mut payment, &supported(), 1_000)
+                .await
+                .unwrap(),
+            ScheduledOutcome::Held(_)
+        ));
+        assert_eq!(payment.status, ScheduledStatus::Pending);
+    }
+}
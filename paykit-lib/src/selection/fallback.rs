@@ -0,0 +1,101 @@
+//! Paying With Fallback
+//!
+//! Runs a payment through the selected methods in order until one succeeds.
+//! Shared by the payer-side schedulers and the voucher redeemer, which all
+//! pay a known amount to a payee's published endpoints.
+//!
+//! Falling back is only safe when the failed method certainly sent nothing.
+//! A failed execution reported by the plugin, or an error raised while
+//! checking the payment, rules that out; any other plugin error may come
+//! after the payment left the wallet, so the run stops there instead of
+//! paying again with the next method.
+
+use super::{PaymentMethodSelector, SelectionPreferences};
+use crate::methods::{Amount, PaymentExecution, PaymentMethodRegistry};
+use crate::{PaykitError, Result, SupportedPayments};
+use std::sync::Arc;
+
+/// Outcome of [`pay_with_fallback`].
+pub(crate) enum Fallback {
+    /// A method paid.
+    Paid(PaymentExecution),
+    /// Every method tried failed without sending anything. Holds the last attempt.
+    Failed(PaymentExecution),
+    /// A method failed with an error that does not rule out the payment
+    /// having been sent; no other method was tried. Holds that attempt.
+    Unresolved(PaymentExecution),
+    /// No selected method has both a plugin and an endpoint.
+    Unavailable,
+}
+
+/// Pay `amount` to one of the payee's `supported` endpoints.
+///
+/// Methods are tried in selection order; `on_attempt` is called before each
+/// payment is handed to a plugin.
+pub(crate) async fn pay_with_fallback(
+    registry: &Arc<PaymentMethodRegistry>,
+    supported: &SupportedPayments,
+    amount: &Amount,
+    preferences: &SelectionPreferences,
+    metadata: &serde_json::Value,
+    mut on_attempt: impl FnMut(),
+) -> Result<Fallback> {
+    let selection =
+        PaymentMethodSelector::new(registry.clone()).select(supported, amount, preferences)?;
+
+    let mut last_failure = None;
+    for method_id in selection.all_methods() {
+        let (Some(plugin), Some(endpoint)) =
+            (registry.get(&method_id), supported.entries.get(&method_id))
+        else {
+            continue;
+        };
+        let failure = |error: String| {
+            PaymentExecution::failure(method_id.clone(), endpoint.clone(), amount.clone(), error)
+        };
+
+        let validation = plugin.validate_endpoint(endpoint);
+        if !validation.valid {
+            last_failure = Some(failure(validation.errors.join(", ")));
+            continue;
+        }
+        if !plugin.supports_amount(amount) {
+            last_failure = Some(failure(format!(
+                "{} does not support this amount",
+                method_id.0
+            )));
+            continue;
+        }
+
+        on_attempt();
+        let execution = match plugin.execute_payment(endpoint, amount, metadata).await {
+            Ok(execution) => execution,
+            Err(e) if failed_before_sending(&e) => failure(e.to_string()),
+            Err(e) => return Ok(Fallback::Unresolved(failure(e.to_string()))),
+        };
+        if execution.success {
+            return Ok(Fallback::Paid(execution));
+        }
+        last_failure = Some(execution);
+    }
+
+    Ok(last_failure.map_or(Fallback::Unavailable, Fallback::Failed))
+}
+
+/// Whether a plugin error is one raised before any payment is made.
+fn failed_before_sending(error: &PaykitError) -> bool {
+    matches!(
+        error,
+        PaykitError::Unimplemented(_)
+            | PaykitError::NotFound { .. }
+            | PaykitError::MethodNotSupported(_)
+            | PaykitError::InvalidData { .. }
+            | PaykitError::ValidationFailed(_)
+            | PaykitError::InsufficientFunds { .. }
+            | PaykitError::InvoiceExpired { .. }
+            | PaykitError::FeeSpike { .. }
+            | PaykitError::Auth(_)
+            | PaykitError::InvalidCredentials(_)
+            | PaykitError::SessionExpired
+    )
+}
@@ -37,9 +37,64 @@
 //! - **SpeedOptimized**: Prioritizes fastest confirmation
 //! - **PrivacyOptimized**: Maximizes privacy (prefers off-chain)
 //! - **PriorityList**: Uses methods in user-specified order
+//!
+//! # Liquidity-Aware Selection
+//!
+//! Lightning can look ideal on paper and still fail for lack of a route.
+//! [`PaymentMethodSelector::select_probed`] probes the payee's Lightning
+//! endpoint through a [`RouteProber`] first and ranks Lightning by the
+//! probe's success probability and expected fee. Probe results are cached
+//! for [`DEFAULT_PROBE_TTL_SECS`] by default.
+//!
+//! ```ignore
+//! let prober = RouteProber::new(lnd_executor);
+//! let result = selector.select_probed(&supported, &amount, &prefs, &prober).await?;
+//! ```
+//!
+//! # Payee Status
+//!
+//! Payees may publish a [`PayeeStatus`](crate::protocol::PayeeStatus)
+//! saying which methods are temporarily unavailable or preferred.
+//! [`PaymentMethodSelector::with_payee_status`] demotes unavailable methods
+//! to last-resort fallbacks and favours the preferred one while the status
+//! is fresh.
+//!
+//! ```ignore
+//! let status = fetch_payee_status(&reader, &payee, now).await?;
+//! let mut selector = PaymentMethodSelector::with_defaults();
+//! if let Some(status) = status {
+//!     selector = selector.with_payee_status(status, now);
+//! }
+//! ```
+//!
+//! # Payee Routing Hints
+//!
+//! Payees may also publish [`PayeeRoutingHints`](crate::protocol::PayeeRoutingHints):
+//! their preferred order of methods with cost and time estimates.
+//! [`PaymentMethodSelector::with_payee_hints`] merges them with the payer's
+//! preferences according to [`SelectionPreferences::payee_hint_policy`]:
+//!
+//! - Excluded methods stay excluded, and payee hints never outrank a
+//!   payer's priority list.
+//! - The payee's cost and time estimates are checked against the payer's
+//!   fee and confirmation-time limits; they can rule a method out but never
+//!   relax a limit.
+//! - Under [`PayeeHintPolicy::Advisory`] (the default) the payee's order
+//!   adds a small bonus; under [`PayeeHintPolicy::TieBreak`] it only orders
+//!   equal scores; [`PayeeHintPolicy::Ignore`] disregards it.
+//!
+//! ```ignore
+//! if let Some(hints) = fetch_routing_hints(&reader, &payee, now).await? {
+//!     selector = selector.with_payee_hints(hints, now);
+//! }
+//! ```
 
+mod fallback;
 mod preferences;
+mod probe;
 mod selector;
 
-pub use preferences::{AmountThresholds, SelectionPreferences, SelectionStrategy};
+pub(crate) use fallback::{pay_with_fallback, Fallback};
+pub use preferences::{AmountThresholds, PayeeHintPolicy, SelectionPreferences, SelectionStrategy};
+pub use probe::{RouteProber, DEFAULT_PROBE_TTL_SECS};
 pub use selector::{PaymentMethodSelector, SelectionResult};
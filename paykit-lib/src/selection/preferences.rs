@@ -21,6 +21,23 @@ pub enum SelectionStrategy {
     PriorityList,
 }
 
+/// How the payee's published routing hints combine with the payer's preferences.
+///
+/// Whatever the policy, the payer wins conflicts: excluded methods stay
+/// excluded, a priority list outranks the payee's order, and the payee's
+/// estimates only tighten the payer's fee and time limits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PayeeHintPolicy {
+    /// Disregard payee hints entirely.
+    Ignore,
+    /// Use the payee's order only between methods that score equally.
+    TieBreak,
+    /// Give the payee's preferred methods a bonus too small to override
+    /// the payer's strategy or priority list (default).
+    #[default]
+    Advisory,
+}
+
 /// User preferences for payment method selection.
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct SelectionPreferences {
@@ -39,6 +56,9 @@ pub struct SelectionPreferences {
     pub prefer_privacy: bool,
     /// Amount thresholds for method selection (in satoshis).
     pub amount_thresholds: AmountThresholds,
+    /// How to weigh the payee's routing hints.
+    #[serde(default)]
+    pub payee_hint_policy: PayeeHintPolicy,
 }
 
 impl SelectionPreferences {
@@ -106,6 +126,12 @@ impl SelectionPreferences {
         self
     }
 
+    /// Set how the payee's routing hints are weighed.
+    pub fn with_payee_hint_policy(mut self, policy: PayeeHintPolicy) -> Self {
+        self.payee_hint_policy = policy;
+        self
+    }
+
     /// Check if a method is excluded.
     pub fn is_excluded(&self, method: &MethodId) -> bool {
         self.excluded_methods.iter().any(|m| m.0 == method.0)
@@ -0,0 +1,158 @@
+//! Lightning Route Probing
+//!
+//! Probes Lightning liquidity before selection and caches the outcome
+//! briefly, so repeated selections for the same payee and amount do not
+//! query the node each time.
+
+use crate::methods::{LightningExecutor, RouteProbe};
+use crate::Result;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Default time a probe result is reused, in seconds.
+pub const DEFAULT_PROBE_TTL_SECS: i64 = 60;
+
+/// Probes Lightning routes through the node and caches the results.
+pub struct RouteProber {
+    executor: Arc<dyn LightningExecutor>,
+    ttl_secs: i64,
+    cache: Mutex<HashMap<(String, u64), (RouteProbe, i64)>>,
+}
+
+impl RouteProber {
+    /// Create a prober backed by a Lightning node.
+    pub fn new(executor: Arc<dyn LightningExecutor>) -> Self {
+        Self {
+            executor,
+            ttl_secs: DEFAULT_PROBE_TTL_SECS,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Set how long probe results are reused.
+    pub fn with_ttl_secs(mut self, ttl_secs: i64) -> Self {
+        self.ttl_secs = ttl_secs;
+        self
+    }
+
+    /// Probe a route to `destination` for `amount_sats`, using the cache.
+    pub async fn probe(&self, destination: &str, amount_sats: u64) -> Result<RouteProbe> {
+        self.probe_at(destination, amount_sats, current_timestamp())
+            .await
+    }
+
+    /// Probe a route to `destination` for `amount_sats`, as of `now`.
+    ///
+    /// Errors are not cached.
+    pub async fn probe_at(
+        &self,
+        destination: &str,
+        amount_sats: u64,
+        now: i64,
+    ) -> Result<RouteProbe> {
+        if let Some(probe) = self.cached_at(destination, amount_sats, now) {
+            return Ok(probe);
+        }
+
+        let probe = self
+            .executor
+            .probe_route(destination, amount_sats.saturating_mul(1000))
+            .await?;
+        self.lock_cache()
+            .insert((destination.to_string(), amount_sats), (probe.clone(), now));
+        Ok(probe)
+    }
+
+    /// Get a cached probe result that is still fresh at `now`.
+    pub fn cached_at(&self, destination: &str, amount_sats: u64, now: i64) -> Option<RouteProbe> {
+        let mut cache = self.lock_cache();
+        cache.retain(|_, (_, probed_at)| now - *probed_at < self.ttl_secs);
+        cache
+            .get(&(destination.to_string(), amount_sats))
+            .map(|(probe, _)| probe.clone())
+    }
+
+    /// Forget all cached probe results.
+    pub fn clear(&self) {
+        self.lock_cache().clear();
+    }
+
+    fn lock_cache(&self) -> std::sync::MutexGuard<'_, HashMap<(String, u64), (RouteProbe, i64)>> {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn current_timestamp() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::methods::MockLightningExecutor;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Counts probes so cache hits can be observed.
+    struct CountingNode {
+        probes: AtomicU32,
+        inner: MockLightningExecutor,
+    }
+
+    #[async_trait]
+    impl LightningExecutor for CountingNode {
+        async fn pay_invoice(
+            &self,
+            invoice: &str,
+            amount_msat: Option<u64>,
+            max_fee_msat: Option<u64>,
+        ) -> Result<crate::methods::LightningPaymentResult> {
+            self.inner
+                .pay_invoice(invoice, amount_msat, max_fee_msat)
+                .await
+        }
+
+        async fn decode_invoice(&self, invoice: &str) -> Result<crate::methods::DecodedInvoice> {
+            self.inner.decode_invoice(invoice).await
+        }
+
+        async fn estimate_fee(&self, invoice: &str) -> Result<u64> {
+            self.inner.estimate_fee(invoice).await
+        }
+
+        async fn get_payment(
+            &self,
+            payment_hash: &str,
+        ) -> Result<Option<crate::methods::LightningPaymentResult>> {
+            self.inner.get_payment(payment_hash).await
+        }
+
+        async fn probe_route(&self, destination: &str, amount_msat: u64) -> Result<RouteProbe> {
+            self.probes.fetch_add(1, Ordering::SeqCst);
+            self.inner.probe_route(destination, amount_msat).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_probe_results_are_cached_briefly() {
+        let node = Arc::new(CountingNode {
+            probes: AtomicU32::new(0),
+            inner: MockLightningExecutor::new(),
+        });
+        let prober = RouteProber::new(node.clone()).with_ttl_secs(30);
+
+        let first = prober.probe_at("lnbc1...", 10_000, 1000).await.unwrap();
+        assert!(first.route_found);
+        prober.probe_at("lnbc1...", 10_000, 1020).await.unwrap();
+        assert_eq!(node.probes.load(Ordering::SeqCst), 1);
+
+        // Different amount, and expired entry, both probe again
+        prober.probe_at("lnbc1...", 20_000, 1020).await.unwrap();
+        prober.probe_at("lnbc1...", 10_000, 1030).await.unwrap();
+        assert_eq!(node.probes.load(Ordering::SeqCst), 3);
+    }
+}
@@ -2,11 +2,26 @@
 //!
 //! This module implements the core logic for selecting payment methods.
 
-use super::preferences::{SelectionPreferences, SelectionStrategy};
-use crate::methods::{Amount, PaymentMethodPlugin, PaymentMethodRegistry};
+use super::preferences::{PayeeHintPolicy, SelectionPreferences, SelectionStrategy};
+use super::probe::RouteProber;
+use crate::i18n::{self, Locale, LocalizedMessage};
+use crate::methods::{
+    Amount, PaymentMethodPlugin, PaymentMethodRegistry, RouteProbe, SettlementKind,
+};
+use crate::protocol::{PayeeRoutingHint, PayeeRoutingHints, PayeeStatus};
 use crate::{MethodId, PaykitError, Result, SupportedPayments};
 use std::sync::Arc;
 
+/// Probed routes less likely than this to succeed count as no route.
+const MIN_PROBE_SUCCESS: f64 = 0.1;
+
+/// Bonus for the method the payee currently prefers.
+const PAYEE_PREFERRED_BONUS: f64 = 15.0;
+
+/// Bonus for the payee's first-ranked routing hint under the advisory
+/// policy; kept below the 10-point step between priority list entries.
+const PAYEE_HINT_MAX_BONUS: f64 = 8.0;
+
 /// Result of payment method selection.
 #[derive(Clone, Debug)]
 pub struct SelectionResult {
@@ -18,6 +33,8 @@ pub struct SelectionResult {
     pub score: f64,
     /// Reason for the selection.
     pub reason: String,
+    /// The reason as message codes, for localized display.
+    pub reason_messages: Vec<LocalizedMessage>,
 }
 
 impl SelectionResult {
@@ -27,6 +44,14 @@ impl SelectionResult {
         methods.extend(self.fallbacks.clone());
         methods
     }
+
+    /// The selection reason in the given locale.
+    pub fn localized_reason(&self, locale: Locale) -> String {
+        if self.reason_messages.is_empty() {
+            return self.reason.clone();
+        }
+        i18n::format_all(&self.reason_messages, locale)
+    }
 }
 
 /// Scored method for internal ranking.
@@ -54,21 +79,59 @@ impl std::fmt::Debug for ScoredMethod {
 /// - User preferences
 /// - Amount being paid
 /// - Method capabilities and constraints
+/// - The payee's self-reported status, if set with [`Self::with_payee_status`]
+/// - The payee's routing hints, if set with [`Self::with_payee_hints`]
+///
+/// The registry is shared, not owned: creating a selector for each checkout
+/// from an `Arc<PaymentMethodRegistry>` costs a reference count, and plugins
+/// registered later are seen by every selector.
 pub struct PaymentMethodSelector {
-    registry: PaymentMethodRegistry,
+    registry: Arc<PaymentMethodRegistry>,
+    /// Fresh payee status and the time it is evaluated at.
+    payee_status: Option<(PayeeStatus, i64)>,
+    /// Fresh payee routing hints.
+    payee_hints: Option<PayeeRoutingHints>,
 }
 
 impl PaymentMethodSelector {
-    /// Create a new selector with the given registry.
-    pub fn new(registry: PaymentMethodRegistry) -> Self {
-        Self { registry }
+    /// Create a new selector over the given registry.
+    ///
+    /// Accepts an owned registry or an `Arc` shared with other components.
+    pub fn new(registry: impl Into<Arc<PaymentMethodRegistry>>) -> Self {
+        Self {
+            registry: registry.into(),
+            payee_status: None,
+            payee_hints: None,
+        }
     }
 
     /// Create a selector with the default registry.
     pub fn with_defaults() -> Self {
-        Self {
-            registry: crate::methods::default_registry(),
-        }
+        Self::new(crate::methods::default_registry())
+    }
+
+    /// The registry this selector draws plugins from.
+    pub fn registry(&self) -> &Arc<PaymentMethodRegistry> {
+        &self.registry
+    }
+
+    /// Take the payee's published status into account, evaluated at `now`.
+    ///
+    /// Methods the payee marked unavailable, or every method while it is
+    /// paused or in maintenance, are demoted to last-resort fallbacks; the
+    /// preferred method gets a bonus. An expired status is ignored.
+    pub fn with_payee_status(mut self, status: PayeeStatus, now: i64) -> Self {
+        self.payee_status = status.is_fresh(now).then_some((status, now));
+        self
+    }
+
+    /// Take the payee's published routing hints into account, evaluated at `now`.
+    ///
+    /// How much they count is up to the payer's
+    /// [`SelectionPreferences::payee_hint_policy`]. Expired hints are ignored.
+    pub fn with_payee_hints(mut self, hints: PayeeRoutingHints, now: i64) -> Self {
+        self.payee_hints = hints.is_fresh(now).then_some(hints);
+        self
     }
 
     /// Select the best payment method.
@@ -79,6 +142,47 @@ impl PaymentMethodSelector {
         supported: &SupportedPayments,
         amount: &Amount,
         preferences: &SelectionPreferences,
+    ) -> Result<SelectionResult> {
+        self.select_with_probe(supported, amount, preferences, None)
+    }
+
+    /// Select the best payment method, probing Lightning liquidity first.
+    ///
+    /// The payee's endpoint for a method whose plugin supports route probing
+    /// (Lightning, among the built-ins) is probed through `prober` (which
+    /// caches results briefly). Probe failures, including nodes that cannot
+    /// probe, fall back to plain selection.
+    pub async fn select_probed(
+        &self,
+        supported: &SupportedPayments,
+        amount: &Amount,
+        preferences: &SelectionPreferences,
+        prober: &RouteProber,
+    ) -> Result<SelectionResult> {
+        let probeable = supported.entries.iter().find(|(method_id, _)| {
+            !preferences.is_excluded(method_id)
+                && self
+                    .registry
+                    .get(method_id)
+                    .is_some_and(|plugin| plugin.capabilities().route_probing)
+        });
+        let probe = match (probeable, amount.as_u64()) {
+            (Some((_, endpoint)), Some(sats)) => prober.probe(&endpoint.0, sats).await.ok(),
+            _ => None,
+        };
+        self.select_with_probe(supported, amount, preferences, probe.as_ref())
+    }
+
+    /// Select the best payment method using a Lightning route probe result.
+    ///
+    /// A missing or unreliable route demotes Lightning below other methods;
+    /// a reliable, cheap route promotes it.
+    pub fn select_with_probe(
+        &self,
+        supported: &SupportedPayments,
+        amount: &Amount,
+        preferences: &SelectionPreferences,
+        probe: Option<&RouteProbe>,
     ) -> Result<SelectionResult> {
         // Get available methods from supported payments
         let available: Vec<MethodId> = supported.entries.keys().cloned().collect();
@@ -90,7 +194,7 @@ impl PaymentMethodSelector {
         }
 
         // Score and rank methods
-        let scored = self.score_methods(&available, amount, preferences)?;
+        let scored = self.score_methods(&available, amount, preferences, probe)?;
 
         if scored.is_empty() {
             return Err(PaykitError::Transport(
@@ -102,13 +206,43 @@ impl PaymentMethodSelector {
         let primary = scored[0].clone();
         let fallbacks: Vec<MethodId> = scored[1..].iter().map(|s| s.method_id.clone()).collect();
 
-        let reason = self.format_reason(&primary, preferences);
+        let mut reason_messages = vec![self.format_reason(&primary, preferences)];
+        if let Some((status, now)) = &self.payee_status {
+            if !status.is_accepting(*now) {
+                reason_messages.push(LocalizedMessage::new("selection.payee_paused"));
+            } else if !status.is_method_available(&primary.method_id, *now) {
+                reason_messages.push(
+                    LocalizedMessage::new("selection.payee_unavailable")
+                        .with_param("method", primary.plugin.display_name()),
+                );
+            } else if status.preferred_method.as_ref() == Some(&primary.method_id) {
+                reason_messages.push(LocalizedMessage::new("selection.payee_preferred"));
+            }
+        }
+        if self.payee_hint_rank(&primary.method_id, preferences) == Some(0) {
+            reason_messages.push(LocalizedMessage::new("selection.payee_hint"));
+        }
+        if let Some(probe) = probe {
+            if !probe.route_found || probe.success_probability < MIN_PROBE_SUCCESS {
+                reason_messages.push(LocalizedMessage::new("selection.probe_no_route"));
+            } else if primary.plugin.capabilities().route_probing {
+                reason_messages.push(
+                    LocalizedMessage::new("selection.probe_route")
+                        .with_param(
+                            "success",
+                            format!("{:.0}", probe.success_probability * 100.0),
+                        )
+                        .with_param("fee_sats", probe.expected_fee_msat.div_ceil(1000)),
+                );
+            }
+        }
 
         Ok(SelectionResult {
             primary: primary.method_id,
             fallbacks,
             score: primary.score,
-            reason,
+            reason: i18n::format_all(&reason_messages, Locale::En),
+            reason_messages,
         })
     }
 
@@ -130,8 +264,10 @@ impl PaymentMethodSelector {
         available: &[MethodId],
         amount: &Amount,
         preferences: &SelectionPreferences,
+        probe: Option<&RouteProbe>,
     ) -> Result<Vec<ScoredMethod>> {
         let mut scored: Vec<ScoredMethod> = Vec::new();
+        let config = self.registry.config();
 
         for method_id in available {
             // Skip excluded methods
@@ -150,17 +286,35 @@ impl PaymentMethodSelector {
                 continue;
             }
 
+            // Payee estimates may only tighten the payer's limits
+            let hint = self.payee_hint(method_id, preferences);
+
             // Check confirmation time constraint
             if let Some(max_time) = preferences.max_confirmation_time_secs {
-                if let Some(est_time) = plugin.estimated_confirmation_time() {
-                    if est_time > max_time {
-                        continue;
-                    }
+                let est_time = plugin
+                    .estimated_confirmation_time()
+                    .max(hint.and_then(|h| h.estimated_time_secs));
+                if est_time.is_some_and(|est_time| est_time > max_time) {
+                    continue;
+                }
+            }
+
+            // Check fee constraint
+            if let Some(max_fee) = preferences.max_fee_sats {
+                if hint
+                    .and_then(|h| h.estimated_cost_sats)
+                    .is_some_and(|cost| cost > max_fee)
+                {
+                    continue;
                 }
             }
 
-            // Calculate score
-            let score = self.calculate_score(&plugin, amount, preferences);
+            // Calculate score; configured weights apply whatever the strategy
+            let score = self.calculate_score(&plugin, amount, preferences)
+                + self.score_probe(&plugin, amount, probe)
+                + self.score_payee_status(method_id)
+                + self.score_payee_hint(method_id, preferences)
+                + config.weight_for(method_id, amount);
 
             scored.push(ScoredMethod {
                 method_id: method_id.clone(),
@@ -169,11 +323,28 @@ impl PaymentMethodSelector {
             });
         }
 
-        // Sort by score (descending)
+        // Sort by score (descending); break ties by configured weight, then
+        // by the payee's order, then by method ID so equal scores rank the
+        // same way every time
         scored.sort_by(|a, b| {
             b.score
                 .partial_cmp(&a.score)
                 .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| {
+                    let weight_a = config.weight_for(&a.method_id, amount);
+                    let weight_b = config.weight_for(&b.method_id, amount);
+                    weight_b
+                        .partial_cmp(&weight_a)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .then_with(|| {
+                    let rank_a = self.payee_hint_rank(&a.method_id, preferences);
+                    let rank_b = self.payee_hint_rank(&b.method_id, preferences);
+                    rank_a
+                        .unwrap_or(usize::MAX)
+                        .cmp(&rank_b.unwrap_or(usize::MAX))
+                })
+                .then_with(|| a.method_id.0.cmp(&b.method_id.0))
         });
 
         Ok(scored)
@@ -221,8 +392,7 @@ impl PaymentMethodSelector {
         preferences: &SelectionPreferences,
     ) -> f64 {
         let mut score = 0.0;
-        let method_id = plugin.method_id();
-        let method = method_id.0.as_str();
+        let caps = plugin.capabilities();
 
         // Speed component (up to 15 points)
         if let Some(time) = plugin.estimated_confirmation_time() {
@@ -237,16 +407,16 @@ impl PaymentMethodSelector {
 
         // Amount fit component (up to 15 points)
         if let Some(sats) = amount.as_u64() {
-            if method == "lightning" && sats < 100_000 {
-                score += 15.0; // Lightning preferred for small amounts
-            } else if method == "onchain" && sats >= 100_000 {
+            if caps.is_off_chain() && sats < 100_000 {
+                score += 15.0; // Off-chain preferred for small amounts
+            } else if caps.is_on_chain() && sats >= 100_000 {
                 score += 10.0; // On-chain for larger amounts
             }
         }
 
         // Privacy component (up to 10 points)
-        if preferences.prefer_privacy && method == "lightning" {
-            score += 10.0; // Lightning is more private
+        if preferences.prefer_privacy && caps.is_off_chain() {
+            score += 10.0; // Off-chain leaves no public record
         }
 
         score
@@ -255,14 +425,13 @@ impl PaymentMethodSelector {
     /// Cost-optimized scoring.
     fn score_cost_optimized(&self, plugin: &Arc<dyn PaymentMethodPlugin>, amount: &Amount) -> f64 {
         let mut score = 0.0;
-        let method_id = plugin.method_id();
-        let method = method_id.0.as_str();
+        let caps = plugin.capabilities();
 
-        // Lightning typically has lower fees for small amounts
+        // Off-chain typically has lower fees for small amounts
         if let Some(sats) = amount.as_u64() {
-            if method == "lightning" && sats < 100_000 {
-                score += 40.0; // Lightning for small amounts
-            } else if method == "onchain" && sats >= 500_000 {
+            if caps.is_off_chain() && sats < 100_000 {
+                score += 40.0; // Off-chain for small amounts
+            } else if caps.is_on_chain() && sats >= 500_000 {
                 // On-chain becomes cost-effective for large amounts
                 score += 30.0;
             }
@@ -293,13 +462,10 @@ impl PaymentMethodSelector {
 
     /// Privacy-optimized scoring.
     fn score_privacy_optimized(&self, plugin: &Arc<dyn PaymentMethodPlugin>) -> f64 {
-        let method_id = plugin.method_id();
-        let method = method_id.0.as_str();
-
-        match method {
-            "lightning" => 40.0, // Off-chain, no public record
-            "onchain" => 10.0,   // Public blockchain
-            _ => 20.0,           // Unknown
+        match plugin.capabilities().settlement {
+            SettlementKind::OffChain => 40.0, // No public record
+            SettlementKind::OnChain => 10.0,  // Public blockchain
+            SettlementKind::Other => 20.0,    // Unknown
         }
     }
 
@@ -331,52 +497,127 @@ impl PaymentMethodSelector {
             return 0.0;
         };
 
-        let method_id = plugin.method_id();
-        let method = method_id.0.as_str();
         let thresholds = &preferences.amount_thresholds;
 
-        if method == "lightning" {
-            if thresholds.prefers_lightning(sats) {
-                10.0
-            } else if !thresholds.lightning_viable(sats) {
-                -50.0 // Penalize if not viable
-            } else {
-                0.0
+        match plugin.capabilities().settlement {
+            SettlementKind::OffChain => {
+                if thresholds.prefers_lightning(sats) {
+                    10.0
+                } else if !thresholds.lightning_viable(sats) {
+                    -50.0 // Penalize if not viable
+                } else {
+                    0.0
+                }
             }
-        } else if method == "onchain" {
-            if thresholds.prefers_onchain(sats) {
-                10.0
-            } else if !thresholds.onchain_viable(sats) {
-                -50.0 // Penalize if not viable
-            } else {
-                0.0
+            SettlementKind::OnChain => {
+                if thresholds.prefers_onchain(sats) {
+                    10.0
+                } else if !thresholds.onchain_viable(sats) {
+                    -50.0 // Penalize if not viable
+                } else {
+                    0.0
+                }
             }
-        } else {
-            0.0
+            SettlementKind::Other => 0.0,
         }
     }
 
-    /// Format a human-readable reason for the selection.
-    fn format_reason(&self, selected: &ScoredMethod, preferences: &SelectionPreferences) -> String {
-        let method_name = selected.plugin.display_name();
+    /// Liquidity adjustment from a route probe, for methods that can be probed.
+    fn score_probe(
+        &self,
+        plugin: &Arc<dyn PaymentMethodPlugin>,
+        amount: &Amount,
+        probe: Option<&RouteProbe>,
+    ) -> f64 {
+        let Some(probe) = probe else {
+            return 0.0;
+        };
+        if !plugin.capabilities().route_probing {
+            return 0.0;
+        }
+        if !probe.route_found || probe.success_probability < MIN_PROBE_SUCCESS {
+            return -100.0; // Keep only as a last-resort fallback
+        }
 
-        match preferences.strategy {
-            SelectionStrategy::Balanced => {
-                format!("Selected {} as best balanced option", method_name)
-            }
-            SelectionStrategy::CostOptimized => {
-                format!("Selected {} for lowest fees", method_name)
-            }
-            SelectionStrategy::SpeedOptimized => {
-                format!("Selected {} for fastest confirmation", method_name)
-            }
-            SelectionStrategy::PrivacyOptimized => {
-                format!("Selected {} for best privacy", method_name)
-            }
-            SelectionStrategy::PriorityList => {
-                format!("Selected {} from priority list", method_name)
-            }
+        // Reliability: up to 20 points either side of an even chance
+        let mut score = (probe.success_probability - 0.5) * 40.0;
+
+        // Fees: one point per 0.1% of the amount, capped
+        if let Some(sats) = amount.as_u64().filter(|s| *s > 0) {
+            let fee_ratio = probe.expected_fee_msat as f64 / (sats as f64 * 1000.0);
+            score -= (fee_ratio * 1000.0).min(30.0);
         }
+
+        score
+    }
+
+    /// Adjustment from the payee's self-reported status.
+    fn score_payee_status(&self, method_id: &MethodId) -> f64 {
+        let Some((status, now)) = &self.payee_status else {
+            return 0.0;
+        };
+        if !status.is_method_available(method_id, *now) {
+            return -100.0; // Keep only as a last-resort fallback
+        }
+        if status.preferred_method.as_ref() == Some(method_id) {
+            return PAYEE_PREFERRED_BONUS;
+        }
+        0.0
+    }
+
+    /// The payee's hint for a method, unless the payer ignores hints.
+    fn payee_hint(
+        &self,
+        method_id: &MethodId,
+        preferences: &SelectionPreferences,
+    ) -> Option<&PayeeRoutingHint> {
+        if preferences.payee_hint_policy == PayeeHintPolicy::Ignore {
+            return None;
+        }
+        self.payee_hints.as_ref()?.hint_for(method_id)
+    }
+
+    /// Position of a method in the payee's preferred order, unless ignored.
+    fn payee_hint_rank(
+        &self,
+        method_id: &MethodId,
+        preferences: &SelectionPreferences,
+    ) -> Option<usize> {
+        if preferences.payee_hint_policy == PayeeHintPolicy::Ignore {
+            return None;
+        }
+        self.payee_hints.as_ref()?.rank_of(method_id)
+    }
+
+    /// Bonus from the payee's preferred order under the advisory policy.
+    fn score_payee_hint(&self, method_id: &MethodId, preferences: &SelectionPreferences) -> f64 {
+        if preferences.payee_hint_policy != PayeeHintPolicy::Advisory {
+            return 0.0;
+        }
+        let (Some(hints), Some(rank)) = (
+            &self.payee_hints,
+            self.payee_hint_rank(method_id, preferences),
+        ) else {
+            return 0.0;
+        };
+        let count = hints.hints.len() as f64;
+        PAYEE_HINT_MAX_BONUS * (count - rank as f64) / count
+    }
+
+    /// Describe why the method was selected.
+    fn format_reason(
+        &self,
+        selected: &ScoredMethod,
+        preferences: &SelectionPreferences,
+    ) -> LocalizedMessage {
+        let code = match preferences.strategy {
+            SelectionStrategy::Balanced => "selection.balanced",
+            SelectionStrategy::CostOptimized => "selection.cost_optimized",
+            SelectionStrategy::SpeedOptimized => "selection.speed_optimized",
+            SelectionStrategy::PrivacyOptimized => "selection.privacy_optimized",
+            SelectionStrategy::PriorityList => "selection.priority_list",
+        };
+        LocalizedMessage::new(code).with_param("method", selected.plugin.display_name())
     }
 }
 
@@ -392,6 +633,58 @@ mod tests {
         SupportedPayments { entries }
     }
 
+    #[test]
+    fn test_selector_shares_registry() {
+        let registry = Arc::new(PaymentMethodRegistry::new());
+        let selector = PaymentMethodSelector::new(registry.clone());
+        let supported = create_test_supported();
+        let prefs = SelectionPreferences::balanced();
+        assert!(selector
+            .select(&supported, &Amount::sats(10000), &prefs)
+            .is_err());
+
+        // Plugins registered after construction are visible without rebuilding
+        registry.register(Box::new(crate::methods::LightningPlugin::new()));
+        let result = selector
+            .select(&supported, &Amount::sats(10000), &prefs)
+            .unwrap();
+        assert_eq!(result.primary.0, "lightning");
+        assert!(Arc::ptr_eq(selector.registry(), &registry));
+    }
+
+    #[test]
+    fn test_registry_weights_apply_across_strategies() {
+        let registry = Arc::new(crate::methods::default_registry());
+        registry
+            .set_method_weight(
+                &MethodId::onchain(),
+                crate::methods::MethodWeight::new(100.0).with_amount_range(Some(5_000), None),
+            )
+            .unwrap();
+        let selector = PaymentMethodSelector::new(registry.clone());
+        let supported = create_test_supported();
+
+        for prefs in [
+            SelectionPreferences::balanced(),
+            SelectionPreferences::speed_optimized(),
+        ] {
+            let result = selector
+                .select(&supported, &Amount::sats(10_000), &prefs)
+                .unwrap();
+            assert_eq!(result.primary.0, "onchain");
+        }
+
+        // Below the configured range the strategy decides again
+        let result = selector
+            .select(
+                &supported,
+                &Amount::sats(1_000),
+                &SelectionPreferences::balanced(),
+            )
+            .unwrap();
+        assert_eq!(result.primary.0, "lightning");
+    }
+
     #[test]
     fn test_select_balanced_small_amount() {
         let selector = PaymentMethodSelector::with_defaults();
@@ -486,6 +779,127 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_select_with_probe() {
+        let selector = PaymentMethodSelector::with_defaults();
+        let supported = create_test_supported();
+        let amount = Amount::sats(10000);
+        let prefs = SelectionPreferences::balanced();
+
+        // No route demotes lightning to a fallback
+        let result = selector
+            .select_with_probe(&supported, &amount, &prefs, Some(&RouteProbe::no_route()))
+            .unwrap();
+        assert_eq!(result.primary.0, "onchain");
+        assert_eq!(result.fallbacks, vec![MethodId("lightning".into())]);
+
+        // A reliable route keeps lightning first
+        let probe = RouteProbe::found(0.9, 12_000, 2);
+        let result = selector
+            .select_with_probe(&supported, &amount, &prefs, Some(&probe))
+            .unwrap();
+        assert_eq!(result.primary.0, "lightning");
+        assert!(result.reason.contains("90% success"));
+        assert!(result
+            .localized_reason(Locale::De)
+            .ends_with("(Routentest: 90% Erfolg, ~12 sat Gebühr)"));
+    }
+
+    #[tokio::test]
+    async fn test_select_probed_uses_node() {
+        use crate::methods::MockLightningExecutor;
+
+        let selector = PaymentMethodSelector::with_defaults();
+        let supported = create_test_supported();
+        let amount = Amount::sats(10000);
+        let prefs = SelectionPreferences::balanced();
+
+        let healthy = RouteProber::new(Arc::new(MockLightningExecutor::new()));
+        let result = selector
+            .select_probed(&supported, &amount, &prefs, &healthy)
+            .await
+            .unwrap();
+        assert_eq!(result.primary.0, "lightning");
+
+        let drained = RouteProber::new(Arc::new(MockLightningExecutor::failing()));
+        let result = selector
+            .select_probed(&supported, &amount, &prefs, &drained)
+            .await
+            .unwrap();
+        assert_eq!(result.primary.0, "onchain");
+    }
+
+    #[test]
+    fn test_select_with_payee_status() {
+        let supported = create_test_supported();
+        let amount = Amount::sats(10000);
+        let prefs = SelectionPreferences::balanced();
+        let lightning = MethodId("lightning".into());
+
+        // Unavailable lightning is demoted, not dropped
+        let status = PayeeStatus::new(100).with_unavailable(lightning.clone());
+        let result = PaymentMethodSelector::with_defaults()
+            .with_payee_status(status.clone(), 200)
+            .select(&supported, &amount, &prefs)
+            .unwrap();
+        assert_eq!(result.primary.0, "onchain");
+        assert_eq!(result.fallbacks, vec![lightning.clone()]);
+
+        // Once the status expires it no longer applies
+        let result = PaymentMethodSelector::with_defaults()
+            .with_payee_status(status, 100 + 3601)
+            .select(&supported, &amount, &prefs)
+            .unwrap();
+        assert_eq!(result.primary, lightning);
+
+        // A paused payee still yields a ranking, with the reason noted
+        let paused = PayeeStatus::new(100).with_accepting(false);
+        let result = PaymentMethodSelector::with_defaults()
+            .with_payee_status(paused, 200)
+            .select(&supported, &amount, &prefs)
+            .unwrap();
+        assert!(result.reason.contains("not accepting payments"));
+    }
+
+    #[test]
+    fn test_select_with_payee_hints() {
+        let supported = create_test_supported();
+        // Neither method gets an amount-fit bonus at this amount
+        let amount = Amount::sats(500_000);
+        let lightning = MethodId::lightning();
+        let onchain = MethodId::onchain();
+        let hints = PayeeRoutingHints::new(100)
+            .with_hint(PayeeRoutingHint::new(onchain.clone(), 0))
+            .with_hint(PayeeRoutingHint::new(lightning.clone(), 1).with_estimated_cost(500));
+        let selector = PaymentMethodSelector::with_defaults().with_payee_hints(hints, 200);
+        let equal = SelectionPreferences::with_priority_list(Vec::new());
+
+        // Equal scores follow the payee's order unless hints are ignored
+        for policy in [PayeeHintPolicy::Advisory, PayeeHintPolicy::TieBreak] {
+            let prefs = equal.clone().with_payee_hint_policy(policy);
+            let result = selector.select(&supported, &amount, &prefs).unwrap();
+            assert_eq!(result.primary, onchain);
+        }
+        let prefs = equal
+            .clone()
+            .with_payee_hint_policy(PayeeHintPolicy::Ignore);
+        let result = selector.select(&supported, &amount, &prefs).unwrap();
+        assert_eq!(result.primary, lightning);
+
+        // The payer's priority list outranks the payee's order
+        let prefs =
+            SelectionPreferences::with_priority_list(vec![lightning.clone(), onchain.clone()]);
+        let result = selector.select(&supported, &amount, &prefs).unwrap();
+        assert_eq!(result.primary, lightning);
+
+        // Payee cost estimates are held against the payer's fee limit
+        let prefs = equal.with_max_fee(100);
+        let result = selector.select(&supported, &amount, &prefs).unwrap();
+        assert_eq!(result.primary, onchain);
+        assert!(result.fallbacks.is_empty());
+        assert!(result.reason.contains("ranked first by the payee"));
+    }
+
     #[test]
     fn test_selection_result_all_methods() {
         let result = SelectionResult {
@@ -493,6 +907,7 @@ mod tests {
             fallbacks: vec![MethodId("onchain".into())],
             score: 100.0,
             reason: "Test".into(),
+            reason_messages: Vec::new(),
         };
 
         let all = result.all_methods();
@@ -0,0 +1,514 @@
+//! Standing Orders
+//!
+//! A standing order is a recurring payment set up entirely on the payer's
+//! side ("send 50k sats to X every Friday"). Unlike a subscription, the
+//! payee does not sign an agreement or run any billing logic: it only needs
+//! to publish endpoints like for any other payment.
+//!
+//! A [`StandingOrder`] holds the payee, the amount, an [`OrderSchedule`] and
+//! an optional method preference. The host application's scheduler calls
+//! [`StandingOrderScheduler::run_at`] periodically; due orders are paid
+//! through the regular method registry, trying the payer's preferred methods
+//! first and falling back to the others the payee supports.
+//!
+//! Missed occurrences are not caught up: an order that was due several
+//! times while the app was offline pays once and moves on to the next
+//! occurrence after the run. A failed payment leaves the order due, so the
+//! next tick retries it. A payment whose outcome is unknown because a
+//! plugin failed mid-payment is reported as [`OrderOutcome::Unresolved`]
+//! and should not be retried until the wallet shows whether it went out.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use paykit_lib::standing_orders::{OrderSchedule, StandingOrder, StandingOrderScheduler};
+//!
+//! let mut order = StandingOrder::new("rent", payee_z32, 50_000, OrderSchedule::Weekly { weekday: 4 }, now)?
+//!     .with_methods(vec![MethodId::lightning()]);
+//! let scheduler = StandingOrderScheduler::new(registry);
+//!
+//! // Called periodically by the app's scheduler
+//! let supported = get_payment_list(&reader, &payee).await?;
+//! if let OrderOutcome::Paid(execution) = scheduler.run_at(&mut order, &supported, now).await? {
+//!     println!("paid via {}", execution.method_id.0);
+//! }
+//! ```
+
+use crate::methods::{Amount, PaymentExecution, PaymentMethodRegistry};
+use crate::selection::{pay_with_fallback, Fallback, SelectionPreferences};
+use crate::{MethodId, PaykitError, Result, SupportedPayments};
+use chrono::{Datelike, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// When a standing order pays.
+///
+/// Weekly and monthly occurrences fall at midnight UTC.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OrderSchedule {
+    /// Every `every_secs` seconds from the order's start.
+    Interval {
+        /// Time between payments, in seconds.
+        every_secs: i64,
+    },
+    /// Once a week.
+    Weekly {
+        /// Day of the week, 0 (Monday) to 6 (Sunday).
+        weekday: u8,
+    },
+    /// Once a month.
+    Monthly {
+        /// Day of the month, 1 to 31; clamped to the last day of shorter months.
+        day: u8,
+    },
+}
+
+impl OrderSchedule {
+    /// Check the schedule has occurrences.
+    pub fn validate(&self) -> Result<()> {
+        let valid = match self {
+            OrderSchedule::Interval { every_secs } => *every_secs > 0,
+            OrderSchedule::Weekly { weekday } => *weekday <= 6,
+            OrderSchedule::Monthly { day } => (1..=31).contains(day),
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(PaykitError::ValidationFailed(format!(
+                "Invalid standing order schedule: {:?}",
+                self
+            )))
+        }
+    }
+
+    /// First occurrence at or after `at`, for an order starting at `starts_at`.
+    pub fn first_at_or_after(&self, at: i64, starts_at: i64) -> i64 {
+        let at = at.max(starts_at);
+        match self {
+            OrderSchedule::Interval { every_secs } => {
+                let every = (*every_secs).max(1);
+                let periods = (at - starts_at + every - 1).div_euclid(every);
+                starts_at + periods * every
+            }
+            OrderSchedule::Weekly { weekday } => {
+                let midnight = at.div_euclid(SECONDS_PER_DAY) * SECONDS_PER_DAY;
+                let day = utc_datetime(midnight).weekday().num_days_from_monday() as i64;
+                let mut offset = (*weekday as i64 - day).rem_euclid(7);
+                if offset == 0 && midnight < at {
+                    offset = 7;
+                }
+                midnight + offset * SECONDS_PER_DAY
+            }
+            OrderSchedule::Monthly { day } => {
+                let date = utc_datetime(at).date_naive();
+                let this_month = billing_date(date.year(), date.month(), *day);
+                if date_start(this_month) >= at {
+                    date_start(this_month)
+                } else {
+                    let (year, month) = if date.month() == 12 {
+                        (date.year() + 1, 1)
+                    } else {
+                        (date.year(), date.month() + 1)
+                    };
+                    date_start(billing_date(year, month, *day))
+                }
+            }
+        }
+    }
+
+    /// First occurrence strictly after `after`.
+    pub fn next_after(&self, after: i64, starts_at: i64) -> i64 {
+        self.first_at_or_after(after.saturating_add(1), starts_at)
+    }
+}
+
+fn utc_datetime(at: i64) -> chrono::DateTime<Utc> {
+    Utc.timestamp_opt(at, 0).single().unwrap_or_default()
+}
+
+fn date_start(date: NaiveDate) -> i64 {
+    date.and_hms_opt(0, 0, 0)
+        .map_or(0, |dt| dt.and_utc().timestamp())
+}
+
+fn billing_date(year: i32, month: u32, day: u8) -> NaiveDate {
+    (1..=day as u32)
+        .rev()
+        .find_map(|d| NaiveDate::from_ymd_opt(year, month, d))
+        .unwrap_or_default()
+}
+
+/// A recurring payment set up by the payer alone.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StandingOrder {
+    /// Unique order identifier.
+    pub order_id: String,
+    /// Payee public key (z-base-32).
+    pub payee: String,
+    /// Amount paid on each occurrence, in satoshis.
+    pub amount_sats: u64,
+    /// When the order pays.
+    pub schedule: OrderSchedule,
+    /// Preferred methods, highest priority first. When set, only these
+    /// methods are used; otherwise any method the payee supports is.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub methods: Vec<MethodId>,
+    /// Note recorded on every payment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+    /// When the schedule starts (unix seconds).
+    pub starts_at: i64,
+    /// No payments after this moment, if set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ends_at: Option<i64>,
+    /// Stop after this many payments, if set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_runs: Option<u32>,
+    /// Next occurrence to pay.
+    pub next_due_at: i64,
+    /// Payments made so far.
+    #[serde(default)]
+    pub runs: u32,
+    /// When the last payment was made.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_run_at: Option<i64>,
+    /// Whether the order is paused.
+    #[serde(default)]
+    pub paused: bool,
+}
+
+impl StandingOrder {
+    /// Create an order whose first payment is the first occurrence at or after `starts_at`.
+    pub fn new(
+        order_id: impl Into<String>,
+        payee: impl Into<String>,
+        amount_sats: u64,
+        schedule: OrderSchedule,
+        starts_at: i64,
+    ) -> Result<Self> {
+        let order = Self {
+            order_id: order_id.into(),
+            payee: payee.into(),
+            amount_sats,
+            next_due_at: schedule.first_at_or_after(starts_at, starts_at),
+            schedule,
+            methods: Vec::new(),
+            memo: None,
+            starts_at,
+            ends_at: None,
+            max_runs: None,
+            runs: 0,
+            last_run_at: None,
+            paused: false,
+        };
+        order.validate()?;
+        Ok(order)
+    }
+
+    /// Restrict the order to these methods, highest priority first.
+    pub fn with_methods(mut self, methods: Vec<MethodId>) -> Self {
+        self.methods = methods;
+        self
+    }
+
+    /// Set the note recorded on every payment.
+    pub fn with_memo(mut self, memo: impl Into<String>) -> Self {
+        self.memo = Some(memo.into());
+        self
+    }
+
+    /// Stop paying after `ends_at`.
+    pub fn with_end(mut self, ends_at: i64) -> Self {
+        self.ends_at = Some(ends_at);
+        self
+    }
+
+    /// Stop after `max_runs` payments.
+    pub fn with_max_runs(mut self, max_runs: u32) -> Self {
+        self.max_runs = Some(max_runs);
+        self
+    }
+
+    /// Check the order can ever pay.
+    pub fn validate(&self) -> Result<()> {
+        if self.order_id.trim().is_empty() {
+            return Err(PaykitError::ValidationFailed(
+                "Standing order ID must not be empty".to_string(),
+            ));
+        }
+        if self.payee.trim().is_empty() {
+            return Err(PaykitError::ValidationFailed(
+                "Standing order payee must not be empty".to_string(),
+            ));
+        }
+        if self.amount_sats == 0 {
+            return Err(PaykitError::ValidationFailed(
+                "Standing order amount must be positive".to_string(),
+            ));
+        }
+        if self.ends_at.is_some_and(|end| end < self.starts_at) {
+            return Err(PaykitError::ValidationFailed(
+                "Standing order must end after it starts".to_string(),
+            ));
+        }
+        self.methods.iter().try_for_each(MethodId::validate)?;
+        self.schedule.validate()
+    }
+
+    /// Whether the order will never pay again.
+    pub fn is_finished(&self) -> bool {
+        self.max_runs.is_some_and(|max| self.runs >= max)
+            || self.ends_at.is_some_and(|end| self.next_due_at > end)
+    }
+
+    /// Why the order should not pay at `now`, or `None` if it is due.
+    pub fn skip_reason(&self, now: i64) -> Option<OrderSkip> {
+        if self.is_finished() {
+            Some(OrderSkip::Finished)
+        } else if self.paused {
+            Some(OrderSkip::Paused)
+        } else if now < self.next_due_at {
+            Some(OrderSkip::NotDue {
+                next_due_at: self.next_due_at,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Whether the order should pay at `now`.
+    pub fn is_due(&self, now: i64) -> bool {
+        self.skip_reason(now).is_none()
+    }
+
+    /// Record a payment made at `now` and move to the next occurrence after it.
+    pub fn record_run(&mut self, now: i64) {
+        self.runs += 1;
+        self.last_run_at = Some(now);
+        self.next_due_at = self.schedule.next_after(now, self.starts_at);
+    }
+}
+
+/// Why a standing order did not pay.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum OrderSkip {
+    /// The next occurrence is in the future.
+    NotDue {
+        /// When the next payment is due.
+        next_due_at: i64,
+    },
+    /// The order is paused.
+    Paused,
+    /// The order reached its end date or run limit.
+    Finished,
+}
+
+impl OrderSkip {
+    /// Human-readable description.
+    pub fn describe(&self) -> String {
+        match self {
+            OrderSkip::NotDue { next_due_at } => format!("next payment due at {}", next_due_at),
+            OrderSkip::Paused => "order is paused".to_string(),
+            OrderSkip::Finished => "order has finished".to_string(),
+        }
+    }
+}
+
+/// Result of running a standing order.
+#[derive(Clone, Debug)]
+pub enum OrderOutcome {
+    /// The payment succeeded and the order moved to its next occurrence.
+    Paid(PaymentExecution),
+    /// Every usable method failed; the order stays due. Holds the last attempt.
+    Failed(PaymentExecution),
+    /// A method failed in a way that does not rule out the payment having
+    /// been sent, and no other method was tried. The order stays due; check
+    /// the wallet before running it again. Holds that attempt.
+    Unresolved(PaymentExecution),
+    /// No payment was attempted.
+    Skipped(OrderSkip),
+}
+
+/// Pays due standing orders through a method registry.
+pub struct StandingOrderScheduler {
+    registry: Arc<PaymentMethodRegistry>,
+}
+
+impl StandingOrderScheduler {
+    /// Create a scheduler paying through `registry`.
+    pub fn new(registry: impl Into<Arc<PaymentMethodRegistry>>) -> Self {
+        Self {
+            registry: registry.into(),
+        }
+    }
+
+    /// Orders due at `now`, earliest first.
+    pub fn due<'a>(&self, orders: &'a [StandingOrder], now: i64) -> Vec<&'a StandingOrder> {
+        let mut due: Vec<&StandingOrder> =
+            orders.iter().filter(|order| order.is_due(now)).collect();
+        due.sort_by_key(|order| order.next_due_at);
+        due
+    }
+
+    /// Pay `order` if it is due at `now`, using the payee's `supported` methods.
+    ///
+    /// Methods are tried in selection order until one succeeds, stopping
+    /// early at an error that may have come after sending; the order is only
+    /// advanced on success.
+    pub async fn run_at(
+        &self,
+        order: &mut StandingOrder,
+        supported: &SupportedPayments,
+        now: i64,
+    ) -> Result<OrderOutcome> {
+        if let Some(skip) = order.skip_reason(now) {
+            return Ok(OrderOutcome::Skipped(skip));
+        }
+
+        let amount = Amount::sats(order.amount_sats);
+        let mut preferences = SelectionPreferences::with_priority_list(order.methods.clone());
+        if !order.methods.is_empty() {
+            for method_id in supported.entries.keys() {
+                if !order.methods.contains(method_id) {
+                    preferences = preferences.exclude_method(method_id.clone());
+                }
+            }
+        }
+        let metadata = serde_json::json!({
+            "standing_order": order.order_id,
+            "memo": order.memo,
+        });
+        let fallback = pay_with_fallback(
+            &self.registry,
+            supported,
+            &amount,
+            &preferences,
+            &metadata,
+            || {},
+        )
+        .await?;
+
+        match fallback {
+            Fallback::Paid(execution) => {
+                order.record_run(now);
+                Ok(OrderOutcome::Paid(execution))
+            }
+            Fallback::Failed(execution) => Ok(OrderOutcome::Failed(execution)),
+            Fallback::Unresolved(execution) => Ok(OrderOutcome::Unresolved(execution)),
+            Fallback::Unavailable => Err(PaykitError::Transport(
+                "No payment methods available for standing order".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EndpointData;
+
+    // 2024-01-05 00:00:00 UTC, a Friday
+    const FRIDAY: i64 = 1_704_412_800;
+
+    #[test]
+    fn test_weekly_and_monthly_occurrences() {
+        let friday = OrderSchedule::Weekly { weekday: 4 };
+        assert_eq!(friday.first_at_or_after(FRIDAY, FRIDAY), FRIDAY);
+        assert_eq!(
+            friday.next_after(FRIDAY, FRIDAY),
+            FRIDAY + 7 * SECONDS_PER_DAY
+        );
+        assert_eq!(
+            friday.first_at_or_after(FRIDAY + 60, FRIDAY),
+            FRIDAY + 7 * SECONDS_PER_DAY
+        );
+
+        // The 31st falls on February 29th in a leap year
+        let end_of_month = OrderSchedule::Monthly { day: 31 };
+        let feb_1 = date_start(NaiveDate::from_ymd_opt(2024, 2, 1).unwrap());
+        assert_eq!(
+            end_of_month.first_at_or_after(feb_1, feb_1),
+            date_start(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap())
+        );
+
+        assert!(OrderSchedule::Weekly { weekday: 7 }.validate().is_err());
+        assert!(OrderSchedule::Interval { every_secs: 0 }
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_order_lifecycle() {
+        let mut order = StandingOrder::new(
+            "rent",
+            "payee",
+            50_000,
+            OrderSchedule::Weekly { weekday: 4 },
+            FRIDAY - 3600,
+        )
+        .unwrap()
+        .with_max_runs(2);
+        assert_eq!(order.next_due_at, FRIDAY);
+        assert!(!order.is_due(FRIDAY - 1));
+        assert!(order.is_due(FRIDAY));
+
+        // Missed occurrences are not caught up
+        order.record_run(FRIDAY + 15 * SECONDS_PER_DAY);
+        assert_eq!(order.next_due_at, FRIDAY + 21 * SECONDS_PER_DAY);
+
+        order.paused = true;
+        assert_eq!(
+            order.skip_reason(order.next_due_at),
+            Some(OrderSkip::Paused)
+        );
+        order.paused = false;
+        order.record_run(order.next_due_at);
+        assert_eq!(order.skip_reason(i64::MAX), Some(OrderSkip::Finished));
+
+        assert!(StandingOrder::new("x", "payee", 0, OrderSchedule::Monthly { day: 1 }, 0).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_pays_preferred_method() {
+        let mut supported = SupportedPayments::default();
+        supported.entries.insert(
+            MethodId::onchain(),
+            EndpointData("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".into()),
+        );
+        supported
+            .entries
+            .insert(MethodId::lightning(), EndpointData("lnbc1payee".into()));
+
+        let scheduler = StandingOrderScheduler::new(crate::methods::default_registry());
+        let mut order = StandingOrder::new(
+            "savings",
+            "payee",
+            5_000,
+            OrderSchedule::Interval { every_secs: 3600 },
+            1_000,
+        )
+        .unwrap()
+        .with_methods(vec![MethodId::onchain()]);
+
+        assert!(matches!(
+            scheduler.run_at(&mut order, &supported, 999).await.unwrap(),
+            OrderOutcome::Skipped(OrderSkip::NotDue { next_due_at: 1_000 })
+        ));
+        assert_eq!(scheduler.due(std::slice::from_ref(&order), 1_000).len(), 1);
+
+        match scheduler
+            .run_at(&mut order, &supported, 1_000)
+            .await
+            .unwrap()
+        {
+            OrderOutcome::Paid(execution) => assert_eq!(execution.method_id, MethodId::onchain()),
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+        assert_eq!(order.runs, 1);
+        assert_eq!(order.next_due_at, 4_600);
+    }
+}
@@ -5,6 +5,8 @@
 //! - Test fixtures for common scenarios
 //! - Assertion helpers for payment verification
 //! - Simulated payment network for E2E testing
+//! - Deterministic simulation primitives (seeded RNG, virtual clock,
+//!   scripted Lightning node) in [`sim`]
 //!
 //! ## Usage
 //!
@@ -27,6 +29,7 @@
 mod assertions;
 mod fixtures;
 mod mock_network;
+pub mod sim;
 
 pub use fixtures::{
     create_test_keypair, random_payment_hash, random_preimage, test_address, test_invoice,
@@ -0,0 +1,589 @@
+//! Deterministic simulation primitives.
+//!
+//! Seeded randomness, virtual time and a scripted Lightning node. Every
+//! random choice a scenario makes comes from one [`SimRng`] and every
+//! timestamp from one [`SimClock`], so a failing run replays exactly from
+//! its seed.
+//!
+//! [`SimLightningNode`] is a [`LightningExecutor`] that keeps a ledger and
+//! injects failures from a [`FaultPlan`]: refused payments, payments that
+//! settle while the caller sees a timeout, and payments that stay in flight
+//! before settling or failing. Those are the outcomes that leave a payer
+//! unsure whether money moved.
+//!
+//! ```rust,ignore
+//! use paykit_lib::test_utils::sim::{FaultPlan, SimClock, SimFault, SimLightningNode};
+//!
+//! let clock = SimClock::new(1_700_000_000);
+//! let node = SimLightningNode::new(42, clock.clone(), 1_000_000)
+//!     .with_faults(FaultPlan::new().at(0, SimFault::LostResponse));
+//! let invoice = node.create_invoice("bob", Some(10_000_000), "order 1");
+//!
+//! // The first payment settles, but the caller only sees a timeout
+//! assert!(node.pay_invoice(&invoice, None, None).await.is_err());
+//! assert_eq!(node.settled().len(), 1);
+//! ```
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+
+use crate::methods::{
+    DecodedInvoice, LightningExecutor, LightningPaymentResult, LightningPaymentStatus,
+};
+use crate::{PaykitError, Result};
+
+/// Seeded pseudo-random generator (SplitMix64).
+///
+/// The output depends only on the seed, on every platform and release.
+#[derive(Clone, Debug)]
+pub struct SimRng {
+    state: u64,
+}
+
+impl SimRng {
+    /// Create a generator from `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `lo..hi`. Returns `lo` when the range is empty.
+    pub fn range(&mut self, lo: u64, hi: u64) -> u64 {
+        if hi <= lo {
+            return lo;
+        }
+        lo + self.next_u64() % (hi - lo)
+    }
+
+    /// `true` with probability `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        let unit = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        unit < p
+    }
+
+    /// A random element of `items`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `items` is empty.
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.range(0, items.len() as u64) as usize]
+    }
+
+    /// 32 random bytes.
+    pub fn bytes32(&mut self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for chunk in bytes.chunks_mut(8) {
+            chunk.copy_from_slice(&self.next_u64().to_le_bytes());
+        }
+        bytes
+    }
+
+    /// An independent generator seeded from this one.
+    ///
+    /// Give each component its own fork so adding a draw in one does not
+    /// shift the sequence another sees.
+    pub fn fork(&mut self) -> SimRng {
+        SimRng::new(self.next_u64())
+    }
+}
+
+/// Virtual clock in unix seconds, shared by clones.
+#[derive(Clone, Debug)]
+pub struct SimClock {
+    now: Arc<AtomicI64>,
+}
+
+impl SimClock {
+    /// Create a clock reading `start`.
+    pub fn new(start: i64) -> Self {
+        Self {
+            now: Arc::new(AtomicI64::new(start)),
+        }
+    }
+
+    /// Current virtual time.
+    pub fn now(&self) -> i64 {
+        self.now.load(Ordering::SeqCst)
+    }
+
+    /// Move time forward by `secs`, returning the new time.
+    pub fn advance(&self, secs: i64) -> i64 {
+        self.now.fetch_add(secs, Ordering::SeqCst) + secs
+    }
+}
+
+/// A failure injected into a payment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SimFault {
+    /// The node refuses the payment; nothing moves.
+    Reject,
+    /// The payment settles but the caller sees a timeout.
+    LostResponse,
+    /// The payment stays in flight for `secs`, then settles or fails.
+    InFlight {
+        /// Seconds until the payment resolves.
+        secs: i64,
+        /// Whether it settles (`true`) or fails (`false`).
+        settles: bool,
+    },
+}
+
+/// Which payments fail, and how.
+///
+/// Scripted faults apply to a given call (counting from 0) and take
+/// precedence over the random rates.
+#[derive(Clone, Debug, Default)]
+pub struct FaultPlan {
+    scripted: HashMap<u64, SimFault>,
+    reject_rate: f64,
+    lost_response_rate: f64,
+    in_flight_rate: f64,
+    max_in_flight_secs: i64,
+}
+
+impl FaultPlan {
+    /// A plan with no faults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inject `fault` into payment call number `call`.
+    pub fn at(mut self, call: u64, fault: SimFault) -> Self {
+        self.scripted.insert(call, fault);
+        self
+    }
+
+    /// Inject random faults at the given rates (each 0.0 - 1.0).
+    ///
+    /// In-flight payments resolve within `max_in_flight_secs` and settle
+    /// half of the time.
+    pub fn with_rates(
+        mut self,
+        reject: f64,
+        lost_response: f64,
+        in_flight: f64,
+        max_in_flight_secs: i64,
+    ) -> Self {
+        self.reject_rate = reject;
+        self.lost_response_rate = lost_response;
+        self.in_flight_rate = in_flight;
+        self.max_in_flight_secs = max_in_flight_secs.max(1);
+        self
+    }
+
+    /// The longest a payment can stay in flight.
+    pub fn max_in_flight_secs(&self) -> i64 {
+        self.scripted
+            .values()
+            .filter_map(|fault| match fault {
+                SimFault::InFlight { secs, .. } => Some(*secs),
+                _ => None,
+            })
+            .fold(self.max_in_flight_secs, i64::max)
+    }
+
+    fn draw(&self, call: u64, rng: &mut SimRng) -> Option<SimFault> {
+        if let Some(fault) = self.scripted.get(&call) {
+            return Some(*fault);
+        }
+        // One draw per category keeps the sequence stable when a rate is 0
+        let reject = rng.chance(self.reject_rate);
+        let lost = rng.chance(self.lost_response_rate);
+        let in_flight = rng.chance(self.in_flight_rate);
+        let secs = rng.range(1, self.max_in_flight_secs as u64 + 1) as i64;
+        let settles = rng.chance(0.5);
+        if reject {
+            Some(SimFault::Reject)
+        } else if lost {
+            Some(SimFault::LostResponse)
+        } else if in_flight {
+            Some(SimFault::InFlight { secs, settles })
+        } else {
+            None
+        }
+    }
+}
+
+/// A payment recorded by [`SimLightningNode`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimPayment {
+    /// Payee label given to [`SimLightningNode::create_invoice`].
+    pub payee: String,
+    /// Invoice memo.
+    pub memo: String,
+    /// Payment hash, hex-encoded.
+    pub payment_hash: String,
+    /// Preimage, hex-encoded.
+    pub preimage: String,
+    /// Amount paid in millisatoshis.
+    pub amount_msat: u64,
+    /// Routing fee in millisatoshis.
+    pub fee_msat: u64,
+    /// Current status.
+    pub status: LightningPaymentStatus,
+    /// Virtual time the payment was sent.
+    pub sent_at: i64,
+}
+
+#[derive(Clone, Debug)]
+struct SimInvoice {
+    payee: String,
+    memo: String,
+    payment_hash: String,
+    preimage: String,
+    amount_msat: Option<u64>,
+    created_at: i64,
+}
+
+#[derive(Debug)]
+struct InFlight {
+    resolves_at: i64,
+    settles: bool,
+}
+
+#[derive(Debug)]
+struct NodeState {
+    rng: SimRng,
+    faults: FaultPlan,
+    calls: u64,
+    balance_msat: u64,
+    invoices: HashMap<String, SimInvoice>,
+    payments: Vec<SimPayment>,
+    in_flight: HashMap<String, InFlight>,
+}
+
+/// Simulated Lightning node with a ledger and scripted failures.
+///
+/// Invoices created with [`create_invoice`](Self::create_invoice) carry a
+/// real SHA-256 payment hash, so preimage proofs from this node verify.
+/// Like a real node, it refuses to pay an invoice twice.
+pub struct SimLightningNode {
+    clock: SimClock,
+    state: Mutex<NodeState>,
+}
+
+/// Routing fee: 1 sat base plus 1000 ppm.
+const BASE_FEE_MSAT: u64 = 1_000;
+const FEE_RATE_PPM: u64 = 1_000;
+
+impl SimLightningNode {
+    /// Create a node holding `balance_sats`, drawing randomness from `seed`.
+    pub fn new(seed: u64, clock: SimClock, balance_sats: u64) -> Self {
+        Self {
+            clock,
+            state: Mutex::new(NodeState {
+                rng: SimRng::new(seed),
+                faults: FaultPlan::new(),
+                calls: 0,
+                balance_msat: balance_sats * 1000,
+                invoices: HashMap::new(),
+                payments: Vec::new(),
+                in_flight: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Inject faults from `plan`.
+    pub fn with_faults(self, plan: FaultPlan) -> Self {
+        self.lock().faults = plan;
+        self
+    }
+
+    fn lock(&self) -> MutexGuard<'_, NodeState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Create an invoice payable to `payee`. `None` leaves the amount to
+    /// the payer.
+    pub fn create_invoice(&self, payee: &str, amount_msat: Option<u64>, memo: &str) -> String {
+        let mut state = self.lock();
+        let preimage = state.rng.bytes32();
+        let payment_hash = hex::encode(Sha256::digest(preimage));
+        let invoice = format!(
+            "lnbcrt{}1p{}{}",
+            amount_msat.map(|msat| msat.to_string()).unwrap_or_default(),
+            payment_hash,
+            hex::encode(state.rng.bytes32()),
+        );
+        state.invoices.insert(
+            invoice.clone(),
+            SimInvoice {
+                payee: payee.to_string(),
+                memo: memo.to_string(),
+                payment_hash,
+                preimage: hex::encode(preimage),
+                amount_msat,
+                created_at: self.clock.now(),
+            },
+        );
+        invoice
+    }
+
+    /// Spendable balance in millisatoshis, excluding in-flight payments.
+    pub fn balance_msat(&self) -> u64 {
+        self.lock().balance_msat
+    }
+
+    /// Every payment sent, in order.
+    pub fn payments(&self) -> Vec<SimPayment> {
+        self.resolve_due();
+        self.lock().payments.clone()
+    }
+
+    /// Payments that have settled.
+    pub fn settled(&self) -> Vec<SimPayment> {
+        self.payments()
+            .into_iter()
+            .filter(|p| p.status == LightningPaymentStatus::Succeeded)
+            .collect()
+    }
+
+    /// Resolve in-flight payments whose time has come.
+    fn resolve_due(&self) {
+        let now = self.clock.now();
+        let mut state = self.lock();
+        let due: Vec<(String, bool)> = state
+            .in_flight
+            .iter()
+            .filter(|(_, flight)| flight.resolves_at <= now)
+            .map(|(hash, flight)| (hash.clone(), flight.settles))
+            .collect();
+        for (hash, settles) in due {
+            state.in_flight.remove(&hash);
+            let Some(index) = state.payments.iter().position(|p| p.payment_hash == hash) else {
+                continue;
+            };
+            if settles {
+                state.payments[index].status = LightningPaymentStatus::Succeeded;
+            } else {
+                let payment = &mut state.payments[index];
+                payment.status = LightningPaymentStatus::Failed;
+                let refund = payment.amount_msat + payment.fee_msat;
+                state.balance_msat += refund;
+            }
+        }
+    }
+
+    fn result(payment: &SimPayment) -> LightningPaymentResult {
+        LightningPaymentResult {
+            preimage: if payment.status == LightningPaymentStatus::Succeeded {
+                payment.preimage.clone()
+            } else {
+                String::new()
+            },
+            payment_hash: payment.payment_hash.clone(),
+            amount_msat: payment.amount_msat,
+            fee_msat: payment.fee_msat,
+            hops: 1,
+            status: payment.status.clone(),
+        }
+    }
+}
+
+fn fee_msat(amount_msat: u64) -> u64 {
+    BASE_FEE_MSAT + amount_msat * FEE_RATE_PPM / 1_000_000
+}
+
+#[async_trait]
+impl LightningExecutor for SimLightningNode {
+    async fn pay_invoice(
+        &self,
+        invoice: &str,
+        amount_msat: Option<u64>,
+        max_fee_msat: Option<u64>,
+    ) -> Result<LightningPaymentResult> {
+        self.resolve_due();
+        let now = self.clock.now();
+        let mut state = self.lock();
+        let call = state.calls;
+        state.calls += 1;
+
+        let record = state
+            .invoices
+            .get(invoice)
+            .cloned()
+            .ok_or_else(|| PaykitError::invalid_data("invoice", "Unknown invoice"))?;
+        let already_paid = state.payments.iter().any(|p| {
+            p.payment_hash == record.payment_hash && p.status != LightningPaymentStatus::Failed
+        });
+        if already_paid {
+            return Err(PaykitError::Payment {
+                payment_id: Some(record.payment_hash),
+                reason: "invoice is already paid".to_string(),
+            });
+        }
+
+        let amount_msat = record.amount_msat.or(amount_msat).ok_or_else(|| {
+            PaykitError::invalid_data("amount_msat", "Amount required for zero-amount invoice")
+        })?;
+        let fee_msat = fee_msat(amount_msat);
+        if max_fee_msat.is_some_and(|max| fee_msat > max) {
+            return Err(PaykitError::Payment {
+                payment_id: Some(record.payment_hash),
+                reason: "no route within fee limit".to_string(),
+            });
+        }
+        if amount_msat + fee_msat > state.balance_msat {
+            return Err(PaykitError::Payment {
+                payment_id: Some(record.payment_hash),
+                reason: "insufficient balance".to_string(),
+            });
+        }
+
+        let faults = state.faults.clone();
+        let fault = faults.draw(call, &mut state.rng);
+        if fault == Some(SimFault::Reject) {
+            return Err(PaykitError::Payment {
+                payment_id: Some(record.payment_hash),
+                reason: "no route".to_string(),
+            });
+        }
+
+        state.balance_msat -= amount_msat + fee_msat;
+        let status = match fault {
+            Some(SimFault::InFlight { secs, settles }) => {
+                state.in_flight.insert(
+                    record.payment_hash.clone(),
+                    InFlight {
+                        resolves_at: now + secs,
+                        settles,
+                    },
+                );
+                LightningPaymentStatus::Pending
+            }
+            _ => LightningPaymentStatus::Succeeded,
+        };
+        let payment = SimPayment {
+            payee: record.payee,
+            memo: record.memo,
+            payment_hash: record.payment_hash,
+            preimage: record.preimage,
+            amount_msat,
+            fee_msat,
+            status,
+            sent_at: now,
+        };
+        state.payments.push(payment.clone());
+
+        if fault == Some(SimFault::LostResponse) {
+            return Err(PaykitError::ConnectionTimeout {
+                operation: "pay_invoice".to_string(),
+                timeout_ms: 30_000,
+            });
+        }
+        Ok(Self::result(&payment))
+    }
+
+    async fn decode_invoice(&self, invoice: &str) -> Result<DecodedInvoice> {
+        let record = self
+            .lock()
+            .invoices
+            .get(invoice)
+            .cloned()
+            .ok_or_else(|| PaykitError::invalid_data("invoice", "Unknown invoice"))?;
+        Ok(DecodedInvoice {
+            payment_hash: record.payment_hash,
+            amount_msat: record.amount_msat,
+            description: Some(record.memo),
+            description_hash: None,
+            payee: record.payee,
+            expiry: 3600,
+            timestamp: record.created_at.max(0) as u64,
+            expired: false,
+        })
+    }
+
+    async fn estimate_fee(&self, invoice: &str) -> Result<u64> {
+        let amount_msat = self.decode_invoice(invoice).await?.amount_msat.unwrap_or(0);
+        Ok(fee_msat(amount_msat))
+    }
+
+    async fn get_payment(&self, payment_hash: &str) -> Result<Option<LightningPaymentResult>> {
+        self.resolve_due();
+        Ok(self
+            .lock()
+            .payments
+            .iter()
+            .rev()
+            .find(|p| p.payment_hash == payment_hash)
+            .map(Self::result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rng_is_deterministic() {
+        let mut a = SimRng::new(7);
+        let mut b = SimRng::new(7);
+        let draws: Vec<u64> = (0..16).map(|_| a.next_u64()).collect();
+        assert_eq!(draws, (0..16).map(|_| b.next_u64()).collect::<Vec<_>>());
+        assert_ne!(SimRng::new(8).next_u64(), draws[0]);
+
+        let mut rng = SimRng::new(1);
+        assert!((0..1000).all(|_| (10..20).contains(&rng.range(10, 20))));
+        assert_eq!(rng.range(5, 5), 5);
+    }
+
+    #[tokio::test]
+    async fn test_lost_response_settles() {
+        let clock = SimClock::new(1_700_000_000);
+        let node = SimLightningNode::new(1, clock, 100)
+            .with_faults(FaultPlan::new().at(0, SimFault::LostResponse));
+        let invoice = node.create_invoice("bob", Some(10_000), "order");
+
+        let result = node.pay_invoice(&invoice, None, None).await;
+        assert!(matches!(result, Err(PaykitError::ConnectionTimeout { .. })));
+        let settled = node.settled();
+        assert_eq!(settled.len(), 1);
+        assert!(node.verify_preimage(&settled[0].preimage, &settled[0].payment_hash));
+
+        // Paying again is refused rather than charged twice
+        assert!(node.pay_invoice(&invoice, None, None).await.is_err());
+        assert_eq!(node.balance_msat(), 100_000 - 10_000 - fee_msat(10_000));
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_resolves_with_clock() {
+        let clock = SimClock::new(0);
+        let node = SimLightningNode::new(2, clock.clone(), 100).with_faults(FaultPlan::new().at(
+            0,
+            SimFault::InFlight {
+                secs: 60,
+                settles: false,
+            },
+        ));
+        let invoice = node.create_invoice("bob", Some(10_000), "order");
+
+        let result = node.pay_invoice(&invoice, None, None).await.unwrap();
+        assert_eq!(result.status, LightningPaymentStatus::Pending);
+        assert!(node.balance_msat() < 100_000);
+
+        clock.advance(60);
+        let result = node
+            .get_payment(&result.payment_hash)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.status, LightningPaymentStatus::Failed);
+        assert_eq!(node.balance_msat(), 100_000);
+
+        // A failed payment may be retried
+        assert!(node.pay_invoice(&invoice, None, None).await.is_ok());
+    }
+}
@@ -1,8 +1,10 @@
+pub mod paging;
 pub mod traits;
 
 #[cfg(feature = "pubky")]
 pub mod pubky;
 
+pub use paging::{ContactPager, DirectoryPager, Page, PaymentPager, DEFAULT_PAGE_LIMIT};
 pub use traits::{AuthenticatedTransport, UnauthenticatedTransportRead};
 
 #[cfg(feature = "pubky")]
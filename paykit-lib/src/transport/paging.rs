@@ -0,0 +1,328 @@
+//! Paged directory listings.
+//!
+//! A homeserver caps how many entries one listing returns, and users with
+//! thousands of follows or published files should not have to hold them all
+//! in memory. [`UnauthenticatedTransportRead::list_directory_page`] returns
+//! one [`Page`] at a time with an opaque cursor for the next one, and the
+//! pagers here walk those pages lazily:
+//!
+//! - [`DirectoryPager`] yields entry names under any path;
+//! - [`ContactPager`] yields followed public keys;
+//! - [`PaymentPager`] yields `(method, endpoint)` pairs, fetching each
+//!   endpoint only when its page is reached.
+//!
+//! Pagers expose their position with `cursor()` so a listing can be resumed
+//! later with `resume_from()`.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut contacts = ContactPager::new(&reader, &owner).with_limit(200);
+//! while let Some(contact) = contacts.try_next().await? {
+//!     println!("{}", contact);
+//! }
+//! ```
+
+use std::collections::VecDeque;
+
+use super::traits::UnauthenticatedTransportRead;
+use crate::{EndpointData, MethodId, PaykitError, PublicKey, Result};
+
+/// Page size used when none is given.
+pub const DEFAULT_PAGE_LIMIT: u16 = 100;
+
+/// Directory holding the payee's supported payment endpoints.
+pub(crate) const PAYMENTS_DIR: &str = "/pub/paykit.app/v0/";
+
+/// Directory holding one file per followed contact.
+pub(crate) const FOLLOWS_DIR: &str = "/pub/pubky.app/follows/";
+
+/// One page of a listing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Page<T> {
+    /// Entries in this page.
+    pub items: Vec<T>,
+    /// Cursor for the next page, or `None` if this is the last one.
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// Whether no further pages follow.
+    pub fn is_last(&self) -> bool {
+        self.next_cursor.is_none()
+    }
+
+    /// Transform the entries, keeping the cursor.
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+        }
+    }
+}
+
+/// Page through a complete listing for transports without server-side paging.
+///
+/// Names are sorted so the cursor (the last name returned) is stable.
+pub(crate) fn page_from_listing(
+    mut names: Vec<String>,
+    cursor: Option<&str>,
+    limit: u16,
+) -> Page<String> {
+    names.sort();
+    let limit = usize::from(limit.max(1));
+    let mut remaining = names
+        .into_iter()
+        .filter(|name| cursor.is_none_or(|cursor| name.as_str() > cursor));
+    let items: Vec<String> = remaining.by_ref().take(limit).collect();
+    let next_cursor = match remaining.next() {
+        Some(_) => items.last().cloned(),
+        None => None,
+    };
+    Page { items, next_cursor }
+}
+
+/// Lazily walks the entry names under a directory.
+pub struct DirectoryPager<'a, R: ?Sized> {
+    reader: &'a R,
+    owner: PublicKey,
+    path: String,
+    limit: u16,
+    cursor: Option<String>,
+    buffer: VecDeque<String>,
+    exhausted: bool,
+}
+
+impl<'a, R> DirectoryPager<'a, R>
+where
+    R: UnauthenticatedTransportRead + ?Sized,
+{
+    /// Page through `path` in `owner`'s storage.
+    pub fn new(reader: &'a R, owner: &PublicKey, path: impl Into<String>) -> Self {
+        Self {
+            reader,
+            owner: owner.clone(),
+            path: path.into(),
+            limit: DEFAULT_PAGE_LIMIT,
+            cursor: None,
+            buffer: VecDeque::new(),
+            exhausted: false,
+        }
+    }
+
+    /// Set the page size.
+    pub fn with_limit(mut self, limit: u16) -> Self {
+        self.limit = limit.max(1);
+        self
+    }
+
+    /// Continue a listing from a cursor saved with [`Self::cursor`].
+    pub fn resume_from(mut self, cursor: impl Into<String>) -> Self {
+        self.cursor = Some(cursor.into());
+        self
+    }
+
+    /// Cursor of the last page fetched, for resuming later.
+    ///
+    /// Entries still buffered from that page are not covered by the cursor.
+    pub fn cursor(&self) -> Option<&str> {
+        self.cursor.as_deref()
+    }
+
+    /// Fetch the next page, or `None` once the listing is exhausted.
+    ///
+    /// Entries buffered by [`Self::try_next`] are returned first.
+    pub async fn next_page(&mut self) -> Result<Option<Vec<String>>> {
+        if !self.buffer.is_empty() {
+            return Ok(Some(self.buffer.drain(..).collect()));
+        }
+        if self.exhausted {
+            return Ok(None);
+        }
+        let page = self
+            .reader
+            .list_directory_page(&self.owner, &self.path, self.cursor.as_deref(), self.limit)
+            .await?;
+        self.exhausted = page.is_last();
+        if let Some(cursor) = page.next_cursor {
+            self.cursor = Some(cursor);
+        }
+        // A page may be empty (e.g. only sub-directories) without being the last
+        Ok(Some(page.items))
+    }
+
+    /// Next entry name, fetching pages as needed.
+    pub async fn try_next(&mut self) -> Result<Option<String>> {
+        loop {
+            if let Some(name) = self.buffer.pop_front() {
+                return Ok(Some(name));
+            }
+            match self.next_page().await? {
+                Some(items) => self.buffer.extend(items),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+/// Lazily walks an owner's followed contacts.
+pub struct ContactPager<'a, R: ?Sized> {
+    inner: DirectoryPager<'a, R>,
+}
+
+impl<'a, R> ContactPager<'a, R>
+where
+    R: UnauthenticatedTransportRead + ?Sized,
+{
+    /// Page through the contacts `owner` follows.
+    pub fn new(reader: &'a R, owner: &PublicKey) -> Self {
+        Self {
+            inner: DirectoryPager::new(reader, owner, FOLLOWS_DIR),
+        }
+    }
+
+    /// Set the page size.
+    pub fn with_limit(mut self, limit: u16) -> Self {
+        self.inner = self.inner.with_limit(limit);
+        self
+    }
+
+    /// Continue a listing from a cursor saved with [`Self::cursor`].
+    pub fn resume_from(mut self, cursor: impl Into<String>) -> Self {
+        self.inner = self.inner.resume_from(cursor);
+        self
+    }
+
+    /// Cursor of the last page fetched, for resuming later.
+    pub fn cursor(&self) -> Option<&str> {
+        self.inner.cursor()
+    }
+
+    /// Fetch the next page of contacts. Invalid entries are skipped.
+    pub async fn next_page(&mut self) -> Result<Option<Vec<PublicKey>>> {
+        Ok(self.inner.next_page().await?.map(|names| {
+            names
+                .iter()
+                .filter_map(|name| parse_contact(name))
+                .collect()
+        }))
+    }
+
+    /// Next contact, fetching pages as needed. Invalid entries are skipped.
+    pub async fn try_next(&mut self) -> Result<Option<PublicKey>> {
+        while let Some(name) = self.inner.try_next().await? {
+            if let Some(contact) = parse_contact(&name) {
+                return Ok(Some(contact));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(feature = "pubky")]
+pub(crate) fn parse_contact(name: &str) -> Option<PublicKey> {
+    name.parse().ok()
+}
+
+#[cfg(not(feature = "pubky"))]
+pub(crate) fn parse_contact(name: &str) -> Option<PublicKey> {
+    Some(PublicKey(name.to_string()))
+}
+
+/// Lazily walks a payee's supported payment endpoints.
+pub struct PaymentPager<'a, R: ?Sized> {
+    inner: DirectoryPager<'a, R>,
+}
+
+impl<'a, R> PaymentPager<'a, R>
+where
+    R: UnauthenticatedTransportRead + ?Sized,
+{
+    /// Page through the endpoints `payee` publishes.
+    pub fn new(reader: &'a R, payee: &PublicKey) -> Self {
+        Self {
+            inner: DirectoryPager::new(reader, payee, PAYMENTS_DIR),
+        }
+    }
+
+    /// Set the page size.
+    pub fn with_limit(mut self, limit: u16) -> Self {
+        self.inner = self.inner.with_limit(limit);
+        self
+    }
+
+    /// Continue a listing from a cursor saved with [`Self::cursor`].
+    pub fn resume_from(mut self, cursor: impl Into<String>) -> Self {
+        self.inner = self.inner.resume_from(cursor);
+        self
+    }
+
+    /// Cursor of the last page fetched, for resuming later.
+    pub fn cursor(&self) -> Option<&str> {
+        self.inner.cursor()
+    }
+
+    /// Fetch the next page of endpoints. Empty endpoint files are skipped.
+    pub async fn next_page(&mut self) -> Result<Option<Vec<(MethodId, EndpointData)>>> {
+        let Some(names) = self.inner.next_page().await? else {
+            return Ok(None);
+        };
+        let mut entries = Vec::with_capacity(names.len());
+        for name in names {
+            if let Some(entry) = self.fetch(name).await? {
+                entries.push(entry);
+            }
+        }
+        Ok(Some(entries))
+    }
+
+    /// Next endpoint, fetching pages as needed.
+    pub async fn try_next(&mut self) -> Result<Option<(MethodId, EndpointData)>> {
+        while let Some(name) = self.inner.try_next().await? {
+            if let Some(entry) = self.fetch(name).await? {
+                return Ok(Some(entry));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn fetch(&self, name: String) -> Result<Option<(MethodId, EndpointData)>> {
+        let method = MethodId(name);
+        let endpoint = self
+            .inner
+            .reader
+            .fetch_payment_endpoint(&self.inner.owner, &method)
+            .await
+            .map_err(|err| match err {
+                PaykitError::Transport(msg) => {
+                    PaykitError::Transport(format!("page endpoint {}: {}", method.0, msg))
+                }
+                other => other,
+            })?;
+        Ok(endpoint.map(|endpoint| (method, endpoint)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_page_from_listing() {
+        let listing = names(&["c", "a", "e", "b", "d"]);
+
+        let first = page_from_listing(listing.clone(), None, 2);
+        assert_eq!(first.items, names(&["a", "b"]));
+        assert_eq!(first.next_cursor.as_deref(), Some("b"));
+
+        let last = page_from_listing(listing.clone(), Some("c"), 2);
+        assert_eq!(last.items, names(&["d", "e"]));
+        assert!(last.is_last());
+
+        assert!(page_from_listing(listing, Some("e"), 2).items.is_empty());
+    }
+}
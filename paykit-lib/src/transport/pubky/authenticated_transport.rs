@@ -1,20 +1,37 @@
 use async_trait::async_trait;
 use pubky::{errors::RequestError, Error as PubkyError, PubkySession, StatusCode};
 
-use super::PAYKIT_PATH_PREFIX;
+use crate::protocol::{PathRoot, PAYKIT_V0_PREFIX};
 use crate::transport::traits::AuthenticatedTransport;
 use crate::{EndpointData, MethodId, PaykitError, Result};
 
 /// Adapter around `pubky::PubkySession` implementing `AuthenticatedTransport`.
+///
+/// Paykit paths are written under the configured [`PathRoot`].
 #[derive(Clone)]
 pub struct PubkyAuthenticatedTransport {
     session: PubkySession,
+    root: PathRoot,
 }
 
 impl PubkyAuthenticatedTransport {
     /// Create a new adapter from an existing session.
     pub fn new(session: PubkySession) -> Self {
-        Self { session }
+        Self {
+            session,
+            root: PathRoot::default(),
+        }
+    }
+
+    /// Write Paykit paths under `root` instead of `paykit.app`.
+    pub fn with_path_root(mut self, root: PathRoot) -> Self {
+        self.root = root;
+        self
+    }
+
+    /// The root Paykit paths are written under.
+    pub fn path_root(&self) -> &PathRoot {
+        &self.root
     }
 
     /// Access the wrapped session for advanced payers/payees.
@@ -25,7 +42,7 @@ impl PubkyAuthenticatedTransport {
 
 impl From<PubkySession> for PubkyAuthenticatedTransport {
     fn from(session: PubkySession) -> Self {
-        Self { session }
+        Self::new(session)
     }
 }
 
@@ -43,7 +60,9 @@ fn is_not_found(err: &PubkyError) -> bool {
 impl AuthenticatedTransport for PubkyAuthenticatedTransport {
     #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, data), fields(method = %method.0, data_len = data.0.len())))]
     async fn upsert_payment_endpoint(&self, method: &MethodId, data: &EndpointData) -> Result<()> {
-        let path = format!("{PAYKIT_PATH_PREFIX}{}", method.0);
+        let path = self
+            .root
+            .rebase(&format!("{PAYKIT_V0_PREFIX}/{}", method.0));
         self.session
             .storage()
             .put(path, data.0.clone())
@@ -54,7 +73,9 @@ impl AuthenticatedTransport for PubkyAuthenticatedTransport {
 
     #[cfg_attr(feature = "tracing", tracing::instrument(skip(self), fields(method = %method.0)))]
     async fn remove_payment_endpoint(&self, method: &MethodId) -> Result<()> {
-        let path = format!("{PAYKIT_PATH_PREFIX}{}", method.0);
+        let path = self
+            .root
+            .rebase(&format!("{PAYKIT_V0_PREFIX}/{}", method.0));
         self.session
             .storage()
             .delete(path)
@@ -66,14 +87,15 @@ impl AuthenticatedTransport for PubkyAuthenticatedTransport {
     async fn put(&self, path: &str, content: &str) -> Result<()> {
         self.session
             .storage()
-            .put(path.to_string(), content.to_string())
+            .put(self.root.rebase(path), content.to_string())
             .await
             .map_err(|err| PaykitError::Transport(format!("put: {err}")))?;
         Ok(())
     }
 
     async fn get(&self, path: &str) -> Result<Option<String>> {
-        match self.session.storage().get(path).await {
+        let path = self.root.rebase(path);
+        match self.session.storage().get(path.as_str()).await {
             Ok(response) => {
                 let bytes = response
                     .bytes()
@@ -93,7 +115,7 @@ impl AuthenticatedTransport for PubkyAuthenticatedTransport {
     async fn delete(&self, path: &str) -> Result<()> {
         self.session
             .storage()
-            .delete(path.to_string())
+            .delete(self.root.rebase(path))
             .await
             .map_err(|err| PaykitError::Transport(format!("delete: {err}")))?;
         Ok(())
@@ -6,20 +6,40 @@ use pubky::{
     PublicStorage as SdkUnauthenticatedTransport, StatusCode,
 };
 
-use super::{PAYKIT_PATH_PREFIX, PUBKY_FOLLOWS_PATH};
+use super::PUBKY_FOLLOWS_PATH;
+use crate::protocol::{PathRootConfig, PAYKIT_V0_PREFIX};
+use crate::transport::paging::{Page, DEFAULT_PAGE_LIMIT};
 use crate::transport::traits::UnauthenticatedTransportRead;
 use crate::{EndpointData, MethodId, PaykitError, PublicKey, Result, SupportedPayments};
 
 /// Adapter around `pubky::PublicStorage` implementing `UnauthenticatedTransportRead`.
+///
+/// Paykit paths are read under the configured [`PathRootConfig`]: the
+/// primary root first, then each fallback while nothing is found.
 #[derive(Clone)]
 pub struct PubkyUnauthenticatedTransport {
     inner: SdkUnauthenticatedTransport,
+    roots: PathRootConfig,
 }
 
 impl PubkyUnauthenticatedTransport {
     /// Build an adapter from an existing SDK handle.
     pub fn new(inner: SdkUnauthenticatedTransport) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            roots: PathRootConfig::default(),
+        }
+    }
+
+    /// Read Paykit paths under `roots` instead of only `paykit.app`.
+    pub fn with_path_roots(mut self, roots: PathRootConfig) -> Self {
+        self.roots = roots;
+        self
+    }
+
+    /// The roots Paykit paths are read under.
+    pub fn path_roots(&self) -> &PathRootConfig {
+        &self.roots
     }
 
     /// Attempt to construct the underlying SDK transport via `pubky::PublicStorage::new()`.
@@ -27,7 +47,7 @@ impl PubkyUnauthenticatedTransport {
         let inner = SdkUnauthenticatedTransport::new().map_err(|err| {
             PaykitError::Transport(format!("failed to create Pubky public transport: {err}"))
         })?;
-        Ok(Self { inner })
+        Ok(Self::new(inner))
     }
 
     /// Access the wrapped SDK transport handle.
@@ -54,14 +74,43 @@ impl PubkyUnauthenticatedTransport {
         }
     }
 
+    /// List every entry under `addr`, following pages past the homeserver's
+    /// per-request limit.
     async fn list_entries(&self, addr: String, label: &str) -> Result<Vec<PubkyResource>> {
-        let builder = match self.inner.list(&addr) {
+        let mut entries = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let page = self
+                .list_entries_page(&addr, cursor.as_deref(), DEFAULT_PAGE_LIMIT, label)
+                .await?;
+            let full = page.len() >= usize::from(DEFAULT_PAGE_LIMIT);
+            cursor = page.last().map(|resource| resource.to_string());
+            entries.extend(page);
+            if !full || cursor.is_none() {
+                return Ok(entries);
+            }
+        }
+    }
+
+    /// List at most `limit` entries under `addr`, after `cursor`.
+    async fn list_entries_page(
+        &self,
+        addr: &str,
+        cursor: Option<&str>,
+        limit: u16,
+        label: &str,
+    ) -> Result<Vec<PubkyResource>> {
+        let builder = match self.inner.list(addr) {
             Ok(builder) => builder,
             Err(err) if is_not_found(&err) => return Ok(Vec::new()),
             Err(err) => return Err(PaykitError::Transport(format!("{label}: {err}"))),
         };
+        let mut builder = builder.shallow(true).limit(limit);
+        if let Some(cursor) = cursor {
+            builder = builder.cursor(cursor);
+        }
 
-        match builder.shallow(true).send().await {
+        match builder.send().await {
             Ok(entries) => Ok(entries),
             Err(err) if is_not_found(&err) => Ok(Vec::new()),
             Err(err) => Err(PaykitError::Transport(format!(
@@ -76,8 +125,15 @@ impl PubkyUnauthenticatedTransport {
 impl UnauthenticatedTransportRead for PubkyUnauthenticatedTransport {
     #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
     async fn fetch_supported_payments(&self, payee: &PublicKey) -> Result<SupportedPayments> {
-        let addr = format!("pubky{payee}{PAYKIT_PATH_PREFIX}");
-        let entries = self.list_entries(addr, "list supported payments").await?;
+        let mut entries = Vec::new();
+        for dir in self.roots.read_paths(&format!("{PAYKIT_V0_PREFIX}/")) {
+            entries = self
+                .list_entries(format!("pubky{payee}{dir}"), "list supported payments")
+                .await?;
+            if !entries.is_empty() {
+                break;
+            }
+        }
 
         let mut map = HashMap::new();
         for resource in entries {
@@ -113,11 +169,14 @@ impl UnauthenticatedTransportRead for PubkyUnauthenticatedTransport {
         payee: &PublicKey,
         method: &MethodId,
     ) -> Result<Option<EndpointData>> {
-        let addr = format!("pubky{payee}{PAYKIT_PATH_PREFIX}{}", method.0);
-        match self.fetch_text(addr, "fetch endpoint").await? {
-            Some(payload) => Ok(Some(EndpointData(payload))),
-            None => Ok(None),
+        let path = format!("{PAYKIT_V0_PREFIX}/{}", method.0);
+        for path in self.roots.read_paths(&path) {
+            let addr = format!("pubky{payee}{path}");
+            if let Some(payload) = self.fetch_text(addr, "fetch endpoint").await? {
+                return Ok(Some(EndpointData(payload)));
+            }
         }
+        Ok(None)
     }
 
     #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
@@ -153,29 +212,80 @@ impl UnauthenticatedTransportRead for PubkyUnauthenticatedTransport {
     }
 
     async fn get(&self, owner: &PublicKey, path: &str) -> Result<Option<String>> {
-        let addr = format!("pubky{owner}{path}");
-        self.fetch_text(addr, "get").await
+        for path in self.roots.read_paths(path) {
+            let addr = format!("pubky{owner}{path}");
+            if let Some(content) = self.fetch_text(addr, "get").await? {
+                return Ok(Some(content));
+            }
+        }
+        Ok(None)
     }
 
     async fn list_directory(&self, owner: &PublicKey, path: &str) -> Result<Vec<String>> {
-        let addr = format!("pubky{owner}{path}");
-        let entries = self.list_entries(addr, "list directory").await?;
-
-        let names: Vec<String> = entries
-            .into_iter()
-            .filter_map(|resource| {
-                resource
-                    .path
-                    .as_str()
-                    .rsplit('/')
-                    .next()
-                    .filter(|s| !s.is_empty())
-                    .map(|s| s.to_string())
-            })
-            .collect();
+        let mut entries = Vec::new();
+        for path in self.roots.read_paths(path) {
+            let addr = format!("pubky{owner}{path}");
+            entries = self.list_entries(addr, "list directory").await?;
+            if !entries.is_empty() {
+                break;
+            }
+        }
+
+        let names: Vec<String> = entries.iter().filter_map(entry_name).collect();
 
         Ok(names)
     }
+
+    async fn list_directory_page(
+        &self,
+        owner: &PublicKey,
+        path: &str,
+        cursor: Option<&str>,
+        limit: u16,
+    ) -> Result<Page<String>> {
+        let limit = limit.max(1);
+        // A cursor names an entry, so later pages stay on the root it is from
+        let mut paths = self.roots.read_paths(path);
+        if let Some(cursor) = cursor {
+            if let Some(index) = paths.iter().position(|p| cursor.contains(p.as_str())) {
+                paths = vec![paths.swap_remove(index)];
+            }
+        }
+
+        let mut entries = Vec::new();
+        for path in paths {
+            let addr = format!("pubky{owner}{path}");
+            entries = self
+                .list_entries_page(&addr, cursor, limit, "list directory page")
+                .await?;
+            if !entries.is_empty() {
+                break;
+            }
+        }
+
+        // The cursor is the last raw entry, so pages that end in a
+        // sub-directory (dropped from `items`) still advance.
+        let next_cursor = if entries.len() >= usize::from(limit) {
+            entries.last().map(|resource| resource.to_string())
+        } else {
+            None
+        };
+        Ok(Page {
+            items: entries.iter().filter_map(entry_name).collect(),
+            next_cursor,
+        })
+    }
+}
+
+/// Final path segment of a file entry; `None` for sub-directories.
+fn entry_name(resource: &PubkyResource) -> Option<String> {
+    resource
+        .path
+        .as_str()
+        .rsplit('/')
+        .next()
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
 }
 
 fn is_not_found(err: &PubkyError) -> bool {
@@ -1,5 +1,6 @@
 use async_trait::async_trait;
 
+use super::paging::{page_from_listing, Page};
 use crate::{EndpointData, MethodId, PublicKey, Result};
 
 /// Trait describing read-only access to public Paykit transport.
@@ -29,6 +30,23 @@ pub trait UnauthenticatedTransportRead {
     ///
     /// Returns a list of file/directory names (not full paths).
     async fn list_directory(&self, owner: &PublicKey, path: &str) -> Result<Vec<String>>;
+
+    /// List at most `limit` entries of a directory, starting after `cursor`.
+    ///
+    /// `cursor` is the opaque `next_cursor` of the previous page, or `None`
+    /// for the first page. The default implementation lists the whole
+    /// directory and slices it; transports backed by a paging server should
+    /// override it.
+    async fn list_directory_page(
+        &self,
+        owner: &PublicKey,
+        path: &str,
+        cursor: Option<&str>,
+        limit: u16,
+    ) -> Result<Page<String>> {
+        let names = self.list_directory(owner, path).await?;
+        Ok(page_from_listing(names, cursor, limit))
+    }
 }
 
 /// Trait describing authenticated write (and optional read) access.
@@ -0,0 +1,735 @@
+//! Treasury Sweep Automation
+//!
+//! Merchants receiving payments on a hot wallet usually want to move funds
+//! to cold storage once they pile up. This module consolidates the hot
+//! wallet's balance into a single destination through the wallet's
+//! [`BitcoinExecutor`], according to a [`SweepPolicy`]:
+//!
+//! - a sweep only happens once the spendable balance reaches the threshold,
+//! - an optional reserve is left behind for upcoming outgoing payments,
+//! - the sweep is skipped when the estimated fee exceeds the fee ceiling,
+//!   and otherwise sent at a fee rate that keeps the fee within it for the
+//!   transaction the wallet says it will build, so the wallet's
+//!   [`spend_hints`](BitcoinExecutor::spend_hints) are required,
+//! - a sweep that still pays more than the ceiling is reported.
+//!
+//! Every sweep produces a [`SweepReceipt`] and every outcome is reported to
+//! [`on_event`](TreasurySweeper::on_event) callbacks.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use paykit_lib::treasury::{SweepPolicy, SweepSchedule, TreasurySweeper};
+//!
+//! let policy = SweepPolicy::new("bc1qcold...", 1_000_000, 5_000)
+//!     .with_reserve_sats(100_000)
+//!     .with_schedule(SweepSchedule::Interval { every_secs: 3600 });
+//! let sweeper = TreasurySweeper::new(wallet, policy)?;
+//!
+//! // Called periodically by the merchant's scheduler
+//! if let SweepOutcome::Swept(receipt) = sweeper.tick().await? {
+//!     println!("swept {} sats in {}", receipt.amount_sats, receipt.txid);
+//! }
+//! ```
+
+use crate::methods::{BitcoinExecutor, PaymentProof, SpendHints};
+use crate::{PaykitError, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Transaction overhead, in vbytes.
+const TX_OVERHEAD_VBYTES: u64 = 11;
+/// Smallest input (taproot key path), in vbytes.
+const MIN_INPUT_VBYTES: u64 = 58;
+/// Largest single-signature input (P2PKH), in vbytes.
+const MAX_INPUT_VBYTES: u64 = 148;
+/// Smallest standard output (P2WPKH), in vbytes.
+const MIN_OUTPUT_VBYTES: u64 = 31;
+/// Largest standard output (P2TR, P2WSH), in vbytes.
+const MAX_OUTPUT_VBYTES: u64 = 43;
+
+/// Callback type for sweep events.
+pub type SweepCallback = Arc<dyn Fn(&SweepEvent) + Send + Sync>;
+
+fn default_target_blocks() -> u32 {
+    6
+}
+
+/// When sweeps run automatically.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SweepSchedule {
+    /// Only sweep when explicitly asked.
+    #[default]
+    Manual,
+    /// Sweep at most once per interval.
+    Interval {
+        /// Minimum time between sweeps, in seconds.
+        every_secs: i64,
+    },
+}
+
+impl SweepSchedule {
+    /// When the next scheduled sweep is due, or `None` for manual sweeps.
+    ///
+    /// A schedule that has never swept is due immediately.
+    pub fn next_due_at(&self, last_sweep_at: Option<i64>, now: i64) -> Option<i64> {
+        match self {
+            SweepSchedule::Manual => None,
+            SweepSchedule::Interval { every_secs } => {
+                Some(last_sweep_at.map_or(now, |last| last + every_secs))
+            }
+        }
+    }
+}
+
+/// Rules for consolidating hot wallet funds.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SweepPolicy {
+    /// Where swept funds are sent (cold storage address).
+    pub destination: String,
+    /// Sweep once the spendable balance reaches this amount.
+    pub threshold_sats: u64,
+    /// Amount left on the hot wallet after a sweep.
+    #[serde(default)]
+    pub reserve_sats: u64,
+    /// Skip the sweep when the estimated fee is above this amount.
+    pub max_fee_sats: u64,
+    /// Confirmation target (in blocks) used for fee estimation.
+    #[serde(default = "default_target_blocks")]
+    pub target_blocks: u32,
+    /// When sweeps run automatically.
+    #[serde(default)]
+    pub schedule: SweepSchedule,
+}
+
+impl SweepPolicy {
+    /// Create a manual sweep policy.
+    pub fn new(destination: impl Into<String>, threshold_sats: u64, max_fee_sats: u64) -> Self {
+        Self {
+            destination: destination.into(),
+            threshold_sats,
+            reserve_sats: 0,
+            max_fee_sats,
+            target_blocks: default_target_blocks(),
+            schedule: SweepSchedule::Manual,
+        }
+    }
+
+    /// Keep `reserve_sats` on the hot wallet.
+    pub fn with_reserve_sats(mut self, reserve_sats: u64) -> Self {
+        self.reserve_sats = reserve_sats;
+        self
+    }
+
+    /// Set the fee estimation confirmation target.
+    pub fn with_target_blocks(mut self, target_blocks: u32) -> Self {
+        self.target_blocks = target_blocks;
+        self
+    }
+
+    /// Set when sweeps run automatically.
+    pub fn with_schedule(mut self, schedule: SweepSchedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// Check that the policy can ever sweep.
+    pub fn validate(&self) -> Result<()> {
+        if self.destination.trim().is_empty() {
+            return Err(PaykitError::ValidationFailed(
+                "Sweep destination must not be empty".to_string(),
+            ));
+        }
+        if self.reserve_sats >= self.threshold_sats {
+            return Err(PaykitError::ValidationFailed(format!(
+                "Sweep threshold ({} sats) must be above the reserve ({} sats)",
+                self.threshold_sats, self.reserve_sats
+            )));
+        }
+        if self.target_blocks == 0 {
+            return Err(PaykitError::ValidationFailed(
+                "Sweep confirmation target must be at least one block".to_string(),
+            ));
+        }
+        if let SweepSchedule::Interval { every_secs } = self.schedule {
+            if every_secs <= 0 {
+                return Err(PaykitError::ValidationFailed(
+                    "Sweep interval must be positive".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Record of a completed sweep.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SweepReceipt {
+    /// Unique sweep identifier.
+    pub sweep_id: String,
+    /// Sweep transaction ID.
+    pub txid: String,
+    /// Where the funds were sent.
+    pub destination: String,
+    /// Amount moved, in satoshis.
+    pub amount_sats: u64,
+    /// Fee paid, in satoshis.
+    pub fee_sats: u64,
+    /// Fee rate paid (sat/vB).
+    pub fee_rate: f64,
+    /// Spendable balance before the sweep, in satoshis.
+    pub balance_before_sats: u64,
+    /// When the sweep was broadcast (unix epoch seconds).
+    pub swept_at: i64,
+}
+
+impl SweepReceipt {
+    /// Proof of the sweep transaction.
+    pub fn proof(&self) -> PaymentProof {
+        PaymentProof::bitcoin_txid(self.txid.clone(), None)
+    }
+}
+
+/// Why a sweep did not happen.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum SweepSkip {
+    /// The schedule has no sweep due yet.
+    NotDue {
+        /// When the next sweep is due, if scheduled.
+        next_due_at: Option<i64>,
+    },
+    /// The balance has not reached the threshold.
+    BelowThreshold {
+        /// Current spendable balance.
+        balance_sats: u64,
+        /// Policy threshold.
+        threshold_sats: u64,
+    },
+    /// The estimated fee exceeds the ceiling.
+    FeeTooHigh {
+        /// Estimated fee.
+        fee_sats: u64,
+        /// Policy fee ceiling.
+        max_fee_sats: u64,
+    },
+    /// Nothing would be left to send once the reserve and fee are taken out.
+    NothingToSweep {
+        /// Current spendable balance.
+        balance_sats: u64,
+        /// Estimated fee.
+        fee_sats: u64,
+    },
+    /// The wallet cannot describe the sweep transaction, so its fee cannot
+    /// be held to the ceiling.
+    FeeUncapped,
+}
+
+impl SweepSkip {
+    /// Human-readable description.
+    pub fn describe(&self) -> String {
+        match self {
+            SweepSkip::NotDue {
+                next_due_at: Some(at),
+            } => format!("next sweep due at {}", at),
+            SweepSkip::NotDue { next_due_at: None } => "sweeps are manual".to_string(),
+            SweepSkip::BelowThreshold {
+                balance_sats,
+                threshold_sats,
+            } => format!(
+                "balance {} sats is below the {} sat threshold",
+                balance_sats, threshold_sats
+            ),
+            SweepSkip::FeeTooHigh {
+                fee_sats,
+                max_fee_sats,
+            } => format!(
+                "estimated fee {} sats exceeds the {} sat ceiling",
+                fee_sats, max_fee_sats
+            ),
+            SweepSkip::NothingToSweep {
+                balance_sats,
+                fee_sats,
+            } => format!(
+                "balance {} sats does not cover the reserve and {} sat fee",
+                balance_sats, fee_sats
+            ),
+            SweepSkip::FeeUncapped => {
+                "the wallet cannot size the sweep, so its fee cannot be capped".to_string()
+            }
+        }
+    }
+}
+
+/// Result of a sweep attempt.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SweepOutcome {
+    /// Funds were swept.
+    Swept(SweepReceipt),
+    /// No sweep was made.
+    Skipped(SweepSkip),
+}
+
+/// Events emitted by the [`TreasurySweeper`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum SweepEvent {
+    /// Funds were swept.
+    Swept(SweepReceipt),
+    /// A due sweep was skipped. Ticks before the schedule is due are silent.
+    Skipped(SweepSkip),
+    /// The wallet failed while sweeping.
+    Failed {
+        /// Error description.
+        error: String,
+    },
+    /// A sweep was broadcast but paid more than the fee ceiling, because the
+    /// wallet built a larger transaction than its spend hints described.
+    FeeCeilingExceeded {
+        /// Sweep transaction ID.
+        txid: String,
+        /// Fee actually paid.
+        fee_sats: u64,
+        /// Policy fee ceiling.
+        max_fee_sats: u64,
+    },
+}
+
+/// Sweeps hot wallet funds to cold storage.
+///
+/// Scheduling is the caller's responsibility; call [`tick`](Self::tick) from
+/// whatever timer drives background work and it sweeps when the policy's
+/// schedule is due. [`sweep_now`](Self::sweep_now) ignores the schedule but
+/// still honours the threshold and fee ceiling.
+pub struct TreasurySweeper {
+    executor: Arc<dyn BitcoinExecutor>,
+    policy: RwLock<SweepPolicy>,
+    last_sweep_at: Mutex<Option<i64>>,
+    callbacks: RwLock<Vec<SweepCallback>>,
+}
+
+impl TreasurySweeper {
+    /// Create a sweeper backed by a wallet executor.
+    pub fn new(executor: Arc<dyn BitcoinExecutor>, policy: SweepPolicy) -> Result<Self> {
+        policy.validate()?;
+        Ok(Self {
+            executor,
+            policy: RwLock::new(policy),
+            last_sweep_at: Mutex::new(None),
+            callbacks: RwLock::new(Vec::new()),
+        })
+    }
+
+    /// Resume the schedule from a previously persisted sweep time.
+    pub fn with_last_sweep_at(self, last_sweep_at: Option<i64>) -> Self {
+        *self.last_sweep_at.lock().unwrap_or_else(|e| e.into_inner()) = last_sweep_at;
+        self
+    }
+
+    /// Get the current policy.
+    pub fn policy(&self) -> SweepPolicy {
+        self.policy
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Replace the policy.
+    pub fn set_policy(&self, policy: SweepPolicy) -> Result<()> {
+        policy.validate()?;
+        *self.policy.write().unwrap_or_else(|e| e.into_inner()) = policy;
+        Ok(())
+    }
+
+    /// When the last sweep was broadcast.
+    pub fn last_sweep_at(&self) -> Option<i64> {
+        *self.last_sweep_at.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Register a callback for sweep events.
+    ///
+    /// If the lock is poisoned, the callback is silently ignored.
+    pub fn on_event(&self, callback: SweepCallback) {
+        if let Ok(mut callbacks) = self.callbacks.write() {
+            callbacks.push(callback);
+        }
+    }
+
+    /// Sweep if the schedule is due.
+    pub async fn tick(&self) -> Result<SweepOutcome> {
+        self.tick_at(current_timestamp()).await
+    }
+
+    /// Sweep if the schedule is due, as of `now`.
+    pub async fn tick_at(&self, now: i64) -> Result<SweepOutcome> {
+        let next_due_at = self
+            .policy()
+            .schedule
+            .next_due_at(self.last_sweep_at(), now);
+        match next_due_at {
+            Some(due) if due <= now => self.sweep_at(now).await,
+            _ => Ok(SweepOutcome::Skipped(SweepSkip::NotDue { next_due_at })),
+        }
+    }
+
+    /// Sweep now regardless of the schedule.
+    pub async fn sweep_now(&self) -> Result<SweepOutcome> {
+        self.sweep_at(current_timestamp()).await
+    }
+
+    /// Sweep regardless of the schedule, as of `now`.
+    pub async fn sweep_at(&self, now: i64) -> Result<SweepOutcome> {
+        match self.try_sweep(now).await {
+            Ok(outcome) => {
+                match &outcome {
+                    SweepOutcome::Swept(receipt) => {
+                        *self.last_sweep_at.lock().unwrap_or_else(|e| e.into_inner()) = Some(now);
+                        self.emit(&SweepEvent::Swept(receipt.clone()));
+                        let max_fee_sats = self.policy().max_fee_sats;
+                        if receipt.fee_sats > max_fee_sats {
+                            self.emit(&SweepEvent::FeeCeilingExceeded {
+                                txid: receipt.txid.clone(),
+                                fee_sats: receipt.fee_sats,
+                                max_fee_sats,
+                            });
+                        }
+                    }
+                    SweepOutcome::Skipped(skip) => self.emit(&SweepEvent::Skipped(skip.clone())),
+                }
+                Ok(outcome)
+            }
+            Err(e) => {
+                self.emit(&SweepEvent::Failed {
+                    error: e.to_string(),
+                });
+                Err(e)
+            }
+        }
+    }
+
+    async fn try_sweep(&self, now: i64) -> Result<SweepOutcome> {
+        let policy = self.policy();
+
+        let balance_sats = self.executor.spendable_balance().await?;
+        if balance_sats < policy.threshold_sats {
+            return Ok(SweepOutcome::Skipped(SweepSkip::BelowThreshold {
+                balance_sats,
+                threshold_sats: policy.threshold_sats,
+            }));
+        }
+
+        let sweepable = balance_sats - policy.reserve_sats;
+        let fee_sats = self
+            .executor
+            .estimate_fee(&policy.destination, sweepable, policy.target_blocks)
+            .await?;
+        if fee_sats > policy.max_fee_sats {
+            return Ok(SweepOutcome::Skipped(SweepSkip::FeeTooHigh {
+                fee_sats,
+                max_fee_sats: policy.max_fee_sats,
+            }));
+        }
+        let amount_sats = sweepable.saturating_sub(fee_sats);
+        if amount_sats == 0 {
+            return Ok(SweepOutcome::Skipped(SweepSkip::NothingToSweep {
+                balance_sats,
+                fee_sats,
+            }));
+        }
+
+        // The estimate is in sats, not a rate, so the rate is derived from
+        // the size of the transaction the wallet will build
+        let hints = match self
+            .executor
+            .spend_hints(&policy.destination, amount_sats)
+            .await
+        {
+            Ok(hints) => hints,
+            Err(PaykitError::Unimplemented(_)) => {
+                return Ok(SweepOutcome::Skipped(SweepSkip::FeeUncapped))
+            }
+            Err(e) => return Err(e),
+        };
+        let fee_rate = capped_fee_rate(&hints, fee_sats, policy.max_fee_sats);
+        let tx = self
+            .executor
+            .send_to_address(&policy.destination, amount_sats, Some(fee_rate))
+            .await?;
+
+        Ok(SweepOutcome::Swept(SweepReceipt {
+            sweep_id: format!(
+                "sweep_{}_{}",
+                now,
+                tx.txid.chars().take(8).collect::<String>()
+            ),
+            txid: tx.txid,
+            destination: policy.destination,
+            amount_sats,
+            fee_sats: tx.fee_sats,
+            fee_rate: tx.fee_rate,
+            balance_before_sats: balance_sats,
+            swept_at: now,
+        }))
+    }
+
+    fn emit(&self, event: &SweepEvent) {
+        let callbacks = match self.callbacks.read() {
+            Ok(callbacks) => callbacks.clone(),
+            Err(_) => return,
+        };
+        for callback in callbacks {
+            callback(event);
+        }
+    }
+}
+
+/// Fee rate, in sat/vB, for the sweep transaction `hints` describes.
+///
+/// Aims for `fee_sats` on the smallest transaction with that many inputs
+/// and outputs, but never more than `max_fee_sats` spread over the largest,
+/// so whatever single-signature scripts the wallet spends, the fee stays
+/// within the ceiling.
+fn capped_fee_rate(hints: &SpendHints, fee_sats: u64, max_fee_sats: u64) -> f64 {
+    let inputs = u64::from(hints.input_count.max(1));
+    let outputs = if hints.change_sats.is_some() { 2 } else { 1 };
+    let min_vsize = TX_OVERHEAD_VBYTES + inputs * MIN_INPUT_VBYTES + outputs * MIN_OUTPUT_VBYTES;
+    let max_vsize = TX_OVERHEAD_VBYTES + inputs * MAX_INPUT_VBYTES + outputs * MAX_OUTPUT_VBYTES;
+    (fee_sats as f64 / min_vsize as f64).min(max_fee_sats as f64 / max_vsize as f64)
+}
+
+fn current_timestamp() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::methods::{BitcoinTxResult, MockBitcoinExecutor};
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn sweeper(balance_sats: u64, policy: SweepPolicy) -> TreasurySweeper {
+        TreasurySweeper::new(
+            Arc::new(MockBitcoinExecutor::with_balance(balance_sats)),
+            policy,
+        )
+        .unwrap()
+    }
+
+    fn events(sweeper: &TreasurySweeper) -> Arc<Mutex<Vec<SweepEvent>>> {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        sweeper.on_event(Arc::new(move |e| sink.lock().unwrap().push(e.clone())));
+        events
+    }
+
+    #[test]
+    fn test_policy_validation() {
+        assert!(SweepPolicy::new("", 1000, 500).validate().is_err());
+        assert!(SweepPolicy::new("bc1q", 1000, 500)
+            .with_reserve_sats(1000)
+            .validate()
+            .is_err());
+        assert!(SweepPolicy::new("bc1q", 1000, 500)
+            .with_schedule(SweepSchedule::Interval { every_secs: 0 })
+            .validate()
+            .is_err());
+        assert!(SweepPolicy::new("bc1q", 1000, 500).validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_sweep_leaves_reserve() {
+        // Mock estimates 280 sats for a 6 block target
+        let sweeper = sweeper(
+            1_000_000,
+            SweepPolicy::new("bc1qcold", 500_000, 1_000).with_reserve_sats(100_000),
+        );
+        let events = events(&sweeper);
+
+        let SweepOutcome::Swept(receipt) = sweeper.sweep_at(NOW).await.unwrap() else {
+            panic!("expected a sweep");
+        };
+        assert_eq!(receipt.amount_sats, 1_000_000 - 100_000 - 280);
+        assert_eq!(receipt.destination, "bc1qcold");
+        assert_eq!(receipt.balance_before_sats, 1_000_000);
+        assert_eq!(sweeper.last_sweep_at(), Some(NOW));
+        assert_eq!(*events.lock().unwrap(), vec![SweepEvent::Swept(receipt)]);
+    }
+
+    #[tokio::test]
+    async fn test_sweep_skips_below_threshold_and_fee_ceiling() {
+        let low = sweeper(10_000, SweepPolicy::new("bc1qcold", 50_000, 1_000));
+        assert!(matches!(
+            low.sweep_at(NOW).await.unwrap(),
+            SweepOutcome::Skipped(SweepSkip::BelowThreshold { .. })
+        ));
+
+        let urgent = sweeper(
+            100_000,
+            SweepPolicy::new("bc1qcold", 50_000, 1_000).with_target_blocks(1),
+        );
+        let events = events(&urgent);
+        let skip = SweepSkip::FeeTooHigh {
+            fee_sats: 1400,
+            max_fee_sats: 1_000,
+        };
+        assert_eq!(
+            urgent.sweep_at(NOW).await.unwrap(),
+            SweepOutcome::Skipped(skip.clone())
+        );
+        assert_eq!(*events.lock().unwrap(), vec![SweepEvent::Skipped(skip)]);
+        assert_eq!(urgent.last_sweep_at(), None);
+    }
+
+    /// Wallet whose sweep transaction pays far more than its spend hints
+    /// imply, or that gives no hints when the flag is unset.
+    struct UnderestimatingWallet(MockBitcoinExecutor, bool);
+
+    #[async_trait::async_trait]
+    impl BitcoinExecutor for UnderestimatingWallet {
+        async fn send_to_address(
+            &self,
+            address: &str,
+            amount_sats: u64,
+            fee_rate: Option<f64>,
+        ) -> Result<BitcoinTxResult> {
+            assert!(fee_rate.is_some());
+            let tx = self.0.send_to_address(address, amount_sats, None).await?;
+            Ok(BitcoinTxResult::new(tx.txid, 0, 5_000, 12.0))
+        }
+
+        async fn spend_hints(&self, address: &str, amount_sats: u64) -> Result<SpendHints> {
+            if !self.1 {
+                return Err(PaykitError::Unimplemented("spend hints"));
+            }
+            self.0.spend_hints(address, amount_sats).await
+        }
+
+        async fn estimate_fee(&self, address: &str, amount_sats: u64, target: u32) -> Result<u64> {
+            self.0.estimate_fee(address, amount_sats, target).await
+        }
+
+        async fn get_transaction(&self, txid: &str) -> Result<Option<BitcoinTxResult>> {
+            self.0.get_transaction(txid).await
+        }
+
+        async fn verify_transaction(&self, txid: &str, address: &str, amount: u64) -> Result<bool> {
+            self.0.verify_transaction(txid, address, amount).await
+        }
+
+        async fn spendable_balance(&self) -> Result<u64> {
+            self.0.spendable_balance().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sweep_reports_actual_fee_over_ceiling() {
+        let sweeper = TreasurySweeper::new(
+            Arc::new(UnderestimatingWallet(
+                MockBitcoinExecutor::with_balance(100_000),
+                true,
+            )),
+            SweepPolicy::new("bc1qcold", 50_000, 1_000),
+        )
+        .unwrap();
+        let events = events(&sweeper);
+
+        let SweepOutcome::Swept(receipt) = sweeper.sweep_at(NOW).await.unwrap() else {
+            panic!("expected a sweep");
+        };
+        assert_eq!(receipt.fee_sats, 5_000);
+        assert_eq!(
+            events.lock().unwrap().last(),
+            Some(&SweepEvent::FeeCeilingExceeded {
+                txid: receipt.txid.clone(),
+                fee_sats: 5_000,
+                max_fee_sats: 1_000,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sweep_needs_spend_hints() {
+        let sweeper = TreasurySweeper::new(
+            Arc::new(UnderestimatingWallet(
+                MockBitcoinExecutor::with_balance(100_000),
+                false,
+            )),
+            SweepPolicy::new("bc1qcold", 50_000, 1_000),
+        )
+        .unwrap();
+
+        assert_eq!(
+            sweeper.sweep_at(NOW).await.unwrap(),
+            SweepOutcome::Skipped(SweepSkip::FeeUncapped)
+        );
+        assert_eq!(sweeper.last_sweep_at(), None);
+    }
+
+    #[test]
+    fn test_fee_rate_is_capped_for_largest_transaction() {
+        let hints = SpendHints {
+            input_count: 3,
+            input_addresses: 3,
+            change_sats: None,
+            change_type_matches: true,
+        };
+
+        // A cheap estimate is aimed for on the smallest transaction (216 vB)
+        assert_eq!(capped_fee_rate(&hints, 1_000, 10_000), 1_000.0 / 216.0);
+
+        // Otherwise the largest transaction (498 vB) still fits the ceiling
+        let rate = capped_fee_rate(&hints, 5_000, 1_000);
+        assert!(rate * 498.0 <= 1_000.0);
+        assert!(rate * 216.0 < 5_000.0);
+    }
+
+    #[tokio::test]
+    async fn test_tick_follows_schedule() {
+        let manual = sweeper(100_000, SweepPolicy::new("bc1qcold", 50_000, 1_000));
+        assert_eq!(
+            manual.tick_at(NOW).await.unwrap(),
+            SweepOutcome::Skipped(SweepSkip::NotDue { next_due_at: None })
+        );
+
+        let hourly = sweeper(
+            100_000,
+            SweepPolicy::new("bc1qcold", 50_000, 1_000)
+                .with_schedule(SweepSchedule::Interval { every_secs: 3600 }),
+        );
+        assert!(matches!(
+            hourly.tick_at(NOW).await.unwrap(),
+            SweepOutcome::Swept(_)
+        ));
+        assert_eq!(
+            hourly.tick_at(NOW + 60).await.unwrap(),
+            SweepOutcome::Skipped(SweepSkip::NotDue {
+                next_due_at: Some(NOW + 3600)
+            })
+        );
+        assert!(matches!(
+            hourly.tick_at(NOW + 3600).await.unwrap(),
+            SweepOutcome::Swept(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_sweep_failure_is_reported() {
+        let sweeper = TreasurySweeper::new(
+            Arc::new(MockBitcoinExecutor::failing()),
+            SweepPolicy::new("bc1qcold", 50_000, 1_000),
+        )
+        .unwrap();
+        let events = events(&sweeper);
+
+        assert!(sweeper.sweep_at(NOW).await.is_err());
+        assert!(matches!(
+            events.lock().unwrap().as_slice(),
+            [SweepEvent::Failed { .. }]
+        ));
+    }
+}
@@ -4,6 +4,8 @@
 //! - `pubky://` URIs for public keys
 //! - Invoice URIs (Lightning invoices, Bitcoin addresses)
 //! - Payment request URIs
+//! - Signed payment request deep links (`paykit://request/...`)
+//! - Human-readable handles (`alice@example.com`)
 //!
 //! # Examples
 //!
@@ -32,7 +34,11 @@
 //! # }
 //! ```
 
+use crate::handles::Handle;
 use crate::{MethodId, PaykitError, PublicKey, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
 /// A parsed Paykit URI.
@@ -66,6 +72,23 @@ pub enum PaykitUri {
         /// The public key of the requester.
         from: PublicKey,
     },
+    /// A signed payment request deep link carrying the full request.
+    ///
+    /// Format: `paykit://request/<payload>.<signature>`
+    ///
+    /// The signature has already been verified against the requester's key
+    /// when this variant is returned by [`parse_uri`]; expiry has not.
+    SignedRequest(Box<SignedPaymentRequestLink>),
+    /// A human-readable handle naming a public key.
+    ///
+    /// Format: `user@domain`
+    ///
+    /// The handle has not been resolved; use a
+    /// [`HandleResolver`](crate::handles::HandleResolver) to get the key.
+    Handle {
+        /// The normalized handle.
+        handle: Handle,
+    },
 }
 
 impl PaykitUri {
@@ -74,6 +97,7 @@ impl PaykitUri {
         match self {
             PaykitUri::Pubky { public_key } => Some(public_key),
             PaykitUri::PaymentRequest { from, .. } => Some(from),
+            PaykitUri::SignedRequest(link) => Some(&link.request().from),
             _ => None,
         }
     }
@@ -96,6 +120,8 @@ impl PaykitUri {
 /// 3. **Bitcoin Addresses**: `bitcoin:<address>` or just `bc1q...` or `1A1...`
 /// 4. **Payment Requests**: `paykit:request?request_id=<id>&from=<pubky-uri>`
 /// 5. **Generic Invoices**: `paykit:invoice?method=<method>&data=<data>`
+/// 6. **Signed Payment Requests**: `paykit://request/<payload>.<signature>`
+/// 7. **Handles**: `alice@example.com`
 ///
 /// # Errors
 ///
@@ -140,6 +166,11 @@ pub fn parse_uri(uri: &str) -> Result<PaykitUri> {
         });
     }
 
+    // Check for a handle (user@domain); addresses and invoices never contain '@'
+    if let Ok(handle) = Handle::parse(uri) {
+        return Ok(PaykitUri::Handle { handle });
+    }
+
     // Check for bitcoin: or Bitcoin address formats
     if let Some(stripped) = uri.strip_prefix("bitcoin:") {
         return Ok(PaykitUri::Invoice {
@@ -212,6 +243,13 @@ fn parse_paykit_uri(uri: &str) -> Result<PaykitUri> {
     // Remove fragment if present
     let uri = uri.split('#').next().unwrap_or(uri);
 
+    // Check for signed deep link: paykit://request/<payload>.<signature>
+    if let Some(link) = uri.strip_prefix("//request/") {
+        let link = SignedPaymentRequestLink::decode(link)?;
+        link.verify_signature()?;
+        return Ok(PaykitUri::SignedRequest(Box::new(link)));
+    }
+
     // Check for request format: paykit:request?request_id=<id>&from=<pubky>
     if let Some(query) = uri.strip_prefix("request?") {
         return parse_payment_request_uri(query);
@@ -338,6 +376,305 @@ fn url_decode(encoded: &str) -> Result<String> {
     Ok(decoded)
 }
 
+// ============================================================================
+// Signed Payment Request Links
+// ============================================================================
+
+/// URI prefix for signed payment request deep links.
+pub const SIGNED_REQUEST_PREFIX: &str = "paykit://request/";
+
+/// Domain separation tag prepended to the payload before signing.
+#[cfg(feature = "pubky")]
+pub const SIGNED_REQUEST_DOMAIN: &[u8] = b"PAYKIT_REQUEST_LINK_V1:";
+
+/// Current payload encoding version.
+const SIGNED_REQUEST_VERSION: u8 = 1;
+
+/// A payment request that can be embedded in a deep link.
+///
+/// Build one with [`PaymentRequestLink::new`], sign it with the requester's
+/// keypair and share the result of [`SignedPaymentRequestLink::to_uri`] over
+/// chat or as a QR code.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "pubky")]
+/// # fn main() -> paykit_lib::Result<()> {
+/// use paykit_lib::uri::{parse_uri, PaykitUri, PaymentRequestLink};
+/// use paykit_lib::MethodId;
+///
+/// let keypair = pubky::Keypair::random();
+/// let link = PaymentRequestLink::new("req-1", keypair.public_key())
+///     .with_amount_sats(5_000)
+///     .with_methods(vec![MethodId::lightning()])
+///     .with_expires_at(chrono::Utc::now().timestamp() + 3600)
+///     .sign(&keypair)?;
+///
+/// match parse_uri(&link.to_uri())? {
+///     PaykitUri::SignedRequest(parsed) => assert_eq!(parsed.request().request_id, "req-1"),
+///     _ => unreachable!(),
+/// }
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "pubky"))]
+/// # fn main() {}
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PaymentRequestLink {
+    /// Unique request ID, echoed back in the payment receipt.
+    pub request_id: String,
+    /// Public key of the requester (the payee).
+    pub from: PublicKey,
+    /// Requested amount in satoshis, if fixed.
+    pub amount_sats: Option<u64>,
+    /// Payment methods the requester accepts, in order of preference.
+    pub methods: Vec<MethodId>,
+    /// Human-readable description.
+    pub description: Option<String>,
+    /// When the request was created (Unix seconds).
+    pub created_at: i64,
+    /// When the request expires (Unix seconds).
+    pub expires_at: Option<i64>,
+}
+
+/// Compact wire form of [`PaymentRequestLink`].
+#[derive(Serialize, Deserialize)]
+struct LinkPayload {
+    #[serde(rename = "v")]
+    version: u8,
+    #[serde(rename = "i")]
+    request_id: String,
+    #[serde(rename = "f")]
+    from: String,
+    #[serde(rename = "a", default, skip_serializing_if = "Option::is_none")]
+    amount_sats: Option<u64>,
+    #[serde(rename = "m", default, skip_serializing_if = "Vec::is_empty")]
+    methods: Vec<String>,
+    #[serde(rename = "d", default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(rename = "c")]
+    created_at: i64,
+    #[serde(rename = "e", default, skip_serializing_if = "Option::is_none")]
+    expires_at: Option<i64>,
+}
+
+impl PaymentRequestLink {
+    /// Create a request link from `from`, timestamped now.
+    pub fn new(request_id: impl Into<String>, from: PublicKey) -> Self {
+        Self {
+            request_id: request_id.into(),
+            from,
+            amount_sats: None,
+            methods: Vec::new(),
+            description: None,
+            created_at: chrono::Utc::now().timestamp(),
+            expires_at: None,
+        }
+    }
+
+    /// Set the requested amount in satoshis.
+    pub fn with_amount_sats(mut self, amount_sats: u64) -> Self {
+        self.amount_sats = Some(amount_sats);
+        self
+    }
+
+    /// Set the accepted payment methods.
+    pub fn with_methods(mut self, methods: Vec<MethodId>) -> Self {
+        self.methods = methods;
+        self
+    }
+
+    /// Set the description.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Set the expiry timestamp (Unix seconds).
+    pub fn with_expires_at(mut self, expires_at: i64) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Check whether the request has expired at `now`.
+    pub fn is_expired_at(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|exp| now >= exp)
+    }
+
+    /// Check whether the request has expired.
+    pub fn is_expired(&self) -> bool {
+        self.is_expired_at(chrono::Utc::now().timestamp())
+    }
+
+    /// Sign the request with the requester's keypair.
+    ///
+    /// The keypair must belong to [`from`](Self::from).
+    #[cfg(feature = "pubky")]
+    pub fn sign(self, keypair: &pubky::Keypair) -> Result<SignedPaymentRequestLink> {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        if keypair.public_key() != self.from {
+            return Err(PaykitError::ValidationFailed(
+                "Signing key does not match the request's 'from' key".to_string(),
+            ));
+        }
+
+        let payload = self.encode_payload()?;
+        let signing_key = SigningKey::from_bytes(&keypair.secret_key());
+        let signature = signing_key.sign(&signing_message(&payload));
+
+        Ok(SignedPaymentRequestLink {
+            request: self,
+            payload,
+            signature: URL_SAFE_NO_PAD.encode(signature.to_bytes()),
+        })
+    }
+
+    #[cfg(feature = "pubky")]
+    fn encode_payload(&self) -> Result<String> {
+        let payload = LinkPayload {
+            version: SIGNED_REQUEST_VERSION,
+            request_id: self.request_id.clone(),
+            from: self.from.to_string(),
+            amount_sats: self.amount_sats,
+            methods: self.methods.iter().map(|m| m.0.clone()).collect(),
+            description: self.description.clone(),
+            created_at: self.created_at,
+            expires_at: self.expires_at,
+        };
+        let json = serde_json::to_vec(&payload)
+            .map_err(|e| PaykitError::Serialization(format!("payment request link: {}", e)))?;
+        Ok(URL_SAFE_NO_PAD.encode(json))
+    }
+}
+
+/// A [`PaymentRequestLink`] together with the requester's signature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignedPaymentRequestLink {
+    request: PaymentRequestLink,
+    /// Base64url payload exactly as signed.
+    payload: String,
+    /// Base64url Ed25519 signature.
+    signature: String,
+}
+
+impl SignedPaymentRequestLink {
+    /// The embedded payment request.
+    pub fn request(&self) -> &PaymentRequestLink {
+        &self.request
+    }
+
+    /// Consume the link and return the embedded request.
+    pub fn into_request(self) -> PaymentRequestLink {
+        self.request
+    }
+
+    /// Encode as a `paykit://request/...` URI.
+    pub fn to_uri(&self) -> String {
+        format!(
+            "{}{}.{}",
+            SIGNED_REQUEST_PREFIX, self.payload, self.signature
+        )
+    }
+
+    /// Decode a link without verifying its signature.
+    ///
+    /// Accepts either the full URI or the part after `paykit://request/`.
+    pub fn decode(link: &str) -> Result<Self> {
+        let link = link.trim();
+        let link = link.strip_prefix(SIGNED_REQUEST_PREFIX).unwrap_or(link);
+        let invalid = |reason: &str| PaykitError::InvalidData {
+            field: "payment_request_link".to_string(),
+            reason: reason.to_string(),
+        };
+
+        let (payload, signature) = link
+            .split_once('.')
+            .ok_or_else(|| invalid("missing signature"))?;
+        let json = URL_SAFE_NO_PAD
+            .decode(payload)
+            .map_err(|_| invalid("payload is not valid base64url"))?;
+        let wire: LinkPayload =
+            serde_json::from_slice(&json).map_err(|e| invalid(&format!("bad payload: {}", e)))?;
+
+        if wire.version != SIGNED_REQUEST_VERSION {
+            return Err(invalid(&format!("unsupported version {}", wire.version)));
+        }
+
+        let request = PaymentRequestLink {
+            request_id: wire.request_id,
+            from: parse_pubky_key(&wire.from)?,
+            amount_sats: wire.amount_sats,
+            methods: wire.methods.into_iter().map(MethodId).collect(),
+            description: wire.description,
+            created_at: wire.created_at,
+            expires_at: wire.expires_at,
+        };
+
+        Ok(Self {
+            request,
+            payload: payload.to_string(),
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Verify the signature against the requester's public key.
+    ///
+    /// Requires the `pubky` feature; without it verification always fails.
+    pub fn verify_signature(&self) -> Result<()> {
+        #[cfg(feature = "pubky")]
+        {
+            use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+            let invalid =
+                || PaykitError::ValidationFailed("Invalid payment request signature".to_string());
+            let bytes = URL_SAFE_NO_PAD
+                .decode(&self.signature)
+                .map_err(|_| invalid())?;
+            let bytes: [u8; 64] = bytes.try_into().map_err(|_| invalid())?;
+            let key =
+                VerifyingKey::from_bytes(&self.request.from.to_bytes()).map_err(|_| invalid())?;
+
+            key.verify(
+                &signing_message(&self.payload),
+                &Signature::from_bytes(&bytes),
+            )
+            .map_err(|_| invalid())
+        }
+
+        #[cfg(not(feature = "pubky"))]
+        {
+            Err(PaykitError::Unimplemented(
+                "payment request signature verification requires the pubky feature",
+            ))
+        }
+    }
+
+    /// Verify the signature and that the request has not expired at `now`.
+    pub fn verify_at(&self, now: i64) -> Result<()> {
+        self.verify_signature()?;
+        if self.request.is_expired_at(now) {
+            return Err(PaykitError::ValidationFailed(
+                "Payment request has expired".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Verify the signature and that the request has not expired.
+    pub fn verify(&self) -> Result<()> {
+        self.verify_at(chrono::Utc::now().timestamp())
+    }
+}
+
+#[cfg(feature = "pubky")]
+fn signing_message(payload: &str) -> Vec<u8> {
+    let mut message = SIGNED_REQUEST_DOMAIN.to_vec();
+    message.extend_from_slice(payload.as_bytes());
+    message
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -515,6 +852,17 @@ mod tests {
         // The important part is that invoice URIs have method_id, which we've tested
     }
 
+    #[test]
+    fn test_parse_handle() {
+        let uri = parse_uri("Alice@Example.com").unwrap();
+        match &uri {
+            PaykitUri::Handle { handle } => assert_eq!(handle.to_string(), "alice@example.com"),
+            _ => panic!("Expected Handle URI"),
+        }
+        assert!(uri.public_key().is_none());
+        assert!(parse_uri("alice@localhost").is_err());
+    }
+
     #[test]
     fn test_invalid_uri() {
         assert!(parse_uri("invalid://uri").is_err());
@@ -526,4 +874,78 @@ mod tests {
         assert!(parse_uri("paykit:request?request_id=req_123").is_err());
         assert!(parse_uri("paykit:request?from=pubky://abc123").is_err());
     }
+
+    #[cfg(feature = "pubky")]
+    fn signed_link(keypair: &pubky::Keypair) -> SignedPaymentRequestLink {
+        PaymentRequestLink::new("req-42", keypair.public_key())
+            .with_amount_sats(2_500)
+            .with_methods(vec![MethodId::lightning(), MethodId::onchain()])
+            .with_description("Coffee & cake")
+            .with_expires_at(2_000_000_000)
+            .sign(keypair)
+            .unwrap()
+    }
+
+    #[cfg(feature = "pubky")]
+    #[test]
+    fn test_signed_request_roundtrip() {
+        let keypair = pubky::Keypair::random();
+        let link = signed_link(&keypair);
+        let uri = link.to_uri();
+        assert!(uri.starts_with(SIGNED_REQUEST_PREFIX));
+
+        match parse_uri(&uri).unwrap() {
+            PaykitUri::SignedRequest(parsed) => {
+                let request = parsed.request();
+                assert_eq!(request.request_id, "req-42");
+                assert_eq!(request.from, keypair.public_key());
+                assert_eq!(request.amount_sats, Some(2_500));
+                assert_eq!(request.methods.len(), 2);
+                assert_eq!(request.description.as_deref(), Some("Coffee & cake"));
+                assert!(parsed.verify_at(1_900_000_000).is_ok());
+            }
+            _ => panic!("Expected SignedRequest URI"),
+        }
+    }
+
+    #[cfg(feature = "pubky")]
+    #[test]
+    fn test_signed_request_tampered_payload() {
+        let keypair = pubky::Keypair::random();
+        let link = signed_link(&keypair);
+
+        let mut forged = link.request().clone();
+        forged.amount_sats = Some(1);
+        let forged_payload = forged.encode_payload().unwrap();
+        let uri = link.to_uri();
+        let (_, signature) = uri.rsplit_once('.').unwrap();
+        let forged_uri = format!("{}{}.{}", SIGNED_REQUEST_PREFIX, forged_payload, signature);
+
+        assert!(parse_uri(&forged_uri).is_err());
+    }
+
+    #[cfg(feature = "pubky")]
+    #[test]
+    fn test_signed_request_wrong_signer() {
+        let keypair = pubky::Keypair::random();
+        let other = pubky::Keypair::random();
+        let result = PaymentRequestLink::new("req", keypair.public_key()).sign(&other);
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "pubky")]
+    #[test]
+    fn test_signed_request_expired() {
+        let keypair = pubky::Keypair::random();
+        let link = signed_link(&keypair);
+        assert!(link.verify_at(2_000_000_000).is_err());
+        assert!(link.request().is_expired_at(2_000_000_001));
+    }
+
+    #[test]
+    fn test_signed_request_malformed() {
+        assert!(SignedPaymentRequestLink::decode("paykit://request/nodot").is_err());
+        assert!(SignedPaymentRequestLink::decode("paykit://request/!!!.sig").is_err());
+        assert!(parse_uri("paykit://request/e30.sig").is_err());
+    }
 }
@@ -0,0 +1,957 @@
+//! Gift and Voucher Codes
+//!
+//! A voucher is a prepaid amount the issuer hands out as a code
+//! (`paykit://voucher/...`), for example as a gift card or a refund. The code
+//! is a [`Voucher`] signed with the issuer's key, so anyone can check who
+//! issued it and for how much, but only the issuer can pay it out.
+//!
+//! The issuer keeps every code it hands out in a [`VoucherLedger`]. To redeem
+//! a code, the holder sends it to the issuer together with their Pubky
+//! identity; the issuer's [`VoucherRedeemer`] claims the voucher in the
+//! ledger and pays the amount from the issuer's wallet to one of the
+//! redeemer's published endpoints, through the regular method registry.
+//!
+//! A claim reserves the voucher before any payment is attempted, so a code
+//! submitted twice (or by two people at once) is paid at most once. A failed
+//! payment releases the reservation and the code can be redeemed again. If
+//! a plugin fails in a way that does not rule out the payout having been
+//! sent, no other method is tried and the voucher stays claimed until the
+//! issuer checks the wallet and completes or releases it.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use paykit_lib::vouchers::{Voucher, VoucherLedger, VoucherRedeemer};
+//!
+//! // Issuer
+//! let signed = Voucher::new("gift-42", keypair.public_key(), 21_000, now)
+//!     .with_memo("Happy birthday")
+//!     .with_expires_at(now + 90 * 86_400)
+//!     .sign(&keypair)?;
+//! ledger.issue(&signed)?;
+//! share(signed.to_code());
+//!
+//! // Later, when the holder claims it
+//! let supported = get_payment_list(&reader, &redeemer_key).await?;
+//! let redeemer = VoucherRedeemer::new(registry);
+//! let execution = redeemer.redeem_at(&ledger, &code, &redeemer_z32, &supported, now).await?;
+//! ```
+
+use crate::methods::{Amount, PaymentExecution, PaymentMethodRegistry};
+use crate::selection::{pay_with_fallback, Fallback, SelectionPreferences};
+use crate::{MethodId, PaykitError, PublicKey, Result, SupportedPayments};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Prefix of voucher codes.
+pub const VOUCHER_PREFIX: &str = "paykit://voucher/";
+
+/// Domain separation tag prepended to the payload before signing.
+#[cfg(feature = "pubky")]
+const VOUCHER_DOMAIN: &[u8] = b"PAYKIT_VOUCHER_V1:";
+
+/// Current payload encoding version.
+const VOUCHER_VERSION: u8 = 1;
+
+/// Maximum length of a voucher ID.
+const MAX_VOUCHER_ID_LEN: usize = 64;
+
+/// A prepaid amount the issuer promises to pay to whoever redeems it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Voucher {
+    /// Issuer-chosen ID, unique among the issuer's vouchers.
+    pub voucher_id: String,
+    /// Public key of the issuer, who pays the voucher out.
+    pub issuer: PublicKey,
+    /// Value of the voucher in satoshis.
+    pub amount_sats: u64,
+    /// Method the voucher must be paid out with, if restricted.
+    pub method: Option<MethodId>,
+    /// Human-readable message for the holder.
+    pub memo: Option<String>,
+    /// When the voucher was issued (Unix seconds).
+    pub created_at: i64,
+    /// When the voucher stops being redeemable (Unix seconds).
+    pub expires_at: Option<i64>,
+}
+
+/// Compact wire form of [`Voucher`].
+#[derive(Serialize, Deserialize)]
+struct VoucherPayload {
+    #[serde(rename = "v")]
+    version: u8,
+    #[serde(rename = "i")]
+    voucher_id: String,
+    #[serde(rename = "f")]
+    issuer: String,
+    #[serde(rename = "a")]
+    amount_sats: u64,
+    #[serde(rename = "m", default, skip_serializing_if = "Option::is_none")]
+    method: Option<String>,
+    #[serde(rename = "d", default, skip_serializing_if = "Option::is_none")]
+    memo: Option<String>,
+    #[serde(rename = "c")]
+    created_at: i64,
+    #[serde(rename = "e", default, skip_serializing_if = "Option::is_none")]
+    expires_at: Option<i64>,
+}
+
+impl Voucher {
+    /// Create a voucher worth `amount_sats`, issued at `created_at`.
+    pub fn new(
+        voucher_id: impl Into<String>,
+        issuer: PublicKey,
+        amount_sats: u64,
+        created_at: i64,
+    ) -> Self {
+        Self {
+            voucher_id: voucher_id.into(),
+            issuer,
+            amount_sats,
+            method: None,
+            memo: None,
+            created_at,
+            expires_at: None,
+        }
+    }
+
+    /// Only pay the voucher out with `method`.
+    pub fn with_method(mut self, method: MethodId) -> Self {
+        self.method = Some(method);
+        self
+    }
+
+    /// Set the message for the holder.
+    pub fn with_memo(mut self, memo: impl Into<String>) -> Self {
+        self.memo = Some(memo.into());
+        self
+    }
+
+    /// Set the expiry timestamp (Unix seconds).
+    pub fn with_expires_at(mut self, expires_at: i64) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Check whether the voucher has expired at `now`.
+    pub fn is_expired_at(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|exp| now >= exp)
+    }
+
+    /// Check the voucher is well-formed.
+    pub fn validate(&self) -> Result<()> {
+        let id_ok = !self.voucher_id.is_empty()
+            && self.voucher_id.len() <= MAX_VOUCHER_ID_LEN
+            && self
+                .voucher_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !id_ok {
+            return Err(PaykitError::invalid_data(
+                "voucher_id",
+                "must be 1-64 ASCII letters, digits, '-' or '_'",
+            ));
+        }
+        if self.amount_sats == 0 {
+            return Err(PaykitError::invalid_data(
+                "amount_sats",
+                "must be greater than zero",
+            ));
+        }
+        if self.expires_at.is_some_and(|exp| exp <= self.created_at) {
+            return Err(PaykitError::invalid_data(
+                "expires_at",
+                "must be after created_at",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Sign the voucher with the issuer's keypair.
+    ///
+    /// The keypair must belong to [`issuer`](Self::issuer).
+    #[cfg(feature = "pubky")]
+    pub fn sign(self, keypair: &pubky::Keypair) -> Result<SignedVoucher> {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        self.validate()?;
+        if keypair.public_key() != self.issuer {
+            return Err(PaykitError::ValidationFailed(
+                "Signing key does not match the voucher's issuer".to_string(),
+            ));
+        }
+
+        let payload = self.encode_payload()?;
+        let signing_key = SigningKey::from_bytes(&keypair.secret_key());
+        let signature = signing_key.sign(&signing_message(&payload));
+
+        Ok(SignedVoucher {
+            voucher: self,
+            payload,
+            signature: URL_SAFE_NO_PAD.encode(signature.to_bytes()),
+        })
+    }
+
+    #[cfg(feature = "pubky")]
+    fn encode_payload(&self) -> Result<String> {
+        let payload = VoucherPayload {
+            version: VOUCHER_VERSION,
+            voucher_id: self.voucher_id.clone(),
+            issuer: self.issuer.to_string(),
+            amount_sats: self.amount_sats,
+            method: self.method.as_ref().map(|m| m.0.clone()),
+            memo: self.memo.clone(),
+            created_at: self.created_at,
+            expires_at: self.expires_at,
+        };
+        let json = serde_json::to_vec(&payload)
+            .map_err(|e| PaykitError::Serialization(format!("voucher: {}", e)))?;
+        Ok(URL_SAFE_NO_PAD.encode(json))
+    }
+}
+
+/// A [`Voucher`] together with the issuer's signature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignedVoucher {
+    voucher: Voucher,
+    /// Base64url payload exactly as signed.
+    payload: String,
+    /// Base64url Ed25519 signature.
+    signature: String,
+}
+
+impl SignedVoucher {
+    /// The embedded voucher.
+    pub fn voucher(&self) -> &Voucher {
+        &self.voucher
+    }
+
+    /// Encode as a `paykit://voucher/...` code.
+    pub fn to_code(&self) -> String {
+        format!("{}{}.{}", VOUCHER_PREFIX, self.payload, self.signature)
+    }
+
+    /// Decode a voucher code without verifying its signature.
+    ///
+    /// Accepts either the full code or the part after `paykit://voucher/`.
+    pub fn decode(code: &str) -> Result<Self> {
+        let code = code.trim();
+        let code = code.strip_prefix(VOUCHER_PREFIX).unwrap_or(code);
+        let invalid = |reason: &str| PaykitError::invalid_data("voucher", reason);
+
+        let (payload, signature) = code
+            .split_once('.')
+            .ok_or_else(|| invalid("missing signature"))?;
+        let json = URL_SAFE_NO_PAD
+            .decode(payload)
+            .map_err(|_| invalid("payload is not valid base64url"))?;
+        let wire: VoucherPayload =
+            serde_json::from_slice(&json).map_err(|e| invalid(&format!("bad payload: {}", e)))?;
+
+        if wire.version != VOUCHER_VERSION {
+            return Err(invalid(&format!("unsupported version {}", wire.version)));
+        }
+
+        let voucher = Voucher {
+            voucher_id: wire.voucher_id,
+            issuer: parse_issuer(&wire.issuer)?,
+            amount_sats: wire.amount_sats,
+            method: wire.method.map(MethodId),
+            memo: wire.memo,
+            created_at: wire.created_at,
+            expires_at: wire.expires_at,
+        };
+        voucher.validate()?;
+
+        Ok(Self {
+            voucher,
+            payload: payload.to_string(),
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Verify the signature against the issuer's public key.
+    ///
+    /// Requires the `pubky` feature; without it verification always fails.
+    pub fn verify_signature(&self) -> Result<()> {
+        #[cfg(feature = "pubky")]
+        {
+            use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+            let invalid = || PaykitError::ValidationFailed("Invalid voucher signature".to_string());
+            let bytes = URL_SAFE_NO_PAD
+                .decode(&self.signature)
+                .map_err(|_| invalid())?;
+            let bytes: [u8; 64] = bytes.try_into().map_err(|_| invalid())?;
+            let key =
+                VerifyingKey::from_bytes(&self.voucher.issuer.to_bytes()).map_err(|_| invalid())?;
+
+            key.verify(
+                &signing_message(&self.payload),
+                &Signature::from_bytes(&bytes),
+            )
+            .map_err(|_| invalid())
+        }
+
+        #[cfg(not(feature = "pubky"))]
+        {
+            Err(PaykitError::Unimplemented(
+                "voucher signature verification requires the pubky feature",
+            ))
+        }
+    }
+}
+
+/// Where a voucher is in its lifecycle, as recorded by the issuer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum VoucherStatus {
+    /// Handed out and not yet redeemed.
+    Issued,
+    /// Claimed by `redeemer`; the payout is in progress.
+    Redeeming {
+        /// Pubky identity (z-base32) of the holder.
+        redeemer: String,
+        /// When the claim was made (Unix seconds).
+        claimed_at: i64,
+    },
+    /// Paid out to `redeemer`.
+    Redeemed {
+        /// Pubky identity (z-base32) of the holder.
+        redeemer: String,
+        /// When the payout succeeded (Unix seconds).
+        redeemed_at: i64,
+        /// Method the payout used.
+        method_id: MethodId,
+        /// Method-specific execution data (e.g., txid, preimage).
+        execution_data: serde_json::Value,
+    },
+    /// Withdrawn by the issuer.
+    Revoked {
+        /// When the voucher was revoked (Unix seconds).
+        revoked_at: i64,
+    },
+}
+
+/// Summary of a voucher's status at a point in time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VoucherState {
+    /// Can be redeemed.
+    Active,
+    /// A payout is in progress.
+    Redeeming,
+    /// Already paid out.
+    Redeemed,
+    /// Withdrawn by the issuer.
+    Revoked,
+    /// Past its expiry without being redeemed.
+    Expired,
+}
+
+impl VoucherState {
+    /// Lowercase name, for display.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Redeeming => "redeeming",
+            Self::Redeemed => "redeemed",
+            Self::Revoked => "revoked",
+            Self::Expired => "expired",
+        }
+    }
+}
+
+/// The issuer's record of a voucher it handed out.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoucherRecord {
+    /// Voucher ID.
+    pub voucher_id: String,
+    /// Value of the voucher in satoshis.
+    pub amount_sats: u64,
+    /// Method the voucher must be paid out with, if restricted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<MethodId>,
+    /// Message for the holder.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+    /// When the voucher was issued (Unix seconds).
+    pub created_at: i64,
+    /// When the voucher stops being redeemable (Unix seconds).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+    /// The code exactly as handed out.
+    pub code: String,
+    /// Current status.
+    pub status: VoucherStatus,
+}
+
+impl VoucherRecord {
+    fn new(signed: &SignedVoucher) -> Self {
+        let voucher = signed.voucher();
+        Self {
+            voucher_id: voucher.voucher_id.clone(),
+            amount_sats: voucher.amount_sats,
+            method: voucher.method.clone(),
+            memo: voucher.memo.clone(),
+            created_at: voucher.created_at,
+            expires_at: voucher.expires_at,
+            code: signed.to_code(),
+            status: VoucherStatus::Issued,
+        }
+    }
+
+    /// The voucher's state at `now`.
+    pub fn state(&self, now: i64) -> VoucherState {
+        match self.status {
+            VoucherStatus::Redeeming { .. } => VoucherState::Redeeming,
+            VoucherStatus::Redeemed { .. } => VoucherState::Redeemed,
+            VoucherStatus::Revoked { .. } => VoucherState::Revoked,
+            VoucherStatus::Issued if self.expires_at.is_some_and(|exp| now >= exp) => {
+                VoucherState::Expired
+            }
+            VoucherStatus::Issued => VoucherState::Active,
+        }
+    }
+}
+
+/// The issuer's record of every voucher it handed out.
+///
+/// All state changes happen under one lock, so [`claim`](Self::claim) lets
+/// exactly one caller through per voucher.
+#[derive(Debug, Default)]
+pub struct VoucherLedger {
+    records: RwLock<HashMap<String, VoucherRecord>>,
+}
+
+impl VoucherLedger {
+    /// Create an empty ledger.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuild a ledger from previously saved [`records`](Self::records).
+    pub fn from_records(records: Vec<VoucherRecord>) -> Self {
+        let records = records
+            .into_iter()
+            .map(|record| (record.voucher_id.clone(), record))
+            .collect();
+        Self {
+            records: RwLock::new(records),
+        }
+    }
+
+    /// All records, oldest first, for persistence or display.
+    pub fn records(&self) -> Vec<VoucherRecord> {
+        let records = self.records.read().unwrap_or_else(|e| e.into_inner());
+        let mut records: Vec<VoucherRecord> = records.values().cloned().collect();
+        records.sort_by(|a, b| (a.created_at, &a.voucher_id).cmp(&(b.created_at, &b.voucher_id)));
+        records
+    }
+
+    /// Get the record of a voucher.
+    pub fn get(&self, voucher_id: &str) -> Option<VoucherRecord> {
+        let records = self.records.read().unwrap_or_else(|e| e.into_inner());
+        records.get(voucher_id).cloned()
+    }
+
+    /// The state of a voucher at `now`.
+    pub fn status_at(&self, voucher_id: &str, now: i64) -> Result<VoucherState> {
+        self.get(voucher_id)
+            .map(|record| record.state(now))
+            .ok_or_else(|| PaykitError::not_found("voucher", voucher_id))
+    }
+
+    /// Record a newly signed voucher.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a voucher with the same ID was already issued.
+    pub fn issue(&self, signed: &SignedVoucher) -> Result<()> {
+        signed.voucher().validate()?;
+        let mut records = self.records.write().unwrap_or_else(|e| e.into_inner());
+        let voucher_id = &signed.voucher().voucher_id;
+        if records.contains_key(voucher_id) {
+            return Err(PaykitError::ValidationFailed(format!(
+                "Voucher {} was already issued",
+                voucher_id
+            )));
+        }
+        records.insert(voucher_id.clone(), VoucherRecord::new(signed));
+        Ok(())
+    }
+
+    /// Revoke an unredeemed voucher.
+    pub fn revoke(&self, voucher_id: &str, now: i64) -> Result<()> {
+        self.transition(voucher_id, |record| match record.status {
+            VoucherStatus::Issued => {
+                record.status = VoucherStatus::Revoked { revoked_at: now };
+                Ok(())
+            }
+            VoucherStatus::Revoked { .. } => Ok(()),
+            _ => Err(PaykitError::ValidationFailed(format!(
+                "Voucher {} is {} and cannot be revoked",
+                voucher_id,
+                record.state(now).as_str()
+            ))),
+        })
+    }
+
+    /// Reserve the voucher behind `code` for `redeemer`.
+    ///
+    /// Checks the signature, that the code is the one this ledger issued,
+    /// and that the voucher is active at `now`. On success the voucher is
+    /// [`Redeeming`](VoucherStatus::Redeeming) until [`complete`](Self::complete)
+    /// or [`release`](Self::release) is called, and further claims fail.
+    pub fn claim(&self, code: &str, redeemer: &str, now: i64) -> Result<Voucher> {
+        let signed = SignedVoucher::decode(code)?;
+        signed.verify_signature()?;
+        let voucher = signed.voucher().clone();
+
+        self.transition(&voucher.voucher_id, |record| {
+            if record.code != signed.to_code() {
+                return Err(PaykitError::ValidationFailed(format!(
+                    "Voucher {} does not match the issued code",
+                    voucher.voucher_id
+                )));
+            }
+            match record.state(now) {
+                VoucherState::Active => {
+                    record.status = VoucherStatus::Redeeming {
+                        redeemer: redeemer.to_string(),
+                        claimed_at: now,
+                    };
+                    Ok(())
+                }
+                VoucherState::Redeemed => Err(PaykitError::PaymentAlreadyCompleted {
+                    payment_id: voucher.voucher_id.clone(),
+                }),
+                VoucherState::Expired => Err(PaykitError::InvoiceExpired {
+                    invoice_id: voucher.voucher_id.clone(),
+                    expired_at: record.expires_at.unwrap_or(now),
+                }),
+                state => Err(PaykitError::ValidationFailed(format!(
+                    "Voucher {} is {}",
+                    voucher.voucher_id,
+                    state.as_str()
+                ))),
+            }
+        })?;
+        Ok(voucher)
+    }
+
+    /// Mark a claimed voucher as paid out by `execution`.
+    pub fn complete(&self, voucher_id: &str, execution: &PaymentExecution) -> Result<()> {
+        self.transition(voucher_id, |record| match &record.status {
+            VoucherStatus::Redeeming { redeemer, .. } => {
+                record.status = VoucherStatus::Redeemed {
+                    redeemer: redeemer.clone(),
+                    redeemed_at: execution.executed_at,
+                    method_id: execution.method_id.clone(),
+                    execution_data: execution.execution_data.clone(),
+                };
+                Ok(())
+            }
+            _ => Err(PaykitError::ValidationFailed(format!(
+                "Voucher {} is not being redeemed",
+                voucher_id
+            ))),
+        })
+    }
+
+    /// Release a claim whose payout failed, making the voucher redeemable again.
+    pub fn release(&self, voucher_id: &str) -> Result<()> {
+        self.transition(voucher_id, |record| {
+            if matches!(record.status, VoucherStatus::Redeeming { .. }) {
+                record.status = VoucherStatus::Issued;
+            }
+            Ok(())
+        })
+    }
+
+    fn transition(
+        &self,
+        voucher_id: &str,
+        f: impl FnOnce(&mut VoucherRecord) -> Result<()>,
+    ) -> Result<()> {
+        let mut records = self.records.write().unwrap_or_else(|e| e.into_inner());
+        let record = records
+            .get_mut(voucher_id)
+            .ok_or_else(|| PaykitError::not_found("voucher", voucher_id))?;
+        f(record)
+    }
+}
+
+/// Outcome of paying out a claimed voucher.
+#[derive(Clone, Debug)]
+pub enum VoucherPayout {
+    /// The payout succeeded; [`complete`](VoucherLedger::complete) the claim.
+    Paid(PaymentExecution),
+    /// Every method failed without sending anything;
+    /// [`release`](VoucherLedger::release) the claim. Holds the last attempt.
+    Failed(PaymentExecution),
+    /// A method failed in a way that does not rule out the payout having
+    /// been sent, and no other method was tried. Keep the claim until the
+    /// wallet shows whether it went out. Holds that attempt.
+    Unresolved(PaymentExecution),
+}
+
+/// Pays claimed vouchers out of the issuer's wallet.
+pub struct VoucherRedeemer {
+    registry: Arc<PaymentMethodRegistry>,
+}
+
+impl VoucherRedeemer {
+    /// Create a redeemer paying through `registry`.
+    pub fn new(registry: impl Into<Arc<PaymentMethodRegistry>>) -> Self {
+        Self {
+            registry: registry.into(),
+        }
+    }
+
+    /// Redeem `code` for `redeemer`, paying to one of their `supported` endpoints.
+    ///
+    /// The claim and its outcome are recorded in `ledger`.
+    ///
+    /// Methods are tried in selection order (only the voucher's method, if
+    /// it has one) until one succeeds. If none does, the claim is released
+    /// and the last failed execution is returned.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the voucher cannot be claimed (see [`VoucherLedger::claim`])
+    /// or the redeemer has no endpoint the voucher can be paid to. Also fails,
+    /// leaving the voucher claimed, when the payout is
+    /// [`Unresolved`](VoucherPayout::Unresolved).
+    pub async fn redeem_at(
+        &self,
+        ledger: &VoucherLedger,
+        code: &str,
+        redeemer: &str,
+        supported: &SupportedPayments,
+        now: i64,
+    ) -> Result<PaymentExecution> {
+        let voucher = ledger.claim(code, redeemer, now)?;
+        match self.pay_claimed(&voucher, supported).await {
+            Ok(VoucherPayout::Paid(execution)) => {
+                ledger.complete(&voucher.voucher_id, &execution)?;
+                Ok(execution)
+            }
+            Ok(VoucherPayout::Failed(execution)) => {
+                ledger.release(&voucher.voucher_id)?;
+                Ok(execution)
+            }
+            Ok(VoucherPayout::Unresolved(execution)) => Err(PaykitError::Payment {
+                payment_id: Some(voucher.voucher_id),
+                reason: format!(
+                    "the {} payout may have been sent ({}); the voucher stays claimed",
+                    execution.method_id.0,
+                    execution.error.unwrap_or_default()
+                ),
+            }),
+            Err(e) => {
+                ledger.release(&voucher.voucher_id)?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Pay out a voucher already claimed in the ledger, without recording the result.
+    ///
+    /// For issuers that persist the claim themselves before paying; call
+    /// [`VoucherLedger::complete`] or [`VoucherLedger::release`] afterwards as
+    /// the [`VoucherPayout`] says. An error means nothing was sent.
+    pub async fn pay_claimed(
+        &self,
+        voucher: &Voucher,
+        supported: &SupportedPayments,
+    ) -> Result<VoucherPayout> {
+        let amount = Amount::sats(voucher.amount_sats);
+        let mut preferences = SelectionPreferences::default();
+        if let Some(method) = &voucher.method {
+            preferences = SelectionPreferences::with_priority_list(vec![method.clone()]);
+            for method_id in supported.entries.keys() {
+                if method_id != method {
+                    preferences = preferences.exclude_method(method_id.clone());
+                }
+            }
+        }
+        let metadata = serde_json::json!({
+            "voucher": voucher.voucher_id,
+            "memo": voucher.memo,
+        });
+        let fallback = pay_with_fallback(
+            &self.registry,
+            supported,
+            &amount,
+            &preferences,
+            &metadata,
+            || {},
+        )
+        .await?;
+
+        match fallback {
+            Fallback::Paid(execution) => Ok(VoucherPayout::Paid(execution)),
+            Fallback::Failed(execution) => Ok(VoucherPayout::Failed(execution)),
+            Fallback::Unresolved(execution) => Ok(VoucherPayout::Unresolved(execution)),
+            Fallback::Unavailable => Err(PaykitError::Transport(
+                "No payment methods available for voucher".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(feature = "pubky")]
+fn signing_message(payload: &str) -> Vec<u8> {
+    let mut message = VOUCHER_DOMAIN.to_vec();
+    message.extend_from_slice(payload.as_bytes());
+    message
+}
+
+fn parse_issuer(key: &str) -> Result<PublicKey> {
+    #[cfg(feature = "pubky")]
+    {
+        key.parse()
+            .map_err(|_| PaykitError::invalid_data("voucher", "issuer is not a valid public key"))
+    }
+
+    #[cfg(not(feature = "pubky"))]
+    {
+        Ok(PublicKey(key.to_string()))
+    }
+}
+
+#[cfg(all(test, feature = "pubky"))]
+mod tests {
+    use super::*;
+    use crate::methods::{PaymentMethodPlugin, PaymentProof, ValidationResult};
+    use crate::EndpointData;
+    use async_trait::async_trait;
+
+    /// Plugin whose payments fail without saying whether anything was sent
+    struct TimingOutPlugin;
+
+    #[async_trait]
+    impl PaymentMethodPlugin for TimingOutPlugin {
+        fn method_id(&self) -> MethodId {
+            MethodId("timing-out".into())
+        }
+
+        fn display_name(&self) -> &str {
+            "Timing Out"
+        }
+
+        fn description(&self) -> &str {
+            "Loses the connection to the wallet mid-payment"
+        }
+
+        fn validate_endpoint(&self, _data: &EndpointData) -> ValidationResult {
+            ValidationResult::valid()
+        }
+
+        async fn execute_payment(
+            &self,
+            _endpoint: &EndpointData,
+            _amount: &Amount,
+            _metadata: &serde_json::Value,
+        ) -> Result<PaymentExecution> {
+            Err(PaykitError::Transport("wallet connection reset".into()))
+        }
+
+        fn generate_proof(&self, _execution: &PaymentExecution) -> Result<PaymentProof> {
+            Err(PaykitError::Unimplemented("proof"))
+        }
+
+        fn format_receipt_metadata(&self, _execution: &PaymentExecution) -> serde_json::Value {
+            serde_json::Value::Null
+        }
+    }
+
+    fn issued(ledger: &VoucherLedger, voucher: Voucher, keypair: &pubky::Keypair) -> String {
+        let signed = voucher.sign(keypair).unwrap();
+        ledger.issue(&signed).unwrap();
+        signed.to_code()
+    }
+
+    #[test]
+    fn test_code_roundtrip_and_tampering() {
+        let keypair = pubky::Keypair::random();
+        let signed = Voucher::new("gift-1", keypair.public_key(), 21_000, 1_000)
+            .with_method(MethodId::lightning())
+            .with_memo("Happy birthday")
+            .with_expires_at(2_000)
+            .sign(&keypair)
+            .unwrap();
+
+        let decoded = SignedVoucher::decode(&signed.to_code()).unwrap();
+        decoded.verify_signature().unwrap();
+        assert_eq!(decoded.voucher(), signed.voucher());
+
+        // Only the issuer can sign, and a raised amount breaks the signature
+        let raised = Voucher::new("gift-1", keypair.public_key(), 1_000_000, 1_000);
+        assert!(raised.clone().sign(&pubky::Keypair::random()).is_err());
+        let raised = raised.sign(&keypair).unwrap();
+        let tampered = SignedVoucher {
+            voucher: raised.voucher().clone(),
+            payload: raised.payload.clone(),
+            signature: signed.signature.clone(),
+        };
+        let tampered = SignedVoucher::decode(&tampered.to_code()).unwrap();
+        assert!(tampered.verify_signature().is_err());
+
+        assert!(Voucher::new("bad id", keypair.public_key(), 1, 0)
+            .sign(&keypair)
+            .is_err());
+    }
+
+    #[test]
+    fn test_claim_prevents_double_redeem() {
+        let keypair = pubky::Keypair::random();
+        let ledger = VoucherLedger::new();
+        let code = issued(
+            &ledger,
+            Voucher::new("gift-2", keypair.public_key(), 5_000, 1_000).with_expires_at(2_000),
+            &keypair,
+        );
+
+        assert_eq!(
+            ledger.status_at("gift-2", 1_500).unwrap(),
+            VoucherState::Active
+        );
+        assert_eq!(
+            ledger.status_at("gift-2", 2_000).unwrap(),
+            VoucherState::Expired
+        );
+        assert!(matches!(
+            ledger.claim(&code, "alice", 2_000),
+            Err(PaykitError::InvoiceExpired { .. })
+        ));
+
+        ledger.claim(&code, "alice", 1_500).unwrap();
+        assert!(ledger.claim(&code, "bob", 1_501).is_err());
+        ledger.release("gift-2").unwrap();
+        ledger.claim(&code, "bob", 1_502).unwrap();
+
+        let execution = PaymentExecution::success(
+            MethodId::lightning(),
+            EndpointData("lnbc1bob".into()),
+            Amount::sats(5_000),
+            serde_json::json!({ "preimage": "00" }),
+        );
+        ledger.complete("gift-2", &execution).unwrap();
+        assert!(matches!(
+            ledger.claim(&code, "bob", 1_503),
+            Err(PaykitError::PaymentAlreadyCompleted { .. })
+        ));
+        assert!(ledger.revoke("gift-2", 1_503).is_err());
+        assert!(matches!(
+            ledger.get("gift-2").unwrap().status,
+            VoucherStatus::Redeemed { ref redeemer, .. } if redeemer == "bob"
+        ));
+
+        // Codes from another issuer, or never issued, are refused
+        let other = pubky::Keypair::random();
+        let foreign = Voucher::new("gift-3", other.public_key(), 5_000, 1_000)
+            .sign(&other)
+            .unwrap();
+        assert!(ledger.claim(&foreign.to_code(), "bob", 1_500).is_err());
+
+        let restored = VoucherLedger::from_records(ledger.records());
+        assert_eq!(
+            restored.status_at("gift-2", 1_600).unwrap(),
+            VoucherState::Redeemed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_redeem_pays_voucher_method() {
+        let keypair = pubky::Keypair::random();
+        let ledger = VoucherLedger::new();
+        let code = issued(
+            &ledger,
+            Voucher::new("gift-4", keypair.public_key(), 10_000, 1_000)
+                .with_method(MethodId::onchain()),
+            &keypair,
+        );
+
+        let mut supported = SupportedPayments::default();
+        supported
+            .entries
+            .insert(MethodId::lightning(), EndpointData("lnbc1redeemer".into()));
+        let redeemer = VoucherRedeemer::new(crate::methods::default_registry());
+        assert!(redeemer
+            .redeem_at(&ledger, &code, "redeemer", &supported, 1_100)
+            .await
+            .is_err());
+        assert_eq!(
+            ledger.status_at("gift-4", 1_100).unwrap(),
+            VoucherState::Active
+        );
+
+        supported.entries.insert(
+            MethodId::onchain(),
+            EndpointData("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".into()),
+        );
+        let execution = redeemer
+            .redeem_at(&ledger, &code, "redeemer", &supported, 1_200)
+            .await
+            .unwrap();
+        assert!(execution.success);
+        assert_eq!(execution.method_id, MethodId::onchain());
+        assert_eq!(
+            ledger.status_at("gift-4", 1_200).unwrap(),
+            VoucherState::Redeemed
+        );
+        assert!(redeemer
+            .redeem_at(&ledger, &code, "redeemer", &supported, 1_300)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unresolved_payout_keeps_voucher_claimed() {
+        let keypair = pubky::Keypair::random();
+        let ledger = VoucherLedger::new();
+        let timing_out = MethodId("timing-out".into());
+        let code = issued(
+            &ledger,
+            Voucher::new("gift-5", keypair.public_key(), 10_000, 1_000)
+                .with_method(timing_out.clone()),
+            &keypair,
+        );
+
+        let registry = crate::methods::default_registry();
+        registry.register(Box::new(TimingOutPlugin));
+        let mut supported = SupportedPayments::default();
+        supported
+            .entries
+            .insert(timing_out, EndpointData("anywhere".into()));
+        let redeemer = VoucherRedeemer::new(registry);
+
+        // The payout may have left the wallet, so the claim is kept and the
+        // code cannot be redeemed again
+        let err = redeemer
+            .redeem_at(&ledger, &code, "redeemer", &supported, 1_100)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, PaykitError::Payment { .. }));
+        assert_eq!(
+            ledger.status_at("gift-5", 1_100).unwrap(),
+            VoucherState::Redeeming
+        );
+        assert!(redeemer
+            .redeem_at(&ledger, &code, "redeemer", &supported, 1_200)
+            .await
+            .is_err());
+
+        // Once the issuer sees nothing was sent, it can be redeemed again
+        ledger.release("gift-5").unwrap();
+        assert_eq!(
+            ledger.status_at("gift-5", 1_300).unwrap(),
+            VoucherState::Active
+        );
+    }
+}
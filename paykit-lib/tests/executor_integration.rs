@@ -11,6 +11,8 @@
 //! cargo test -p paykit-lib --features http-executor --test executor_integration
 //! ```
 //!
+//! The Fedimint tests also need the `fedimint` feature.
+//!
 //! ## Real testnet tests (requires running nodes)
 //!
 //! These tests are marked `#[ignore]` and require manual setup:
@@ -37,11 +39,12 @@
 
 use paykit_lib::executors::{
     testnet::{get_lnd_config_from_env, TestnetConfig},
-    EsploraConfig, EsploraExecutor, LndConfig, LndExecutor,
+    BtcPayConfig, BtcPayInvoiceRequest, BtcPayInvoiceStatus, BtcPayReceiver, ClnConfig,
+    ClnExecutor, EsploraConfig, EsploraExecutor, LndConfig, LndExecutor,
 };
 use paykit_lib::methods::LightningExecutor;
 use wiremock::{
-    matchers::{header, method, path, path_regex},
+    matchers::{header, method, path, path_regex, query_param},
     Mock, MockServer, ResponseTemplate,
 };
 
@@ -217,6 +220,246 @@ async fn test_lnd_auth_failure_mock() {
     );
 }
 
+#[tokio::test]
+async fn test_lnd_wait_for_payment_mock() {
+    let mock_server = MockServer::start().await;
+
+    // In-flight payments are only listed with include_incomplete
+    Mock::given(method("GET"))
+        .and(path("/v1/payments"))
+        .and(query_param("include_incomplete", "true"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "payments": [
+                {
+                    "payment_hash": "settled_hash",
+                    "payment_preimage": "preimage123",
+                    "value_msat": "50000",
+                    "fee_msat": "10",
+                    "status": "SUCCEEDED"
+                },
+                {
+                    "payment_hash": "inflight_hash",
+                    "value_msat": "100000",
+                    "status": "IN_FLIGHT"
+                }
+            ]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let config = LndConfig::new(mock_server.uri(), "test_macaroon").with_poll_interval(10);
+    let executor = LndExecutor::new(config).unwrap();
+    let timeout = std::time::Duration::from_millis(50);
+
+    let settled = executor
+        .wait_for_payment("settled_hash", timeout)
+        .await
+        .unwrap();
+    assert_eq!(
+        settled.status,
+        paykit_lib::methods::LightningPaymentStatus::Succeeded
+    );
+
+    let pending = executor
+        .wait_for_payment("inflight_hash", timeout)
+        .await
+        .unwrap();
+    assert_eq!(
+        pending.status,
+        paykit_lib::methods::LightningPaymentStatus::Pending
+    );
+
+    let missing = executor.wait_for_payment("unknown_hash", timeout).await;
+    assert!(missing.is_err());
+}
+
+// ============================================================================
+// CLN Executor Mock Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_cln_pay_and_estimate_fee_mock() {
+    let mock_server = MockServer::start().await;
+    let created_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    Mock::given(method("POST"))
+        .and(path("/v1/decode"))
+        .and(header("Rune", "test_rune"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "type": "bolt11 invoice",
+            "valid": true,
+            "payee": "02abc",
+            "payment_hash": "hash123",
+            "amount_msat": 100000,
+            "description": "coffee",
+            "created_at": created_at,
+            "expiry": 3600
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/getroute"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "route": [
+                {"id": "03def", "amount_msat": 100015},
+                {"id": "02abc", "amount_msat": 100000}
+            ]
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/pay"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "payment_preimage": "preimage123",
+            "payment_hash": "hash123",
+            "amount_msat": 100000,
+            "amount_sent_msat": 100015,
+            "parts": 1,
+            "status": "complete"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let executor = ClnExecutor::new(ClnConfig::new(mock_server.uri(), "test_rune")).unwrap();
+
+    let decoded = executor.decode_invoice("lnbc1u1ptest").await.unwrap();
+    assert_eq!(decoded.payee, "02abc");
+    assert_eq!(decoded.amount_msat, Some(100000));
+    assert!(!decoded.expired);
+
+    assert_eq!(executor.estimate_fee("lnbc1u1ptest").await.unwrap(), 15);
+
+    let result = executor
+        .pay_invoice("lnbc1u1ptest", None, None)
+        .await
+        .unwrap();
+    assert_eq!(result.preimage, "preimage123");
+    assert_eq!(result.fee_msat, 15);
+    assert_eq!(
+        result.status,
+        paykit_lib::methods::LightningPaymentStatus::Succeeded
+    );
+}
+
+#[tokio::test]
+async fn test_cln_no_route_mock() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/getroute"))
+        .respond_with(ResponseTemplate::new(500).set_body_json(serde_json::json!({
+            "code": 205,
+            "message": "Could not find a route"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let executor = ClnExecutor::new(ClnConfig::new(mock_server.uri(), "test_rune")).unwrap();
+    let probe = executor.probe_route("02abc", 100000).await.unwrap();
+    assert!(!probe.route_found);
+}
+
+// ============================================================================
+// BTCPay Receiver Mock Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_btcpay_create_invoice_mock() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v1/stores/store1/invoices"))
+        .and(header("Authorization", "token test_key"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "inv1",
+            "checkoutLink": "https://btcpay.example.com/i/inv1",
+            "status": "New",
+            "amount": "21000",
+            "currency": "SATS",
+            "createdTime": 1700000000,
+            "expirationTime": 1700000900,
+            "metadata": { "orderId": "receipt_1" }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v1/stores/store1/invoices/inv1/payment-methods"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            { "paymentMethodId": "BTC-CHAIN", "destination": "tb1qpayee" },
+            { "paymentMethodId": "BTC-LN", "destination": "lntb210u1..." }
+        ])))
+        .mount(&mock_server)
+        .await;
+
+    let receiver =
+        BtcPayReceiver::new(BtcPayConfig::new(mock_server.uri(), "test_key", "store1")).unwrap();
+    let invoice = receiver
+        .create_invoice(&BtcPayInvoiceRequest::new(21_000).with_order_id("receipt_1"))
+        .await
+        .unwrap();
+    assert_eq!(invoice.status, BtcPayInvoiceStatus::New);
+    assert_eq!(invoice.order_id(), Some("receipt_1"));
+
+    let endpoints = receiver.invoice_endpoints(&invoice.id).await.unwrap();
+    assert_eq!(endpoints.len(), 2);
+    assert_eq!(endpoints[0].0.as_str(), "onchain");
+    assert_eq!(endpoints[1].1.as_str(), "lntb210u1...");
+}
+
+// ============================================================================
+// Fedimint Executor Mock Tests
+// ============================================================================
+
+#[cfg(feature = "fedimint")]
+#[tokio::test]
+async fn test_fedimint_spend_notes_mock() {
+    use paykit_lib::executors::{FedimintConfig, FedimintExecutor};
+    use paykit_lib::methods::{verify_ecash_proof, Amount, EcashPlugin, PaymentMethodPlugin};
+    use paykit_lib::EndpointData;
+    use std::sync::Arc;
+
+    let federation = "15db8cb4f1ec8e484d73b889372bec94812580f929e8148b7437d359af422cd3";
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v2/admin/federation-ids"))
+        .and(header("Authorization", "Bearer pw"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "federationIds": [federation]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/v2/mint/spend"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "operation": "op1",
+            "notes": "AwEEsZ2GH4"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let executor = FedimintExecutor::new(FedimintConfig::new(mock_server.uri(), "pw")).unwrap();
+    let plugin = EcashPlugin::with_executor(Arc::new(executor));
+    let endpoint = EndpointData::new(format!(r#"{{"federation_id":"{}"}}"#, federation));
+    let execution = plugin
+        .execute_payment(&endpoint, &Amount::sats(1000), &serde_json::Value::Null)
+        .await
+        .unwrap();
+    assert!(execution.success);
+    assert_eq!(
+        execution.execution_data["transcript"]["operation_id"],
+        "op1"
+    );
+
+    let proof = plugin.generate_proof(&execution).unwrap();
+    assert!(verify_ecash_proof(&proof, "AwEEsZ2GH4").unwrap());
+}
+
 // ============================================================================
 // Esplora Executor Mock Tests
 // ============================================================================
@@ -0,0 +1,129 @@
+//! Mobile FFI round-trip benchmarks
+//!
+//! A call from Swift or Kotlin serializes its arguments into a `RustBuffer`,
+//! the scaffolding lifts them into Rust values, the method runs, and the
+//! result is lowered back into a buffer for the app to read. These
+//! benchmarks time that whole round trip for the calls made on every
+//! checkout, next to the bare Rust call, so the cost of crossing the
+//! boundary stays visible.
+//!
+//! Run with: `cargo bench --bench ffi_benchmarks`
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use paykit_mobile::{
+    PaykitClient, PaymentMethod, SelectionPreferences, SelectionStrategy, UniFfiTag,
+};
+use std::sync::Arc;
+use uniffi::{Lift, Lower};
+
+const QR: &str = "lightning:lnbc10u1pj9x3qapp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypq";
+
+/// Send `value` across the boundary: lower it into a `RustBuffer` as the
+/// generated bindings do, then lift it back out as the receiving side does.
+fn cross<T>(value: T) -> T
+where
+    T: Lower<UniFfiTag> + Lift<UniFfiTag>,
+{
+    let buffer = <T as Lower<UniFfiTag>>::lower_into_rust_buffer(value);
+    <T as Lift<UniFfiTag>>::try_lift_from_rust_buffer(buffer).expect("value lifts")
+}
+
+fn client() -> Arc<PaykitClient> {
+    PaykitClient::new().expect("client starts")
+}
+
+fn supported_methods() -> Vec<PaymentMethod> {
+    vec![
+        PaymentMethod {
+            method_id: "lightning".to_string(),
+            endpoint: "lnbc10u1pj9x3qapp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypq"
+                .to_string(),
+        },
+        PaymentMethod {
+            method_id: "onchain".to_string(),
+            endpoint: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+        },
+    ]
+}
+
+/// Benchmark parsing a scanned QR code from the app
+fn bench_scan(c: &mut Criterion) {
+    let client = client();
+
+    let mut group = c.benchmark_group("ffi_parse_scanned_qr");
+    group.bench_function("rust", |b| {
+        b.iter(|| client.parse_scanned_qr(black_box(QR.to_string())).unwrap())
+    });
+    group.bench_function("round_trip", |b| {
+        b.iter(|| {
+            let scanned = cross(black_box(QR.to_string()));
+            cross(client.parse_scanned_qr(scanned).unwrap())
+        })
+    });
+    group.finish();
+}
+
+/// Benchmark method selection called from the app
+fn bench_select(c: &mut Criterion) {
+    let client = client();
+    let preferences = SelectionPreferences {
+        strategy: SelectionStrategy::Balanced,
+        excluded_methods: Vec::new(),
+        max_fee_sats: Some(500),
+        max_confirmation_time_secs: None,
+    };
+
+    let mut group = c.benchmark_group("ffi_select_method");
+    group.bench_function("rust", |b| {
+        b.iter(|| {
+            client
+                .select_method(
+                    black_box(supported_methods()),
+                    10_000,
+                    Some(preferences.clone()),
+                )
+                .unwrap()
+        })
+    });
+    group.bench_function("round_trip", |b| {
+        b.iter(|| {
+            let methods = cross(black_box(supported_methods()));
+            let preferences = cross(Some(preferences.clone()));
+            cross(client.select_method(methods, 10_000, preferences).unwrap())
+        })
+    });
+    group.finish();
+}
+
+/// Benchmark creating a receipt from the app
+fn bench_receipt(c: &mut Criterion) {
+    let client = client();
+    let payer = pubky_key(1);
+    let payee = pubky_key(2);
+
+    c.bench_function("ffi_create_receipt_round_trip", |b| {
+        b.iter(|| {
+            let receipt = client
+                .create_receipt(
+                    cross(payer.clone()),
+                    cross(payee.clone()),
+                    cross("lightning".to_string()),
+                    cross(Some("25000".to_string())),
+                    cross(Some("SAT".to_string())),
+                    cross(Some("Coffee".to_string())),
+                    cross(false),
+                )
+                .unwrap();
+            cross(receipt)
+        })
+    });
+}
+
+fn pubky_key(seed: u8) -> String {
+    pkarr::Keypair::from_secret_key(&[seed; 32])
+        .public_key()
+        .to_z32()
+}
+
+criterion_group!(benches, bench_scan, bench_select, bench_receipt);
+criterion_main!(benches);
@@ -1,6 +1,6 @@
 #!/bin/bash
 #
-# Generate Swift and Kotlin bindings for Paykit Mobile
+# Generate Swift, Kotlin and Python bindings for Paykit Mobile
 #
 # This script uses uniffi-bindgen to generate platform-specific bindings
 # from the Rust FFI definitions.
@@ -179,6 +179,25 @@ generate_bindings() {
         uniffi-bindgen generate --library "$LIB_PATH" -l kotlin -o paykit-mobile/kotlin/generated
     fi
     
+    echo ""
+    if cargo run --bin generate-bindings --features bindgen-cli -- \
+        --library "$LIB_PATH" -l python -o paykit-mobile/python/generated; then
+        cp "$LIB_PATH" paykit-mobile/python/generated/
+        echo "✅ Python bindings generated via built-in generator"
+    else
+        if ! command -v uniffi-bindgen &> /dev/null; then
+            echo ""
+            echo "⚠️  uniffi-bindgen not found and built-in generator failed."
+            echo "Install uniffi-bindgen or fix the built-in generator build."
+            echo ""
+            return 1
+        fi
+        echo "Built-in generator failed; falling back to system uniffi-bindgen for Python..."
+        mkdir -p paykit-mobile/python/generated
+        uniffi-bindgen generate --library "$LIB_PATH" -l python -o paykit-mobile/python/generated
+        cp "$LIB_PATH" paykit-mobile/python/generated/
+    fi
+
     echo ""
     echo "Bindings generated successfully!"
     echo ""
@@ -188,6 +207,9 @@ generate_bindings() {
     echo ""
     echo "  Kotlin:"
     ls -la paykit-mobile/kotlin/generated/ 2>/dev/null || echo "    (none)"
+    echo ""
+    echo "  Python:"
+    ls -la paykit-mobile/python/generated/ 2>/dev/null || echo "    (none)"
 }
 
 # Generate bindings if host library exists
@@ -229,9 +251,15 @@ echo "  - fetch_supported_payments: Discover payment methods"
 echo "  - fetch_payment_endpoint: Get specific endpoint"
 echo "  - add_contact, remove_contact, list_contacts: Contact management"
 echo ""
+echo "Receipt Reporting (also the Python scripting surface):"
+echo "  - parse_receipts_json, export_receipts_csv: Read and flatten receipt exports"
+echo "  - verify_payment_proof: Check Lightning preimages and txids"
+echo "  - summarize_receipts: Totals by currency, method, payee and payer"
+echo ""
 echo "For usage examples, see:"
 echo "  - paykit-mobile/README.md"
 echo "  - paykit-mobile/BITKIT_INTEGRATION_GUIDE.md"
 echo "  - paykit-mobile/FFI_API_REFERENCE.md"
+echo "  - paykit-mobile/python/README.md"
 echo ""
 echo "Done!"
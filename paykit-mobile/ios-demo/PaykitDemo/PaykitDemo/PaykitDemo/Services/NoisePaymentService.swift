@@ -934,29 +934,15 @@ public class ServerReceiptGenerator: ReceiptGeneratorCallback {
         // Generate invoice if generator is set, otherwise use mock
         let invoice = invoiceGenerator?(request) ?? "mock_invoice_\(request.receiptId)"
         
-        // Create confirmed receipt with invoice in metadata
-        var metadataDict = [String: String]()
-        if let metadataData = request.metadataJson.data(using: .utf8),
-           let parsed = try? JSONSerialization.jsonObject(with: metadataData) as? [String: String] {
-            metadataDict = parsed
-        }
-        metadataDict["invoice"] = invoice
-        metadataDict["confirmed_at"] = ISO8601DateFormatter().string(from: Date())
-        
-        let updatedMetadataJson = (try? JSONSerialization.data(withJSONObject: metadataDict))
+        // Add the invoice to the request's metadata; Rust merges and validates it
+        let additions = [
+            "invoice": invoice,
+            "confirmed_at": ISO8601DateFormatter().string(from: Date())
+        ]
+        let metadataJson = (try? JSONSerialization.data(withJSONObject: additions))
             .flatMap { String(data: $0, encoding: .utf8) } ?? "{}"
         
-        let confirmedReceipt = ReceiptRequest(
-            receiptId: request.receiptId,
-            payer: request.payer,
-            payee: request.payee,
-            methodId: request.methodId,
-            amount: request.amount,
-            currency: request.currency,
-            metadataJson: updatedMetadataJson
-        )
-        
-        return ReceiptGenerationResult(success: true, receipt: confirmedReceipt, error: nil)
+        return .metadata(metadataJson: metadataJson)
     }
 }
 
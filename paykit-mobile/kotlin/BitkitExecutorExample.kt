@@ -168,12 +168,15 @@ class BitkitBitcoinExecutor(
      * @param address Destination Bitcoin address
      * @param amountSats Amount to send in satoshis
      * @param feeRate Optional fee rate in sat/vB (uses wallet default if null)
+     * @param coinSelection Payer's coin-selection hints; honor them with the
+     *        wallet's coin control where possible
      * @return Transaction result with txid and fee details
      */
     override fun sendToAddress(
         address: String,
         amountSats: ULong,
-        feeRate: Double?
+        feeRate: Double?,
+        coinSelection: CoinSelectionFfi?
     ): BitcoinTxResultFfi {
         return try {
             val tx = wallet.sendToAddress(address, amountSats, feeRate)
@@ -292,6 +292,47 @@ class KeyManager(context: Context) {
         )
     }
     
+    /**
+     * Export identity to an armored backup
+     *
+     * Uses the shared Paykit backup format, which the CLI and web demos can
+     * restore as well.
+     *
+     * @param password Password to encrypt the backup
+     * @return Armored backup text
+     */
+    fun exportPortableBackup(password: String): String {
+        val secretHex = prefs.getString(KEY_SECRET, null)
+            ?: throw KeyManagerException.NoIdentity()
+        
+        return exportIdentityBackup(secretHex, password)
+    }
+    
+    /**
+     * Import identity from an armored backup (or legacy CLI/web JSON)
+     *
+     * @param backup Backup text
+     * @param password Password to decrypt
+     * @return The restored keypair
+     */
+    fun importPortableBackup(backup: String, password: String): Ed25519Keypair {
+        val keypair = importIdentityBackup(backup, password)
+        
+        // Store in encrypted preferences
+        prefs.edit()
+            .putString(KEY_SECRET, keypair.secretKeyHex)
+            .putString(KEY_PUBLIC, keypair.publicKeyHex)
+            .putString(KEY_PUBLIC_Z32, keypair.publicKeyZ32)
+            .apply()
+        
+        // Update state
+        _hasIdentity.value = true
+        _publicKeyZ32.value = keypair.publicKeyZ32
+        _publicKeyHex.value = keypair.publicKeyHex
+        
+        return keypair
+    }
+    
     // ============================================================================
     // Private Helpers
     // ============================================================================
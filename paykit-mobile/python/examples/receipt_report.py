@@ -0,0 +1,64 @@
+#!/usr/bin/env python3
+"""Summarize a Paykit receipt export.
+
+Reads the JSON written by ReceiptStore.export_receipts_json(), prints totals
+per currency and method, checks any payment proofs stored in the receipts'
+metadata under "proof", and optionally writes the receipts as CSV.
+
+Requires the generated bindings on PYTHONPATH (see ../README.md).
+"""
+
+import argparse
+import json
+import sys
+
+import paykit_mobile as pk
+
+
+def print_totals(title, rows):
+    print(f"\n{title}")
+    for row in rows:
+        print(f"  {row.key:20.20} {row.receipt_count:6} {row.total:>14} {row.currency}")
+
+
+def check_proofs(receipts):
+    failures = 0
+    for receipt in receipts:
+        proof = json.loads(receipt.metadata_json or "{}").get("proof")
+        if proof is None:
+            continue
+        result = pk.verify_payment_proof(json.dumps(proof))
+        if not result.valid:
+            failures += 1
+            print(f"  {receipt.receipt_id}: {'; '.join(result.errors)}")
+    return failures
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__.splitlines()[0])
+    parser.add_argument("export", help="receipt export (JSON)")
+    parser.add_argument("--csv", help="also write the receipts to this CSV file")
+    args = parser.parse_args()
+
+    with open(args.export) as f:
+        receipts = pk.parse_receipts_json(f.read())
+
+    summary = pk.summarize_receipts(receipts)
+    print(f"{summary.receipt_count} receipts ({summary.unpriced_count} without an amount)")
+    print_totals("By currency", summary.by_currency)
+    print_totals("By method", summary.by_method)
+
+    print("\nProof checks")
+    failures = check_proofs(receipts)
+    print(f"  {failures} failed")
+
+    if args.csv:
+        with open(args.csv, "w") as f:
+            f.write(pk.export_receipts_csv(receipts))
+        print(f"\nWrote {args.csv}")
+
+    return 1 if failures else 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,439 @@
+//! Multi-Signature Approval FFI Bindings
+//!
+//! This module exposes the K-of-N approval queue to mobile applications.
+//! Once a queue is attached with `PaykitClient::set_approval_queue`,
+//! `execute_payment()` refuses payments above the threshold with
+//! `PaykitMobileError::ApprovalRequired` and queues them. Co-signers approve
+//! either on this device (`sign_approval`) or remotely, sending back a signed
+//! approval that is applied with `apply_approval`. Once approved, retrying
+//! the same payment proceeds to the executor.
+//!
+//! # Example Flow
+//!
+//! ```ignore
+//! let queue = ApprovalQueueFFI::new(ApprovalPolicyFFI {
+//!     threshold_sats: 1_000_000,
+//!     required: 2,
+//!     cosigners: vec![alice, bob, carol],
+//!     ttl_secs: 86_400,
+//! })?;
+//! client.set_approval_queue(queue.clone());
+//!
+//! if let Err(PaykitMobileError::ApprovalRequired { .. }) = client.execute_payment(..) {
+//!     for pending in queue.list_pending()? {
+//!         // Local co-signer
+//!         queue.apply_approval(queue.sign_approval(alice_secret_hex, pending.approval_id, true)?)?;
+//!         // Remote co-signer: send pending.pending_json over Noise, apply the reply
+//!     }
+//! }
+//! ```
+
+use crate::{PaykitMobileError, Result};
+use paykit_lib::approvals::{
+    ApprovalDecision, ApprovalPolicy, ApprovalQueue, ApprovalStatus, PaymentProposal,
+    PendingApproval, SignedApproval,
+};
+use std::sync::{Arc, RwLock};
+
+// ============================================================================
+// FFI Types
+// ============================================================================
+
+/// FFI-safe approval policy.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct ApprovalPolicyFFI {
+    /// Payments strictly above this amount need approval
+    pub threshold_sats: u64,
+    /// Number of approvals required (K)
+    pub required: u32,
+    /// Co-signer public keys (N), z-base-32
+    pub cosigners: Vec<String>,
+    /// How long a payment may wait for approvals, in seconds
+    pub ttl_secs: i64,
+}
+
+impl From<&ApprovalPolicy> for ApprovalPolicyFFI {
+    fn from(policy: &ApprovalPolicy) -> Self {
+        Self {
+            threshold_sats: policy.threshold_sats,
+            required: policy.required,
+            cosigners: policy.cosigners.clone(),
+            ttl_secs: policy.ttl_secs,
+        }
+    }
+}
+
+impl TryFrom<ApprovalPolicyFFI> for ApprovalPolicy {
+    type Error = PaykitMobileError;
+
+    fn try_from(policy: ApprovalPolicyFFI) -> Result<Self> {
+        Ok(
+            ApprovalPolicy::new(policy.threshold_sats, policy.required, policy.cosigners)?
+                .with_ttl_secs(policy.ttl_secs),
+        )
+    }
+}
+
+/// FFI-safe approval status.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, uniffi::Enum)]
+pub enum ApprovalStatusFFI {
+    /// Waiting for approvals
+    Pending,
+    /// Enough approvals collected
+    Approved,
+    /// Approval is no longer possible
+    Rejected,
+    /// Not approved before the deadline
+    Expired,
+    /// Approved and executed
+    Executed,
+}
+
+impl From<ApprovalStatus> for ApprovalStatusFFI {
+    fn from(status: ApprovalStatus) -> Self {
+        match status {
+            ApprovalStatus::Pending => ApprovalStatusFFI::Pending,
+            ApprovalStatus::Approved => ApprovalStatusFFI::Approved,
+            ApprovalStatus::Rejected => ApprovalStatusFFI::Rejected,
+            ApprovalStatus::Expired => ApprovalStatusFFI::Expired,
+            ApprovalStatus::Executed => ApprovalStatusFFI::Executed,
+        }
+    }
+}
+
+/// FFI-safe queued payment.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct PendingApprovalFFI {
+    /// Queue identifier
+    pub approval_id: String,
+    /// Recipient (public key or payment endpoint)
+    pub payee: String,
+    /// Payment method identifier
+    pub method_id: String,
+    /// Amount in satoshis
+    pub amount_sats: u64,
+    /// What the payment is for
+    pub description: Option<String>,
+    /// Approvals required (K)
+    pub required: u32,
+    /// Public keys that approved
+    pub approved_by: Vec<String>,
+    /// Public keys that rejected
+    pub rejected_by: Vec<String>,
+    /// Co-signers who have not decided yet
+    pub outstanding: Vec<String>,
+    /// Current status
+    pub status: ApprovalStatusFFI,
+    /// Unix timestamp when the payment was queued
+    pub created_at: i64,
+    /// Unix timestamp after which it can no longer be approved
+    pub expires_at: i64,
+    /// Full entry as JSON, for sending to remote co-signers
+    pub pending_json: String,
+}
+
+impl From<&PendingApproval> for PendingApprovalFFI {
+    fn from(pending: &PendingApproval) -> Self {
+        Self {
+            approval_id: pending.approval_id.clone(),
+            payee: pending.proposal.payee.clone(),
+            method_id: pending.proposal.method_id.clone(),
+            amount_sats: pending.proposal.amount_sats,
+            description: pending.proposal.description.clone(),
+            required: pending.required,
+            approved_by: pending.approvals.iter().map(|a| a.signer.clone()).collect(),
+            rejected_by: pending
+                .rejections
+                .iter()
+                .map(|a| a.signer.clone())
+                .collect(),
+            outstanding: pending
+                .outstanding()
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            status: pending.status.into(),
+            created_at: pending.created_at,
+            expires_at: pending.expires_at,
+            pending_json: serde_json::to_string(pending).unwrap_or_default(),
+        }
+    }
+}
+
+// ============================================================================
+// Approval Queue
+// ============================================================================
+
+/// Thread-safe wrapper around the approval queue.
+#[derive(uniffi::Object)]
+pub struct ApprovalQueueFFI {
+    queue: RwLock<ApprovalQueue>,
+}
+
+impl ApprovalQueueFFI {
+    fn read(&self) -> Result<std::sync::RwLockReadGuard<'_, ApprovalQueue>> {
+        self.queue.read().map_err(|_| PaykitMobileError::Internal {
+            msg: "Failed to acquire approval queue lock".to_string(),
+        })
+    }
+
+    fn write(&self) -> Result<std::sync::RwLockWriteGuard<'_, ApprovalQueue>> {
+        self.queue.write().map_err(|_| PaykitMobileError::Internal {
+            msg: "Failed to acquire approval queue lock".to_string(),
+        })
+    }
+
+    /// Gate a payment: `Ok(Some(id))` if it was approved and may proceed,
+    /// `Ok(None)` if no approval is needed, or `ApprovalRequired` after
+    /// queueing it.
+    pub(crate) fn gate_payment(
+        &self,
+        payee: &str,
+        method_id: &str,
+        amount_sats: u64,
+    ) -> Result<Option<String>> {
+        let mut queue = self.write()?;
+        if !queue.requires_approval(amount_sats) {
+            return Ok(None);
+        }
+        if let Some(approved) = queue.find_approved(payee, method_id, amount_sats) {
+            return Ok(Some(approved.approval_id.clone()));
+        }
+
+        let pending = queue.submit(
+            PaymentProposal::new(payee, method_id, amount_sats),
+            current_timestamp(),
+        );
+        Err(PaykitMobileError::ApprovalRequired {
+            msg: format!(
+                "{} ({} of {} approvals)",
+                pending.approval_id,
+                pending.approvals.len(),
+                pending.required
+            ),
+        })
+    }
+
+    /// Mark an approved payment as executed.
+    pub(crate) fn complete_payment(&self, approval_id: &str) -> Result<()> {
+        Ok(self.write()?.mark_executed(approval_id)?)
+    }
+}
+
+#[uniffi::export]
+impl ApprovalQueueFFI {
+    /// Create an empty queue enforcing `policy`.
+    #[uniffi::constructor]
+    pub fn new(policy: ApprovalPolicyFFI) -> Result<Arc<Self>> {
+        Ok(Arc::new(Self {
+            queue: RwLock::new(ApprovalQueue::new(policy.try_into()?)?),
+        }))
+    }
+
+    /// Restore a queue from state produced by `export_state()`.
+    #[uniffi::constructor]
+    pub fn from_state(state_json: String) -> Result<Arc<Self>> {
+        let queue: ApprovalQueue =
+            serde_json::from_str(&state_json).map_err(|e| PaykitMobileError::Serialization {
+                msg: format!("Invalid approval queue state: {}", e),
+            })?;
+        Ok(Arc::new(Self {
+            queue: RwLock::new(queue),
+        }))
+    }
+
+    /// Export the queue as JSON for persistence.
+    pub fn export_state(&self) -> Result<String> {
+        let queue = self.read()?;
+        serde_json::to_string(&*queue)
+            .map_err(|e| PaykitMobileError::Serialization { msg: e.to_string() })
+    }
+
+    /// Get the current policy.
+    pub fn get_policy(&self) -> Result<ApprovalPolicyFFI> {
+        Ok(self.read()?.policy().into())
+    }
+
+    /// Replace the policy. Payments already queued keep their K-of-N.
+    pub fn set_policy(&self, policy: ApprovalPolicyFFI) -> Result<()> {
+        Ok(self.write()?.set_policy(policy.try_into()?)?)
+    }
+
+    /// Whether a payment of `amount_sats` needs approval.
+    pub fn requires_approval(&self, amount_sats: u64) -> Result<bool> {
+        Ok(self.read()?.requires_approval(amount_sats))
+    }
+
+    /// Queue a payment for approval.
+    pub fn submit(
+        &self,
+        payee: String,
+        method_id: String,
+        amount_sats: u64,
+        description: Option<String>,
+    ) -> Result<PendingApprovalFFI> {
+        let mut proposal = PaymentProposal::new(payee, method_id, amount_sats);
+        proposal.description = description;
+        Ok((&self.write()?.submit(proposal, current_timestamp())).into())
+    }
+
+    /// Get a queued payment.
+    pub fn get(&self, approval_id: String) -> Result<Option<PendingApprovalFFI>> {
+        Ok(self.read()?.get(&approval_id).map(Into::into))
+    }
+
+    /// List payments still waiting for approvals, oldest first.
+    pub fn list_pending(&self) -> Result<Vec<PendingApprovalFFI>> {
+        let mut queue = self.write()?;
+        queue.expire(current_timestamp());
+        Ok(queue.pending().into_iter().map(Into::into).collect())
+    }
+
+    /// List all queued payments, oldest first.
+    pub fn list_all(&self) -> Result<Vec<PendingApprovalFFI>> {
+        Ok(self.read()?.entries().into_iter().map(Into::into).collect())
+    }
+
+    /// Sign a decision as a co-signer whose key is on this device.
+    ///
+    /// Returns the signed approval as JSON, to apply locally with
+    /// `apply_approval` or send back to the initiator.
+    pub fn sign_approval(
+        &self,
+        secret_key_hex: String,
+        approval_id: String,
+        approve: bool,
+    ) -> Result<String> {
+        let queue = self.read()?;
+        let pending = queue
+            .get(&approval_id)
+            .ok_or_else(|| PaykitMobileError::NotFound {
+                msg: format!("Approval not found: {}", approval_id),
+            })?;
+        sign_pending(&secret_key_hex, pending, approve)
+    }
+
+    /// Apply a signed approval JSON from a local or remote co-signer.
+    pub fn apply_approval(&self, approval_json: String) -> Result<ApprovalStatusFFI> {
+        let approval = SignedApproval::from_json(&approval_json)?;
+        Ok(self.write()?.apply(&approval, current_timestamp())?.into())
+    }
+
+    /// Remove a payment from the queue. Returns whether it was present.
+    pub fn cancel(&self, approval_id: String) -> Result<bool> {
+        Ok(self.write()?.cancel(&approval_id).is_some())
+    }
+}
+
+/// Sign a decision on a pending payment received from a remote initiator.
+///
+/// `pending_json` is the `pending_json` field of the initiator's
+/// `PendingApprovalFFI`. Returns the signed approval JSON to send back.
+#[uniffi::export]
+pub fn sign_remote_approval(
+    secret_key_hex: String,
+    pending_json: String,
+    approve: bool,
+) -> Result<String> {
+    let pending: PendingApproval =
+        serde_json::from_str(&pending_json).map_err(|e| PaykitMobileError::Serialization {
+            msg: format!("Invalid pending approval: {}", e),
+        })?;
+    sign_pending(&secret_key_hex, &pending, approve)
+}
+
+fn sign_pending(secret_key_hex: &str, pending: &PendingApproval, approve: bool) -> Result<String> {
+    let bytes = hex::decode(secret_key_hex).map_err(|e| PaykitMobileError::Validation {
+        msg: format!("Invalid hex: {}", e),
+    })?;
+    let secret: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| PaykitMobileError::Validation {
+            msg: "Secret key must be 32 bytes".to_string(),
+        })?;
+    let decision = if approve {
+        ApprovalDecision::Approve
+    } else {
+        ApprovalDecision::Reject
+    };
+    Ok(
+        SignedApproval::sign_with_secret_key(&secret, pending, decision, current_timestamp())
+            .to_json()?,
+    )
+}
+
+fn current_timestamp() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cosigner() -> crate::keys::Ed25519Keypair {
+        crate::keys::generate_ed25519_keypair().unwrap()
+    }
+
+    #[test]
+    fn test_gate_payment_requires_approval() {
+        let (alice, bob) = (cosigner(), cosigner());
+        let queue = ApprovalQueueFFI::new(ApprovalPolicyFFI {
+            threshold_sats: 10_000,
+            required: 2,
+            cosigners: vec![alice.public_key_z32.clone(), bob.public_key_z32.clone()],
+            ttl_secs: 3600,
+        })
+        .unwrap();
+
+        assert_eq!(
+            queue.gate_payment("lnbc1", "lightning", 5_000).unwrap(),
+            None
+        );
+        assert!(matches!(
+            queue.gate_payment("lnbc1", "lightning", 50_000),
+            Err(PaykitMobileError::ApprovalRequired { .. })
+        ));
+
+        let pending = queue.list_pending().unwrap().remove(0);
+        assert_eq!(pending.outstanding.len(), 2);
+
+        let local = queue
+            .sign_approval(alice.secret_key_hex, pending.approval_id.clone(), true)
+            .unwrap();
+        assert_eq!(
+            queue.apply_approval(local).unwrap(),
+            ApprovalStatusFFI::Pending
+        );
+
+        let remote =
+            sign_remote_approval(bob.secret_key_hex, pending.pending_json.clone(), true).unwrap();
+        assert_eq!(
+            queue.apply_approval(remote).unwrap(),
+            ApprovalStatusFFI::Approved
+        );
+
+        let approved = queue.gate_payment("lnbc1", "lightning", 50_000).unwrap();
+        assert_eq!(approved, Some(pending.approval_id.clone()));
+        queue.complete_payment(&pending.approval_id).unwrap();
+
+        let restored = ApprovalQueueFFI::from_state(queue.export_state().unwrap()).unwrap();
+        assert_eq!(
+            restored.get(pending.approval_id).unwrap().unwrap().status,
+            ApprovalStatusFFI::Executed
+        );
+    }
+
+    #[test]
+    fn test_invalid_policy_rejected() {
+        let result = ApprovalQueueFFI::new(ApprovalPolicyFFI {
+            threshold_sats: 0,
+            required: 3,
+            cosigners: vec![cosigner().public_key_z32],
+            ttl_secs: 3600,
+        });
+        assert!(result.is_err());
+    }
+}
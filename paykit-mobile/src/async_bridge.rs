@@ -176,12 +176,6 @@ impl AsyncRuntime {
     }
 }
 
-impl Default for AsyncRuntime {
-    fn default() -> Self {
-        Self::new().expect("Failed to create default runtime")
-    }
-}
-
 /// Callback wrapper for FFI.
 ///
 /// This provides a simple way to bridge callbacks across FFI boundaries.
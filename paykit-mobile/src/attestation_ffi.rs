@@ -0,0 +1,246 @@
+//! Attestation and Key Pinning FFI Bindings
+//!
+//! This module exposes attestation verification and trust-on-first-use key
+//! pinning to mobile applications. After an NN handshake the app verifies the
+//! peer's attestation against the handshake hash, then checks the attested
+//! key against the pin store and prompts the user if it changed.
+//!
+//! # Example Flow
+//!
+//! ```ignore
+//! let pins = PinStoreFFI::new();
+//! let key = verify_attestation(peer_pk_hex, signature_hex, handshake_hash)?;
+//!
+//! match pins.check(peer_id, key)? {
+//!     PinCheckFFI::FirstUse | PinCheckFFI::Match => proceed(),
+//!     PinCheckFFI::Changed { pinned } => show_key_changed_warning(pinned),
+//! }
+//!
+//! // Persist between launches
+//! save(pins.export_state()?);
+//! ```
+
+use crate::{PaykitMobileError, Result};
+use paykit_interactive::attestation::{self, PinCheck, PinStore, PinnedKey};
+use std::sync::{Arc, RwLock};
+
+// ============================================================================
+// FFI Types
+// ============================================================================
+
+/// FFI-safe pinned key.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct PinnedKeyFFI {
+    /// Peer identifier chosen by the app
+    pub peer: String,
+    /// Pinned Ed25519 key (hex)
+    pub ed25519_pk_hex: String,
+    /// Unix timestamp when the key was first pinned
+    pub first_seen: i64,
+    /// Unix timestamp when the key was last presented
+    pub last_seen: i64,
+}
+
+impl From<&PinnedKey> for PinnedKeyFFI {
+    fn from(pin: &PinnedKey) -> Self {
+        Self {
+            peer: pin.peer.clone(),
+            ed25519_pk_hex: pin.ed25519_pk_hex.clone(),
+            first_seen: pin.first_seen,
+            last_seen: pin.last_seen,
+        }
+    }
+}
+
+/// FFI-safe outcome of checking a key against its pin.
+#[derive(Clone, Debug, uniffi::Enum)]
+pub enum PinCheckFFI {
+    /// Peer was unknown; the key is now pinned
+    FirstUse,
+    /// Key matches the pin
+    Match,
+    /// Key differs from the pin; ask the user before continuing
+    Changed { pinned: PinnedKeyFFI },
+}
+
+impl From<PinCheck> for PinCheckFFI {
+    fn from(check: PinCheck) -> Self {
+        match check {
+            PinCheck::FirstUse => PinCheckFFI::FirstUse,
+            PinCheck::Match => PinCheckFFI::Match,
+            PinCheck::Changed { pinned } => PinCheckFFI::Changed {
+                pinned: (&pinned).into(),
+            },
+        }
+    }
+}
+
+// ============================================================================
+// Attestation
+// ============================================================================
+
+/// Verify a peer's attestation over the handshake hash.
+///
+/// Returns the attested Ed25519 key (hex) on success.
+#[uniffi::export]
+pub fn verify_attestation(
+    ed25519_pk_hex: String,
+    signature_hex: String,
+    handshake_hash: Vec<u8>,
+) -> Result<String> {
+    let key = attestation::verify_attestation_hex(&ed25519_pk_hex, &signature_hex, &handshake_hash)
+        .map_err(|e| PaykitMobileError::AuthenticationError { msg: e.to_string() })?;
+    Ok(hex::encode(key))
+}
+
+/// Sign the handshake hash with the identity secret key.
+///
+/// Returns the signature (hex) to send in an attestation message.
+#[uniffi::export]
+pub fn sign_attestation(secret_key_hex: String, handshake_hash: Vec<u8>) -> Result<String> {
+    let secret_key = parse_key(&secret_key_hex, "secret key")?;
+    Ok(hex::encode(attestation::sign_attestation(
+        &secret_key,
+        &handshake_hash,
+    )))
+}
+
+fn parse_key(hex_str: &str, what: &str) -> Result<[u8; 32]> {
+    hex::decode(hex_str)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| PaykitMobileError::Validation {
+            msg: format!("Invalid {}: expected 32 hex-encoded bytes", what),
+        })
+}
+
+// ============================================================================
+// Pin Store
+// ============================================================================
+
+/// Thread-safe wrapper around the trust-on-first-use pin store.
+#[derive(uniffi::Object)]
+pub struct PinStoreFFI {
+    pins: RwLock<PinStore>,
+}
+
+impl PinStoreFFI {
+    fn read(&self) -> Result<std::sync::RwLockReadGuard<'_, PinStore>> {
+        self.pins.read().map_err(|_| PaykitMobileError::Internal {
+            msg: "Failed to acquire pin store lock".to_string(),
+        })
+    }
+
+    fn write(&self) -> Result<std::sync::RwLockWriteGuard<'_, PinStore>> {
+        self.pins.write().map_err(|_| PaykitMobileError::Internal {
+            msg: "Failed to acquire pin store lock".to_string(),
+        })
+    }
+}
+
+#[uniffi::export]
+impl PinStoreFFI {
+    /// Create an empty pin store.
+    #[uniffi::constructor]
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            pins: RwLock::new(PinStore::new()),
+        })
+    }
+
+    /// Restore a pin store from state produced by `export_state()`.
+    #[uniffi::constructor]
+    pub fn from_state(state_json: String) -> Result<Arc<Self>> {
+        let pins: PinStore =
+            serde_json::from_str(&state_json).map_err(|e| PaykitMobileError::Serialization {
+                msg: format!("Invalid pin store state: {}", e),
+            })?;
+        Ok(Arc::new(Self {
+            pins: RwLock::new(pins),
+        }))
+    }
+
+    /// Export the pins as JSON for persistence.
+    pub fn export_state(&self) -> Result<String> {
+        let pins = self.read()?;
+        serde_json::to_string(&*pins)
+            .map_err(|e| PaykitMobileError::Serialization { msg: e.to_string() })
+    }
+
+    /// Check an attested key, pinning it if the peer is new.
+    pub fn check(&self, peer: String, ed25519_pk_hex: String) -> Result<PinCheckFFI> {
+        let key = parse_key(&ed25519_pk_hex, "public key")?;
+        Ok(self.write()?.check(&peer, &key, current_timestamp()).into())
+    }
+
+    /// Check an attested key, failing if it differs from the pin.
+    pub fn enforce(&self, peer: String, ed25519_pk_hex: String) -> Result<()> {
+        let key = parse_key(&ed25519_pk_hex, "public key")?;
+        self.write()?
+            .enforce(&peer, &key, current_timestamp())
+            .map_err(|e| PaykitMobileError::AuthenticationError { msg: e.to_string() })
+    }
+
+    /// Pin a key, replacing any existing pin (after the user accepts a change).
+    pub fn pin(&self, peer: String, ed25519_pk_hex: String) -> Result<PinnedKeyFFI> {
+        let key = parse_key(&ed25519_pk_hex, "public key")?;
+        Ok(self.write()?.pin(&peer, &key, current_timestamp()).into())
+    }
+
+    /// Forget a peer's pin. Returns whether it was present.
+    pub fn unpin(&self, peer: String) -> Result<bool> {
+        Ok(self.write()?.unpin(&peer).is_some())
+    }
+
+    /// Get the pin for a peer.
+    pub fn get_pin(&self, peer: String) -> Result<Option<PinnedKeyFFI>> {
+        Ok(self.read()?.get(&peer).map(Into::into))
+    }
+
+    /// List all pins.
+    pub fn pins(&self) -> Result<Vec<PinnedKeyFFI>> {
+        Ok(self.read()?.pins().into_iter().map(Into::into).collect())
+    }
+}
+
+fn current_timestamp() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attest_and_pin() {
+        let keypair = crate::keys::generate_ed25519_keypair().unwrap();
+        let signature = sign_attestation(keypair.secret_key_hex.clone(), b"hash".to_vec()).unwrap();
+        let key = verify_attestation(
+            keypair.public_key_hex.clone(),
+            signature.clone(),
+            b"hash".to_vec(),
+        )
+        .unwrap();
+        assert_eq!(key, keypair.public_key_hex);
+        assert!(verify_attestation(key.clone(), signature, b"other".to_vec()).is_err());
+
+        let pins = PinStoreFFI::new();
+        assert!(matches!(
+            pins.check("bob".into(), key.clone()).unwrap(),
+            PinCheckFFI::FirstUse
+        ));
+        let other = crate::keys::generate_ed25519_keypair().unwrap();
+        assert!(matches!(
+            pins.check("bob".into(), other.public_key_hex.clone())
+                .unwrap(),
+            PinCheckFFI::Changed { .. }
+        ));
+        assert!(pins.enforce("bob".into(), other.public_key_hex).is_err());
+
+        let restored = PinStoreFFI::from_state(pins.export_state().unwrap()).unwrap();
+        assert_eq!(restored.pins().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,256 @@
+//! Upcoming Charges FFI Bindings
+//!
+//! This module exposes the subscription charge calendar to mobile
+//! applications for budgeting screens. It reads agreements, auto-pay rules
+//! and scheduled modifications from the app's subscription storage directory
+//! and projects the charges due over the next N days.
+//!
+//! # Example Flow
+//!
+//! ```ignore
+//! let calendar = create_subscription_calendar(subscriptions_dir)?;
+//!
+//! for charge in calendar.get_upcoming_charges(30)? {
+//!     render_row(charge.due_at, charge.amount, charge.currency, charge.autopay);
+//! }
+//!
+//! let totals = calendar.get_upcoming_totals(30)?;
+//! ```
+
+use crate::{PaykitMobileError, Result};
+use paykit_subscriptions::storage::{FileSubscriptionStorage, SubscriptionStorage};
+use paykit_subscriptions::{ChargeCalendar, UpcomingCharge};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+// ============================================================================
+// FFI Types
+// ============================================================================
+
+/// FFI-safe projected charge.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct UpcomingChargeFFI {
+    /// Subscription the charge belongs to
+    pub subscription_id: String,
+    /// Provider public key (z-base32 encoded)
+    pub provider: String,
+    /// Subscriber public key (z-base32 encoded)
+    pub subscriber: String,
+    /// Unix timestamp when the charge falls due
+    pub due_at: i64,
+    /// Amount due after scheduled modifications
+    pub amount: String,
+    /// Currency of the amount
+    pub currency: String,
+    /// Payment method that will be used
+    pub method_id: String,
+    /// Subscription description
+    pub description: String,
+    /// Whether auto-pay will pay this without confirmation
+    pub autopay: bool,
+    /// Whether a scheduled modification changed the amount or method
+    pub modified: bool,
+}
+
+impl From<&UpcomingCharge> for UpcomingChargeFFI {
+    fn from(charge: &UpcomingCharge) -> Self {
+        Self {
+            subscription_id: charge.subscription_id.clone(),
+            provider: charge.provider.to_z32(),
+            subscriber: charge.subscriber.to_z32(),
+            due_at: charge.due_at,
+            amount: charge.amount.to_string(),
+            currency: charge.currency.clone(),
+            method_id: charge.method.0.clone(),
+            description: charge.description.clone(),
+            autopay: charge.autopay,
+            modified: charge.modified,
+        }
+    }
+}
+
+/// FFI-safe total due in one currency.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct CurrencyTotalFFI {
+    /// Currency code
+    pub currency: String,
+    /// Total due over the window
+    pub total: String,
+    /// Number of charges in this currency
+    pub charge_count: u32,
+}
+
+// ============================================================================
+// Subscription Calendar
+// ============================================================================
+
+/// Read-only view of upcoming subscription charges.
+#[derive(uniffi::Object)]
+pub struct SubscriptionCalendarFFI {
+    storage: FileSubscriptionStorage,
+    runtime: tokio::runtime::Runtime,
+}
+
+#[uniffi::export]
+impl SubscriptionCalendarFFI {
+    /// Open the calendar over a subscription storage directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `storage_path` - Directory holding the app's subscription storage
+    #[uniffi::constructor]
+    pub fn new(storage_path: String) -> Result<Arc<Self>> {
+        let storage = FileSubscriptionStorage::new(PathBuf::from(storage_path)).map_err(|e| {
+            PaykitMobileError::Internal {
+                msg: format!("Failed to open subscription storage: {}", e),
+            }
+        })?;
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .map_err(|e| PaykitMobileError::Internal {
+                msg: format!("Failed to create runtime: {}", e),
+            })?;
+
+        Ok(Arc::new(Self { storage, runtime }))
+    }
+
+    /// Charges due on active subscriptions over the next `days` days.
+    ///
+    /// Ordered by due date. `days` must be between 1 and 366.
+    pub fn get_upcoming_charges(&self, days: u32) -> Result<Vec<UpcomingChargeFFI>> {
+        let calendar = self.calendar(days)?;
+        Ok(calendar
+            .charges
+            .iter()
+            .map(UpcomingChargeFFI::from)
+            .collect())
+    }
+
+    /// Totals per currency over the next `days` days.
+    pub fn get_upcoming_totals(&self, days: u32) -> Result<Vec<CurrencyTotalFFI>> {
+        let calendar = self.calendar(days)?;
+        Ok(calendar
+            .totals_by_currency()
+            .into_iter()
+            .map(|(currency, total)| CurrencyTotalFFI {
+                charge_count: calendar
+                    .charges
+                    .iter()
+                    .filter(|c| c.currency == currency)
+                    .count() as u32,
+                currency,
+                total: total.to_string(),
+            })
+            .collect())
+    }
+}
+
+// ============================================================================
+// Private Helpers (not exposed via FFI)
+// ============================================================================
+
+impl SubscriptionCalendarFFI {
+    fn calendar(&self, days: u32) -> Result<ChargeCalendar> {
+        let mut calendar = ChargeCalendar::new(current_timestamp(), days)
+            .map_err(|e| PaykitMobileError::Validation { msg: e.to_string() })?;
+
+        let loaded: paykit_subscriptions::Result<()> = self.runtime.block_on(async {
+            for signed in self.storage.list_active_subscriptions().await? {
+                let id = &signed.subscription.subscription_id;
+                let rule = self.storage.get_autopay_rule(id).await?;
+                let modifications = self.storage.list_modifications(id).await?;
+                calendar.add_subscription(&signed.subscription, rule.as_ref(), &modifications);
+            }
+            Ok(())
+        });
+        loaded.map_err(|e| PaykitMobileError::Internal {
+            msg: format!("Failed to read subscriptions: {}", e),
+        })?;
+
+        Ok(calendar)
+    }
+}
+
+fn current_timestamp() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+// ============================================================================
+// Standalone Functions
+// ============================================================================
+
+/// Open the upcoming charges calendar over a subscription storage directory.
+#[uniffi::export]
+pub fn create_subscription_calendar(storage_path: String) -> Result<Arc<SubscriptionCalendarFFI>> {
+    SubscriptionCalendarFFI::new(storage_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paykit_lib::MethodId;
+    use paykit_subscriptions::{
+        signing, Amount, ModificationRequest, PaymentFrequency, SignedSubscription, Subscription,
+        SubscriptionTerms,
+    };
+
+    #[test]
+    fn test_get_upcoming_charges() {
+        let dir = std::env::temp_dir().join(format!("paykit_test_{}", rand::random::<u32>()));
+        let calendar = SubscriptionCalendarFFI::new(dir.to_string_lossy().to_string()).unwrap();
+
+        let subscriber = pkarr::Keypair::random();
+        let provider = pkarr::Keypair::random();
+        let subscription = Subscription::new(
+            subscriber.public_key(),
+            provider.public_key(),
+            SubscriptionTerms::new(
+                Amount::from_sats(500),
+                "SAT".to_string(),
+                PaymentFrequency::Weekly,
+                MethodId("lightning".to_string()),
+                "Newsletter".to_string(),
+            ),
+        );
+        let sig1 =
+            signing::sign_subscription_ed25519(&subscription, &subscriber, &rand::random(), 3600)
+                .unwrap();
+        let sig2 =
+            signing::sign_subscription_ed25519(&subscription, &provider, &rand::random(), 3600)
+                .unwrap();
+        let downgrade = ModificationRequest::downgrade(
+            &subscription,
+            Amount::from_sats(300),
+            subscription.starts_at + 86400,
+        );
+        calendar.runtime.block_on(async {
+            calendar
+                .storage
+                .save_signed_subscription(&SignedSubscription::new(subscription, sig1, sig2))
+                .await
+                .unwrap();
+            calendar
+                .storage
+                .save_modification(&downgrade)
+                .await
+                .unwrap();
+        });
+
+        let charges = calendar.get_upcoming_charges(14).unwrap();
+        let amounts: Vec<&str> = charges.iter().map(|c| c.amount.as_str()).collect();
+        assert_eq!(amounts, vec!["500", "300"]);
+        assert!(charges[1].modified);
+        assert!(!charges[0].autopay);
+
+        let totals = calendar.get_upcoming_totals(14).unwrap();
+        assert_eq!(totals.len(), 1);
+        assert_eq!(totals[0].total, "800");
+        assert_eq!(totals[0].charge_count, 2);
+
+        assert!(calendar.get_upcoming_charges(0).is_err());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,210 @@
+//! Compliance Screening FFI Bindings
+//!
+//! Lets mobile apps plug a sanctions/compliance check into payment flows.
+//! The app implements `ComplianceScreenerCallback` in Swift/Kotlin, usually
+//! calling out to its compliance provider, and registers it with
+//! `PaykitInteractiveManagerFFI::set_compliance_screener`.
+//!
+//! Blocked payments are refused: outgoing requests fail with a validation
+//! error and incoming requests are answered with a `COMPLIANCE_BLOCKED`
+//! error message. Every result is recorded in the receipt metadata under
+//! `"compliance"`.
+//!
+//! # Example (Swift)
+//!
+//! ```swift
+//! class ProviderScreener: ComplianceScreenerCallback {
+//!     func screen(request: ScreeningRequestFfi) throws -> ScreeningResultFfi {
+//!         let hit = try provider.check(request.payer, request.payee)
+//!         return hit ? .block(reason: hit.listName) : .pass
+//!     }
+//! }
+//!
+//! try manager.setComplianceScreener(screener: ProviderScreener())
+//! ```
+
+use crate::{PaykitMobileError, Result};
+use paykit_lib::policy::{ScreeningDirection, ScreeningRequest, ScreeningResult};
+use std::sync::Arc;
+
+// ============================================================================
+// FFI Types
+// ============================================================================
+
+/// FFI-safe screening direction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, uniffi::Enum)]
+pub enum ScreeningDirectionFFI {
+    /// We are paying the payee
+    Outgoing,
+    /// The payer is paying us
+    Incoming,
+}
+
+impl From<ScreeningDirection> for ScreeningDirectionFFI {
+    fn from(direction: ScreeningDirection) -> Self {
+        match direction {
+            ScreeningDirection::Outgoing => ScreeningDirectionFFI::Outgoing,
+            ScreeningDirection::Incoming => ScreeningDirectionFFI::Incoming,
+        }
+    }
+}
+
+/// FFI-safe screening request.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct ScreeningRequestFFI {
+    /// Which side of the payment we are on
+    pub direction: ScreeningDirectionFFI,
+    /// Payer public key
+    pub payer: String,
+    /// Payee public key
+    pub payee: String,
+    /// Payment method identifier
+    pub method_id: String,
+    /// Amount, if known
+    pub amount: Option<String>,
+    /// Currency code, if known
+    pub currency: Option<String>,
+    /// Payment metadata as JSON
+    pub metadata_json: String,
+}
+
+impl From<&ScreeningRequest> for ScreeningRequestFFI {
+    fn from(request: &ScreeningRequest) -> Self {
+        Self {
+            direction: request.direction.into(),
+            payer: request.payer.clone(),
+            payee: request.payee.clone(),
+            method_id: request.method_id.clone(),
+            amount: request.amount.clone(),
+            currency: request.currency.clone(),
+            metadata_json: request.metadata.to_string(),
+        }
+    }
+}
+
+/// FFI-safe screening result.
+#[derive(Clone, Debug, PartialEq, Eq, uniffi::Enum)]
+pub enum ScreeningResultFFI {
+    /// No match; the payment may proceed
+    Pass,
+    /// Possible match; the payment proceeds flagged for review
+    Review { reason: String },
+    /// Confirmed match; the payment is refused
+    Block { reason: String },
+}
+
+impl From<ScreeningResult> for ScreeningResultFFI {
+    fn from(result: ScreeningResult) -> Self {
+        match result {
+            ScreeningResult::Pass => ScreeningResultFFI::Pass,
+            ScreeningResult::Review { reason } => ScreeningResultFFI::Review { reason },
+            ScreeningResult::Block { reason } => ScreeningResultFFI::Block { reason },
+        }
+    }
+}
+
+impl From<ScreeningResultFFI> for ScreeningResult {
+    fn from(result: ScreeningResultFFI) -> Self {
+        match result {
+            ScreeningResultFFI::Pass => ScreeningResult::Pass,
+            ScreeningResultFFI::Review { reason } => ScreeningResult::Review { reason },
+            ScreeningResultFFI::Block { reason } => ScreeningResult::Block { reason },
+        }
+    }
+}
+
+// ============================================================================
+// Callback Interface
+// ============================================================================
+
+/// Compliance screener callback interface.
+///
+/// Implementations may block while they call an external service; they are
+/// never invoked on the UI thread by Paykit itself. Returning an error
+/// fails the payment closed.
+#[uniffi::export(callback_interface)]
+pub trait ComplianceScreenerCallback: Send + Sync {
+    /// Screen a payment before it is executed.
+    fn screen(
+        &self,
+        request: ScreeningRequestFFI,
+    ) -> std::result::Result<ScreeningResultFFI, PaykitMobileError>;
+}
+
+// ============================================================================
+// Bridge
+// ============================================================================
+
+/// Bridge from FFI callback to Rust ComplianceScreener trait.
+///
+/// This struct wraps a `ComplianceScreenerCallback` implementation and
+/// provides the `paykit_lib::policy::ComplianceScreener` trait.
+pub struct ComplianceScreenerBridge {
+    ffi: Arc<dyn ComplianceScreenerCallback>,
+}
+
+impl ComplianceScreenerBridge {
+    /// Create a new bridge wrapping an FFI screener.
+    pub fn new(ffi: Arc<dyn ComplianceScreenerCallback>) -> Self {
+        Self { ffi }
+    }
+
+    /// Screen a request synchronously.
+    pub(crate) fn screen_blocking(&self, request: &ScreeningRequest) -> Result<ScreeningResult> {
+        Ok(self.ffi.screen(request.into())?.into())
+    }
+}
+
+#[async_trait::async_trait]
+impl paykit_lib::policy::ComplianceScreener for ComplianceScreenerBridge {
+    async fn screen(&self, request: &ScreeningRequest) -> paykit_lib::Result<ScreeningResult> {
+        self.screen_blocking(request)
+            .map_err(|e| paykit_lib::PaykitError::Transport(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paykit_lib::policy::ComplianceScreener;
+
+    struct Denylist(&'static str);
+
+    impl ComplianceScreenerCallback for Denylist {
+        fn screen(
+            &self,
+            request: ScreeningRequestFFI,
+        ) -> std::result::Result<ScreeningResultFFI, PaykitMobileError> {
+            if request.payee == self.0 {
+                Ok(ScreeningResultFFI::Block {
+                    reason: "denylist".to_string(),
+                })
+            } else {
+                Ok(ScreeningResultFFI::Pass)
+            }
+        }
+    }
+
+    fn request(payee: &str) -> ScreeningRequest {
+        ScreeningRequest {
+            direction: ScreeningDirection::Outgoing,
+            payer: "payer".to_string(),
+            payee: payee.to_string(),
+            method_id: "lightning".to_string(),
+            amount: Some("1000".to_string()),
+            currency: Some("SAT".to_string()),
+            metadata: serde_json::json!({}),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bridge_screens_through_callback() {
+        let bridge = ComplianceScreenerBridge::new(Arc::new(Denylist("bad")));
+
+        assert_eq!(
+            bridge.screen(&request("good")).await.unwrap(),
+            ScreeningResult::Pass
+        );
+        assert!(bridge.screen(&request("bad")).await.unwrap().is_blocked());
+    }
+}
@@ -0,0 +1,67 @@
+//! Contact Verification FFI Bindings
+//!
+//! Protects against a substituted key when adding a contact. Both users open
+//! the verification screen and either compare the 12-digit code from
+//! `contact_short_auth_string`, or one shows the QR code from
+//! `create_contact_verification_code` and the other scans it and checks it
+//! with `verify_contact_code`. On a match the app records the result with
+//! `ContactCacheFFI::mark_verified`, and `TrustPolicyFFI` can require
+//! verified contacts for large payments.
+//!
+//! # Example (Swift)
+//!
+//! ```swift
+//! // Read aloud or show side by side
+//! codeLabel.text = try contactShortAuthString(localKey: myKey, contactKey: contact.pubkey)
+//!
+//! // Or show a QR code...
+//! let code = try createContactVerificationCode(localKey: myKey, contactKey: contact.pubkey)
+//! // ...which the other device scans
+//! try verifyContactCode(code: scanned, localKey: myKey, contactKey: contact.pubkey)
+//! try contacts.markVerified(pubkey: contact.pubkey, verifiedAt: Int64(Date().timeIntervalSince1970))
+//! ```
+
+use crate::Result;
+use paykit_lib::contact_verification::{short_auth_string, VerificationChallenge};
+
+/// Short authentication string both users compare, e.g. `0482 7731 5590`.
+#[uniffi::export]
+pub fn contact_short_auth_string(local_key: String, contact_key: String) -> Result<String> {
+    Ok(short_auth_string(&local_key, &contact_key)?)
+}
+
+/// Verification code (`paykit://verify/...`) to show as a QR code.
+#[uniffi::export]
+pub fn create_contact_verification_code(local_key: String, contact_key: String) -> Result<String> {
+    Ok(VerificationChallenge::new(&local_key, &contact_key)?.to_code())
+}
+
+/// Check a scanned verification code shown by `contact_key`.
+///
+/// Fails with a validation error if the code was shown by someone else or
+/// either party holds a substituted key.
+#[uniffi::export]
+pub fn verify_contact_code(code: String, local_key: String, contact_key: String) -> Result<()> {
+    Ok(VerificationChallenge::parse(&code)?.verify(&local_key, &contact_key)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contact_verification_ffi() {
+        let alice = pkarr::Keypair::random().public_key().to_string();
+        let bob = pkarr::Keypair::random().public_key().to_string();
+        let mallory = pkarr::Keypair::random().public_key().to_string();
+
+        assert_eq!(
+            contact_short_auth_string(alice.clone(), bob.clone()).unwrap(),
+            contact_short_auth_string(bob.clone(), alice.clone()).unwrap()
+        );
+
+        let code = create_contact_verification_code(alice.clone(), bob.clone()).unwrap();
+        assert!(verify_contact_code(code.clone(), bob.clone(), alice.clone()).is_ok());
+        assert!(verify_contact_code(code, bob, mallory).is_err());
+    }
+}
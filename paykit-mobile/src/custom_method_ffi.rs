@@ -0,0 +1,208 @@
+//! Custom Payment Method FFI Bindings
+//!
+//! Lets wallets add proprietary payment rails (internal ledger transfers,
+//! store credit, ...) from Swift/Kotlin. The app implements
+//! `CustomMethodCallbacksFFI` and registers it with
+//! `PaykitClient::register_custom_method`; the method then takes part in
+//! selection, execution and proof generation like the built-in methods.
+//!
+//! Endpoint data, metadata and execution data cross the boundary as JSON
+//! strings, so callbacks are free to define their own formats.
+//!
+//! # Example (Swift)
+//!
+//! ```swift
+//! class LedgerTransfer: CustomMethodCallbacksFfi {
+//!     func validateEndpoint(endpoint: String) -> [String] {
+//!         endpoint.hasPrefix("acct:") ? [] : ["Expected an account ID"]
+//!     }
+//!
+//!     func executePayment(endpoint: String, amountSats: UInt64, metadataJson: String) throws -> String {
+//!         let transfer = try ledger.transfer(to: endpoint, sats: amountSats)
+//!         return "{\"transfer_id\":\"\(transfer.id)\"}"
+//!     }
+//!
+//!     func generateProof(executionDataJson: String) throws -> String {
+//!         return executionDataJson
+//!     }
+//! }
+//!
+//! try client.registerCustomMethod(
+//!     methodId: "com.acme.ledger",
+//!     displayName: "ACME Ledger",
+//!     capabilities: MethodCapabilities(settlement: .other, execution: true, proofs: true,
+//!                                      privateEndpoints: false, routeProbing: false,
+//!                                      minAmountSats: nil, maxAmountSats: nil),
+//!     callbacks: LedgerTransfer()
+//! )
+//! ```
+
+use std::sync::Arc;
+
+use crate::PaykitMobileError;
+use paykit_lib::methods::{Amount, CustomMethodHandler, PaymentExecution, ValidationResult};
+use paykit_lib::EndpointData;
+
+/// Callbacks implementing a custom payment method.
+#[uniffi::export(callback_interface)]
+pub trait CustomMethodCallbacksFFI: Send + Sync {
+    /// Validate an endpoint, returning the problems found (empty if valid).
+    fn validate_endpoint(&self, endpoint: String) -> Vec<String>;
+
+    /// Pay `amount_sats` to `endpoint`.
+    ///
+    /// Returns method-specific execution data as a JSON string. Errors are
+    /// reported as a failed `PaymentExecutionResult`.
+    fn execute_payment(
+        &self,
+        endpoint: String,
+        amount_sats: u64,
+        metadata_json: String,
+    ) -> Result<String, PaykitMobileError>;
+
+    /// Produce proof data (JSON) from the execution data of a successful payment.
+    fn generate_proof(&self, execution_data_json: String) -> Result<String, PaykitMobileError>;
+}
+
+/// Bridge from FFI callbacks to the Rust `CustomMethodHandler` trait.
+pub struct CustomMethodBridge {
+    ffi: Arc<dyn CustomMethodCallbacksFFI>,
+}
+
+impl CustomMethodBridge {
+    /// Create a new bridge wrapping FFI callbacks.
+    pub fn new(ffi: Arc<dyn CustomMethodCallbacksFFI>) -> Self {
+        Self { ffi }
+    }
+}
+
+impl std::fmt::Debug for CustomMethodBridge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomMethodBridge")
+            .field("ffi", &"<CustomMethodCallbacksFFI>")
+            .finish()
+    }
+}
+
+impl CustomMethodHandler for CustomMethodBridge {
+    fn validate_endpoint(&self, endpoint: &EndpointData) -> ValidationResult {
+        let errors = self.ffi.validate_endpoint(endpoint.0.clone());
+        if errors.is_empty() {
+            ValidationResult::valid()
+        } else {
+            ValidationResult::invalid(errors)
+        }
+    }
+
+    fn execute_payment(
+        &self,
+        endpoint: &EndpointData,
+        amount: &Amount,
+        metadata: &serde_json::Value,
+    ) -> paykit_lib::Result<serde_json::Value> {
+        let amount_sats = amount
+            .as_u64()
+            .filter(|_| amount.currency.eq_ignore_ascii_case("SAT"))
+            .ok_or_else(|| {
+                paykit_lib::PaykitError::invalid_data("amount", "Amount must be whole sats")
+            })?;
+        let data = self
+            .ffi
+            .execute_payment(endpoint.0.clone(), amount_sats, metadata.to_string())
+            .map_err(|e| paykit_lib::PaykitError::Transport(e.to_string()))?;
+        parse_json(&data)
+    }
+
+    fn generate_proof(
+        &self,
+        execution: &PaymentExecution,
+    ) -> paykit_lib::Result<serde_json::Value> {
+        let proof = self
+            .ffi
+            .generate_proof(execution.execution_data.to_string())
+            .map_err(|e| paykit_lib::PaykitError::Transport(e.to_string()))?;
+        parse_json(&proof)
+    }
+}
+
+/// Parse callback JSON; an empty string means no data.
+fn parse_json(json: &str) -> paykit_lib::Result<serde_json::Value> {
+    if json.trim().is_empty() {
+        return Ok(serde_json::Value::Null);
+    }
+    serde_json::from_str(json).map_err(|e| paykit_lib::PaykitError::Serialization(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MockLedger;
+
+    impl CustomMethodCallbacksFFI for MockLedger {
+        fn validate_endpoint(&self, endpoint: String) -> Vec<String> {
+            if endpoint.starts_with("acct:") {
+                Vec::new()
+            } else {
+                vec!["Expected acct: prefix".to_string()]
+            }
+        }
+
+        fn execute_payment(
+            &self,
+            endpoint: String,
+            amount_sats: u64,
+            _metadata_json: String,
+        ) -> Result<String, PaykitMobileError> {
+            if endpoint == "acct:frozen" {
+                return Err(PaykitMobileError::Transport {
+                    msg: "account frozen".to_string(),
+                });
+            }
+            Ok(format!(
+                r#"{{"transfer":"{}","amount_sats":{}}}"#,
+                endpoint, amount_sats
+            ))
+        }
+
+        fn generate_proof(&self, execution_data_json: String) -> Result<String, PaykitMobileError> {
+            Ok(execution_data_json)
+        }
+    }
+
+    #[test]
+    fn test_bridge_validates_and_executes() {
+        let bridge = CustomMethodBridge::new(Arc::new(MockLedger));
+
+        assert!(
+            bridge
+                .validate_endpoint(&EndpointData("acct:1".into()))
+                .valid
+        );
+        assert!(!bridge.validate_endpoint(&EndpointData("bc1q".into())).valid);
+
+        let data = bridge
+            .execute_payment(
+                &EndpointData("acct:1".into()),
+                &Amount::sats(500),
+                &serde_json::Value::Null,
+            )
+            .unwrap();
+        assert_eq!(data["amount_sats"], 500);
+
+        assert!(bridge
+            .execute_payment(
+                &EndpointData("acct:frozen".into()),
+                &Amount::sats(1),
+                &serde_json::Value::Null,
+            )
+            .is_err());
+        assert!(bridge
+            .execute_payment(
+                &EndpointData("acct:1".into()),
+                &Amount::new("5", "USD"),
+                &serde_json::Value::Null,
+            )
+            .is_err());
+    }
+}
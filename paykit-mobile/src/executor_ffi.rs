@@ -40,8 +40,8 @@
 //! class BitkitBitcoinExecutor: BitcoinExecutorFFI {
 //!     let wallet: BitkitWallet
 //!
-//!     func sendToAddress(address: String, amountSats: UInt64, feeRate: Double?) throws -> BitcoinTxResultFFI {
-//!         let tx = try wallet.send(to: address, amount: amountSats, feeRate: feeRate)
+//!     func sendToAddress(address: String, amountSats: UInt64, feeRate: Double?, coinSelection: CoinSelectionFfi?) throws -> BitcoinTxResultFFI {
+//!         let tx = try wallet.send(to: address, amount: amountSats, feeRate: feeRate, coinControl: coinSelection)
 //!         return BitcoinTxResultFFI(
 //!             txid: tx.txid,
 //!             rawTx: tx.rawHex,
@@ -116,6 +116,61 @@ impl BitcoinTxResultFFI {
     }
 }
 
+/// What the wallet should do with change (FFI-compatible).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, uniffi::Enum)]
+pub enum ChangePreferenceFFI {
+    /// Leave it to the wallet.
+    #[default]
+    Default,
+    /// Prefer inputs that need no change output, adding leftovers to the fee.
+    Avoid,
+    /// Send change to an address of the same type as the payment.
+    MatchPaymentType,
+}
+
+impl From<paykit_lib::methods::ChangePreference> for ChangePreferenceFFI {
+    fn from(change: paykit_lib::methods::ChangePreference) -> Self {
+        match change {
+            paykit_lib::methods::ChangePreference::Default => ChangePreferenceFFI::Default,
+            paykit_lib::methods::ChangePreference::Avoid => ChangePreferenceFFI::Avoid,
+            paykit_lib::methods::ChangePreference::MatchPaymentType => {
+                ChangePreferenceFFI::MatchPaymentType
+            }
+        }
+    }
+}
+
+/// Payer's coin-selection hints for an on-chain payment (FFI-compatible).
+///
+/// Passed to `BitcoinExecutorFFI::sendToAddress()` when the payment metadata
+/// carries `coin_selection`. Wallets honor them as far as their coin control
+/// allows.
+#[derive(Clone, Debug, Default, PartialEq, Eq, uniffi::Record)]
+pub struct CoinSelectionFFI {
+    /// Outputs (`txid:vout`) never to spend, such as tainted or dust-attack coins.
+    pub avoid_outpoints: Vec<String>,
+
+    /// Only spend confirmed outputs.
+    pub confirmed_only: bool,
+
+    /// Maximum number of inputs.
+    pub max_inputs: Option<u32>,
+
+    /// What to do with change.
+    pub change: ChangePreferenceFFI,
+}
+
+impl From<&paykit_lib::methods::CoinSelection> for CoinSelectionFFI {
+    fn from(selection: &paykit_lib::methods::CoinSelection) -> Self {
+        Self {
+            avoid_outpoints: selection.avoid_outpoints.clone(),
+            confirmed_only: selection.confirmed_only,
+            max_inputs: selection.max_inputs,
+            change: selection.change.into(),
+        }
+    }
+}
+
 /// Result of a Lightning payment (FFI-compatible).
 ///
 /// This type is returned by `LightningExecutorFFI::payInvoice()` after
@@ -229,6 +284,7 @@ pub trait BitcoinExecutorFFI: Send + Sync {
     /// * `address` - The destination Bitcoin address
     /// * `amount_sats` - The amount to send in satoshis
     /// * `fee_rate` - Optional fee rate in sat/vB (uses wallet default if None)
+    /// * `coin_selection` - Payer's coin-selection hints, if any
     ///
     /// # Returns
     ///
@@ -246,6 +302,7 @@ pub trait BitcoinExecutorFFI: Send + Sync {
         address: String,
         amount_sats: u64,
         fee_rate: Option<f64>,
+        coin_selection: Option<CoinSelectionFFI>,
     ) -> Result<BitcoinTxResultFFI, PaykitMobileError>;
 
     /// Estimate the fee for a transaction.
@@ -425,20 +482,7 @@ impl paykit_lib::methods::BitcoinExecutor for BitcoinExecutorBridge {
         amount_sats: u64,
         fee_rate: Option<f64>,
     ) -> paykit_lib::Result<paykit_lib::methods::BitcoinTxResult> {
-        let result = self
-            .ffi
-            .send_to_address(address.to_string(), amount_sats, fee_rate)
-            .map_err(|e| paykit_lib::PaykitError::Transport(e.to_string()))?;
-
-        Ok(paykit_lib::methods::BitcoinTxResult {
-            txid: result.txid,
-            raw_tx: result.raw_tx,
-            vout: result.vout,
-            fee_sats: result.fee_sats,
-            fee_rate: result.fee_rate,
-            block_height: result.block_height,
-            confirmations: result.confirmations,
-        })
+        self.send(address, amount_sats, fee_rate, None)
     }
 
     async fn estimate_fee(
@@ -482,6 +526,41 @@ impl paykit_lib::methods::BitcoinExecutor for BitcoinExecutorBridge {
             .verify_transaction(txid.to_string(), address.to_string(), amount_sats)
             .map_err(|e| paykit_lib::PaykitError::Transport(e.to_string()))
     }
+
+    async fn send_with_coin_selection(
+        &self,
+        address: &str,
+        amount_sats: u64,
+        fee_rate: Option<f64>,
+        selection: &paykit_lib::methods::CoinSelection,
+    ) -> paykit_lib::Result<paykit_lib::methods::BitcoinTxResult> {
+        self.send(address, amount_sats, fee_rate, Some(selection.into()))
+    }
+}
+
+impl BitcoinExecutorBridge {
+    fn send(
+        &self,
+        address: &str,
+        amount_sats: u64,
+        fee_rate: Option<f64>,
+        coin_selection: Option<CoinSelectionFFI>,
+    ) -> paykit_lib::Result<paykit_lib::methods::BitcoinTxResult> {
+        let result = self
+            .ffi
+            .send_to_address(address.to_string(), amount_sats, fee_rate, coin_selection)
+            .map_err(|e| paykit_lib::PaykitError::Transport(e.to_string()))?;
+
+        Ok(paykit_lib::methods::BitcoinTxResult {
+            txid: result.txid,
+            raw_tx: result.raw_tx,
+            vout: result.vout,
+            fee_sats: result.fee_sats,
+            fee_rate: result.fee_rate,
+            block_height: result.block_height,
+            confirmations: result.confirmations,
+        })
+    }
 }
 
 /// Bridge from FFI callback to Rust LightningExecutor trait.
@@ -688,6 +767,7 @@ mod tests {
     struct MockBitcoinExecutorFFI {
         send_count: AtomicU32,
         should_fail: bool,
+        last_coin_selection: std::sync::Mutex<Option<CoinSelectionFFI>>,
     }
 
     impl MockBitcoinExecutorFFI {
@@ -695,6 +775,7 @@ mod tests {
             Self {
                 send_count: AtomicU32::new(0),
                 should_fail: false,
+                last_coin_selection: std::sync::Mutex::new(None),
             }
         }
 
@@ -702,6 +783,7 @@ mod tests {
             Self {
                 send_count: AtomicU32::new(0),
                 should_fail: true,
+                last_coin_selection: std::sync::Mutex::new(None),
             }
         }
     }
@@ -712,12 +794,14 @@ mod tests {
             _address: String,
             _amount_sats: u64,
             fee_rate: Option<f64>,
+            coin_selection: Option<CoinSelectionFFI>,
         ) -> Result<BitcoinTxResultFFI, PaykitMobileError> {
             if self.should_fail {
                 return Err(PaykitMobileError::Transport {
                     msg: "Mock failure".to_string(),
                 });
             }
+            *self.last_coin_selection.lock().unwrap() = coin_selection;
             self.send_count.fetch_add(1, Ordering::SeqCst);
             Ok(BitcoinTxResultFFI {
                 txid: format!("mock_txid_{}", self.send_count.load(Ordering::SeqCst)),
@@ -913,7 +997,7 @@ mod tests {
     fn test_mock_bitcoin_executor_send() {
         let executor = MockBitcoinExecutorFFI::new();
         let result = executor
-            .send_to_address("bc1qtest".to_string(), 10000, Some(2.0))
+            .send_to_address("bc1qtest".to_string(), 10000, Some(2.0), None)
             .unwrap();
         assert!(result.txid.starts_with("mock_txid"));
         assert_eq!(result.fee_sats, 280); // 2.0 * 140
@@ -922,7 +1006,7 @@ mod tests {
     #[test]
     fn test_mock_bitcoin_executor_failure() {
         let executor = MockBitcoinExecutorFFI::failing();
-        let result = executor.send_to_address("bc1qtest".to_string(), 10000, None);
+        let result = executor.send_to_address("bc1qtest".to_string(), 10000, None, None);
         assert!(result.is_err());
     }
 
@@ -1031,6 +1115,30 @@ mod tests {
         assert!(verified);
     }
 
+    #[tokio::test]
+    async fn test_bitcoin_executor_bridge_passes_coin_selection() {
+        use paykit_lib::methods::{Amount, OnchainPlugin, PaymentMethodPlugin};
+
+        let mock = Arc::new(MockBitcoinExecutorFFI::new());
+        let plugin =
+            OnchainPlugin::with_executor(Arc::new(BitcoinExecutorBridge::new(mock.clone())));
+        let endpoint =
+            paykit_lib::EndpointData("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string());
+        let metadata = serde_json::json!({
+            "coin_selection": { "confirmed_only": true, "change": "avoid" }
+        });
+
+        let execution = plugin
+            .execute_payment(&endpoint, &Amount::sats(10_000), &metadata)
+            .await
+            .unwrap();
+        assert_eq!(execution.execution_data["coin_selection"]["honored"], true);
+
+        let passed = mock.last_coin_selection.lock().unwrap().clone().unwrap();
+        assert!(passed.confirmed_only);
+        assert_eq!(passed.change, ChangePreferenceFFI::Avoid);
+    }
+
     #[tokio::test]
     async fn test_bitcoin_executor_bridge_error() {
         let mock = Arc::new(MockBitcoinExecutorFFI::failing());
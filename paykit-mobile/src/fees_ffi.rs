@@ -0,0 +1,52 @@
+//! Fee Guardrail FFI Types
+//!
+//! FFI-safe limits for the fees `execute_payment()` may pay without asking.
+//! Above them the payment fails with `PaykitMobileError::FeeSpike`; after
+//! the user confirms, the app retries with `"allow_fee_spike": true` in the
+//! metadata.
+//!
+//! # Example Flow
+//!
+//! ```ignore
+//! client.set_fee_guardrails(FeeGuardrailsFFI {
+//!     max_onchain_fee_rate: Some(50.0),
+//!     max_lightning_fee_percent: Some(1.0),
+//! });
+//!
+//! match client.execute_payment("onchain".into(), address, 100_000, None, None) {
+//!     Err(PaykitMobileError::FeeSpike { msg }) if confirm(&msg) => {
+//!         let metadata = r#"{"allow_fee_spike":true}"#.to_string();
+//!         client.execute_payment("onchain".into(), address, 100_000, Some(metadata), None)?;
+//!     }
+//!     result => handle(result),
+//! }
+//! ```
+
+use paykit_lib::policy::FeeGuardrails;
+
+/// FFI-safe fee guardrails.
+#[derive(Clone, Debug, Default, uniffi::Record)]
+pub struct FeeGuardrailsFFI {
+    /// Highest on-chain fee rate in sat/vB, if limited
+    pub max_onchain_fee_rate: Option<f64>,
+    /// Highest Lightning routing fee as a percentage of the amount, if limited
+    pub max_lightning_fee_percent: Option<f64>,
+}
+
+impl From<FeeGuardrails> for FeeGuardrailsFFI {
+    fn from(guardrails: FeeGuardrails) -> Self {
+        Self {
+            max_onchain_fee_rate: guardrails.max_onchain_fee_rate,
+            max_lightning_fee_percent: guardrails.max_lightning_fee_percent,
+        }
+    }
+}
+
+impl From<FeeGuardrailsFFI> for FeeGuardrails {
+    fn from(guardrails: FeeGuardrailsFFI) -> Self {
+        Self {
+            max_onchain_fee_rate: guardrails.max_onchain_fee_rate,
+            max_lightning_fee_percent: guardrails.max_lightning_fee_percent,
+        }
+    }
+}
@@ -0,0 +1,212 @@
+//! Handle Resolution FFI Bindings
+//!
+//! Lets users pay `alice@example.com` instead of a z-base32 key. The app
+//! does the networking: it implements `HandleLookupCallback` with its own
+//! HTTP client and DNS resolver, wraps it in a `HandleResolverFFI` (which
+//! verifies the signed records and caches the results), and resolves
+//! handles with `PaykitClient::resolve_handle` before discovery. The
+//! scanner reports handles as `UriType::Handle`.
+//!
+//! # Example (Swift)
+//!
+//! ```swift
+//! class AppHandleLookup: HandleLookupCallback {
+//!     func fetchDocument(url: String) throws -> String? {
+//!         let (data, response) = try http.getSync(url)
+//!         return response.statusCode == 404 ? nil : String(data: data, encoding: .utf8)
+//!     }
+//!     func lookupTxt(name: String) throws -> [String] {
+//!         return try dns.txtRecords(name)
+//!     }
+//! }
+//!
+//! let resolver = HandleResolverFfi(lookup: AppHandleLookup(), ttlSecs: nil)
+//! let resolved = try client.resolveHandle(resolver: resolver, handle: "alice@example.com")
+//! let methods = try client.fetchSupportedPayments(
+//!     transport: transport, ownerPubkey: resolved.publicKey)
+//! ```
+
+use crate::PaykitMobileError;
+use paykit_lib::handles::{HandleLookup, HandleResolver, HandleSource, ResolvedHandle};
+use std::sync::Arc;
+
+// ============================================================================
+// FFI Types
+// ============================================================================
+
+/// FFI-safe handle source.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, uniffi::Enum)]
+pub enum HandleSourceFFI {
+    /// The domain's well-known document
+    WellKnown,
+    /// The domain's DNS TXT record
+    Dns,
+}
+
+impl From<HandleSource> for HandleSourceFFI {
+    fn from(source: HandleSource) -> Self {
+        match source {
+            HandleSource::WellKnown => HandleSourceFFI::WellKnown,
+            HandleSource::Dns => HandleSourceFFI::Dns,
+        }
+    }
+}
+
+/// FFI-safe resolved handle.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct ResolvedHandleFFI {
+    /// The normalized handle, `user@domain`
+    pub handle: String,
+    /// The verified public key, z-base-32
+    pub public_key: String,
+    /// Where the record was found
+    pub source: HandleSourceFFI,
+    /// Unix timestamp the record was fetched
+    pub resolved_at: i64,
+    /// Unix timestamp the cached result expires
+    pub cached_until: i64,
+}
+
+impl From<ResolvedHandle> for ResolvedHandleFFI {
+    fn from(resolved: ResolvedHandle) -> Self {
+        Self {
+            handle: resolved.handle.to_string(),
+            public_key: resolved.public_key.to_string(),
+            source: resolved.source.into(),
+            resolved_at: resolved.resolved_at,
+            cached_until: resolved.cached_until,
+        }
+    }
+}
+
+/// Network access for handle resolution, implemented by the app.
+///
+/// Calls may block; Paykit never makes them on the UI thread itself.
+#[uniffi::export(callback_interface)]
+pub trait HandleLookupCallback: Send + Sync {
+    /// Fetch the document at an HTTPS URL, or `None` on HTTP 404.
+    fn fetch_document(&self, url: String)
+        -> std::result::Result<Option<String>, PaykitMobileError>;
+
+    /// All TXT record values at a DNS name, empty if there are none.
+    fn lookup_txt(&self, name: String) -> std::result::Result<Vec<String>, PaykitMobileError>;
+}
+
+// ============================================================================
+// Bridge
+// ============================================================================
+
+/// Bridge from FFI callback to the Rust `HandleLookup` trait.
+struct HandleLookupBridge {
+    ffi: Box<dyn HandleLookupCallback>,
+}
+
+#[async_trait::async_trait]
+impl HandleLookup for HandleLookupBridge {
+    async fn fetch_document(&self, url: &str) -> paykit_lib::Result<Option<String>> {
+        self.ffi
+            .fetch_document(url.to_string())
+            .map_err(|e| paykit_lib::PaykitError::Transport(e.to_string()))
+    }
+
+    async fn lookup_txt(&self, name: &str) -> paykit_lib::Result<Vec<String>> {
+        self.ffi
+            .lookup_txt(name.to_string())
+            .map_err(|e| paykit_lib::PaykitError::Transport(e.to_string()))
+    }
+}
+
+// ============================================================================
+// Handle Resolver
+// ============================================================================
+
+/// Resolves handles through the app's lookup, caching verified results.
+#[derive(uniffi::Object)]
+pub struct HandleResolverFFI {
+    resolver: HandleResolver,
+}
+
+impl HandleResolverFFI {
+    /// The underlying resolver.
+    pub(crate) fn resolver(&self) -> &HandleResolver {
+        &self.resolver
+    }
+}
+
+#[uniffi::export]
+impl HandleResolverFFI {
+    /// Create a resolver, caching results for `ttl_secs` (default 1 hour).
+    #[uniffi::constructor]
+    pub fn new(lookup: Box<dyn HandleLookupCallback>, ttl_secs: Option<i64>) -> Arc<Self> {
+        let mut resolver = HandleResolver::new(Arc::new(HandleLookupBridge { ffi: lookup }));
+        if let Some(ttl_secs) = ttl_secs {
+            resolver = resolver.with_ttl(ttl_secs);
+        }
+        Arc::new(Self { resolver })
+    }
+
+    /// Forget a cached handle, e.g. after the payee reports a new key.
+    pub fn invalidate(&self, handle: String) {
+        self.resolver.invalidate(&handle);
+    }
+
+    /// Forget every cached handle.
+    pub fn clear_cache(&self) {
+        self.resolver.clear_cache();
+    }
+}
+
+/// Whether `value` is a handle (`user@domain`) that needs resolving.
+#[uniffi::export]
+pub fn is_handle(value: String) -> bool {
+    paykit_lib::handles::Handle::is_handle(&value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paykit_lib::handles::{Handle, HandleRecord};
+
+    struct StaticLookup(String);
+
+    impl HandleLookupCallback for StaticLookup {
+        fn fetch_document(
+            &self,
+            url: String,
+        ) -> std::result::Result<Option<String>, PaykitMobileError> {
+            Ok((url == "https://example.com/.well-known/paykit/alice").then(|| self.0.clone()))
+        }
+
+        fn lookup_txt(&self, _name: String) -> std::result::Result<Vec<String>, PaykitMobileError> {
+            Err(PaykitMobileError::Transport {
+                msg: "offline".to_string(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handle_resolver_bridge() {
+        let keypair = pkarr::Keypair::random();
+        let handle = Handle::parse("alice@example.com").unwrap();
+        let record = HandleRecord::sign(&handle, &keypair, 0, None).unwrap();
+        let resolver =
+            HandleResolverFFI::new(Box::new(StaticLookup(record.to_json().unwrap())), None);
+
+        let resolved: ResolvedHandleFFI = resolver
+            .resolver()
+            .resolve_at("alice@example.com", 100)
+            .await
+            .unwrap()
+            .into();
+        assert_eq!(resolved.public_key, keypair.public_key().to_string());
+        assert_eq!(resolved.source, HandleSourceFFI::WellKnown);
+
+        // The DNS fallback's error is reported when nothing is published
+        assert!(resolver
+            .resolver()
+            .resolve_at("bob@example.com", 100)
+            .await
+            .is_err());
+        assert!(is_handle("bob@example.com".to_string()));
+    }
+}
@@ -37,7 +37,15 @@
 //! }
 //! ```
 
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use paykit_interactive::flow::{FlowConfig, FlowEvent, FlowState, ReceiptFlow};
+use paykit_lib::protocol::{parse_document, MAX_DOCUMENT_BYTES};
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
 
 use crate::{PaykitMobileError, Result};
 
@@ -61,7 +69,7 @@ pub enum PaykitMessageType {
 }
 
 /// FFI-safe private endpoint offer.
-#[derive(Clone, Debug, uniffi::Record)]
+#[derive(Clone, Debug, PartialEq, Eq, uniffi::Record)]
 pub struct PrivateEndpointOffer {
     pub method_id: String,
     pub endpoint: String,
@@ -79,6 +87,34 @@ pub struct ReceiptRequest {
     pub metadata_json: String,
 }
 
+/// FFI-safe receipt search query.
+///
+/// Mirrors `paykit_interactive::ReceiptQuery`. Mobile receipts carry no
+/// timestamp, so `from_timestamp`/`to_timestamp` only match receipts
+/// imported with one and are otherwise best left unset.
+#[derive(Clone, Debug, Default, uniffi::Record)]
+pub struct ReceiptSearchQuery {
+    pub payer: Option<String>,
+    pub payee: Option<String>,
+    pub method_id: Option<String>,
+    pub min_amount: Option<u64>,
+    pub from_timestamp: Option<i64>,
+    pub to_timestamp: Option<i64>,
+    pub text: Option<String>,
+    /// Page size (0 uses the default of 50).
+    pub limit: u32,
+    /// Cursor from a previous page.
+    pub cursor: Option<String>,
+}
+
+/// FFI-safe page of receipt search results.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct ReceiptSearchPage {
+    pub receipts: Vec<ReceiptRequest>,
+    /// Cursor for the next page, or `None` on the last page.
+    pub next_cursor: Option<String>,
+}
+
 /// FFI-safe error message.
 #[derive(Clone, Debug, uniffi::Record)]
 pub struct ErrorMessage {
@@ -256,113 +292,21 @@ impl PaykitMessageBuilder {
     ///
     /// Parsed message for processing.
     pub fn parse_message(&self, message_json: String) -> Result<ParsedMessage> {
-        let value: serde_json::Value =
-            serde_json::from_str(&message_json).map_err(|e| PaykitMobileError::Validation {
-                msg: format!("Invalid message JSON: {}", e),
-            })?;
-
-        let msg_type = value.get("type").and_then(|t| t.as_str()).ok_or_else(|| {
-            PaykitMobileError::Validation {
-                msg: "Missing message type".to_string(),
-            }
-        })?;
-
-        match msg_type {
-            "OfferPrivateEndpoint" => {
-                let payload =
-                    value
-                        .get("payload")
-                        .ok_or_else(|| PaykitMobileError::Validation {
-                            msg: "Missing payload".to_string(),
-                        })?;
-
-                let method_id = payload
-                    .get("method_id")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or_default()
-                    .to_string();
-                let endpoint = payload
-                    .get("endpoint")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or_default()
-                    .to_string();
-
-                Ok(ParsedMessage::OfferPrivateEndpoint {
-                    offer: PrivateEndpointOffer {
-                        method_id,
-                        endpoint,
-                    },
-                })
-            }
-            "RequestReceipt" => {
-                let payload =
-                    value
-                        .get("payload")
-                        .ok_or_else(|| PaykitMobileError::Validation {
-                            msg: "Missing payload".to_string(),
-                        })?;
-
-                let receipt = payload.get("provisional_receipt").ok_or_else(|| {
-                    PaykitMobileError::Validation {
-                        msg: "Missing provisional_receipt".to_string(),
-                    }
-                })?;
-
-                Ok(ParsedMessage::RequestReceipt {
-                    request: parse_receipt_from_value(receipt)?,
-                })
-            }
-            "ConfirmReceipt" => {
-                let payload =
-                    value
-                        .get("payload")
-                        .ok_or_else(|| PaykitMobileError::Validation {
-                            msg: "Missing payload".to_string(),
-                        })?;
-
-                let receipt =
-                    payload
-                        .get("receipt")
-                        .ok_or_else(|| PaykitMobileError::Validation {
-                            msg: "Missing receipt".to_string(),
-                        })?;
-
-                Ok(ParsedMessage::ConfirmReceipt {
-                    receipt: parse_receipt_from_value(receipt)?,
-                })
-            }
-            "Ack" => Ok(ParsedMessage::Ack),
-            "Error" => {
-                let payload =
-                    value
-                        .get("payload")
-                        .ok_or_else(|| PaykitMobileError::Validation {
-                            msg: "Missing payload".to_string(),
-                        })?;
+        parse_wire_message(message_json.as_bytes())
+    }
 
-                let code = payload
-                    .get("code")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or_default()
-                    .to_string();
-                let message = payload
-                    .get("message")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or_default()
-                    .to_string();
-
-                Ok(ParsedMessage::Error {
-                    error: ErrorMessage { code, message },
-                })
-            }
-            _ => Err(PaykitMobileError::Validation {
-                msg: format!("Unknown message type: {}", msg_type),
-            }),
-        }
+    /// Parse a received message from the raw channel bytes.
+    ///
+    /// Use this when the Noise plaintext is already a byte array, to skip
+    /// decoding it into a platform string first.
+    pub fn parse_message_bytes(&self, message: Vec<u8>) -> Result<ParsedMessage> {
+        parse_wire_message(&message)
     }
 
     /// Get the message type from a JSON message.
     ///
+    /// Only the `type` field is read; the payload is skipped unparsed.
+    ///
     /// # Arguments
     ///
     /// * `message_json` - JSON-encoded message
@@ -371,30 +315,168 @@ impl PaykitMessageBuilder {
     ///
     /// The message type.
     pub fn get_message_type(&self, message_json: String) -> Result<PaykitMessageType> {
-        let value: serde_json::Value =
-            serde_json::from_str(&message_json).map_err(|e| PaykitMobileError::Validation {
-                msg: format!("Invalid message JSON: {}", e),
-            })?;
-
-        let msg_type = value.get("type").and_then(|t| t.as_str()).ok_or_else(|| {
-            PaykitMobileError::Validation {
-                msg: "Missing message type".to_string(),
-            }
-        })?;
+        let message: WireMessage = parse_wire(message_json.as_bytes())?;
 
-        match msg_type {
+        match message.kind.as_ref() {
             "OfferPrivateEndpoint" => Ok(PaykitMessageType::OfferPrivateEndpoint),
             "RequestReceipt" => Ok(PaykitMessageType::RequestReceipt),
             "ConfirmReceipt" => Ok(PaykitMessageType::ConfirmReceipt),
             "Ack" => Ok(PaykitMessageType::Ack),
             "Error" => Ok(PaykitMessageType::Error),
-            _ => Err(PaykitMobileError::Validation {
-                msg: format!("Unknown message type: {}", msg_type),
+            other => Err(PaykitMobileError::Validation {
+                msg: format!("Unknown message type: {}", other),
             }),
         }
     }
 }
 
+// ============================================================================
+// Wire Parsing
+// ============================================================================
+
+/// Message envelope. The payload stays raw JSON until the type is known.
+#[derive(Deserialize)]
+struct WireMessage<'a> {
+    #[serde(rename = "type", borrow)]
+    kind: Cow<'a, str>,
+    #[serde(borrow, default)]
+    payload: Option<&'a RawValue>,
+}
+
+#[derive(Deserialize)]
+struct WireOffer<'a> {
+    #[serde(borrow, default)]
+    method_id: Cow<'a, str>,
+    #[serde(borrow, default)]
+    endpoint: Cow<'a, str>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct WireReceipt<'a> {
+    #[serde(borrow, default)]
+    receipt_id: Cow<'a, str>,
+    #[serde(borrow, default)]
+    payer: Cow<'a, str>,
+    #[serde(borrow, default)]
+    payee: Cow<'a, str>,
+    #[serde(borrow, default)]
+    method_id: Cow<'a, str>,
+    #[serde(borrow, default)]
+    amount: Option<Cow<'a, str>>,
+    #[serde(borrow, default)]
+    currency: Option<Cow<'a, str>>,
+    #[serde(borrow, default, skip_serializing_if = "Option::is_none")]
+    metadata: Option<&'a RawValue>,
+}
+
+impl<'a> From<&'a ReceiptRequest> for WireReceipt<'a> {
+    fn from(receipt: &'a ReceiptRequest) -> Self {
+        Self {
+            receipt_id: Cow::Borrowed(&receipt.receipt_id),
+            payer: Cow::Borrowed(&receipt.payer),
+            payee: Cow::Borrowed(&receipt.payee),
+            method_id: Cow::Borrowed(&receipt.method_id),
+            amount: receipt.amount.as_deref().map(Cow::Borrowed),
+            currency: receipt.currency.as_deref().map(Cow::Borrowed),
+            // Invalid metadata is dropped rather than failing the export
+            metadata: serde_json::from_str(&receipt.metadata_json).ok(),
+        }
+    }
+}
+
+impl From<WireReceipt<'_>> for ReceiptRequest {
+    fn from(receipt: WireReceipt<'_>) -> Self {
+        Self {
+            receipt_id: receipt.receipt_id.into_owned(),
+            payer: receipt.payer.into_owned(),
+            payee: receipt.payee.into_owned(),
+            method_id: receipt.method_id.into_owned(),
+            amount: receipt.amount.map(Cow::into_owned),
+            currency: receipt.currency.map(Cow::into_owned),
+            // Copied verbatim: the metadata is never parsed into a tree
+            metadata_json: receipt
+                .metadata
+                .map(|raw| raw.get().to_string())
+                .unwrap_or_else(|| "{}".to_string()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct WireReceiptRequest<'a> {
+    #[serde(borrow)]
+    provisional_receipt: WireReceipt<'a>,
+}
+
+#[derive(Deserialize)]
+struct WireReceiptConfirm<'a> {
+    #[serde(borrow)]
+    receipt: WireReceipt<'a>,
+}
+
+#[derive(Deserialize)]
+struct WireError<'a> {
+    #[serde(borrow, default)]
+    code: Cow<'a, str>,
+    #[serde(borrow, default)]
+    message: Cow<'a, str>,
+}
+
+fn parse_wire<'a, T: Deserialize<'a>>(json: &'a [u8]) -> Result<T> {
+    parse_document("message", json, MAX_DOCUMENT_BYTES).map_err(|e| PaykitMobileError::Validation {
+        msg: format!("Invalid message JSON: {}", e),
+    })
+}
+
+fn parse_wire_message(json: &[u8]) -> Result<ParsedMessage> {
+    let message: WireMessage = parse_wire(json)?;
+    let payload = || {
+        message
+            .payload
+            .map(|raw| raw.get().as_bytes())
+            .ok_or_else(|| PaykitMobileError::Validation {
+                msg: "Missing payload".to_string(),
+            })
+    };
+
+    match message.kind.as_ref() {
+        "OfferPrivateEndpoint" => {
+            let offer: WireOffer = parse_wire(payload()?)?;
+            Ok(ParsedMessage::OfferPrivateEndpoint {
+                offer: PrivateEndpointOffer {
+                    method_id: offer.method_id.into_owned(),
+                    endpoint: offer.endpoint.into_owned(),
+                },
+            })
+        }
+        "RequestReceipt" => {
+            let request: WireReceiptRequest = parse_wire(payload()?)?;
+            Ok(ParsedMessage::RequestReceipt {
+                request: request.provisional_receipt.into(),
+            })
+        }
+        "ConfirmReceipt" => {
+            let confirm: WireReceiptConfirm = parse_wire(payload()?)?;
+            Ok(ParsedMessage::ConfirmReceipt {
+                receipt: confirm.receipt.into(),
+            })
+        }
+        "Ack" => Ok(ParsedMessage::Ack),
+        "Error" => {
+            let error: WireError = parse_wire(payload()?)?;
+            Ok(ParsedMessage::Error {
+                error: ErrorMessage {
+                    code: error.code.into_owned(),
+                    message: error.message.into_owned(),
+                },
+            })
+        }
+        other => Err(PaykitMobileError::Validation {
+            msg: format!("Unknown message type: {}", other),
+        }),
+    }
+}
+
 // ============================================================================
 // Receipt Storage
 // ============================================================================
@@ -409,6 +491,20 @@ pub struct ReceiptStore {
     private_endpoints: std::sync::RwLock<std::collections::HashMap<String, PrivateEndpointOffer>>,
 }
 
+impl crate::memory_ffi::MemoryReporter for ReceiptStore {
+    fn memory_usage(&self) -> Vec<crate::memory_ffi::SubsystemMemoryFFI> {
+        let receipts = self.receipts.read().unwrap_or_else(|e| e.into_inner());
+        let endpoints = self
+            .private_endpoints
+            .read()
+            .unwrap_or_else(|e| e.into_inner());
+        vec![
+            crate::memory_ffi::map_usage("receipt_store.receipts", &receipts),
+            crate::memory_ffi::map_usage("receipt_store.private_endpoints", &endpoints),
+        ]
+    }
+}
+
 #[uniffi::export]
 impl ReceiptStore {
     /// Create a new receipt store.
@@ -454,6 +550,25 @@ impl ReceiptStore {
         Ok(receipts.values().cloned().collect())
     }
 
+    /// Search receipts and return one page of results.
+    pub fn query_receipts(&self, query: ReceiptSearchQuery) -> Result<ReceiptSearchPage> {
+        let receipts = self
+            .receipts
+            .read()
+            .map_err(|_| PaykitMobileError::Internal {
+                msg: "Lock poisoned".to_string(),
+            })?;
+
+        let page = to_core_query(query)
+            .apply(receipts.values().cloned())
+            .map_err(|e| PaykitMobileError::Validation { msg: e.to_string() })?;
+
+        Ok(ReceiptSearchPage {
+            receipts: page.items,
+            next_cursor: page.next_cursor,
+        })
+    }
+
     /// Delete a receipt.
     pub fn delete_receipt(&self, receipt_id: String) -> Result<()> {
         let mut receipts = self
@@ -543,17 +658,7 @@ impl ReceiptStore {
                 msg: "Lock poisoned".to_string(),
             })?;
 
-        let list: Vec<_> = receipts.values().map(|r| {
-            serde_json::json!({
-                "receipt_id": r.receipt_id,
-                "payer": r.payer,
-                "payee": r.payee,
-                "method_id": r.method_id,
-                "amount": r.amount,
-                "currency": r.currency,
-                "metadata": serde_json::from_str::<serde_json::Value>(&r.metadata_json).unwrap_or(serde_json::json!({}))
-            })
-        }).collect();
+        let list: Vec<WireReceipt> = receipts.values().map(WireReceipt::from).collect();
 
         serde_json::to_string(&list)
             .map_err(|e| PaykitMobileError::Serialization { msg: e.to_string() })
@@ -561,7 +666,7 @@ impl ReceiptStore {
 
     /// Import receipts from JSON.
     pub fn import_receipts_json(&self, json: String) -> Result<u32> {
-        let list: Vec<serde_json::Value> = serde_json::from_str(&json)
+        let list: Vec<&RawValue> = serde_json::from_str(&json)
             .map_err(|e| PaykitMobileError::Serialization { msg: e.to_string() })?;
 
         let mut receipts = self
@@ -572,8 +677,9 @@ impl ReceiptStore {
             })?;
 
         let mut count = 0;
-        for value in list {
-            if let Ok(receipt) = parse_receipt_from_value(&value) {
+        for raw in list {
+            if let Ok(receipt) = serde_json::from_str::<WireReceipt>(raw.get()) {
+                let receipt = ReceiptRequest::from(receipt);
                 receipts.insert(receipt.receipt_id.clone(), receipt);
                 count += 1;
             }
@@ -594,41 +700,65 @@ fn current_timestamp() -> i64 {
         .as_secs() as i64
 }
 
-fn parse_receipt_from_value(value: &serde_json::Value) -> Result<ReceiptRequest> {
-    Ok(ReceiptRequest {
-        receipt_id: value
-            .get("receipt_id")
-            .and_then(|v| v.as_str())
-            .unwrap_or_default()
-            .to_string(),
-        payer: value
-            .get("payer")
-            .and_then(|v| v.as_str())
-            .unwrap_or_default()
-            .to_string(),
-        payee: value
-            .get("payee")
-            .and_then(|v| v.as_str())
-            .unwrap_or_default()
-            .to_string(),
-        method_id: value
-            .get("method_id")
-            .and_then(|v| v.as_str())
-            .unwrap_or_default()
-            .to_string(),
-        amount: value
-            .get("amount")
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string()),
-        currency: value
-            .get("currency")
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string()),
-        metadata_json: value
-            .get("metadata")
-            .map(|v| v.to_string())
-            .unwrap_or_else(|| "{}".to_string()),
-    })
+impl paykit_interactive::SearchableReceipt for ReceiptRequest {
+    fn receipt_id(&self) -> &str {
+        &self.receipt_id
+    }
+
+    fn payer(&self) -> String {
+        self.payer.clone()
+    }
+
+    fn payee(&self) -> String {
+        self.payee.clone()
+    }
+
+    fn method_id(&self) -> &str {
+        &self.method_id
+    }
+
+    fn amount(&self) -> Option<&str> {
+        self.amount.as_deref()
+    }
+
+    fn created_at(&self) -> Option<i64> {
+        None
+    }
+
+    fn search_text(&self) -> String {
+        format!(
+            "{} {} {}",
+            self.receipt_id,
+            self.currency.as_deref().unwrap_or_default(),
+            self.metadata_json
+        )
+    }
+}
+
+fn to_core_query(query: ReceiptSearchQuery) -> paykit_interactive::ReceiptQuery {
+    let date_range = if query.from_timestamp.is_some() || query.to_timestamp.is_some() {
+        Some(paykit_interactive::DateRange::new(
+            query.from_timestamp,
+            query.to_timestamp,
+        ))
+    } else {
+        None
+    };
+
+    paykit_interactive::ReceiptQuery {
+        payer: query.payer,
+        payee: query.payee,
+        method: query.method_id,
+        min_amount: query.min_amount,
+        date_range,
+        text: query.text,
+        limit: if query.limit == 0 {
+            paykit_interactive::query::DEFAULT_PAGE_SIZE
+        } else {
+            query.limit as usize
+        },
+        cursor: query.cursor,
+    }
 }
 
 /// Create a new message builder.
@@ -647,58 +777,179 @@ pub fn create_receipt_store() -> Arc<ReceiptStore> {
 // Receipt Generator Callback
 // ============================================================================
 
-/// Result type for receipt generation.
-///
-/// Used to communicate success/failure from mobile callbacks.
-#[derive(Clone, Debug, uniffi::Record)]
-pub struct ReceiptGenerationResult {
-    /// Whether generation succeeded
-    pub success: bool,
-    /// The generated receipt (if successful)
-    pub receipt: Option<ReceiptRequest>,
-    /// Error message (if failed)
-    pub error: Option<String>,
+/// Why a receipt generator refused a request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, uniffi::Enum)]
+pub enum DeclineCode {
+    /// Amount below what this payee accepts.
+    AmountTooLow,
+    /// Amount above what this payee accepts.
+    AmountTooHigh,
+    /// Payment method not offered.
+    UnsupportedMethod,
+    /// Currency not accepted.
+    UnsupportedCurrency,
+    /// Temporarily unable to issue receipts (node offline, no liquidity).
+    Unavailable,
+    /// Too many requests from this payer.
+    RateLimited,
+    /// Any other reason; explain in the message.
+    Other,
+}
+
+impl DeclineCode {
+    /// The code sent to the payer in the `Error` message.
+    pub fn wire_code(&self) -> &'static str {
+        match self {
+            Self::AmountTooLow => "AMOUNT_TOO_LOW",
+            Self::AmountTooHigh => "AMOUNT_TOO_HIGH",
+            Self::UnsupportedMethod => "UNSUPPORTED_METHOD",
+            Self::UnsupportedCurrency => "UNSUPPORTED_CURRENCY",
+            Self::Unavailable => "UNAVAILABLE",
+            Self::RateLimited => "RATE_LIMITED",
+            Self::Other => "GENERATION_FAILED",
+        }
+    }
+}
+
+/// A structured refusal to issue a receipt.
+#[derive(Clone, Debug, PartialEq, Eq, uniffi::Record)]
+pub struct ReceiptDecline {
+    /// Why the request was refused.
+    pub code: DeclineCode,
+    /// Explanation shown to the payer.
+    pub message: String,
+    /// Seconds after which the payer may try again, for temporary refusals.
+    pub retry_after_secs: Option<u64>,
+}
+
+impl ReceiptDecline {
+    /// The message sent to the payer, with the retry hint if any.
+    fn wire_message(&self) -> String {
+        match self.retry_after_secs {
+            Some(secs) => format!("{} (retry after {}s)", self.message, secs),
+            None => self.message.clone(),
+        }
+    }
+}
+
+/// What a receipt generator returns.
+#[derive(Clone, Debug, uniffi::Enum)]
+pub enum ReceiptGenerationResult {
+    /// The finished receipt.
+    ///
+    /// `receipt_id`, `payer`, `payee`, `method_id`, `amount` and `currency`
+    /// must be those of the request.
+    Issued { receipt: ReceiptRequest },
+    /// Metadata to add to the request, e.g. `{"invoice":"lnbc1..."}`.
+    ///
+    /// Keys are merged into the request's metadata, replacing keys of the
+    /// same name; the rest of the receipt is the request's.
+    Metadata { metadata_json: String },
+    /// The request was refused.
+    Declined { decline: ReceiptDecline },
 }
 
 impl ReceiptGenerationResult {
     /// Create a successful result.
     pub fn ok(receipt: ReceiptRequest) -> Self {
-        Self {
-            success: true,
-            receipt: Some(receipt),
-            error: None,
-        }
+        Self::Issued { receipt }
     }
 
     /// Create a failed result.
     pub fn err(message: String) -> Self {
-        Self {
-            success: false,
-            receipt: None,
-            error: Some(message),
+        Self::Declined {
+            decline: ReceiptDecline {
+                code: DeclineCode::Other,
+                message,
+                retry_after_secs: None,
+            },
         }
     }
 }
 
+/// Turn a generator's result for `request` into the receipt to store.
+///
+/// Generators run in app code, so the result is checked here before it can
+/// reach the store: the receipt must describe the same payment as the
+/// request, and its metadata must be a JSON object within
+/// `MAX_DOCUMENT_BYTES`.
+///
+/// Returns the receipt, or the decline to send to the payer.
+fn finalize_generated_receipt(
+    request: &ReceiptRequest,
+    result: ReceiptGenerationResult,
+) -> std::result::Result<ReceiptRequest, ReceiptDecline> {
+    let invalid = |message: String| ReceiptDecline {
+        code: DeclineCode::Other,
+        message: format!("Generator returned an invalid receipt: {}", message),
+        retry_after_secs: None,
+    };
+
+    let receipt = match result {
+        ReceiptGenerationResult::Issued { receipt } => receipt,
+        ReceiptGenerationResult::Metadata { metadata_json } => {
+            let mut metadata: serde_json::Map<String, serde_json::Value> =
+                serde_json::from_str(&request.metadata_json).unwrap_or_default();
+            let additions: serde_json::Map<String, serde_json::Value> =
+                serde_json::from_str(&metadata_json)
+                    .map_err(|_| invalid("metadata must be a JSON object".to_string()))?;
+            metadata.extend(additions);
+            ReceiptRequest {
+                metadata_json: serde_json::Value::Object(metadata).to_string(),
+                ..request.clone()
+            }
+        }
+        ReceiptGenerationResult::Declined { decline } => return Err(decline),
+    };
+
+    let fields = [
+        ("receipt_id", &receipt.receipt_id, &request.receipt_id),
+        ("payer", &receipt.payer, &request.payer),
+        ("payee", &receipt.payee, &request.payee),
+        ("method_id", &receipt.method_id, &request.method_id),
+    ];
+    for (name, got, expected) in fields {
+        if got != expected {
+            return Err(invalid(format!("{} changed", name)));
+        }
+    }
+    if receipt.amount != request.amount {
+        return Err(invalid("amount changed".to_string()));
+    }
+    if receipt.currency != request.currency {
+        return Err(invalid("currency changed".to_string()));
+    }
+    if receipt.metadata_json.len() > MAX_DOCUMENT_BYTES {
+        return Err(invalid("metadata too large".to_string()));
+    }
+    if !matches!(
+        serde_json::from_str::<serde_json::Value>(&receipt.metadata_json),
+        Ok(serde_json::Value::Object(_))
+    ) {
+        return Err(invalid("metadata must be a JSON object".to_string()));
+    }
+    Ok(receipt)
+}
+
 /// Callback interface for mobile receipt generation.
 ///
 /// Mobile apps implement this to generate receipts (e.g., create Lightning invoices).
 /// When a payment request is received, this callback is invoked to produce
-/// the final receipt with payment endpoint.
+/// the final receipt with payment endpoint. The result is validated before
+/// it is stored or sent; run [`check_receipt_generator`] in the app's tests.
 ///
 /// # Example (Swift)
 ///
 /// ```swift
 /// class MyReceiptGenerator: ReceiptGeneratorCallback {
 ///     func generateReceipt(request: ReceiptRequest) -> ReceiptGenerationResult {
-///         // Create Lightning invoice
+///         guard request.methodId == "lightning" else {
+///             return .declined(decline: ReceiptDecline(
+///                 code: .unsupportedMethod, message: "Lightning only", retryAfterSecs: nil))
+///         }
+///         // Create Lightning invoice and add it to the request's metadata
 ///         let invoice = createInvoice(amount: request.amount)
-///         
-///         // Update receipt with invoice in metadata
-///         var receipt = request
-///         receipt.metadataJson = "{\"invoice\":\"\(invoice)\"}"
-///         
-///         return ReceiptGenerationResult.ok(receipt: receipt)
+///         return .metadata(metadataJson: "{\"invoice\":\"\(invoice)\"}")
 ///     }
 /// }
 /// ```
@@ -708,14 +959,220 @@ pub trait ReceiptGeneratorCallback: Send + Sync {
     ///
     /// # Arguments
     ///
-    /// * `request` - The provisional receipt request from the payer
+    /// * `request` - The provisional receipt request from the payer; its
+    ///   `receipt_id` identifies the payment intent
     ///
     /// # Returns
     ///
-    /// A `ReceiptGenerationResult` with either the finalized receipt or an error.
+    /// The issued receipt, metadata to add to the request, or a decline.
     fn generate_receipt(&self, request: ReceiptRequest) -> ReceiptGenerationResult;
 }
 
+// ============================================================================
+// Generator Conformance
+// ============================================================================
+
+/// Outcome of one conformance check.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct ConformanceCheck {
+    /// Check name, e.g. `issues_valid_receipt`.
+    pub name: String,
+    /// Whether the generator passed.
+    pub passed: bool,
+    /// What went wrong, if it failed.
+    pub detail: Option<String>,
+}
+
+/// Outcome of [`check_receipt_generator`].
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct ConformanceReport {
+    /// Whether every check passed.
+    pub passed: bool,
+    /// Each check in order.
+    pub checks: Vec<ConformanceCheck>,
+}
+
+/// Method ID no real generator supports.
+const CONFORMANCE_UNSUPPORTED_METHOD: &str = "paykit-conformance-unsupported";
+
+/// Run a receipt generator through the checks the manager relies on.
+///
+/// Call from the app's unit tests (XCTest, JUnit) with the generator the app
+/// ships, configured to accept `method_id` payments of `amount`:
+///
+/// - `issues_valid_receipt`: a normal request gets a receipt that passes
+///   validation (same payment, JSON object metadata);
+/// - `keeps_request_metadata`: metadata sent by the payer is not dropped;
+/// - `declines_unsupported_method`: an unknown method is declined with
+///   `UnsupportedMethod` and a message;
+/// - `decline_retry_hint`: any `retry_after_secs` is at most a day.
+#[uniffi::export]
+pub fn check_receipt_generator(
+    generator: Box<dyn ReceiptGeneratorCallback>,
+    method_id: String,
+    amount: Option<String>,
+    currency: Option<String>,
+) -> ConformanceReport {
+    let request = |receipt_id: &str, method_id: &str| ReceiptRequest {
+        receipt_id: receipt_id.to_string(),
+        payer: "conformance-payer".to_string(),
+        payee: "conformance-payee".to_string(),
+        method_id: method_id.to_string(),
+        amount: amount.clone(),
+        currency: currency.clone(),
+        metadata_json: r#"{"order_id":"conformance-order"}"#.to_string(),
+    };
+    let mut checks = Vec::new();
+    let mut check = |name: &str, outcome: std::result::Result<(), String>| {
+        checks.push(ConformanceCheck {
+            name: name.to_string(),
+            passed: outcome.is_ok(),
+            detail: outcome.err(),
+        });
+    };
+
+    let valid = request("conformance-valid", &method_id);
+    let issued = finalize_generated_receipt(&valid, generator.generate_receipt(valid.clone()));
+    check(
+        "issues_valid_receipt",
+        issued
+            .as_ref()
+            .map(|_| ())
+            .map_err(|decline| decline.message.clone()),
+    );
+    check(
+        "keeps_request_metadata",
+        match &issued {
+            Ok(receipt) => serde_json::from_str::<serde_json::Value>(&receipt.metadata_json)
+                .ok()
+                .filter(|m| m["order_id"] == "conformance-order")
+                .map(|_| ())
+                .ok_or_else(|| "order_id from the request is missing".to_string()),
+            Err(_) => Err("no receipt issued".to_string()),
+        },
+    );
+
+    let unsupported = request("conformance-unsupported", CONFORMANCE_UNSUPPORTED_METHOD);
+    let declined = finalize_generated_receipt(
+        &unsupported,
+        generator.generate_receipt(unsupported.clone()),
+    );
+    check(
+        "declines_unsupported_method",
+        match &declined {
+            Err(d) if d.code == DeclineCode::UnsupportedMethod && !d.message.is_empty() => Ok(()),
+            Err(d) => Err(format!("declined with {:?}: {}", d.code, d.message)),
+            Ok(_) => Err("issued a receipt for an unknown method".to_string()),
+        },
+    );
+    check(
+        "decline_retry_hint",
+        match &declined {
+            Err(ReceiptDecline {
+                retry_after_secs: Some(secs),
+                ..
+            }) if *secs > 86_400 => Err(format!("retry_after_secs of {} exceeds a day", secs)),
+            _ => Ok(()),
+        },
+    );
+
+    ConformanceReport {
+        passed: checks.iter().all(|c| c.passed),
+        checks,
+    }
+}
+
+// ============================================================================
+// Flow Tracking
+// ============================================================================
+
+/// FFI-safe state of a receipt exchange, for progress UIs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, uniffi::Enum)]
+pub enum ReceiptFlowState {
+    /// Nothing sent or received yet.
+    Idle,
+    /// Payer: request sent, waiting for the confirmation.
+    RequestSent,
+    /// Payee: request received, receipt being generated.
+    RequestReceived,
+    /// Payee: confirmation sent, waiting for the Ack.
+    ConfirmSent,
+    /// Exchange finished.
+    Completed,
+    /// The peer refused, or answered with the wrong receipt.
+    Failed,
+    /// Every attempt went unanswered.
+    TimedOut,
+}
+
+impl From<FlowState> for ReceiptFlowState {
+    fn from(state: FlowState) -> Self {
+        match state {
+            FlowState::Idle => Self::Idle,
+            FlowState::RequestSent => Self::RequestSent,
+            FlowState::RequestReceived => Self::RequestReceived,
+            FlowState::ConfirmSent => Self::ConfirmSent,
+            FlowState::Completed => Self::Completed,
+            FlowState::Failed => Self::Failed,
+            FlowState::TimedOut => Self::TimedOut,
+        }
+    }
+}
+
+/// FFI-safe progress of one receipt exchange.
+#[derive(Clone, Debug, PartialEq, Eq, uniffi::Record)]
+pub struct ReceiptFlowStatus {
+    /// Receipt being exchanged.
+    pub receipt_id: String,
+    /// Peer on the other side.
+    pub peer: String,
+    /// Current state.
+    pub state: ReceiptFlowState,
+    /// Sends of the current message so far.
+    pub attempts: u32,
+}
+
+/// A message to send again because its deadline passed.
+#[derive(Clone, Debug, PartialEq, Eq, uniffi::Record)]
+pub struct Retransmission {
+    /// Receipt being exchanged.
+    pub receipt_id: String,
+    /// Peer to send to.
+    pub peer: String,
+    /// JSON message to send over the Noise channel.
+    pub message_json: String,
+}
+
+/// A tracked exchange and the last message we sent in it.
+struct TrackedFlow {
+    flow: ReceiptFlow,
+    peer: String,
+    message_json: String,
+    started: Instant,
+    last_sent: Instant,
+}
+
+impl crate::memory_ffi::HeapSize for TrackedFlow {
+    fn heap_size(&self) -> usize {
+        self.flow.receipt_id.capacity() + self.peer.capacity() + self.message_json.capacity()
+    }
+}
+
+impl TrackedFlow {
+    fn status(&self) -> ReceiptFlowStatus {
+        ReceiptFlowStatus {
+            receipt_id: self.flow.receipt_id.clone(),
+            peer: self.peer.clone(),
+            state: self.flow.state().into(),
+            attempts: self.flow.attempts(),
+        }
+    }
+}
+
+fn flow_error(e: paykit_interactive::InteractiveError) -> PaykitMobileError {
+    PaykitMobileError::Validation { msg: e.to_string() }
+}
+
 // ============================================================================
 // Interactive Manager FFI
 // ============================================================================
@@ -740,6 +1197,116 @@ pub struct PaykitInteractiveManagerFFI {
     generator: std::sync::RwLock<Option<Box<dyn ReceiptGeneratorCallback>>>,
     /// Message builder for serialization
     message_builder: Arc<PaykitMessageBuilder>,
+    /// Compliance screener (provided by mobile app)
+    screener: std::sync::RwLock<Option<crate::compliance_ffi::ComplianceScreenerBridge>>,
+    /// Deadlines and retransmission limits
+    flow_config: std::sync::RwLock<FlowConfig>,
+    /// Receipt exchanges in progress, by receipt ID
+    flows: std::sync::Mutex<HashMap<String, TrackedFlow>>,
+}
+
+impl crate::memory_ffi::MemoryReporter for PaykitInteractiveManagerFFI {
+    fn memory_usage(&self) -> Vec<crate::memory_ffi::SubsystemMemoryFFI> {
+        let flows = self.flows.lock().unwrap_or_else(|e| e.into_inner());
+        vec![crate::memory_ffi::map_usage(
+            "interactive.receipt_flows",
+            &flows,
+        )]
+    }
+}
+
+impl PaykitInteractiveManagerFFI {
+    /// Screen a payment, if a screener is set.
+    fn screen(
+        &self,
+        direction: paykit_lib::policy::ScreeningDirection,
+        request: &ReceiptRequest,
+    ) -> Result<Option<paykit_lib::policy::ScreeningResult>> {
+        let guard = self
+            .screener
+            .read()
+            .map_err(|_| PaykitMobileError::Internal {
+                msg: "Screener lock poisoned".to_string(),
+            })?;
+        let Some(screener) = guard.as_ref() else {
+            return Ok(None);
+        };
+
+        let result = screener.screen_blocking(&paykit_lib::policy::ScreeningRequest {
+            direction,
+            payer: request.payer.clone(),
+            payee: request.payee.clone(),
+            method_id: request.method_id.clone(),
+            amount: request.amount.clone(),
+            currency: request.currency.clone(),
+            metadata: serde_json::from_str(&request.metadata_json).unwrap_or(serde_json::json!({})),
+        })?;
+        Ok(Some(result))
+    }
+}
+
+impl PaykitInteractiveManagerFFI {
+    fn flows(&self) -> std::sync::MutexGuard<'_, HashMap<String, TrackedFlow>> {
+        self.flows.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn flow_config(&self) -> FlowConfig {
+        *self.flow_config.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Track a new exchange in which we just sent `message_json`.
+    fn start_flow(
+        &self,
+        receipt_id: &str,
+        peer: String,
+        events: &[FlowEvent],
+        message_json: &str,
+    ) -> Result<()> {
+        let mut flow = ReceiptFlow::new(receipt_id);
+        for event in events {
+            flow.apply(*event).map_err(flow_error)?;
+        }
+        self.flows().insert(
+            receipt_id.to_string(),
+            TrackedFlow {
+                flow,
+                peer,
+                message_json: message_json.to_string(),
+                started: Instant::now(),
+                last_sent: Instant::now(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Apply `event` to the exchange of `receipt_id`, if it is tracked.
+    fn advance(&self, receipt_id: &str, event: FlowEvent) -> Result<Option<FlowState>> {
+        match self.flows().get_mut(receipt_id) {
+            Some(tracked) => tracked.flow.apply(event).map(Some).map_err(flow_error),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Whether `request` repeats the request `existing` was issued for.
+fn is_same_request(existing: &ReceiptRequest, request: &ReceiptRequest) -> bool {
+    existing.payer == request.payer
+        && existing.payee == request.payee
+        && existing.method_id == request.method_id
+        && existing.amount == request.amount
+        && existing.currency == request.currency
+}
+
+/// Record a screening result in a receipt's metadata.
+fn record_screening(
+    result: &paykit_lib::policy::ScreeningResult,
+    receipt: &mut ReceiptRequest,
+    screened_at: i64,
+) {
+    let mut metadata: serde_json::Value =
+        serde_json::from_str(&receipt.metadata_json).unwrap_or(serde_json::json!({}));
+    result.record_in(&mut metadata, screened_at);
+    receipt.metadata_json = metadata.to_string();
 }
 
 #[uniffi::export]
@@ -757,6 +1324,9 @@ impl PaykitInteractiveManagerFFI {
             store,
             generator: std::sync::RwLock::new(None),
             message_builder: PaykitMessageBuilder::new(),
+            screener: std::sync::RwLock::new(None),
+            flow_config: std::sync::RwLock::new(FlowConfig::default()),
+            flows: std::sync::Mutex::new(HashMap::new()),
         })
     }
 
@@ -782,6 +1352,31 @@ impl PaykitInteractiveManagerFFI {
         Ok(())
     }
 
+    /// Set the compliance screener callback.
+    ///
+    /// Once set, outgoing payment requests and incoming receipt requests are
+    /// screened. Blocked payments are refused and every result is recorded
+    /// in the receipt metadata.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the internal lock is poisoned.
+    pub fn set_compliance_screener(
+        &self,
+        screener: Box<dyn crate::compliance_ffi::ComplianceScreenerCallback>,
+    ) -> Result<()> {
+        let mut guard = self
+            .screener
+            .write()
+            .map_err(|_| PaykitMobileError::Internal {
+                msg: "Screener lock poisoned".to_string(),
+            })?;
+        *guard = Some(crate::compliance_ffi::ComplianceScreenerBridge::new(
+            Arc::from(screener),
+        ));
+        Ok(())
+    }
+
     /// Handle an incoming message from a peer.
     ///
     /// This processes a JSON message received over a Noise channel and returns
@@ -834,6 +1429,33 @@ impl PaykitInteractiveManagerFFI {
                     return Ok(Some(response));
                 }
 
+                // A retransmitted request gets the receipt we already issued
+                if let Some(existing) = self.store.get_receipt(request.receipt_id.clone())? {
+                    if is_same_request(&existing, &request) {
+                        let response = self.message_builder.create_receipt_confirm(existing)?;
+                        if let Some(tracked) = self.flows().get_mut(&request.receipt_id) {
+                            if tracked.flow.state() == FlowState::ConfirmSent {
+                                tracked
+                                    .flow
+                                    .apply(FlowEvent::ReceiveRequest)
+                                    .map_err(flow_error)?;
+                            }
+                        }
+                        return Ok(Some(response));
+                    }
+                }
+
+                // Screen the payer before issuing a receipt
+                let screened_at = current_timestamp();
+                let screening =
+                    self.screen(paykit_lib::policy::ScreeningDirection::Incoming, &request)?;
+                if let Some(paykit_lib::policy::ScreeningResult::Block { reason }) = &screening {
+                    let response = self
+                        .message_builder
+                        .create_error("COMPLIANCE_BLOCKED".to_string(), reason.clone())?;
+                    return Ok(Some(response));
+                }
+
                 // Get the generator
                 let generator_guard =
                     self.generator
@@ -853,40 +1475,64 @@ impl PaykitInteractiveManagerFFI {
                     }
                 };
 
-                // Generate receipt using the callback
+                // Generate receipt using the callback, checking the result
                 let result = generator.generate_receipt(request.clone());
-                if result.success {
-                    if let Some(confirmed_receipt) = result.receipt {
+                match finalize_generated_receipt(&request, result) {
+                    Ok(mut confirmed_receipt) => {
+                        if let Some(screening) = &screening {
+                            record_screening(screening, &mut confirmed_receipt, screened_at);
+                        }
                         // Save locally
                         self.store.save_receipt(confirmed_receipt.clone())?;
-                        // Respond with confirmation
+                        // Respond with confirmation, resent until the payer acks
+                        let receipt_id = confirmed_receipt.receipt_id.clone();
                         let response = self
                             .message_builder
                             .create_receipt_confirm(confirmed_receipt)?;
+                        self.start_flow(
+                            &receipt_id,
+                            peer_pubkey,
+                            &[FlowEvent::ReceiveRequest, FlowEvent::SendConfirm],
+                            &response,
+                        )?;
                         Ok(Some(response))
-                    } else {
+                    }
+                    Err(decline) => {
                         let response = self.message_builder.create_error(
-                            "GENERATION_FAILED".to_string(),
-                            "No receipt returned".to_string(),
+                            decline.code.wire_code().to_string(),
+                            decline.wire_message(),
                         )?;
                         Ok(Some(response))
                     }
-                } else {
-                    let err_msg = result.error.unwrap_or_else(|| "Unknown error".to_string());
-                    let response = self
-                        .message_builder
-                        .create_error("GENERATION_FAILED".to_string(), err_msg)?;
-                    Ok(Some(response))
                 }
             }
             ParsedMessage::ConfirmReceipt { receipt } => {
-                // Handle confirmation (late arrival or unsolicited)
+                // Handle confirmation (late arrival or unsolicited); a repeat
+                // of one we accepted means our Ack was lost
+                if let Err(e) = self.advance(&receipt.receipt_id, FlowEvent::ReceiveConfirm) {
+                    let response = self
+                        .message_builder
+                        .create_error("UNEXPECTED_CONFIRM".to_string(), e.to_string())?;
+                    return Ok(Some(response));
+                }
                 self.store.save_receipt(receipt)?;
                 let response = self.message_builder.create_ack()?;
                 Ok(Some(response))
             }
             ParsedMessage::Ack => {
-                // Nothing to do
+                // Acks carry no receipt ID; Noise channels are ordered, so it
+                // answers the peer's oldest unacknowledged confirmation
+                let mut flows = self.flows();
+                let oldest = flows
+                    .values_mut()
+                    .filter(|t| t.peer == peer_pubkey && t.flow.state() == FlowState::ConfirmSent)
+                    .min_by_key(|t| t.started);
+                if let Some(tracked) = oldest {
+                    tracked
+                        .flow
+                        .apply(FlowEvent::ReceiveAck)
+                        .map_err(flow_error)?;
+                }
                 Ok(None)
             }
             ParsedMessage::Error { error } => {
@@ -929,7 +1575,7 @@ impl PaykitInteractiveManagerFFI {
         metadata_json: Option<String>,
     ) -> Result<String> {
         let receipt_id = generate_receipt_id();
-        let request = ReceiptRequest {
+        let mut request = ReceiptRequest {
             receipt_id,
             payer,
             payee,
@@ -939,11 +1585,25 @@ impl PaykitInteractiveManagerFFI {
             metadata_json: metadata_json.unwrap_or_else(|| "{}".to_string()),
         };
 
+        match self.screen(paykit_lib::policy::ScreeningDirection::Outgoing, &request)? {
+            Some(paykit_lib::policy::ScreeningResult::Block { reason }) => {
+                return Err(PaykitMobileError::Validation {
+                    msg: format!("Payment blocked by compliance screening: {}", reason),
+                });
+            }
+            Some(screening) => record_screening(&screening, &mut request, current_timestamp()),
+            None => {}
+        }
+
         // Save provisional receipt
         self.store.save_receipt(request.clone())?;
 
-        // Create message
-        self.message_builder.create_receipt_request(request)
+        // Create message, resent by `due_retransmissions` until confirmed
+        let receipt_id = request.receipt_id.clone();
+        let payee = request.payee.clone();
+        let message = self.message_builder.create_receipt_request(request)?;
+        self.start_flow(&receipt_id, payee, &[FlowEvent::SendRequest], &message)?;
+        Ok(message)
     }
 
     /// Handle a payment confirmation response.
@@ -956,6 +1616,9 @@ impl PaykitInteractiveManagerFFI {
     /// * `response_json` - The JSON response from the Noise channel
     /// * `original_receipt_id` - The receipt ID from your original request
     ///
+    /// On success, send `PaykitMessageBuilder::create_ack` back so the payee
+    /// stops retransmitting its confirmation.
+    ///
     /// # Returns
     ///
     /// The confirmed receipt if successful, or an error.
@@ -970,6 +1633,7 @@ impl PaykitInteractiveManagerFFI {
             ParsedMessage::ConfirmReceipt { receipt } => {
                 // Validate receipt matches request ID
                 if receipt.receipt_id != original_receipt_id {
+                    self.advance(&original_receipt_id, FlowEvent::Fail)?;
                     return Err(PaykitMobileError::Validation {
                         msg: format!(
                             "Receipt ID mismatch: expected {}, got {}",
@@ -979,16 +1643,84 @@ impl PaykitInteractiveManagerFFI {
                 }
 
                 // Save confirmed receipt
+                self.advance(&original_receipt_id, FlowEvent::ReceiveConfirm)?;
                 self.store.save_receipt(receipt.clone())?;
                 Ok(receipt)
             }
-            ParsedMessage::Error { error } => Err(PaykitMobileError::Transport {
-                msg: format!("Payment rejected: {} - {}", error.code, error.message),
-            }),
-            _ => Err(PaykitMobileError::Validation {
-                msg: "Unexpected response type".to_string(),
-            }),
+            ParsedMessage::Error { error } => {
+                self.advance(&original_receipt_id, FlowEvent::Fail)?;
+                Err(PaykitMobileError::Transport {
+                    msg: format!("Payment rejected: {} - {}", error.code, error.message),
+                })
+            }
+            _ => {
+                self.advance(&original_receipt_id, FlowEvent::Fail)?;
+                Err(PaykitMobileError::Validation {
+                    msg: "Unexpected response type".to_string(),
+                })
+            }
+        }
+    }
+
+    /// Set how long to wait for each reply and how many times to send.
+    ///
+    /// Defaults to 10 seconds and 3 sends.
+    pub fn set_flow_timeouts(&self, exchange_timeout_ms: u64, max_attempts: u32) {
+        let config = FlowConfig::default()
+            .with_exchange_timeout(Duration::from_millis(exchange_timeout_ms))
+            .with_max_attempts(max_attempts);
+        *self.flow_config.write().unwrap_or_else(|e| e.into_inner()) = config;
+    }
+
+    /// Progress of the receipt exchange for `receipt_id`, if tracked.
+    pub fn get_flow_state(&self, receipt_id: String) -> Option<ReceiptFlowStatus> {
+        self.flows().get(&receipt_id).map(TrackedFlow::status)
+    }
+
+    /// Progress of every tracked receipt exchange.
+    pub fn list_flows(&self) -> Vec<ReceiptFlowStatus> {
+        self.flows().values().map(TrackedFlow::status).collect()
+    }
+
+    /// Messages whose deadline passed without a reply.
+    ///
+    /// Call this periodically (e.g. every second while a payment screen is
+    /// open) and send each message to its peer again. Exchanges that have
+    /// used all their attempts move to `TimedOut` instead.
+    pub fn due_retransmissions(&self) -> Result<Vec<Retransmission>> {
+        let config = self.flow_config();
+        let now = Instant::now();
+        let mut due = Vec::new();
+        for tracked in self.flows().values_mut() {
+            let waiting = matches!(
+                tracked.flow.state(),
+                FlowState::RequestSent | FlowState::ConfirmSent
+            );
+            if !waiting || now.duration_since(tracked.last_sent) < config.exchange_timeout {
+                continue;
+            }
+            if !tracked.flow.can_retransmit(&config) {
+                tracked.flow.apply(FlowEvent::Expire).map_err(flow_error)?;
+                continue;
+            }
+            tracked
+                .flow
+                .apply(FlowEvent::Retransmit)
+                .map_err(flow_error)?;
+            tracked.last_sent = now;
+            due.push(Retransmission {
+                receipt_id: tracked.flow.receipt_id.clone(),
+                peer: tracked.peer.clone(),
+                message_json: tracked.message_json.clone(),
+            });
         }
+        Ok(due)
+    }
+
+    /// Stop tracking exchanges that are over.
+    pub fn clear_finished_flows(&self) {
+        self.flows()
+            .retain(|_, tracked| !tracked.flow.state().is_terminal());
     }
 
     /// Create a private endpoint offer message.
@@ -1136,6 +1868,39 @@ mod tests {
         assert_eq!(msg_type, PaykitMessageType::Ack);
     }
 
+    #[test]
+    fn test_parse_message_bytes_keeps_metadata() {
+        let builder = PaykitMessageBuilder::new();
+
+        let request = builder
+            .create_receipt_request(ReceiptRequest {
+                receipt_id: "r1".to_string(),
+                payer: "payer".to_string(),
+                payee: "payee".to_string(),
+                method_id: "lightning".to_string(),
+                amount: Some("1000".to_string()),
+                currency: None,
+                metadata_json: r#"{"order":{"lines":[1,2]}}"#.to_string(),
+            })
+            .unwrap();
+
+        match builder.parse_message_bytes(request.into_bytes()).unwrap() {
+            ParsedMessage::RequestReceipt { request } => {
+                assert_eq!(request.receipt_id, "r1");
+                assert_eq!(request.amount.as_deref(), Some("1000"));
+                assert!(request.currency.is_none());
+                assert_eq!(request.metadata_json, r#"{"order":{"lines":[1,2]}}"#);
+            }
+            _ => panic!("Expected RequestReceipt"),
+        }
+
+        let oversized = format!(
+            r#"{{"type":"Ack","pad":"{}"}}"#,
+            "x".repeat(paykit_lib::protocol::MAX_DOCUMENT_BYTES)
+        );
+        assert!(builder.parse_message(oversized).is_err());
+    }
+
     #[test]
     fn test_receipt_store() {
         let store = ReceiptStore::new();
@@ -1235,6 +2000,60 @@ mod tests {
         assert_eq!(store.list_receipts().unwrap().len(), 2);
     }
 
+    #[test]
+    fn test_receipt_store_query() {
+        let store = ReceiptStore::new();
+        for i in 0..5u32 {
+            store
+                .save_receipt(ReceiptRequest {
+                    receipt_id: format!("r{}", i),
+                    payer: "payer".to_string(),
+                    payee: "payee".to_string(),
+                    method_id: if i % 2 == 0 { "lightning" } else { "onchain" }.to_string(),
+                    amount: Some((i * 1000).to_string()),
+                    currency: Some("SAT".to_string()),
+                    metadata_json: format!(r#"{{"order":"order-{}"}}"#, i),
+                })
+                .unwrap();
+        }
+
+        let first = store
+            .query_receipts(ReceiptSearchQuery {
+                method_id: Some("lightning".to_string()),
+                limit: 2,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(first.receipts.len(), 2);
+        assert!(first.next_cursor.is_some());
+
+        let second = store
+            .query_receipts(ReceiptSearchQuery {
+                method_id: Some("lightning".to_string()),
+                limit: 2,
+                cursor: first.next_cursor,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(second.receipts.len(), 1);
+        assert!(second.next_cursor.is_none());
+
+        let by_text = store
+            .query_receipts(ReceiptSearchQuery {
+                text: Some("order-3".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(by_text.receipts.len(), 1);
+        assert_eq!(by_text.receipts[0].receipt_id, "r3");
+
+        let bad_cursor = store.query_receipts(ReceiptSearchQuery {
+            cursor: Some("garbage".to_string()),
+            ..Default::default()
+        });
+        assert!(bad_cursor.is_err());
+    }
+
     // ========================================================================
     // Interactive Manager Tests
     // ========================================================================
@@ -1463,6 +2282,88 @@ mod tests {
         assert_eq!(receipts.len(), 1);
     }
 
+    /// Test screener that blocks one payer/payee key.
+    struct DenylistScreener(&'static str);
+
+    impl crate::compliance_ffi::ComplianceScreenerCallback for DenylistScreener {
+        fn screen(
+            &self,
+            request: crate::compliance_ffi::ScreeningRequestFFI,
+        ) -> std::result::Result<crate::compliance_ffi::ScreeningResultFFI, PaykitMobileError>
+        {
+            if request.payer == self.0 || request.payee == self.0 {
+                Ok(crate::compliance_ffi::ScreeningResultFFI::Block {
+                    reason: "denylist".to_string(),
+                })
+            } else {
+                Ok(crate::compliance_ffi::ScreeningResultFFI::Review {
+                    reason: "new counterparty".to_string(),
+                })
+            }
+        }
+    }
+
+    #[test]
+    fn test_manager_compliance_screening() {
+        let manager = create_test_manager(Box::new(EchoReceiptGenerator));
+        manager
+            .set_compliance_screener(Box::new(DenylistScreener("sanctioned")))
+            .unwrap();
+
+        // Outgoing payment to a blocked payee is refused
+        let err = manager.create_payment_request(
+            "my_pubkey".to_string(),
+            "sanctioned".to_string(),
+            "lightning".to_string(),
+            Some("1000".to_string()),
+            None,
+            None,
+        );
+        assert!(matches!(err, Err(PaykitMobileError::Validation { .. })));
+        assert!(manager.list_receipts().unwrap().is_empty());
+
+        // Review results are recorded in the provisional receipt
+        manager
+            .create_payment_request(
+                "my_pubkey".to_string(),
+                "payee_pubkey".to_string(),
+                "lightning".to_string(),
+                Some("1000".to_string()),
+                None,
+                None,
+            )
+            .unwrap();
+        let saved = &manager.list_receipts().unwrap()[0];
+        assert!(saved.metadata_json.contains("\"review\""));
+
+        // Incoming request from a blocked payer is answered with an error
+        let builder = PaykitMessageBuilder::new();
+        let request_msg = builder
+            .create_receipt_request(ReceiptRequest {
+                receipt_id: "blocked_receipt".to_string(),
+                payer: "sanctioned".to_string(),
+                payee: "my_pubkey".to_string(),
+                method_id: "lightning".to_string(),
+                amount: Some("1000".to_string()),
+                currency: Some("SAT".to_string()),
+                metadata_json: "{}".to_string(),
+            })
+            .unwrap();
+        let response = manager
+            .handle_message(
+                request_msg,
+                "sanctioned".to_string(),
+                "my_pubkey".to_string(),
+            )
+            .unwrap()
+            .unwrap();
+        assert!(response.contains("COMPLIANCE_BLOCKED"));
+        assert!(manager
+            .get_receipt("blocked_receipt".to_string())
+            .unwrap()
+            .is_none());
+    }
+
     #[test]
     fn test_manager_handle_payment_response() {
         let manager = create_test_manager(Box::new(EchoReceiptGenerator));
@@ -1506,4 +2407,277 @@ mod tests {
         // Ack should not produce a response
         assert!(response.is_none());
     }
+
+    #[test]
+    fn test_manager_flow_retransmission() {
+        let payer = create_test_manager(Box::new(EchoReceiptGenerator));
+        let payee = create_test_manager(Box::new(EchoReceiptGenerator));
+        payer.set_flow_timeouts(0, 2);
+        payee.set_flow_timeouts(0, 2);
+
+        let request = payer
+            .create_payment_request(
+                "payer".to_string(),
+                "payee".to_string(),
+                "lightning".to_string(),
+                Some("1000".to_string()),
+                Some("SAT".to_string()),
+                None,
+            )
+            .unwrap();
+        let receipt_id = payer.list_flows()[0].receipt_id.clone();
+        assert_eq!(
+            payer.get_flow_state(receipt_id.clone()).unwrap().state,
+            ReceiptFlowState::RequestSent
+        );
+
+        // The request is lost and sent again
+        let due = payer.due_retransmissions().unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].message_json, request);
+        assert_eq!(due[0].peer, "payee");
+
+        // Both copies arrive; the second gets the same receipt
+        let confirm = payee
+            .handle_message(request.clone(), "payer".to_string(), "payee".to_string())
+            .unwrap()
+            .unwrap();
+        let repeat = payee
+            .handle_message(request, "payer".to_string(), "payee".to_string())
+            .unwrap()
+            .unwrap();
+        assert_eq!(confirm, repeat);
+        assert_eq!(
+            payee.get_flow_state(receipt_id.clone()).unwrap().state,
+            ReceiptFlowState::ConfirmSent
+        );
+
+        payer
+            .handle_payment_response(confirm.clone(), receipt_id.clone())
+            .unwrap();
+        assert_eq!(
+            payer.get_flow_state(receipt_id.clone()).unwrap().state,
+            ReceiptFlowState::Completed
+        );
+        // A repeated confirmation is acknowledged again
+        let ack = payer
+            .handle_message(confirm, "payee".to_string(), "payer".to_string())
+            .unwrap()
+            .unwrap();
+        payee
+            .handle_message(ack, "payer".to_string(), "payee".to_string())
+            .unwrap();
+        assert_eq!(
+            payee.get_flow_state(receipt_id.clone()).unwrap().state,
+            ReceiptFlowState::Completed
+        );
+
+        payer.clear_finished_flows();
+        assert!(payer.get_flow_state(receipt_id).is_none());
+    }
+
+    #[test]
+    fn test_poisoned_store_returns_errors() {
+        let store = ReceiptStore::new();
+        crate::test_support::poison_rwlock(&store.receipts);
+
+        let receipt = ReceiptRequest {
+            receipt_id: "poisoned".to_string(),
+            payer: "payer".to_string(),
+            payee: "payee".to_string(),
+            method_id: "lightning".to_string(),
+            amount: None,
+            currency: None,
+            metadata_json: "{}".to_string(),
+        };
+        assert!(matches!(
+            store.save_receipt(receipt),
+            Err(PaykitMobileError::Internal { .. })
+        ));
+        assert!(matches!(
+            store.list_receipts(),
+            Err(PaykitMobileError::Internal { .. })
+        ));
+        // Diagnostics keep working
+        let usage = crate::memory_ffi::MemoryReporter::memory_usage(&*store);
+        assert_eq!(usage.len(), 2);
+    }
+
+    #[test]
+    fn test_poisoned_flows_recover() {
+        let manager = create_test_manager(Box::new(EchoReceiptGenerator));
+        crate::test_support::poison_mutex(&manager.flows);
+
+        manager
+            .create_payment_request(
+                "payer".to_string(),
+                "payee".to_string(),
+                "lightning".to_string(),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(manager.list_flows().len(), 1);
+        assert!(manager.due_retransmissions().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_manager_flow_times_out() {
+        let manager = create_test_manager(Box::new(EchoReceiptGenerator));
+        manager.set_flow_timeouts(0, 1);
+        manager
+            .create_payment_request(
+                "payer".to_string(),
+                "payee".to_string(),
+                "lightning".to_string(),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+
+        assert!(manager.due_retransmissions().unwrap().is_empty());
+        let flows = manager.list_flows();
+        assert_eq!(flows[0].state, ReceiptFlowState::TimedOut);
+        assert_eq!(flows[0].attempts, 1);
+    }
+
+    /// Generator that adds an invoice and declines unknown methods.
+    struct ConformingGenerator;
+
+    impl ReceiptGeneratorCallback for ConformingGenerator {
+        fn generate_receipt(&self, request: ReceiptRequest) -> ReceiptGenerationResult {
+            if request.method_id != "lightning" {
+                return ReceiptGenerationResult::Declined {
+                    decline: ReceiptDecline {
+                        code: DeclineCode::UnsupportedMethod,
+                        message: "Lightning only".to_string(),
+                        retry_after_secs: None,
+                    },
+                };
+            }
+            ReceiptGenerationResult::Metadata {
+                metadata_json: r#"{"invoice":"lnbc1..."}"#.to_string(),
+            }
+        }
+    }
+
+    fn generator_request() -> ReceiptRequest {
+        ReceiptRequest {
+            receipt_id: "test_receipt".to_string(),
+            payer: "payer_pubkey".to_string(),
+            payee: "my_pubkey".to_string(),
+            method_id: "lightning".to_string(),
+            amount: Some("1000".to_string()),
+            currency: Some("SAT".to_string()),
+            metadata_json: r#"{"order_id":"A1"}"#.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_generator_result_validation() {
+        let request = generator_request();
+
+        // Metadata is merged into the request's
+        let receipt = finalize_generated_receipt(
+            &request,
+            ReceiptGenerationResult::Metadata {
+                metadata_json: r#"{"invoice":"lnbc1..."}"#.to_string(),
+            },
+        )
+        .unwrap();
+        let metadata: serde_json::Value = serde_json::from_str(&receipt.metadata_json).unwrap();
+        assert_eq!(metadata["order_id"], "A1");
+        assert_eq!(metadata["invoice"], "lnbc1...");
+
+        // A receipt for another amount is refused
+        let mut tampered = request.clone();
+        tampered.amount = Some("1".to_string());
+        let decline = finalize_generated_receipt(&request, ReceiptGenerationResult::ok(tampered))
+            .unwrap_err();
+        assert_eq!(decline.code, DeclineCode::Other);
+        assert!(decline.message.contains("amount"));
+
+        // So is metadata that is not a JSON object
+        assert!(finalize_generated_receipt(
+            &request,
+            ReceiptGenerationResult::Metadata {
+                metadata_json: "[1]".to_string(),
+            },
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_manager_structured_decline() {
+        struct BusyGenerator;
+        impl ReceiptGeneratorCallback for BusyGenerator {
+            fn generate_receipt(&self, _request: ReceiptRequest) -> ReceiptGenerationResult {
+                ReceiptGenerationResult::Declined {
+                    decline: ReceiptDecline {
+                        code: DeclineCode::Unavailable,
+                        message: "Node syncing".to_string(),
+                        retry_after_secs: Some(30),
+                    },
+                }
+            }
+        }
+
+        let manager = create_test_manager(Box::new(BusyGenerator));
+        let request_msg = PaykitMessageBuilder::new()
+            .create_receipt_request(generator_request())
+            .unwrap();
+        let response = manager
+            .handle_message(
+                request_msg,
+                "payer_pubkey".to_string(),
+                "my_pubkey".to_string(),
+            )
+            .unwrap()
+            .unwrap();
+
+        match PaykitMessageBuilder::new().parse_message(response).unwrap() {
+            ParsedMessage::Error { error } => {
+                assert_eq!(error.code, "UNAVAILABLE");
+                assert_eq!(error.message, "Node syncing (retry after 30s)");
+            }
+            other => panic!("Expected Error, got {:?}", other),
+        }
+        assert!(manager
+            .get_receipt("test_receipt".to_string())
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_generator_conformance() {
+        let report = check_receipt_generator(
+            Box::new(ConformingGenerator),
+            "lightning".to_string(),
+            Some("1000".to_string()),
+            Some("SAT".to_string()),
+        );
+        assert!(report.passed, "{:?}", report.checks);
+        assert_eq!(report.checks.len(), 4);
+
+        // Echo replaces the payer's metadata and accepts any method
+        let report = check_receipt_generator(
+            Box::new(EchoReceiptGenerator),
+            "lightning".to_string(),
+            None,
+            None,
+        );
+        let failed: Vec<&str> = report
+            .checks
+            .iter()
+            .filter(|c| !c.passed)
+            .map(|c| c.name.as_str())
+            .collect();
+        assert!(!report.passed);
+        assert_eq!(
+            failed,
+            ["keeps_request_metadata", "declines_unsupported_method"]
+        );
+    }
 }
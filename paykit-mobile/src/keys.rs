@@ -17,9 +17,10 @@
 //!
 //! - Secret keys should be stored in platform-secure storage (Keychain/EncryptedSharedPreferences)
 //! - Keys are zeroized from memory after use where possible
-//! - Export uses encryption to protect backup data
+//! - Export uses the shared Paykit backup format (Argon2id + AES-256-GCM)
 
 use crate::{PaykitMobileError, Result};
+use paykit_lib::backup::{KdfParams, KeyBackup as PortableBackup};
 
 /// Generated Ed25519 keypair for identity.
 #[derive(Clone, uniffi::Record)]
@@ -46,7 +47,10 @@ pub struct X25519Keypair {
     pub epoch: u32,
 }
 
-/// Encrypted key backup for export/import.
+/// Encrypted key backup in the version 1 field layout.
+///
+/// Kept for existing callers; new code should exchange the armored text from
+/// [`export_identity_backup`], which every Paykit front-end can restore.
 #[derive(Clone, uniffi::Record)]
 pub struct KeyBackup {
     /// Version of the backup format.
@@ -61,6 +65,19 @@ pub struct KeyBackup {
     pub public_key_z32: String,
 }
 
+/// Unencrypted details of an armored identity backup.
+#[derive(Clone, uniffi::Record)]
+pub struct IdentityBackupInfo {
+    /// Backup format version.
+    pub version: u8,
+    /// Public key of the backed-up identity (z-base32).
+    pub public_key_z32: String,
+    /// Argon2id memory cost in KiB.
+    pub kdf_memory_kib: u32,
+    /// Argon2id passes.
+    pub kdf_iterations: u32,
+}
+
 /// Generate a new Ed25519 keypair for identity.
 ///
 /// This creates a new random identity. The secret key should be stored
@@ -148,7 +165,7 @@ pub fn derive_x25519_keypair(
     let device_id_bytes = device_id.as_bytes();
 
     // Use pubky-noise KDF for derivation
-    let x25519_secret = derive_x25519_for_device_epoch(&seed, device_id_bytes, epoch);
+    let x25519_secret = derive_x25519_for_device_epoch(&seed, device_id_bytes, epoch)?;
     let x25519_public = x25519_pk_from_sk(&x25519_secret);
 
     Ok(X25519Keypair {
@@ -224,6 +241,9 @@ pub fn verify_signature(
 
 /// Export keypair to encrypted backup.
 ///
+/// Writes the version 1 field layout for existing callers. Prefer
+/// [`export_identity_backup`], whose output the CLI and web demos can restore.
+///
 /// # Arguments
 ///
 /// * `secret_key_hex` - The secret key to backup.
@@ -295,64 +315,85 @@ pub fn export_keypair_to_backup(secret_key_hex: String, password: String) -> Res
 /// The decrypted keypair.
 #[uniffi::export]
 pub fn import_keypair_from_backup(backup: KeyBackup, password: String) -> Result<Ed25519Keypair> {
-    use aes_gcm::{
-        aead::{Aead, KeyInit},
-        Aes256Gcm, Nonce,
-    };
-    use argon2::Argon2;
-
     if backup.version != 1 {
         return Err(PaykitMobileError::Validation {
             msg: format!("Unsupported backup version: {}", backup.version),
         });
     }
 
-    let salt = hex::decode(&backup.salt_hex).map_err(|e| PaykitMobileError::Validation {
-        msg: format!("Invalid salt: {}", e),
-    })?;
-
-    let nonce_bytes =
-        hex::decode(&backup.nonce_hex).map_err(|e| PaykitMobileError::Validation {
-            msg: format!("Invalid nonce: {}", e),
-        })?;
-
-    let encrypted =
-        hex::decode(&backup.encrypted_data_hex).map_err(|e| PaykitMobileError::Validation {
-            msg: format!("Invalid encrypted data: {}", e),
-        })?;
+    let portable = PortableBackup::from_legacy_parts(
+        &backup.encrypted_data_hex,
+        &backup.salt_hex,
+        &backup.nonce_hex,
+        &backup.public_key_z32,
+    )?;
+    open_portable_backup(&portable, &password)
+}
 
-    // Derive encryption key from password
-    let mut encryption_key = [0u8; 32];
-    Argon2::default()
-        .hash_password_into(password.as_bytes(), &salt, &mut encryption_key)
-        .map_err(|e| PaykitMobileError::Internal {
-            msg: format!("Key derivation failed: {}", e),
-        })?;
+/// Export keypair to an armored, passphrase-protected backup.
+///
+/// Uses the shared Paykit backup format (Argon2id + AES-256-GCM with a
+/// checksum), so the text can be restored by the CLI and web demos as well.
+///
+/// # Arguments
+///
+/// * `secret_key_hex` - The secret key to backup.
+/// * `password` - Password to encrypt the backup.
+///
+/// # Returns
+///
+/// Armored backup text, safe to store in a file or share.
+#[uniffi::export]
+pub fn export_identity_backup(secret_key_hex: String, password: String) -> Result<String> {
+    let secret_bytes = zeroize::Zeroizing::new(hex_to_32_bytes(&secret_key_hex)?);
+    let keypair = ed25519_keypair_from_secret(secret_key_hex)?;
 
-    // Decrypt
-    let cipher =
-        Aes256Gcm::new_from_slice(&encryption_key).map_err(|e| PaykitMobileError::Internal {
-            msg: format!("Cipher init failed: {}", e),
-        })?;
+    let backup = PortableBackup::seal(
+        &secret_bytes,
+        &keypair.public_key_z32,
+        &password,
+        KdfParams::mobile(),
+    )?;
+    Ok(backup.to_armored()?)
+}
 
-    if nonce_bytes.len() != 12 {
-        return Err(PaykitMobileError::Validation {
-            msg: "Invalid nonce length".to_string(),
-        });
-    }
-    let nonce = Nonce::from_slice(&nonce_bytes);
+/// Import keypair from an armored backup.
+///
+/// Also accepts the version 1 JSON written by older CLI and web releases.
+///
+/// # Arguments
+///
+/// * `backup` - Backup text.
+/// * `password` - Password to decrypt the backup.
+///
+/// # Returns
+///
+/// The decrypted keypair.
+#[uniffi::export]
+pub fn import_identity_backup(backup: String, password: String) -> Result<Ed25519Keypair> {
+    let portable = PortableBackup::decode(&backup)?;
+    open_portable_backup(&portable, &password)
+}
 
-    let decrypted = cipher.decrypt(nonce, encrypted.as_ref()).map_err(|_| {
-        PaykitMobileError::AuthenticationError {
-            msg: "Invalid password or corrupted backup".to_string(),
-        }
-    })?;
+/// Read the unencrypted details of a backup without the password.
+#[uniffi::export]
+pub fn inspect_identity_backup(backup: String) -> Result<IdentityBackupInfo> {
+    let portable = PortableBackup::decode(&backup)?;
+    let kdf = portable.kdf_params();
+    Ok(IdentityBackupInfo {
+        version: portable.version(),
+        public_key_z32: portable.public_key_z32().to_string(),
+        kdf_memory_kib: kdf.memory_kib,
+        kdf_iterations: kdf.iterations,
+    })
+}
 
-    let secret_key_hex = hex::encode(&decrypted);
-    let keypair = ed25519_keypair_from_secret(secret_key_hex)?;
+fn open_portable_backup(backup: &PortableBackup, password: &str) -> Result<Ed25519Keypair> {
+    let secret_key = backup.open(password)?;
+    let keypair = ed25519_keypair_from_secret(hex::encode(*secret_key))?;
 
     // Verify public key matches
-    if keypair.public_key_z32 != backup.public_key_z32 {
+    if keypair.public_key_z32 != backup.public_key_z32() {
         return Err(PaykitMobileError::Validation {
             msg: "Backup public key mismatch".to_string(),
         });
@@ -408,7 +449,7 @@ fn hex_to_32_bytes(hex_str: &str) -> Result<[u8; 32]> {
 }
 
 /// Z-base32 encoding (pkarr format).
-fn z32_encode(bytes: &[u8; 32]) -> String {
+pub(crate) fn z32_encode(bytes: &[u8; 32]) -> String {
     // z-base32 alphabet: ybndrfg8ejkmcpqxot1uwisza345h769
     const ALPHABET: &[u8] = b"ybndrfg8ejkmcpqxot1uwisza345h769";
 
@@ -437,7 +478,7 @@ fn z32_encode(bytes: &[u8; 32]) -> String {
 }
 
 /// Z-base32 decoding (pkarr format).
-fn z32_decode(s: &str) -> Result<[u8; 32]> {
+pub(crate) fn z32_decode(s: &str) -> Result<[u8; 32]> {
     const ALPHABET: &[u8] = b"ybndrfg8ejkmcpqxot1uwisza345h769";
 
     let mut result = Vec::with_capacity(32);
@@ -475,7 +516,11 @@ fn z32_decode(s: &str) -> Result<[u8; 32]> {
 }
 
 // Re-implement the KDF functions from pubky-noise to avoid circular dependency
-fn derive_x25519_for_device_epoch(seed: &[u8; 32], device_id: &[u8], epoch: u32) -> [u8; 32] {
+fn derive_x25519_for_device_epoch(
+    seed: &[u8; 32],
+    device_id: &[u8],
+    epoch: u32,
+) -> Result<[u8; 32]> {
     use hkdf::Hkdf;
     use sha2::Sha512;
 
@@ -487,14 +532,17 @@ fn derive_x25519_for_device_epoch(seed: &[u8; 32], device_id: &[u8], epoch: u32)
     info.extend_from_slice(&epoch.to_le_bytes());
 
     let mut sk = [0u8; 32];
-    hk.expand(&info, &mut sk).expect("hkdf expand");
+    hk.expand(&info, &mut sk)
+        .map_err(|e| PaykitMobileError::Internal {
+            msg: format!("HKDF expand failed: {}", e),
+        })?;
 
     // Clamp for X25519
     sk[0] &= 248;
     sk[31] &= 127;
     sk[31] |= 64;
 
-    sk
+    Ok(sk)
 }
 
 fn x25519_pk_from_sk(sk: &[u8; 32]) -> [u8; 32] {
@@ -608,6 +656,25 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_identity_backup_round_trip() {
+        let keypair = generate_ed25519_keypair().unwrap();
+        let armored =
+            export_identity_backup(keypair.secret_key_hex.clone(), "hunter2".to_string()).unwrap();
+        assert!(armored.starts_with("-----BEGIN PAYKIT KEY BACKUP-----"));
+
+        let info = inspect_identity_backup(armored.clone()).unwrap();
+        assert_eq!(info.version, 2);
+        assert_eq!(info.public_key_z32, keypair.public_key_z32);
+
+        let restored = import_identity_backup(armored.clone(), "hunter2".to_string()).unwrap();
+        assert_eq!(restored.secret_key_hex, keypair.secret_key_hex);
+        assert!(matches!(
+            import_identity_backup(armored, "wrong".to_string()),
+            Err(PaykitMobileError::AuthenticationError { .. })
+        ));
+    }
+
     #[test]
     fn test_z32_roundtrip() {
         let keypair = generate_ed25519_keypair().unwrap();
@@ -1,7 +1,8 @@
 //! Paykit Mobile FFI Bindings
 //!
 //! This crate provides UniFFI bindings for Paykit, enabling integration
-//! with iOS (Swift) and Android (Kotlin) applications.
+//! with iOS (Swift) and Android (Kotlin) applications. The same bindings
+//! generate a Python module for scripting over receipts.
 //!
 //! # Architecture
 //!
@@ -15,16 +16,53 @@
 //!
 //! All exposed types are thread-safe and can be used from any thread.
 //! Async operations use the Tokio runtime.
-
+//!
+//! # Panics
+//!
+//! Nothing reachable from the bindings should panic: a panic that unwinds
+//! into Swift or Kotlin can abort the app. `unwrap`, `expect` and `panic!`
+//! are denied outside tests. A poisoned lock either surfaces as
+//! [`PaykitMobileError::Internal`] or, where the guarded data cannot be left
+//! half-updated, is recovered from.
+
+#![cfg_attr(
+    not(test),
+    deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)
+)]
+
+pub mod approvals_ffi;
 pub mod async_bridge;
+pub mod attestation_ffi;
+pub mod calendar_ffi;
+pub mod compliance_ffi;
+pub mod contact_verification_ffi;
+pub mod custom_method_ffi;
 pub mod executor_ffi;
+pub mod fees_ffi;
+pub mod handles_ffi;
 pub mod interactive_ffi;
 pub mod keys;
+pub mod lifecycle_ffi;
+pub mod memory_ffi;
+pub mod nfc;
 pub mod noise_ffi;
+pub mod prefetch_ffi;
+pub mod privacy_ffi;
+pub mod proxy_ffi;
+pub mod push_ffi;
+pub mod receipts_ffi;
+pub mod rotation_ffi;
 pub mod scanner;
+pub mod scheduled_payment_ffi;
 pub mod spending_ffi;
+pub mod standing_order_ffi;
 pub mod storage;
 pub mod transport_ffi;
+pub mod trust_ffi;
+pub mod velocity_ffi;
+
+#[cfg(test)]
+mod test_support;
 
 // Re-export transport types for easier access
 pub use transport_ffi::{
@@ -35,25 +73,51 @@ pub use transport_ffi::{
 
 // Re-export interactive types for easier access
 pub use interactive_ffi::{
-    ErrorMessage, ParsedMessage, PaykitInteractiveManagerFFI, PaykitMessageBuilder,
-    PaykitMessageType, PrivateEndpointOffer, ReceiptGenerationResult, ReceiptGeneratorCallback,
-    ReceiptRequest, ReceiptStore,
+    ConformanceCheck, ConformanceReport, DeclineCode, ErrorMessage, ParsedMessage,
+    PaykitInteractiveManagerFFI, PaykitMessageBuilder, PaykitMessageType, PrivateEndpointOffer,
+    ReceiptDecline, ReceiptFlowState, ReceiptFlowStatus, ReceiptGenerationResult,
+    ReceiptGeneratorCallback, ReceiptRequest, ReceiptSearchPage, ReceiptSearchQuery, ReceiptStore,
+    Retransmission,
 };
 
 // Re-export key management types for easier access
-pub use keys::{Ed25519Keypair, KeyBackup, X25519Keypair};
+pub use keys::{Ed25519Keypair, IdentityBackupInfo, KeyBackup, X25519Keypair};
+
+// Re-export app lifecycle types for foreground/background scheduling
+pub use lifecycle_ffi::{
+    AppStateFFI, ForegroundRefreshFFI, LifecycleConfigFFI, LifecycleListener, PollingScheduleFFI,
+};
+
+// Re-export memory diagnostics types for debug screens
+pub use memory_ffi::{AllocationStatsFFI, MemoryMonitorFFI, MemorySnapshotFFI, SubsystemMemoryFFI};
+
+// Re-export NFC payload types for tap-to-pay
+pub use nfc::NfcPayload;
 
 // Re-export noise FFI types for easier access
 pub use noise_ffi::{
-    NoiseConnectionStatus, NoiseEndpointInfo, NoiseHandshakeResult, NoisePaymentMessage,
-    NoisePaymentMessageType, NoiseServerConfig, NoiseServerStatus, NoiseSessionInfo,
+    NoiseConnectionStatus, NoiseEndpointInfo, NoiseHandshakeResult, NoiseKeyInfo,
+    NoisePaymentMessage, NoisePaymentMessageType, NoiseServerConfig, NoiseServerStatus,
+    NoiseSessionInfo,
 };
 
 // Re-export executor FFI types for wallet integration (Bitkit, etc.)
 pub use executor_ffi::{
     BitcoinExecutorBridge, BitcoinExecutorFFI, BitcoinNetworkFFI, BitcoinTxResultFFI,
-    DecodedInvoiceFFI, LightningExecutorBridge, LightningExecutorFFI, LightningNetworkFFI,
-    LightningPaymentResultFFI, LightningPaymentStatusFFI,
+    ChangePreferenceFFI, CoinSelectionFFI, DecodedInvoiceFFI, LightningExecutorBridge,
+    LightningExecutorFFI, LightningNetworkFFI, LightningPaymentResultFFI,
+    LightningPaymentStatusFFI,
+};
+
+// Re-export custom method FFI types for wallet-defined payment rails
+pub use custom_method_ffi::{CustomMethodBridge, CustomMethodCallbacksFFI};
+
+// Re-export fee guardrail FFI types for fee-spike confirmations
+pub use fees_ffi::FeeGuardrailsFFI;
+
+// Re-export rotation FFI types for endpoint rotation policies
+pub use rotation_ffi::{
+    RotationDecisionFFI, RotationEngineFFI, RotationRecordFFI, RotationStatusFFI,
 };
 
 // Re-export spending FFI types for atomic spending limit operations
@@ -61,7 +125,67 @@ pub use spending_ffi::{
     PeerSpendingLimitFFI, SpendingCheckResultFFI, SpendingManagerFFI, SpendingReservationFFI,
 };
 
-use std::sync::{Arc, RwLock};
+// Re-export calendar FFI types for upcoming subscription charges
+pub use calendar_ffi::{CurrencyTotalFFI, SubscriptionCalendarFFI, UpcomingChargeFFI};
+
+// Re-export approval FFI types for multi-signature payment approval
+pub use approvals_ffi::{
+    ApprovalPolicyFFI, ApprovalQueueFFI, ApprovalStatusFFI, PendingApprovalFFI,
+};
+
+// Re-export compliance screening FFI types for counterparty checks
+pub use compliance_ffi::{
+    ComplianceScreenerBridge, ComplianceScreenerCallback, ScreeningDirectionFFI,
+    ScreeningRequestFFI, ScreeningResultFFI,
+};
+
+// Re-export attestation and key pinning FFI types for NN handshakes
+pub use attestation_ffi::{PinCheckFFI, PinStoreFFI, PinnedKeyFFI};
+
+// Re-export payee prefetch types for checkout warm-up
+pub use prefetch_ffi::{PayeePrefetcherFFI, PayeeSnapshotFFI};
+
+// Re-export handle resolution types for paying user@domain handles
+pub use handles_ffi::{
+    HandleLookupCallback, HandleResolverFFI, HandleSourceFFI, ResolvedHandleFFI,
+};
+
+// Re-export privacy scoring types for payment previews
+pub use privacy_ffi::{PlannedPaymentFFI, PrivacyLeakFFI, PrivacyReportFFI};
+
+// Re-export client and proxy configuration types
+pub use proxy_ffi::{PaykitClientConfig, ProxyOverrideFFI, Socks5ProxyFFI, TransportKindFFI};
+
+// Re-export push hint types for waking apps with inbox messages
+pub use push_ffi::{PushPlatformFFI, PushRegistrarFFI, PushWakeUpFFI};
+
+// Re-export receipt reporting types for scripting and history screens
+pub use receipts_ffi::{ProofVerificationFFI, ReceiptAnalyticsFFI, ReceiptTotalFFI};
+
+// Re-export scheduled payment FFI types for send-later payments
+pub use scheduled_payment_ffi::{
+    ScheduledPaymentBookFFI, ScheduledPaymentFFI, ScheduledPaymentRunFFI, ScheduledPaymentSpecFFI,
+    ScheduledStatusFFI,
+};
+
+// Re-export standing order FFI types for sender-side recurring payments
+pub use standing_order_ffi::{
+    OrderScheduleFFI, StandingOrderBookFFI, StandingOrderFFI, StandingOrderRunFFI,
+    StandingOrderSpecFFI,
+};
+
+// Re-export trust policy FFI types for payee allowlist/blocklist
+pub use trust_ffi::{TrustDecisionFFI, TrustEntryFFI, TrustLevelFFI, TrustPolicyFFI};
+
+// Re-export velocity FFI types for anomaly holds on outgoing payments
+pub use velocity_ffi::{
+    AnomalyFFI, HeldPaymentFFI, VelocityConfigFFI, VelocityEventFFI, VelocityEventListener,
+};
+
+use parking_lot::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 // UniFFI scaffolding
 uniffi::setup_scaffolding!();
@@ -116,6 +240,22 @@ pub enum PaykitMobileError {
     /// Permission denied.
     #[error("Permission denied: {msg}")]
     PermissionDenied { msg: String },
+
+    /// Payment held by velocity checks until the user approves it.
+    #[error("Payment held: {msg}")]
+    PaymentHeld { msg: String },
+
+    /// Payment queued until enough co-signers approve it.
+    #[error("Approval required: {msg}")]
+    ApprovalRequired { msg: String },
+
+    /// Fee above the configured guardrails; retry with the user's confirmation.
+    #[error("Fee spike: {msg}")]
+    FeeSpike { msg: String },
+
+    /// The client was shut down; create a new one to continue.
+    #[error("Client shut down: {msg}")]
+    AlreadyShutdown { msg: String },
 }
 
 impl From<paykit_lib::PaykitError> for PaykitMobileError {
@@ -182,6 +322,7 @@ impl From<paykit_lib::PaykitError> for PaykitMobileError {
             paykit_lib::PaykitError::PaymentAlreadyCompleted { payment_id } => Self::Transport {
                 msg: format!("Payment {} already completed", payment_id),
             },
+            e @ paykit_lib::PaykitError::FeeSpike { .. } => Self::FeeSpike { msg: e.to_string() },
             paykit_lib::PaykitError::Storage(msg) => Self::Internal { msg },
             paykit_lib::PaykitError::QuotaExceeded { used, limit } => Self::Internal {
                 msg: format!("Quota exceeded: {} of {} used", used, limit),
@@ -298,6 +439,26 @@ pub struct SelectionResult {
     pub primary_method: String,
     pub fallback_methods: Vec<String>,
     pub reason: String,
+    /// The reason as message codes; format with `localize_message()`.
+    pub reason_messages: Vec<LocalizedMessage>,
+}
+
+/// A message code with named parameters, for translated display.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct LocalizedMessage {
+    /// Stable code, e.g. "selection.balanced".
+    pub code: String,
+    /// Values substituted into the message.
+    pub params: HashMap<String, String>,
+}
+
+impl From<&paykit_lib::i18n::LocalizedMessage> for LocalizedMessage {
+    fn from(message: &paykit_lib::i18n::LocalizedMessage) -> Self {
+        Self {
+            code: message.code.clone(),
+            params: message.params.clone().into_iter().collect(),
+        }
+    }
 }
 
 // ============================================================================
@@ -328,6 +489,49 @@ pub struct PaymentStatusInfo {
     pub error: Option<String>,
 }
 
+impl From<paykit_interactive::PaymentStatusInfo> for PaymentStatusInfo {
+    fn from(info: paykit_interactive::PaymentStatusInfo) -> Self {
+        Self {
+            status: match info.status {
+                paykit_interactive::PaymentStatus::Pending => PaymentStatus::Pending,
+                paykit_interactive::PaymentStatus::Processing => PaymentStatus::Processing,
+                paykit_interactive::PaymentStatus::Confirmed => PaymentStatus::Confirmed,
+                paykit_interactive::PaymentStatus::Finalized => PaymentStatus::Finalized,
+                paykit_interactive::PaymentStatus::Failed => PaymentStatus::Failed,
+                paykit_interactive::PaymentStatus::Cancelled => PaymentStatus::Cancelled,
+                paykit_interactive::PaymentStatus::Expired => PaymentStatus::Expired,
+            },
+            receipt_id: info.receipt_id,
+            method_id: info.method_id.0,
+            updated_at: info.updated_at,
+            confirmations: info.confirmations,
+            required_confirmations: info.required_confirmations,
+            error: info.error,
+        }
+    }
+}
+
+/// One amount band of a confirmation policy.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct ConfirmationTier {
+    /// Upper bound (exclusive) in satoshis, or `None` for any amount.
+    pub below_sats: Option<u64>,
+    /// Confirmations required for payments in this band.
+    pub confirmations: u64,
+    /// Only apply this tier when the counterparty is trusted.
+    pub trusted_only: bool,
+}
+
+impl From<ConfirmationTier> for paykit_interactive::ConfirmationTier {
+    fn from(tier: ConfirmationTier) -> Self {
+        Self {
+            below_sats: tier.below_sats,
+            confirmations: tier.confirmations,
+            trusted_only: tier.trusted_only,
+        }
+    }
+}
+
 // ============================================================================
 // Payment Execution Types
 // ============================================================================
@@ -409,6 +613,117 @@ pub struct FallbackExecutionResult {
     pub summary: String,
 }
 
+// ============================================================================
+// Method Capability Types
+// ============================================================================
+
+/// Where a payment method settles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, uniffi::Enum)]
+pub enum SettlementKind {
+    OnChain,
+    OffChain,
+    Other,
+}
+
+impl From<paykit_lib::methods::SettlementKind> for SettlementKind {
+    fn from(kind: paykit_lib::methods::SettlementKind) -> Self {
+        match kind {
+            paykit_lib::methods::SettlementKind::OnChain => Self::OnChain,
+            paykit_lib::methods::SettlementKind::OffChain => Self::OffChain,
+            paykit_lib::methods::SettlementKind::Other => Self::Other,
+        }
+    }
+}
+
+impl From<SettlementKind> for paykit_lib::methods::SettlementKind {
+    fn from(kind: SettlementKind) -> Self {
+        match kind {
+            SettlementKind::OnChain => Self::OnChain,
+            SettlementKind::OffChain => Self::OffChain,
+            SettlementKind::Other => Self::Other,
+        }
+    }
+}
+
+/// What a registered payment method supports.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct MethodCapabilities {
+    /// Where payments settle.
+    pub settlement: SettlementKind,
+    /// Whether payments are really executed (false without an executor).
+    pub execution: bool,
+    /// Whether verifiable proofs are produced.
+    pub proofs: bool,
+    /// Whether fresh private endpoints can be generated.
+    pub private_endpoints: bool,
+    /// Whether routes can be probed before paying.
+    pub route_probing: bool,
+    /// Smallest payable amount in sats, if limited.
+    pub min_amount_sats: Option<u64>,
+    /// Largest payable amount in sats, if limited.
+    pub max_amount_sats: Option<u64>,
+}
+
+impl From<MethodCapabilities> for paykit_lib::methods::PluginCapabilities {
+    fn from(caps: MethodCapabilities) -> Self {
+        Self {
+            settlement: caps.settlement.into(),
+            execution: caps.execution,
+            proofs: caps.proofs,
+            private_endpoints: caps.private_endpoints,
+            route_probing: caps.route_probing,
+            min_amount_sats: caps.min_amount_sats,
+            max_amount_sats: caps.max_amount_sats,
+        }
+    }
+}
+
+/// A registered payment method and its capabilities.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct MethodDescriptor {
+    pub method_id: String,
+    pub display_name: String,
+    pub description: String,
+    pub capabilities: MethodCapabilities,
+}
+
+impl From<paykit_lib::methods::MethodDescriptor> for MethodDescriptor {
+    fn from(descriptor: paykit_lib::methods::MethodDescriptor) -> Self {
+        let caps = descriptor.capabilities;
+        Self {
+            method_id: descriptor.method_id.0,
+            display_name: descriptor.display_name,
+            description: descriptor.description,
+            capabilities: MethodCapabilities {
+                settlement: caps.settlement.into(),
+                execution: caps.execution,
+                proofs: caps.proofs,
+                private_endpoints: caps.private_endpoints,
+                route_probing: caps.route_probing,
+                min_amount_sats: caps.min_amount_sats,
+                max_amount_sats: caps.max_amount_sats,
+            },
+        }
+    }
+}
+
+/// Base selection weight of a payment method, applied whatever the strategy.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct MethodWeight {
+    /// Points added to the method's selection score (may be negative).
+    pub weight: f64,
+    /// Only apply the weight from this amount in sats upwards.
+    pub min_amount_sats: Option<u64>,
+    /// Only apply the weight up to this amount in sats.
+    pub max_amount_sats: Option<u64>,
+}
+
+impl From<MethodWeight> for paykit_lib::methods::MethodWeight {
+    fn from(weight: MethodWeight) -> Self {
+        Self::new(weight.weight).with_amount_range(weight.min_amount_sats, weight.max_amount_sats)
+    }
+}
+
 // ============================================================================
 // Health Types
 // ============================================================================
@@ -432,6 +747,23 @@ pub struct HealthCheckResult {
     pub error: Option<String>,
 }
 
+impl From<paykit_lib::health::HealthCheckResult> for HealthCheckResult {
+    fn from(r: paykit_lib::health::HealthCheckResult) -> Self {
+        Self {
+            method_id: r.method_id.0,
+            status: match r.status {
+                paykit_lib::health::HealthStatus::Healthy => HealthStatus::Healthy,
+                paykit_lib::health::HealthStatus::Degraded => HealthStatus::Degraded,
+                paykit_lib::health::HealthStatus::Unavailable => HealthStatus::Unavailable,
+                paykit_lib::health::HealthStatus::Unknown => HealthStatus::Unknown,
+            },
+            checked_at: r.checked_at,
+            latency_ms: r.latency_ms,
+            error: r.error,
+        }
+    }
+}
+
 // ============================================================================
 // Subscription Types
 // ============================================================================
@@ -532,6 +864,130 @@ pub enum ModificationType {
     Resume,
 }
 
+/// Reason code for cancelling a subscription.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, uniffi::Enum)]
+pub enum CancellationReason {
+    NoLongerNeeded,
+    TooExpensive,
+    SwitchingProvider,
+    PaymentIssue,
+    ServiceIssue,
+    TermsViolation,
+    Other,
+}
+
+impl From<CancellationReason> for paykit_subscriptions::CancellationReason {
+    fn from(r: CancellationReason) -> Self {
+        match r {
+            CancellationReason::NoLongerNeeded => {
+                paykit_subscriptions::CancellationReason::NoLongerNeeded
+            }
+            CancellationReason::TooExpensive => {
+                paykit_subscriptions::CancellationReason::TooExpensive
+            }
+            CancellationReason::SwitchingProvider => {
+                paykit_subscriptions::CancellationReason::SwitchingProvider
+            }
+            CancellationReason::PaymentIssue => {
+                paykit_subscriptions::CancellationReason::PaymentIssue
+            }
+            CancellationReason::ServiceIssue => {
+                paykit_subscriptions::CancellationReason::ServiceIssue
+            }
+            CancellationReason::TermsViolation => {
+                paykit_subscriptions::CancellationReason::TermsViolation
+            }
+            CancellationReason::Other => paykit_subscriptions::CancellationReason::Other,
+        }
+    }
+}
+
+impl From<paykit_subscriptions::CancellationReason> for CancellationReason {
+    fn from(r: paykit_subscriptions::CancellationReason) -> Self {
+        match r {
+            paykit_subscriptions::CancellationReason::NoLongerNeeded => {
+                CancellationReason::NoLongerNeeded
+            }
+            paykit_subscriptions::CancellationReason::TooExpensive => {
+                CancellationReason::TooExpensive
+            }
+            paykit_subscriptions::CancellationReason::SwitchingProvider => {
+                CancellationReason::SwitchingProvider
+            }
+            paykit_subscriptions::CancellationReason::PaymentIssue => {
+                CancellationReason::PaymentIssue
+            }
+            paykit_subscriptions::CancellationReason::ServiceIssue => {
+                CancellationReason::ServiceIssue
+            }
+            paykit_subscriptions::CancellationReason::TermsViolation => {
+                CancellationReason::TermsViolation
+            }
+            paykit_subscriptions::CancellationReason::Other => CancellationReason::Other,
+        }
+    }
+}
+
+/// A signed subscription cancellation ready to send to the counterparty.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct SubscriptionCancellation {
+    pub subscription_id: String,
+    pub cancelled_by: String,
+    pub counterparty: String,
+    pub reason: CancellationReason,
+    pub note: Option<String>,
+    pub cancelled_at: i64,
+    /// Signed cancellation JSON, kept until the counterparty acknowledges.
+    pub cancellation_json: String,
+    /// `CancelSubscription` message JSON to send over the Noise channel.
+    pub message_json: String,
+}
+
+/// Rounding applied to prorated amounts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, uniffi::Enum)]
+pub enum RoundingMode {
+    /// Round to the nearest unit.
+    Nearest,
+    /// Always round up: charges favor the provider, credits the subscriber.
+    Up,
+    /// Always round down: charges favor the subscriber, credits the provider.
+    Down,
+}
+
+impl From<RoundingMode> for paykit_subscriptions::RoundingMode {
+    fn from(m: RoundingMode) -> Self {
+        match m {
+            RoundingMode::Nearest => paykit_subscriptions::RoundingMode::Nearest,
+            RoundingMode::Up => paykit_subscriptions::RoundingMode::Up,
+            RoundingMode::Down => paykit_subscriptions::RoundingMode::Down,
+        }
+    }
+}
+
+/// Proration result with the details of the calculation.
+///
+/// Amounts are in the smallest unit of `currency`.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct ProrationBreakdown {
+    pub credit: i64,
+    pub charge: i64,
+    pub net: i64,
+    pub is_refund: bool,
+    pub currency: String,
+    pub rounding_mode: RoundingMode,
+    /// Current amount per day, unrounded, as a decimal string.
+    pub old_daily_rate: String,
+    /// New amount per day, unrounded, as a decimal string.
+    pub new_daily_rate: String,
+    pub total_days: u32,
+    pub days_used: u32,
+    pub days_remaining: u32,
+    pub seconds_remaining: i64,
+    pub period_start: i64,
+    pub period_end: i64,
+    pub change_date: i64,
+}
+
 /// Proration result.
 #[derive(Clone, Debug, uniffi::Record)]
 pub struct ProrationResult {
@@ -608,17 +1064,95 @@ pub struct PrivateEndpoint {
 #[derive(uniffi::Object)]
 pub struct PaykitClient {
     /// Plugin registry (thread-safe for concurrent access).
-    registry: Arc<std::sync::RwLock<paykit_lib::methods::PaymentMethodRegistry>>,
+    registry: Arc<paykit_lib::methods::PaymentMethodRegistry>,
     /// Health monitor.
     health_monitor: Arc<paykit_lib::health::HealthMonitor>,
     /// Status tracker.
     status_tracker: Arc<paykit_interactive::PaymentStatusTracker>,
-    /// Tokio runtime for async operations.
-    runtime: tokio::runtime::Runtime,
+    /// Tokio runtime for async operations, taken by `shutdown`.
+    runtime: RwLock<Option<tokio::runtime::Runtime>>,
+    /// Set once `shutdown` has been called.
+    shut_down: AtomicBool,
     /// Configured Bitcoin network.
     bitcoin_network: executor_ffi::BitcoinNetworkFFI,
     /// Configured Lightning network.
     lightning_network: executor_ffi::LightningNetworkFFI,
+    /// Outgoing payment velocity checks.
+    velocity_guard: Arc<paykit_lib::policy::VelocityGuard>,
+    /// Fee estimate cache and guardrails shared by the executor plugins.
+    fee_guard: Arc<paykit_lib::policy::FeeGuard>,
+    /// Multi-signature approval queue for large payments.
+    approval_queue: RwLock<Option<Arc<approvals_ffi::ApprovalQueueFFI>>>,
+    /// Proxy settings the app applies to its transports.
+    proxy: paykit_lib::proxy::ProxyConfig,
+    /// Foreground/background state and polling schedules.
+    lifecycle: lifecycle_ffi::Lifecycle,
+}
+
+impl memory_ffi::MemoryReporter for PaykitClient {
+    fn memory_usage(&self) -> Vec<memory_ffi::SubsystemMemoryFFI> {
+        // The tracker does not expose its entries, so count inline size only
+        let statuses = self.status_tracker.len();
+        vec![memory_ffi::SubsystemMemoryFFI {
+            name: "client.payment_statuses".to_string(),
+            entries: statuses as u64,
+            approx_bytes: (statuses
+                * std::mem::size_of::<(String, paykit_interactive::PaymentStatusInfo)>())
+                as u64,
+        }]
+    }
+}
+
+impl PaykitClient {
+    /// Fail with `AlreadyShutdown` once `shutdown` has been called.
+    fn ensure_running(&self) -> Result<()> {
+        if self.shut_down.load(Ordering::Acquire) {
+            return Err(client_shut_down());
+        }
+        Ok(())
+    }
+
+    /// Run a fallible future on the client's runtime.
+    ///
+    /// Holds the runtime's read lock until the future completes, which is
+    /// what `shutdown` waits on.
+    fn block_on<T, E>(
+        &self,
+        future: impl std::future::Future<Output = std::result::Result<T, E>>,
+    ) -> Result<T>
+    where
+        PaykitMobileError: From<E>,
+    {
+        let runtime = self.runtime.read();
+        match runtime.as_ref() {
+            Some(runtime) => Ok(runtime.block_on(future)?),
+            None => Err(client_shut_down()),
+        }
+    }
+}
+
+fn lifecycle_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+fn client_shut_down() -> PaykitMobileError {
+    PaykitMobileError::AlreadyShutdown {
+        msg: "PaykitClient::shutdown was called".to_string(),
+    }
+}
+
+impl Drop for PaykitClient {
+    fn drop(&mut self) {
+        // Dropping a runtime blocks until its tasks stop and panics inside
+        // an async context. Hosts often release the client from the UI
+        // thread or a callback, so let the tasks wind down in the background.
+        if let Some(runtime) = self.runtime.get_mut().take() {
+            runtime.shutdown_background();
+        }
+    }
 }
 
 #[uniffi::export]
@@ -653,19 +1187,172 @@ impl PaykitClient {
         bitcoin_network: executor_ffi::BitcoinNetworkFFI,
         lightning_network: executor_ffi::LightningNetworkFFI,
     ) -> Result<Arc<Self>> {
+        Self::new_with_config(PaykitClientConfig {
+            bitcoin_network,
+            lightning_network,
+            ..proxy_ffi::default_client_config()
+        })
+    }
+
+    /// Create a new Paykit client from a full configuration.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// // Route everything through Tor
+    /// let client = PaykitClient::new_with_config(PaykitClientConfig {
+    ///     proxy: Some(tor_proxy()),
+    ///     ..default_client_config()
+    /// })?;
+    /// ```
+    #[uniffi::constructor]
+    pub fn new_with_config(config: PaykitClientConfig) -> Result<Arc<Self>> {
         let runtime = tokio::runtime::Runtime::new()
             .map_err(|e| PaykitMobileError::Internal { msg: e.to_string() })?;
 
         Ok(Arc::new(Self {
-            registry: Arc::new(RwLock::new(paykit_lib::methods::default_registry())),
+            registry: Arc::new(paykit_lib::methods::default_registry()),
             health_monitor: Arc::new(paykit_lib::health::HealthMonitor::with_defaults()),
             status_tracker: Arc::new(paykit_interactive::PaymentStatusTracker::new()),
-            runtime,
-            bitcoin_network,
-            lightning_network,
+            runtime: RwLock::new(Some(runtime)),
+            shut_down: AtomicBool::new(false),
+            bitcoin_network: config.bitcoin_network,
+            lightning_network: config.lightning_network,
+            velocity_guard: Arc::new(paykit_lib::policy::VelocityGuard::default()),
+            fee_guard: Arc::new(paykit_lib::policy::FeeGuard::default()),
+            approval_queue: RwLock::new(None),
+            proxy: config.proxy_config(),
+            lifecycle: lifecycle_ffi::Lifecycle::new(),
         }))
     }
 
+    /// Shut the client down, for example when the app is terminating.
+    ///
+    /// Stops accepting work, releases the executors, custom methods,
+    /// velocity listeners and approval queue the app registered so it can
+    /// close their connections, then waits up to `timeout_ms` for calls in
+    /// progress (payments, health checks, standing order runs) before
+    /// stopping the runtime and cancelling whatever is left on it.
+    ///
+    /// Afterwards every fallible method returns `AlreadyShutdown` and the
+    /// others return empty results. The client keeps no files of its own;
+    /// stores such as `SpendingManagerFFI` write through on every call, so
+    /// there is nothing to flush.
+    ///
+    /// Returns `NetworkTimeout` if calls were still running at the deadline.
+    /// Calling `shutdown` again retries stopping the runtime and otherwise
+    /// does nothing.
+    pub fn shutdown(&self, timeout_ms: u64) -> Result<()> {
+        let started = Instant::now();
+        let timeout = Duration::from_millis(timeout_ms);
+
+        if !self.shut_down.swap(true, Ordering::AcqRel) {
+            *self.approval_queue.write() = None;
+            self.velocity_guard.clear_callbacks();
+            self.lifecycle.clear_listeners();
+            for method_id in self.registry.list_methods() {
+                self.registry.unregister(&method_id);
+            }
+        }
+
+        let Some(mut guard) = self.runtime.try_write_for(timeout) else {
+            return Err(PaykitMobileError::NetworkTimeout {
+                msg: format!("calls still running after {} ms", timeout_ms),
+            });
+        };
+        let Some(runtime) = guard.take() else {
+            return Ok(());
+        };
+        drop(guard);
+
+        if tokio::runtime::Handle::try_current().is_ok() {
+            // Blocking here would panic
+            runtime.shutdown_background();
+        } else {
+            runtime.shutdown_timeout(timeout.saturating_sub(started.elapsed()));
+        }
+        Ok(())
+    }
+
+    /// Whether `shutdown` has been called.
+    pub fn is_shut_down(&self) -> bool {
+        self.shut_down.load(Ordering::Acquire)
+    }
+
+    /// Tell the client the app moved to the background.
+    ///
+    /// Switches to the background polling schedule, notifies lifecycle
+    /// listeners so the app can stretch or cancel its timers, and stops
+    /// `check_health` from probing nodes. Calling it again has no effect.
+    pub fn on_app_background(&self) -> Result<()> {
+        self.ensure_running()?;
+        self.lifecycle.enter_background(lifecycle_now());
+        Ok(())
+    }
+
+    /// Tell the client the app returned to the foreground.
+    ///
+    /// Restores the foreground schedule, re-checks health of every payment
+    /// method and asks lifecycle listeners to fetch the mailbox right away.
+    /// Returns the fresh health results and in-progress payment statuses so
+    /// the app can update its screens without further calls.
+    pub fn on_app_foreground(&self) -> Result<lifecycle_ffi::ForegroundRefreshFFI> {
+        self.ensure_running()?;
+        let background_secs = self.lifecycle.enter_foreground(lifecycle_now());
+        let health = self
+            .block_on(async { Ok::<_, PaykitMobileError>(self.health_monitor.check_all().await) })?
+            .into_iter()
+            .map(HealthCheckResult::from)
+            .collect();
+        self.lifecycle.request_refresh();
+        Ok(lifecycle_ffi::ForegroundRefreshFFI {
+            background_secs,
+            health,
+            in_progress_payments: self.get_in_progress_payments(),
+        })
+    }
+
+    /// Get the app state last reported to the client.
+    pub fn app_state(&self) -> lifecycle_ffi::AppStateFFI {
+        self.lifecycle.app_state()
+    }
+
+    /// Get the polling schedule for the current app state.
+    pub fn polling_schedule(&self) -> lifecycle_ffi::PollingScheduleFFI {
+        self.lifecycle.schedule()
+    }
+
+    /// Get the configured foreground and background schedules.
+    pub fn lifecycle_config(&self) -> lifecycle_ffi::LifecycleConfigFFI {
+        self.lifecycle.config()
+    }
+
+    /// Replace the foreground and background schedules.
+    ///
+    /// Listeners are told the schedule for the current state straight away.
+    pub fn set_lifecycle_config(&self, config: lifecycle_ffi::LifecycleConfigFFI) -> Result<()> {
+        self.ensure_running()?;
+        self.lifecycle.set_config(config);
+        Ok(())
+    }
+
+    /// Register a listener for schedule changes and refresh requests.
+    pub fn add_lifecycle_listener(&self, listener: Box<dyn lifecycle_ffi::LifecycleListener>) {
+        self.lifecycle.add_listener(Arc::from(listener));
+    }
+
+    /// Get the proxy a transport should use, if any.
+    pub fn proxy_for(&self, transport: TransportKindFFI) -> Option<Socks5ProxyFFI> {
+        self.proxy.for_transport(transport.into()).map(Into::into)
+    }
+
+    /// Get the proxy URL a transport should use (e.g. `socks5h://127.0.0.1:9050`), if any.
+    pub fn proxy_url_for(&self, transport: TransportKindFFI) -> Option<String> {
+        self.proxy
+            .for_transport(transport.into())
+            .map(|proxy| proxy.url())
+    }
+
     /// Get the configured Bitcoin network.
     pub fn bitcoin_network(&self) -> executor_ffi::BitcoinNetworkFFI {
         self.bitcoin_network
@@ -679,27 +1366,66 @@ impl PaykitClient {
     /// Get the list of registered payment methods.
     pub fn list_methods(&self) -> Vec<String> {
         self.registry
-            .read()
-            .unwrap()
             .list_methods()
             .into_iter()
             .map(|m| m.0)
             .collect()
     }
 
+    /// Describe the registered payment methods and what each supports.
+    pub fn describe_methods(&self) -> Vec<MethodDescriptor> {
+        self.registry
+            .describe()
+            .into_iter()
+            .map(MethodDescriptor::from)
+            .collect()
+    }
+
+    /// Replace the method weighting configuration with one given as JSON.
+    ///
+    /// The format is `{"methods": {"<method_id>": {"weight": 30,
+    /// "max_amount_sats": 500000}}}`. Weights are added to every method's
+    /// selection score, whatever the strategy.
+    pub fn load_registry_config(&self, config_json: String) -> Result<()> {
+        self.ensure_running()?;
+        let config = paykit_lib::methods::RegistryConfig::from_json(&config_json)?;
+        self.registry.set_config(config)?;
+        Ok(())
+    }
+
+    /// Get the method weighting configuration as JSON.
+    pub fn registry_config_json(&self) -> Result<String> {
+        self.ensure_running()?;
+        Ok(self.registry.config().to_json()?)
+    }
+
+    /// Set the selection weight of one method.
+    pub fn set_method_weight(&self, method_id: String, weight: MethodWeight) -> Result<()> {
+        self.ensure_running()?;
+        self.registry
+            .set_method_weight(&paykit_lib::MethodId(method_id), weight.into())?;
+        Ok(())
+    }
+
+    /// Remove the selection weight of one method, returning whether it had one.
+    pub fn remove_method_weight(&self, method_id: String) -> bool {
+        self.registry
+            .remove_method_weight(&paykit_lib::MethodId(method_id))
+            .is_some()
+    }
+
     /// Validate an endpoint for a specific method.
     pub fn validate_endpoint(&self, method_id: String, endpoint: String) -> Result<bool> {
+        self.ensure_running()?;
         let method = paykit_lib::MethodId(method_id);
         let data = paykit_lib::EndpointData(endpoint);
 
-        let plugin =
-            self.registry
-                .read()
-                .unwrap()
-                .get(&method)
-                .ok_or(PaykitMobileError::NotFound {
-                    msg: format!("Method not found: {}", method.0),
-                })?;
+        let plugin = self
+            .registry
+            .get(&method)
+            .ok_or(PaykitMobileError::NotFound {
+                msg: format!("Method not found: {}", method.0),
+            })?;
 
         let result = plugin.validate_endpoint(&data);
         Ok(result.valid)
@@ -712,6 +1438,7 @@ impl PaykitClient {
         amount_sats: u64,
         preferences: Option<SelectionPreferences>,
     ) -> Result<SelectionResult> {
+        self.ensure_running()?;
         use paykit_lib::selection::{PaymentMethodSelector, SelectionPreferences as LibPrefs};
 
         // Convert to internal types
@@ -751,12 +1478,7 @@ impl PaykitClient {
             })
             .unwrap_or_default();
 
-        let selector = PaymentMethodSelector::new(
-            self.registry
-                .read()
-                .unwrap_or_else(|e| e.into_inner())
-                .clone(),
-        );
+        let selector = PaymentMethodSelector::new(self.registry.clone());
         let result = selector
             .select(&supported, &amount, &prefs)
             .map_err(|e| PaykitMobileError::Validation { msg: e.to_string() })?;
@@ -765,29 +1487,34 @@ impl PaykitClient {
             primary_method: result.primary.0,
             fallback_methods: result.fallbacks.into_iter().map(|m| m.0).collect(),
             reason: result.reason,
+            reason_messages: result
+                .reason_messages
+                .iter()
+                .map(LocalizedMessage::from)
+                .collect(),
         })
     }
 
     /// Check health of all payment methods.
+    ///
+    /// While the app is backgrounded this returns the last results without
+    /// probing any nodes. Returns nothing once the client is shut down.
     pub fn check_health(&self) -> Vec<HealthCheckResult> {
-        self.runtime.block_on(async {
-            let results = self.health_monitor.check_all().await;
-            results
+        if self.is_shut_down() {
+            return Vec::new();
+        }
+        if self.lifecycle.is_backgrounded() {
+            return self
+                .health_monitor
+                .cached_results()
                 .into_iter()
-                .map(|r| HealthCheckResult {
-                    method_id: r.method_id.0,
-                    status: match r.status {
-                        paykit_lib::health::HealthStatus::Healthy => HealthStatus::Healthy,
-                        paykit_lib::health::HealthStatus::Degraded => HealthStatus::Degraded,
-                        paykit_lib::health::HealthStatus::Unavailable => HealthStatus::Unavailable,
-                        paykit_lib::health::HealthStatus::Unknown => HealthStatus::Unknown,
-                    },
-                    checked_at: r.checked_at,
-                    latency_ms: r.latency_ms,
-                    error: r.error,
-                })
-                .collect()
-        })
+                .map(HealthCheckResult::from)
+                .collect();
+        }
+        let results = self
+            .block_on(async { Ok::<_, PaykitMobileError>(self.health_monitor.check_all().await) })
+            .unwrap_or_default();
+        results.into_iter().map(HealthCheckResult::from).collect()
     }
 
     /// Get health status of a specific method.
@@ -812,23 +1539,7 @@ impl PaykitClient {
     pub fn get_payment_status(&self, receipt_id: String) -> Option<PaymentStatusInfo> {
         self.status_tracker
             .get(&receipt_id)
-            .map(|info| PaymentStatusInfo {
-                status: match info.status {
-                    paykit_interactive::PaymentStatus::Pending => PaymentStatus::Pending,
-                    paykit_interactive::PaymentStatus::Processing => PaymentStatus::Processing,
-                    paykit_interactive::PaymentStatus::Confirmed => PaymentStatus::Confirmed,
-                    paykit_interactive::PaymentStatus::Finalized => PaymentStatus::Finalized,
-                    paykit_interactive::PaymentStatus::Failed => PaymentStatus::Failed,
-                    paykit_interactive::PaymentStatus::Cancelled => PaymentStatus::Cancelled,
-                    paykit_interactive::PaymentStatus::Expired => PaymentStatus::Expired,
-                },
-                receipt_id: info.receipt_id,
-                method_id: info.method_id.0,
-                updated_at: info.updated_at,
-                confirmations: info.confirmations,
-                required_confirmations: info.required_confirmations,
-                error: info.error,
-            })
+            .map(PaymentStatusInfo::from)
     }
 
     /// Get all in-progress payments.
@@ -836,26 +1547,52 @@ impl PaykitClient {
         self.status_tracker
             .get_in_progress()
             .into_iter()
-            .map(|info| PaymentStatusInfo {
-                status: match info.status {
-                    paykit_interactive::PaymentStatus::Pending => PaymentStatus::Pending,
-                    paykit_interactive::PaymentStatus::Processing => PaymentStatus::Processing,
-                    paykit_interactive::PaymentStatus::Confirmed => PaymentStatus::Confirmed,
-                    paykit_interactive::PaymentStatus::Finalized => PaymentStatus::Finalized,
-                    paykit_interactive::PaymentStatus::Failed => PaymentStatus::Failed,
-                    paykit_interactive::PaymentStatus::Cancelled => PaymentStatus::Cancelled,
-                    paykit_interactive::PaymentStatus::Expired => PaymentStatus::Expired,
-                },
-                receipt_id: info.receipt_id,
-                method_id: info.method_id.0,
-                updated_at: info.updated_at,
-                confirmations: info.confirmations,
-                required_confirmations: info.required_confirmations,
-                error: info.error,
-            })
+            .map(PaymentStatusInfo::from)
             .collect()
     }
 
+    /// Configure the confirmation tiers for a payment method.
+    ///
+    /// Tiers are evaluated in order; the first matching tier decides how many
+    /// confirmations a payment needs before it is marked Finalized. Applies to
+    /// payments tracked after the call.
+    pub fn set_confirmation_tiers(&self, method_id: String, tiers: Vec<ConfirmationTier>) {
+        let mut policy = self.status_tracker.confirmation_policy();
+        policy.set_method(
+            paykit_lib::MethodId(method_id),
+            tiers.into_iter().map(Into::into).collect(),
+        );
+        self.status_tracker.set_confirmation_policy(policy);
+    }
+
+    /// Get the number of confirmations required for a payment.
+    pub fn required_confirmations(
+        &self,
+        method_id: String,
+        amount_sats: Option<u64>,
+        trusted_peer: bool,
+    ) -> u64 {
+        self.status_tracker
+            .confirmation_policy()
+            .required_confirmations(&paykit_lib::MethodId(method_id), amount_sats, trusted_peer)
+    }
+
+    /// Record observed confirmations for a tracked payment.
+    ///
+    /// Call this from the app's payment watcher. The payment is marked
+    /// Finalized once the confirmations fixed by the policy when its receipt
+    /// was created are reached. Returns `None` if the receipt is not being
+    /// tracked.
+    pub fn record_payment_confirmations(
+        &self,
+        receipt_id: String,
+        confirmations: u64,
+    ) -> Option<PaymentStatusInfo> {
+        self.status_tracker
+            .update_confirmations(&receipt_id, confirmations)
+            .map(PaymentStatusInfo::from)
+    }
+
     // ========================================================================
     // Executor Registration Methods (Bitkit Integration)
     // ========================================================================
@@ -892,6 +1629,7 @@ impl PaykitClient {
         &self,
         executor: Box<dyn executor_ffi::BitcoinExecutorFFI>,
     ) -> Result<()> {
+        self.ensure_running()?;
         // Create a bridge that wraps the FFI executor
         let bridge = executor_ffi::BitcoinExecutorBridge::new(Arc::from(executor));
 
@@ -899,13 +1637,11 @@ impl PaykitClient {
         let plugin = paykit_lib::methods::OnchainPlugin::with_network_and_executor(
             self.bitcoin_network.into(),
             Arc::new(bridge),
-        );
+        )
+        .with_fee_guard(self.fee_guard.clone());
 
-        // Register the plugin (replaces the default one)
-        self.registry
-            .write()
-            .unwrap_or_else(|e| e.into_inner())
-            .register(Box::new(plugin));
+        // Replace the default plugin
+        self.registry.replace(Box::new(plugin));
 
         Ok(())
     }
@@ -939,6 +1675,7 @@ impl PaykitClient {
         &self,
         executor: Box<dyn executor_ffi::LightningExecutorFFI>,
     ) -> Result<()> {
+        self.ensure_running()?;
         // Create a bridge that wraps the FFI executor
         let bridge = executor_ffi::LightningExecutorBridge::new(Arc::from(executor));
 
@@ -946,13 +1683,11 @@ impl PaykitClient {
         let plugin = paykit_lib::methods::LightningPlugin::with_network_and_executor(
             self.lightning_network.into(),
             Arc::new(bridge),
-        );
+        )
+        .with_fee_guard(self.fee_guard.clone());
 
-        // Register the plugin (replaces the default one)
-        self.registry
-            .write()
-            .unwrap_or_else(|e| e.into_inner())
-            .register(Box::new(plugin));
+        // Replace the default plugin
+        self.registry.replace(Box::new(plugin));
 
         Ok(())
     }
@@ -963,8 +1698,6 @@ impl PaykitClient {
     /// `register_bitcoin_executor`, this will return true.
     pub fn has_bitcoin_executor(&self) -> bool {
         self.registry
-            .read()
-            .unwrap()
             .get(&paykit_lib::MethodId("onchain".to_string()))
             .is_some()
     }
@@ -975,12 +1708,61 @@ impl PaykitClient {
     /// `register_lightning_executor`, this will return true.
     pub fn has_lightning_executor(&self) -> bool {
         self.registry
-            .read()
-            .unwrap()
             .get(&paykit_lib::MethodId("lightning".to_string()))
             .is_some()
     }
 
+    /// Register a wallet-defined payment method.
+    ///
+    /// The method takes part in selection, `execute_payment` and
+    /// `generate_payment_proof` like the built-in methods. The ID must be
+    /// namespaced (e.g. "com.acme.ledger"), which keeps it clear of the
+    /// built-in methods; use the executor registration methods for those.
+    /// Registering an existing custom method ID replaces it.
+    ///
+    /// # Arguments
+    ///
+    /// * `method_id` - Namespaced ID of the method (e.g. "com.acme.ledger")
+    /// * `display_name` - Human-readable name
+    /// * `capabilities` - What the method supports, used during selection
+    /// * `callbacks` - Implementation of `CustomMethodCallbacksFFI` from the wallet
+    pub fn register_custom_method(
+        &self,
+        method_id: String,
+        display_name: String,
+        capabilities: MethodCapabilities,
+        callbacks: Box<dyn custom_method_ffi::CustomMethodCallbacksFFI>,
+    ) -> Result<()> {
+        self.ensure_running()?;
+        let bridge = custom_method_ffi::CustomMethodBridge::new(Arc::from(callbacks));
+        let plugin = paykit_lib::methods::CustomMethodPlugin::new(
+            paykit_lib::MethodId(method_id),
+            display_name,
+            Arc::new(bridge),
+        )
+        .map_err(|e| PaykitMobileError::Validation { msg: e.to_string() })?
+        .with_capabilities(capabilities.into());
+
+        self.registry.replace(Box::new(plugin));
+
+        Ok(())
+    }
+
+    /// Remove a custom payment method.
+    ///
+    /// Returns whether the method was registered. Built-in methods cannot
+    /// be removed.
+    pub fn unregister_custom_method(&self, method_id: String) -> Result<bool> {
+        self.ensure_running()?;
+        let method_id = paykit_lib::MethodId(method_id);
+        if !method_id.is_namespaced() {
+            return Err(PaykitMobileError::Validation {
+                msg: format!("{} is not a custom method", method_id.0),
+            });
+        }
+        Ok(self.registry.unregister(&method_id).is_some())
+    }
+
     // ========================================================================
     // Payment Execution Methods
     // ========================================================================
@@ -996,6 +1778,8 @@ impl PaykitClient {
     /// * `endpoint` - Payment destination (Bitcoin address or Lightning invoice)
     /// * `amount_sats` - Amount to send in satoshis
     /// * `metadata_json` - Optional JSON metadata (e.g., fee rate preferences)
+    /// * `memo` - Optional note for the payee, sanitized and passed to the
+    ///   executor as `memo` in the metadata
     ///
     /// # Returns
     ///
@@ -1009,44 +1793,77 @@ impl PaykitClient {
     ///     "lightning",
     ///     "lnbc1000n1...",
     ///     1000,
-    ///     None
+    ///     None,
+    ///     Some("thanks for lunch".into()),
     /// )?;
     ///
     /// if result.success {
     ///     println!("Payment succeeded: {}", result.execution_id);
     /// }
     /// ```
+    #[uniffi::method(default(memo = None))]
     pub fn execute_payment(
         &self,
         method_id: String,
         endpoint: String,
         amount_sats: u64,
         metadata_json: Option<String>,
+        memo: Option<String>,
     ) -> Result<PaymentExecutionResult> {
+        self.ensure_running()?;
         let plugin = self
             .registry
-            .read()
-            .unwrap()
             .get(&paykit_lib::MethodId(method_id.clone()))
             .ok_or(PaykitMobileError::NotFound {
                 msg: format!("Payment method not registered: {}", method_id),
             })?;
 
+        // Large payments need co-signer approval first
+        let approval_queue = self.approval_queue.read().clone();
+        let approval_id = match &approval_queue {
+            Some(queue) => queue.gate_payment(&endpoint, &method_id, amount_sats)?,
+            None => None,
+        };
+
+        // Hold anomalous payments until the user approves them
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        if let paykit_lib::policy::VelocityVerdict::Hold(held) =
+            self.velocity_guard.check(&endpoint, amount_sats, now)
+        {
+            let reasons: Vec<String> = held.anomalies.iter().map(|a| a.describe()).collect();
+            return Err(PaykitMobileError::PaymentHeld {
+                msg: format!("{} ({})", held.hold_id, reasons.join(", ")),
+            });
+        }
+
         let endpoint_data = paykit_lib::EndpointData(endpoint.clone());
         let amount = paykit_lib::methods::Amount::sats(amount_sats);
 
-        let metadata: serde_json::Value = metadata_json
+        let mut metadata: serde_json::Value = metadata_json
             .as_ref()
             .map(|s| serde_json::from_str(s).unwrap_or(serde_json::json!({})))
             .unwrap_or(serde_json::json!({}));
+        if memo.is_some() {
+            paykit_interactive::metadata::set_memo_in_metadata(&mut metadata, memo.as_deref());
+        }
 
         // Execute payment asynchronously
-        let execution = self.runtime.block_on(async {
+        let execution = self.block_on(async {
             plugin
                 .execute_payment(&endpoint_data, &amount, &metadata)
                 .await
         })?;
 
+        if execution.success {
+            self.velocity_guard.record(&endpoint, amount_sats, now);
+            if let (Some(queue), Some(approval_id)) = (&approval_queue, &approval_id) {
+                queue.complete_payment(approval_id)?;
+            }
+        }
+
         Ok(PaymentExecutionResult {
             execution_id: format!("exec_{}", rand_suffix()),
             method_id,
@@ -1060,6 +1877,246 @@ impl PaykitClient {
         })
     }
 
+    // ========================================================================
+    // Approval Methods
+    // ========================================================================
+
+    /// Require co-signer approval for large payments made with `execute_payment`.
+    ///
+    /// Payments above the queue's threshold fail with `ApprovalRequired` and
+    /// are queued; once approved, the same payment can be retried.
+    pub fn set_approval_queue(&self, queue: Arc<approvals_ffi::ApprovalQueueFFI>) {
+        *self.approval_queue.write() = Some(queue);
+    }
+
+    /// Stop requiring approvals.
+    pub fn clear_approval_queue(&self) {
+        *self.approval_queue.write() = None;
+    }
+
+    /// Get the attached approval queue, if any.
+    pub fn get_approval_queue(&self) -> Option<Arc<approvals_ffi::ApprovalQueueFFI>> {
+        self.approval_queue.read().clone()
+    }
+
+    // ========================================================================
+    // Standing Order Methods
+    // ========================================================================
+
+    /// Pay a standing order from `book` if it is due at `now`.
+    ///
+    /// `supported_methods` are the payee's published methods. The order's
+    /// preferred methods are tried first, then the others it allows. Standing
+    /// orders are authorized when created, so runs skip the velocity and
+    /// approval checks of `execute_payment`.
+    pub fn run_standing_order(
+        &self,
+        book: Arc<standing_order_ffi::StandingOrderBookFFI>,
+        order_id: String,
+        supported_methods: Vec<PaymentMethod>,
+        now: i64,
+    ) -> Result<standing_order_ffi::StandingOrderRunFFI> {
+        self.ensure_running()?;
+        use paykit_lib::standing_orders::{OrderOutcome, StandingOrderScheduler};
+
+        let mut order = book.order(&order_id)?;
+        let entries = supported_methods
+            .into_iter()
+            .map(|m| {
+                (
+                    paykit_lib::MethodId(m.method_id),
+                    paykit_lib::EndpointData(m.endpoint),
+                )
+            })
+            .collect();
+        let supported = paykit_lib::SupportedPayments { entries };
+
+        let scheduler = StandingOrderScheduler::new(self.registry.clone());
+        let outcome = self.block_on(scheduler.run_at(&mut order, &supported, now))?;
+        book.update(order.clone());
+
+        let unresolved = matches!(outcome, OrderOutcome::Unresolved(_));
+        let (skipped_reason, execution) = match outcome {
+            OrderOutcome::Paid(execution)
+            | OrderOutcome::Failed(execution)
+            | OrderOutcome::Unresolved(execution) => (None, Some(execution)),
+            OrderOutcome::Skipped(skip) => (Some(skip.describe()), None),
+        };
+        Ok(standing_order_ffi::StandingOrderRunFFI {
+            order: (&order).into(),
+            skipped_reason,
+            execution: execution.map(|execution| PaymentExecutionResult {
+                execution_id: format!("exec_{}", rand_suffix()),
+                method_id: execution.method_id.0,
+                endpoint: execution.endpoint.0,
+                amount_sats: order.amount_sats,
+                success: execution.success,
+                executed_at: execution.executed_at,
+                execution_data_json: serde_json::to_string(&execution.execution_data)
+                    .unwrap_or_default(),
+                error: execution.error,
+            }),
+            unresolved,
+        })
+    }
+
+    // ========================================================================
+    // Scheduled Payment Methods
+    // ========================================================================
+
+    /// Send a scheduled payment from `book` if it is due at `now`.
+    ///
+    /// `supported_methods` are the payee's published methods. The payment's
+    /// preferred methods are tried first, skipping those the health monitor
+    /// reports as unusable. The velocity checks of `execute_payment` apply: a
+    /// held payment stays pending and is sent on a later run once the hold
+    /// is approved. A payment whose window has closed is marked expired.
+    pub fn run_scheduled_payment(
+        &self,
+        book: Arc<scheduled_payment_ffi::ScheduledPaymentBookFFI>,
+        payment_id: String,
+        supported_methods: Vec<PaymentMethod>,
+        now: i64,
+    ) -> Result<scheduled_payment_ffi::ScheduledPaymentRunFFI> {
+        self.ensure_running()?;
+        use paykit_lib::scheduled_payments::{PaymentScheduler, ScheduledOutcome};
+
+        let mut payment = book.payment(&payment_id)?;
+        let entries = supported_methods
+            .into_iter()
+            .map(|m| {
+                (
+                    paykit_lib::MethodId(m.method_id),
+                    paykit_lib::EndpointData(m.endpoint),
+                )
+            })
+            .collect();
+        let supported = paykit_lib::SupportedPayments { entries };
+
+        let scheduler = PaymentScheduler::new(self.registry.clone())
+            .with_health_monitor(self.health_monitor.clone())
+            .with_velocity_guard(self.velocity_guard.clone());
+        let outcome = self.block_on(scheduler.run_at(&mut payment, &supported, now))?;
+        book.update(payment.clone());
+
+        let unresolved = matches!(outcome, ScheduledOutcome::Unresolved(_));
+        let (skipped_reason, held, execution) = match outcome {
+            ScheduledOutcome::Paid(execution)
+            | ScheduledOutcome::Failed(execution)
+            | ScheduledOutcome::Unresolved(execution) => (None, None, Some(execution)),
+            ScheduledOutcome::Held(held) => (None, Some(held.into()), None),
+            ScheduledOutcome::Skipped(skip) => (Some(skip.describe()), None, None),
+        };
+        Ok(scheduled_payment_ffi::ScheduledPaymentRunFFI {
+            payment: (&payment).into(),
+            skipped_reason,
+            held,
+            execution: execution.map(|execution| PaymentExecutionResult {
+                execution_id: format!("exec_{}", rand_suffix()),
+                method_id: execution.method_id.0,
+                endpoint: execution.endpoint.0,
+                amount_sats: payment.amount_sats,
+                success: execution.success,
+                executed_at: execution.executed_at,
+                execution_data_json: serde_json::to_string(&execution.execution_data)
+                    .unwrap_or_default(),
+                error: execution.error,
+            }),
+            unresolved,
+        })
+    }
+
+    // ========================================================================
+    // Velocity Check Methods
+    // ========================================================================
+
+    /// Get the velocity thresholds used by `execute_payment`.
+    pub fn get_velocity_config(&self) -> velocity_ffi::VelocityConfigFFI {
+        self.velocity_guard.config().into()
+    }
+
+    /// Replace the velocity thresholds used by `execute_payment`.
+    pub fn set_velocity_config(&self, config: velocity_ffi::VelocityConfigFFI) {
+        self.velocity_guard.set_config(config.into());
+    }
+
+    /// Register a listener for held, approved and rejected payments.
+    pub fn add_velocity_listener(&self, listener: Box<dyn velocity_ffi::VelocityEventListener>) {
+        let listener: Arc<dyn velocity_ffi::VelocityEventListener> = Arc::from(listener);
+        self.velocity_guard.on_event(Arc::new(move |event| {
+            listener.on_velocity_event(event.into());
+        }));
+    }
+
+    /// List payments held for confirmation.
+    pub fn held_payments(&self) -> Vec<velocity_ffi::HeldPaymentFFI> {
+        self.velocity_guard
+            .held_payments()
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// Approve a held payment. Retrying the same payment is then allowed.
+    pub fn approve_held_payment(&self, hold_id: String) -> Result<velocity_ffi::HeldPaymentFFI> {
+        self.ensure_running()?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        self.velocity_guard
+            .approve(&hold_id, now)
+            .map(Into::into)
+            .ok_or(PaykitMobileError::NotFound {
+                msg: format!("Held payment not found: {}", hold_id),
+            })
+    }
+
+    /// Reject a held payment.
+    pub fn reject_held_payment(&self, hold_id: String) -> Result<velocity_ffi::HeldPaymentFFI> {
+        self.ensure_running()?;
+        self.velocity_guard
+            .reject(&hold_id)
+            .map(Into::into)
+            .ok_or(PaykitMobileError::NotFound {
+                msg: format!("Held payment not found: {}", hold_id),
+            })
+    }
+
+    // ========================================================================
+    // Privacy Scoring Methods
+    // ========================================================================
+
+    /// Score a payment for privacy leaks before executing it.
+    ///
+    /// Nothing is sent; apps show the returned warnings in the payment
+    /// preview so the user can pick a private endpoint or drop the memo.
+    pub fn analyze_payment_privacy(
+        &self,
+        payment: privacy_ffi::PlannedPaymentFFI,
+    ) -> privacy_ffi::PrivacyReportFFI {
+        paykit_lib::policy::PrivacyAnalyzer::default()
+            .analyze(&payment.into())
+            .into()
+    }
+
+    // ========================================================================
+    // Fee Guardrail Methods
+    // ========================================================================
+
+    /// Get the fee limits enforced by the registered executors.
+    pub fn get_fee_guardrails(&self) -> fees_ffi::FeeGuardrailsFFI {
+        self.fee_guard.guardrails().into()
+    }
+
+    /// Replace the fee limits enforced by the registered executors.
+    ///
+    /// Payments above them fail with `FeeSpike`; retry with
+    /// `"allow_fee_spike": true` in the metadata once the user confirms.
+    pub fn set_fee_guardrails(&self, guardrails: fees_ffi::FeeGuardrailsFFI) {
+        self.fee_guard.set_guardrails(guardrails.into());
+    }
+
     /// Execute a payment with automatic fallback to alternative methods.
     ///
     /// This method implements the PDF-mandated fallback behavior:
@@ -1097,6 +2154,7 @@ impl PaykitClient {
         amount_sats: u64,
         metadata_json: Option<String>,
     ) -> Result<FallbackExecutionResult> {
+        self.ensure_running()?;
         if candidates.is_empty() {
             return Ok(FallbackExecutionResult {
                 success: false,
@@ -1112,8 +2170,6 @@ impl PaykitClient {
             // Check if executor is registered
             let plugin_exists = self
                 .registry
-                .read()
-                .unwrap()
                 .get(&paykit_lib::MethodId(candidate.method_id.clone()))
                 .is_some();
 
@@ -1134,6 +2190,7 @@ impl PaykitClient {
                 candidate.endpoint.clone(),
                 amount_sats,
                 metadata_json.clone(),
+                None,
             ) {
                 Ok(result) if result.success => {
                     attempts.push(PaymentAttempt {
@@ -1231,10 +2288,9 @@ impl PaykitClient {
         method_id: String,
         execution_data_json: String,
     ) -> Result<PaymentProofResult> {
+        self.ensure_running()?;
         let plugin = self
             .registry
-            .read()
-            .unwrap()
             .get(&paykit_lib::MethodId(method_id.clone()))
             .ok_or(PaykitMobileError::NotFound {
                 msg: format!("Payment method not registered: {}", method_id),
@@ -1302,6 +2358,7 @@ impl PaykitClient {
         provider: String,
         terms: SubscriptionTerms,
     ) -> Result<Subscription> {
+        self.ensure_running()?;
         use std::str::FromStr;
 
         let subscriber_key = paykit_lib::PublicKey::from_str(&subscriber).map_err(|e| {
@@ -1345,20 +2402,141 @@ impl PaykitClient {
             terms.description.clone(),
         );
 
-        let sub = paykit_subscriptions::Subscription::new(subscriber_key, provider_key, lib_terms);
+        let sub = paykit_subscriptions::Subscription::new(subscriber_key, provider_key, lib_terms);
+
+        Ok(Subscription {
+            subscription_id: sub.subscription_id.clone(),
+            subscriber,
+            provider,
+            terms,
+            created_at: sub.created_at,
+            starts_at: sub.starts_at,
+            ends_at: sub.ends_at,
+            is_active: sub.is_active(),
+        })
+    }
+
+    /// Cancel a subscription as one of its parties.
+    ///
+    /// Signs a cancellation with the given reason code. Send `message_json`
+    /// to the counterparty and pass its reply to
+    /// `confirm_cancellation_ack`; the cancellation is final once the ack
+    /// verifies.
+    pub fn cancel_subscription(
+        &self,
+        subscription: Subscription,
+        secret_key_hex: String,
+        reason: CancellationReason,
+        note: Option<String>,
+    ) -> Result<SubscriptionCancellation> {
+        self.ensure_running()?;
+        let secret = parse_secret_key(&secret_key_hex)?;
+        let me = keys::ed25519_keypair_from_secret(secret_key_hex)?.public_key_z32;
+        let counterparty = if me == subscription.subscriber {
+            subscription.provider.clone()
+        } else if me == subscription.provider {
+            subscription.subscriber.clone()
+        } else {
+            return Err(PaykitMobileError::Validation {
+                msg: format!(
+                    "Not a party to subscription {}",
+                    subscription.subscription_id
+                ),
+            });
+        };
+
+        let cancellation = paykit_subscriptions::CancelSubscription {
+            subscription_id: subscription.subscription_id.clone(),
+            cancelled_by: parse_public_key(&me)?,
+            counterparty: parse_public_key(&counterparty)?,
+            reason: reason.into(),
+            note: note.clone(),
+            cancelled_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs() as i64,
+        };
+        let cancelled_at = cancellation.cancelled_at;
+        let signed =
+            paykit_subscriptions::SignedCancellation::sign_with_secret_key(cancellation, &secret)
+                .map_err(|e| PaykitMobileError::Internal { msg: e.to_string() })?;
 
-        Ok(Subscription {
-            subscription_id: sub.subscription_id.clone(),
-            subscriber,
-            provider,
-            terms,
-            created_at: sub.created_at,
-            starts_at: sub.starts_at,
-            ends_at: sub.ends_at,
-            is_active: sub.is_active(),
+        let cancellation_json = serde_json::to_value(&signed)
+            .map_err(|e| PaykitMobileError::Serialization { msg: e.to_string() })?;
+        let message_json = serde_json::to_string(
+            &paykit_interactive::PaykitNoiseMessage::CancelSubscription {
+                cancellation: cancellation_json.clone(),
+            },
+        )
+        .map_err(|e| PaykitMobileError::Serialization { msg: e.to_string() })?;
+
+        Ok(SubscriptionCancellation {
+            subscription_id: subscription.subscription_id,
+            cancelled_by: me,
+            counterparty,
+            reason,
+            note,
+            cancelled_at,
+            cancellation_json: cancellation_json.to_string(),
+            message_json,
         })
     }
 
+    /// Acknowledge a cancellation received from the counterparty.
+    ///
+    /// `message_json` is the received `CancelSubscription` message. Returns
+    /// the `CancelSubscriptionAck` message JSON to send back.
+    pub fn acknowledge_cancellation(
+        &self,
+        message_json: String,
+        secret_key_hex: String,
+    ) -> Result<String> {
+        self.ensure_running()?;
+        let signed = parse_cancellation_message(&message_json)?;
+        let valid = signed
+            .verify()
+            .map_err(|e| PaykitMobileError::Validation { msg: e.to_string() })?;
+        if !valid {
+            return Err(PaykitMobileError::Validation {
+                msg: "Invalid cancellation signature".to_string(),
+            });
+        }
+
+        let ack = signed
+            .acknowledge_with_secret_key(&parse_secret_key(&secret_key_hex)?)
+            .map_err(|e| PaykitMobileError::Validation { msg: e.to_string() })?;
+        let ack = serde_json::to_value(&ack)
+            .map_err(|e| PaykitMobileError::Serialization { msg: e.to_string() })?;
+        serde_json::to_string(
+            &paykit_interactive::PaykitNoiseMessage::CancelSubscriptionAck { ack },
+        )
+        .map_err(|e| PaykitMobileError::Serialization { msg: e.to_string() })
+    }
+
+    /// Check that a counterparty's reply acknowledges our cancellation.
+    ///
+    /// `cancellation_json` is from `cancel_subscription`; `reply_json` is the
+    /// received message. Returns false for any reply other than a valid ack.
+    pub fn confirm_cancellation_ack(
+        &self,
+        cancellation_json: String,
+        reply_json: String,
+    ) -> Result<bool> {
+        self.ensure_running()?;
+        let signed: paykit_subscriptions::SignedCancellation =
+            serde_json::from_str(&cancellation_json)
+                .map_err(|e| PaykitMobileError::Serialization { msg: e.to_string() })?;
+        let ack = match serde_json::from_str(&reply_json) {
+            Ok(paykit_interactive::PaykitNoiseMessage::CancelSubscriptionAck { ack }) => ack,
+            _ => return Ok(false),
+        };
+        let ack: paykit_subscriptions::SignedCancellationAck = match serde_json::from_value(ack) {
+            Ok(ack) => ack,
+            Err(_) => return Ok(false),
+        };
+        ack.verify_for(&signed)
+            .map_err(|e| PaykitMobileError::Validation { msg: e.to_string() })
+    }
     /// Calculate proration for a subscription modification.
     pub fn calculate_proration(
         &self,
@@ -1368,6 +2546,7 @@ impl PaykitClient {
         period_end: i64,
         change_date: i64,
     ) -> Result<ProrationResult> {
+        self.ensure_running()?;
         let calculator = paykit_subscriptions::ProrationCalculator::new();
 
         let result = calculator
@@ -1389,6 +2568,68 @@ impl PaykitClient {
         })
     }
 
+    /// Calculate proration with a currency and rounding mode.
+    ///
+    /// Time is counted in whole days; use `calculate_proration_by_seconds`
+    /// for Custom-frequency subscriptions.
+    #[allow(clippy::too_many_arguments)]
+    pub fn calculate_proration_detailed(
+        &self,
+        current_amount: i64,
+        new_amount: i64,
+        period_start: i64,
+        period_end: i64,
+        change_date: i64,
+        currency: String,
+        rounding_mode: RoundingMode,
+    ) -> Result<ProrationBreakdown> {
+        self.ensure_running()?;
+        let calculator =
+            paykit_subscriptions::ProrationCalculator::new().with_rounding(rounding_mode.into());
+        let result = calculator
+            .calculate(
+                &paykit_subscriptions::Amount::from_sats(current_amount),
+                &paykit_subscriptions::Amount::from_sats(new_amount),
+                period_start,
+                period_end,
+                change_date,
+                &currency,
+            )
+            .map_err(|e| PaykitMobileError::Validation { msg: e.to_string() })?;
+        Ok(proration_breakdown(result, rounding_mode))
+    }
+
+    /// Calculate proration counting time in seconds.
+    ///
+    /// For Custom-frequency subscriptions, whose periods need not be a whole
+    /// number of days.
+    #[allow(clippy::too_many_arguments)]
+    pub fn calculate_proration_by_seconds(
+        &self,
+        current_amount: i64,
+        new_amount: i64,
+        period_start: i64,
+        period_end: i64,
+        change_date: i64,
+        currency: String,
+        rounding_mode: RoundingMode,
+    ) -> Result<ProrationBreakdown> {
+        self.ensure_running()?;
+        let calculator =
+            paykit_subscriptions::ProrationCalculator::new().with_rounding(rounding_mode.into());
+        let result = calculator
+            .calculate_by_seconds(
+                &paykit_subscriptions::Amount::from_sats(current_amount),
+                &paykit_subscriptions::Amount::from_sats(new_amount),
+                period_start,
+                period_end,
+                change_date,
+                &currency,
+            )
+            .map_err(|e| PaykitMobileError::Validation { msg: e.to_string() })?;
+        Ok(proration_breakdown(result, rounding_mode))
+    }
+
     /// Get days remaining in current billing period.
     pub fn days_remaining_in_period(&self, period_end: i64) -> u32 {
         let now = std::time::SystemTime::now()
@@ -1419,6 +2660,7 @@ impl PaykitClient {
         description: String,
         expires_in_secs: Option<u64>,
     ) -> Result<PaymentRequest> {
+        self.ensure_running()?;
         use std::str::FromStr;
 
         let from_key = paykit_lib::PublicKey::from_str(&from_pubkey).map_err(|e| {
@@ -1466,6 +2708,13 @@ impl PaykitClient {
     // ========================================================================
 
     /// Create a new receipt.
+    ///
+    /// `memo` is the payer's note for the payee; it is sanitized and stored
+    /// as `memo` in the receipt metadata. The payment's status is tracked
+    /// from here on; `trusted_peer` says whether the other party is trusted,
+    /// which the confirmation tiers may use to need fewer confirmations.
+    #[uniffi::method(default(memo = None, trusted_peer = false))]
+    #[allow(clippy::too_many_arguments)]
     pub fn create_receipt(
         &self,
         payer: String,
@@ -1473,7 +2722,10 @@ impl PaykitClient {
         method_id: String,
         amount: Option<String>,
         currency: Option<String>,
+        memo: Option<String>,
+        trusted_peer: bool,
     ) -> Result<Receipt> {
+        self.ensure_running()?;
         use std::str::FromStr;
 
         let payer_key =
@@ -1501,6 +2753,11 @@ impl PaykitClient {
             currency.clone(),
             serde_json::json!({}),
         );
+        let receipt = match &memo {
+            Some(memo) => receipt.with_memo(memo),
+            None => receipt,
+        };
+        self.status_tracker.track_from_peer(&receipt, trusted_peer);
 
         Ok(Receipt {
             receipt_id,
@@ -1510,12 +2767,13 @@ impl PaykitClient {
             amount,
             currency,
             created_at: receipt.created_at,
-            metadata_json: "{}".to_string(),
+            metadata_json: receipt.metadata.to_string(),
         })
     }
 
     /// Parse receipt metadata as JSON.
     pub fn parse_receipt_metadata(&self, metadata_json: String) -> Result<String> {
+        self.ensure_running()?;
         // Validate JSON
         serde_json::from_str::<serde_json::Value>(&metadata_json)
             .map_err(|e| PaykitMobileError::Serialization { msg: e.to_string() })?;
@@ -1528,6 +2786,7 @@ impl PaykitClient {
 
     /// Parse scanned QR code data as a Paykit URI.
     pub fn parse_scanned_qr(&self, scanned_data: String) -> Result<scanner::ScannedUri> {
+        self.ensure_running()?;
         scanner::parse_scanned_uri(scanned_data)
             .map_err(|e| PaykitMobileError::Validation { msg: e })
     }
@@ -1571,6 +2830,7 @@ impl PaykitClient {
         method_id: String,
         endpoint_data: String,
     ) -> Result<()> {
+        self.ensure_running()?;
         transport_ffi::publish_payment_endpoint(&transport, &method_id, &endpoint_data)
     }
 
@@ -1585,6 +2845,7 @@ impl PaykitClient {
         transport: Arc<AuthenticatedTransportFFI>,
         method_id: String,
     ) -> Result<()> {
+        self.ensure_running()?;
         transport_ffi::remove_payment_endpoint(&transport, &method_id)
     }
 
@@ -1613,6 +2874,7 @@ impl PaykitClient {
         transport: Arc<UnauthenticatedTransportFFI>,
         owner_pubkey: String,
     ) -> Result<Vec<PaymentMethod>> {
+        self.ensure_running()?;
         transport_ffi::fetch_supported_payments(&transport, &owner_pubkey)
     }
 
@@ -1633,6 +2895,7 @@ impl PaykitClient {
         owner_pubkey: String,
         method_id: String,
     ) -> Result<Option<String>> {
+        self.ensure_running()?;
         transport_ffi::fetch_payment_endpoint(&transport, &owner_pubkey, &method_id)
     }
 
@@ -1651,9 +2914,46 @@ impl PaykitClient {
         transport: Arc<UnauthenticatedTransportFFI>,
         owner_pubkey: String,
     ) -> Result<Vec<String>> {
+        self.ensure_running()?;
         transport_ffi::fetch_known_contacts(&transport, &owner_pubkey)
     }
 
+    /// Fetch everything checkout needs about a payee in one call.
+    ///
+    /// Supported payments, the payee status and the Noise endpoint are read
+    /// concurrently. Use `PayeePrefetcherFFI` to keep snapshots cached.
+    ///
+    /// # Arguments
+    ///
+    /// * `transport` - Unauthenticated transport for reading
+    /// * `owner_pubkey` - The payee's public key
+    pub fn prefetch_payee(
+        &self,
+        transport: Arc<UnauthenticatedTransportFFI>,
+        owner_pubkey: String,
+    ) -> PayeeSnapshotFFI {
+        prefetch_ffi::prefetch_payee(transport, owner_pubkey)
+    }
+
+    /// Resolve a handle such as `alice@example.com` to a verified public key.
+    ///
+    /// Use the returned `public_key` wherever a payee key is expected, e.g.
+    /// `fetch_supported_payments`. Results are cached by the resolver.
+    ///
+    /// # Arguments
+    ///
+    /// * `resolver` - Resolver wrapping the app's HTTP and DNS lookups
+    /// * `handle` - The handle, `user@domain`
+    pub fn resolve_handle(
+        &self,
+        resolver: Arc<HandleResolverFFI>,
+        handle: String,
+    ) -> Result<ResolvedHandleFFI> {
+        self.ensure_running()?;
+        let resolved = self.block_on(resolver.resolver().resolve(&handle))?;
+        Ok(resolved.into())
+    }
+
     // ========================================================================
     // Contact Management
     // ========================================================================
@@ -1669,6 +2969,7 @@ impl PaykitClient {
         transport: Arc<AuthenticatedTransportFFI>,
         contact_pubkey: String,
     ) -> Result<()> {
+        self.ensure_running()?;
         transport_ffi::add_contact(&transport, &contact_pubkey)
     }
 
@@ -1683,6 +2984,7 @@ impl PaykitClient {
         transport: Arc<AuthenticatedTransportFFI>,
         contact_pubkey: String,
     ) -> Result<()> {
+        self.ensure_running()?;
         transport_ffi::remove_contact(&transport, &contact_pubkey)
     }
 
@@ -1696,6 +2998,7 @@ impl PaykitClient {
     ///
     /// List of contact public keys.
     pub fn list_contacts(&self, transport: Arc<AuthenticatedTransportFFI>) -> Result<Vec<String>> {
+        self.ensure_running()?;
         transport_ffi::list_contacts(&transport)
     }
 
@@ -1729,6 +3032,7 @@ impl PaykitClient {
         transport: Arc<UnauthenticatedTransportFFI>,
         recipient_pubkey: String,
     ) -> Result<Option<NoiseEndpointInfo>> {
+        self.ensure_running()?;
         noise_ffi::discover_noise_endpoint(transport, recipient_pubkey)
     }
 
@@ -1751,6 +3055,7 @@ impl PaykitClient {
         noise_pubkey: String,
         metadata: Option<String>,
     ) -> Result<()> {
+        self.ensure_running()?;
         noise_ffi::publish_noise_endpoint(transport, host, port, noise_pubkey, metadata)
     }
 
@@ -1762,6 +3067,7 @@ impl PaykitClient {
     ///
     /// * `transport` - Authenticated transport for writing
     pub fn remove_noise_endpoint(&self, transport: Arc<AuthenticatedTransportFFI>) -> Result<()> {
+        self.ensure_running()?;
         noise_ffi::remove_noise_endpoint(transport)
     }
 
@@ -1784,6 +3090,7 @@ impl PaykitClient {
         amount: Option<String>,
         currency: Option<String>,
     ) -> Result<NoisePaymentMessage> {
+        self.ensure_running()?;
         noise_ffi::create_receipt_request_message(
             receipt_id,
             payer_pubkey,
@@ -1816,6 +3123,7 @@ impl PaykitClient {
         currency: Option<String>,
         signature: Option<String>,
     ) -> Result<NoisePaymentMessage> {
+        self.ensure_running()?;
         noise_ffi::create_receipt_confirmation_message(
             receipt_id,
             payer_pubkey,
@@ -1838,6 +3146,7 @@ impl PaykitClient {
         code: String,
         message: String,
     ) -> Result<NoisePaymentMessage> {
+        self.ensure_running()?;
         noise_ffi::create_error_message(code, message)
     }
 
@@ -1847,6 +3156,7 @@ impl PaykitClient {
     ///
     /// * `json` - The JSON string to parse
     pub fn parse_noise_payment_message(&self, json: String) -> Result<NoisePaymentMessage> {
+        self.ensure_running()?;
         noise_ffi::parse_payment_message(json)
     }
 }
@@ -1867,6 +3177,62 @@ pub fn get_version() -> String {
     env!("CARGO_PKG_VERSION").to_string()
 }
 
+/// Format a message for a locale tag such as "es-MX".
+///
+/// Unsupported languages fall back to English.
+#[uniffi::export]
+pub fn localize_message(message: LocalizedMessage, locale: String) -> String {
+    paykit_lib::i18n::LocalizedMessage {
+        code: message.code,
+        params: message.params.into_iter().collect(),
+    }
+    .format(paykit_lib::i18n::Locale::from_tag(&locale))
+}
+
+/// Language codes with built-in translations.
+#[uniffi::export]
+pub fn supported_locales() -> Vec<String> {
+    paykit_lib::i18n::Locale::ALL
+        .iter()
+        .map(|locale| locale.as_str().to_string())
+        .collect()
+}
+
+/// Format satoshis with digit grouping, e.g. "1,234,567 sats".
+#[uniffi::export]
+pub fn format_sats(sats: u64, locale: String) -> String {
+    paykit_lib::formatting::format_sats(sats, paykit_lib::i18n::Locale::from_tag(&locale))
+}
+
+/// Format satoshis as BTC with eight decimals, e.g. "0.01234567 BTC".
+#[uniffi::export]
+pub fn format_btc(sats: u64, locale: String) -> String {
+    paykit_lib::formatting::format_btc(sats, paykit_lib::i18n::Locale::from_tag(&locale))
+}
+
+/// Format satoshis in short form, e.g. "1.2M sats".
+#[uniffi::export]
+pub fn format_compact_sats(sats: u64, locale: String) -> String {
+    paykit_lib::formatting::format_compact_sats(sats, paykit_lib::i18n::Locale::from_tag(&locale))
+}
+
+/// Format a fiat amount, e.g. "$1,234.50" or "1.234,50 €".
+#[uniffi::export]
+pub fn format_fiat(amount: f64, currency: String, locale: String) -> String {
+    paykit_lib::formatting::format_fiat(
+        amount,
+        &currency,
+        paykit_lib::i18n::Locale::from_tag(&locale),
+    )
+}
+
+/// Parse a BTC decimal string such as "0.001" into satoshis.
+#[uniffi::export]
+pub fn btc_to_sats(btc: String) -> Result<u64> {
+    paykit_lib::formatting::btc_to_sats(&btc)
+        .map_err(|e| PaykitMobileError::Validation { msg: e.to_string() })
+}
+
 /// Generate a random suffix for IDs.
 fn rand_suffix() -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -1877,6 +3243,69 @@ fn rand_suffix() -> String {
     format!("{:08x}", nanos)
 }
 
+/// Convert a proration result to its FFI breakdown.
+fn proration_breakdown(
+    result: paykit_subscriptions::ProratedAmount,
+    rounding_mode: RoundingMode,
+) -> ProrationBreakdown {
+    let details = &result.details;
+    ProrationBreakdown {
+        credit: result.credit.as_sats(),
+        charge: result.charge.as_sats(),
+        net: result.net_amount.as_sats(),
+        is_refund: result.is_refund(),
+        currency: result.currency.clone(),
+        rounding_mode,
+        old_daily_rate: details.old_daily_rate().normalize().to_string(),
+        new_daily_rate: details.new_daily_rate().normalize().to_string(),
+        total_days: details.total_days,
+        days_used: details.days_at_old_rate,
+        days_remaining: details.days_at_new_rate,
+        seconds_remaining: details.seconds_remaining(),
+        period_start: details.period_start,
+        period_end: details.period_end,
+        change_date: details.change_date,
+    }
+}
+
+/// Parse a hex-encoded Ed25519 secret key.
+fn parse_secret_key(secret_key_hex: &str) -> Result<[u8; 32]> {
+    let bytes = hex::decode(secret_key_hex).map_err(|e| PaykitMobileError::Validation {
+        msg: format!("Invalid hex: {}", e),
+    })?;
+    bytes.try_into().map_err(|_| PaykitMobileError::Validation {
+        msg: "Secret key must be 32 bytes".to_string(),
+    })
+}
+
+/// Parse a z-base-32 public key.
+fn parse_public_key(key: &str) -> Result<paykit_lib::PublicKey> {
+    use std::str::FromStr;
+
+    paykit_lib::PublicKey::from_str(key).map_err(|e| PaykitMobileError::Validation {
+        msg: format!("Invalid public key: {}", e),
+    })
+}
+
+/// Extract the signed cancellation from a `CancelSubscription` message.
+fn parse_cancellation_message(
+    message_json: &str,
+) -> Result<paykit_subscriptions::SignedCancellation> {
+    let message: paykit_interactive::PaykitNoiseMessage = serde_json::from_str(message_json)
+        .map_err(|e| PaykitMobileError::Validation {
+            msg: format!("Invalid message JSON: {}", e),
+        })?;
+    match message {
+        paykit_interactive::PaykitNoiseMessage::CancelSubscription { cancellation } => {
+            serde_json::from_value(cancellation)
+                .map_err(|e| PaykitMobileError::Serialization { msg: e.to_string() })
+        }
+        _ => Err(PaykitMobileError::Validation {
+            msg: "Not a CancelSubscription message".to_string(),
+        }),
+    }
+}
+
 /// Classify an error from `PaymentExecutionResult.error` to determine if retryable.
 ///
 /// Returns (retryable, error_message).
@@ -1946,6 +3375,14 @@ fn classify_mobile_error(error: &PaykitMobileError) -> (bool, String) {
         PaykitMobileError::AuthenticationError { .. } => (false, msg),
         PaykitMobileError::PermissionDenied { .. } => (false, msg),
 
+        // Held payments need the user, not another method
+        PaykitMobileError::PaymentHeld { .. } => (false, msg),
+        PaykitMobileError::ApprovalRequired { .. } => (false, msg),
+        PaykitMobileError::FeeSpike { .. } => (false, msg),
+
+        // No other method will work on a shut down client
+        PaykitMobileError::AlreadyShutdown { .. } => (false, msg),
+
         // Rate limits - retryable but might hit same limit
         PaykitMobileError::RateLimitError { .. } => (true, msg),
 
@@ -1973,6 +3410,141 @@ mod tests {
         assert!(methods.contains(&"lightning".to_string()));
     }
 
+    #[test]
+    fn test_describe_methods() {
+        let client = PaykitClient::new().unwrap();
+        let methods = client.describe_methods();
+        let lightning = methods.iter().find(|m| m.method_id == "lightning").unwrap();
+        assert_eq!(lightning.capabilities.settlement, SettlementKind::OffChain);
+        assert!(lightning.capabilities.route_probing);
+        // No executor registered yet
+        assert!(!lightning.capabilities.execution);
+    }
+
+    #[test]
+    fn test_method_weights() {
+        let client = PaykitClient::new().unwrap();
+        client
+            .set_method_weight(
+                "onchain".to_string(),
+                MethodWeight {
+                    weight: 100.0,
+                    min_amount_sats: None,
+                    max_amount_sats: None,
+                },
+            )
+            .unwrap();
+        let json = client.registry_config_json().unwrap();
+        assert!(json.contains("onchain"));
+
+        let empty = r#"{"methods":{}}"#;
+        client.load_registry_config(empty.to_string()).unwrap();
+        assert!(!client.remove_method_weight("onchain".to_string()));
+
+        let inverted =
+            r#"{"methods":{"onchain":{"weight":1,"min_amount_sats":9,"max_amount_sats":1}}}"#;
+        assert!(client.load_registry_config(inverted.to_string()).is_err());
+    }
+
+    struct EchoLedger;
+
+    impl CustomMethodCallbacksFFI for EchoLedger {
+        fn validate_endpoint(&self, endpoint: String) -> Vec<String> {
+            if endpoint.starts_with("acct:") {
+                Vec::new()
+            } else {
+                vec!["Expected acct: prefix".to_string()]
+            }
+        }
+
+        fn execute_payment(
+            &self,
+            endpoint: String,
+            amount_sats: u64,
+            _metadata_json: String,
+        ) -> Result<String> {
+            Ok(format!(
+                r#"{{"transfer":"{}","amount_sats":{}}}"#,
+                endpoint, amount_sats
+            ))
+        }
+
+        fn generate_proof(&self, execution_data_json: String) -> Result<String> {
+            Ok(execution_data_json)
+        }
+    }
+
+    #[test]
+    fn test_register_custom_method() {
+        let client = PaykitClient::new().unwrap();
+        let capabilities = MethodCapabilities {
+            settlement: SettlementKind::Other,
+            execution: true,
+            proofs: true,
+            private_endpoints: false,
+            route_probing: false,
+            min_amount_sats: None,
+            max_amount_sats: Some(10_000),
+        };
+
+        assert!(client
+            .register_custom_method(
+                "onchain".to_string(),
+                "Fake".to_string(),
+                capabilities.clone(),
+                Box::new(EchoLedger),
+            )
+            .is_err());
+
+        client
+            .register_custom_method(
+                "org.example.ledger".to_string(),
+                "Ledger".to_string(),
+                capabilities,
+                Box::new(EchoLedger),
+            )
+            .unwrap();
+        assert!(client
+            .list_methods()
+            .contains(&"org.example.ledger".to_string()));
+        assert!(client
+            .validate_endpoint("org.example.ledger".to_string(), "acct:42".to_string())
+            .unwrap());
+
+        let result = client
+            .execute_payment(
+                "org.example.ledger".to_string(),
+                "acct:42".to_string(),
+                500,
+                None,
+                None,
+            )
+            .unwrap();
+        assert!(result.success);
+        let proof = client
+            .generate_payment_proof("org.example.ledger".to_string(), result.execution_data_json)
+            .unwrap();
+        assert!(proof.proof_data_json.contains("acct:42"));
+
+        // Over the declared limit
+        assert!(client
+            .execute_payment(
+                "org.example.ledger".to_string(),
+                "acct:42".to_string(),
+                20_000,
+                None,
+                None
+            )
+            .is_err());
+
+        assert!(client
+            .unregister_custom_method("org.example.ledger".to_string())
+            .unwrap());
+        assert!(client
+            .unregister_custom_method("lightning".to_string())
+            .is_err());
+    }
+
     #[test]
     fn test_validate_endpoint() {
         let client = PaykitClient::new().unwrap();
@@ -2006,6 +3578,15 @@ mod tests {
 
         let selection = result.unwrap();
         assert_eq!(selection.primary_method, "lightning");
+
+        let localized: Vec<String> = selection
+            .reason_messages
+            .iter()
+            .map(|m| localize_message(m.clone(), "en-US".to_string()))
+            .collect();
+        assert_eq!(localized.join(" "), selection.reason);
+        let german = localize_message(selection.reason_messages[0].clone(), "de".to_string());
+        assert!(german.contains("ausgewählt"));
     }
 
     #[test]
@@ -2015,6 +3596,85 @@ mod tests {
         assert!(!results.is_empty());
     }
 
+    #[test]
+    fn test_shutdown_rejects_later_calls() {
+        let client = PaykitClient::new().unwrap();
+        client.shutdown(1_000).unwrap();
+        assert!(client.is_shut_down());
+
+        assert!(matches!(
+            client.validate_endpoint("lightning".to_string(), "lnbc1...".to_string()),
+            Err(PaykitMobileError::AlreadyShutdown { .. })
+        ));
+        assert!(client.check_health().is_empty());
+        assert!(client.list_methods().is_empty());
+
+        // Shutting down again is harmless
+        client.shutdown(1_000).unwrap();
+    }
+
+    #[test]
+    fn test_shutdown_deadline_with_call_in_progress() {
+        let client = PaykitClient::new().unwrap();
+
+        // Stand in for a payment still running on the runtime
+        let in_flight = client.runtime.read();
+        assert!(matches!(
+            client.shutdown(10),
+            Err(PaykitMobileError::NetworkTimeout { .. })
+        ));
+        assert!(client.is_shut_down());
+        drop(in_flight);
+
+        client.shutdown(1_000).unwrap();
+        assert!(client.runtime.read().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_and_drop_inside_async_context() {
+        // Blocking on a runtime shutdown here would panic
+        let client = PaykitClient::new().unwrap();
+        client.shutdown(1_000).unwrap();
+
+        let client = PaykitClient::new().unwrap();
+        assert!(!client.is_shut_down());
+        drop(client);
+    }
+
+    #[test]
+    fn test_background_serves_cached_health() {
+        let client = PaykitClient::new().unwrap();
+        client.on_app_background().unwrap();
+        assert_eq!(client.app_state(), AppStateFFI::Background);
+        assert_eq!(client.polling_schedule().keepalive_secs, 0);
+
+        // Nothing has been checked yet and backgrounded calls don't probe
+        assert!(client.check_health().is_empty());
+
+        let refresh = client.on_app_foreground().unwrap();
+        assert_eq!(client.app_state(), AppStateFFI::Foreground);
+        assert_eq!(refresh.health.len(), 2);
+        assert!(refresh.in_progress_payments.is_empty());
+
+        client.on_app_background().unwrap();
+        assert_eq!(client.check_health().len(), 2);
+    }
+
+    #[test]
+    fn test_lifecycle_after_shutdown() {
+        let client = PaykitClient::new().unwrap();
+        client.shutdown(1_000).unwrap();
+
+        assert!(matches!(
+            client.on_app_background(),
+            Err(PaykitMobileError::AlreadyShutdown { .. })
+        ));
+        assert!(matches!(
+            client.on_app_foreground(),
+            Err(PaykitMobileError::AlreadyShutdown { .. })
+        ));
+    }
+
     #[test]
     fn test_calculate_proration() {
         let client = PaykitClient::new().unwrap();
@@ -2038,6 +3698,103 @@ mod tests {
         assert!(!proration.is_refund);
     }
 
+    #[test]
+    fn test_cancel_subscription_acknowledged() {
+        let client = PaykitClient::new().unwrap();
+        let subscriber = keys::generate_ed25519_keypair().unwrap();
+        let provider = keys::generate_ed25519_keypair().unwrap();
+        let subscription = client
+            .create_subscription(
+                subscriber.public_key_z32.clone(),
+                provider.public_key_z32.clone(),
+                SubscriptionTerms {
+                    amount_sats: 1000,
+                    currency: "SAT".to_string(),
+                    frequency: PaymentFrequency::Monthly { day_of_month: 1 },
+                    method_id: "lightning".to_string(),
+                    description: "Pro plan".to_string(),
+                },
+            )
+            .unwrap();
+
+        let cancellation = client
+            .cancel_subscription(
+                subscription.clone(),
+                subscriber.secret_key_hex.clone(),
+                CancellationReason::TooExpensive,
+                Some("Over budget".to_string()),
+            )
+            .unwrap();
+        assert_eq!(cancellation.counterparty, provider.public_key_z32);
+
+        // Only the counterparty can acknowledge
+        assert!(client
+            .acknowledge_cancellation(
+                cancellation.message_json.clone(),
+                subscriber.secret_key_hex.clone(),
+            )
+            .is_err());
+        let ack = client
+            .acknowledge_cancellation(cancellation.message_json.clone(), provider.secret_key_hex)
+            .unwrap();
+        assert!(client
+            .confirm_cancellation_ack(cancellation.cancellation_json.clone(), ack)
+            .unwrap());
+        assert!(!client
+            .confirm_cancellation_ack(
+                cancellation.cancellation_json,
+                r#"{"type":"Ack","payload":null}"#.to_string(),
+            )
+            .unwrap());
+
+        let outsider = keys::generate_ed25519_keypair().unwrap();
+        assert!(client
+            .cancel_subscription(
+                subscription,
+                outsider.secret_key_hex,
+                CancellationReason::Other,
+                None,
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_calculate_proration_by_seconds() {
+        let client = PaykitClient::new().unwrap();
+
+        // 12-hour custom period, downgrade after 3 hours
+        let result = client
+            .calculate_proration_by_seconds(
+                4320,
+                2160,
+                0,
+                12 * 3600,
+                3 * 3600,
+                "USD".to_string(),
+                RoundingMode::Up,
+            )
+            .unwrap();
+        assert_eq!(result.credit, 3240);
+        assert_eq!(result.charge, 1620);
+        assert!(result.is_refund);
+        assert_eq!(result.currency, "USD");
+        assert_eq!(result.old_daily_rate, "8640");
+        assert_eq!(result.seconds_remaining, 9 * 3600);
+
+        // Whole-day proration rejects sub-day periods
+        assert!(client
+            .calculate_proration_detailed(
+                4320,
+                2160,
+                0,
+                12 * 3600,
+                3 * 3600,
+                "USD".to_string(),
+                RoundingMode::Up,
+            )
+            .is_err());
+    }
+
     #[test]
     fn test_days_remaining() {
         let client = PaykitClient::new().unwrap();
@@ -2260,6 +4017,53 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_create_receipt_with_memo() {
+        let client = PaykitClient::new().unwrap();
+        let payer = keys::generate_ed25519_keypair().unwrap().public_key_z32;
+        let payee = keys::generate_ed25519_keypair().unwrap().public_key_z32;
+
+        let receipt = client
+            .create_receipt(
+                payer,
+                payee,
+                "lightning".to_string(),
+                Some("1000".to_string()),
+                Some("SAT".to_string()),
+                Some("thanks\nfor lunch\u{202E}".to_string()),
+                false,
+            )
+            .unwrap();
+        let metadata: serde_json::Value = serde_json::from_str(&receipt.metadata_json).unwrap();
+        assert_eq!(metadata["memo"], "thanks for lunch");
+    }
+
+    #[test]
+    fn test_created_receipts_are_tracked() {
+        let client = PaykitClient::new().unwrap();
+        let payer = keys::generate_ed25519_keypair().unwrap().public_key_z32;
+        let payee = keys::generate_ed25519_keypair().unwrap().public_key_z32;
+
+        let receipt = client
+            .create_receipt(
+                payer,
+                payee,
+                "onchain".to_string(),
+                Some("10000".to_string()),
+                Some("SAT".to_string()),
+                None,
+                true,
+            )
+            .unwrap();
+        let status = client.get_payment_status(receipt.receipt_id.clone()).unwrap();
+        assert_eq!(status.required_confirmations, Some(0));
+
+        let status = client
+            .record_payment_confirmations(receipt.receipt_id, 0)
+            .unwrap();
+        assert!(matches!(status.status, PaymentStatus::Finalized));
+    }
+
     #[test]
     fn test_create_receipt_request_message() {
         let client = PaykitClient::new().unwrap();
@@ -2393,6 +4197,7 @@ mod tests {
                 _address: String,
                 _amount_sats: u64,
                 _fee_rate: Option<f64>,
+                _coin_selection: Option<executor_ffi::CoinSelectionFFI>,
             ) -> Result<executor_ffi::BitcoinTxResultFFI> {
                 self.call_count.fetch_add(1, Ordering::SeqCst);
                 Ok(executor_ffi::BitcoinTxResultFFI::new(
@@ -2526,6 +4331,7 @@ mod tests {
                 _address: String,
                 _amount_sats: u64,
                 _fee_rate: Option<f64>,
+                _coin_selection: Option<executor_ffi::CoinSelectionFFI>,
             ) -> Result<executor_ffi::BitcoinTxResultFFI> {
                 Ok(executor_ffi::BitcoinTxResultFFI {
                     txid: "abc123def456".to_string(),
@@ -2581,6 +4387,7 @@ mod tests {
                 "tb1qtest123".to_string(),
                 10000,
                 None,
+                None,
             )
             .unwrap();
 
@@ -2656,7 +4463,7 @@ mod tests {
         );
 
         let result = client
-            .execute_payment("lightning".to_string(), mock_invoice, 1000, None)
+            .execute_payment("lightning".to_string(), mock_invoice, 1000, None, None)
             .unwrap();
 
         assert!(result.success);
@@ -2673,6 +4480,7 @@ mod tests {
             "some_endpoint".to_string(),
             1000,
             None,
+            None,
         );
 
         assert!(result.is_err());
@@ -0,0 +1,310 @@
+//! App Lifecycle FFI Types
+//!
+//! Lets the host app tell [`PaykitClient`](crate::PaykitClient) when it moves
+//! between foreground and background, so one pair of calls controls how much
+//! work Paykit does instead of the app throttling each subsystem itself.
+//!
+//! Paykit does not run timers of its own: the app polls inboxes, checks
+//! payment status and keeps transport connections alive. The client owns
+//! the schedule for that work and tells registered listeners whenever it
+//! changes, so the app only has to follow it.
+//!
+//! While backgrounded, `check_health()` serves cached results instead of
+//! probing nodes. On foreground the client re-checks health, collects
+//! in-progress payment statuses and asks listeners to fetch the mailbox
+//! right away.
+//!
+//! # Example Flow
+//!
+//! ```ignore
+//! client.add_lifecycle_listener(Box::new(MyScheduler));
+//!
+//! // applicationDidEnterBackground / onStop
+//! client.on_app_background()?;
+//! // MyScheduler::on_schedule_changed stretches or cancels its timers
+//!
+//! // applicationWillEnterForeground / onStart
+//! let refresh = client.on_app_foreground()?;
+//! show_health(refresh.health);
+//! // MyScheduler::on_refresh_requested fetches the inbox now
+//! ```
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Whether the host app is visible to the user.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, uniffi::Enum)]
+pub enum AppStateFFI {
+    Foreground,
+    Background,
+}
+
+/// How often the app should run each kind of background work.
+///
+/// An interval of 0 means the work is paused.
+#[derive(Clone, Debug, PartialEq, Eq, uniffi::Record)]
+pub struct PollingScheduleFFI {
+    /// Seconds between inbox / mailbox fetches
+    pub inbox_poll_secs: u64,
+    /// Seconds between payment status and retransmission checks
+    pub status_poll_secs: u64,
+    /// Seconds between payment method health checks
+    pub health_check_secs: u64,
+    /// Seconds between keepalives on open transport connections
+    pub keepalive_secs: u64,
+}
+
+/// Polling schedules for each app state.
+#[derive(Clone, Debug, PartialEq, Eq, uniffi::Record)]
+pub struct LifecycleConfigFFI {
+    /// Schedule while the app is in the foreground
+    pub foreground: PollingScheduleFFI,
+    /// Schedule while the app is in the background
+    pub background: PollingScheduleFFI,
+}
+
+impl LifecycleConfigFFI {
+    fn schedule_for(&self, state: AppStateFFI) -> PollingScheduleFFI {
+        match state {
+            AppStateFFI::Foreground => self.foreground.clone(),
+            AppStateFFI::Background => self.background.clone(),
+        }
+    }
+}
+
+/// What the client refreshed when the app returned to the foreground.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct ForegroundRefreshFFI {
+    /// Seconds spent in the background, 0 if the app was already in front
+    pub background_secs: u64,
+    /// Fresh health check results for every payment method
+    pub health: Vec<crate::HealthCheckResult>,
+    /// Payments still waiting on confirmations
+    pub in_progress_payments: Vec<crate::PaymentStatusInfo>,
+}
+
+/// Callback interface for lifecycle events.
+///
+/// Implement this in Swift/Kotlin where the app schedules its polling.
+#[uniffi::export(callback_interface)]
+pub trait LifecycleListener: Send + Sync {
+    /// Called when the app state or the configured schedules change.
+    fn on_schedule_changed(&self, state: AppStateFFI, schedule: PollingScheduleFFI);
+
+    /// Called on foreground; fetch the inbox / mailbox now.
+    fn on_refresh_requested(&self);
+}
+
+/// Default schedules: frequent polling in the foreground, inbox checks
+/// every 15 minutes and no keepalives in the background.
+#[uniffi::export]
+pub fn default_lifecycle_config() -> LifecycleConfigFFI {
+    LifecycleConfigFFI {
+        foreground: PollingScheduleFFI {
+            inbox_poll_secs: 30,
+            status_poll_secs: 15,
+            health_check_secs: 300,
+            keepalive_secs: 25,
+        },
+        background: PollingScheduleFFI {
+            inbox_poll_secs: 900,
+            status_poll_secs: 0,
+            health_check_secs: 0,
+            keepalive_secs: 0,
+        },
+    }
+}
+
+struct LifecycleState {
+    app_state: AppStateFFI,
+    backgrounded_at: Option<i64>,
+    config: LifecycleConfigFFI,
+}
+
+/// Lifecycle state held by the client.
+pub(crate) struct Lifecycle {
+    state: Mutex<LifecycleState>,
+    listeners: Mutex<Vec<Arc<dyn LifecycleListener>>>,
+}
+
+impl Lifecycle {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: Mutex::new(LifecycleState {
+                app_state: AppStateFFI::Foreground,
+                backgrounded_at: None,
+                config: default_lifecycle_config(),
+            }),
+            listeners: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, LifecycleState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn listeners(&self) -> Vec<Arc<dyn LifecycleListener>> {
+        self.listeners
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub(crate) fn app_state(&self) -> AppStateFFI {
+        self.state().app_state
+    }
+
+    pub(crate) fn is_backgrounded(&self) -> bool {
+        self.app_state() == AppStateFFI::Background
+    }
+
+    pub(crate) fn schedule(&self) -> PollingScheduleFFI {
+        let state = self.state();
+        state.config.schedule_for(state.app_state)
+    }
+
+    pub(crate) fn config(&self) -> LifecycleConfigFFI {
+        self.state().config.clone()
+    }
+
+    pub(crate) fn set_config(&self, config: LifecycleConfigFFI) {
+        let (app_state, schedule) = {
+            let mut state = self.state();
+            state.config = config;
+            (state.app_state, state.config.schedule_for(state.app_state))
+        };
+        self.notify_schedule(app_state, schedule);
+    }
+
+    /// Move to the background. Returns false if already there.
+    pub(crate) fn enter_background(&self, now: i64) -> bool {
+        let schedule = {
+            let mut state = self.state();
+            if state.app_state == AppStateFFI::Background {
+                return false;
+            }
+            state.app_state = AppStateFFI::Background;
+            state.backgrounded_at = Some(now);
+            state.config.background.clone()
+        };
+        self.notify_schedule(AppStateFFI::Background, schedule);
+        true
+    }
+
+    /// Move to the foreground, returning the seconds spent in the background.
+    pub(crate) fn enter_foreground(&self, now: i64) -> u64 {
+        let (background_secs, schedule) = {
+            let mut state = self.state();
+            if state.app_state == AppStateFFI::Foreground {
+                return 0;
+            }
+            state.app_state = AppStateFFI::Foreground;
+            let since = state.backgrounded_at.take().unwrap_or(now);
+            (
+                now.saturating_sub(since).max(0) as u64,
+                state.config.foreground.clone(),
+            )
+        };
+        self.notify_schedule(AppStateFFI::Foreground, schedule);
+        background_secs
+    }
+
+    pub(crate) fn request_refresh(&self) {
+        for listener in self.listeners() {
+            listener.on_refresh_requested();
+        }
+    }
+
+    pub(crate) fn add_listener(&self, listener: Arc<dyn LifecycleListener>) {
+        self.listeners
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(listener);
+    }
+
+    pub(crate) fn clear_listeners(&self) {
+        self.listeners
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    // Called without holding the state lock so listeners can call back
+    // into the client.
+    fn notify_schedule(&self, app_state: AppStateFFI, schedule: PollingScheduleFFI) {
+        for listener in self.listeners() {
+            listener.on_schedule_changed(app_state, schedule.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder {
+        schedules: Mutex<Vec<(AppStateFFI, PollingScheduleFFI)>>,
+        refreshes: Mutex<u32>,
+    }
+
+    impl LifecycleListener for Recorder {
+        fn on_schedule_changed(&self, state: AppStateFFI, schedule: PollingScheduleFFI) {
+            self.schedules.lock().unwrap().push((state, schedule));
+        }
+
+        fn on_refresh_requested(&self) {
+            *self.refreshes.lock().unwrap() += 1;
+        }
+    }
+
+    #[test]
+    fn test_transitions_notify_once() {
+        let lifecycle = Lifecycle::new();
+        let recorder = Arc::new(Recorder::default());
+        lifecycle.add_listener(recorder.clone());
+
+        assert!(lifecycle.enter_background(1_000));
+        assert!(!lifecycle.enter_background(1_010));
+        assert_eq!(lifecycle.schedule().keepalive_secs, 0);
+
+        assert_eq!(lifecycle.enter_foreground(1_120), 120);
+        assert_eq!(lifecycle.enter_foreground(1_130), 0);
+
+        let schedules = recorder.schedules.lock().unwrap();
+        assert_eq!(schedules.len(), 2);
+        assert_eq!(schedules[0].0, AppStateFFI::Background);
+        assert_eq!(schedules[1].0, AppStateFFI::Foreground);
+        assert_eq!(schedules[1].1, default_lifecycle_config().foreground);
+    }
+
+    #[test]
+    fn test_set_config_notifies_current_schedule() {
+        let lifecycle = Lifecycle::new();
+        let recorder = Arc::new(Recorder::default());
+        lifecycle.add_listener(recorder.clone());
+        lifecycle.enter_background(0);
+
+        let mut config = default_lifecycle_config();
+        config.background.inbox_poll_secs = 3_600;
+        lifecycle.set_config(config);
+
+        let schedules = recorder.schedules.lock().unwrap();
+        let (state, schedule) = schedules.last().unwrap();
+        assert_eq!(*state, AppStateFFI::Background);
+        assert_eq!(schedule.inbox_poll_secs, 3_600);
+    }
+
+    #[test]
+    fn test_cleared_listeners_are_not_called() {
+        let lifecycle = Lifecycle::new();
+        let recorder = Arc::new(Recorder::default());
+        lifecycle.add_listener(recorder.clone());
+        lifecycle.clear_listeners();
+
+        lifecycle.enter_background(0);
+        lifecycle.request_refresh();
+
+        assert!(recorder.schedules.lock().unwrap().is_empty());
+        assert_eq!(*recorder.refreshes.lock().unwrap(), 0);
+    }
+}
@@ -0,0 +1,473 @@
+//! Memory Diagnostics FFI Bindings
+//!
+//! Low-end Android devices kill apps that spike their memory use, and those
+//! spikes are hard to attribute from the host side. This module reports how
+//! much Paykit itself is holding, per subsystem (caches, stores, sessions),
+//! and, in debug builds with the `alloc-tracking` feature, counters from an
+//! instrumented global allocator.
+//!
+//! # Example Flow
+//!
+//! ```ignore
+//! let monitor = MemoryMonitorFFI::new();
+//! monitor.track_client(client.clone());
+//! monitor.track_receipt_store(store.clone());
+//! monitor.track_prefetcher(prefetcher.clone());
+//!
+//! // On the diagnostics screen
+//! let snapshot = monitor.snapshot();
+//! for subsystem in snapshot.subsystems {
+//!     show_row(subsystem.name, subsystem.entries, subsystem.approx_bytes);
+//! }
+//! if let Some(allocations) = snapshot.allocations {
+//!     show_peak(allocations.peak_bytes);
+//! }
+//! ```
+//!
+//! Byte counts are estimates: the table space of each map plus the heap
+//! owned by its entries, without allocator overhead. They are meant for
+//! spotting growth, not for exact accounting.
+//!
+//! # Allocation Tracking
+//!
+//! Building with `--features alloc-tracking` installs a global allocator that
+//! wraps the system allocator with a handful of relaxed atomic counters. It
+//! is only installed when `debug_assertions` are on, so the feature cannot
+//! slow down release builds by accident. The host binary must not install
+//! its own `#[global_allocator]` at the same time.
+
+use crate::interactive_ffi::{PaykitInteractiveManagerFFI, PrivateEndpointOffer, ReceiptRequest};
+use crate::noise_ffi::NoiseEndpointInfo;
+use crate::prefetch_ffi::{PayeePrefetcherFFI, PayeeSnapshotFFI};
+use crate::spending_ffi::SpendingManagerFFI;
+use crate::{PaykitClient, PaymentMethod, ReceiptStore};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+
+/// Allocations at least this large are counted as large allocations.
+pub const LARGE_ALLOCATION_BYTES: u64 = 1024 * 1024;
+
+fn now_timestamp() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+// ============================================================================
+// FFI Types
+// ============================================================================
+
+/// Counters from the tracking allocator.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct AllocationStatsFFI {
+    /// Bytes currently allocated.
+    pub current_bytes: u64,
+    /// Highest `current_bytes` seen since start or the last peak reset.
+    pub peak_bytes: u64,
+    /// Number of allocations and reallocations since start.
+    pub total_allocations: u64,
+    /// Bytes requested by those allocations.
+    pub total_allocated_bytes: u64,
+    /// Allocations of at least [`LARGE_ALLOCATION_BYTES`].
+    pub large_allocations: u64,
+    /// Largest single allocation since start or the last peak reset.
+    pub largest_allocation_bytes: u64,
+}
+
+/// Memory held by one subsystem.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct SubsystemMemoryFFI {
+    /// Subsystem name, such as "receipt_store.receipts".
+    pub name: String,
+    /// Number of entries held.
+    pub entries: u64,
+    /// Estimated bytes held by those entries.
+    pub approx_bytes: u64,
+}
+
+/// Point-in-time view of Paykit's memory use.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct MemorySnapshotFFI {
+    /// When the snapshot was taken (unix seconds).
+    pub taken_at: i64,
+    /// Usage of every tracked subsystem.
+    pub subsystems: Vec<SubsystemMemoryFFI>,
+    /// Sum of `approx_bytes` over all subsystems.
+    pub total_approx_bytes: u64,
+    /// Allocator counters, or `None` when allocation tracking is off.
+    pub allocations: Option<AllocationStatsFFI>,
+}
+
+// ============================================================================
+// Tracking Allocator
+// ============================================================================
+
+#[cfg(all(feature = "alloc-tracking", debug_assertions))]
+mod tracking {
+    use super::{AllocationStatsFFI, LARGE_ALLOCATION_BYTES};
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static CURRENT: AtomicU64 = AtomicU64::new(0);
+    static PEAK: AtomicU64 = AtomicU64::new(0);
+    static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+    static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+    static LARGE: AtomicU64 = AtomicU64::new(0);
+    static LARGEST: AtomicU64 = AtomicU64::new(0);
+
+    /// System allocator that keeps running totals.
+    ///
+    /// Only touches atomics, so it never allocates itself.
+    struct TrackingAllocator;
+
+    fn record_alloc(size: u64) {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(size, Ordering::Relaxed);
+        let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK.fetch_max(current, Ordering::Relaxed);
+        LARGEST.fetch_max(size, Ordering::Relaxed);
+        if size >= LARGE_ALLOCATION_BYTES {
+            LARGE.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn record_dealloc(size: u64) {
+        CURRENT.fetch_sub(size, Ordering::Relaxed);
+    }
+
+    unsafe impl GlobalAlloc for TrackingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc(layout);
+            if !ptr.is_null() {
+                record_alloc(layout.size() as u64);
+            }
+            ptr
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc_zeroed(layout);
+            if !ptr.is_null() {
+                record_alloc(layout.size() as u64);
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout);
+            record_dealloc(layout.size() as u64);
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = System.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                record_dealloc(layout.size() as u64);
+                record_alloc(new_size as u64);
+            }
+            new_ptr
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: TrackingAllocator = TrackingAllocator;
+
+    pub(super) fn stats() -> AllocationStatsFFI {
+        AllocationStatsFFI {
+            current_bytes: CURRENT.load(Ordering::Relaxed),
+            peak_bytes: PEAK.load(Ordering::Relaxed),
+            total_allocations: ALLOCATIONS.load(Ordering::Relaxed),
+            total_allocated_bytes: ALLOCATED_BYTES.load(Ordering::Relaxed),
+            large_allocations: LARGE.load(Ordering::Relaxed),
+            largest_allocation_bytes: LARGEST.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(super) fn reset_peak() {
+        PEAK.store(CURRENT.load(Ordering::Relaxed), Ordering::Relaxed);
+        LARGEST.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Whether this build tracks allocations.
+#[uniffi::export]
+pub fn allocation_tracking_enabled() -> bool {
+    cfg!(all(feature = "alloc-tracking", debug_assertions))
+}
+
+/// Current allocator counters, or `None` when allocation tracking is off.
+#[uniffi::export]
+pub fn allocation_stats() -> Option<AllocationStatsFFI> {
+    #[cfg(all(feature = "alloc-tracking", debug_assertions))]
+    {
+        Some(tracking::stats())
+    }
+    #[cfg(not(all(feature = "alloc-tracking", debug_assertions)))]
+    {
+        None
+    }
+}
+
+/// Restart peak tracking from the current usage.
+///
+/// Call before a flow to measure the peak that flow alone causes.
+/// Does nothing when allocation tracking is off.
+#[uniffi::export]
+pub fn reset_allocation_peak() {
+    #[cfg(all(feature = "alloc-tracking", debug_assertions))]
+    tracking::reset_peak();
+}
+
+// ============================================================================
+// Size Estimation
+// ============================================================================
+
+/// Bytes a value owns on the heap, beyond its inline size.
+pub(crate) trait HeapSize {
+    fn heap_size(&self) -> usize;
+}
+
+impl HeapSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Option<T> {
+    fn heap_size(&self) -> usize {
+        self.as_ref().map_or(0, HeapSize::heap_size)
+    }
+}
+
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * std::mem::size_of::<T>()
+            + self.iter().map(HeapSize::heap_size).sum::<usize>()
+    }
+}
+
+impl HeapSize for PaymentMethod {
+    fn heap_size(&self) -> usize {
+        self.method_id.heap_size() + self.endpoint.heap_size()
+    }
+}
+
+impl HeapSize for NoiseEndpointInfo {
+    fn heap_size(&self) -> usize {
+        self.recipient_pubkey.heap_size()
+            + self.host.heap_size()
+            + self.server_noise_pubkey.heap_size()
+            + self.metadata.heap_size()
+    }
+}
+
+impl HeapSize for PayeeSnapshotFFI {
+    fn heap_size(&self) -> usize {
+        self.payee.heap_size()
+            + self.methods.heap_size()
+            + self.unavailable_methods.heap_size()
+            + self.preferred_method.heap_size()
+            + self.status_message.heap_size()
+            + self.noise_endpoint.heap_size()
+            + self.failures.heap_size()
+    }
+}
+
+impl HeapSize for ReceiptRequest {
+    fn heap_size(&self) -> usize {
+        self.receipt_id.heap_size()
+            + self.payer.heap_size()
+            + self.payee.heap_size()
+            + self.method_id.heap_size()
+            + self.amount.heap_size()
+            + self.currency.heap_size()
+            + self.metadata_json.heap_size()
+    }
+}
+
+impl HeapSize for PrivateEndpointOffer {
+    fn heap_size(&self) -> usize {
+        self.method_id.heap_size() + self.endpoint.heap_size()
+    }
+}
+
+/// Estimate the memory held by a map of `name`.
+///
+/// Counts one control byte per bucket on top of the key/value slots, as
+/// `hashbrown` lays them out.
+pub(crate) fn map_usage<K: HeapSize, V: HeapSize>(
+    name: &str,
+    map: &HashMap<K, V>,
+) -> SubsystemMemoryFFI {
+    let table = map.capacity() * (std::mem::size_of::<(K, V)>() + 1);
+    let owned: usize = map.iter().map(|(k, v)| k.heap_size() + v.heap_size()).sum();
+    SubsystemMemoryFFI {
+        name: name.to_string(),
+        entries: map.len() as u64,
+        approx_bytes: (table + owned) as u64,
+    }
+}
+
+/// An object that can report the memory its subsystems hold.
+pub(crate) trait MemoryReporter: Send + Sync {
+    fn memory_usage(&self) -> Vec<SubsystemMemoryFFI>;
+}
+
+// ============================================================================
+// Memory Monitor
+// ============================================================================
+
+/// Collects memory usage from the Paykit objects an app registers.
+///
+/// Holds weak references only: tracking an object never keeps it alive, and
+/// dropped objects disappear from the next snapshot.
+#[derive(uniffi::Object)]
+pub struct MemoryMonitorFFI {
+    reporters: Mutex<Vec<Weak<dyn MemoryReporter>>>,
+}
+
+impl MemoryMonitorFFI {
+    fn track(&self, reporter: Weak<dyn MemoryReporter>) {
+        self.reporters
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(reporter);
+    }
+}
+
+#[uniffi::export]
+impl MemoryMonitorFFI {
+    /// Create a monitor with nothing tracked.
+    #[uniffi::constructor]
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            reporters: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Track a client's payment status tracker.
+    pub fn track_client(&self, client: Arc<PaykitClient>) {
+        self.track(Arc::downgrade(&client) as Weak<dyn MemoryReporter>);
+    }
+
+    /// Track a receipt store's receipts and private endpoints.
+    pub fn track_receipt_store(&self, store: Arc<ReceiptStore>) {
+        self.track(Arc::downgrade(&store) as Weak<dyn MemoryReporter>);
+    }
+
+    /// Track a prefetcher's snapshot cache.
+    pub fn track_prefetcher(&self, prefetcher: Arc<PayeePrefetcherFFI>) {
+        self.track(Arc::downgrade(&prefetcher) as Weak<dyn MemoryReporter>);
+    }
+
+    /// Track an interactive manager's receipt exchanges in progress.
+    ///
+    /// Does not include its receipt store; track that separately.
+    pub fn track_interactive_manager(&self, manager: Arc<PaykitInteractiveManagerFFI>) {
+        self.track(Arc::downgrade(&manager) as Weak<dyn MemoryReporter>);
+    }
+
+    /// Track a spending manager's in-flight reservations.
+    pub fn track_spending_manager(&self, manager: Arc<SpendingManagerFFI>) {
+        self.track(Arc::downgrade(&manager) as Weak<dyn MemoryReporter>);
+    }
+
+    /// Number of tracked objects that are still alive.
+    pub fn tracked_count(&self) -> u32 {
+        let mut reporters = self.reporters.lock().unwrap_or_else(|e| e.into_inner());
+        reporters.retain(|r| r.strong_count() > 0);
+        reporters.len() as u32
+    }
+
+    /// Take a snapshot of every tracked subsystem and the allocator counters.
+    ///
+    /// Intended for a debug or diagnostics screen; each call walks every
+    /// tracked map under its lock.
+    pub fn snapshot(&self) -> MemorySnapshotFFI {
+        // Upgrade under the lock, report outside it
+        let alive: Vec<Arc<dyn MemoryReporter>> = {
+            let mut reporters = self.reporters.lock().unwrap_or_else(|e| e.into_inner());
+            reporters.retain(|r| r.strong_count() > 0);
+            reporters.iter().filter_map(Weak::upgrade).collect()
+        };
+
+        let subsystems: Vec<SubsystemMemoryFFI> =
+            alive.iter().flat_map(|r| r.memory_usage()).collect();
+        let total_approx_bytes = subsystems.iter().map(|s| s.approx_bytes).sum();
+
+        MemorySnapshotFFI {
+            taken_at: now_timestamp(),
+            subsystems,
+            total_approx_bytes,
+            allocations: allocation_stats(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receipt(id: &str) -> ReceiptRequest {
+        ReceiptRequest {
+            receipt_id: id.to_string(),
+            payer: "payer".to_string(),
+            payee: "payee".to_string(),
+            method_id: "lightning".to_string(),
+            amount: Some("1000".to_string()),
+            currency: Some("SAT".to_string()),
+            metadata_json: "{}".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_snapshot_reports_receipt_store_growth() {
+        let monitor = MemoryMonitorFFI::new();
+        let store = ReceiptStore::new();
+        monitor.track_receipt_store(store.clone());
+
+        let empty = monitor.snapshot();
+        let receipts = |s: &MemorySnapshotFFI| {
+            s.subsystems
+                .iter()
+                .find(|s| s.name == "receipt_store.receipts")
+                .cloned()
+                .expect("receipts subsystem")
+        };
+        assert_eq!(receipts(&empty).entries, 0);
+
+        for i in 0..50 {
+            store.save_receipt(receipt(&format!("r{}", i))).unwrap();
+        }
+        let full = monitor.snapshot();
+        assert_eq!(receipts(&full).entries, 50);
+        assert!(receipts(&full).approx_bytes > receipts(&empty).approx_bytes);
+        assert!(full.total_approx_bytes >= receipts(&full).approx_bytes);
+    }
+
+    #[test]
+    fn test_dropped_objects_leave_the_snapshot() {
+        let monitor = MemoryMonitorFFI::new();
+        let store = ReceiptStore::new();
+        monitor.track_receipt_store(store.clone());
+        monitor.track_prefetcher(PayeePrefetcherFFI::new(0));
+
+        // The prefetcher had no other owner
+        assert_eq!(monitor.tracked_count(), 1);
+        drop(store);
+        assert_eq!(monitor.tracked_count(), 0);
+        assert!(monitor.snapshot().subsystems.is_empty());
+    }
+
+    #[test]
+    fn test_allocation_stats_match_feature() {
+        assert_eq!(allocation_stats().is_some(), allocation_tracking_enabled());
+        reset_allocation_peak();
+
+        if let Some(before) = allocation_stats() {
+            let buffer = vec![0u8; 2 * LARGE_ALLOCATION_BYTES as usize];
+            let after = allocation_stats().unwrap();
+            assert!(after.large_allocations > before.large_allocations);
+            assert!(after.largest_allocation_bytes >= buffer.len() as u64);
+        }
+    }
+}
@@ -0,0 +1,441 @@
+//! NFC Tap-to-Pay Payloads
+//!
+//! This module produces and parses compact Paykit payloads for NFC exchanges,
+//! so host apps can implement tap-to-pay without inventing their own framing.
+//!
+//! # Format
+//!
+//! Payloads are CBOR maps with single-letter keys and public keys stored as
+//! raw 32-byte strings, which keeps a typical payment intent well under the
+//! 144 bytes available on an NTAG213 tag.
+//!
+//! For tags and Host Card Emulation the payload is wrapped in a single NDEF
+//! record with the NFC Forum external type `paykit.app:p`. Apps can either
+//! write the full NDEF message from [`encode_nfc_ndef_message`] or embed the
+//! raw payload from [`encode_nfc_payload`] in a record of their own.
+//!
+//! # Example
+//!
+//! ```ignore
+//! // Merchant device
+//! let message = encode_nfc_ndef_message(NfcPayload::PaymentIntent {
+//!     payee: my_pubkey_z32,
+//!     amount_sats: Some(2100),
+//!     methods: vec!["lightning".into()],
+//!     request_id: Some("order-17".into()),
+//!     description: None,
+//! })?;
+//! nfc_write(message);
+//!
+//! // Customer device
+//! match decode_nfc_ndef_message(nfc_read())? {
+//!     NfcPayload::PaymentIntent { payee, amount_sats, .. } => pay(payee, amount_sats),
+//!     _ => {}
+//! }
+//! ```
+
+use crate::interactive_ffi::PrivateEndpointOffer;
+use crate::keys::{z32_decode, z32_encode};
+use crate::{PaykitMobileError, Result};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// NFC Forum external record type for Paykit payloads.
+pub const NFC_RECORD_TYPE: &str = "paykit.app:p";
+
+/// Current payload encoding version.
+const NFC_PAYLOAD_VERSION: u8 = 1;
+
+/// NDEF Type Name Format for NFC Forum external types.
+const TNF_EXTERNAL: u8 = 0x04;
+
+const NDEF_MB: u8 = 0x80;
+const NDEF_ME: u8 = 0x40;
+const NDEF_CF: u8 = 0x20;
+const NDEF_SR: u8 = 0x10;
+const NDEF_IL: u8 = 0x08;
+const NDEF_TNF_MASK: u8 = 0x07;
+
+// ============================================================================
+// FFI Types
+// ============================================================================
+
+/// A Paykit payload exchanged over NFC.
+#[derive(Clone, Debug, PartialEq, uniffi::Enum)]
+pub enum NfcPayload {
+    /// Share an identity (e.g. to add a contact or discover methods).
+    Identity {
+        /// Public key (z-base32)
+        public_key: String,
+        /// Optional display name
+        name: Option<String>,
+    },
+    /// Ask the tapping device to pay.
+    PaymentIntent {
+        /// Payee public key (z-base32)
+        payee: String,
+        /// Requested amount in satoshis, if fixed
+        amount_sats: Option<u64>,
+        /// Accepted payment methods in order of preference
+        methods: Vec<String>,
+        /// Request ID to echo back in the receipt
+        request_id: Option<String>,
+        /// Human-readable description
+        description: Option<String>,
+    },
+    /// Offer a private endpoint to the tapping device.
+    PrivateEndpointOffer {
+        /// Public key of the offering peer (z-base32)
+        public_key: String,
+        /// The offered endpoint
+        offer: PrivateEndpointOffer,
+    },
+}
+
+// ============================================================================
+// Wire Format
+// ============================================================================
+
+const KIND_IDENTITY: u8 = 0;
+const KIND_PAYMENT_INTENT: u8 = 1;
+const KIND_ENDPOINT_OFFER: u8 = 2;
+
+/// Compact CBOR form of [`NfcPayload`].
+#[derive(Serialize, Deserialize)]
+struct WirePayload {
+    #[serde(rename = "v")]
+    version: u8,
+    #[serde(rename = "t")]
+    kind: u8,
+    #[serde(rename = "k")]
+    public_key: KeyBytes,
+    #[serde(rename = "n", default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(rename = "a", default, skip_serializing_if = "Option::is_none")]
+    amount_sats: Option<u64>,
+    #[serde(rename = "m", default, skip_serializing_if = "Vec::is_empty")]
+    methods: Vec<String>,
+    #[serde(rename = "r", default, skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+    #[serde(rename = "d", default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(rename = "e", default, skip_serializing_if = "Option::is_none")]
+    endpoint: Option<String>,
+}
+
+/// Public key serialized as a CBOR byte string rather than an array.
+struct KeyBytes([u8; 32]);
+
+impl Serialize for KeyBytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyBytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct KeyVisitor;
+
+        impl serde::de::Visitor<'_> for KeyVisitor {
+            type Value = KeyBytes;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("32 bytes")
+            }
+
+            fn visit_bytes<E: serde::de::Error>(
+                self,
+                v: &[u8],
+            ) -> std::result::Result<KeyBytes, E> {
+                v.try_into()
+                    .map(KeyBytes)
+                    .map_err(|_| E::invalid_length(v.len(), &self))
+            }
+        }
+
+        deserializer.deserialize_bytes(KeyVisitor)
+    }
+}
+
+fn validation(msg: impl Into<String>) -> PaykitMobileError {
+    PaykitMobileError::Validation { msg: msg.into() }
+}
+
+impl NfcPayload {
+    fn to_wire(&self) -> Result<WirePayload> {
+        let mut wire = WirePayload {
+            version: NFC_PAYLOAD_VERSION,
+            kind: KIND_IDENTITY,
+            public_key: KeyBytes([0; 32]),
+            name: None,
+            amount_sats: None,
+            methods: Vec::new(),
+            request_id: None,
+            description: None,
+            endpoint: None,
+        };
+
+        match self {
+            Self::Identity { public_key, name } => {
+                wire.public_key = KeyBytes(z32_decode(public_key)?);
+                wire.name = name.clone();
+            }
+            Self::PaymentIntent {
+                payee,
+                amount_sats,
+                methods,
+                request_id,
+                description,
+            } => {
+                wire.kind = KIND_PAYMENT_INTENT;
+                wire.public_key = KeyBytes(z32_decode(payee)?);
+                wire.amount_sats = *amount_sats;
+                wire.methods = methods.clone();
+                wire.request_id = request_id.clone();
+                wire.description = description.clone();
+            }
+            Self::PrivateEndpointOffer { public_key, offer } => {
+                wire.kind = KIND_ENDPOINT_OFFER;
+                wire.public_key = KeyBytes(z32_decode(public_key)?);
+                wire.methods = vec![offer.method_id.clone()];
+                wire.endpoint = Some(offer.endpoint.clone());
+            }
+        }
+
+        Ok(wire)
+    }
+
+    fn from_wire(wire: WirePayload) -> Result<Self> {
+        if wire.version != NFC_PAYLOAD_VERSION {
+            return Err(validation(format!(
+                "Unsupported NFC payload version {}",
+                wire.version
+            )));
+        }
+
+        let public_key = z32_encode(&wire.public_key.0);
+        match wire.kind {
+            KIND_IDENTITY => Ok(Self::Identity {
+                public_key,
+                name: wire.name,
+            }),
+            KIND_PAYMENT_INTENT => Ok(Self::PaymentIntent {
+                payee: public_key,
+                amount_sats: wire.amount_sats,
+                methods: wire.methods,
+                request_id: wire.request_id,
+                description: wire.description,
+            }),
+            KIND_ENDPOINT_OFFER => {
+                let method_id = wire
+                    .methods
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| validation("Endpoint offer is missing its method"))?;
+                let endpoint = wire
+                    .endpoint
+                    .ok_or_else(|| validation("Endpoint offer is missing its endpoint"))?;
+                Ok(Self::PrivateEndpointOffer {
+                    public_key,
+                    offer: PrivateEndpointOffer {
+                        method_id,
+                        endpoint,
+                    },
+                })
+            }
+            other => Err(validation(format!("Unknown NFC payload type {}", other))),
+        }
+    }
+}
+
+// ============================================================================
+// Exported Functions
+// ============================================================================
+
+/// Encode a payload as compact CBOR bytes.
+#[uniffi::export]
+pub fn encode_nfc_payload(payload: NfcPayload) -> Result<Vec<u8>> {
+    let wire = payload.to_wire()?;
+    let mut bytes = Vec::new();
+    ciborium::into_writer(&wire, &mut bytes).map_err(|e| PaykitMobileError::Serialization {
+        msg: format!("Failed to encode NFC payload: {}", e),
+    })?;
+    Ok(bytes)
+}
+
+/// Decode a payload from CBOR bytes.
+#[uniffi::export]
+pub fn decode_nfc_payload(bytes: Vec<u8>) -> Result<NfcPayload> {
+    let wire: WirePayload =
+        ciborium::from_reader(bytes.as_slice()).map_err(|e| PaykitMobileError::Serialization {
+            msg: format!("Invalid NFC payload: {}", e),
+        })?;
+    NfcPayload::from_wire(wire)
+}
+
+/// Encode a payload as a complete single-record NDEF message.
+#[uniffi::export]
+pub fn encode_nfc_ndef_message(payload: NfcPayload) -> Result<Vec<u8>> {
+    let body = encode_nfc_payload(payload)?;
+    let record_type = NFC_RECORD_TYPE.as_bytes();
+    let short = body.len() <= u8::MAX as usize;
+
+    let mut message = Vec::with_capacity(body.len() + record_type.len() + 6);
+    let mut header = NDEF_MB | NDEF_ME | TNF_EXTERNAL;
+    if short {
+        header |= NDEF_SR;
+    }
+    message.push(header);
+    message.push(record_type.len() as u8);
+    if short {
+        message.push(body.len() as u8);
+    } else {
+        message.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    }
+    message.extend_from_slice(record_type);
+    message.extend_from_slice(&body);
+
+    Ok(message)
+}
+
+/// Find and decode the Paykit record in an NDEF message.
+///
+/// Other records in the message (e.g. an Android Application Record) are
+/// skipped.
+#[uniffi::export]
+pub fn decode_nfc_ndef_message(message: Vec<u8>) -> Result<NfcPayload> {
+    let truncated = || validation("Truncated NDEF message");
+    let mut pos = 0;
+
+    while pos < message.len() {
+        let header = message[pos];
+        pos += 1;
+
+        if header & NDEF_CF != 0 {
+            return Err(validation("Chunked NDEF records are not supported"));
+        }
+
+        let type_len = *message.get(pos).ok_or_else(truncated)? as usize;
+        pos += 1;
+
+        let payload_len = if header & NDEF_SR != 0 {
+            let len = *message.get(pos).ok_or_else(truncated)? as usize;
+            pos += 1;
+            len
+        } else {
+            let bytes = message.get(pos..pos + 4).ok_or_else(truncated)?;
+            pos += 4;
+            u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize
+        };
+
+        let id_len = if header & NDEF_IL != 0 {
+            let len = *message.get(pos).ok_or_else(truncated)? as usize;
+            pos += 1;
+            len
+        } else {
+            0
+        };
+
+        // Lengths come from the tag, so sums may overflow on 32-bit targets
+        let type_end = pos.checked_add(type_len).ok_or_else(truncated)?;
+        let record_type = message.get(pos..type_end).ok_or_else(truncated)?;
+        pos = type_end.checked_add(id_len).ok_or_else(truncated)?;
+        let payload_end = pos.checked_add(payload_len).ok_or_else(truncated)?;
+        let payload = message.get(pos..payload_end).ok_or_else(truncated)?;
+        pos = payload_end;
+
+        if header & NDEF_TNF_MASK == TNF_EXTERNAL && record_type == NFC_RECORD_TYPE.as_bytes() {
+            return decode_nfc_payload(payload.to_vec());
+        }
+
+        if header & NDEF_ME != 0 {
+            break;
+        }
+    }
+
+    Err(validation("No Paykit record found in NDEF message"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_key() -> String {
+        z32_encode(&[7u8; 32])
+    }
+
+    fn intent() -> NfcPayload {
+        NfcPayload::PaymentIntent {
+            payee: test_key(),
+            amount_sats: Some(2_100),
+            methods: vec!["lightning".to_string(), "onchain".to_string()],
+            request_id: Some("order-17".to_string()),
+            description: Some("Espresso".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_nfc_payload_roundtrip() {
+        let payloads = vec![
+            NfcPayload::Identity {
+                public_key: test_key(),
+                name: Some("Alice".to_string()),
+            },
+            intent(),
+            NfcPayload::PrivateEndpointOffer {
+                public_key: test_key(),
+                offer: PrivateEndpointOffer {
+                    method_id: "lightning".to_string(),
+                    endpoint: "lno1qcp4256ypq".to_string(),
+                },
+            },
+        ];
+
+        for payload in payloads {
+            let bytes = encode_nfc_payload(payload.clone()).unwrap();
+            assert_eq!(decode_nfc_payload(bytes).unwrap(), payload);
+        }
+    }
+
+    #[test]
+    fn test_nfc_payload_is_compact() {
+        let bytes = encode_nfc_payload(intent()).unwrap();
+        // Fits an NTAG213 together with NDEF framing
+        assert!(bytes.len() < 120, "payload is {} bytes", bytes.len());
+    }
+
+    #[test]
+    fn test_nfc_ndef_roundtrip() {
+        let message = encode_nfc_ndef_message(intent()).unwrap();
+        assert_eq!(message[0] & NDEF_TNF_MASK, TNF_EXTERNAL);
+        assert_eq!(decode_nfc_ndef_message(message).unwrap(), intent());
+    }
+
+    #[test]
+    fn test_nfc_ndef_skips_foreign_records() {
+        // A URI record (TNF well-known, type "U") followed by the Paykit record
+        let mut message = vec![NDEF_MB | NDEF_SR | 0x01, 1, 4, b'U', 0x04, b'a', b'.', b'b'];
+        let mut paykit = encode_nfc_ndef_message(intent()).unwrap();
+        paykit[0] &= !NDEF_MB;
+        message.extend(paykit);
+
+        assert_eq!(decode_nfc_ndef_message(message).unwrap(), intent());
+    }
+
+    #[test]
+    fn test_nfc_decode_rejects_invalid() {
+        assert!(decode_nfc_payload(vec![0xff, 0x00]).is_err());
+        assert!(decode_nfc_ndef_message(vec![0xd4, 12]).is_err());
+        assert!(decode_nfc_payload(encode_nfc_payload(intent()).unwrap()[..10].to_vec()).is_err());
+        // A long record claiming a 4 GiB payload
+        let oversized = vec![
+            NDEF_MB | NDEF_ME | TNF_EXTERNAL,
+            1,
+            0xff,
+            0xff,
+            0xff,
+            0xff,
+            b'p',
+        ];
+        assert!(decode_nfc_ndef_message(oversized).is_err());
+    }
+}
@@ -25,9 +25,21 @@
 //! 2. Publish noise endpoint to directory
 //! 3. Accept incoming connections
 //! 4. Handle payment requests and send confirmations
+//!
+//! ## Key Rotation
+//!
+//! `schedule_noise_key_rotation()` publishes the next X25519 key next to the
+//! current one. Servers accept every epoch in `accepted_noise_epochs()`;
+//! clients that get a key-mismatch hint (`is_noise_key_mismatch()`) call
+//! `refresh_noise_endpoint()` and retry once.
 
+use std::borrow::Cow;
 use std::sync::Arc;
 
+use paykit_lib::protocol::{
+    is_key_mismatch_hint, parse_document, NoiseEndpointRecord, NoiseKey, MAX_DOCUMENT_BYTES,
+};
+
 use crate::{PaykitMobileError, Result};
 
 /// Get current unix timestamp
@@ -136,18 +148,20 @@ pub fn discover_noise_endpoint(
 
     match content {
         Some(json_str) => {
-            // Parse the endpoint JSON
-            let info: NoiseEndpointData =
-                serde_json::from_str(&json_str).map_err(|e| PaykitMobileError::Serialization {
-                    msg: format!("Invalid noise endpoint format: {}", e),
-                })?;
+            let record = parse_endpoint_record(&json_str)?;
+            // Connect with the newest key currently valid; records without
+            // rotation data fall back to their single published key
+            let server_noise_pubkey = record
+                .preferred_key(now_timestamp())
+                .map(|key| key.pubkey)
+                .unwrap_or_else(|| record.pubkey.clone());
 
             Ok(Some(NoiseEndpointInfo {
                 recipient_pubkey,
-                host: info.host,
-                port: info.port,
-                server_noise_pubkey: info.pubkey,
-                metadata: info.metadata,
+                host: record.host,
+                port: record.port,
+                server_noise_pubkey,
+                metadata: record.metadata,
             }))
         }
         None => Ok(None),
@@ -165,6 +179,10 @@ pub fn discover_noise_endpoint(
 /// * `port` - Port number where the Noise server is listening
 /// * `noise_pubkey` - This server's Noise public key (X25519, hex encoded)
 /// * `metadata` - Optional metadata about the endpoint
+///
+/// This publishes a single epoch-0 key and replaces any scheduled rotation.
+/// Use `schedule_noise_key_rotation()` to change keys without breaking
+/// payers that cached the current one.
 #[uniffi::export]
 pub fn publish_noise_endpoint(
     transport: Arc<crate::AuthenticatedTransportFFI>,
@@ -173,19 +191,9 @@ pub fn publish_noise_endpoint(
     noise_pubkey: String,
     metadata: Option<String>,
 ) -> Result<()> {
-    let endpoint_data = NoiseEndpointData {
-        host,
-        port,
-        pubkey: noise_pubkey,
-        metadata,
-    };
-
-    let json =
-        serde_json::to_string(&endpoint_data).map_err(|e| PaykitMobileError::Serialization {
-            msg: format!("Failed to serialize noise endpoint: {}", e),
-        })?;
-
-    transport.put(NOISE_ENDPOINT_PATH.to_string(), json)
+    let mut record = NoiseEndpointRecord::new(host, port, noise_pubkey, 0, now_timestamp());
+    record.metadata = metadata;
+    put_endpoint_record(&transport, &record)
 }
 
 /// Remove the Noise endpoint from the directory.
@@ -200,14 +208,138 @@ pub fn remove_noise_endpoint(transport: Arc<crate::AuthenticatedTransportFFI>) -
     transport.delete(NOISE_ENDPOINT_PATH.to_string())
 }
 
-/// Internal struct for serializing/deserializing noise endpoint data.
-#[derive(serde::Serialize, serde::Deserialize)]
-struct NoiseEndpointData {
-    host: String,
-    port: u16,
-    pubkey: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    metadata: Option<String>,
+fn parse_endpoint_record(json: &str) -> Result<NoiseEndpointRecord> {
+    parse_document("noise_endpoint", json.as_bytes(), MAX_DOCUMENT_BYTES).map_err(|e| {
+        PaykitMobileError::Serialization {
+            msg: format!("Invalid noise endpoint format: {}", e),
+        }
+    })
+}
+
+fn put_endpoint_record(
+    transport: &crate::AuthenticatedTransportFFI,
+    record: &NoiseEndpointRecord,
+) -> Result<()> {
+    let json = serde_json::to_string(record).map_err(|e| PaykitMobileError::Serialization {
+        msg: format!("Failed to serialize noise endpoint: {}", e),
+    })?;
+    transport.put(NOISE_ENDPOINT_PATH.to_string(), json)
+}
+
+// ============================================================================
+// Noise Key Rotation
+// ============================================================================
+
+/// A published Noise static key and its validity window.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct NoiseKeyInfo {
+    /// X25519 public key (hex encoded).
+    pub pubkey: String,
+    /// Derivation epoch of the key.
+    pub epoch: u32,
+    /// Unix timestamp from which the key is accepted.
+    pub valid_from: i64,
+    /// Unix timestamp after which the key is no longer accepted.
+    pub valid_until: Option<i64>,
+}
+
+impl From<NoiseKey> for NoiseKeyInfo {
+    fn from(key: NoiseKey) -> Self {
+        Self {
+            pubkey: key.pubkey,
+            epoch: key.epoch,
+            valid_from: key.valid_from,
+            valid_until: key.valid_until,
+        }
+    }
+}
+
+/// Schedule a rotation of this device's Noise static key.
+///
+/// Publishes `next_noise_pubkey` alongside the current key. The new key is
+/// preferred from `activate_at`; the current key stays accepted until
+/// `activate_at + overlap_secs` so payers holding it can still connect.
+/// The server must accept handshakes for every epoch returned by
+/// `accepted_noise_epochs()` until then.
+///
+/// # Arguments
+///
+/// * `transport` - Authenticated transport for reading and writing
+/// * `next_noise_pubkey` - The next Noise public key (X25519, hex encoded)
+/// * `next_epoch` - Epoch the next key was derived with
+/// * `activate_at` - Unix timestamp at which clients switch to the next key
+/// * `overlap_secs` - How long the current key stays accepted afterwards
+#[uniffi::export]
+pub fn schedule_noise_key_rotation(
+    transport: Arc<crate::AuthenticatedTransportFFI>,
+    next_noise_pubkey: String,
+    next_epoch: u32,
+    activate_at: i64,
+    overlap_secs: i64,
+) -> Result<()> {
+    let json = transport
+        .get(NOISE_ENDPOINT_PATH.to_string())?
+        .ok_or_else(|| PaykitMobileError::NotFound {
+            msg: "No noise endpoint published; publish one before rotating".to_string(),
+        })?;
+    let mut record = parse_endpoint_record(&json)?;
+    record.prune(now_timestamp());
+    record.schedule_rotation(next_noise_pubkey, next_epoch, activate_at, overlap_secs)?;
+    put_endpoint_record(&transport, &record)
+}
+
+/// Epochs this device's server should accept handshakes for right now.
+///
+/// Build one Noise server per epoch. Returns an empty list if no endpoint
+/// is published.
+#[uniffi::export]
+pub fn accepted_noise_epochs(transport: Arc<crate::AuthenticatedTransportFFI>) -> Result<Vec<u32>> {
+    match transport.get(NOISE_ENDPOINT_PATH.to_string())? {
+        Some(json) => Ok(parse_endpoint_record(&json)?
+            .accepted_keys(now_timestamp())
+            .into_iter()
+            .map(|key| key.epoch)
+            .collect()),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// List the Noise keys a recipient publishes, including scheduled ones.
+#[uniffi::export]
+pub fn discover_noise_keys(
+    transport: Arc<crate::UnauthenticatedTransportFFI>,
+    recipient_pubkey: String,
+) -> Result<Vec<NoiseKeyInfo>> {
+    match transport.get(recipient_pubkey, NOISE_ENDPOINT_PATH.to_string())? {
+        Some(json) => Ok(parse_endpoint_record(&json)?
+            .all_keys()
+            .into_iter()
+            .map(Into::into)
+            .collect()),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Whether a handshake response is the server's key-mismatch hint.
+///
+/// The hint means the key used is no longer accepted; call
+/// `refresh_noise_endpoint()` and retry the handshake once.
+#[uniffi::export]
+pub fn is_noise_key_mismatch(response: Vec<u8>) -> bool {
+    is_key_mismatch_hint(&response)
+}
+
+/// Refetch a recipient's endpoint after a key-mismatch hint.
+///
+/// Returns the updated endpoint if its preferred key differs from the one in
+/// `stale`, or `None` if nothing changed (retrying would not help).
+#[uniffi::export]
+pub fn refresh_noise_endpoint(
+    transport: Arc<crate::UnauthenticatedTransportFFI>,
+    stale: NoiseEndpointInfo,
+) -> Result<Option<NoiseEndpointInfo>> {
+    let fresh = discover_noise_endpoint(transport, stale.recipient_pubkey.clone())?;
+    Ok(fresh.filter(|endpoint| endpoint.server_noise_pubkey != stale.server_noise_pubkey))
 }
 
 // ============================================================================
@@ -446,34 +578,14 @@ pub fn create_error_message(code: String, message: String) -> Result<NoisePaymen
 
 /// Parse a payment message from JSON.
 ///
+/// Only the `type` field is decoded; the message is returned as received.
+///
 /// # Arguments
 ///
 /// * `json` - The JSON string to parse
 #[uniffi::export]
 pub fn parse_payment_message(json: String) -> Result<NoisePaymentMessage> {
-    let value: serde_json::Value =
-        serde_json::from_str(&json).map_err(|e| PaykitMobileError::Serialization {
-            msg: format!("Invalid message JSON: {}", e),
-        })?;
-
-    let msg_type = value
-        .get("type")
-        .and_then(|t| t.as_str())
-        .unwrap_or("unknown");
-
-    let message_type = match msg_type {
-        "request_receipt" => NoisePaymentMessageType::ReceiptRequest,
-        "confirm_receipt" => NoisePaymentMessageType::ReceiptConfirmation,
-        "private_endpoint_offer" => NoisePaymentMessageType::PrivateEndpointOffer,
-        "error" => NoisePaymentMessageType::Error,
-        "ping" => NoisePaymentMessageType::Ping,
-        "pong" => NoisePaymentMessageType::Pong,
-        _ => {
-            return Err(PaykitMobileError::Validation {
-                msg: format!("Unknown message type: {}", msg_type),
-            })
-        }
-    };
+    let message_type = payment_message_type(json.as_bytes())?;
 
     Ok(NoisePaymentMessage {
         message_type,
@@ -481,6 +593,53 @@ pub fn parse_payment_message(json: String) -> Result<NoisePaymentMessage> {
     })
 }
 
+/// Parse a payment message from the raw channel bytes.
+///
+/// The bytes are moved into the returned message without copying.
+///
+/// # Arguments
+///
+/// * `bytes` - UTF-8 encoded JSON message
+#[uniffi::export]
+pub fn parse_payment_message_bytes(bytes: Vec<u8>) -> Result<NoisePaymentMessage> {
+    let message_type = payment_message_type(&bytes)?;
+    let payload_json = String::from_utf8(bytes).map_err(|e| PaykitMobileError::Serialization {
+        msg: format!("Invalid message encoding: {}", e),
+    })?;
+
+    Ok(NoisePaymentMessage {
+        message_type,
+        payload_json,
+    })
+}
+
+/// Type tag of a payment message; every other field is skipped.
+#[derive(serde::Deserialize)]
+struct MessageTag<'a> {
+    #[serde(rename = "type", borrow, default)]
+    kind: Option<Cow<'a, str>>,
+}
+
+fn payment_message_type(json: &[u8]) -> Result<NoisePaymentMessageType> {
+    let tag: MessageTag = parse_document("message", json, MAX_DOCUMENT_BYTES).map_err(|e| {
+        PaykitMobileError::Serialization {
+            msg: format!("Invalid message JSON: {}", e),
+        }
+    })?;
+
+    match tag.kind.as_deref().unwrap_or("unknown") {
+        "request_receipt" => Ok(NoisePaymentMessageType::ReceiptRequest),
+        "confirm_receipt" => Ok(NoisePaymentMessageType::ReceiptConfirmation),
+        "private_endpoint_offer" => Ok(NoisePaymentMessageType::PrivateEndpointOffer),
+        "error" => Ok(NoisePaymentMessageType::Error),
+        "ping" => Ok(NoisePaymentMessageType::Ping),
+        "pong" => Ok(NoisePaymentMessageType::Pong),
+        other => Err(PaykitMobileError::Validation {
+            msg: format!("Unknown message type: {}", other),
+        }),
+    }
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -529,6 +688,45 @@ mod tests {
         assert_eq!(endpoint.metadata, Some("Test endpoint".to_string()));
     }
 
+    #[test]
+    fn test_noise_key_rotation_overlap() {
+        let auth = AuthenticatedTransportFFI::new_mock("test_owner".to_string());
+        let unauth = UnauthenticatedTransportFFI::from_authenticated(auth.clone()).unwrap();
+
+        publish_noise_endpoint(
+            auth.clone(),
+            "127.0.0.1".to_string(),
+            8888,
+            "aa".to_string(),
+            None,
+        )
+        .unwrap();
+        let stale = discover_noise_endpoint(unauth.clone(), "test_owner".to_string())
+            .unwrap()
+            .unwrap();
+
+        // Activate the next key immediately with an hour of overlap
+        schedule_noise_key_rotation(auth.clone(), "bb".to_string(), 1, now_timestamp(), 3600)
+            .unwrap();
+
+        assert_eq!(accepted_noise_epochs(auth).unwrap(), vec![0, 1]);
+        assert_eq!(
+            discover_noise_keys(unauth.clone(), "test_owner".to_string())
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(is_noise_key_mismatch(
+            paykit_lib::protocol::KEY_MISMATCH_HINT.to_vec()
+        ));
+
+        let refreshed = refresh_noise_endpoint(unauth.clone(), stale)
+            .unwrap()
+            .unwrap();
+        assert_eq!(refreshed.server_noise_pubkey, "bb");
+        assert!(refresh_noise_endpoint(unauth, refreshed).unwrap().is_none());
+    }
+
     #[test]
     fn test_remove_noise_endpoint() {
         let auth = AuthenticatedTransportFFI::new_mock("test_owner".to_string());
@@ -643,6 +841,16 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_parse_payment_message_bytes() {
+        let json = r#"{"type":"ping","large":{"ignored":[1,2,3]}}"#;
+        let msg = parse_payment_message_bytes(json.as_bytes().to_vec()).unwrap();
+
+        assert!(matches!(msg.message_type, NoisePaymentMessageType::Ping));
+        assert_eq!(msg.payload_json, json);
+        assert!(parse_payment_message_bytes(vec![0xff, 0xfe]).is_err());
+    }
+
     #[test]
     fn test_parse_unknown_message_type() {
         let json = r#"{"type":"unknown_type"}"#;
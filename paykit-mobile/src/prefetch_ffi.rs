@@ -0,0 +1,283 @@
+//! Payee Prefetch FFI Bindings
+//!
+//! Checkout on mobile used to read the payee's supported payments, status
+//! document and Noise endpoint one call after another. This module reads
+//! them concurrently and returns a single [`PayeeSnapshotFFI`], so apps can
+//! "warm up" a payee as soon as it is scanned or selected.
+//!
+//! # Example Flow
+//!
+//! ```ignore
+//! let prefetcher = PayeePrefetcherFFI::new(120);
+//!
+//! // On scan: start loading in the background
+//! prefetcher.prefetch(transport, payee_key);
+//!
+//! // On the checkout screen: served from memory while fresh
+//! let snapshot = prefetcher.prefetch(transport, payee_key);
+//! if !snapshot.accepting_payments { show_paused(snapshot.status_message) }
+//! ```
+
+use crate::noise_ffi::{discover_noise_endpoint, NoiseEndpointInfo};
+use crate::transport_ffi::{fetch_supported_payments, UnauthenticatedTransportFFI};
+use crate::{PaykitMobileError, PaymentMethod, Result};
+use paykit_lib::protocol::{payee_status_path, PayeeStatus};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Default time a snapshot is reused, in seconds.
+const DEFAULT_TTL_SECS: i64 = paykit_lib::prefetch::DEFAULT_SNAPSHOT_TTL_SECS;
+
+fn now_timestamp() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+// ============================================================================
+// FFI Types
+// ============================================================================
+
+/// Everything checkout needs to know about a payee, fetched together.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct PayeeSnapshotFFI {
+    /// The payee's public key (z-base32 encoded).
+    pub payee: String,
+    /// Published payment methods.
+    pub methods: Vec<PaymentMethod>,
+    /// Whether the payee currently accepts payments.
+    pub accepting_payments: bool,
+    /// Methods the payee marked temporarily unavailable.
+    pub unavailable_methods: Vec<String>,
+    /// Method the payee would prefer right now.
+    pub preferred_method: Option<String>,
+    /// Message from the payee's status document.
+    pub status_message: Option<String>,
+    /// The payee's Noise endpoint, if published.
+    pub noise_endpoint: Option<NoiseEndpointInfo>,
+    /// When the snapshot was taken (unix seconds).
+    pub fetched_at: i64,
+    /// Parts that could not be fetched, as "part: error".
+    pub failures: Vec<String>,
+}
+
+impl PayeeSnapshotFFI {
+    fn is_fresh(&self, now: i64, ttl_secs: i64) -> bool {
+        now - self.fetched_at < ttl_secs
+    }
+}
+
+// ============================================================================
+// Prefetch
+// ============================================================================
+
+fn fetch_status(
+    transport: &UnauthenticatedTransportFFI,
+    owner_pubkey: &str,
+    now: i64,
+) -> Result<Option<PayeeStatus>> {
+    match transport.get(owner_pubkey.to_string(), payee_status_path().to_string())? {
+        Some(json) => {
+            let status: PayeeStatus =
+                serde_json::from_str(&json).map_err(|e| PaykitMobileError::Serialization {
+                    msg: format!("Invalid payee status: {}", e),
+                })?;
+            Ok(status.is_fresh(now).then_some(status))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Fetch a payee's supported payments, status and Noise endpoint concurrently.
+///
+/// Failed reads are listed in `failures` instead of failing the call.
+#[uniffi::export]
+pub fn prefetch_payee(
+    transport: Arc<UnauthenticatedTransportFFI>,
+    owner_pubkey: String,
+) -> PayeeSnapshotFFI {
+    let now = now_timestamp();
+    let (methods, status, noise) = std::thread::scope(|scope| {
+        let methods = scope.spawn(|| fetch_supported_payments(&transport, &owner_pubkey));
+        let status = scope.spawn(|| fetch_status(&transport, &owner_pubkey, now));
+        let noise =
+            scope.spawn(|| discover_noise_endpoint(transport.clone(), owner_pubkey.clone()));
+        (join(methods), join(status), join(noise))
+    });
+
+    let mut failures = Vec::new();
+    let methods = methods.unwrap_or_else(|e| {
+        failures.push(format!("supported_payments: {}", e));
+        Vec::new()
+    });
+    let status = status.unwrap_or_else(|e| {
+        failures.push(format!("status: {}", e));
+        None
+    });
+    let noise_endpoint = noise.unwrap_or_else(|e| {
+        failures.push(format!("noise_endpoint: {}", e));
+        None
+    });
+
+    PayeeSnapshotFFI {
+        payee: owner_pubkey,
+        methods,
+        accepting_payments: status.as_ref().is_none_or(|s| s.is_accepting(now)),
+        unavailable_methods: status
+            .as_ref()
+            .map(|s| s.unavailable_methods.iter().map(|m| m.0.clone()).collect())
+            .unwrap_or_default(),
+        preferred_method: status
+            .as_ref()
+            .and_then(|s| s.preferred_method.as_ref().map(|m| m.0.clone())),
+        status_message: status.and_then(|s| s.message),
+        noise_endpoint,
+        fetched_at: now,
+        failures,
+    }
+}
+
+fn join<T>(handle: std::thread::ScopedJoinHandle<'_, Result<T>>) -> Result<T> {
+    handle.join().unwrap_or_else(|_| {
+        Err(PaykitMobileError::Internal {
+            msg: "Prefetch worker panicked".to_string(),
+        })
+    })
+}
+
+// ============================================================================
+// Snapshot Cache
+// ============================================================================
+
+/// Prefetches payees and keeps recent snapshots in memory.
+#[derive(uniffi::Object)]
+pub struct PayeePrefetcherFFI {
+    ttl_secs: i64,
+    snapshots: Mutex<HashMap<String, PayeeSnapshotFFI>>,
+}
+
+impl PayeePrefetcherFFI {
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, PayeeSnapshotFFI>> {
+        self.snapshots.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl crate::memory_ffi::MemoryReporter for PayeePrefetcherFFI {
+    fn memory_usage(&self) -> Vec<crate::memory_ffi::SubsystemMemoryFFI> {
+        vec![crate::memory_ffi::map_usage(
+            "prefetch.snapshots",
+            &self.lock(),
+        )]
+    }
+}
+
+#[uniffi::export]
+impl PayeePrefetcherFFI {
+    /// Create a prefetcher that reuses snapshots for `ttl_secs` (0 for the default).
+    #[uniffi::constructor]
+    pub fn new(ttl_secs: i64) -> Arc<Self> {
+        Arc::new(Self {
+            ttl_secs: if ttl_secs > 0 {
+                ttl_secs
+            } else {
+                DEFAULT_TTL_SECS
+            },
+            snapshots: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Return a fresh cached snapshot, or prefetch and cache a new one.
+    pub fn prefetch(
+        &self,
+        transport: Arc<UnauthenticatedTransportFFI>,
+        owner_pubkey: String,
+    ) -> PayeeSnapshotFFI {
+        if let Some(snapshot) = self.cached(owner_pubkey.clone()) {
+            return snapshot;
+        }
+        let snapshot = prefetch_payee(transport, owner_pubkey.clone());
+        self.lock().insert(owner_pubkey, snapshot.clone());
+        snapshot
+    }
+
+    /// Get a cached snapshot if it is still fresh.
+    pub fn cached(&self, owner_pubkey: String) -> Option<PayeeSnapshotFFI> {
+        let now = now_timestamp();
+        let mut snapshots = self.lock();
+        snapshots.retain(|_, snapshot| snapshot.is_fresh(now, self.ttl_secs));
+        snapshots.get(&owner_pubkey).cloned()
+    }
+
+    /// Forget the snapshot for a payee, e.g. after a payment failed.
+    pub fn invalidate(&self, owner_pubkey: String) {
+        self.lock().remove(&owner_pubkey);
+    }
+
+    /// Forget all snapshots.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::noise_ffi::publish_noise_endpoint;
+    use crate::transport_ffi::publish_payment_endpoint;
+    use crate::AuthenticatedTransportFFI;
+    use paykit_lib::MethodId;
+
+    #[test]
+    fn test_prefetch_payee_snapshot() {
+        let auth = AuthenticatedTransportFFI::new_mock("payee".to_string());
+        let unauth = UnauthenticatedTransportFFI::from_authenticated(auth.clone()).unwrap();
+
+        publish_payment_endpoint(&auth, "lightning", "lnurl1abc").unwrap();
+        publish_noise_endpoint(
+            auth.clone(),
+            "127.0.0.1".to_string(),
+            9000,
+            "aa".to_string(),
+            None,
+        )
+        .unwrap();
+        let status = PayeeStatus::new(now_timestamp())
+            .with_unavailable(MethodId("onchain".into()))
+            .with_message("back soon");
+        auth.put(
+            payee_status_path().to_string(),
+            serde_json::to_string(&status).unwrap(),
+        )
+        .unwrap();
+
+        let snapshot = prefetch_payee(unauth, "payee".to_string());
+        assert!(snapshot.failures.is_empty());
+        assert!(snapshot.accepting_payments);
+        assert_eq!(snapshot.unavailable_methods, vec!["onchain".to_string()]);
+        assert_eq!(snapshot.status_message.as_deref(), Some("back soon"));
+        assert_eq!(snapshot.noise_endpoint.unwrap().port, 9000);
+        assert!(snapshot
+            .methods
+            .iter()
+            .any(|m| m.method_id == "lightning" && m.endpoint == "lnurl1abc"));
+    }
+
+    #[test]
+    fn test_prefetcher_caches_snapshot() {
+        let auth = AuthenticatedTransportFFI::new_mock("payee".to_string());
+        let unauth = UnauthenticatedTransportFFI::from_authenticated(auth.clone()).unwrap();
+        let prefetcher = PayeePrefetcherFFI::new(60);
+
+        prefetcher.prefetch(unauth.clone(), "payee".to_string());
+        publish_payment_endpoint(&auth, "lightning", "lnurl1abc").unwrap();
+
+        // Served from the cache until invalidated
+        let cached = prefetcher.prefetch(unauth.clone(), "payee".to_string());
+        assert!(cached.methods.is_empty());
+
+        prefetcher.invalidate("payee".to_string());
+        let refreshed = prefetcher.prefetch(unauth, "payee".to_string());
+        assert_eq!(refreshed.methods.len(), 1);
+    }
+}
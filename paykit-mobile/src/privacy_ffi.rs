@@ -0,0 +1,142 @@
+//! Privacy Scoring FFI Types
+//!
+//! FFI-safe types for scoring a payment before it is executed. Apps call
+//! `PaykitClient::analyze_payment_privacy()` while showing the payment
+//! preview and surface the returned warnings before the user confirms.
+//!
+//! # Example Flow
+//!
+//! ```ignore
+//! let report = client.analyze_payment_privacy(PlannedPaymentFFI {
+//!     method_id: "onchain".into(),
+//!     is_private_endpoint: false,
+//!     amount_sats: Some(100_000),
+//!     memo: Some("rent".into()),
+//!     memo_encrypted: false,
+//!     prior_payments_to_endpoint: 2,
+//! });
+//! if report.has_warnings {
+//!     // show report.leaks[..].description next to the confirm button
+//! }
+//! ```
+
+use paykit_lib::policy::{
+    EndpointSource, PlannedPayment, PrivacyLeak, PrivacyReport, PrivacySeverity,
+};
+
+/// FFI-safe description of a payment about to be made.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct PlannedPaymentFFI {
+    /// Payment method (e.g. "lightning", "onchain")
+    pub method_id: String,
+    /// Whether the endpoint was shared privately rather than published
+    pub is_private_endpoint: bool,
+    /// Amount in satoshis, if known
+    pub amount_sats: Option<u64>,
+    /// Memo sent with the payment, if any
+    pub memo: Option<String>,
+    /// Whether the memo only travels over an encrypted channel
+    pub memo_encrypted: bool,
+    /// Earlier payments made to the same endpoint
+    pub prior_payments_to_endpoint: u32,
+}
+
+impl From<PlannedPaymentFFI> for PlannedPayment {
+    fn from(payment: PlannedPaymentFFI) -> Self {
+        let source = if payment.is_private_endpoint {
+            EndpointSource::Private
+        } else {
+            EndpointSource::Public
+        };
+        let mut planned = PlannedPayment::new(paykit_lib::MethodId(payment.method_id), source)
+            .with_prior_payments(payment.prior_payments_to_endpoint);
+        if let Some(amount_sats) = payment.amount_sats {
+            planned = planned.with_amount_sats(amount_sats);
+        }
+        if let Some(memo) = payment.memo {
+            planned = planned.with_memo(memo, payment.memo_encrypted);
+        }
+        planned
+    }
+}
+
+/// FFI-safe privacy leak.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct PrivacyLeakFFI {
+    /// Leak kind ("address_reuse", "public_endpoint", "identifiable_change",
+    /// "round_amount", "input_merge", "cleartext_memo")
+    pub kind: String,
+    /// Severity ("low", "medium", "high")
+    pub severity: String,
+    /// Human-readable description
+    pub description: String,
+}
+
+impl From<&PrivacyLeak> for PrivacyLeakFFI {
+    fn from(leak: &PrivacyLeak) -> Self {
+        let kind = match leak {
+            PrivacyLeak::AddressReuse { .. } => "address_reuse",
+            PrivacyLeak::PublicEndpoint => "public_endpoint",
+            PrivacyLeak::IdentifiableChange => "identifiable_change",
+            PrivacyLeak::RoundAmount { .. } => "round_amount",
+            PrivacyLeak::InputMerge { .. } => "input_merge",
+            PrivacyLeak::CleartextMemo => "cleartext_memo",
+        };
+        let severity = match leak.severity() {
+            PrivacySeverity::Low => "low",
+            PrivacySeverity::Medium => "medium",
+            PrivacySeverity::High => "high",
+        };
+        Self {
+            kind: kind.to_string(),
+            severity: severity.to_string(),
+            description: leak.describe(),
+        }
+    }
+}
+
+/// FFI-safe privacy report.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct PrivacyReportFFI {
+    /// 100 for no known leaks, lower for more and worse leaks
+    pub score: u8,
+    /// Leaks found, worst first
+    pub leaks: Vec<PrivacyLeakFFI>,
+    /// Whether the user should confirm before paying
+    pub has_warnings: bool,
+}
+
+impl From<PrivacyReport> for PrivacyReportFFI {
+    fn from(report: PrivacyReport) -> Self {
+        Self {
+            score: report.score,
+            leaks: report.leaks.iter().map(Into::into).collect(),
+            has_warnings: report.has_warnings(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paykit_lib::policy::PrivacyAnalyzer;
+
+    #[test]
+    fn test_public_cleartext_memo_report() {
+        let planned = PlannedPaymentFFI {
+            method_id: "lightning".to_string(),
+            is_private_endpoint: false,
+            amount_sats: Some(1_000),
+            memo: Some("coffee".to_string()),
+            memo_encrypted: false,
+            prior_payments_to_endpoint: 0,
+        };
+        let report: PrivacyReportFFI = PrivacyAnalyzer::default().analyze(&planned.into()).into();
+
+        assert_eq!(report.score, 70);
+        assert!(report.has_warnings);
+        let kinds: Vec<_> = report.leaks.iter().map(|l| l.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["public_endpoint", "cleartext_memo"]);
+        assert_eq!(report.leaks[0].severity, "medium");
+    }
+}
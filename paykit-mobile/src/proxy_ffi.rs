@@ -0,0 +1,188 @@
+//! Proxy Configuration FFI Bindings
+//!
+//! Mobile apps perform their own HTTP and socket I/O (through the transport
+//! callbacks and their Noise connectors), so the client only stores the proxy
+//! configuration and answers which proxy each transport should use. Apps
+//! apply it to their URLSession / OkHttp clients and socket connectors.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let config = PaykitClientConfig {
+//!     proxy: Some(tor_proxy()),
+//!     proxy_overrides: vec![ProxyOverrideFFI { transport: TransportKindFFI::Lnd, proxy: None }],
+//!     ..default_client_config()
+//! };
+//! let client = PaykitClient::new_with_config(config)?;
+//!
+//! if let Some(url) = client.proxy_url_for(TransportKindFFI::Pubky) {
+//!     configure_http_client(url); // "socks5h://127.0.0.1:9050"
+//! }
+//! ```
+
+use crate::executor_ffi::{BitcoinNetworkFFI, LightningNetworkFFI};
+use crate::Result;
+use paykit_lib::proxy::{ProxyConfig, Socks5Proxy, TransportKind};
+
+// ============================================================================
+// FFI Types
+// ============================================================================
+
+/// Network transport that can be routed through a proxy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, uniffi::Enum)]
+pub enum TransportKindFFI {
+    /// Pubky homeserver and relay HTTP traffic
+    Pubky,
+    /// Esplora block explorer API
+    Esplora,
+    /// LND REST API
+    Lnd,
+    /// Outgoing Noise TCP connections
+    Noise,
+}
+
+impl From<TransportKindFFI> for TransportKind {
+    fn from(kind: TransportKindFFI) -> Self {
+        match kind {
+            TransportKindFFI::Pubky => TransportKind::Pubky,
+            TransportKindFFI::Esplora => TransportKind::Esplora,
+            TransportKindFFI::Lnd => TransportKind::Lnd,
+            TransportKindFFI::Noise => TransportKind::Noise,
+        }
+    }
+}
+
+/// FFI-safe SOCKS5 proxy.
+#[derive(Clone, Debug, PartialEq, Eq, uniffi::Record)]
+pub struct Socks5ProxyFFI {
+    /// Proxy host
+    pub host: String,
+    /// Proxy port
+    pub port: u16,
+    /// Resolve host names at the proxy (required for Tor)
+    pub remote_dns: bool,
+    /// Optional username (Tor uses it for circuit isolation)
+    pub username: Option<String>,
+    /// Optional password
+    pub password: Option<String>,
+}
+
+impl From<Socks5ProxyFFI> for Socks5Proxy {
+    fn from(proxy: Socks5ProxyFFI) -> Self {
+        Self {
+            host: proxy.host,
+            port: proxy.port,
+            remote_dns: proxy.remote_dns,
+            username: proxy.username,
+            password: proxy.password,
+        }
+    }
+}
+
+impl From<&Socks5Proxy> for Socks5ProxyFFI {
+    fn from(proxy: &Socks5Proxy) -> Self {
+        Self {
+            host: proxy.host.clone(),
+            port: proxy.port,
+            remote_dns: proxy.remote_dns,
+            username: proxy.username.clone(),
+            password: proxy.password.clone(),
+        }
+    }
+}
+
+/// Per-transport proxy override. `proxy: None` connects directly.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct ProxyOverrideFFI {
+    /// Transport the override applies to
+    pub transport: TransportKindFFI,
+    /// Proxy to use instead of the default, or none for a direct connection
+    pub proxy: Option<Socks5ProxyFFI>,
+}
+
+/// Configuration for creating a `PaykitClient`.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct PaykitClientConfig {
+    /// Bitcoin network to use
+    pub bitcoin_network: BitcoinNetworkFFI,
+    /// Lightning network to use
+    pub lightning_network: LightningNetworkFFI,
+    /// Default SOCKS5 proxy for all transports
+    pub proxy: Option<Socks5ProxyFFI>,
+    /// Per-transport overrides of the default proxy
+    pub proxy_overrides: Vec<ProxyOverrideFFI>,
+}
+
+impl PaykitClientConfig {
+    pub(crate) fn proxy_config(&self) -> ProxyConfig {
+        let mut config = ProxyConfig {
+            proxy: self.proxy.clone().map(Into::into),
+            ..Default::default()
+        };
+        for entry in &self.proxy_overrides {
+            config =
+                config.with_override(entry.transport.into(), entry.proxy.clone().map(Into::into));
+        }
+        config
+    }
+}
+
+// ============================================================================
+// Functions
+// ============================================================================
+
+/// Default client configuration: mainnet, no proxy.
+#[uniffi::export]
+pub fn default_client_config() -> PaykitClientConfig {
+    PaykitClientConfig {
+        bitcoin_network: BitcoinNetworkFFI::Mainnet,
+        lightning_network: LightningNetworkFFI::Mainnet,
+        proxy: None,
+        proxy_overrides: Vec::new(),
+    }
+}
+
+/// The local Tor daemon's SOCKS port (`127.0.0.1:9050`).
+#[uniffi::export]
+pub fn tor_proxy() -> Socks5ProxyFFI {
+    (&Socks5Proxy::tor()).into()
+}
+
+/// Parse a proxy URL such as `socks5h://127.0.0.1:9050`.
+#[uniffi::export]
+pub fn parse_proxy_url(url: String) -> Result<Socks5ProxyFFI> {
+    Ok((&Socks5Proxy::parse(&url)?).into())
+}
+
+/// Format a proxy as a URL for HTTP client configuration.
+#[uniffi::export]
+pub fn proxy_url(proxy: Socks5ProxyFFI) -> String {
+    Socks5Proxy::from(proxy).url()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_config_overrides() {
+        let config = PaykitClientConfig {
+            proxy: Some(tor_proxy()),
+            proxy_overrides: vec![ProxyOverrideFFI {
+                transport: TransportKindFFI::Lnd,
+                proxy: None,
+            }],
+            ..default_client_config()
+        };
+        let proxy = config.proxy_config();
+        assert!(proxy.for_transport(TransportKind::Lnd).is_none());
+        assert_eq!(
+            proxy.for_transport(TransportKind::Noise).unwrap().url(),
+            "socks5h://127.0.0.1:9050"
+        );
+        assert_eq!(
+            parse_proxy_url("socks5h://127.0.0.1:9050".to_string()).unwrap(),
+            tor_proxy()
+        );
+    }
+}
@@ -0,0 +1,256 @@
+//! Push Notification FFI Bindings
+//!
+//! Messages left in a peer inbox wait until the app polls. Registering the
+//! device's APNS/FCM token publishes a push hint: the token sealed to a
+//! notification relay the user picked, so senders can ask that relay to wake
+//! the app without ever seeing the token.
+//!
+//! # Example Flow
+//!
+//! ```ignore
+//! let registrar = PushRegistrarFFI::new(transport, relay_url, relay_pubkey_hex,
+//!     PushPlatformFFI::Apns, Some("app.bitkit".into()))?;
+//!
+//! // In didRegisterForRemoteNotifications / onNewToken; publishes only when
+//! // the token changed
+//! registrar.register(device_token)?;
+//!
+//! // Sender, after leaving a message in the recipient's inbox
+//! if let Some(wake) = push_wake_up(reader, recipient)? {
+//!     http_post(wake.relay_url, wake.body_json);
+//! }
+//! ```
+
+use crate::transport_ffi::{AuthenticatedTransportFFI, UnauthenticatedTransportFFI};
+use crate::{PaykitMobileError, Result};
+use paykit_interactive::inbox::seal_push_registration;
+use paykit_lib::protocol::{
+    parse_document, push_hint_path, PushHintRecord, PushPlatform, PushRegistration,
+    MAX_DOCUMENT_BYTES,
+};
+use paykit_lib::PublicKey;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+fn now_timestamp() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+// ============================================================================
+// FFI Types
+// ============================================================================
+
+/// Push service a device token belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, uniffi::Enum)]
+pub enum PushPlatformFFI {
+    /// Apple Push Notification service
+    Apns,
+    /// Firebase Cloud Messaging
+    Fcm,
+}
+
+impl From<PushPlatformFFI> for PushPlatform {
+    fn from(platform: PushPlatformFFI) -> Self {
+        match platform {
+            PushPlatformFFI::Apns => PushPlatform::Apns,
+            PushPlatformFFI::Fcm => PushPlatform::Fcm,
+        }
+    }
+}
+
+/// A wake-up request for a sender to POST.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct PushWakeUpFFI {
+    /// Relay endpoint to POST to.
+    pub relay_url: String,
+    /// JSON request body.
+    pub body_json: String,
+}
+
+fn parse_pubkey(pubkey: &str) -> Result<PublicKey> {
+    PublicKey::from_str(pubkey).map_err(|e| PaykitMobileError::Validation {
+        msg: format!("Invalid public key: {}", e),
+    })
+}
+
+// ============================================================================
+// Registration
+// ============================================================================
+
+/// Publishes and rotates this device's push hint.
+#[derive(uniffi::Object)]
+pub struct PushRegistrarFFI {
+    transport: Arc<AuthenticatedTransportFFI>,
+    owner: PublicKey,
+    relay_url: String,
+    relay_pubkey: [u8; 32],
+    platform: PushPlatformFFI,
+    app_id: Option<String>,
+    last_token: Mutex<Option<String>>,
+}
+
+impl PushRegistrarFFI {
+    fn last_token(&self) -> std::sync::MutexGuard<'_, Option<String>> {
+        self.last_token.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[uniffi::export]
+impl PushRegistrarFFI {
+    /// Create a registrar publishing through `transport`.
+    ///
+    /// # Arguments
+    ///
+    /// * `relay_url` - https endpoint of the notification relay
+    /// * `relay_pubkey_hex` - Relay's X25519 public key (64 hex characters)
+    /// * `platform` - Push service of this device
+    /// * `app_id` - Bundle or package ID, if the relay serves several apps
+    #[uniffi::constructor]
+    pub fn new(
+        transport: Arc<AuthenticatedTransportFFI>,
+        relay_url: String,
+        relay_pubkey_hex: String,
+        platform: PushPlatformFFI,
+        app_id: Option<String>,
+    ) -> Result<Arc<Self>> {
+        let owner = parse_pubkey(&transport.owner_pubkey())?;
+        let relay_pubkey = hex::decode(&relay_pubkey_hex)
+            .ok()
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .ok_or_else(|| PaykitMobileError::Validation {
+                msg: "Relay public key must be 64 hex characters".to_string(),
+            })?;
+        Ok(Arc::new(Self {
+            transport,
+            owner,
+            relay_url,
+            relay_pubkey,
+            platform,
+            app_id,
+            last_token: Mutex::new(None),
+        }))
+    }
+
+    /// Publish the hint for `token` if it differs from the last one.
+    ///
+    /// Push services hand out the token on every launch and rotate it from
+    /// time to time, so call this each time the OS reports a token. Returns
+    /// whether a new hint was published.
+    pub fn register(&self, token: String) -> Result<bool> {
+        if token.is_empty() {
+            return Err(PaykitMobileError::Validation {
+                msg: "Push token cannot be empty".to_string(),
+            });
+        }
+        let mut last_token = self.last_token();
+        if last_token.as_deref() == Some(token.as_str()) {
+            return Ok(false);
+        }
+
+        let registration = PushRegistration {
+            platform: self.platform.into(),
+            token: token.clone(),
+            app_id: self.app_id.clone(),
+        };
+        let sealed_token = seal_push_registration(&self.owner, &self.relay_pubkey, &registration)
+            .map_err(|e| PaykitMobileError::Internal { msg: e.to_string() })?;
+        let record = PushHintRecord {
+            relay_url: self.relay_url.clone(),
+            relay_pubkey: hex::encode(self.relay_pubkey),
+            sealed_token,
+            updated_at: now_timestamp(),
+        };
+        record
+            .validate()
+            .map_err(|e| PaykitMobileError::Validation { msg: e.to_string() })?;
+        let json = serde_json::to_string(&record)
+            .map_err(|e| PaykitMobileError::Serialization { msg: e.to_string() })?;
+
+        self.transport.put(push_hint_path().to_string(), json)?;
+        *last_token = Some(token);
+        Ok(true)
+    }
+
+    /// Remove the hint, e.g. when the user turns notifications off.
+    pub fn unregister(&self) -> Result<()> {
+        self.transport.delete(push_hint_path().to_string())?;
+        *self.last_token() = None;
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Wake-up
+// ============================================================================
+
+/// Build the wake-up request for `recipient`, if it published a push hint.
+///
+/// Call after leaving a message in the recipient's inbox and POST
+/// `body_json` to `relay_url`.
+#[uniffi::export]
+pub fn push_wake_up(
+    transport: Arc<UnauthenticatedTransportFFI>,
+    recipient: String,
+) -> Result<Option<PushWakeUpFFI>> {
+    let recipient_key = parse_pubkey(&recipient)?;
+    let Some(json) = transport.get(recipient, push_hint_path().to_string())? else {
+        return Ok(None);
+    };
+    let record: PushHintRecord =
+        parse_document("push_hint", json.as_bytes(), MAX_DOCUMENT_BYTES)
+            .map_err(|e| PaykitMobileError::Serialization { msg: e.to_string() })?;
+    let body_json = serde_json::to_string(&record.wake_up(&recipient_key))
+        .map_err(|e| PaykitMobileError::Serialization { msg: e.to_string() })?;
+    Ok(Some(PushWakeUpFFI {
+        relay_url: record.relay_url,
+        body_json,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paykit_interactive::inbox::open_push_registration;
+    use paykit_lib::protocol::WakeUpRequest;
+
+    #[test]
+    fn test_register_rotate_and_wake() {
+        let identity = crate::keys::generate_ed25519_keypair().unwrap();
+        let owner = identity.public_key_z32;
+        let auth = AuthenticatedTransportFFI::new_mock(owner.clone());
+        let unauth = UnauthenticatedTransportFFI::from_authenticated(auth.clone()).unwrap();
+        let relay =
+            crate::keys::derive_x25519_keypair(identity.secret_key_hex, "relay".into(), 0).unwrap();
+        let relay_sk: [u8; 32] = hex::decode(&relay.secret_key_hex)
+            .unwrap()
+            .try_into()
+            .unwrap();
+
+        let registrar = PushRegistrarFFI::new(
+            auth,
+            "https://push.example.com/wake".to_string(),
+            relay.public_key_hex,
+            PushPlatformFFI::Apns,
+            None,
+        )
+        .unwrap();
+        assert!(registrar.register("token-1".to_string()).unwrap());
+        assert!(!registrar.register("token-1".to_string()).unwrap());
+        assert!(registrar.register("token-2".to_string()).unwrap());
+
+        let wake = push_wake_up(unauth.clone(), owner.clone())
+            .unwrap()
+            .unwrap();
+        assert_eq!(wake.relay_url, "https://push.example.com/wake");
+        let request: WakeUpRequest = serde_json::from_str(&wake.body_json).unwrap();
+        let registration = open_push_registration(&relay_sk, &request).unwrap();
+        assert_eq!(registration.token, "token-2");
+        assert_eq!(registration.platform, PushPlatform::Apns);
+
+        registrar.unregister().unwrap();
+        assert!(push_wake_up(unauth, owner).unwrap().is_none());
+    }
+}
@@ -0,0 +1,296 @@
+//! Receipt Reporting FFI Bindings
+//!
+//! Read-side helpers for receipts that have already been collected: parse
+//! an exported receipt file, check payment proofs, flatten receipts to CSV
+//! and total them up. None of these touch the network or a wallet, which
+//! makes them the natural surface for scripting (the Python bindings) as
+//! well as for history screens in the apps.
+//!
+//! # Example Flow
+//!
+//! ```ignore
+//! let receipts = parse_receipts_json(exported_json)?;
+//!
+//! let summary = summarize_receipts(receipts.clone());
+//! for row in summary.by_method {
+//!     println!("{} {} {}", row.key, row.total, row.currency);
+//! }
+//!
+//! std::fs::write("receipts.csv", export_receipts_csv(receipts))?;
+//! ```
+
+use crate::interactive_ffi::WireReceipt;
+use crate::{PaykitMobileError, ReceiptRequest, Result};
+use paykit_lib::methods::{preimage_matches_hash, PaymentProof};
+use serde_json::value::RawValue;
+use std::collections::BTreeMap;
+
+/// Currency assumed for receipts that do not name one.
+const DEFAULT_CURRENCY: &str = "SAT";
+
+// ============================================================================
+// FFI Types
+// ============================================================================
+
+/// FFI-safe outcome of a payment proof check.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct ProofVerificationFFI {
+    /// Whether the proof checks out
+    pub valid: bool,
+    /// Method the proof is for ("lightning", "onchain" or a custom ID)
+    pub method_id: String,
+    /// Whether `valid` rests on cryptography rather than format alone
+    pub cryptographic: bool,
+    /// Why the proof was rejected
+    pub errors: Vec<String>,
+}
+
+/// FFI-safe total for one group of receipts.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct ReceiptTotalFFI {
+    /// Group key: a method ID or a counterparty public key
+    pub key: String,
+    /// Currency of the total
+    pub currency: String,
+    /// Number of receipts in the group
+    pub receipt_count: u32,
+    /// Sum of the group's integer amounts
+    pub total: String,
+}
+
+/// FFI-safe summary of a set of receipts.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct ReceiptAnalyticsFFI {
+    /// Number of receipts summarized
+    pub receipt_count: u32,
+    /// Receipts left out of the totals for lacking an integer amount
+    pub unpriced_count: u32,
+    /// Totals per currency; `key` repeats the currency
+    pub by_currency: Vec<ReceiptTotalFFI>,
+    /// Totals per payment method and currency
+    pub by_method: Vec<ReceiptTotalFFI>,
+    /// Totals per payee and currency
+    pub by_payee: Vec<ReceiptTotalFFI>,
+    /// Totals per payer and currency
+    pub by_payer: Vec<ReceiptTotalFFI>,
+}
+
+// ============================================================================
+// Exported Functions
+// ============================================================================
+
+/// Parse receipts in the format written by `ReceiptStore::export_receipts_json`.
+///
+/// Entries that are not receipts are skipped, as on import.
+#[uniffi::export]
+pub fn parse_receipts_json(json: String) -> Result<Vec<ReceiptRequest>> {
+    let list: Vec<&RawValue> = serde_json::from_str(&json)
+        .map_err(|e| PaykitMobileError::Serialization { msg: e.to_string() })?;
+
+    Ok(list
+        .into_iter()
+        .filter_map(|raw| serde_json::from_str::<WireReceipt>(raw.get()).ok())
+        .map(ReceiptRequest::from)
+        .collect())
+}
+
+/// Check a payment proof (`{"type": "LightningPreimage", ...}`,
+/// `{"type": "BitcoinTxid", ...}` or `{"type": "Custom", ...}`).
+///
+/// Lightning preimages are verified against the payment hash. On-chain
+/// proofs can only be checked for a well-formed txid without a chain
+/// source, and custom proofs are never accepted here.
+#[uniffi::export]
+pub fn verify_payment_proof(proof_json: String) -> Result<ProofVerificationFFI> {
+    let proof: PaymentProof = serde_json::from_str(&proof_json)
+        .map_err(|e| PaykitMobileError::Serialization { msg: e.to_string() })?;
+
+    let (method_id, cryptographic, error) = match &proof {
+        PaymentProof::LightningPreimage {
+            preimage,
+            payment_hash,
+        } => (
+            "lightning".to_string(),
+            true,
+            (!preimage_matches_hash(preimage, payment_hash))
+                .then_some("Preimage does not match payment hash"),
+        ),
+        PaymentProof::BitcoinTxid { txid, .. } => (
+            "onchain".to_string(),
+            false,
+            (txid.len() != 64 || !txid.chars().all(|c| c.is_ascii_hexdigit()))
+                .then_some("Invalid or pending txid"),
+        ),
+        PaymentProof::Custom { method, .. } => (
+            method.0.clone(),
+            false,
+            Some("Custom proofs must be verified by the method's handler"),
+        ),
+    };
+
+    Ok(ProofVerificationFFI {
+        valid: error.is_none(),
+        method_id,
+        cryptographic,
+        errors: error.map(str::to_string).into_iter().collect(),
+    })
+}
+
+/// Flatten receipts to CSV, one row per receipt after a header row.
+#[uniffi::export]
+pub fn export_receipts_csv(receipts: Vec<ReceiptRequest>) -> String {
+    let mut csv = String::from("receipt_id,payer,payee,method_id,amount,currency,metadata\n");
+    for receipt in &receipts {
+        let fields: [&str; 7] = [
+            &receipt.receipt_id,
+            &receipt.payer,
+            &receipt.payee,
+            &receipt.method_id,
+            receipt.amount.as_deref().unwrap_or_default(),
+            receipt.currency.as_deref().unwrap_or_default(),
+            &receipt.metadata_json,
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Total receipts by currency, method, payee and payer.
+///
+/// Amounts are summed as integers in their own currency, matching the
+/// receipt search's `min_amount` rule; receipts without an integer amount
+/// are counted in `unpriced_count` only.
+#[uniffi::export]
+pub fn summarize_receipts(receipts: Vec<ReceiptRequest>) -> ReceiptAnalyticsFFI {
+    let mut by_currency = Totals::default();
+    let mut by_method = Totals::default();
+    let mut by_payee = Totals::default();
+    let mut by_payer = Totals::default();
+    let mut unpriced_count = 0;
+
+    for receipt in &receipts {
+        let Some(amount) = receipt
+            .amount
+            .as_deref()
+            .and_then(|a| a.trim().parse::<u64>().ok())
+        else {
+            unpriced_count += 1;
+            continue;
+        };
+        let currency = receipt.currency.as_deref().unwrap_or(DEFAULT_CURRENCY);
+
+        by_currency.add(currency, currency, amount);
+        by_method.add(&receipt.method_id, currency, amount);
+        by_payee.add(&receipt.payee, currency, amount);
+        by_payer.add(&receipt.payer, currency, amount);
+    }
+
+    ReceiptAnalyticsFFI {
+        receipt_count: receipts.len() as u32,
+        unpriced_count,
+        by_currency: by_currency.into_rows(),
+        by_method: by_method.into_rows(),
+        by_payee: by_payee.into_rows(),
+        by_payer: by_payer.into_rows(),
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Running totals keyed by (group, currency), in key order.
+#[derive(Default)]
+struct Totals(BTreeMap<(String, String), (u32, u128)>);
+
+impl Totals {
+    fn add(&mut self, key: &str, currency: &str, amount: u64) {
+        let entry = self
+            .0
+            .entry((key.to_string(), currency.to_string()))
+            .or_default();
+        entry.0 += 1;
+        entry.1 += u128::from(amount);
+    }
+
+    fn into_rows(self) -> Vec<ReceiptTotalFFI> {
+        self.0
+            .into_iter()
+            .map(
+                |((key, currency), (receipt_count, total))| ReceiptTotalFFI {
+                    key,
+                    currency,
+                    receipt_count,
+                    total: total.to_string(),
+                },
+            )
+            .collect()
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receipt(id: &str, method_id: &str, amount: Option<&str>) -> ReceiptRequest {
+        ReceiptRequest {
+            receipt_id: id.to_string(),
+            payer: "payer".to_string(),
+            payee: "payee".to_string(),
+            method_id: method_id.to_string(),
+            amount: amount.map(str::to_string),
+            currency: None,
+            metadata_json: r#"{"note":"a, b"}"#.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_summarize_and_export() {
+        let receipts = vec![
+            receipt("r1", "lightning", Some("1000")),
+            receipt("r2", "lightning", Some("500")),
+            receipt("r3", "onchain", Some("20000")),
+            receipt("r4", "onchain", None),
+        ];
+
+        let summary = summarize_receipts(receipts.clone());
+        assert_eq!(summary.receipt_count, 4);
+        assert_eq!(summary.unpriced_count, 1);
+        assert_eq!(summary.by_currency[0].total, "21500");
+        assert_eq!(summary.by_method[0].key, "lightning");
+        assert_eq!(summary.by_method[0].total, "1500");
+        assert_eq!(summary.by_method[1].receipt_count, 1);
+
+        let csv = export_receipts_csv(receipts);
+        assert_eq!(csv.lines().count(), 5);
+        assert!(csv.contains(r#","{""note"":""a, b""}""#));
+    }
+
+    #[test]
+    fn test_verify_payment_proof() {
+        let proof = format!(
+            r#"{{"type":"LightningPreimage","preimage":"{}","payment_hash":"{}"}}"#,
+            "00".repeat(32),
+            "66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925"
+        );
+        let result = verify_payment_proof(proof).unwrap();
+        assert!(result.valid && result.cryptographic);
+
+        let result =
+            verify_payment_proof(r#"{"type":"BitcoinTxid","txid":"pending"}"#.into()).unwrap();
+        assert!(!result.valid);
+        assert_eq!(result.method_id, "onchain");
+
+        assert!(verify_payment_proof("{}".into()).is_err());
+    }
+}
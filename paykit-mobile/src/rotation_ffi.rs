@@ -0,0 +1,357 @@
+//! Endpoint Rotation FFI Bindings
+//!
+//! This module exposes the shared [`RotationEngine`] to mobile applications,
+//! so apps evaluate rotation policies exactly like the CLI does.
+//!
+//! The engine only decides *when* to rotate. The app generates the new
+//! endpoint with its wallet, publishes it, and then calls
+//! `record_rotation()`.
+//!
+//! # Example Flow
+//!
+//! ```ignore
+//! let engine = RotationEngineFFI::new();
+//! engine.set_policy("onchain".into(), "after:3".into())?;
+//!
+//! // After each payment
+//! let decision = engine.on_payment("onchain".into())?;
+//! if decision.should_rotate {
+//!     let address = wallet.new_address();
+//!     publish_endpoint("onchain", &address);
+//!     engine.record_rotation("onchain".into(), decision.reason.unwrap(), Some(address))?;
+//! }
+//!
+//! // Persist between launches
+//! save(engine.export_state()?);
+//! ```
+
+use crate::{PaykitMobileError, Result};
+use paykit_lib::rotation::{
+    MethodRotationStatus, RotationEngine, RotationPolicy, RotationReason, RotationRecord,
+};
+use paykit_lib::MethodId;
+use std::sync::{Arc, RwLock};
+
+// ============================================================================
+// FFI Types
+// ============================================================================
+
+/// FFI-safe rotation decision for a method.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct RotationDecisionFFI {
+    /// Method ID
+    pub method_id: String,
+    /// Whether the endpoint should be rotated now
+    pub should_rotate: bool,
+    /// Why rotation is due ("usage", "periodic", "manual")
+    pub reason: Option<String>,
+}
+
+/// FFI-safe rotation status for a method.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct RotationStatusFFI {
+    /// Method ID
+    pub method_id: String,
+    /// Effective policy ("on-use", "after:N", "periodic:SECS", "manual")
+    pub policy: String,
+    /// Human-readable policy description
+    pub policy_description: String,
+    /// Uses of the current endpoint
+    pub use_count: u32,
+    /// Unix timestamp when the current endpoint came into use
+    pub created_at: i64,
+    /// Unix timestamp of the last use
+    pub last_used_at: Option<i64>,
+    /// Whether a rotation was requested but not yet recorded
+    pub rotation_pending: bool,
+    /// Total rotations in history
+    pub rotations: u32,
+    /// Unix timestamp of the most recent rotation
+    pub last_rotated_at: Option<i64>,
+}
+
+impl From<MethodRotationStatus> for RotationStatusFFI {
+    fn from(status: MethodRotationStatus) -> Self {
+        Self {
+            method_id: status.method_id,
+            policy: status.policy.to_spec(),
+            policy_description: status.policy.describe(),
+            use_count: status.use_count,
+            created_at: status.created_at,
+            last_used_at: status.last_used_at,
+            rotation_pending: status.rotation_pending,
+            rotations: status.rotations,
+            last_rotated_at: status.last_rotated_at,
+        }
+    }
+}
+
+/// FFI-safe rotation history record.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct RotationRecordFFI {
+    /// Method ID
+    pub method_id: String,
+    /// Why the rotation happened
+    pub reason: String,
+    /// Uses of the replaced endpoint
+    pub use_count: u32,
+    /// Unix timestamp of the rotation
+    pub rotated_at: i64,
+    /// The new endpoint, if recorded
+    pub new_endpoint: Option<String>,
+}
+
+impl From<RotationRecord> for RotationRecordFFI {
+    fn from(record: RotationRecord) -> Self {
+        Self {
+            method_id: record.method_id,
+            reason: record.reason.as_str().to_string(),
+            use_count: record.use_count,
+            rotated_at: record.rotated_at,
+            new_endpoint: record.new_endpoint,
+        }
+    }
+}
+
+// ============================================================================
+// Rotation Engine
+// ============================================================================
+
+/// Thread-safe wrapper around the shared rotation policy engine.
+#[derive(uniffi::Object)]
+pub struct RotationEngineFFI {
+    engine: RwLock<RotationEngine>,
+}
+
+impl RotationEngineFFI {
+    fn read(&self) -> Result<std::sync::RwLockReadGuard<'_, RotationEngine>> {
+        self.engine.read().map_err(|_| PaykitMobileError::Internal {
+            msg: "Failed to acquire rotation engine lock".to_string(),
+        })
+    }
+
+    fn write(&self) -> Result<std::sync::RwLockWriteGuard<'_, RotationEngine>> {
+        self.engine
+            .write()
+            .map_err(|_| PaykitMobileError::Internal {
+                msg: "Failed to acquire rotation engine lock".to_string(),
+            })
+    }
+}
+
+#[uniffi::export]
+impl RotationEngineFFI {
+    /// Create an engine with the default configuration (rotate on every use).
+    #[uniffi::constructor]
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            engine: RwLock::new(RotationEngine::default()),
+        })
+    }
+
+    /// Restore an engine from state produced by `export_state()`.
+    #[uniffi::constructor]
+    pub fn from_state(state_json: String) -> Result<Arc<Self>> {
+        let engine: RotationEngine =
+            serde_json::from_str(&state_json).map_err(|e| PaykitMobileError::Serialization {
+                msg: format!("Invalid rotation state: {}", e),
+            })?;
+        Ok(Arc::new(Self {
+            engine: RwLock::new(engine),
+        }))
+    }
+
+    /// Export configuration, counters and history as JSON for persistence.
+    pub fn export_state(&self) -> Result<String> {
+        let engine = self.read()?;
+        serde_json::to_string(&*engine)
+            .map_err(|e| PaykitMobileError::Serialization { msg: e.to_string() })
+    }
+
+    /// Set the policy for a method ("on-use", "after:N", "periodic:SECS", "manual").
+    pub fn set_policy(&self, method_id: String, policy: String) -> Result<()> {
+        let policy = RotationPolicy::parse(&policy)?;
+        self.write()?.set_policy(&MethodId(method_id), policy);
+        Ok(())
+    }
+
+    /// Remove a method-specific policy so the default applies again.
+    pub fn clear_policy(&self, method_id: String) -> Result<()> {
+        self.write()?.clear_policy(&MethodId(method_id));
+        Ok(())
+    }
+
+    /// Set the default policy for methods without their own policy.
+    pub fn set_default_policy(&self, policy: String) -> Result<()> {
+        let policy = RotationPolicy::parse(&policy)?;
+        self.write()?.set_default_policy(policy);
+        Ok(())
+    }
+
+    /// Get the effective policy for a method.
+    pub fn get_policy(&self, method_id: String) -> Result<String> {
+        Ok(self
+            .read()?
+            .config()
+            .policy_for(&MethodId(method_id))
+            .to_spec())
+    }
+
+    /// Enable or disable rotation decisions after payments.
+    pub fn set_auto_rotate(&self, enabled: bool) -> Result<()> {
+        self.write()?.set_auto_rotate(enabled);
+        Ok(())
+    }
+
+    /// Check whether rotation after payments is enabled.
+    pub fn is_auto_rotate_enabled(&self) -> Result<bool> {
+        Ok(self.read()?.config().auto_rotate_on_payment)
+    }
+
+    /// Record a payment on a method and get the rotation decision.
+    pub fn on_payment(&self, method_id: String) -> Result<RotationDecisionFFI> {
+        let decision = self
+            .write()?
+            .on_payment(&MethodId(method_id.clone()), current_timestamp());
+        Ok(RotationDecisionFFI {
+            method_id,
+            should_rotate: decision.should_rotate(),
+            reason: decision.reason().map(|r| r.as_str().to_string()),
+        })
+    }
+
+    /// Get all methods whose endpoints are due for rotation.
+    ///
+    /// Call periodically (e.g. on app foreground) to pick up time-based rotations.
+    pub fn due_rotations(&self) -> Result<Vec<RotationDecisionFFI>> {
+        Ok(self
+            .read()?
+            .due(current_timestamp())
+            .into_iter()
+            .map(|(method_id, reason)| RotationDecisionFFI {
+                method_id: method_id.0,
+                should_rotate: true,
+                reason: Some(reason.as_str().to_string()),
+            })
+            .collect())
+    }
+
+    /// Request a rotation for a method regardless of policy.
+    pub fn request_rotation(&self, method_id: String) -> Result<()> {
+        self.write()?
+            .request_rotation(&MethodId(method_id), current_timestamp());
+        Ok(())
+    }
+
+    /// Record that the app rotated a method's endpoint.
+    pub fn record_rotation(
+        &self,
+        method_id: String,
+        reason: String,
+        new_endpoint: Option<String>,
+    ) -> Result<RotationRecordFFI> {
+        let reason: RotationReason = reason.parse()?;
+        let record = self.write()?.record_rotation(
+            &MethodId(method_id),
+            reason,
+            new_endpoint,
+            current_timestamp(),
+        );
+        Ok(record.into())
+    }
+
+    /// Get rotation status for all tracked methods.
+    pub fn status(&self) -> Result<Vec<RotationStatusFFI>> {
+        Ok(self.read()?.status().into_iter().map(Into::into).collect())
+    }
+
+    /// Get rotation history, optionally filtered by method.
+    pub fn history(&self, method_id: Option<String>) -> Result<Vec<RotationRecordFFI>> {
+        let method_id = method_id.map(MethodId);
+        Ok(self
+            .read()?
+            .history(method_id.as_ref())
+            .into_iter()
+            .cloned()
+            .map(Into::into)
+            .collect())
+    }
+
+    /// Clear rotation history.
+    pub fn clear_history(&self) -> Result<()> {
+        self.write()?.clear_history();
+        Ok(())
+    }
+}
+
+fn current_timestamp() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_engine_threshold() {
+        let engine = RotationEngineFFI::new();
+        engine
+            .set_policy("onchain".to_string(), "after:2".to_string())
+            .unwrap();
+
+        assert!(
+            !engine
+                .on_payment("onchain".to_string())
+                .unwrap()
+                .should_rotate
+        );
+        let decision = engine.on_payment("onchain".to_string()).unwrap();
+        assert!(decision.should_rotate);
+        assert_eq!(decision.reason.as_deref(), Some("usage"));
+
+        let record = engine
+            .record_rotation(
+                "onchain".to_string(),
+                decision.reason.unwrap(),
+                Some("bc1qnew".to_string()),
+            )
+            .unwrap();
+        assert_eq!(record.use_count, 2);
+
+        let status = engine.status().unwrap();
+        assert_eq!(status[0].policy, "after:2");
+        assert_eq!(status[0].use_count, 0);
+        assert_eq!(status[0].rotations, 1);
+    }
+
+    #[test]
+    fn test_rotation_engine_invalid_policy() {
+        let engine = RotationEngineFFI::new();
+        assert!(engine
+            .set_policy("onchain".to_string(), "sometimes".to_string())
+            .is_err());
+        assert!(engine
+            .record_rotation("onchain".to_string(), "bogus".to_string(), None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_rotation_engine_state_roundtrip() {
+        let engine = RotationEngineFFI::new();
+        engine.set_default_policy("manual".to_string()).unwrap();
+        engine.request_rotation("lightning".to_string()).unwrap();
+
+        let restored = RotationEngineFFI::from_state(engine.export_state().unwrap()).unwrap();
+        assert_eq!(
+            restored.get_policy("lightning".to_string()).unwrap(),
+            "manual"
+        );
+
+        let due = restored.due_rotations().unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].reason.as_deref(), Some("manual"));
+    }
+}
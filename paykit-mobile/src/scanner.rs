@@ -28,6 +28,7 @@
 //! }
 //! ```
 
+use paykit_lib::handles::Handle;
 use paykit_lib::uri::{parse_uri, PaykitUri};
 use paykit_lib::PublicKey;
 
@@ -53,6 +54,14 @@ pub struct ScannedUri {
     pub request_id: Option<String>,
     /// The requester's public key if this is a PaymentRequest URI.
     pub requester: Option<String>,
+    /// Requested amount in satoshis (signed payment requests only).
+    pub amount_sats: Option<u64>,
+    /// Accepted payment methods in order of preference (signed payment requests only).
+    pub methods: Vec<String>,
+    /// Request description (signed payment requests only).
+    pub description: Option<String>,
+    /// Request expiry as a Unix timestamp (signed payment requests only).
+    pub expires_at: Option<i64>,
 }
 
 /// Type of scanned URI.
@@ -64,6 +73,15 @@ pub enum UriType {
     Invoice,
     /// A payment request URI.
     PaymentRequest,
+    /// A signed payment request deep link with the full request embedded.
+    ///
+    /// The signature has been verified and the request has not expired, so
+    /// the app can go straight to the pay flow.
+    SignedPaymentRequest,
+    /// A human-readable handle (`alice@example.com`) in `data`.
+    ///
+    /// Resolve it with `PaykitClient::resolve_handle` before paying.
+    Handle,
     /// Unknown or invalid format.
     Unknown,
 }
@@ -76,6 +94,9 @@ pub enum UriType {
 /// - Lightning invoices (`lightning:` or `lnbc1...`)
 /// - Bitcoin addresses (`bitcoin:` or direct addresses)
 /// - Payment request URIs (`paykit:request?...`)
+/// - Signed payment request deep links (`paykit://request/...`), rejected if
+///   the signature is invalid or the request has expired
+/// - Handles (`alice@example.com`), returned unresolved
 ///
 /// # Arguments
 ///
@@ -113,6 +134,10 @@ pub fn parse_scanned_uri(scanned_data: String) -> Result<ScannedUri, String> {
             data: None,
             request_id: None,
             requester: None,
+            amount_sats: None,
+            methods: Vec::new(),
+            description: None,
+            expires_at: None,
         }),
         PaykitUri::Invoice { method, data } => Ok(ScannedUri {
             uri_type: UriType::Invoice,
@@ -121,6 +146,10 @@ pub fn parse_scanned_uri(scanned_data: String) -> Result<ScannedUri, String> {
             data: Some(data),
             request_id: None,
             requester: None,
+            amount_sats: None,
+            methods: Vec::new(),
+            description: None,
+            expires_at: None,
         }),
         PaykitUri::PaymentRequest { request_id, from } => Ok(ScannedUri {
             uri_type: UriType::PaymentRequest,
@@ -129,7 +158,39 @@ pub fn parse_scanned_uri(scanned_data: String) -> Result<ScannedUri, String> {
             data: None,
             request_id: Some(request_id),
             requester: Some(public_key_to_string(&from)),
+            amount_sats: None,
+            methods: Vec::new(),
+            description: None,
+            expires_at: None,
         }),
+        PaykitUri::Handle { handle } => Ok(ScannedUri {
+            uri_type: UriType::Handle,
+            public_key: None,
+            method_id: None,
+            data: Some(handle.to_string()),
+            request_id: None,
+            requester: None,
+            amount_sats: None,
+            methods: Vec::new(),
+            description: None,
+            expires_at: None,
+        }),
+        PaykitUri::SignedRequest(link) => {
+            link.verify().map_err(|e| e.to_string())?;
+            let request = link.into_request();
+            Ok(ScannedUri {
+                uri_type: UriType::SignedPaymentRequest,
+                public_key: None,
+                method_id: request.methods.first().map(|m| m.0.clone()),
+                data: None,
+                request_id: Some(request.request_id),
+                requester: Some(public_key_to_string(&request.from)),
+                amount_sats: request.amount_sats,
+                methods: request.methods.into_iter().map(|m| m.0).collect(),
+                description: request.description,
+                expires_at: request.expires_at,
+            })
+        }
     }
 }
 
@@ -159,6 +220,7 @@ pub fn is_paykit_uri(scanned_data: String) -> bool {
         || (data.starts_with("1") && data.len() >= 26 && data.len() <= 35)
         || (data.starts_with("3") && data.len() >= 26 && data.len() <= 35)
         || data.starts_with("paykit:")
+        || Handle::is_handle(data)
 }
 
 /// Extract public key from scanned data if it's a Pubky URI.
@@ -227,6 +289,15 @@ mod tests {
         assert_eq!(result.method_id, Some("onchain".to_string()));
     }
 
+    #[test]
+    fn test_parse_scanned_handle() {
+        let result = parse_scanned_uri("Alice@Example.com".to_string()).unwrap();
+        assert_eq!(result.uri_type, UriType::Handle);
+        assert_eq!(result.data, Some("alice@example.com".to_string()));
+        assert!(result.public_key.is_none());
+        assert!(is_paykit_uri("alice@example.com".to_string()));
+    }
+
     #[test]
     fn test_is_paykit_uri() {
         assert!(is_paykit_uri("pubky://abc123".to_string()));
@@ -237,6 +308,12 @@ mod tests {
         assert!(!is_paykit_uri("not a uri".to_string()));
     }
 
+    #[test]
+    fn test_parse_scanned_signed_request_invalid() {
+        assert!(is_paykit_uri("paykit://request/e30.sig".to_string()));
+        assert!(parse_scanned_uri("paykit://request/e30.sig".to_string()).is_err());
+    }
+
     #[test]
     fn test_extract_public_key() {
         // Test that non-pubky URIs return None
@@ -0,0 +1,356 @@
+//! Scheduled Payment FFI Bindings
+//!
+//! Scheduled payments are one-off payments set up now and sent later
+//! ("pay X 20k sats on the 1st"), inside an execution window. The app keeps
+//! them in a `ScheduledPaymentBookFFI`, persists it with `export_state()`,
+//! and from its background scheduler sends the due ones with
+//! `PaykitClient::run_scheduled_payment`, which skips unhealthy methods and
+//! applies the client's velocity limits. Payments can be cancelled until
+//! they are sent.
+//!
+//! # Example Flow
+//!
+//! ```ignore
+//! let book = ScheduledPaymentBookFFI::from_state(saved_json)?;
+//! book.add(ScheduledPaymentSpecFFI {
+//!     payment_id: "rent-march".into(),
+//!     payee: payee_z32,
+//!     amount_sats: 20_000,
+//!     not_before: march_1,
+//!     not_after: Some(march_1 + 3 * 86_400),
+//!     methods: vec!["lightning".into()],
+//!     memo: Some("March rent".into()),
+//! }, now)?;
+//!
+//! // Background task
+//! for payment in book.due(now) {
+//!     let methods = fetch_payee_methods(&payment.payee)?;
+//!     let run = client.run_scheduled_payment(book.clone(), payment.payment_id, methods, now)?;
+//!     if let Some(execution) = run.execution.filter(|e| e.success) {
+//!         save_receipt(execution);
+//!     }
+//! }
+//! save(book.export_state()?);
+//! ```
+
+use crate::velocity_ffi::HeldPaymentFFI;
+use crate::{PaykitMobileError, PaymentExecutionResult, Result};
+use parking_lot::RwLock;
+use paykit_lib::scheduled_payments::{ScheduledPayment, ScheduledStatus};
+use paykit_lib::MethodId;
+use std::sync::Arc;
+
+// ============================================================================
+// FFI Types
+// ============================================================================
+
+/// FFI-safe scheduled payment status.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, uniffi::Enum)]
+pub enum ScheduledStatusFFI {
+    /// Waiting for its window, or retrying inside it
+    Pending,
+    /// Sent
+    Paid,
+    /// Cancelled before it was sent
+    Cancelled,
+    /// The window closed before it could be sent
+    Expired,
+}
+
+impl From<ScheduledStatus> for ScheduledStatusFFI {
+    fn from(status: ScheduledStatus) -> Self {
+        match status {
+            ScheduledStatus::Pending => ScheduledStatusFFI::Pending,
+            ScheduledStatus::Paid => ScheduledStatusFFI::Paid,
+            ScheduledStatus::Cancelled => ScheduledStatusFFI::Cancelled,
+            ScheduledStatus::Expired => ScheduledStatusFFI::Expired,
+        }
+    }
+}
+
+/// FFI-safe description of a new scheduled payment.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct ScheduledPaymentSpecFFI {
+    /// Unique payment identifier
+    pub payment_id: String,
+    /// Payee public key, z-base-32
+    pub payee: String,
+    /// Amount to pay, in satoshis
+    pub amount_sats: u64,
+    /// Unix timestamp of the earliest moment to pay
+    pub not_before: i64,
+    /// Unix timestamp after which the payment expires unsent
+    pub not_after: Option<i64>,
+    /// Only use these methods, highest priority first (empty for any)
+    pub methods: Vec<String>,
+    /// Note recorded with the payment
+    pub memo: Option<String>,
+}
+
+impl ScheduledPaymentSpecFFI {
+    fn into_payment(self, created_at: i64) -> Result<ScheduledPayment> {
+        let mut payment = ScheduledPayment::new(
+            self.payment_id,
+            self.payee,
+            self.amount_sats,
+            self.not_before,
+            created_at,
+        )?
+        .with_methods(self.methods.into_iter().map(MethodId).collect());
+        payment.not_after = self.not_after;
+        payment.memo = self.memo;
+        payment.validate()?;
+        Ok(payment)
+    }
+}
+
+/// FFI-safe scheduled payment.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct ScheduledPaymentFFI {
+    /// Unique payment identifier
+    pub payment_id: String,
+    /// Payee public key, z-base-32
+    pub payee: String,
+    /// Amount to pay, in satoshis
+    pub amount_sats: u64,
+    /// Unix timestamp of the earliest moment to pay
+    pub not_before: i64,
+    /// Unix timestamp after which the payment expires unsent
+    pub not_after: Option<i64>,
+    /// Preferred methods, highest priority first
+    pub methods: Vec<String>,
+    /// Note recorded with the payment
+    pub memo: Option<String>,
+    /// Unix timestamp the payment was scheduled
+    pub created_at: i64,
+    /// Lifecycle status
+    pub status: ScheduledStatusFFI,
+    /// Payment attempts made so far
+    pub attempts: u32,
+    /// Error of the last failed attempt
+    pub last_error: Option<String>,
+    /// Unix timestamp the payment was paid, cancelled or expired
+    pub settled_at: Option<i64>,
+}
+
+impl From<&ScheduledPayment> for ScheduledPaymentFFI {
+    fn from(payment: &ScheduledPayment) -> Self {
+        Self {
+            payment_id: payment.payment_id.clone(),
+            payee: payment.payee.clone(),
+            amount_sats: payment.amount_sats,
+            not_before: payment.not_before,
+            not_after: payment.not_after,
+            methods: payment.methods.iter().map(|m| m.0.clone()).collect(),
+            memo: payment.memo.clone(),
+            created_at: payment.created_at,
+            status: payment.status.into(),
+            attempts: payment.attempts,
+            last_error: payment.last_error.clone(),
+            settled_at: payment.settled_at,
+        }
+    }
+}
+
+/// FFI-safe result of running a scheduled payment.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct ScheduledPaymentRunFFI {
+    /// The payment after the run
+    pub payment: ScheduledPaymentFFI,
+    /// Why no payment was attempted, if skipped
+    pub skipped_reason: Option<String>,
+    /// The velocity hold, if the payment is waiting for approval
+    pub held: Option<HeldPaymentFFI>,
+    /// The successful payment, or the last failed attempt
+    pub execution: Option<PaymentExecutionResult>,
+    /// Whether the failed attempt may still have been sent; check the
+    /// wallet before running it again
+    pub unresolved: bool,
+}
+
+// ============================================================================
+// Scheduled Payment Book
+// ============================================================================
+
+/// Thread-safe collection of scheduled payments.
+#[derive(uniffi::Object)]
+pub struct ScheduledPaymentBookFFI {
+    payments: RwLock<Vec<ScheduledPayment>>,
+}
+
+impl ScheduledPaymentBookFFI {
+    /// Copy of a payment, for running outside the lock.
+    pub(crate) fn payment(&self, payment_id: &str) -> Result<ScheduledPayment> {
+        self.payments
+            .read()
+            .iter()
+            .find(|payment| payment.payment_id == payment_id)
+            .cloned()
+            .ok_or_else(|| PaykitMobileError::NotFound {
+                msg: format!("Scheduled payment not found: {}", payment_id),
+            })
+    }
+
+    /// Store a payment's state after a run.
+    ///
+    /// A cancellation made while the payment was running wins unless the
+    /// run paid it.
+    pub(crate) fn update(&self, updated: ScheduledPayment) {
+        let mut payments = self.payments.write();
+        if let Some(payment) = payments
+            .iter_mut()
+            .find(|payment| payment.payment_id == updated.payment_id)
+        {
+            if payment.status != ScheduledStatus::Cancelled
+                || updated.status == ScheduledStatus::Paid
+            {
+                *payment = updated;
+            }
+        }
+    }
+}
+
+#[uniffi::export]
+impl ScheduledPaymentBookFFI {
+    /// Create an empty book.
+    #[uniffi::constructor]
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            payments: RwLock::new(Vec::new()),
+        })
+    }
+
+    /// Restore a book from state produced by `export_state()`.
+    #[uniffi::constructor]
+    pub fn from_state(state_json: String) -> Result<Arc<Self>> {
+        let payments: Vec<ScheduledPayment> =
+            serde_json::from_str(&state_json).map_err(|e| PaykitMobileError::Serialization {
+                msg: format!("Invalid scheduled payment state: {}", e),
+            })?;
+        Ok(Arc::new(Self {
+            payments: RwLock::new(payments),
+        }))
+    }
+
+    /// Export the book as JSON for persistence.
+    pub fn export_state(&self) -> Result<String> {
+        serde_json::to_string(&*self.payments.read())
+            .map_err(|e| PaykitMobileError::Serialization { msg: e.to_string() })
+    }
+
+    /// Schedule a payment, created at `now`.
+    pub fn add(&self, spec: ScheduledPaymentSpecFFI, now: i64) -> Result<ScheduledPaymentFFI> {
+        let payment = spec.into_payment(now)?;
+        let mut payments = self.payments.write();
+        if payments.iter().any(|p| p.payment_id == payment.payment_id) {
+            return Err(PaykitMobileError::Validation {
+                msg: format!("Scheduled payment {} already exists", payment.payment_id),
+            });
+        }
+        let ffi = (&payment).into();
+        payments.push(payment);
+        Ok(ffi)
+    }
+
+    /// Get a payment.
+    pub fn get(&self, payment_id: String) -> Option<ScheduledPaymentFFI> {
+        self.payments
+            .read()
+            .iter()
+            .find(|payment| payment.payment_id == payment_id)
+            .map(Into::into)
+    }
+
+    /// List all payments, including settled ones, in creation order.
+    pub fn list(&self) -> Vec<ScheduledPaymentFFI> {
+        self.payments.read().iter().map(Into::into).collect()
+    }
+
+    /// List the payments not yet paid, cancelled or expired.
+    pub fn pending(&self) -> Vec<ScheduledPaymentFFI> {
+        self.payments
+            .read()
+            .iter()
+            .filter(|payment| !payment.is_settled())
+            .map(Into::into)
+            .collect()
+    }
+
+    /// List the payments due at `now`, earliest window first.
+    pub fn due(&self, now: i64) -> Vec<ScheduledPaymentFFI> {
+        let payments = self.payments.read();
+        let mut due: Vec<&ScheduledPayment> = payments.iter().filter(|p| p.is_due(now)).collect();
+        due.sort_by_key(|payment| payment.not_before);
+        due.into_iter().map(Into::into).collect()
+    }
+
+    /// Cancel a payment before it is sent.
+    pub fn cancel(&self, payment_id: String, now: i64) -> Result<ScheduledPaymentFFI> {
+        let mut payments = self.payments.write();
+        let payment = payments
+            .iter_mut()
+            .find(|payment| payment.payment_id == payment_id)
+            .ok_or_else(|| PaykitMobileError::NotFound {
+                msg: format!("Scheduled payment not found: {}", payment_id),
+            })?;
+        payment.cancel(now)?;
+        Ok((&*payment).into())
+    }
+
+    /// Remove a payment. Returns whether it was present.
+    pub fn remove(&self, payment_id: String) -> bool {
+        let mut payments = self.payments.write();
+        let before = payments.len();
+        payments.retain(|payment| payment.payment_id != payment_id);
+        payments.len() != before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(payment_id: &str) -> ScheduledPaymentSpecFFI {
+        ScheduledPaymentSpecFFI {
+            payment_id: payment_id.to_string(),
+            payee: "payee".to_string(),
+            amount_sats: 20_000,
+            not_before: 1_000,
+            not_after: Some(2_000),
+            methods: vec!["lightning".to_string()],
+            memo: Some("rent".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_scheduled_payment_book() {
+        let book = ScheduledPaymentBookFFI::new();
+        book.add(spec("rent"), 0).unwrap();
+        assert!(book.add(spec("rent"), 0).is_err());
+        assert!(book
+            .add(
+                ScheduledPaymentSpecFFI {
+                    not_after: Some(999),
+                    ..spec("backwards")
+                },
+                0
+            )
+            .is_err());
+
+        assert!(book.due(999).is_empty());
+        assert_eq!(book.due(1_000).len(), 1);
+        assert!(book.due(2_001).is_empty());
+
+        let restored = ScheduledPaymentBookFFI::from_state(book.export_state().unwrap()).unwrap();
+        let payment = restored.get("rent".to_string()).unwrap();
+        assert_eq!(payment.methods, vec!["lightning".to_string()]);
+        assert_eq!(payment.status, ScheduledStatusFFI::Pending);
+
+        let cancelled = restored.cancel("rent".to_string(), 1_500).unwrap();
+        assert_eq!(cancelled.status, ScheduledStatusFFI::Cancelled);
+        assert_eq!(cancelled.settled_at, Some(1_500));
+        assert!(restored.due(1_500).is_empty());
+        assert!(restored.pending().is_empty());
+        assert_eq!(restored.list().len(), 1);
+    }
+}
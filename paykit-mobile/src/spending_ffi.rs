@@ -35,7 +35,7 @@ use crate::{PaykitMobileError, Result};
 use paykit_subscriptions::{Amount, PeerSpendingLimit};
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, RwLock, RwLockWriteGuard};
 
 // ============================================================================
 // FFI Types
@@ -124,6 +124,22 @@ struct ReservationData {
     created_at: i64,
 }
 
+impl crate::memory_ffi::HeapSize for ReservationData {
+    fn heap_size(&self) -> usize {
+        self.peer_pubkey.capacity()
+    }
+}
+
+impl crate::memory_ffi::MemoryReporter for SpendingManagerFFI {
+    fn memory_usage(&self) -> Vec<crate::memory_ffi::SubsystemMemoryFFI> {
+        let reservations = self.reservations.read().unwrap_or_else(|e| e.into_inner());
+        vec![crate::memory_ffi::map_usage(
+            "spending.reservations",
+            &reservations,
+        )]
+    }
+}
+
 #[uniffi::export]
 impl SpendingManagerFFI {
     /// Create a new spending manager with the given storage path.
@@ -400,12 +416,7 @@ impl SpendingManagerFFI {
             let reservation_id = format!("rsv_{}_{:08x}", now, rand::random::<u32>());
 
             // Store reservation in memory for tracking
-            let mut reservations =
-                self.reservations
-                    .write()
-                    .map_err(|_| PaykitMobileError::Internal {
-                        msg: "Failed to acquire reservations lock".to_string(),
-                    })?;
+            let mut reservations = self.reservations_mut();
             reservations.insert(
                 reservation_id.clone(),
                 ReservationData {
@@ -438,12 +449,7 @@ impl SpendingManagerFFI {
     ///
     /// * `reservation_id` - The reservation ID from `try_reserve_spending()`
     pub fn commit_spending(&self, reservation_id: String) -> Result<()> {
-        let mut reservations =
-            self.reservations
-                .write()
-                .map_err(|_| PaykitMobileError::Internal {
-                    msg: "Failed to acquire reservations lock".to_string(),
-                })?;
+        let mut reservations = self.reservations_mut();
 
         // Remove the reservation from tracking
         // The spending was already applied when we reserved it
@@ -467,12 +473,7 @@ impl SpendingManagerFFI {
 
         // Get the reservation data
         let reservation_data = {
-            let mut reservations =
-                self.reservations
-                    .write()
-                    .map_err(|_| PaykitMobileError::Internal {
-                        msg: "Failed to acquire reservations lock".to_string(),
-                    })?;
+            let mut reservations = self.reservations_mut();
 
             match reservations.remove(&reservation_id) {
                 Some(data) => data,
@@ -580,8 +581,8 @@ impl SpendingManagerFFI {
     pub fn active_reservations_count(&self) -> u32 {
         self.reservations
             .read()
-            .map(|r| r.len() as u32)
-            .unwrap_or(0)
+            .unwrap_or_else(|e| e.into_inner())
+            .len() as u32
     }
 }
 
@@ -594,6 +595,16 @@ impl SpendingManagerFFI {
         PathBuf::from(&self.storage_path_str)
     }
 
+    /// Lock the in-flight reservations for writing.
+    ///
+    /// Each update is a single insert or remove, so the map stays consistent
+    /// even if a panic elsewhere poisoned the lock. Recovering matters here:
+    /// failing after `try_reserve_spending` has written the limit file would
+    /// leave an amount reserved that can never be rolled back.
+    fn reservations_mut(&self) -> RwLockWriteGuard<'_, HashMap<String, ReservationData>> {
+        self.reservations.write().unwrap_or_else(|e| e.into_inner())
+    }
+
     fn peer_limit_path(&self, peer_pubkey: &str) -> PathBuf {
         // Sanitize the pubkey for use as filename
         let safe_name = peer_pubkey.replace(['/', '\\', ':', '*', '?', '"', '<', '>', '|'], "_");
@@ -739,6 +750,26 @@ mod tests {
         assert_eq!(limit.remaining_sats, 10000);
     }
 
+    #[test]
+    fn test_poisoned_reservations_recover() {
+        let (_temp_dir, manager) = create_test_manager();
+        let peer = generate_test_pubkey();
+        manager
+            .set_peer_spending_limit(peer.clone(), 10000, "daily".to_string())
+            .unwrap();
+        crate::test_support::poison_rwlock(&manager.reservations);
+
+        // The reservation is still tracked, so it can be rolled back
+        let reservation = manager.try_reserve_spending(peer.clone(), 3000).unwrap();
+        assert_eq!(manager.active_reservations_count(), 1);
+        manager
+            .rollback_spending(reservation.reservation_id)
+            .unwrap();
+
+        let limit = manager.get_peer_spending_limit(peer).unwrap().unwrap();
+        assert_eq!(limit.current_spent_sats, 0);
+    }
+
     #[test]
     fn test_reserve_exceeds_limit() {
         let (_temp_dir, manager) = create_test_manager();
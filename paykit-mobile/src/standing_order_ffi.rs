@@ -0,0 +1,358 @@
+//! Standing Order FFI Bindings
+//!
+//! Standing orders are recurring payments set up on the payer's side only
+//! ("send 50k sats to X every Friday"); the payee just publishes endpoints.
+//! The app keeps its orders in a `StandingOrderBookFFI`, persists it with
+//! `export_state()`, and from its background scheduler pays the due ones
+//! with `PaykitClient::run_standing_order`. Each payment comes back as an
+//! ordinary `PaymentExecutionResult` to store as a receipt.
+//!
+//! # Example Flow
+//!
+//! ```ignore
+//! let book = StandingOrderBookFFI::from_state(saved_json)?;
+//! book.add(StandingOrderSpecFFI {
+//!     order_id: "savings".into(),
+//!     payee: payee_z32,
+//!     amount_sats: 50_000,
+//!     schedule: OrderScheduleFFI::Weekly { weekday: 4 },
+//!     methods: vec!["lightning".into()],
+//!     memo: None,
+//!     starts_at: now,
+//!     ends_at: None,
+//!     max_runs: None,
+//! })?;
+//!
+//! // Background task
+//! for order in book.due(now)? {
+//!     let methods = fetch_payee_methods(&order.payee)?;
+//!     let run = client.run_standing_order(book.clone(), order.order_id, methods, now)?;
+//!     if let Some(execution) = run.execution.filter(|e| e.success) {
+//!         save_receipt(execution);
+//!     }
+//! }
+//! save(book.export_state()?);
+//! ```
+
+use crate::{PaykitMobileError, PaymentExecutionResult, Result};
+use parking_lot::RwLock;
+use paykit_lib::standing_orders::{OrderSchedule, StandingOrder};
+use paykit_lib::MethodId;
+use std::sync::Arc;
+
+// ============================================================================
+// FFI Types
+// ============================================================================
+
+/// FFI-safe standing order schedule.
+///
+/// Weekly and monthly payments fall at midnight UTC.
+#[derive(Clone, Debug, PartialEq, Eq, uniffi::Enum)]
+pub enum OrderScheduleFFI {
+    /// Every `every_secs` seconds from the start
+    Interval { every_secs: i64 },
+    /// Weekly, 0 (Monday) to 6 (Sunday)
+    Weekly { weekday: u8 },
+    /// Monthly on this day, clamped to the end of shorter months
+    Monthly { day: u8 },
+}
+
+impl From<OrderScheduleFFI> for OrderSchedule {
+    fn from(schedule: OrderScheduleFFI) -> Self {
+        match schedule {
+            OrderScheduleFFI::Interval { every_secs } => OrderSchedule::Interval { every_secs },
+            OrderScheduleFFI::Weekly { weekday } => OrderSchedule::Weekly { weekday },
+            OrderScheduleFFI::Monthly { day } => OrderSchedule::Monthly { day },
+        }
+    }
+}
+
+impl From<&OrderSchedule> for OrderScheduleFFI {
+    fn from(schedule: &OrderSchedule) -> Self {
+        match *schedule {
+            OrderSchedule::Interval { every_secs } => OrderScheduleFFI::Interval { every_secs },
+            OrderSchedule::Weekly { weekday } => OrderScheduleFFI::Weekly { weekday },
+            OrderSchedule::Monthly { day } => OrderScheduleFFI::Monthly { day },
+        }
+    }
+}
+
+/// FFI-safe description of a new standing order.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct StandingOrderSpecFFI {
+    /// Unique order identifier
+    pub order_id: String,
+    /// Payee public key, z-base-32
+    pub payee: String,
+    /// Amount paid each time, in satoshis
+    pub amount_sats: u64,
+    /// When the order pays
+    pub schedule: OrderScheduleFFI,
+    /// Only use these methods, highest priority first (empty for any)
+    pub methods: Vec<String>,
+    /// Note recorded on every payment
+    pub memo: Option<String>,
+    /// Unix timestamp the schedule starts from
+    pub starts_at: i64,
+    /// No payments after this unix timestamp
+    pub ends_at: Option<i64>,
+    /// Stop after this many payments
+    pub max_runs: Option<u32>,
+}
+
+impl TryFrom<StandingOrderSpecFFI> for StandingOrder {
+    type Error = PaykitMobileError;
+
+    fn try_from(spec: StandingOrderSpecFFI) -> Result<Self> {
+        let mut order = StandingOrder::new(
+            spec.order_id,
+            spec.payee,
+            spec.amount_sats,
+            spec.schedule.into(),
+            spec.starts_at,
+        )?
+        .with_methods(spec.methods.into_iter().map(MethodId).collect());
+        order.memo = spec.memo;
+        order.ends_at = spec.ends_at;
+        order.max_runs = spec.max_runs;
+        order.validate()?;
+        Ok(order)
+    }
+}
+
+/// FFI-safe standing order.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct StandingOrderFFI {
+    /// Unique order identifier
+    pub order_id: String,
+    /// Payee public key, z-base-32
+    pub payee: String,
+    /// Amount paid each time, in satoshis
+    pub amount_sats: u64,
+    /// When the order pays
+    pub schedule: OrderScheduleFFI,
+    /// Preferred methods, highest priority first
+    pub methods: Vec<String>,
+    /// Note recorded on every payment
+    pub memo: Option<String>,
+    /// Unix timestamp of the next payment
+    pub next_due_at: i64,
+    /// Payments made so far
+    pub runs: u32,
+    /// Unix timestamp of the last payment
+    pub last_run_at: Option<i64>,
+    /// Whether the order is paused
+    pub paused: bool,
+    /// Whether the order reached its end date or run limit
+    pub finished: bool,
+}
+
+impl From<&StandingOrder> for StandingOrderFFI {
+    fn from(order: &StandingOrder) -> Self {
+        Self {
+            order_id: order.order_id.clone(),
+            payee: order.payee.clone(),
+            amount_sats: order.amount_sats,
+            schedule: (&order.schedule).into(),
+            methods: order.methods.iter().map(|m| m.0.clone()).collect(),
+            memo: order.memo.clone(),
+            next_due_at: order.next_due_at,
+            runs: order.runs,
+            last_run_at: order.last_run_at,
+            paused: order.paused,
+            finished: order.is_finished(),
+        }
+    }
+}
+
+/// FFI-safe result of running a standing order.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct StandingOrderRunFFI {
+    /// The order after the run
+    pub order: StandingOrderFFI,
+    /// Why no payment was attempted, if skipped
+    pub skipped_reason: Option<String>,
+    /// The successful payment, or the last failed attempt
+    pub execution: Option<PaymentExecutionResult>,
+    /// Whether the failed attempt may still have been sent; check the
+    /// wallet before running it again
+    pub unresolved: bool,
+}
+
+// ============================================================================
+// Standing Order Book
+// ============================================================================
+
+/// Thread-safe collection of standing orders.
+#[derive(uniffi::Object)]
+pub struct StandingOrderBookFFI {
+    orders: RwLock<Vec<StandingOrder>>,
+}
+
+impl StandingOrderBookFFI {
+    /// Copy of an order, for running outside the lock.
+    pub(crate) fn order(&self, order_id: &str) -> Result<StandingOrder> {
+        self.orders
+            .read()
+            .iter()
+            .find(|order| order.order_id == order_id)
+            .cloned()
+            .ok_or_else(|| PaykitMobileError::NotFound {
+                msg: format!("Standing order not found: {}", order_id),
+            })
+    }
+
+    /// Store an order's state after a run.
+    pub(crate) fn update(&self, updated: StandingOrder) {
+        let mut orders = self.orders.write();
+        if let Some(order) = orders
+            .iter_mut()
+            .find(|order| order.order_id == updated.order_id)
+        {
+            *order = updated;
+        }
+    }
+
+    fn set_paused(&self, order_id: &str, paused: bool) -> Result<StandingOrderFFI> {
+        let mut orders = self.orders.write();
+        let order = orders
+            .iter_mut()
+            .find(|order| order.order_id == order_id)
+            .ok_or_else(|| PaykitMobileError::NotFound {
+                msg: format!("Standing order not found: {}", order_id),
+            })?;
+        order.paused = paused;
+        Ok((&*order).into())
+    }
+}
+
+#[uniffi::export]
+impl StandingOrderBookFFI {
+    /// Create an empty book.
+    #[uniffi::constructor]
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            orders: RwLock::new(Vec::new()),
+        })
+    }
+
+    /// Restore a book from state produced by `export_state()`.
+    #[uniffi::constructor]
+    pub fn from_state(state_json: String) -> Result<Arc<Self>> {
+        let orders: Vec<StandingOrder> =
+            serde_json::from_str(&state_json).map_err(|e| PaykitMobileError::Serialization {
+                msg: format!("Invalid standing order state: {}", e),
+            })?;
+        Ok(Arc::new(Self {
+            orders: RwLock::new(orders),
+        }))
+    }
+
+    /// Export the book as JSON for persistence.
+    pub fn export_state(&self) -> Result<String> {
+        serde_json::to_string(&*self.orders.read())
+            .map_err(|e| PaykitMobileError::Serialization { msg: e.to_string() })
+    }
+
+    /// Add a standing order.
+    pub fn add(&self, spec: StandingOrderSpecFFI) -> Result<StandingOrderFFI> {
+        let order: StandingOrder = spec.try_into()?;
+        let mut orders = self.orders.write();
+        if orders.iter().any(|o| o.order_id == order.order_id) {
+            return Err(PaykitMobileError::Validation {
+                msg: format!("Standing order {} already exists", order.order_id),
+            });
+        }
+        let ffi = (&order).into();
+        orders.push(order);
+        Ok(ffi)
+    }
+
+    /// Get an order.
+    pub fn get(&self, order_id: String) -> Option<StandingOrderFFI> {
+        self.orders
+            .read()
+            .iter()
+            .find(|order| order.order_id == order_id)
+            .map(Into::into)
+    }
+
+    /// List all orders, in creation order.
+    pub fn list(&self) -> Vec<StandingOrderFFI> {
+        self.orders.read().iter().map(Into::into).collect()
+    }
+
+    /// List the orders due at `now`, earliest first.
+    pub fn due(&self, now: i64) -> Vec<StandingOrderFFI> {
+        let orders = self.orders.read();
+        let mut due: Vec<&StandingOrder> = orders.iter().filter(|o| o.is_due(now)).collect();
+        due.sort_by_key(|order| order.next_due_at);
+        due.into_iter().map(Into::into).collect()
+    }
+
+    /// Pause an order.
+    pub fn pause(&self, order_id: String) -> Result<StandingOrderFFI> {
+        self.set_paused(&order_id, true)
+    }
+
+    /// Resume a paused order.
+    pub fn resume(&self, order_id: String) -> Result<StandingOrderFFI> {
+        self.set_paused(&order_id, false)
+    }
+
+    /// Remove an order. Returns whether it was present.
+    pub fn remove(&self, order_id: String) -> bool {
+        let mut orders = self.orders.write();
+        let before = orders.len();
+        orders.retain(|order| order.order_id != order_id);
+        orders.len() != before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(order_id: &str) -> StandingOrderSpecFFI {
+        StandingOrderSpecFFI {
+            order_id: order_id.to_string(),
+            payee: "payee".to_string(),
+            amount_sats: 50_000,
+            schedule: OrderScheduleFFI::Interval { every_secs: 3600 },
+            methods: vec!["lightning".to_string()],
+            memo: Some("savings".to_string()),
+            starts_at: 1_000,
+            ends_at: None,
+            max_runs: Some(4),
+        }
+    }
+
+    #[test]
+    fn test_standing_order_book() {
+        let book = StandingOrderBookFFI::new();
+        book.add(spec("savings")).unwrap();
+        assert!(book.add(spec("savings")).is_err());
+        assert!(book
+            .add(StandingOrderSpecFFI {
+                amount_sats: 0,
+                ..spec("empty")
+            })
+            .is_err());
+
+        assert!(book.due(999).is_empty());
+        assert_eq!(book.due(1_000).len(), 1);
+        book.pause("savings".to_string()).unwrap();
+        assert!(book.due(1_000).is_empty());
+        assert!(book.resume("savings".to_string()).unwrap().next_due_at == 1_000);
+
+        let restored = StandingOrderBookFFI::from_state(book.export_state().unwrap()).unwrap();
+        let order = restored.get("savings".to_string()).unwrap();
+        assert_eq!(order.methods, vec!["lightning".to_string()]);
+        assert_eq!(
+            order.schedule,
+            OrderScheduleFFI::Interval { every_secs: 3600 }
+        );
+        assert!(restored.remove("savings".to_string()));
+        assert!(restored.list().is_empty());
+    }
+}
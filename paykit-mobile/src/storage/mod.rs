@@ -342,6 +342,9 @@ pub struct CachedContact {
     pub added_at: i64,
     /// When the contact was last synced (unix timestamp).
     pub last_synced_at: Option<i64>,
+    /// When the contact's key was verified out of band (unix timestamp).
+    #[serde(default)]
+    pub verified_at: Option<i64>,
 }
 
 impl CachedContact {
@@ -352,6 +355,7 @@ impl CachedContact {
             name: None,
             added_at: current_timestamp(),
             last_synced_at: None,
+            verified_at: None,
         }
     }
 
@@ -362,6 +366,7 @@ impl CachedContact {
             name: Some(name.into()),
             added_at: current_timestamp(),
             last_synced_at: None,
+            verified_at: None,
         }
     }
 }
@@ -437,6 +442,32 @@ impl<S: SecureStorage> LocalContactCache<S> {
         self.upsert(CachedContact::with_name(pubkey, name))
     }
 
+    /// Mark a contact verified, e.g. after comparing short authentication
+    /// strings. Fails if the contact is not cached.
+    pub fn mark_verified(&self, pubkey: &str, verified_at: i64) -> StorageResult<()> {
+        self.set_verified_at(pubkey, Some(verified_at))
+    }
+
+    /// Clear a contact's verification, e.g. after the contact changed keys.
+    pub fn clear_verification(&self, pubkey: &str) -> StorageResult<()> {
+        self.set_verified_at(pubkey, None)
+    }
+
+    /// Check if a contact is cached and verified.
+    pub fn is_verified(&self, pubkey: &str) -> StorageResult<bool> {
+        Ok(self.get(pubkey)?.is_some_and(|c| c.verified_at.is_some()))
+    }
+
+    fn set_verified_at(&self, pubkey: &str, verified_at: Option<i64>) -> StorageResult<()> {
+        let mut contacts = self.get_all()?;
+        let contact = contacts
+            .iter_mut()
+            .find(|c| c.pubkey == pubkey)
+            .ok_or_else(|| StorageError::not_found(pubkey))?;
+        contact.verified_at = verified_at;
+        self.storage.store_json(&self.cache_key, &contacts)
+    }
+
     /// Remove a contact by public key.
     pub fn remove(&self, pubkey: &str) -> StorageResult<()> {
         let mut contacts = self.get_all()?;
@@ -485,6 +516,7 @@ impl<S: SecureStorage> LocalContactCache<S> {
                     name: None,
                     added_at: now,
                     last_synced_at: Some(now),
+                    verified_at: None,
                 });
                 added += 1;
             } else {
@@ -601,6 +633,32 @@ impl ContactCacheFFI {
         Ok(())
     }
 
+    /// Mark a contact verified at `verified_at` (unix timestamp).
+    pub fn mark_verified(&self, pubkey: String, verified_at: i64) -> Result<(), StorageCacheError> {
+        let cache = self.cache.write().map_err(|_| StorageCacheError::Lock {
+            msg: "Lock poisoned".to_string(),
+        })?;
+        cache.mark_verified(&pubkey, verified_at)?;
+        Ok(())
+    }
+
+    /// Clear a contact's verification.
+    pub fn clear_verification(&self, pubkey: String) -> Result<(), StorageCacheError> {
+        let cache = self.cache.write().map_err(|_| StorageCacheError::Lock {
+            msg: "Lock poisoned".to_string(),
+        })?;
+        cache.clear_verification(&pubkey)?;
+        Ok(())
+    }
+
+    /// Check if a contact is cached and verified.
+    pub fn is_verified(&self, pubkey: String) -> Result<bool, StorageCacheError> {
+        let cache = self.cache.read().map_err(|_| StorageCacheError::Lock {
+            msg: "Lock poisoned".to_string(),
+        })?;
+        Ok(cache.is_verified(&pubkey)?)
+    }
+
     /// Remove a contact by public key.
     pub fn remove(&self, pubkey: String) -> Result<(), StorageCacheError> {
         let cache = self.cache.write().map_err(|_| StorageCacheError::Lock {
@@ -652,6 +710,7 @@ pub struct CachedContactFFI {
     pub name: Option<String>,
     pub added_at: i64,
     pub last_synced_at: Option<i64>,
+    pub verified_at: Option<i64>,
 }
 
 impl From<CachedContact> for CachedContactFFI {
@@ -661,6 +720,7 @@ impl From<CachedContact> for CachedContactFFI {
             name: c.name,
             added_at: c.added_at,
             last_synced_at: c.last_synced_at,
+            verified_at: c.verified_at,
         }
     }
 }
@@ -900,4 +960,29 @@ mod tests {
         // Should still be only one contact
         assert_eq!(cache.count().unwrap(), 1);
     }
+
+    #[test]
+    fn test_contact_cache_verification() {
+        let storage = InMemoryStorage::new();
+        let cache = LocalContactCache::with_default_key(storage);
+
+        assert!(cache.mark_verified("pubkey1", 1_700_000_000).is_err());
+
+        cache.add("pubkey1").unwrap();
+        assert!(!cache.is_verified("pubkey1").unwrap());
+
+        cache.mark_verified("pubkey1", 1_700_000_000).unwrap();
+        assert_eq!(
+            cache.get("pubkey1").unwrap().unwrap().verified_at,
+            Some(1_700_000_000)
+        );
+        assert!(cache.is_verified("pubkey1").unwrap());
+
+        // Syncing keeps the verification
+        cache.sync(&["pubkey1".to_string()]).unwrap();
+        assert!(cache.is_verified("pubkey1").unwrap());
+
+        cache.clear_verification("pubkey1").unwrap();
+        assert!(!cache.is_verified("pubkey1").unwrap());
+    }
 }
@@ -0,0 +1,22 @@
+//! Helpers shared by unit tests across modules.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Mutex, RwLock};
+
+/// Poison `lock` by panicking while holding its write guard.
+pub(crate) fn poison_rwlock<T>(lock: &RwLock<T>) {
+    let _ = catch_unwind(AssertUnwindSafe(|| {
+        let _guard = lock.write().unwrap();
+        panic!("poisoning lock for test");
+    }));
+    assert!(lock.is_poisoned());
+}
+
+/// Poison `lock` by panicking while holding its guard.
+pub(crate) fn poison_mutex<T>(lock: &Mutex<T>) {
+    let _ = catch_unwind(AssertUnwindSafe(|| {
+        let _guard = lock.lock().unwrap();
+        panic!("poisoning lock for test");
+    }));
+    assert!(lock.is_poisoned());
+}
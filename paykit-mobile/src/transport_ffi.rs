@@ -55,6 +55,8 @@
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
+use paykit_lib::protocol::{PathRoot, PathRootConfig};
+
 use crate::{PaykitMobileError, PaymentMethod, Result};
 
 // ============================================================================
@@ -255,6 +257,8 @@ pub struct AuthenticatedTransportFFI {
     owner_pubkey: String,
     /// Storage backend (mock or callback-based).
     backend: RwLock<StorageBackend>,
+    /// Directory root payment endpoints are written under.
+    roots: RwLock<PathRootConfig>,
 }
 
 #[uniffi::export]
@@ -273,6 +277,7 @@ impl AuthenticatedTransportFFI {
             backend: RwLock::new(StorageBackend::Mock(Arc::new(RwLock::new(
                 MockStorage::default(),
             )))),
+            roots: RwLock::new(PathRootConfig::default()),
         })
     }
 
@@ -313,6 +318,7 @@ impl AuthenticatedTransportFFI {
         Arc::new(Self {
             owner_pubkey,
             backend: RwLock::new(StorageBackend::Callback(callback)),
+            roots: RwLock::new(PathRootConfig::default()),
         })
     }
 
@@ -344,6 +350,19 @@ impl AuthenticatedTransportFFI {
         self.owner_pubkey.clone()
     }
 
+    /// Publish payment endpoints under a white-label root such as
+    /// `acmepay.app` instead of `paykit.app`.
+    pub fn set_path_root(&self, app: String) -> Result<()> {
+        let root = parse_path_root(app)?;
+        *write_roots(&self.roots)? = PathRootConfig::new(root);
+        Ok(())
+    }
+
+    /// The root payment endpoints are published under.
+    pub fn path_root(&self) -> Result<String> {
+        Ok(self.path_roots()?.root.to_string())
+    }
+
     /// Check if this transport uses a real callback (production) or mock storage.
     ///
     /// Returns `true` for mock transport, `false` for callback-based transport.
@@ -525,6 +544,8 @@ enum UnauthenticatedStorageBackend {
 pub struct UnauthenticatedTransportFFI {
     /// Storage backend (mock or callback-based).
     backend: RwLock<UnauthenticatedStorageBackend>,
+    /// Directory roots payment endpoints are looked up in.
+    roots: RwLock<PathRootConfig>,
 }
 
 #[uniffi::export]
@@ -538,6 +559,7 @@ impl UnauthenticatedTransportFFI {
             backend: RwLock::new(UnauthenticatedStorageBackend::Mock(Arc::new(RwLock::new(
                 MockStorage::default(),
             )))),
+            roots: RwLock::new(PathRootConfig::default()),
         })
     }
 
@@ -563,6 +585,7 @@ impl UnauthenticatedTransportFFI {
     pub fn from_callback(callback: Box<dyn PubkyUnauthenticatedStorageCallback>) -> Arc<Self> {
         Arc::new(Self {
             backend: RwLock::new(UnauthenticatedStorageBackend::Callback(callback)),
+            roots: RwLock::new(PathRootConfig::default()),
         })
     }
 
@@ -609,6 +632,7 @@ impl UnauthenticatedTransportFFI {
         match &*backend {
             StorageBackend::Mock(storage) => Ok(Arc::new(Self {
                 backend: RwLock::new(UnauthenticatedStorageBackend::Mock(storage.clone())),
+                roots: RwLock::new(auth.path_roots()?),
             })),
             StorageBackend::Callback(_) => Err(PaykitMobileError::Validation {
                 msg: "Cannot create unauthenticated transport from callback-based authenticated transport. Use from_callback() instead.".to_string(),
@@ -616,6 +640,24 @@ impl UnauthenticatedTransportFFI {
         }
     }
 
+    /// Look payment endpoints up under a white-label root such as
+    /// `acmepay.app`, then under `paykit.app` when `fallback_to_paykit` is
+    /// set and nothing was found.
+    pub fn set_path_root(&self, app: String, fallback_to_paykit: bool) -> Result<()> {
+        let root = parse_path_root(app)?;
+        *write_roots(&self.roots)? = if fallback_to_paykit {
+            PathRootConfig::white_label(root)
+        } else {
+            PathRootConfig::new(root)
+        };
+        Ok(())
+    }
+
+    /// The first root payment endpoints are looked up under.
+    pub fn path_root(&self) -> Result<String> {
+        Ok(self.path_roots()?.root.to_string())
+    }
+
     /// Check if this transport uses a real callback (production) or mock storage.
     ///
     /// Returns `true` for mock transport, `false` for callback-based transport.
@@ -698,11 +740,46 @@ impl UnauthenticatedTransportFFI {
     }
 }
 
+impl AuthenticatedTransportFFI {
+    fn path_roots(&self) -> Result<PathRootConfig> {
+        read_roots(&self.roots)
+    }
+}
+
+impl UnauthenticatedTransportFFI {
+    fn path_roots(&self) -> Result<PathRootConfig> {
+        read_roots(&self.roots)
+    }
+}
+
+fn parse_path_root(app: String) -> Result<PathRoot> {
+    PathRoot::new(app).map_err(|e| PaykitMobileError::Validation { msg: e.to_string() })
+}
+
+fn read_roots(roots: &RwLock<PathRootConfig>) -> Result<PathRootConfig> {
+    roots
+        .read()
+        .map(|roots| roots.clone())
+        .map_err(|_| PaykitMobileError::Internal {
+            msg: "Lock poisoned".to_string(),
+        })
+}
+
+fn write_roots(
+    roots: &RwLock<PathRootConfig>,
+) -> Result<std::sync::RwLockWriteGuard<'_, PathRootConfig>> {
+    roots.write().map_err(|_| PaykitMobileError::Internal {
+        msg: "Lock poisoned".to_string(),
+    })
+}
+
 // ============================================================================
 // Directory Operations
 // ============================================================================
 
 /// Path prefix for Paykit payment endpoints.
+///
+/// Transports configured with `set_path_root` use their own root instead.
 pub const PAYKIT_PATH_PREFIX: &str = "/pub/paykit.app/v0/";
 
 /// Path prefix for Pubky follows/contacts.
@@ -720,7 +797,9 @@ pub fn publish_payment_endpoint(
     method_id: &str,
     endpoint_data: &str,
 ) -> Result<()> {
-    let path = format!("{}{}", PAYKIT_PATH_PREFIX, method_id);
+    let path = transport
+        .path_roots()?
+        .rebase(&format!("{}{}", PAYKIT_PATH_PREFIX, method_id));
     transport.put(path, endpoint_data.to_string())
 }
 
@@ -734,7 +813,9 @@ pub fn remove_payment_endpoint(
     transport: &AuthenticatedTransportFFI,
     method_id: &str,
 ) -> Result<()> {
-    let path = format!("{}{}", PAYKIT_PATH_PREFIX, method_id);
+    let path = transport
+        .path_roots()?
+        .rebase(&format!("{}{}", PAYKIT_PATH_PREFIX, method_id));
     transport.delete(path)
 }
 
@@ -752,21 +833,27 @@ pub fn fetch_supported_payments(
     transport: &UnauthenticatedTransportFFI,
     owner_pubkey: &str,
 ) -> Result<Vec<PaymentMethod>> {
-    let paths = transport.list(owner_pubkey.to_string(), PAYKIT_PATH_PREFIX.to_string())?;
-
-    let mut methods = Vec::new();
-    for path in paths {
-        if let Some(method_id) = path.strip_prefix(PAYKIT_PATH_PREFIX) {
-            if let Some(endpoint) = transport.get(owner_pubkey.to_string(), path.clone())? {
-                methods.push(PaymentMethod {
-                    method_id: method_id.to_string(),
-                    endpoint,
-                });
+    // The first root with any endpoints wins
+    for prefix in transport.path_roots()?.read_paths(PAYKIT_PATH_PREFIX) {
+        let paths = transport.list(owner_pubkey.to_string(), prefix.clone())?;
+
+        let mut methods = Vec::new();
+        for path in paths {
+            if let Some(method_id) = path.strip_prefix(prefix.as_str()) {
+                if let Some(endpoint) = transport.get(owner_pubkey.to_string(), path.clone())? {
+                    methods.push(PaymentMethod {
+                        method_id: method_id.to_string(),
+                        endpoint,
+                    });
+                }
             }
         }
+        if !methods.is_empty() {
+            return Ok(methods);
+        }
     }
 
-    Ok(methods)
+    Ok(Vec::new())
 }
 
 /// Fetch a specific payment endpoint for a public key.
@@ -786,7 +873,12 @@ pub fn fetch_payment_endpoint(
     method_id: &str,
 ) -> Result<Option<String>> {
     let path = format!("{}{}", PAYKIT_PATH_PREFIX, method_id);
-    transport.get(owner_pubkey.to_string(), path)
+    for path in transport.path_roots()?.read_paths(&path) {
+        if let Some(endpoint) = transport.get(owner_pubkey.to_string(), path)? {
+            return Ok(Some(endpoint));
+        }
+    }
+    Ok(None)
 }
 
 /// Fetch known contacts for a public key.
@@ -950,6 +1042,36 @@ mod tests {
         assert_eq!(methods.len(), 2);
     }
 
+    #[test]
+    fn test_white_label_root_falls_back_to_paykit() {
+        let auth = AuthenticatedTransportFFI::new_mock("test_owner".to_string());
+        let unauth = UnauthenticatedTransportFFI::from_authenticated(auth.clone()).unwrap();
+
+        // Published by a client on the default root
+        publish_payment_endpoint(&auth, "onchain", "bc1q...").unwrap();
+
+        auth.set_path_root("acmepay.app".to_string()).unwrap();
+        publish_payment_endpoint(&auth, "lightning", "lnbc1...").unwrap();
+        assert_eq!(
+            auth.get("/pub/acmepay.app/v0/lightning".to_string())
+                .unwrap(),
+            Some("lnbc1...".to_string())
+        );
+
+        unauth
+            .set_path_root("acmepay.app".to_string(), true)
+            .unwrap();
+        let result = fetch_payment_endpoint(&unauth, "test_owner", "onchain").unwrap();
+        assert_eq!(result, Some("bc1q...".to_string()));
+
+        // The primary root has endpoints, so the fallback is not merged in
+        let methods = fetch_supported_payments(&unauth, "test_owner").unwrap();
+        assert_eq!(methods.len(), 1);
+        assert_eq!(methods[0].method_id, "lightning");
+
+        assert!(auth.set_path_root("../etc".to_string()).is_err());
+    }
+
     #[test]
     fn test_remove_payment_endpoint() {
         let auth = AuthenticatedTransportFFI::new_mock("test_owner".to_string());
@@ -0,0 +1,290 @@
+//! Payee Trust Policy FFI Bindings
+//!
+//! This module exposes the payee allowlist/blocklist to mobile applications.
+//! Apps evaluate the payee before every payment, show a confirmation prompt
+//! for unknown keys, and refuse blocked keys.
+//!
+//! # Example Flow
+//!
+//! ```ignore
+//! let policy = TrustPolicyFFI::new();
+//! policy.block(scammer_key, Some("reported by user".into()));
+//!
+//! match policy.evaluate(payee_key)? {
+//!     TrustDecisionFFI::Allow => pay(),
+//!     TrustDecisionFFI::RequireConfirmation => show_confirmation_dialog(),
+//!     TrustDecisionFFI::Block { reason } => show_blocked(reason),
+//! }
+//!
+//! // Large payments only to contacts verified in person
+//! policy.set_require_verified_above(Some(100_000))?;
+//! policy.check_verified_payment(payee_key, amount_sats, contacts.is_verified(payee_key)?)?;
+//!
+//! // Persist between launches
+//! save(policy.export_state()?);
+//! ```
+
+use crate::{PaykitMobileError, Result};
+use paykit_lib::policy::{TrustDecision, TrustEntry, TrustLevel, TrustPolicy};
+use std::sync::{Arc, RwLock};
+
+// ============================================================================
+// FFI Types
+// ============================================================================
+
+/// FFI-safe trust level.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, uniffi::Enum)]
+pub enum TrustLevelFFI {
+    /// Payments proceed without extra confirmation
+    Trusted,
+    /// Outgoing payments are refused
+    Blocked,
+}
+
+impl From<TrustLevel> for TrustLevelFFI {
+    fn from(level: TrustLevel) -> Self {
+        match level {
+            TrustLevel::Trusted => TrustLevelFFI::Trusted,
+            TrustLevel::Blocked => TrustLevelFFI::Blocked,
+        }
+    }
+}
+
+/// FFI-safe trust entry.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct TrustEntryFFI {
+    /// Public key (z-base-32)
+    pub public_key: String,
+    /// Trust level
+    pub level: TrustLevelFFI,
+    /// Why the key was trusted or blocked
+    pub reason: Option<String>,
+    /// Unix timestamp of the last change
+    pub updated_at: i64,
+}
+
+impl From<&TrustEntry> for TrustEntryFFI {
+    fn from(entry: &TrustEntry) -> Self {
+        Self {
+            public_key: entry.public_key.clone(),
+            level: entry.level.into(),
+            reason: entry.reason.clone(),
+            updated_at: entry.updated_at,
+        }
+    }
+}
+
+/// FFI-safe outcome of evaluating a payee.
+#[derive(Clone, Debug, PartialEq, Eq, uniffi::Enum)]
+pub enum TrustDecisionFFI {
+    /// Payment may proceed
+    Allow,
+    /// Payee is unknown; ask the user before paying
+    RequireConfirmation,
+    /// Payee is blocked
+    Block { reason: Option<String> },
+}
+
+impl From<TrustDecision> for TrustDecisionFFI {
+    fn from(decision: TrustDecision) -> Self {
+        match decision {
+            TrustDecision::Allow => TrustDecisionFFI::Allow,
+            TrustDecision::RequireConfirmation => TrustDecisionFFI::RequireConfirmation,
+            TrustDecision::Block { reason } => TrustDecisionFFI::Block { reason },
+        }
+    }
+}
+
+// ============================================================================
+// Trust Policy
+// ============================================================================
+
+/// Thread-safe wrapper around the payee trust policy.
+#[derive(uniffi::Object)]
+pub struct TrustPolicyFFI {
+    policy: RwLock<TrustPolicy>,
+}
+
+impl TrustPolicyFFI {
+    fn read(&self) -> Result<std::sync::RwLockReadGuard<'_, TrustPolicy>> {
+        self.policy.read().map_err(|_| PaykitMobileError::Internal {
+            msg: "Failed to acquire trust policy lock".to_string(),
+        })
+    }
+
+    fn write(&self) -> Result<std::sync::RwLockWriteGuard<'_, TrustPolicy>> {
+        self.policy
+            .write()
+            .map_err(|_| PaykitMobileError::Internal {
+                msg: "Failed to acquire trust policy lock".to_string(),
+            })
+    }
+}
+
+#[uniffi::export]
+impl TrustPolicyFFI {
+    /// Create an empty policy that requires confirmation for unknown payees.
+    #[uniffi::constructor]
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            policy: RwLock::new(TrustPolicy::new()),
+        })
+    }
+
+    /// Restore a policy from state produced by `export_state()`.
+    #[uniffi::constructor]
+    pub fn from_state(state_json: String) -> Result<Arc<Self>> {
+        let policy: TrustPolicy =
+            serde_json::from_str(&state_json).map_err(|e| PaykitMobileError::Serialization {
+                msg: format!("Invalid trust policy state: {}", e),
+            })?;
+        Ok(Arc::new(Self {
+            policy: RwLock::new(policy),
+        }))
+    }
+
+    /// Export the policy as JSON for persistence.
+    pub fn export_state(&self) -> Result<String> {
+        let policy = self.read()?;
+        serde_json::to_string(&*policy)
+            .map_err(|e| PaykitMobileError::Serialization { msg: e.to_string() })
+    }
+
+    /// Mark a public key as trusted.
+    pub fn trust(&self, public_key: String, reason: Option<String>) -> Result<TrustEntryFFI> {
+        Ok(self.write()?.trust(&public_key, reason.as_deref()).into())
+    }
+
+    /// Mark a public key as blocked.
+    pub fn block(&self, public_key: String, reason: Option<String>) -> Result<TrustEntryFFI> {
+        Ok(self.write()?.block(&public_key, reason.as_deref()).into())
+    }
+
+    /// Remove a public key from the policy. Returns whether it was present.
+    pub fn remove(&self, public_key: String) -> Result<bool> {
+        Ok(self.write()?.remove(&public_key).is_some())
+    }
+
+    /// Get the entry for a public key.
+    pub fn get_entry(&self, public_key: String) -> Result<Option<TrustEntryFFI>> {
+        Ok(self.read()?.entry(&public_key).map(Into::into))
+    }
+
+    /// List all entries.
+    pub fn entries(&self) -> Result<Vec<TrustEntryFFI>> {
+        Ok(self.read()?.entries().into_iter().map(Into::into).collect())
+    }
+
+    /// Set whether unknown payees require confirmation.
+    pub fn set_confirm_unknown(&self, required: bool) -> Result<()> {
+        self.write()?.set_confirm_unknown(required);
+        Ok(())
+    }
+
+    /// Check whether unknown payees require confirmation.
+    pub fn is_confirm_unknown(&self) -> Result<bool> {
+        Ok(self.read()?.confirm_unknown())
+    }
+
+    /// Evaluate a payee before paying.
+    pub fn evaluate(&self, public_key: String) -> Result<TrustDecisionFFI> {
+        Ok(self.read()?.evaluate_key(&public_key).into())
+    }
+
+    /// Enforce the policy for a payment.
+    ///
+    /// Pass `confirmed = true` once the user has approved an unknown payee.
+    /// Blocked payees always fail with a validation error.
+    pub fn check_payment(&self, public_key: String, confirmed: bool) -> Result<()> {
+        Ok(self.read()?.check_payment_key(&public_key, confirmed)?)
+    }
+
+    /// Require verified contacts for payments above `amount_sats`, or lift
+    /// the requirement with `None`.
+    pub fn set_require_verified_above(&self, amount_sats: Option<u64>) -> Result<()> {
+        self.write()?.set_require_verified_above(amount_sats);
+        Ok(())
+    }
+
+    /// Amount above which payees must be verified contacts, if any.
+    pub fn require_verified_above(&self) -> Result<Option<u64>> {
+        Ok(self.read()?.require_verified_above())
+    }
+
+    /// Enforce the verified-contact requirement for a payment.
+    ///
+    /// Pass whether the payee is verified in the contact cache
+    /// (`ContactCacheFFI::is_verified`).
+    pub fn check_verified_payment(
+        &self,
+        public_key: String,
+        amount_sats: u64,
+        verified: bool,
+    ) -> Result<()> {
+        Ok(self
+            .read()?
+            .check_verified_payment(&public_key, amount_sats, verified)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "8pinxxgqs41n4aididenw5apqp1urfmzdztr8jt4abrkdn435ewo";
+
+    #[test]
+    fn test_trust_policy_ffi_decisions() {
+        let policy = TrustPolicyFFI::new();
+        assert_eq!(
+            policy.evaluate(KEY.to_string()).unwrap(),
+            TrustDecisionFFI::RequireConfirmation
+        );
+        assert!(policy.check_payment(KEY.to_string(), false).is_err());
+        assert!(policy.check_payment(KEY.to_string(), true).is_ok());
+
+        let entry = policy
+            .block(KEY.to_string(), Some("scam".to_string()))
+            .unwrap();
+        assert_eq!(entry.level, TrustLevelFFI::Blocked);
+        assert_eq!(
+            policy.evaluate(KEY.to_string()).unwrap(),
+            TrustDecisionFFI::Block {
+                reason: Some("scam".to_string())
+            }
+        );
+        assert!(policy.check_payment(KEY.to_string(), true).is_err());
+    }
+
+    #[test]
+    fn test_trust_policy_ffi_state_roundtrip() {
+        let policy = TrustPolicyFFI::new();
+        policy.trust(KEY.to_string(), None).unwrap();
+        policy.set_confirm_unknown(false).unwrap();
+
+        let restored = TrustPolicyFFI::from_state(policy.export_state().unwrap()).unwrap();
+        assert_eq!(restored.entries().unwrap().len(), 1);
+        assert!(!restored.is_confirm_unknown().unwrap());
+        assert!(restored.remove(KEY.to_string()).unwrap());
+        assert!(!restored.remove(KEY.to_string()).unwrap());
+    }
+
+    #[test]
+    fn test_trust_policy_ffi_verified_contacts() {
+        let policy = TrustPolicyFFI::new();
+        policy.set_require_verified_above(Some(10_000)).unwrap();
+
+        assert!(policy
+            .check_verified_payment(KEY.to_string(), 10_000, false)
+            .is_ok());
+        assert!(policy
+            .check_verified_payment(KEY.to_string(), 20_000, false)
+            .is_err());
+        assert!(policy
+            .check_verified_payment(KEY.to_string(), 20_000, true)
+            .is_ok());
+
+        let restored = TrustPolicyFFI::from_state(policy.export_state().unwrap()).unwrap();
+        assert_eq!(restored.require_verified_above().unwrap(), Some(10_000));
+    }
+}
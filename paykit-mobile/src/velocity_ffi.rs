@@ -0,0 +1,206 @@
+//! Velocity Check FFI Types
+//!
+//! FFI-safe types for the outgoing payment velocity guard. The guard itself
+//! lives on [`PaykitClient`](crate::PaykitClient), which checks it in
+//! `execute_payment()`.
+//!
+//! When a payment looks anomalous, `execute_payment()` fails with
+//! `PaykitMobileError::PaymentHeld` and a `VelocityEventFFI::Held` event is
+//! delivered to registered listeners. After the user confirms, the app calls
+//! `approve_held_payment()` and retries the payment.
+//!
+//! # Example Flow
+//!
+//! ```ignore
+//! client.add_velocity_listener(Box::new(MyListener));
+//!
+//! match client.execute_payment("lightning".into(), invoice, 250_000, None, None) {
+//!     Err(PaykitMobileError::PaymentHeld { .. }) => {
+//!         // MyListener::on_velocity_event shows the confirmation dialog
+//!     }
+//!     result => handle(result),
+//! }
+//!
+//! // When the user taps "Send anyway"
+//! client.approve_held_payment(hold_id)?;
+//! client.execute_payment("lightning".into(), invoice, 250_000, None, None)?;
+//! ```
+
+use paykit_lib::policy::{Anomaly, HeldPayment, VelocityConfig, VelocityEvent};
+
+/// FFI-safe velocity thresholds.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct VelocityConfigFFI {
+    /// Sliding window for rate checks, in seconds
+    pub window_secs: i64,
+    /// Maximum payments within the window
+    pub max_payments_per_window: u32,
+    /// Payments at or below this amount count as "small"
+    pub small_payment_sats: u64,
+    /// Maximum small payments within the window
+    pub max_small_payments_per_window: u32,
+    /// Maximum total sent within the window, if limited
+    pub max_amount_per_window_sats: Option<u64>,
+    /// First payments to new peers above this multiple of the average are held
+    pub new_peer_spike_multiplier: f64,
+    /// First payments to new peers below this amount are never held
+    pub new_peer_spike_min_sats: u64,
+    /// How long payment history is kept, in seconds
+    pub history_secs: i64,
+    /// How long an approved hold stays valid, in seconds
+    pub approval_ttl_secs: i64,
+}
+
+impl From<VelocityConfig> for VelocityConfigFFI {
+    fn from(config: VelocityConfig) -> Self {
+        Self {
+            window_secs: config.window_secs,
+            max_payments_per_window: config.max_payments_per_window,
+            small_payment_sats: config.small_payment_sats,
+            max_small_payments_per_window: config.max_small_payments_per_window,
+            max_amount_per_window_sats: config.max_amount_per_window_sats,
+            new_peer_spike_multiplier: config.new_peer_spike_multiplier,
+            new_peer_spike_min_sats: config.new_peer_spike_min_sats,
+            history_secs: config.history_secs,
+            approval_ttl_secs: config.approval_ttl_secs,
+        }
+    }
+}
+
+impl From<VelocityConfigFFI> for VelocityConfig {
+    fn from(config: VelocityConfigFFI) -> Self {
+        Self {
+            window_secs: config.window_secs,
+            max_payments_per_window: config.max_payments_per_window,
+            small_payment_sats: config.small_payment_sats,
+            max_small_payments_per_window: config.max_small_payments_per_window,
+            max_amount_per_window_sats: config.max_amount_per_window_sats,
+            new_peer_spike_multiplier: config.new_peer_spike_multiplier,
+            new_peer_spike_min_sats: config.new_peer_spike_min_sats,
+            history_secs: config.history_secs,
+            approval_ttl_secs: config.approval_ttl_secs,
+        }
+    }
+}
+
+/// FFI-safe anomaly description.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct AnomalyFFI {
+    /// Anomaly kind ("rate_exceeded", "small_payment_burst",
+    /// "window_amount_exceeded", "new_peer_spike")
+    pub kind: String,
+    /// Human-readable description
+    pub description: String,
+}
+
+impl From<&Anomaly> for AnomalyFFI {
+    fn from(anomaly: &Anomaly) -> Self {
+        let kind = match anomaly {
+            Anomaly::RateExceeded { .. } => "rate_exceeded",
+            Anomaly::SmallPaymentBurst { .. } => "small_payment_burst",
+            Anomaly::WindowAmountExceeded { .. } => "window_amount_exceeded",
+            Anomaly::NewPeerSpike { .. } => "new_peer_spike",
+        };
+        Self {
+            kind: kind.to_string(),
+            description: anomaly.describe(),
+        }
+    }
+}
+
+/// FFI-safe payment held for confirmation.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct HeldPaymentFFI {
+    /// Identifier used to approve or reject the hold
+    pub hold_id: String,
+    /// Counterparty (payment endpoint or public key)
+    pub peer: String,
+    /// Amount in satoshis
+    pub amount_sats: u64,
+    /// Why the payment was held
+    pub anomalies: Vec<AnomalyFFI>,
+    /// Unix timestamp when the hold was created
+    pub created_at: i64,
+}
+
+impl From<HeldPayment> for HeldPaymentFFI {
+    fn from(held: HeldPayment) -> Self {
+        Self {
+            anomalies: held.anomalies.iter().map(Into::into).collect(),
+            hold_id: held.hold_id,
+            peer: held.peer,
+            amount_sats: held.amount_sats,
+            created_at: held.created_at,
+        }
+    }
+}
+
+/// FFI-safe velocity event.
+#[derive(Clone, Debug, uniffi::Enum)]
+pub enum VelocityEventFFI {
+    /// A payment was held for confirmation
+    Held { payment: HeldPaymentFFI },
+    /// A held payment was approved
+    Approved { payment: HeldPaymentFFI },
+    /// A held payment was rejected
+    Rejected { payment: HeldPaymentFFI },
+}
+
+impl From<&VelocityEvent> for VelocityEventFFI {
+    fn from(event: &VelocityEvent) -> Self {
+        match event.clone() {
+            VelocityEvent::Held(held) => VelocityEventFFI::Held {
+                payment: held.into(),
+            },
+            VelocityEvent::Approved(held) => VelocityEventFFI::Approved {
+                payment: held.into(),
+            },
+            VelocityEvent::Rejected(held) => VelocityEventFFI::Rejected {
+                payment: held.into(),
+            },
+        }
+    }
+}
+
+/// Callback interface for velocity events.
+///
+/// Implement this in Swift/Kotlin to show a confirmation prompt when a
+/// payment is held.
+#[uniffi::export(callback_interface)]
+pub trait VelocityEventListener: Send + Sync {
+    /// Called when a payment is held, approved or rejected.
+    fn on_velocity_event(&self, event: VelocityEventFFI);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_velocity_config_roundtrip() {
+        let config = VelocityConfig {
+            max_amount_per_window_sats: Some(1_000_000),
+            ..Default::default()
+        };
+        let ffi: VelocityConfigFFI = config.clone().into();
+        assert_eq!(VelocityConfig::from(ffi), config);
+    }
+
+    #[test]
+    fn test_held_payment_conversion() {
+        let held = HeldPayment {
+            hold_id: "hold_1_1".to_string(),
+            peer: "lnbc1...".to_string(),
+            amount_sats: 500,
+            anomalies: vec![Anomaly::SmallPaymentBurst {
+                count: 6,
+                window_secs: 60,
+            }],
+            created_at: 1,
+        };
+
+        let ffi: HeldPaymentFFI = held.into();
+        assert_eq!(ffi.anomalies[0].kind, "small_payment_burst");
+        assert!(ffi.anomalies[0].description.contains("6 small payments"));
+    }
+}
@@ -110,11 +110,14 @@ public final class BitkitBitcoinExecutor: BitcoinExecutorFFI {
     ///   - address: Destination Bitcoin address
     ///   - amountSats: Amount to send in satoshis
     ///   - feeRate: Optional fee rate in sat/vB (uses wallet default if nil)
+    ///   - coinSelection: Payer's coin-selection hints; honor them with the
+    ///     wallet's coin control where possible
     /// - Returns: Transaction result with txid and fee details
     public func sendToAddress(
         address: String,
         amountSats: UInt64,
-        feeRate: Double?
+        feeRate: Double?,
+        coinSelection: CoinSelectionFfi?
     ) throws -> BitcoinTxResultFfi {
         do {
             let tx = try wallet.sendToAddress(
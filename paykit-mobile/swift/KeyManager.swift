@@ -270,6 +270,46 @@ public final class KeyManager: ObservableObject {
         )
     }
     
+    /// Export identity to an armored backup
+    ///
+    /// Uses the shared Paykit backup format, which the CLI and web demos can
+    /// restore as well.
+    ///
+    /// - Parameter password: Password to encrypt the backup
+    /// - Returns: Armored backup text
+    public func exportPortableBackup(password: String) throws -> String {
+        guard let secretHex = try keychain.retrieveString(key: Keys.secretKey) else {
+            throw KeyManagerError.noIdentity
+        }
+        
+        return try exportIdentityBackup(secretKeyHex: secretHex, password: password)
+    }
+    
+    /// Import identity from an armored backup (or legacy CLI/web JSON)
+    ///
+    /// - Parameters:
+    ///   - backup: Backup text
+    ///   - password: Password to decrypt
+    /// - Returns: The restored keypair
+    @discardableResult
+    public func importPortableBackup(_ backup: String, password: String) throws -> Ed25519Keypair {
+        let keypair = try importIdentityBackup(backup: backup, password: password)
+        
+        // Store in keychain
+        try keychain.store(key: Keys.secretKey, string: keypair.secretKeyHex)
+        try keychain.store(key: Keys.publicKey, string: keypair.publicKeyHex)
+        try keychain.store(key: Keys.publicKeyZ32, string: keypair.publicKeyZ32)
+        
+        // Update published state
+        DispatchQueue.main.async {
+            self.hasIdentity = true
+            self.publicKeyZ32 = keypair.publicKeyZ32
+            self.publicKeyHex = keypair.publicKeyHex
+        }
+        
+        return keypair
+    }
+    
     // MARK: - Private Helpers
     
     private func loadIdentityState() {
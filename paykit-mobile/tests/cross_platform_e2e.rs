@@ -626,6 +626,7 @@ impl BitcoinExecutorFFI for CrossPlatformMockBitcoinExecutor {
         _address: String,
         _amount_sats: u64,
         _fee_rate: Option<f64>,
+        _coin_selection: Option<CoinSelectionFFI>,
     ) -> Result<BitcoinTxResultFFI> {
         let count = self.call_count.fetch_add(1, Ordering::SeqCst);
         Ok(BitcoinTxResultFFI {
@@ -750,6 +751,7 @@ fn test_executor_cross_platform_ios_simulation() {
             "tb1qtest123456789012345678901234567890".to_string(),
             50000,
             None,
+            None,
         )
         .unwrap();
 
@@ -782,6 +784,7 @@ fn test_executor_cross_platform_android_simulation() {
             "tb1qtest123456789012345678901234567890".to_string(),
             50000,
             None,
+            None,
         )
         .unwrap();
 
@@ -818,6 +821,7 @@ fn test_executor_payment_proof_format_consistency() {
             "tb1qtest123456789012345678901234567890".to_string(),
             10000,
             None,
+            None,
         )
         .unwrap();
 
@@ -827,6 +831,7 @@ fn test_executor_payment_proof_format_consistency() {
             "tb1qtest123456789012345678901234567890".to_string(),
             10000,
             None,
+            None,
         )
         .unwrap();
 
@@ -881,6 +886,7 @@ fn test_executor_concurrent_cross_platform_payments() {
                     "tb1qtest123456789012345678901234567890".to_string(),
                     10000,
                     None,
+                    None,
                 )
                 .unwrap();
 
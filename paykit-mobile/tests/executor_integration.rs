@@ -74,6 +74,7 @@ impl BitcoinExecutorFFI for MockBitcoinExecutor {
         address: String,
         amount_sats: u64,
         fee_rate: Option<f64>,
+        _coin_selection: Option<CoinSelectionFFI>,
     ) -> Result<BitcoinTxResultFFI> {
         if self.should_fail.load(Ordering::SeqCst) {
             return Err(PaykitMobileError::Transport {
@@ -408,6 +409,7 @@ fn test_execute_bitcoin_payment_success() {
             "tb1qtest12345678901234567890123456789012345".to_string(),
             50000,
             None,
+            None,
         )
         .unwrap();
 
@@ -438,6 +440,7 @@ fn test_execute_bitcoin_payment_with_fee_rate() {
             "tb1qtest12345678901234567890123456789012345".to_string(),
             50000,
             Some(serde_json::to_string(&metadata).unwrap()),
+            None,
         )
         .unwrap();
 
@@ -461,6 +464,7 @@ fn test_execute_bitcoin_payment_failure() {
         "tb1qtest12345678901234567890123456789012345".to_string(),
         50000,
         None,
+        None,
     );
 
     // The payment should fail but not panic
@@ -483,6 +487,7 @@ fn test_execute_bitcoin_payment_dust_limit() {
         "tb1qtest12345678901234567890123456789012345".to_string(),
         100, // Below dust
         None,
+        None,
     );
 
     // Should fail validation
@@ -507,7 +512,7 @@ fn test_execute_lightning_payment_success() {
     let invoice = format!("lntb1000n1p{}", "0".repeat(200));
 
     let result = client
-        .execute_payment("lightning".to_string(), invoice, 1000, None)
+        .execute_payment("lightning".to_string(), invoice, 1000, None, None)
         .unwrap();
 
     assert!(result.success);
@@ -529,7 +534,7 @@ fn test_execute_lightning_payment_with_amount() {
     let invoice = format!("lntb1p{}", "0".repeat(200)); // Zero-amount invoice
 
     let result = client
-        .execute_payment("lightning".to_string(), invoice, 5000, None)
+        .execute_payment("lightning".to_string(), invoice, 5000, None, None)
         .unwrap();
 
     assert!(result.success);
@@ -547,7 +552,7 @@ fn test_execute_lightning_payment_failure() {
 
     let invoice = format!("lntb1000n1p{}", "0".repeat(200));
 
-    let result = client.execute_payment("lightning".to_string(), invoice, 1000, None);
+    let result = client.execute_payment("lightning".to_string(), invoice, 1000, None, None);
 
     assert!(result.is_err() || !result.unwrap().success);
 }
@@ -567,7 +572,7 @@ fn test_execute_lightning_payment_pending() {
     let invoice = format!("lntb1000n1p{}", "0".repeat(200));
 
     let result = client
-        .execute_payment("lightning".to_string(), invoice, 1000, None)
+        .execute_payment("lightning".to_string(), invoice, 1000, None, None)
         .unwrap();
 
     // Payment initiated - the executor returned a result (pending is still a valid result)
@@ -636,6 +641,7 @@ fn test_execute_payment_unknown_method() {
         "some_endpoint".to_string(),
         1000,
         None,
+        None,
     );
 
     assert!(result.is_err());
@@ -696,7 +702,13 @@ fn test_full_bitcoin_payment_flow() {
 
     // Step 3: Execute payment
     let result = client
-        .execute_payment("onchain".to_string(), address.to_string(), 100000, None)
+        .execute_payment(
+            "onchain".to_string(),
+            address.to_string(),
+            100000,
+            None,
+            None,
+        )
         .unwrap();
 
     assert!(result.success);
@@ -732,7 +744,7 @@ fn test_full_lightning_payment_flow() {
 
     // Step 3: Execute payment
     let result = client
-        .execute_payment("lightning".to_string(), invoice.clone(), 1000, None)
+        .execute_payment("lightning".to_string(), invoice.clone(), 1000, None, None)
         .unwrap();
 
     assert!(result.success);
@@ -768,8 +780,10 @@ fn test_multiple_payments_sequential() {
             address: String,
             amount_sats: u64,
             fee_rate: Option<f64>,
+            coin_selection: Option<CoinSelectionFFI>,
         ) -> Result<BitcoinTxResultFFI> {
-            self.0.send_to_address(address, amount_sats, fee_rate)
+            self.0
+                .send_to_address(address, amount_sats, fee_rate, coin_selection)
         }
 
         fn estimate_fee(
@@ -807,6 +821,7 @@ fn test_multiple_payments_sequential() {
                 "tb1qtest12345678901234567890123456789012345".to_string(),
                 10000 * (i + 1),
                 None,
+                None,
             )
             .unwrap();
         assert!(result.success);
@@ -877,6 +892,7 @@ fn test_executor_called_from_multiple_threads() {
                 "tb1qtest12345678901234567890123456789012345".to_string(),
                 10000 + i * 1000,
                 None,
+                None,
             );
             assert!(result.is_ok());
             assert!(result.unwrap().success);
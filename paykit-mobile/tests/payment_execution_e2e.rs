@@ -45,6 +45,7 @@ impl BitcoinExecutorFFI for MockBitcoinExecutorE2E {
         address: String,
         amount_sats: u64,
         fee_rate: Option<f64>,
+        _coin_selection: Option<CoinSelectionFFI>,
     ) -> Result<BitcoinTxResultFFI> {
         if self.should_fail {
             return Err(PaykitMobileError::Transport {
@@ -255,7 +256,13 @@ fn test_e2e_onchain_payment_full_flow() {
 
     // Step 4: Execute payment
     let result = client
-        .execute_payment("onchain".to_string(), address.to_string(), 50000, None)
+        .execute_payment(
+            "onchain".to_string(),
+            address.to_string(),
+            50000,
+            None,
+            None,
+        )
         .unwrap();
 
     // Step 5: Verify execution result
@@ -300,6 +307,7 @@ fn test_e2e_onchain_payment_with_custom_fee() {
             "tb1qtest123456789012345678901234567890".to_string(),
             100000,
             Some(serde_json::to_string(&metadata).unwrap()),
+            None,
         )
         .unwrap();
 
@@ -336,7 +344,7 @@ fn test_e2e_lightning_payment_full_flow() {
 
     // Step 4: Execute payment
     let result = client
-        .execute_payment("lightning".to_string(), invoice.clone(), 1000, None)
+        .execute_payment("lightning".to_string(), invoice.clone(), 1000, None, None)
         .unwrap();
 
     // Step 5: Verify execution result
@@ -372,7 +380,7 @@ fn test_e2e_lightning_payment_with_amount() {
     let invoice = format!("lntb1p{}", "0".repeat(200));
 
     let result = client
-        .execute_payment("lightning".to_string(), invoice, 5000, None)
+        .execute_payment("lightning".to_string(), invoice, 5000, None, None)
         .unwrap();
 
     assert!(result.success);
@@ -391,6 +399,7 @@ fn test_e2e_payment_method_not_found() {
         "some_endpoint".to_string(),
         1000,
         None,
+        None,
     );
 
     assert!(result.is_err());
@@ -420,6 +429,7 @@ fn test_e2e_bitcoin_executor_failure() {
         "tb1qtest123456789012345678901234567890".to_string(),
         100000,
         None,
+        None,
     );
 
     // Should fail with executor error
@@ -441,7 +451,7 @@ fn test_e2e_lightning_executor_failure() {
 
     let invoice = format!("lntb1000n1p{}", "0".repeat(200));
 
-    let result = client.execute_payment("lightning".to_string(), invoice, 1000, None);
+    let result = client.execute_payment("lightning".to_string(), invoice, 1000, None, None);
 
     // Should fail with executor error
     assert!(result.is_err() || !result.as_ref().unwrap().success);
@@ -496,6 +506,7 @@ fn test_e2e_multiple_sequential_payments() {
                 "tb1qtest123456789012345678901234567890".to_string(),
                 10000 * i,
                 None,
+                None,
             )
             .unwrap();
 
@@ -525,6 +536,7 @@ fn test_e2e_mixed_payment_methods() {
             "tb1qtest123456789012345678901234567890".to_string(),
             50000,
             None,
+            None,
         )
         .unwrap();
     assert!(btc_result.success);
@@ -533,7 +545,7 @@ fn test_e2e_mixed_payment_methods() {
     // Execute Lightning payment
     let ln_invoice = format!("lntb1000n1p{}", "0".repeat(200));
     let ln_result = client
-        .execute_payment("lightning".to_string(), ln_invoice, 1000, None)
+        .execute_payment("lightning".to_string(), ln_invoice, 1000, None, None)
         .unwrap();
     assert!(ln_result.success);
     assert_eq!(ln_result.method_id, "lightning");
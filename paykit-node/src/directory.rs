@@ -0,0 +1,142 @@
+//! Directory discovery and publishing.
+
+use crate::{parse_public_key, parse_secret_key, to_js_error};
+use napi::{Error, Status};
+use napi_derive::napi;
+use paykit_lib::{
+    get_payment_endpoint, get_payment_list, remove_payment_endpoint, set_payment_endpoint,
+    EndpointData, MethodId, PubkyAuthenticatedTransport, PubkyUnauthenticatedTransport,
+};
+use pubky::{Pubky, PubkySession, PublicStorage};
+use std::sync::RwLock;
+
+/// A payment method published in a directory.
+#[napi(object)]
+#[derive(Clone, Debug)]
+pub struct PaymentMethod {
+    pub method_id: String,
+    pub endpoint: String,
+}
+
+/// Options for [`DirectoryClient::sign_in`].
+#[napi(object)]
+pub struct SignInOptions {
+    /// Hex encoded 32-byte secret key of the merchant identity.
+    pub secret_key: String,
+    /// Public key of the homeserver to sign up with if sign-in fails.
+    pub homeserver: String,
+    /// Token for homeservers that require one to sign up.
+    pub signup_token: Option<String>,
+    /// Use the local Pubky testnet.
+    pub testnet: Option<bool>,
+}
+
+/// Reads any payee's directory; publishes to the merchant's own once
+/// signed in.
+#[napi]
+pub struct DirectoryClient {
+    reader: PubkyUnauthenticatedTransport,
+    session: RwLock<Option<PubkySession>>,
+}
+
+#[napi]
+impl DirectoryClient {
+    #[napi(constructor)]
+    pub fn new() -> napi::Result<Self> {
+        let storage = PublicStorage::new().map_err(pubky_error)?;
+        Ok(Self {
+            reader: PubkyUnauthenticatedTransport::new(storage),
+            session: RwLock::new(None),
+        })
+    }
+
+    /// All methods published by `publicKey`; empty when there are none.
+    #[napi]
+    pub async fn query_methods(&self, public_key: String) -> napi::Result<Vec<PaymentMethod>> {
+        let payee = parse_public_key(&public_key)?;
+        let supported = get_payment_list(&self.reader, &payee)
+            .await
+            .map_err(to_js_error)?;
+        let mut methods: Vec<PaymentMethod> = supported
+            .entries
+            .into_iter()
+            .map(|(method_id, endpoint)| PaymentMethod {
+                method_id: method_id.0,
+                endpoint: endpoint.0,
+            })
+            .collect();
+        methods.sort_by(|a, b| a.method_id.cmp(&b.method_id));
+        Ok(methods)
+    }
+
+    /// One published endpoint, or `null`.
+    #[napi]
+    pub async fn fetch_endpoint(
+        &self,
+        public_key: String,
+        method_id: String,
+    ) -> napi::Result<Option<String>> {
+        let payee = parse_public_key(&public_key)?;
+        let endpoint = get_payment_endpoint(&self.reader, &payee, &MethodId(method_id))
+            .await
+            .map_err(to_js_error)?;
+        Ok(endpoint.map(|endpoint| endpoint.0))
+    }
+
+    /// Sign in as the merchant, signing up on first use. Resolves to the
+    /// merchant's public key.
+    #[napi]
+    pub async fn sign_in(&self, options: SignInOptions) -> napi::Result<String> {
+        let keypair = parse_secret_key(&options.secret_key)?;
+        let homeserver = parse_public_key(&options.homeserver)?;
+
+        let pubky = if options.testnet.unwrap_or(false) {
+            Pubky::testnet()
+        } else {
+            Pubky::new()
+        }
+        .map_err(pubky_error)?;
+        let signer = pubky.signer(keypair.clone());
+        let session = match signer.signin().await {
+            Ok(session) => session,
+            Err(_) => signer
+                .signup(&homeserver, options.signup_token.as_deref())
+                .await
+                .map_err(pubky_error)?,
+        };
+
+        *self.session.write().unwrap_or_else(|e| e.into_inner()) = Some(session);
+        Ok(keypair.public_key().to_string())
+    }
+
+    /// Publish or replace the merchant's endpoint for `methodId`.
+    #[napi]
+    pub async fn publish_endpoint(&self, method_id: String, endpoint: String) -> napi::Result<()> {
+        let transport = self.transport()?;
+        set_payment_endpoint(&transport, MethodId(method_id), EndpointData(endpoint))
+            .await
+            .map_err(to_js_error)
+    }
+
+    /// Remove the merchant's endpoint for `methodId`.
+    #[napi]
+    pub async fn remove_endpoint(&self, method_id: String) -> napi::Result<()> {
+        let transport = self.transport()?;
+        remove_payment_endpoint(&transport, MethodId(method_id))
+            .await
+            .map_err(to_js_error)
+    }
+
+    fn transport(&self) -> napi::Result<PubkyAuthenticatedTransport> {
+        self.session
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+            .map(PubkyAuthenticatedTransport::new)
+            .ok_or_else(|| Error::new(Status::GenericFailure, "call signIn() before publishing"))
+    }
+}
+
+fn pubky_error(err: impl std::fmt::Display) -> Error {
+    Error::new(Status::GenericFailure, err.to_string())
+}
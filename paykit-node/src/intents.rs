@@ -0,0 +1,126 @@
+//! Payment intents: signed payment request links.
+//!
+//! A merchant signs an intent for each checkout and hands the resulting
+//! `paykit://request/...` URI to the customer. Any party can verify the
+//! link without contacting the merchant's homeserver.
+
+use crate::{parse_secret_key, to_js_error, to_sats};
+use napi_derive::napi;
+use paykit_lib::uri::{PaymentRequestLink, SignedPaymentRequestLink};
+use paykit_lib::MethodId;
+
+/// What to ask the customer for.
+#[napi(object)]
+pub struct PaymentIntentOptions {
+    /// Unique ID, echoed back in the customer's receipt.
+    pub request_id: String,
+    pub amount_sats: Option<i64>,
+    /// Accepted methods in order of preference; empty accepts any.
+    pub methods: Option<Vec<String>>,
+    pub description: Option<String>,
+    /// Unix seconds after which the intent is rejected.
+    pub expires_at: Option<i64>,
+}
+
+/// A signed payment intent.
+#[napi(object)]
+pub struct PaymentIntent {
+    /// The `paykit://request/...` URI to share.
+    pub uri: String,
+    pub request_id: String,
+    /// Public key of the merchant that signed the intent.
+    pub from: String,
+    pub amount_sats: Option<i64>,
+    pub methods: Vec<String>,
+    pub description: Option<String>,
+    pub created_at: i64,
+    pub expires_at: Option<i64>,
+}
+
+impl From<&SignedPaymentRequestLink> for PaymentIntent {
+    fn from(link: &SignedPaymentRequestLink) -> Self {
+        let request = link.request();
+        Self {
+            uri: link.to_uri(),
+            request_id: request.request_id.clone(),
+            from: request.from.to_string(),
+            amount_sats: request
+                .amount_sats
+                .and_then(|sats| i64::try_from(sats).ok()),
+            methods: request.methods.iter().map(|m| m.0.clone()).collect(),
+            description: request.description.clone(),
+            created_at: request.created_at,
+            expires_at: request.expires_at,
+        }
+    }
+}
+
+/// Sign a payment intent with the merchant's hex encoded secret key.
+#[napi]
+pub fn create_payment_intent(
+    secret_key: String,
+    options: PaymentIntentOptions,
+) -> napi::Result<PaymentIntent> {
+    let keypair = parse_secret_key(&secret_key)?;
+    let mut request = PaymentRequestLink::new(options.request_id, keypair.public_key());
+    if let Some(amount_sats) = options.amount_sats {
+        request = request.with_amount_sats(to_sats(amount_sats, "amountSats")?);
+    }
+    if let Some(methods) = options.methods {
+        request = request.with_methods(methods.into_iter().map(MethodId).collect());
+    }
+    if let Some(description) = options.description {
+        request = request.with_description(description);
+    }
+    if let Some(expires_at) = options.expires_at {
+        request = request.with_expires_at(expires_at);
+    }
+
+    let link = request.sign(&keypair).map_err(to_js_error)?;
+    Ok(PaymentIntent::from(&link))
+}
+
+/// Decode and verify a payment intent URI.
+///
+/// Throws if the signature does not match or the intent has expired.
+#[napi]
+pub fn verify_payment_intent(uri: String) -> napi::Result<PaymentIntent> {
+    let link = SignedPaymentRequestLink::decode(&uri).map_err(to_js_error)?;
+    link.verify().map_err(to_js_error)?;
+    Ok(PaymentIntent::from(&link))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(expires_at: Option<i64>) -> PaymentIntentOptions {
+        PaymentIntentOptions {
+            request_id: "order-42".into(),
+            amount_sats: Some(21_000),
+            methods: Some(vec!["lightning".into()]),
+            description: Some("Coffee".into()),
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn test_payment_intent_roundtrip() {
+        let secret_key = "11".repeat(32);
+        let intent = create_payment_intent(secret_key.clone(), options(None)).unwrap();
+        let verified = verify_payment_intent(intent.uri.clone()).unwrap();
+        assert_eq!(verified.request_id, "order-42");
+        assert_eq!(verified.amount_sats, Some(21_000));
+        assert_eq!(verified.from, intent.from);
+
+        // Tampering with the payload breaks the signature
+        let (payload, signature) = intent.uri.rsplit_once('.').unwrap();
+        let other = create_payment_intent(secret_key.clone(), options(Some(1))).unwrap();
+        let (_, other_signature) = other.uri.rsplit_once('.').unwrap();
+        assert_ne!(signature, other_signature);
+        assert!(verify_payment_intent(format!("{}.{}", payload, other_signature)).is_err());
+
+        // Expired intents are rejected
+        assert!(verify_payment_intent(other.uri).is_err());
+    }
+}
@@ -0,0 +1,90 @@
+//! Paykit Node.js Bindings
+//!
+//! N-API bindings for merchants running Node backends, so the server applies
+//! the same rules as the browser demo: both are thin layers over `paykit-lib`.
+//!
+//! # Exports
+//!
+//! - [`DirectoryClient`]: discover payees' methods and publish your own.
+//! - [`select_method`]: choose the method to pay a payee with.
+//! - [`create_payment_intent`] / [`verify_payment_intent`]: signed payment
+//!   request links to hand to customers.
+//! - [`verify_payment_proof`]: check the proof attached to a receipt.
+//!
+//! Network operations are `async` and return Promises. Field names are
+//! camelCased on the JavaScript side; amounts are integers in satoshis.
+//! Errors are thrown as `Error`s whose `code` is `InvalidArg` for bad input
+//! and `GenericFailure` otherwise. Panics would take down the Node process,
+//! so `unwrap`, `expect` and `panic!` are denied outside tests.
+
+#![cfg_attr(
+    not(test),
+    deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)
+)]
+
+use napi::{Error, Status};
+use paykit_lib::PaykitError;
+
+mod directory;
+mod intents;
+mod receipts;
+mod selection;
+
+pub use directory::*;
+pub use intents::*;
+pub use receipts::*;
+pub use selection::*;
+
+/// Convert a core error into a JavaScript exception.
+pub(crate) fn to_js_error(err: PaykitError) -> Error {
+    let status = match err {
+        PaykitError::InvalidData { .. } | PaykitError::ValidationFailed(_) => Status::InvalidArg,
+        _ => Status::GenericFailure,
+    };
+    Error::new(status, err.to_string())
+}
+
+pub(crate) fn invalid_arg(message: impl Into<String>) -> Error {
+    Error::new(Status::InvalidArg, message.into())
+}
+
+/// Parse a z-base32 public key argument.
+pub(crate) fn parse_public_key(value: &str) -> napi::Result<pubky::PublicKey> {
+    value
+        .parse()
+        .map_err(|_| invalid_arg(format!("invalid public key: {}", value)))
+}
+
+/// Build a keypair from a hex encoded 32-byte secret key argument.
+pub(crate) fn parse_secret_key(value: &str) -> napi::Result<pubky::Keypair> {
+    let bytes: [u8; 32] = hex::decode(value)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| invalid_arg("secret key must be 32 bytes of hex"))?;
+    Ok(pubky::Keypair::from_secret_key(&bytes))
+}
+
+/// Convert a JavaScript integer to satoshis.
+pub(crate) fn to_sats(value: i64, name: &str) -> napi::Result<u64> {
+    u64::try_from(value).map_err(|_| invalid_arg(format!("{} must not be negative", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_argument_parsing() {
+        let keypair = parse_secret_key(&"07".repeat(32)).unwrap();
+        let public_key = keypair.public_key().to_string();
+        assert_eq!(parse_public_key(&public_key).unwrap(), keypair.public_key());
+
+        assert!(parse_secret_key("abcd").is_err());
+        assert!(parse_public_key("not-a-key").is_err());
+        assert_eq!(to_sats(1_000, "amount").unwrap(), 1_000);
+        assert_eq!(
+            to_sats(-1, "amount").unwrap_err().status,
+            Status::InvalidArg
+        );
+    }
+}
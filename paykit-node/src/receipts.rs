@@ -0,0 +1,91 @@
+//! Receipt proof verification.
+
+use crate::invalid_arg;
+use napi_derive::napi;
+use paykit_lib::methods::{preimage_matches_hash, verify_bitcoin_proof, PaymentProof};
+use serde_json::Value;
+
+/// Outcome of [`verify_payment_proof`].
+#[napi(object)]
+pub struct ProofVerification {
+    pub valid: bool,
+    pub errors: Vec<String>,
+}
+
+impl ProofVerification {
+    fn check(valid: bool, error: &str) -> Self {
+        Self {
+            valid,
+            errors: if valid {
+                Vec::new()
+            } else {
+                vec![error.to_string()]
+            },
+        }
+    }
+}
+
+/// Verify a receipt's payment proof, as stored in the receipt's `proof`
+/// field (`{"type": "LightningPreimage", ...}` or `{"type": "BitcoinTxid", ...}`).
+///
+/// Lightning proofs are checked cryptographically. On-chain proofs are only
+/// checked for a well-formed txid; confirm them against your own node or
+/// block explorer before shipping goods.
+#[napi]
+pub async fn verify_payment_proof(proof: Value) -> napi::Result<ProofVerification> {
+    let proof: PaymentProof = serde_json::from_value(proof)
+        .map_err(|e| invalid_arg(format!("invalid payment proof: {}", e)))?;
+
+    Ok(match &proof {
+        PaymentProof::LightningPreimage {
+            preimage,
+            payment_hash,
+        } => ProofVerification::check(
+            preimage_matches_hash(preimage, payment_hash),
+            "Preimage hash mismatch",
+        ),
+        PaymentProof::BitcoinTxid { .. } => {
+            // Without an executor the address and amount are not consulted
+            let valid = verify_bitcoin_proof(&proof, "", 0, None)
+                .await
+                .unwrap_or(false);
+            ProofVerification::check(valid, "Invalid or pending txid")
+        }
+        PaymentProof::Custom { method, .. } => ProofVerification::check(
+            false,
+            &format!("No verifier for custom method {}", method.0),
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_verify_payment_proof() {
+        let lightning = json!({
+            "type": "LightningPreimage",
+            "preimage": "00".repeat(32),
+            "payment_hash": "66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925",
+        });
+        assert!(verify_payment_proof(lightning).await.unwrap().valid);
+
+        let forged = json!({
+            "type": "LightningPreimage",
+            "preimage": "01".repeat(32),
+            "payment_hash": "66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925",
+        });
+        let result = verify_payment_proof(forged).await.unwrap();
+        assert!(!result.valid);
+        assert_eq!(result.errors, vec!["Preimage hash mismatch"]);
+
+        let pending = json!({"type": "BitcoinTxid", "txid": "pending"});
+        assert!(!verify_payment_proof(pending).await.unwrap().valid);
+
+        assert!(verify_payment_proof(json!({"type": "Unknown"}))
+            .await
+            .is_err());
+    }
+}
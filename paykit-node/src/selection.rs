@@ -0,0 +1,121 @@
+//! Payment method selection.
+
+use crate::{invalid_arg, to_js_error, to_sats, PaymentMethod};
+use napi_derive::napi;
+use paykit_lib::methods::Amount;
+use paykit_lib::selection::{PaymentMethodSelector, SelectionPreferences, SelectionStrategy};
+use paykit_lib::{EndpointData, MethodId, SupportedPayments};
+
+/// Selection preferences; every field is optional.
+#[napi(object)]
+#[derive(Default)]
+pub struct SelectMethodOptions {
+    /// `balanced` (default), `cost`, `speed`, `privacy` or `priority`.
+    pub strategy: Option<String>,
+    /// Preferred methods, highest first; used by the `priority` strategy.
+    pub priority_list: Option<Vec<String>>,
+    pub excluded_methods: Option<Vec<String>>,
+    pub max_fee_sats: Option<i64>,
+    pub max_confirmation_time_secs: Option<i64>,
+}
+
+/// The chosen method and what to fall back to.
+#[napi(object)]
+pub struct MethodSelection {
+    pub primary_method: String,
+    pub fallback_methods: Vec<String>,
+    pub reason: String,
+}
+
+impl TryFrom<SelectMethodOptions> for SelectionPreferences {
+    type Error = napi::Error;
+
+    fn try_from(options: SelectMethodOptions) -> napi::Result<Self> {
+        let strategy = match options.strategy.as_deref().unwrap_or("balanced") {
+            "balanced" => SelectionStrategy::Balanced,
+            "cost" => SelectionStrategy::CostOptimized,
+            "speed" => SelectionStrategy::SpeedOptimized,
+            "privacy" => SelectionStrategy::PrivacyOptimized,
+            "priority" => SelectionStrategy::PriorityList,
+            other => return Err(invalid_arg(format!("unknown strategy: {}", other))),
+        };
+        let method_ids =
+            |ids: Option<Vec<String>>| ids.unwrap_or_default().into_iter().map(MethodId).collect();
+        Ok(Self {
+            strategy,
+            priority_list: method_ids(options.priority_list),
+            excluded_methods: method_ids(options.excluded_methods),
+            max_fee_sats: options
+                .max_fee_sats
+                .map(|fee| to_sats(fee, "maxFeeSats"))
+                .transpose()?,
+            max_confirmation_time_secs: options
+                .max_confirmation_time_secs
+                .map(|secs| to_sats(secs, "maxConfirmationTimeSecs"))
+                .transpose()?,
+            ..Default::default()
+        })
+    }
+}
+
+/// Choose how to pay `amountSats` given a payee's published methods.
+#[napi]
+pub fn select_method(
+    supported: Vec<PaymentMethod>,
+    amount_sats: i64,
+    options: Option<SelectMethodOptions>,
+) -> napi::Result<MethodSelection> {
+    let supported = SupportedPayments {
+        entries: supported
+            .into_iter()
+            .map(|method| (MethodId(method.method_id), EndpointData(method.endpoint)))
+            .collect(),
+    };
+    let amount = Amount::sats(to_sats(amount_sats, "amountSats")?);
+    let preferences = SelectionPreferences::try_from(options.unwrap_or_default())?;
+
+    let result = PaymentMethodSelector::with_defaults()
+        .select(&supported, &amount, &preferences)
+        .map_err(to_js_error)?;
+    Ok(MethodSelection {
+        primary_method: result.primary.0,
+        fallback_methods: result
+            .fallbacks
+            .into_iter()
+            .map(|method| method.0)
+            .collect(),
+        reason: result.reason,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn method(method_id: &str, endpoint: &str) -> PaymentMethod {
+        PaymentMethod {
+            method_id: method_id.into(),
+            endpoint: endpoint.into(),
+        }
+    }
+
+    #[test]
+    fn test_select_method() {
+        let supported = vec![
+            method("lightning", "lnbc1..."),
+            method("onchain", "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq"),
+        ];
+        let options = SelectMethodOptions {
+            excluded_methods: Some(vec!["lightning".into()]),
+            ..Default::default()
+        };
+        let selection = select_method(supported.clone(), 50_000, Some(options)).unwrap();
+        assert_eq!(selection.primary_method, "onchain");
+
+        let options = SelectMethodOptions {
+            strategy: Some("fastest".into()),
+            ..Default::default()
+        };
+        assert!(select_method(supported, 50_000, Some(options)).is_err());
+    }
+}
@@ -0,0 +1,261 @@
+//! Subscription Assignment
+//!
+//! When a provider rotates its identity or sells a service, its
+//! subscriptions move to a new provider key. The current provider signs a
+//! [`SubscriptionAssignment`] handing a subscription to the new key, the new
+//! provider countersigns the transferred agreement, and the subscriber
+//! accepts it with [`SubscriptionManager::accept_assignment`], which
+//! re-signs the agreement and repoints its auto-pay rule in one storage
+//! update.
+//!
+//! The transferred agreement keeps the subscription ID and terms; only the
+//! provider changes, and the previous provider is recorded in its metadata
+//! under [`ASSIGNED_FROM_METADATA_KEY`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! // Old provider
+//! let assignment = SubscriptionAssignment::new(&signed.subscription, new_provider_pk)?;
+//! let mut handoff = SignedSubscriptionAssignment::sign(assignment, &old_keypair)?;
+//!
+//! // New provider
+//! handoff.countersign(&signed.subscription, &new_keypair)?;
+//!
+//! // Subscriber
+//! let transferred = manager.accept_assignment(&handoff, &subscriber_keypair).await?;
+//! ```
+//!
+//! [`SubscriptionManager::accept_assignment`]: crate::SubscriptionManager::accept_assignment
+
+use crate::signing::{self, Signature};
+use crate::{Result, Subscription, SubscriptionError};
+use paykit_lib::PublicKey;
+use serde::{Deserialize, Serialize};
+
+/// Metadata key recording the provider a subscription was assigned from.
+pub const ASSIGNED_FROM_METADATA_KEY: &str = "assigned_from";
+
+/// How long assignment signatures stay valid.
+pub const ASSIGNMENT_SIGNATURE_LIFETIME: i64 = 30 * 24 * 60 * 60;
+
+/// The current provider's handoff of a subscription to a new key.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubscriptionAssignment {
+    /// Subscription being transferred.
+    pub subscription_id: String,
+    /// Subscriber of the subscription.
+    pub subscriber: PublicKey,
+    /// Current provider (must be the signer).
+    pub from_provider: PublicKey,
+    /// Provider taking over.
+    pub to_provider: PublicKey,
+    /// Human-readable explanation, e.g. "Key rotation".
+    pub reason: Option<String>,
+    /// When the assignment was issued.
+    pub assigned_at: i64,
+}
+
+impl SubscriptionAssignment {
+    /// Create an assignment of `subscription` to `to_provider`.
+    pub fn new(subscription: &Subscription, to_provider: PublicKey) -> Result<Self> {
+        if to_provider == subscription.provider {
+            return Err(SubscriptionError::InvalidArgument(
+                "Subscription is already held by this provider".to_string(),
+            )
+            .into());
+        }
+        if to_provider == subscription.subscriber {
+            return Err(SubscriptionError::InvalidArgument(
+                "Cannot assign a subscription to its subscriber".to_string(),
+            )
+            .into());
+        }
+
+        Ok(Self {
+            subscription_id: subscription.subscription_id.clone(),
+            subscriber: subscription.subscriber.clone(),
+            from_provider: subscription.provider.clone(),
+            to_provider,
+            reason: None,
+            assigned_at: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// Add a reason.
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    /// Whether the assignment applies to `subscription` as currently held.
+    pub fn applies_to(&self, subscription: &Subscription) -> bool {
+        self.subscription_id == subscription.subscription_id
+            && self.subscriber == subscription.subscriber
+            && self.from_provider == subscription.provider
+    }
+
+    /// The agreement as it stands after the transfer.
+    ///
+    /// Deterministic, so the new provider and the subscriber sign the same
+    /// subscription.
+    pub fn transferred(&self, subscription: &Subscription) -> Subscription {
+        let mut transferred = subscription.clone();
+        transferred.provider = self.to_provider.clone();
+        if !transferred.metadata.is_object() {
+            transferred.metadata = serde_json::json!({});
+        }
+        transferred.metadata[ASSIGNED_FROM_METADATA_KEY] =
+            serde_json::Value::String(self.from_provider.to_string());
+        transferred
+    }
+}
+
+/// An assignment signed by the current provider and countersigned by the new one.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SignedSubscriptionAssignment {
+    /// The assignment.
+    pub assignment: SubscriptionAssignment,
+    /// Current provider's signature over the assignment.
+    pub signature: Signature,
+    /// New provider's signature over the transferred subscription.
+    pub new_provider_signature: Option<Signature>,
+}
+
+impl SignedSubscriptionAssignment {
+    /// Sign an assignment with the current provider's keypair.
+    pub fn sign(assignment: SubscriptionAssignment, keypair: &pubky::Keypair) -> Result<Self> {
+        if keypair.public_key() != assignment.from_provider {
+            anyhow::bail!("Assignments must be signed by the current provider");
+        }
+
+        let nonce = rand::random::<[u8; 32]>();
+        let signature = signing::sign_assignment_ed25519(
+            &assignment,
+            keypair,
+            &nonce,
+            ASSIGNMENT_SIGNATURE_LIFETIME,
+        )?;
+        Ok(Self {
+            assignment,
+            signature,
+            new_provider_signature: None,
+        })
+    }
+
+    /// Countersign the transferred agreement as the new provider.
+    pub fn countersign(
+        &mut self,
+        subscription: &Subscription,
+        keypair: &pubky::Keypair,
+    ) -> Result<()> {
+        if keypair.public_key() != self.assignment.to_provider {
+            anyhow::bail!("Only the new provider can countersign an assignment");
+        }
+        if !self.assignment.applies_to(subscription) {
+            anyhow::bail!(
+                "Assignment does not apply to subscription {}",
+                subscription.subscription_id
+            );
+        }
+
+        let nonce = rand::random::<[u8; 32]>();
+        self.new_provider_signature = Some(signing::sign_subscription_ed25519(
+            &self.assignment.transferred(subscription),
+            keypair,
+            &nonce,
+            ASSIGNMENT_SIGNATURE_LIFETIME,
+        )?);
+        Ok(())
+    }
+
+    /// Check the assignment was signed by the current provider and has not expired.
+    pub fn verify(&self) -> Result<bool> {
+        if self.signature.public_key != self.assignment.from_provider.to_bytes() {
+            return Ok(false);
+        }
+        signing::verify_assignment_ed25519(&self.assignment, &self.signature)
+    }
+
+    /// Verify both signatures against the subscription as currently held.
+    ///
+    /// Fails closed: an assignment that has not been countersigned does not
+    /// verify.
+    pub fn verify_for(&self, subscription: &Subscription) -> Result<bool> {
+        if !self.assignment.applies_to(subscription) || !self.verify()? {
+            return Ok(false);
+        }
+        let countersignature = match &self.new_provider_signature {
+            Some(signature) => signature,
+            None => return Ok(false),
+        };
+        if countersignature.public_key != self.assignment.to_provider.to_bytes() {
+            return Ok(false);
+        }
+        signing::verify_signature_ed25519(
+            &self.assignment.transferred(subscription),
+            countersignature,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Amount, PaymentFrequency, SubscriptionTerms};
+    use paykit_lib::MethodId;
+
+    fn subscription(provider: &pubky::Keypair) -> Subscription {
+        Subscription::new(
+            pkarr::Keypair::random().public_key(),
+            provider.public_key(),
+            SubscriptionTerms::new(
+                Amount::from_sats(1000),
+                "SAT".to_string(),
+                PaymentFrequency::Monthly { day_of_month: 1 },
+                MethodId("lightning".to_string()),
+                "Pro plan".to_string(),
+            ),
+        )
+    }
+
+    #[test]
+    fn test_assignment_requires_both_providers() {
+        let old_provider = pkarr::Keypair::random();
+        let new_provider = pkarr::Keypair::random();
+        let sub = subscription(&old_provider);
+
+        let assignment = SubscriptionAssignment::new(&sub, new_provider.public_key())
+            .unwrap()
+            .with_reason("Key rotation");
+        assert!(SignedSubscriptionAssignment::sign(assignment.clone(), &new_provider).is_err());
+
+        let mut signed = SignedSubscriptionAssignment::sign(assignment, &old_provider).unwrap();
+        assert!(signed.verify().unwrap());
+        // Not final until the new provider countersigns
+        assert!(!signed.verify_for(&sub).unwrap());
+        assert!(signed.countersign(&sub, &old_provider).is_err());
+
+        signed.countersign(&sub, &new_provider).unwrap();
+        assert!(signed.verify_for(&sub).unwrap());
+
+        let transferred = signed.assignment.transferred(&sub);
+        assert_eq!(transferred.provider, new_provider.public_key());
+        assert_eq!(transferred.subscription_id, sub.subscription_id);
+        assert_eq!(
+            transferred.metadata[ASSIGNED_FROM_METADATA_KEY],
+            old_provider.public_key().to_string()
+        );
+
+        // The handoff cannot be replayed against the transferred agreement
+        assert!(!signed.verify_for(&transferred).unwrap());
+    }
+
+    #[test]
+    fn test_assignment_rejects_same_provider() {
+        let provider = pkarr::Keypair::random();
+        let sub = subscription(&provider);
+        assert!(SubscriptionAssignment::new(&sub, provider.public_key()).is_err());
+        assert!(SubscriptionAssignment::new(&sub, sub.subscriber.clone()).is_err());
+    }
+}
@@ -0,0 +1,382 @@
+//! Consolidated billing for payers with several subscriptions
+//!
+//! When a provider bills the same payer for more than one subscription in a
+//! billing run, the due charges are combined into one invoice with a line
+//! item per subscription. The payer settles the invoice with a single
+//! payment, and the provider keeps a [`BillingRecord`] that allocates the
+//! total back to each subscription.
+//!
+//! The allocations also travel in the request metadata under
+//! [`BILLING_METADATA_KEY`], so the payer can check every line against its
+//! own agreements before auto-paying.
+//!
+//! The history also keeps the charges that were never paid, as
+//! [`MissedPayment`]s raised by the expectations monitor (see
+//! [`crate::expectations`]).
+
+use crate::expectations::{Escalation, MissedPayment};
+use crate::{Amount, InvoiceItem, PaymentRequest, Result, SignedSubscription};
+use paykit_lib::{MethodId, PublicKey};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Metadata key holding the allocations of a consolidated request.
+pub const BILLING_METADATA_KEY: &str = "billing";
+
+/// Invoice item category used for subscription line items.
+pub const SUBSCRIPTION_ITEM_CATEGORY: &str = "subscription";
+
+/// Share of a consolidated payment attributed to one subscription.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BillingAllocation {
+    /// Subscription being charged.
+    pub subscription_id: String,
+    /// Amount charged for this subscription.
+    pub amount: Amount,
+    /// Line item description.
+    pub description: String,
+}
+
+/// Record of one consolidated invoice and how its total is allocated.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BillingRecord {
+    /// Invoice number sent to the payer.
+    pub invoice_number: String,
+    /// Payment request carrying the invoice.
+    pub request_id: String,
+    /// Provider issuing the invoice.
+    pub provider: PublicKey,
+    /// Payer being billed.
+    pub payer: PublicKey,
+    /// Currency of every allocation.
+    pub currency: String,
+    /// Payment method of every allocation.
+    pub method: MethodId,
+    /// Total billed (sum of allocations).
+    pub total: Amount,
+    /// Per-subscription allocations, in line item order.
+    pub allocations: Vec<BillingAllocation>,
+    /// When the invoice was issued.
+    pub billed_at: i64,
+}
+
+impl BillingRecord {
+    /// Get the allocation for a subscription, if it is on this invoice.
+    pub fn allocation_for(&self, subscription_id: &str) -> Option<&BillingAllocation> {
+        self.allocations
+            .iter()
+            .find(|a| a.subscription_id == subscription_id)
+    }
+
+    /// IDs of the subscriptions billed on this invoice.
+    pub fn subscription_ids(&self) -> Vec<&str> {
+        self.allocations
+            .iter()
+            .map(|a| a.subscription_id.as_str())
+            .collect()
+    }
+}
+
+/// Consolidated invoices issued by a provider, and missed charges.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct BillingHistory {
+    /// Records in chronological order.
+    pub records: Vec<BillingRecord>,
+    /// Missed charges, ordered by due date.
+    #[serde(default)]
+    pub missed: Vec<MissedPayment>,
+}
+
+impl BillingHistory {
+    /// Create a new empty history.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a record, keeping the history in chronological order.
+    pub fn record(&mut self, record: BillingRecord) {
+        let index = self
+            .records
+            .partition_point(|r| r.billed_at <= record.billed_at);
+        self.records.insert(index, record);
+    }
+
+    /// Get the most recent record.
+    pub fn latest(&self) -> Option<&BillingRecord> {
+        self.records.last()
+    }
+
+    /// Get the records that billed a subscription.
+    pub fn for_subscription(&self, subscription_id: &str) -> Vec<&BillingRecord> {
+        self.records
+            .iter()
+            .filter(|r| r.allocation_for(subscription_id).is_some())
+            .collect()
+    }
+
+    /// Get the records issued to a payer.
+    pub fn for_payer(&self, payer: &PublicKey) -> Vec<&BillingRecord> {
+        self.records.iter().filter(|r| &r.payer == payer).collect()
+    }
+
+    /// Add or update a missed charge, keeping misses ordered by due date.
+    pub fn record_missed(&mut self, missed: MissedPayment) {
+        self.missed
+            .retain(|m| m.subscription_id != missed.subscription_id || m.due_at != missed.due_at);
+        let index = self.missed.partition_point(|m| m.due_at <= missed.due_at);
+        self.missed.insert(index, missed);
+    }
+
+    /// Get a subscription's missed charges that are still unpaid.
+    pub fn unresolved_misses(&self, subscription_id: &str) -> Vec<&MissedPayment> {
+        self.missed
+            .iter()
+            .filter(|m| m.subscription_id == subscription_id && !m.is_resolved())
+            .collect()
+    }
+
+    /// Whether a subscription has an unpaid charge that marked it delinquent.
+    pub fn is_delinquent(&self, subscription_id: &str) -> bool {
+        self.unresolved_misses(subscription_id)
+            .iter()
+            .any(|m| m.escalation == Escalation::MarkDelinquent)
+    }
+
+    /// IDs of delinquent subscriptions.
+    pub fn delinquent_subscriptions(&self) -> Vec<&str> {
+        let mut ids: Vec<&str> = self
+            .missed
+            .iter()
+            .filter(|m| !m.is_resolved() && m.escalation == Escalation::MarkDelinquent)
+            .map(|m| m.subscription_id.as_str())
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+}
+
+/// A consolidated payment request and its billing record.
+#[derive(Clone, Debug)]
+pub struct ConsolidatedInvoice {
+    /// Request to send to the payer.
+    pub request: PaymentRequest,
+    /// Allocation record to keep on the provider side.
+    pub record: BillingRecord,
+}
+
+/// Group due subscriptions into charges that can share one payment.
+///
+/// Subscriptions are grouped by provider, payer, currency and method, since
+/// a single payment cannot mix currencies or methods. Groups are returned in
+/// a stable order.
+pub fn group_due_charges(due: Vec<SignedSubscription>) -> Vec<Vec<SignedSubscription>> {
+    let mut groups: BTreeMap<(String, String, String, String), Vec<SignedSubscription>> =
+        BTreeMap::new();
+    for signed in due {
+        let sub = &signed.subscription;
+        let key = (
+            sub.provider.to_string(),
+            sub.subscriber.to_string(),
+            sub.terms.currency.clone(),
+            sub.terms.method.0.clone(),
+        );
+        groups.entry(key).or_default().push(signed);
+    }
+    groups.into_values().collect()
+}
+
+/// Build one invoice covering every charge in a group.
+///
+/// `sequence` distinguishes invoices issued in the same billing run.
+/// Fails if the group is empty or mixes payers, currencies or methods.
+pub fn consolidate(
+    group: &[SignedSubscription],
+    now: i64,
+    sequence: usize,
+) -> Result<ConsolidatedInvoice> {
+    let first = match group.first() {
+        Some(first) => &first.subscription,
+        None => anyhow::bail!("Cannot consolidate an empty group of charges"),
+    };
+
+    let mut items = Vec::with_capacity(group.len());
+    let mut allocations = Vec::with_capacity(group.len());
+    let mut total = Amount::zero();
+    for signed in group {
+        let sub = &signed.subscription;
+        if sub.provider != first.provider
+            || sub.subscriber != first.subscriber
+            || sub.terms.currency != first.terms.currency
+            || sub.terms.method != first.terms.method
+        {
+            anyhow::bail!(
+                "Subscription {} cannot share an invoice with {}",
+                sub.subscription_id,
+                first.subscription_id
+            );
+        }
+
+        items.push(
+            InvoiceItem::new(sub.terms.description.clone(), 1, sub.terms.amount)
+                .with_item_id(sub.subscription_id.clone())
+                .with_category(SUBSCRIPTION_ITEM_CATEGORY),
+        );
+        allocations.push(BillingAllocation {
+            subscription_id: sub.subscription_id.clone(),
+            amount: sub.terms.amount,
+            description: sub.terms.description.clone(),
+        });
+        total = total.add(&sub.terms.amount);
+    }
+
+    let invoice_number = format!("INV-{}-{}", now, sequence);
+    let mut request = PaymentRequest::new(
+        first.provider.clone(),
+        first.subscriber.clone(),
+        total,
+        first.terms.currency.clone(),
+        first.terms.method.clone(),
+    )
+    .with_description(format!("Subscription payments ({})", group.len()))
+    .with_invoice_number(invoice_number.clone())
+    .with_items(items);
+    request.request_id = format!("req_{}_{}", now, sequence);
+    request.created_at = now;
+    request.metadata = serde_json::json!({
+        BILLING_METADATA_KEY: { "invoice_number": invoice_number, "allocations": allocations },
+    });
+
+    let record = BillingRecord {
+        invoice_number,
+        request_id: request.request_id.clone(),
+        provider: first.provider.clone(),
+        payer: first.subscriber.clone(),
+        currency: first.terms.currency.clone(),
+        method: first.terms.method.clone(),
+        total,
+        allocations,
+        billed_at: now,
+    };
+
+    Ok(ConsolidatedInvoice { request, record })
+}
+
+/// Read the allocations of a consolidated request.
+///
+/// Returns `None` for requests that bill a single subscription.
+pub fn allocations_from_request(request: &PaymentRequest) -> Option<Vec<BillingAllocation>> {
+    let allocations = request
+        .metadata
+        .get(BILLING_METADATA_KEY)?
+        .get("allocations")?
+        .clone();
+    serde_json::from_value(allocations).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{signing, PaymentFrequency, Subscription, SubscriptionTerms};
+    use std::str::FromStr;
+
+    fn test_pubkey() -> PublicKey {
+        let keypair = pkarr::Keypair::random();
+        PublicKey::from_str(&keypair.public_key().to_z32()).unwrap()
+    }
+
+    fn signed(
+        payer: &PublicKey,
+        provider: &PublicKey,
+        sats: i64,
+        currency: &str,
+    ) -> SignedSubscription {
+        let terms = SubscriptionTerms::new(
+            Amount::from_sats(sats),
+            currency.to_string(),
+            PaymentFrequency::Monthly { day_of_month: 1 },
+            MethodId("lightning".to_string()),
+            format!("Plan {}", sats),
+        );
+        let mut subscription = Subscription::new(payer.clone(), provider.clone(), terms);
+        subscription.subscription_id = format!("sub_{}_{}", currency, sats);
+        let keypair = pkarr::Keypair::random();
+        let sig1 =
+            signing::sign_subscription_ed25519(&subscription, &keypair, &rand::random(), 3600)
+                .unwrap();
+        let sig2 =
+            signing::sign_subscription_ed25519(&subscription, &keypair, &rand::random(), 3600)
+                .unwrap();
+        SignedSubscription::new(subscription, sig1, sig2)
+    }
+
+    #[test]
+    fn test_charges_grouped_per_payer_and_currency() {
+        let provider = test_pubkey();
+        let alice = test_pubkey();
+        let bob = test_pubkey();
+
+        let groups = group_due_charges(vec![
+            signed(&alice, &provider, 1000, "SAT"),
+            signed(&bob, &provider, 500, "SAT"),
+            signed(&alice, &provider, 2000, "SAT"),
+            signed(&alice, &provider, 300, "USD"),
+        ]);
+
+        assert_eq!(groups.len(), 3);
+        let alice_sats = groups
+            .iter()
+            .find(|g| {
+                g[0].subscription.subscriber == alice && g[0].subscription.terms.currency == "SAT"
+            })
+            .unwrap();
+        assert_eq!(alice_sats.len(), 2);
+    }
+
+    #[test]
+    fn test_consolidated_invoice_allocates_each_subscription() {
+        let provider = test_pubkey();
+        let payer = test_pubkey();
+        let group = vec![
+            signed(&payer, &provider, 1000, "SAT"),
+            signed(&payer, &provider, 2500, "SAT"),
+        ];
+
+        let consolidated = consolidate(&group, 1_700_000_000, 0).unwrap();
+        let request = &consolidated.request;
+        assert_eq!(request.amount, Amount::from_sats(3500));
+        assert_eq!(request.items.len(), 2);
+        assert_eq!(request.to_invoice().total, Amount::from_sats(3500));
+        assert_eq!(request.from, provider);
+        assert_eq!(request.to, payer);
+
+        let record = &consolidated.record;
+        assert_eq!(record.request_id, request.request_id);
+        let first_id = &group[0].subscription.subscription_id;
+        assert_eq!(
+            record.allocation_for(first_id).unwrap().amount,
+            Amount::from_sats(1000)
+        );
+        assert_eq!(
+            allocations_from_request(request).unwrap(),
+            record.allocations
+        );
+
+        let mut history = BillingHistory::new();
+        history.record(consolidated.record.clone());
+        assert_eq!(history.for_subscription(first_id).len(), 1);
+        assert_eq!(history.for_payer(&payer).len(), 1);
+        assert!(history.for_payer(&provider).is_empty());
+    }
+
+    #[test]
+    fn test_consolidate_rejects_mixed_payers() {
+        let provider = test_pubkey();
+        let group = vec![
+            signed(&test_pubkey(), &provider, 1000, "SAT"),
+            signed(&test_pubkey(), &provider, 1000, "SAT"),
+        ];
+        assert!(consolidate(&group, 1_700_000_000, 0).is_err());
+        assert!(consolidate(&[], 1_700_000_000, 0).is_err());
+    }
+}
@@ -0,0 +1,446 @@
+//! Upcoming Charges Calendar
+//!
+//! Projects stored agreements forward to show what will be charged over the
+//! next N days, for budgeting views. Amounts reflect scheduled upgrades,
+//! downgrades, pauses and cancellations as of each due date, and each charge
+//! records whether its auto-pay rule will pay it unattended.
+//!
+//! The calendar covers whole UTC days starting with the day containing the
+//! reference time. Monthly and yearly charges fall at midnight UTC on their
+//! billing day, clamped to the last day of shorter months; daily, weekly and
+//! custom intervals step from the subscription's start.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut calendar = ChargeCalendar::new(chrono::Utc::now().timestamp(), 30)?;
+//! calendar.add_subscription(&signed.subscription, rule.as_ref(), &modifications);
+//!
+//! for (currency, total) in calendar.totals_by_currency() {
+//!     println!("{}: {}", currency, total);
+//! }
+//! ```
+
+use crate::{
+    Amount, AutoPayRule, ModificationRequest, ModificationType, PaymentFrequency, Result,
+    Subscription, SubscriptionError,
+};
+use chrono::{Datelike, NaiveDate};
+use paykit_lib::{MethodId, PublicKey};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Longest window a calendar can cover.
+pub const MAX_CALENDAR_DAYS: u32 = 366;
+
+/// Cap on charges projected for one subscription, so short custom intervals
+/// cannot blow up the calendar.
+pub const MAX_CHARGES_PER_SUBSCRIPTION: usize = 1000;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// A single projected charge.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UpcomingCharge {
+    /// Subscription the charge belongs to.
+    pub subscription_id: String,
+    /// Provider being paid.
+    pub provider: PublicKey,
+    /// Subscriber paying.
+    pub subscriber: PublicKey,
+    /// When the charge falls due (unix timestamp).
+    pub due_at: i64,
+    /// Amount due, after scheduled modifications.
+    pub amount: Amount,
+    /// Currency of the amount.
+    pub currency: String,
+    /// Payment method that will be used.
+    pub method: MethodId,
+    /// Subscription description.
+    pub description: String,
+    /// Whether an enabled auto-pay rule will pay this without confirmation.
+    pub autopay: bool,
+    /// Whether a scheduled modification changed the amount or method.
+    pub modified: bool,
+}
+
+/// Upcoming charges over a window of whole days.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChargeCalendar {
+    /// Start of the window (midnight UTC, inclusive).
+    pub from: i64,
+    /// End of the window (exclusive).
+    pub until: i64,
+    /// Charges ordered by due date.
+    pub charges: Vec<UpcomingCharge>,
+}
+
+impl ChargeCalendar {
+    /// Create an empty calendar covering `days` days from the day containing `from`.
+    pub fn new(from: i64, days: u32) -> Result<Self> {
+        if days == 0 || days > MAX_CALENDAR_DAYS {
+            return Err(SubscriptionError::InvalidArgument(format!(
+                "Calendar must cover 1 to {} days",
+                MAX_CALENDAR_DAYS
+            ))
+            .into());
+        }
+
+        let from = from - from.rem_euclid(SECONDS_PER_DAY);
+        Ok(Self {
+            from,
+            until: from + i64::from(days) * SECONDS_PER_DAY,
+            charges: Vec::new(),
+        })
+    }
+
+    /// Project a subscription's charges into the calendar.
+    ///
+    /// Modifications for other subscriptions are ignored. Undated changes
+    /// (method, billing date, frequency) apply to every projected charge;
+    /// amount changes and cancellations apply from their effective date.
+    pub fn add_subscription(
+        &mut self,
+        subscription: &Subscription,
+        rule: Option<&AutoPayRule>,
+        modifications: &[ModificationRequest],
+    ) {
+        let schedule = Schedule::new(subscription, modifications);
+
+        for due_at in schedule
+            .due_dates(self.from, self.until)
+            .into_iter()
+            .take(MAX_CHARGES_PER_SUBSCRIPTION)
+        {
+            let amount = schedule.amount_at(due_at);
+            let autopay = rule.is_some_and(|rule| {
+                rule.enabled
+                    && !rule.require_confirmation
+                    && rule
+                        .max_amount_per_payment
+                        .is_none_or(|max| amount.is_within_limit(&max))
+            });
+
+            self.charges.push(UpcomingCharge {
+                subscription_id: subscription.subscription_id.clone(),
+                provider: subscription.provider.clone(),
+                subscriber: subscription.subscriber.clone(),
+                due_at,
+                amount,
+                currency: subscription.terms.currency.clone(),
+                method: schedule.method.clone(),
+                description: subscription.terms.description.clone(),
+                autopay,
+                modified: amount != subscription.terms.amount
+                    || schedule.method != subscription.terms.method,
+            });
+        }
+
+        self.charges
+            .sort_by(|a, b| (a.due_at, &a.subscription_id).cmp(&(b.due_at, &b.subscription_id)));
+    }
+
+    /// Total due per currency over the window.
+    pub fn totals_by_currency(&self) -> BTreeMap<String, Amount> {
+        let mut totals: BTreeMap<String, Amount> = BTreeMap::new();
+        for charge in &self.charges {
+            let total = totals
+                .entry(charge.currency.clone())
+                .or_insert_with(Amount::zero);
+            *total = total.saturating_add(&charge.amount);
+        }
+        totals
+    }
+
+    /// Charges grouped by UTC day.
+    pub fn by_day(&self) -> BTreeMap<NaiveDate, Vec<&UpcomingCharge>> {
+        let mut days: BTreeMap<NaiveDate, Vec<&UpcomingCharge>> = BTreeMap::new();
+        for charge in &self.charges {
+            if let Some(date) = chrono::DateTime::from_timestamp(charge.due_at, 0) {
+                days.entry(date.date_naive()).or_default().push(charge);
+            }
+        }
+        days
+    }
+
+    /// Whether no charges fall in the window.
+    pub fn is_empty(&self) -> bool {
+        self.charges.is_empty()
+    }
+}
+
+/// A subscription's billing schedule with its modifications folded in.
+struct Schedule {
+    starts_at: i64,
+    ends_at: Option<i64>,
+    frequency: PaymentFrequency,
+    method: MethodId,
+    amount: Amount,
+    /// Amount changes as (effective date, new amount), in date order.
+    amount_changes: Vec<(i64, Amount)>,
+    /// Paused ranges as [start, end).
+    pauses: Vec<(i64, i64)>,
+}
+
+impl Schedule {
+    fn new(subscription: &Subscription, modifications: &[ModificationRequest]) -> Self {
+        let mut schedule = Self {
+            starts_at: subscription.starts_at,
+            ends_at: subscription.ends_at,
+            frequency: subscription.terms.frequency.clone(),
+            method: subscription.terms.method.clone(),
+            amount: subscription.terms.amount,
+            amount_changes: Vec::new(),
+            pauses: Vec::new(),
+        };
+
+        if let Some(resume) = subscription
+            .metadata
+            .get("paused_until")
+            .and_then(|v| v.as_i64())
+        {
+            schedule.pauses.push((i64::MIN, resume));
+        }
+
+        let mut modifications: Vec<&ModificationRequest> = modifications
+            .iter()
+            .filter(|m| m.subscription_id == subscription.subscription_id)
+            .collect();
+        modifications.sort_by_key(|m| m.created_at);
+
+        for modification in modifications {
+            match &modification.modification_type {
+                ModificationType::Upgrade {
+                    new_amount,
+                    effective_date,
+                }
+                | ModificationType::Downgrade {
+                    new_amount,
+                    effective_date,
+                } => schedule.amount_changes.push((*effective_date, *new_amount)),
+                ModificationType::ChangeMethod { new_method } => {
+                    schedule.method = new_method.clone();
+                }
+                ModificationType::ChangeBillingDate { new_day } => {
+                    if let PaymentFrequency::Monthly { .. } = schedule.frequency {
+                        schedule.frequency = PaymentFrequency::Monthly {
+                            day_of_month: *new_day,
+                        };
+                    }
+                }
+                ModificationType::ChangeFrequency { new_frequency } => {
+                    schedule.frequency = new_frequency.clone();
+                }
+                ModificationType::Cancel { effective_date, .. } => {
+                    schedule.ends_at = Some(
+                        schedule
+                            .ends_at
+                            .map_or(*effective_date, |end| end.min(*effective_date)),
+                    );
+                }
+                ModificationType::Pause { resume_date } => {
+                    schedule
+                        .pauses
+                        .push((modification.created_at, *resume_date));
+                }
+                ModificationType::Resume => {
+                    for pause in &mut schedule.pauses {
+                        if pause.0 <= modification.created_at && modification.created_at < pause.1 {
+                            pause.1 = modification.created_at;
+                        }
+                    }
+                }
+            }
+        }
+
+        // Stable sort keeps the later request when two share a date
+        schedule.amount_changes.sort_by_key(|(at, _)| *at);
+        schedule
+    }
+
+    fn amount_at(&self, due_at: i64) -> Amount {
+        self.amount_changes
+            .iter()
+            .take_while(|(at, _)| *at <= due_at)
+            .last()
+            .map_or(self.amount, |(_, amount)| *amount)
+    }
+
+    fn is_billable(&self, at: i64) -> bool {
+        at >= self.starts_at
+            && self.ends_at.is_none_or(|end| at < end)
+            && !self
+                .pauses
+                .iter()
+                .any(|(start, end)| *start <= at && at < *end)
+    }
+
+    fn due_dates(&self, from: i64, until: i64) -> Vec<i64> {
+        let candidates: Vec<i64> = match self.frequency {
+            PaymentFrequency::Monthly { day_of_month } => month_starts(from, until)
+                .filter_map(|(year, month)| billing_date(year, month, day_of_month))
+                .collect(),
+            PaymentFrequency::Yearly { month, day } => year_range(from, until)
+                .filter_map(|year| billing_date(year, u32::from(month), day))
+                .collect(),
+            ref frequency => {
+                let interval = frequency.to_seconds() as i64;
+                if interval <= 0 {
+                    return Vec::new();
+                }
+                let mut next = self.starts_at;
+                if next < from {
+                    next += (from - next + interval - 1) / interval * interval;
+                }
+                std::iter::successors(Some(next), |at| at.checked_add(interval))
+                    .take_while(|at| *at < until)
+                    .take(MAX_CHARGES_PER_SUBSCRIPTION)
+                    .collect()
+            }
+        };
+
+        candidates
+            .into_iter()
+            .filter(|at| *at >= from && *at < until && self.is_billable(*at))
+            .collect()
+    }
+}
+
+/// First billing date after `after` for a `frequency` schedule starting at `starts_at`.
+pub(crate) fn next_billing_date(
+    frequency: &PaymentFrequency,
+    starts_at: i64,
+    after: i64,
+) -> Option<i64> {
+    let schedule = Schedule {
+        starts_at,
+        ends_at: None,
+        frequency: frequency.clone(),
+        method: MethodId(String::new()),
+        amount: Amount::zero(),
+        amount_changes: Vec::new(),
+        pauses: Vec::new(),
+    };
+    // A year and a day covers every calendar frequency
+    let window = (frequency.to_seconds() as i64).max(367 * SECONDS_PER_DAY) + 1;
+    let from = after.saturating_add(1);
+    schedule
+        .due_dates(from, from.saturating_add(window))
+        .into_iter()
+        .next()
+}
+
+fn date_of(timestamp: i64) -> NaiveDate {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .date_naive()
+}
+
+/// Every (year, month) touched by the window.
+fn month_starts(from: i64, until: i64) -> impl Iterator<Item = (i32, u32)> {
+    let first = date_of(from);
+    let last = date_of(until);
+    let months = (last.year() - first.year()) * 12 + last.month() as i32 - first.month() as i32;
+    (0..=months).map(move |offset| {
+        let index = first.month0() as i32 + offset;
+        (
+            first.year() + index.div_euclid(12),
+            index.rem_euclid(12) as u32 + 1,
+        )
+    })
+}
+
+fn year_range(from: i64, until: i64) -> std::ops::RangeInclusive<i32> {
+    date_of(from).year()..=date_of(until).year()
+}
+
+/// Midnight UTC on `day` of the month, clamped to the month's last day.
+fn billing_date(year: i32, month: u32, day: u8) -> Option<i64> {
+    (1..=u32::from(day.max(1)).min(31))
+        .rev()
+        .find_map(|d| NaiveDate::from_ymd_opt(year, month, d))
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|datetime| datetime.and_utc().timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SubscriptionTerms;
+
+    fn ts(year: i32, month: u32, day: u32) -> i64 {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc()
+            .timestamp()
+    }
+
+    fn subscription(frequency: PaymentFrequency, starts_at: i64) -> Subscription {
+        let mut sub = Subscription::new(
+            pkarr::Keypair::random().public_key(),
+            pkarr::Keypair::random().public_key(),
+            SubscriptionTerms::new(
+                Amount::from_sats(1000),
+                "SAT".to_string(),
+                frequency,
+                MethodId("lightning".to_string()),
+                "Pro plan".to_string(),
+            ),
+        );
+        sub.starts_at = starts_at;
+        sub
+    }
+
+    #[test]
+    fn test_monthly_charges_clamp_to_month_end() {
+        let sub = subscription(
+            PaymentFrequency::Monthly { day_of_month: 31 },
+            ts(2025, 1, 1),
+        );
+        let mut calendar = ChargeCalendar::new(ts(2025, 1, 15) + 3600, 60).unwrap();
+        calendar.add_subscription(&sub, None, &[]);
+
+        let due: Vec<i64> = calendar.charges.iter().map(|c| c.due_at).collect();
+        assert_eq!(due, vec![ts(2025, 1, 31), ts(2025, 2, 28)]);
+        assert!(calendar.charges.iter().all(|c| !c.autopay && !c.modified));
+    }
+
+    #[test]
+    fn test_scheduled_modifications_change_amounts() {
+        let sub = subscription(PaymentFrequency::Weekly, ts(2025, 3, 3));
+        let upgrade = ModificationRequest::upgrade(&sub, Amount::from_sats(1500), ts(2025, 3, 12));
+        let cancel = ModificationRequest::cancel(&sub, ts(2025, 3, 24), None);
+        let rule = AutoPayRule::new(
+            sub.subscription_id.clone(),
+            sub.provider.clone(),
+            MethodId("lightning".to_string()),
+        )
+        .with_max_payment_amount(Amount::from_sats(1200));
+
+        let mut calendar = ChargeCalendar::new(ts(2025, 3, 1), 31).unwrap();
+        calendar.add_subscription(&sub, Some(&rule), &[upgrade, cancel]);
+
+        let charges: Vec<(i64, i64, bool)> = calendar
+            .charges
+            .iter()
+            .map(|c| (c.due_at, c.amount.as_sats(), c.autopay))
+            .collect();
+        assert_eq!(
+            charges,
+            vec![
+                (ts(2025, 3, 3), 1000, true),
+                (ts(2025, 3, 10), 1000, true),
+                // Above the rule's per-payment cap
+                (ts(2025, 3, 17), 1500, false),
+            ]
+        );
+        assert!(calendar.charges[2].modified);
+        assert_eq!(
+            calendar.totals_by_currency()["SAT"],
+            Amount::from_sats(3500)
+        );
+        assert!(ChargeCalendar::new(0, 0).is_err());
+    }
+}
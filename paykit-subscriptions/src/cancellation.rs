@@ -0,0 +1,429 @@
+//! Subscription Cancellation Protocol
+//!
+//! Either party can end a subscription by sending a signed
+//! [`CancelSubscription`] over Noise. The counterparty verifies it against
+//! its copy of the agreement and answers with a signed
+//! [`CancellationAck`] bound to the cancellation's nonce. Both sides keep a
+//! [`CancellationRecord`]; once the ack is attached the cancellation is
+//! final and the subscription no longer shows up as active.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use paykit_subscriptions::cancellation::CancellationReason;
+//!
+//! // Either party
+//! let record = manager
+//!     .cancel_subscription(&mut channel, &sub_id, &keypair, CancellationReason::TooExpensive, None, true)
+//!     .await?;
+//! assert!(record.is_acknowledged());
+//!
+//! // Counterparty, on PaykitNoiseMessage::CancelSubscription { cancellation }
+//! manager.handle_cancellation(&mut channel, cancellation, &keypair, false).await?;
+//! ```
+
+use crate::signing::{self, Signature};
+use crate::{Result, Subscription, SubscriptionError};
+use paykit_lib::PublicKey;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// How long cancellation and acknowledgment signatures stay valid.
+pub const CANCELLATION_SIGNATURE_LIFETIME: i64 = 7 * 24 * 60 * 60;
+
+/// Why a subscription was cancelled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CancellationReason {
+    /// The subscriber no longer needs the service.
+    NoLongerNeeded,
+    /// The price is too high.
+    TooExpensive,
+    /// Moving to another provider.
+    SwitchingProvider,
+    /// Payments keep failing.
+    PaymentIssue,
+    /// The service did not meet expectations.
+    ServiceIssue,
+    /// The other party broke the terms.
+    TermsViolation,
+    /// Anything else; see the note.
+    Other,
+}
+
+impl CancellationReason {
+    /// Every reason code, in display order.
+    pub const ALL: [CancellationReason; 7] = [
+        CancellationReason::NoLongerNeeded,
+        CancellationReason::TooExpensive,
+        CancellationReason::SwitchingProvider,
+        CancellationReason::PaymentIssue,
+        CancellationReason::ServiceIssue,
+        CancellationReason::TermsViolation,
+        CancellationReason::Other,
+    ];
+
+    /// Wire name of the reason code.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CancellationReason::NoLongerNeeded => "no_longer_needed",
+            CancellationReason::TooExpensive => "too_expensive",
+            CancellationReason::SwitchingProvider => "switching_provider",
+            CancellationReason::PaymentIssue => "payment_issue",
+            CancellationReason::ServiceIssue => "service_issue",
+            CancellationReason::TermsViolation => "terms_violation",
+            CancellationReason::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for CancellationReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CancellationReason {
+    type Err = SubscriptionError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let normalized = s.trim().to_lowercase().replace('-', "_");
+        Self::ALL
+            .into_iter()
+            .find(|reason| reason.as_str() == normalized)
+            .ok_or_else(|| {
+                SubscriptionError::InvalidArgument(format!("Unknown cancellation reason: {}", s))
+            })
+    }
+}
+
+/// A party's request to end a subscription.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CancelSubscription {
+    /// Subscription being cancelled.
+    pub subscription_id: String,
+    /// Party cancelling (must be the signer).
+    pub cancelled_by: PublicKey,
+    /// Party expected to acknowledge.
+    pub counterparty: PublicKey,
+    /// Reason code.
+    pub reason: CancellationReason,
+    /// Free-form explanation.
+    pub note: Option<String>,
+    /// When the cancellation was issued.
+    pub cancelled_at: i64,
+}
+
+impl CancelSubscription {
+    /// Create a cancellation of `subscription` by one of its parties.
+    pub fn new(
+        subscription: &Subscription,
+        cancelled_by: &PublicKey,
+        reason: CancellationReason,
+    ) -> Result<Self> {
+        let counterparty = counterparty_of(subscription, cancelled_by).ok_or_else(|| {
+            SubscriptionError::InvalidArgument(format!(
+                "{} is not a party to subscription {}",
+                cancelled_by, subscription.subscription_id
+            ))
+        })?;
+
+        Ok(Self {
+            subscription_id: subscription.subscription_id.clone(),
+            cancelled_by: cancelled_by.clone(),
+            counterparty,
+            reason,
+            note: None,
+            cancelled_at: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// Add a note.
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+}
+
+/// A cancellation signed by the cancelling party.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SignedCancellation {
+    /// The cancellation.
+    pub cancellation: CancelSubscription,
+    /// Canceller's signature over the cancellation.
+    pub signature: Signature,
+}
+
+impl SignedCancellation {
+    /// Sign a cancellation with the cancelling party's keypair.
+    pub fn sign(cancellation: CancelSubscription, keypair: &pubky::Keypair) -> Result<Self> {
+        if keypair.public_key() != cancellation.cancelled_by {
+            anyhow::bail!("Cancellations must be signed by the cancelling party");
+        }
+
+        let nonce = rand::random::<[u8; 32]>();
+        let signature = signing::sign_cancellation_ed25519(
+            &cancellation,
+            keypair,
+            &nonce,
+            CANCELLATION_SIGNATURE_LIFETIME,
+        )?;
+        Ok(Self {
+            cancellation,
+            signature,
+        })
+    }
+
+    /// Sign a cancellation with the cancelling party's raw Ed25519 secret key.
+    pub fn sign_with_secret_key(
+        cancellation: CancelSubscription,
+        secret_key: &[u8; 32],
+    ) -> Result<Self> {
+        Self::sign(cancellation, &pubky::Keypair::from_secret_key(secret_key))
+    }
+
+    /// Check the cancellation was signed by its canceller and has not expired.
+    pub fn verify(&self) -> Result<bool> {
+        if self.signature.public_key != self.cancellation.cancelled_by.to_bytes() {
+            return Ok(false);
+        }
+        signing::verify_cancellation_ed25519(&self.cancellation, &self.signature)
+    }
+
+    /// Verify the cancellation and check it is between the parties of `subscription`.
+    pub fn verify_for(&self, subscription: &Subscription) -> Result<bool> {
+        let cancellation = &self.cancellation;
+        if cancellation.subscription_id != subscription.subscription_id
+            || counterparty_of(subscription, &cancellation.cancelled_by).as_ref()
+                != Some(&cancellation.counterparty)
+        {
+            return Ok(false);
+        }
+        self.verify()
+    }
+
+    /// Acknowledge the cancellation as its counterparty.
+    pub fn acknowledge(&self, keypair: &pubky::Keypair) -> Result<SignedCancellationAck> {
+        if keypair.public_key() != self.cancellation.counterparty {
+            anyhow::bail!("Only the counterparty can acknowledge a cancellation");
+        }
+
+        let ack = CancellationAck {
+            subscription_id: self.cancellation.subscription_id.clone(),
+            acknowledged_by: self.cancellation.counterparty.clone(),
+            cancellation_nonce: self.signature.nonce,
+            acknowledged_at: chrono::Utc::now().timestamp(),
+        };
+        let nonce = rand::random::<[u8; 32]>();
+        let signature = signing::sign_cancellation_ack_ed25519(
+            &ack,
+            keypair,
+            &nonce,
+            CANCELLATION_SIGNATURE_LIFETIME,
+        )?;
+        Ok(SignedCancellationAck { ack, signature })
+    }
+
+    /// Acknowledge the cancellation with the counterparty's raw Ed25519 secret key.
+    pub fn acknowledge_with_secret_key(
+        &self,
+        secret_key: &[u8; 32],
+    ) -> Result<SignedCancellationAck> {
+        self.acknowledge(&pubky::Keypair::from_secret_key(secret_key))
+    }
+}
+
+/// The counterparty's confirmation that it received a cancellation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CancellationAck {
+    /// Subscription that was cancelled.
+    pub subscription_id: String,
+    /// Counterparty acknowledging (must be the signer).
+    pub acknowledged_by: PublicKey,
+    /// Nonce of the cancellation signature being acknowledged.
+    pub cancellation_nonce: [u8; 32],
+    /// When the cancellation was acknowledged.
+    pub acknowledged_at: i64,
+}
+
+/// A cancellation acknowledgment signed by the counterparty.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SignedCancellationAck {
+    /// The acknowledgment.
+    pub ack: CancellationAck,
+    /// Counterparty's signature over the acknowledgment.
+    pub signature: Signature,
+}
+
+impl SignedCancellationAck {
+    /// Verify the acknowledgment answers `cancellation` and was signed by its counterparty.
+    pub fn verify_for(&self, cancellation: &SignedCancellation) -> Result<bool> {
+        let ack = &self.ack;
+        if ack.subscription_id != cancellation.cancellation.subscription_id
+            || ack.acknowledged_by != cancellation.cancellation.counterparty
+            || ack.cancellation_nonce != cancellation.signature.nonce
+            || self.signature.public_key != ack.acknowledged_by.to_bytes()
+        {
+            return Ok(false);
+        }
+        signing::verify_cancellation_ack_ed25519(ack, &self.signature)
+    }
+}
+
+/// Where a cancellation stands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CancellationState {
+    /// Sent, waiting for the counterparty.
+    AwaitingAck,
+    /// Both parties agree the subscription has ended.
+    Acknowledged,
+}
+
+/// A cancellation as kept in each party's store.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CancellationRecord {
+    /// The signed cancellation.
+    pub cancellation: SignedCancellation,
+    /// The counterparty's acknowledgment, once received.
+    pub ack: Option<SignedCancellationAck>,
+}
+
+impl CancellationRecord {
+    /// Start a record for a cancellation awaiting acknowledgment.
+    pub fn new(cancellation: SignedCancellation) -> Self {
+        Self {
+            cancellation,
+            ack: None,
+        }
+    }
+
+    /// Subscription the record is about.
+    pub fn subscription_id(&self) -> &str {
+        &self.cancellation.cancellation.subscription_id
+    }
+
+    /// Current state.
+    pub fn state(&self) -> CancellationState {
+        if self.ack.is_some() {
+            CancellationState::Acknowledged
+        } else {
+            CancellationState::AwaitingAck
+        }
+    }
+
+    /// Whether the counterparty has acknowledged.
+    pub fn is_acknowledged(&self) -> bool {
+        self.state() == CancellationState::Acknowledged
+    }
+
+    /// Attach the counterparty's acknowledgment after verifying it.
+    pub fn acknowledge(&mut self, ack: SignedCancellationAck) -> Result<()> {
+        if !ack.verify_for(&self.cancellation)? {
+            anyhow::bail!(
+                "Invalid acknowledgment for cancellation of {}",
+                self.subscription_id()
+            );
+        }
+        self.ack = Some(ack);
+        Ok(())
+    }
+}
+
+fn counterparty_of(subscription: &Subscription, party: &PublicKey) -> Option<PublicKey> {
+    if party == &subscription.subscriber {
+        Some(subscription.provider.clone())
+    } else if party == &subscription.provider {
+        Some(subscription.subscriber.clone())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Amount, PaymentFrequency, SubscriptionTerms};
+    use paykit_lib::MethodId;
+
+    fn subscription(subscriber: &pubky::Keypair, provider: &pubky::Keypair) -> Subscription {
+        Subscription::new(
+            subscriber.public_key(),
+            provider.public_key(),
+            SubscriptionTerms::new(
+                Amount::from_sats(1000),
+                "SAT".to_string(),
+                PaymentFrequency::Monthly { day_of_month: 1 },
+                MethodId("lightning".to_string()),
+                "Pro plan".to_string(),
+            ),
+        )
+    }
+
+    #[test]
+    fn test_reason_codes_round_trip() {
+        for reason in CancellationReason::ALL {
+            assert_eq!(
+                reason.as_str().parse::<CancellationReason>().unwrap(),
+                reason
+            );
+        }
+        assert_eq!(
+            "too-expensive".parse::<CancellationReason>().unwrap(),
+            CancellationReason::TooExpensive
+        );
+        assert!("bored".parse::<CancellationReason>().is_err());
+    }
+
+    #[test]
+    fn test_cancellation_acknowledged_by_counterparty() {
+        let subscriber = pkarr::Keypair::random();
+        let provider = pkarr::Keypair::random();
+        let sub = subscription(&subscriber, &provider);
+
+        let cancellation = CancelSubscription::new(
+            &sub,
+            &subscriber.public_key(),
+            CancellationReason::SwitchingProvider,
+        )
+        .unwrap()
+        .with_note("Found a cheaper plan");
+        assert_eq!(cancellation.counterparty, provider.public_key());
+
+        // Only the canceller can sign, only the counterparty can ack
+        assert!(SignedCancellation::sign(cancellation.clone(), &provider).is_err());
+        let signed = SignedCancellation::sign(cancellation, &subscriber).unwrap();
+        assert!(signed.verify_for(&sub).unwrap());
+        assert!(signed.acknowledge(&subscriber).is_err());
+
+        let mut record = CancellationRecord::new(signed.clone());
+        assert_eq!(record.state(), CancellationState::AwaitingAck);
+        record
+            .acknowledge(signed.acknowledge(&provider).unwrap())
+            .unwrap();
+        assert!(record.is_acknowledged());
+
+        // An ack for a different cancellation is rejected
+        let other = SignedCancellation::sign(
+            CancelSubscription::new(&sub, &subscriber.public_key(), CancellationReason::Other)
+                .unwrap(),
+            &subscriber,
+        )
+        .unwrap();
+        let mut pending = CancellationRecord::new(signed);
+        assert!(pending
+            .acknowledge(other.acknowledge(&provider).unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_outsider_cannot_cancel() {
+        let sub = subscription(&pkarr::Keypair::random(), &pkarr::Keypair::random());
+        let outsider = pkarr::Keypair::random();
+        assert!(
+            CancelSubscription::new(&sub, &outsider.public_key(), CancellationReason::Other)
+                .is_err()
+        );
+    }
+}
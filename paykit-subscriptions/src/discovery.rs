@@ -25,7 +25,8 @@
 use crate::PaymentRequest;
 use paykit_lib::protocol::{
     payment_request_aad, payment_request_path, payment_requests_dir, subscription_proposal_aad,
-    subscription_proposal_path, subscription_proposals_dir,
+    subscription_proposal_path, subscription_proposals_dir, subscription_status_aad,
+    subscription_status_path,
 };
 use paykit_lib::{AuthenticatedTransport, PublicKey, UnauthenticatedTransportRead};
 use pubky_noise::sealed_blob::{is_sealed_blob, sealed_blob_decrypt, sealed_blob_encrypt};
@@ -565,6 +566,90 @@ pub async fn discover_subscription_cancellations<R: UnauthenticatedTransportRead
     Ok(cancellations)
 }
 
+/// Publish a signed subscription status notice for the subscriber.
+///
+/// The notice is encrypted to the subscriber's Noise key and stored on the
+/// provider's storage, replacing any earlier notice for the subscription.
+///
+/// # Path Format
+///
+/// Notices are stored at: `/pub/paykit.app/v0/subscriptions/status/{subscriber_scope}/{subscription_id}`
+pub async fn publish_status_notice<T: AuthenticatedTransport>(
+    transport: &T,
+    signed: &crate::SignedStatusNotice,
+    subscriber_noise_pk: &[u8; 32],
+) -> crate::Result<()> {
+    let plaintext = serde_json::to_vec(signed)?;
+
+    let subscriber_pubkey_z32 = signed.notice.subscriber.to_string();
+    let subscription_id = &signed.notice.subscription_id;
+    let path = subscription_status_path(&subscriber_pubkey_z32, subscription_id)
+        .map_err(|e| anyhow::anyhow!("Invalid subscriber pubkey: {}", e))?;
+    let aad = subscription_status_aad(&subscriber_pubkey_z32, subscription_id)
+        .map_err(|e| anyhow::anyhow!("Failed to build AAD: {}", e))?;
+
+    let envelope = sealed_blob_encrypt(
+        subscriber_noise_pk,
+        &plaintext,
+        &aad,
+        Some("subscription_status"),
+    )
+    .map_err(|e| anyhow::anyhow!("Failed to encrypt status notice: {}", e))?;
+
+    transport
+        .put(&path, &envelope)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to publish status notice: {}", e))?;
+
+    Ok(())
+}
+
+/// Fetch and verify the provider's latest status notice for a subscription.
+///
+/// Returns `None` if no notice is published, or if the notice cannot be
+/// decrypted, is not signed by `provider`, or its signature has expired.
+/// Apps should fall back to their own view of the subscription in that case.
+///
+/// # Arguments
+///
+/// * `reader` - Unauthenticated transport for reading
+/// * `provider` - The provider's public key
+/// * `my_pubkey_z32` - My z-base-32 encoded pubkey (to compute my scope)
+/// * `subscription_id` - The subscription to check
+/// * `my_noise_sk` - My Noise secret key for decryption
+pub async fn fetch_status_notice<R: UnauthenticatedTransportRead>(
+    reader: &R,
+    provider: &PublicKey,
+    my_pubkey_z32: &str,
+    subscription_id: &str,
+    my_noise_sk: &[u8; 32],
+) -> crate::Result<Option<crate::SignedStatusNotice>> {
+    let path = subscription_status_path(my_pubkey_z32, subscription_id)
+        .map_err(|e| anyhow::anyhow!("Invalid pubkey: {}", e))?;
+
+    let content = match reader.get(provider, &path).await {
+        Ok(Some(content)) => content,
+        Ok(None) => return Ok(None),
+        Err(e) => return Err(anyhow::anyhow!("Failed to fetch status notice: {}", e)),
+    };
+
+    let Some(signed) =
+        try_decrypt_status_notice(&content, my_pubkey_z32, subscription_id, my_noise_sk)
+    else {
+        return Ok(None);
+    };
+
+    if !signed.verify_for(provider, subscription_id)? {
+        tracing::warn!(
+            "SECURITY: Rejected status notice for {} not validly signed by the provider",
+            subscription_id
+        );
+        return Ok(None);
+    }
+
+    Ok(Some(signed))
+}
+
 /// Decrypt an encrypted subscription proposal.
 ///
 /// SECURITY: Only encrypted Sealed Blob v1 format is accepted.
@@ -662,6 +747,44 @@ fn try_decrypt_cancellation(
     }
 }
 
+/// Decrypt an encrypted subscription status notice.
+///
+/// SECURITY: Only encrypted Sealed Blob v1 format is accepted.
+fn try_decrypt_status_notice(
+    content: &str,
+    subscriber_pubkey_z32: &str,
+    subscription_id: &str,
+    my_noise_sk: &[u8; 32],
+) -> Option<crate::SignedStatusNotice> {
+    if !is_sealed_blob(content) {
+        tracing::warn!(
+            "SECURITY: Rejected plaintext status notice for {}. Only encrypted blobs accepted.",
+            subscription_id
+        );
+        return None;
+    }
+
+    let aad = match subscription_status_aad(subscriber_pubkey_z32, subscription_id) {
+        Ok(aad) => aad,
+        Err(e) => {
+            tracing::warn!(
+                "Failed to build AAD for status notice {}: {}",
+                subscription_id,
+                e
+            );
+            return None;
+        }
+    };
+
+    match sealed_blob_decrypt(my_noise_sk, content, &aad) {
+        Ok(plaintext) => serde_json::from_slice(&plaintext).ok(),
+        Err(e) => {
+            tracing::warn!("Failed to decrypt status notice {}: {}", subscription_id, e);
+            None
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,579 @@
+//! Expected Payment Monitoring
+//!
+//! Providers want to know when a charge that should have arrived did not.
+//! An [`ExpectationMonitor`] projects each agreement's due dates (with
+//! scheduled modifications folded in, as in [`ChargeCalendar`]) into
+//! [`ExpectedPayment`] windows, matches them against payments actually
+//! observed — receipts, or executions reported by the caller — and raises a
+//! [`MissedPayment`] for every window that closed unpaid.
+//!
+//! Each miss carries an [`Escalation`]: the first misses ask for a fresh
+//! payment request, and once a subscription has missed
+//! [`ExpectationPolicy::delinquent_after`] charges in a row it is marked
+//! delinquent. Misses are kept in [`BillingHistory`] next to the invoices
+//! they relate to, and are resolved there when a late payment shows up.
+//!
+//! A payment settles an expectation when it names the subscription (or the
+//! consolidated invoice request that billed it), or else when it comes from
+//! the payer to the provider in the same currency for at least the expected
+//! amount. Each payment settles one expectation, except a consolidated
+//! invoice payment, which settles every subscription it allocates.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let monitor = ExpectationMonitor::new(ExpectationPolicy::default());
+//! let expected = monitor.expected_payments(&subscription, &modifications, since, now);
+//! let observed: Vec<ObservedPayment> = receipts.iter().map(ObservedPayment::from_receipt).collect();
+//!
+//! let report = monitor.check(&expected, &observed, &history, now);
+//! for missed in &report.missed {
+//!     match missed.escalation {
+//!         Escalation::RetryRequest => resend_request(missed),
+//!         Escalation::MarkDelinquent => publish_past_due(missed),
+//!     }
+//! }
+//! ```
+
+use crate::calendar::{ChargeCalendar, UpcomingCharge, MAX_CALENDAR_DAYS};
+use crate::{Amount, BillingHistory, ModificationRequest, Result, Subscription, SubscriptionError};
+use paykit_interactive::PaykitReceipt;
+use paykit_lib::{MethodId, PublicKey};
+use serde::{Deserialize, Serialize};
+
+/// Receipt metadata key naming the subscription a payment is for.
+pub const SUBSCRIPTION_ID_METADATA_KEY: &str = "subscription_id";
+
+/// Receipt metadata key naming the payment request a payment settles.
+pub const REQUEST_ID_METADATA_KEY: &str = "request_id";
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// When payments are expected and how misses escalate.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpectationPolicy {
+    /// How long before the due date a payment still counts.
+    pub early_secs: i64,
+    /// How long after the due date to wait before calling it missed.
+    pub grace_secs: i64,
+    /// Consecutive misses after which a subscription is marked delinquent.
+    pub delinquent_after: u32,
+}
+
+impl Default for ExpectationPolicy {
+    fn default() -> Self {
+        Self {
+            early_secs: SECONDS_PER_DAY,
+            grace_secs: 3 * SECONDS_PER_DAY,
+            delinquent_after: 2,
+        }
+    }
+}
+
+impl ExpectationPolicy {
+    /// Set how long after the due date a payment may arrive.
+    pub fn with_grace_secs(mut self, grace_secs: i64) -> Self {
+        self.grace_secs = grace_secs;
+        self
+    }
+
+    /// Set how long before the due date a payment counts.
+    pub fn with_early_secs(mut self, early_secs: i64) -> Self {
+        self.early_secs = early_secs;
+        self
+    }
+
+    /// Set the number of consecutive misses that makes a subscription delinquent.
+    pub fn with_delinquent_after(mut self, misses: u32) -> Self {
+        self.delinquent_after = misses;
+        self
+    }
+
+    /// Check that the policy is usable.
+    pub fn validate(&self) -> Result<()> {
+        if self.early_secs < 0 || self.grace_secs < 0 {
+            return Err(SubscriptionError::InvalidArgument(
+                "Payment windows cannot be negative".to_string(),
+            )
+            .into());
+        }
+        if self.delinquent_after == 0 {
+            return Err(SubscriptionError::InvalidArgument(
+                "delinquent_after must be at least 1".to_string(),
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// A charge the provider expects to receive.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExpectedPayment {
+    /// Subscription being paid.
+    pub subscription_id: String,
+    /// Provider receiving the payment.
+    pub provider: PublicKey,
+    /// Subscriber expected to pay.
+    pub payer: PublicKey,
+    /// Amount due, after scheduled modifications.
+    pub amount: Amount,
+    /// Currency of the amount.
+    pub currency: String,
+    /// Payment method in effect on the due date.
+    pub method: MethodId,
+    /// When the charge falls due.
+    pub due_at: i64,
+    /// Earliest time a payment counts for this charge.
+    pub window_opens_at: i64,
+    /// Time after which the charge is missed.
+    pub window_closes_at: i64,
+}
+
+impl ExpectedPayment {
+    fn from_charge(charge: UpcomingCharge, policy: &ExpectationPolicy) -> Self {
+        Self {
+            window_opens_at: charge.due_at.saturating_sub(policy.early_secs),
+            window_closes_at: charge.due_at.saturating_add(policy.grace_secs),
+            subscription_id: charge.subscription_id,
+            provider: charge.provider,
+            payer: charge.subscriber,
+            amount: charge.amount,
+            currency: charge.currency,
+            method: charge.method,
+            due_at: charge.due_at,
+        }
+    }
+
+    /// Whether `payment` is for this charge, ignoring when it was made.
+    ///
+    /// `history` resolves consolidated invoice requests to the subscriptions
+    /// they billed.
+    pub fn is_paid_by(&self, payment: &ObservedPayment, history: &BillingHistory) -> bool {
+        if payment.payer != self.payer || payment.payee != self.provider {
+            return false;
+        }
+        if let Some(subscription_id) = &payment.subscription_id {
+            return subscription_id == &self.subscription_id;
+        }
+        if let Some(record) = payment
+            .request_id
+            .as_deref()
+            .and_then(|id| history.records.iter().find(|r| r.request_id == id))
+        {
+            return record.allocation_for(&self.subscription_id).is_some();
+        }
+        payment
+            .currency
+            .as_ref()
+            .is_none_or(|currency| currency.eq_ignore_ascii_case(&self.currency))
+            && payment
+                .amount
+                .is_none_or(|amount| self.amount.is_within_limit(&amount))
+    }
+
+    fn is_key(&self, subscription_id: &str, due_at: i64) -> bool {
+        self.subscription_id == subscription_id && self.due_at == due_at
+    }
+}
+
+/// A payment seen arriving, from a receipt or an executor.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ObservedPayment {
+    /// Who paid.
+    pub payer: PublicKey,
+    /// Who was paid.
+    pub payee: PublicKey,
+    /// Amount paid, if known.
+    pub amount: Option<Amount>,
+    /// Currency paid in, if known.
+    pub currency: Option<String>,
+    /// When the payment was made.
+    pub paid_at: i64,
+    /// Payment request the payment settles, if known.
+    pub request_id: Option<String>,
+    /// Subscription the payment is for, if known.
+    pub subscription_id: Option<String>,
+}
+
+impl ObservedPayment {
+    /// Describe a payment from its receipt.
+    ///
+    /// The subscription and request are read from the receipt metadata
+    /// under [`SUBSCRIPTION_ID_METADATA_KEY`] and [`REQUEST_ID_METADATA_KEY`].
+    pub fn from_receipt(receipt: &PaykitReceipt) -> Self {
+        let metadata_str = |key: &str| {
+            receipt
+                .metadata
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+        };
+        Self {
+            payer: receipt.payer.clone(),
+            payee: receipt.payee.clone(),
+            amount: receipt
+                .amount
+                .as_deref()
+                .and_then(|amount| amount.parse().ok()),
+            currency: receipt.currency.clone(),
+            paid_at: receipt.created_at,
+            request_id: metadata_str(REQUEST_ID_METADATA_KEY),
+            subscription_id: metadata_str(SUBSCRIPTION_ID_METADATA_KEY),
+        }
+    }
+}
+
+/// What the provider should do about a missed payment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Escalation {
+    /// Send the payer a new payment request.
+    RetryRequest,
+    /// Treat the subscription as delinquent.
+    MarkDelinquent,
+}
+
+/// A charge whose payment window closed without a payment.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MissedPayment {
+    /// Subscription that was not paid.
+    pub subscription_id: String,
+    /// Provider owed the payment.
+    pub provider: PublicKey,
+    /// Subscriber who did not pay.
+    pub payer: PublicKey,
+    /// Amount that was due.
+    pub amount: Amount,
+    /// Currency of the amount.
+    pub currency: String,
+    /// Payment method in effect on the due date.
+    pub method: MethodId,
+    /// When the charge fell due.
+    pub due_at: i64,
+    /// When the miss was detected.
+    pub detected_at: i64,
+    /// Unresolved misses for the subscription, including this one.
+    pub consecutive_misses: u32,
+    /// Action recommended for this miss.
+    pub escalation: Escalation,
+    /// Payment request issued to recover the charge.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_request_id: Option<String>,
+    /// When a late payment settled the charge.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<i64>,
+}
+
+impl MissedPayment {
+    /// Whether a late payment has since settled the charge.
+    pub fn is_resolved(&self) -> bool {
+        self.resolved_at.is_some()
+    }
+
+    fn expected(&self) -> ExpectedPayment {
+        ExpectedPayment {
+            subscription_id: self.subscription_id.clone(),
+            provider: self.provider.clone(),
+            payer: self.payer.clone(),
+            amount: self.amount,
+            currency: self.currency.clone(),
+            method: self.method.clone(),
+            due_at: self.due_at,
+            window_opens_at: self.due_at,
+            window_closes_at: i64::MAX,
+        }
+    }
+}
+
+/// Outcome of one [`ExpectationMonitor::check`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExpectationReport {
+    /// Expected charges that were paid in their window.
+    pub settled: Vec<ExpectedPayment>,
+    /// Expected charges whose window is still open.
+    pub awaiting: Vec<ExpectedPayment>,
+    /// Newly missed charges.
+    pub missed: Vec<MissedPayment>,
+    /// Earlier misses settled by a late payment.
+    pub resolved: Vec<MissedPayment>,
+}
+
+/// Derives expected payments and detects misses.
+#[derive(Clone, Debug, Default)]
+pub struct ExpectationMonitor {
+    policy: ExpectationPolicy,
+}
+
+impl ExpectationMonitor {
+    /// Create a monitor with the given policy.
+    pub fn new(policy: ExpectationPolicy) -> Self {
+        Self { policy }
+    }
+
+    /// Get the policy.
+    pub fn policy(&self) -> &ExpectationPolicy {
+        &self.policy
+    }
+
+    /// Charges of a subscription falling due from `since` up to `until`.
+    ///
+    /// At most [`MAX_CALENDAR_DAYS`] before `until` are covered.
+    pub fn expected_payments(
+        &self,
+        subscription: &Subscription,
+        modifications: &[ModificationRequest],
+        since: i64,
+        until: i64,
+    ) -> Vec<ExpectedPayment> {
+        let days = ((until - since) / SECONDS_PER_DAY + 1).clamp(1, i64::from(MAX_CALENDAR_DAYS));
+        let from = since.max(until - (days - 1) * SECONDS_PER_DAY);
+        let Ok(mut calendar) = ChargeCalendar::new(from, days as u32) else {
+            return Vec::new();
+        };
+        calendar.add_subscription(subscription, None, modifications);
+
+        calendar
+            .charges
+            .into_iter()
+            .filter(|charge| charge.due_at >= since && charge.due_at <= until)
+            .map(|charge| ExpectedPayment::from_charge(charge, &self.policy))
+            .collect()
+    }
+
+    /// Match expected charges against observed payments at `now`.
+    ///
+    /// Charges already recorded as missed in `history` are not reported
+    /// again; a payment arriving for one of them after its window resolves
+    /// it instead.
+    pub fn check(
+        &self,
+        expected: &[ExpectedPayment],
+        observed: &[ObservedPayment],
+        history: &BillingHistory,
+        now: i64,
+    ) -> ExpectationReport {
+        let mut report = ExpectationReport::default();
+        let mut used = vec![false; observed.len()];
+
+        let mut expected: Vec<&ExpectedPayment> = expected.iter().collect();
+        expected.sort_by_key(|e| (e.due_at, e.subscription_id.clone()));
+
+        for expectation in expected {
+            let already_missed = history
+                .missed
+                .iter()
+                .any(|m| expectation.is_key(&m.subscription_id, m.due_at));
+            if already_missed {
+                continue;
+            }
+
+            let paid = take_payment(expectation, observed, &mut used, history, |payment| {
+                payment.paid_at >= expectation.window_opens_at
+                    && payment.paid_at <= expectation.window_closes_at
+            });
+            if paid {
+                report.settled.push(expectation.clone());
+            } else if now <= expectation.window_closes_at {
+                report.awaiting.push(expectation.clone());
+            } else {
+                let consecutive_misses = history
+                    .unresolved_misses(&expectation.subscription_id)
+                    .len() as u32
+                    + report
+                        .missed
+                        .iter()
+                        .filter(|m| m.subscription_id == expectation.subscription_id)
+                        .count() as u32
+                    + 1;
+                report.missed.push(MissedPayment {
+                    subscription_id: expectation.subscription_id.clone(),
+                    provider: expectation.provider.clone(),
+                    payer: expectation.payer.clone(),
+                    amount: expectation.amount,
+                    currency: expectation.currency.clone(),
+                    method: expectation.method.clone(),
+                    due_at: expectation.due_at,
+                    detected_at: now,
+                    consecutive_misses,
+                    escalation: if consecutive_misses >= self.policy.delinquent_after {
+                        Escalation::MarkDelinquent
+                    } else {
+                        Escalation::RetryRequest
+                    },
+                    retry_request_id: None,
+                    resolved_at: None,
+                });
+            }
+        }
+
+        for missed in history.missed.iter().filter(|m| !m.is_resolved()) {
+            let expectation = missed.expected();
+            let late = observed
+                .iter()
+                .zip(used.iter_mut())
+                .filter(|(payment, used)| !**used && payment.paid_at >= missed.due_at)
+                .find(|(payment, _)| expectation.is_paid_by(payment, history));
+            if let Some((payment, used)) = late {
+                *used = true;
+                let mut resolved = missed.clone();
+                resolved.resolved_at = Some(payment.paid_at);
+                report.resolved.push(resolved);
+            }
+        }
+
+        report
+    }
+}
+
+/// Mark the first unused payment for `expectation` that passes `in_window`.
+///
+/// A consolidated invoice payment is left available for the other
+/// subscriptions on the same invoice.
+fn take_payment(
+    expectation: &ExpectedPayment,
+    observed: &[ObservedPayment],
+    used: &mut [bool],
+    history: &BillingHistory,
+    in_window: impl Fn(&ObservedPayment) -> bool,
+) -> bool {
+    for (payment, used) in observed.iter().zip(used.iter_mut()) {
+        if *used || !in_window(payment) || !expectation.is_paid_by(payment, history) {
+            continue;
+        }
+        let consolidated = payment.subscription_id.is_none()
+            && payment
+                .request_id
+                .as_deref()
+                .is_some_and(|id| history.records.iter().any(|r| r.request_id == id));
+        if !consolidated {
+            *used = true;
+        }
+        return true;
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BillingAllocation, BillingRecord, PaymentFrequency, SubscriptionTerms};
+
+    const DAY: i64 = SECONDS_PER_DAY;
+    // 2025-01-01 00:00 UTC
+    const JAN_1: i64 = 1_735_689_600;
+
+    fn weekly_subscription(sats: i64) -> Subscription {
+        let mut sub = Subscription::new(
+            pkarr::Keypair::random().public_key(),
+            pkarr::Keypair::random().public_key(),
+            SubscriptionTerms::new(
+                Amount::from_sats(sats),
+                "SAT".to_string(),
+                PaymentFrequency::Weekly,
+                MethodId("lightning".to_string()),
+                "Weekly plan".to_string(),
+            ),
+        );
+        sub.starts_at = JAN_1;
+        sub
+    }
+
+    fn payment(sub: &Subscription, sats: i64, paid_at: i64) -> ObservedPayment {
+        ObservedPayment {
+            payer: sub.subscriber.clone(),
+            payee: sub.provider.clone(),
+            amount: Some(Amount::from_sats(sats)),
+            currency: Some("SAT".to_string()),
+            paid_at,
+            request_id: None,
+            subscription_id: None,
+        }
+    }
+
+    #[test]
+    fn test_missed_payments_escalate_then_resolve() {
+        let sub = weekly_subscription(1000);
+        let monitor = ExpectationMonitor::default();
+        let now = JAN_1 + 17 * DAY;
+        let expected = monitor.expected_payments(&sub, &[], JAN_1, now);
+        assert_eq!(
+            expected.iter().map(|e| e.due_at).collect::<Vec<_>>(),
+            vec![JAN_1, JAN_1 + 7 * DAY, JAN_1 + 14 * DAY]
+        );
+
+        // First charge paid on time, the second underpaid, the third pending
+        let observed = vec![
+            payment(&sub, 1000, JAN_1 + 3600),
+            payment(&sub, 500, JAN_1 + 7 * DAY),
+        ];
+        let mut history = BillingHistory::new();
+        let report = monitor.check(&expected, &observed, &history, now);
+        assert_eq!(report.settled.len(), 1);
+        assert_eq!(report.awaiting.len(), 1);
+        assert_eq!(report.missed.len(), 1);
+        assert_eq!(report.missed[0].escalation, Escalation::RetryRequest);
+        history.record_missed(report.missed[0].clone());
+
+        // The third charge is missed too, making the subscription delinquent
+        let now = JAN_1 + 18 * DAY;
+        let report = monitor.check(&expected, &observed, &history, now);
+        assert!(report.resolved.is_empty());
+        assert_eq!(report.missed.len(), 1);
+        assert_eq!(report.missed[0].consecutive_misses, 2);
+        assert_eq!(report.missed[0].escalation, Escalation::MarkDelinquent);
+        history.record_missed(report.missed[0].clone());
+        assert!(history.is_delinquent(&sub.subscription_id));
+
+        // A late payment settles the oldest miss
+        let late = vec![payment(&sub, 1000, JAN_1 + 19 * DAY)];
+        let report = monitor.check(&expected, &late, &history, now + DAY);
+        assert!(report.missed.is_empty());
+        assert_eq!(report.resolved.len(), 1);
+        assert_eq!(report.resolved[0].due_at, JAN_1 + 7 * DAY);
+        for resolved in report.resolved {
+            history.record_missed(resolved);
+        }
+        assert_eq!(history.unresolved_misses(&sub.subscription_id).len(), 1);
+    }
+
+    #[test]
+    fn test_consolidated_payment_settles_each_allocation() {
+        let first = weekly_subscription(1000);
+        let mut second = weekly_subscription(2000);
+        second.subscriber = first.subscriber.clone();
+        second.provider = first.provider.clone();
+        second.subscription_id = format!("{}_2", first.subscription_id);
+
+        let mut history = BillingHistory::new();
+        history.record(BillingRecord {
+            invoice_number: "INV-1".to_string(),
+            request_id: "req_batch".to_string(),
+            provider: first.provider.clone(),
+            payer: first.subscriber.clone(),
+            currency: "SAT".to_string(),
+            method: MethodId("lightning".to_string()),
+            total: Amount::from_sats(3000),
+            allocations: [&first, &second]
+                .iter()
+                .map(|sub| BillingAllocation {
+                    subscription_id: sub.subscription_id.clone(),
+                    amount: sub.terms.amount,
+                    description: sub.terms.description.clone(),
+                })
+                .collect(),
+            billed_at: JAN_1,
+        });
+
+        let monitor = ExpectationMonitor::default();
+        let now = JAN_1 + 5 * DAY;
+        let mut expected = monitor.expected_payments(&first, &[], JAN_1, now);
+        expected.extend(monitor.expected_payments(&second, &[], JAN_1, now));
+
+        let mut batch = payment(&first, 3000, JAN_1 + DAY);
+        batch.request_id = Some("req_batch".to_string());
+        let report = monitor.check(&expected, &[batch], &history, now);
+        assert_eq!(report.settled.len(), 2);
+        assert!(report.missed.is_empty());
+    }
+}
@@ -12,6 +12,10 @@
 //! 3. Records failed attempts for later analysis
 //! 4. Notifies on success or final failure
 //!
+//! Retry timing across billing checks is described by
+//! [`RetryPolicy`](crate::retry::RetryPolicy), which can be derived from a
+//! [`SubscriptionFallbackPolicy`].
+//!
 //! # Example
 //!
 //! ```ignore
@@ -1,7 +1,19 @@
 //! Invoice Types for Payment Requests
 //!
 //! This module provides standardized invoice structures for payment requests,
-//! including line items, tax information, and shipping details.
+//! including line items, tax information, and shipping details. Providers
+//! billing on a schedule use the [`template`] module to generate invoices
+//! every cycle, and the [`tax`] module to calculate their tax by jurisdiction.
+
+pub mod tax;
+pub mod template;
+
+pub use tax::{TaxHook, TAX_METADATA_KEY};
+
+pub use template::{
+    DueTerms, InvoiceBook, InvoiceHook, InvoiceScheduler, InvoiceStatus, InvoiceTemplate,
+    IssuedInvoice, RecurringInvoice, TaxRule, INVOICE_METADATA_KEY,
+};
 
 use crate::Amount;
 use rust_decimal::Decimal;
@@ -393,11 +405,9 @@ impl Invoice {
 
     /// Check if the invoice has shipping.
     pub fn has_shipping(&self) -> bool {
-        self.shipping.is_some()
-            && !matches!(
-                self.shipping.as_ref().unwrap().method,
-                ShippingMethod::Digital
-            )
+        self.shipping
+            .as_ref()
+            .is_some_and(|shipping| !matches!(shipping.method, ShippingMethod::Digital))
     }
 }
 
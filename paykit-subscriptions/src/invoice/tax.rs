@@ -0,0 +1,192 @@
+//! Calculated Tax for Recurring Invoices
+//!
+//! [`TaxHook`] runs a [`TaxCalculator`] over every invoice the
+//! [`InvoiceScheduler`](super::InvoiceScheduler) issues, using the payer's
+//! and provider's jurisdictions. The result replaces the template's tax, the
+//! request total is recalculated, and the calculation breakdown is recorded
+//! under [`TAX_METADATA_KEY`] in the request metadata. Receipts copy that
+//! metadata, so exported receipts carry the same breakdown.
+
+use super::template::{InvoiceHook, RecurringInvoice};
+use super::TaxInfo;
+use crate::{Amount, PaymentRequest, Result, SubscriptionError};
+use paykit_interactive::metadata::tax::{TaxCalculator, TaxContext};
+use paykit_interactive::MetadataItem;
+use paykit_lib::PublicKey;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Metadata key carrying the calculated tax.
+pub const TAX_METADATA_KEY: &str = "tax";
+
+/// Invoice hook that calculates tax by jurisdiction.
+pub struct TaxHook {
+    calculator: Arc<dyn TaxCalculator>,
+    provider_jurisdiction: Option<String>,
+    payer_jurisdictions: HashMap<PublicKey, String>,
+}
+
+impl TaxHook {
+    /// Create a hook using `calculator`.
+    pub fn new(calculator: Arc<dyn TaxCalculator>) -> Self {
+        Self {
+            calculator,
+            provider_jurisdiction: None,
+            payer_jurisdictions: HashMap::new(),
+        }
+    }
+
+    /// Set the provider's jurisdiction.
+    pub fn with_provider_jurisdiction(mut self, jurisdiction: impl Into<String>) -> Self {
+        self.provider_jurisdiction = Some(jurisdiction.into());
+        self
+    }
+
+    /// Set a payer's jurisdiction.
+    pub fn with_payer_jurisdiction(
+        mut self,
+        payer: PublicKey,
+        jurisdiction: impl Into<String>,
+    ) -> Self {
+        self.payer_jurisdictions.insert(payer, jurisdiction.into());
+        self
+    }
+
+    /// Calculate the tax on `request` and update its total and metadata.
+    pub fn apply(&self, request: &mut PaymentRequest) -> Result<()> {
+        let mut context = TaxContext::new(request.currency.clone());
+        context.payer_jurisdiction = self.payer_jurisdictions.get(&request.to).cloned();
+        context.payee_jurisdiction = self.provider_jurisdiction.clone();
+
+        let items: Vec<MetadataItem> = request
+            .items
+            .iter()
+            .map(|item| {
+                let mut line = MetadataItem::new(
+                    item.description.clone(),
+                    item.quantity,
+                    item.unit_price.to_string(),
+                    request.currency.clone(),
+                );
+                line.sku = item.sku.clone();
+                line
+            })
+            .collect();
+        let calculated = self
+            .calculator
+            .calculate(&items, &context)
+            .map_err(|e| SubscriptionError::InvalidArgument(e.to_string()))?;
+
+        request.tax = match &calculated {
+            Some(tax) => {
+                let rate = tax
+                    .rate
+                    .and_then(|rate| Decimal::try_from(rate).ok())
+                    .unwrap_or_default();
+                let amount = tax
+                    .amount
+                    .as_deref()
+                    .map(Amount::from_str)
+                    .transpose()
+                    .map_err(SubscriptionError::InvalidArgument)?
+                    .unwrap_or_else(Amount::zero);
+                let mut info = TaxInfo::new(
+                    tax.description.clone().unwrap_or_else(|| "Tax".to_string()),
+                    rate,
+                    amount,
+                );
+                info.jurisdiction = tax.jurisdiction.clone();
+                info.tax_id = tax.tax_id.clone();
+                Some(info)
+            }
+            None => None,
+        };
+        request.amount = request.to_invoice().total;
+
+        if let Some(metadata) = request.metadata.as_object_mut() {
+            match calculated {
+                Some(tax) => {
+                    metadata.insert(TAX_METADATA_KEY.to_string(), serde_json::to_value(tax)?);
+                }
+                None => {
+                    metadata.remove(TAX_METADATA_KEY);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl InvoiceHook for TaxHook {
+    fn before_issue(
+        &self,
+        _schedule: &RecurringInvoice,
+        request: &mut PaymentRequest,
+    ) -> Result<()> {
+        self.apply(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::invoice::{InvoiceItem, InvoiceScheduler, InvoiceTemplate};
+    use crate::PaymentFrequency;
+    use paykit_interactive::{TaxMetadata, TaxRateRule, TaxRulesTable};
+    use paykit_lib::MethodId;
+
+    fn test_pubkey() -> PublicKey {
+        let keypair = pkarr::Keypair::random();
+        PublicKey::from_str(&keypair.public_key().to_z32()).unwrap()
+    }
+
+    #[test]
+    fn test_tax_hook_records_breakdown() {
+        let table = TaxRulesTable::new()
+            .with_rule(TaxRateRule::new("DE", "VAT", 1900).with_exempt_sku("BOOK"))
+            .with_rule(TaxRateRule::new("US", "Sales Tax", 500));
+        let payer = test_pubkey();
+        let hook = TaxHook::new(Arc::new(table))
+            .with_provider_jurisdiction("US")
+            .with_payer_jurisdiction(payer.clone(), "DE");
+
+        let template = InvoiceTemplate::new(
+            "plan",
+            vec![
+                InvoiceItem::new("Plan", 1, Amount::from_sats(10_000)),
+                InvoiceItem::new("Handbook", 1, Amount::from_sats(2_000)).with_sku("BOOK"),
+            ],
+        );
+        let schedule = RecurringInvoice::new(
+            "plan-de",
+            template,
+            test_pubkey(),
+            payer,
+            "SAT",
+            MethodId("lightning".to_string()),
+            PaymentFrequency::Daily,
+            1_700_000_000,
+        )
+        .unwrap();
+
+        let scheduler = InvoiceScheduler::new();
+        scheduler.add_hook(Arc::new(hook));
+        scheduler.add_schedule(schedule).unwrap();
+        let request = scheduler.generate_due(1_700_000_000).unwrap().remove(0);
+
+        assert_eq!(
+            request.tax.as_ref().unwrap().amount,
+            Amount::from_sats(1_900)
+        );
+        assert_eq!(request.amount, Amount::from_sats(13_900));
+        assert_eq!(scheduler.outstanding()[0].total, request.amount);
+
+        let tax: TaxMetadata =
+            serde_json::from_value(request.metadata[TAX_METADATA_KEY].clone()).unwrap();
+        assert_eq!(tax.jurisdiction.as_deref(), Some("DE"));
+        assert_eq!(tax.breakdown.len(), 2);
+        assert!(tax.is_reconciled());
+    }
+}
@@ -0,0 +1,855 @@
+//! Invoice Templates and Recurring Invoices
+//!
+//! An [`InvoiceTemplate`] describes what a provider bills every cycle: line
+//! items, a [`TaxRule`] and [`DueTerms`]. A [`RecurringInvoice`] binds a
+//! template to a payer and a billing frequency, and the [`InvoiceScheduler`]
+//! turns due schedules into itemized [`PaymentRequest`]s, one per cycle.
+//!
+//! Every invoice the scheduler issues is tracked as an [`IssuedInvoice`].
+//! Receipts are matched back to invoices through the invoice metadata the
+//! request carries (and the payer copies into the receipt), so the provider
+//! can see which invoices are paid, open or overdue.
+//!
+//! [`InvoiceHook`]s run around generation: they can adjust or veto a request
+//! before it is issued, and are told when an invoice is issued or paid.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use paykit_subscriptions::invoice::{DueTerms, InvoiceTemplate, RecurringInvoice, TaxRule};
+//!
+//! let template = InvoiceTemplate::new("hosting", vec![
+//!     InvoiceItem::new("VPS", 1, Amount::from_sats(40_000)).with_category("service"),
+//!     InvoiceItem::new("Backups", 1, Amount::from_sats(5_000)).with_category("service"),
+//! ])
+//! .with_tax(TaxRule::new("VAT", dec!(20)).with_jurisdiction("EU"))
+//! .with_due(DueTerms::NetDays { days: 14 });
+//!
+//! let schedule = RecurringInvoice::new("hosting-alice", template, provider, alice,
+//!     "SAT", MethodId::lightning(), PaymentFrequency::Monthly { day_of_month: 1 }, now)?;
+//! scheduler.add_schedule(schedule)?;
+//!
+//! // Each billing run
+//! for request in scheduler.generate_due(now)? {
+//!     manager.publish_payment_request(&request).await?;
+//! }
+//! ```
+
+use super::{Invoice, InvoiceItem, TaxInfo};
+use crate::calendar::next_billing_date;
+use crate::{Amount, PaymentFrequency, PaymentRequest, Result, SubscriptionError};
+use paykit_interactive::PaykitReceipt;
+use paykit_lib::{MethodId, PublicKey};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Metadata key carrying the invoice a request (and its receipt) is for.
+pub const INVOICE_METADATA_KEY: &str = "invoice";
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Tax applied to an invoice generated from a template.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaxRule {
+    /// Tax description (e.g., "Sales Tax", "VAT").
+    pub description: String,
+    /// Tax rate as a percentage (e.g., dec!(8.25) for 8.25%).
+    pub rate: Decimal,
+    /// Jurisdiction (e.g., "CA", "EU", "UK").
+    pub jurisdiction: Option<String>,
+    /// Tax ID or registration number.
+    pub tax_id: Option<String>,
+    /// Item categories the tax applies to. Empty means every item.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<String>,
+}
+
+impl TaxRule {
+    /// Create a rule taxing every item at `rate` percent.
+    pub fn new(description: impl Into<String>, rate: Decimal) -> Self {
+        Self {
+            description: description.into(),
+            rate,
+            jurisdiction: None,
+            tax_id: None,
+            categories: Vec::new(),
+        }
+    }
+
+    /// Set jurisdiction.
+    pub fn with_jurisdiction(mut self, jurisdiction: impl Into<String>) -> Self {
+        self.jurisdiction = Some(jurisdiction.into());
+        self
+    }
+
+    /// Set tax ID.
+    pub fn with_tax_id(mut self, tax_id: impl Into<String>) -> Self {
+        self.tax_id = Some(tax_id.into());
+        self
+    }
+
+    /// Only tax items in these categories.
+    pub fn for_categories(mut self, categories: Vec<String>) -> Self {
+        self.categories = categories;
+        self
+    }
+
+    /// Tax due on `items`, or `None` if no item is taxable.
+    pub fn apply(&self, items: &[InvoiceItem]) -> Option<TaxInfo> {
+        let taxable: Vec<&InvoiceItem> = items
+            .iter()
+            .filter(|item| {
+                self.categories.is_empty()
+                    || item
+                        .category
+                        .as_ref()
+                        .is_some_and(|c| self.categories.contains(c))
+            })
+            .collect();
+        if taxable.is_empty() {
+            return None;
+        }
+
+        let subtotal = taxable
+            .iter()
+            .fold(Amount::zero(), |acc, item| acc.add(&item.total));
+        let mut tax = TaxInfo::from_subtotal(self.description.clone(), self.rate, &subtotal);
+        tax.jurisdiction = self.jurisdiction.clone();
+        tax.tax_id = self.tax_id.clone();
+        Some(tax)
+    }
+}
+
+/// When a generated invoice is due.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DueTerms {
+    /// Due when issued.
+    #[default]
+    OnReceipt,
+    /// Due a number of days after it is issued.
+    NetDays {
+        /// Days between issue and due date.
+        days: u32,
+    },
+}
+
+impl DueTerms {
+    /// Due date of an invoice issued at `issued_at`.
+    pub fn due_date(&self, issued_at: i64) -> i64 {
+        match self {
+            DueTerms::OnReceipt => issued_at,
+            DueTerms::NetDays { days } => issued_at + i64::from(*days) * SECONDS_PER_DAY,
+        }
+    }
+
+    /// Human-readable payment terms.
+    pub fn describe(&self) -> String {
+        match self {
+            DueTerms::OnReceipt => "Due on receipt".to_string(),
+            DueTerms::NetDays { days } => format!("Net {}", days),
+        }
+    }
+}
+
+/// What a provider bills every cycle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InvoiceTemplate {
+    /// Template identifier.
+    pub template_id: String,
+    /// Line items billed every cycle.
+    pub items: Vec<InvoiceItem>,
+    /// Tax applied to the items.
+    pub tax: Option<TaxRule>,
+    /// When invoices are due.
+    #[serde(default)]
+    pub due: DueTerms,
+    /// Notes printed on every invoice.
+    pub notes: Option<String>,
+    /// Prefix of invoice numbers. Defaults to the template ID.
+    pub number_prefix: Option<String>,
+}
+
+impl InvoiceTemplate {
+    /// Create a template billing `items`, due on receipt.
+    pub fn new(template_id: impl Into<String>, items: Vec<InvoiceItem>) -> Self {
+        Self {
+            template_id: template_id.into(),
+            items,
+            tax: None,
+            due: DueTerms::OnReceipt,
+            notes: None,
+            number_prefix: None,
+        }
+    }
+
+    /// Set the tax rule.
+    pub fn with_tax(mut self, tax: TaxRule) -> Self {
+        self.tax = Some(tax);
+        self
+    }
+
+    /// Set the due terms.
+    pub fn with_due(mut self, due: DueTerms) -> Self {
+        self.due = due;
+        self
+    }
+
+    /// Set invoice notes.
+    pub fn with_notes(mut self, notes: impl Into<String>) -> Self {
+        self.notes = Some(notes.into());
+        self
+    }
+
+    /// Set the invoice number prefix.
+    pub fn with_number_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.number_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Check the template can generate invoices.
+    pub fn validate(&self) -> Result<()> {
+        if self.template_id.is_empty() {
+            return Err(invalid("Invoice template ID cannot be empty"));
+        }
+        if self.items.is_empty() {
+            return Err(invalid("Invoice template needs at least one item"));
+        }
+        if self.items.iter().any(|item| item.quantity == 0) {
+            return Err(invalid("Invoice template items need a quantity"));
+        }
+        if let Some(tax) = &self.tax {
+            if tax.rate < Decimal::ZERO || tax.rate > Decimal::from(100) {
+                return Err(invalid("Tax rate must be between 0 and 100 percent"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Invoice number for billing cycle `cycle` (starting at 1).
+    pub fn invoice_number(&self, cycle: u32) -> String {
+        let prefix = self.number_prefix.as_ref().unwrap_or(&self.template_id);
+        format!("{}-{:04}", prefix, cycle)
+    }
+
+    /// Render the invoice issued at `issued_at`.
+    pub fn render(&self, invoice_number: impl Into<String>, issued_at: i64) -> Invoice {
+        let mut invoice = Invoice::new(invoice_number, self.items.clone())
+            .with_terms(self.due.describe())
+            .with_due_date(self.due.due_date(issued_at));
+        invoice.invoice_date = issued_at;
+        if let Some(tax) = self.tax.as_ref().and_then(|rule| rule.apply(&self.items)) {
+            invoice = invoice.with_tax(tax);
+        }
+        if let Some(notes) = &self.notes {
+            invoice = invoice.with_notes(notes.clone());
+        }
+        invoice
+    }
+}
+
+/// A template billed to one payer on a schedule.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecurringInvoice {
+    /// Schedule identifier.
+    pub schedule_id: String,
+    /// What is billed every cycle.
+    pub template: InvoiceTemplate,
+    /// Provider issuing the invoices.
+    pub provider: PublicKey,
+    /// Payer being billed.
+    pub payer: PublicKey,
+    /// Currency of the invoices.
+    pub currency: String,
+    /// Payment method requested.
+    pub method: MethodId,
+    /// How often an invoice is issued.
+    pub frequency: PaymentFrequency,
+    /// When billing starts.
+    pub starts_at: i64,
+    /// When billing stops (exclusive).
+    pub ends_at: Option<i64>,
+    /// When the next invoice is issued; `None` once the schedule is over.
+    pub next_issue_at: Option<i64>,
+    /// Invoices issued so far.
+    pub cycles_issued: u32,
+    /// Subscription the invoices bill, if any.
+    pub subscription_id: Option<String>,
+}
+
+impl RecurringInvoice {
+    /// Create a schedule whose first invoice is issued on the first billing
+    /// date at or after `starts_at`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        schedule_id: impl Into<String>,
+        template: InvoiceTemplate,
+        provider: PublicKey,
+        payer: PublicKey,
+        currency: impl Into<String>,
+        method: MethodId,
+        frequency: PaymentFrequency,
+        starts_at: i64,
+    ) -> Result<Self> {
+        template.validate()?;
+        let schedule = Self {
+            schedule_id: schedule_id.into(),
+            template,
+            provider,
+            payer,
+            currency: currency.into(),
+            method,
+            next_issue_at: next_billing_date(&frequency, starts_at, starts_at - 1),
+            frequency,
+            starts_at,
+            ends_at: None,
+            cycles_issued: 0,
+            subscription_id: None,
+        };
+        if schedule.schedule_id.is_empty() {
+            return Err(invalid("Invoice schedule ID cannot be empty"));
+        }
+        if schedule.currency.is_empty() {
+            return Err(invalid("Currency cannot be empty"));
+        }
+        Ok(schedule)
+    }
+
+    /// Stop billing at `ends_at`.
+    pub fn with_end(mut self, ends_at: i64) -> Self {
+        self.ends_at = Some(ends_at);
+        self.next_issue_at = self.next_issue_at.filter(|at| *at < ends_at);
+        self
+    }
+
+    /// Link the invoices to a subscription.
+    pub fn with_subscription(mut self, subscription_id: impl Into<String>) -> Self {
+        self.subscription_id = Some(subscription_id.into());
+        self
+    }
+
+    /// Whether an invoice is due to be issued at `now`.
+    pub fn is_due(&self, now: i64) -> bool {
+        self.next_issue_at.is_some_and(|at| at <= now)
+    }
+
+    /// Build the request for the next cycle, issued at `now`.
+    pub fn next_request(&self, now: i64) -> PaymentRequest {
+        let cycle = self.cycles_issued + 1;
+        let invoice_number = self.template.invoice_number(cycle);
+        let invoice = self.template.render(invoice_number.clone(), now);
+
+        let mut request = PaymentRequest::new(
+            self.provider.clone(),
+            self.payer.clone(),
+            invoice.total,
+            self.currency.clone(),
+            self.method.clone(),
+        )
+        .with_description(format!("Invoice {}", invoice_number))
+        .with_invoice_number(invoice_number.clone())
+        .with_items(invoice.items);
+        if let Some(tax) = invoice.tax {
+            request = request.with_tax(tax);
+        }
+        if let Some(notes) = invoice.notes {
+            request = request.with_notes(notes);
+        }
+        if let Some(due_date) = invoice.due_date {
+            request = request.with_due_date(due_date);
+        }
+        request.request_id = format!("req_{}_{}", self.schedule_id, cycle);
+        request.created_at = now;
+        request.metadata = serde_json::json!({
+            INVOICE_METADATA_KEY: {
+                "invoice_number": invoice_number,
+                "schedule_id": self.schedule_id,
+                "cycle": cycle,
+                "subscription_id": self.subscription_id,
+            },
+        });
+        request
+    }
+
+    /// Move to the cycle after the one issued at `now`.
+    fn advance(&mut self, now: i64) {
+        self.cycles_issued += 1;
+        let after = self.next_issue_at.unwrap_or(now).max(now);
+        self.next_issue_at = next_billing_date(&self.frequency, self.starts_at, after)
+            .filter(|at| self.ends_at.is_none_or(|end| *at < end));
+    }
+}
+
+/// Payment status of an issued invoice.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum InvoiceStatus {
+    /// Not paid yet.
+    Open,
+    /// Settled by a receipt.
+    Paid {
+        /// Receipt that paid the invoice.
+        receipt_id: String,
+        /// When the receipt was created.
+        paid_at: i64,
+    },
+    /// Cancelled by the provider.
+    Void,
+}
+
+/// An invoice the scheduler issued, and whether it was paid.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IssuedInvoice {
+    /// Invoice number.
+    pub invoice_number: String,
+    /// Request carrying the invoice.
+    pub request_id: String,
+    /// Schedule that generated it.
+    pub schedule_id: String,
+    /// Billing cycle (starting at 1).
+    pub cycle: u32,
+    /// Payer being billed.
+    pub payer: PublicKey,
+    /// Amount due.
+    pub total: Amount,
+    /// Currency of the amount.
+    pub currency: String,
+    /// When the invoice was issued.
+    pub issued_at: i64,
+    /// When the invoice is due.
+    pub due_date: Option<i64>,
+    /// Whether the request reached the payer's mailbox.
+    #[serde(default)]
+    pub delivered: bool,
+    /// Payment status.
+    pub status: InvoiceStatus,
+}
+
+impl IssuedInvoice {
+    /// Whether the invoice is unpaid past its due date at `now`.
+    pub fn is_overdue(&self, now: i64) -> bool {
+        self.status == InvoiceStatus::Open && self.due_date.is_some_and(|due| now > due)
+    }
+
+    /// Whether `receipt` pays this invoice in full.
+    fn is_paid_by(&self, receipt: &PaykitReceipt) -> bool {
+        let currency_ok = receipt
+            .currency
+            .as_ref()
+            .is_none_or(|currency| *currency == self.currency);
+        let amount_ok = receipt
+            .amount
+            .as_deref()
+            .and_then(|amount| Amount::from_str(amount).ok())
+            .is_some_and(|paid| self.total.is_within_limit(&paid));
+        receipt.payer == self.payer && currency_ok && amount_ok
+    }
+}
+
+/// Hooks called while invoices are generated and paid.
+///
+/// Every method has a no-op default, so hooks only implement what they need.
+pub trait InvoiceHook: Send + Sync {
+    /// Adjust the request before it is issued, or return an error to skip
+    /// this cycle's invoice. A skipped cycle is retried on the next run.
+    fn before_issue(
+        &self,
+        _schedule: &RecurringInvoice,
+        _request: &mut PaymentRequest,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// An invoice was issued.
+    fn after_issue(&self, _invoice: &IssuedInvoice) {}
+
+    /// An invoice was paid.
+    fn on_paid(&self, _invoice: &IssuedInvoice) {}
+}
+
+/// Schedules and issued invoices, for persistence.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InvoiceBook {
+    /// Recurring invoice schedules.
+    #[serde(default)]
+    pub schedules: Vec<RecurringInvoice>,
+    /// Issued invoices in issue order.
+    #[serde(default)]
+    pub issued: Vec<IssuedInvoice>,
+}
+
+/// Generates recurring invoices and tracks their payment.
+#[derive(Default)]
+pub struct InvoiceScheduler {
+    book: Mutex<InvoiceBook>,
+    hooks: Mutex<Vec<Arc<dyn InvoiceHook>>>,
+}
+
+impl InvoiceScheduler {
+    /// Create a scheduler with no schedules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restore a scheduler from a saved [`book`](Self::book).
+    pub fn from_book(book: InvoiceBook) -> Self {
+        Self {
+            book: Mutex::new(book),
+            hooks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Snapshot of the schedules and issued invoices.
+    pub fn book(&self) -> InvoiceBook {
+        self.lock_book().clone()
+    }
+
+    /// Register a hook.
+    pub fn add_hook(&self, hook: Arc<dyn InvoiceHook>) {
+        self.hooks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(hook);
+    }
+
+    /// Add a schedule.
+    pub fn add_schedule(&self, schedule: RecurringInvoice) -> Result<()> {
+        schedule.template.validate()?;
+        let mut book = self.lock_book();
+        if book
+            .schedules
+            .iter()
+            .any(|s| s.schedule_id == schedule.schedule_id)
+        {
+            return Err(invalid(&format!(
+                "Invoice schedule {} already exists",
+                schedule.schedule_id
+            )));
+        }
+        book.schedules.push(schedule);
+        Ok(())
+    }
+
+    /// Remove a schedule, returning it. Its issued invoices are kept.
+    pub fn remove_schedule(&self, schedule_id: &str) -> Option<RecurringInvoice> {
+        let mut book = self.lock_book();
+        let index = book
+            .schedules
+            .iter()
+            .position(|s| s.schedule_id == schedule_id)?;
+        Some(book.schedules.remove(index))
+    }
+
+    /// Issue the invoices due at `now`, one per due schedule.
+    ///
+    /// A schedule that fell several cycles behind issues one invoice and
+    /// moves on to its next billing date after `now`.
+    pub fn generate_due(&self, now: i64) -> Result<Vec<PaymentRequest>> {
+        let hooks = self.hooks.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let mut book = self.lock_book();
+        let mut requests = Vec::new();
+        let mut issued = Vec::new();
+
+        for schedule in book.schedules.iter_mut().filter(|s| s.is_due(now)) {
+            let mut request = schedule.next_request(now);
+            if hooks
+                .iter()
+                .try_for_each(|hook| hook.before_issue(schedule, &mut request))
+                .is_err()
+            {
+                continue;
+            }
+
+            schedule.advance(now);
+            issued.push(IssuedInvoice {
+                invoice_number: request.invoice_number.clone().unwrap_or_default(),
+                request_id: request.request_id.clone(),
+                schedule_id: schedule.schedule_id.clone(),
+                cycle: schedule.cycles_issued,
+                payer: request.to.clone(),
+                total: request.amount,
+                currency: request.currency.clone(),
+                issued_at: now,
+                due_date: request.due_date,
+                delivered: false,
+                status: InvoiceStatus::Open,
+            });
+            requests.push(request);
+        }
+
+        book.issued.extend(issued.iter().cloned());
+        drop(book);
+        for invoice in &issued {
+            hooks.iter().for_each(|hook| hook.after_issue(invoice));
+        }
+        Ok(requests)
+    }
+
+    /// Note that the request for `invoice_number` reached the payer.
+    pub fn mark_delivered(&self, invoice_number: &str) {
+        if let Some(invoice) = self
+            .lock_book()
+            .issued
+            .iter_mut()
+            .find(|i| i.invoice_number == invoice_number)
+        {
+            invoice.delivered = true;
+        }
+    }
+
+    /// Match a receipt to the invoice it pays.
+    ///
+    /// The receipt must name the invoice in its metadata, come from the
+    /// invoiced payer and cover the full amount. Returns the invoice if it
+    /// was open and is now paid.
+    pub fn record_receipt(&self, receipt: &PaykitReceipt) -> Option<IssuedInvoice> {
+        let invoice_number = receipt
+            .metadata
+            .get(INVOICE_METADATA_KEY)?
+            .get("invoice_number")?
+            .as_str()?;
+
+        let paid = {
+            let mut book = self.lock_book();
+            let invoice = book
+                .issued
+                .iter_mut()
+                .find(|i| i.invoice_number == invoice_number)?;
+            if invoice.status != InvoiceStatus::Open || !invoice.is_paid_by(receipt) {
+                return None;
+            }
+            invoice.status = InvoiceStatus::Paid {
+                receipt_id: receipt.receipt_id.clone(),
+                paid_at: receipt.created_at,
+            };
+            invoice.clone()
+        };
+
+        let hooks = self.hooks.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        hooks.iter().for_each(|hook| hook.on_paid(&paid));
+        Some(paid)
+    }
+
+    /// Void an open invoice, returning whether it was open.
+    pub fn void(&self, invoice_number: &str) -> bool {
+        let mut book = self.lock_book();
+        match book
+            .issued
+            .iter_mut()
+            .find(|i| i.invoice_number == invoice_number)
+        {
+            Some(invoice) if invoice.status == InvoiceStatus::Open => {
+                invoice.status = InvoiceStatus::Void;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Get an issued invoice.
+    pub fn invoice(&self, invoice_number: &str) -> Option<IssuedInvoice> {
+        self.lock_book()
+            .issued
+            .iter()
+            .find(|i| i.invoice_number == invoice_number)
+            .cloned()
+    }
+
+    /// Invoices not yet paid or voided.
+    pub fn outstanding(&self) -> Vec<IssuedInvoice> {
+        self.filter_issued(|i| i.status == InvoiceStatus::Open)
+    }
+
+    /// Outstanding invoices past their due date at `now`.
+    pub fn overdue(&self, now: i64) -> Vec<IssuedInvoice> {
+        self.filter_issued(|i| i.is_overdue(now))
+    }
+
+    /// Outstanding invoices whose request has not reached the payer.
+    pub fn undelivered(&self) -> Vec<IssuedInvoice> {
+        self.filter_issued(|i| !i.delivered && i.status == InvoiceStatus::Open)
+    }
+
+    fn filter_issued(&self, keep: impl Fn(&IssuedInvoice) -> bool) -> Vec<IssuedInvoice> {
+        self.lock_book()
+            .issued
+            .iter()
+            .filter(|i| keep(i))
+            .cloned()
+            .collect()
+    }
+
+    fn lock_book(&self) -> MutexGuard<'_, InvoiceBook> {
+        self.book.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn invalid(message: &str) -> anyhow::Error {
+    SubscriptionError::InvalidArgument(message.to_string()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use rust_decimal_macros::dec;
+
+    fn ts(year: i32, month: u32, day: u32) -> i64 {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc()
+            .timestamp()
+    }
+
+    fn test_pubkey() -> PublicKey {
+        let keypair = pkarr::Keypair::random();
+        PublicKey::from_str(&keypair.public_key().to_z32()).unwrap()
+    }
+
+    fn template() -> InvoiceTemplate {
+        InvoiceTemplate::new(
+            "hosting",
+            vec![
+                InvoiceItem::new("VPS", 2, Amount::from_sats(20_000)).with_category("service"),
+                InvoiceItem::new("Setup guide", 1, Amount::from_sats(5_000)).with_category("book"),
+            ],
+        )
+        .with_tax(TaxRule::new("VAT", dec!(10)).for_categories(vec!["service".to_string()]))
+        .with_due(DueTerms::NetDays { days: 14 })
+        .with_number_prefix("INV-HOST")
+    }
+
+    fn schedule(payer: &PublicKey) -> RecurringInvoice {
+        RecurringInvoice::new(
+            "hosting-alice",
+            template(),
+            test_pubkey(),
+            payer.clone(),
+            "SAT",
+            MethodId("lightning".to_string()),
+            PaymentFrequency::Monthly { day_of_month: 1 },
+            ts(2024, 1, 15),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_template_renders_taxed_items() {
+        let invoice = template().render("INV-HOST-0001", ts(2024, 2, 1));
+
+        assert_eq!(invoice.subtotal, Amount::from_sats(45_000));
+        // Only the 40k of services is taxed
+        assert_eq!(
+            invoice.tax.as_ref().unwrap().amount,
+            Amount::from_sats(4_000)
+        );
+        assert_eq!(invoice.total, Amount::from_sats(49_000));
+        assert_eq!(invoice.due_date, Some(ts(2024, 2, 15)));
+        assert_eq!(invoice.terms.as_deref(), Some("Net 14"));
+
+        assert!(InvoiceTemplate::new("empty", vec![]).validate().is_err());
+        assert!(template()
+            .with_tax(TaxRule::new("Bad", dec!(150)))
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_scheduler_issues_one_invoice_per_cycle() {
+        let payer = test_pubkey();
+        let scheduler = InvoiceScheduler::new();
+        scheduler.add_schedule(schedule(&payer)).unwrap();
+        assert!(scheduler.add_schedule(schedule(&payer)).is_err());
+
+        // Billing starts on the first 1st after the start date
+        assert!(scheduler.generate_due(ts(2024, 1, 31)).unwrap().is_empty());
+        let requests = scheduler.generate_due(ts(2024, 2, 1)).unwrap();
+        assert_eq!(requests.len(), 1);
+        let request = &requests[0];
+        assert_eq!(request.invoice_number.as_deref(), Some("INV-HOST-0001"));
+        assert_eq!(request.amount, Amount::from_sats(49_000));
+        assert_eq!(request.to_invoice().total, request.amount);
+        assert_eq!(
+            request.metadata[INVOICE_METADATA_KEY]["schedule_id"],
+            "hosting-alice"
+        );
+
+        // Same cycle again is a no-op; the next one is on March 1st
+        assert!(scheduler.generate_due(ts(2024, 2, 20)).unwrap().is_empty());
+        let requests = scheduler.generate_due(ts(2024, 3, 1)).unwrap();
+        assert_eq!(requests[0].invoice_number.as_deref(), Some("INV-HOST-0002"));
+
+        assert_eq!(scheduler.outstanding().len(), 2);
+        assert_eq!(scheduler.undelivered().len(), 2);
+        scheduler.mark_delivered("INV-HOST-0001");
+        assert_eq!(scheduler.undelivered().len(), 1);
+        assert_eq!(scheduler.overdue(ts(2024, 3, 2)).len(), 1);
+
+        let restored = InvoiceScheduler::from_book(scheduler.book());
+        assert_eq!(restored.book().schedules[0].cycles_issued, 2);
+    }
+
+    #[test]
+    fn test_receipts_settle_invoices_and_hooks_run() {
+        struct Hooks {
+            paid: Mutex<Vec<String>>,
+        }
+        impl InvoiceHook for Hooks {
+            fn before_issue(
+                &self,
+                _schedule: &RecurringInvoice,
+                request: &mut PaymentRequest,
+            ) -> Result<()> {
+                request.notes = Some("Thank you".to_string());
+                Ok(())
+            }
+
+            fn on_paid(&self, invoice: &IssuedInvoice) {
+                self.paid
+                    .lock()
+                    .unwrap()
+                    .push(invoice.invoice_number.clone());
+            }
+        }
+
+        let payer = test_pubkey();
+        let scheduler = InvoiceScheduler::new();
+        let hooks = Arc::new(Hooks {
+            paid: Mutex::new(Vec::new()),
+        });
+        scheduler.add_hook(hooks.clone());
+        scheduler.add_schedule(schedule(&payer)).unwrap();
+        let request = scheduler.generate_due(ts(2024, 2, 1)).unwrap().remove(0);
+        assert_eq!(request.notes.as_deref(), Some("Thank you"));
+
+        let receipt = |amount: i64, payer: &PublicKey| {
+            PaykitReceipt::new(
+                format!("rcpt_{}", amount),
+                payer.clone(),
+                request.from.clone(),
+                request.method.clone(),
+                Some(amount.to_string()),
+                Some("SAT".to_string()),
+                request.metadata.clone(),
+            )
+        };
+
+        // Short payments and strangers do not settle the invoice
+        assert!(scheduler.record_receipt(&receipt(40_000, &payer)).is_none());
+        let stranger = test_pubkey();
+        assert!(scheduler
+            .record_receipt(&receipt(49_000, &stranger))
+            .is_none());
+
+        let paid = scheduler.record_receipt(&receipt(49_000, &payer)).unwrap();
+        assert!(matches!(paid.status, InvoiceStatus::Paid { .. }));
+        assert!(scheduler.record_receipt(&receipt(49_000, &payer)).is_none());
+        assert!(scheduler.outstanding().is_empty());
+        assert!(!scheduler.void("INV-HOST-0001"));
+        assert_eq!(*hooks.paid.lock().unwrap(), vec!["INV-HOST-0001"]);
+    }
+}
@@ -16,8 +16,13 @@
 //! - Storage operations now atomic
 
 pub mod amount;
+pub mod assignment;
 pub mod autopay;
+pub mod billing;
+pub mod calendar;
+pub mod cancellation;
 pub mod discovery;
+pub mod expectations;
 pub mod fallback;
 pub mod invoice;
 pub mod manager;
@@ -25,7 +30,9 @@ pub mod modifications;
 pub mod nonce_store;
 pub mod proration;
 pub mod request;
+pub mod retry;
 pub mod signing;
+pub mod status;
 pub mod storage;
 pub mod subscription;
 
@@ -38,11 +45,15 @@ pub mod monitor;
 // pub mod storage_wasm;
 
 pub use amount::Amount;
+pub use assignment::{SignedSubscriptionAssignment, SubscriptionAssignment};
 pub use invoice::{
-    Invoice, InvoiceFormat, InvoiceItem, ShippingAddress, ShippingInfo, ShippingMethod, TaxInfo,
+    DueTerms, Invoice, InvoiceFormat, InvoiceHook, InvoiceItem, InvoiceScheduler, InvoiceTemplate,
+    IssuedInvoice, RecurringInvoice, ShippingAddress, ShippingInfo, ShippingMethod, TaxHook,
+    TaxInfo, TaxRule,
 };
 pub use nonce_store::NonceStore;
 pub use request::{PaymentRequest, PaymentRequestResponse, RequestNotification, RequestStatus};
+pub use retry::{BackoffSchedule, RetryDecision, RetryPolicy, RetryPolicySet, RetryState};
 pub use storage::{Direction, RequestFilter, ReservationToken, SubscriptionStorage};
 
 // Platform-specific storage implementations
@@ -52,6 +63,16 @@ pub use storage::FileSubscriptionStorage;
 // WASM storage implementation (WasmSubscriptionStorage) is future work
 // See FINAL_SWEEP_REPORT.md for details
 pub use autopay::{AutoPayRule, PeerSpendingLimit};
+pub use billing::{BillingAllocation, BillingHistory, BillingRecord, ConsolidatedInvoice};
+pub use calendar::{ChargeCalendar, UpcomingCharge};
+pub use cancellation::{
+    CancelSubscription, CancellationAck, CancellationReason, CancellationRecord, CancellationState,
+    SignedCancellation, SignedCancellationAck,
+};
+pub use expectations::{
+    Escalation, ExpectationMonitor, ExpectationPolicy, ExpectationReport, ExpectedPayment,
+    MissedPayment, ObservedPayment,
+};
 pub use fallback::{FallbackHandler, FallbackRecord, FallbackStatus, SubscriptionFallbackPolicy};
 pub use manager::SubscriptionManager;
 pub use modifications::{
@@ -60,14 +81,15 @@ pub use modifications::{
 };
 pub use proration::{ProratedAmount, ProrationCalculator, ProrationDetails, RoundingMode};
 pub use signing::{sign_subscription_ed25519, verify_signature_ed25519, Signature};
+pub use status::{ServiceAccess, SignedStatusNotice, SubscriptionStatus, SubscriptionStatusNotice};
 pub use subscription::{PaymentFrequency, SignedSubscription, Subscription, SubscriptionTerms};
 
 // Re-export subscription discovery functions
 pub use discovery::{
     discover_subscription_agreement, discover_subscription_agreements,
     discover_subscription_cancellations, discover_subscription_proposal,
-    discover_subscription_proposals, PAYKIT_AGREEMENTS_PATH, PAYKIT_CANCELLATIONS_PATH,
-    PAYKIT_PROPOSALS_PATH,
+    discover_subscription_proposals, fetch_status_notice, publish_status_notice,
+    PAYKIT_AGREEMENTS_PATH, PAYKIT_CANCELLATIONS_PATH, PAYKIT_PROPOSALS_PATH,
 };
 
 // Monitor only available on native platforms
@@ -1,12 +1,23 @@
 use crate::{
+    assignment::SignedSubscriptionAssignment,
+    billing::{self, BillingAllocation},
+    calendar::ChargeCalendar,
+    cancellation::{
+        CancelSubscription, CancellationReason, CancellationRecord, SignedCancellation,
+        SignedCancellationAck,
+    },
     signing::{self, Signature},
-    NonceStore, PaymentRequest, PaymentRequestResponse, RequestStatus, Result, SignedSubscription,
-    Subscription, SubscriptionStorage,
+    status::SignedStatusNotice,
+    ModificationRequest, NonceStore, PaymentRequest, PaymentRequestResponse, RequestStatus, Result,
+    SignedSubscription, Subscription, SubscriptionStorage,
 };
 use paykit_interactive::{PaykitInteractiveManager, PaykitNoiseChannel, PaykitNoiseMessage};
-use paykit_lib::protocol::{subscription_proposal_aad, subscription_proposal_path};
+use paykit_lib::policy::{SharedTrustPolicy, TrustDecision, VelocityGuard, VelocityVerdict};
+use paykit_lib::protocol::{
+    noise_endpoint_path, subscription_proposal_aad, subscription_proposal_path, PathRootConfig,
+};
 use paykit_lib::PublicKey;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -18,10 +29,10 @@ pub enum SubscriptionMessage {
     PaymentRequestResponse(Box<PaymentRequestResponse>),
     SubscriptionProposal(Box<Subscription>),
     SubscriptionAcceptance(Box<SignedSubscription>),
-    SubscriptionCancellation {
-        subscription_id: String,
-        reason: Option<String>,
-    },
+    SubscriptionCancellation(Box<SignedCancellation>),
+    CancellationAck(Box<SignedCancellationAck>),
+    SubscriptionAssignment(Box<SignedSubscriptionAssignment>),
+    StatusNotice(Box<SignedStatusNotice>),
 }
 
 pub struct SubscriptionManager {
@@ -33,6 +44,12 @@ pub struct SubscriptionManager {
     my_noise_sk: Option<[u8; 32]>,
     /// Cache of peer Noise public keys (pubkey -> noise_pk)
     noise_pk_cache: Arc<RwLock<HashMap<String, [u8; 32]>>>,
+    /// Payee allowlist/blocklist consulted before auto-paying
+    trust_policy: Option<SharedTrustPolicy>,
+    /// Outgoing payment rate tracking for anomaly holds
+    velocity_guard: Option<Arc<VelocityGuard>>,
+    /// Root objects are written under, and roots peers are looked up in
+    path_roots: PathRootConfig,
 }
 
 impl SubscriptionManager {
@@ -47,6 +64,9 @@ impl SubscriptionManager {
             nonce_store: Arc::new(NonceStore::new()),
             my_noise_sk: None,
             noise_pk_cache: Arc::new(RwLock::new(HashMap::new())),
+            trust_policy: None,
+            velocity_guard: None,
+            path_roots: PathRootConfig::default(),
         }
     }
 
@@ -61,6 +81,58 @@ impl SubscriptionManager {
         self
     }
 
+    /// Consult a trust policy before auto-paying.
+    ///
+    /// Blocked providers are never paid, and unknown providers are not
+    /// auto-paid while the policy requires confirmation for them.
+    pub fn with_trust_policy(mut self, policy: SharedTrustPolicy) -> Self {
+        self.trust_policy = Some(policy);
+        self
+    }
+
+    /// Hold anomalous auto-payments for confirmation.
+    ///
+    /// Held payments are surfaced through the guard's event callbacks; once
+    /// approved, the next auto-pay attempt for the same amount proceeds.
+    pub fn with_velocity_guard(mut self, guard: Arc<VelocityGuard>) -> Self {
+        self.velocity_guard = Some(guard);
+        self
+    }
+
+    /// Write under a white-label root, and look peers up in its read roots.
+    ///
+    /// Encryption AAD always uses the canonical `paykit.app` path, so
+    /// objects stay readable by peers configured with a different root.
+    pub fn with_path_roots(mut self, roots: PathRootConfig) -> Self {
+        self.path_roots = roots;
+        self
+    }
+
+    /// Check an outgoing payment against the velocity guard, if one is configured
+    fn velocity_check(&self, request: &PaymentRequest) -> VelocityVerdict {
+        match &self.velocity_guard {
+            Some(guard) => guard.check(
+                &request.from.to_string(),
+                request.amount.as_sats().max(0) as u64,
+                chrono::Utc::now().timestamp(),
+            ),
+            None => VelocityVerdict::Allow,
+        }
+    }
+
+    /// Evaluate a payee against the trust policy, if one is configured
+    fn trust_decision(&self, payee: &PublicKey) -> Result<TrustDecision> {
+        match &self.trust_policy {
+            Some(policy) => {
+                let policy = policy
+                    .read()
+                    .map_err(|_| anyhow::anyhow!("Trust policy lock poisoned"))?;
+                Ok(policy.evaluate(payee))
+            }
+            None => Ok(TrustDecision::Allow),
+        }
+    }
+
     /// Get the Noise secret key if configured
     pub fn noise_sk(&self) -> Option<&[u8; 32]> {
         self.my_noise_sk.as_ref()
@@ -325,12 +397,21 @@ impl SubscriptionManager {
     }
 
     /// Cancel a subscription
+    ///
+    /// Either party may cancel. The signed cancellation is recorded locally,
+    /// sent to the counterparty, and finalized once its signed acknowledgment
+    /// comes back. If the counterparty does not acknowledge, the record stays
+    /// awaiting acknowledgment. With `publish`, the final record is also
+    /// stored encrypted for both parties in Pubky.
     pub async fn cancel_subscription(
         &self,
         channel: &mut dyn PaykitNoiseChannel,
         subscription_id: &str,
-        reason: Option<String>,
-    ) -> Result<()> {
+        keypair: &pubky::Keypair,
+        reason: CancellationReason,
+        note: Option<String>,
+        publish: bool,
+    ) -> Result<CancellationRecord> {
         // Load subscription
         let subscription = self
             .storage
@@ -338,21 +419,315 @@ impl SubscriptionManager {
             .await?
             .ok_or_else(|| anyhow::anyhow!("Subscription {} not found", subscription_id))?;
 
-        // Mark as cancelled locally (we could add status field)
-        // For now, we just don't return it from list_active_subscriptions
+        let mut cancellation =
+            CancelSubscription::new(&subscription.subscription, &keypair.public_key(), reason)?;
+        cancellation.note = note;
+        let signed = SignedCancellation::sign(cancellation, keypair)?;
+        if !self
+            .nonce_store
+            .as_ref()
+            .check_and_mark(&signed.signature.nonce, signed.signature.expires_at)?
+        {
+            return Err(anyhow::anyhow!("Nonce already used"));
+        }
 
-        // Send cancellation message
-        channel.send(PaykitNoiseMessage::Ack).await?;
+        // Record locally before sending so the cancellation survives a dropped channel
+        let mut record = CancellationRecord::new(signed.clone());
+        self.storage.save_cancellation(&record).await?;
 
-        // Store cancellation in Pubky
-        if let Some(session) = &self.pubky_session {
-            self.store_subscription_cancellation(session, &subscription, reason)
+        // Send cancellation and wait for the counterparty's acknowledgment
+        channel
+            .send(PaykitNoiseMessage::CancelSubscription {
+                cancellation: serde_json::to_value(&signed)?,
+            })
+            .await?;
+        match channel.recv().await? {
+            PaykitNoiseMessage::CancelSubscriptionAck { ack } => {
+                record.acknowledge(serde_json::from_value(ack)?)?;
+            }
+            PaykitNoiseMessage::Error { code, message } => {
+                return Err(anyhow::anyhow!(
+                    "Cancellation of {} rejected: {} ({})",
+                    subscription_id,
+                    message,
+                    code
+                ));
+            }
+            other => {
+                return Err(anyhow::anyhow!(
+                    "Unexpected reply to cancellation: {:?}",
+                    other
+                ));
+            }
+        }
+        self.storage.save_cancellation(&record).await?;
+
+        if publish {
+            self.publish_cancellation(&subscription, &record).await?;
+        }
+
+        Ok(record)
+    }
+
+    /// Handle a cancellation received from the counterparty
+    ///
+    /// Verifies it against the stored agreement, records it as final and
+    /// replies with a signed acknowledgment. On failure an error is sent back
+    /// instead.
+    pub async fn handle_cancellation(
+        &self,
+        channel: &mut dyn PaykitNoiseChannel,
+        cancellation: serde_json::Value,
+        keypair: &pubky::Keypair,
+        publish: bool,
+    ) -> Result<CancellationRecord> {
+        let result = self.acknowledge_cancellation(cancellation, keypair).await;
+        let (subscription, record) = match result {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                channel
+                    .send(PaykitNoiseMessage::Error {
+                        code: "cancellation_rejected".to_string(),
+                        message: e.to_string(),
+                    })
+                    .await?;
+                return Err(e);
+            }
+        };
+
+        if let Some(ack) = &record.ack {
+            channel
+                .send(PaykitNoiseMessage::CancelSubscriptionAck {
+                    ack: serde_json::to_value(ack)?,
+                })
                 .await?;
         }
 
+        if publish {
+            self.publish_cancellation(&subscription, &record).await?;
+        }
+
+        Ok(record)
+    }
+
+    async fn acknowledge_cancellation(
+        &self,
+        cancellation: serde_json::Value,
+        keypair: &pubky::Keypair,
+    ) -> Result<(SignedSubscription, CancellationRecord)> {
+        let signed: SignedCancellation = serde_json::from_value(cancellation)?;
+        let subscription_id = &signed.cancellation.subscription_id;
+        let subscription = self
+            .storage
+            .get_signed_subscription(subscription_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Subscription {} not found", subscription_id))?;
+
+        if !signed.verify_for(&subscription.subscription)? {
+            anyhow::bail!(
+                "Cancellation of {} is not signed by a party to it",
+                subscription_id
+            );
+        }
+        if !self
+            .nonce_store
+            .as_ref()
+            .check_and_mark(&signed.signature.nonce, signed.signature.expires_at)?
+        {
+            anyhow::bail!("Cancellation nonce already used (replay attack)");
+        }
+
+        let ack = signed.acknowledge(keypair)?;
+        let mut record = CancellationRecord::new(signed);
+        record.acknowledge(ack)?;
+        self.storage.save_cancellation(&record).await?;
+
+        Ok((subscription, record))
+    }
+
+    /// Get the recorded cancellation of a subscription
+    pub async fn get_cancellation(
+        &self,
+        subscription_id: &str,
+    ) -> Result<Option<CancellationRecord>> {
+        self.storage.get_cancellation(subscription_id).await
+    }
+
+    async fn publish_cancellation(
+        &self,
+        subscription: &SignedSubscription,
+        record: &CancellationRecord,
+    ) -> Result<()> {
+        let session = self
+            .pubky_session
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Publishing cancellations requires a Pubky session"))?;
+        self.store_subscription_cancellation(session, subscription, record)
+            .await
+    }
+
+    /// Check an assignment against the stored agreement it transfers
+    ///
+    /// Valid only if signed by the current provider and countersigned by the
+    /// new one.
+    pub async fn verify_assignment(
+        &self,
+        assignment: &SignedSubscriptionAssignment,
+    ) -> Result<bool> {
+        let subscription_id = &assignment.assignment.subscription_id;
+        let current = self
+            .storage
+            .get_signed_subscription(subscription_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Subscription {} not found", subscription_id))?;
+        assignment.verify_for(&current.subscription)
+    }
+
+    /// Accept the transfer of a subscription to a new provider
+    ///
+    /// Called by the subscriber. Re-signs the transferred agreement and
+    /// repoints its auto-pay rule at the new provider in one storage update.
+    pub async fn accept_assignment(
+        &self,
+        assignment: &SignedSubscriptionAssignment,
+        keypair: &pubky::Keypair,
+    ) -> Result<SignedSubscription> {
+        let subscription_id = &assignment.assignment.subscription_id;
+        let current = self
+            .storage
+            .get_signed_subscription(subscription_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Subscription {} not found", subscription_id))?;
+
+        if keypair.public_key() != current.subscription.subscriber {
+            return Err(anyhow::anyhow!(
+                "Only the subscriber can accept an assignment"
+            ));
+        }
+        if !assignment.verify_for(&current.subscription)? {
+            return Err(anyhow::anyhow!(
+                "Assignment of {} is not signed by both providers",
+                subscription_id
+            ));
+        }
+        if !self
+            .nonce_store
+            .as_ref()
+            .check_and_mark(&assignment.signature.nonce, assignment.signature.expires_at)?
+        {
+            return Err(anyhow::anyhow!(
+                "Assignment nonce already used (replay attack)"
+            ));
+        }
+
+        let transferred = assignment.assignment.transferred(&current.subscription);
+        let new_provider_signature = assignment
+            .new_provider_signature
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("Assignment is missing the new provider signature"))?;
+        let nonce = rand::random::<[u8; 32]>();
+        let subscriber_signature = signing::sign_subscription_ed25519(
+            &transferred,
+            keypair,
+            &nonce,
+            3600 * 24 * 7, // 7 days
+        )?;
+        let signed =
+            SignedSubscription::new(transferred, subscriber_signature, new_provider_signature);
+        if !signed.verify_signatures()? {
+            return Err(anyhow::anyhow!("Signature verification failed"));
+        }
+
+        let rule = self
+            .storage
+            .get_autopay_rule(subscription_id)
+            .await?
+            .map(|mut rule| {
+                rule.peer = assignment.assignment.to_provider.clone();
+                rule
+            });
+        self.storage
+            .save_assigned_subscription(&signed, rule.as_ref())
+            .await?;
+
+        if let Some(session) = &self.pubky_session {
+            self.store_signed_subscription(session, &signed).await?;
+        }
+
+        Ok(signed)
+    }
+
+    /// Publish a signed status notice for the subscriber to fetch
+    ///
+    /// Requires a Pubky session; the notice is encrypted to the subscriber.
+    pub async fn publish_status_notice(&self, signed: &SignedStatusNotice) -> Result<()> {
+        let session = self
+            .pubky_session
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Publishing status notices requires a Pubky session"))?;
+        let subscriber_noise_pk = self.discover_noise_pk(&signed.notice.subscriber).await?;
+        let transport = paykit_lib::PubkyAuthenticatedTransport::new(session.clone());
+        crate::discovery::publish_status_notice(&transport, signed, &subscriber_noise_pk).await
+    }
+
+    /// Whether published data can be written to the homeserver
+    pub fn has_pubky_session(&self) -> bool {
+        self.pubky_session.is_some()
+    }
+
+    /// Publish a payment request to the payer's request mailbox
+    ///
+    /// Requires a Pubky session; the request is encrypted to the payer.
+    pub async fn publish_payment_request(&self, request: &PaymentRequest) -> Result<()> {
+        let session = self.pubky_session.as_ref().ok_or_else(|| {
+            anyhow::anyhow!("Publishing payment requests requires a Pubky session")
+        })?;
+        let payer_noise_pk = self.discover_noise_pk(&request.to).await?;
+        let transport = paykit_lib::PubkyAuthenticatedTransport::new(session.clone());
+        crate::discovery::publish_payment_request(&transport, request, &payer_noise_pk).await
+    }
+
+    /// Push a signed status notice to the subscriber over Noise
+    pub async fn send_status_notice(
+        &self,
+        channel: &mut dyn PaykitNoiseChannel,
+        signed: &SignedStatusNotice,
+    ) -> Result<()> {
+        channel
+            .send(PaykitNoiseMessage::SubscriptionStatus {
+                notice: serde_json::to_value(signed)?,
+            })
+            .await?;
         Ok(())
     }
 
+    /// Accept a status notice received over Noise
+    ///
+    /// The notice must be about a known subscription and validly signed by
+    /// that subscription's provider.
+    pub async fn receive_status_notice(
+        &self,
+        notice: serde_json::Value,
+    ) -> Result<SignedStatusNotice> {
+        let signed: SignedStatusNotice = serde_json::from_value(notice)?;
+        let subscription_id = &signed.notice.subscription_id;
+        let subscription = self
+            .storage
+            .get_signed_subscription(subscription_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Subscription {} not found", subscription_id))?;
+
+        if signed.notice.subscriber != subscription.subscription.subscriber
+            || !signed.verify_for(&subscription.subscription.provider, subscription_id)?
+        {
+            anyhow::bail!(
+                "Status notice for {} is not signed by its provider",
+                subscription_id
+            );
+        }
+        Ok(signed)
+    }
+
     /// List active subscriptions with a peer
     pub async fn list_subscriptions_with_peer(
         &self,
@@ -366,13 +741,50 @@ impl SubscriptionManager {
         self.storage.list_active_subscriptions().await
     }
 
+    /// Record an agreed modification so it shows up in projected charges
+    pub async fn schedule_modification(&self, request: &ModificationRequest) -> Result<()> {
+        let signed = self
+            .storage
+            .get_signed_subscription(&request.subscription_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Subscription not found"))?;
+
+        request.validate(&signed.subscription)?;
+        self.storage.save_modification(request).await
+    }
+
+    /// List modifications scheduled for a subscription
+    pub async fn list_modifications(
+        &self,
+        subscription_id: &str,
+    ) -> Result<Vec<ModificationRequest>> {
+        self.storage.list_modifications(subscription_id).await
+    }
+
+    /// Calendar of charges due on active subscriptions over the next `days` days
+    ///
+    /// Amounts reflect scheduled modifications as of each due date.
+    pub async fn get_upcoming_charges(&self, days: u32) -> Result<ChargeCalendar> {
+        let mut calendar = ChargeCalendar::new(chrono::Utc::now().timestamp(), days)?;
+
+        for signed in self.storage.list_active_subscriptions().await? {
+            let id = &signed.subscription.subscription_id;
+            let rule = self.storage.get_autopay_rule(id).await?;
+            let modifications = self.storage.list_modifications(id).await?;
+            calendar.add_subscription(&signed.subscription, rule.as_ref(), &modifications);
+        }
+
+        Ok(calendar)
+    }
+
     // ============================================================
     // Private helper methods for Pubky storage
     // ============================================================
 
     /// Discover the Noise public key for a peer from their `/pub/paykit.app/v0/noise` endpoint.
     ///
-    /// Uses caching to avoid repeated network requests.
+    /// Each configured read root is tried in order. Uses caching to avoid
+    /// repeated network requests.
     async fn discover_noise_pk(&self, pubkey: &PublicKey) -> Result<[u8; 32]> {
         let pubkey_str = pubkey.to_string();
 
@@ -388,19 +800,30 @@ impl SubscriptionManager {
         let public_storage = pubky::PublicStorage::new()
             .map_err(|e| anyhow::anyhow!("Failed to create public storage: {}", e))?;
 
-        let path = format!("pubky://{}/pub/paykit.app/v0/noise", pubkey_str);
-        let response = public_storage
-            .get(&path)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to fetch noise endpoint: {}", e))?;
-
-        let content = response
-            .bytes()
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to read noise endpoint response: {}", e))?;
+        let mut content = Vec::new();
+        let mut last_error = None;
+        for path in self.path_roots.read_paths(noise_endpoint_path()) {
+            let url = format!("pubky://{}{}", pubkey_str, path);
+            let response = match public_storage.get(&url).await {
+                Ok(response) => response,
+                Err(e) => {
+                    last_error = Some(anyhow::anyhow!("Failed to fetch noise endpoint: {}", e));
+                    continue;
+                }
+            };
+            let bytes = response
+                .bytes()
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to read noise endpoint response: {}", e))?;
+            if !bytes.is_empty() {
+                content = bytes.to_vec();
+                break;
+            }
+        }
 
         if content.is_empty() {
-            anyhow::bail!("No noise endpoint found for {}", pubkey_str);
+            return Err(last_error
+                .unwrap_or_else(|| anyhow::anyhow!("No noise endpoint found for {}", pubkey_str)));
         }
 
         // Parse the noise endpoint JSON to extract public key
@@ -477,7 +900,7 @@ impl SubscriptionManager {
         // Store encrypted envelope on provider storage
         session
             .storage()
-            .put(path, envelope.as_bytes().to_vec())
+            .put(self.path_roots.rebase(&path), envelope.as_bytes().to_vec())
             .await
             .map_err(|e| anyhow::anyhow!("Failed to store proposal: {}", e))?;
 
@@ -519,7 +942,10 @@ impl SubscriptionManager {
 
         session
             .storage()
-            .put(path_subscriber, envelope_subscriber.as_bytes().to_vec())
+            .put(
+                self.path_roots.rebase(&path_subscriber),
+                envelope_subscriber.as_bytes().to_vec(),
+            )
             .await
             .map_err(|e| anyhow::anyhow!("Failed to store subscription for subscriber: {}", e))?;
 
@@ -545,7 +971,10 @@ impl SubscriptionManager {
 
         session
             .storage()
-            .put(path_provider, envelope_provider.as_bytes().to_vec())
+            .put(
+                self.path_roots.rebase(&path_provider),
+                envelope_provider.as_bytes().to_vec(),
+            )
             .await
             .map_err(|e| anyhow::anyhow!("Failed to store subscription for provider: {}", e))?;
 
@@ -559,14 +988,9 @@ impl SubscriptionManager {
         &self,
         session: &pubky::PubkySession,
         subscription: &SignedSubscription,
-        reason: Option<String>,
+        record: &CancellationRecord,
     ) -> Result<()> {
-        let cancellation = serde_json::json!({
-            "subscription_id": subscription.subscription.subscription_id,
-            "reason": reason,
-            "cancelled_at": chrono::Utc::now().timestamp(),
-        });
-        let plaintext = serde_json::to_vec(&cancellation)?;
+        let plaintext = serde_json::to_vec(record)?;
 
         // Store for subscriber (encrypted to subscriber's Noise PK)
         let path_subscriber = format!(
@@ -590,7 +1014,10 @@ impl SubscriptionManager {
 
         session
             .storage()
-            .put(path_subscriber, envelope_subscriber.as_bytes().to_vec())
+            .put(
+                self.path_roots.rebase(&path_subscriber),
+                envelope_subscriber.as_bytes().to_vec(),
+            )
             .await
             .map_err(|e| anyhow::anyhow!("Failed to store cancellation for subscriber: {}", e))?;
 
@@ -616,7 +1043,10 @@ impl SubscriptionManager {
 
         session
             .storage()
-            .put(path_provider, envelope_provider.as_bytes().to_vec())
+            .put(
+                self.path_roots.rebase(&path_provider),
+                envelope_provider.as_bytes().to_vec(),
+            )
             .await
             .map_err(|e| anyhow::anyhow!("Failed to store cancellation for provider: {}", e))?;
 
@@ -629,10 +1059,25 @@ impl SubscriptionManager {
 
     /// Check if payment request should be auto-paid
     pub async fn should_autopay(&self, request: &PaymentRequest) -> Result<bool> {
+        // Consolidated invoices are checked line by line
+        if let Some(allocations) = billing::allocations_from_request(request) {
+            return self.should_autopay_batch(request, &allocations).await;
+        }
+
         // Check if request is from a valid subscription
         let subscription = self.find_matching_subscription(request).await?;
 
         if let Some(sub) = subscription {
+            // Blocked or unconfirmed providers always need the user
+            if !self.trust_decision(&request.from)?.is_allowed() {
+                return Ok(false);
+            }
+
+            // Anomalous payments wait for the user to approve the hold
+            if !self.velocity_check(request).is_allowed() {
+                return Ok(false);
+            }
+
             // Check auto-pay rule
             let rule = self
                 .storage
@@ -666,6 +1111,83 @@ impl SubscriptionManager {
         Ok(false)
     }
 
+    /// Check a consolidated invoice against each billed subscription
+    ///
+    /// Every allocation must belong to an active subscription with the
+    /// provider, match its terms, and be allowed by its auto-pay rule. The
+    /// allocations must add up to the requested amount, and each subscription
+    /// may be billed only once, so its total is the single line checked.
+    async fn should_autopay_batch(
+        &self,
+        request: &PaymentRequest,
+        allocations: &[BillingAllocation],
+    ) -> Result<bool> {
+        if allocations.is_empty() {
+            return Ok(false);
+        }
+
+        // A subscription billed twice would pass every per-line check
+        let mut billed = HashSet::new();
+        if !allocations
+            .iter()
+            .all(|a| billed.insert(a.subscription_id.as_str()))
+        {
+            return Ok(false);
+        }
+
+        let allocated = allocations
+            .iter()
+            .try_fold(crate::Amount::zero(), |acc, a| acc.checked_add(&a.amount));
+        if allocated != Some(request.amount) {
+            return Ok(false);
+        }
+
+        if !self.trust_decision(&request.from)?.is_allowed() {
+            return Ok(false);
+        }
+        if !self.velocity_check(request).is_allowed() {
+            return Ok(false);
+        }
+
+        let subs = self
+            .storage
+            .list_subscriptions_with_peer(&request.from)
+            .await?;
+        for allocation in allocations {
+            let Some(sub) = subs
+                .iter()
+                .find(|s| s.subscription.subscription_id == allocation.subscription_id)
+            else {
+                return Ok(false);
+            };
+            if !self.is_billable(sub).await? || sub.subscription.provider != request.from {
+                return Ok(false);
+            }
+
+            // Each line must match its subscription as a standalone request would
+            let mut line = request.clone();
+            line.amount = allocation.amount;
+            if !self.matches_subscription_terms(sub, &line) {
+                return Ok(false);
+            }
+
+            match self
+                .storage
+                .get_autopay_rule(&allocation.subscription_id)
+                .await?
+            {
+                Some(rule)
+                    if rule.enabled
+                        && !rule.require_confirmation
+                        && rule.is_amount_within_limit(&allocation.amount) => {}
+                _ => return Ok(false),
+            }
+        }
+
+        // Peer limits apply to the single combined payment
+        self.check_peer_limits(request).await
+    }
+
     /// Find subscription matching payment request
     async fn find_matching_subscription(
         &self,
@@ -679,7 +1201,7 @@ impl SubscriptionManager {
 
         // Find active subscription matching terms
         for sub in subs {
-            if sub.is_active() && self.matches_subscription_terms(&sub, request) {
+            if self.matches_subscription_terms(&sub, request) && self.is_billable(&sub).await? {
                 return Ok(Some(sub));
             }
         }
@@ -687,6 +1209,16 @@ impl SubscriptionManager {
         Ok(None)
     }
 
+    /// Whether a subscription is active and has not been cancelled
+    async fn is_billable(&self, sub: &SignedSubscription) -> Result<bool> {
+        Ok(sub.is_active()
+            && self
+                .storage
+                .get_cancellation(&sub.subscription.subscription_id)
+                .await?
+                .is_none())
+    }
+
     /// Check if request matches subscription terms
     fn matches_subscription_terms(
         &self,
@@ -761,6 +1293,25 @@ impl SubscriptionManager {
         request: PaymentRequest,
         local_pk: &PublicKey,
     ) -> Result<paykit_interactive::PaykitReceipt> {
+        if let TrustDecision::Block { reason } = self.trust_decision(&request.from)? {
+            anyhow::bail!(
+                "Provider is blocked{}",
+                reason.map(|r| format!(": {}", r)).unwrap_or_default()
+            );
+        }
+
+        if let VelocityVerdict::Hold(held) = self.velocity_check(&request) {
+            anyhow::bail!(
+                "Payment held for confirmation ({}): {}",
+                held.hold_id,
+                held.anomalies
+                    .iter()
+                    .map(|a| a.describe())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+
         // Phase 4: Atomic check-and-reserve spending limit
         let reservation = self
             .storage
@@ -789,6 +1340,13 @@ impl SubscriptionManager {
             Ok(receipt) => {
                 // Payment succeeded - commit the reservation
                 self.storage.commit_spending(reservation).await?;
+                if let Some(guard) = &self.velocity_guard {
+                    guard.record(
+                        &request.from.to_string(),
+                        request.amount.as_sats().max(0) as u64,
+                        chrono::Utc::now().timestamp(),
+                    );
+                }
                 self.storage
                     .update_request_status(&request.request_id, RequestStatus::Fulfilled)
                     .await?;
@@ -1078,4 +1636,485 @@ mod tests {
         request.expires_at = None;
         assert!(manager.validate_request(&request).is_ok());
     }
+
+    #[tokio::test]
+    async fn test_execute_autopay_blocked_provider() {
+        let temp_dir = tempdir().unwrap();
+        let storage: Arc<Box<dyn SubscriptionStorage>> = Arc::new(Box::new(
+            FileSubscriptionStorage::new(temp_dir.path().to_path_buf()).unwrap(),
+        ));
+
+        let mock_storage: Arc<Box<dyn PaykitStorage>> = Arc::new(Box::new(MockStorage));
+        let mock_generator: Arc<Box<dyn ReceiptGenerator>> = Arc::new(Box::new(MockGenerator));
+
+        let provider = test_pubkey();
+        let mut policy = paykit_lib::policy::TrustPolicy::new();
+        policy.block(&provider.to_string(), Some("chargeback fraud"));
+
+        let interactive = Arc::new(PaykitInteractiveManager::new(mock_storage, mock_generator));
+        let manager =
+            SubscriptionManager::new(storage, interactive).with_trust_policy(policy.shared());
+
+        let me = test_pubkey();
+        let request = PaymentRequest::new(
+            provider,
+            me.clone(),
+            Amount::from_sats(1000),
+            "SAT".to_string(),
+            MethodId("lightning".to_string()),
+        );
+
+        let mut channel = MockChannel;
+        let err = manager
+            .execute_autopay(&mut channel, request, &me)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("chargeback fraud"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_autopay_velocity_hold() {
+        let temp_dir = tempdir().unwrap();
+        let storage: Arc<Box<dyn SubscriptionStorage>> = Arc::new(Box::new(
+            FileSubscriptionStorage::new(temp_dir.path().to_path_buf()).unwrap(),
+        ));
+
+        let mock_storage: Arc<Box<dyn PaykitStorage>> = Arc::new(Box::new(MockStorage));
+        let mock_generator: Arc<Box<dyn ReceiptGenerator>> = Arc::new(Box::new(MockGenerator));
+
+        let guard = Arc::new(VelocityGuard::new(paykit_lib::policy::VelocityConfig {
+            max_payments_per_window: 0,
+            ..Default::default()
+        }));
+
+        let interactive = Arc::new(PaykitInteractiveManager::new(mock_storage, mock_generator));
+        let manager =
+            SubscriptionManager::new(storage, interactive).with_velocity_guard(guard.clone());
+
+        let provider = test_pubkey();
+        let me = test_pubkey();
+        let request = PaymentRequest::new(
+            provider,
+            me.clone(),
+            Amount::from_sats(1000),
+            "SAT".to_string(),
+            MethodId("lightning".to_string()),
+        );
+
+        let mut channel = MockChannel;
+        let err = manager
+            .execute_autopay(&mut channel, request, &me)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("held for confirmation"));
+        assert_eq!(guard.held_payments().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_should_autopay_consolidated_invoice() {
+        let temp_dir = tempdir().unwrap();
+        let storage: Arc<Box<dyn SubscriptionStorage>> = Arc::new(Box::new(
+            FileSubscriptionStorage::new(temp_dir.path().to_path_buf()).unwrap(),
+        ));
+
+        let mock_storage: Arc<Box<dyn PaykitStorage>> = Arc::new(Box::new(MockStorage));
+        let mock_generator: Arc<Box<dyn ReceiptGenerator>> = Arc::new(Box::new(MockGenerator));
+        let interactive = Arc::new(PaykitInteractiveManager::new(mock_storage, mock_generator));
+        let manager = SubscriptionManager::new(storage.clone(), interactive);
+
+        let provider = test_pubkey();
+        let me = test_pubkey();
+        let mut group = Vec::new();
+        for sats in [1000, 2500] {
+            let terms = crate::SubscriptionTerms::new(
+                Amount::from_sats(sats),
+                "SAT".to_string(),
+                crate::PaymentFrequency::Monthly { day_of_month: 1 },
+                MethodId("lightning".to_string()),
+                format!("Plan {}", sats),
+            );
+            let mut subscription = Subscription::new(me.clone(), provider.clone(), terms);
+            subscription.subscription_id = format!("sub_{}", sats);
+            let keypair = pkarr::Keypair::random();
+            let sig1 =
+                signing::sign_subscription_ed25519(&subscription, &keypair, &rand::random(), 3600)
+                    .unwrap();
+            let sig2 =
+                signing::sign_subscription_ed25519(&subscription, &keypair, &rand::random(), 3600)
+                    .unwrap();
+            let signed = SignedSubscription::new(subscription, sig1, sig2);
+            storage.save_signed_subscription(&signed).await.unwrap();
+            storage
+                .save_autopay_rule(&crate::AutoPayRule::new(
+                    signed.subscription.subscription_id.clone(),
+                    provider.clone(),
+                    MethodId("lightning".to_string()),
+                ))
+                .await
+                .unwrap();
+            group.push(signed);
+        }
+
+        let consolidated = billing::consolidate(&group, chrono::Utc::now().timestamp(), 0).unwrap();
+        assert!(manager.should_autopay(&consolidated.request).await.unwrap());
+
+        // An allocation that does not match its subscription needs the user
+        let mut inflated = consolidated.request.clone();
+        inflated.metadata[billing::BILLING_METADATA_KEY]["allocations"][1]["amount"] =
+            serde_json::to_value(Amount::from_sats(5000)).unwrap();
+        inflated.amount = Amount::from_sats(6000);
+        assert!(!manager.should_autopay(&inflated).await.unwrap());
+
+        // So does a total that differs from the allocations
+        let mut padded = consolidated.request.clone();
+        padded.amount = Amount::from_sats(4000);
+        assert!(!manager.should_autopay(&padded).await.unwrap());
+
+        // And a subscription billed twice at its normal amount
+        let mut duplicated = consolidated.request;
+        let allocations = &mut duplicated.metadata[billing::BILLING_METADATA_KEY]["allocations"];
+        allocations[1] = allocations[0].clone();
+        duplicated.amount = Amount::from_sats(2000);
+        assert!(!manager.should_autopay(&duplicated).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_receive_status_notice() {
+        let temp_dir = tempdir().unwrap();
+        let storage: Arc<Box<dyn SubscriptionStorage>> = Arc::new(Box::new(
+            FileSubscriptionStorage::new(temp_dir.path().to_path_buf()).unwrap(),
+        ));
+
+        let mock_storage: Arc<Box<dyn PaykitStorage>> = Arc::new(Box::new(MockStorage));
+        let mock_generator: Arc<Box<dyn ReceiptGenerator>> = Arc::new(Box::new(MockGenerator));
+        let interactive = Arc::new(PaykitInteractiveManager::new(mock_storage, mock_generator));
+        let manager = SubscriptionManager::new(storage.clone(), interactive);
+
+        let provider = pkarr::Keypair::random();
+        let subscription = Subscription::new(
+            test_pubkey(),
+            provider.public_key(),
+            crate::SubscriptionTerms::new(
+                Amount::from_sats(1000),
+                "SAT".to_string(),
+                crate::PaymentFrequency::Monthly { day_of_month: 1 },
+                MethodId("lightning".to_string()),
+                "Pro plan".to_string(),
+            ),
+        );
+        let sig1 =
+            signing::sign_subscription_ed25519(&subscription, &provider, &rand::random(), 3600)
+                .unwrap();
+        let sig2 =
+            signing::sign_subscription_ed25519(&subscription, &provider, &rand::random(), 3600)
+                .unwrap();
+        storage
+            .save_signed_subscription(&SignedSubscription::new(subscription.clone(), sig1, sig2))
+            .await
+            .unwrap();
+
+        let notice = crate::SubscriptionStatusNotice::past_due(&subscription, i64::MAX);
+        let signed = SignedStatusNotice::sign(notice, &provider, 3600).unwrap();
+        let received = manager
+            .receive_status_notice(serde_json::to_value(&signed).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(received.notice.status, crate::SubscriptionStatus::PastDue);
+
+        let mut forged = signed;
+        forged.notice.status = crate::SubscriptionStatus::Current;
+        assert!(manager
+            .receive_status_notice(serde_json::to_value(&forged).unwrap())
+            .await
+            .is_err());
+    }
+
+    /// In-memory channel end connected to a peer end.
+    struct PairedChannel {
+        tx: tokio::sync::mpsc::UnboundedSender<PaykitNoiseMessage>,
+        rx: tokio::sync::mpsc::UnboundedReceiver<PaykitNoiseMessage>,
+    }
+
+    fn channel_pair() -> (PairedChannel, PairedChannel) {
+        let (a_tx, b_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (b_tx, a_rx) = tokio::sync::mpsc::unbounded_channel();
+        (
+            PairedChannel { tx: a_tx, rx: a_rx },
+            PairedChannel { tx: b_tx, rx: b_rx },
+        )
+    }
+
+    #[async_trait::async_trait]
+    impl PaykitNoiseChannel for PairedChannel {
+        async fn send(&mut self, msg: PaykitNoiseMessage) -> paykit_interactive::Result<()> {
+            let _ = self.tx.send(msg);
+            Ok(())
+        }
+        async fn recv(&mut self) -> paykit_interactive::Result<PaykitNoiseMessage> {
+            Ok(self.rx.recv().await.unwrap_or(PaykitNoiseMessage::Ack))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancellation_recorded_by_both_parties() {
+        let subscriber = pkarr::Keypair::random();
+        let provider = pkarr::Keypair::random();
+        let subscription = Subscription::new(
+            subscriber.public_key(),
+            provider.public_key(),
+            crate::SubscriptionTerms::new(
+                Amount::from_sats(1000),
+                "SAT".to_string(),
+                crate::PaymentFrequency::Monthly { day_of_month: 1 },
+                MethodId("lightning".to_string()),
+                "Pro plan".to_string(),
+            ),
+        );
+        let sig1 =
+            signing::sign_subscription_ed25519(&subscription, &provider, &rand::random(), 3600)
+                .unwrap();
+        let sig2 =
+            signing::sign_subscription_ed25519(&subscription, &subscriber, &rand::random(), 3600)
+                .unwrap();
+        let signed = SignedSubscription::new(subscription.clone(), sig1, sig2);
+
+        let dirs = [tempdir().unwrap(), tempdir().unwrap()];
+        let managers: Vec<SubscriptionManager> = dirs
+            .iter()
+            .map(|dir| {
+                let storage: Arc<Box<dyn SubscriptionStorage>> = Arc::new(Box::new(
+                    FileSubscriptionStorage::new(dir.path().to_path_buf()).unwrap(),
+                ));
+                let mock_storage: Arc<Box<dyn PaykitStorage>> = Arc::new(Box::new(MockStorage));
+                let mock_generator: Arc<Box<dyn ReceiptGenerator>> =
+                    Arc::new(Box::new(MockGenerator));
+                let interactive =
+                    Arc::new(PaykitInteractiveManager::new(mock_storage, mock_generator));
+                SubscriptionManager::new(storage, interactive)
+            })
+            .collect();
+        let (subscriber_side, provider_side) = (&managers[0], &managers[1]);
+        for manager in &managers {
+            manager
+                .storage()
+                .save_signed_subscription(&signed)
+                .await
+                .unwrap();
+        }
+        subscriber_side
+            .storage()
+            .save_autopay_rule(&crate::AutoPayRule::new(
+                subscription.subscription_id.clone(),
+                provider.public_key(),
+                MethodId("lightning".to_string()),
+            ))
+            .await
+            .unwrap();
+        let renewal = PaymentRequest::new(
+            provider.public_key(),
+            subscriber.public_key(),
+            Amount::from_sats(1000),
+            "SAT".to_string(),
+            MethodId("lightning".to_string()),
+        );
+        assert!(subscriber_side.should_autopay(&renewal).await.unwrap());
+
+        let (mut subscriber_channel, mut provider_channel) = channel_pair();
+        let (cancelled, acknowledged) = tokio::join!(
+            subscriber_side.cancel_subscription(
+                &mut subscriber_channel,
+                &subscription.subscription_id,
+                &subscriber,
+                CancellationReason::TooExpensive,
+                Some("Over budget".to_string()),
+                false,
+            ),
+            async {
+                match provider_channel.recv().await.unwrap() {
+                    PaykitNoiseMessage::CancelSubscription { cancellation } => {
+                        provider_side
+                            .handle_cancellation(
+                                &mut provider_channel,
+                                cancellation,
+                                &provider,
+                                false,
+                            )
+                            .await
+                    }
+                    other => panic!("unexpected message: {:?}", other),
+                }
+            }
+        );
+
+        let cancelled = cancelled.unwrap();
+        let acknowledged = acknowledged.unwrap();
+        assert!(cancelled.is_acknowledged());
+        assert_eq!(cancelled, acknowledged);
+        for manager in &managers {
+            let record = manager
+                .get_cancellation(&subscription.subscription_id)
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(record.is_acknowledged());
+            assert_eq!(
+                record.cancellation.cancellation.reason,
+                CancellationReason::TooExpensive
+            );
+            assert!(manager
+                .list_active_subscriptions()
+                .await
+                .unwrap()
+                .is_empty());
+        }
+
+        // The cancelled provider's requests are no longer paid automatically
+        assert!(!subscriber_side.should_autopay(&renewal).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_accept_assignment_moves_agreement_and_autopay() {
+        let temp_dir = tempdir().unwrap();
+        let storage: Arc<Box<dyn SubscriptionStorage>> = Arc::new(Box::new(
+            FileSubscriptionStorage::new(temp_dir.path().to_path_buf()).unwrap(),
+        ));
+        let mock_storage: Arc<Box<dyn PaykitStorage>> = Arc::new(Box::new(MockStorage));
+        let mock_generator: Arc<Box<dyn ReceiptGenerator>> = Arc::new(Box::new(MockGenerator));
+        let interactive = Arc::new(PaykitInteractiveManager::new(mock_storage, mock_generator));
+        let manager = SubscriptionManager::new(storage.clone(), interactive);
+
+        let subscriber = pkarr::Keypair::random();
+        let old_provider = pkarr::Keypair::random();
+        let new_provider = pkarr::Keypair::random();
+        let subscription = Subscription::new(
+            subscriber.public_key(),
+            old_provider.public_key(),
+            crate::SubscriptionTerms::new(
+                Amount::from_sats(1000),
+                "SAT".to_string(),
+                crate::PaymentFrequency::Monthly { day_of_month: 1 },
+                MethodId("lightning".to_string()),
+                "Pro plan".to_string(),
+            ),
+        );
+        let sig1 =
+            signing::sign_subscription_ed25519(&subscription, &subscriber, &rand::random(), 3600)
+                .unwrap();
+        let sig2 =
+            signing::sign_subscription_ed25519(&subscription, &old_provider, &rand::random(), 3600)
+                .unwrap();
+        storage
+            .save_signed_subscription(&SignedSubscription::new(subscription.clone(), sig1, sig2))
+            .await
+            .unwrap();
+        let rule = manager
+            .get_or_create_autopay_rule(&subscription.subscription_id)
+            .await
+            .unwrap();
+        manager
+            .enable_autopay(&subscription.subscription_id, rule)
+            .await
+            .unwrap();
+
+        let mut assignment = SignedSubscriptionAssignment::sign(
+            crate::SubscriptionAssignment::new(&subscription, new_provider.public_key()).unwrap(),
+            &old_provider,
+        )
+        .unwrap();
+
+        // Not accepted until the new provider countersigns
+        assert!(!manager.verify_assignment(&assignment).await.unwrap());
+        assert!(manager
+            .accept_assignment(&assignment, &subscriber)
+            .await
+            .is_err());
+
+        assignment
+            .countersign(&subscription, &new_provider)
+            .unwrap();
+        assert!(manager
+            .accept_assignment(&assignment, &new_provider)
+            .await
+            .is_err());
+        let transferred = manager
+            .accept_assignment(&assignment, &subscriber)
+            .await
+            .unwrap();
+        assert_eq!(transferred.subscription.provider, new_provider.public_key());
+
+        let stored = storage
+            .get_signed_subscription(&subscription.subscription_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored, transferred);
+        let rule = storage
+            .get_autopay_rule(&subscription.subscription_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(rule.peer, new_provider.public_key());
+
+        // Replaying the handoff against the transferred agreement fails
+        assert!(manager
+            .accept_assignment(&assignment, &subscriber)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_upcoming_charges_applies_scheduled_upgrade() {
+        let temp_dir = tempdir().unwrap();
+        let storage: Arc<Box<dyn SubscriptionStorage>> = Arc::new(Box::new(
+            FileSubscriptionStorage::new(temp_dir.path().to_path_buf()).unwrap(),
+        ));
+        let mock_storage: Arc<Box<dyn PaykitStorage>> = Arc::new(Box::new(MockStorage));
+        let mock_generator: Arc<Box<dyn ReceiptGenerator>> = Arc::new(Box::new(MockGenerator));
+        let interactive = Arc::new(PaykitInteractiveManager::new(mock_storage, mock_generator));
+        let manager = SubscriptionManager::new(storage.clone(), interactive);
+
+        let subscriber = pkarr::Keypair::random();
+        let provider = pkarr::Keypair::random();
+        let subscription = Subscription::new(
+            subscriber.public_key(),
+            provider.public_key(),
+            crate::SubscriptionTerms::new(
+                Amount::from_sats(1000),
+                "SAT".to_string(),
+                crate::PaymentFrequency::Daily,
+                MethodId("lightning".to_string()),
+                "Daily digest".to_string(),
+            ),
+        );
+        let sig1 =
+            signing::sign_subscription_ed25519(&subscription, &subscriber, &rand::random(), 3600)
+                .unwrap();
+        let sig2 =
+            signing::sign_subscription_ed25519(&subscription, &provider, &rand::random(), 3600)
+                .unwrap();
+        storage
+            .save_signed_subscription(&SignedSubscription::new(subscription.clone(), sig1, sig2))
+            .await
+            .unwrap();
+
+        let upgrade = ModificationRequest::upgrade(
+            &subscription,
+            Amount::from_sats(2000),
+            subscription.starts_at + 5 * 86400 / 2,
+        );
+        manager.schedule_modification(&upgrade).await.unwrap();
+
+        let calendar = manager.get_upcoming_charges(7).await.unwrap();
+        let amounts: Vec<i64> = calendar
+            .charges
+            .iter()
+            .map(|c| c.amount.as_sats())
+            .collect();
+        assert_eq!(amounts, vec![1000, 1000, 1000, 2000, 2000, 2000, 2000]);
+        assert_eq!(
+            calendar.totals_by_currency()["SAT"],
+            Amount::from_sats(11000)
+        );
+        assert!(manager.get_upcoming_charges(0).await.is_err());
+    }
 }
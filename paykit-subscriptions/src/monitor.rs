@@ -5,10 +5,19 @@
 //! web workers.
 
 use crate::{
-    subscription::PaymentFrequency, PaymentRequest, Result, SignedSubscription, SubscriptionManager,
+    billing,
+    expectations::{
+        Escalation, ExpectationMonitor, ExpectationPolicy, ExpectationReport, ObservedPayment,
+        SUBSCRIPTION_ID_METADATA_KEY,
+    },
+    invoice::InvoiceScheduler,
+    retry::{RetryDecision, RetryPolicySet, RetryState},
+    subscription::PaymentFrequency,
+    PaymentRequest, Result, SignedSubscription, SubscriptionManager,
 };
 use chrono::Datelike;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::time::sleep;
 
@@ -19,6 +28,11 @@ use tokio::time::sleep;
 pub struct SubscriptionMonitor {
     manager: Arc<SubscriptionManager>,
     check_interval: Duration,
+    batch_invoices: bool,
+    retry_policies: RetryPolicySet,
+    retries: Mutex<HashMap<String, RetryState>>,
+    invoices: Option<Arc<InvoiceScheduler>>,
+    expectations: ExpectationMonitor,
 }
 
 impl SubscriptionMonitor {
@@ -27,6 +41,11 @@ impl SubscriptionMonitor {
         Self {
             manager,
             check_interval,
+            batch_invoices: false,
+            retry_policies: RetryPolicySet::default(),
+            retries: Mutex::new(HashMap::new()),
+            invoices: None,
+            expectations: ExpectationMonitor::default(),
         }
     }
 
@@ -35,6 +54,179 @@ impl SubscriptionMonitor {
         Self::new(manager, Duration::from_secs(3600))
     }
 
+    /// Consolidate charges due from the same payer into one invoice
+    ///
+    /// With batching enabled, a payer with several subscriptions due in the
+    /// same check receives one itemized request to settle with a single
+    /// payment, and a [`billing::BillingRecord`] allocating it per
+    /// subscription is saved. Payers with one due subscription are billed
+    /// as before.
+    pub fn with_invoice_batching(mut self, enabled: bool) -> Self {
+        self.batch_invoices = enabled;
+        self
+    }
+
+    /// Generate recurring invoices on every check
+    ///
+    /// Invoices due from the scheduler are saved as requests alongside the
+    /// subscription charges and, when the manager has a Pubky session,
+    /// published to each payer's mailbox. Invoices that could not be
+    /// published are retried on the next check.
+    pub fn with_invoice_scheduler(mut self, scheduler: Arc<InvoiceScheduler>) -> Self {
+        self.invoices = Some(scheduler);
+        self
+    }
+
+    /// Set the retry policies applied to failed charges
+    pub fn with_retry_policies(mut self, policies: RetryPolicySet) -> Result<Self> {
+        policies.validate()?;
+        self.retry_policies = policies;
+        Ok(self)
+    }
+
+    /// Get the retry policies
+    pub fn retry_policies(&self) -> &RetryPolicySet {
+        &self.retry_policies
+    }
+
+    /// Set the payment windows and escalation used by
+    /// [`check_expected_payments`](Self::check_expected_payments)
+    pub fn with_expectation_policy(mut self, policy: ExpectationPolicy) -> Result<Self> {
+        policy.validate()?;
+        self.expectations = ExpectationMonitor::new(policy);
+        Ok(self)
+    }
+
+    /// Find charges due since `since` that were not paid
+    ///
+    /// Charges on active subscriptions are matched against `observed`
+    /// payments (e.g. [`ObservedPayment::from_receipt`] for each receipt
+    /// received). New misses and misses settled by a late payment are saved
+    /// to the billing history. A miss escalated to
+    /// [`Escalation::RetryRequest`] gets a fresh payment request, saved like
+    /// any other charge; delinquent subscriptions are left to the caller,
+    /// e.g. to publish a past-due status notice.
+    pub async fn check_expected_payments(
+        &self,
+        observed: &[ObservedPayment],
+        since: i64,
+    ) -> Result<ExpectationReport> {
+        let now = chrono::Utc::now().timestamp();
+        let storage = self.manager.storage();
+
+        let mut expected = Vec::new();
+        for signed in storage.list_active_subscriptions().await? {
+            let sub = &signed.subscription;
+            let modifications = storage.list_modifications(&sub.subscription_id).await?;
+            expected.extend(
+                self.expectations
+                    .expected_payments(sub, &modifications, since, now),
+            );
+        }
+
+        let history = storage.get_billing_history().await?;
+        let mut report = self.expectations.check(&expected, observed, &history, now);
+        for missed in &mut report.missed {
+            if missed.escalation == Escalation::RetryRequest {
+                let mut request = PaymentRequest::new(
+                    missed.provider.clone(),
+                    missed.payer.clone(),
+                    missed.amount,
+                    missed.currency.clone(),
+                    missed.method.clone(),
+                )
+                .with_description(format!(
+                    "Missed subscription payment for {}",
+                    missed.subscription_id
+                ));
+                request.request_id =
+                    format!("req_missed_{}_{}", missed.subscription_id, missed.due_at);
+                request.metadata = serde_json::json!({
+                    SUBSCRIPTION_ID_METADATA_KEY: missed.subscription_id,
+                });
+                storage.save_request(&request).await?;
+                missed.retry_request_id = Some(request.request_id);
+            }
+            storage.save_missed_payment(missed).await?;
+        }
+        for resolved in &report.resolved {
+            storage.save_missed_payment(resolved).await?;
+        }
+
+        Ok(report)
+    }
+
+    /// Record a failed charge and decide what happens next
+    ///
+    /// Retries are issued by [`check_due_payments`](Self::check_due_payments)
+    /// once they are due. Suspended subscriptions are not billed until
+    /// [`resume`](Self::resume) is called.
+    pub fn report_failure(
+        &self,
+        subscription: &SignedSubscription,
+        error: impl Into<String>,
+        failed_at: i64,
+    ) -> RetryDecision {
+        let sub = &subscription.subscription;
+        let mut retries = self.lock_retries();
+        let failures = match retries.get(&sub.subscription_id) {
+            // A charge that was given up on starts a fresh count
+            Some(state) if !matches!(state.decision, RetryDecision::GiveUp { .. }) => {
+                state.failures + 1
+            }
+            _ => 1,
+        };
+
+        let decision = self
+            .retry_policies
+            .for_subscription(&sub.subscription_id)
+            .evaluate(&sub.terms.method, failures, failed_at);
+        retries.insert(
+            sub.subscription_id.clone(),
+            RetryState {
+                subscription_id: sub.subscription_id.clone(),
+                failures,
+                last_error: Some(error.into()),
+                decision: decision.clone(),
+                retry_request_id: None,
+            },
+        );
+        decision
+    }
+
+    /// Record a successful charge, clearing its retry state
+    pub fn report_success(&self, subscription_id: &str) {
+        self.lock_retries().remove(subscription_id);
+    }
+
+    /// Resume billing a suspended subscription
+    pub fn resume(&self, subscription_id: &str) -> bool {
+        self.lock_retries().remove(subscription_id).is_some()
+    }
+
+    /// Get the retry state of a subscription
+    pub fn retry_state(&self, subscription_id: &str) -> Option<RetryState> {
+        self.lock_retries().get(subscription_id).cloned()
+    }
+
+    /// IDs of subscriptions suspended after repeated failures
+    pub fn suspended_subscriptions(&self) -> Vec<String> {
+        self.lock_retries()
+            .values()
+            .filter(|s| matches!(s.decision, RetryDecision::Suspend { .. }))
+            .map(|s| s.subscription_id.clone())
+            .collect()
+    }
+
+    /// Predict the retry plan if a charge for `subscription` fails at
+    /// `failed_at` and every retry fails too
+    pub fn dry_run(&self, subscription: &SignedSubscription, failed_at: i64) -> Vec<RetryDecision> {
+        let sub = &subscription.subscription;
+        self.retry_policies
+            .for_subscription(&sub.subscription_id)
+            .dry_run(&sub.terms.method, failed_at)
+    }
+
     /// Start monitoring loop
     ///
     /// Errors during payment checks are silently ignored and retried on the next interval.
@@ -53,19 +245,131 @@ impl SubscriptionMonitor {
         let now = chrono::Utc::now().timestamp();
         let subscriptions = self.manager.storage().list_active_subscriptions().await?;
 
-        let mut due_requests = Vec::new();
-
+        let mut due = Vec::new();
+        let mut retries = Vec::new();
         for sub in subscriptions {
+            if let Some(state) = self.retry_state(&sub.subscription.subscription_id) {
+                match state.decision {
+                    RetryDecision::Suspend { .. } => continue,
+                    RetryDecision::Retry { retry, at, method } => {
+                        if at <= now && state.retry_request_id.is_none() {
+                            retries.push((sub, retry, method));
+                        }
+                        continue;
+                    }
+                    // The failed charge was dropped; bill on the normal schedule
+                    RetryDecision::GiveUp { .. } => {}
+                }
+            }
+
             if self.is_payment_due(&sub, now) {
-                // Generate payment request for this subscription
-                let request = self.generate_payment_request(&sub)?;
+                due.push(sub);
+            }
+        }
 
-                // Save locally (will be sent when peer connects)
-                self.manager.storage().save_request(&request).await?;
+        // Retries are billed on their own, with the method the policy chose
+        let mut retry_requests = Vec::new();
+        for (sub, retry, method) in retries {
+            let mut request = self
+                .generate_payment_request(&sub)?
+                .with_description(format!(
+                    "Subscription payment retry {}: {}",
+                    retry, sub.subscription.terms.description
+                ));
+            request.method = method;
+            self.manager.storage().save_request(&request).await?;
+
+            if let Some(state) = self
+                .lock_retries()
+                .get_mut(&sub.subscription.subscription_id)
+            {
+                state.retry_request_id = Some(request.request_id.clone());
+            }
+            retry_requests.push(request);
+        }
 
-                due_requests.push(request);
+        let mut due_requests = if self.batch_invoices {
+            self.bill_consolidated(due, now).await?
+        } else {
+            self.bill_each(due).await?
+        };
+        due_requests.extend(retry_requests);
+        due_requests.extend(self.issue_invoices(now).await?);
+        Ok(due_requests)
+    }
+
+    /// Issue due recurring invoices and deliver undelivered ones
+    async fn issue_invoices(&self, now: i64) -> Result<Vec<PaymentRequest>> {
+        let Some(invoices) = &self.invoices else {
+            return Ok(Vec::new());
+        };
+
+        let requests = invoices.generate_due(now)?;
+        for request in &requests {
+            self.manager.storage().save_request(request).await?;
+        }
+        if !self.manager.has_pubky_session() {
+            return Ok(requests);
+        }
+
+        for invoice in invoices.undelivered() {
+            let Some(request) = self
+                .manager
+                .storage()
+                .get_request(&invoice.request_id)
+                .await?
+            else {
+                continue;
+            };
+            // Delivery is best effort; the payer also receives the request
+            // over Noise and the next check retries the mailbox
+            if self.manager.publish_payment_request(&request).await.is_ok() {
+                invoices.mark_delivered(&invoice.invoice_number);
             }
         }
+        Ok(requests)
+    }
+
+    /// Bill due subscriptions with one request each
+    async fn bill_each(&self, due: Vec<SignedSubscription>) -> Result<Vec<PaymentRequest>> {
+        let mut due_requests = Vec::new();
+
+        for sub in due {
+            // Generate payment request for this subscription
+            let request = self.generate_payment_request(&sub)?;
+
+            // Save locally (will be sent when peer connects)
+            self.manager.storage().save_request(&request).await?;
+
+            due_requests.push(request);
+        }
+
+        Ok(due_requests)
+    }
+
+    /// Bill due subscriptions with one request per payer
+    async fn bill_consolidated(
+        &self,
+        due: Vec<SignedSubscription>,
+        now: i64,
+    ) -> Result<Vec<PaymentRequest>> {
+        let mut due_requests = Vec::new();
+
+        for (sequence, group) in billing::group_due_charges(due).into_iter().enumerate() {
+            let request = if group.len() == 1 {
+                self.generate_payment_request(&group[0])?
+            } else {
+                let consolidated = billing::consolidate(&group, now, sequence)?;
+                self.manager
+                    .storage()
+                    .save_billing_record(&consolidated.record)
+                    .await?;
+                consolidated.request
+            };
+
+            self.manager.storage().save_request(&request).await?;
+            due_requests.push(request);
+        }
 
         Ok(due_requests)
     }
@@ -138,6 +442,10 @@ impl SubscriptionMonitor {
         Ok(request)
     }
 
+    fn lock_retries(&self) -> std::sync::MutexGuard<'_, HashMap<String, RetryState>> {
+        self.retries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
     /// Get manager reference
     pub fn manager(&self) -> &Arc<SubscriptionManager> {
         &self.manager
@@ -148,6 +456,7 @@ impl SubscriptionMonitor {
 mod tests {
     use super::*;
     use crate::{
+        retry::{BackoffSchedule, RetryPolicy},
         signing,
         storage::{FileSubscriptionStorage, SubscriptionStorage},
         subscription::{SignedSubscription, Subscription, SubscriptionTerms},
@@ -227,11 +536,17 @@ mod tests {
     }
 
     fn create_test_subscription(monthly_day: u8) -> SignedSubscription {
-        let subscriber = test_pubkey();
-        let provider = test_pubkey();
+        create_subscription_between(test_pubkey(), test_pubkey(), monthly_day, 100)
+    }
 
+    fn create_subscription_between(
+        subscriber: PublicKey,
+        provider: PublicKey,
+        monthly_day: u8,
+        sats: i64,
+    ) -> SignedSubscription {
         let terms = SubscriptionTerms::new(
-            Amount::from_sats(100),
+            Amount::from_sats(sats),
             "SAT".to_string(),
             PaymentFrequency::Monthly {
                 day_of_month: monthly_day,
@@ -240,7 +555,8 @@ mod tests {
             "Test subscription".to_string(),
         );
 
-        let subscription = Subscription::new(subscriber, provider, terms);
+        let mut subscription = Subscription::new(subscriber, provider, terms);
+        subscription.subscription_id = format!("sub_{}_{}", subscription.subscription_id, sats);
         let keypair = pkarr::Keypair::random();
         let nonce1 = rand::random();
         let nonce2 = rand::random();
@@ -304,4 +620,171 @@ mod tests {
         assert_eq!(due_requests[0].amount, Amount::from_sats(100));
         assert_eq!(due_requests[0].currency, "SAT");
     }
+
+    #[tokio::test]
+    async fn test_due_charges_batched_per_payer() {
+        let temp_dir = tempdir().unwrap();
+        let storage: Arc<Box<dyn SubscriptionStorage>> = Arc::new(Box::new(
+            FileSubscriptionStorage::new(temp_dir.path().to_path_buf()).unwrap(),
+        ));
+
+        let mock_storage: Arc<Box<dyn PaykitStorage>> = Arc::new(Box::new(MockStorage));
+        let mock_generator: Arc<Box<dyn ReceiptGenerator>> = Arc::new(Box::new(MockGenerator));
+        let interactive = Arc::new(PaykitInteractiveManager::new(mock_storage, mock_generator));
+
+        let manager = Arc::new(SubscriptionManager::new(storage.clone(), interactive));
+        let monitor =
+            SubscriptionMonitor::with_default_interval(manager).with_invoice_batching(true);
+
+        let today_day = chrono::Utc::now().day() as u8;
+        let provider = test_pubkey();
+        let payer = test_pubkey();
+        for sub in [
+            create_subscription_between(payer.clone(), provider.clone(), today_day, 100),
+            create_subscription_between(payer.clone(), provider.clone(), today_day, 250),
+            create_subscription_between(test_pubkey(), provider.clone(), today_day, 100),
+        ] {
+            storage.save_signed_subscription(&sub).await.unwrap();
+        }
+
+        let due_requests = monitor.check_due_payments().await.unwrap();
+        assert_eq!(due_requests.len(), 2);
+
+        let batched = due_requests.iter().find(|r| r.to == payer).unwrap();
+        assert_eq!(batched.amount, Amount::from_sats(350));
+        assert_eq!(batched.items.len(), 2);
+
+        let history = storage.get_billing_history().await.unwrap();
+        assert_eq!(history.records.len(), 1);
+        let record = history.latest().unwrap();
+        assert_eq!(record.request_id, batched.request_id);
+        assert_eq!(record.allocations.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_charge_retried_then_suspended() {
+        let temp_dir = tempdir().unwrap();
+        let storage: Arc<Box<dyn SubscriptionStorage>> = Arc::new(Box::new(
+            FileSubscriptionStorage::new(temp_dir.path().to_path_buf()).unwrap(),
+        ));
+
+        let mock_storage: Arc<Box<dyn PaykitStorage>> = Arc::new(Box::new(MockStorage));
+        let mock_generator: Arc<Box<dyn ReceiptGenerator>> = Arc::new(Box::new(MockGenerator));
+        let interactive = Arc::new(PaykitInteractiveManager::new(mock_storage, mock_generator));
+
+        let manager = Arc::new(SubscriptionManager::new(storage.clone(), interactive));
+        let policy = RetryPolicy::new(5)
+            .with_backoff(BackoffSchedule::Fixed { delay_secs: 0 })
+            .with_fallback_after(1, vec![MethodId("onchain".to_string())])
+            .with_suspend_after(2);
+        let monitor = SubscriptionMonitor::with_default_interval(manager)
+            .with_retry_policies(RetryPolicySet::new(policy))
+            .unwrap();
+
+        // Due on another day, so only the retry is billed
+        let today_day = chrono::Utc::now().day() as u8;
+        let subscription = create_test_subscription(today_day % 28 + 1);
+        storage
+            .save_signed_subscription(&subscription)
+            .await
+            .unwrap();
+        let id = subscription.subscription.subscription_id.clone();
+
+        let plan = monitor.dry_run(&subscription, 1000);
+        assert_eq!(plan.len(), 2);
+        assert!(matches!(plan[1], RetryDecision::Suspend { failures: 2 }));
+
+        let now = chrono::Utc::now().timestamp();
+        assert!(monitor
+            .report_failure(&subscription, "no route", now)
+            .is_retry());
+        let requests = monitor.check_due_payments().await.unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method.0, "onchain");
+        assert!(monitor.retry_state(&id).unwrap().retry_request_id.is_some());
+
+        // The retry is only issued once
+        assert!(monitor.check_due_payments().await.unwrap().is_empty());
+
+        assert!(matches!(
+            monitor.report_failure(&subscription, "insufficient funds", now),
+            RetryDecision::Suspend { .. }
+        ));
+        assert_eq!(monitor.suspended_subscriptions(), vec![id.clone()]);
+        assert!(monitor.resume(&id));
+        assert!(monitor.retry_state(&id).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unpaid_charges_raise_missed_payments() {
+        let temp_dir = tempdir().unwrap();
+        let storage: Arc<Box<dyn SubscriptionStorage>> = Arc::new(Box::new(
+            FileSubscriptionStorage::new(temp_dir.path().to_path_buf()).unwrap(),
+        ));
+
+        let mock_storage: Arc<Box<dyn PaykitStorage>> = Arc::new(Box::new(MockStorage));
+        let mock_generator: Arc<Box<dyn ReceiptGenerator>> = Arc::new(Box::new(MockGenerator));
+        let interactive = Arc::new(PaykitInteractiveManager::new(mock_storage, mock_generator));
+
+        let manager = Arc::new(SubscriptionManager::new(storage.clone(), interactive));
+        let monitor = SubscriptionMonitor::with_default_interval(manager)
+            .with_expectation_policy(ExpectationPolicy::default())
+            .unwrap();
+
+        // Weekly charges fell due 20, 13 and 6 days ago
+        let starts_at = chrono::Utc::now().timestamp() - 20 * 86_400;
+        let terms = SubscriptionTerms::new(
+            Amount::from_sats(100),
+            "SAT".to_string(),
+            PaymentFrequency::Weekly,
+            MethodId("lightning".to_string()),
+            "Weekly subscription".to_string(),
+        );
+        let subscription =
+            Subscription::new(test_pubkey(), test_pubkey(), terms).with_starts_at(starts_at);
+        let keypair = pkarr::Keypair::random();
+        let sig1 =
+            signing::sign_subscription_ed25519(&subscription, &keypair, &rand::random(), 3600)
+                .unwrap();
+        let sig2 =
+            signing::sign_subscription_ed25519(&subscription, &keypair, &rand::random(), 3600)
+                .unwrap();
+        let signed = SignedSubscription::new(subscription, sig1, sig2);
+        storage.save_signed_subscription(&signed).await.unwrap();
+        let id = signed.subscription.subscription_id.clone();
+
+        // Only the first charge was paid
+        let paid = ObservedPayment {
+            payer: signed.subscription.subscriber.clone(),
+            payee: signed.subscription.provider.clone(),
+            amount: Some(Amount::from_sats(100)),
+            currency: Some("SAT".to_string()),
+            paid_at: starts_at + 60,
+            request_id: None,
+            subscription_id: Some(id.clone()),
+        };
+        let report = monitor
+            .check_expected_payments(&[paid.clone()], starts_at)
+            .await
+            .unwrap();
+        assert_eq!(report.settled.len(), 1);
+        assert_eq!(report.missed.len(), 2);
+        assert_eq!(report.missed[0].escalation, Escalation::RetryRequest);
+        assert_eq!(report.missed[1].escalation, Escalation::MarkDelinquent);
+
+        let retry_id = report.missed[0].retry_request_id.clone().unwrap();
+        let retry = storage.get_request(&retry_id).await.unwrap().unwrap();
+        assert_eq!(retry.amount, Amount::from_sats(100));
+
+        let history = storage.get_billing_history().await.unwrap();
+        assert_eq!(history.missed.len(), 2);
+        assert!(history.is_delinquent(&id));
+
+        // Misses are raised once
+        let report = monitor
+            .check_expected_payments(&[paid], starts_at)
+            .await
+            .unwrap();
+        assert!(report.missed.is_empty());
+    }
 }
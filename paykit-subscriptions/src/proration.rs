@@ -28,7 +28,7 @@
 //! ```
 
 use crate::modifications::{ModificationRequest, ModificationType};
-use crate::{Amount, Result, Subscription, SubscriptionError};
+use crate::{Amount, PaymentFrequency, Result, Subscription, SubscriptionError};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
@@ -91,6 +91,35 @@ pub struct ProrationDetails {
     pub change_date: i64,
 }
 
+impl ProrationDetails {
+    /// Seconds in the billing period.
+    pub fn total_seconds(&self) -> i64 {
+        self.period_end - self.period_start
+    }
+
+    /// Seconds left in the period at the change date.
+    pub fn seconds_remaining(&self) -> i64 {
+        self.period_end - self.change_date
+    }
+
+    /// Old amount per day of the period (unrounded).
+    pub fn old_daily_rate(&self) -> Decimal {
+        daily_rate(&self.old_amount, self.total_seconds())
+    }
+
+    /// New amount per day of the period (unrounded).
+    pub fn new_daily_rate(&self) -> Decimal {
+        daily_rate(&self.new_amount, self.total_seconds())
+    }
+}
+
+fn daily_rate(amount: &Amount, total_seconds: i64) -> Decimal {
+    if total_seconds <= 0 {
+        return Decimal::ZERO;
+    }
+    amount.as_decimal() * Decimal::from(86400) / Decimal::from(total_seconds)
+}
+
 /// Calculator for prorated amounts.
 #[derive(Clone, Debug)]
 pub struct ProrationCalculator {
@@ -182,6 +211,8 @@ impl ProrationCalculator {
     }
 
     /// Calculate proration for any amount change.
+    ///
+    /// Time is counted in whole days, so the period must be at least a day long.
     pub fn calculate(
         &self,
         old_amount: &Amount,
@@ -191,46 +222,114 @@ impl ProrationCalculator {
         change_date: i64,
         currency: &str,
     ) -> Result<ProratedAmount> {
-        // Validate dates
-        if change_date < period_start || change_date > period_end {
+        self.validate_period(period_start, period_end, change_date)?;
+
+        // Calculate days
+        let total_seconds = period_end - period_start;
+        let total_days = (total_seconds / 86400) as u32;
+        if total_days == 0 {
             return Err(SubscriptionError::InvalidArgument(
-                "Change date must be within the billing period".to_string(),
+                "Billing period must be at least one day".to_string(),
             )
             .into());
         }
 
-        if period_end <= period_start {
+        let days_at_old_rate = ((change_date - period_start) / 86400) as u32;
+        let days_at_new_rate = total_days - days_at_old_rate;
+
+        Ok(self.prorate(
+            old_amount,
+            new_amount,
+            Decimal::from(days_at_new_rate),
+            Decimal::from(total_days),
+            ProrationDetails {
+                old_amount: *old_amount,
+                new_amount: *new_amount,
+                total_days,
+                days_at_old_rate,
+                days_at_new_rate,
+                period_start,
+                period_end,
+                change_date,
+            },
+            currency,
+        ))
+    }
+
+    /// Calculate proration counting time in seconds.
+    ///
+    /// Use this for `Custom` frequencies, whose periods need not be a whole
+    /// number of days. The day counts in the details are informational and
+    /// rounded down.
+    pub fn calculate_by_seconds(
+        &self,
+        old_amount: &Amount,
+        new_amount: &Amount,
+        period_start: i64,
+        period_end: i64,
+        change_date: i64,
+        currency: &str,
+    ) -> Result<ProratedAmount> {
+        self.validate_period(period_start, period_end, change_date)?;
+
+        let total_seconds = period_end - period_start;
+        let seconds_remaining = period_end - change_date;
+        let total_days = (total_seconds / 86400) as u32;
+        let days_at_old_rate = ((change_date - period_start) / 86400) as u32;
+
+        Ok(self.prorate(
+            old_amount,
+            new_amount,
+            Decimal::from(seconds_remaining),
+            Decimal::from(total_seconds),
+            ProrationDetails {
+                old_amount: *old_amount,
+                new_amount: *new_amount,
+                total_days,
+                days_at_old_rate,
+                days_at_new_rate: (seconds_remaining / 86400) as u32,
+                period_start,
+                period_end,
+                change_date,
+            },
+            currency,
+        ))
+    }
+
+    fn validate_period(&self, period_start: i64, period_end: i64, change_date: i64) -> Result<()> {
+        // Validate dates
+        if change_date < period_start || change_date > period_end {
             return Err(SubscriptionError::InvalidArgument(
-                "Period end must be after period start".to_string(),
+                "Change date must be within the billing period".to_string(),
             )
             .into());
         }
 
-        // Calculate days
-        let total_seconds = period_end - period_start;
-        let total_days = (total_seconds / 86400) as u32;
-        if total_days == 0 {
+        if period_end <= period_start {
             return Err(SubscriptionError::InvalidArgument(
-                "Billing period must be at least one day".to_string(),
+                "Period end must be after period start".to_string(),
             )
             .into());
         }
 
-        let days_at_old_rate = ((change_date - period_start) / 86400) as u32;
-        let days_at_new_rate = total_days - days_at_old_rate;
-
-        // Calculate prorated amounts
-        let old_decimal = old_amount.as_decimal();
-        let new_decimal = new_amount.as_decimal();
-        let total_days_decimal = Decimal::from(total_days);
+        Ok(())
+    }
 
-        // Credit = (old_amount / total_days) * days_remaining
-        let daily_old = old_decimal / total_days_decimal;
-        let credit = daily_old * Decimal::from(days_at_new_rate);
+    /// Credit and charge the `remaining` of `total` units (days or seconds) of the period.
+    fn prorate(
+        &self,
+        old_amount: &Amount,
+        new_amount: &Amount,
+        remaining: Decimal,
+        total: Decimal,
+        details: ProrationDetails,
+        currency: &str,
+    ) -> ProratedAmount {
+        // Credit = (old_amount / total) * remaining
+        let credit = old_amount.as_decimal() / total * remaining;
 
-        // Charge = (new_amount / total_days) * days_at_new_rate
-        let daily_new = new_decimal / total_days_decimal;
-        let charge = daily_new * Decimal::from(days_at_new_rate);
+        // Charge = (new_amount / total) * remaining
+        let charge = new_amount.as_decimal() / total * remaining;
 
         // Net = charge - credit
         let net = charge - credit;
@@ -239,25 +338,18 @@ impl ProrationCalculator {
         let (credit_rounded, charge_rounded, net_rounded) =
             self.apply_rounding(credit, charge, net);
 
-        Ok(ProratedAmount {
+        ProratedAmount {
             credit: Amount::new(credit_rounded, currency.to_string()),
             charge: Amount::new(charge_rounded, currency.to_string()),
             net_amount: Amount::new(net_rounded, currency.to_string()),
             currency: currency.to_string(),
-            details: ProrationDetails {
-                old_amount: *old_amount,
-                new_amount: *new_amount,
-                total_days,
-                days_at_old_rate,
-                days_at_new_rate,
-                period_start,
-                period_end,
-                change_date,
-            },
-        })
+            details,
+        }
     }
 
     /// Calculate proration from a modification request.
+    ///
+    /// Subscriptions with a `Custom` frequency are prorated by seconds.
     pub fn calculate_from_modification(
         &self,
         subscription: &Subscription,
@@ -265,32 +357,42 @@ impl ProrationCalculator {
         period_start: i64,
         period_end: i64,
     ) -> Result<Option<ProratedAmount>> {
-        match &request.modification_type {
+        let (new_amount, effective_date) = match &request.modification_type {
             ModificationType::Upgrade {
                 new_amount,
                 effective_date,
-            } => Ok(Some(self.calculate(
+            }
+            | ModificationType::Downgrade {
+                new_amount,
+                effective_date,
+            } => (new_amount, *effective_date),
+            // Other modification types don't require proration
+            _ => return Ok(None),
+        };
+
+        let prorated = if matches!(
+            subscription.terms.frequency,
+            PaymentFrequency::Custom { .. }
+        ) {
+            self.calculate_by_seconds(
                 &subscription.terms.amount,
                 new_amount,
                 period_start,
                 period_end,
-                *effective_date,
-                &subscription.terms.currency,
-            )?)),
-            ModificationType::Downgrade {
-                new_amount,
                 effective_date,
-            } => Ok(Some(self.calculate(
+                &subscription.terms.currency,
+            )?
+        } else {
+            self.calculate(
                 &subscription.terms.amount,
                 new_amount,
                 period_start,
                 period_end,
-                *effective_date,
+                effective_date,
                 &subscription.terms.currency,
-            )?)),
-            // Other modification types don't require proration
-            _ => Ok(None),
-        }
+            )?
+        };
+        Ok(Some(prorated))
     }
 
     /// Apply rounding based on configuration.
@@ -349,7 +451,6 @@ pub fn days_remaining_in_period(subscription: &Subscription) -> u32 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::PaymentFrequency;
     use std::str::FromStr;
 
     fn test_pubkey() -> paykit_lib::PublicKey {
@@ -555,6 +656,46 @@ mod tests {
         assert!(result_up.charge.as_decimal() >= result_down.charge.as_decimal());
     }
 
+    #[test]
+    fn test_proration_by_seconds_for_short_periods() {
+        let calc = ProrationCalculator::new();
+
+        // 12-hour custom period, change after 3 hours
+        let period_end = 12 * 3600;
+        let change_date = 3 * 3600;
+
+        // Day-based proration cannot handle sub-day periods
+        assert!(calc
+            .calculate(
+                &Amount::from_sats(1200),
+                &Amount::from_sats(2400),
+                0,
+                period_end,
+                change_date,
+                "SAT",
+            )
+            .is_err());
+
+        let result = calc
+            .calculate_by_seconds(
+                &Amount::from_sats(1200),
+                &Amount::from_sats(2400),
+                0,
+                period_end,
+                change_date,
+                "SAT",
+            )
+            .unwrap();
+
+        // 9 of 12 hours remaining: credit 900, charge 1800
+        assert_eq!(result.credit, Amount::from_sats(900));
+        assert_eq!(result.charge, Amount::from_sats(1800));
+        assert_eq!(result.net_amount, Amount::from_sats(900));
+        assert_eq!(result.details.seconds_remaining(), 9 * 3600);
+        // 1200 sats per 12 hours is 2400 sats per day
+        assert_eq!(result.details.old_daily_rate(), Decimal::from(2400));
+    }
+
     #[test]
     fn test_calculate_from_modification() {
         let calc = ProrationCalculator::new();
@@ -0,0 +1,449 @@
+//! Declarative Retry Policies for Auto-Pay Failures
+//!
+//! A [`RetryPolicy`] describes what happens after a subscription charge
+//! fails: how many times to retry, how long to wait between attempts, when
+//! to move from the subscription's own method to fallback methods, and when
+//! to suspend the subscription instead of retrying further.
+//!
+//! Policies are plain data, so they can be stored, shown to users, and
+//! overridden per subscription through a [`RetryPolicySet`]. The
+//! [`RetryPolicy::dry_run`] evaluator predicts the full retry plan for a
+//! charge that keeps failing, without executing anything.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use paykit_subscriptions::retry::{BackoffSchedule, RetryPolicy};
+//!
+//! let policy = RetryPolicy::new(4)
+//!     .with_backoff(BackoffSchedule::Exponential { base_secs: 300, factor: 2, max_secs: 86400 })
+//!     .with_fallback_after(2, vec!["onchain".into()])
+//!     .with_suspend_after(6);
+//!
+//! for step in policy.dry_run(&"lightning".into(), failed_at) {
+//!     println!("{}", step.describe());
+//! }
+//! ```
+
+use crate::fallback::SubscriptionFallbackPolicy;
+use crate::{Result, SubscriptionError};
+use paykit_lib::MethodId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How long to wait before each retry.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BackoffSchedule {
+    /// The same delay before every retry.
+    Fixed { delay_secs: u64 },
+    /// `base_secs * factor^(n-1)` before retry `n`, capped at `max_secs`.
+    Exponential {
+        base_secs: u64,
+        factor: u32,
+        max_secs: u64,
+    },
+    /// Explicit delays per retry; the last one repeats.
+    Steps { delays_secs: Vec<u64> },
+}
+
+impl Default for BackoffSchedule {
+    fn default() -> Self {
+        BackoffSchedule::Exponential {
+            base_secs: 300,
+            factor: 2,
+            max_secs: 86400,
+        }
+    }
+}
+
+impl BackoffSchedule {
+    /// Delay before retry number `retry` (1-based).
+    pub fn delay_for(&self, retry: u32) -> u64 {
+        let retry = retry.max(1);
+        match self {
+            BackoffSchedule::Fixed { delay_secs } => *delay_secs,
+            BackoffSchedule::Exponential {
+                base_secs,
+                factor,
+                max_secs,
+            } => {
+                let multiplier = (*factor as u64).checked_pow(retry - 1).unwrap_or(u64::MAX);
+                base_secs.saturating_mul(multiplier).min(*max_secs)
+            }
+            BackoffSchedule::Steps { delays_secs } => delays_secs
+                .get(retry as usize - 1)
+                .or(delays_secs.last())
+                .copied()
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Policy for retrying failed subscription charges.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Retries allowed after the first failure before giving up.
+    pub max_retries: u32,
+    /// Delay schedule between retries.
+    #[serde(default)]
+    pub backoff: BackoffSchedule,
+    /// Switch to fallback methods after this many consecutive failures.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback_after: Option<u32>,
+    /// Fallback methods, tried in order and then cycled.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback_methods: Vec<MethodId>,
+    /// Suspend the subscription after this many consecutive failures.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suspend_after: Option<u32>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            backoff: BackoffSchedule::default(),
+            fallback_after: None,
+            fallback_methods: Vec::new(),
+            suspend_after: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create a policy allowing `max_retries` retries with the default backoff.
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..Default::default()
+        }
+    }
+
+    /// Set the backoff schedule.
+    pub fn with_backoff(mut self, backoff: BackoffSchedule) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Switch to `methods` after `failures` consecutive failures.
+    pub fn with_fallback_after(mut self, failures: u32, methods: Vec<MethodId>) -> Self {
+        self.fallback_after = Some(failures);
+        self.fallback_methods = methods;
+        self
+    }
+
+    /// Suspend the subscription after `failures` consecutive failures.
+    pub fn with_suspend_after(mut self, failures: u32) -> Self {
+        self.suspend_after = Some(failures);
+        self
+    }
+
+    /// Validate the policy.
+    pub fn validate(&self) -> Result<()> {
+        if self.fallback_after.is_some() && self.fallback_methods.is_empty() {
+            return Err(SubscriptionError::InvalidArgument(
+                "fallback_after requires at least one fallback method".to_string(),
+            )
+            .into());
+        }
+        if self.fallback_after == Some(0) || self.suspend_after == Some(0) {
+            return Err(SubscriptionError::InvalidArgument(
+                "Failure thresholds must be at least 1".to_string(),
+            )
+            .into());
+        }
+        if let BackoffSchedule::Steps { delays_secs } = &self.backoff {
+            if delays_secs.is_empty() {
+                return Err(SubscriptionError::InvalidArgument(
+                    "Step backoff needs at least one delay".to_string(),
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Decide what to do after `failures` consecutive failures, the last at `failed_at`.
+    pub fn evaluate(&self, primary: &MethodId, failures: u32, failed_at: i64) -> RetryDecision {
+        if let Some(limit) = self.suspend_after {
+            if failures >= limit {
+                return RetryDecision::Suspend { failures };
+            }
+        }
+        if failures > self.max_retries {
+            return RetryDecision::GiveUp { failures };
+        }
+
+        let retry = failures.max(1);
+        RetryDecision::Retry {
+            retry,
+            at: failed_at.saturating_add(self.backoff.delay_for(retry) as i64),
+            method: self.method_for(primary, failures),
+        }
+    }
+
+    /// Method to use for the attempt following `failures` failures.
+    pub fn method_for(&self, primary: &MethodId, failures: u32) -> MethodId {
+        match self.fallback_after {
+            Some(after) if failures >= after && !self.fallback_methods.is_empty() => {
+                let index = (failures - after) as usize % self.fallback_methods.len();
+                self.fallback_methods[index].clone()
+            }
+            _ => primary.clone(),
+        }
+    }
+
+    /// Predict every step for a charge that fails first at `failed_at` and
+    /// keeps failing.
+    ///
+    /// Retries are assumed to run exactly when scheduled. The last step is
+    /// always a [`RetryDecision::Suspend`] or [`RetryDecision::GiveUp`].
+    pub fn dry_run(&self, primary: &MethodId, failed_at: i64) -> Vec<RetryDecision> {
+        let mut steps = Vec::new();
+        let mut failures = 1;
+        let mut last_failure = failed_at;
+        loop {
+            let decision = self.evaluate(primary, failures, last_failure);
+            if let RetryDecision::Retry { at, .. } = &decision {
+                last_failure = *at;
+                steps.push(decision);
+                failures += 1;
+            } else {
+                steps.push(decision);
+                return steps;
+            }
+        }
+    }
+}
+
+impl From<&SubscriptionFallbackPolicy> for RetryPolicy {
+    /// Express a fallback policy declaratively: each method gets its retries
+    /// before the next one in priority order takes over.
+    fn from(policy: &SubscriptionFallbackPolicy) -> Self {
+        let per_method = (policy.max_retries_per_method as u32).max(1);
+        let methods: Vec<MethodId> = policy
+            .ordered_methods()
+            .into_iter()
+            .take(policy.max_methods as usize)
+            .collect();
+        let total = per_method * methods.len().max(1) as u32;
+
+        let mut retry =
+            RetryPolicy::new(total.saturating_sub(1)).with_backoff(BackoffSchedule::Fixed {
+                delay_secs: policy.retry_delay_secs,
+            });
+        if methods.len() > 1 {
+            retry = retry.with_fallback_after(per_method, methods[1..].to_vec());
+        }
+        retry
+    }
+}
+
+/// Outcome of evaluating a retry policy after a failure.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum RetryDecision {
+    /// Retry the charge.
+    Retry {
+        /// Retry number (1-based).
+        retry: u32,
+        /// When to retry.
+        at: i64,
+        /// Method to retry with.
+        method: MethodId,
+    },
+    /// Stop retrying and suspend the subscription.
+    Suspend { failures: u32 },
+    /// Stop retrying this charge; the subscription stays active.
+    GiveUp { failures: u32 },
+}
+
+impl RetryDecision {
+    /// Whether another attempt is scheduled.
+    pub fn is_retry(&self) -> bool {
+        matches!(self, RetryDecision::Retry { .. })
+    }
+
+    /// Human-readable description.
+    pub fn describe(&self) -> String {
+        match self {
+            RetryDecision::Retry { retry, at, method } => {
+                format!("retry {} via {} at {}", retry, method.0, at)
+            }
+            RetryDecision::Suspend { failures } => {
+                format!("suspend after {} consecutive failures", failures)
+            }
+            RetryDecision::GiveUp { failures } => {
+                format!("give up on this charge after {} failures", failures)
+            }
+        }
+    }
+}
+
+/// A default retry policy with per-subscription overrides.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RetryPolicySet {
+    /// Policy for subscriptions without an override.
+    pub default: RetryPolicy,
+    /// Overrides keyed by subscription ID.
+    #[serde(default)]
+    pub overrides: HashMap<String, RetryPolicy>,
+}
+
+impl RetryPolicySet {
+    /// Create a set with the given default policy.
+    pub fn new(default: RetryPolicy) -> Self {
+        Self {
+            default,
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// Override the policy for one subscription.
+    pub fn with_override(
+        mut self,
+        subscription_id: impl Into<String>,
+        policy: RetryPolicy,
+    ) -> Self {
+        self.set_override(subscription_id, policy);
+        self
+    }
+
+    /// Override the policy for one subscription.
+    pub fn set_override(&mut self, subscription_id: impl Into<String>, policy: RetryPolicy) {
+        self.overrides.insert(subscription_id.into(), policy);
+    }
+
+    /// Remove a subscription's override.
+    pub fn clear_override(&mut self, subscription_id: &str) -> Option<RetryPolicy> {
+        self.overrides.remove(subscription_id)
+    }
+
+    /// Policy that applies to a subscription.
+    pub fn for_subscription(&self, subscription_id: &str) -> &RetryPolicy {
+        self.overrides.get(subscription_id).unwrap_or(&self.default)
+    }
+
+    /// Validate the default and every override.
+    pub fn validate(&self) -> Result<()> {
+        self.default.validate()?;
+        for policy in self.overrides.values() {
+            policy.validate()?;
+        }
+        Ok(())
+    }
+}
+
+/// Failure state of a charge being retried.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryState {
+    /// Subscription being charged.
+    pub subscription_id: String,
+    /// Consecutive failures so far.
+    pub failures: u32,
+    /// Last error reported.
+    pub last_error: Option<String>,
+    /// Decision taken after the last failure.
+    pub decision: RetryDecision,
+    /// Request issued for the scheduled retry, once it has been sent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_request_id: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lightning() -> MethodId {
+        MethodId("lightning".to_string())
+    }
+
+    fn onchain() -> MethodId {
+        MethodId("onchain".to_string())
+    }
+
+    #[test]
+    fn test_backoff_schedules() {
+        let exp = BackoffSchedule::Exponential {
+            base_secs: 60,
+            factor: 2,
+            max_secs: 300,
+        };
+        assert_eq!(exp.delay_for(1), 60);
+        assert_eq!(exp.delay_for(3), 240);
+        assert_eq!(exp.delay_for(4), 300);
+        assert_eq!(exp.delay_for(100), 300);
+
+        let steps = BackoffSchedule::Steps {
+            delays_secs: vec![10, 60],
+        };
+        assert_eq!(steps.delay_for(1), 10);
+        assert_eq!(steps.delay_for(5), 60);
+    }
+
+    #[test]
+    fn test_evaluate_switches_method_and_suspends() {
+        let policy = RetryPolicy::new(5)
+            .with_backoff(BackoffSchedule::Fixed { delay_secs: 100 })
+            .with_fallback_after(2, vec![onchain()])
+            .with_suspend_after(4);
+        policy.validate().unwrap();
+
+        assert_eq!(
+            policy.evaluate(&lightning(), 1, 1000),
+            RetryDecision::Retry {
+                retry: 1,
+                at: 1100,
+                method: lightning()
+            }
+        );
+        assert!(matches!(
+            policy.evaluate(&lightning(), 2, 1000),
+            RetryDecision::Retry { method, .. } if method == onchain()
+        ));
+        assert_eq!(
+            policy.evaluate(&lightning(), 4, 1000),
+            RetryDecision::Suspend { failures: 4 }
+        );
+    }
+
+    #[test]
+    fn test_dry_run_predicts_full_plan() {
+        let policy = RetryPolicy::new(2).with_backoff(BackoffSchedule::Steps {
+            delays_secs: vec![60, 600],
+        });
+
+        let plan = policy.dry_run(&lightning(), 1000);
+        assert_eq!(plan.len(), 3);
+        assert!(matches!(plan[0], RetryDecision::Retry { at: 1060, .. }));
+        assert!(matches!(plan[1], RetryDecision::Retry { at: 1660, .. }));
+        assert_eq!(plan[2], RetryDecision::GiveUp { failures: 3 });
+    }
+
+    #[test]
+    fn test_overrides_and_validation() {
+        let set = RetryPolicySet::default()
+            .with_override("sub_vip", RetryPolicy::new(10).with_suspend_after(12));
+        assert_eq!(set.for_subscription("sub_vip").max_retries, 10);
+        assert_eq!(set.for_subscription("sub_other").max_retries, 3);
+        set.validate().unwrap();
+
+        let invalid = RetryPolicy {
+            fallback_after: Some(1),
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_from_fallback_policy() {
+        let fallback = SubscriptionFallbackPolicy::default().with_max_retries(2);
+        let policy = RetryPolicy::from(&fallback);
+
+        assert_eq!(policy.max_retries, 3);
+        assert_eq!(policy.fallback_after, Some(2));
+        assert_eq!(policy.method_for(&lightning(), 1), lightning());
+        assert_eq!(policy.method_for(&lightning(), 2), onchain());
+    }
+}
@@ -14,7 +14,10 @@
 //! - X25519 signing removed (Ed25519 only)
 //! - Domain separation added
 
-use crate::{Result, Subscription, SubscriptionError};
+use crate::{
+    CancelSubscription, CancellationAck, Result, Subscription, SubscriptionAssignment,
+    SubscriptionError, SubscriptionStatusNotice,
+};
 use ed25519_dalek::{Signature as DalekSig, Signer, SigningKey, Verifier, VerifyingKey};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -22,6 +25,18 @@ use sha2::{Digest, Sha256};
 /// Domain separation constant for subscription signatures
 const SUBSCRIPTION_DOMAIN: &[u8] = b"PAYKIT_SUBSCRIPTION_V2";
 
+/// Domain separation constant for subscription status notice signatures
+const STATUS_NOTICE_DOMAIN: &[u8] = b"PAYKIT_STATUS_NOTICE_V1";
+
+/// Domain separation constant for subscription cancellation signatures
+const CANCELLATION_DOMAIN: &[u8] = b"PAYKIT_CANCELLATION_V1";
+
+/// Domain separation constant for cancellation acknowledgment signatures
+const CANCELLATION_ACK_DOMAIN: &[u8] = b"PAYKIT_CANCELLATION_ACK_V1";
+
+/// Domain separation constant for subscription assignment signatures
+const ASSIGNMENT_DOMAIN: &[u8] = b"PAYKIT_ASSIGNMENT_V1";
+
 /// Signature with replay protection
 ///
 /// # Security
@@ -231,6 +246,278 @@ pub fn verify_signature_ed25519(
     Ok(verifying_key.verify(&message, &sig).is_ok())
 }
 
+/// Data structure for signing status notices (includes replay protection)
+#[derive(Serialize)]
+struct StatusNoticePayload<'a> {
+    domain: &'static [u8],
+    notice: &'a SubscriptionStatusNotice,
+    nonce: &'a [u8; 32],
+    timestamp: i64,
+    expires_at: i64,
+}
+
+/// Hash a status notice for signing (DETERMINISTIC)
+fn hash_status_notice_canonical(
+    notice: &SubscriptionStatusNotice,
+    nonce: &[u8; 32],
+    timestamp: i64,
+    expires_at: i64,
+) -> Result<[u8; 32]> {
+    let payload = StatusNoticePayload {
+        domain: STATUS_NOTICE_DOMAIN,
+        notice,
+        nonce,
+        timestamp,
+        expires_at,
+    };
+
+    let canonical_bytes = postcard::to_allocvec(&payload)
+        .map_err(|e| SubscriptionError::Serialization(format!("Serialization error: {}", e)))?;
+
+    let hash = Sha256::digest(&canonical_bytes);
+    let mut result = [0u8; 32];
+    result.copy_from_slice(&hash);
+    Ok(result)
+}
+
+/// Sign a subscription status notice with Ed25519 keypair
+///
+/// # Security
+///
+/// - Domain-separated (PAYKIT_STATUS_NOTICE_V1), so a notice signature can
+///   never be replayed as a subscription signature
+/// - Includes nonce, timestamp and expiration like subscription signatures
+pub fn sign_status_notice_ed25519(
+    notice: &SubscriptionStatusNotice,
+    keypair: &pubky::Keypair,
+    nonce: &[u8; 32],
+    lifetime_seconds: i64,
+) -> Result<Signature> {
+    let timestamp = chrono::Utc::now().timestamp();
+    let expires_at = timestamp + lifetime_seconds;
+
+    let message = hash_status_notice_canonical(notice, nonce, timestamp, expires_at)?;
+
+    let secret_bytes = keypair.secret_key();
+    let signing_key = SigningKey::from_bytes(&secret_bytes);
+    let signature_bytes = signing_key.sign(&message);
+
+    Ok(Signature::new_ed25519(
+        signature_bytes.to_bytes(),
+        keypair.public_key().to_bytes(),
+        *nonce,
+        timestamp,
+        expires_at,
+    ))
+}
+
+/// Verify Ed25519 signature over a subscription status notice
+///
+/// Returns `Ok(false)` if the signature is invalid or expired. Callers must
+/// also check that `signature.public_key` belongs to the provider.
+pub fn verify_status_notice_ed25519(
+    notice: &SubscriptionStatusNotice,
+    signature: &Signature,
+) -> Result<bool> {
+    let now = chrono::Utc::now().timestamp();
+    if now > signature.expires_at {
+        return Ok(false);
+    }
+
+    let message = hash_status_notice_canonical(
+        notice,
+        &signature.nonce,
+        signature.timestamp,
+        signature.expires_at,
+    )?;
+
+    let verifying_key = VerifyingKey::from_bytes(&signature.public_key)
+        .map_err(|e| SubscriptionError::Crypto(format!("Invalid public key: {}", e)))?;
+
+    let sig_bytes = signature
+        .signature_bytes()
+        .ok_or_else(|| SubscriptionError::Crypto("Invalid signature length".to_string()))?;
+    let sig = DalekSig::from_bytes(&sig_bytes);
+
+    Ok(verifying_key.verify(&message, &sig).is_ok())
+}
+
+/// Data structure for signing domain-separated messages (includes replay protection)
+#[derive(Serialize)]
+struct DomainPayload<'a, T: Serialize> {
+    domain: &'static [u8],
+    message: &'a T,
+    nonce: &'a [u8; 32],
+    timestamp: i64,
+    expires_at: i64,
+}
+
+/// Hash a domain-separated message for signing (DETERMINISTIC)
+fn hash_domain_canonical<T: Serialize>(
+    domain: &'static [u8],
+    message: &T,
+    nonce: &[u8; 32],
+    timestamp: i64,
+    expires_at: i64,
+) -> Result<[u8; 32]> {
+    let payload = DomainPayload {
+        domain,
+        message,
+        nonce,
+        timestamp,
+        expires_at,
+    };
+
+    let canonical_bytes = postcard::to_allocvec(&payload)
+        .map_err(|e| SubscriptionError::Serialization(format!("Serialization error: {}", e)))?;
+
+    let hash = Sha256::digest(&canonical_bytes);
+    let mut result = [0u8; 32];
+    result.copy_from_slice(&hash);
+    Ok(result)
+}
+
+fn sign_domain_message<T: Serialize>(
+    domain: &'static [u8],
+    message: &T,
+    keypair: &pubky::Keypair,
+    nonce: &[u8; 32],
+    lifetime_seconds: i64,
+) -> Result<Signature> {
+    let timestamp = chrono::Utc::now().timestamp();
+    let expires_at = timestamp + lifetime_seconds;
+
+    let hash = hash_domain_canonical(domain, message, nonce, timestamp, expires_at)?;
+
+    let secret_bytes = keypair.secret_key();
+    let signing_key = SigningKey::from_bytes(&secret_bytes);
+    let signature_bytes = signing_key.sign(&hash);
+
+    Ok(Signature::new_ed25519(
+        signature_bytes.to_bytes(),
+        keypair.public_key().to_bytes(),
+        *nonce,
+        timestamp,
+        expires_at,
+    ))
+}
+
+fn verify_domain_message<T: Serialize>(
+    domain: &'static [u8],
+    message: &T,
+    signature: &Signature,
+) -> Result<bool> {
+    let now = chrono::Utc::now().timestamp();
+    if now > signature.expires_at {
+        return Ok(false);
+    }
+
+    let hash = hash_domain_canonical(
+        domain,
+        message,
+        &signature.nonce,
+        signature.timestamp,
+        signature.expires_at,
+    )?;
+
+    let verifying_key = VerifyingKey::from_bytes(&signature.public_key)
+        .map_err(|e| SubscriptionError::Crypto(format!("Invalid public key: {}", e)))?;
+
+    let sig_bytes = signature
+        .signature_bytes()
+        .ok_or_else(|| SubscriptionError::Crypto("Invalid signature length".to_string()))?;
+    let sig = DalekSig::from_bytes(&sig_bytes);
+
+    Ok(verifying_key.verify(&hash, &sig).is_ok())
+}
+
+/// Sign a subscription cancellation with Ed25519 keypair
+///
+/// Domain-separated (PAYKIT_CANCELLATION_V1). Callers must check that the
+/// signer is a party to the subscription.
+pub fn sign_cancellation_ed25519(
+    cancellation: &CancelSubscription,
+    keypair: &pubky::Keypair,
+    nonce: &[u8; 32],
+    lifetime_seconds: i64,
+) -> Result<Signature> {
+    sign_domain_message(
+        CANCELLATION_DOMAIN,
+        cancellation,
+        keypair,
+        nonce,
+        lifetime_seconds,
+    )
+}
+
+/// Verify Ed25519 signature over a subscription cancellation
+///
+/// Returns `Ok(false)` if the signature is invalid or expired.
+pub fn verify_cancellation_ed25519(
+    cancellation: &CancelSubscription,
+    signature: &Signature,
+) -> Result<bool> {
+    verify_domain_message(CANCELLATION_DOMAIN, cancellation, signature)
+}
+
+/// Sign a cancellation acknowledgment with Ed25519 keypair
+///
+/// Domain-separated (PAYKIT_CANCELLATION_ACK_V1), so an acknowledgment can
+/// never be replayed as a cancellation.
+pub fn sign_cancellation_ack_ed25519(
+    ack: &CancellationAck,
+    keypair: &pubky::Keypair,
+    nonce: &[u8; 32],
+    lifetime_seconds: i64,
+) -> Result<Signature> {
+    sign_domain_message(
+        CANCELLATION_ACK_DOMAIN,
+        ack,
+        keypair,
+        nonce,
+        lifetime_seconds,
+    )
+}
+
+/// Verify Ed25519 signature over a cancellation acknowledgment
+///
+/// Returns `Ok(false)` if the signature is invalid or expired.
+pub fn verify_cancellation_ack_ed25519(
+    ack: &CancellationAck,
+    signature: &Signature,
+) -> Result<bool> {
+    verify_domain_message(CANCELLATION_ACK_DOMAIN, ack, signature)
+}
+
+/// Sign a subscription assignment with Ed25519 keypair
+///
+/// Domain-separated (PAYKIT_ASSIGNMENT_V1). Callers must check that the
+/// signer is the current provider.
+pub fn sign_assignment_ed25519(
+    assignment: &SubscriptionAssignment,
+    keypair: &pubky::Keypair,
+    nonce: &[u8; 32],
+    lifetime_seconds: i64,
+) -> Result<Signature> {
+    sign_domain_message(
+        ASSIGNMENT_DOMAIN,
+        assignment,
+        keypair,
+        nonce,
+        lifetime_seconds,
+    )
+}
+
+/// Verify Ed25519 signature over a subscription assignment
+///
+/// Returns `Ok(false)` if the signature is invalid or expired.
+pub fn verify_assignment_ed25519(
+    assignment: &SubscriptionAssignment,
+    signature: &Signature,
+) -> Result<bool> {
+    verify_domain_message(ASSIGNMENT_DOMAIN, assignment, signature)
+}
+
 /// Generic signing function (Ed25519 only in v0.2)
 ///
 /// # Security
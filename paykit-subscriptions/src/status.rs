@@ -0,0 +1,247 @@
+//! Subscription Status Notices
+//!
+//! Providers tell subscribers where a subscription stands (current, past
+//! due, suspended, canceled) with signed [`SubscriptionStatusNotice`]s.
+//! Notices are published encrypted to the provider's storage at
+//! `/pub/paykit.app/v0/subscriptions/status/{subscriber_scope}/{subscription_id}`
+//! and can also be pushed over an open Noise channel.
+//!
+//! Client apps fetch the latest notice, verify it was signed by the
+//! provider, and gate features with [`SubscriptionStatusNotice::access_at`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! use paykit_subscriptions::status::{SignedStatusNotice, SubscriptionStatusNotice};
+//!
+//! // Provider side
+//! let notice = SubscriptionStatusNotice::past_due(&subscription, grace_ends_at)
+//!     .with_reason("Payment for March failed");
+//! let signed = SignedStatusNotice::sign(notice, &provider_keypair, 7 * 86400)?;
+//! publish_status_notice(&transport, &signed, &subscriber_noise_pk).await?;
+//!
+//! // Subscriber side
+//! if let Some(signed) = fetch_status_notice(&reader, &provider, sub_id, &me, &noise_sk).await? {
+//!     match signed.notice.access_at(now) {
+//!         ServiceAccess::Full => {}
+//!         ServiceAccess::Restricted => show_payment_banner(),
+//!         ServiceAccess::Denied => lock_features(),
+//!     }
+//! }
+//! ```
+
+use crate::signing::{self, Signature};
+use crate::{Result, Subscription};
+use paykit_lib::PublicKey;
+use serde::{Deserialize, Serialize};
+
+/// Standing of a subscription as reported by its provider.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionStatus {
+    /// Paid up.
+    Current,
+    /// A payment is overdue; service may continue until the grace period ends.
+    PastDue,
+    /// Service is restricted until the balance is settled.
+    Suspended,
+    /// The subscription has ended.
+    Canceled,
+}
+
+impl SubscriptionStatus {
+    /// Wire name of the status.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SubscriptionStatus::Current => "current",
+            SubscriptionStatus::PastDue => "past_due",
+            SubscriptionStatus::Suspended => "suspended",
+            SubscriptionStatus::Canceled => "canceled",
+        }
+    }
+}
+
+/// Level of service a client app should offer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceAccess {
+    /// All features available.
+    Full,
+    /// Paywalled features restricted; the subscriber can still pay.
+    Restricted,
+    /// No subscription features.
+    Denied,
+}
+
+/// A provider's statement about a subscription's standing.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubscriptionStatusNotice {
+    /// Subscription the notice is about.
+    pub subscription_id: String,
+    /// Provider issuing the notice (must be the signer).
+    pub provider: PublicKey,
+    /// Subscriber the notice is addressed to.
+    pub subscriber: PublicKey,
+    /// Reported status.
+    pub status: SubscriptionStatus,
+    /// Human-readable explanation.
+    pub reason: Option<String>,
+    /// When a past-due subscription becomes restricted.
+    pub grace_period_ends_at: Option<i64>,
+    /// When the notice was issued.
+    pub issued_at: i64,
+}
+
+impl SubscriptionStatusNotice {
+    /// Create a notice for a subscription.
+    pub fn new(subscription: &Subscription, status: SubscriptionStatus) -> Self {
+        Self {
+            subscription_id: subscription.subscription_id.clone(),
+            provider: subscription.provider.clone(),
+            subscriber: subscription.subscriber.clone(),
+            status,
+            reason: None,
+            grace_period_ends_at: None,
+            issued_at: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    /// Create a past-due notice with a grace period ending at `grace_period_ends_at`.
+    pub fn past_due(subscription: &Subscription, grace_period_ends_at: i64) -> Self {
+        let mut notice = Self::new(subscription, SubscriptionStatus::PastDue);
+        notice.grace_period_ends_at = Some(grace_period_ends_at);
+        notice
+    }
+
+    /// Add a reason.
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    /// Level of service to offer at `now`.
+    ///
+    /// Past-due subscriptions keep full access until the grace period ends.
+    pub fn access_at(&self, now: i64) -> ServiceAccess {
+        match self.status {
+            SubscriptionStatus::Current => ServiceAccess::Full,
+            SubscriptionStatus::PastDue => match self.grace_period_ends_at {
+                Some(ends_at) if now < ends_at => ServiceAccess::Full,
+                _ => ServiceAccess::Restricted,
+            },
+            SubscriptionStatus::Suspended => ServiceAccess::Restricted,
+            SubscriptionStatus::Canceled => ServiceAccess::Denied,
+        }
+    }
+
+    /// Whether the grace period is still running at `now`.
+    pub fn in_grace_period(&self, now: i64) -> bool {
+        self.status == SubscriptionStatus::PastDue
+            && self
+                .grace_period_ends_at
+                .is_some_and(|ends_at| now < ends_at)
+    }
+}
+
+/// A status notice signed by the provider.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SignedStatusNotice {
+    /// The notice.
+    pub notice: SubscriptionStatusNotice,
+    /// Provider's signature over the notice.
+    pub signature: Signature,
+}
+
+impl SignedStatusNotice {
+    /// Sign a notice with the provider's keypair, valid for `lifetime_seconds`.
+    pub fn sign(
+        notice: SubscriptionStatusNotice,
+        keypair: &pubky::Keypair,
+        lifetime_seconds: i64,
+    ) -> Result<Self> {
+        if keypair.public_key() != notice.provider {
+            anyhow::bail!("Status notices must be signed by the provider");
+        }
+
+        let nonce = rand::random::<[u8; 32]>();
+        let signature =
+            signing::sign_status_notice_ed25519(&notice, keypair, &nonce, lifetime_seconds)?;
+        Ok(Self { notice, signature })
+    }
+
+    /// Check the notice was signed by its provider and has not expired.
+    pub fn verify(&self) -> Result<bool> {
+        if self.signature.public_key != self.notice.provider.to_bytes() {
+            return Ok(false);
+        }
+        signing::verify_status_notice_ed25519(&self.notice, &self.signature)
+    }
+
+    /// Verify the notice and check it is about `subscription_id` from `provider`.
+    pub fn verify_for(&self, provider: &PublicKey, subscription_id: &str) -> Result<bool> {
+        if &self.notice.provider != provider || self.notice.subscription_id != subscription_id {
+            return Ok(false);
+        }
+        self.verify()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Amount, PaymentFrequency, SubscriptionTerms};
+    use paykit_lib::MethodId;
+
+    fn subscription(provider: &pubky::Keypair) -> Subscription {
+        Subscription::new(
+            pkarr::Keypair::random().public_key(),
+            provider.public_key(),
+            SubscriptionTerms::new(
+                Amount::from_sats(1000),
+                "SAT".to_string(),
+                PaymentFrequency::Monthly { day_of_month: 1 },
+                MethodId("lightning".to_string()),
+                "Pro plan".to_string(),
+            ),
+        )
+    }
+
+    #[test]
+    fn test_access_follows_status_and_grace_period() {
+        let provider = pkarr::Keypair::random();
+        let sub = subscription(&provider);
+
+        let past_due = SubscriptionStatusNotice::past_due(&sub, 2000);
+        assert_eq!(past_due.access_at(1000), ServiceAccess::Full);
+        assert!(past_due.in_grace_period(1000));
+        assert_eq!(past_due.access_at(2000), ServiceAccess::Restricted);
+
+        let suspended = SubscriptionStatusNotice::new(&sub, SubscriptionStatus::Suspended);
+        assert_eq!(suspended.access_at(1000), ServiceAccess::Restricted);
+        let canceled = SubscriptionStatusNotice::new(&sub, SubscriptionStatus::Canceled);
+        assert_eq!(canceled.access_at(1000), ServiceAccess::Denied);
+    }
+
+    #[test]
+    fn test_signed_notice_verifies_only_for_provider() {
+        let provider = pkarr::Keypair::random();
+        let sub = subscription(&provider);
+        let notice = SubscriptionStatusNotice::new(&sub, SubscriptionStatus::Suspended)
+            .with_reason("Payment failed");
+
+        let signed = SignedStatusNotice::sign(notice.clone(), &provider, 3600).unwrap();
+        assert!(signed.verify().unwrap());
+        assert!(signed
+            .verify_for(&sub.provider, &sub.subscription_id)
+            .unwrap());
+        assert!(!signed.verify_for(&sub.provider, "sub_other").unwrap());
+
+        // Someone else cannot sign for the provider
+        assert!(SignedStatusNotice::sign(notice, &pkarr::Keypair::random(), 3600).is_err());
+
+        // Tampering with the status breaks the signature
+        let mut tampered = signed;
+        tampered.notice.status = SubscriptionStatus::Current;
+        assert!(!tampered.verify().unwrap());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
-    Amount, AutoPayRule, PaymentRequest, PeerSpendingLimit, RequestStatus, SignedSubscription,
+    Amount, AutoPayRule, BillingHistory, BillingRecord, CancellationRecord, MissedPayment,
+    ModificationRequest, PaymentRequest, PeerSpendingLimit, RequestStatus, SignedSubscription,
     Subscription, SubscriptionError,
 };
 use async_trait::async_trait;
@@ -67,8 +68,26 @@ pub trait SubscriptionStorage: Send + Sync {
         &self,
         peer: &PublicKey,
     ) -> Result<Vec<SignedSubscription>>;
+    /// List signed subscriptions that have started, not ended and not been cancelled
     async fn list_active_subscriptions(&self) -> Result<Vec<SignedSubscription>>;
 
+    /// Replace a signed subscription with its transferred version and save
+    /// its updated auto-pay rule as one update: either both are written or
+    /// neither is.
+    async fn save_assigned_subscription(
+        &self,
+        sub: &SignedSubscription,
+        rule: Option<&AutoPayRule>,
+    ) -> Result<()>;
+
+    // Cancellations
+    async fn save_cancellation(&self, record: &CancellationRecord) -> Result<()>;
+    async fn get_cancellation(&self, subscription_id: &str) -> Result<Option<CancellationRecord>>;
+
+    // Scheduled modifications
+    async fn save_modification(&self, request: &ModificationRequest) -> Result<()>;
+    async fn list_modifications(&self, subscription_id: &str) -> Result<Vec<ModificationRequest>>;
+
     // Auto-pay rules
     async fn save_autopay_rule(&self, rule: &AutoPayRule) -> Result<()>;
     async fn get_autopay_rule(&self, subscription_id: &str) -> Result<Option<AutoPayRule>>;
@@ -77,6 +96,13 @@ pub trait SubscriptionStorage: Send + Sync {
     async fn save_peer_limit(&self, limit: &PeerSpendingLimit) -> Result<()>;
     async fn get_peer_limit(&self, peer: &PublicKey) -> Result<Option<PeerSpendingLimit>>;
 
+    // Consolidated billing
+    async fn save_billing_record(&self, record: &BillingRecord) -> Result<()>;
+    /// Save a missed charge, replacing any earlier version of it
+    async fn save_missed_payment(&self, missed: &MissedPayment) -> Result<()>;
+    /// Billing records and missed charges
+    async fn get_billing_history(&self) -> Result<BillingHistory>;
+
     // Atomic spending operations (Phase 4: fixes VULN-005 & VULN-006)
     /// Atomically check if spending is within limits and reserve the amount
     ///
@@ -132,6 +158,10 @@ impl FileSubscriptionStorage {
         std::fs::create_dir_all(base_path.join("signed_subscriptions"))?;
         std::fs::create_dir_all(base_path.join("autopay_rules"))?;
         std::fs::create_dir_all(base_path.join("peer_limits"))?;
+        std::fs::create_dir_all(base_path.join("billing"))?;
+        std::fs::create_dir_all(base_path.join("billing").join("missed"))?;
+        std::fs::create_dir_all(base_path.join("cancellations"))?;
+        std::fs::create_dir_all(base_path.join("modifications"))?;
 
         Ok(Self {
             base_path,
@@ -171,6 +201,31 @@ impl FileSubscriptionStorage {
             .join("peer_limits")
             .join(format!("{}.json", peer_str))
     }
+
+    fn billing_record_path(&self, invoice_number: &str) -> PathBuf {
+        self.base_path
+            .join("billing")
+            .join(format!("{}.json", invoice_number))
+    }
+
+    fn missed_payment_path(&self, subscription_id: &str, due_at: i64) -> PathBuf {
+        self.base_path
+            .join("billing")
+            .join("missed")
+            .join(format!("{}_{}.json", subscription_id, due_at))
+    }
+
+    fn cancellation_path(&self, subscription_id: &str) -> PathBuf {
+        self.base_path
+            .join("cancellations")
+            .join(format!("{}.json", subscription_id))
+    }
+
+    fn modifications_path(&self, subscription_id: &str) -> PathBuf {
+        self.base_path
+            .join("modifications")
+            .join(format!("{}.json", subscription_id))
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -321,6 +376,9 @@ impl SubscriptionStorage for FileSubscriptionStorage {
             .filter(|s| {
                 s.subscription.starts_at <= now
                     && s.subscription.ends_at.is_none_or(|end| end > now)
+                    && !self
+                        .cancellation_path(&s.subscription.subscription_id)
+                        .exists()
             })
             .cloned()
             .collect();
@@ -372,6 +430,155 @@ impl SubscriptionStorage for FileSubscriptionStorage {
         Ok(Some(limit))
     }
 
+    async fn save_assigned_subscription(
+        &self,
+        sub: &SignedSubscription,
+        rule: Option<&AutoPayRule>,
+    ) -> Result<()> {
+        let sub_path = self.signed_subscription_path(&sub.subscription.subscription_id);
+        let sub_tmp = sub_path.with_extension("json.tmp");
+        std::fs::write(&sub_tmp, serde_json::to_string_pretty(sub)?)?;
+
+        let rule_paths = match rule {
+            Some(rule) => {
+                let path = self.autopay_rule_path(&rule.subscription_id);
+                let tmp = path.with_extension("json.tmp");
+                if let Err(e) = std::fs::write(&tmp, serde_json::to_string_pretty(rule)?) {
+                    let _ = std::fs::remove_file(&sub_tmp);
+                    return Err(e.into());
+                }
+                Some((tmp, path))
+            }
+            None => None,
+        };
+
+        // Both files are staged; swap them in, restoring the old agreement
+        // if the rule cannot be moved into place.
+        let previous = std::fs::read(&sub_path).ok();
+        std::fs::rename(&sub_tmp, &sub_path)?;
+        if let Some((tmp, path)) = &rule_paths {
+            if let Err(e) = std::fs::rename(tmp, path) {
+                match &previous {
+                    Some(bytes) => std::fs::write(&sub_path, bytes)?,
+                    None => std::fs::remove_file(&sub_path)?,
+                }
+                let _ = std::fs::remove_file(tmp);
+                return Err(e.into());
+            }
+        }
+
+        self.signed_subscriptions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(sub.subscription.subscription_id.clone(), sub.clone());
+        if let Some(rule) = rule {
+            self.autopay_rules
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(rule.subscription_id.clone(), rule.clone());
+        }
+
+        Ok(())
+    }
+
+    async fn save_cancellation(&self, record: &CancellationRecord) -> Result<()> {
+        let path = self.cancellation_path(record.subscription_id());
+        let json = serde_json::to_string_pretty(record)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    async fn get_cancellation(&self, subscription_id: &str) -> Result<Option<CancellationRecord>> {
+        let path = self.cancellation_path(subscription_id);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let json = std::fs::read_to_string(path)?;
+        let record: CancellationRecord = serde_json::from_str(&json)?;
+        Ok(Some(record))
+    }
+
+    async fn save_modification(&self, request: &ModificationRequest) -> Result<()> {
+        let mut requests = self.list_modifications(&request.subscription_id).await?;
+
+        // Request IDs are only unique per second, so match the type as well
+        match requests.iter_mut().find(|r| {
+            r.request_id == request.request_id && r.modification_type == request.modification_type
+        }) {
+            Some(existing) => *existing = request.clone(),
+            None => requests.push(request.clone()),
+        }
+
+        let path = self.modifications_path(&request.subscription_id);
+        let json = serde_json::to_string_pretty(&requests)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    async fn list_modifications(&self, subscription_id: &str) -> Result<Vec<ModificationRequest>> {
+        let path = self.modifications_path(subscription_id);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let json = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    async fn save_billing_record(&self, record: &BillingRecord) -> Result<()> {
+        let path = self.billing_record_path(&record.invoice_number);
+        let json = serde_json::to_string_pretty(record)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    async fn save_missed_payment(&self, missed: &MissedPayment) -> Result<()> {
+        let path = self.missed_payment_path(&missed.subscription_id, missed.due_at);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(missed)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    async fn get_billing_history(&self) -> Result<BillingHistory> {
+        let billing_dir = self.base_path.join("billing");
+        let mut history = BillingHistory::new();
+
+        if !billing_dir.exists() {
+            return Ok(history);
+        }
+
+        for entry in std::fs::read_dir(&billing_dir)? {
+            let entry = entry?;
+            let path = entry.path();
+
+            if !path.is_file() || path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+
+            let json = std::fs::read_to_string(&path)?;
+            history.record(serde_json::from_str(&json)?);
+        }
+
+        let missed_dir = billing_dir.join("missed");
+        if missed_dir.exists() {
+            for entry in std::fs::read_dir(missed_dir)? {
+                let path = entry?.path();
+                if !path.is_file() || path.extension().and_then(|s| s.to_str()) != Some("json") {
+                    continue;
+                }
+
+                let json = std::fs::read_to_string(&path)?;
+                history.record_missed(serde_json::from_str(&json)?);
+            }
+        }
+
+        Ok(history)
+    }
+
     // ====================================================================
     // Phase 4: Atomic Spending Operations (VULN-005 & VULN-006 fixes)
     // ====================================================================